@@ -1,15 +1,32 @@
 // P2PMesh UniFFI Bridge
 // Wraps the core Rust library for Kotlin/Swift - Full Integration
 
-use p2pmesh::identity::{Did, Keypair};
-use p2pmesh::iou::{IOUBuilder, SignedIOU as CoreSignedIOU};
-use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::identity::{Did, KeySigner, Keypair, PublicKey, Signature};
+use p2pmesh::iou::{
+    IOUBuilder, IOUCodec, IOUValidator, NonceManager, PaymentRequestBuilder, RejectionReason as CoreRejectionReason,
+    PaymentRequest, SignedCancellation as CoreSignedCancellation, SignedIOU as CoreSignedIOU,
+    SignedPaymentRequest, SignedRejection as CoreSignedRejection,
+};
+use p2pmesh::ledger::{
+    verify_evidence, BloomSummary, ConflictDetector, ConflictEvidence, Delta, IOUEntry, MeshState,
+    NodeId, StateSummary,
+};
+use p2pmesh::sync::{decode_payload, pack_payload};
+use p2pmesh::storage::{self, KdfParams, MeshStore, SealError, StoreError};
+use p2pmesh::transport::{
+    ConnectionId, PeerAddress, TcpTransport, TcpTransportConfig, Transport as _, TransportConfig,
+    TransportEvent,
+};
 use p2pmesh::vault::Vault;
 use p2pmesh::gateway::{
     Collector as CoreCollector, CollectorConfig, SettlerConfig,
     SettlementBatch as CoreSettlementBatch, BatchStatus,
 };
-use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 uniffi::setup_scaffolding!();
 
@@ -33,30 +50,292 @@ pub enum MeshError {
     SyncError,
     #[error("Transport error")]
     TransportError,
-    #[error("Serialization error")]
-    SerializationError,
+    #[error("Serialization error: {reason}")]
+    SerializationError { reason: String },
     #[error("Recipient mismatch")]
     RecipientMismatch,
     #[error("Duplicate transaction")]
     DuplicateTransaction,
+    #[error("Nonce conflict")]
+    NonceConflict,
+    #[error("Wallet not found")]
+    WalletNotFound,
+    #[error("Wallet already exists")]
+    WalletAlreadyExists,
+    #[error("Invalid wallet label")]
+    InvalidLabel,
+    #[error("Deletion not confirmed")]
+    DeleteNotConfirmed,
+    #[error("Rate limited")]
+    RateLimited,
+    #[error("Nothing to consolidate")]
+    NothingToConsolidate,
+    #[error("Payment request expired")]
+    RequestExpired,
+    #[error("Payment request already paid")]
+    RequestAlreadyPaid,
+    #[error("Payment was cancelled by its sender")]
+    PaymentCancelled,
+    #[error("Payment already settled")]
+    AlreadySettled,
+    #[error("Invalid derivation path")]
+    InvalidDerivationPath,
+}
+
+impl MeshError {
+    fn serialization(reason: impl Into<String>) -> Self {
+        MeshError::SerializationError { reason: reason.into() }
+    }
+}
+
+// ============================================================================
+// WALLET LISTENER (callbacks into Kotlin/Swift)
+// ============================================================================
+
+/// Event callbacks for a wallet, implemented on the app side.
+/// Invoked after internal locks are released, so implementations may call back into the wallet.
+#[uniffi::export(with_foreign)]
+pub trait WalletListener: Send + Sync {
+    /// An incoming IOU was accepted (queued as pending or credited directly)
+    fn on_payment_received(&self, iou: Arc<SignedIOU>);
+    /// The wallet balance changed
+    fn on_balance_changed(&self, new_balance: u64);
+    /// A mesh state merge finished
+    fn on_sync_completed(&self, stats: SyncStats);
+}
+
+/// Mesh event callbacks for a node, implemented on the app side.
+/// May be invoked from any thread, after internal locks are released.
+#[uniffi::export(with_foreign)]
+pub trait MeshEventListener: Send + Sync {
+    /// A sync brought in an IOU paying this node's wallet (not yet credited)
+    fn on_payment_received(&self, iou: Arc<SignedIOU>);
+    /// A merge of remote state or a delta finished
+    fn on_sync_complete(&self, result: MergeResult);
+    /// An attached transport connected to a new peer
+    fn on_peer_connected(&self, peer: PeerInfo);
+}
+
+/// Listener slot shared between a node and the transports attached to it
+type EventSlot = Arc<Mutex<Option<Arc<dyn MeshEventListener>>>>;
+
+/// Snapshot of the listener in `slot`, so callbacks run without holding its lock
+fn event_listener(slot: &EventSlot) -> Option<Arc<dyn MeshEventListener>> {
+    slot.lock().unwrap().clone()
+}
+
+// ============================================================================
+// EXTERNAL SIGNER (keys held by the app, e.g. Android Keystore / Secure Enclave)
+// ============================================================================
+
+/// Ed25519 signing key held outside the library, implemented on the app side.
+/// Lets the app route signing through a platform keystore; the secret key never crosses the bridge.
+#[uniffi::export(with_foreign)]
+pub trait ExternalSigner: Send + Sync {
+    /// The 32-byte public key
+    fn public_key(&self) -> Vec<u8>;
+    /// The 64-byte signature over `message`, or an empty vector if signing failed
+    fn sign(&self, message: Vec<u8>) -> Vec<u8>;
+}
+
+/// Adapts an `ExternalSigner` to the core `KeySigner`
+struct ForeignSigner {
+    signer: Arc<dyn ExternalSigner>,
+    public_key: PublicKey,
+}
+
+impl ForeignSigner {
+    fn new(signer: Arc<dyn ExternalSigner>) -> Result<Self, MeshError> {
+        let public_key = PublicKey::from_bytes(&signer.public_key())
+            .map_err(|_| MeshError::InvalidKey)?;
+        Ok(Self { signer, public_key })
+    }
+}
+
+impl KeySigner for ForeignSigner {
+    fn public_key(&self) -> PublicKey {
+        self.public_key.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Signature {
+        // A malformed signature becomes one that never verifies; callers check before use
+        Signature::from_bytes(&self.signer.sign(message.to_vec()))
+            .unwrap_or_else(|_| Signature::from_bytes(&[0u8; 64]).expect("64 bytes"))
+    }
+}
+
+/// Create and sign an IOU payment with a key held by the app.
+/// The caller is responsible for the nonce: it must increase with every payment from this key.
+/// Fails with `InvalidSignature` if the signer returns a signature that does not verify.
+#[uniffi::export]
+pub fn create_payment_with_signer(
+    signer: Arc<dyn ExternalSigner>,
+    recipient_did: String,
+    amount: u64,
+    nonce: u64,
+) -> Result<Arc<SignedIOU>, MeshError> {
+    let recipient = Did::parse(&recipient_did)
+        .map_err(|_| MeshError::InvalidKey)?;
+    let signer = ForeignSigner::new(signer)?;
+
+    let signed_iou = IOUBuilder::new()
+        .sender_signer(&signer)
+        .recipient(recipient)
+        .amount(amount)
+        .nonce(nonce)
+        .build()
+        .map_err(|_| MeshError::InvalidIOU)?;
+    if !signed_iou.verify(&signer.public_key) {
+        return Err(MeshError::InvalidSignature);
+    }
+
+    Ok(Arc::new(SignedIOU { inner: signed_iou }))
 }
 
 // ============================================================================
 // WALLET - Full Integration
 // ============================================================================
 
+/// How long a request from `Wallet::create_payment_request` can be paid
+const PAYMENT_REQUEST_TTL_SECS: u64 = 60 * 60;
+
 #[derive(uniffi::Object)]
 pub struct Wallet {
     keypair: Keypair,
+    /// Key this wallet was derived from, whose mnemonic backs it up (None for a root wallet)
+    master: Option<Keypair>,
     did: Did,
     vault: Mutex<Vault>,
     mesh_state: Mutex<MeshState>,
     pending_ious: Mutex<Vec<Arc<SignedIOU>>>,
-    nonce_counter: Mutex<u64>,
+    nonces: Mutex<NonceManager>,
+    store: Option<MeshStore>,
+    listener: Mutex<Option<Arc<dyn WalletListener>>>,
+}
+
+impl Wallet {
+    /// Persist vault, mesh state and nonce counter (no-op without storage)
+    fn persist(&self) -> Result<(), MeshError> {
+        let store = match &self.store {
+            Some(store) => store,
+            None => return Ok(()),
+        };
+
+        let vault = self.vault.lock().unwrap();
+        store.save_vault(&vault).map_err(|_| MeshError::StorageError)?;
+        drop(vault);
+
+        let state = self.mesh_state.lock().unwrap();
+        store.save_mesh_state(&state).map_err(|_| MeshError::StorageError)?;
+        drop(state);
+
+        let nonce = self.nonces.lock().unwrap().last_used(&self.did).unwrap_or(0);
+        store.save_nonce_counter(nonce).map_err(|_| MeshError::StorageError)?;
+
+        store.flush().map_err(|_| MeshError::StorageError)
+    }
+
+    /// Notify the listener about a credited IOU (no locks held)
+    /// `on_payment_received` is skipped if it already fired when the IOU was queued as pending
+    fn notify_payment_processed(&self, iou: Arc<SignedIOU>, was_pending: bool) {
+        let listener = match self.listener() {
+            Some(listener) => listener,
+            None => return,
+        };
+
+        if !was_pending {
+            listener.on_payment_received(iou);
+        }
+        listener.on_balance_changed(self.balance());
+    }
+
+    /// Refuse an IOU whose sender has cancelled it
+    fn check_not_cancelled(&self, iou: &SignedIOU) -> Result<(), MeshError> {
+        if self.mesh_state.lock().unwrap().is_cancelled(&iou.inner.id()) {
+            return Err(MeshError::PaymentCancelled);
+        }
+        Ok(())
+    }
+
+    /// Snapshot of the registered listener, so callbacks run without holding its lock
+    fn listener(&self) -> Option<Arc<dyn WalletListener>> {
+        self.listener.lock().unwrap().clone()
+    }
+
+    /// Sign and record an IOU from `builder`, spending `amount`.
+    /// When paying `request`, refuses if this wallet has already paid it.
+    fn send_iou<'a>(
+        &'a self,
+        builder: IOUBuilder<'a>,
+        amount: u64,
+        request: Option<&PaymentRequest>,
+    ) -> Result<Arc<SignedIOU>, MeshError> {
+        let mut vault = self.vault.lock().unwrap();
+        let mut state = self.mesh_state.lock().unwrap();
+        let mut nonces = self.nonces.lock().unwrap();
+
+        // Checked under the vault lock so concurrent payments of one request can't both pass
+        if request.is_some_and(|request| vault.has_paid_request(&request.id())) {
+            return Err(MeshError::RequestAlreadyPaid);
+        }
+
+        if vault.available_balance() < amount {
+            return Err(MeshError::InsufficientBalance);
+        }
+
+        // Allocate from a copy of the nonces, committed only once everything succeeds
+        let mut next_nonces = nonces.clone();
+        if vault.highest_sent_nonce().is_some_and(|highest| next_nonces.peek(&self.did) <= highest) {
+            return Err(MeshError::NonceConflict);
+        }
+
+        let signed_iou = builder
+            .sender(&self.keypair)
+            .nonce_from(&mut next_nonces)
+            .build()
+            .map_err(|_| MeshError::InvalidIOU)?;
+
+        if state.has_iou(&signed_iou.id()) || vault.has_processed_iou(&signed_iou.id()) {
+            return Err(MeshError::DuplicateTransaction);
+        }
+
+        vault.record_sent_iou(signed_iou.clone()).map_err(spend_error)?;
+
+        // Cannot fail for a fresh, self-signed IOU (duplicate checked above)
+        let _ = state.add_iou(signed_iou.clone(), &self.keypair.public_key());
+
+        *nonces = next_nonces;
+        drop(nonces);
+        drop(state);
+        drop(vault);
+
+        self.persist()?;
+
+        Ok(Arc::new(SignedIOU { inner: signed_iou }))
+    }
+}
+
+/// Map a vault error from recording a sent IOU
+fn spend_error(error: p2pmesh::vault::VaultError) -> MeshError {
+    match error {
+        p2pmesh::vault::VaultError::InsufficientBalance { .. } => MeshError::InsufficientBalance,
+        p2pmesh::vault::VaultError::DuplicateTransaction => MeshError::DuplicateTransaction,
+        _ => MeshError::InvalidIOU,
+    }
 }
 
 #[uniffi::export]
 impl Wallet {
+    /// Register a listener for payment, balance and sync events (replaces any previous one)
+    pub fn set_listener(&self, listener: Arc<dyn WalletListener>) {
+        *self.listener.lock().unwrap() = Some(listener);
+    }
+
+    /// Remove the registered listener
+    pub fn clear_listener(&self) {
+        *self.listener.lock().unwrap() = None;
+    }
+
     /// Get the DID string (did:mesh:xxx)
     pub fn did(&self) -> String {
         self.did.to_string()
@@ -72,6 +351,45 @@ impl Wallet {
         self.keypair.secret_key().to_bytes().to_vec()
     }
 
+    /// Get the 24-word BIP39 backup phrase for this wallet's secret key.
+    /// Sub-wallets return their master's phrase, which restores them via `derive_sub_wallet`.
+    pub fn mnemonic(&self) -> String {
+        self.master.as_ref().unwrap_or(&self.keypair).to_mnemonic()
+    }
+
+    /// Derive a sub-wallet at a hardened SLIP-0010 path like `m/44'/0'/1'`.
+    /// Paths are always taken from the master key, so a sub-wallet's sub-wallets are its siblings.
+    /// The new wallet has its own empty in-memory vault and state but shares this wallet's mnemonic.
+    /// Malformed or non-hardened paths return `MeshError::InvalidDerivationPath`.
+    pub fn derive_sub_wallet(&self, path: String) -> Result<Arc<Wallet>, MeshError> {
+        let master = self.master.as_ref().unwrap_or(&self.keypair);
+        let keypair = master
+            .derive_child(&path)
+            .map_err(|_| MeshError::InvalidDerivationPath)?;
+        let did = Did::from_public_key(&keypair.public_key());
+        let node_id = NodeId::from_public_key(&keypair.public_key());
+        let pubkey = keypair.public_key();
+
+        Ok(Arc::new(Wallet {
+            keypair,
+            master: Some(master.clone()),
+            did,
+            vault: Mutex::new(Vault::new(pubkey)),
+            mesh_state: Mutex::new(MeshState::new(node_id)),
+            pending_ious: Mutex::new(Vec::new()),
+            nonces: Mutex::new(NonceManager::new()),
+            store: None,
+            listener: Mutex::new(None),
+        }))
+    }
+
+    /// Derive numbered sub-account `index` (e.g. 0 for savings, 1 for spending).
+    /// Same as `derive_sub_wallet` at `m/44'/0'/<index>'`; indexes of 2^31 and up
+    /// return `MeshError::InvalidDerivationPath`.
+    pub fn derive_sub_account(&self, index: u32) -> Result<Arc<Wallet>, MeshError> {
+        self.derive_sub_wallet(p2pmesh::identity::sub_account_path(index))
+    }
+
     /// Get current balance (total UTXOs)
     pub fn balance(&self) -> u64 {
         self.vault.lock().unwrap().balance()
@@ -87,7 +405,60 @@ impl Wallet {
         self.vault.lock().unwrap().utxo_set().len() as u64
     }
 
-    /// Create and sign an IOU payment to a recipient
+    /// Create, sign and record an IOU payment in one step.
+    /// Balance check, nonce allocation, vault spend and mesh-state insertion happen under a
+    /// single lock scope; on any failure the nonce counter is left untouched.
+    pub fn send_payment(&self, recipient_did: String, amount: u64) -> Result<Arc<SignedIOU>, MeshError> {
+        let recipient = Did::parse(&recipient_did)
+            .map_err(|_| MeshError::InvalidKey)?;
+
+        self.send_iou(IOUBuilder::new().recipient(recipient).amount(amount), amount, None)
+    }
+
+    /// Create a signed payment request for `amount` to this wallet, valid for an hour.
+    /// Hand the bytes to the payer (e.g. as a QR code), who pays it with `pay_request`.
+    pub fn create_payment_request(&self, amount: u64, memo: Option<String>) -> Result<Vec<u8>, MeshError> {
+        let mut builder = PaymentRequestBuilder::new()
+            .requester(&self.keypair)
+            .amount(amount)
+            .ttl_secs(PAYMENT_REQUEST_TTL_SECS);
+        if let Some(memo) = memo {
+            builder = builder.memo(memo);
+        }
+
+        let request = builder.build().map_err(|_| MeshError::InvalidIOU)?;
+        Ok(request.to_bytes())
+    }
+
+    /// Pay a payment request created with `create_payment_request`.
+    /// Fails with `RequestExpired` past its expiry and `RequestAlreadyPaid` if this wallet paid it before.
+    pub fn pay_request(&self, request_bytes: Vec<u8>) -> Result<Arc<SignedIOU>, MeshError> {
+        let signed_request = SignedPaymentRequest::from_bytes(&request_bytes)
+            .map_err(|e| MeshError::serialization(e.to_string()))?;
+        if !signed_request.verify_requester() {
+            return Err(MeshError::InvalidSignature);
+        }
+
+        let request = signed_request.request();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if request.is_expired_at(now) {
+            return Err(MeshError::RequestExpired);
+        }
+
+        self.send_iou(
+            IOUBuilder::new().for_request(request),
+            request.amount(),
+            Some(request),
+        )
+    }
+
+    /// Create and sign an IOU payment to a recipient (advanced).
+    /// Does not spend anything or use up a nonce: the caller must follow up with `mark_sent`,
+    /// and until then the next payment created gets the same nonce. Prefer `send_payment`,
+    /// which performs both steps atomically.
     pub fn create_payment(&self, recipient_did: String, amount: u64) -> Result<Arc<SignedIOU>, MeshError> {
         let recipient = Did::parse(&recipient_did)
             .map_err(|_| MeshError::InvalidKey)?;
@@ -98,68 +469,86 @@ impl Wallet {
         if vault.available_balance() < amount {
             return Err(MeshError::InsufficientBalance);
         }
+        let highest_sent = vault.highest_sent_nonce();
         drop(vault);
 
-        // Get next nonce
-        let mut nonce_counter = self.nonce_counter.lock().unwrap();
-        *nonce_counter += 1;
-        let nonce = *nonce_counter;
-        drop(nonce_counter);
+        // Sign with the next nonce from a copy, so none is used up until `mark_sent`,
+        // refusing to reuse one already spent from this vault
+        let mut nonces = self.nonces.lock().unwrap().clone();
+        if highest_sent.is_some_and(|highest| nonces.peek(&self.did) <= highest) {
+            return Err(MeshError::NonceConflict);
+        }
 
         // Build and sign the IOU
         let signed_iou = IOUBuilder::new()
             .sender(&self.keypair)
             .recipient(recipient)
             .amount(amount)
-            .nonce(nonce)
+            .nonce_from(&mut nonces)
             .build()
             .map_err(|_| MeshError::InvalidIOU)?;
 
         Ok(Arc::new(SignedIOU { inner: signed_iou }))
     }
 
-    /// Mark an IOU as sent (record in vault and mesh state)
+    /// Mark an IOU as sent (record in vault and mesh state) (advanced).
+    /// Second half of the two-step `create_payment` flow; prefer `send_payment`.
+    /// Refuses, changing nothing, an IOU the mesh state or vault already holds and
+    /// one whose nonce an earlier payment used.
     pub fn mark_sent(&self, iou: Arc<SignedIOU>) -> Result<(), MeshError> {
+        let signed_iou = &iou.inner;
         let mut vault = self.vault.lock().unwrap();
+        let mut state = self.mesh_state.lock().unwrap();
+        let mut nonces = self.nonces.lock().unwrap();
 
-        // Record the sent IOU in vault
-        vault.record_sent_iou(iou.inner.clone())
-            .map_err(|_| MeshError::DuplicateTransaction)?;
-        drop(vault);
+        if state.has_iou(&signed_iou.id()) || vault.has_processed_iou(&signed_iou.id()) {
+            return Err(MeshError::DuplicateTransaction);
+        }
+        let nonce = signed_iou.iou().nonce();
+        if vault.highest_sent_nonce().is_some_and(|highest| nonce <= highest) {
+            return Err(MeshError::NonceConflict);
+        }
+        IOUValidator::validate(signed_iou, &self.keypair.public_key()).map_err(|_| MeshError::InvalidIOU)?;
 
-        // Add to mesh state
-        let mut state = self.mesh_state.lock().unwrap();
-        state.add_iou(iou.inner.clone(), &self.keypair.public_key())
-            .map_err(|_| MeshError::DuplicateTransaction)?;
+        vault.record_sent_iou(signed_iou.clone()).map_err(spend_error)?;
 
-        Ok(())
+        // Cannot fail: duplicate and signature checked above
+        let _ = state.add_iou(signed_iou.clone(), &self.keypair.public_key());
+
+        nonces.seed(&self.did, nonce);
+        drop(nonces);
+        drop(state);
+        drop(vault);
+
+        self.persist()
     }
 
     /// Receive an IOU (add to pending for verification)
     pub fn receive_payment(&self, iou: Arc<SignedIOU>) -> Result<(), MeshError> {
-        // Verify the IOU is for us
-        let recipient_did = Did::parse(&iou.recipient())
-            .map_err(|_| MeshError::InvalidKey)?;
-
-        if recipient_did != self.did {
+        // Verify the IOU is for us (any output of a multi-output IOU)
+        if !iou.inner.iou().recipients().contains(&&self.did) {
             return Err(MeshError::RecipientMismatch);
         }
+        self.check_not_cancelled(&iou)?;
 
         // Add to pending
-        self.pending_ious.lock().unwrap().push(iou);
+        self.pending_ious.lock().unwrap().push(iou.clone());
+
+        if let Some(listener) = self.listener() {
+            listener.on_payment_received(iou);
+        }
         Ok(())
     }
 
     /// Process a received IOU (verify signature and add to vault)
     pub fn process_payment(&self, iou: Arc<SignedIOU>) -> Result<(), MeshError> {
-        // Verify the IOU is for us
-        let recipient_did = Did::parse(&iou.recipient())
-            .map_err(|_| MeshError::InvalidKey)?;
-
-        if recipient_did != self.did {
+        // Verify the IOU is for us (any output of a multi-output IOU)
+        if !iou.inner.iou().recipients().contains(&&self.did) {
             return Err(MeshError::RecipientMismatch);
         }
 
+        self.check_not_cancelled(&iou)?;
+
         // Extract sender's public key from their DID
         let sender_did = Did::parse(&iou.sender())
             .map_err(|_| MeshError::InvalidKey)?;
@@ -180,26 +569,31 @@ impl Wallet {
         // Add to mesh state
         let mut state = self.mesh_state.lock().unwrap();
         let _ = state.add_iou(iou.inner.clone(), &sender_pubkey);
+        drop(state);
 
         // Remove from pending
         let mut pending = self.pending_ious.lock().unwrap();
+        let pending_before = pending.len();
         pending.retain(|p| p.id() != iou.id());
+        let was_pending = pending.len() != pending_before;
+        drop(pending);
 
+        self.persist()?;
+        self.notify_payment_processed(iou, was_pending);
         Ok(())
     }
 
     /// Process a payment with explicit sender public key (for when DID lookup isn't possible)
     pub fn process_payment_with_key(&self, iou: Arc<SignedIOU>, sender_pubkey: Vec<u8>) -> Result<(), MeshError> {
-        // Verify the IOU is for us
-        let recipient_did = Did::parse(&iou.recipient())
-            .map_err(|_| MeshError::InvalidKey)?;
-
-        if recipient_did != self.did {
+        // Verify the IOU is for us (any output of a multi-output IOU)
+        if !iou.inner.iou().recipients().contains(&&self.did) {
             return Err(MeshError::RecipientMismatch);
         }
 
+        self.check_not_cancelled(&iou)?;
+
         // Parse sender public key
-        let pubkey = p2pmesh::identity::PublicKey::from_bytes(&sender_pubkey)
+        let pubkey = PublicKey::from_bytes(&sender_pubkey)
             .map_err(|_| MeshError::InvalidKey)?;
 
         // Add to vault
@@ -216,11 +610,17 @@ impl Wallet {
         // Add to mesh state
         let mut state = self.mesh_state.lock().unwrap();
         let _ = state.add_iou(iou.inner.clone(), &pubkey);
+        drop(state);
 
         // Remove from pending
         let mut pending = self.pending_ious.lock().unwrap();
+        let pending_before = pending.len();
         pending.retain(|p| p.id() != iou.id());
+        let was_pending = pending.len() != pending_before;
+        drop(pending);
 
+        self.persist()?;
+        self.notify_payment_processed(iou, was_pending);
         Ok(())
     }
 
@@ -229,83 +629,248 @@ impl Wallet {
         self.pending_ious.lock().unwrap().clone()
     }
 
-    /// Clear a specific pending IOU (e.g., rejected)
+    /// Clear a specific pending IOU locally.
+    /// The sender's funds stay spent; use `reject_payment` to let the sender reclaim them.
     pub fn clear_pending(&self, iou_id: String) {
         let mut pending = self.pending_ious.lock().unwrap();
         pending.retain(|p| p.id() != iou_id);
     }
 
+    /// Reject a pending IOU, removing it and returning a signed rejection for the sender
+    pub fn reject_payment(&self, iou_id: String, reason: RejectionReason) -> Result<Arc<SignedRejection>, MeshError> {
+        let mut pending = self.pending_ious.lock().unwrap();
+        let position = pending.iter()
+            .position(|p| p.id() == iou_id)
+            .ok_or(MeshError::InvalidIOU)?;
+        let iou = pending.remove(position);
+        drop(pending);
+
+        let inner = CoreSignedRejection::sign(&self.keypair, iou.inner.id(), reason.into());
+        Ok(Arc::new(SignedRejection { inner }))
+    }
+
+    /// Process a rejection of an IOU this wallet sent, refunding its amount
+    pub fn process_rejection(&self, rejection: Arc<SignedRejection>) -> Result<(), MeshError> {
+        let mut vault = self.vault.lock().unwrap();
+        vault.process_rejection(&rejection.inner)
+            .map_err(|e| match e {
+                p2pmesh::vault::VaultError::InvalidSignature => MeshError::InvalidSignature,
+                p2pmesh::vault::VaultError::RecipientMismatch => MeshError::RecipientMismatch,
+                p2pmesh::vault::VaultError::DuplicateTransaction => MeshError::DuplicateTransaction,
+                _ => MeshError::InvalidIOU,
+            })?;
+        let balance = vault.balance();
+        drop(vault);
+
+        self.persist()?;
+
+        if let Some(listener) = self.listener() {
+            listener.on_balance_changed(balance);
+        }
+        Ok(())
+    }
+
+    /// Cancel a payment this wallet sent that the recipient hasn't accepted, restoring its amount.
+    /// Send the returned cancellation to the recipient; it also spreads with mesh sync.
+    pub fn cancel_payment(&self, iou_id: String) -> Result<Arc<SignedCancellation>, MeshError> {
+        let mut vault = self.vault.lock().unwrap();
+        let sent_id = vault.sent_transactions()
+            .iter()
+            .map(|t| t.iou().id())
+            .find(|id| hex::encode(id.as_bytes()) == iou_id)
+            .ok_or(MeshError::InvalidIOU)?;
+        let inner = CoreSignedCancellation::sign(&self.keypair, sent_id);
+        vault.cancel_sent_iou(&inner)
+            .map_err(|e| match e {
+                p2pmesh::vault::VaultError::AlreadySettled => MeshError::AlreadySettled,
+                p2pmesh::vault::VaultError::DuplicateTransaction => MeshError::DuplicateTransaction,
+                _ => MeshError::InvalidIOU,
+            })?;
+        let balance = vault.balance();
+        drop(vault);
+
+        let mut state = self.mesh_state.lock().unwrap();
+        let _ = state.add_cancellation(inner.clone());
+        drop(state);
+
+        self.persist()?;
+
+        if let Some(listener) = self.listener() {
+            listener.on_balance_changed(balance);
+        }
+        Ok(Arc::new(SignedCancellation { inner }))
+    }
+
+    /// Process a sender's cancellation: drop the IOU from pending and refuse it from now on.
+    /// An IOU already accepted into the vault stays accepted.
+    pub fn process_cancellation(&self, cancellation: Arc<SignedCancellation>) -> Result<(), MeshError> {
+        let mut state = self.mesh_state.lock().unwrap();
+        state.add_cancellation(cancellation.inner.clone())
+            .map_err(|_| MeshError::InvalidSignature)?;
+        drop(state);
+
+        let iou_id = cancellation.iou_id();
+        self.pending_ious.lock().unwrap().retain(|p| p.id() != iou_id);
+
+        self.persist()
+    }
+
+    /// Whether the wallet holds at least `threshold` spendable UTXOs worth consolidating.
+    /// Cheap enough to call periodically.
+    pub fn should_consolidate(&self, threshold: u32) -> bool {
+        self.vault.lock().unwrap().should_consolidate(threshold as usize)
+    }
+
+    /// Merge up to `max_inputs` of the smallest spendable UTXOs into one.
+    /// The balance is unchanged; the returned self-addressed IOU is recorded in the mesh
+    /// state so peers learn the inputs are spent.
+    pub fn consolidate_utxos(&self, max_inputs: u32) -> Result<Arc<SignedIOU>, MeshError> {
+        let mut vault = self.vault.lock().unwrap();
+        let mut state = self.mesh_state.lock().unwrap();
+        let mut nonces = self.nonces.lock().unwrap();
+
+        let signed_iou = vault.consolidate_utxos(&self.keypair, max_inputs as usize)
+            .map_err(|e| match e {
+                p2pmesh::vault::VaultError::NothingToConsolidate => MeshError::NothingToConsolidate,
+                _ => MeshError::InvalidIOU,
+            })?;
+
+        // Cannot fail for a fresh, self-signed IOU
+        let _ = state.add_iou(signed_iou.clone(), &self.keypair.public_key());
+
+        // The vault picked the next sent nonce; keep the counter in step
+        nonces.seed(&self.did, signed_iou.iou().nonce());
+        drop(nonces);
+        drop(state);
+        drop(vault);
+
+        self.persist()?;
+
+        Ok(Arc::new(SignedIOU { inner: signed_iou }))
+    }
+
     /// Get transaction history
     pub fn transaction_count(&self) -> u64 {
         self.vault.lock().unwrap().transaction_count() as u64
     }
 
+    /// Export transaction history as CSV (header row, then one row per transaction)
+    pub fn export_transactions_csv(&self) -> String {
+        self.vault.lock().unwrap().export_transactions_csv()
+    }
+
+    /// Export transaction history as a JSON array (one object per transaction)
+    pub fn export_transactions_json(&self) -> String {
+        self.vault.lock().unwrap().export_transactions_json()
+    }
+
     /// Export wallet state as bytes (for persistence)
     pub fn export_state(&self) -> Vec<u8> {
         let vault = self.vault.lock().unwrap();
         let state = self.mesh_state.lock().unwrap();
-        let nonce = *self.nonce_counter.lock().unwrap();
+        let nonces = self.nonces.lock().unwrap();
 
         // Combine exports
         let vault_bytes = vault.to_bytes();
         let state_bytes = state.to_bytes();
+        let nonce = nonces.last_used(&self.did).unwrap_or(0);
+        let nonce_bytes = nonces.to_bytes();
 
         let mut result = Vec::new();
-        // Format: [vault_len:4][vault_bytes][state_len:4][state_bytes][nonce:8]
+        // Format: [vault_len:4][vault_bytes][state_len:4][state_bytes][nonce:8][nonces_len:4][nonces_bytes]
         result.extend_from_slice(&(vault_bytes.len() as u32).to_le_bytes());
         result.extend_from_slice(&vault_bytes);
         result.extend_from_slice(&(state_bytes.len() as u32).to_le_bytes());
         result.extend_from_slice(&state_bytes);
         result.extend_from_slice(&nonce.to_le_bytes());
+        result.extend_from_slice(&(nonce_bytes.len() as u32).to_le_bytes());
+        result.extend_from_slice(&nonce_bytes);
         result
     }
 
-    /// Import wallet state from bytes
-    pub fn import_state(&self, data: Vec<u8>) -> Result<(), MeshError> {
-        if data.len() < 16 {
-            return Err(MeshError::SerializationError);
+    /// Export the secret key and state encrypted under `passphrase`
+    /// (Argon2id 19 MiB / 2 iterations, XChaCha20-Poly1305).
+    /// Restore with `restore_wallet_encrypted`.
+    pub fn export_encrypted(&self, passphrase: String) -> Vec<u8> {
+        let params = KdfParams::default();
+        self.export_encrypted_with_params(passphrase, params.memory_kib, params.iterations)
+            .expect("default KDF params are valid")
+    }
+
+    /// Like `export_encrypted` with explicit Argon2id memory (KiB) and iterations.
+    /// Blob layout: sealed header (magic, version, KDF params, salt, nonce) followed by
+    /// the encrypted `[secret_key:32][export_state()]` payload.
+    pub fn export_encrypted_with_params(&self, passphrase: String, memory_kib: u32, iterations: u32) -> Result<Vec<u8>, MeshError> {
+        let mut payload = Zeroizing::new(self.keypair.to_bytes().to_vec());
+        payload.extend_from_slice(&self.export_state());
+
+        storage::seal(&payload, passphrase.as_bytes(), &KdfParams::new(memory_kib, iterations))
+            .map_err(|e| MeshError::serialization(e.to_string()))
+    }
+
+    /// Export only the vault (balance, UTXOs, history) encrypted under `passphrase`,
+    /// for backups at rest. Restore with `import_vault_encrypted`.
+    pub fn export_vault_encrypted(&self, passphrase: String) -> Vec<u8> {
+        self.vault.lock().unwrap().to_encrypted_bytes(&passphrase)
+    }
+
+    /// Like `export_vault_encrypted` with explicit Argon2id memory (KiB) and iterations
+    pub fn export_vault_encrypted_with_params(&self, passphrase: String, memory_kib: u32, iterations: u32) -> Result<Vec<u8>, MeshError> {
+        self.vault
+            .lock()
+            .unwrap()
+            .to_encrypted_bytes_with_params(&passphrase, &KdfParams::new(memory_kib, iterations))
+            .map_err(|e| MeshError::serialization(e.to_string()))
+    }
+
+    /// Replace the vault with one from `export_vault_encrypted`.
+    /// A wrong passphrase returns `MeshError::InvalidKey`; the vault must belong
+    /// to this wallet's key (`MeshError::InvalidKey` otherwise).
+    /// Blobs sealed with more than the default Argon2id costs are refused.
+    pub fn import_vault_encrypted(&self, data: Vec<u8>, passphrase: String) -> Result<(), MeshError> {
+        let params = KdfParams::default();
+        self.import_vault_encrypted_with_limits(data, passphrase, params.memory_kib, params.iterations)
+    }
+
+    /// Like `import_vault_encrypted` for blobs sealed with up to `max_memory_kib` and
+    /// `max_iterations`, as passed to `export_vault_encrypted_with_params`
+    pub fn import_vault_encrypted_with_limits(
+        &self,
+        data: Vec<u8>,
+        passphrase: String,
+        max_memory_kib: u32,
+        max_iterations: u32,
+    ) -> Result<(), MeshError> {
+        let max = KdfParams::new(max_memory_kib, max_iterations);
+        let vault = Vault::from_encrypted_bytes_within(&data, &passphrase, &max).map_err(|e| match e {
+            p2pmesh::vault::VaultError::WrongPassphrase => MeshError::InvalidKey,
+            other => MeshError::serialization(other.to_string()),
+        })?;
+        if vault.owner() != &self.keypair.public_key() {
+            return Err(MeshError::InvalidKey);
         }
 
-        let mut offset = 0;
+        self.nonces.lock().unwrap().seed_from_vault(&vault);
+        *self.vault.lock().unwrap() = vault;
 
-        // Read vault
-        let vault_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
-        offset += 4;
-        if data.len() < offset + vault_len + 12 {
-            return Err(MeshError::SerializationError);
-        }
-        let vault_bytes = &data[offset..offset + vault_len];
-        offset += vault_len;
-
-        // Read state
-        let state_len = u32::from_le_bytes([
-            data[offset], data[offset + 1], data[offset + 2], data[offset + 3]
-        ]) as usize;
-        offset += 4;
-        if data.len() < offset + state_len + 8 {
-            return Err(MeshError::SerializationError);
-        }
-        let state_bytes = &data[offset..offset + state_len];
-        offset += state_len;
-
-        // Read nonce
-        let nonce = u64::from_le_bytes([
-            data[offset], data[offset + 1], data[offset + 2], data[offset + 3],
-            data[offset + 4], data[offset + 5], data[offset + 6], data[offset + 7],
-        ]);
-
-        // Apply imports
-        let mut vault = self.vault.lock().unwrap();
-        *vault = Vault::from_bytes(vault_bytes)
-            .map_err(|_| MeshError::SerializationError)?;
+        self.persist()
+    }
 
-        let mut state = self.mesh_state.lock().unwrap();
-        *state = MeshState::from_bytes(state_bytes)
-            .map_err(|_| MeshError::SerializationError)?;
+    /// Import wallet state from bytes
+    /// The exported vault must belong to this wallet's key (`MeshError::InvalidKey` otherwise)
+    pub fn import_state(&self, data: Vec<u8>) -> Result<(), MeshError> {
+        let (vault, state, nonces) = parse_exported_state(&data)?;
 
-        *self.nonce_counter.lock().unwrap() = nonce;
+        if vault.owner() != &self.keypair.public_key() {
+            return Err(MeshError::InvalidKey);
+        }
 
-        Ok(())
+        let nonces = resume_nonces(&vault, nonces);
+        *self.vault.lock().unwrap() = vault;
+        *self.mesh_state.lock().unwrap() = state;
+        *self.nonces.lock().unwrap() = nonces;
+
+        self.persist()
     }
 
     /// Simulate receiving funds (for testing/initial funding)
@@ -324,7 +889,9 @@ impl Wallet {
         let mut vault = self.vault.lock().unwrap();
         vault.receive_iou(signed_iou, &self.keypair.public_key())
             .map_err(|_| MeshError::InvalidIOU)?;
-        Ok(())
+        drop(vault);
+
+        self.persist()
     }
 }
 
@@ -337,11 +904,14 @@ pub fn create_wallet() -> Result<Arc<Wallet>, MeshError> {
 
     Ok(Arc::new(Wallet {
         keypair,
+        master: None,
         did,
         vault: Mutex::new(Vault::new(pubkey)),
         mesh_state: Mutex::new(MeshState::new(node_id)),
         pending_ious: Mutex::new(Vec::new()),
-        nonce_counter: Mutex::new(0),
+        nonces: Mutex::new(NonceManager::new()),
+        store: None,
+        listener: Mutex::new(None),
     }))
 }
 
@@ -355,75 +925,651 @@ pub fn restore_wallet(secret_key: Vec<u8>) -> Result<Arc<Wallet>, MeshError> {
 
     Ok(Arc::new(Wallet {
         keypair,
+        master: None,
         did,
         vault: Mutex::new(Vault::new(pubkey)),
         mesh_state: Mutex::new(MeshState::new(node_id)),
         pending_ious: Mutex::new(Vec::new()),
-        nonce_counter: Mutex::new(0),
+        nonces: Mutex::new(NonceManager::new()),
+        store: None,
+        listener: Mutex::new(None),
     }))
 }
 
-// ============================================================================
-// SIGNED IOU
-// ============================================================================
+/// Restore a wallet from a 24-word BIP39 backup phrase (see `Wallet::mnemonic()`).
+/// Whitespace and case are normalized; bad words, checksum or word count return `MeshError::InvalidKey`.
+#[uniffi::export]
+pub fn restore_wallet_from_mnemonic(phrase: String) -> Result<Arc<Wallet>, MeshError> {
+    let keypair = Keypair::from_mnemonic(&phrase)
+        .map_err(|_| MeshError::InvalidKey)?;
+    restore_wallet(keypair.to_bytes().to_vec())
+}
+
+/// Restore a wallet from its secret key and a blob produced by `export_state()`.
+/// Rejects state whose vault belongs to a different key with `MeshError::InvalidKey`.
+#[uniffi::export]
+pub fn restore_wallet_with_state(secret_key: Vec<u8>, state: Vec<u8>) -> Result<Arc<Wallet>, MeshError> {
+    let keypair = Keypair::from_bytes(&secret_key)
+        .map_err(|_| MeshError::InvalidKey)?;
+    let did = Did::from_public_key(&keypair.public_key());
+
+    let (vault, mesh_state, nonces) = parse_exported_state(&state)?;
+    if vault.owner() != &keypair.public_key() {
+        return Err(MeshError::InvalidKey);
+    }
+    let nonces = resume_nonces(&vault, nonces);
+
+    Ok(Arc::new(Wallet {
+        keypair,
+        master: None,
+        did,
+        vault: Mutex::new(vault),
+        mesh_state: Mutex::new(mesh_state),
+        pending_ious: Mutex::new(Vec::new()),
+        nonces: Mutex::new(nonces),
+        store: None,
+        listener: Mutex::new(None),
+    }))
+}
+
+/// Restore a wallet from a blob produced by `export_encrypted`.
+/// A wrong passphrase returns `MeshError::InvalidKey`.
+/// Blobs sealed with more than the default Argon2id costs are refused.
+#[uniffi::export]
+pub fn restore_wallet_encrypted(blob: Vec<u8>, passphrase: String) -> Result<Arc<Wallet>, MeshError> {
+    let params = KdfParams::default();
+    restore_wallet_encrypted_with_limits(blob, passphrase, params.memory_kib, params.iterations)
+}
+
+/// Like `restore_wallet_encrypted` for blobs sealed with up to `max_memory_kib` and
+/// `max_iterations`, as passed to `export_encrypted_with_params`
+#[uniffi::export]
+pub fn restore_wallet_encrypted_with_limits(
+    blob: Vec<u8>,
+    passphrase: String,
+    max_memory_kib: u32,
+    max_iterations: u32,
+) -> Result<Arc<Wallet>, MeshError> {
+    let max = KdfParams::new(max_memory_kib, max_iterations);
+    let payload = storage::unseal_within(&blob, passphrase.as_bytes(), &max).map_err(|e| match e {
+        SealError::DecryptionFailed => MeshError::InvalidKey,
+        other => MeshError::serialization(other.to_string()),
+    })?;
+    if payload.len() < 32 {
+        return Err(MeshError::serialization("encrypted payload too short"));
+    }
+
+    restore_wallet_with_state(payload[..32].to_vec(), payload[32..].to_vec())
+}
+
+/// Nonces to resume from: never behind the highest nonce the vault has sent
+fn resume_nonces(vault: &Vault, mut saved: NonceManager) -> NonceManager {
+    saved.seed_from_vault(vault);
+    saved
+}
+
+/// Parse the `export_state()` format:
+/// [vault_len:4][vault_bytes][state_len:4][state_bytes][nonce:8][nonces_len:4][nonces_bytes]
+///
+/// Exports from before the nonce manager end after `nonce`, the owner's last used nonce.
+fn parse_exported_state(data: &[u8]) -> Result<(Vault, MeshState, NonceManager), MeshError> {
+    let mut reader = StateReader { remaining: data };
+    let vault_bytes = reader.section("vault")?;
+    let state_bytes = reader.section("mesh state")?;
+    let nonce = u64::from_le_bytes(reader.array("nonce")?);
+    let nonce_bytes = if reader.remaining.is_empty() { None } else { Some(reader.section("nonces")?) };
+    if !reader.remaining.is_empty() {
+        return Err(MeshError::serialization(format!(
+            "{} trailing bytes after nonces",
+            reader.remaining.len()
+        )));
+    }
+
+    let vault = Vault::from_bytes(vault_bytes)
+        .map_err(|e| MeshError::serialization(format!("vault: {}", e)))?;
+    let state = MeshState::from_bytes(state_bytes)
+        .map_err(|e| MeshError::serialization(format!("mesh state: {}", e)))?;
+
+    let mut nonces = match nonce_bytes {
+        Some(bytes) => NonceManager::from_bytes(bytes)
+            .map_err(|e| MeshError::serialization(format!("nonces: {}", e)))?,
+        None => NonceManager::new(),
+    };
+    nonces.seed(&Did::from_public_key(vault.owner()), nonce);
+
+    Ok((vault, state, nonces))
+}
+
+/// Cursor over an exported state that checks every length against the bytes left
+/// before slicing, so malformed input is an error rather than a panic
+struct StateReader<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    fn take(&mut self, len: usize, what: &str) -> Result<&'a [u8], MeshError> {
+        if len > self.remaining.len() {
+            return Err(MeshError::serialization(format!(
+                "truncated {}: need {} bytes, {} left",
+                what,
+                len,
+                self.remaining.len()
+            )));
+        }
+        let (head, rest) = self.remaining.split_at(len);
+        self.remaining = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self, what: &str) -> Result<[u8; N], MeshError> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.take(N, what)?);
+        Ok(bytes)
+    }
+
+    /// A `[len:4][bytes]` section
+    fn section(&mut self, what: &str) -> Result<&'a [u8], MeshError> {
+        let len = u32::from_le_bytes(self.array(what)?);
+        let len = usize::try_from(len)
+            .map_err(|_| MeshError::serialization(format!("{} length {} too large", what, len)))?;
+        self.take(len, what)
+    }
+}
+
+/// How long opening a store waits for a just-dropped handle to release its lock
+const STORE_LOCK_WAIT: Duration = Duration::from_secs(2);
+
+/// Pause between attempts to take the store's file lock
+const STORE_LOCK_RETRY: Duration = Duration::from_millis(20);
+
+/// Open the MeshStore at `path`
+///
+/// sled's background threads can hold the file lock for a moment after the last
+/// handle to a store is dropped, so a lock failure is retried for up to
+/// `STORE_LOCK_WAIT` before giving up with `MeshError::StorageError`.
+fn open_store(path: &str) -> Result<MeshStore, MeshError> {
+    let deadline = Instant::now() + STORE_LOCK_WAIT;
+    loop {
+        match MeshStore::open(path) {
+            Ok(store) => return Ok(store),
+            Err(StoreError::OpenFailed(reason))
+                if reason.contains("could not acquire lock") && Instant::now() < deadline =>
+            {
+                std::thread::sleep(STORE_LOCK_RETRY);
+            }
+            Err(_) => return Err(MeshError::StorageError),
+        }
+    }
+}
+
+/// Create a new wallet that persists its state in a MeshStore at `path`.
+/// Vault, mesh state and nonce counter are saved after every mutating call.
+/// The secret key is not written to storage; back it up via `secret_key()`.
+#[uniffi::export]
+pub fn create_wallet_with_storage(path: String) -> Result<Arc<Wallet>, MeshError> {
+    let store = open_store(&path)?;
+
+    let keypair = Keypair::generate();
+    let did = Did::from_public_key(&keypair.public_key());
+    let node_id = NodeId::from_public_key(&keypair.public_key());
+    let pubkey = keypair.public_key();
+
+    let wallet = Arc::new(Wallet {
+        keypair,
+        master: None,
+        did,
+        vault: Mutex::new(Vault::new(pubkey)),
+        mesh_state: Mutex::new(MeshState::new(node_id)),
+        pending_ious: Mutex::new(Vec::new()),
+        nonces: Mutex::new(NonceManager::new()),
+        store: Some(store),
+        listener: Mutex::new(None),
+    });
+    wallet.persist()?;
+
+    Ok(wallet)
+}
+
+/// Open a wallet persisted at `path`, reloading vault, mesh state and nonce counter.
+/// Missing entries start empty; corrupted entries or a vault owned by a
+/// different key return `MeshError::StorageError`.
+#[uniffi::export]
+pub fn open_wallet(path: String, secret_key: Vec<u8>) -> Result<Arc<Wallet>, MeshError> {
+    let keypair = Keypair::from_bytes(&secret_key)
+        .map_err(|_| MeshError::InvalidKey)?;
+    let store = open_store(&path)?;
+
+    Ok(Arc::new(load_wallet(keypair, store)?))
+}
+
+/// Build a wallet for `keypair` from whatever state `store` holds
+fn load_wallet(keypair: Keypair, store: MeshStore) -> Result<Wallet, MeshError> {
+    let did = Did::from_public_key(&keypair.public_key());
+    let node_id = NodeId::from_public_key(&keypair.public_key());
+    let pubkey = keypair.public_key();
+
+    let vault = match store.load_vault().map_err(|_| MeshError::StorageError)? {
+        Some(vault) if vault.owner() == &pubkey => vault,
+        Some(_) => return Err(MeshError::StorageError),
+        None => Vault::new(pubkey),
+    };
+    let mesh_state = store
+        .load_mesh_state()
+        .map_err(|_| MeshError::StorageError)?
+        .unwrap_or_else(|| MeshState::new(node_id));
+    let nonce = store
+        .load_nonce_counter()
+        .map_err(|_| MeshError::StorageError)?
+        .unwrap_or(0);
+    let mut nonces = NonceManager::new();
+    nonces.seed(&did, nonce);
+    let nonces = resume_nonces(&vault, nonces);
+
+    Ok(Wallet {
+        keypair,
+        master: None,
+        did,
+        vault: Mutex::new(vault),
+        mesh_state: Mutex::new(mesh_state),
+        pending_ious: Mutex::new(Vec::new()),
+        nonces: Mutex::new(nonces),
+        store: Some(store),
+        listener: Mutex::new(None),
+    })
+}
+
+// ============================================================================
+// WALLET MANAGER - Multiple labeled wallets in one store
+// ============================================================================
+
+/// Namespace prefix for a labeled wallet's sled tree
+const WALLET_NAMESPACE_PREFIX: &str = "wallet:";
+
+/// Key in the root tree holding the default wallet's label
+const DEFAULT_WALLET_KEY: &[u8] = b"manager:default_wallet";
+
+#[derive(Clone, uniffi::Record)]
+pub struct WalletSummary {
+    pub label: String,
+    pub did: String,
+    pub balance: u64,
+    pub is_default: bool,
+}
+
+/// Manages several wallets in one MeshStore, each in its own namespace
+/// holding its secret key and state. The first wallet created becomes the default.
+#[derive(uniffi::Object)]
+pub struct WalletManager {
+    store: MeshStore,
+    wallets: Mutex<HashMap<String, Arc<Wallet>>>,
+}
+
+impl WalletManager {
+    fn namespace_for(label: &str) -> String {
+        format!("{}{}", WALLET_NAMESPACE_PREFIX, label)
+    }
+
+    fn default_label(&self) -> Result<Option<String>, MeshError> {
+        let bytes = self.store
+            .get_raw(DEFAULT_WALLET_KEY)
+            .map_err(|_| MeshError::StorageError)?;
+        Ok(bytes.and_then(|b| String::from_utf8(b).ok()))
+    }
+
+    fn set_default_label(&self, label: Option<&str>) -> Result<(), MeshError> {
+        match label {
+            Some(label) => self.store.put_raw(DEFAULT_WALLET_KEY, label.as_bytes()),
+            None => self.store.delete(DEFAULT_WALLET_KEY),
+        }
+        .map_err(|_| MeshError::StorageError)?;
+        self.store.flush().map_err(|_| MeshError::StorageError)
+    }
+
+    /// Load a wallet into the cache (caller holds the cache lock)
+    fn load_cached(
+        &self,
+        wallets: &mut HashMap<String, Arc<Wallet>>,
+        label: &str,
+    ) -> Result<Arc<Wallet>, MeshError> {
+        if let Some(wallet) = wallets.get(label) {
+            return Ok(wallet.clone());
+        }
+
+        let namespace = Self::namespace_for(label);
+        if !self.store.has_namespace(&namespace) {
+            return Err(MeshError::WalletNotFound);
+        }
+        let store = self.store.namespace(&namespace).map_err(|_| MeshError::StorageError)?;
+        let keypair = store
+            .load_keypair()
+            .map_err(|_| MeshError::StorageError)?
+            .ok_or(MeshError::WalletNotFound)?;
+
+        let wallet = Arc::new(load_wallet(keypair, store)?);
+        wallets.insert(label.to_string(), wallet.clone());
+        Ok(wallet)
+    }
+}
+
+#[uniffi::export]
+impl WalletManager {
+    /// Open (or create) a wallet manager backed by a MeshStore at `path`
+    #[uniffi::constructor]
+    pub fn new(path: String) -> Result<Arc<Self>, MeshError> {
+        let store = open_store(&path)?;
+        Ok(Arc::new(Self {
+            store,
+            wallets: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Create a new wallet under `label`, persisting its secret key and state
+    pub fn create(&self, label: String) -> Result<Arc<Wallet>, MeshError> {
+        if label.is_empty() {
+            return Err(MeshError::InvalidLabel);
+        }
+
+        let mut wallets = self.wallets.lock().unwrap();
+        let namespace = Self::namespace_for(&label);
+        if self.store.has_namespace(&namespace) {
+            return Err(MeshError::WalletAlreadyExists);
+        }
+
+        let store = self.store.namespace(&namespace).map_err(|_| MeshError::StorageError)?;
+        let keypair = Keypair::generate();
+        store.save_keypair(&keypair).map_err(|_| MeshError::StorageError)?;
+
+        let wallet = Arc::new(load_wallet(keypair, store)?);
+        wallet.persist()?;
+        wallets.insert(label.clone(), wallet.clone());
+        drop(wallets);
+
+        if self.default_label()?.is_none() {
+            self.set_default_label(Some(&label))?;
+        }
+
+        Ok(wallet)
+    }
+
+    /// Summaries of all wallets, sorted by label
+    pub fn list(&self) -> Result<Vec<WalletSummary>, MeshError> {
+        let default = self.default_label()?;
+        let mut labels: Vec<String> = self.store
+            .list_namespaces(WALLET_NAMESPACE_PREFIX)
+            .into_iter()
+            .map(|ns| ns[WALLET_NAMESPACE_PREFIX.len()..].to_string())
+            .collect();
+        labels.sort();
+
+        let mut wallets = self.wallets.lock().unwrap();
+        let mut summaries = Vec::with_capacity(labels.len());
+        for label in labels {
+            let wallet = self.load_cached(&mut wallets, &label)?;
+            summaries.push(WalletSummary {
+                is_default: default.as_deref() == Some(label.as_str()),
+                did: wallet.did(),
+                balance: wallet.balance(),
+                label,
+            });
+        }
+        Ok(summaries)
+    }
+
+    /// Get the wallet stored under `label`.
+    /// Every caller receives the same shared instance.
+    pub fn get(&self, label: String) -> Result<Arc<Wallet>, MeshError> {
+        let mut wallets = self.wallets.lock().unwrap();
+        self.load_cached(&mut wallets, &label)
+    }
+
+    /// Delete a wallet and wipe its secret key and state from storage.
+    /// `confirm_delete` must be true; otherwise `MeshError::DeleteNotConfirmed`.
+    pub fn delete(&self, label: String, confirm_delete: bool) -> Result<(), MeshError> {
+        if !confirm_delete {
+            return Err(MeshError::DeleteNotConfirmed);
+        }
+
+        let mut wallets = self.wallets.lock().unwrap();
+        let wiped = self.store
+            .wipe_namespace(&Self::namespace_for(&label))
+            .map_err(|_| MeshError::StorageError)?;
+        if !wiped {
+            return Err(MeshError::WalletNotFound);
+        }
+        wallets.remove(&label);
+        drop(wallets);
+
+        if self.default_label()?.as_deref() == Some(label.as_str()) {
+            self.set_default_label(None)?;
+        }
+        Ok(())
+    }
+
+    /// Get the default wallet (`MeshError::WalletNotFound` if none is set)
+    pub fn default_wallet(&self) -> Result<Arc<Wallet>, MeshError> {
+        let label = self.default_label()?.ok_or(MeshError::WalletNotFound)?;
+        self.get(label)
+    }
+
+    /// Make the wallet under `label` the default
+    pub fn set_default(&self, label: String) -> Result<(), MeshError> {
+        if !self.store.has_namespace(&Self::namespace_for(&label)) {
+            return Err(MeshError::WalletNotFound);
+        }
+        self.set_default_label(Some(&label))
+    }
+}
+
+// ============================================================================
+// SIGNED IOU
+// ============================================================================
+
+#[derive(uniffi::Object)]
+pub struct SignedIOU {
+    inner: CoreSignedIOU,
+}
+
+#[uniffi::export]
+impl SignedIOU {
+    /// Get unique ID as hex string
+    pub fn id(&self) -> String {
+        hex::encode(self.inner.id().as_bytes())
+    }
+
+    /// Get sender DID
+    pub fn sender(&self) -> String {
+        self.inner.iou().sender().to_string()
+    }
+
+    /// Get recipient DID
+    pub fn recipient(&self) -> String {
+        self.inner.iou().recipient().to_string()
+    }
+
+    /// Get amount (the total of all outputs for a multi-output IOU)
+    pub fn amount(&self) -> u64 {
+        self.inner.iou().amount()
+    }
+
+    /// Amount this IOU pays to `did` (0 if it isn't a recipient)
+    pub fn amount_for(&self, did: String) -> Result<u64, MeshError> {
+        let did = Did::parse(&did).map_err(|_| MeshError::InvalidKey)?;
+        Ok(self.inner.iou().amount_for(&did))
+    }
+
+    /// Get timestamp
+    pub fn timestamp(&self) -> u64 {
+        self.inner.iou().timestamp()
+    }
+
+    /// Get nonce
+    pub fn nonce(&self) -> u64 {
+        self.inner.iou().nonce()
+    }
+
+    /// Get the memo (e.g. an order number), if any
+    pub fn memo(&self) -> Option<String> {
+        self.inner.memo().map(str::to_string)
+    }
+
+    /// Get the ID (hex) of the payment request this IOU pays, if any
+    pub fn request_id(&self) -> Option<String> {
+        self.inner.iou().request_id().map(|id| hex::encode(id.as_bytes()))
+    }
+
+    /// Check whether this IOU pays the given payment request bytes
+    pub fn fulfills(&self, request_bytes: Vec<u8>) -> Result<bool, MeshError> {
+        let request = SignedPaymentRequest::from_bytes(&request_bytes)
+            .map_err(|e| MeshError::serialization(e.to_string()))?;
+        Ok(self.inner.fulfills(request.request()))
+    }
+
+    /// Serialize to bytes (for transmission)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        IOUCodec::encode(&self.inner)
+    }
+
+    /// Encode as a versioned, checksummed QR string (`p2pm:1:...`)
+    pub fn to_qr_string(&self) -> String {
+        IOUCodec::encode_qr(&self.inner)
+    }
+
+    /// Verify the signature against sender's public key (from DID)
+    pub fn verify(&self) -> Result<bool, MeshError> {
+        let sender_did = Did::parse(&self.sender())
+            .map_err(|_| MeshError::InvalidKey)?;
+        let sender_pubkey = sender_did.public_key()
+            .map_err(|_| MeshError::InvalidKey)?;
+        Ok(self.inner.verify(&sender_pubkey))
+    }
+}
+
+#[uniffi::export]
+pub fn signed_iou_from_bytes(data: Vec<u8>) -> Result<Arc<SignedIOU>, MeshError> {
+    let inner = IOUCodec::decode(&data)
+        .map_err(|e| MeshError::serialization(e.to_string()))?;
+    Ok(Arc::new(SignedIOU { inner }))
+}
+
+/// Decode a SignedIOU from a string produced by `to_qr_string()`.
+/// Prefix, version and checksum are validated; failures carry the reason.
+#[uniffi::export]
+pub fn signed_iou_from_qr_string(s: String) -> Result<Arc<SignedIOU>, MeshError> {
+    let inner = IOUCodec::decode_qr(&s)
+        .map_err(|e| MeshError::serialization(e.to_string()))?;
+    Ok(Arc::new(SignedIOU { inner }))
+}
+
+// ============================================================================
+// REJECTIONS
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum RejectionReason {
+    Declined,
+    WrongAmount,
+    Expired,
+    UnknownSender,
+    Other,
+}
+
+impl From<RejectionReason> for CoreRejectionReason {
+    fn from(reason: RejectionReason) -> Self {
+        match reason {
+            RejectionReason::Declined => CoreRejectionReason::Declined,
+            RejectionReason::WrongAmount => CoreRejectionReason::WrongAmount,
+            RejectionReason::Expired => CoreRejectionReason::Expired,
+            RejectionReason::UnknownSender => CoreRejectionReason::UnknownSender,
+            RejectionReason::Other => CoreRejectionReason::Other,
+        }
+    }
+}
+
+impl From<CoreRejectionReason> for RejectionReason {
+    fn from(reason: CoreRejectionReason) -> Self {
+        match reason {
+            CoreRejectionReason::Declined => RejectionReason::Declined,
+            CoreRejectionReason::WrongAmount => RejectionReason::WrongAmount,
+            CoreRejectionReason::Expired => RejectionReason::Expired,
+            CoreRejectionReason::UnknownSender => RejectionReason::UnknownSender,
+            CoreRejectionReason::Other => RejectionReason::Other,
+        }
+    }
+}
 
 #[derive(uniffi::Object)]
-pub struct SignedIOU {
-    inner: CoreSignedIOU,
+pub struct SignedRejection {
+    inner: CoreSignedRejection,
 }
 
 #[uniffi::export]
-impl SignedIOU {
-    /// Get unique ID as hex string
-    pub fn id(&self) -> String {
-        hex::encode(self.inner.id().as_bytes())
-    }
-
-    /// Get sender DID
-    pub fn sender(&self) -> String {
-        self.inner.iou().sender().to_string()
+impl SignedRejection {
+    /// ID of the rejected IOU as hex string
+    pub fn iou_id(&self) -> String {
+        hex::encode(self.inner.rejection().iou_id().as_bytes())
     }
 
-    /// Get recipient DID
+    /// DID of the recipient who rejected the IOU
     pub fn recipient(&self) -> String {
-        self.inner.iou().recipient().to_string()
+        self.inner.rejection().recipient().to_string()
     }
 
-    /// Get amount
-    pub fn amount(&self) -> u64 {
-        self.inner.iou().amount()
+    /// Why the IOU was rejected
+    pub fn reason(&self) -> RejectionReason {
+        self.inner.rejection().reason().into()
     }
 
-    /// Get timestamp
+    /// When the rejection was issued (Unix seconds)
     pub fn timestamp(&self) -> u64 {
-        self.inner.iou().timestamp()
+        self.inner.rejection().timestamp()
     }
 
-    /// Get nonce
-    pub fn nonce(&self) -> u64 {
-        self.inner.iou().nonce()
+    /// Serialize to bytes for transmission back to the sender
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
     }
+}
 
-    /// Serialize to bytes (for transmission)
-    pub fn to_bytes(&self) -> Vec<u8> {
-        postcard::to_allocvec(&self.inner).unwrap_or_default()
+#[uniffi::export]
+pub fn signed_rejection_from_bytes(data: Vec<u8>) -> Result<Arc<SignedRejection>, MeshError> {
+    let inner = CoreSignedRejection::from_bytes(&data)
+        .map_err(|e| MeshError::serialization(e.to_string()))?;
+    Ok(Arc::new(SignedRejection { inner }))
+}
+
+#[derive(uniffi::Object)]
+pub struct SignedCancellation {
+    inner: CoreSignedCancellation,
+}
+
+#[uniffi::export]
+impl SignedCancellation {
+    /// ID of the cancelled IOU as hex string
+    pub fn iou_id(&self) -> String {
+        hex::encode(self.inner.cancellation().iou_id().as_bytes())
     }
 
-    /// Verify the signature against sender's public key (from DID)
-    pub fn verify(&self) -> Result<bool, MeshError> {
-        let sender_did = Did::parse(&self.sender())
-            .map_err(|_| MeshError::InvalidKey)?;
-        let sender_pubkey = sender_did.public_key()
-            .map_err(|_| MeshError::InvalidKey)?;
-        Ok(self.inner.verify(&sender_pubkey))
+    /// DID of the sender who cancelled the IOU
+    pub fn sender(&self) -> String {
+        self.inner.cancellation().sender().to_string()
+    }
+
+    /// When the cancellation was issued (Unix seconds)
+    pub fn timestamp(&self) -> u64 {
+        self.inner.cancellation().timestamp()
+    }
+
+    /// Serialize to bytes for transmission to the recipient
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
     }
 }
 
 #[uniffi::export]
-pub fn signed_iou_from_bytes(data: Vec<u8>) -> Result<Arc<SignedIOU>, MeshError> {
-    let inner: CoreSignedIOU = postcard::from_bytes(&data)
-        .map_err(|_| MeshError::SerializationError)?;
-    Ok(Arc::new(SignedIOU { inner }))
+pub fn signed_cancellation_from_bytes(data: Vec<u8>) -> Result<Arc<SignedCancellation>, MeshError> {
+    let inner = CoreSignedCancellation::from_bytes(&data)
+        .map_err(|e| MeshError::serialization(e.to_string()))?;
+    Ok(Arc::new(SignedCancellation { inner }))
 }
 
 // ============================================================================
@@ -435,14 +1581,65 @@ pub struct MeshNode {
     wallet: Arc<Wallet>,
     sync_count: Mutex<u64>,
     last_sync: Mutex<u64>,
+    events: EventSlot,
+    /// Smallest state or delta payload to deflate (0 = never compress)
+    compression_min_bytes: Mutex<u64>,
 }
 
-#[derive(uniffi::Record)]
+#[derive(Clone, uniffi::Record)]
 pub struct MergeResult {
     pub new_entries: u64,
     pub total_entries: u64,
 }
 
+/// A double spend seen in the mesh state, with signed evidence to hand to support
+#[derive(uniffi::Record)]
+pub struct ConflictSummary {
+    /// Hex ID of the contested UTXO or nonce slot
+    pub utxo_id: String,
+    /// DID of the sender who double-spent
+    pub sender: String,
+    /// Hex IDs of the IOU seen first and the one conflicting with it
+    pub first_iou_id: String,
+    pub second_iou_id: String,
+    /// When this node first saw each IOU (Unix milliseconds)
+    pub first_seen: u64,
+    pub second_seen: u64,
+    /// Whether both IOUs reuse one nonce
+    pub nonce_reuse: bool,
+    /// Evidence signed by this node's wallet key, for `verify_conflict_evidence`
+    pub evidence: Vec<u8>,
+    /// The same evidence as JSON
+    pub evidence_json: String,
+}
+
+impl From<ConflictEvidence> for ConflictSummary {
+    fn from(evidence: ConflictEvidence) -> Self {
+        Self {
+            utxo_id: hex::encode(evidence.utxo_id().as_bytes()),
+            sender: evidence.first().iou().sender().to_string(),
+            first_iou_id: hex::encode(evidence.first().id().as_bytes()),
+            second_iou_id: hex::encode(evidence.second().id().as_bytes()),
+            first_seen: evidence.first_seen(),
+            second_seen: evidence.second_seen(),
+            nonce_reuse: evidence.is_nonce_reuse(),
+            evidence: evidence.to_bytes(),
+            evidence_json: evidence.to_json(),
+        }
+    }
+}
+
+/// Check that `evidence` from `MeshNode::conflicts` proves the holder of
+/// `sender_pubkey` double-spent. Returns false if it doesn't.
+#[uniffi::export]
+pub fn verify_conflict_evidence(evidence: Vec<u8>, sender_pubkey: Vec<u8>) -> Result<bool, MeshError> {
+    let evidence = ConflictEvidence::from_bytes(&evidence)
+        .map_err(|e| MeshError::serialization(e.to_string()))?;
+    let sender_pubkey = PublicKey::from_bytes(&sender_pubkey)
+        .map_err(|_| MeshError::InvalidKey)?;
+    Ok(verify_evidence(&evidence, &sender_pubkey).is_ok())
+}
+
 #[derive(uniffi::Record)]
 pub struct SyncStats {
     pub total_ious: u64,
@@ -458,46 +1655,120 @@ impl MeshNode {
             wallet,
             sync_count: Mutex::new(0),
             last_sync: Mutex::new(0),
+            events: Arc::new(Mutex::new(None)),
+            compression_min_bytes: Mutex::new(0),
         })
     }
 
+    /// Deflate outgoing state and delta payloads of at least `min_bytes` (0 = off)
+    ///
+    /// Compressed payloads carry a one-byte header; incoming ones are detected
+    /// either way, but peers must be new enough to read them.
+    pub fn set_compression(&self, min_bytes: u64) {
+        *self.compression_min_bytes.lock().unwrap() = min_bytes;
+    }
+
+    /// Register a listener for payment, sync and peer events (replaces any previous one)
+    pub fn set_listener(&self, listener: Arc<dyn MeshEventListener>) {
+        *self.events.lock().unwrap() = Some(listener);
+    }
+
+    /// Remove the registered listener
+    pub fn clear_listener(&self) {
+        *self.events.lock().unwrap() = None;
+    }
+
+    /// Report `transport`'s new peer connections to this node's listener
+    pub fn attach_transport(&self, transport: Arc<Transport>) {
+        *transport.events.lock().unwrap() = Some(self.events.clone());
+    }
+
     /// Get the local mesh state as bytes
     pub fn get_state(&self) -> Vec<u8> {
-        self.wallet.mesh_state.lock().unwrap().to_bytes()
+        let state = self.wallet.mesh_state.lock().unwrap().to_bytes();
+        self.pack(&state)
     }
 
-    /// Merge remote state (from another node)
+    /// Merge remote state (from another node), compressed or not
     pub fn merge_state(&self, remote_state: Vec<u8>) -> Result<MergeResult, MeshError> {
-        let remote = MeshState::from_bytes(&remote_state)
-            .map_err(|_| MeshError::SerializationError)?;
+        let remote = decode_payload(&remote_state, MeshState::from_bytes)
+            .map_err(|e| MeshError::serialization(e.to_string()))?;
 
         let mut local = self.wallet.mesh_state.lock().unwrap();
+        let incoming = self.incoming_payments(&local, remote.all_entries());
         let result = local.merge(&remote);
+        let received = Self::merged(&local, incoming);
+        let total_entries = local.iou_count() as u64;
+        drop(local);
 
-        // Update sync stats
-        *self.sync_count.lock().unwrap() += 1;
-        *self.last_sync.lock().unwrap() = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-
-        Ok(MergeResult {
-            new_entries: result.new_entries as u64,
-            total_entries: local.iou_count() as u64,
-        })
+        Ok(self.record_sync(result.new_entries as u64, total_entries, received))
     }
 
     /// Get delta (what we have that remote doesn't)
     pub fn get_delta(&self, remote_state: Vec<u8>) -> Vec<u8> {
-        let remote = match MeshState::from_bytes(&remote_state) {
+        let remote = match decode_payload(&remote_state, MeshState::from_bytes) {
             Ok(s) => s,
             Err(_) => return Vec::new(),
         };
 
         let local = self.wallet.mesh_state.lock().unwrap();
-        let delta = local.delta(&remote);
+        let delta = postcard::to_allocvec(&local.delta(&remote)).unwrap_or_default();
+        drop(local);
+
+        self.pack(&delta)
+    }
+
+    /// Compact bloom summary of the local state, to send instead of `get_state()`
+    pub fn get_bloom_summary(&self, fp_rate: f64) -> Vec<u8> {
+        let local = self.wallet.mesh_state.lock().unwrap();
+        local.bloom_summary(fp_rate).to_bytes()
+    }
+
+    /// Get delta against a peer's bloom summary (entries the peer probably lacks).
+    /// Same encoding as `get_delta`.
+    pub fn get_delta_from_bloom(&self, summary: Vec<u8>) -> Result<Vec<u8>, MeshError> {
+        let summary = BloomSummary::from_bytes(&summary)
+            .map_err(|e| MeshError::serialization(e.to_string()))?;
+
+        let local = self.wallet.mesh_state.lock().unwrap();
+        let delta = postcard::to_allocvec(&local.delta_against_bloom(&summary)).unwrap_or_default();
+        drop(local);
+
+        Ok(self.pack(&delta))
+    }
+
+    /// Merkle summary of the local state, to send instead of `get_state()`
+    ///
+    /// Its size doesn't grow with the state, and the delta against it only
+    /// carries the few buckets that differ.
+    pub fn get_summary(&self) -> Vec<u8> {
+        let local = self.wallet.mesh_state.lock().unwrap();
+        local.summary().to_bytes()
+    }
+
+    /// Get the delta against a peer's Merkle summary, for the peer's `apply_delta`
+    pub fn get_delta_from_summary(&self, summary: Vec<u8>) -> Result<Vec<u8>, MeshError> {
+        let summary = StateSummary::from_bytes(&summary)
+            .map_err(|e| MeshError::serialization(e.to_string()))?;
+
+        let delta = self.wallet.mesh_state.lock().unwrap().diff_against_summary(&summary).to_bytes();
+        Ok(self.pack(&delta))
+    }
+
+    /// Merge a delta from `get_delta_from_summary`, compressed or not; invalid
+    /// IOUs in it are skipped
+    pub fn apply_delta(&self, delta: Vec<u8>) -> Result<MergeResult, MeshError> {
+        let delta = decode_payload(&delta, Delta::from_bytes)
+            .map_err(|e| MeshError::serialization(e.to_string()))?;
+
+        let mut local = self.wallet.mesh_state.lock().unwrap();
+        let incoming = self.incoming_payments(&local, delta.entries().iter());
+        let result = local.apply_delta(delta);
+        let received = Self::merged(&local, incoming);
+        let total_entries = local.iou_count() as u64;
+        drop(local);
 
-        postcard::to_allocvec(&delta).unwrap_or_default()
+        Ok(self.record_sync(result.new_entries as u64, total_entries, received))
     }
 
     /// Get sync statistics
@@ -516,12 +1787,81 @@ impl MeshNode {
     pub fn iou_count(&self) -> u64 {
         self.wallet.mesh_state.lock().unwrap().iou_count() as u64
     }
+
+    /// Double spends in the mesh state: IOUs from one sender reusing a nonce.
+    /// Each comes with evidence signed by this node's wallet key.
+    pub fn conflicts(&self) -> Vec<ConflictSummary> {
+        let mut detector = ConflictDetector::new();
+        let local = self.wallet.mesh_state.lock().unwrap();
+        for entry in local.all_entries() {
+            // A conflict is recorded either way; it's exported below
+            let _ = detector.register_entry(entry);
+        }
+        drop(local);
+
+        detector
+            .export_all_evidence(&self.wallet.keypair)
+            .into_iter()
+            .map(ConflictSummary::from)
+            .collect()
+    }
+}
+
+impl MeshNode {
+    /// Pack outgoing state or delta bytes under the compression setting
+    fn pack(&self, bytes: &[u8]) -> Vec<u8> {
+        pack_payload(bytes, *self.compression_min_bytes.lock().unwrap() as usize)
+    }
+
+    /// Entries paying this node's wallet that `local` doesn't have yet
+    fn incoming_payments<'a>(
+        &self,
+        local: &MeshState,
+        entries: impl IntoIterator<Item = &'a IOUEntry>,
+    ) -> Vec<CoreSignedIOU> {
+        entries
+            .into_iter()
+            .filter(|entry| entry.iou().iou().amount_for(&self.wallet.did) > 0)
+            .filter(|entry| !local.has_iou(&entry.id()))
+            .map(|entry| entry.iou().clone())
+            .collect()
+    }
+
+    /// The `incoming` IOUs the merge actually accepted
+    fn merged(local: &MeshState, incoming: Vec<CoreSignedIOU>) -> Vec<CoreSignedIOU> {
+        incoming.into_iter().filter(|iou| local.has_iou(&iou.id())).collect()
+    }
+
+    /// Update sync stats and notify the listeners after a merge (no locks held)
+    fn record_sync(&self, new_entries: u64, total_entries: u64, received: Vec<CoreSignedIOU>) -> MergeResult {
+        *self.sync_count.lock().unwrap() += 1;
+        *self.last_sync.lock().unwrap() = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        if let Some(listener) = self.wallet.listener() {
+            listener.on_sync_completed(self.stats());
+        }
+
+        let result = MergeResult { new_entries, total_entries };
+        if let Some(listener) = event_listener(&self.events) {
+            for inner in received {
+                listener.on_payment_received(Arc::new(SignedIOU { inner }));
+            }
+            listener.on_sync_complete(result.clone());
+        }
+        result
+    }
 }
 
 // ============================================================================
 // TRANSPORT
 // ============================================================================
 
+/// Largest message `Transport::send` accepts; a peer announcing a bigger frame is dropped
+const MAX_FRAME_BYTES: usize = 1 << 20;
+
 #[derive(Clone, uniffi::Record)]
 pub struct PeerInfo {
     pub address: String,
@@ -529,73 +1869,296 @@ pub struct PeerInfo {
     pub connected: bool,
 }
 
+/// A whole message received from a peer
+#[derive(Clone, uniffi::Record)]
+pub struct ReceivedMessage {
+    /// Address of the peer as listed in `connected_peers`
+    pub address: String,
+    pub data: Vec<u8>,
+}
+
+/// A live TCP connection and any partial frame received on it
+struct Link {
+    id: ConnectionId,
+    address: String,
+    pending: Vec<u8>,
+}
+
+impl Link {
+    fn peer_info(&self) -> PeerInfo {
+        PeerInfo {
+            address: self.address.clone(),
+            transport_type: "tcp".to_string(),
+            connected: true,
+        }
+    }
+
+    /// Append received bytes and split off every complete frame.
+    /// Frames are a big-endian u32 length followed by the message; `None` if the
+    /// peer announced a frame over `MAX_FRAME_BYTES`.
+    fn take_frames(&mut self, data: &[u8]) -> Option<Vec<Vec<u8>>> {
+        self.pending.extend_from_slice(data);
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        while self.pending.len() - offset >= 4 {
+            let header: [u8; 4] = self.pending[offset..offset + 4].try_into().unwrap();
+            let len = u32::from_be_bytes(header) as usize;
+            if len > MAX_FRAME_BYTES {
+                return None;
+            }
+            if self.pending.len() - offset - 4 < len {
+                break;
+            }
+            frames.push(self.pending[offset + 4..offset + 4 + len].to_vec());
+            offset += 4 + len;
+        }
+        self.pending.drain(..offset);
+        Some(frames)
+    }
+}
+
+/// Bridge to the core `TcpTransport`, driven synchronously on a private runtime.
+/// Incoming connections and data are picked up whenever the transport is queried.
 #[derive(uniffi::Object)]
 pub struct Transport {
-    peers: Mutex<Vec<PeerInfo>>,
+    runtime: tokio::runtime::Runtime,
+    inner: Mutex<TcpTransport>,
+    links: Mutex<Vec<Link>>,
+    inbox: Mutex<Vec<ReceivedMessage>>,
     bind_address: String,
+    /// Listener slot of the node this transport is attached to
+    events: Mutex<Option<EventSlot>>,
+}
+
+impl Transport {
+    /// Apply pending core transport events; returns peers that connected to us
+    fn pump(&self, inner: &mut TcpTransport) -> Vec<PeerInfo> {
+        let events = self.runtime.block_on(inner.poll_events());
+        let mut links = self.links.lock().unwrap();
+        let mut inbox = self.inbox.lock().unwrap();
+        let mut connected = Vec::new();
+        let mut oversized = Vec::new();
+
+        for event in events {
+            match event {
+                TransportEvent::Connected { connection_id, address } => {
+                    // Outbound links are recorded by connect()
+                    if links.iter().any(|l| l.id == connection_id) {
+                        continue;
+                    }
+                    let link = Link {
+                        id: connection_id,
+                        address: tcp_address_string(&address),
+                        pending: Vec::new(),
+                    };
+                    connected.push(link.peer_info());
+                    links.push(link);
+                }
+                TransportEvent::Disconnected { connection_id, .. } => {
+                    links.retain(|l| l.id != connection_id);
+                }
+                TransportEvent::MessageReceived { connection_id, data } => {
+                    let Some(link) = links.iter_mut().find(|l| l.id == connection_id) else {
+                        continue;
+                    };
+                    match link.take_frames(&data) {
+                        Some(frames) => inbox.extend(frames.into_iter().map(|data| ReceivedMessage {
+                            address: link.address.clone(),
+                            data,
+                        })),
+                        None => oversized.push(connection_id),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for id in oversized {
+            links.retain(|l| l.id != id);
+            let _ = self.runtime.block_on(inner.disconnect(&id));
+        }
+        connected
+    }
+
+    fn notify_connected(&self, peers: Vec<PeerInfo>) {
+        if peers.is_empty() {
+            return;
+        }
+        let listener = self.events.lock().unwrap().as_ref().and_then(event_listener);
+        if let Some(listener) = listener {
+            for peer in peers {
+                listener.on_peer_connected(peer);
+            }
+        }
+    }
+
+    /// Pick up incoming connections and data
+    fn refresh(&self) {
+        let connected = self.pump(&mut self.inner.lock().unwrap());
+        self.notify_connected(connected);
+    }
 }
 
 #[uniffi::export]
 impl Transport {
-    /// Connect to a peer
+    /// Connect to a peer at `host:port`
     pub fn connect(&self, address: String) -> Result<(), MeshError> {
-        let mut peers = self.peers.lock().unwrap();
+        let peer_address = parse_tcp_address(&address)?;
+        let mut inner = self.inner.lock().unwrap();
+        let mut connected = self.pump(&mut inner);
 
         // Check if already connected
-        if peers.iter().any(|p| p.address == address) {
+        if self.links.lock().unwrap().iter().any(|l| l.address == address) {
+            drop(inner);
+            self.notify_connected(connected);
             return Ok(());
         }
 
-        peers.push(PeerInfo {
-            address,
-            transport_type: "tcp".to_string(),
-            connected: true,
-        });
-        Ok(())
+        let result = self.runtime.block_on(inner.connect(peer_address));
+        if let Ok(id) = result.as_ref() {
+            let link = Link {
+                id: id.clone(),
+                address,
+                pending: Vec::new(),
+            };
+            connected.push(link.peer_info());
+            self.links.lock().unwrap().push(link);
+        }
+        drop(inner);
+
+        self.notify_connected(connected);
+        result.map(|_| ()).map_err(|_| MeshError::TransportError)
     }
 
     /// Disconnect from a peer
     pub fn disconnect(&self, address: String) {
-        let mut peers = self.peers.lock().unwrap();
-        peers.retain(|p| p.address != address);
+        let mut inner = self.inner.lock().unwrap();
+        let mut links = self.links.lock().unwrap();
+        let ids: Vec<ConnectionId> = links
+            .iter()
+            .filter(|l| l.address == address)
+            .map(|l| l.id.clone())
+            .collect();
+        links.retain(|l| l.address != address);
+        drop(links);
+
+        for id in ids {
+            let _ = self.runtime.block_on(inner.disconnect(&id));
+        }
     }
 
-    /// Send data to a peer
-    pub fn send(&self, address: String, _data: Vec<u8>) -> Result<(), MeshError> {
-        let peers = self.peers.lock().unwrap();
-        if !peers.iter().any(|p| p.address == address && p.connected) {
+    /// Send one message to a connected peer; it arrives whole in the peer's `receive`
+    pub fn send(&self, address: String, data: Vec<u8>) -> Result<(), MeshError> {
+        if data.len() > MAX_FRAME_BYTES {
             return Err(MeshError::TransportError);
         }
-        // In real implementation, this would send over network
-        Ok(())
+        let mut inner = self.inner.lock().unwrap();
+        let connected = self.pump(&mut inner);
+        let id = self
+            .links
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|l| l.address == address)
+            .map(|l| l.id.clone());
+
+        let result = match id {
+            Some(id) => {
+                let mut frame = Vec::with_capacity(4 + data.len());
+                frame.extend_from_slice(&(data.len() as u32).to_be_bytes());
+                frame.extend_from_slice(&data);
+                self.runtime
+                    .block_on(inner.send(&id, &frame))
+                    .map(|_| ())
+                    .map_err(|_| MeshError::TransportError)
+            }
+            None => Err(MeshError::TransportError),
+        };
+        drop(inner);
+
+        self.notify_connected(connected);
+        result
+    }
+
+    /// Take the messages received since the last call, oldest first
+    pub fn receive(&self) -> Vec<ReceivedMessage> {
+        self.refresh();
+        std::mem::take(&mut *self.inbox.lock().unwrap())
     }
 
     /// Get list of connected peers
     pub fn connected_peers(&self) -> Vec<PeerInfo> {
-        self.peers.lock().unwrap().clone()
+        self.refresh();
+        self.links.lock().unwrap().iter().map(Link::peer_info).collect()
     }
 
     /// Check if connected to any peers
     pub fn is_connected(&self) -> bool {
-        !self.peers.lock().unwrap().is_empty()
+        self.peer_count() > 0
     }
 
     /// Get peer count
     pub fn peer_count(&self) -> u64 {
-        self.peers.lock().unwrap().len() as u64
+        self.refresh();
+        self.links.lock().unwrap().len() as u64
     }
 
-    /// Get bind address
+    /// Get the address the transport listens on, with the bound port filled in
     pub fn bind_address(&self) -> String {
         self.bind_address.clone()
     }
 }
 
+/// Parse `host:port` into a core TCP address
+fn parse_tcp_address(address: &str) -> Result<PeerAddress, MeshError> {
+    let (host, port) = address.rsplit_once(':').ok_or(MeshError::TransportError)?;
+    let port = port.parse().map_err(|_| MeshError::TransportError)?;
+    Ok(PeerAddress::tcp(host, port))
+}
+
+fn tcp_address_string(address: &PeerAddress) -> String {
+    match address {
+        PeerAddress::Tcp { host, port } => format!("{}:{}", host, port),
+        other => other.to_string(),
+    }
+}
+
+/// Start a TCP transport listening on `bind_address` (`host:port`, port 0 for any)
 #[uniffi::export]
 pub fn create_tcp_transport(bind_address: String) -> Result<Arc<Transport>, MeshError> {
+    let bind = parse_tcp_address(&bind_address)?;
+    let PeerAddress::Tcp { host, port } = bind else {
+        return Err(MeshError::TransportError);
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("p2pmesh-transport")
+        .enable_all()
+        .build()
+        .map_err(|_| MeshError::TransportError)?;
+
+    // Heartbeats are written unframed, so the bridge leaves them off
+    let config = TcpTransportConfig::new()
+        .with_bind_address(&host)
+        .with_bind_port(port)
+        .with_keepalive_secs(None)
+        .with_base_config(TransportConfig::new().with_connection_timeout(10));
+    let mut inner = TcpTransport::new(config);
+    runtime
+        .block_on(inner.start())
+        .map_err(|_| MeshError::TransportError)?;
+    let bind_address = inner
+        .local_address()
+        .map(|address| tcp_address_string(&address))
+        .unwrap_or(bind_address);
+
     Ok(Arc::new(Transport {
-        peers: Mutex::new(Vec::new()),
+        runtime,
+        inner: Mutex::new(inner),
+        links: Mutex::new(Vec::new()),
+        inbox: Mutex::new(Vec::new()),
         bind_address,
+        events: Mutex::new(None),
     }))
 }
 
@@ -615,6 +2178,11 @@ impl SettlementBatch {
         hex::encode(self.inner.id().as_bytes())
     }
 
+    /// Get the key a settlement backend should deduplicate on (hex)
+    pub fn idempotency_key(&self) -> String {
+        self.inner.idempotency_key()
+    }
+
     /// Get number of entries
     pub fn entry_count(&self) -> u64 {
         self.inner.entries().len() as u64
@@ -820,63 +2388,178 @@ pub fn faucet_did() -> String {
     did.to_string()
 }
 
-/// Request funds from the faucet.
-/// Returns a signed IOU that can be processed by the recipient's wallet.
-///
-/// # Arguments
-/// * `recipient_did` - The DID of the wallet requesting funds (e.g., "did:mesh:abc123...")
-/// * `amount` - The amount of credits to receive
-///
-/// # Returns
-/// A SignedIOU from the faucet to the recipient
+/// Limits applied by a `Faucet`
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct FaucetConfig {
+    /// Largest amount a single request may mint
+    pub max_amount_per_request: u64,
+    /// Requests allowed per recipient DID within the window
+    pub max_requests_per_recipient: u32,
+    /// Length of the sliding rate-limit window in seconds
+    pub window_secs: u64,
+}
+
+impl Default for FaucetConfig {
+    fn default() -> Self {
+        Self {
+            max_amount_per_request: 1_000_000,
+            max_requests_per_recipient: 10,
+            window_secs: 3600,
+        }
+    }
+}
+
+/// Default faucet limits (1,000,000 per request, 10 requests per recipient per hour)
 #[uniffi::export]
-pub fn request_from_faucet(recipient_did: String, amount: u64) -> Result<Arc<SignedIOU>, MeshError> {
-    if amount == 0 {
-        return Err(MeshError::InvalidIOU);
+pub fn default_faucet_config() -> FaucetConfig {
+    FaucetConfig::default()
+}
+
+/// Rate-limited faucet: caps each request's amount and how often a recipient may request.
+/// Limits that are exceeded return `MeshError::RateLimited`.
+#[derive(uniffi::Object)]
+pub struct Faucet {
+    config: FaucetConfig,
+    /// Recipient DID -> times of requests still inside the window
+    requests: Mutex<HashMap<String, Vec<Instant>>>,
+}
+
+#[uniffi::export]
+impl Faucet {
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Self::with_config(FaucetConfig::default())
     }
 
-    // Parse recipient DID
-    let recipient = Did::parse(&recipient_did)
-        .map_err(|_| MeshError::InvalidKey)?;
+    #[uniffi::constructor]
+    pub fn with_config(config: FaucetConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            requests: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Get the faucet's limits
+    pub fn config(&self) -> FaucetConfig {
+        self.config.clone()
+    }
+
+    /// Request a signed IOU from the faucet to `recipient_did`
+    pub fn request(&self, recipient_did: String, amount: u64) -> Result<Arc<SignedIOU>, MeshError> {
+        if amount == 0 {
+            return Err(MeshError::InvalidIOU);
+        }
+        let recipient = Did::parse(&recipient_did)
+            .map_err(|_| MeshError::InvalidKey)?;
+        if amount > self.config.max_amount_per_request {
+            return Err(MeshError::RateLimited);
+        }
+
+        let now = Instant::now();
+        let window = Duration::from_secs(self.config.window_secs);
+        let mut requests = self.requests.lock().unwrap();
+        let recent = requests.entry(recipient.to_string()).or_default();
+        recent.retain(|at| now.duration_since(*at) < window);
+        if recent.len() >= self.config.max_requests_per_recipient as usize {
+            return Err(MeshError::RateLimited);
+        }
+        recent.push(now);
+        drop(requests);
 
+        Ok(Arc::new(SignedIOU { inner: mint_faucet_iou(recipient, amount)? }))
+    }
+
+    /// Requests `recipient_did` can still make in the current window
+    pub fn remaining_requests(&self, recipient_did: String) -> u32 {
+        let key = match Did::parse(&recipient_did) {
+            Ok(did) => did.to_string(),
+            Err(_) => return 0,
+        };
+        let now = Instant::now();
+        let window = Duration::from_secs(self.config.window_secs);
+        let requests = self.requests.lock().unwrap();
+        let used = requests
+            .get(&key)
+            .map(|times| times.iter().filter(|at| now.duration_since(**at) < window).count())
+            .unwrap_or(0) as u32;
+        self.config.max_requests_per_recipient.saturating_sub(used)
+    }
+
+    /// Request funds and process them into `wallet` in one call
+    pub fn fund_wallet(&self, wallet: Arc<Wallet>, amount: u64) -> Result<(), MeshError> {
+        let iou = self.request(wallet.did(), amount)?;
+        wallet.process_payment_with_key(iou, faucet_public_key())
+    }
+}
+
+/// Shared faucet used by the free functions
+fn default_faucet() -> &'static Faucet {
+    static FAUCET: OnceLock<Arc<Faucet>> = OnceLock::new();
+    FAUCET.get_or_init(Faucet::new)
+}
+
+/// Next value of the process-wide faucet nonce counter.
+/// Seeded from the clock so separate processes start far apart.
+fn next_faucet_counter() -> u64 {
+    static COUNTER: OnceLock<AtomicU64> = OnceLock::new();
+    COUNTER
+        .get_or_init(|| {
+            let seed = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos() as u64;
+            AtomicU64::new(seed)
+        })
+        .fetch_add(1, Ordering::Relaxed)
+}
+
+/// Build and sign an IOU from the faucet to `recipient` (no limits applied)
+fn mint_faucet_iou(recipient: Did, amount: u64) -> Result<CoreSignedIOU, MeshError> {
     // Create faucet keypair from seed
     let faucet_keypair = Keypair::from_bytes(&FAUCET_SEED)
         .expect("Faucet seed is valid");
 
-    // Generate unique nonce based on recipient and current time
-    let nonce = {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-        // Mix in recipient DID to prevent same-second collisions
-        time ^ (recipient_did.len() as u64 * 31)
-    };
+    // The counter never repeats within a process and XOR with a fixed per-recipient
+    // mask is a bijection, so no recipient is ever issued the same nonce twice
+    let recipient_key = recipient.public_key()
+        .map_err(|_| MeshError::InvalidKey)?;
+    let mut mask = [0u8; 8];
+    mask.copy_from_slice(&recipient_key.as_bytes()[..8]);
+    let nonce = next_faucet_counter() ^ u64::from_le_bytes(mask);
 
     // Build and sign IOU from faucet to recipient
-    let signed_iou = IOUBuilder::new()
+    IOUBuilder::new()
         .sender(&faucet_keypair)
         .recipient(recipient)
         .amount(amount)
         .nonce(nonce)
         .build()
-        .map_err(|_| MeshError::InvalidIOU)?;
+        .map_err(|_| MeshError::InvalidIOU)
+}
 
-    Ok(Arc::new(SignedIOU { inner: signed_iou }))
+/// Request funds from the faucet.
+/// Returns a signed IOU that can be processed by the recipient's wallet.
+/// Uses a shared faucet with the default limits (see `default_faucet_config`).
+///
+/// # Arguments
+/// * `recipient_did` - The DID of the wallet requesting funds (e.g., "did:mesh:abc123...")
+/// * `amount` - The amount of credits to receive
+///
+/// # Returns
+/// A SignedIOU from the faucet to the recipient
+#[uniffi::export]
+pub fn request_from_faucet(recipient_did: String, amount: u64) -> Result<Arc<SignedIOU>, MeshError> {
+    default_faucet().request(recipient_did, amount)
 }
 
 /// Fund a wallet directly from the faucet.
 /// This is a convenience function that requests funds and processes them in one call.
+/// Uses a shared faucet with the default limits (see `default_faucet_config`).
 ///
 /// # Arguments
 /// * `wallet` - The wallet to fund
 /// * `amount` - The amount of credits to add
 #[uniffi::export]
 pub fn fund_wallet_from_faucet(wallet: Arc<Wallet>, amount: u64) -> Result<(), MeshError> {
-    // Get faucet IOU
-    let iou = request_from_faucet(wallet.did(), amount)?;
-
-    // Process it with the faucet's public key
-    wallet.process_payment_with_key(iou, faucet_public_key())
+    default_faucet().fund_wallet(wallet, amount)
 }
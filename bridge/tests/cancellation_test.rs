@@ -0,0 +1,104 @@
+// Cancellation tests for the bridge module
+// Tests the cancel_payment / process_cancellation flow
+
+use p2pmesh_bridge::{create_wallet, fund_wallet_from_faucet, signed_cancellation_from_bytes, MeshError, MeshNode, Wallet};
+use std::sync::Arc;
+
+fn funded_wallet(amount: u64) -> Arc<Wallet> {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), amount).unwrap();
+    wallet
+}
+
+#[test]
+fn test_cancel_payment_restores_balance() {
+    let sender = funded_wallet(100);
+    let recipient = create_wallet().unwrap();
+    let iou = sender.send_payment(recipient.did(), 40).unwrap();
+
+    let cancellation = sender.cancel_payment(iou.id()).unwrap();
+
+    assert_eq!(sender.balance(), 100);
+    assert_eq!(cancellation.iou_id(), iou.id());
+    assert_eq!(cancellation.sender(), sender.did());
+}
+
+#[test]
+fn test_cancelled_payment_refused_by_recipient() {
+    let sender = funded_wallet(100);
+    let recipient = create_wallet().unwrap();
+    let iou = sender.send_payment(recipient.did(), 40).unwrap();
+    recipient.receive_payment(iou.clone()).unwrap();
+
+    let cancellation = sender.cancel_payment(iou.id()).unwrap();
+    let delivered = signed_cancellation_from_bytes(cancellation.to_bytes()).unwrap();
+    recipient.process_cancellation(delivered).unwrap();
+
+    assert!(recipient.pending_ious().is_empty());
+    assert!(matches!(recipient.process_payment(iou.clone()), Err(MeshError::PaymentCancelled)));
+    assert!(matches!(recipient.receive_payment(iou), Err(MeshError::PaymentCancelled)));
+    assert_eq!(recipient.balance(), 0);
+}
+
+#[test]
+fn test_cancellation_spreads_through_sync() {
+    let sender = funded_wallet(100);
+    let recipient = create_wallet().unwrap();
+    let iou = sender.send_payment(recipient.did(), 40).unwrap();
+    sender.cancel_payment(iou.id()).unwrap();
+
+    MeshNode::new(recipient.clone())
+        .merge_state(MeshNode::new(sender.clone()).get_state())
+        .unwrap();
+
+    assert!(matches!(recipient.process_payment(iou), Err(MeshError::PaymentCancelled)));
+}
+
+#[test]
+fn test_accepted_payment_survives_cancellation() {
+    let sender = funded_wallet(100);
+    let recipient = create_wallet().unwrap();
+    let iou = sender.send_payment(recipient.did(), 40).unwrap();
+    recipient.process_payment(iou.clone()).unwrap();
+
+    recipient.process_cancellation(sender.cancel_payment(iou.id()).unwrap()).unwrap();
+
+    assert_eq!(recipient.balance(), 40);
+}
+
+#[test]
+fn test_cancel_twice_fails() {
+    let sender = funded_wallet(100);
+    let recipient = create_wallet().unwrap();
+    let iou = sender.send_payment(recipient.did(), 40).unwrap();
+    sender.cancel_payment(iou.id()).unwrap();
+
+    let result = sender.cancel_payment(iou.id());
+
+    assert!(matches!(result, Err(MeshError::DuplicateTransaction)));
+    assert_eq!(sender.balance(), 100);
+}
+
+#[test]
+fn test_cancel_unknown_payment_fails() {
+    let sender = funded_wallet(100);
+
+    assert!(matches!(sender.cancel_payment("00".repeat(32)), Err(MeshError::InvalidIOU)));
+}
+
+#[test]
+fn test_cancellation_only_affects_named_iou() {
+    let sender = funded_wallet(100);
+    let mallory = funded_wallet(100);
+    let recipient = create_wallet().unwrap();
+    let iou = sender.send_payment(recipient.did(), 40).unwrap();
+    recipient.process_payment(iou.clone()).unwrap();
+    let own = mallory.send_payment(recipient.did(), 1).unwrap();
+    let unrelated = mallory.cancel_payment(own.id()).unwrap();
+
+    // Mallory's cancellation names her own IOU, so it can't touch the sender's
+    recipient.process_cancellation(unrelated).unwrap();
+
+    assert_eq!(recipient.balance(), 40);
+    assert!(recipient.process_payment(own).is_err());
+}
@@ -0,0 +1,65 @@
+// UTXO consolidation tests for the bridge module
+// Tests merging small UTXOs without changing the balance
+
+use p2pmesh_bridge::{create_wallet, fund_wallet_from_faucet, MeshError};
+
+// ============================================================================
+// CONSOLIDATION TESTS
+// ============================================================================
+
+#[test]
+fn test_consolidate_utxos_merges_and_keeps_balance() {
+    let wallet = create_wallet().unwrap();
+    for amount in [10, 20, 30] {
+        fund_wallet_from_faucet(wallet.clone(), amount).unwrap();
+    }
+    assert_eq!(wallet.utxo_count(), 3);
+
+    let iou = wallet.consolidate_utxos(10).unwrap();
+
+    assert_eq!(iou.amount(), 60);
+    assert_eq!(iou.sender(), wallet.did());
+    assert_eq!(iou.recipient(), wallet.did());
+    assert_eq!(wallet.utxo_count(), 1);
+    assert_eq!(wallet.balance(), 60);
+}
+
+#[test]
+fn test_should_consolidate_threshold() {
+    let wallet = create_wallet().unwrap();
+    for amount in [10, 20, 30] {
+        fund_wallet_from_faucet(wallet.clone(), amount).unwrap();
+    }
+
+    assert!(wallet.should_consolidate(3));
+    assert!(!wallet.should_consolidate(4));
+
+    wallet.consolidate_utxos(10).unwrap();
+    assert!(!wallet.should_consolidate(2));
+}
+
+#[test]
+fn test_consolidate_single_utxo_fails() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 100).unwrap();
+
+    let result = wallet.consolidate_utxos(10);
+
+    assert!(matches!(result, Err(MeshError::NothingToConsolidate)));
+    assert_eq!(wallet.balance(), 100);
+}
+
+#[test]
+fn test_send_after_consolidate_uses_next_nonce() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 10).unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 20).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let consolidation = wallet.consolidate_utxos(10).unwrap();
+    let payment = wallet.send_payment(recipient.did(), 5).unwrap();
+
+    assert_eq!(consolidation.nonce(), 1);
+    assert_eq!(payment.nonce(), 2);
+    assert_eq!(wallet.balance(), 25);
+}
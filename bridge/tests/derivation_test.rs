@@ -0,0 +1,122 @@
+// Sub-wallet tests for the bridge module
+// Tests deriving separate wallets from one master mnemonic
+
+use p2pmesh_bridge::{
+    create_wallet, fund_wallet_from_faucet, restore_wallet, restore_wallet_from_mnemonic, MeshError,
+};
+
+// ============================================================================
+// DERIVE SUB-WALLET TESTS
+// ============================================================================
+
+#[test]
+fn test_sub_wallet_has_own_identity_and_vault() {
+    let master = create_wallet().unwrap();
+    fund_wallet_from_faucet(master.clone(), 1000).unwrap();
+
+    let sub = master.derive_sub_wallet("m/44'/0'/1'".to_string()).unwrap();
+
+    assert_ne!(sub.did(), master.did());
+    assert_ne!(sub.secret_key(), master.secret_key());
+    assert_eq!(sub.balance(), 0);
+    assert_eq!(master.balance(), 1000);
+}
+
+#[test]
+fn test_sub_wallet_shares_master_mnemonic() {
+    let master = create_wallet().unwrap();
+    let sub = master.derive_sub_wallet("m/44'/0'/1'".to_string()).unwrap();
+
+    assert_eq!(sub.mnemonic(), master.mnemonic());
+}
+
+#[test]
+fn test_sub_wallet_restores_from_mnemonic() {
+    let master = create_wallet().unwrap();
+    let sub = master.derive_sub_wallet("m/44'/0'/1'".to_string()).unwrap();
+
+    let restored = restore_wallet_from_mnemonic(sub.mnemonic()).unwrap();
+    assert_eq!(restored.did(), master.did());
+
+    let again = restored.derive_sub_wallet("m/44'/0'/1'".to_string()).unwrap();
+    assert_eq!(again.did(), sub.did());
+    assert_eq!(again.secret_key(), sub.secret_key());
+}
+
+#[test]
+fn test_sub_wallet_restores_from_its_secret_key() {
+    let master = create_wallet().unwrap();
+    let sub = master.derive_sub_wallet("m/44'/0'/2'".to_string()).unwrap();
+
+    let restored = restore_wallet(sub.secret_key()).unwrap();
+    assert_eq!(restored.did(), sub.did());
+}
+
+#[test]
+fn test_paths_are_taken_from_master() {
+    let master = create_wallet().unwrap();
+    let first = master.derive_sub_wallet("m/44'/0'/1'".to_string()).unwrap();
+
+    let sibling = first.derive_sub_wallet("m/44'/0'/2'".to_string()).unwrap();
+    let direct = master.derive_sub_wallet("m/44'/0'/2'".to_string()).unwrap();
+
+    assert_eq!(sibling.did(), direct.did());
+    assert_eq!(first.derive_sub_wallet("m/44'/0'/1'".to_string()).unwrap().did(), first.did());
+}
+
+#[test]
+fn test_sub_wallets_pay_each_other() {
+    let master = create_wallet().unwrap();
+    let shop = master.derive_sub_wallet("m/44'/0'/1'".to_string()).unwrap();
+    let till = master.derive_sub_wallet("m/44'/0'/2'".to_string()).unwrap();
+    fund_wallet_from_faucet(shop.clone(), 500).unwrap();
+
+    let iou = shop.create_payment(till.did(), 200).unwrap();
+    shop.mark_sent(iou.clone()).unwrap();
+    till.process_payment(iou).unwrap();
+
+    assert_eq!(shop.balance(), 300);
+    assert_eq!(till.balance(), 200);
+}
+
+#[test]
+fn test_invalid_paths_rejected() {
+    let master = create_wallet().unwrap();
+
+    for path in ["", "44'/0'", "m/44'/0/1'", "m/2147483648'", "m/abc'"] {
+        assert!(
+            matches!(
+                master.derive_sub_wallet(path.to_string()),
+                Err(MeshError::InvalidDerivationPath)
+            ),
+            "{:?} should be rejected",
+            path
+        );
+    }
+}
+
+// ============================================================================
+// SUB-ACCOUNT TESTS
+// ============================================================================
+
+#[test]
+fn test_sub_account_matches_numbered_path() {
+    let master = create_wallet().unwrap();
+
+    let savings = master.derive_sub_account(0).unwrap();
+    let spending = master.derive_sub_account(1).unwrap();
+
+    assert_eq!(savings.did(), master.derive_sub_wallet("m/44'/0'/0'".to_string()).unwrap().did());
+    assert_ne!(savings.did(), spending.did());
+    assert_eq!(spending.mnemonic(), master.mnemonic());
+}
+
+#[test]
+fn test_sub_account_index_out_of_range() {
+    let master = create_wallet().unwrap();
+
+    assert!(matches!(
+        master.derive_sub_account(1 << 31),
+        Err(MeshError::InvalidDerivationPath)
+    ));
+}
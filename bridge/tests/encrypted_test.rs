@@ -0,0 +1,194 @@
+// Encrypted export tests for the bridge module
+// Tests passphrase-protected backup and restore of a wallet
+
+use p2pmesh_bridge::{
+    create_wallet, fund_wallet_from_faucet, restore_wallet, restore_wallet_encrypted,
+    restore_wallet_encrypted_with_limits, MeshError,
+};
+
+/// Cheap Argon2id params so tests stay fast
+const TEST_MEMORY_KIB: u32 = 64;
+const TEST_ITERATIONS: u32 = 1;
+
+// ============================================================================
+// ROUNDTRIP TESTS
+// ============================================================================
+
+#[test]
+fn test_encrypted_export_roundtrip() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 800).unwrap();
+
+    let blob = wallet
+        .export_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, TEST_ITERATIONS)
+        .unwrap();
+    let restored = restore_wallet_encrypted(blob, "hunter2".to_string()).unwrap();
+
+    assert_eq!(restored.did(), wallet.did());
+    assert_eq!(restored.secret_key(), wallet.secret_key());
+    assert_eq!(restored.balance(), 800);
+}
+
+#[test]
+fn test_encrypted_export_default_params() {
+    let wallet = create_wallet().unwrap();
+
+    let blob = wallet.export_encrypted("correct horse".to_string());
+    let restored = restore_wallet_encrypted(blob, "correct horse".to_string()).unwrap();
+
+    assert_eq!(restored.did(), wallet.did());
+}
+
+#[test]
+fn test_encrypted_export_hides_secret_key() {
+    let wallet = create_wallet().unwrap();
+
+    let blob = wallet
+        .export_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, TEST_ITERATIONS)
+        .unwrap();
+
+    let secret = wallet.secret_key();
+    assert!(!blob.windows(secret.len()).any(|w| w == secret.as_slice()));
+}
+
+// ============================================================================
+// FAILURE TESTS
+// ============================================================================
+
+#[test]
+fn test_wrong_passphrase_is_invalid_key() {
+    let wallet = create_wallet().unwrap();
+    let blob = wallet
+        .export_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, TEST_ITERATIONS)
+        .unwrap();
+
+    let result = restore_wallet_encrypted(blob, "hunter3".to_string());
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+}
+
+#[test]
+fn test_garbage_blob_is_serialization_error() {
+    let result = restore_wallet_encrypted(vec![1, 2, 3], "hunter2".to_string());
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
+
+#[test]
+fn test_invalid_params_rejected() {
+    let wallet = create_wallet().unwrap();
+
+    let result = wallet.export_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, 0);
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
+
+// ============================================================================
+// ENCRYPTED VAULT TESTS
+// ============================================================================
+
+#[test]
+fn test_encrypted_vault_roundtrip() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 600).unwrap();
+    let blob = wallet
+        .export_vault_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, TEST_ITERATIONS)
+        .unwrap();
+
+    // Same key on a fresh device, without the vault
+    let fresh = restore_wallet(wallet.secret_key()).unwrap();
+    assert_eq!(fresh.balance(), 0);
+
+    fresh.import_vault_encrypted(blob, "hunter2".to_string()).unwrap();
+    assert_eq!(fresh.balance(), 600);
+    assert_eq!(fresh.transaction_count(), wallet.transaction_count());
+}
+
+#[test]
+fn test_encrypted_vault_default_params() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 50).unwrap();
+
+    let blob = wallet.export_vault_encrypted("correct horse".to_string());
+    let fresh = restore_wallet(wallet.secret_key()).unwrap();
+    fresh.import_vault_encrypted(blob, "correct horse".to_string()).unwrap();
+
+    assert_eq!(fresh.balance(), 50);
+}
+
+#[test]
+fn test_encrypted_vault_wrong_passphrase_is_invalid_key() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 50).unwrap();
+    let blob = wallet
+        .export_vault_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, TEST_ITERATIONS)
+        .unwrap();
+
+    let result = wallet.import_vault_encrypted(blob, "hunter3".to_string());
+
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+    assert_eq!(wallet.balance(), 50);
+}
+
+#[test]
+fn test_encrypted_vault_garbage_is_serialization_error() {
+    let wallet = create_wallet().unwrap();
+
+    let result = wallet.import_vault_encrypted(vec![1, 2, 3], "hunter2".to_string());
+
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
+
+#[test]
+fn test_encrypted_vault_of_other_wallet_rejected() {
+    let wallet = create_wallet().unwrap();
+    let other = create_wallet().unwrap();
+    fund_wallet_from_faucet(other.clone(), 70).unwrap();
+    let blob = other
+        .export_vault_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, TEST_ITERATIONS)
+        .unwrap();
+
+    let result = wallet.import_vault_encrypted(blob, "hunter2".to_string());
+
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+    assert_eq!(wallet.balance(), 0);
+}
+
+// ============================================================================
+// COST LIMIT TESTS
+// ============================================================================
+
+/// One pass more than the default, so only an explicit limit accepts it
+const COSTLY_ITERATIONS: u32 = 3;
+
+#[test]
+fn test_restore_above_default_cost_needs_limit() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 40).unwrap();
+    let blob = wallet
+        .export_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, COSTLY_ITERATIONS)
+        .unwrap();
+
+    let result = restore_wallet_encrypted(blob.clone(), "hunter2".to_string());
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+
+    let restored =
+        restore_wallet_encrypted_with_limits(blob, "hunter2".to_string(), TEST_MEMORY_KIB, COSTLY_ITERATIONS)
+            .unwrap();
+    assert_eq!(restored.balance(), 40);
+}
+
+#[test]
+fn test_import_vault_above_default_cost_needs_limit() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 60).unwrap();
+    let blob = wallet
+        .export_vault_encrypted_with_params("hunter2".to_string(), TEST_MEMORY_KIB, COSTLY_ITERATIONS)
+        .unwrap();
+    let fresh = restore_wallet(wallet.secret_key()).unwrap();
+
+    let result = fresh.import_vault_encrypted(blob.clone(), "hunter2".to_string());
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+
+    fresh
+        .import_vault_encrypted_with_limits(blob, "hunter2".to_string(), TEST_MEMORY_KIB, COSTLY_ITERATIONS)
+        .unwrap();
+    assert_eq!(fresh.balance(), 60);
+}
@@ -0,0 +1,246 @@
+// Mesh event listener tests for the bridge module
+// Tests that MeshEventListener callbacks fire for synced payments, merges and peer
+// connections, including when the node is driven from background threads
+
+use p2pmesh_bridge::{
+    create_tcp_transport, create_wallet, fund_wallet_from_faucet, MergeResult, MeshEventListener,
+    MeshNode, PeerInfo, SignedIOU, Transport,
+};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    PaymentReceived(u64),
+    SyncComplete(u64),
+    PeerConnected(String),
+}
+
+/// Forwards every callback to a channel; optionally calls back into the node to catch deadlocks
+struct ChannelListener {
+    events: Mutex<Sender<Event>>,
+    node: Mutex<Option<Arc<MeshNode>>>,
+}
+
+impl ChannelListener {
+    fn new() -> (Arc<Self>, Receiver<Event>) {
+        let (tx, rx) = mpsc::channel();
+        let listener = Arc::new(Self {
+            events: Mutex::new(tx),
+            node: Mutex::new(None),
+        });
+        (listener, rx)
+    }
+
+    fn reentrant(node: Arc<MeshNode>) -> (Arc<Self>, Receiver<Event>) {
+        let (listener, rx) = Self::new();
+        *listener.node.lock().unwrap() = Some(node);
+        (listener, rx)
+    }
+
+    fn send(&self, event: Event) {
+        if let Some(node) = self.node.lock().unwrap().as_ref() {
+            let _ = node.stats();
+            let _ = node.get_state();
+        }
+        let _ = self.events.lock().unwrap().send(event);
+    }
+}
+
+impl MeshEventListener for ChannelListener {
+    fn on_payment_received(&self, iou: Arc<SignedIOU>) {
+        self.send(Event::PaymentReceived(iou.amount()));
+    }
+
+    fn on_sync_complete(&self, result: MergeResult) {
+        self.send(Event::SyncComplete(result.new_entries));
+    }
+
+    fn on_peer_connected(&self, peer: PeerInfo) {
+        self.send(Event::PeerConnected(peer.address));
+    }
+}
+
+/// A transport listening on an ephemeral loopback port, for others to connect to
+fn listening_peer() -> Arc<Transport> {
+    create_tcp_transport("127.0.0.1:0".to_string()).unwrap()
+}
+
+fn drain(rx: &Receiver<Event>) -> Vec<Event> {
+    rx.try_iter().collect()
+}
+
+/// Alice's node holding her faucet funding and a payment of `amount` to Bob's wallet
+fn alice_paying_bob(amount: u64) -> (Arc<MeshNode>, Arc<MeshNode>) {
+    let alice = create_wallet().unwrap();
+    fund_wallet_from_faucet(alice.clone(), 500).unwrap();
+    let bob = create_wallet().unwrap();
+    alice.send_payment(bob.did(), amount).unwrap();
+
+    (MeshNode::new(alice), MeshNode::new(bob))
+}
+
+// ============================================================================
+// SYNC EVENTS
+// ============================================================================
+
+#[test]
+fn test_merge_state_fires_payment_then_sync() {
+    let (alice_node, bob_node) = alice_paying_bob(200);
+    let (listener, rx) = ChannelListener::new();
+    bob_node.set_listener(listener);
+
+    bob_node.merge_state(alice_node.get_state()).unwrap();
+
+    // The faucet IOU pays Alice, so only the payment to Bob is reported
+    assert_eq!(
+        drain(&rx),
+        vec![Event::PaymentReceived(200), Event::SyncComplete(2)]
+    );
+}
+
+#[test]
+fn test_known_payment_is_not_reported_again() {
+    let (alice_node, bob_node) = alice_paying_bob(200);
+    let (listener, rx) = ChannelListener::new();
+    bob_node.set_listener(listener);
+
+    bob_node.merge_state(alice_node.get_state()).unwrap();
+    drain(&rx);
+    bob_node.merge_state(alice_node.get_state()).unwrap();
+
+    assert_eq!(drain(&rx), vec![Event::SyncComplete(0)]);
+}
+
+#[test]
+fn test_apply_delta_fires_payment_then_sync() {
+    let (alice_node, bob_node) = alice_paying_bob(75);
+    let (listener, rx) = ChannelListener::new();
+    bob_node.set_listener(listener);
+
+    let delta = alice_node.get_delta_from_summary(bob_node.get_summary()).unwrap();
+    bob_node.apply_delta(delta).unwrap();
+
+    assert_eq!(
+        drain(&rx),
+        vec![Event::PaymentReceived(75), Event::SyncComplete(2)]
+    );
+}
+
+#[test]
+fn test_clear_listener_stops_events() {
+    let (alice_node, bob_node) = alice_paying_bob(200);
+    let (listener, rx) = ChannelListener::new();
+    bob_node.set_listener(listener);
+    bob_node.clear_listener();
+
+    bob_node.merge_state(alice_node.get_state()).unwrap();
+
+    assert!(drain(&rx).is_empty());
+}
+
+// ============================================================================
+// PEER EVENTS
+// ============================================================================
+
+#[test]
+fn test_attached_transport_fires_peer_connected_once() {
+    let node = MeshNode::new(create_wallet().unwrap());
+    let transport = create_tcp_transport("0.0.0.0:0".to_string()).unwrap();
+    let (listener, rx) = ChannelListener::new();
+    node.set_listener(listener);
+    node.attach_transport(transport.clone());
+
+    let peer = listening_peer();
+
+    transport.connect(peer.bind_address()).unwrap();
+    transport.connect(peer.bind_address()).unwrap();
+
+    assert_eq!(drain(&rx), vec![Event::PeerConnected(peer.bind_address())]);
+}
+
+#[test]
+fn test_listener_set_after_attach_receives_peer_events() {
+    let node = MeshNode::new(create_wallet().unwrap());
+    let transport = create_tcp_transport("0.0.0.0:0".to_string()).unwrap();
+    node.attach_transport(transport.clone());
+
+    let (listener, rx) = ChannelListener::new();
+    node.set_listener(listener);
+    let peer = listening_peer();
+    transport.connect(peer.bind_address()).unwrap();
+
+    assert_eq!(drain(&rx), vec![Event::PeerConnected(peer.bind_address())]);
+}
+
+#[test]
+fn test_unattached_transport_fires_nothing() {
+    let node = MeshNode::new(create_wallet().unwrap());
+    let transport = create_tcp_transport("0.0.0.0:0".to_string()).unwrap();
+    let (listener, rx) = ChannelListener::new();
+    node.set_listener(listener);
+
+    transport.connect(listening_peer().bind_address()).unwrap();
+
+    assert!(drain(&rx).is_empty());
+}
+
+// ============================================================================
+// THREADING TESTS
+// ============================================================================
+
+#[test]
+fn test_listener_invoked_from_background_thread() {
+    let (alice_node, bob_node) = alice_paying_bob(120);
+    let (listener, rx) = ChannelListener::new();
+    bob_node.set_listener(listener);
+
+    let state = alice_node.get_state();
+    let node = bob_node.clone();
+    let handle = thread::spawn(move || {
+        node.merge_state(state).unwrap();
+    });
+
+    let timeout = Duration::from_secs(5);
+    assert_eq!(rx.recv_timeout(timeout).unwrap(), Event::PaymentReceived(120));
+    assert_eq!(rx.recv_timeout(timeout).unwrap(), Event::SyncComplete(2));
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_concurrent_syncs_report_each_payment_once() {
+    let (alice_node, bob_node) = alice_paying_bob(90);
+    let (listener, rx) = ChannelListener::reentrant(bob_node.clone());
+    bob_node.set_listener(listener);
+    let transport = create_tcp_transport("0.0.0.0:0".to_string()).unwrap();
+    bob_node.attach_transport(transport.clone());
+
+    // Would deadlock if callbacks ran while node or wallet mutexes were held
+    let state = alice_node.get_state();
+    let peers: Vec<_> = (0..4).map(|_| listening_peer()).collect();
+    let handles: Vec<_> = peers
+        .iter()
+        .map(|peer| {
+            let node = bob_node.clone();
+            let transport = transport.clone();
+            let state = state.clone();
+            let address = peer.bind_address();
+            thread::spawn(move || {
+                transport.connect(address).unwrap();
+                node.merge_state(state).unwrap();
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let events = drain(&rx);
+    let count = |matches: fn(&Event) -> bool| events.iter().filter(|e| matches(e)).count();
+    assert_eq!(count(|e| matches!(e, Event::PaymentReceived(90))), 1);
+    assert_eq!(count(|e| matches!(e, Event::SyncComplete(_))), 4);
+    assert_eq!(count(|e| matches!(e, Event::PeerConnected(_))), 4);
+    assert_eq!(bob_node.stats().total_syncs, 4);
+}
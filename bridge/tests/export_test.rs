@@ -0,0 +1,43 @@
+// Transaction export tests for the bridge module
+// Tests that Wallet exposes CSV and JSON history to Kotlin/Swift
+
+use p2pmesh::vault::{TransactionDirection, TransactionExport};
+use p2pmesh_bridge::{create_wallet, fund_wallet_from_faucet, Wallet};
+use std::sync::Arc;
+
+fn funded_wallet(amount: u64) -> Arc<Wallet> {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), amount).unwrap();
+    wallet
+}
+
+#[test]
+fn test_csv_export_has_header_and_rows() {
+    let sender = funded_wallet(100);
+    let recipient = create_wallet().unwrap();
+    let iou = sender.send_payment(recipient.did(), 40).unwrap();
+
+    let csv = sender.export_transactions_csv();
+    let rows: Vec<&str> = csv.split_terminator("\r\n").collect();
+
+    assert_eq!(rows[0], "direction,counterparty,amount,timestamp,iou_id,memo");
+    assert_eq!(rows.len(), 3);
+    assert!(rows[2].starts_with(&format!("sent,{},40,", recipient.did())));
+    assert!(rows[2].contains(&iou.id()));
+}
+
+#[test]
+fn test_json_export_parses_back() {
+    let sender = funded_wallet(100);
+    let recipient = create_wallet().unwrap();
+    let iou = sender.send_payment(recipient.did(), 40).unwrap();
+    recipient.process_payment(iou.clone()).unwrap();
+
+    let exports = TransactionExport::parse_json(&recipient.export_transactions_json()).unwrap();
+
+    assert_eq!(exports.len(), 1);
+    assert_eq!(exports[0].direction(), TransactionDirection::Received);
+    assert_eq!(exports[0].counterparty(), sender.did());
+    assert_eq!(exports[0].amount(), 40);
+    assert_eq!(hex::encode(exports[0].iou_id().as_bytes()), iou.id());
+}
@@ -2,8 +2,11 @@
 // Tests the offline funding mechanism for hackathon demo
 
 use p2pmesh_bridge::{
-    create_wallet, faucet_did, faucet_public_key, fund_wallet_from_faucet, request_from_faucet,
+    create_wallet, default_faucet_config, faucet_did, faucet_public_key, fund_wallet_from_faucet,
+    request_from_faucet, Faucet, FaucetConfig, MeshError,
 };
+use std::thread;
+use std::time::Duration;
 
 // ============================================================================
 // FAUCET IDENTITY TESTS
@@ -153,11 +156,12 @@ fn test_fund_wallet_zero_amount_fails() {
 fn test_fund_wallet_large_amount() {
     let wallet = create_wallet().unwrap();
 
-    fund_wallet_from_faucet(wallet.clone(), 1_000_000_000).unwrap();
+    let max_amount = default_faucet_config().max_amount_per_request;
+    fund_wallet_from_faucet(wallet.clone(), max_amount).unwrap();
 
     assert_eq!(
         wallet.balance(),
-        1_000_000_000,
+        max_amount,
         "Should handle large amounts"
     );
 }
@@ -237,3 +241,163 @@ fn test_insufficient_balance_after_faucet_funding() {
 
     assert!(result.is_err(), "Should fail when trying to send more than balance");
 }
+
+// ============================================================================
+// RATE LIMIT TESTS
+// ============================================================================
+
+fn limited_faucet(max_requests: u32, window_secs: u64) -> std::sync::Arc<Faucet> {
+    Faucet::with_config(FaucetConfig {
+        max_amount_per_request: 1000,
+        max_requests_per_recipient: max_requests,
+        window_secs,
+    })
+}
+
+#[test]
+fn test_default_faucet_rejects_amount_over_cap() {
+    let wallet = create_wallet().unwrap();
+    let cap = default_faucet_config().max_amount_per_request;
+
+    let result = request_from_faucet(wallet.did(), cap + 1);
+    assert!(matches!(result, Err(MeshError::RateLimited)));
+}
+
+#[test]
+fn test_faucet_rejects_amount_over_cap() {
+    let faucet = limited_faucet(5, 60);
+    let wallet = create_wallet().unwrap();
+
+    assert!(faucet.request(wallet.did(), 1000).is_ok());
+    assert!(matches!(faucet.request(wallet.did(), 1001), Err(MeshError::RateLimited)));
+}
+
+#[test]
+fn test_faucet_rate_limits_recipient() {
+    let faucet = limited_faucet(2, 60);
+    let wallet = create_wallet().unwrap();
+
+    faucet.request(wallet.did(), 100).unwrap();
+    faucet.request(wallet.did(), 100).unwrap();
+    let result = faucet.request(wallet.did(), 100);
+
+    assert!(matches!(result, Err(MeshError::RateLimited)));
+    assert_eq!(faucet.remaining_requests(wallet.did()), 0);
+}
+
+#[test]
+fn test_faucet_limits_are_per_recipient() {
+    let faucet = limited_faucet(1, 60);
+    let alice = create_wallet().unwrap();
+    let bob = create_wallet().unwrap();
+
+    faucet.request(alice.did(), 100).unwrap();
+
+    assert!(faucet.request(bob.did(), 100).is_ok(), "Bob has his own quota");
+    assert!(matches!(faucet.request(alice.did(), 100), Err(MeshError::RateLimited)));
+}
+
+#[test]
+fn test_faucet_rejected_requests_do_not_consume_quota() {
+    let faucet = limited_faucet(1, 60);
+    let wallet = create_wallet().unwrap();
+
+    assert!(faucet.request(wallet.did(), 0).is_err());
+    assert!(faucet.request(wallet.did(), 5000).is_err());
+
+    assert_eq!(faucet.remaining_requests(wallet.did()), 1);
+    assert!(faucet.request(wallet.did(), 100).is_ok());
+}
+
+#[test]
+fn test_faucet_window_expiry_resets_counter() {
+    let faucet = limited_faucet(1, 1);
+    let wallet = create_wallet().unwrap();
+
+    faucet.request(wallet.did(), 100).unwrap();
+    assert!(matches!(faucet.request(wallet.did(), 100), Err(MeshError::RateLimited)));
+
+    thread::sleep(Duration::from_millis(1100));
+
+    assert_eq!(faucet.remaining_requests(wallet.did()), 1);
+    assert!(faucet.request(wallet.did(), 100).is_ok(), "Window expired, quota restored");
+}
+
+#[test]
+fn test_faucet_fund_wallet_respects_limits() {
+    let faucet = limited_faucet(1, 60);
+    let wallet = create_wallet().unwrap();
+
+    faucet.fund_wallet(wallet.clone(), 400).unwrap();
+    let result = faucet.fund_wallet(wallet.clone(), 400);
+
+    assert!(matches!(result, Err(MeshError::RateLimited)));
+    assert_eq!(wallet.balance(), 400);
+}
+
+// ============================================================================
+// FAUCET NONCE UNIQUENESS TESTS
+// ============================================================================
+
+#[test]
+fn test_faucet_iou_ids_unique_for_same_length_dids() {
+    let faucet = limited_faucet(100, 60);
+    // Base58 DIDs vary in length, so keep generating until five share one
+    let first = create_wallet().unwrap();
+    let mut wallets = vec![first.clone()];
+    while wallets.len() < 5 {
+        let wallet = create_wallet().unwrap();
+        if wallet.did().len() == first.did().len() {
+            wallets.push(wallet);
+        }
+    }
+
+    // Same amount, requested back to back, so only the nonce tells them apart
+    let mut ids = std::collections::HashSet::new();
+    for _ in 0..50 {
+        for wallet in &wallets {
+            let iou = faucet.request(wallet.did(), 10).unwrap();
+            assert!(ids.insert(iou.id()), "Duplicate faucet IOU id");
+        }
+    }
+    assert_eq!(ids.len(), 250);
+}
+
+#[test]
+fn test_faucet_ious_for_one_recipient_all_accepted() {
+    let faucet = limited_faucet(100, 60);
+    let wallet = create_wallet().unwrap();
+
+    for _ in 0..50 {
+        faucet.fund_wallet(wallet.clone(), 10).unwrap();
+    }
+
+    assert_eq!(wallet.balance(), 500);
+}
+
+#[test]
+fn test_faucet_nonces_unique_across_threads() {
+    let faucet = limited_faucet(1000, 60);
+    let wallet = create_wallet().unwrap();
+    let did = wallet.did();
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let faucet = faucet.clone();
+            let did = did.clone();
+            thread::spawn(move || {
+                (0..50)
+                    .map(|_| faucet.request(did.clone(), 10).unwrap().nonce())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut nonces = std::collections::HashSet::new();
+    for handle in handles {
+        for nonce in handle.join().unwrap() {
+            assert!(nonces.insert(nonce), "Duplicate faucet nonce");
+        }
+    }
+    assert_eq!(nonces.len(), 200);
+}
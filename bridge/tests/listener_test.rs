@@ -0,0 +1,194 @@
+// Listener tests for the bridge module
+// Tests that WalletListener callbacks fire in order and without holding wallet locks
+
+use p2pmesh_bridge::{
+    create_wallet, fund_wallet_from_faucet, MeshNode, SignedIOU, SyncStats, Wallet,
+    WalletListener,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    PaymentReceived(u64),
+    BalanceChanged(u64),
+    SyncCompleted(u64),
+}
+
+/// Records every callback; optionally calls back into the wallet to catch deadlocks
+struct MockListener {
+    events: Mutex<Vec<Event>>,
+    wallet: Mutex<Option<Arc<Wallet>>>,
+}
+
+impl MockListener {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            events: Mutex::new(Vec::new()),
+            wallet: Mutex::new(None),
+        })
+    }
+
+    fn reentrant(wallet: Arc<Wallet>) -> Arc<Self> {
+        Arc::new(Self {
+            events: Mutex::new(Vec::new()),
+            wallet: Mutex::new(Some(wallet)),
+        })
+    }
+
+    fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap().clone()
+    }
+
+    fn touch_wallet(&self) {
+        if let Some(wallet) = self.wallet.lock().unwrap().as_ref() {
+            let _ = wallet.balance();
+            let _ = wallet.pending_ious();
+            let _ = wallet.export_state();
+        }
+    }
+}
+
+impl WalletListener for MockListener {
+    fn on_payment_received(&self, iou: Arc<SignedIOU>) {
+        self.touch_wallet();
+        self.events.lock().unwrap().push(Event::PaymentReceived(iou.amount()));
+    }
+
+    fn on_balance_changed(&self, new_balance: u64) {
+        self.touch_wallet();
+        self.events.lock().unwrap().push(Event::BalanceChanged(new_balance));
+    }
+
+    fn on_sync_completed(&self, stats: SyncStats) {
+        self.touch_wallet();
+        self.events.lock().unwrap().push(Event::SyncCompleted(stats.total_syncs));
+    }
+}
+
+// ============================================================================
+// EVENT ORDERING TESTS
+// ============================================================================
+
+#[test]
+fn test_process_payment_fires_received_then_balance() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 500).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let listener = MockListener::new();
+    recipient.set_listener(listener.clone());
+
+    let iou = sender.create_payment(recipient.did(), 200).unwrap();
+    recipient.process_payment(iou).unwrap();
+
+    assert_eq!(
+        listener.events(),
+        vec![Event::PaymentReceived(200), Event::BalanceChanged(200)]
+    );
+}
+
+#[test]
+fn test_pending_then_processed_payment_fires_received_once() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 500).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let listener = MockListener::new();
+    recipient.set_listener(listener.clone());
+
+    let iou = sender.create_payment(recipient.did(), 100).unwrap();
+    recipient.receive_payment(iou.clone()).unwrap();
+    assert_eq!(listener.events(), vec![Event::PaymentReceived(100)]);
+
+    recipient.process_payment(iou).unwrap();
+    assert_eq!(
+        listener.events(),
+        vec![Event::PaymentReceived(100), Event::BalanceChanged(100)]
+    );
+}
+
+#[test]
+fn test_faucet_funding_fires_balance_changed() {
+    let wallet = create_wallet().unwrap();
+    let listener = MockListener::new();
+    wallet.set_listener(listener.clone());
+
+    fund_wallet_from_faucet(wallet.clone(), 300).unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 200).unwrap();
+
+    assert_eq!(
+        listener.events(),
+        vec![
+            Event::PaymentReceived(300),
+            Event::BalanceChanged(300),
+            Event::PaymentReceived(200),
+            Event::BalanceChanged(500),
+        ]
+    );
+}
+
+#[test]
+fn test_failed_payment_fires_nothing() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 500).unwrap();
+    let recipient = create_wallet().unwrap();
+    let bystander = create_wallet().unwrap();
+
+    let listener = MockListener::new();
+    bystander.set_listener(listener.clone());
+
+    let iou = sender.create_payment(recipient.did(), 100).unwrap();
+    assert!(bystander.process_payment(iou).is_err());
+    assert!(listener.events().is_empty());
+}
+
+#[test]
+fn test_merge_state_fires_sync_completed() {
+    let alice = create_wallet().unwrap();
+    fund_wallet_from_faucet(alice.clone(), 500).unwrap();
+    let bob = create_wallet().unwrap();
+
+    let listener = MockListener::new();
+    bob.set_listener(listener.clone());
+
+    let alice_node = MeshNode::new(alice);
+    let bob_node = MeshNode::new(bob);
+
+    bob_node.merge_state(alice_node.get_state()).unwrap();
+    bob_node.merge_state(alice_node.get_state()).unwrap();
+
+    assert_eq!(
+        listener.events(),
+        vec![Event::SyncCompleted(1), Event::SyncCompleted(2)]
+    );
+}
+
+#[test]
+fn test_clear_listener_stops_events() {
+    let wallet = create_wallet().unwrap();
+    let listener = MockListener::new();
+    wallet.set_listener(listener.clone());
+    wallet.clear_listener();
+
+    fund_wallet_from_faucet(wallet, 100).unwrap();
+    assert!(listener.events().is_empty());
+}
+
+// ============================================================================
+// REENTRANCY TESTS
+// ============================================================================
+
+#[test]
+fn test_listener_can_call_back_into_wallet() {
+    let wallet = create_wallet().unwrap();
+    let listener = MockListener::reentrant(wallet.clone());
+    wallet.set_listener(listener.clone());
+
+    // Would deadlock if callbacks ran while wallet mutexes were held
+    fund_wallet_from_faucet(wallet.clone(), 100).unwrap();
+
+    let node = MeshNode::new(wallet.clone());
+    node.merge_state(node.get_state()).unwrap();
+
+    assert_eq!(listener.events().len(), 3);
+}
@@ -0,0 +1,205 @@
+// Wallet manager tests for the bridge module
+// Tests labeled wallets sharing one MeshStore
+
+use p2pmesh_bridge::{fund_wallet_from_faucet, MeshError, WalletManager};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+
+/// Unique temporary directory for a test store
+fn temp_store_path(name: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("p2pmesh-manager-{}-{}", name, nanos))
+}
+
+fn open_manager(path: &Path) -> Arc<WalletManager> {
+    WalletManager::new(path.to_string_lossy().to_string()).unwrap()
+}
+
+// ============================================================================
+// CREATE / GET / LIST TESTS
+// ============================================================================
+
+#[test]
+fn test_create_and_get_wallet() {
+    let path = temp_store_path("create");
+    let manager = open_manager(&path);
+
+    let wallet = manager.create("main".to_string()).unwrap();
+    let fetched = manager.get("main".to_string()).unwrap();
+
+    assert!(Arc::ptr_eq(&wallet, &fetched));
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_create_duplicate_label_fails() {
+    let path = temp_store_path("duplicate");
+    let manager = open_manager(&path);
+
+    manager.create("main".to_string()).unwrap();
+    let result = manager.create("main".to_string());
+
+    assert!(matches!(result, Err(MeshError::WalletAlreadyExists)));
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_create_empty_label_fails() {
+    let path = temp_store_path("empty-label");
+    let manager = open_manager(&path);
+
+    assert!(matches!(manager.create(String::new()), Err(MeshError::InvalidLabel)));
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_get_unknown_wallet_fails() {
+    let path = temp_store_path("unknown");
+    let manager = open_manager(&path);
+
+    assert!(matches!(manager.get("ghost".to_string()), Err(MeshError::WalletNotFound)));
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_list_returns_sorted_summaries() {
+    let path = temp_store_path("list");
+    let manager = open_manager(&path);
+
+    let savings = manager.create("savings".to_string()).unwrap();
+    manager.create("daily".to_string()).unwrap();
+    fund_wallet_from_faucet(savings.clone(), 700).unwrap();
+
+    let summaries = manager.list().unwrap();
+
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].label, "daily");
+    assert_eq!(summaries[1].label, "savings");
+    assert_eq!(summaries[1].did, savings.did());
+    assert_eq!(summaries[1].balance, 700);
+    assert!(summaries[1].is_default, "First created wallet is the default");
+    assert!(!summaries[0].is_default);
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_concurrent_get_returns_same_instance() {
+    let path = temp_store_path("concurrent");
+    {
+        let manager = open_manager(&path);
+        manager.create("shared".to_string()).unwrap();
+    }
+
+    // Fresh manager so both threads race to load from storage
+    let manager = open_manager(&path);
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let manager = manager.clone();
+            thread::spawn(move || manager.get("shared".to_string()).unwrap())
+        })
+        .collect();
+    let wallets: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+    assert!(Arc::ptr_eq(&wallets[0], &wallets[1]));
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+// ============================================================================
+// PERSISTENCE TESTS
+// ============================================================================
+
+#[test]
+fn test_wallets_survive_reopen() {
+    let path = temp_store_path("reopen");
+    let (did, secret_key);
+
+    {
+        let manager = open_manager(&path);
+        let wallet = manager.create("main".to_string()).unwrap();
+        fund_wallet_from_faucet(wallet.clone(), 400).unwrap();
+        did = wallet.did();
+        secret_key = wallet.secret_key();
+    }
+
+    let manager = open_manager(&path);
+    let wallet = manager.get("main".to_string()).unwrap();
+
+    assert_eq!(wallet.did(), did);
+    assert_eq!(wallet.secret_key(), secret_key);
+    assert_eq!(wallet.balance(), 400);
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+// ============================================================================
+// DEFAULT WALLET TESTS
+// ============================================================================
+
+#[test]
+fn test_default_wallet() {
+    let path = temp_store_path("default");
+    let manager = open_manager(&path);
+
+    assert!(matches!(manager.default_wallet(), Err(MeshError::WalletNotFound)));
+
+    let first = manager.create("first".to_string()).unwrap();
+    let second = manager.create("second".to_string()).unwrap();
+    assert!(Arc::ptr_eq(&manager.default_wallet().unwrap(), &first));
+
+    manager.set_default("second".to_string()).unwrap();
+    assert!(Arc::ptr_eq(&manager.default_wallet().unwrap(), &second));
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+// ============================================================================
+// DELETE TESTS
+// ============================================================================
+
+#[test]
+fn test_delete_requires_confirmation() {
+    let path = temp_store_path("delete-unconfirmed");
+    let manager = open_manager(&path);
+    manager.create("main".to_string()).unwrap();
+
+    let result = manager.delete("main".to_string(), false);
+
+    assert!(matches!(result, Err(MeshError::DeleteNotConfirmed)));
+    assert!(manager.get("main".to_string()).is_ok(), "Wallet must still exist");
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_delete_wipes_wallet() {
+    let path = temp_store_path("delete");
+    {
+        let manager = open_manager(&path);
+        manager.create("main".to_string()).unwrap();
+        manager.create("other".to_string()).unwrap();
+        manager.delete("main".to_string(), true).unwrap();
+
+        assert!(matches!(manager.get("main".to_string()), Err(MeshError::WalletNotFound)));
+        assert!(matches!(manager.default_wallet(), Err(MeshError::WalletNotFound)));
+    }
+
+    let manager = open_manager(&path);
+    let labels: Vec<_> = manager.list().unwrap().into_iter().map(|s| s.label).collect();
+    assert_eq!(labels, vec!["other".to_string()]);
+
+    // Label can be reused for a brand-new key
+    let recreated = manager.create("main".to_string()).unwrap();
+    assert_eq!(recreated.balance(), 0);
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_delete_unknown_wallet_fails() {
+    let path = temp_store_path("delete-unknown");
+    let manager = open_manager(&path);
+
+    let result = manager.delete("ghost".to_string(), true);
+    assert!(matches!(result, Err(MeshError::WalletNotFound)));
+    let _ = std::fs::remove_dir_all(&path);
+}
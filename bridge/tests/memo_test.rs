@@ -0,0 +1,72 @@
+// Memo tests for the bridge module
+// Tests that IOU memos reach Kotlin/Swift intact
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, IOUCodec};
+use p2pmesh_bridge::{create_wallet, fund_wallet_from_faucet, signed_iou_from_bytes, signed_iou_from_qr_string};
+
+/// Bytes of an IOU with `memo` from a fresh sender to `recipient_did`
+fn memo_iou_bytes(recipient_did: &str, memo: &str) -> Vec<u8> {
+    let sender = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&sender)
+        .recipient(Did::parse(recipient_did).unwrap())
+        .amount(25)
+        .memo(memo)
+        .build()
+        .unwrap();
+    IOUCodec::encode(&iou)
+}
+
+#[test]
+fn test_memo_exposed_on_decoded_iou() {
+    let wallet = create_wallet().unwrap();
+
+    let iou = signed_iou_from_bytes(memo_iou_bytes(&wallet.did(), "order #4711")).unwrap();
+
+    assert_eq!(iou.memo(), Some("order #4711".to_string()));
+    assert!(iou.verify().unwrap());
+}
+
+#[test]
+fn test_memo_survives_bytes_and_qr() {
+    let wallet = create_wallet().unwrap();
+    let iou = signed_iou_from_bytes(memo_iou_bytes(&wallet.did(), "table 7")).unwrap();
+
+    let from_bytes = signed_iou_from_bytes(iou.to_bytes()).unwrap();
+    let from_qr = signed_iou_from_qr_string(iou.to_qr_string()).unwrap();
+
+    assert_eq!(from_bytes.memo(), Some("table 7".to_string()));
+    assert_eq!(from_qr.memo(), Some("table 7".to_string()));
+    assert_eq!(from_qr.id(), iou.id());
+}
+
+#[test]
+fn test_payment_without_memo() {
+    let sender = create_wallet().unwrap();
+    let recipient = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 100).unwrap();
+
+    let iou = sender.create_payment(recipient.did(), 10).unwrap();
+
+    assert_eq!(iou.memo(), None);
+}
+
+#[test]
+fn test_pre_memo_bytes_still_decode() {
+    let sender = create_wallet().unwrap();
+    let recipient = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 100).unwrap();
+    let iou = sender.create_payment(recipient.did(), 10).unwrap();
+
+    // Bytes from before the memo field lack the trailing memo, request ID, outputs,
+    // denomination and hashlock
+    let mut old_bytes = iou.to_bytes();
+    for _ in 0..5 {
+        assert_eq!(old_bytes.pop(), Some(0));
+    }
+
+    let decoded = signed_iou_from_bytes(old_bytes).unwrap();
+    assert_eq!(decoded.id(), iou.id());
+    assert!(decoded.verify().unwrap());
+}
@@ -0,0 +1,99 @@
+// QR encoding tests for the bridge module
+// Tests that SignedIOUs survive the QR string round-trip and tampering is rejected
+
+use p2pmesh_bridge::{
+    create_wallet, fund_wallet_from_faucet, signed_iou_from_qr_string, MeshError, SignedIOU,
+};
+use std::sync::Arc;
+
+/// Create a funded sender and a payment to a fresh recipient
+fn make_payment(amount: u64) -> Arc<SignedIOU> {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 1000).unwrap();
+    let recipient = create_wallet().unwrap();
+    sender.create_payment(recipient.did(), amount).unwrap()
+}
+
+// ============================================================================
+// ROUND-TRIP TESTS
+// ============================================================================
+
+#[test]
+fn test_qr_string_roundtrip() {
+    let iou = make_payment(250);
+    let qr = iou.to_qr_string();
+    assert!(qr.starts_with("p2pm:1:"));
+
+    let decoded = signed_iou_from_qr_string(qr).unwrap();
+    assert_eq!(decoded.id(), iou.id());
+    assert_eq!(decoded.amount(), 250);
+    assert!(decoded.verify().unwrap());
+}
+
+#[test]
+fn test_qr_string_is_url_safe() {
+    let qr = make_payment(1).to_qr_string();
+    assert!(qr
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == ':' || c == '-' || c == '_'));
+}
+
+#[test]
+fn test_scanned_qr_can_be_processed() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 1000).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let qr = sender.create_payment(recipient.did(), 400).unwrap().to_qr_string();
+    let scanned = signed_iou_from_qr_string(qr).unwrap();
+
+    recipient.process_payment(scanned).unwrap();
+    assert_eq!(recipient.balance(), 400);
+}
+
+// ============================================================================
+// TAMPERING TESTS
+// ============================================================================
+
+fn reason(result: Result<Arc<SignedIOU>, MeshError>) -> String {
+    match result {
+        Err(MeshError::SerializationError { reason }) => reason,
+        Err(other) => panic!("Expected SerializationError, got {:?}", other),
+        Ok(_) => panic!("Expected decoding to fail"),
+    }
+}
+
+#[test]
+fn test_tampered_qr_payload_rejected() {
+    let qr = make_payment(100).to_qr_string();
+    let mut chars: Vec<char> = qr.chars().collect();
+    let idx = chars.len() / 2;
+    chars[idx] = if chars[idx] == 'x' { 'y' } else { 'x' };
+
+    let reason = reason(signed_iou_from_qr_string(chars.into_iter().collect()));
+    assert!(reason.contains("checksum"), "unexpected reason: {}", reason);
+}
+
+#[test]
+fn test_qr_wrong_prefix_rejected() {
+    let qr = make_payment(100).to_qr_string().replacen("p2pm:", "btc:", 1);
+
+    let reason = reason(signed_iou_from_qr_string(qr));
+    assert!(reason.contains("prefix"), "unexpected reason: {}", reason);
+}
+
+#[test]
+fn test_qr_wrong_version_rejected() {
+    let qr = make_payment(100).to_qr_string().replacen("p2pm:1:", "p2pm:9:", 1);
+
+    let reason = reason(signed_iou_from_qr_string(qr));
+    assert!(reason.contains("version"), "unexpected reason: {}", reason);
+}
+
+#[test]
+fn test_qr_garbage_rejected() {
+    assert!(matches!(
+        signed_iou_from_qr_string("hello".to_string()),
+        Err(MeshError::SerializationError { .. })
+    ));
+}
@@ -0,0 +1,104 @@
+// Rejection tests for the bridge module
+// Tests that a recipient can refuse an IOU and the sender reclaims the funds
+
+use p2pmesh_bridge::{
+    create_wallet, fund_wallet_from_faucet, signed_rejection_from_bytes, MeshError,
+    RejectionReason,
+};
+
+// ============================================================================
+// REJECTION FLOW TESTS
+// ============================================================================
+
+#[test]
+fn test_reject_payment_refunds_sender() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 500).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let iou = sender.send_payment(recipient.did(), 200).unwrap();
+    assert_eq!(sender.balance(), 300);
+
+    recipient.receive_payment(iou.clone()).unwrap();
+    let rejection = recipient.reject_payment(iou.id(), RejectionReason::WrongAmount).unwrap();
+
+    assert_eq!(rejection.iou_id(), iou.id());
+    assert_eq!(rejection.recipient(), recipient.did());
+    assert_eq!(rejection.reason(), RejectionReason::WrongAmount);
+    assert!(recipient.pending_ious().is_empty());
+
+    // Rejection travels back over the wire
+    let received = signed_rejection_from_bytes(rejection.to_bytes()).unwrap();
+    sender.process_rejection(received).unwrap();
+
+    assert_eq!(sender.balance(), 500);
+    assert_eq!(recipient.balance(), 0);
+}
+
+#[test]
+fn test_process_rejection_twice_is_duplicate() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 500).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let iou = sender.send_payment(recipient.did(), 200).unwrap();
+    recipient.receive_payment(iou.clone()).unwrap();
+    let rejection = recipient.reject_payment(iou.id(), RejectionReason::Declined).unwrap();
+
+    sender.process_rejection(rejection.clone()).unwrap();
+    let result = sender.process_rejection(rejection);
+
+    assert!(matches!(result, Err(MeshError::DuplicateTransaction)));
+    assert_eq!(sender.balance(), 500);
+}
+
+#[test]
+fn test_reject_unknown_pending_fails() {
+    let recipient = create_wallet().unwrap();
+
+    let result = recipient.reject_payment("00".repeat(32), RejectionReason::Declined);
+    assert!(matches!(result, Err(MeshError::InvalidIOU)));
+}
+
+#[test]
+fn test_rejection_for_other_sender_is_refused() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 500).unwrap();
+    let bystander = create_wallet().unwrap();
+    fund_wallet_from_faucet(bystander.clone(), 500).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let iou = sender.send_payment(recipient.did(), 200).unwrap();
+    recipient.receive_payment(iou.clone()).unwrap();
+    let rejection = recipient.reject_payment(iou.id(), RejectionReason::Declined).unwrap();
+
+    assert!(bystander.process_rejection(rejection).is_err());
+    assert_eq!(bystander.balance(), 500);
+}
+
+#[test]
+fn test_rejection_survives_reopen_as_duplicate() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 500).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let iou = sender.send_payment(recipient.did(), 100).unwrap();
+    recipient.receive_payment(iou.clone()).unwrap();
+    let rejection = recipient.reject_payment(iou.id(), RejectionReason::Declined).unwrap();
+    sender.process_rejection(rejection.clone()).unwrap();
+
+    let restored =
+        p2pmesh_bridge::restore_wallet_with_state(sender.secret_key(), sender.export_state())
+            .unwrap();
+    assert!(matches!(
+        restored.process_rejection(rejection),
+        Err(MeshError::DuplicateTransaction)
+    ));
+    assert_eq!(restored.balance(), 500);
+}
+
+#[test]
+fn test_malformed_rejection_bytes_fail() {
+    let result = signed_rejection_from_bytes(vec![1, 2, 3]);
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
@@ -0,0 +1,137 @@
+// Payment request tests for the bridge module
+// Tests the create_payment_request / pay_request invoice flow
+
+use p2pmesh::identity::Keypair;
+use p2pmesh::iou::{PaymentRequestBuilder, SignedPaymentRequest};
+use p2pmesh_bridge::{create_wallet, fund_wallet_from_faucet, MeshError, Wallet};
+use std::sync::Arc;
+
+fn funded_wallet(amount: u64) -> Arc<Wallet> {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), amount).unwrap();
+    wallet
+}
+
+#[test]
+fn test_pay_request_sends_requested_amount() {
+    let merchant = create_wallet().unwrap();
+    let customer = funded_wallet(100);
+    let request = merchant.create_payment_request(30, Some("order #4711".to_string())).unwrap();
+
+    let iou = customer.pay_request(request.clone()).unwrap();
+
+    assert_eq!(iou.recipient(), merchant.did());
+    assert_eq!(iou.amount(), 30);
+    assert_eq!(iou.memo(), Some("order #4711".to_string()));
+    assert!(iou.fulfills(request).unwrap());
+    assert!(iou.request_id().is_some());
+    assert_eq!(customer.balance(), 70);
+
+    merchant.process_payment(iou).unwrap();
+    assert_eq!(merchant.balance(), 30);
+}
+
+#[test]
+fn test_request_signed_by_merchant() {
+    let merchant = create_wallet().unwrap();
+
+    let bytes = merchant.create_payment_request(10, None).unwrap();
+
+    let request = SignedPaymentRequest::from_bytes(&bytes).unwrap();
+    assert!(request.verify_requester());
+    assert_eq!(request.request().recipient().to_string(), merchant.did());
+    assert!(request.request().expiry().is_some());
+}
+
+#[test]
+fn test_pay_request_twice_fails() {
+    let merchant = create_wallet().unwrap();
+    let customer = funded_wallet(100);
+    let request = merchant.create_payment_request(30, None).unwrap();
+    customer.pay_request(request.clone()).unwrap();
+
+    let result = customer.pay_request(request);
+
+    assert!(matches!(result, Err(MeshError::RequestAlreadyPaid)));
+    assert_eq!(customer.balance(), 70);
+}
+
+#[test]
+fn test_pay_expired_request_fails() {
+    let merchant = create_wallet().unwrap();
+    let customer = funded_wallet(100);
+    let keypair = Keypair::from_bytes(&merchant.secret_key()).unwrap();
+    let expired = PaymentRequestBuilder::new()
+        .requester(&keypair)
+        .amount(30)
+        .timestamp(1_000)
+        .expiry(2_000)
+        .build()
+        .unwrap();
+
+    let result = customer.pay_request(expired.to_bytes());
+
+    assert!(matches!(result, Err(MeshError::RequestExpired)));
+    assert_eq!(customer.balance(), 100);
+}
+
+#[test]
+fn test_pay_forged_request_fails() {
+    let merchant = create_wallet().unwrap();
+    let customer = funded_wallet(100);
+    let request = SignedPaymentRequest::from_bytes(&merchant.create_payment_request(30, None).unwrap()).unwrap();
+    let cheaper = SignedPaymentRequest::from_bytes(&merchant.create_payment_request(1, None).unwrap()).unwrap();
+
+    let forged = SignedPaymentRequest::from_parts(request.request().clone(), cheaper.signature().clone());
+    let result = customer.pay_request(forged.to_bytes());
+
+    assert!(matches!(result, Err(MeshError::InvalidSignature)));
+    assert_eq!(customer.balance(), 100);
+}
+
+#[test]
+fn test_pay_request_insufficient_balance() {
+    let merchant = create_wallet().unwrap();
+    let customer = funded_wallet(10);
+    let request = merchant.create_payment_request(30, None).unwrap();
+
+    let result = customer.pay_request(request.clone());
+
+    assert!(matches!(result, Err(MeshError::InsufficientBalance)));
+
+    // A failed attempt doesn't count as paid
+    fund_wallet_from_faucet(customer.clone(), 50).unwrap();
+    assert!(customer.pay_request(request).is_ok());
+}
+
+#[test]
+fn test_pay_request_garbage_fails() {
+    let customer = funded_wallet(100);
+
+    let result = customer.pay_request(vec![0xFF; 8]);
+
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
+
+#[test]
+fn test_distinct_requests_can_each_be_paid() {
+    let merchant = create_wallet().unwrap();
+    let customer = funded_wallet(100);
+
+    customer.pay_request(merchant.create_payment_request(20, None).unwrap()).unwrap();
+    customer.pay_request(merchant.create_payment_request(20, None).unwrap()).unwrap();
+
+    assert_eq!(customer.balance(), 60);
+}
+
+#[test]
+fn test_plain_payment_does_not_fulfill_request() {
+    let merchant = create_wallet().unwrap();
+    let customer = funded_wallet(100);
+    let request = merchant.create_payment_request(30, None).unwrap();
+
+    let iou = customer.send_payment(merchant.did(), 30).unwrap();
+
+    assert!(!iou.fulfills(request).unwrap());
+    assert_eq!(iou.request_id(), None);
+}
@@ -0,0 +1,403 @@
+// Restore tests for the bridge module
+// Tests that a wallet can be rebuilt from its secret key and exported state
+
+use p2pmesh::identity::Did;
+use p2pmesh::iou::NonceManager;
+use p2pmesh_bridge::{
+    create_wallet, fund_wallet_from_faucet, restore_wallet_from_mnemonic, restore_wallet_with_state,
+    MeshError,
+};
+
+// ============================================================================
+// RESTORE WITH STATE TESTS
+// ============================================================================
+
+#[test]
+fn test_restore_with_state_on_fresh_device_keeps_balance() {
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 1000).unwrap();
+
+    let restored = restore_wallet_with_state(original.secret_key(), original.export_state()).unwrap();
+
+    assert_eq!(restored.did(), original.did());
+    assert_eq!(restored.balance(), 1000);
+    assert_eq!(restored.transaction_count(), original.transaction_count());
+}
+
+#[test]
+fn test_restored_wallet_receives_new_ious() {
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 500).unwrap();
+
+    let restored = restore_wallet_with_state(original.secret_key(), original.export_state()).unwrap();
+
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 300).unwrap();
+    let iou = sender.create_payment(restored.did(), 200).unwrap();
+    sender.mark_sent(iou.clone()).unwrap();
+
+    restored.process_payment(iou).unwrap();
+    assert_eq!(restored.balance(), 700);
+}
+
+#[test]
+fn test_restored_wallet_does_not_reuse_nonces() {
+    let recipient = create_wallet().unwrap();
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 1000).unwrap();
+
+    let first = original.create_payment(recipient.did(), 100).unwrap();
+    original.mark_sent(first.clone()).unwrap();
+
+    let restored = restore_wallet_with_state(original.secret_key(), original.export_state()).unwrap();
+    assert_eq!(restored.balance(), 900);
+
+    let next = restored.create_payment(recipient.did(), 50).unwrap();
+    assert!(next.nonce() > first.nonce(), "Nonce must continue after restore");
+    assert_ne!(next.id(), first.id());
+}
+
+#[test]
+fn test_restore_with_state_rejects_mismatched_key() {
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 100).unwrap();
+    let other = create_wallet().unwrap();
+
+    let result = restore_wallet_with_state(other.secret_key(), original.export_state());
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+}
+
+#[test]
+fn test_restore_with_state_rejects_malformed_state() {
+    let original = create_wallet().unwrap();
+
+    let result = restore_wallet_with_state(original.secret_key(), vec![1, 2, 3]);
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
+
+#[test]
+fn test_import_state_rejects_other_owner() {
+    let alice = create_wallet().unwrap();
+    fund_wallet_from_faucet(alice.clone(), 100).unwrap();
+    let bob = create_wallet().unwrap();
+
+    let result = bob.import_state(alice.export_state());
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+    assert_eq!(bob.balance(), 0, "Rejected import must not touch the vault");
+}
+
+#[test]
+fn test_import_state_accepts_own_export() {
+    let alice = create_wallet().unwrap();
+    fund_wallet_from_faucet(alice.clone(), 100).unwrap();
+    let snapshot = alice.export_state();
+
+    let restored = restore_wallet_with_state(alice.secret_key(), snapshot.clone()).unwrap();
+    restored.import_state(snapshot).unwrap();
+    assert_eq!(restored.balance(), 100);
+}
+
+// ============================================================================
+// NONCE RECOVERY TESTS
+// ============================================================================
+
+/// Offset of the nonce counter in an exported state blob
+fn nonce_offset(state: &[u8]) -> usize {
+    let section_end = |start: usize| {
+        let len = u32::from_le_bytes(state[start..start + 4].try_into().unwrap()) as usize;
+        start + 4 + len
+    };
+    section_end(section_end(0))
+}
+
+/// Rewrite an exported state blob in the format from before per-sender nonces,
+/// which ends with the nonce counter
+fn with_saved_nonce(state: Vec<u8>, nonce: u64) -> Vec<u8> {
+    let offset = nonce_offset(&state);
+    let mut legacy = state[..offset].to_vec();
+    legacy.extend_from_slice(&nonce.to_le_bytes());
+    legacy
+}
+
+#[test]
+fn test_restore_resumes_nonce_from_sent_history() {
+    let recipient = create_wallet().unwrap();
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 1000).unwrap();
+
+    let mut last_nonce = 0;
+    for _ in 0..3 {
+        let iou = original.create_payment(recipient.did(), 10).unwrap();
+        last_nonce = iou.nonce();
+        original.mark_sent(iou).unwrap();
+    }
+
+    // Simulate a stale counter in the exported blob
+    let state = with_saved_nonce(original.export_state(), 0);
+    let restored = restore_wallet_with_state(original.secret_key(), state).unwrap();
+
+    let next = restored.create_payment(recipient.did(), 10).unwrap();
+    assert_eq!(next.nonce(), last_nonce + 1);
+}
+
+#[test]
+fn test_import_resumes_nonce_from_sent_history() {
+    let recipient = create_wallet().unwrap();
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 1000).unwrap();
+
+    let iou = original.create_payment(recipient.did(), 10).unwrap();
+    let sent_nonce = iou.nonce();
+    original.mark_sent(iou).unwrap();
+
+    let other_device = restore_wallet_with_state(
+        original.secret_key(),
+        with_saved_nonce(original.export_state(), 0),
+    )
+    .unwrap();
+    other_device.import_state(with_saved_nonce(original.export_state(), 0)).unwrap();
+
+    let next = other_device.create_payment(recipient.did(), 10).unwrap();
+    assert!(next.nonce() > sent_nonce);
+}
+
+#[test]
+fn test_mark_sent_moves_nonce_past_recorded_payment() {
+    let recipient = create_wallet().unwrap();
+    let device_a = create_wallet().unwrap();
+    fund_wallet_from_faucet(device_a.clone(), 1000).unwrap();
+    let device_b = restore_wallet_with_state(device_a.secret_key(), device_a.export_state()).unwrap();
+
+    // Device B spends, and the IOU is recorded on device A too
+    let from_b = device_b.create_payment(recipient.did(), 10).unwrap();
+    device_b.mark_sent(from_b.clone()).unwrap();
+    device_a.mark_sent(from_b.clone()).unwrap();
+
+    let next = device_a.create_payment(recipient.did(), 10).unwrap();
+    assert_eq!(next.nonce(), from_b.nonce() + 1);
+}
+
+#[test]
+fn test_restore_reuses_nonce_of_unrecorded_payment() {
+    let recipient = create_wallet().unwrap();
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 1000).unwrap();
+
+    // Handed out but never marked sent, so it used up nothing
+    let pending = original.create_payment(recipient.did(), 10).unwrap();
+
+    let restored = restore_wallet_with_state(original.secret_key(), original.export_state()).unwrap();
+    let next = restored.create_payment(recipient.did(), 10).unwrap();
+    assert_eq!(next.nonce(), pending.nonce());
+}
+
+#[test]
+fn test_restore_prefers_nonce_section_over_legacy_counter() {
+    let recipient = create_wallet().unwrap();
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 1000).unwrap();
+
+    // Zero the legacy counter and replace the nonce section with one ahead of it
+    let mut nonces = NonceManager::new();
+    nonces.seed(&Did::parse(&original.did()).unwrap(), 5);
+    let nonce_bytes = nonces.to_bytes();
+    let mut state = original.export_state();
+    state.truncate(nonce_offset(&state));
+    state.extend_from_slice(&0u64.to_le_bytes());
+    state.extend_from_slice(&(nonce_bytes.len() as u32).to_le_bytes());
+    state.extend_from_slice(&nonce_bytes);
+
+    let restored = restore_wallet_with_state(original.secret_key(), state).unwrap();
+    let next = restored.create_payment(recipient.did(), 10).unwrap();
+    assert_eq!(next.nonce(), 6);
+}
+
+#[test]
+fn test_restore_legacy_state_keeps_saved_counter() {
+    let recipient = create_wallet().unwrap();
+    let original = create_wallet().unwrap();
+    fund_wallet_from_faucet(original.clone(), 1000).unwrap();
+
+    let state = with_saved_nonce(original.export_state(), 41);
+    let restored = restore_wallet_with_state(original.secret_key(), state).unwrap();
+
+    let next = restored.create_payment(recipient.did(), 10).unwrap();
+    assert_eq!(next.nonce(), 42);
+}
+
+#[test]
+fn test_restore_rejects_corrupt_nonce_section() {
+    let original = create_wallet().unwrap();
+    let mut state = original.export_state();
+    let offset = nonce_offset(&state) + 8;
+    state.truncate(offset);
+    state.extend_from_slice(&3u32.to_le_bytes());
+    state.extend_from_slice(&[0xFF, 0xFF, 0xFF]);
+
+    let result = restore_wallet_with_state(original.secret_key(), state);
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
+
+// ============================================================================
+// MNEMONIC RESTORE TESTS
+// ============================================================================
+
+#[test]
+fn test_restore_wallet_from_mnemonic_matches_original() {
+    let original = create_wallet().unwrap();
+    let phrase = original.mnemonic();
+    assert_eq!(phrase.split_whitespace().count(), 24);
+
+    let restored = restore_wallet_from_mnemonic(phrase).unwrap();
+    assert_eq!(restored.did(), original.did());
+    assert_eq!(restored.secret_key(), original.secret_key());
+}
+
+#[test]
+fn test_restore_wallet_from_mnemonic_normalizes_whitespace() {
+    let original = create_wallet().unwrap();
+    let phrase = format!("  {}  ", original.mnemonic().replace(' ', "\n  "));
+
+    let restored = restore_wallet_from_mnemonic(phrase).unwrap();
+    assert_eq!(restored.did(), original.did());
+}
+
+#[test]
+fn test_restore_wallet_from_mnemonic_vector() {
+    // BIP39 reference vector for all-0x80 entropy; every platform must derive this DID
+    let phrase = "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd \
+                  amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless";
+
+    let wallet = restore_wallet_from_mnemonic(phrase.to_string()).unwrap();
+    assert_eq!(wallet.did(), "did:mesh:GSWmWFSy27pcZDVH9NA52BFapq8DUwTyuzT8JsrhctL1");
+    assert_eq!(wallet.secret_key(), vec![0x80; 32]);
+    assert_eq!(wallet.mnemonic(), phrase);
+}
+
+#[test]
+fn test_restore_wallet_from_bad_mnemonic_fails() {
+    let result = restore_wallet_from_mnemonic("abandon abandon abandon".to_string());
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+}
+
+// ============================================================================
+// MALFORMED STATE TESTS
+// ============================================================================
+
+/// Exported state of a wallet with some history
+fn exported_state() -> (std::sync::Arc<p2pmesh_bridge::Wallet>, Vec<u8>) {
+    let wallet = create_wallet().unwrap();
+    let recipient = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 1000).unwrap();
+    let iou = wallet.create_payment(recipient.did(), 10).unwrap();
+    wallet.mark_sent(iou).unwrap();
+    let state = wallet.export_state();
+    (wallet, state)
+}
+
+fn is_graceful(result: &Result<(), MeshError>) -> bool {
+    matches!(result, Ok(()) | Err(MeshError::SerializationError { .. }) | Err(MeshError::InvalidKey))
+}
+
+#[test]
+fn test_import_truncated_state_fails_gracefully() {
+    let (wallet, state) = exported_state();
+    // Ending right after the nonce counter is the older format, which still imports
+    let legacy_len = nonce_offset(&state) + 8;
+
+    for len in (0..state.len()).filter(|&len| len != legacy_len) {
+        let result = wallet.import_state(state[..len].to_vec());
+        assert!(
+            matches!(result, Err(MeshError::SerializationError { .. })),
+            "Truncation to {} bytes should fail",
+            len
+        );
+    }
+    assert!(wallet.import_state(state).is_ok());
+}
+
+#[test]
+fn test_import_state_with_trailing_bytes_fails() {
+    let (wallet, mut state) = exported_state();
+    state.push(0);
+
+    let result = wallet.import_state(state);
+
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
+
+#[test]
+fn test_import_state_with_oversized_lengths_fails() {
+    let (wallet, state) = exported_state();
+    let vault_len = u32::from_le_bytes(state[..4].try_into().unwrap()) as usize;
+    let state_len_at = 4 + vault_len;
+
+    for bad_len in [u32::MAX, u32::MAX - 3, state.len() as u32, (state.len() - 4) as u32] {
+        let mut corrupted = state.clone();
+        corrupted[..4].copy_from_slice(&bad_len.to_le_bytes());
+        let result = wallet.import_state(corrupted);
+        assert!(matches!(result, Err(MeshError::SerializationError { .. })), "vault_len {}", bad_len);
+
+        let mut corrupted = state.clone();
+        corrupted[state_len_at..state_len_at + 4].copy_from_slice(&bad_len.to_le_bytes());
+        let result = wallet.import_state(corrupted);
+        assert!(matches!(result, Err(MeshError::SerializationError { .. })), "state_len {}", bad_len);
+    }
+}
+
+#[test]
+fn test_import_state_with_shifted_lengths_fails_gracefully() {
+    let (wallet, state) = exported_state();
+    let vault_len = u32::from_le_bytes(state[..4].try_into().unwrap());
+
+    // Lengths that still fit the buffer but split the sections in the wrong place
+    for delta in [1u32, 2, 8, 12] {
+        for bad_len in [vault_len - delta, vault_len + delta] {
+            let mut corrupted = state.clone();
+            corrupted[..4].copy_from_slice(&bad_len.to_le_bytes());
+            let result = wallet.import_state(corrupted);
+            assert!(is_graceful(&result) && result.is_err(), "vault_len {}", bad_len);
+        }
+    }
+}
+
+#[test]
+fn test_import_corrupted_state_never_panics() {
+    let (wallet, state) = exported_state();
+    let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    for _ in 0..500 {
+        let mut corrupted = state.clone();
+        for _ in 0..1 + next() % 4 {
+            let at = (next() % corrupted.len() as u64) as usize;
+            corrupted[at] = next() as u8;
+        }
+        let result = wallet.import_state(corrupted);
+        assert!(is_graceful(&result), "Unexpected error: {:?}", result.err());
+    }
+}
+
+#[test]
+fn test_restore_with_random_bytes_never_panics() {
+    let (wallet, _) = exported_state();
+    let mut seed = 0xD1B5_4A32_D192_ED03u64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    for _ in 0..200 {
+        let len = (next() % 64) as usize;
+        let garbage: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+        let result = restore_wallet_with_state(wallet.secret_key(), garbage).map(|_| ());
+        assert!(is_graceful(&result) && result.is_err());
+    }
+}
@@ -0,0 +1,153 @@
+// Send payment tests for the bridge module
+// Tests the atomic single-call payment flow
+
+use p2pmesh_bridge::{create_wallet, fund_wallet_from_faucet, restore_wallet_with_state, MeshError, MeshNode};
+
+// ============================================================================
+// SEND PAYMENT TESTS
+// ============================================================================
+
+#[test]
+fn test_send_payment_deducts_balance_and_records_state() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 1000).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let iou = sender.send_payment(recipient.did(), 300).unwrap();
+
+    assert_eq!(iou.amount(), 300);
+    assert_eq!(iou.recipient(), recipient.did());
+    assert_eq!(sender.balance(), 700);
+    assert_eq!(sender.transaction_count(), 2);
+
+    recipient.process_payment(iou).unwrap();
+    assert_eq!(recipient.balance(), 300);
+}
+
+#[test]
+fn test_send_payment_insufficient_balance() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 100).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let result = sender.send_payment(recipient.did(), 500);
+    assert!(matches!(result, Err(MeshError::InsufficientBalance)));
+    assert_eq!(sender.balance(), 100);
+}
+
+#[test]
+fn test_send_payment_failure_does_not_consume_nonce() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 100).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    assert!(sender.send_payment(recipient.did(), 500).is_err());
+    assert!(sender.send_payment(sender.did(), 10).is_err());
+
+    let iou = sender.send_payment(recipient.did(), 10).unwrap();
+    assert_eq!(iou.nonce(), 1, "Failed sends must not advance the nonce");
+}
+
+#[test]
+fn test_send_payment_nonces_increase() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 1000).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let first = sender.send_payment(recipient.did(), 10).unwrap();
+    let second = sender.send_payment(recipient.did(), 10).unwrap();
+
+    assert_eq!(second.nonce(), first.nonce() + 1);
+    assert_ne!(first.id(), second.id());
+    assert_eq!(sender.balance(), 980);
+}
+
+#[test]
+fn test_send_payment_invalid_recipient() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 100).unwrap();
+
+    let result = sender.send_payment("not-a-did".to_string(), 10);
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+}
+
+#[test]
+fn test_send_payment_after_two_step_flow_continues_nonce() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 1000).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let manual = sender.create_payment(recipient.did(), 50).unwrap();
+    sender.mark_sent(manual.clone()).unwrap();
+
+    let atomic = sender.send_payment(recipient.did(), 50).unwrap();
+    assert!(atomic.nonce() > manual.nonce());
+    assert_eq!(sender.balance(), 900);
+}
+
+// ============================================================================
+// TWO-STEP FLOW TESTS
+// ============================================================================
+
+#[test]
+fn test_unmarked_payment_uses_up_no_nonce() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 1000).unwrap();
+    let recipient = create_wallet().unwrap();
+
+    let abandoned = sender.create_payment(recipient.did(), 50).unwrap();
+    let sent = sender.send_payment(recipient.did(), 50).unwrap();
+
+    assert_eq!(sent.nonce(), abandoned.nonce());
+    assert_eq!(sender.balance(), 950);
+}
+
+#[test]
+fn test_mark_sent_refuses_second_payment_with_same_nonce() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 1000).unwrap();
+    let recipient = create_wallet().unwrap();
+    let first = sender.create_payment(recipient.did(), 50).unwrap();
+    let second = sender.create_payment(recipient.did(), 70).unwrap();
+    sender.mark_sent(first).unwrap();
+
+    let result = sender.mark_sent(second);
+
+    assert!(matches!(result, Err(MeshError::NonceConflict)));
+    assert_eq!(sender.balance(), 950);
+}
+
+#[test]
+fn test_mark_sent_twice_changes_nothing() {
+    let sender = create_wallet().unwrap();
+    fund_wallet_from_faucet(sender.clone(), 1000).unwrap();
+    let recipient = create_wallet().unwrap();
+    let iou = sender.create_payment(recipient.did(), 50).unwrap();
+    sender.mark_sent(iou.clone()).unwrap();
+    let count = sender.transaction_count();
+
+    let result = sender.mark_sent(iou);
+
+    assert!(matches!(result, Err(MeshError::DuplicateTransaction)));
+    assert_eq!(sender.balance(), 950);
+    assert_eq!(sender.transaction_count(), count);
+}
+
+#[test]
+fn test_mark_sent_of_synced_iou_leaves_vault_untouched() {
+    let device_a = create_wallet().unwrap();
+    fund_wallet_from_faucet(device_a.clone(), 1000).unwrap();
+    let device_b = restore_wallet_with_state(device_a.secret_key(), device_a.export_state()).unwrap();
+    let recipient = create_wallet().unwrap();
+    let iou = device_b.create_payment(recipient.did(), 50).unwrap();
+    device_b.mark_sent(iou.clone()).unwrap();
+
+    // The IOU reaches device A's mesh state by sync before it is marked sent there
+    MeshNode::new(device_a.clone())
+        .merge_state(MeshNode::new(device_b).get_state())
+        .unwrap();
+    let result = device_a.mark_sent(iou);
+
+    assert!(matches!(result, Err(MeshError::DuplicateTransaction)));
+    assert_eq!(device_a.balance(), 1000);
+}
@@ -0,0 +1,114 @@
+// External signer tests for the bridge module
+// Tests that payments can be signed through an app-provided ExternalSigner
+
+use p2pmesh::identity::{Keypair, Signer};
+use p2pmesh_bridge::{create_payment_with_signer, create_wallet, ExternalSigner, MeshError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Stands in for a platform keystore holding the secret key
+struct KeystoreSigner {
+    keypair: Keypair,
+    calls: AtomicUsize,
+}
+
+impl KeystoreSigner {
+    fn new() -> Arc<Self> {
+        Arc::new(Self { keypair: Keypair::generate(), calls: AtomicUsize::new(0) })
+    }
+}
+
+impl ExternalSigner for KeystoreSigner {
+    fn public_key(&self) -> Vec<u8> {
+        self.keypair.public_key().as_bytes().to_vec()
+    }
+
+    fn sign(&self, message: Vec<u8>) -> Vec<u8> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Signer::sign(&self.keypair, &message).as_bytes().to_vec()
+    }
+}
+
+/// Returns a fixed public key and signature, whatever is asked
+struct BrokenSigner {
+    public_key: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl ExternalSigner for BrokenSigner {
+    fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    fn sign(&self, _message: Vec<u8>) -> Vec<u8> {
+        self.signature.clone()
+    }
+}
+
+#[test]
+fn test_create_payment_with_signer() {
+    let signer = KeystoreSigner::new();
+    let recipient = create_wallet().unwrap();
+
+    let iou = create_payment_with_signer(signer.clone(), recipient.did(), 250, 1).unwrap();
+
+    assert_eq!(signer.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(iou.amount(), 250);
+    assert_eq!(iou.nonce(), 1);
+    assert_eq!(iou.recipient(), recipient.did());
+    assert!(iou.verify().unwrap());
+}
+
+#[test]
+fn test_signer_payment_accepted_by_recipient() {
+    let signer = KeystoreSigner::new();
+    let recipient = create_wallet().unwrap();
+
+    let iou = create_payment_with_signer(signer, recipient.did(), 100, 1).unwrap();
+    recipient.receive_payment(iou).unwrap();
+
+    assert_eq!(recipient.pending_ious().len(), 1);
+}
+
+#[test]
+fn test_signer_invalid_public_key() {
+    let signer = Arc::new(BrokenSigner { public_key: vec![1, 2, 3], signature: vec![0; 64] });
+    let recipient = create_wallet().unwrap();
+
+    let result = create_payment_with_signer(signer, recipient.did(), 100, 1);
+
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+}
+
+#[test]
+fn test_signer_garbage_signature() {
+    let public_key = Keypair::generate().public_key().as_bytes().to_vec();
+    let recipient = create_wallet().unwrap();
+
+    for signature in [Vec::new(), vec![7; 10], vec![7; 64]] {
+        let signer = Arc::new(BrokenSigner { public_key: public_key.clone(), signature });
+        let result = create_payment_with_signer(signer, recipient.did(), 100, 1);
+        assert!(matches!(result, Err(MeshError::InvalidSignature)));
+    }
+}
+
+#[test]
+fn test_signer_wrong_key_signature() {
+    let other = Keypair::generate();
+    let signer = Arc::new(BrokenSigner {
+        public_key: Keypair::generate().public_key().as_bytes().to_vec(),
+        signature: Signer::sign(&other, b"not the iou").as_bytes().to_vec(),
+    });
+    let recipient = create_wallet().unwrap();
+
+    let result = create_payment_with_signer(signer, recipient.did(), 100, 1);
+
+    assert!(matches!(result, Err(MeshError::InvalidSignature)));
+}
+
+#[test]
+fn test_signer_invalid_recipient() {
+    let result = create_payment_with_signer(KeystoreSigner::new(), "not-a-did".to_string(), 100, 1);
+
+    assert!(matches!(result, Err(MeshError::InvalidKey)));
+}
@@ -0,0 +1,49 @@
+// Multi-output IOU tests for the bridge module
+// Tests that every recipient of a split payment can accept their share
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, IOUCodec};
+use p2pmesh_bridge::{create_wallet, signed_iou_from_bytes};
+
+#[test]
+fn test_each_split_recipient_accepts_share() {
+    let payer = Keypair::generate();
+    let alice = create_wallet().unwrap();
+    let bob = create_wallet().unwrap();
+    let split = IOUBuilder::new()
+        .sender(&payer)
+        .add_output(Did::parse(&alice.did()).unwrap(), 30)
+        .add_output(Did::parse(&bob.did()).unwrap(), 70)
+        .build()
+        .unwrap();
+    let bytes = IOUCodec::encode(&split);
+
+    alice.process_payment(signed_iou_from_bytes(bytes.clone()).unwrap()).unwrap();
+    bob.receive_payment(signed_iou_from_bytes(bytes.clone()).unwrap()).unwrap();
+    bob.process_payment(signed_iou_from_bytes(bytes).unwrap()).unwrap();
+
+    assert_eq!(alice.balance(), 30);
+    assert_eq!(bob.balance(), 70);
+}
+
+#[test]
+fn test_split_amount_for_each_recipient() {
+    let payer = Keypair::generate();
+    let alice = create_wallet().unwrap();
+    let bob = create_wallet().unwrap();
+    let outsider = create_wallet().unwrap();
+    let split = IOUBuilder::new()
+        .sender(&payer)
+        .add_output(Did::parse(&alice.did()).unwrap(), 30)
+        .add_output(Did::parse(&bob.did()).unwrap(), 70)
+        .build()
+        .unwrap();
+
+    let iou = signed_iou_from_bytes(IOUCodec::encode(&split)).unwrap();
+
+    assert_eq!(iou.amount(), 100);
+    assert_eq!(iou.amount_for(alice.did()).unwrap(), 30);
+    assert_eq!(iou.amount_for(bob.did()).unwrap(), 70);
+    assert_eq!(iou.amount_for(outsider.did()).unwrap(), 0);
+    assert!(outsider.process_payment(iou).is_err());
+}
@@ -0,0 +1,79 @@
+// Storage tests for the bridge module
+// Tests that wallets persist and reload their state through MeshStore
+
+use p2pmesh_bridge::{
+    create_wallet, create_wallet_with_storage, fund_wallet_from_faucet, open_wallet, MeshError,
+};
+use std::path::PathBuf;
+
+/// Unique temporary directory for a test store
+fn temp_store_path(name: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("p2pmesh-bridge-{}-{}", name, nanos))
+}
+
+// ============================================================================
+// PERSISTENCE TESTS
+// ============================================================================
+
+#[test]
+fn test_wallet_with_storage_reopens_with_balance() {
+    let path = temp_store_path("reopen");
+    let secret_key;
+
+    {
+        let wallet = create_wallet_with_storage(path.to_string_lossy().to_string()).unwrap();
+        fund_wallet_from_faucet(wallet.clone(), 1000).unwrap();
+        secret_key = wallet.secret_key();
+    }
+
+    let reopened = open_wallet(path.to_string_lossy().to_string(), secret_key).unwrap();
+    assert_eq!(reopened.balance(), 1000, "Balance should survive a restart");
+    assert_eq!(reopened.transaction_count(), 1);
+
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_wallet_with_storage_persists_nonce_counter() {
+    let path = temp_store_path("nonce");
+    let recipient = create_wallet().unwrap();
+    let secret_key;
+    let first_nonce;
+
+    {
+        let wallet = create_wallet_with_storage(path.to_string_lossy().to_string()).unwrap();
+        fund_wallet_from_faucet(wallet.clone(), 1000).unwrap();
+        let iou = wallet.create_payment(recipient.did(), 100).unwrap();
+        first_nonce = iou.nonce();
+        wallet.mark_sent(iou).unwrap();
+        secret_key = wallet.secret_key();
+    }
+
+    let reopened = open_wallet(path.to_string_lossy().to_string(), secret_key).unwrap();
+    assert_eq!(reopened.balance(), 900);
+
+    let next = reopened.create_payment(recipient.did(), 50).unwrap();
+    assert!(next.nonce() > first_nonce, "Nonce must not be reused after restart");
+
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_open_wallet_with_wrong_key_fails() {
+    let path = temp_store_path("wrong-key");
+
+    {
+        let wallet = create_wallet_with_storage(path.to_string_lossy().to_string()).unwrap();
+        fund_wallet_from_faucet(wallet, 100).unwrap();
+    }
+
+    let other = create_wallet().unwrap();
+    let result = open_wallet(path.to_string_lossy().to_string(), other.secret_key());
+    assert!(matches!(result, Err(MeshError::StorageError)));
+
+    let _ = std::fs::remove_dir_all(&path);
+}
@@ -0,0 +1,159 @@
+// Sync tests for the bridge module
+// Tests bloom- and Merkle-summary delta exchange between mesh nodes
+
+use p2pmesh::ledger::IOUEntry;
+use p2pmesh_bridge::{create_wallet, fund_wallet_from_faucet, MeshError, MeshNode};
+
+// ============================================================================
+// BLOOM DELTA TESTS
+// ============================================================================
+
+#[test]
+fn test_delta_from_bloom_returns_missing_entries() {
+    let ahead = create_wallet().unwrap();
+    for _ in 0..3 {
+        fund_wallet_from_faucet(ahead.clone(), 100).unwrap();
+    }
+    let behind = create_wallet().unwrap();
+
+    let ahead_node = MeshNode::new(ahead);
+    let behind_node = MeshNode::new(behind);
+
+    let summary = behind_node.get_bloom_summary(0.01);
+    let delta = ahead_node.get_delta_from_bloom(summary).unwrap();
+    let entries: Vec<IOUEntry> = postcard::from_bytes(&delta).unwrap();
+
+    assert_eq!(entries.len(), 3);
+}
+
+#[test]
+fn test_delta_from_bloom_after_full_sync_is_empty() {
+    let ahead = create_wallet().unwrap();
+    fund_wallet_from_faucet(ahead.clone(), 100).unwrap();
+    let behind = create_wallet().unwrap();
+
+    let ahead_node = MeshNode::new(ahead);
+    let behind_node = MeshNode::new(behind);
+    behind_node.merge_state(ahead_node.get_state()).unwrap();
+
+    let delta = ahead_node
+        .get_delta_from_bloom(behind_node.get_bloom_summary(0.01))
+        .unwrap();
+    let entries: Vec<IOUEntry> = postcard::from_bytes(&delta).unwrap();
+
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn test_delta_from_malformed_bloom_fails() {
+    let node = MeshNode::new(create_wallet().unwrap());
+
+    let result = node.get_delta_from_bloom(vec![0xff, 0xff]);
+    assert!(matches!(result, Err(MeshError::SerializationError { .. })));
+}
+
+// ============================================================================
+// MERKLE SUMMARY DELTA TESTS
+// ============================================================================
+
+#[test]
+fn test_summary_delta_brings_node_up_to_date() {
+    let ahead = create_wallet().unwrap();
+    for _ in 0..3 {
+        fund_wallet_from_faucet(ahead.clone(), 100).unwrap();
+    }
+    let behind = create_wallet().unwrap();
+
+    let ahead_node = MeshNode::new(ahead);
+    let behind_node = MeshNode::new(behind);
+
+    let delta = ahead_node.get_delta_from_summary(behind_node.get_summary()).unwrap();
+    let result = behind_node.apply_delta(delta).unwrap();
+
+    assert_eq!(result.new_entries, 3);
+    assert_eq!(behind_node.iou_count(), 3);
+    assert_eq!(behind_node.stats().total_syncs, 1);
+}
+
+#[test]
+fn test_summary_delta_after_full_sync_is_empty() {
+    let ahead = create_wallet().unwrap();
+    fund_wallet_from_faucet(ahead.clone(), 100).unwrap();
+    let behind = create_wallet().unwrap();
+
+    let ahead_node = MeshNode::new(ahead);
+    let behind_node = MeshNode::new(behind);
+    behind_node.merge_state(ahead_node.get_state()).unwrap();
+
+    let delta = ahead_node.get_delta_from_summary(behind_node.get_summary()).unwrap();
+    let result = behind_node.apply_delta(delta).unwrap();
+
+    assert_eq!(result.new_entries, 0);
+}
+
+#[test]
+fn test_delta_from_malformed_summary_fails() {
+    let node = MeshNode::new(create_wallet().unwrap());
+
+    assert!(matches!(
+        node.get_delta_from_summary(vec![0xff, 0xff]),
+        Err(MeshError::SerializationError { .. })
+    ));
+    assert!(matches!(
+        node.apply_delta(vec![0xff, 0xff]),
+        Err(MeshError::SerializationError { .. })
+    ));
+}
+
+// ============================================================================
+// COMPRESSION TESTS
+// ============================================================================
+
+/// A node holding its faucet funding and `payments` payments to one recipient
+fn paying_node(payments: usize) -> std::sync::Arc<MeshNode> {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 1_000).unwrap();
+    let recipient = create_wallet().unwrap().did();
+    for _ in 0..payments {
+        wallet.send_payment(recipient.clone(), 1).unwrap();
+    }
+    MeshNode::new(wallet)
+}
+
+#[test]
+fn test_compressed_state_is_smaller_and_merges() {
+    let ahead_node = paying_node(39);
+    let raw = ahead_node.get_state();
+    ahead_node.set_compression(256);
+
+    let compressed = ahead_node.get_state();
+    let behind_node = MeshNode::new(create_wallet().unwrap());
+    let result = behind_node.merge_state(compressed.clone()).unwrap();
+
+    assert!(compressed.len() < raw.len());
+    assert_eq!(result.new_entries, 40);
+    assert_eq!(behind_node.iou_count(), 40);
+}
+
+#[test]
+fn test_uncompressed_state_still_merges_into_compressing_node() {
+    let ahead_node = paying_node(2);
+    let behind_node = MeshNode::new(create_wallet().unwrap());
+    behind_node.set_compression(1);
+
+    let result = behind_node.merge_state(ahead_node.get_state()).unwrap();
+
+    assert_eq!(result.new_entries, 3);
+}
+
+#[test]
+fn test_compressed_summary_delta_applies() {
+    let ahead_node = paying_node(19);
+    ahead_node.set_compression(1);
+    let behind_node = MeshNode::new(create_wallet().unwrap());
+
+    let delta = ahead_node.get_delta_from_summary(behind_node.get_summary()).unwrap();
+    let result = behind_node.apply_delta(delta).unwrap();
+
+    assert_eq!(result.new_entries, 20);
+}
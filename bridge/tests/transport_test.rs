@@ -0,0 +1,130 @@
+// Transport tests for the bridge module
+// Tests that bridge transports move whole messages between each other over TCP
+
+use p2pmesh_bridge::{
+    create_tcp_transport, create_wallet, fund_wallet_from_faucet, signed_iou_from_bytes,
+    ReceivedMessage, Transport,
+};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn loopback() -> Arc<Transport> {
+    create_tcp_transport("127.0.0.1:0".to_string()).unwrap()
+}
+
+/// Poll `transport` until `count` messages arrived, or fail after a few seconds
+fn receive(transport: &Transport, count: usize) -> Vec<ReceivedMessage> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut messages = Vec::new();
+    while messages.len() < count {
+        assert!(Instant::now() < deadline, "timed out with {} messages", messages.len());
+        messages.extend(transport.receive());
+        thread::sleep(Duration::from_millis(10));
+    }
+    messages
+}
+
+/// Poll until `transport` sees `count` peers, or fail after a few seconds
+fn wait_for_peers(transport: &Transport, count: u64) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while transport.peer_count() != count {
+        assert!(Instant::now() < deadline, "timed out with {} peers", transport.peer_count());
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+// ============================================================================
+// CONNECTION TESTS
+// ============================================================================
+
+#[test]
+fn test_bind_address_reports_bound_port() {
+    let transport = loopback();
+
+    let address = transport.bind_address();
+
+    assert!(address.starts_with("127.0.0.1:"));
+    assert_ne!(address, "127.0.0.1:0");
+}
+
+#[test]
+fn test_connected_peers_reflect_live_connections() {
+    let alice = loopback();
+    let bob = loopback();
+
+    alice.connect(bob.bind_address()).unwrap();
+    wait_for_peers(&bob, 1);
+
+    assert!(alice.is_connected());
+    assert_eq!(alice.connected_peers()[0].address, bob.bind_address());
+    assert!(bob.connected_peers()[0].connected);
+
+    // The remote end notices the close
+    alice.disconnect(bob.bind_address());
+    assert!(!alice.is_connected());
+    wait_for_peers(&bob, 0);
+}
+
+#[test]
+fn test_connect_to_closed_port_fails() {
+    // Bind then drop a transport so the port is known to be free
+    let address = loopback().bind_address();
+    let alice = loopback();
+
+    assert!(alice.connect(address).is_err());
+    assert!(!alice.is_connected());
+}
+
+#[test]
+fn test_send_to_unknown_peer_fails() {
+    let alice = loopback();
+
+    assert!(alice.send("127.0.0.1:9".to_string(), vec![1, 2, 3]).is_err());
+}
+
+// ============================================================================
+// END-TO-END
+// ============================================================================
+
+#[test]
+fn test_signed_iou_travels_between_transports() {
+    let wallet = create_wallet().unwrap();
+    fund_wallet_from_faucet(wallet.clone(), 500).unwrap();
+    let iou = wallet.send_payment(create_wallet().unwrap().did(), 125).unwrap();
+    let alice = loopback();
+    let bob = loopback();
+
+    alice.connect(bob.bind_address()).unwrap();
+    alice.send(bob.bind_address(), iou.to_bytes()).unwrap();
+
+    let messages = receive(&bob, 1);
+    let received = signed_iou_from_bytes(messages[0].data.clone()).unwrap();
+    assert_eq!(received.id(), iou.id());
+    assert_eq!(received.amount(), 125);
+    assert!(received.verify().unwrap());
+
+    // Bob can answer on the inbound connection
+    bob.send(messages[0].address.clone(), b"ack".to_vec()).unwrap();
+    assert_eq!(receive(&alice, 1)[0].data, b"ack".to_vec());
+}
+
+#[test]
+fn test_messages_arrive_whole_and_in_order() {
+    let alice = loopback();
+    let bob = loopback();
+    alice.connect(bob.bind_address()).unwrap();
+
+    // Larger than a single socket read, followed by small ones that may share a read
+    let large: Vec<u8> = (0..20_000u32).map(|i| i as u8).collect();
+    alice.send(bob.bind_address(), large.clone()).unwrap();
+    for i in 0..5u8 {
+        alice.send(bob.bind_address(), vec![i; 3]).unwrap();
+    }
+
+    let messages = receive(&bob, 6);
+    assert_eq!(messages[0].data, large);
+    for i in 0..5u8 {
+        assert_eq!(messages[1 + i as usize].data, vec![i; 3]);
+    }
+}
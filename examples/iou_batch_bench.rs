@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, IOUValidator, SignedIOU};
+
+const BATCH_SIZE: usize = 1000;
+
+fn bench<F: Fn()>(name: &str, iterations: u32, f: F) -> Duration {
+    // Warmup
+    f();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let per_run = start.elapsed() / iterations;
+    println!("{:.<40} {:>10.3} ms per {} IOUs", name, per_run.as_secs_f64() * 1000.0, BATCH_SIZE);
+    per_run
+}
+
+fn main() {
+    println!("\n========================================");
+    println!("   IOU Batch Signing Benchmarks");
+    println!("========================================\n");
+
+    let merchant = Keypair::generate();
+    let pubkey = merchant.public_key();
+    let items: Vec<(Did, u64)> = (0..BATCH_SIZE)
+        .map(|i| (Did::from_public_key(&Keypair::generate().public_key()), i as u64 + 1))
+        .collect();
+
+    // Signing: one builder per IOU vs build_batch
+    bench("IOUBuilder::build() x1000", 10, || {
+        for (i, (recipient, amount)) in items.iter().enumerate() {
+            let _ = IOUBuilder::new()
+                .sender(&merchant)
+                .recipient(recipient.clone())
+                .amount(*amount)
+                .nonce(i as u64 + 1)
+                .build();
+        }
+    });
+    bench("IOUBuilder::build_batch()", 10, || {
+        let _ = IOUBuilder::build_batch(&items, &merchant, 1);
+    });
+
+    // Verification: one signature at a time vs a single batch
+    let ious = IOUBuilder::build_batch(&items, &merchant, 1).unwrap();
+    let single = bench("SignedIOU::verify() x1000", 10, || {
+        let _: Vec<bool> = ious.iter().map(|iou| iou.verify(&pubkey)).collect();
+    });
+    let batch = bench("SignedIOU::verify_batch()", 10, || {
+        let _ = SignedIOU::verify_batch(&ious, &pubkey);
+    });
+
+    // Verification of a received stream: one IOU from each of 1000 senders
+    let stream: Vec<(SignedIOU, _)> = items
+        .iter()
+        .map(|(recipient, amount)| {
+            let sender = Keypair::generate();
+            let signed = IOUBuilder::new()
+                .sender(&sender)
+                .recipient(recipient.clone())
+                .amount(*amount)
+                .build()
+                .unwrap();
+            (signed, sender.public_key())
+        })
+        .collect();
+    let stream_single = bench("verify() x1000 [1000 senders]", 10, || {
+        let _: Vec<bool> = stream.iter().map(|(iou, key)| iou.verify(key)).collect();
+    });
+    let stream_batch = bench("IOUValidator::verify_batch()", 10, || {
+        let _ = IOUValidator::verify_batch(&stream);
+    });
+    bench("IOUValidator::first_invalid()", 10, || {
+        let _ = IOUValidator::first_invalid(&stream);
+    });
+
+    println!("----------------------------------------");
+    println!("Batch verification speedup:  {:>10.2}x", single.as_secs_f64() / batch.as_secs_f64());
+    println!("Stream verification speedup: {:>10.2}x", stream_single.as_secs_f64() / stream_batch.as_secs_f64());
+}
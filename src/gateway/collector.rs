@@ -1,11 +1,13 @@
 // Collector - Gathers IOUs for settlement
 // Responsible for collecting IOUs from mesh state and creating settlement batches
 
-use crate::identity::Did;
-use crate::iou::{IOUId, SignedIOU};
-use crate::ledger::MeshState;
+use crate::identity::{Did, Keypair};
+use crate::iou::json::JsonObject;
+use crate::iou::{Denomination, IOUId, IOUValidator, SignedIOU};
+use crate::ledger::{MeshState, SignedCheckpoint};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
@@ -70,6 +72,20 @@ pub enum BatchStatus {
     Cancelled,
 }
 
+impl BatchStatus {
+    /// Lowercase name used in the JSON form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchStatus::Pending => "pending",
+            BatchStatus::Processing => "processing",
+            BatchStatus::Submitted => "submitted",
+            BatchStatus::Confirmed => "confirmed",
+            BatchStatus::Failed => "failed",
+            BatchStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
 // ============================================================================
 // SETTLEMENT ENTRY
 // ============================================================================
@@ -82,18 +98,31 @@ pub struct SettlementEntry {
     recipient: Did,
     amount: u64,
     timestamp: u64,
+    denomination: Option<Denomination>,
 }
 
 impl SettlementEntry {
-    /// Create a settlement entry from a signed IOU
-    pub fn from_iou(iou: &SignedIOU) -> Self {
-        Self {
+    /// Create settlement entries from a signed IOU: one per output, so a
+    /// single-recipient IOU gives exactly one entry
+    pub fn from_iou(iou: &SignedIOU) -> Vec<Self> {
+        let inner = iou.iou();
+        let entry = |recipient: &Did, amount: u64| Self {
             iou_id: iou.id(),
-            sender: iou.iou().sender().clone(),
-            recipient: iou.iou().recipient().clone(),
-            amount: iou.iou().amount(),
-            timestamp: iou.iou().timestamp(),
+            sender: inner.sender().clone(),
+            recipient: recipient.clone(),
+            amount,
+            timestamp: inner.timestamp(),
+            denomination: inner.denomination().cloned(),
+        };
+
+        if !inner.is_multi_output() {
+            return vec![entry(inner.recipient(), inner.amount())];
         }
+        inner
+            .outputs()
+            .iter()
+            .map(|output| entry(output.recipient(), output.amount()))
+            .collect()
     }
 
     /// Get the IOU ID
@@ -116,6 +145,23 @@ impl SettlementEntry {
         self.amount
     }
 
+    /// Get the denomination of the IOU the entry came from, if any
+    pub fn denomination(&self) -> Option<&Denomination> {
+        self.denomination.as_ref()
+    }
+
+    /// Canonical JSON object: `iou_id`, `sender`, `recipient`, `amount`, `timestamp`,
+    /// then `denomination` (`{"code","exponent"}`) when set
+    pub fn to_json(&self) -> String {
+        let json = JsonObject::new()
+            .bytes("iou_id", self.iou_id.as_bytes())
+            .string("sender", &self.sender.to_string())
+            .string("recipient", &self.recipient.to_string())
+            .u64("amount", self.amount)
+            .u64("timestamp", self.timestamp);
+        with_denomination(json, self.denomination.as_ref()).finish()
+    }
+
     /// Serialize to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
         postcard::to_allocvec(self).unwrap_or_default()
@@ -131,11 +177,12 @@ impl SettlementEntry {
 // NET POSITION
 // ============================================================================
 
-/// Net position of a party in a settlement batch
+/// Net position of a party in a settlement batch, in one denomination
 #[derive(Clone, Debug)]
 pub struct NetPosition {
     party: Did,
     net_amount: i64,
+    denomination: Option<Denomination>,
 }
 
 impl NetPosition {
@@ -148,6 +195,42 @@ impl NetPosition {
     pub fn net_amount(&self) -> i64 {
         self.net_amount
     }
+
+    /// Get the denomination the net amount is counted in, if any
+    pub fn denomination(&self) -> Option<&Denomination> {
+        self.denomination.as_ref()
+    }
+}
+
+/// A single payment that settles part of a batch's net positions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transfer {
+    from: Did,
+    to: Did,
+    amount: u64,
+    denomination: Option<Denomination>,
+}
+
+impl Transfer {
+    /// Get the paying party
+    pub fn from(&self) -> &Did {
+        &self.from
+    }
+
+    /// Get the receiving party
+    pub fn to(&self) -> &Did {
+        &self.to
+    }
+
+    /// Get the amount paid
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    /// Get the denomination the amount is paid in, if any
+    pub fn denomination(&self) -> Option<&Denomination> {
+        self.denomination.as_ref()
+    }
 }
 
 // ============================================================================
@@ -211,29 +294,207 @@ impl SettlementBatch {
         self.created_at
     }
 
+    /// Key a settlement target can deduplicate on, as 64 hex characters
+    ///
+    /// Hashes the batch's distinct IOU IDs in sorted order, so unlike the batch
+    /// ID it is the same for any batch of the same IOUs, however they were added.
+    pub fn idempotency_key(&self) -> String {
+        let ids: BTreeSet<&[u8; 32]> = self.entries.iter().map(|e| e.iou_id.as_bytes()).collect();
+        let mut hasher = Sha256::new();
+        hasher.update(b"p2pmesh:settlement:");
+        for id in ids {
+            hasher.update(id);
+        }
+        hex::encode(hasher.finalize())
+    }
+
     /// Add an entry to the batch
+    ///
+    /// Doesn't check the denomination; use [`SettlementBatch::try_add_entry`] to keep
+    /// the batch to one.
     pub fn add_entry(&mut self, entry: SettlementEntry) {
         self.total_amount += entry.amount;
         self.entries.push(entry);
     }
 
+    /// Add the entries for every output of an IOU to the batch
+    pub fn add_iou(&mut self, iou: &SignedIOU) {
+        for entry in SettlementEntry::from_iou(iou) {
+            self.add_entry(entry);
+        }
+    }
+
+    /// Add an entry, rejecting it if its denomination differs from the batch's
+    pub fn try_add_entry(&mut self, entry: SettlementEntry) -> Result<(), CollectorError> {
+        if let Some(first) = self.entries.first() {
+            if first.denomination != entry.denomination {
+                return Err(CollectorError::MixedDenominations);
+            }
+        }
+        self.add_entry(entry);
+        Ok(())
+    }
+
+    /// Add an IOU's entries, rejecting the IOU if its denomination differs from the batch's
+    pub fn try_add_iou(&mut self, iou: &SignedIOU) -> Result<(), CollectorError> {
+        let compatible = self
+            .entries
+            .first()
+            .is_none_or(|first| first.denomination.as_ref() == iou.iou().denomination());
+        if !compatible {
+            return Err(CollectorError::MixedDenominations);
+        }
+        self.add_iou(iou);
+        Ok(())
+    }
+
+    /// The denomination every entry shares (`None` for bare credits or an empty batch)
+    ///
+    /// Fails with `MixedDenominations` if entries disagree, so the batch total means nothing.
+    pub fn denomination(&self) -> Result<Option<&Denomination>, CollectorError> {
+        let denomination = self.entries.first().and_then(SettlementEntry::denomination);
+        if self.entries.iter().any(|entry| entry.denomination() != denomination) {
+            return Err(CollectorError::MixedDenominations);
+        }
+        Ok(denomination)
+    }
+
     /// Calculate net positions for all parties in the batch
+    ///
+    /// Amounts in different denominations are never added together: a party gets
+    /// one position per denomination it appears in.
     pub fn calculate_net_positions(&self) -> Vec<NetPosition> {
-        let mut positions: HashMap<Did, i64> = HashMap::new();
+        let mut positions: HashMap<(Did, Option<Denomination>), i64> = HashMap::new();
 
         for entry in &self.entries {
+            let denomination = &entry.denomination;
             // Sender loses money (negative)
-            *positions.entry(entry.sender.clone()).or_insert(0) -= entry.amount as i64;
+            *positions.entry((entry.sender.clone(), denomination.clone())).or_insert(0) -= entry.amount as i64;
             // Recipient gains money (positive)
-            *positions.entry(entry.recipient.clone()).or_insert(0) += entry.amount as i64;
+            *positions.entry((entry.recipient.clone(), denomination.clone())).or_insert(0) += entry.amount as i64;
         }
 
         positions
             .into_iter()
-            .map(|(party, net_amount)| NetPosition { party, net_amount })
+            .map(|((party, denomination), net_amount)| NetPosition { party, net_amount, denomination })
             .collect()
     }
 
+    /// Transfers that settle every party's net position, with debts simplified
+    ///
+    /// Greedily matches the largest debtor with the largest creditor until all nets
+    /// are zero, so `n` parties with a non-zero net need at most `n - 1` transfers
+    /// per denomination. Ties are broken by DID so the result is deterministic.
+    pub fn minimal_transfers(&self) -> Vec<Transfer> {
+        let mut by_denomination: HashMap<Option<Denomination>, Vec<NetPosition>> = HashMap::new();
+        for position in self.calculate_net_positions() {
+            by_denomination.entry(position.denomination.clone()).or_default().push(position);
+        }
+
+        let mut denominations: Vec<_> = by_denomination.into_iter().collect();
+        denominations.sort_by_key(|(denomination, _)| denomination.as_ref().map(|d| d.code().to_string()));
+        denominations
+            .into_iter()
+            .flat_map(|(denomination, positions)| Self::settle_positions(positions, denomination))
+            .collect()
+    }
+
+    /// Greedy debt simplification over positions that share one denomination
+    fn settle_positions(positions: Vec<NetPosition>, denomination: Option<Denomination>) -> Vec<Transfer> {
+        let mut debtors = Vec::new();
+        let mut creditors = Vec::new();
+        for position in positions {
+            let amount = position.net_amount.unsigned_abs();
+            if position.net_amount < 0 {
+                debtors.push((position.party, amount));
+            } else if position.net_amount > 0 {
+                creditors.push((position.party, amount));
+            }
+        }
+
+        let largest_first = |a: &(Did, u64), b: &(Did, u64)| {
+            b.1.cmp(&a.1).then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+        };
+        debtors.sort_by(largest_first);
+        creditors.sort_by(largest_first);
+
+        let mut transfers = Vec::new();
+        let (mut d, mut c) = (0, 0);
+        while d < debtors.len() && c < creditors.len() {
+            let amount = debtors[d].1.min(creditors[c].1);
+            transfers.push(Transfer {
+                from: debtors[d].0.clone(),
+                to: creditors[c].0.clone(),
+                amount,
+                denomination: denomination.clone(),
+            });
+
+            debtors[d].1 -= amount;
+            creditors[c].1 -= amount;
+            if debtors[d].1 == 0 {
+                d += 1;
+            }
+            if creditors[c].1 == 0 {
+                c += 1;
+            }
+        }
+
+        transfers
+    }
+
+    /// Canonical JSON for POSTing to a settlement backend
+    ///
+    /// Keys in order, no whitespace: `id`, `status`, `created_at`, `total_amount`,
+    /// `entries` (see [`SettlementEntry::to_json`]). Byte fields are unpadded
+    /// base64url and integers are decimal strings, as in [`IOUCodec::to_json`](crate::iou::IOUCodec::to_json).
+    pub fn to_json(&self) -> String {
+        JsonObject::new()
+            .bytes("id", self.id.as_bytes())
+            .string("status", self.status.as_str())
+            .u64("created_at", self.created_at)
+            .u64("total_amount", self.total_amount)
+            .array("entries", self.entries.iter().map(SettlementEntry::to_json))
+            .finish()
+    }
+
+    /// Canonical JSON of the batch's net positions, the body an HTTP settlement target POSTs
+    ///
+    /// Keys in order: `batch_id`, `total_amount`, `positions` (each `{"party","net_amount"}`
+    /// plus `denomination` when set, sorted by party DID). Net amounts are signed decimal strings.
+    pub fn net_positions_to_json(&self) -> String {
+        let mut positions = self.calculate_net_positions();
+        positions.sort_by_key(|position| {
+            (position.party.to_string(), position.denomination.as_ref().map(|d| d.code().to_string()))
+        });
+
+        JsonObject::new()
+            .bytes("batch_id", self.id.as_bytes())
+            .u64("total_amount", self.total_amount)
+            .array(
+                "positions",
+                positions.iter().map(|position| {
+                    let json = JsonObject::new()
+                        .string("party", &position.party.to_string())
+                        .string("net_amount", &position.net_amount.to_string());
+                    with_denomination(json, position.denomination.as_ref()).finish()
+                }),
+            )
+            .finish()
+    }
+
+    /// Sign a checkpoint of the batch's IOUs so mesh nodes can prune them
+    ///
+    /// Fails with `BatchNotConfirmed` until the settlement is confirmed.
+    pub fn checkpoint(&self, gateway: &Keypair) -> Result<SignedCheckpoint, CollectorError> {
+        if self.status != BatchStatus::Confirmed {
+            return Err(CollectorError::BatchNotConfirmed);
+        }
+        Ok(SignedCheckpoint::sign(
+            gateway,
+            self.entries.iter().map(|entry| entry.iou_id.clone()),
+        ))
+    }
+
     /// Serialize to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
         postcard::to_allocvec(self).unwrap_or_default()
@@ -251,6 +512,20 @@ impl Default for SettlementBatch {
     }
 }
 
+/// Append a `denomination` object (`{"code","exponent"}`) when one is set
+fn with_denomination(json: JsonObject, denomination: Option<&Denomination>) -> JsonObject {
+    match denomination {
+        Some(denomination) => json.object(
+            "denomination",
+            JsonObject::new()
+                .string("code", denomination.code())
+                .u64("exponent", u64::from(denomination.exponent()))
+                .finish(),
+        ),
+        None => json,
+    }
+}
+
 // ============================================================================
 // COLLECTOR CONFIG
 // ============================================================================
@@ -339,6 +614,8 @@ pub struct CollectorStats {
     pub total_collected: u64,
     pub total_amount_collected: u64,
     pub batches_created: u64,
+    /// IOUs skipped because their signature didn't verify
+    pub total_invalid: u64,
 }
 
 // ============================================================================
@@ -359,6 +636,12 @@ pub enum CollectorError {
 
     #[error("Deserialization failed")]
     DeserializationFailed,
+
+    #[error("Batch mixes entries of different denominations")]
+    MixedDenominations,
+
+    #[error("Batch settlement is not confirmed")]
+    BatchNotConfirmed,
 }
 
 // ============================================================================
@@ -407,43 +690,19 @@ impl Collector {
 
     /// Collect IOUs from mesh state
     pub fn collect_from_state(&mut self, state: &MeshState) -> Result<usize, CollectorError> {
-        let mut collected = 0;
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
-        for entry in state.all_entries() {
-            let iou = entry.iou();
-            let iou_id = iou.id();
-            let id_bytes = iou_id.as_bytes().to_vec();
-
-            // Skip if already collected
-            if self.collected_ids.contains(&id_bytes) {
-                continue;
-            }
-
-            // Check minimum amount
-            if iou.iou().amount() < self.config.min_amount {
-                continue;
-            }
-
-            // Check minimum age
+        let (min_amount, min_age) = (self.config.min_amount, self.config.min_iou_age_secs);
+        let candidates = state.all_entries().into_iter().map(|entry| entry.iou()).filter(|iou| {
+            // Check minimum amount and age
             let age = now.saturating_sub(iou.iou().timestamp());
-            if age < self.config.min_iou_age_secs {
-                continue;
-            }
-
-            // Collect this IOU
-            let settlement_entry = SettlementEntry::from_iou(iou);
-            self.stats.total_amount_collected += settlement_entry.amount;
-            self.collected_ious.push(settlement_entry);
-            self.collected_ids.insert(id_bytes);
-            self.stats.total_collected += 1;
-            collected += 1;
-        }
+            iou.iou().amount() >= min_amount && age >= min_age
+        });
 
-        Ok(collected)
+        Ok(self.collect_verified(candidates))
     }
 
     /// Collect IOUs by sender
@@ -452,28 +711,8 @@ impl Collector {
         state: &MeshState,
         sender: &Did,
     ) -> Result<usize, CollectorError> {
-        let mut collected = 0;
-
-        for entry in state.get_ious_by_sender(sender) {
-            let iou = entry.iou();
-            let iou_id = iou.id();
-            let id_bytes = iou_id.as_bytes().to_vec();
-
-            // Skip if already collected
-            if self.collected_ids.contains(&id_bytes) {
-                continue;
-            }
-
-            // Collect this IOU
-            let settlement_entry = SettlementEntry::from_iou(iou);
-            self.stats.total_amount_collected += settlement_entry.amount;
-            self.collected_ious.push(settlement_entry);
-            self.collected_ids.insert(id_bytes);
-            self.stats.total_collected += 1;
-            collected += 1;
-        }
-
-        Ok(collected)
+        let candidates = state.get_ious_by_sender(sender).into_iter().map(|entry| entry.iou());
+        Ok(self.collect_verified(candidates))
     }
 
     /// Collect IOUs by recipient
@@ -482,47 +721,72 @@ impl Collector {
         state: &MeshState,
         recipient: &Did,
     ) -> Result<usize, CollectorError> {
-        let mut collected = 0;
-
-        for entry in state.get_ious_by_recipient(recipient) {
-            let iou = entry.iou();
-            let iou_id = iou.id();
-            let id_bytes = iou_id.as_bytes().to_vec();
+        let candidates = state.get_ious_by_recipient(recipient).into_iter().map(|entry| entry.iou());
+        Ok(self.collect_verified(candidates))
+    }
 
+    /// Collect the IOUs not yet collected whose signatures verify, returning how many were added
+    ///
+    /// Signatures are batch-verified, since merged mesh state isn't re-checked. IOUs
+    /// that fail are skipped and counted in `CollectorStats::total_invalid`.
+    fn collect_verified<'a>(&mut self, candidates: impl Iterator<Item = &'a SignedIOU>) -> usize {
+        let mut signed = Vec::new();
+        for iou in candidates {
             // Skip if already collected
-            if self.collected_ids.contains(&id_bytes) {
+            if self.collected_ids.contains(iou.id().as_bytes().as_slice()) {
+                continue;
+            }
+            match iou.iou().sender().public_key() {
+                Ok(key) => signed.push((iou.clone(), key)),
+                Err(_) => self.stats.total_invalid += 1,
+            }
+        }
+
+        let mut collected = 0;
+        for ((iou, _), valid) in signed.iter().zip(IOUValidator::verify_batch(&signed)) {
+            if !valid {
+                self.stats.total_invalid += 1;
                 continue;
             }
 
             // Collect this IOU
-            let settlement_entry = SettlementEntry::from_iou(iou);
-            self.stats.total_amount_collected += settlement_entry.amount;
-            self.collected_ious.push(settlement_entry);
-            self.collected_ids.insert(id_bytes);
+            self.stats.total_amount_collected += iou.iou().amount();
+            self.collected_ious.extend(SettlementEntry::from_iou(iou));
+            self.collected_ids.insert(iou.id().as_bytes().to_vec());
             self.stats.total_collected += 1;
             collected += 1;
         }
-
-        Ok(collected)
+        collected
     }
 
     /// Create a batch from collected IOUs
+    ///
+    /// A batch holds one denomination: that of the oldest collected entry. Entries
+    /// in other denominations wait for a later batch.
     pub fn create_batch(&mut self) -> Result<SettlementBatch, CollectorError> {
-        if self.collected_ious.len() < self.config.min_batch_size as usize {
+        let denomination = self.collected_ious.first().and_then(|entry| entry.denomination.clone());
+        let matching = self
+            .collected_ious
+            .iter()
+            .filter(|entry| entry.denomination == denomination)
+            .count();
+        if matching < self.config.min_batch_size as usize {
             return Err(CollectorError::InsufficientIOUs);
         }
 
         let mut batch = SettlementBatch::new();
 
-        // Take up to max_batch_size entries
-        let take_count = std::cmp::min(
-            self.collected_ious.len(),
-            self.config.max_batch_size as usize,
-        );
-
-        for entry in self.collected_ious.drain(..take_count) {
-            batch.add_entry(entry);
+        // Take up to max_batch_size entries of the batch's denomination
+        let take_count = std::cmp::min(matching, self.config.max_batch_size as usize);
+        let mut remaining = Vec::with_capacity(self.collected_ious.len() - take_count);
+        for entry in self.collected_ious.drain(..) {
+            if batch.entries.len() < take_count && entry.denomination == denomination {
+                batch.add_entry(entry);
+            } else {
+                remaining.push(entry);
+            }
         }
+        self.collected_ious = remaining;
 
         self.stats.batches_created += 1;
 
@@ -0,0 +1,122 @@
+// HTTP settlement target - POSTs batches to a settlement backend
+// Only built with the `http-settlement` feature
+
+use super::{SettlementBatch, SettlementTarget, SettlerConfig, SettlerError};
+use crate::iou::json::JsonValue;
+use async_trait::async_trait;
+use std::time::Duration;
+
+// ============================================================================
+// HTTP SETTLEMENT TARGET
+// ============================================================================
+
+/// Settlement target that POSTs a batch's net positions as JSON to an HTTP endpoint
+///
+/// The body is [`SettlementBatch::net_positions_to_json`], sent with the API key
+/// as a Bearer token when one is set. A 2xx response must carry a JSON object with
+/// a `transaction_id` string. Non-2xx responses, timeouts and network errors are
+/// returned as failures, so the `Settler` retries them.
+pub struct HttpSettlementTarget {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+}
+
+impl HttpSettlementTarget {
+    /// Target for `endpoint`, with requests timing out after `timeout_secs`
+    pub fn new(endpoint: &str, timeout_secs: u64) -> Result<Self, SettlerError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout_secs))
+            .build()
+            .map_err(|e| SettlerError::InvalidConfig(format!("HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            endpoint: endpoint.to_string(),
+            api_key: None,
+        })
+    }
+
+    /// Target built from a settler config's `endpoint`, `api_key` and `timeout_secs`
+    pub fn from_config(config: &SettlerConfig) -> Result<Self, SettlerError> {
+        config.validate()?;
+        let endpoint = config
+            .endpoint
+            .as_deref()
+            .ok_or_else(|| SettlerError::InvalidConfig("endpoint is required".to_string()))?;
+
+        let target = Self::new(endpoint, config.timeout_secs)?;
+        Ok(match &config.api_key {
+            Some(key) => target.with_api_key(key),
+            None => target,
+        })
+    }
+
+    /// Send `key` as a Bearer token
+    pub fn with_api_key(mut self, key: &str) -> Self {
+        self.api_key = Some(key.to_string());
+        self
+    }
+
+    /// Get the endpoint URL
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Pull the transaction ID out of a settlement response body
+    fn parse_transaction_id(body: &str) -> Result<String, String> {
+        let response =
+            JsonValue::parse(body).map_err(|e| format!("Invalid settlement response: {}", e))?;
+        let transaction_id = response
+            .str_field("transaction_id")
+            .map_err(|e| format!("Invalid settlement response: {}", e))?;
+        if transaction_id.is_empty() {
+            return Err("Invalid settlement response: empty transaction_id".to_string());
+        }
+        Ok(transaction_id.to_string())
+    }
+}
+
+#[async_trait]
+impl SettlementTarget for HttpSettlementTarget {
+    async fn settle(&self, batch: &SettlementBatch) -> Result<String, String> {
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("Idempotency-Key", batch.idempotency_key())
+            .body(batch.net_positions_to_json());
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                "Timeout".to_string()
+            } else {
+                format!("Request failed: {}", e)
+            }
+        })?;
+
+        let status = response.status();
+        // Only the delay-seconds form; an HTTP date falls back to backoff
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read response: {}", e))?;
+
+        if !status.is_success() {
+            let mut error = format!("Settlement endpoint returned {}: {}", status.as_u16(), body);
+            if let Some(secs) = retry_after {
+                error.push_str(&format!(" (retry after {}s)", secs));
+            }
+            return Err(error);
+        }
+        Self::parse_transaction_id(&body)
+    }
+}
@@ -2,7 +2,11 @@
 // Handles collecting IOUs and settling them to external systems (banks, blockchains)
 
 mod collector;
+#[cfg(feature = "http-settlement")]
+mod http;
 mod settler;
 
 pub use collector::*;
+#[cfg(feature = "http-settlement")]
+pub use http::*;
 pub use settler::*;
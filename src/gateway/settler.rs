@@ -1,8 +1,14 @@
 // Settler - Pushes settlements to external systems
 // Responsible for submitting batches to banks, blockchains, or other settlement targets
+//
+// With a store attached, every batch status change and result is written as it
+// happens, so a gateway restarted mid-settlement knows which batches settled and
+// which were handed to the target without an answer, and never pays twice.
 
 use super::{BatchId, BatchStatus, SettlementBatch};
+use crate::storage::{MeshStore, StoreError, StoreWrite};
 use async_trait::async_trait;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -18,7 +24,25 @@ use thiserror::Error;
 pub trait SettlementTarget: Send + Sync {
     /// Attempt to settle a batch
     /// Returns transaction ID on success, error message on failure
+    ///
+    /// A batch may be sent again after a retry or a crash; implementations
+    /// should pass on `batch.idempotency_key()` so the far side settles it once.
     async fn settle(&self, batch: &SettlementBatch) -> Result<String, String>;
+
+    /// How long the target asked to be left alone after failing with `error`
+    ///
+    /// By default this reads a `retry after <secs>s` hint from the message.
+    fn retry_after(&self, error: &str) -> Option<Duration> {
+        parse_retry_after(error)
+    }
+}
+
+/// The wait a failure message asks for with `retry after <secs>s`, if any
+pub fn parse_retry_after(error: &str) -> Option<Duration> {
+    const HINT: &str = "retry after ";
+    let start = error.to_ascii_lowercase().find(HINT)? + HINT.len();
+    let digits: String = error[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok().map(Duration::from_secs)
 }
 
 // ============================================================================
@@ -180,7 +204,7 @@ impl SettlementReceipt {
 // ============================================================================
 
 /// Result of a settlement attempt
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SettlementResult {
     batch_id: BatchId,
     success: bool,
@@ -294,8 +318,14 @@ pub enum SettlerEvent {
 pub struct SettlerConfig {
     /// Maximum number of retry attempts
     pub max_retries: u32,
-    /// Delay between retries in seconds
+    /// Delay before the first retry in seconds
     pub retry_delay_secs: u64,
+    /// Factor the delay grows by with each further retry
+    pub retry_multiplier: f64,
+    /// Longest delay between retries in seconds
+    pub max_retry_delay_secs: u64,
+    /// Fraction of each delay randomly shaved off, so failed gateways don't retry in step
+    pub retry_jitter: f64,
     /// Timeout for settlement attempts in seconds
     pub timeout_secs: u64,
     /// Optional endpoint URL for the settlement target
@@ -322,6 +352,55 @@ impl SettlerConfig {
         self
     }
 
+    /// Set the factor the retry delay grows by
+    pub fn with_retry_multiplier(mut self, multiplier: f64) -> Self {
+        self.retry_multiplier = multiplier;
+        self
+    }
+
+    /// Set the longest retry delay in seconds
+    pub fn with_max_retry_delay_secs(mut self, secs: u64) -> Self {
+        self.max_retry_delay_secs = secs;
+        self
+    }
+
+    /// Set the fraction of each retry delay left to chance (0 to 1)
+    pub fn with_retry_jitter(mut self, jitter: f64) -> Self {
+        self.retry_jitter = jitter;
+        self
+    }
+
+    /// Delay before retry number `retry` (the first is 1), without jitter
+    ///
+    /// Grows from `retry_delay_secs` by `retry_multiplier` per retry, up to
+    /// `max_retry_delay_secs`.
+    pub fn backoff_delay(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let secs = self.retry_delay_secs as f64 * self.retry_multiplier.powi(exponent);
+        // NaN.min(cap) is the cap, so a broken multiplier can't panic here
+        Duration::from_secs_f64(secs.min(self.max_retry_delay_secs as f64).max(0.0))
+    }
+
+    /// Delay before retry number `retry`, with jitter
+    ///
+    /// A `retry_after` hint from the target is honoured up to the cap.
+    pub fn retry_delay<R: Rng + ?Sized>(
+        &self,
+        retry: u32,
+        retry_after: Option<Duration>,
+        rng: &mut R,
+    ) -> Duration {
+        let mut delay = self.backoff_delay(retry);
+        let jitter = self.retry_jitter.clamp(0.0, 1.0);
+        if jitter > 0.0 {
+            delay = delay.mul_f64(1.0 - jitter * rng.gen::<f64>());
+        }
+        match retry_after {
+            Some(hint) => delay.max(hint.min(Duration::from_secs(self.max_retry_delay_secs))),
+            None => delay,
+        }
+    }
+
     /// Set the timeout in seconds
     pub fn with_timeout_secs(mut self, secs: u64) -> Self {
         self.timeout_secs = secs;
@@ -347,6 +426,16 @@ impl SettlerConfig {
                 "timeout_secs must be > 0".to_string(),
             ));
         }
+        if self.retry_multiplier.is_nan() || self.retry_multiplier < 1.0 {
+            return Err(SettlerError::InvalidConfig(
+                "retry_multiplier must be >= 1".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.retry_jitter) {
+            return Err(SettlerError::InvalidConfig(
+                "retry_jitter must be between 0 and 1".to_string(),
+            ));
+        }
         Ok(())
     }
 }
@@ -356,6 +445,9 @@ impl Default for SettlerConfig {
         Self {
             max_retries: 3,
             retry_delay_secs: 10,
+            retry_multiplier: 2.0,
+            max_retry_delay_secs: 300,
+            retry_jitter: 0.2,
             timeout_secs: 60,
             endpoint: None,
             api_key: None,
@@ -407,16 +499,60 @@ pub enum SettlerError {
 
     #[error("Deserialization failed")]
     DeserializationFailed,
+
+    #[error("Batch mixes entries of different denominations")]
+    MixedDenominations,
+
+    #[error("Batch was in flight when the settler stopped; reconcile it first")]
+    BatchInFlight,
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] StoreError),
 }
 
 // ============================================================================
 // SETTLER
 // ============================================================================
 
+/// Key prefixes for persisted settler records, followed by the batch ID
+mod keys {
+    pub const BATCH: &[u8] = b"settler:batch:";
+    pub const RESULT: &[u8] = b"settler:result:";
+}
+
+fn batch_key(prefix: &[u8], batch_id: &BatchId) -> Vec<u8> {
+    [prefix, batch_id.as_bytes()].concat()
+}
+
+/// Write `batch`, and its result if it has one, to `store` in one atomic batch
+fn save(
+    store: Option<&MeshStore>,
+    batch: &SettlementBatch,
+    result: Option<&SettlementResult>,
+) -> Result<(), StoreError> {
+    let Some(store) = store else {
+        return Ok(());
+    };
+    let mut writes = vec![StoreWrite::Put {
+        key: batch_key(keys::BATCH, batch.id()),
+        value: batch.to_bytes(),
+    }];
+    if let Some(result) = result {
+        let value = postcard::to_allocvec(result).map_err(|e| StoreError::SerializationFailed(e.to_string()))?;
+        writes.push(StoreWrite::Put {
+            key: batch_key(keys::RESULT, batch.id()),
+            value,
+        });
+    }
+    store.apply_batch(&writes)
+}
+
 /// Settler for submitting batches to external systems
 pub struct Settler {
     config: SettlerConfig,
     target: Option<Box<dyn SettlementTarget>>,
+    /// Where batch status changes and results are persisted, if anywhere
+    store: Option<MeshStore>,
     /// Batches that have been submitted
     batches: HashMap<BatchId, SettlementBatch>,
     /// Results of processed batches
@@ -433,6 +569,7 @@ impl Settler {
         Self {
             config,
             target: None,
+            store: None,
             batches: HashMap::new(),
             results: HashMap::new(),
             events: Vec::new(),
@@ -445,6 +582,7 @@ impl Settler {
         Self {
             config,
             target: Some(target),
+            store: None,
             batches: HashMap::new(),
             results: HashMap::new(),
             events: Vec::new(),
@@ -452,11 +590,100 @@ impl Settler {
         }
     }
 
+    /// Persist batch status changes and results to `store`
+    ///
+    /// Call `recover` after attaching to pick up batches from before a restart.
+    pub fn with_store(mut self, store: MeshStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
     /// Check if a target is configured
     pub fn has_target(&self) -> bool {
         self.target.is_some()
     }
 
+    /// Reload the batches and results persisted in the store
+    ///
+    /// Statistics are re-derived from the reloaded batches. Returns the IDs of
+    /// batches that were handed to the target with no answer recorded: the target
+    /// may or may not have settled them, so `process` refuses them until they are
+    /// checked against the target and passed to `reconcile`.
+    pub fn recover(&mut self) -> Result<Vec<BatchId>, SettlerError> {
+        let Some(store) = &self.store else {
+            return Ok(Vec::new());
+        };
+        for (_, value) in store.scan_prefix(keys::BATCH)? {
+            let batch = SettlementBatch::from_bytes(&value).map_err(|_| SettlerError::DeserializationFailed)?;
+            self.batches.insert(batch.id().clone(), batch);
+        }
+        for (_, value) in store.scan_prefix(keys::RESULT)? {
+            let result: SettlementResult =
+                postcard::from_bytes(&value).map_err(|_| SettlerError::DeserializationFailed)?;
+            self.results.insert(result.batch_id().clone(), result);
+        }
+
+        let mut stats = SettlerStats {
+            batches_submitted: self.batches.len() as u64,
+            ..SettlerStats::default()
+        };
+        for batch in self.batches.values() {
+            match batch.status() {
+                BatchStatus::Confirmed => {
+                    stats.batches_settled += 1;
+                    stats.total_entries_settled += batch.entries().len() as u64;
+                    stats.total_amount_settled += batch.total_amount();
+                }
+                BatchStatus::Failed => stats.batches_failed += 1,
+                _ => {}
+            }
+        }
+        self.stats = stats;
+
+        Ok(self.in_flight().into_iter().map(|batch| batch.id().clone()).collect())
+    }
+
+    /// Batches handed to the target with no answer recorded (see `recover`)
+    pub fn in_flight(&self) -> Vec<&SettlementBatch> {
+        self.batches
+            .values()
+            .filter(|b| matches!(b.status(), BatchStatus::Processing | BatchStatus::Submitted))
+            .collect()
+    }
+
+    /// Settle an in-flight batch by what the target says happened to it
+    ///
+    /// With the target's transaction ID the batch is confirmed without being sent
+    /// again; without one the target never got it, so it goes back to pending
+    /// for `process` to retry.
+    pub fn reconcile(&mut self, batch_id: &BatchId, transaction_id: Option<String>) -> Result<(), SettlerError> {
+        let batch = self.batches.get_mut(batch_id).ok_or(SettlerError::BatchNotFound)?;
+        if !matches!(batch.status(), BatchStatus::Processing | BatchStatus::Submitted) {
+            return Err(SettlerError::BatchAlreadyProcessed);
+        }
+
+        let Some(tx_id) = transaction_id else {
+            batch.set_status(BatchStatus::Pending);
+            return Ok(save(self.store.as_ref(), batch, None)?);
+        };
+
+        batch.set_status(BatchStatus::Confirmed);
+        let result = SettlementResult::success(batch_id.clone(), tx_id.clone())
+            .with_receipt(SettlementReceipt::new(&tx_id, batch.total_amount()));
+        save(self.store.as_ref(), batch, Some(&result))?;
+
+        self.stats.batches_settled += 1;
+        self.stats.total_entries_settled += batch.entries().len() as u64;
+        self.stats.total_amount_settled += batch.total_amount();
+        self.events.push(SettlerEvent::SettlementComplete {
+            batch_id: batch_id.clone(),
+            success: true,
+            transaction_id: Some(tx_id),
+        });
+        self.results.insert(batch_id.clone(), result);
+        Ok(())
+    }
+
     /// Get the number of pending settlements
     pub fn pending_settlements(&self) -> usize {
         self.batches
@@ -474,12 +701,20 @@ impl Settler {
     }
 
     /// Submit a batch for settlement
+    ///
+    /// Refused as a duplicate if a batch with the same idempotency key is held
+    /// and hasn't failed, since that would settle the same IOUs twice.
     pub async fn submit(&mut self, batch: SettlementBatch) -> Result<(), SettlerError> {
         // Check for empty batch
         if batch.entries().is_empty() {
             return Err(SettlerError::EmptyBatch);
         }
 
+        // A mixed batch has no meaningful total
+        if batch.denomination().is_err() {
+            return Err(SettlerError::MixedDenominations);
+        }
+
         // Check for target
         if self.target.is_none() {
             return Err(SettlerError::NoTarget);
@@ -490,6 +725,16 @@ impl Settler {
             return Err(SettlerError::DuplicateBatch);
         }
 
+        // Or the same IOUs under another batch, unless that one failed
+        let key = batch.idempotency_key();
+        if self
+            .batches
+            .values()
+            .any(|known| *known.status() != BatchStatus::Failed && known.idempotency_key() == key)
+        {
+            return Err(SettlerError::DuplicateBatch);
+        }
+
         // Emit event
         self.events.push(SettlerEvent::BatchSubmitted {
             batch_id: batch.id().clone(),
@@ -497,9 +742,9 @@ impl Settler {
             total_amount: batch.total_amount(),
         });
 
+        // Store the batch, persisted first so a failed write leaves no trace
+        save(self.store.as_ref(), &batch, None)?;
         self.stats.batches_submitted += 1;
-
-        // Store the batch
         self.batches.insert(batch.id().clone(), batch);
 
         Ok(())
@@ -516,8 +761,20 @@ impl Settler {
         // Get the target
         let target = self.target.as_ref().ok_or(SettlerError::NoTarget)?;
 
-        // Update status
+        // Never send a batch again once the target has, or may have, settled it
+        match batch.status() {
+            BatchStatus::Confirmed => return Err(SettlerError::BatchAlreadyProcessed),
+            BatchStatus::Processing | BatchStatus::Submitted => return Err(SettlerError::BatchInFlight),
+            _ => {}
+        }
+
+        // Update status, recorded before the target sees the batch
+        let previous = batch.status().clone();
         batch.set_status(BatchStatus::Processing);
+        if let Err(e) = save(self.store.as_ref(), batch, None) {
+            batch.set_status(previous);
+            return Err(e.into());
+        }
 
         // Try to settle with retries
         let mut attempts = 0u32;
@@ -548,9 +805,11 @@ impl Settler {
                         transaction_id: Some(tx_id.clone()),
                     });
 
-                    let result = SettlementResult::success(batch_id.clone(), tx_id)
-                        .with_attempts(attempts);
+                    let result = SettlementResult::success(batch_id.clone(), tx_id.clone())
+                        .with_attempts(attempts)
+                        .with_receipt(SettlementReceipt::new(&tx_id, batch.total_amount()));
                     self.results.insert(batch_id.clone(), result.clone());
+                    save(self.store.as_ref(), batch, Some(&result))?;
 
                     return Ok(result);
                 }
@@ -567,9 +826,11 @@ impl Settler {
                 break;
             }
 
-            // Wait before retry
-            if self.config.retry_delay_secs > 0 {
-                tokio::time::sleep(Duration::from_secs(self.config.retry_delay_secs)).await;
+            // Back off before retrying, longer each time
+            let retry_after = target.retry_after(&last_error);
+            let delay = self.config.retry_delay(attempts, retry_after, &mut rand::thread_rng());
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
             }
         }
 
@@ -594,6 +855,7 @@ impl Settler {
         let result =
             SettlementResult::failure(batch_id.clone(), last_error).with_attempts(attempts);
         self.results.insert(batch_id.clone(), result.clone());
+        save(self.store.as_ref(), batch, Some(&result))?;
 
         Ok(result)
     }
@@ -613,8 +875,16 @@ impl Settler {
             _ => {}
         }
 
+        if let Some(store) = &self.store {
+            store.apply_batch(&[
+                StoreWrite::Delete { key: batch_key(keys::BATCH, batch_id) },
+                StoreWrite::Delete { key: batch_key(keys::RESULT, batch_id) },
+            ])?;
+        }
+
         // Remove the batch
         self.batches.remove(batch_id);
+        self.results.remove(batch_id);
 
         Ok(())
     }
@@ -624,6 +894,11 @@ impl Settler {
         self.batches.get(batch_id).map(|b| b.status().clone())
     }
 
+    /// Get the result of a processed batch
+    pub fn get_result(&self, batch_id: &BatchId) -> Option<&SettlementResult> {
+        self.results.get(batch_id)
+    }
+
     /// List batches by status
     pub fn list_by_status(&self, status: BatchStatus) -> Vec<&SettlementBatch> {
         self.batches
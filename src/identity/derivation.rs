@@ -0,0 +1,124 @@
+// Hierarchical key derivation - SLIP-0010 for Ed25519
+// One master key yields distinct keypairs (and DIDs) per device or terminal
+
+use crate::identity::Keypair;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Offset added to an index to mark it hardened (written `i'` or `iH` in a path)
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Path under which `Keypair::derive_sub_account` numbers sub-accounts
+pub const SUB_ACCOUNT_PATH_PREFIX: &str = "m/44'/0'";
+
+/// HMAC key for the SLIP-0010 Ed25519 master node
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+/// Errors from deriving a child keypair
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DerivationError {
+    #[error("Invalid derivation path: {0}")]
+    InvalidPath(String),
+
+    #[error("Non-hardened index {0}: Ed25519 only supports hardened derivation")]
+    NonHardened(u32),
+
+    #[error("Index out of range: {0}")]
+    IndexOutOfRange(String),
+
+    #[error("Invalid seed length: {0} bytes, expected 16 to 64")]
+    InvalidSeedLength(usize),
+}
+
+/// Path of sub-account `index`, e.g. `m/44'/0'/3'` for index 3
+pub fn sub_account_path(index: u32) -> String {
+    format!("{}/{}'", SUB_ACCOUNT_PATH_PREFIX, index)
+}
+
+/// Parse a path like `m/44'/0'/1'` into hardened indexes
+///
+/// Every component must be hardened, marked with `'`, `h` or `H`. `m` alone is the
+/// master key and yields no indexes.
+pub fn parse_derivation_path(path: &str) -> Result<Vec<u32>, DerivationError> {
+    let mut components = path.trim().split('/');
+    if components.next() != Some("m") {
+        return Err(DerivationError::InvalidPath(format!("'{}' must start with m", path)));
+    }
+
+    components
+        .map(|component| {
+            let (digits, hardened) = match component.strip_suffix(['\'', 'h', 'H']) {
+                Some(digits) => (digits, true),
+                None => (component, false),
+            };
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(DerivationError::InvalidPath(format!(
+                    "'{}' is not an index in '{}'",
+                    component, path
+                )));
+            }
+            let index: u32 = digits
+                .parse()
+                .ok()
+                .filter(|&index| index < HARDENED_OFFSET)
+                .ok_or_else(|| DerivationError::IndexOutOfRange(component.to_string()))?;
+            if !hardened {
+                return Err(DerivationError::NonHardened(index));
+            }
+            Ok(index + HARDENED_OFFSET)
+        })
+        .collect()
+}
+
+impl Keypair {
+    /// Derive the keypair at `path` from this keypair, using its secret key as the
+    /// SLIP-0010 seed
+    ///
+    /// The same secret and path give the same keypair on every platform, so one
+    /// mnemonic backs up every child. `m` returns the SLIP-0010 master key, not `self`.
+    pub fn derive_child(&self, path: &str) -> Result<Keypair, DerivationError> {
+        Self::derive_from_seed(&self.to_bytes(), path)
+    }
+
+    /// Derive sub-account `index` (savings, spending, ...) at [`sub_account_path`]
+    ///
+    /// Each index gives its own keypair and DID. Derivation is hardened, so a
+    /// sub-account's key reveals nothing about this one or its siblings. Indexes
+    /// must be below `HARDENED_OFFSET`.
+    pub fn derive_sub_account(&self, index: u32) -> Result<Keypair, DerivationError> {
+        self.derive_child(&sub_account_path(index))
+    }
+
+    /// Derive the keypair at `path` from a 16 to 64 byte SLIP-0010 seed
+    pub fn derive_from_seed(seed: &[u8], path: &str) -> Result<Keypair, DerivationError> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(DerivationError::InvalidSeedLength(seed.len()));
+        }
+        let indexes = parse_derivation_path(path)?;
+
+        let (mut key, mut chain_code) = hmac_split(ED25519_SEED_KEY, &[seed]);
+        for index in indexes {
+            (key, chain_code) = hmac_split(&chain_code[..], &[&[0u8], &key[..], &index.to_be_bytes()]);
+        }
+
+        Ok(Keypair::from_bytes(&key[..]).expect("SLIP-0010 keys are 32 bytes"))
+    }
+}
+
+/// HMAC-SHA512 over `parts`, split into (key, chain code) halves
+fn hmac_split(key: &[u8], parts: &[&[u8]]) -> (Zeroizing<[u8; 32]>, Zeroizing<[u8; 32]>) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    let mut output = Zeroizing::new([0u8; 64]);
+    output.copy_from_slice(&mac.finalize().into_bytes());
+
+    let mut left = Zeroizing::new([0u8; 32]);
+    let mut right = Zeroizing::new([0u8; 32]);
+    left.copy_from_slice(&output[..32]);
+    right.copy_from_slice(&output[32..]);
+    (left, right)
+}
@@ -6,6 +6,19 @@ use thiserror::Error;
 
 const DID_PREFIX: &str = "did:mesh:";
 
+/// Multicodec prefix for ed25519-pub (varint 0xed)
+pub(crate) const ED25519_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+/// Ed25519 public key length in bytes
+const KEY_LEN: usize = 32;
+
+/// Encode an Ed25519 public key as multibase (base58btc) multicodec
+pub(crate) fn encode_multibase(key: &[u8]) -> String {
+    let mut multicodec = ED25519_MULTICODEC.to_vec();
+    multicodec.extend_from_slice(key);
+    format!("z{}", bs58::encode(multicodec).into_string())
+}
+
 #[derive(Error, Debug)]
 pub enum DidError {
     #[error("Invalid DID format: {0}")]
@@ -19,9 +32,43 @@ pub enum DidError {
 
     #[error("Invalid public key: {0}")]
     InvalidPublicKey(#[from] KeypairError),
+
+    #[error("Invalid DID document: {0}")]
+    InvalidDocument(String),
+
+    #[error("Invalid DID: {0}")]
+    Parse(#[from] DidParseError),
+}
+
+/// Why a DID string was rejected by [`Did::parse`]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DidParseError {
+    #[error("Expected 'did:' prefix, got {0:?}")]
+    BadPrefix(String),
+
+    #[error("Unsupported DID method {0:?}, expected 'mesh'")]
+    UnsupportedMethod(String),
+
+    #[error("Bad key encoding: {0}")]
+    BadEncoding(String),
+
+    #[error("Wrong key length: expected 32 bytes, got {0}")]
+    WrongKeyLength(usize),
+}
+
+/// String form of a DID's key part
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DidFormat {
+    /// `did:mesh:<base58 key>`, the original form and the `Display` output
+    #[default]
+    Legacy,
+    /// `did:mesh:z<base58btc multicodec key>`, as in W3C did:key
+    Multibase,
 }
 
 /// Decentralized Identifier in the format: did:mesh:<base58_public_key>
+///
+/// Also parses `did:mesh:z<multibase>`; both forms of one key are equal.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Did {
     /// The base58-encoded public key
@@ -35,46 +82,50 @@ impl Did {
         Self { key_part }
     }
 
-    /// Parse a DID from a string
-    pub fn parse(s: &str) -> Result<Self, DidError> {
-        // Check empty
-        if s.is_empty() {
-            return Err(DidError::InvalidFormat("DID cannot be empty".into()));
+    /// Parse a DID in either [`DidFormat`]
+    ///
+    /// Strict: no surrounding whitespace, lowercase scheme and method, and the
+    /// key must decode to exactly 32 bytes.
+    pub fn parse(s: &str) -> Result<Self, DidParseError> {
+        let rest = s.strip_prefix("did:").ok_or_else(|| {
+            DidParseError::BadPrefix(s.chars().take(DID_PREFIX.len()).collect())
+        })?;
+
+        let (method, key_part) = rest.split_once(':').unwrap_or((rest, ""));
+        if method != "mesh" {
+            return Err(DidParseError::UnsupportedMethod(method.to_string()));
         }
-
-        // Split by ':'
-        let parts: Vec<&str> = s.split(':').collect();
-
-        if parts.len() != 3 {
-            return Err(DidError::InvalidFormat(
-                format!("Expected 3 parts separated by ':', got {}", parts.len())
-            ));
+        if key_part.is_empty() {
+            return Err(DidParseError::BadEncoding("key part is empty".into()));
         }
 
-        // Check scheme
-        if parts[0] != "did" {
-            return Err(DidError::InvalidFormat(
-                format!("Expected 'did' scheme, got '{}'", parts[0])
-            ));
-        }
+        let key = Self::decode_key(key_part)?;
+        Ok(Self {
+            key_part: bs58::encode(key).into_string(),
+        })
+    }
 
-        // Check method
-        if parts[1] != "mesh" {
-            return Err(DidError::InvalidMethod(parts[1].to_string()));
+    /// Decode a multibase key, falling back to the legacy raw base58 key
+    fn decode_key(key_part: &str) -> Result<Vec<u8>, DidParseError> {
+        let multicodec = key_part
+            .strip_prefix('z')
+            .and_then(|encoded| bs58::decode(encoded).into_vec().ok())
+            .filter(|bytes| bytes.starts_with(&ED25519_MULTICODEC));
+        if let Some(bytes) = &multicodec {
+            if bytes.len() == ED25519_MULTICODEC.len() + KEY_LEN {
+                return Ok(bytes[ED25519_MULTICODEC.len()..].to_vec());
+            }
         }
 
-        // Check key part is not empty
-        if parts[2].is_empty() {
-            return Err(DidError::InvalidFormat("Key part cannot be empty".into()));
+        // A legacy key may start with 'z' too, so only fail once neither form fits
+        match bs58::decode(key_part).into_vec() {
+            Ok(bytes) if bytes.len() == KEY_LEN => Ok(bytes),
+            _ if multicodec.is_some() => Err(DidParseError::WrongKeyLength(
+                multicodec.map_or(0, |bytes| bytes.len() - ED25519_MULTICODEC.len()),
+            )),
+            Ok(bytes) => Err(DidParseError::WrongKeyLength(bytes.len())),
+            Err(e) => Err(DidParseError::BadEncoding(e.to_string())),
         }
-
-        // Validate base58 encoding by attempting to decode
-        let key_part = parts[2].to_string();
-        bs58::decode(&key_part)
-            .into_vec()
-            .map_err(|e| DidError::InvalidBase58(e.to_string()))?;
-
-        Ok(Self { key_part })
     }
 
     /// Extract the public key from this DID
@@ -90,11 +141,22 @@ impl Did {
     pub fn key_part(&self) -> &str {
         &self.key_part
     }
+
+    /// Format the DID in the given form; `to_string` is [`DidFormat::Legacy`]
+    pub fn to_string_as(&self, format: DidFormat) -> String {
+        match format {
+            DidFormat::Legacy => format!("{}{}", DID_PREFIX, self.key_part),
+            DidFormat::Multibase => {
+                let key = bs58::decode(&self.key_part).into_vec().unwrap_or_default();
+                format!("{}{}", DID_PREFIX, encode_multibase(&key))
+            }
+        }
+    }
 }
 
 impl fmt::Display for Did {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}", DID_PREFIX, self.key_part)
+        f.write_str(&self.to_string_as(DidFormat::default()))
     }
 }
 
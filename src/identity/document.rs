@@ -0,0 +1,632 @@
+// DID Document - W3C-style resolvable description of a did:mesh identity
+
+use crate::identity::did::{encode_multibase, ED25519_MULTICODEC};
+use crate::identity::{Did, DidError, KeySigner, PublicKey, Signature, Signer};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// JSON-LD contexts for a DID document with Ed25519 2020 keys
+pub const DID_CONTEXT_V1: &str = "https://www.w3.org/ns/did/v1";
+pub const ED25519_2020_CONTEXT: &str = "https://w3id.org/security/suites/ed25519-2020/v1";
+
+/// Verification method type for Ed25519 public keys
+pub const ED25519_VERIFICATION_KEY_2020: &str = "Ed25519VerificationKey2020";
+
+/// Domain separator for signed DID documents
+const DID_DOCUMENT_DOMAIN: &[u8] = b"p2pmesh:did-document:v1";
+
+/// A public key entry in a DID document
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationMethod {
+    /// Fragment URL, e.g. did:mesh:xxx#key-1
+    pub id: String,
+    /// Key type (Ed25519VerificationKey2020)
+    #[serde(rename = "type")]
+    pub method_type: String,
+    /// DID that controls this key
+    pub controller: String,
+    /// Multibase (base58btc, 'z' prefix) encoded multicodec public key
+    #[serde(rename = "publicKeyMultibase")]
+    pub public_key_multibase: String,
+}
+
+impl VerificationMethod {
+    /// Decode the Ed25519 public key from the multibase value
+    pub fn public_key(&self) -> Result<PublicKey, DidError> {
+        if self.method_type != ED25519_VERIFICATION_KEY_2020 {
+            return Err(DidError::InvalidDocument(format!(
+                "unsupported verification method type '{}'",
+                self.method_type
+            )));
+        }
+
+        let encoded = self.public_key_multibase.strip_prefix('z').ok_or_else(|| {
+            DidError::InvalidDocument("publicKeyMultibase must be base58btc ('z')".into())
+        })?;
+        let bytes = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|e| DidError::InvalidBase58(e.to_string()))?;
+
+        let key = bytes.strip_prefix(&ED25519_MULTICODEC[..]).ok_or_else(|| {
+            DidError::InvalidDocument("publicKeyMultibase is not an ed25519-pub key".into())
+        })?;
+
+        PublicKey::from_bytes(key).map_err(DidError::InvalidPublicKey)
+    }
+}
+
+/// Where a node can be reached, e.g. `tcp://1.2.3.4:9000` or `lora://0x42@915MHz`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceEndpoint {
+    /// Fragment URL, e.g. did:mesh:xxx#service-1
+    pub id: String,
+    /// Service type, e.g. MeshTransport
+    #[serde(rename = "type")]
+    pub service_type: String,
+    /// Transport URL
+    #[serde(rename = "serviceEndpoint")]
+    pub endpoint: String,
+}
+
+impl ServiceEndpoint {
+    /// Socket address of a `tcp://ip:port` endpoint
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        self.endpoint.strip_prefix("tcp://")?.parse().ok()
+    }
+}
+
+/// DID document describing a did:mesh identity
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "verificationMethod")]
+    pub verification_method: Vec<VerificationMethod>,
+    pub authentication: Vec<String>,
+    #[serde(rename = "assertionMethod")]
+    pub assertion_method: Vec<String>,
+    #[serde(default)]
+    pub service: Vec<ServiceEndpoint>,
+}
+
+impl Did {
+    /// Build the DID document for this identity
+    pub fn to_document(&self) -> DidDocument {
+        let id = self.to_string();
+        let key_id = format!("{}#key-1", id);
+        let key = bs58::decode(self.key_part()).into_vec().unwrap_or_default();
+
+        DidDocument {
+            context: vec![DID_CONTEXT_V1.to_string(), ED25519_2020_CONTEXT.to_string()],
+            id: id.clone(),
+            verification_method: vec![VerificationMethod {
+                id: key_id.clone(),
+                method_type: ED25519_VERIFICATION_KEY_2020.to_string(),
+                controller: id,
+                public_key_multibase: encode_multibase(&key),
+            }],
+            authentication: vec![key_id.clone()],
+            assertion_method: vec![key_id],
+            service: Vec::new(),
+        }
+    }
+}
+
+impl DidDocument {
+    /// Parse the document's id as a DID
+    pub fn did(&self) -> Result<Did, DidError> {
+        Ok(Did::parse(&self.id)?)
+    }
+
+    /// Public key of the first authentication method
+    ///
+    /// Checks that the key belongs to this DID, so a document cannot claim a foreign key.
+    pub fn public_key(&self) -> Result<PublicKey, DidError> {
+        let auth_ref = self.authentication.first().ok_or_else(|| {
+            DidError::InvalidDocument("no authentication method".into())
+        })?;
+        let method = self
+            .verification_method
+            .iter()
+            .find(|m| &m.id == auth_ref)
+            .ok_or_else(|| {
+                DidError::InvalidDocument(format!("unknown authentication reference '{}'", auth_ref))
+            })?;
+
+        let public_key = method.public_key()?;
+        if Did::from_public_key(&public_key) != self.did()? {
+            return Err(DidError::InvalidDocument("key does not match document id".into()));
+        }
+        Ok(public_key)
+    }
+
+    /// Add another key controlled by this DID (as `#key-N`)
+    pub fn with_key(mut self, public_key: &PublicKey) -> Self {
+        let key_id = format!("{}#key-{}", self.id, self.verification_method.len() + 1);
+        self.verification_method.push(VerificationMethod {
+            id: key_id,
+            method_type: ED25519_VERIFICATION_KEY_2020.to_string(),
+            controller: self.id.clone(),
+            public_key_multibase: encode_multibase(public_key.as_bytes()),
+        });
+        self
+    }
+
+    /// Add a service endpoint (as `#service-N`)
+    pub fn with_service(mut self, service_type: impl Into<String>, endpoint: impl Into<String>) -> Self {
+        let service_id = format!("{}#service-{}", self.id, self.service.len() + 1);
+        self.service.push(ServiceEndpoint {
+            id: service_id,
+            service_type: service_type.into(),
+            endpoint: endpoint.into(),
+        });
+        self
+    }
+
+    /// All verification keys, the controlling key included
+    pub fn keys(&self) -> Result<Vec<PublicKey>, DidError> {
+        self.verification_method.iter().map(VerificationMethod::public_key).collect()
+    }
+
+    /// Sign the document with its controlling key
+    ///
+    /// `updated_at` orders versions of the document; `expires_at` (unix seconds) bounds its lifetime.
+    pub fn sign(
+        self,
+        signer: &dyn KeySigner,
+        updated_at: u64,
+        expires_at: Option<u64>,
+    ) -> Result<SignedDidDocument, DidError> {
+        if signer.public_key() != self.public_key()? {
+            return Err(DidError::InvalidDocument("signer does not control the document".into()));
+        }
+
+        let signing_bytes = SignedDidDocument::signing_bytes(&self, updated_at, expires_at);
+        let signature = signer.sign(&signing_bytes);
+        Ok(SignedDidDocument { document: self, updated_at, expires_at, signature })
+    }
+
+    /// Serialize to a JSON string
+    pub fn to_json(&self) -> String {
+        let methods: Vec<String> = self
+            .verification_method
+            .iter()
+            .map(|m| {
+                format!(
+                    "{{\"id\":{},\"type\":{},\"controller\":{},\"publicKeyMultibase\":{}}}",
+                    json::string(&m.id),
+                    json::string(&m.method_type),
+                    json::string(&m.controller),
+                    json::string(&m.public_key_multibase),
+                )
+            })
+            .collect();
+
+        // Only documents that advertise endpoints carry a service list
+        let service = if self.service.is_empty() {
+            String::new()
+        } else {
+            let entries: Vec<String> = self
+                .service
+                .iter()
+                .map(|s| {
+                    format!(
+                        "{{\"id\":{},\"type\":{},\"serviceEndpoint\":{}}}",
+                        json::string(&s.id),
+                        json::string(&s.service_type),
+                        json::string(&s.endpoint),
+                    )
+                })
+                .collect();
+            format!(",\"service\":[{}]", entries.join(","))
+        };
+
+        format!(
+            "{{\"@context\":{},\"id\":{},\"verificationMethod\":[{}],\"authentication\":{},\"assertionMethod\":{}{}}}",
+            json::string_array(&self.context),
+            json::string(&self.id),
+            methods.join(","),
+            json::string_array(&self.authentication),
+            json::string_array(&self.assertion_method),
+            service,
+        )
+    }
+
+    /// Parse from a JSON string
+    ///
+    /// Unknown fields are ignored; `assertionMethod` and `service` are optional.
+    pub fn from_json(s: &str) -> Result<Self, DidError> {
+        let value = json::parse(s).map_err(DidError::InvalidDocument)?;
+        let doc = value.as_object("document")?;
+
+        let context = match json::field(doc, "@context") {
+            Some(json::Value::String(s)) => vec![s.clone()],
+            Some(v) => v.as_string_array("@context")?,
+            None => Vec::new(),
+        };
+
+        let verification_method = json::required(doc, "verificationMethod")?
+            .as_array("verificationMethod")?
+            .iter()
+            .map(|m| {
+                let m = m.as_object("verificationMethod")?;
+                Ok(VerificationMethod {
+                    id: json::required(m, "id")?.as_str("id")?.to_string(),
+                    method_type: json::required(m, "type")?.as_str("type")?.to_string(),
+                    controller: json::required(m, "controller")?.as_str("controller")?.to_string(),
+                    public_key_multibase: json::required(m, "publicKeyMultibase")?
+                        .as_str("publicKeyMultibase")?
+                        .to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, DidError>>()?;
+
+        let assertion_method = match json::field(doc, "assertionMethod") {
+            Some(v) => v.as_string_array("assertionMethod")?,
+            None => Vec::new(),
+        };
+
+        let service = match json::field(doc, "service") {
+            Some(v) => v
+                .as_array("service")?
+                .iter()
+                .map(|s| {
+                    let s = s.as_object("service")?;
+                    Ok(ServiceEndpoint {
+                        id: json::required(s, "id")?.as_str("id")?.to_string(),
+                        service_type: json::required(s, "type")?.as_str("type")?.to_string(),
+                        endpoint: json::required(s, "serviceEndpoint")?
+                            .as_str("serviceEndpoint")?
+                            .to_string(),
+                    })
+                })
+                .collect::<Result<Vec<_>, DidError>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            context,
+            id: json::required(doc, "id")?.as_str("id")?.to_string(),
+            verification_method,
+            authentication: json::required(doc, "authentication")?.as_string_array("authentication")?,
+            assertion_method,
+            service,
+        })
+    }
+}
+
+/// DID document signed by its controlling key, for publishing to peers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedDidDocument {
+    document: DidDocument,
+    updated_at: u64,
+    expires_at: Option<u64>,
+    signature: Signature,
+}
+
+impl SignedDidDocument {
+    fn signing_bytes(document: &DidDocument, updated_at: u64, expires_at: Option<u64>) -> Vec<u8> {
+        let mut bytes = DID_DOCUMENT_DOMAIN.to_vec();
+        bytes.extend(postcard::to_allocvec(&(document, updated_at, expires_at)).unwrap_or_default());
+        bytes
+    }
+
+    /// Get the document
+    pub fn document(&self) -> &DidDocument {
+        &self.document
+    }
+
+    /// When this version was published (unix seconds); newer versions supersede older ones
+    pub fn updated_at(&self) -> u64 {
+        self.updated_at
+    }
+
+    /// When the document stops being valid (unix seconds), if ever
+    pub fn expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
+
+    /// Get the signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Parse the document's id as a DID
+    pub fn did(&self) -> Result<Did, DidError> {
+        self.document.did()
+    }
+
+    /// Check the signature against the document's controlling key
+    pub fn verify(&self) -> bool {
+        let Ok(public_key) = self.document.public_key() else {
+            return false;
+        };
+        let signing_bytes = Self::signing_bytes(&self.document, self.updated_at, self.expires_at);
+        Signer::verify(&public_key, &signing_bytes, &self.signature)
+    }
+
+    /// Check if the document has expired at the given time (unix seconds)
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// Serialize to bytes (postcard)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes (postcard)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DidError> {
+        postcard::from_bytes(bytes).map_err(|e| DidError::InvalidDocument(e.to_string()))
+    }
+}
+
+/// Minimal JSON support for DID documents (no serde_json dependency)
+mod json {
+    use crate::identity::DidError;
+
+    /// Parsed JSON value; scalars other than strings are not needed by DID documents
+    #[derive(Debug)]
+    pub enum Value {
+        Null,
+        Bool,
+        Number,
+        String(String),
+        Array(Vec<Value>),
+        Object(Vec<(String, Value)>),
+    }
+
+    impl Value {
+        pub fn as_object(&self, name: &str) -> Result<&[(String, Value)], DidError> {
+            match self {
+                Value::Object(fields) => Ok(fields),
+                _ => Err(type_error(name, "an object")),
+            }
+        }
+
+        pub fn as_array(&self, name: &str) -> Result<&[Value], DidError> {
+            match self {
+                Value::Array(items) => Ok(items),
+                _ => Err(type_error(name, "an array")),
+            }
+        }
+
+        pub fn as_str(&self, name: &str) -> Result<&str, DidError> {
+            match self {
+                Value::String(s) => Ok(s),
+                _ => Err(type_error(name, "a string")),
+            }
+        }
+
+        pub fn as_string_array(&self, name: &str) -> Result<Vec<String>, DidError> {
+            self.as_array(name)?
+                .iter()
+                .map(|v| v.as_str(name).map(str::to_string))
+                .collect()
+        }
+    }
+
+    fn type_error(name: &str, expected: &str) -> DidError {
+        DidError::InvalidDocument(format!("'{}' must be {}", name, expected))
+    }
+
+    pub fn field<'a>(fields: &'a [(String, Value)], name: &str) -> Option<&'a Value> {
+        fields.iter().find(|(k, _)| k == name).map(|(_, v)| v)
+    }
+
+    pub fn required<'a>(fields: &'a [(String, Value)], name: &str) -> Result<&'a Value, DidError> {
+        field(fields, name).ok_or_else(|| DidError::InvalidDocument(format!("missing '{}'", name)))
+    }
+
+    /// Encode a JSON string literal
+    pub fn string(s: &str) -> String {
+        let mut out = String::with_capacity(s.len() + 2);
+        out.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+        out
+    }
+
+    /// Encode an array of JSON strings
+    pub fn string_array(items: &[String]) -> String {
+        let items: Vec<String> = items.iter().map(|s| string(s)).collect();
+        format!("[{}]", items.join(","))
+    }
+
+    /// Parse a complete JSON document
+    pub fn parse(input: &str) -> Result<Value, String> {
+        let mut parser = Parser { bytes: input.as_bytes(), pos: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_ws();
+        if parser.pos != parser.bytes.len() {
+            return Err(format!("trailing characters at offset {}", parser.pos));
+        }
+        Ok(value)
+    }
+
+    /// Nesting limit to keep recursion bounded on hostile input
+    const MAX_DEPTH: usize = 32;
+
+    struct Parser<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+        depth: usize,
+    }
+
+    impl Parser<'_> {
+        fn skip_ws(&mut self) {
+            while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.pos) {
+                self.pos += 1;
+            }
+        }
+
+        fn expect(&mut self, byte: u8) -> Result<(), String> {
+            self.skip_ws();
+            if self.bytes.get(self.pos) == Some(&byte) {
+                self.pos += 1;
+                Ok(())
+            } else {
+                Err(format!("expected '{}' at offset {}", byte as char, self.pos))
+            }
+        }
+
+        fn literal(&mut self, word: &str, value: Value) -> Result<Value, String> {
+            if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+                self.pos += word.len();
+                Ok(value)
+            } else {
+                Err(format!("invalid literal at offset {}", self.pos))
+            }
+        }
+
+        fn value(&mut self) -> Result<Value, String> {
+            self.skip_ws();
+            match self.bytes.get(self.pos) {
+                Some(b'{') => self.nested(Self::object),
+                Some(b'[') => self.nested(Self::array),
+                Some(b'"') => Ok(Value::String(self.string()?)),
+                Some(b't') => self.literal("true", Value::Bool),
+                Some(b'f') => self.literal("false", Value::Bool),
+                Some(b'n') => self.literal("null", Value::Null),
+                Some(b'-' | b'0'..=b'9') => Ok(self.number()),
+                Some(_) => Err(format!("unexpected character at offset {}", self.pos)),
+                None => Err("unexpected end of input".into()),
+            }
+        }
+
+        fn nested(&mut self, f: fn(&mut Self) -> Result<Value, String>) -> Result<Value, String> {
+            self.depth += 1;
+            if self.depth > MAX_DEPTH {
+                return Err("nesting too deep".into());
+            }
+            let value = f(self);
+            self.depth -= 1;
+            value
+        }
+
+        fn object(&mut self) -> Result<Value, String> {
+            self.expect(b'{')?;
+            let mut fields = Vec::new();
+            self.skip_ws();
+            if self.bytes.get(self.pos) == Some(&b'}') {
+                self.pos += 1;
+                return Ok(Value::Object(fields));
+            }
+            loop {
+                self.skip_ws();
+                let key = self.string()?;
+                self.expect(b':')?;
+                fields.push((key, self.value()?));
+                self.skip_ws();
+                match self.bytes.get(self.pos) {
+                    Some(b',') => self.pos += 1,
+                    Some(b'}') => {
+                        self.pos += 1;
+                        return Ok(Value::Object(fields));
+                    }
+                    _ => return Err(format!("expected ',' or '}}' at offset {}", self.pos)),
+                }
+            }
+        }
+
+        fn array(&mut self) -> Result<Value, String> {
+            self.expect(b'[')?;
+            let mut items = Vec::new();
+            self.skip_ws();
+            if self.bytes.get(self.pos) == Some(&b']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            loop {
+                items.push(self.value()?);
+                self.skip_ws();
+                match self.bytes.get(self.pos) {
+                    Some(b',') => self.pos += 1,
+                    Some(b']') => {
+                        self.pos += 1;
+                        return Ok(Value::Array(items));
+                    }
+                    _ => return Err(format!("expected ',' or ']' at offset {}", self.pos)),
+                }
+            }
+        }
+
+        fn number(&mut self) -> Value {
+            while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.bytes.get(self.pos) {
+                self.pos += 1;
+            }
+            Value::Number
+        }
+
+        fn string(&mut self) -> Result<String, String> {
+            if self.bytes.get(self.pos) != Some(&b'"') {
+                return Err(format!("expected string at offset {}", self.pos));
+            }
+            self.pos += 1;
+
+            let mut out = String::new();
+            loop {
+                let start = self.pos;
+                while let Some(&b) = self.bytes.get(self.pos) {
+                    if b == b'"' || b == b'\\' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                out.push_str(
+                    std::str::from_utf8(&self.bytes[start..self.pos])
+                        .map_err(|_| "invalid UTF-8 in string".to_string())?,
+                );
+
+                match self.bytes.get(self.pos) {
+                    Some(b'"') => {
+                        self.pos += 1;
+                        return Ok(out);
+                    }
+                    Some(b'\\') => {
+                        self.pos += 1;
+                        out.push(self.escape()?);
+                    }
+                    _ => return Err("unterminated string".into()),
+                }
+            }
+        }
+
+        fn escape(&mut self) -> Result<char, String> {
+            let c = match self.bytes.get(self.pos) {
+                Some(b'"') => '"',
+                Some(b'\\') => '\\',
+                Some(b'/') => '/',
+                Some(b'b') => '\u{08}',
+                Some(b'f') => '\u{0c}',
+                Some(b'n') => '\n',
+                Some(b'r') => '\r',
+                Some(b't') => '\t',
+                Some(b'u') => {
+                    let hex = self
+                        .bytes
+                        .get(self.pos + 1..self.pos + 5)
+                        .and_then(|h| std::str::from_utf8(h).ok())
+                        .ok_or("truncated unicode escape")?;
+                    let code = u32::from_str_radix(hex, 16).map_err(|_| "invalid unicode escape")?;
+                    self.pos += 4;
+                    char::from_u32(code).ok_or("unsupported unicode escape")?
+                }
+                _ => return Err(format!("invalid escape at offset {}", self.pos)),
+            };
+            self.pos += 1;
+            Ok(c)
+        }
+    }
+}
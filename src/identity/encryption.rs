@@ -0,0 +1,219 @@
+// Sealed envelopes - public-key encryption to a peer's Ed25519 identity
+//
+// Layout:
+// [version:1][ephemeral_public:32][nonce:24][ciphertext+tag]
+//
+// Both identity keys are converted to X25519. The sender generates a fresh
+// ephemeral key per envelope, so envelopes are unlinkable and the sender needs
+// no long-term secret. The symmetric key is HKDF-SHA256 over the shared secret,
+// salted with both public keys; the payload is encrypted with XChaCha20-Poly1305
+// using the version byte and ephemeral key as associated data.
+
+use crate::identity::{Keypair, PublicKey};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+/// Current envelope format version
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// XChaCha20 nonce length in bytes
+pub const ENVELOPE_NONCE_LEN: usize = 24;
+
+/// Header length in bytes
+const HEADER_LEN: usize = 1 + 32 + ENVELOPE_NONCE_LEN;
+
+/// Poly1305 tag length in bytes
+const TAG_LEN: usize = 16;
+
+/// HKDF info string binding derived keys to this scheme
+const KDF_INFO: &[u8] = b"p2pmesh:encryption:v1";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionError {
+    #[error("Unsupported envelope version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Invalid envelope format: {0}")]
+    InvalidFormat(String),
+
+    #[error("Key cannot be used for key agreement")]
+    InvalidKey,
+
+    #[error("Decryption failed (wrong recipient or tampered envelope)")]
+    DecryptionFailed,
+}
+
+/// Payload encrypted to a single recipient's public key
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedEnvelope {
+    version: u8,
+    ephemeral_public: [u8; 32],
+    nonce: [u8; ENVELOPE_NONCE_LEN],
+    ciphertext: Vec<u8>,
+}
+
+impl SealedEnvelope {
+    /// Envelope format version
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Sender's ephemeral X25519 public key
+    pub fn ephemeral_public(&self) -> &[u8; 32] {
+        &self.ephemeral_public
+    }
+
+    /// XChaCha20 nonce
+    pub fn nonce(&self) -> &[u8; ENVELOPE_NONCE_LEN] {
+        &self.nonce
+    }
+
+    /// Encrypted payload including the authentication tag
+    pub fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    /// Serialize to the wire layout
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.ciphertext.len());
+        bytes.push(self.version);
+        bytes.extend_from_slice(&self.ephemeral_public);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    /// Parse the wire layout, rejecting unknown versions and truncated input
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EncryptionError> {
+        let version = *bytes
+            .first()
+            .ok_or_else(|| EncryptionError::InvalidFormat("empty envelope".to_string()))?;
+        if version != ENVELOPE_VERSION {
+            return Err(EncryptionError::UnsupportedVersion(version));
+        }
+        if bytes.len() < HEADER_LEN + TAG_LEN {
+            return Err(EncryptionError::InvalidFormat("truncated envelope".to_string()));
+        }
+
+        Ok(Self {
+            version,
+            ephemeral_public: bytes[1..33].try_into().unwrap(),
+            nonce: bytes[33..HEADER_LEN].try_into().unwrap(),
+            ciphertext: bytes[HEADER_LEN..].to_vec(),
+        })
+    }
+
+    fn associated_data(&self) -> [u8; 33] {
+        let mut aad = [0u8; 33];
+        aad[0] = self.version;
+        aad[1..].copy_from_slice(&self.ephemeral_public);
+        aad
+    }
+}
+
+/// X25519 shared secret between our keypair and a peer's public key
+///
+/// Symmetric: both sides derive the same value. Fails with `InvalidKey` for
+/// low-order peer keys, which would give a predictable secret.
+pub fn derive_shared_secret(
+    keypair: &Keypair,
+    peer: &PublicKey,
+) -> Result<Zeroizing<[u8; 32]>, EncryptionError> {
+    let secret = StaticSecret::from(keypair.signing_key().to_scalar_bytes());
+    let peer = X25519PublicKey::from(peer.inner().to_montgomery().to_bytes());
+    agree(&secret, &peer)
+}
+
+/// Encrypt `plaintext` so only the holder of `recipient`'s secret key can read it
+pub fn encrypt_for(recipient: &PublicKey, plaintext: &[u8]) -> Result<SealedEnvelope, EncryptionError> {
+    use rand::RngCore;
+    let mut ephemeral = Zeroizing::new([0u8; 32]);
+    let mut nonce = [0u8; ENVELOPE_NONCE_LEN];
+    rand::thread_rng().fill_bytes(ephemeral.as_mut());
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    encrypt_for_with(recipient, plaintext, &ephemeral, &nonce)
+}
+
+/// Encrypt with an explicit ephemeral secret and nonce.
+/// Only for reproducible test vectors; never reuse an ephemeral key in practice.
+pub fn encrypt_for_with(
+    recipient: &PublicKey,
+    plaintext: &[u8],
+    ephemeral_secret: &[u8; 32],
+    nonce: &[u8; ENVELOPE_NONCE_LEN],
+) -> Result<SealedEnvelope, EncryptionError> {
+    let ephemeral = StaticSecret::from(*ephemeral_secret);
+    let ephemeral_public = X25519PublicKey::from(&ephemeral);
+    let recipient_x = X25519PublicKey::from(recipient.inner().to_montgomery().to_bytes());
+    let shared = agree(&ephemeral, &recipient_x)?;
+
+    let mut envelope = SealedEnvelope {
+        version: ENVELOPE_VERSION,
+        ephemeral_public: ephemeral_public.to_bytes(),
+        nonce: *nonce,
+        ciphertext: Vec::new(),
+    };
+
+    let cipher = derive_cipher(&shared, &envelope.ephemeral_public, recipient_x.as_bytes());
+    envelope.ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(nonce),
+            Payload { msg: plaintext, aad: &envelope.associated_data() },
+        )
+        .map_err(|_| EncryptionError::InvalidFormat("encryption failed".to_string()))?;
+    Ok(envelope)
+}
+
+/// Decrypt an envelope addressed to `keypair`
+///
+/// Any envelope not encrypted to this keypair, or modified in transit,
+/// returns `EncryptionError::DecryptionFailed`.
+pub fn decrypt(keypair: &Keypair, envelope: &SealedEnvelope) -> Result<Vec<u8>, EncryptionError> {
+    if envelope.version != ENVELOPE_VERSION {
+        return Err(EncryptionError::UnsupportedVersion(envelope.version));
+    }
+    if envelope.ciphertext.len() < TAG_LEN {
+        return Err(EncryptionError::InvalidFormat("truncated ciphertext".to_string()));
+    }
+
+    let secret = StaticSecret::from(keypair.signing_key().to_scalar_bytes());
+    let our_public = X25519PublicKey::from(&secret);
+    let ephemeral = X25519PublicKey::from(envelope.ephemeral_public);
+    // A low-order ephemeral key can only come from a forged envelope
+    let shared = agree(&secret, &ephemeral).map_err(|_| EncryptionError::DecryptionFailed)?;
+
+    let cipher = derive_cipher(&shared, &envelope.ephemeral_public, our_public.as_bytes());
+    cipher
+        .decrypt(
+            XNonce::from_slice(&envelope.nonce),
+            Payload { msg: &envelope.ciphertext, aad: &envelope.associated_data() },
+        )
+        .map_err(|_| EncryptionError::DecryptionFailed)
+}
+
+fn agree(secret: &StaticSecret, peer: &X25519PublicKey) -> Result<Zeroizing<[u8; 32]>, EncryptionError> {
+    let shared = secret.diffie_hellman(peer);
+    if !shared.was_contributory() {
+        return Err(EncryptionError::InvalidKey);
+    }
+    Ok(Zeroizing::new(shared.to_bytes()))
+}
+
+fn derive_cipher(shared: &[u8; 32], ephemeral_public: &[u8; 32], recipient_public: &[u8; 32]) -> XChaCha20Poly1305 {
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public);
+    salt[32..].copy_from_slice(recipient_public);
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(KDF_INFO, key.as_mut())
+        .expect("32-byte output is a valid HKDF length");
+    XChaCha20Poly1305::new_from_slice(key.as_ref()).expect("32-byte key")
+}
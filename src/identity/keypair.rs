@@ -1,7 +1,9 @@
+use bip39::{Language, Mnemonic};
 use ed25519_dalek::{SigningKey, VerifyingKey};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
 
 #[derive(Error, Debug)]
 pub enum KeypairError {
@@ -10,8 +12,14 @@ pub enum KeypairError {
 
     #[error("Invalid key bytes: {0}")]
     InvalidBytes(String),
+
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
 }
 
+/// Number of words in a keypair backup phrase (256 bits of entropy)
+pub const MNEMONIC_WORD_COUNT: usize = 24;
+
 /// Ed25519 public key (32 bytes)
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PublicKey(VerifyingKey);
@@ -111,14 +119,17 @@ impl PublicKey {
     }
 }
 
-/// Ed25519 secret key (32 bytes)
+/// Ed25519 secret key (32 bytes), zeroed on drop
 #[derive(Clone)]
 pub struct SecretKey(SigningKey);
 
+// SigningKey wipes its secret bytes on drop
+impl ZeroizeOnDrop for SecretKey {}
+
 impl SecretKey {
-    /// Get the raw bytes of the secret key
-    pub fn to_bytes(&self) -> [u8; 32] {
-        self.0.to_bytes()
+    /// Get the raw bytes of the secret key (zeroed when the returned buffer is dropped)
+    pub fn to_bytes(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.0.to_bytes())
     }
 
     /// Create a secret key from raw bytes
@@ -130,9 +141,9 @@ impl SecretKey {
             });
         }
 
-        let bytes_array: [u8; 32] = bytes.try_into().map_err(|_| {
+        let bytes_array: Zeroizing<[u8; 32]> = Zeroizing::new(bytes.try_into().map_err(|_| {
             KeypairError::InvalidBytes("Failed to convert to array".into())
-        })?;
+        })?);
 
         let signing_key = SigningKey::from_bytes(&bytes_array);
         Ok(Self(signing_key))
@@ -144,12 +155,15 @@ impl SecretKey {
     }
 }
 
-/// Ed25519 keypair containing both public and secret keys
+/// Ed25519 keypair containing both public and secret keys, zeroed on drop
 #[derive(Clone)]
 pub struct Keypair {
     signing_key: SigningKey,
 }
 
+// SigningKey wipes its secret bytes on drop
+impl ZeroizeOnDrop for Keypair {}
+
 impl Keypair {
     /// Generate a new random keypair
     pub fn generate() -> Self {
@@ -167,9 +181,9 @@ impl Keypair {
         SecretKey(self.signing_key.clone())
     }
 
-    /// Serialize the keypair to bytes (secret key bytes)
-    pub fn to_bytes(&self) -> Vec<u8> {
-        self.signing_key.to_bytes().to_vec()
+    /// Serialize the keypair to bytes (secret key bytes, zeroed when the returned buffer is dropped)
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.signing_key.to_bytes().to_vec())
     }
 
     /// Deserialize a keypair from bytes
@@ -181,14 +195,47 @@ impl Keypair {
             });
         }
 
-        let bytes_array: [u8; 32] = bytes.try_into().map_err(|_| {
+        let bytes_array: Zeroizing<[u8; 32]> = Zeroizing::new(bytes.try_into().map_err(|_| {
             KeypairError::InvalidBytes("Failed to convert to array".into())
-        })?;
+        })?);
 
         let signing_key = SigningKey::from_bytes(&bytes_array);
         Ok(Self { signing_key })
     }
 
+    /// Encode the secret key as a 24-word BIP39 (English) backup phrase
+    pub fn to_mnemonic(&self) -> String {
+        let entropy = Zeroizing::new(self.signing_key.to_bytes());
+        Mnemonic::from_entropy_in(Language::English, entropy.as_ref())
+            .expect("32 bytes is valid BIP39 entropy")
+            .to_string()
+    }
+
+    /// Restore a keypair from a 24-word BIP39 (English) backup phrase
+    ///
+    /// Words may be separated by any whitespace and are matched case-insensitively.
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, KeypairError> {
+        let normalized = phrase
+            .split_whitespace()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let word_count = normalized.split(' ').filter(|w| !w.is_empty()).count();
+        if word_count != MNEMONIC_WORD_COUNT {
+            return Err(KeypairError::InvalidMnemonic(format!(
+                "expected {} words, got {}",
+                MNEMONIC_WORD_COUNT, word_count
+            )));
+        }
+
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, &normalized)
+            .map_err(|e| KeypairError::InvalidMnemonic(e.to_string()))?;
+
+        let entropy = Zeroizing::new(mnemonic.to_entropy());
+        Self::from_bytes(&entropy)
+    }
+
     /// Create a keypair from an existing secret key
     pub fn from_secret_key(secret: SecretKey) -> Self {
         Self {
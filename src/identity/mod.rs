@@ -3,8 +3,14 @@
 
 mod keypair;
 mod did;
+mod document;
 mod signer;
+mod derivation;
+mod encryption;
 
 pub use keypair::*;
 pub use did::*;
+pub use document::*;
 pub use signer::*;
+pub use derivation::*;
+pub use encryption::*;
@@ -113,6 +113,38 @@ impl Signer {
     pub fn verify(public_key: &PublicKey, message: &[u8], signature: &Signature) -> bool {
         public_key.inner().verify(message, signature.inner()).is_ok()
     }
+
+    /// Verify many signatures in a single batch, the i-th message signed by the i-th key
+    ///
+    /// Faster than verifying each signature, but only says whether all of them are
+    /// valid. False if the slices differ in length.
+    pub fn verify_batch(messages: &[&[u8]], signatures: &[Signature], public_keys: &[PublicKey]) -> bool {
+        let signatures: Vec<DalekSignature> = signatures.iter().map(|sig| *sig.inner()).collect();
+        let keys: Vec<_> = public_keys.iter().map(|key| *key.inner()).collect();
+        ed25519_dalek::verify_batch(messages, &signatures, &keys).is_ok()
+    }
+}
+
+/// Anything that can sign with an Ed25519 key, in memory or not
+///
+/// Implemented by [`Keypair`]; implement it to keep the secret key outside the process,
+/// e.g. in a platform keystore. Signatures still verify with [`Signer::verify`].
+pub trait KeySigner {
+    /// Public key the signatures verify against
+    fn public_key(&self) -> PublicKey;
+
+    /// Sign a message
+    fn sign(&self, message: &[u8]) -> Signature;
+}
+
+impl KeySigner for Keypair {
+    fn public_key(&self) -> PublicKey {
+        Keypair::public_key(self)
+    }
+
+    fn sign(&self, message: &[u8]) -> Signature {
+        Signer::sign(self, message)
+    }
 }
 
 #[cfg(test)]
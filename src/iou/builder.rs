@@ -1,5 +1,8 @@
-use crate::identity::{Did, Keypair, Signer};
-use crate::iou::{IOU, SignedIOU};
+use crate::identity::{Did, KeySigner, Keypair};
+use crate::iou::{
+    Denomination, Hashlock, IOUOutput, NonceError, NonceManager, PaymentRequest, PaymentRequestId,
+    SignedIOU, IOU, MAX_MEMO_BYTES,
+};
 use rand::Rng;
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
@@ -10,6 +13,9 @@ pub enum IOUError {
     #[error("Missing sender: sender keypair is required")]
     MissingSender,
 
+    #[error("Missing requester: requester keypair is required")]
+    MissingRequester,
+
     #[error("Missing recipient: recipient DID is required")]
     MissingRecipient,
 
@@ -19,17 +25,42 @@ pub enum IOUError {
     #[error("Invalid amount: {0}")]
     InvalidAmount(String),
 
+    #[error("Amount too large: {amount} exceeds {max}")]
+    AmountTooLarge { amount: u64, max: u64 },
+
     #[error("Self-payment not allowed: sender and recipient cannot be the same")]
     SelfPayment,
+
+    #[error("Invalid expiry: {0}")]
+    InvalidExpiry(String),
+
+    #[error("Memo too long: {len} bytes exceeds {max}")]
+    MemoTooLong { len: usize, max: usize },
+
+    #[error("Invalid outputs: {0}")]
+    InvalidOutputs(String),
+
+    #[error("Nonce allocation failed: {0}")]
+    Nonce(#[from] NonceError),
 }
 
 /// Builder for creating signed IOUs
 pub struct IOUBuilder<'a> {
-    sender: Option<&'a Keypair>,
+    sender: Option<&'a dyn KeySigner>,
     recipient: Option<Did>,
     amount: Option<u64>,
     nonce: Option<u64>,
+    nonce_source: Option<&'a mut NonceManager>,
     timestamp: Option<u64>,
+    expiry: Option<u64>,
+    ttl_secs: Option<u64>,
+    memo: Option<String>,
+    request_id: Option<PaymentRequestId>,
+    outputs: Vec<IOUOutput>,
+    denomination: Option<Denomination>,
+    hashlock: Option<Hashlock>,
+    allow_self_payment: bool,
+    max_amount: Option<u64>,
 }
 
 impl<'a> IOUBuilder<'a> {
@@ -40,13 +71,28 @@ impl<'a> IOUBuilder<'a> {
             recipient: None,
             amount: None,
             nonce: None,
+            nonce_source: None,
             timestamp: None,
+            expiry: None,
+            ttl_secs: None,
+            memo: None,
+            request_id: None,
+            outputs: Vec::new(),
+            denomination: None,
+            hashlock: None,
+            allow_self_payment: false,
+            max_amount: None,
         }
     }
 
     /// Set the sender (required)
-    pub fn sender(mut self, keypair: &'a Keypair) -> Self {
-        self.sender = Some(keypair);
+    pub fn sender(self, keypair: &'a Keypair) -> Self {
+        self.sender_signer(keypair)
+    }
+
+    /// Set the sender to a signer whose key may live outside the process (required)
+    pub fn sender_signer(mut self, signer: &'a dyn KeySigner) -> Self {
+        self.sender = Some(signer);
         self
     }
 
@@ -62,9 +108,28 @@ impl<'a> IOUBuilder<'a> {
         self
     }
 
+    /// Reject amounts above `max` (optional - any `u64` if not set)
+    ///
+    /// For multi-output IOUs the limit applies to the total. Use
+    /// [`MAX_SAFE_AMOUNT`](crate::iou::MAX_SAFE_AMOUNT) where amounts feed settlement net positions.
+    pub fn max_amount(mut self, max: u64) -> Self {
+        self.max_amount = Some(max);
+        self
+    }
+
     /// Set the nonce (optional - auto-generated if not provided)
     pub fn nonce(mut self, nonce: u64) -> Self {
         self.nonce = Some(nonce);
+        self.nonce_source = None;
+        self
+    }
+
+    /// Take the nonce from `manager` (replaces `nonce`)
+    ///
+    /// The nonce is allocated in `build`, only once every other check has passed.
+    pub fn nonce_from(mut self, manager: &'a mut NonceManager) -> Self {
+        self.nonce_source = Some(manager);
+        self.nonce = None;
         self
     }
 
@@ -74,30 +139,118 @@ impl<'a> IOUBuilder<'a> {
         self
     }
 
+    /// Set an absolute expiry timestamp in Unix seconds (optional)
+    pub fn expiry(mut self, expiry: u64) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Set a time-to-live relative to the IOU timestamp (optional)
+    ///
+    /// Ignored if an absolute expiry is also set.
+    pub fn ttl_secs(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = Some(ttl_secs);
+        self
+    }
+
+    /// Same as [`IOUBuilder::expiry`]
+    pub fn expires_at(self, expiry: u64) -> Self {
+        self.expiry(expiry)
+    }
+
+    /// Same as [`IOUBuilder::ttl_secs`]
+    pub fn expires_in_secs(self, secs: u64) -> Self {
+        self.ttl_secs(secs)
+    }
+
+    /// Attach a memo such as an order number (optional, at most `MAX_MEMO_BYTES` bytes)
+    ///
+    /// The memo is signed, so it can't be altered in transit.
+    pub fn memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Pay `amount` to `did` as one output of a multi-output IOU
+    ///
+    /// Use instead of `recipient`; the IOU amount becomes the sum of all outputs.
+    /// A single output builds an ordinary single-recipient IOU.
+    pub fn add_output(mut self, did: Did, amount: u64) -> Self {
+        self.outputs.push(IOUOutput::new(did, amount));
+        self
+    }
+
+    /// Mark the IOU as paying the payment request with this ID (optional)
+    pub fn request_id(mut self, request_id: PaymentRequestId) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    /// Set the unit the amount is counted in, e.g. USD with 2 decimal places (optional)
+    ///
+    /// Without one the amount is bare credits.
+    pub fn denomination(mut self, denomination: Denomination) -> Self {
+        self.denomination = Some(denomination);
+        self
+    }
+
+    /// Hold the payment until the preimage of `hashlock` is revealed (optional)
+    ///
+    /// Requires an expiry (or TTL): once it passes, the sender can reclaim the
+    /// IOU if the recipient never revealed the preimage.
+    pub fn hashlock(mut self, hashlock: Hashlock) -> Self {
+        self.hashlock = Some(hashlock);
+        self
+    }
+
+    /// Pay a payment request: sets recipient, amount and request ID, and
+    /// carries the request's memo unless one was already set
+    pub fn for_request(mut self, request: &PaymentRequest) -> Self {
+        if self.memo.is_none() {
+            self.memo = request.memo().map(str::to_string);
+        }
+        self.recipient(request.recipient().clone())
+            .amount(request.amount())
+            .request_id(request.id())
+    }
+
+    /// Permit sender and recipient to be the same DID (optional)
+    ///
+    /// Used for vault-internal transfers such as UTXO consolidation.
+    pub fn allow_self_payment(mut self) -> Self {
+        self.allow_self_payment = true;
+        self
+    }
+
     /// Build and sign the IOU
     pub fn build(self) -> Result<SignedIOU, IOUError> {
         // Validate required fields
-        let sender_keypair = self.sender.ok_or(IOUError::MissingSender)?;
-        let recipient = self.recipient.ok_or(IOUError::MissingRecipient)?;
-        let amount = self.amount.ok_or(IOUError::MissingAmount)?;
+        let sender = self.sender.ok_or(IOUError::MissingSender)?;
+        let (recipient, amount, outputs) = if self.outputs.is_empty() {
+            let recipient = self.recipient.ok_or(IOUError::MissingRecipient)?;
+            let amount = self.amount.ok_or(IOUError::MissingAmount)?;
+            (recipient, amount, Vec::new())
+        } else {
+            Self::resolve_outputs(self.outputs, self.recipient, self.amount)?
+        };
 
         // Validate amount is not zero
         if amount == 0 {
             return Err(IOUError::InvalidAmount("amount cannot be zero".to_string()));
         }
+        if let Some(max) = self.max_amount.filter(|max| amount > *max) {
+            return Err(IOUError::AmountTooLarge { amount, max });
+        }
 
-        // Derive sender DID from keypair
-        let sender_did = Did::from_public_key(&sender_keypair.public_key());
+        // Derive sender DID from the signer's key
+        let sender_did = Did::from_public_key(&sender.public_key());
 
         // Check for self-payment
-        if sender_did == recipient {
+        let pays_sender = sender_did == recipient || outputs.iter().any(|o| o.recipient() == &sender_did);
+        if pays_sender && !self.allow_self_payment {
             return Err(IOUError::SelfPayment);
         }
 
-        // Generate nonce if not provided
-        let nonce = self.nonce.unwrap_or_else(|| {
-            rand::thread_rng().gen::<u64>()
-        });
 
         // Generate timestamp if not provided
         let timestamp = self.timestamp.unwrap_or_else(|| {
@@ -107,15 +260,152 @@ impl<'a> IOUBuilder<'a> {
                 .as_secs()
         });
 
+        // Resolve expiry (absolute expiry takes precedence over TTL)
+        let expiry = match (self.expiry, self.ttl_secs) {
+            (Some(expiry), _) => Some(expiry),
+            (None, Some(ttl)) => Some(timestamp.checked_add(ttl).ok_or_else(|| {
+                IOUError::InvalidExpiry("ttl overflows timestamp".to_string())
+            })?),
+            (None, None) => None,
+        };
+
+        if let Some(expiry) = expiry {
+            if expiry <= timestamp {
+                return Err(IOUError::InvalidExpiry(
+                    "expiry must be after the IOU timestamp".to_string(),
+                ));
+            }
+        }
+        if self.hashlock.is_some() && expiry.is_none() {
+            return Err(IOUError::InvalidExpiry(
+                "a hashlocked IOU needs an expiry to time out".to_string(),
+            ));
+        }
+
+        if let Some(memo) = &self.memo {
+            if memo.len() > MAX_MEMO_BYTES {
+                return Err(IOUError::MemoTooLong { len: memo.len(), max: MAX_MEMO_BYTES });
+            }
+        }
+
+        // Allocate the nonce last so a rejected IOU doesn't consume one
+        let nonce = match (self.nonce, self.nonce_source) {
+            (Some(nonce), _) => nonce,
+            (None, Some(manager)) => manager.next(&sender_did)?,
+            (None, None) => rand::thread_rng().gen::<u64>(),
+        };
+
         // Create the IOU
-        let iou = IOU::new(sender_did, recipient, amount, nonce, timestamp);
+        let mut iou = IOU::new(sender_did, recipient, amount, nonce, timestamp);
+        if let Some(expiry) = expiry {
+            iou = iou.with_expiry(expiry);
+        }
+        if let Some(memo) = self.memo {
+            iou = iou.with_memo(memo);
+        }
+        if let Some(request_id) = self.request_id {
+            iou = iou.with_request_id(request_id);
+        }
+        if !outputs.is_empty() {
+            iou = iou.with_outputs(outputs);
+        }
+        if let Some(denomination) = self.denomination {
+            iou = iou.with_denomination(denomination);
+        }
+        if let Some(hashlock) = self.hashlock {
+            iou = iou.with_hashlock(hashlock);
+        }
 
         // Sign it
         let signing_bytes = iou.to_signing_bytes();
-        let signature = Signer::sign(sender_keypair, &signing_bytes);
+        let signature = sender.sign(&signing_bytes);
 
         Ok(SignedIOU::from_parts(iou, signature))
     }
+
+    /// Build and sign one IOU per `(recipient, amount)` item, with nonces counting up from `nonce_start`
+    ///
+    /// Every item is checked before anything is signed, so a bad item fails the whole
+    /// batch. All IOUs share one timestamp.
+    pub fn build_batch(
+        items: &[(Did, u64)],
+        keypair: &Keypair,
+        nonce_start: u64,
+    ) -> Result<Vec<SignedIOU>, IOUError> {
+        let sender_did = Did::from_public_key(&keypair.public_key());
+        for (recipient, amount) in items {
+            if *amount == 0 {
+                return Err(IOUError::InvalidAmount("amount cannot be zero".to_string()));
+            }
+            if *recipient == sender_did {
+                return Err(IOUError::SelfPayment);
+            }
+        }
+        if nonce_start.checked_add(items.len().saturating_sub(1) as u64).is_none() {
+            return Err(NonceError::Exhausted(sender_did).into());
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, (recipient, amount))| {
+                IOUBuilder::new()
+                    .sender(keypair)
+                    .recipient(recipient.clone())
+                    .amount(*amount)
+                    .nonce(nonce_start + i as u64)
+                    .timestamp(timestamp)
+                    .build()
+            })
+            .collect()
+    }
+
+    /// Check outputs added with `add_output` and derive the IOU's recipient and total.
+    /// A lone output collapses to the single-recipient form.
+    fn resolve_outputs(
+        outputs: Vec<IOUOutput>,
+        recipient: Option<Did>,
+        amount: Option<u64>,
+    ) -> Result<(Did, u64, Vec<IOUOutput>), IOUError> {
+        if recipient.is_some() {
+            return Err(IOUError::InvalidOutputs(
+                "use either recipient or add_output, not both".to_string(),
+            ));
+        }
+
+        let mut total = 0u64;
+        for (i, output) in outputs.iter().enumerate() {
+            if output.amount() == 0 {
+                return Err(IOUError::InvalidAmount("output amount cannot be zero".to_string()));
+            }
+            if outputs[..i].iter().any(|earlier| earlier.recipient() == output.recipient()) {
+                return Err(IOUError::InvalidOutputs(format!(
+                    "duplicate recipient {}",
+                    output.recipient()
+                )));
+            }
+            total = total
+                .checked_add(output.amount())
+                .ok_or_else(|| IOUError::InvalidAmount("outputs overflow total".to_string()))?;
+        }
+
+        if amount.is_some_and(|amount| amount != total) {
+            return Err(IOUError::InvalidAmount(
+                "amount must equal the sum of outputs".to_string(),
+            ));
+        }
+
+        let first = outputs[0].recipient().clone();
+        if outputs.len() == 1 {
+            return Ok((first, total, Vec::new()));
+        }
+        Ok((first, total, outputs))
+    }
 }
 
 impl<'a> Default for IOUBuilder<'a> {
@@ -0,0 +1,121 @@
+// IOU cancellation - sender-signed withdrawal of an IOU the recipient hasn't accepted
+
+use crate::identity::{Did, Keypair, PublicKey, Signature, Signer};
+use crate::iou::{IOUId, SignedIOU};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Domain separator so a cancellation signature can never be replayed as another message
+const CANCELLATION_DOMAIN: &[u8] = b"p2pmesh:cancellation:v1";
+
+/// An unsigned cancellation of a specific IOU
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cancellation {
+    iou_id: IOUId,
+    sender: Did,
+    timestamp: u64,
+}
+
+impl Cancellation {
+    /// Create a new cancellation
+    pub fn new(iou_id: IOUId, sender: Did, timestamp: u64) -> Self {
+        Self {
+            iou_id,
+            sender,
+            timestamp,
+        }
+    }
+
+    /// ID of the cancelled IOU
+    pub fn iou_id(&self) -> &IOUId {
+        &self.iou_id
+    }
+
+    /// The IOU sender issuing the cancellation
+    pub fn sender(&self) -> &Did {
+        &self.sender
+    }
+
+    /// When the cancellation was issued (Unix seconds)
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Get the bytes that should be signed
+    pub fn to_signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(CANCELLATION_DOMAIN);
+        bytes.extend_from_slice(self.iou_id.as_bytes());
+
+        let sender_str = self.sender.to_string();
+        bytes.extend_from_slice(&(sender_str.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(sender_str.as_bytes());
+
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes
+    }
+}
+
+/// A cancellation signed by the IOU's sender
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedCancellation {
+    cancellation: Cancellation,
+    signature: Signature,
+}
+
+impl SignedCancellation {
+    /// Cancel an IOU as its sender, timestamped now
+    pub fn sign(keypair: &Keypair, iou_id: IOUId) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let sender = Did::from_public_key(&keypair.public_key());
+        let cancellation = Cancellation::new(iou_id, sender, timestamp);
+        let signature = Signer::sign(keypair, &cancellation.to_signing_bytes());
+        Self { cancellation, signature }
+    }
+
+    /// Create a SignedCancellation from parts
+    pub fn from_parts(cancellation: Cancellation, signature: Signature) -> Self {
+        Self { cancellation, signature }
+    }
+
+    /// Get the underlying cancellation
+    pub fn cancellation(&self) -> &Cancellation {
+        &self.cancellation
+    }
+
+    /// Get the signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Verify the signature against a public key
+    pub fn verify(&self, public_key: &PublicKey) -> bool {
+        Signer::verify(public_key, &self.cancellation.to_signing_bytes(), &self.signature)
+    }
+
+    /// Verify the signature against the sender DID's own key
+    pub fn verify_sender(&self) -> bool {
+        match self.cancellation.sender.public_key() {
+            Ok(public_key) => self.verify(&public_key),
+            Err(_) => false,
+        }
+    }
+
+    /// Whether this cancels `iou`: same ID, issued by its sender
+    pub fn cancels(&self, iou: &SignedIOU) -> bool {
+        &iou.id() == self.cancellation.iou_id() && iou.iou().sender() == self.cancellation.sender()
+    }
+
+    /// Serialize to bytes (postcard)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes (postcard)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
@@ -0,0 +1,246 @@
+// Canonical IOU encoding - the byte form IOU IDs and signatures are computed over
+// Hand-written rather than derived so it stays byte-identical across serde/postcard versions
+//
+// IOU (`IOU::to_canonical_bytes`), all integers little-endian:
+//   [sender_len:4][sender DID][recipient_len:4][recipient DID]
+//   [amount:8][nonce:8][timestamp:8]
+//   then each optional field that is set, in tag order:
+//   [0x01][expiry:8]
+//   [0x02][memo_len:4][memo UTF-8]
+//   [0x03][request_id:32]
+//   [0x04][count:4] count x ([recipient_len:4][recipient DID][amount:8])
+//   [0x05][code_len:4][code ASCII][exponent:1]
+//   [0x06][hashlock:32]
+//
+// Signed IOU (`SignedIOU::to_canonical_bytes`):
+//   [version:1][iou_len:4][IOU canonical bytes][signature:64]
+
+use crate::identity::{Did, Signature};
+use crate::iou::{CodecError, Denomination, Hashlock, IOUOutput, PaymentRequestId, SignedIOU, IOU};
+
+/// Current canonical `SignedIOU` encoding version
+pub const CANONICAL_VERSION: u8 = 1;
+
+const TAG_EXPIRY: u8 = 0x01;
+const TAG_MEMO: u8 = 0x02;
+const TAG_REQUEST_ID: u8 = 0x03;
+const TAG_OUTPUTS: u8 = 0x04;
+const TAG_DENOMINATION: u8 = 0x05;
+const TAG_HASHLOCK: u8 = 0x06;
+const SIGNATURE_LEN: usize = 64;
+
+impl IOU {
+    /// Canonical encoding of this IOU, the bytes its ID and signature cover
+    ///
+    /// Fails only if a field is too long for its 4-byte length prefix.
+    pub fn to_canonical_bytes(&self) -> Result<Vec<u8>, CodecError> {
+        let mut bytes = Vec::new();
+        write_str(&mut bytes, "sender", &self.sender().to_string())?;
+        write_str(&mut bytes, "recipient", &self.recipient().to_string())?;
+        bytes.extend_from_slice(&self.amount().to_le_bytes());
+        bytes.extend_from_slice(&self.nonce().to_le_bytes());
+        bytes.extend_from_slice(&self.timestamp().to_le_bytes());
+
+        // Optional fields are only written when set, so IOUs without them keep their IDs
+        if let Some(expiry) = self.expiry() {
+            bytes.push(TAG_EXPIRY);
+            bytes.extend_from_slice(&expiry.to_le_bytes());
+        }
+        if let Some(memo) = self.memo() {
+            bytes.push(TAG_MEMO);
+            write_str(&mut bytes, "memo", memo)?;
+        }
+        if let Some(request_id) = self.request_id() {
+            bytes.push(TAG_REQUEST_ID);
+            bytes.extend_from_slice(request_id.as_bytes());
+        }
+        if !self.outputs().is_empty() {
+            bytes.push(TAG_OUTPUTS);
+            write_len(&mut bytes, "outputs", self.outputs().len())?;
+            for output in self.outputs() {
+                write_str(&mut bytes, "output recipient", &output.recipient().to_string())?;
+                bytes.extend_from_slice(&output.amount().to_le_bytes());
+            }
+        }
+        if let Some(denomination) = self.denomination() {
+            bytes.push(TAG_DENOMINATION);
+            write_str(&mut bytes, "denomination", denomination.code())?;
+            bytes.push(denomination.exponent());
+        }
+        if let Some(hashlock) = self.hashlock() {
+            bytes.push(TAG_HASHLOCK);
+            bytes.extend_from_slice(hashlock.as_bytes());
+        }
+
+        Ok(bytes)
+    }
+
+    /// Decode bytes produced by [`IOU::to_canonical_bytes`]
+    ///
+    /// Only the canonical form is accepted, so re-encoding gives back the same bytes.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let iou = reader.iou()?;
+        reader.finish()?;
+        Ok(iou)
+    }
+}
+
+impl SignedIOU {
+    /// Canonical encoding of this signed IOU: a version byte, the length-prefixed
+    /// canonical IOU and the signature
+    ///
+    /// Unlike [`IOUCodec::encode`](crate::iou::IOUCodec::encode), which follows postcard,
+    /// this layout is fixed and documented in this module.
+    pub fn to_canonical_bytes(&self) -> Result<Vec<u8>, CodecError> {
+        let iou = self.iou().to_canonical_bytes()?;
+
+        let mut bytes = Vec::with_capacity(1 + 4 + iou.len() + SIGNATURE_LEN);
+        bytes.push(CANONICAL_VERSION);
+        write_len(&mut bytes, "IOU", iou.len())?;
+        bytes.extend_from_slice(&iou);
+        bytes.extend_from_slice(self.signature().as_bytes());
+        Ok(bytes)
+    }
+
+    /// Decode bytes produced by [`SignedIOU::to_canonical_bytes`]
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let version = reader.byte()?;
+        if version != CANONICAL_VERSION {
+            return Err(CodecError::UnsupportedVersion(version.to_string()));
+        }
+        let iou_len = reader.len()?;
+        let iou = IOU::from_canonical_bytes(reader.take(iou_len)?)?;
+        let signature = Signature::from_bytes(reader.take(SIGNATURE_LEN)?)
+            .map_err(|e| CodecError::DecodeError(e.to_string()))?;
+        reader.finish()?;
+
+        Ok(SignedIOU::from_parts(iou, signature))
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, field: &str, len: usize) -> Result<(), CodecError> {
+    let len = u32::try_from(len)
+        .map_err(|_| CodecError::EncodeError(format!("{} too long: {} exceeds u32", field, len)))?;
+    out.extend_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn write_str(out: &mut Vec<u8>, field: &str, s: &str) -> Result<(), CodecError> {
+    write_len(out, field, s.len())?;
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn non_canonical(reason: &str) -> CodecError {
+    CodecError::DecodeError(format!("non-canonical IOU: {}", reason))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], CodecError> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len());
+        let end = end.ok_or_else(|| CodecError::DecodeError("canonical IOU is truncated".to_string()))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, CodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, CodecError> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn len(&mut self) -> Result<usize, CodecError> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buf) as usize)
+    }
+
+    fn string(&mut self) -> Result<String, CodecError> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|e| CodecError::DecodeError(e.to_string()))
+    }
+
+    /// A DID, which must be written exactly as `Did`'s `Display` writes it
+    fn did(&mut self) -> Result<Did, CodecError> {
+        let s = self.string()?;
+        let did = Did::parse(&s).map_err(|e| CodecError::DecodeError(e.to_string()))?;
+        if did.to_string() != s {
+            return Err(non_canonical("DID not in display form"));
+        }
+        Ok(did)
+    }
+
+    fn iou(&mut self) -> Result<IOU, CodecError> {
+        let sender = self.did()?;
+        let recipient = self.did()?;
+        let amount = self.u64()?;
+        let nonce = self.u64()?;
+        let timestamp = self.u64()?;
+        let mut iou = IOU::new(sender, recipient, amount, nonce, timestamp);
+
+        // Each optional field at most once, in increasing tag order
+        let mut last_tag = 0;
+        while self.pos < self.bytes.len() {
+            let tag = self.byte()?;
+            if tag <= last_tag {
+                return Err(non_canonical("optional fields out of order"));
+            }
+            last_tag = tag;
+
+            iou = match tag {
+                TAG_EXPIRY => iou.with_expiry(self.u64()?),
+                TAG_MEMO => iou.with_memo(self.string()?),
+                TAG_REQUEST_ID => {
+                    let mut id = [0u8; 32];
+                    id.copy_from_slice(self.take(32)?);
+                    iou.with_request_id(PaymentRequestId::from_bytes(id))
+                }
+                TAG_OUTPUTS => {
+                    let count = self.len()?;
+                    if count == 0 {
+                        return Err(non_canonical("empty outputs are written as absent"));
+                    }
+                    let mut outputs = Vec::new();
+                    for _ in 0..count {
+                        outputs.push(IOUOutput::new(self.did()?, self.u64()?));
+                    }
+                    iou.with_outputs(outputs)
+                }
+                TAG_DENOMINATION => {
+                    let code = self.string()?;
+                    let denomination = Denomination::new(code, self.byte()?)
+                        .map_err(|e| CodecError::DecodeError(e.to_string()))?;
+                    iou.with_denomination(denomination)
+                }
+                TAG_HASHLOCK => {
+                    let mut hash = [0u8; 32];
+                    hash.copy_from_slice(self.take(32)?);
+                    iou.with_hashlock(Hashlock::from_bytes(hash))
+                }
+                _ => return Err(CodecError::DecodeError(format!("unknown IOU field tag {}", tag))),
+            };
+        }
+
+        Ok(iou)
+    }
+
+    fn finish(&self) -> Result<(), CodecError> {
+        if self.pos != self.bytes.len() {
+            return Err(CodecError::DecodeError("trailing bytes in canonical IOU".to_string()));
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,21 @@
-use crate::iou::SignedIOU;
+use crate::identity::{Did, Signature};
+use crate::iou::json::{JsonObject, JsonValue};
+use crate::iou::{
+    CompactIOU, Denomination, Frame, Hashlock, IOUOutput, KeyHash, PaymentRequestId, SignedIOU, IOU,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+/// Prefix identifying a p2pmesh QR payload
+pub const QR_PREFIX: &str = "p2pm:";
+
+/// Current QR payload format version
+pub const QR_VERSION: u8 = 1;
+
+/// Number of SHA-256 bytes appended to QR payloads as a checksum
+const QR_CHECKSUM_LEN: usize = 4;
+
 /// Errors that can occur during encoding/decoding
 #[derive(Error, Debug)]
 pub enum CodecError {
@@ -15,11 +30,53 @@ pub enum CodecError {
 
     #[error("Invalid base64 string: {0}")]
     InvalidBase64(String),
+
+    #[error("Invalid QR string: {0}")]
+    InvalidQr(String),
+
+    #[error("Unsupported QR version: {0}")]
+    UnsupportedVersion(String),
+
+    #[error("QR checksum mismatch")]
+    ChecksumMismatch,
+
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("Invalid compact frame: {0}")]
+    InvalidFrame(String),
+
+    #[error("No known DID for key hash {0}")]
+    UnresolvedDid(KeyHash),
 }
 
 /// Codec for serializing/deserializing IOUs
 pub struct IOUCodec;
 
+/// `SignedIOU` layout from before IOUs carried an optional expiry
+#[derive(Deserialize)]
+struct LegacySignedIOU {
+    iou: LegacyIOU,
+    signature: Signature,
+}
+
+#[derive(Deserialize)]
+struct LegacyIOU {
+    sender: Did,
+    recipient: Did,
+    amount: u64,
+    nonce: u64,
+    timestamp: u64,
+}
+
+impl From<LegacySignedIOU> for SignedIOU {
+    fn from(legacy: LegacySignedIOU) -> Self {
+        let LegacyIOU { sender, recipient, amount, nonce, timestamp } = legacy.iou;
+        // Without an expiry the signing bytes, and so the ID and signature, are unchanged
+        SignedIOU::from_parts(IOU::new(sender, recipient, amount, nonce, timestamp), legacy.signature)
+    }
+}
+
 impl IOUCodec {
     /// Encode a SignedIOU to binary bytes (using postcard for compact serialization)
     pub fn encode(signed_iou: &SignedIOU) -> Vec<u8> {
@@ -27,8 +84,21 @@ impl IOUCodec {
     }
 
     /// Decode a SignedIOU from binary bytes
+    ///
+    /// Each field after the signature was added by a later release, so an encoding
+    /// that stops before one of them is read with it and every later field unset.
+    /// IOUs encoded before the expiry field existed are also accepted.
     pub fn decode(bytes: &[u8]) -> Result<SignedIOU, CodecError> {
-        postcard::from_bytes(bytes)
+        let ((iou, signature), rest) = match postcard::take_from_bytes::<(IOU, Signature)>(bytes) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                return postcard::from_bytes::<LegacySignedIOU>(bytes)
+                    .map(SignedIOU::from)
+                    .map_err(|_| CodecError::DecodeError(e.to_string()))
+            }
+        };
+        decode_trailing(iou, rest)
+            .map(|iou| SignedIOU::from_parts(iou, signature))
             .map_err(|e| CodecError::DecodeError(e.to_string()))
     }
 
@@ -57,4 +127,198 @@ impl IOUCodec {
             .map_err(|e| CodecError::InvalidBase64(e.to_string()))?;
         Self::decode(&bytes)
     }
+
+    /// Encode to a QR-friendly string: `p2pm:<version>:<base64url(bytes || checksum)>`
+    ///
+    /// The checksum is the first 4 bytes of SHA-256 over the encoded IOU.
+    pub fn encode_qr(signed_iou: &SignedIOU) -> String {
+        use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+        let mut payload = Self::encode(signed_iou);
+        let checksum = Sha256::digest(&payload);
+        payload.extend_from_slice(&checksum[..QR_CHECKSUM_LEN]);
+        format!("{}{}:{}", QR_PREFIX, QR_VERSION, URL_SAFE_NO_PAD.encode(payload))
+    }
+
+    /// Decode a string produced by `encode_qr`, validating prefix, version and checksum
+    pub fn decode_qr(qr: &str) -> Result<SignedIOU, CodecError> {
+        use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+        let rest = qr.trim().strip_prefix(QR_PREFIX)
+            .ok_or_else(|| CodecError::InvalidQr(format!("missing '{}' prefix", QR_PREFIX)))?;
+        let (version, data) = rest.split_once(':')
+            .ok_or_else(|| CodecError::InvalidQr("missing version separator".to_string()))?;
+
+        if version != QR_VERSION.to_string() {
+            return Err(CodecError::UnsupportedVersion(version.to_string()));
+        }
+
+        let bytes = URL_SAFE_NO_PAD.decode(data)
+            .map_err(|e| CodecError::InvalidBase64(e.to_string()))?;
+        if bytes.len() <= QR_CHECKSUM_LEN {
+            return Err(CodecError::InvalidQr("payload too short".to_string()));
+        }
+
+        let (payload, checksum) = bytes.split_at(bytes.len() - QR_CHECKSUM_LEN);
+        if Sha256::digest(payload)[..QR_CHECKSUM_LEN] != *checksum {
+            return Err(CodecError::ChecksumMismatch);
+        }
+
+        Self::decode(payload)
+    }
+
+    /// Encode to LoRa-sized frames (see [`CompactIOU`])
+    ///
+    /// The receiver needs every DID the IOU names in its `DidResolver`.
+    pub fn encode_compact(signed_iou: &SignedIOU) -> Result<Vec<Frame>, CodecError> {
+        let id = signed_iou.id();
+        let message_id = u16::from_be_bytes([id.as_bytes()[0], id.as_bytes()[1]]);
+        CompactIOU::from_signed(signed_iou).to_frames(message_id)
+    }
+
+    /// Encode to canonical JSON, for clients that can't read postcard
+    ///
+    /// Keys appear in this order with no whitespace, optional keys only when set:
+    /// `sender`, `recipient`, `amount`, `nonce`, `timestamp`, `expiry`, `memo`,
+    /// `request_id`, `outputs` (each `{"recipient","amount"}`), `denomination`
+    /// (`{"code","exponent"}`), `hashlock`, `signature`.
+    /// DIDs are strings, integers are decimal strings (so JavaScript keeps full
+    /// u64 precision) and byte fields are unpadded base64url. Every signed field
+    /// is carried, so the signature still verifies after a JSON round trip.
+    pub fn to_json(signed_iou: &SignedIOU) -> String {
+        let iou = signed_iou.iou();
+        let mut json = JsonObject::new()
+            .string("sender", &iou.sender().to_string())
+            .string("recipient", &iou.recipient().to_string())
+            .u64("amount", iou.amount())
+            .u64("nonce", iou.nonce())
+            .u64("timestamp", iou.timestamp());
+        if let Some(expiry) = iou.expiry() {
+            json = json.u64("expiry", expiry);
+        }
+        if let Some(memo) = iou.memo() {
+            json = json.string("memo", memo);
+        }
+        if let Some(request_id) = iou.request_id() {
+            json = json.bytes("request_id", request_id.as_bytes());
+        }
+        if iou.is_multi_output() {
+            json = json.array(
+                "outputs",
+                iou.outputs().iter().map(|output| {
+                    JsonObject::new()
+                        .string("recipient", &output.recipient().to_string())
+                        .u64("amount", output.amount())
+                        .finish()
+                }),
+            );
+        }
+        if let Some(denomination) = iou.denomination() {
+            json = json.object(
+                "denomination",
+                JsonObject::new()
+                    .string("code", denomination.code())
+                    .u64("exponent", u64::from(denomination.exponent()))
+                    .finish(),
+            );
+        }
+        if let Some(hashlock) = iou.hashlock() {
+            json = json.bytes("hashlock", hashlock.as_bytes());
+        }
+        json.bytes("signature", signed_iou.signature().as_bytes()).finish()
+    }
+
+    /// Decode JSON produced by `to_json` (or by another client following the same layout)
+    ///
+    /// Key order and whitespace don't matter when reading; unknown keys are ignored.
+    pub fn from_json(json: &str) -> Result<SignedIOU, CodecError> {
+        let value = JsonValue::parse(json).map_err(CodecError::InvalidJson)?;
+        Self::signed_iou_from_json(&value).map_err(CodecError::InvalidJson)
+    }
+
+    fn signed_iou_from_json(value: &JsonValue) -> Result<SignedIOU, String> {
+        let did = |s: &str| Did::parse(s).map_err(|e| e.to_string());
+
+        let mut iou = IOU::new(
+            did(value.str_field("sender")?)?,
+            did(value.str_field("recipient")?)?,
+            value.u64_field("amount")?,
+            value.u64_field("nonce")?,
+            value.u64_field("timestamp")?,
+        );
+        if let Some(expiry) = value.opt_u64_field("expiry")? {
+            iou = iou.with_expiry(expiry);
+        }
+        if let Some(memo) = value.opt_str_field("memo")? {
+            iou = iou.with_memo(memo.to_string());
+        }
+        if value.get("request_id").is_some() {
+            let bytes: [u8; 32] = value
+                .bytes_field("request_id")?
+                .try_into()
+                .map_err(|_| "'request_id' must be 32 bytes".to_string())?;
+            iou = iou.with_request_id(PaymentRequestId::from_bytes(bytes));
+        }
+        let outputs = value
+            .array_field("outputs")?
+            .iter()
+            .map(|output| {
+                Ok(IOUOutput::new(
+                    did(output.str_field("recipient")?)?,
+                    output.u64_field("amount")?,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if !outputs.is_empty() {
+            iou = iou.with_outputs(outputs);
+        }
+        if let Some(denomination) = value.get("denomination").filter(|v| **v != JsonValue::Null) {
+            let exponent = u8::try_from(denomination.u64_field("exponent")?)
+                .map_err(|_| "'exponent' is not a valid u8".to_string())?;
+            let denomination = Denomination::new(denomination.str_field("code")?, exponent)
+                .map_err(|e| e.to_string())?;
+            iou = iou.with_denomination(denomination);
+        }
+        if value.get("hashlock").is_some() {
+            let bytes: [u8; 32] = value
+                .bytes_field("hashlock")?
+                .try_into()
+                .map_err(|_| "'hashlock' must be 32 bytes".to_string())?;
+            iou = iou.with_hashlock(Hashlock::from_bytes(bytes));
+        }
+
+        let signature =
+            Signature::from_bytes(&value.bytes_field("signature")?).map_err(|e| e.to_string())?;
+        Ok(SignedIOU::from_parts(iou, signature))
+    }
+}
+
+/// Read the memo, request ID, outputs, denomination and hashlock that trail a
+/// signed encoding, in that order, leaving unset any the encoding ends before
+pub(crate) fn decode_trailing(mut iou: IOU, mut rest: &[u8]) -> Result<IOU, postcard::Error> {
+    if let Some(memo) = take_trailing::<Option<String>>(&mut rest)?.flatten() {
+        iou = iou.with_memo(memo);
+    }
+    if let Some(request_id) = take_trailing::<Option<PaymentRequestId>>(&mut rest)?.flatten() {
+        iou = iou.with_request_id(request_id);
+    }
+    if let Some(outputs) = take_trailing::<Vec<IOUOutput>>(&mut rest)? {
+        iou = iou.with_outputs(outputs);
+    }
+    if let Some(denomination) = take_trailing::<Option<Denomination>>(&mut rest)?.flatten() {
+        iou = iou.with_denomination(denomination);
+    }
+    if let Some(hashlock) = take_trailing::<Option<Hashlock>>(&mut rest)?.flatten() {
+        iou = iou.with_hashlock(hashlock);
+    }
+    Ok(iou)
+}
+
+/// Decode the next trailing field, or `None` if the encoding ends before it
+fn take_trailing<'a, T: Deserialize<'a>>(rest: &mut &'a [u8]) -> Result<Option<T>, postcard::Error> {
+    if rest.is_empty() {
+        return Ok(None);
+    }
+    let (value, remaining) = postcard::take_from_bytes(rest)?;
+    *rest = remaining;
+    Ok(Some(value))
 }
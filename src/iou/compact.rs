@@ -0,0 +1,567 @@
+// Compact IOU encoding for low-bandwidth links such as LoRa
+// DIDs shrink to 8-byte hashes resolved against a local table, integers are varints,
+// and the result is split into frames small enough for an SF12 LoRa payload
+
+use crate::identity::{Did, Signature};
+use crate::iou::{CodecError, Denomination, Hashlock, IOUOutput, PaymentRequestId, SignedIOU, IOU};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// Largest frame produced by `encode_compact` (the SF12 LoRa payload limit)
+pub const MAX_COMPACT_FRAME_LEN: usize = 51;
+
+/// Bytes of each frame taken by the frame header
+pub const COMPACT_FRAME_HEADER_LEN: usize = 4;
+
+/// Current compact encoding version
+pub const COMPACT_VERSION: u8 = 1;
+
+/// Partially received IOUs a `CompactDecoder` keeps before dropping the oldest
+pub const MAX_PENDING_COMPACT_IOUS: usize = 16;
+
+const FRAME_PAYLOAD_LEN: usize = MAX_COMPACT_FRAME_LEN - COMPACT_FRAME_HEADER_LEN;
+const SIGNATURE_LEN: usize = 64;
+
+const FLAG_EXPIRY: u8 = 0x01;
+const FLAG_MEMO: u8 = 0x02;
+const FLAG_REQUEST_ID: u8 = 0x04;
+const FLAG_OUTPUTS: u8 = 0x08;
+const FLAG_DENOMINATION: u8 = 0x10;
+const FLAG_HASHLOCK: u8 = 0x20;
+
+// ============================================================================
+// KEY HASHES
+// ============================================================================
+
+/// First 8 bytes of SHA-256 over a DID string, standing in for the DID on the wire
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyHash([u8; 8]);
+
+impl KeyHash {
+    /// Hash a DID
+    pub fn of(did: &Did) -> Self {
+        let digest = Sha256::digest(did.to_string().as_bytes());
+        let mut hash = [0u8; 8];
+        hash.copy_from_slice(&digest[..8]);
+        Self(hash)
+    }
+
+    pub fn from_bytes(bytes: [u8; 8]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for KeyHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// Maps key hashes back to the DIDs they were taken from
+pub trait DidResolver {
+    /// The DID with this hash, if known
+    fn resolve(&self, hash: &KeyHash) -> Option<Did>;
+}
+
+/// In-memory [`DidResolver`] over a set of known DIDs (typically our peers and ourselves)
+#[derive(Clone, Debug, Default)]
+pub struct DidTable {
+    dids: HashMap<KeyHash, Did>,
+}
+
+impl DidTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `did` resolvable, returning its hash
+    pub fn insert(&mut self, did: Did) -> KeyHash {
+        let hash = KeyHash::of(&did);
+        self.dids.insert(hash, did);
+        hash
+    }
+
+    pub fn len(&self) -> usize {
+        self.dids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dids.is_empty()
+    }
+}
+
+impl DidResolver for DidTable {
+    fn resolve(&self, hash: &KeyHash) -> Option<Did> {
+        self.dids.get(hash).cloned()
+    }
+}
+
+// ============================================================================
+// COMPACT IOU
+// ============================================================================
+
+/// A signed IOU with its DIDs replaced by key hashes
+///
+/// Layout: version, flags, sender hash, recipient hash, varint amount, nonce and
+/// timestamp, then the optional expiry (varint), memo (varint length + UTF-8),
+/// request ID (32 bytes), outputs (varint count, then hash + varint amount each),
+/// denomination (varint code length + code, exponent byte) and hashlock (32 bytes) as
+/// set in the flags, and finally the 64-byte signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactIOU {
+    sender: KeyHash,
+    recipient: KeyHash,
+    amount: u64,
+    nonce: u64,
+    timestamp: u64,
+    expiry: Option<u64>,
+    memo: Option<String>,
+    request_id: Option<PaymentRequestId>,
+    outputs: Vec<(KeyHash, u64)>,
+    denomination: Option<Denomination>,
+    hashlock: Option<Hashlock>,
+    signature: [u8; SIGNATURE_LEN],
+}
+
+impl CompactIOU {
+    /// Compact a signed IOU
+    pub fn from_signed(signed_iou: &SignedIOU) -> Self {
+        let iou = signed_iou.iou();
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature.copy_from_slice(signed_iou.signature().as_bytes());
+
+        Self {
+            sender: KeyHash::of(iou.sender()),
+            recipient: KeyHash::of(iou.recipient()),
+            amount: iou.amount(),
+            nonce: iou.nonce(),
+            timestamp: iou.timestamp(),
+            expiry: iou.expiry(),
+            memo: iou.memo().map(str::to_string),
+            request_id: iou.request_id().cloned(),
+            outputs: iou
+                .outputs()
+                .iter()
+                .map(|output| (KeyHash::of(output.recipient()), output.amount()))
+                .collect(),
+            denomination: iou.denomination().cloned(),
+            hashlock: iou.hashlock().copied(),
+            signature,
+        }
+    }
+
+    /// Hash of the sender's DID
+    pub fn sender(&self) -> &KeyHash {
+        &self.sender
+    }
+
+    /// Hash of the recipient's DID
+    pub fn recipient(&self) -> &KeyHash {
+        &self.recipient
+    }
+
+    /// Restore the full signed IOU, looking DIDs up in `resolver`
+    ///
+    /// A hash that resolves to the wrong DID yields an IOU whose signature won't verify.
+    pub fn resolve(&self, resolver: &impl DidResolver) -> Result<SignedIOU, CodecError> {
+        let did = |hash: &KeyHash| resolver.resolve(hash).ok_or(CodecError::UnresolvedDid(*hash));
+
+        let mut iou = IOU::new(did(&self.sender)?, did(&self.recipient)?, self.amount, self.nonce, self.timestamp);
+        if let Some(expiry) = self.expiry {
+            iou = iou.with_expiry(expiry);
+        }
+        if let Some(memo) = &self.memo {
+            iou = iou.with_memo(memo.clone());
+        }
+        if let Some(request_id) = &self.request_id {
+            iou = iou.with_request_id(request_id.clone());
+        }
+        if !self.outputs.is_empty() {
+            let outputs = self
+                .outputs
+                .iter()
+                .map(|(hash, amount)| Ok(IOUOutput::new(did(hash)?, *amount)))
+                .collect::<Result<Vec<_>, CodecError>>()?;
+            iou = iou.with_outputs(outputs);
+        }
+        if let Some(denomination) = &self.denomination {
+            iou = iou.with_denomination(denomination.clone());
+        }
+        if let Some(hashlock) = self.hashlock {
+            iou = iou.with_hashlock(hashlock);
+        }
+
+        let signature = Signature::from_bytes(&self.signature)
+            .map_err(|e| CodecError::DecodeError(e.to_string()))?;
+        Ok(SignedIOU::from_parts(iou, signature))
+    }
+
+    /// Encode to bytes (before framing)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.expiry.is_some() {
+            flags |= FLAG_EXPIRY;
+        }
+        if self.memo.is_some() {
+            flags |= FLAG_MEMO;
+        }
+        if self.request_id.is_some() {
+            flags |= FLAG_REQUEST_ID;
+        }
+        if !self.outputs.is_empty() {
+            flags |= FLAG_OUTPUTS;
+        }
+        if self.denomination.is_some() {
+            flags |= FLAG_DENOMINATION;
+        }
+        if self.hashlock.is_some() {
+            flags |= FLAG_HASHLOCK;
+        }
+
+        let mut bytes = vec![COMPACT_VERSION, flags];
+        bytes.extend_from_slice(self.sender.as_bytes());
+        bytes.extend_from_slice(self.recipient.as_bytes());
+        write_varint(&mut bytes, self.amount);
+        write_varint(&mut bytes, self.nonce);
+        write_varint(&mut bytes, self.timestamp);
+        if let Some(expiry) = self.expiry {
+            write_varint(&mut bytes, expiry);
+        }
+        if let Some(memo) = &self.memo {
+            write_varint(&mut bytes, memo.len() as u64);
+            bytes.extend_from_slice(memo.as_bytes());
+        }
+        if let Some(request_id) = &self.request_id {
+            bytes.extend_from_slice(request_id.as_bytes());
+        }
+        if !self.outputs.is_empty() {
+            write_varint(&mut bytes, self.outputs.len() as u64);
+            for (hash, amount) in &self.outputs {
+                bytes.extend_from_slice(hash.as_bytes());
+                write_varint(&mut bytes, *amount);
+            }
+        }
+        if let Some(denomination) = &self.denomination {
+            write_varint(&mut bytes, denomination.code().len() as u64);
+            bytes.extend_from_slice(denomination.code().as_bytes());
+            bytes.push(denomination.exponent());
+        }
+        if let Some(hashlock) = &self.hashlock {
+            bytes.extend_from_slice(hashlock.as_bytes());
+        }
+        bytes.extend_from_slice(&self.signature);
+        bytes
+    }
+
+    /// Decode bytes produced by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let version = reader.byte()?;
+        if version != COMPACT_VERSION {
+            return Err(CodecError::UnsupportedVersion(version.to_string()));
+        }
+        let flags = reader.byte()?;
+
+        let sender = reader.key_hash()?;
+        let recipient = reader.key_hash()?;
+        let amount = reader.varint()?;
+        let nonce = reader.varint()?;
+        let timestamp = reader.varint()?;
+        let expiry = if flags & FLAG_EXPIRY != 0 { Some(reader.varint()?) } else { None };
+        let memo = if flags & FLAG_MEMO != 0 {
+            let len = usize::try_from(reader.varint()?).map_err(|_| truncated())?;
+            let memo = String::from_utf8(reader.take(len)?.to_vec())
+                .map_err(|e| CodecError::DecodeError(e.to_string()))?;
+            Some(memo)
+        } else {
+            None
+        };
+        let request_id = if flags & FLAG_REQUEST_ID != 0 {
+            let mut id = [0u8; 32];
+            id.copy_from_slice(reader.take(32)?);
+            Some(PaymentRequestId::from_bytes(id))
+        } else {
+            None
+        };
+        let mut outputs = Vec::new();
+        if flags & FLAG_OUTPUTS != 0 {
+            let count = reader.varint()?;
+            for _ in 0..count {
+                outputs.push((reader.key_hash()?, reader.varint()?));
+            }
+        }
+        let denomination = if flags & FLAG_DENOMINATION != 0 {
+            let len = usize::try_from(reader.varint()?).map_err(|_| truncated())?;
+            let code = String::from_utf8(reader.take(len)?.to_vec())
+                .map_err(|e| CodecError::DecodeError(e.to_string()))?;
+            let denomination = Denomination::new(code, reader.byte()?)
+                .map_err(|e| CodecError::DecodeError(e.to_string()))?;
+            Some(denomination)
+        } else {
+            None
+        };
+        let hashlock = if flags & FLAG_HASHLOCK != 0 {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(reader.take(32)?);
+            Some(Hashlock::from_bytes(hash))
+        } else {
+            None
+        };
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature.copy_from_slice(reader.take(SIGNATURE_LEN)?);
+
+        if reader.pos != bytes.len() {
+            return Err(CodecError::DecodeError("trailing bytes after signature".to_string()));
+        }
+
+        Ok(Self {
+            sender,
+            recipient,
+            amount,
+            nonce,
+            timestamp,
+            expiry,
+            memo,
+            request_id,
+            outputs,
+            denomination,
+            hashlock,
+            signature,
+        })
+    }
+
+    /// Split the encoding into frames of at most `MAX_COMPACT_FRAME_LEN` bytes
+    ///
+    /// Frames share a message ID taken from the IOU ID so a receiver can tell
+    /// interleaved IOUs apart.
+    pub fn to_frames(&self, message_id: u16) -> Result<Vec<Frame>, CodecError> {
+        let bytes = self.to_bytes();
+        let chunks: Vec<&[u8]> = bytes.chunks(FRAME_PAYLOAD_LEN).collect();
+        let total = u8::try_from(chunks.len())
+            .map_err(|_| CodecError::EncodeError(format!("needs {} frames, limit is 255", chunks.len())))?;
+
+        Ok(chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| Frame {
+                message_id,
+                index: index as u8,
+                total,
+                payload: chunk.to_vec(),
+            })
+            .collect())
+    }
+}
+
+fn truncated() -> CodecError {
+    CodecError::DecodeError("compact IOU is truncated".to_string())
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], CodecError> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len()).ok_or_else(truncated)?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, CodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn key_hash(&mut self) -> Result<KeyHash, CodecError> {
+        let mut hash = [0u8; 8];
+        hash.copy_from_slice(self.take(8)?);
+        Ok(KeyHash(hash))
+    }
+
+    fn varint(&mut self) -> Result<u64, CodecError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::DecodeError("varint too long".to_string()))
+    }
+}
+
+// ============================================================================
+// FRAMES
+// ============================================================================
+
+/// One radio-sized piece of a compact IOU
+///
+/// Wire layout: message ID (2 bytes, big-endian), index, total, payload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    message_id: u16,
+    index: u8,
+    total: u8,
+    payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn message_id(&self) -> u16 {
+        self.message_id
+    }
+
+    /// Position of this frame, from 0
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Number of frames in the message
+    pub fn total(&self) -> u8 {
+        self.total
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Encoded length, header included
+    pub fn len(&self) -> usize {
+        COMPACT_FRAME_HEADER_LEN + self.payload.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.payload.is_empty()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+        bytes.extend_from_slice(&self.message_id.to_be_bytes());
+        bytes.push(self.index);
+        bytes.push(self.total);
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        if bytes.len() < COMPACT_FRAME_HEADER_LEN || bytes.len() > MAX_COMPACT_FRAME_LEN {
+            return Err(CodecError::InvalidFrame(format!("length {}", bytes.len())));
+        }
+        let (index, total) = (bytes[2], bytes[3]);
+        if total == 0 || index >= total {
+            return Err(CodecError::InvalidFrame(format!("index {} of {}", index, total)));
+        }
+        Ok(Self {
+            message_id: u16::from_be_bytes([bytes[0], bytes[1]]),
+            index,
+            total,
+            payload: bytes[COMPACT_FRAME_HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+// ============================================================================
+// DECODER
+// ============================================================================
+
+/// Frames received so far for one message
+struct PendingMessage {
+    parts: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+/// Reassembles compact IOU frames and resolves their DIDs
+///
+/// Frames may arrive in any order and interleaved with other messages. Only the
+/// newest `MAX_PENDING_COMPACT_IOUS` incomplete messages are kept.
+pub struct CompactDecoder<R: DidResolver> {
+    resolver: R,
+    pending: HashMap<u16, PendingMessage>,
+    /// Message IDs in arrival order, for dropping the oldest
+    order: VecDeque<u16>,
+}
+
+impl<R: DidResolver> CompactDecoder<R> {
+    pub fn new(resolver: R) -> Self {
+        Self {
+            resolver,
+            pending: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+
+    pub fn resolver_mut(&mut self) -> &mut R {
+        &mut self.resolver
+    }
+
+    /// Number of messages still waiting for frames
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Decode and add a raw frame; see [`CompactDecoder::push_frame`]
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Option<SignedIOU>, CodecError> {
+        self.push_frame(Frame::from_bytes(bytes)?)
+    }
+
+    /// Add a frame, returning the IOU once its last frame arrives
+    ///
+    /// A repeated frame is ignored; a frame that disagrees with earlier ones about
+    /// the frame count restarts the message.
+    pub fn push_frame(&mut self, frame: Frame) -> Result<Option<SignedIOU>, CodecError> {
+        let id = frame.message_id;
+        let total = frame.total as usize;
+
+        if self.pending.get(&id).is_some_and(|pending| pending.parts.len() != total) {
+            self.forget(id);
+        }
+        if !self.pending.contains_key(&id) {
+            if self.order.len() >= MAX_PENDING_COMPACT_IOUS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.pending.remove(&oldest);
+                }
+            }
+            self.order.push_back(id);
+            self.pending.insert(id, PendingMessage { parts: vec![None; total], received: 0 });
+        }
+
+        let pending = self.pending.get_mut(&id).expect("inserted above");
+        let slot = &mut pending.parts[frame.index as usize];
+        if slot.is_none() {
+            *slot = Some(frame.payload);
+            pending.received += 1;
+        }
+        if pending.received < total {
+            return Ok(None);
+        }
+
+        let bytes: Vec<u8> = pending.parts.iter().flatten().flatten().copied().collect();
+        self.forget(id);
+        CompactIOU::from_bytes(&bytes)?.resolve(&self.resolver).map(Some)
+    }
+
+    fn forget(&mut self, id: u16) {
+        self.pending.remove(&id);
+        self.order.retain(|&pending| pending != id);
+    }
+}
@@ -0,0 +1,83 @@
+// Denomination - the unit an IOU amount is counted in
+//
+// Amounts stay integer minor units; a denomination names the currency (an
+// ISO-4217-like code) and how many decimal places the minor unit has, so 1250
+// in USD with exponent 2 is 12.50 USD.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Maximum decimal exponent (10^18 still fits in a u64)
+pub const MAX_DENOMINATION_EXPONENT: u8 = 18;
+
+/// Minimum and maximum denomination code length in characters
+pub const MIN_DENOMINATION_CODE_LEN: usize = 3;
+pub const MAX_DENOMINATION_CODE_LEN: usize = 8;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DenominationError {
+    #[error("Invalid denomination code: {0:?} (expected 3-8 characters A-Z or 0-9)")]
+    InvalidCode(String),
+
+    #[error("Denomination exponent too large: {0} (max {MAX_DENOMINATION_EXPONENT})")]
+    ExponentTooLarge(u8),
+}
+
+/// Currency code plus decimal exponent, e.g. `USD` with 2 decimal places
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Denomination {
+    code: String,
+    exponent: u8,
+}
+
+impl Denomination {
+    /// Create a denomination, checking the code and exponent
+    pub fn new(code: impl Into<String>, exponent: u8) -> Result<Self, DenominationError> {
+        let denomination = Self { code: code.into(), exponent };
+        denomination.validate()?;
+        Ok(denomination)
+    }
+
+    /// Currency code, e.g. `USD`
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Number of decimal places in the minor unit
+    pub fn exponent(&self) -> u8 {
+        self.exponent
+    }
+
+    /// Check the code and exponent
+    ///
+    /// Decoded denominations bypass `new`, so decoders call this themselves.
+    pub fn validate(&self) -> Result<(), DenominationError> {
+        let valid_len = (MIN_DENOMINATION_CODE_LEN..=MAX_DENOMINATION_CODE_LEN).contains(&self.code.len());
+        let valid_chars = self.code.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+        if !valid_len || !valid_chars {
+            return Err(DenominationError::InvalidCode(self.code.clone()));
+        }
+        if self.exponent > MAX_DENOMINATION_EXPONENT {
+            return Err(DenominationError::ExponentTooLarge(self.exponent));
+        }
+        Ok(())
+    }
+
+    /// Format an amount in minor units, e.g. `1250` as `12.50 USD`
+    pub fn format_amount(&self, amount: u64) -> String {
+        let exponent = self.exponent as usize;
+        if exponent == 0 {
+            return format!("{} {}", amount, self.code);
+        }
+        let digits = format!("{:0>width$}", amount, width = exponent + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - exponent);
+        format!("{}.{} {}", whole, fraction, self.code)
+    }
+}
+
+impl fmt::Display for Denomination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.code)
+    }
+}
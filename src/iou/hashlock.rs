@@ -0,0 +1,35 @@
+// Hashlock - a SHA256 commitment that holds an IOU until its preimage is revealed
+//
+// A hashlocked IOU is signed and delivered like any other, but the recipient's
+// vault only turns it into a spendable UTXO once they show a preimage hashing to
+// the committed value. The IOU's expiry doubles as the timeout after which the
+// sender may reclaim it, which is what makes atomic swaps and escrow possible.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// SHA256 hash a hashlocked IOU commits to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Hashlock([u8; 32]);
+
+impl Hashlock {
+    /// Create a Hashlock from a raw SHA256 hash
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Lock to the SHA256 hash of `preimage`
+    pub fn from_preimage(preimage: &[u8]) -> Self {
+        Self(Sha256::digest(preimage).into())
+    }
+
+    /// Get the raw hash bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Whether `preimage` hashes to this lock
+    pub fn is_unlocked_by(&self, preimage: &[u8]) -> bool {
+        Self::from_preimage(preimage) == *self
+    }
+}
@@ -0,0 +1,365 @@
+// Minimal JSON support for the canonical IOU and settlement formats
+// Writing is done field by field so key order is fixed; reading accepts any valid JSON
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+/// Deepest nesting accepted when parsing
+const MAX_DEPTH: usize = 32;
+
+/// A parsed JSON value (numbers are kept as their source text)
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parse a complete JSON document
+    pub(crate) fn parse(input: &str) -> Result<Self, String> {
+        let mut parser = Parser { bytes: input.as_bytes(), pos: 0 };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(format!("trailing characters at {}", parser.pos));
+        }
+        Ok(value)
+    }
+
+    /// Look up a key in an object (None for other values or missing keys)
+    pub(crate) fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Required string field
+    pub(crate) fn str_field(&self, key: &str) -> Result<&str, String> {
+        match self.get(key) {
+            Some(JsonValue::String(s)) => Ok(s),
+            Some(_) => Err(format!("'{}' must be a string", key)),
+            None => Err(format!("missing '{}'", key)),
+        }
+    }
+
+    /// Optional string field (absent or null gives None)
+    pub(crate) fn opt_str_field(&self, key: &str) -> Result<Option<&str>, String> {
+        match self.get(key) {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(_) => self.str_field(key).map(Some),
+        }
+    }
+
+    /// Required u64 field, written as a decimal string (a bare integer is also accepted)
+    pub(crate) fn u64_field(&self, key: &str) -> Result<u64, String> {
+        let text = match self.get(key) {
+            Some(JsonValue::String(s)) | Some(JsonValue::Number(s)) => s,
+            Some(_) => return Err(format!("'{}' must be a decimal string", key)),
+            None => return Err(format!("missing '{}'", key)),
+        };
+        text.parse().map_err(|_| format!("'{}' is not a valid u64", key))
+    }
+
+    /// Optional u64 field (absent or null gives None)
+    pub(crate) fn opt_u64_field(&self, key: &str) -> Result<Option<u64>, String> {
+        match self.get(key) {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(_) => self.u64_field(key).map(Some),
+        }
+    }
+
+    /// Required base64url byte field
+    pub(crate) fn bytes_field(&self, key: &str) -> Result<Vec<u8>, String> {
+        URL_SAFE_NO_PAD
+            .decode(self.str_field(key)?)
+            .map_err(|e| format!("'{}' is not base64url: {}", key, e))
+    }
+
+    /// Optional array field (absent or null gives an empty slice)
+    pub(crate) fn array_field(&self, key: &str) -> Result<&[JsonValue], String> {
+        match self.get(key) {
+            None | Some(JsonValue::Null) => Ok(&[]),
+            Some(JsonValue::Array(items)) => Ok(items),
+            Some(_) => Err(format!("'{}' must be an array", key)),
+        }
+    }
+}
+
+// ============================================================================
+// WRITING
+// ============================================================================
+
+/// Builds one JSON object, keeping fields in the order they are written
+pub(crate) struct JsonObject {
+    out: String,
+}
+
+impl JsonObject {
+    pub(crate) fn new() -> Self {
+        Self { out: String::from("{") }
+    }
+
+    fn key(&mut self, key: &str) {
+        if self.out.len() > 1 {
+            self.out.push(',');
+        }
+        write_string(&mut self.out, key);
+        self.out.push(':');
+    }
+
+    pub(crate) fn string(mut self, key: &str, value: &str) -> Self {
+        self.key(key);
+        write_string(&mut self.out, value);
+        self
+    }
+
+    /// String, or null when absent
+    pub(crate) fn opt_string(mut self, key: &str, value: Option<&str>) -> Self {
+        match value {
+            Some(value) => self.string(key, value),
+            None => {
+                self.key(key);
+                self.out.push_str("null");
+                self
+            }
+        }
+    }
+
+    /// u64 written as a decimal string so JavaScript readers keep full precision
+    pub(crate) fn u64(self, key: &str, value: u64) -> Self {
+        self.string(key, &value.to_string())
+    }
+
+    /// Bytes written as unpadded base64url
+    pub(crate) fn bytes(self, key: &str, value: &[u8]) -> Self {
+        self.string(key, &URL_SAFE_NO_PAD.encode(value))
+    }
+
+    /// Already-encoded JSON values, written as an array
+    pub(crate) fn array(mut self, key: &str, items: impl IntoIterator<Item = String>) -> Self {
+        self.key(key);
+        self.out.push('[');
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            self.out.push_str(&item);
+        }
+        self.out.push(']');
+        self
+    }
+
+    /// An already-encoded JSON object
+    pub(crate) fn object(mut self, key: &str, value: String) -> Self {
+        self.key(key);
+        self.out.push_str(&value);
+        self
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.out.push('}');
+        self.out
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at {}", byte as char, self.pos))
+        }
+    }
+
+    fn literal(&mut self, text: &str, value: JsonValue) -> Result<JsonValue, String> {
+        if self.bytes[self.pos..].starts_with(text.as_bytes()) {
+            self.pos += text.len();
+            Ok(value)
+        } else {
+            Err(format!("unexpected token at {}", self.pos))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<JsonValue, String> {
+        if depth > MAX_DEPTH {
+            return Err("nesting too deep".to_string());
+        }
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b't') => self.literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.literal("false", JsonValue::Bool(false)),
+            Some(b'n') => self.literal("null", JsonValue::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(format!("unexpected character at {}", self.pos)),
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<JsonValue, String> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            if fields.iter().any(|(k, _)| *k == key) {
+                return Err(format!("duplicate key '{}'", key));
+            }
+            self.expect(b':')?;
+            fields.push((key, self.value(depth + 1)?));
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(fields));
+                }
+                _ => return Err(format!("expected ',' or '}}' at {}", self.pos)),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<JsonValue, String> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                _ => return Err(format!("expected ',' or ']' at {}", self.pos)),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<JsonValue, String> {
+        let start = self.pos;
+        while matches!(
+            self.bytes.get(self.pos),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).map_err(|e| e.to_string())?;
+        Ok(JsonValue::Number(text.to_string()))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| format!("invalid \\u escape at {}", self.pos))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            while !matches!(self.bytes.get(self.pos), None | Some(b'"' | b'\\')) {
+                self.pos += 1;
+            }
+            out.push_str(std::str::from_utf8(&self.bytes[start..self.pos]).map_err(|e| e.to_string())?);
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escape = *self.bytes.get(self.pos).ok_or("unterminated string")?;
+                    self.pos += 1;
+                    match escape {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'n' => out.push('\n'),
+                        b'r' => out.push('\r'),
+                        b't' => out.push('\t'),
+                        b'u' => out.push(self.unicode_escape()?),
+                        _ => return Err(format!("invalid escape at {}", self.pos - 1)),
+                    }
+                }
+                _ => return Err("unterminated string".to_string()),
+            }
+        }
+    }
+
+    /// The character for a `\u` escape, pairing surrogates
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.bytes[self.pos..].starts_with(b"\\u") {
+                return Err(format!("unpaired surrogate at {}", self.pos));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(format!("invalid low surrogate at {}", self.pos));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| format!("invalid code point at {}", self.pos))
+    }
+}
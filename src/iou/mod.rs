@@ -5,8 +5,27 @@ mod model;
 mod builder;
 mod validator;
 mod codec;
+mod rejection;
+mod request;
+mod cancellation;
+mod compact;
+mod nonce;
+mod canonical;
+mod denomination;
+mod hashlock;
+mod multisig;
+pub(crate) mod json;
 
 pub use model::*;
 pub use builder::*;
 pub use validator::*;
 pub use codec::*;
+pub use rejection::*;
+pub use request::*;
+pub use cancellation::*;
+pub use compact::*;
+pub use nonce::*;
+pub use canonical::*;
+pub use denomination::*;
+pub use hashlock::*;
+pub use multisig::*;
@@ -1,8 +1,16 @@
 use crate::identity::{Did, PublicKey, Signature, Signer};
-use serde::{Deserialize, Serialize};
+use crate::iou::{Denomination, Hashlock, PaymentRequest, PaymentRequestId};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Sha256, Digest};
 use std::hash::{Hash, Hasher};
 
+/// Maximum memo length in bytes (UTF-8)
+pub const MAX_MEMO_BYTES: usize = 140;
+
+/// Largest amount that stays safe in signed net-position math: even a batch of
+/// `u32::MAX` IOUs at this amount sums to less than `i64::MAX`
+pub const MAX_SAFE_AMOUNT: u64 = i64::MAX as u64 / u32::MAX as u64;
+
 /// Unique identifier for an IOU (SHA256 hash of contents)
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IOUId([u8; 32]);
@@ -25,6 +33,30 @@ impl Hash for IOUId {
     }
 }
 
+/// One recipient's share of a multi-output IOU
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IOUOutput {
+    recipient: Did,
+    amount: u64,
+}
+
+impl IOUOutput {
+    /// Create a new output paying `amount` to `recipient`
+    pub fn new(recipient: Did, amount: u64) -> Self {
+        Self { recipient, amount }
+    }
+
+    /// Get the recipient DID
+    pub fn recipient(&self) -> &Did {
+        &self.recipient
+    }
+
+    /// Get the amount (the total of all outputs for a multi-output IOU)
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+}
+
 /// The IOU (payment packet) - an unsigned representation of a payment intent
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IOU {
@@ -33,6 +65,29 @@ pub struct IOU {
     amount: u64,
     nonce: u64,
     timestamp: u64,
+    /// Optional expiry (Unix timestamp in seconds) after which the IOU is no longer valid
+    expiry: Option<u64>,
+    /// Optional payment reference, e.g. an order number.
+    /// Encoded after the signature by `SignedIOU` so older nodes can still decode.
+    #[serde(skip)]
+    memo: Option<String>,
+    /// Optional payment request this IOU pays, also encoded after the signature
+    #[serde(skip)]
+    request_id: Option<PaymentRequestId>,
+    /// Recipients of a multi-output IOU, also encoded after the signature.
+    /// Empty for the common single-recipient form; otherwise `recipient` is the
+    /// first output's and `amount` the sum of all outputs.
+    #[serde(skip)]
+    outputs: Vec<IOUOutput>,
+    /// Unit the amount is counted in, also encoded after the signature.
+    /// `None` means bare credits.
+    #[serde(skip)]
+    denomination: Option<Denomination>,
+    /// Hash whose preimage releases this IOU to its recipient, also encoded after
+    /// the signature. Hashlocked IOUs always carry an expiry, after which the
+    /// sender can reclaim them.
+    #[serde(skip)]
+    hashlock: Option<Hashlock>,
 }
 
 impl IOU {
@@ -50,9 +105,54 @@ impl IOU {
             amount,
             nonce,
             timestamp,
+            expiry: None,
+            memo: None,
+            request_id: None,
+            outputs: Vec::new(),
+            denomination: None,
+            hashlock: None,
         }
     }
 
+    /// Set an expiry timestamp (Unix seconds) on this IOU
+    pub fn with_expiry(mut self, expiry: u64) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Attach a memo to this IOU
+    pub fn with_memo(mut self, memo: String) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    /// Mark this IOU as paying the payment request with the given ID
+    pub fn with_request_id(mut self, request_id: PaymentRequestId) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    /// Split this IOU across several recipients
+    ///
+    /// `recipient` and `amount` are left as given; `IOUBuilder` sets them to the
+    /// first output and the total, and the validator rejects IOUs where they disagree.
+    pub fn with_outputs(mut self, outputs: Vec<IOUOutput>) -> Self {
+        self.outputs = outputs;
+        self
+    }
+
+    /// Set the unit the amount is counted in
+    pub fn with_denomination(mut self, denomination: Denomination) -> Self {
+        self.denomination = Some(denomination);
+        self
+    }
+
+    /// Hold this IOU until the preimage of `hashlock` is revealed
+    pub fn with_hashlock(mut self, hashlock: Hashlock) -> Self {
+        self.hashlock = Some(hashlock);
+        self
+    }
+
     /// Get the sender DID
     pub fn sender(&self) -> &Did {
         &self.sender
@@ -78,7 +178,71 @@ impl IOU {
         self.timestamp
     }
 
-    /// Compute the unique ID for this IOU (SHA256 of all fields)
+    /// Get the expiry timestamp, if any
+    pub fn expiry(&self) -> Option<u64> {
+        self.expiry
+    }
+
+    /// Get the memo, if any
+    pub fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
+
+    /// Get the ID of the payment request this IOU pays, if any
+    pub fn request_id(&self) -> Option<&PaymentRequestId> {
+        self.request_id.as_ref()
+    }
+
+    /// Get the outputs of a multi-output IOU (empty for a single-recipient IOU)
+    pub fn outputs(&self) -> &[IOUOutput] {
+        &self.outputs
+    }
+
+    /// Get the denomination, if any
+    pub fn denomination(&self) -> Option<&Denomination> {
+        self.denomination.as_ref()
+    }
+
+    /// Get the hashlock, if any
+    pub fn hashlock(&self) -> Option<&Hashlock> {
+        self.hashlock.as_ref()
+    }
+
+    /// Whether this IOU pays several recipients
+    pub fn is_multi_output(&self) -> bool {
+        !self.outputs.is_empty()
+    }
+
+    /// Every recipient this IOU pays, in output order
+    pub fn recipients(&self) -> Vec<&Did> {
+        if self.outputs.is_empty() {
+            return vec![&self.recipient];
+        }
+        self.outputs.iter().map(|output| &output.recipient).collect()
+    }
+
+    /// Amount this IOU pays to `did` (0 if `did` isn't a recipient)
+    pub fn amount_for(&self, did: &Did) -> u64 {
+        if self.outputs.is_empty() {
+            return if &self.recipient == did { self.amount } else { 0 };
+        }
+        self.outputs
+            .iter()
+            .filter(|output| &output.recipient == did)
+            .fold(0u64, |total, output| total.saturating_add(output.amount))
+    }
+
+    /// Check if this IOU has expired at the given time (Unix seconds)
+    ///
+    /// IOUs without an expiry never expire.
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        match self.expiry {
+            Some(expiry) => now >= expiry,
+            None => false,
+        }
+    }
+
+    /// Compute the unique ID for this IOU (SHA256 of its canonical encoding)
     pub fn id(&self) -> IOUId {
         let bytes = self.to_signing_bytes();
         let hash = Sha256::digest(&bytes);
@@ -87,41 +251,82 @@ impl IOU {
         IOUId(id)
     }
 
-    /// Get the bytes that should be signed
+    /// Get the bytes that should be signed: the IOU's canonical encoding
+    ///
+    /// Panics if a field is longer than a 4-byte length prefix allows (over 4 GiB);
+    /// use [`IOU::to_canonical_bytes`] to get that as an error instead.
     pub fn to_signing_bytes(&self) -> Vec<u8> {
-        // Deterministic serialization of all fields
-        let mut bytes = Vec::new();
-
-        // Sender DID string
-        let sender_str = self.sender.to_string();
-        bytes.extend_from_slice(&(sender_str.len() as u32).to_le_bytes());
-        bytes.extend_from_slice(sender_str.as_bytes());
-
-        // Recipient DID string
-        let recipient_str = self.recipient.to_string();
-        bytes.extend_from_slice(&(recipient_str.len() as u32).to_le_bytes());
-        bytes.extend_from_slice(recipient_str.as_bytes());
-
-        // Amount
-        bytes.extend_from_slice(&self.amount.to_le_bytes());
-
-        // Nonce
-        bytes.extend_from_slice(&self.nonce.to_le_bytes());
-
-        // Timestamp
-        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
-
-        bytes
+        self.to_canonical_bytes()
+            .expect("IOU fields fit the canonical length prefixes")
     }
 }
 
 /// A signed IOU - contains the IOU and its signature
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct SignedIOU {
     iou: IOU,
     signature: Signature,
 }
 
+/// Wire layout of `SignedIOU`: the memo, request ID, outputs, denomination and hashlock
+/// trail the signature, where nodes that predate them stop reading
+#[derive(Serialize)]
+struct SignedIOURef<'a> {
+    iou: &'a IOU,
+    signature: &'a Signature,
+    memo: &'a Option<String>,
+    request_id: &'a Option<PaymentRequestId>,
+    outputs: &'a Vec<IOUOutput>,
+    denomination: &'a Option<Denomination>,
+    hashlock: &'a Option<Hashlock>,
+}
+
+/// Trailing fields default when a format reports that the encoding ended before
+/// them. Postcard can't, so `IOUCodec::decode` reads them one at a time instead.
+#[derive(Deserialize)]
+struct SignedIOUWire {
+    iou: IOU,
+    signature: Signature,
+    #[serde(default)]
+    memo: Option<String>,
+    #[serde(default)]
+    request_id: Option<PaymentRequestId>,
+    #[serde(default)]
+    outputs: Vec<IOUOutput>,
+    #[serde(default)]
+    denomination: Option<Denomination>,
+    #[serde(default)]
+    hashlock: Option<Hashlock>,
+}
+
+impl Serialize for SignedIOU {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SignedIOURef {
+            iou: &self.iou,
+            signature: &self.signature,
+            memo: &self.iou.memo,
+            request_id: &self.iou.request_id,
+            outputs: &self.iou.outputs,
+            denomination: &self.iou.denomination,
+            hashlock: &self.iou.hashlock,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SignedIOU {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let SignedIOUWire { mut iou, signature, memo, request_id, outputs, denomination, hashlock } =
+            SignedIOUWire::deserialize(deserializer)?;
+        iou.memo = memo;
+        iou.request_id = request_id;
+        iou.outputs = outputs;
+        iou.denomination = denomination;
+        iou.hashlock = hashlock;
+        Ok(Self { iou, signature })
+    }
+}
+
 impl SignedIOU {
     /// Create a SignedIOU from parts
     pub fn from_parts(iou: IOU, signature: Signature) -> Self {
@@ -143,11 +348,57 @@ impl SignedIOU {
         self.iou.id()
     }
 
+    /// Get the memo, if any
+    pub fn memo(&self) -> Option<&str> {
+        self.iou.memo()
+    }
+
+    /// Get the denomination, if any
+    pub fn denomination(&self) -> Option<&Denomination> {
+        self.iou.denomination()
+    }
+
+    /// Get the hashlock, if any
+    pub fn hashlock(&self) -> Option<&Hashlock> {
+        self.iou.hashlock()
+    }
+
+    /// Check whether this IOU pays the given payment request
+    ///
+    /// The recipient and amount must match and the IOU must carry the request's ID.
+    /// Signatures and request expiry are not checked here.
+    pub fn fulfills(&self, request: &PaymentRequest) -> bool {
+        self.iou.recipient == *request.recipient()
+            && self.iou.amount == request.amount()
+            && self.iou.request_id.as_ref() == Some(&request.id())
+    }
+
     /// Verify the signature against a public key
     pub fn verify(&self, public_key: &PublicKey) -> bool {
         let bytes = self.iou.to_signing_bytes();
         Signer::verify(public_key, &bytes, &self.signature)
     }
+
+    /// Verify the signatures of IOUs all signed by `public_key`, one result per IOU
+    ///
+    /// Checks the whole slice with ed25519 batch verification, falling back to
+    /// verifying each IOU only when the batch fails, to find which ones are bad.
+    pub fn verify_batch(ious: &[SignedIOU], public_key: &PublicKey) -> Vec<bool> {
+        let messages: Vec<Vec<u8>> = ious.iter().map(|signed| signed.iou.to_signing_bytes()).collect();
+        let message_refs: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+        let signatures: Vec<Signature> = ious.iter().map(|signed| signed.signature.clone()).collect();
+
+        let keys = vec![public_key.clone(); ious.len()];
+
+        if ious.is_empty() || Signer::verify_batch(&message_refs, &signatures, &keys) {
+            return vec![true; ious.len()];
+        }
+        message_refs
+            .iter()
+            .zip(&signatures)
+            .map(|(message, signature)| Signer::verify(public_key, message, signature))
+            .collect()
+    }
 }
 
 impl PartialEq for SignedIOU {
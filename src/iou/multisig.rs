@@ -0,0 +1,498 @@
+// Multi-signature IOUs - payments from a shared wallet that need M of N signers
+//
+// The signed payload commits to the IOU, the threshold and the full signer set,
+// so a signature can't be lifted onto a weaker policy or a different payment.
+// As with `SignedIOU`, the IOU's memo, request ID, outputs, denomination and
+// hashlock are encoded after the signatures, so every signed field survives a
+// round trip and encodings that stop before some of them still decode.
+
+use super::codec::decode_trailing;
+use crate::identity::{Did, Keypair, PublicKey, Signature, Signer};
+use crate::iou::{Denomination, Hashlock, IOUId, IOUOutput, PaymentRequestId, IOU};
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Domain separator so a multi-signature can never be replayed as another message
+const MULTISIG_DOMAIN: &[u8] = b"p2pmesh:multisig:v1";
+
+/// Errors from building or validating a multi-signature IOU
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MultiSigError {
+    #[error("Invalid threshold: {threshold} of {signers} signers")]
+    InvalidThreshold { threshold: u32, signers: usize },
+
+    #[error("Duplicate signer: a key appears more than once")]
+    DuplicateSigner,
+
+    #[error("Unknown signer: key is not in the signer set")]
+    UnknownSigner,
+
+    #[error("Invalid signature: signature does not match the multi-signature payload")]
+    InvalidSignature,
+
+    #[error("Insufficient signatures: {valid} of {required} required")]
+    InsufficientSignatures { valid: usize, required: usize },
+
+    #[error("Policy mismatch: threshold or signer set differs from the expected policy")]
+    PolicyMismatch,
+
+    #[error("Missing field: {0} is required")]
+    MissingField(&'static str),
+
+    #[error("Invalid amount: amount cannot be zero")]
+    InvalidAmount,
+
+    #[error("Self-payment not allowed: sender and recipient cannot be the same")]
+    SelfPayment,
+
+    #[error("Invalid expiry: {0}")]
+    InvalidExpiry(String),
+
+    #[error("Expired: IOU is past its expiry time")]
+    Expired,
+}
+
+// ============================================================================
+// POLICY
+// ============================================================================
+
+/// Which keys may sign for a shared wallet, and how many must
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiSigPolicy {
+    threshold: u32,
+    /// Signer keys in byte order
+    signers: Vec<PublicKey>,
+}
+
+impl MultiSigPolicy {
+    /// Require `threshold` of `signers`; the order of `signers` doesn't matter
+    pub fn new(threshold: u32, signers: Vec<PublicKey>) -> Result<Self, MultiSigError> {
+        let mut signers = signers;
+        signers.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        if signers.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(MultiSigError::DuplicateSigner);
+        }
+        if threshold == 0 || threshold as usize > signers.len() {
+            return Err(MultiSigError::InvalidThreshold {
+                threshold,
+                signers: signers.len(),
+            });
+        }
+        Ok(Self { threshold, signers })
+    }
+
+    /// Number of signatures required
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// Keys allowed to sign, in byte order
+    pub fn signers(&self) -> &[PublicKey] {
+        &self.signers
+    }
+
+    /// Whether `key` is in the signer set
+    pub fn is_signer(&self, key: &PublicKey) -> bool {
+        self.signers.contains(key)
+    }
+
+    /// Check the invariants `new` enforces, for policies that arrived over the wire
+    fn check(&self) -> Result<(), MultiSigError> {
+        let rebuilt = Self::new(self.threshold, self.signers.clone())?;
+        if rebuilt.signers != self.signers {
+            return Err(MultiSigError::PolicyMismatch);
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// MULTI-SIGNATURE IOU
+// ============================================================================
+
+/// An IOU from a shared wallet, carrying signatures from some of its signers
+#[derive(Clone, Debug)]
+pub struct MultiSigIOU {
+    iou: IOU,
+    policy: MultiSigPolicy,
+    /// Signatures in the order they were added
+    signatures: Vec<(PublicKey, Signature)>,
+}
+
+/// Wire layout of `MultiSigIOU`: the IOU fields `IOU` itself skips trail the signatures
+#[derive(Serialize)]
+struct MultiSigIOURef<'a> {
+    iou: &'a IOU,
+    policy: &'a MultiSigPolicy,
+    signatures: &'a [(PublicKey, Signature)],
+    memo: Option<&'a str>,
+    request_id: Option<&'a PaymentRequestId>,
+    outputs: &'a [IOUOutput],
+    denomination: Option<&'a Denomination>,
+    hashlock: Option<&'a Hashlock>,
+}
+
+#[derive(Deserialize)]
+struct MultiSigIOUWire {
+    iou: IOU,
+    policy: MultiSigPolicy,
+    signatures: Vec<(PublicKey, Signature)>,
+    #[serde(default)]
+    memo: Option<String>,
+    #[serde(default)]
+    request_id: Option<PaymentRequestId>,
+    #[serde(default)]
+    outputs: Vec<IOUOutput>,
+    #[serde(default)]
+    denomination: Option<Denomination>,
+    #[serde(default)]
+    hashlock: Option<Hashlock>,
+}
+
+impl Serialize for MultiSigIOU {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MultiSigIOURef {
+            iou: &self.iou,
+            policy: &self.policy,
+            signatures: &self.signatures,
+            memo: self.iou.memo(),
+            request_id: self.iou.request_id(),
+            outputs: self.iou.outputs(),
+            denomination: self.iou.denomination(),
+            hashlock: self.iou.hashlock(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MultiSigIOU {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let MultiSigIOUWire { mut iou, policy, signatures, memo, request_id, outputs, denomination, hashlock } =
+            MultiSigIOUWire::deserialize(deserializer)?;
+        if let Some(memo) = memo {
+            iou = iou.with_memo(memo);
+        }
+        if let Some(request_id) = request_id {
+            iou = iou.with_request_id(request_id);
+        }
+        iou = iou.with_outputs(outputs);
+        if let Some(denomination) = denomination {
+            iou = iou.with_denomination(denomination);
+        }
+        if let Some(hashlock) = hashlock {
+            iou = iou.with_hashlock(hashlock);
+        }
+        Ok(Self { iou, policy, signatures })
+    }
+}
+
+impl MultiSigIOU {
+    /// Create from parts, e.g. after collecting signatures elsewhere
+    pub fn from_parts(iou: IOU, policy: MultiSigPolicy, signatures: Vec<(PublicKey, Signature)>) -> Self {
+        Self { iou, policy, signatures }
+    }
+
+    /// Get the underlying IOU
+    pub fn iou(&self) -> &IOU {
+        &self.iou
+    }
+
+    /// Get the policy the signatures commit to
+    pub fn policy(&self) -> &MultiSigPolicy {
+        &self.policy
+    }
+
+    /// Get the collected signatures
+    pub fn signatures(&self) -> &[(PublicKey, Signature)] {
+        &self.signatures
+    }
+
+    /// Get the bytes every signer signs: the IOU, the threshold and the signer set
+    pub fn to_signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MULTISIG_DOMAIN);
+
+        let iou_bytes = self.iou.to_signing_bytes();
+        bytes.extend_from_slice(&(iou_bytes.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&iou_bytes);
+
+        bytes.extend_from_slice(&self.policy.threshold.to_le_bytes());
+        bytes.extend_from_slice(&(self.policy.signers.len() as u32).to_le_bytes());
+        for signer in &self.policy.signers {
+            bytes.extend_from_slice(signer.as_bytes());
+        }
+        bytes
+    }
+
+    /// Compute the ID (SHA256 of the signing bytes)
+    pub fn id(&self) -> IOUId {
+        let hash = Sha256::digest(self.to_signing_bytes());
+        IOUId::from_bytes(hash.into())
+    }
+
+    /// Add a co-signer's signature
+    ///
+    /// Fails if the key isn't in the signer set or has already signed.
+    pub fn sign(&mut self, keypair: &Keypair) -> Result<(), MultiSigError> {
+        let public_key = keypair.public_key();
+        if !self.policy.is_signer(&public_key) {
+            return Err(MultiSigError::UnknownSigner);
+        }
+        if self.signatures.iter().any(|(key, _)| key == &public_key) {
+            return Err(MultiSigError::DuplicateSigner);
+        }
+        let signature = Signer::sign(keypair, &self.to_signing_bytes());
+        self.signatures.push((public_key, signature));
+        Ok(())
+    }
+
+    /// Whether enough signatures have been collected (without verifying them)
+    pub fn has_threshold(&self) -> bool {
+        self.signatures.len() >= self.policy.threshold as usize
+    }
+
+    /// Serialize to bytes (postcard)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes (postcard)
+    ///
+    /// Also accepts encodings that end before the trailing IOU fields.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        let ((iou, policy, signatures), rest) =
+            postcard::take_from_bytes::<(IOU, MultiSigPolicy, Vec<(PublicKey, Signature)>)>(bytes)?;
+        Ok(Self { iou: decode_trailing(iou, rest)?, policy, signatures })
+    }
+}
+
+// ============================================================================
+// BUILDER
+// ============================================================================
+
+/// Builder for multi-signature IOUs
+///
+/// ```ignore
+/// let iou = MultiSigIOUBuilder::new(policy)
+///     .sender(wallet_did)
+///     .recipient(shop_did)
+///     .amount(500)
+///     .sign_with(&alice)
+///     .sign_with(&bob)
+///     .build()?;
+/// ```
+///
+/// Fewer than `threshold` signatures is allowed, so a partly signed IOU can be
+/// passed on and finished with [`MultiSigIOU::sign`].
+pub struct MultiSigIOUBuilder<'a> {
+    policy: MultiSigPolicy,
+    sender: Option<Did>,
+    recipient: Option<Did>,
+    amount: Option<u64>,
+    nonce: Option<u64>,
+    timestamp: Option<u64>,
+    expiry: Option<u64>,
+    ttl_secs: Option<u64>,
+    signers: Vec<&'a Keypair>,
+}
+
+impl<'a> MultiSigIOUBuilder<'a> {
+    /// Create a builder for an IOU governed by `policy`
+    pub fn new(policy: MultiSigPolicy) -> Self {
+        Self {
+            policy,
+            sender: None,
+            recipient: None,
+            amount: None,
+            nonce: None,
+            timestamp: None,
+            expiry: None,
+            ttl_secs: None,
+            signers: Vec::new(),
+        }
+    }
+
+    /// Set the shared wallet's DID (required)
+    pub fn sender(mut self, did: Did) -> Self {
+        self.sender = Some(did);
+        self
+    }
+
+    /// Set the recipient DID (required)
+    pub fn recipient(mut self, did: Did) -> Self {
+        self.recipient = Some(did);
+        self
+    }
+
+    /// Set the payment amount (required)
+    pub fn amount(mut self, amount: u64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Set the nonce (optional - random if not provided)
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Set the timestamp (optional - auto-generated if not provided)
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Set an absolute expiry timestamp in Unix seconds (optional)
+    pub fn expiry(mut self, expiry: u64) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Set a time-to-live relative to the IOU timestamp (optional)
+    ///
+    /// Ignored if an absolute expiry is also set.
+    pub fn ttl_secs(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = Some(ttl_secs);
+        self
+    }
+
+    /// Add a signer; every signer signs when `build` is called
+    pub fn sign_with(mut self, keypair: &'a Keypair) -> Self {
+        self.signers.push(keypair);
+        self
+    }
+
+    /// Build the IOU and collect a signature from each signer
+    pub fn build(self) -> Result<MultiSigIOU, MultiSigError> {
+        let sender = self.sender.ok_or(MultiSigError::MissingField("sender"))?;
+        let recipient = self.recipient.ok_or(MultiSigError::MissingField("recipient"))?;
+        let amount = self.amount.ok_or(MultiSigError::MissingField("amount"))?;
+        if amount == 0 {
+            return Err(MultiSigError::InvalidAmount);
+        }
+        if sender == recipient {
+            return Err(MultiSigError::SelfPayment);
+        }
+
+        let timestamp = self.timestamp.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        });
+        let nonce = self.nonce.unwrap_or_else(|| rand::thread_rng().gen::<u64>());
+
+        // Absolute expiry takes precedence over TTL
+        let expiry = match (self.expiry, self.ttl_secs) {
+            (Some(expiry), _) => Some(expiry),
+            (None, Some(ttl)) => Some(timestamp.checked_add(ttl).ok_or_else(|| {
+                MultiSigError::InvalidExpiry("ttl overflows timestamp".to_string())
+            })?),
+            (None, None) => None,
+        };
+
+        let mut iou = IOU::new(sender, recipient, amount, nonce, timestamp);
+        if let Some(expiry) = expiry {
+            if expiry <= timestamp {
+                return Err(MultiSigError::InvalidExpiry(
+                    "expiry must be after the IOU timestamp".to_string(),
+                ));
+            }
+            iou = iou.with_expiry(expiry);
+        }
+        let mut multisig = MultiSigIOU::from_parts(iou, self.policy, Vec::new());
+        for keypair in self.signers {
+            multisig.sign(keypair)?;
+        }
+        Ok(multisig)
+    }
+}
+
+// ============================================================================
+// VALIDATOR
+// ============================================================================
+
+/// Checks multi-signature IOUs against a wallet's known policy
+///
+/// The expected policy comes from the validator, not the IOU, so an IOU that
+/// names its own, weaker signer set is rejected.
+#[derive(Clone, Debug)]
+pub struct MultiSigValidator {
+    policy: MultiSigPolicy,
+}
+
+impl MultiSigValidator {
+    /// Validate against `policy`
+    pub fn new(policy: MultiSigPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// Validate against `threshold` of `signers`
+    pub fn with_signers(threshold: u32, signers: Vec<PublicKey>) -> Result<Self, MultiSigError> {
+        Ok(Self::new(MultiSigPolicy::new(threshold, signers)?))
+    }
+
+    /// The expected policy
+    pub fn policy(&self) -> &MultiSigPolicy {
+        &self.policy
+    }
+
+    /// Check that at least `threshold` distinct configured signers signed this IOU
+    /// and that it hasn't expired
+    ///
+    /// Any signature from an unknown key, from a key that already signed, or that
+    /// doesn't verify over the payload fails the whole IOU.
+    pub fn validate(&self, multisig: &MultiSigIOU) -> Result<(), MultiSigError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.validate_at(multisig, now)
+    }
+
+    /// [`MultiSigValidator::validate`] at time `now` (Unix seconds)
+    pub fn validate_at(&self, multisig: &MultiSigIOU, now: u64) -> Result<(), MultiSigError> {
+        multisig.policy.check()?;
+        if multisig.policy != self.policy {
+            return Err(MultiSigError::PolicyMismatch);
+        }
+        if multisig.iou.amount() == 0 {
+            return Err(MultiSigError::InvalidAmount);
+        }
+        if multisig.iou.is_expired_at(now) {
+            return Err(MultiSigError::Expired);
+        }
+
+        let payload = multisig.to_signing_bytes();
+        let mut seen: Vec<&PublicKey> = Vec::new();
+        for (key, signature) in &multisig.signatures {
+            if !self.policy.is_signer(key) {
+                return Err(MultiSigError::UnknownSigner);
+            }
+            if seen.contains(&key) {
+                return Err(MultiSigError::DuplicateSigner);
+            }
+            if !Signer::verify(key, &payload, signature) {
+                return Err(MultiSigError::InvalidSignature);
+            }
+            seen.push(key);
+        }
+
+        let required = self.policy.threshold as usize;
+        if seen.len() < required {
+            return Err(MultiSigError::InsufficientSignatures {
+                valid: seen.len(),
+                required,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the IOU passes `validate`
+    pub fn is_valid(&self, multisig: &MultiSigIOU) -> bool {
+        self.validate(multisig).is_ok()
+    }
+}
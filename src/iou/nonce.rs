@@ -0,0 +1,114 @@
+// Nonce management - monotonically increasing per-sender IOU nonces
+
+use crate::identity::Did;
+use crate::vault::Vault;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Errors from a `NonceManager`
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum NonceError {
+    #[error("Nonce regression for {sender}: {nonce} is not above last used {last_used}")]
+    Regression { sender: Did, nonce: u64, last_used: u64 },
+
+    #[error("Nonces exhausted for {0}")]
+    Exhausted(Did),
+
+    #[error("Deserialization failed")]
+    DeserializationFailed,
+}
+
+/// Hands out strictly increasing nonces per sender, starting at 1
+///
+/// Tracks the last nonce used by each sender. Seeding and merging only ever raise
+/// a counter, so restoring an older copy can't make a sender reuse a nonce.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceManager {
+    last_used: HashMap<Did, u64>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Manager seeded from the IOUs `vault` has sent
+    pub fn from_vault(vault: &Vault) -> Self {
+        let mut manager = Self::new();
+        manager.seed_from_vault(vault);
+        manager
+    }
+
+    /// Allocate the next nonce for `sender`
+    pub fn next(&mut self, sender: &Did) -> Result<u64, NonceError> {
+        let last_used = self.last_used.entry(sender.clone()).or_insert(0);
+        *last_used = last_used
+            .checked_add(1)
+            .ok_or_else(|| NonceError::Exhausted(sender.clone()))?;
+        Ok(*last_used)
+    }
+
+    /// The nonce `next` would return for `sender`, without allocating it
+    /// (saturates at `u64::MAX`, where `next` fails)
+    pub fn peek(&self, sender: &Did) -> u64 {
+        self.last_used(sender).unwrap_or(0).saturating_add(1)
+    }
+
+    /// The last nonce allocated or observed for `sender`
+    pub fn last_used(&self, sender: &Did) -> Option<u64> {
+        self.last_used.get(sender).copied()
+    }
+
+    /// Record a nonce `sender` used elsewhere, failing if it doesn't move the counter forward
+    pub fn observe(&mut self, sender: &Did, nonce: u64) -> Result<(), NonceError> {
+        if let Some(last_used) = self.last_used(sender).filter(|&last| nonce <= last) {
+            return Err(NonceError::Regression {
+                sender: sender.clone(),
+                nonce,
+                last_used,
+            });
+        }
+        self.last_used.insert(sender.clone(), nonce);
+        Ok(())
+    }
+
+    /// Raise `sender`'s counter to at least `last_used` (never lowers it)
+    pub fn seed(&mut self, sender: &Did, last_used: u64) {
+        let current = self.last_used.entry(sender.clone()).or_insert(0);
+        *current = (*current).max(last_used);
+    }
+
+    /// Raise the vault owner's counter past every nonce the vault has sent
+    pub fn seed_from_vault(&mut self, vault: &Vault) {
+        if let Some(highest) = vault.highest_sent_nonce() {
+            self.seed(&Did::from_public_key(vault.owner()), highest);
+        }
+    }
+
+    /// Take the higher counter for every sender in `other`
+    pub fn merge(&mut self, other: &NonceManager) {
+        for (sender, &last_used) in &other.last_used {
+            self.seed(sender, last_used);
+        }
+    }
+
+    /// Number of senders tracked
+    pub fn len(&self) -> usize {
+        self.last_used.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.last_used.is_empty()
+    }
+
+    /// Serialize to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NonceError> {
+        postcard::from_bytes(bytes).map_err(|_| NonceError::DeserializationFailed)
+    }
+}
@@ -0,0 +1,163 @@
+// IOU rejection - recipient-signed notice that a payment was refused
+
+use crate::identity::{Did, Keypair, PublicKey, Signature, Signer};
+use crate::iou::IOUId;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Domain separator so a rejection signature can never be replayed as an IOU signature
+const REJECTION_DOMAIN: &[u8] = b"p2pmesh:rejection:v1";
+
+/// Why a recipient refused an IOU
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RejectionReason {
+    /// Recipient declined the payment
+    Declined,
+    /// Amount was not what the recipient expected
+    WrongAmount,
+    /// IOU arrived after it expired
+    Expired,
+    /// Recipient does not know or trust the sender
+    UnknownSender,
+    /// Any other reason
+    Other,
+}
+
+impl RejectionReason {
+    /// Stable numeric code for this reason
+    pub fn code(&self) -> u8 {
+        match self {
+            RejectionReason::Declined => 0,
+            RejectionReason::WrongAmount => 1,
+            RejectionReason::Expired => 2,
+            RejectionReason::UnknownSender => 3,
+            RejectionReason::Other => 255,
+        }
+    }
+
+    /// Reason for a numeric code (unknown codes map to `Other`)
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => RejectionReason::Declined,
+            1 => RejectionReason::WrongAmount,
+            2 => RejectionReason::Expired,
+            3 => RejectionReason::UnknownSender,
+            _ => RejectionReason::Other,
+        }
+    }
+}
+
+/// An unsigned rejection of a specific IOU
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rejection {
+    iou_id: IOUId,
+    recipient: Did,
+    reason: RejectionReason,
+    timestamp: u64,
+}
+
+impl Rejection {
+    /// Create a new rejection
+    pub fn new(iou_id: IOUId, recipient: Did, reason: RejectionReason, timestamp: u64) -> Self {
+        Self {
+            iou_id,
+            recipient,
+            reason,
+            timestamp,
+        }
+    }
+
+    /// ID of the rejected IOU
+    pub fn iou_id(&self) -> &IOUId {
+        &self.iou_id
+    }
+
+    /// The IOU recipient issuing the rejection
+    pub fn recipient(&self) -> &Did {
+        &self.recipient
+    }
+
+    /// Why the IOU was rejected
+    pub fn reason(&self) -> RejectionReason {
+        self.reason
+    }
+
+    /// When the rejection was issued (Unix seconds)
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Get the bytes that should be signed
+    pub fn to_signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(REJECTION_DOMAIN);
+        bytes.extend_from_slice(self.iou_id.as_bytes());
+
+        let recipient_str = self.recipient.to_string();
+        bytes.extend_from_slice(&(recipient_str.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(recipient_str.as_bytes());
+
+        bytes.push(self.reason.code());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes
+    }
+}
+
+/// A rejection signed by the IOU's recipient
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedRejection {
+    rejection: Rejection,
+    signature: Signature,
+}
+
+impl SignedRejection {
+    /// Reject an IOU as its recipient, timestamped now
+    pub fn sign(keypair: &Keypair, iou_id: IOUId, reason: RejectionReason) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let recipient = Did::from_public_key(&keypair.public_key());
+        let rejection = Rejection::new(iou_id, recipient, reason, timestamp);
+        let signature = Signer::sign(keypair, &rejection.to_signing_bytes());
+        Self { rejection, signature }
+    }
+
+    /// Create a SignedRejection from parts
+    pub fn from_parts(rejection: Rejection, signature: Signature) -> Self {
+        Self { rejection, signature }
+    }
+
+    /// Get the underlying rejection
+    pub fn rejection(&self) -> &Rejection {
+        &self.rejection
+    }
+
+    /// Get the signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Verify the signature against a public key
+    pub fn verify(&self, public_key: &PublicKey) -> bool {
+        Signer::verify(public_key, &self.rejection.to_signing_bytes(), &self.signature)
+    }
+
+    /// Verify the signature against the recipient DID's own key
+    pub fn verify_recipient(&self) -> bool {
+        match self.rejection.recipient.public_key() {
+            Ok(public_key) => self.verify(&public_key),
+            Err(_) => false,
+        }
+    }
+
+    /// Serialize to bytes (postcard)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes (postcard)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
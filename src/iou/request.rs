@@ -0,0 +1,327 @@
+// Payment request - payee-signed invoice asking for a specific payment
+
+use crate::identity::{Did, Keypair, PublicKey, Signature, Signer};
+use crate::iou::{IOUError, MAX_MEMO_BYTES};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Domain separator so a request signature can never be replayed as an IOU signature
+const PAYMENT_REQUEST_DOMAIN: &[u8] = b"p2pmesh:payment-request:v1";
+
+/// Unique identifier for a payment request (SHA256 of its contents)
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PaymentRequestId([u8; 32]);
+
+impl PaymentRequestId {
+    /// Create a PaymentRequestId from raw bytes
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Get the raw bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// An unsigned request for `amount` to be paid to `recipient`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentRequest {
+    recipient: Did,
+    amount: u64,
+    memo: Option<String>,
+    /// Optional expiry (Unix timestamp in seconds) after which the request can't be paid
+    expiry: Option<u64>,
+    nonce: u64,
+    timestamp: u64,
+}
+
+impl PaymentRequest {
+    /// Create a new payment request
+    pub fn new(recipient: Did, amount: u64, nonce: u64, timestamp: u64) -> Self {
+        Self {
+            recipient,
+            amount,
+            memo: None,
+            expiry: None,
+            nonce,
+            timestamp,
+        }
+    }
+
+    /// Set an expiry timestamp (Unix seconds) on this request
+    pub fn with_expiry(mut self, expiry: u64) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Attach a memo to this request
+    pub fn with_memo(mut self, memo: String) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    /// The requester, who is to receive the payment
+    pub fn recipient(&self) -> &Did {
+        &self.recipient
+    }
+
+    /// Amount requested
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    /// Get the memo, if any
+    pub fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
+
+    /// Get the expiry timestamp, if any
+    pub fn expiry(&self) -> Option<u64> {
+        self.expiry
+    }
+
+    /// Get the nonce
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// When the request was created (Unix seconds)
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Check if this request has expired at the given time (Unix seconds)
+    ///
+    /// Requests without an expiry never expire.
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        match self.expiry {
+            Some(expiry) => now >= expiry,
+            None => false,
+        }
+    }
+
+    /// Compute the unique ID for this request (SHA256 of all fields)
+    pub fn id(&self) -> PaymentRequestId {
+        let hash = Sha256::digest(self.to_signing_bytes());
+        let mut id = [0u8; 32];
+        id.copy_from_slice(&hash);
+        PaymentRequestId(id)
+    }
+
+    /// Get the bytes that should be signed
+    pub fn to_signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(PAYMENT_REQUEST_DOMAIN);
+
+        let recipient_str = self.recipient.to_string();
+        bytes.extend_from_slice(&(recipient_str.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(recipient_str.as_bytes());
+
+        bytes.extend_from_slice(&self.amount.to_le_bytes());
+        bytes.extend_from_slice(&self.nonce.to_le_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+
+        match self.expiry {
+            Some(expiry) => {
+                bytes.push(0x01);
+                bytes.extend_from_slice(&expiry.to_le_bytes());
+            }
+            None => bytes.push(0x00),
+        }
+
+        match &self.memo {
+            Some(memo) => {
+                bytes.push(0x01);
+                bytes.extend_from_slice(&(memo.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(memo.as_bytes());
+            }
+            None => bytes.push(0x00),
+        }
+
+        bytes
+    }
+}
+
+/// A payment request signed by its requester
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedPaymentRequest {
+    request: PaymentRequest,
+    signature: Signature,
+}
+
+impl SignedPaymentRequest {
+    /// Create a SignedPaymentRequest from parts
+    pub fn from_parts(request: PaymentRequest, signature: Signature) -> Self {
+        Self { request, signature }
+    }
+
+    /// Get the underlying request
+    pub fn request(&self) -> &PaymentRequest {
+        &self.request
+    }
+
+    /// Get the signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Get the unique ID of this request
+    pub fn id(&self) -> PaymentRequestId {
+        self.request.id()
+    }
+
+    /// Verify the signature against a public key
+    pub fn verify(&self, public_key: &PublicKey) -> bool {
+        Signer::verify(public_key, &self.request.to_signing_bytes(), &self.signature)
+    }
+
+    /// Verify the signature against the requester DID's own key
+    pub fn verify_requester(&self) -> bool {
+        match self.request.recipient.public_key() {
+            Ok(public_key) => self.verify(&public_key),
+            Err(_) => false,
+        }
+    }
+
+    /// Serialize to bytes (postcard)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes (postcard)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
+
+/// Builder for creating signed payment requests, mirroring `IOUBuilder`
+pub struct PaymentRequestBuilder<'a> {
+    requester: Option<&'a Keypair>,
+    amount: Option<u64>,
+    memo: Option<String>,
+    nonce: Option<u64>,
+    timestamp: Option<u64>,
+    expiry: Option<u64>,
+    ttl_secs: Option<u64>,
+}
+
+impl<'a> PaymentRequestBuilder<'a> {
+    /// Create a new PaymentRequestBuilder
+    pub fn new() -> Self {
+        Self {
+            requester: None,
+            amount: None,
+            memo: None,
+            nonce: None,
+            timestamp: None,
+            expiry: None,
+            ttl_secs: None,
+        }
+    }
+
+    /// Set the requester, who signs the request and receives the payment (required)
+    pub fn requester(mut self, keypair: &'a Keypair) -> Self {
+        self.requester = Some(keypair);
+        self
+    }
+
+    /// Set the amount (required)
+    pub fn amount(mut self, amount: u64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Attach a memo such as an order number (optional, at most `MAX_MEMO_BYTES` bytes)
+    pub fn memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Set the nonce (optional - auto-generated if not provided)
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Set the timestamp (optional - auto-generated if not provided)
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Set an absolute expiry timestamp in Unix seconds (optional)
+    pub fn expiry(mut self, expiry: u64) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Set a time-to-live relative to the request timestamp (optional)
+    ///
+    /// Ignored if an absolute expiry is also set.
+    pub fn ttl_secs(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = Some(ttl_secs);
+        self
+    }
+
+    /// Build and sign the request
+    pub fn build(self) -> Result<SignedPaymentRequest, IOUError> {
+        let requester = self.requester.ok_or(IOUError::MissingRequester)?;
+        let amount = self.amount.ok_or(IOUError::MissingAmount)?;
+
+        if amount == 0 {
+            return Err(IOUError::InvalidAmount("amount cannot be zero".to_string()));
+        }
+
+        if let Some(memo) = &self.memo {
+            if memo.len() > MAX_MEMO_BYTES {
+                return Err(IOUError::MemoTooLong { len: memo.len(), max: MAX_MEMO_BYTES });
+            }
+        }
+
+        let nonce = self.nonce.unwrap_or_else(|| rand::thread_rng().gen::<u64>());
+        let timestamp = self.timestamp.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        });
+
+        // Resolve expiry (absolute expiry takes precedence over TTL)
+        let expiry = match (self.expiry, self.ttl_secs) {
+            (Some(expiry), _) => Some(expiry),
+            (None, Some(ttl)) => Some(timestamp.checked_add(ttl).ok_or_else(|| {
+                IOUError::InvalidExpiry("ttl overflows timestamp".to_string())
+            })?),
+            (None, None) => None,
+        };
+
+        if let Some(expiry) = expiry {
+            if expiry <= timestamp {
+                return Err(IOUError::InvalidExpiry(
+                    "expiry must be after the request timestamp".to_string(),
+                ));
+            }
+        }
+
+        let recipient = Did::from_public_key(&requester.public_key());
+        let mut request = PaymentRequest::new(recipient, amount, nonce, timestamp);
+        if let Some(expiry) = expiry {
+            request = request.with_expiry(expiry);
+        }
+        if let Some(memo) = self.memo {
+            request = request.with_memo(memo);
+        }
+
+        let signature = Signer::sign(requester, &request.to_signing_bytes());
+        Ok(SignedPaymentRequest::from_parts(request, signature))
+    }
+}
+
+impl<'a> Default for PaymentRequestBuilder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,6 @@
-use crate::identity::{Did, PublicKey};
-use crate::iou::{IOU, SignedIOU};
+use crate::identity::{Did, PublicKey, Signer};
+use crate::iou::{DenominationError, IOU, SignedIOU, MAX_MEMO_BYTES};
+use std::collections::HashSet;
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
@@ -23,12 +24,213 @@ pub enum ValidationError {
 
     #[error("Sender mismatch: the provided public key does not match the sender DID")]
     SenderMismatch,
+
+    #[error("Memo too long: memo exceeds {MAX_MEMO_BYTES} bytes")]
+    MemoTooLong,
+
+    #[error("Invalid output: output amount cannot be zero")]
+    ZeroAmountOutput,
+
+    #[error("Duplicate output: a recipient appears in more than one output")]
+    DuplicateOutputRecipient,
+
+    #[error("Output mismatch: recipient and amount must match the first output and the output total")]
+    OutputMismatch,
+
+    #[error("Amount too small: {amount} is below the minimum of {min}")]
+    AmountBelowMinimum { amount: u64, min: u64 },
+
+    #[error("Amount too large: {amount} exceeds the maximum of {max}")]
+    AmountAboveMaximum { amount: u64, max: u64 },
+
+    #[error("Missing expiry: policy requires IOUs to carry an expiry")]
+    MissingExpiry,
+
+    #[error("Sender not allowed: {0} is not on the allow list")]
+    SenderNotAllowed(Did),
+
+    #[error("Sender denied: {0} is on the deny list")]
+    SenderDenied(Did),
+
+    #[error("Invalid denomination: {0}")]
+    InvalidDenomination(#[from] DenominationError),
+
+    #[error("Missing expiry: a hashlocked IOU needs an expiry for the sender to reclaim it")]
+    HashlockWithoutExpiry,
 }
 
+// ============================================================================
+// VALIDATION POLICY
+// ============================================================================
+
+/// Deployment-specific rules applied on top of [`IOUValidator::validate`]
+///
+/// The default policy adds no rules.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationPolicy {
+    max_clock_skew_secs: Option<u64>,
+    min_amount: Option<u64>,
+    max_amount: Option<u64>,
+    require_expiry: bool,
+    allowed_senders: Option<HashSet<Did>>,
+    denied_senders: HashSet<Did>,
+}
+
+impl ValidationPolicy {
+    /// Policy with no extra rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject IOUs timestamped more than `secs` ahead of our clock
+    pub fn with_max_clock_skew_secs(mut self, secs: u64) -> Self {
+        self.max_clock_skew_secs = Some(secs);
+        self
+    }
+
+    /// Reject IOUs whose total amount is below `amount`
+    pub fn with_min_amount(mut self, amount: u64) -> Self {
+        self.min_amount = Some(amount);
+        self
+    }
+
+    /// Reject IOUs whose total amount is above `amount`
+    pub fn with_max_amount(mut self, amount: u64) -> Self {
+        self.max_amount = Some(amount);
+        self
+    }
+
+    /// Reject IOUs that don't carry an expiry
+    pub fn with_require_expiry(mut self, required: bool) -> Self {
+        self.require_expiry = required;
+        self
+    }
+
+    /// Accept only these senders (the first call turns the allow list on)
+    pub fn allow_sender(mut self, did: Did) -> Self {
+        self.allowed_senders.get_or_insert_with(HashSet::new).insert(did);
+        self
+    }
+
+    /// Reject this sender, even if it is also on the allow list
+    pub fn deny_sender(mut self, did: Did) -> Self {
+        self.denied_senders.insert(did);
+        self
+    }
+
+    pub fn max_clock_skew_secs(&self) -> Option<u64> {
+        self.max_clock_skew_secs
+    }
+
+    pub fn min_amount(&self) -> Option<u64> {
+        self.min_amount
+    }
+
+    pub fn max_amount(&self) -> Option<u64> {
+        self.max_amount
+    }
+
+    pub fn requires_expiry(&self) -> bool {
+        self.require_expiry
+    }
+
+    /// Whether the policy's sender lists let `did` pay us
+    pub fn is_sender_allowed(&self, did: &Did) -> bool {
+        !self.denied_senders.contains(did)
+            && self.allowed_senders.as_ref().is_none_or(|allowed| allowed.contains(did))
+    }
+
+    /// Check an IOU against this policy's rules at time `now` (Unix seconds)
+    ///
+    /// Only the policy rules are checked; signatures and the basic rules are
+    /// left to [`IOUValidator::validate`].
+    pub fn check(&self, iou: &IOU, now: u64) -> Result<(), ValidationError> {
+        let sender = iou.sender();
+        if self.denied_senders.contains(sender) {
+            return Err(ValidationError::SenderDenied(sender.clone()));
+        }
+        if self.allowed_senders.as_ref().is_some_and(|allowed| !allowed.contains(sender)) {
+            return Err(ValidationError::SenderNotAllowed(sender.clone()));
+        }
+
+        let amount = iou.amount();
+        if let Some(min) = self.min_amount.filter(|&min| amount < min) {
+            return Err(ValidationError::AmountBelowMinimum { amount, min });
+        }
+        if let Some(max) = self.max_amount.filter(|&max| amount > max) {
+            return Err(ValidationError::AmountAboveMaximum { amount, max });
+        }
+
+        if self.require_expiry && iou.expiry().is_none() {
+            return Err(ValidationError::MissingExpiry);
+        }
+
+        if self
+            .max_clock_skew_secs
+            .is_some_and(|skew| iou.timestamp() > now.saturating_add(skew))
+        {
+            return Err(ValidationError::FutureTimestamp);
+        }
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// VALIDATOR
+// ============================================================================
+
 /// Validator for IOUs
-pub struct IOUValidator;
+///
+/// The associated functions apply the built-in rules only; a validator built with
+/// [`IOUValidator::with_policy`] also applies a [`ValidationPolicy`].
+#[derive(Clone, Debug, Default)]
+pub struct IOUValidator {
+    policy: ValidationPolicy,
+}
 
 impl IOUValidator {
+    /// Validator that applies `policy` on top of the built-in rules
+    pub fn with_policy(policy: ValidationPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// Get the policy this validator applies
+    pub fn policy(&self) -> &ValidationPolicy {
+        &self.policy
+    }
+
+    /// Validate an IOU against the built-in rules and this validator's policy
+    pub fn check(&self, signed_iou: &SignedIOU, sender_pubkey: &PublicKey) -> Result<IOU, ValidationError> {
+        Self::validate_with_policy(signed_iou, sender_pubkey, &self.policy)
+    }
+
+    /// [`IOUValidator::check`] at time `now` (Unix seconds)
+    pub fn check_at(&self, signed_iou: &SignedIOU, sender_pubkey: &PublicKey, now: u64) -> Result<IOU, ValidationError> {
+        Self::validate_with_policy_at(signed_iou, sender_pubkey, &self.policy, now)
+    }
+
+    /// Validate an IOU against the built-in rules, then `policy`
+    pub fn validate_with_policy(
+        signed_iou: &SignedIOU,
+        sender_pubkey: &PublicKey,
+        policy: &ValidationPolicy,
+    ) -> Result<IOU, ValidationError> {
+        Self::validate_with_policy_at(signed_iou, sender_pubkey, policy, now_secs())
+    }
+
+    /// [`IOUValidator::validate_with_policy`] at time `now` (Unix seconds)
+    pub fn validate_with_policy_at(
+        signed_iou: &SignedIOU,
+        sender_pubkey: &PublicKey,
+        policy: &ValidationPolicy,
+        now: u64,
+    ) -> Result<IOU, ValidationError> {
+        let iou = Self::validate_at(signed_iou, sender_pubkey, now)?;
+        policy.check(&iou, now)?;
+        Ok(iou)
+    }
+
     /// Validate an IOU signature and basic rules
     ///
     /// This performs:
@@ -36,7 +238,31 @@ impl IOUValidator {
     /// - Self-payment check
     /// - Zero amount check
     /// - Sender DID matches public key check
+    /// - Expiry check (if the IOU carries an expiry)
+    /// - Memo length check
+    /// - Output checks for multi-output IOUs (no zero amounts or repeated recipients)
     pub fn validate(signed_iou: &SignedIOU, sender_pubkey: &PublicKey) -> Result<IOU, ValidationError> {
+        Self::validate_at(signed_iou, sender_pubkey, now_secs())
+    }
+
+    /// [`IOUValidator::validate`] at time `now` (Unix seconds), for the expiry check
+    pub fn validate_at(signed_iou: &SignedIOU, sender_pubkey: &PublicKey, now: u64) -> Result<IOU, ValidationError> {
+        let iou = Self::validate_replicated(signed_iou, sender_pubkey)?;
+
+        // Check the IOU's own expiry
+        if iou.is_expired_at(now) {
+            return Err(ValidationError::Expired);
+        }
+
+        Ok(iou)
+    }
+
+    /// Validate an IOU copied from another node's ledger
+    ///
+    /// Every rule of [`IOUValidator::validate`] except expiry: an IOU accepted
+    /// before it expired stays in the ledger, so replicas must keep it too or
+    /// they would never converge.
+    pub fn validate_replicated(signed_iou: &SignedIOU, sender_pubkey: &PublicKey) -> Result<IOU, ValidationError> {
         let iou = signed_iou.iou();
 
         // Check sender DID matches the public key
@@ -60,9 +286,103 @@ impl IOUValidator {
             return Err(ValidationError::InvalidAmount);
         }
 
+        Self::validate_outputs(iou)?;
+
+        // Check memo length
+        if iou.memo().is_some_and(|memo| memo.len() > MAX_MEMO_BYTES) {
+            return Err(ValidationError::MemoTooLong);
+        }
+
+        // Check the denomination (decoding doesn't always go through `Denomination::new`)
+        if let Some(denomination) = iou.denomination() {
+            denomination.validate()?;
+        }
+
+        // A hashlock without a timeout could lock the sender's funds forever
+        if iou.hashlock().is_some() && iou.expiry().is_none() {
+            return Err(ValidationError::HashlockWithoutExpiry);
+        }
+
         Ok(iou.clone())
     }
 
+    /// Verify each IOU's signature against the key paired with it, one result per entry
+    ///
+    /// Checks the whole slice with ed25519 batch verification, about three times
+    /// faster than [`SignedIOU::verify`] on each entry. Entries are only verified one
+    /// by one when the batch fails, to find which are bad. Like `SignedIOU::verify`,
+    /// only signatures are checked.
+    pub fn verify_batch(entries: &[(SignedIOU, PublicKey)]) -> Vec<bool> {
+        if Self::batch_is_valid(entries) {
+            return vec![true; entries.len()];
+        }
+        entries.iter().map(|(signed, key)| signed.verify(key)).collect()
+    }
+
+    /// Index of the first entry whose signature doesn't verify, if any
+    ///
+    /// Fast-fail form of [`IOUValidator::verify_batch`]: a failed batch is bisected
+    /// with further batch checks rather than verifying every entry.
+    pub fn first_invalid(entries: &[(SignedIOU, PublicKey)]) -> Option<usize> {
+        if Self::batch_is_valid(entries) {
+            return None;
+        }
+
+        // `suspect` always holds the first invalid entry, starting at `offset`
+        let mut offset = 0;
+        let mut suspect = entries;
+        while suspect.len() > 1 {
+            let (left, right) = suspect.split_at(suspect.len() / 2);
+            if Self::batch_is_valid(left) {
+                offset += left.len();
+                suspect = right;
+            } else {
+                suspect = left;
+            }
+        }
+        Some(offset)
+    }
+
+    fn batch_is_valid(entries: &[(SignedIOU, PublicKey)]) -> bool {
+        if entries.is_empty() {
+            return true;
+        }
+        let messages: Vec<Vec<u8>> = entries.iter().map(|(signed, _)| signed.iou().to_signing_bytes()).collect();
+        let message_refs: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+        let signatures: Vec<_> = entries.iter().map(|(signed, _)| signed.signature().clone()).collect();
+        let keys: Vec<PublicKey> = entries.iter().map(|(_, key)| key.clone()).collect();
+        Signer::verify_batch(&message_refs, &signatures, &keys)
+    }
+
+    /// Check the outputs of a multi-output IOU against each other and the IOU header
+    fn validate_outputs(iou: &IOU) -> Result<(), ValidationError> {
+        let outputs = iou.outputs();
+        if outputs.is_empty() {
+            return Ok(());
+        }
+
+        let mut total = 0u64;
+        for (i, output) in outputs.iter().enumerate() {
+            if output.amount() == 0 {
+                return Err(ValidationError::ZeroAmountOutput);
+            }
+            if outputs[..i].iter().any(|earlier| earlier.recipient() == output.recipient()) {
+                return Err(ValidationError::DuplicateOutputRecipient);
+            }
+            if output.recipient() == iou.sender() {
+                return Err(ValidationError::SelfPayment);
+            }
+            total = total
+                .checked_add(output.amount())
+                .ok_or(ValidationError::OutputMismatch)?;
+        }
+
+        if iou.recipient() != outputs[0].recipient() || iou.amount() != total {
+            return Err(ValidationError::OutputMismatch);
+        }
+        Ok(())
+    }
+
     /// Validate with timestamp check (for clock skew protection)
     ///
     /// tolerance_secs: How many seconds into the future a timestamp is allowed
@@ -139,3 +459,11 @@ impl IOUValidator {
         Ok(iou)
     }
 }
+
+/// Wall-clock time in Unix seconds
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,131 @@
+// Bloom Summary - Compact "what I have" digest for delta sync
+//
+// A node sends a bloom filter of its IOU IDs instead of its full state.
+// The peer replies with every entry the filter does not contain.
+// False positives only mean an entry is skipped this round; it is picked up
+// on the next sync against a fresh filter.
+
+use crate::iou::IOUId;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Domain separator for bloom hashing
+const BLOOM_DOMAIN: &[u8] = b"p2pmesh:bloom:v1";
+
+/// Upper bound on hash functions, so a hostile summary can't make lookups expensive
+pub const MAX_BLOOM_HASHES: u32 = 32;
+
+/// Upper bound on filter size in bytes (1 MiB)
+pub const MAX_BLOOM_BYTES: usize = 1024 * 1024;
+
+/// Errors from bloom summary handling
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BloomError {
+    #[error("Invalid bloom parameters: {0}")]
+    InvalidParameters(String),
+
+    #[error("Deserialization failed")]
+    DeserializationFailed,
+}
+
+/// Bloom filter over IOU IDs
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BloomSummary {
+    /// Bit array
+    bits: Vec<u8>,
+    /// Number of hash functions
+    num_hashes: u32,
+    /// Number of IDs inserted
+    item_count: u64,
+}
+
+impl BloomSummary {
+    /// Create an empty filter sized for `expected_items` at false-positive rate `fp_rate`.
+    /// `fp_rate` is clamped to [0.0001, 0.5].
+    pub fn with_capacity(expected_items: usize, fp_rate: f64) -> Self {
+        let fp_rate = if fp_rate.is_nan() { 0.01 } else { fp_rate.clamp(0.0001, 0.5) };
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+
+        let num_bits = (-(n * fp_rate.ln()) / (ln2 * ln2)).ceil().max(8.0);
+        let num_bytes = ((num_bits / 8.0).ceil() as usize).min(MAX_BLOOM_BYTES);
+        let num_hashes = ((num_bytes * 8) as f64 / n * ln2).round() as u32;
+
+        Self {
+            bits: vec![0u8; num_bytes],
+            num_hashes: num_hashes.clamp(1, MAX_BLOOM_HASHES),
+            item_count: 0,
+        }
+    }
+
+    /// Add an IOU ID
+    pub fn insert(&mut self, iou_id: &IOUId) {
+        for index in self.bit_indexes(iou_id) {
+            self.bits[index / 8] |= 1 << (index % 8);
+        }
+        self.item_count += 1;
+    }
+
+    /// Check whether an IOU ID is probably present (never false for inserted IDs)
+    pub fn contains(&self, iou_id: &IOUId) -> bool {
+        self.bit_indexes(iou_id)
+            .all(|index| self.bits[index / 8] & (1 << (index % 8)) != 0)
+    }
+
+    /// Number of IDs inserted
+    pub fn item_count(&self) -> u64 {
+        self.item_count
+    }
+
+    /// Number of hash functions
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Size of the bit array in bits
+    pub fn num_bits(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    /// Bit positions for an ID (double hashing over SHA-256)
+    fn bit_indexes(&self, iou_id: &IOUId) -> impl Iterator<Item = usize> {
+        let mut hasher = Sha256::new();
+        hasher.update(BLOOM_DOMAIN);
+        hasher.update(iou_id.as_bytes());
+        let digest = hasher.finalize();
+
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap()) | 1;
+        let num_bits = self.num_bits() as u64;
+
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+
+    /// Serialize to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes, rejecting empty or oversized filters
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BloomError> {
+        let summary: BloomSummary =
+            postcard::from_bytes(bytes).map_err(|_| BloomError::DeserializationFailed)?;
+
+        if summary.bits.is_empty() || summary.bits.len() > MAX_BLOOM_BYTES {
+            return Err(BloomError::InvalidParameters(format!(
+                "filter size {} bytes",
+                summary.bits.len()
+            )));
+        }
+        if summary.num_hashes == 0 || summary.num_hashes > MAX_BLOOM_HASHES {
+            return Err(BloomError::InvalidParameters(format!(
+                "{} hash functions",
+                summary.num_hashes
+            )));
+        }
+
+        Ok(summary)
+    }
+}
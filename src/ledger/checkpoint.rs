@@ -0,0 +1,163 @@
+// Checkpoint - gateway-signed list of settled IOUs that nodes may prune
+//
+// The G-Set never forgets an IOU, so settled entries pile up. A gateway signs
+// the IDs it has settled; a node applying the checkpoint drops those entries
+// and remembers the IDs, so a merge with a peer that hasn't pruned yet can't
+// bring them back. Nodes that apply the same checkpoints converge again.
+
+use crate::identity::{Did, Keypair, PublicKey, Signature, Signer};
+use crate::iou::IOUId;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Domain separator so a checkpoint signature can never be replayed as another message
+const CHECKPOINT_DOMAIN: &[u8] = b"p2pmesh:checkpoint:v1";
+
+/// Unique identifier for a checkpoint (hash of its signing bytes)
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CheckpointId([u8; 32]);
+
+impl CheckpointId {
+    /// Create from raw bytes
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Get raw bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for CheckpointId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "checkpoint:{}", hex::encode(&self.0[..8]))
+    }
+}
+
+/// An unsigned list of settled IOU IDs
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Settled IOU IDs, sorted and without duplicates
+    iou_ids: Vec<IOUId>,
+    issuer: Did,
+    timestamp: u64,
+}
+
+impl Checkpoint {
+    /// Create a new checkpoint; the IDs are sorted and deduplicated
+    pub fn new(iou_ids: impl IntoIterator<Item = IOUId>, issuer: Did, timestamp: u64) -> Self {
+        let mut iou_ids: Vec<IOUId> = iou_ids.into_iter().collect();
+        iou_ids.sort_by_key(|id| *id.as_bytes());
+        iou_ids.dedup();
+        Self {
+            iou_ids,
+            issuer,
+            timestamp,
+        }
+    }
+
+    /// IDs of the settled IOUs, in byte order
+    pub fn iou_ids(&self) -> &[IOUId] {
+        &self.iou_ids
+    }
+
+    /// The gateway issuing the checkpoint
+    pub fn issuer(&self) -> &Did {
+        &self.issuer
+    }
+
+    /// When the checkpoint was issued (Unix seconds)
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Get the bytes that should be signed
+    pub fn to_signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(CHECKPOINT_DOMAIN);
+
+        let issuer_str = self.issuer.to_string();
+        bytes.extend_from_slice(&(issuer_str.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(issuer_str.as_bytes());
+
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes.extend_from_slice(&(self.iou_ids.len() as u32).to_le_bytes());
+        for iou_id in &self.iou_ids {
+            bytes.extend_from_slice(iou_id.as_bytes());
+        }
+        bytes
+    }
+
+    /// Compute the checkpoint ID
+    pub fn id(&self) -> CheckpointId {
+        let hash = Sha256::digest(self.to_signing_bytes());
+        CheckpointId(hash.into())
+    }
+}
+
+/// A checkpoint signed by the gateway that settled its IOUs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedCheckpoint {
+    checkpoint: Checkpoint,
+    signature: Signature,
+}
+
+impl SignedCheckpoint {
+    /// Checkpoint `iou_ids` as the gateway holding `keypair`, timestamped now
+    pub fn sign(keypair: &Keypair, iou_ids: impl IntoIterator<Item = IOUId>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let issuer = Did::from_public_key(&keypair.public_key());
+        let checkpoint = Checkpoint::new(iou_ids, issuer, timestamp);
+        let signature = Signer::sign(keypair, &checkpoint.to_signing_bytes());
+        Self { checkpoint, signature }
+    }
+
+    /// Create a SignedCheckpoint from parts
+    pub fn from_parts(checkpoint: Checkpoint, signature: Signature) -> Self {
+        Self { checkpoint, signature }
+    }
+
+    /// Get the underlying checkpoint
+    pub fn checkpoint(&self) -> &Checkpoint {
+        &self.checkpoint
+    }
+
+    /// Get the signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Get the checkpoint ID
+    pub fn id(&self) -> CheckpointId {
+        self.checkpoint.id()
+    }
+
+    /// Verify the signature against a public key
+    pub fn verify(&self, public_key: &PublicKey) -> bool {
+        Signer::verify(public_key, &self.checkpoint.to_signing_bytes(), &self.signature)
+    }
+
+    /// Verify the signature against the issuer DID's own key
+    pub fn verify_issuer(&self) -> bool {
+        match self.checkpoint.issuer.public_key() {
+            Ok(public_key) => self.verify(&public_key),
+            Err(_) => false,
+        }
+    }
+
+    /// Serialize to bytes (postcard)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes (postcard)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
@@ -1,7 +1,9 @@
 // Conflict Detection - Detects double-spends in the distributed mesh
 
-use crate::identity::PublicKey;
-use crate::iou::IOUId;
+use crate::identity::{KeySigner, PublicKey};
+use crate::iou::{IOUId, SignedIOU};
+use crate::ledger::crdt::IOUEntry;
+use crate::ledger::evidence::{nonce_slot, ConflictEvidence};
 use crate::ledger::state::NodeId;
 use crate::vault::UTXOId;
 use serde::{Deserialize, Serialize};
@@ -27,12 +29,22 @@ pub enum ConflictError {
     DoubleSpend {
         utxo_id: UTXOId,
         conflict_type: ConflictType,
-        first_claim: SpendingClaim,
-        second_claim: SpendingClaim,
+        /// Boxed, like `second_claim`, so every `Result<_, ConflictError>` stays small
+        first_claim: Box<SpendingClaim>,
+        second_claim: Box<SpendingClaim>,
     },
 
     #[error("Deserialization failed")]
     DeserializationFailed,
+
+    #[error("No conflict on UTXO {0:?}")]
+    NoConflict(UTXOId),
+
+    #[error("Signed IOU {0:?} was not recorded with its claim")]
+    MissingIOU(IOUId),
+
+    #[error("Invalid evidence: {0}")]
+    InvalidEvidence(&'static str),
 }
 
 /// Resolution strategy for conflicts
@@ -46,6 +58,84 @@ pub enum ConflictResolution {
     Custom,
 }
 
+/// How `ConflictDetector::resolve_all` picks the winner among claims on one UTXO
+///
+/// Every strategy breaks ties by IOU ID and ignores the order claims arrived in,
+/// so detectors holding the same claims pick the same winners.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResolutionStrategy {
+    /// The claim with the earliest timestamp wins
+    #[default]
+    FirstTimestampWins,
+    /// The claim whose IOU ID sorts lowest wins
+    LowestIOUIdWins,
+    /// A claim whose IOU is marked settled wins; otherwise the earliest timestamp
+    PreferSettled,
+    /// No winner is picked; resolve by hand with `clear_conflict`
+    Manual,
+}
+
+/// How `resolve_all` settled the claims on one UTXO
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedConflict {
+    /// The contested UTXO
+    pub utxo_id: UTXOId,
+    /// The IOU allowed to spend it, or `None` under `ResolutionStrategy::Manual`
+    pub winner: Option<IOUId>,
+    /// The other IOUs claiming it, in byte order (empty when there is no winner)
+    pub losers: Vec<IOUId>,
+}
+
+/// How a merge settles IOUs that spend the same UTXO
+///
+/// Each policy only looks at signed IOU contents and breaks ties by IOU ID,
+/// so every node holding the same IOUs picks the same winner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// The IOU with the earliest signed timestamp survives
+    FirstSeenWins,
+    /// The IOU with the lowest sender nonce survives
+    LowestNonceWins,
+    /// Every conflicting IOU is dropped
+    RejectBoth,
+}
+
+impl ConflictPolicy {
+    /// The IOU that survives among `contenders`, or `None` if all are dropped
+    pub fn pick_winner<'a>(&self, contenders: &[&'a SignedIOU]) -> Option<&'a SignedIOU> {
+        match self {
+            ConflictPolicy::FirstSeenWins => contenders
+                .iter()
+                .min_by_key(|iou| (iou.iou().timestamp(), *iou.id().as_bytes()))
+                .copied(),
+            ConflictPolicy::LowestNonceWins => contenders
+                .iter()
+                .min_by_key(|iou| (iou.iou().nonce(), *iou.id().as_bytes()))
+                .copied(),
+            ConflictPolicy::RejectBoth => None,
+        }
+    }
+}
+
+/// Why a merge dropped an IOU
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DropReason {
+    /// Another IOU spending the same UTXO won under the policy
+    Superseded { winner: IOUId },
+    /// The policy drops every IOU spending a contested UTXO
+    Rejected,
+}
+
+/// An IOU removed while resolving a conflict
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DroppedEntry {
+    /// The dropped IOU
+    pub iou_id: IOUId,
+    /// The UTXO it was contesting
+    pub utxo_id: UTXOId,
+    pub reason: DropReason,
+}
+
 /// A claim that a UTXO was spent in a specific transaction
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SpendingClaim {
@@ -78,6 +168,20 @@ impl SpendingClaim {
         }
     }
 
+    /// Claim on the sender's nonce by `entry`, first seen when it was received
+    ///
+    /// Two IOUs from one sender with the same nonce claim the same slot, so they
+    /// conflict like two spends of one UTXO.
+    pub fn for_nonce(entry: &IOUEntry) -> Self {
+        let iou = entry.iou().iou();
+        Self::with_timestamp(
+            nonce_slot(iou.sender(), iou.nonce()),
+            entry.id(),
+            entry.sender_pubkey().clone(),
+            entry.received_at(),
+        )
+    }
+
     /// Create with explicit timestamp
     pub fn with_timestamp(
         utxo_id: UTXOId,
@@ -177,6 +281,28 @@ pub struct ConflictDetector {
     claims: HashMap<UTXOId, Vec<SpendingClaim>>,
     /// Count of detected conflicts
     conflict_count: usize,
+    /// IOUs known to be settled, preferred by `ResolutionStrategy::PreferSettled`
+    settled: HashSet<IOUId>,
+    /// Signed IOUs behind claims, kept to export as evidence
+    ious: HashMap<IOUId, SignedIOU>,
+    /// How `resolve_all` picks winners; node config, so not serialized
+    #[serde(skip)]
+    strategy: ResolutionStrategy,
+}
+
+/// Conflict detector as serialized before signed IOUs were kept for evidence
+#[derive(Deserialize)]
+struct PreEvidenceConflictDetector {
+    claims: HashMap<UTXOId, Vec<SpendingClaim>>,
+    conflict_count: usize,
+    settled: HashSet<IOUId>,
+}
+
+/// Conflict detector as serialized before settled IOUs were tracked
+#[derive(Deserialize)]
+struct PreSettledConflictDetector {
+    claims: HashMap<UTXOId, Vec<SpendingClaim>>,
+    conflict_count: usize,
 }
 
 impl Default for ConflictDetector {
@@ -191,9 +317,40 @@ impl ConflictDetector {
         Self {
             claims: HashMap::new(),
             conflict_count: 0,
+            settled: HashSet::new(),
+            ious: HashMap::new(),
+            strategy: ResolutionStrategy::default(),
         }
     }
 
+    /// Create a detector that resolves conflicts with `strategy`
+    pub fn with_strategy(strategy: ResolutionStrategy) -> Self {
+        Self {
+            strategy,
+            ..Self::new()
+        }
+    }
+
+    /// The strategy used by `resolve_all`
+    pub fn strategy(&self) -> ResolutionStrategy {
+        self.strategy
+    }
+
+    /// Change the strategy used by `resolve_all`
+    pub fn set_strategy(&mut self, strategy: ResolutionStrategy) {
+        self.strategy = strategy;
+    }
+
+    /// Record that an IOU has been settled (see `ResolutionStrategy::PreferSettled`)
+    pub fn mark_settled(&mut self, iou_id: IOUId) {
+        self.settled.insert(iou_id);
+    }
+
+    /// Check whether an IOU has been marked settled
+    pub fn is_settled(&self, iou_id: &IOUId) -> bool {
+        self.settled.contains(iou_id)
+    }
+
     /// Get the number of registered claims
     pub fn claim_count(&self) -> usize {
         self.claims.values().map(|v| v.len()).sum()
@@ -220,32 +377,87 @@ impl ConflictDetector {
             }
 
             // Different IOU spending same UTXO = DOUBLE SPEND!
-            let first_claim = existing_claims[0].clone();
+            let first_claim = Box::new(existing_claims[0].clone());
             self.conflict_count += 1;
 
             // Still record the claim for conflict resolution later
             self.claims
                 .entry(utxo_id.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(claim.clone());
 
             return Err(ConflictError::DoubleSpend {
                 utxo_id,
                 conflict_type: ConflictType::SameUtxoDifferentRecipient,
                 first_claim,
-                second_claim: claim,
+                second_claim: Box::new(claim),
             });
         }
 
         // No existing claims, register this one
         self.claims
             .entry(utxo_id)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(claim);
 
         Ok(())
     }
 
+    /// Register a claim together with its signed IOU, so it can be exported as evidence
+    pub fn register_signed_claim(&mut self, claim: SpendingClaim, iou: SignedIOU) -> Result<(), ConflictError> {
+        self.ious.entry(iou.id()).or_insert(iou);
+        self.register_claim(claim)
+    }
+
+    /// Register `entry`'s claim on its sender's nonce (see [`SpendingClaim::for_nonce`])
+    pub fn register_entry(&mut self, entry: &IOUEntry) -> Result<(), ConflictError> {
+        self.register_signed_claim(SpendingClaim::for_nonce(entry), entry.iou().clone())
+    }
+
+    /// Signed evidence of the conflict on `utxo_id`, signed by `signer` as detector
+    ///
+    /// Covers the first claim and the earliest one conflicting with it, ordered as
+    /// under `ResolutionStrategy::FirstTimestampWins`. Both IOUs must have been
+    /// registered with `register_signed_claim` or `register_entry`.
+    pub fn export_evidence<S: KeySigner + ?Sized>(
+        &self,
+        utxo_id: &UTXOId,
+        signer: &S,
+    ) -> Result<ConflictEvidence, ConflictError> {
+        let mut claims = self.get_conflicts_for_utxo(utxo_id);
+        if claims.len() < 2 {
+            return Err(ConflictError::NoConflict(utxo_id.clone()));
+        }
+        claims.sort_by_key(|c| (c.timestamp(), *c.spending_iou_id().as_bytes()));
+
+        let signed = |claim: &SpendingClaim| {
+            self.ious
+                .get(claim.spending_iou_id())
+                .cloned()
+                .ok_or_else(|| ConflictError::MissingIOU(claim.spending_iou_id().clone()))
+        };
+        let (first, second) = (claims[0], claims[1]);
+        Ok(ConflictEvidence::sign(
+            utxo_id.clone(),
+            signed(first)?,
+            first.timestamp(),
+            signed(second)?,
+            second.timestamp(),
+            signer,
+        ))
+    }
+
+    /// Evidence for every conflicting UTXO, ordered by UTXO ID; conflicts whose
+    /// IOUs weren't recorded are skipped
+    pub fn export_all_evidence<S: KeySigner + ?Sized>(&self, signer: &S) -> Vec<ConflictEvidence> {
+        let mut utxo_ids = self.conflicting_utxos();
+        utxo_ids.sort_by_key(|id| *id.as_bytes());
+        utxo_ids
+            .into_iter()
+            .filter_map(|utxo_id| self.export_evidence(utxo_id, signer).ok())
+            .collect()
+    }
+
     /// Get all claims for a specific UTXO
     pub fn get_claims_for_utxo(&self, utxo_id: &UTXOId) -> Vec<&SpendingClaim> {
         self.claims
@@ -270,6 +482,25 @@ impl ConflictDetector {
             .unwrap_or(false)
     }
 
+    /// The earliest claim spending one of `iou_id`'s sources ahead of it, if any
+    ///
+    /// Claims are ordered by timestamp then IOU ID, as under
+    /// `ResolutionStrategy::FirstTimestampWins`. `None` means the IOU has no
+    /// registered claims or is the first spend of every UTXO it claims.
+    pub fn prior_spend(&self, iou_id: &IOUId) -> Option<&SpendingClaim> {
+        let order = |c: &SpendingClaim| (c.timestamp(), *c.spending_iou_id().as_bytes());
+        self.claims
+            .values()
+            .filter_map(|claims| {
+                let own = claims.iter().find(|c| c.spending_iou_id() == iou_id)?;
+                claims
+                    .iter()
+                    .filter(|c| order(c) < order(own))
+                    .min_by_key(|c| order(c))
+            })
+            .min_by_key(|c| order(c))
+    }
+
     /// Resolve a conflict using the specified strategy
     pub fn resolve_conflict(
         &self,
@@ -309,6 +540,66 @@ impl ConflictDetector {
         }
     }
 
+    /// Resolve every conflicting UTXO with the detector's strategy
+    ///
+    /// Results are ordered by UTXO ID. The outcome depends only on the claims held
+    /// and the settled IOUs, never on the order they were registered or merged in.
+    pub fn resolve_all(&self) -> Vec<ResolvedConflict> {
+        let mut utxo_ids = self.conflicting_utxos();
+        utxo_ids.sort_by_key(|id| *id.as_bytes());
+
+        utxo_ids
+            .into_iter()
+            .map(|utxo_id| {
+                let claims = &self.claims[utxo_id];
+                let winner = self.pick_winner(claims).map(|c| c.spending_iou_id().clone());
+                let mut losers: Vec<IOUId> = match &winner {
+                    Some(winner) => claims
+                        .iter()
+                        .map(|c| c.spending_iou_id())
+                        .filter(|id| *id != winner)
+                        .cloned()
+                        .collect(),
+                    None => Vec::new(),
+                };
+                losers.sort_by_key(|id| *id.as_bytes());
+                ResolvedConflict {
+                    utxo_id: utxo_id.clone(),
+                    winner,
+                    losers,
+                }
+            })
+            .collect()
+    }
+
+    /// IDs of every IOU that lost under `resolve_all`, in byte order
+    ///
+    /// Hand these to `Vault::invalidate_received_iou` to reverse their credit.
+    pub fn losing_iou_ids(&self) -> Vec<IOUId> {
+        let mut losers: Vec<IOUId> = self
+            .resolve_all()
+            .into_iter()
+            .flat_map(|resolved| resolved.losers)
+            .collect();
+        losers.sort_by_key(|id| *id.as_bytes());
+        losers.dedup();
+        losers
+    }
+
+    fn pick_winner<'a>(&self, claims: &'a [SpendingClaim]) -> Option<&'a SpendingClaim> {
+        let earliest = |c: &&SpendingClaim| (c.timestamp(), *c.spending_iou_id().as_bytes());
+        match self.strategy {
+            ResolutionStrategy::FirstTimestampWins => claims.iter().min_by_key(earliest),
+            ResolutionStrategy::LowestIOUIdWins => claims.iter().min_by_key(|c| *c.spending_iou_id().as_bytes()),
+            ResolutionStrategy::PreferSettled => claims
+                .iter()
+                .filter(|c| self.settled.contains(c.spending_iou_id()))
+                .min_by_key(earliest)
+                .or_else(|| claims.iter().min_by_key(earliest)),
+            ResolutionStrategy::Manual => None,
+        }
+    }
+
     /// Merge another detector into this one
     pub fn merge(&mut self, other: &ConflictDetector) -> DetectorMergeResult {
         let mut new_claims = 0;
@@ -325,13 +616,14 @@ impl ConflictDetector {
                     .unwrap_or(false);
 
                 if already_exists {
-                    // Merge witnesses
+                    // Merge witnesses; keep the earliest sighting so timestamps converge
                     if let Some(claims) = self.claims.get_mut(utxo_id) {
                         for c in claims.iter_mut() {
                             if c.spending_iou_id() == claim.spending_iou_id() {
                                 for witness in claim.witnesses() {
                                     c.add_witness(witness.clone());
                                 }
+                                c.timestamp = c.timestamp.min(claim.timestamp);
                             }
                         }
                     }
@@ -350,13 +642,18 @@ impl ConflictDetector {
                 // Add the claim
                 self.claims
                     .entry(utxo_id.clone())
-                    .or_insert_with(Vec::new)
+                    .or_default()
                     .push(claim.clone());
 
                 new_claims += 1;
             }
         }
 
+        self.settled.extend(other.settled.iter().cloned());
+        for (iou_id, iou) in &other.ious {
+            self.ious.entry(iou_id.clone()).or_insert_with(|| iou.clone());
+        }
+
         DetectorMergeResult {
             new_claims,
             conflicts_detected,
@@ -386,9 +683,26 @@ impl ConflictDetector {
         postcard::to_allocvec(self).unwrap_or_default()
     }
 
-    /// Deserialize from bytes
+    /// Deserialize from bytes; detectors saved by earlier versions still load
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConflictError> {
-        postcard::from_bytes(bytes).map_err(|_| ConflictError::DeserializationFailed)
+        if let Ok(detector) = postcard::from_bytes(bytes) {
+            return Ok(detector);
+        }
+        if let Ok(old) = postcard::from_bytes::<PreEvidenceConflictDetector>(bytes) {
+            return Ok(Self {
+                claims: old.claims,
+                conflict_count: old.conflict_count,
+                settled: old.settled,
+                ..Self::new()
+            });
+        }
+        let old: PreSettledConflictDetector =
+            postcard::from_bytes(bytes).map_err(|_| ConflictError::DeserializationFailed)?;
+        Ok(Self {
+            claims: old.claims,
+            conflict_count: old.conflict_count,
+            ..Self::new()
+        })
     }
 
     /// Get all UTXOs that have conflicts
@@ -403,6 +717,9 @@ impl ConflictDetector {
     /// Clear resolved conflicts (after settlement)
     pub fn clear_conflict(&mut self, utxo_id: &UTXOId, winning_iou_id: &IOUId) {
         if let Some(claims) = self.claims.get_mut(utxo_id) {
+            for loser in claims.iter().filter(|c| c.spending_iou_id() != winning_iou_id) {
+                self.ious.remove(loser.spending_iou_id());
+            }
             claims.retain(|c| c.spending_iou_id() == winning_iou_id);
             if claims.len() <= 1 {
                 // No longer a conflict
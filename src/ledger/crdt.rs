@@ -9,12 +9,38 @@ use std::hash::Hash;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Result of a merge operation
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MergeResult {
     /// Number of new entries added during merge
     pub new_entries: usize,
     /// Total entries after merge
     pub total_after_merge: usize,
+    /// Entries from the other side dropped because a checkpoint already pruned them
+    pub resurrections_prevented: usize,
+    /// Number of incoming entries whose signature was checked
+    pub verified_entries: usize,
+    /// Number of incoming entries skipped because they failed verification
+    pub rejected_entries: usize,
+    /// The skipped entries and why, in IOU ID order
+    pub rejected: Vec<RejectedEntry>,
+}
+
+/// Why an incoming entry failed verification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryRejection {
+    /// The IOU's sender DID doesn't encode a public key
+    InvalidSenderDid,
+    /// The entry's sender key isn't the one in the sender DID
+    SenderKeyMismatch,
+    /// The signature doesn't verify against the sender DID's key
+    InvalidSignature,
+}
+
+/// An incoming entry a merge refused to add
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RejectedEntry {
+    pub iou_id: IOUId,
+    pub reason: EntryRejection,
 }
 
 /// G-Set (Grow-only Set) - A CRDT where elements can only be added, never removed
@@ -98,9 +124,18 @@ where
         MergeResult {
             new_entries: after - before,
             total_after_merge: after,
+            ..MergeResult::default()
         }
     }
 
+    /// Remove elements failing `keep`
+    ///
+    /// Breaks the grow-only guarantee, so only for dropping entries that lost a conflict
+    /// or were pruned by a checkpoint.
+    pub(crate) fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        self.elements.retain(keep);
+    }
+
     /// Get elements that are in this set but not in other (for efficient sync)
     pub fn delta(&self, other: &GSet<T>) -> GSet<T> {
         let mut delta = GSet::new();
@@ -208,6 +243,22 @@ impl IOUEntry {
     pub fn verify(&self) -> bool {
         self.iou.verify(&self.sender_pubkey)
     }
+
+    /// Verify the IOU signature against the key in its sender DID
+    ///
+    /// Unlike `verify`, this doesn't trust the attached `sender_pubkey`, which must
+    /// match the DID.
+    pub fn check_signature(&self) -> Result<(), EntryRejection> {
+        let sender_key = self.iou.iou().sender().public_key()
+            .map_err(|_| EntryRejection::InvalidSenderDid)?;
+        if sender_key != self.sender_pubkey {
+            return Err(EntryRejection::SenderKeyMismatch);
+        }
+        if !self.iou.verify(&sender_key) {
+            return Err(EntryRejection::InvalidSignature);
+        }
+        Ok(())
+    }
 }
 
 impl PartialEq for IOUEntry {
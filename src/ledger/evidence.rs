@@ -0,0 +1,201 @@
+// Conflict Evidence - signed, self-contained proof of a double-spend
+//
+// A detector that sees two IOUs spend the same source exports both signed IOUs,
+// the contested claim and when each was first seen, and signs the bundle. Support
+// staff or a gateway can check it offline with the sender's public key: the
+// sender's signatures prove both IOUs are genuine, and the detector's signature
+// vouches for the UTXO claim. A reused nonce needs no such trust, since both
+// IOUs carry it.
+
+use crate::identity::{Did, KeySigner, PublicKey, Signature, Signer};
+use crate::iou::SignedIOU;
+use crate::ledger::conflict::ConflictError;
+use crate::vault::UTXOId;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Domain separator so an evidence signature can never be replayed as another message
+const EVIDENCE_DOMAIN: &[u8] = b"p2pmesh:evidence:v1";
+
+/// Domain separator for the claim ID of a sender's nonce
+const NONCE_SLOT_DOMAIN: &[u8] = b"p2pmesh:nonce-slot:v1";
+
+/// The claim ID two IOUs from `sender` with the same `nonce` both spend
+pub fn nonce_slot(sender: &Did, nonce: u64) -> UTXOId {
+    let sender = sender.to_string();
+    let mut hasher = Sha256::new();
+    hasher.update(NONCE_SLOT_DOMAIN);
+    hasher.update((sender.len() as u32).to_le_bytes());
+    hasher.update(sender.as_bytes());
+    hasher.update(nonce.to_le_bytes());
+    UTXOId::from_bytes(hasher.finalize().into())
+}
+
+/// Two IOUs spending the same source, signed by the detector that saw them
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConflictEvidence {
+    /// The contested UTXO, or the sender's nonce slot (see [`nonce_slot`])
+    utxo_id: UTXOId,
+    /// The IOU seen spending it first
+    first: SignedIOU,
+    /// The conflicting IOU
+    second: SignedIOU,
+    /// When the detector first saw each IOU (Unix milliseconds)
+    first_seen: u64,
+    second_seen: u64,
+    /// Key of the detector vouching for the claim
+    detector: PublicKey,
+    signature: Signature,
+}
+
+impl ConflictEvidence {
+    /// Bundle two conflicting IOUs and sign them as the detector holding `signer`
+    pub fn sign<S: KeySigner + ?Sized>(
+        utxo_id: UTXOId,
+        first: SignedIOU,
+        first_seen: u64,
+        second: SignedIOU,
+        second_seen: u64,
+        signer: &S,
+    ) -> Self {
+        let mut evidence = Self {
+            utxo_id,
+            first,
+            second,
+            first_seen,
+            second_seen,
+            detector: signer.public_key(),
+            signature: Signature::from_bytes(&[0u8; 64]).expect("64 bytes"),
+        };
+        evidence.signature = signer.sign(&evidence.to_signing_bytes());
+        evidence
+    }
+
+    /// The contested UTXO or nonce slot
+    pub fn utxo_id(&self) -> &UTXOId {
+        &self.utxo_id
+    }
+
+    /// The IOU seen spending the source first
+    pub fn first(&self) -> &SignedIOU {
+        &self.first
+    }
+
+    /// The IOU conflicting with it
+    pub fn second(&self) -> &SignedIOU {
+        &self.second
+    }
+
+    /// When the detector first saw the first IOU (Unix milliseconds)
+    pub fn first_seen(&self) -> u64 {
+        self.first_seen
+    }
+
+    /// When the detector first saw the second IOU (Unix milliseconds)
+    pub fn second_seen(&self) -> u64 {
+        self.second_seen
+    }
+
+    /// Key of the detector that signed the evidence
+    pub fn detector(&self) -> &PublicKey {
+        &self.detector
+    }
+
+    /// The detector's signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Whether the contested source is the sender's nonce, reused by both IOUs
+    pub fn is_nonce_reuse(&self) -> bool {
+        let first = self.first.iou();
+        first.nonce() == self.second.iou().nonce()
+            && self.utxo_id == nonce_slot(first.sender(), first.nonce())
+    }
+
+    /// Get the bytes the detector signs
+    pub fn to_signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(EVIDENCE_DOMAIN);
+        bytes.extend_from_slice(self.utxo_id.as_bytes());
+        bytes.extend_from_slice(self.first.id().as_bytes());
+        bytes.extend_from_slice(self.second.id().as_bytes());
+        bytes.extend_from_slice(&self.first_seen.to_le_bytes());
+        bytes.extend_from_slice(&self.second_seen.to_le_bytes());
+        bytes.extend_from_slice(self.detector.as_bytes());
+        bytes
+    }
+
+    /// Serialize to bytes (postcard)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes (postcard)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConflictError> {
+        postcard::from_bytes(bytes).map_err(|_| ConflictError::DeserializationFailed)
+    }
+
+    /// Human-readable JSON for support staff; binary fields are hex and each IOU
+    /// is included as its postcard encoding, so the evidence can be rebuilt from it
+    pub fn to_json(&self) -> String {
+        let iou = |signed: &SignedIOU, seen: u64| {
+            let body = signed.iou();
+            format!(
+                r#"{{"id":"{}","sender":"{}","nonce":{},"amount":{},"timestamp":{},"firstSeen":{},"bytes":"{}"}}"#,
+                hex::encode(signed.id().as_bytes()),
+                body.sender(),
+                body.nonce(),
+                body.amount(),
+                body.timestamp(),
+                seen,
+                hex::encode(postcard::to_allocvec(signed).unwrap_or_default()),
+            )
+        };
+        format!(
+            r#"{{"utxoId":"{}","nonceReuse":{},"first":{},"second":{},"detector":"{}","signature":"{}"}}"#,
+            hex::encode(self.utxo_id.as_bytes()),
+            self.is_nonce_reuse(),
+            iou(&self.first, self.first_seen),
+            iou(&self.second, self.second_seen),
+            hex::encode(self.detector.as_bytes()),
+            hex::encode(self.signature.as_bytes()),
+        )
+    }
+}
+
+/// Check that `evidence` proves `sender_pubkey` signed two IOUs spending one source
+///
+/// Both IOUs must be distinct, from the sender and signed by it, and the detector's
+/// signature must cover the bundle. For a reused nonce the IOUs alone prove the
+/// double spend; for a UTXO the verifier trusts the detector's claim.
+pub fn verify_evidence(evidence: &ConflictEvidence, sender_pubkey: &PublicKey) -> Result<(), ConflictError> {
+    let invalid = |reason: &'static str| Err(ConflictError::InvalidEvidence(reason));
+    if evidence.first.id() == evidence.second.id() {
+        return invalid("both IOUs are the same");
+    }
+
+    let sender = Did::from_public_key(sender_pubkey);
+    for iou in [&evidence.first, &evidence.second] {
+        if iou.iou().sender() != &sender {
+            return invalid("IOU not sent by the sender");
+        }
+        if !iou.verify(sender_pubkey) {
+            return invalid("IOU signature does not verify");
+        }
+    }
+
+    if !Signer::verify(&evidence.detector, &evidence.to_signing_bytes(), &evidence.signature) {
+        return invalid("detector signature does not verify");
+    }
+
+    // A nonce slot is checkable from the IOUs, so it must match both of them
+    let first = evidence.first.iou();
+    let second = evidence.second.iou();
+    let is_slot = |nonce| evidence.utxo_id == nonce_slot(&sender, nonce);
+    if (is_slot(first.nonce()) || is_slot(second.nonce())) && first.nonce() != second.nonce()
+    {
+        return invalid("nonce slot does not match both IOUs");
+    }
+    Ok(())
+}
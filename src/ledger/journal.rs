@@ -0,0 +1,116 @@
+// State Journal - Incremental persistence of a mesh state
+//
+// Writing the whole state on every change costs time proportional to its size.
+// Instead a node takes a save point after each write, then persists only what
+// changed since (`MeshState::changes_since`). On load the deltas are replayed
+// on the last full write, and every so often the state is written whole again
+// and the deltas dropped. A delta records the shape of the state it was taken
+// against, so one replayed out of order is refused rather than misapplied.
+
+use crate::iou::{IOUId, SignedCancellation};
+use crate::ledger::checkpoint::CheckpointId;
+use crate::ledger::crdt::IOUEntry;
+use crate::ledger::state::MeshStateError;
+use crate::ledger::version::VersionVector;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Where a state stood when it was last persisted
+///
+/// Holds IDs only: the log position, the logged entries no longer held, and the
+/// cancellations, checkpoints and pruned and settled IOUs known at the time.
+#[derive(Clone, Debug, Default)]
+pub struct SavePoint {
+    pub(crate) log_len: u64,
+    pub(crate) version: u64,
+    pub(crate) clock: VersionVector,
+    pub(crate) removed: HashSet<IOUId>,
+    pub(crate) cancellations: HashSet<IOUId>,
+    pub(crate) checkpoints: HashSet<CheckpointId>,
+    pub(crate) pruned: HashSet<IOUId>,
+    pub(crate) settled: HashSet<IOUId>,
+}
+
+impl SavePoint {
+    /// Sync cursor of the state at the save point
+    pub fn cursor(&self) -> u64 {
+        self.log_len
+    }
+
+    /// Version of the state at the save point
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+/// Everything that changed in a state since a save point
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StateDelta {
+    /// Log length and version of the state this delta applies to
+    pub(crate) base_log_len: u64,
+    pub(crate) base_version: u64,
+    /// IDs appended to the log, in order
+    pub(crate) log: Vec<IOUId>,
+    /// The logged entries still held
+    pub(crate) entries: Vec<IOUEntry>,
+    /// Previously held entries since pruned, frozen or dropped
+    pub(crate) removed: Vec<IOUId>,
+    pub(crate) cancellations: Vec<SignedCancellation>,
+    pub(crate) checkpoints: Vec<CheckpointId>,
+    pub(crate) pruned: Vec<IOUId>,
+    pub(crate) settled: Vec<IOUId>,
+    /// Version after the changes, and the version vector if it changed
+    pub(crate) version: u64,
+    pub(crate) clock: Option<VersionVector>,
+    /// Merkle root after the changes
+    pub(crate) root: [u8; 32],
+}
+
+impl StateDelta {
+    /// Whether nothing changed, so there is nothing to persist
+    pub fn is_empty(&self) -> bool {
+        self.log.is_empty()
+            && self.removed.is_empty()
+            && self.cancellations.is_empty()
+            && self.checkpoints.is_empty()
+            && self.pruned.is_empty()
+            && self.settled.is_empty()
+            && self.version == self.base_version
+            && self.clock.is_none()
+    }
+
+    /// New entries carried by the delta
+    pub fn entries(&self) -> &[IOUEntry] {
+        &self.entries
+    }
+
+    /// IDs of entries the delta removes
+    pub fn removed(&self) -> &[IOUId] {
+        &self.removed
+    }
+
+    /// Version of the state the delta applies to
+    pub fn base_version(&self) -> u64 {
+        self.base_version
+    }
+
+    /// Version after the delta is applied
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Merkle root the state should have once the delta is applied
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Serialize to bytes (postcard)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes (postcard)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MeshStateError> {
+        postcard::from_bytes(bytes).map_err(|_| MeshStateError::DeserializationFailed)
+    }
+}
@@ -1,13 +1,38 @@
 // Ledger module - THE SHARED HISTORY
 // Handles distributed state, CRDT, and conflict detection
 
+mod bloom;
+mod checkpoint;
 mod conflict;
 mod crdt;
+mod evidence;
+mod journal;
+mod query;
+mod snapshot;
 mod state;
+mod summary;
+mod version;
 
+pub use bloom::{BloomError, BloomSummary, MAX_BLOOM_BYTES, MAX_BLOOM_HASHES};
+pub use checkpoint::{Checkpoint, CheckpointId, SignedCheckpoint};
 pub use conflict::{
-    ConflictDetector, ConflictError, ConflictResolution, ConflictType,
-    DetectorMergeResult, SpendingClaim,
+    ConflictDetector, ConflictError, ConflictPolicy, ConflictResolution, ConflictType,
+    DetectorMergeResult, DropReason, DroppedEntry, ResolutionStrategy, ResolvedConflict,
+    SpendingClaim,
 };
-pub use crdt::{GSet, GSetError, IOUEntry, MergeResult};
-pub use state::{MeshState, MeshStateError, MeshStatistics, NodeId};
+pub use crdt::{EntryRejection, GSet, GSetError, IOUEntry, MergeResult, RejectedEntry};
+pub use evidence::{nonce_slot, verify_evidence, ConflictEvidence};
+pub use journal::{SavePoint, StateDelta};
+pub use query::MeshQuery;
+pub use snapshot::{
+    CompactionStats, SnapshotError, SnapshotSegment, StateSnapshot, SNAPSHOT_BLOOM_FP_RATE,
+};
+pub use state::{
+    MergePolicy, MeshState, MeshStateError, MeshStatistics, NodeId, PartyTotals, PolicyMergeResult,
+    STATE_MAGIC, STATE_VERSION,
+};
+pub use summary::{
+    Delta, StateSummary, SummaryError, MAX_SUMMARY_BUCKETS, SUMMARY_BUCKET_HASH_LEN,
+    SUMMARY_TARGET_BUCKET_LOAD,
+};
+pub use version::{CausalRelation, VersionVector};
@@ -0,0 +1,114 @@
+// Mesh Query - filtered views over the shared ledger
+//
+// A query starts from whichever secondary index narrows it most (sender,
+// recipient or timestamp) and filters the rest, so a UI listing one party's
+// recent IOUs never walks the whole G-Set.
+
+use crate::identity::Did;
+use crate::ledger::crdt::IOUEntry;
+use crate::ledger::state::MeshState;
+
+/// Filters over a `MeshState`, built with [`MeshState::query`]
+///
+/// Iterating yields matching entries oldest first (ties in IOU ID order).
+/// `after` is inclusive and `before` exclusive, like `MeshState::ious_between`.
+#[derive(Clone, Debug)]
+pub struct MeshQuery<'a> {
+    state: &'a MeshState,
+    pub(crate) sender: Option<Did>,
+    pub(crate) recipient: Option<Did>,
+    pub(crate) after: Option<u64>,
+    pub(crate) before: Option<u64>,
+    pub(crate) min_amount: Option<u64>,
+    pub(crate) unsettled_only: bool,
+}
+
+impl<'a> MeshQuery<'a> {
+    pub(crate) fn new(state: &'a MeshState) -> Self {
+        Self {
+            state,
+            sender: None,
+            recipient: None,
+            after: None,
+            before: None,
+            min_amount: None,
+            unsettled_only: false,
+        }
+    }
+
+    /// Only IOUs sent by `sender`
+    pub fn sender(mut self, sender: Did) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Only IOUs paying `recipient` (any output of a multi-output IOU)
+    pub fn recipient(mut self, recipient: Did) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+
+    /// Only IOUs timestamped at or after `timestamp` (Unix seconds)
+    pub fn after(mut self, timestamp: u64) -> Self {
+        self.after = Some(timestamp);
+        self
+    }
+
+    /// Only IOUs timestamped before `timestamp` (Unix seconds)
+    pub fn before(mut self, timestamp: u64) -> Self {
+        self.before = Some(timestamp);
+        self
+    }
+
+    /// Only IOUs worth at least `amount`
+    ///
+    /// With a recipient filter this is the recipient's output, otherwise the IOU total.
+    pub fn min_amount(mut self, amount: u64) -> Self {
+        self.min_amount = Some(amount);
+        self
+    }
+
+    /// Skip IOUs marked settled with `MeshState::mark_settled`
+    pub fn unsettled_only(mut self) -> Self {
+        self.unsettled_only = true;
+        self
+    }
+
+    /// Whether `entry` passes every filter
+    pub(crate) fn matches(&self, entry: &IOUEntry) -> bool {
+        let iou = entry.iou().iou();
+        if self.sender.as_ref().is_some_and(|sender| iou.sender() != sender) {
+            return false;
+        }
+        if let Some(recipient) = &self.recipient {
+            if !iou.recipients().contains(&recipient) {
+                return false;
+            }
+        }
+        if self.after.is_some_and(|after| iou.timestamp() < after) {
+            return false;
+        }
+        if self.before.is_some_and(|before| iou.timestamp() >= before) {
+            return false;
+        }
+        if let Some(min_amount) = self.min_amount {
+            let amount = match &self.recipient {
+                Some(recipient) => iou.amount_for(recipient),
+                None => iou.amount(),
+            };
+            if amount < min_amount {
+                return false;
+            }
+        }
+        !(self.unsettled_only && self.state.is_settled(&entry.id()))
+    }
+}
+
+impl<'a> IntoIterator for MeshQuery<'a> {
+    type Item = &'a IOUEntry;
+    type IntoIter = std::vec::IntoIter<&'a IOUEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.state.run_query(&self).into_iter()
+    }
+}
@@ -0,0 +1,235 @@
+// State Snapshot - Compressed, hash-chained segments of old entries
+//
+// A long-running node freezes entries older than a cutoff into a segment:
+// the entries are deflate-compressed and never decoded on load, so startup
+// only pays for the live set. Each segment commits to the previous one, so a
+// truncated or edited chain is caught before it is attached to a state.
+// A bloom filter of each segment's IOU IDs lets merges skip entries that are
+// already frozen without decompressing anything.
+
+use crate::iou::IOUId;
+use crate::ledger::bloom::BloomSummary;
+use crate::ledger::crdt::IOUEntry;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Domain separator for segment hashing
+const SEGMENT_DOMAIN: &[u8] = b"p2pmesh:snapshot:v1";
+
+/// False-positive rate of each segment's ID filter
+pub const SNAPSHOT_BLOOM_FP_RATE: f64 = 0.0001;
+
+/// Deflate level used for segments (0-10)
+const SEGMENT_COMPRESSION_LEVEL: u8 = 6;
+
+/// Errors from snapshot handling
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    #[error("Broken hash chain at segment {0}")]
+    BrokenChain(usize),
+
+    #[error("Segment {0} failed to decompress")]
+    Decompression(usize),
+
+    #[error("Deserialization failed")]
+    DeserializationFailed,
+}
+
+/// Frozen entries older than a cutoff, compressed and chained to the previous segment
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSegment {
+    /// Hash of the previous segment (all zeros for the first)
+    prev_hash: [u8; 32],
+    /// Every entry in the segment has an IOU timestamp below this
+    cutoff: u64,
+    /// Number of entries in the segment
+    entry_count: u64,
+    /// Size of the entries before compression
+    raw_len: u64,
+    /// Deflate-compressed postcard encoding of the entries, in ID order
+    data: Vec<u8>,
+    /// IDs of the entries in the segment
+    ids: BloomSummary,
+    /// Hash over all of the above
+    hash: [u8; 32],
+}
+
+impl SnapshotSegment {
+    /// Freeze `entries` (all older than `cutoff`) into a segment following `prev_hash`
+    fn seal(prev_hash: [u8; 32], cutoff: u64, mut entries: Vec<IOUEntry>) -> Self {
+        entries.sort_by_cached_key(|entry| *entry.id().as_bytes());
+        let mut ids = BloomSummary::with_capacity(entries.len(), SNAPSHOT_BLOOM_FP_RATE);
+        for entry in &entries {
+            ids.insert(&entry.id());
+        }
+        let raw = postcard::to_allocvec(&entries).unwrap_or_default();
+        let data = miniz_oxide::deflate::compress_to_vec(&raw, SEGMENT_COMPRESSION_LEVEL);
+
+        let mut segment = Self {
+            prev_hash,
+            cutoff,
+            entry_count: entries.len() as u64,
+            raw_len: raw.len() as u64,
+            data,
+            ids,
+            hash: [0u8; 32],
+        };
+        segment.hash = segment.compute_hash();
+        segment
+    }
+
+    fn compute_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(SEGMENT_DOMAIN);
+        hasher.update(self.prev_hash);
+        hasher.update(self.cutoff.to_le_bytes());
+        hasher.update(self.entry_count.to_le_bytes());
+        hasher.update(self.raw_len.to_le_bytes());
+        hasher.update(Sha256::digest(&self.data));
+        hasher.update(Sha256::digest(self.ids.to_bytes()));
+        hasher.finalize().into()
+    }
+
+    /// Hash of this segment, which the next segment chains to
+    pub fn hash(&self) -> &[u8; 32] {
+        &self.hash
+    }
+
+    /// Hash of the previous segment
+    pub fn prev_hash(&self) -> &[u8; 32] {
+        &self.prev_hash
+    }
+
+    /// Every entry in the segment is older than this
+    pub fn cutoff(&self) -> u64 {
+        self.cutoff
+    }
+
+    /// Number of entries in the segment
+    pub fn entry_count(&self) -> u64 {
+        self.entry_count
+    }
+
+    /// Size of the entries before compression
+    pub fn raw_len(&self) -> u64 {
+        self.raw_len
+    }
+
+    /// Size of the entries after compression
+    pub fn compressed_len(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    /// Whether the segment may hold an IOU with this ID and timestamp.
+    /// False positives are possible; false negatives aren't.
+    pub fn may_contain(&self, iou_id: &IOUId, timestamp: u64) -> bool {
+        timestamp < self.cutoff && self.ids.contains(iou_id)
+    }
+}
+
+/// Hash-chained segments of frozen entries, oldest first
+///
+/// Produced by [`MeshState::snapshot`](crate::ledger::MeshState::snapshot) and
+/// stored next to the live state's `to_bytes`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    segments: Vec<SnapshotSegment>,
+}
+
+impl StateSnapshot {
+    /// Create an empty snapshot
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The segments, oldest first
+    pub fn segments(&self) -> &[SnapshotSegment] {
+        &self.segments
+    }
+
+    /// Check if no entries are frozen
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Total entries across all segments
+    pub fn entry_count(&self) -> u64 {
+        self.segments.iter().map(|s| s.entry_count).sum()
+    }
+
+    /// Hash of the newest segment (all zeros when empty)
+    pub fn head_hash(&self) -> [u8; 32] {
+        self.segments.last().map_or([0u8; 32], |s| s.hash)
+    }
+
+    /// Whether a segment may hold this IOU (see [`SnapshotSegment::may_contain`])
+    pub fn may_contain(&self, iou_id: &IOUId, timestamp: u64) -> bool {
+        self.segments.iter().any(|s| s.may_contain(iou_id, timestamp))
+    }
+
+    /// Freeze `entries` older than `cutoff` into a new segment chained to the head
+    pub(crate) fn push(&mut self, cutoff: u64, entries: Vec<IOUEntry>) {
+        let segment = SnapshotSegment::seal(self.head_hash(), cutoff, entries);
+        self.segments.push(segment);
+    }
+
+    /// Check every segment's hash and its link to the one before
+    pub fn verify_chain(&self) -> Result<(), SnapshotError> {
+        let mut prev_hash = [0u8; 32];
+        for (index, segment) in self.segments.iter().enumerate() {
+            if segment.prev_hash != prev_hash || segment.compute_hash() != segment.hash {
+                return Err(SnapshotError::BrokenChain(index));
+            }
+            prev_hash = segment.hash;
+        }
+        Ok(())
+    }
+
+    /// Decompress every frozen entry, oldest segment first
+    pub fn entries(&self) -> Result<Vec<IOUEntry>, SnapshotError> {
+        let mut entries = Vec::with_capacity(self.entry_count() as usize);
+        for (index, segment) in self.segments.iter().enumerate() {
+            let raw = miniz_oxide::inflate::decompress_to_vec_with_limit(&segment.data, segment.raw_len as usize)
+                .map_err(|_| SnapshotError::Decompression(index))?;
+            let decoded: Vec<IOUEntry> =
+                postcard::from_bytes(&raw).map_err(|_| SnapshotError::Decompression(index))?;
+            entries.extend(decoded);
+        }
+        Ok(entries)
+    }
+
+    /// Serialize to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        postcard::from_bytes(bytes).map_err(|_| SnapshotError::DeserializationFailed)
+    }
+}
+
+/// Space saved by freezing entries, from [`MeshState::compaction_stats`](crate::ledger::MeshState::compaction_stats)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompactionStats {
+    /// Number of snapshot segments
+    pub segments: usize,
+    /// Entries frozen in the snapshot
+    pub frozen_entries: u64,
+    /// Entries still in the live set
+    pub live_entries: usize,
+    /// Size of the frozen entries before compression
+    pub raw_bytes: u64,
+    /// Size of the frozen entries after compression
+    pub compressed_bytes: u64,
+    /// Size of the live state's `to_bytes`
+    pub live_bytes: u64,
+}
+
+impl CompactionStats {
+    /// Bytes saved by compressing the frozen entries
+    pub fn bytes_saved(&self) -> u64 {
+        self.raw_bytes.saturating_sub(self.compressed_bytes)
+    }
+}
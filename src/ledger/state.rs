@@ -1,13 +1,27 @@
 // Mesh State - Tracks the current state of the distributed ledger
 
 use crate::identity::{Did, PublicKey};
-use crate::iou::{IOUId, IOUValidator, SignedIOU};
-use crate::ledger::crdt::{GSet, IOUEntry, MergeResult};
+use crate::iou::{IOUId, IOUValidator, SignedCancellation, SignedIOU};
+use crate::ledger::bloom::BloomSummary;
+use crate::ledger::checkpoint::{CheckpointId, SignedCheckpoint};
+use crate::ledger::conflict::{ConflictDetector, ConflictPolicy, DropReason, DroppedEntry};
+use crate::ledger::crdt::{GSet, IOUEntry, MergeResult, RejectedEntry};
+use crate::ledger::journal::{SavePoint, StateDelta};
+use crate::ledger::query::MeshQuery;
+use crate::ledger::snapshot::{CompactionStats, SnapshotError, StateSnapshot};
+use crate::ledger::summary::{Delta, StateSummary, SUMMARY_TARGET_BUCKET_LOAD};
+use crate::ledger::version::{CausalRelation, VersionVector};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use thiserror::Error;
 
+/// Magic bytes at the start of `MeshState::to_bytes`
+pub const STATE_MAGIC: &[u8; 4] = b"P2PS";
+
+/// Current `MeshState::to_bytes` format version, after the magic
+pub const STATE_VERSION: u8 = 1;
+
 /// Unique identifier for a node in the mesh
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NodeId([u8; 32]);
@@ -57,6 +71,30 @@ pub enum MeshStateError {
 
     #[error("Deserialization failed")]
     DeserializationFailed,
+
+    #[error("Unsupported state format version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("IOU was cancelled by its sender")]
+    Cancelled,
+
+    #[error("Invalid signature on cancellation")]
+    InvalidCancellation,
+
+    #[error("Cancellation was not issued by the IOU's sender")]
+    CancellationSenderMismatch,
+
+    #[error("IOU was settled and pruned by a checkpoint")]
+    Pruned,
+
+    #[error("Invalid signature on checkpoint")]
+    InvalidCheckpoint,
+
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(#[from] SnapshotError),
+
+    #[error("Delta applies to version {expected}, state is at version {actual}")]
+    DeltaBaseMismatch { expected: u64, actual: u64 },
 }
 
 /// Statistics about the mesh state
@@ -66,6 +104,47 @@ pub struct MeshStatistics {
     pub unique_senders: usize,
     pub unique_recipients: usize,
     pub total_value: u64,
+    /// Sent and received totals for every sender and recipient
+    pub party_totals: HashMap<Did, PartyTotals>,
+}
+
+/// Value one party has sent and received across the mesh state
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PartyTotals {
+    /// Total of IOUs the party sent
+    pub sent: u64,
+    /// Total of outputs paying the party
+    pub received: u64,
+}
+
+/// Result of [`MeshState::merge_with_policy`]
+#[derive(Clone, Debug)]
+pub struct PolicyMergeResult {
+    /// Number of entries from the other state that were added and kept
+    pub new_entries: usize,
+    /// Total entries after merge and conflict resolution
+    pub total_after_merge: usize,
+    /// IOUs removed by the policy, in UTXO ID order
+    pub dropped: Vec<DroppedEntry>,
+    /// Incoming entries that failed verification, in IOU ID order
+    pub rejected: Vec<RejectedEntry>,
+}
+
+/// Which incoming entries a merge re-verifies before accepting them
+///
+/// A peer's state can't be trusted: its entries may never have been checked by
+/// the peer, or may be forged outright.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Verify every incoming entry, even ones this node already holds, so a peer
+    /// sending forged copies is always reported
+    VerifyAll,
+    /// Verify only entries this node doesn't hold yet; the cost grows with the
+    /// delta, not the size of either state
+    #[default]
+    VerifyNew,
+    /// Accept incoming entries unchecked, for constrained devices that trust their peers
+    Trust,
 }
 
 /// The shared mesh state - contains all known IOUs across the network
@@ -73,7 +152,7 @@ pub struct MeshStatistics {
 pub struct MeshState {
     /// This node's unique ID
     node_id: NodeId,
-    /// G-Set of all known IOUs (grows only, never shrinks)
+    /// G-Set of all known IOUs (only `merge_with_policy` and checkpoints ever remove entries)
     ious: GSet<IOUEntry>,
     /// Index: IOU ID -> IOUEntry for fast lookup
     #[serde(skip)]
@@ -84,8 +163,37 @@ pub struct MeshState {
     /// Index: Recipient DID -> list of IOU IDs
     #[serde(skip)]
     recipient_index: HashMap<Did, Vec<IOUId>>,
+    /// Index: IOU timestamp -> list of IOU IDs, for time-range queries
+    #[serde(skip)]
+    time_index: BTreeMap<u64, Vec<IOUId>>,
     /// Version counter (logical clock)
     version: u64,
+    /// Sender cancellations by IOU ID (grows only, like `ious`)
+    cancellations: HashMap<IOUId, SignedCancellation>,
+    /// IOU IDs in the order this node learned of them; position + 1 is the sync cursor
+    log: Vec<IOUId>,
+    /// Checkpoints applied to this state
+    checkpoints: HashSet<CheckpointId>,
+    /// IOU IDs pruned by a checkpoint; merges never add them back
+    pruned: HashSet<IOUId>,
+    /// IOU IDs this node knows are settled but not yet pruned; not shared on merge
+    settled: HashSet<IOUId>,
+    /// Entries originated per node, as far as this state has seen
+    clock: VersionVector,
+    /// Which incoming entries merges verify; node config, so not persisted
+    #[serde(skip)]
+    merge_policy: MergePolicy,
+    /// Old entries frozen out of the live set; stored apart from `to_bytes`
+    #[serde(skip)]
+    snapshot: StateSnapshot,
+}
+
+/// Mesh state as serialized before cancellations were tracked
+#[derive(Deserialize)]
+struct PreCancellationMeshState {
+    node_id: NodeId,
+    ious: GSet<IOUEntry>,
+    version: u64,
 }
 
 impl MeshState {
@@ -97,7 +205,16 @@ impl MeshState {
             iou_index: HashMap::new(),
             sender_index: HashMap::new(),
             recipient_index: HashMap::new(),
+            time_index: BTreeMap::new(),
             version: 0,
+            cancellations: HashMap::new(),
+            log: Vec::new(),
+            checkpoints: HashSet::new(),
+            pruned: HashSet::new(),
+            settled: HashSet::new(),
+            clock: VersionVector::new(),
+            merge_policy: MergePolicy::default(),
+            snapshot: StateSnapshot::new(),
         }
     }
 
@@ -106,6 +223,17 @@ impl MeshState {
         &self.node_id
     }
 
+    /// Which incoming entries merges verify
+    pub fn merge_policy(&self) -> MergePolicy {
+        self.merge_policy
+    }
+
+    /// Set which incoming entries merges verify.
+    /// The policy isn't persisted, so set it again after loading a state.
+    pub fn set_merge_policy(&mut self, policy: MergePolicy) {
+        self.merge_policy = policy;
+    }
+
     /// Check if the state is empty
     pub fn is_empty(&self) -> bool {
         self.ious.is_empty()
@@ -121,23 +249,70 @@ impl MeshState {
         self.version
     }
 
+    /// Entries originated per node that this state has seen
+    pub fn clock(&self) -> &VersionVector {
+        &self.clock
+    }
+
+    /// How this state relates to `other` by version vector
+    ///
+    /// `Ahead` and `Equal` mean `other` has nothing this state lacks, so there is
+    /// no point pulling from it; `Behind` and `Equal` mean there is nothing to push.
+    pub fn compare(&self, other: &MeshState) -> CausalRelation {
+        self.clock.compare(&other.clock)
+    }
+
+    /// Replace the version vector of a state built to carry a peer's entries
+    pub(crate) fn set_clock(&mut self, clock: VersionVector) {
+        self.clock = clock;
+    }
+
     /// Check if an IOU is in the state
     pub fn has_iou(&self, iou_id: &IOUId) -> bool {
         self.iou_index.contains_key(iou_id)
     }
 
     /// Add an IOU to the mesh state
+    ///
+    /// For newly accepted IOUs: one past its expiry is refused. Entries copied
+    /// from a peer's ledger go through `add_replicated_iou` instead.
     pub fn add_iou(&mut self, iou: SignedIOU, sender_pubkey: &PublicKey) -> Result<(), MeshStateError> {
+        self.insert_iou(iou, sender_pubkey, true)
+    }
+
+    /// Add an IOU copied from another node's ledger
+    ///
+    /// Validated like `add_iou` except for expiry, so an IOU that expired after
+    /// it was accepted still replicates and every node's ledger converges.
+    pub fn add_replicated_iou(&mut self, iou: SignedIOU, sender_pubkey: &PublicKey) -> Result<(), MeshStateError> {
+        self.insert_iou(iou, sender_pubkey, false)
+    }
+
+    fn insert_iou(&mut self, iou: SignedIOU, sender_pubkey: &PublicKey, check_expiry: bool) -> Result<(), MeshStateError> {
         let iou_id = iou.id();
 
-        // Check for duplicate
-        if self.iou_index.contains_key(&iou_id) {
+        // Check for duplicate, including entries frozen in the snapshot
+        if self.iou_index.contains_key(&iou_id) || self.is_frozen(&iou_id, iou.iou().timestamp()) {
             return Err(MeshStateError::DuplicateIOU);
         }
 
+        // A known cancellation means the recipient must not accept it
+        if self.is_cancelled(&iou_id) {
+            return Err(MeshStateError::Cancelled);
+        }
+
+        // Settled and pruned; accepting it again would undo the checkpoint
+        if self.pruned.contains(&iou_id) {
+            return Err(MeshStateError::Pruned);
+        }
+
         // Validate signature
-        IOUValidator::validate(&iou, sender_pubkey)
-            .map_err(|e| MeshStateError::ValidationFailed(e.to_string()))?;
+        let validated = if check_expiry {
+            IOUValidator::validate(&iou, sender_pubkey)
+        } else {
+            IOUValidator::validate_replicated(&iou, sender_pubkey)
+        };
+        validated.map_err(|e| MeshStateError::ValidationFailed(e.to_string()))?;
 
         // Create entry
         let entry = IOUEntry::new(iou.clone(), sender_pubkey.clone());
@@ -147,13 +322,137 @@ impl MeshState {
 
         // Update indexes
         self.index_entry(&entry);
+        self.log.push(iou_id);
 
         // Increment version
         self.version += 1;
+        self.clock.increment(&self.node_id);
 
         Ok(())
     }
 
+    /// Record a sender's cancellation so it spreads with the next merge
+    ///
+    /// Returns `Ok(false)` if the IOU was already cancelled. An IOU already in the
+    /// state stays there; whoever accepted it first keeps it, and `is_cancelled`
+    /// tells everyone else not to.
+    pub fn add_cancellation(&mut self, cancellation: SignedCancellation) -> Result<bool, MeshStateError> {
+        if !cancellation.verify_sender() {
+            return Err(MeshStateError::InvalidCancellation);
+        }
+        let iou_id = cancellation.cancellation().iou_id().clone();
+        if let Some(entry) = self.iou_index.get(&iou_id) {
+            if !cancellation.cancels(entry.iou()) {
+                return Err(MeshStateError::CancellationSenderMismatch);
+            }
+        }
+        if self.cancellations.contains_key(&iou_id) {
+            return Ok(false);
+        }
+
+        self.cancellations.insert(iou_id, cancellation);
+        self.version += 1;
+        Ok(true)
+    }
+
+    /// Check whether the sender has cancelled an IOU
+    pub fn is_cancelled(&self, iou_id: &IOUId) -> bool {
+        self.cancellations.contains_key(iou_id)
+    }
+
+    /// Get the cancellation for an IOU, if any
+    pub fn get_cancellation(&self, iou_id: &IOUId) -> Option<&SignedCancellation> {
+        self.cancellations.get(iou_id)
+    }
+
+    /// Number of known cancellations
+    pub fn cancellation_count(&self) -> usize {
+        self.cancellations.len()
+    }
+
+    /// Take cancellations `other` knows and this state doesn't. Returns how many were new.
+    fn merge_cancellations(&mut self, other: &MeshState) -> usize {
+        let mut added = 0;
+        for (iou_id, cancellation) in &other.cancellations {
+            if !self.cancellations.contains_key(iou_id) {
+                self.cancellations.insert(iou_id.clone(), cancellation.clone());
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Drop settled IOUs named by a gateway checkpoint
+    ///
+    /// The IDs are remembered, so merging with a peer that still holds them doesn't
+    /// resurrect them. Only apply checkpoints from a gateway you trust; the signature
+    /// is checked against `checkpoint.checkpoint().issuer()`. Returns the number of
+    /// entries removed (0 if the checkpoint was already applied).
+    pub fn apply_checkpoint(&mut self, checkpoint: &SignedCheckpoint) -> Result<usize, MeshStateError> {
+        if !checkpoint.verify_issuer() {
+            return Err(MeshStateError::InvalidCheckpoint);
+        }
+        if !self.checkpoints.insert(checkpoint.id()) {
+            return Ok(0);
+        }
+
+        for iou_id in checkpoint.checkpoint().iou_ids() {
+            self.settled.remove(iou_id);
+            self.pruned.insert(iou_id.clone());
+        }
+        let removed = self.drop_pruned();
+        if removed > 0 {
+            self.rebuild_indexes();
+        }
+        self.version += 1;
+
+        Ok(removed)
+    }
+
+    /// Whether a checkpoint has been applied to this state
+    pub fn has_checkpoint(&self, checkpoint_id: &CheckpointId) -> bool {
+        self.checkpoints.contains(checkpoint_id)
+    }
+
+    /// Number of checkpoints applied
+    pub fn checkpoint_count(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// Whether an IOU was pruned by a checkpoint
+    pub fn is_pruned(&self, iou_id: &IOUId) -> bool {
+        self.pruned.contains(iou_id)
+    }
+
+    /// Remove pruned IOUs from the G-Set, returning how many were removed.
+    /// The indexes are left for the caller to update.
+    fn drop_pruned(&mut self) -> usize {
+        if self.pruned.is_empty() {
+            return 0;
+        }
+        let before = self.ious.len();
+        let pruned = &self.pruned;
+        self.ious.retain(|entry| !pruned.contains(&entry.id()));
+        before - self.ious.len()
+    }
+
+    /// Mark an IOU as settled, e.g. once its gateway confirms the batch
+    ///
+    /// This is local knowledge: it is kept across `to_bytes` but not shared on
+    /// merge, since only a gateway checkpoint proves settlement to other nodes.
+    /// Returns `false` if the IOU was already marked or has been pruned.
+    pub fn mark_settled(&mut self, iou_id: &IOUId) -> bool {
+        if self.pruned.contains(iou_id) {
+            return false;
+        }
+        self.settled.insert(iou_id.clone())
+    }
+
+    /// Whether an IOU has been marked settled and not yet pruned
+    pub fn is_settled(&self, iou_id: &IOUId) -> bool {
+        self.settled.contains(iou_id)
+    }
+
     /// Index an entry for fast lookup
     fn index_entry(&mut self, entry: &IOUEntry) {
         let iou = entry.iou();
@@ -169,11 +468,18 @@ impl MeshState {
             .or_insert_with(Vec::new)
             .push(iou_id.clone());
 
-        // Recipient index
-        let recipient = iou.iou().recipient().clone();
-        self.recipient_index
-            .entry(recipient)
-            .or_insert_with(Vec::new)
+        // Recipient index (every output of a multi-output IOU)
+        for recipient in iou.iou().recipients() {
+            self.recipient_index
+                .entry(recipient.clone())
+                .or_insert_with(Vec::new)
+                .push(iou_id.clone());
+        }
+
+        // Timestamp index
+        self.time_index
+            .entry(iou.iou().timestamp())
+            .or_default()
             .push(iou_id);
     }
 
@@ -182,6 +488,7 @@ impl MeshState {
         self.iou_index.clear();
         self.sender_index.clear();
         self.recipient_index.clear();
+        self.time_index.clear();
 
         // Collect entries first to avoid borrow issues
         let entries: Vec<IOUEntry> = self.ious.iter().cloned().collect();
@@ -190,6 +497,62 @@ impl MeshState {
         }
     }
 
+    /// Append entries missing from the index to the log, in ID order so it is deterministic.
+    /// Call before `rebuild_indexes`.
+    fn log_new_entries(&mut self) {
+        let mut new_ids: Vec<IOUId> = self
+            .ious
+            .iter()
+            .map(|entry| entry.id())
+            .filter(|id| !self.iou_index.contains_key(id))
+            .collect();
+        new_ids.sort_by_key(|id| *id.as_bytes());
+        self.log.extend(new_ids);
+    }
+
+    /// Log and index entries missing from the index, in ID order.
+    /// Cheaper than `rebuild_indexes` when nothing was removed.
+    fn index_new_entries(&mut self) {
+        let mut new_entries: Vec<IOUEntry> = self
+            .ious
+            .iter()
+            .filter(|entry| !self.iou_index.contains_key(&entry.id()))
+            .cloned()
+            .collect();
+        new_entries.sort_by_cached_key(|entry| *entry.id().as_bytes());
+        for entry in new_entries {
+            self.log.push(entry.id());
+            self.index_entry(&entry);
+        }
+    }
+
+    /// Sync cursor after the latest IOU this node learned of
+    ///
+    /// Cursors count up as IOUs arrive and are only meaningful to this node.
+    pub fn cursor(&self) -> u64 {
+        self.log.len() as u64
+    }
+
+    /// Up to `limit` entries learned after `cursor`, oldest first, and the cursor to resume from.
+    /// Entries since removed by a merge policy are skipped. A limit of 0 is treated as 1.
+    pub fn entries_since(&self, cursor: u64, limit: usize) -> (Vec<IOUEntry>, u64) {
+        let limit = limit.max(1);
+        let mut entries = Vec::new();
+        let mut next = cursor.min(self.cursor());
+
+        for id in self.log.iter().skip(next as usize) {
+            if entries.len() == limit {
+                break;
+            }
+            next += 1;
+            if let Some(entry) = self.iou_index.get(id) {
+                entries.push(entry.clone());
+            }
+        }
+
+        (entries, next)
+    }
+
     /// Get an IOU by ID
     pub fn get_iou(&self, iou_id: &IOUId) -> Option<&IOUEntry> {
         self.iou_index.get(iou_id)
@@ -219,29 +582,333 @@ impl MeshState {
             .unwrap_or_default()
     }
 
+    /// Get all IOUs with a timestamp in `[start_ts, end_ts)`, oldest first
+    ///
+    /// The start is inclusive and the end exclusive, so consecutive windows
+    /// such as `[t - 86400, t)` and `[t, t + 86400)` never share an IOU.
+    pub fn ious_between(&self, start_ts: u64, end_ts: u64) -> Vec<&IOUEntry> {
+        if start_ts >= end_ts {
+            return Vec::new();
+        }
+        self.time_index
+            .range(start_ts..end_ts)
+            .flat_map(|(_, ids)| ids.iter())
+            .filter_map(|id| self.iou_index.get(id))
+            .collect()
+    }
+
+    /// Get IOUs sent by a specific DID with a timestamp in `[start_ts, end_ts)`, oldest first
+    pub fn ious_by_sender_between(&self, sender: &Did, start_ts: u64, end_ts: u64) -> Vec<&IOUEntry> {
+        let mut entries: Vec<&IOUEntry> = self
+            .get_ious_by_sender(sender)
+            .into_iter()
+            .filter(|entry| (start_ts..end_ts).contains(&entry.iou().iou().timestamp()))
+            .collect();
+        entries.sort_by_key(|entry| entry.iou().iou().timestamp());
+        entries
+    }
+
+    /// Query IOUs by sender, recipient, time range, amount and settlement status
+    ///
+    /// ```ignore
+    /// for entry in state.query().sender(alice).after(start).unsettled_only() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn query(&self) -> MeshQuery<'_> {
+        MeshQuery::new(self)
+    }
+
+    /// Entries matching `query`, oldest first
+    pub(crate) fn run_query<'a>(&'a self, query: &MeshQuery<'_>) -> Vec<&'a IOUEntry> {
+        let start = query.after.unwrap_or(0);
+        if query.before.is_some_and(|end| start >= end) {
+            return Vec::new();
+        }
+
+        // Start from the narrowest index that applies
+        let by_sender = query.sender.as_ref().map(|did| self.sender_index.get(did).map_or(&[][..], Vec::as_slice));
+        let by_recipient = query
+            .recipient
+            .as_ref()
+            .map(|did| self.recipient_index.get(did).map_or(&[][..], Vec::as_slice));
+        let candidates: Box<dyn Iterator<Item = &IOUId>> = match (by_sender, by_recipient) {
+            (Some(sent), Some(received)) if received.len() < sent.len() => Box::new(received.iter()),
+            (Some(ids), _) | (None, Some(ids)) => Box::new(ids.iter()),
+            (None, None) => match query.before {
+                Some(end) => Box::new(self.time_index.range(start..end).flat_map(|(_, ids)| ids.iter())),
+                None => Box::new(self.time_index.range(start..).flat_map(|(_, ids)| ids.iter())),
+            },
+        };
+
+        let mut entries: Vec<&IOUEntry> = candidates
+            .filter_map(|id| self.iou_index.get(id))
+            .filter(|entry| query.matches(entry))
+            .collect();
+        entries.sort_by_cached_key(|entry| (entry.iou().iou().timestamp(), *entry.id().as_bytes()));
+        // A multi-output IOU paying the same party twice is indexed twice
+        entries.dedup_by(|a, b| a.id() == b.id());
+        entries
+    }
+
     /// Merge another state into this one (CRDT merge)
+    ///
+    /// Incoming entries are verified against their sender DID as set by
+    /// `set_merge_policy`; ones that fail are skipped and listed in `rejected`.
+    /// Entries pruned by a checkpoint stay out and are counted in `resurrections_prevented`.
+    /// New entries are added to the indexes without rebuilding them. The version
+    /// vectors are merged element-wise unless an entry was rejected.
     pub fn merge(&mut self, other: &MeshState) -> MergeResult {
-        let result = self.ious.merge_with_result(&other.ious);
+        self.merge_under(other, self.merge_policy)
+    }
+
+    /// `merge` with an explicit policy, e.g. `Trust` for a state built with `add_iou`
+    /// whose entries were already validated
+    pub(crate) fn merge_under(&mut self, other: &MeshState, policy: MergePolicy) -> MergeResult {
+        let result = self.merge_entries(other, policy);
+        let new_cancellations = self.merge_cancellations(other);
 
         if result.new_entries > 0 {
-            // Rebuild indexes to include new entries
-            self.rebuild_indexes();
+            self.index_new_entries();
+        }
+        if result.new_entries > 0 || new_cancellations > 0 {
             self.version += 1;
         }
+        self.merge_clock(other, &result);
+
+        result
+    }
+
+    /// Take the element-wise max with `other`'s version vector, unless entries were
+    /// rejected: then this state doesn't hold everything `other`'s vector covers
+    fn merge_clock(&mut self, other: &MeshState, result: &MergeResult) {
+        if result.rejected.is_empty() {
+            self.clock.merge(&other.clock);
+        }
+    }
+
+    /// Add `other`'s entries that pass verification under `policy`, leaving out pruned ones.
+    /// The new entries are not indexed or logged.
+    fn merge_entries(&mut self, other: &MeshState, policy: MergePolicy) -> MergeResult {
+        let mut result = MergeResult::default();
+        for entry in other.ious.iter() {
+            let iou_id = entry.id();
+            let is_new = !self.ious.contains(entry) && !self.is_frozen(&iou_id, entry.iou().iou().timestamp());
+            if is_new && self.pruned.contains(&iou_id) {
+                result.resurrections_prevented += 1;
+                continue;
+            }
+
+            let verify = match policy {
+                MergePolicy::VerifyAll => true,
+                MergePolicy::VerifyNew => is_new,
+                MergePolicy::Trust => false,
+            };
+            if verify {
+                result.verified_entries += 1;
+                if let Err(reason) = entry.check_signature() {
+                    result.rejected.push(RejectedEntry { iou_id, reason });
+                    continue;
+                }
+            }
+
+            if is_new {
+                self.ious.insert(entry.clone());
+                result.new_entries += 1;
+            }
+        }
 
+        result.rejected.sort_by_key(|rejected| *rejected.iou_id.as_bytes());
+        result.rejected_entries = result.rejected.len();
+        result.total_after_merge = self.ious.len();
         result
     }
 
+    /// Merge another state, then settle double-spends recorded in `claims` with `policy`
+    ///
+    /// Incoming entries are verified as by `merge`. Only IOUs present after the merge
+    /// take part in a conflict. Losers are removed from this state, including ones it
+    /// already held, so two nodes merging the same IOUs under the same policy end up
+    /// with the same entries in either order.
+    pub fn merge_with_policy(
+        &mut self,
+        other: &MeshState,
+        claims: &ConflictDetector,
+        policy: ConflictPolicy,
+    ) -> PolicyMergeResult {
+        let before: HashSet<IOUId> = self.iou_index.keys().cloned().collect();
+        let merged = self.merge_entries(other, self.merge_policy);
+        self.merge_clock(other, &merged);
+        let rejected = merged.rejected;
+        self.log_new_entries();
+        self.rebuild_indexes();
+        let new_cancellations = self.merge_cancellations(other);
+
+        let mut contested = claims.conflicting_utxos();
+        contested.sort_by_key(|utxo_id| *utxo_id.as_bytes());
+
+        let mut dropped: Vec<DroppedEntry> = Vec::new();
+        for utxo_id in contested {
+            let contenders: Vec<&SignedIOU> = claims
+                .get_claims_for_utxo(utxo_id)
+                .into_iter()
+                .filter_map(|claim| self.iou_index.get(claim.spending_iou_id()))
+                .map(|entry| entry.iou())
+                .collect();
+            if contenders.len() < 2 {
+                continue;
+            }
+
+            let winner = policy.pick_winner(&contenders).map(|iou| iou.id());
+            for iou in contenders {
+                let iou_id = iou.id();
+                if Some(&iou_id) == winner.as_ref() || dropped.iter().any(|d| d.iou_id == iou_id) {
+                    continue;
+                }
+                let reason = match &winner {
+                    Some(winner) => DropReason::Superseded { winner: winner.clone() },
+                    None => DropReason::Rejected,
+                };
+                dropped.push(DroppedEntry { iou_id, utxo_id: utxo_id.clone(), reason });
+            }
+        }
+
+        if !dropped.is_empty() {
+            let losers: HashSet<&IOUId> = dropped.iter().map(|d| &d.iou_id).collect();
+            self.ious.retain(|entry| !losers.contains(&entry.id()));
+            self.rebuild_indexes();
+        }
+
+        let new_entries = self.iou_index.keys().filter(|id| !before.contains(*id)).count();
+        if new_entries > 0 || !dropped.is_empty() || new_cancellations > 0 {
+            self.version += 1;
+        }
+
+        PolicyMergeResult {
+            new_entries,
+            total_after_merge: self.iou_count(),
+            dropped,
+            rejected,
+        }
+    }
+
     /// Get entries that this state has but other doesn't (for efficient sync)
     pub fn delta(&self, other: &MeshState) -> Vec<IOUEntry> {
         self.ious.delta(&other.ious).to_vec()
     }
 
+    /// Bloom filter of this state's IOU IDs, to send to a peer instead of the full state
+    pub fn bloom_summary(&self, fp_rate: f64) -> BloomSummary {
+        let mut summary = BloomSummary::with_capacity(self.iou_count(), fp_rate);
+        for entry in self.ious.iter() {
+            summary.insert(&entry.id());
+        }
+        summary
+    }
+
+    /// Entries the owner of `summary` is probably missing.
+    /// False positives can omit a few entries; they are picked up on a later sync.
+    pub fn delta_against_bloom(&self, summary: &BloomSummary) -> Vec<IOUEntry> {
+        self.ious
+            .iter()
+            .filter(|entry| !summary.contains(&entry.id()))
+            .cloned()
+            .collect()
+    }
+
+    /// Merkle summary of this state's IOU IDs, to send to a peer instead of the full state
+    ///
+    /// Sized for about `SUMMARY_TARGET_BUCKET_LOAD` IOUs per bucket.
+    pub fn summary(&self) -> StateSummary {
+        self.summary_with_buckets(self.iou_count().div_ceil(SUMMARY_TARGET_BUCKET_LOAD))
+    }
+
+    /// Merkle summary over `bucket_count` buckets (see [`StateSummary::from_ids`])
+    pub fn summary_with_buckets(&self, bucket_count: usize) -> StateSummary {
+        StateSummary::from_ids(self.iou_index.keys(), bucket_count)
+    }
+
+    /// Entries the owner of `summary` is missing: every entry in a bucket whose hash
+    /// differs from ours. Empty when the states hold the same IOUs.
+    pub fn diff_against_summary(&self, summary: &StateSummary) -> Delta {
+        let ours = self.summary_with_buckets(summary.bucket_count());
+        let differing: HashSet<usize> = summary.differing_buckets(&ours).into_iter().collect();
+        if differing.is_empty() {
+            return Delta::default();
+        }
+
+        let mut entries: Vec<IOUEntry> = self
+            .ious
+            .iter()
+            .filter(|entry| differing.contains(&summary.bucket_of(&entry.id())))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|entry| *entry.id().as_bytes());
+        Delta::new(entries)
+    }
+
+    /// Add the entries of a delta received from a peer
+    ///
+    /// Each IOU is validated as by `add_replicated_iou`; invalid ones are skipped.
+    pub fn apply_delta(&mut self, delta: Delta) -> MergeResult {
+        let mut received = MeshState::new(self.node_id.clone());
+        for entry in delta.into_entries() {
+            if self.has_iou(&entry.id()) {
+                continue;
+            }
+            let sender_pubkey = entry.sender_pubkey().clone();
+            let _ = received.add_replicated_iou(entry.iou().clone(), &sender_pubkey);
+        }
+        // A delta carries no version vector; don't count its entries as ours
+        received.set_clock(VersionVector::new());
+        self.merge_under(&received, MergePolicy::Trust)
+    }
+
+    /// Merkle root over the IOU and cancellation IDs
+    ///
+    /// Independent of arrival order, so two nodes holding the same entries get
+    /// the same root; a cheap convergence check. All zeros for an empty state.
+    pub fn merkle_root(&self) -> [u8; 32] {
+        let mut leaves: Vec<[u8; 32]> = self
+            .ious
+            .iter()
+            .map(|entry| Self::merkle_leaf(b"iou:", entry.id().as_bytes()))
+            .chain(self.cancellations.keys().map(|id| Self::merkle_leaf(b"cancel:", id.as_bytes())))
+            .collect();
+        if leaves.is_empty() {
+            return [0u8; 32];
+        }
+        leaves.sort_unstable();
+
+        while leaves.len() > 1 {
+            leaves = leaves
+                .chunks(2)
+                .map(|pair| {
+                    // An odd node out is paired with itself
+                    let right = pair.get(1).unwrap_or(&pair[0]);
+                    let mut hasher = Sha256::new();
+                    hasher.update(b"node:");
+                    hasher.update(pair[0]);
+                    hasher.update(right);
+                    hasher.finalize().into()
+                })
+                .collect();
+        }
+        leaves[0]
+    }
+
+    fn merkle_leaf(tag: &[u8], id: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(tag);
+        hasher.update(id);
+        hasher.finalize().into()
+    }
+
     /// Calculate total received by a DID
     pub fn total_received(&self, did: &Did) -> u64 {
         self.get_ious_by_recipient(did)
             .iter()
-            .map(|e| e.iou().iou().amount())
+            .map(|e| e.iou().iou().amount_for(did))
             .sum()
     }
 
@@ -263,27 +930,229 @@ impl MeshState {
             .map(|e| e.iou().iou().amount())
             .sum();
 
+        let mut party_totals: HashMap<Did, PartyTotals> = HashMap::new();
+        for entry in self.ious.iter() {
+            let iou = entry.iou().iou();
+            let sender = party_totals.entry(iou.sender().clone()).or_default();
+            sender.sent = sender.sent.saturating_add(iou.amount());
+            for recipient in iou.recipients() {
+                let totals = party_totals.entry(recipient.clone()).or_default();
+                totals.received = totals.received.saturating_add(iou.amount_for(recipient));
+            }
+        }
+
         MeshStatistics {
             total_ious,
             unique_senders,
             unique_recipients,
             total_value,
+            party_totals,
         }
     }
 
-    /// Serialize to bytes
+    /// Serialize to bytes: `STATE_MAGIC`, `STATE_VERSION`, then the state
     pub fn to_bytes(&self) -> Vec<u8> {
-        postcard::to_allocvec(self).unwrap_or_default()
+        let mut bytes = STATE_MAGIC.to_vec();
+        bytes.push(STATE_VERSION);
+        postcard::to_extend(self, bytes).unwrap_or_default()
     }
 
     /// Deserialize from bytes
+    ///
+    /// Bytes without `STATE_MAGIC` are read as the unversioned format from before
+    /// cancellations were tracked, whose IOUs are logged in ID order. A node ID that
+    /// happens to start with the magic (1 in 2^32) can't be read that way.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, MeshStateError> {
-        let mut state: MeshState = postcard::from_bytes(bytes)
-            .map_err(|_| MeshStateError::DeserializationFailed)?;
+        let mut state = match bytes.strip_prefix(STATE_MAGIC.as_slice()) {
+            Some([STATE_VERSION, rest @ ..]) => {
+                postcard::from_bytes::<MeshState>(rest).map_err(|_| MeshStateError::DeserializationFailed)?
+            }
+            Some([version, ..]) => return Err(MeshStateError::UnsupportedVersion(*version)),
+            Some([]) => return Err(MeshStateError::DeserializationFailed),
+            None => Self::from_unversioned_bytes(bytes)?,
+        };
         state.rebuild_indexes();
         Ok(state)
     }
 
+    /// Read a state serialized before `STATE_MAGIC` was written
+    fn from_unversioned_bytes(bytes: &[u8]) -> Result<Self, MeshStateError> {
+        let old: PreCancellationMeshState =
+            postcard::from_bytes(bytes).map_err(|_| MeshStateError::DeserializationFailed)?;
+        let mut state = MeshState::new(old.node_id);
+        state.ious = old.ious;
+        state.version = old.version;
+        state.log_new_entries();
+        Ok(state)
+    }
+
+    // ========================================================================
+    // SNAPSHOTS
+    // ========================================================================
+
+    /// Freeze live entries with an IOU timestamp below `cutoff` into a new snapshot segment
+    ///
+    /// Frozen entries leave the live set, its indexes and `to_bytes`, and are kept
+    /// compressed until asked for with [`StateSnapshot::entries`]. Store the returned
+    /// snapshot next to `to_bytes()` and reload both with [`MeshState::from_snapshot`].
+    /// Merges and `add_iou` still treat frozen entries as held, by bloom filter, so
+    /// they aren't re-added; a rare false positive only affects entries older than
+    /// a segment's cutoff. Frozen entries are not offered to peers in syncs.
+    /// Nothing is added if no live entry is older than `cutoff`.
+    pub fn snapshot(&mut self, cutoff: u64) -> StateSnapshot {
+        let frozen: Vec<IOUEntry> = self
+            .ious
+            .iter()
+            .filter(|entry| entry.iou().iou().timestamp() < cutoff)
+            .cloned()
+            .collect();
+        if !frozen.is_empty() {
+            let ids: HashSet<IOUId> = frozen.iter().map(|entry| entry.id()).collect();
+            self.ious.retain(|entry| !ids.contains(&entry.id()));
+            self.snapshot.push(cutoff, frozen);
+            self.rebuild_indexes();
+        }
+        self.snapshot.clone()
+    }
+
+    /// Rebuild a state from a snapshot and the live state's `to_bytes`
+    ///
+    /// The snapshot's hash chain is checked but its entries stay compressed.
+    pub fn from_snapshot(snapshot: StateSnapshot, live_bytes: &[u8]) -> Result<Self, MeshStateError> {
+        snapshot.verify_chain()?;
+        let mut state = Self::from_bytes(live_bytes)?;
+        state.snapshot = snapshot;
+        Ok(state)
+    }
+
+    /// The frozen entries of this state
+    pub fn frozen(&self) -> &StateSnapshot {
+        &self.snapshot
+    }
+
+    /// Whether an IOU is (probably) frozen in the snapshot
+    fn is_frozen(&self, iou_id: &IOUId, timestamp: u64) -> bool {
+        !self.snapshot.is_empty() && self.snapshot.may_contain(iou_id, timestamp)
+    }
+
+    /// How much the snapshot saves over keeping every entry live
+    pub fn compaction_stats(&self) -> CompactionStats {
+        let segments = self.snapshot.segments();
+        CompactionStats {
+            segments: segments.len(),
+            frozen_entries: self.snapshot.entry_count(),
+            live_entries: self.iou_count(),
+            raw_bytes: segments.iter().map(|s| s.raw_len()).sum(),
+            compressed_bytes: segments.iter().map(|s| s.compressed_len()).sum(),
+            live_bytes: self.to_bytes().len() as u64,
+        }
+    }
+
+    // ========================================================================
+    // INCREMENTAL PERSISTENCE
+    // ========================================================================
+
+    /// Mark where the state stands, after writing it or a delta to storage
+    pub fn save_point(&self) -> SavePoint {
+        SavePoint {
+            log_len: self.cursor(),
+            version: self.version,
+            clock: self.clock.clone(),
+            removed: self.unheld_logged(self.log.len()).cloned().collect(),
+            cancellations: self.cancellations.keys().cloned().collect(),
+            checkpoints: self.checkpoints.clone(),
+            pruned: self.pruned.clone(),
+            settled: self.settled.clone(),
+        }
+    }
+
+    /// Everything that changed since `save_point`, to persist instead of `to_bytes`
+    ///
+    /// Applying the delta with [`MeshState::apply_state_delta`] to the state as
+    /// it was at the save point gives this state, with the same merkle root.
+    /// Frozen snapshot segments are not included; store them as before.
+    pub fn changes_since(&self, save_point: &SavePoint) -> StateDelta {
+        let start = (save_point.log_len as usize).min(self.log.len());
+        let log = self.log[start..].to_vec();
+        let entries = log.iter().filter_map(|id| self.iou_index.get(id)).cloned().collect();
+        let removed = self
+            .unheld_logged(start)
+            .filter(|id| !save_point.removed.contains(*id))
+            .cloned()
+            .collect();
+
+        let mut cancellations: Vec<SignedCancellation> = self
+            .cancellations
+            .iter()
+            .filter(|(id, _)| !save_point.cancellations.contains(*id))
+            .map(|(_, cancellation)| cancellation.clone())
+            .collect();
+        cancellations.sort_by_key(|c| *c.cancellation().iou_id().as_bytes());
+        let new_ids = |ids: &HashSet<IOUId>, known: &HashSet<IOUId>| {
+            let mut new: Vec<IOUId> = ids.difference(known).cloned().collect();
+            new.sort_by_key(|id| *id.as_bytes());
+            new
+        };
+
+        StateDelta {
+            base_log_len: save_point.log_len,
+            base_version: save_point.version,
+            log,
+            entries,
+            removed,
+            cancellations,
+            checkpoints: self.checkpoints.difference(&save_point.checkpoints).cloned().collect(),
+            pruned: new_ids(&self.pruned, &save_point.pruned),
+            settled: new_ids(&self.settled, &save_point.settled),
+            version: self.version,
+            clock: (self.clock != save_point.clock).then(|| self.clock.clone()),
+            root: self.merkle_root(),
+        }
+    }
+
+    /// IDs among the first `len` logged that are no longer held
+    fn unheld_logged(&self, len: usize) -> impl Iterator<Item = &IOUId> {
+        self.log[..len].iter().filter(|id| !self.iou_index.contains_key(*id))
+    }
+
+    /// Replay a delta from `changes_since` on the state it was taken against
+    ///
+    /// Entries are trusted, as with `from_bytes`, since they come from our own
+    /// storage. Fails without changing anything if the state's log position or
+    /// version differ from the delta's base, e.g. when deltas are replayed out of order.
+    pub fn apply_state_delta(&mut self, delta: StateDelta) -> Result<(), MeshStateError> {
+        if self.cursor() != delta.base_log_len || self.version != delta.base_version {
+            return Err(MeshStateError::DeltaBaseMismatch {
+                expected: delta.base_version,
+                actual: self.version,
+            });
+        }
+
+        if !delta.removed.is_empty() {
+            let removed: HashSet<IOUId> = delta.removed.into_iter().collect();
+            self.ious.retain(|entry| !removed.contains(&entry.id()));
+        }
+        for entry in delta.entries {
+            self.ious.insert(entry);
+        }
+        self.log.extend(delta.log);
+        for cancellation in delta.cancellations {
+            self.cancellations.insert(cancellation.cancellation().iou_id().clone(), cancellation);
+        }
+        self.checkpoints.extend(delta.checkpoints);
+        self.settled.extend(delta.settled);
+        for iou_id in delta.pruned {
+            self.settled.remove(&iou_id);
+            self.pruned.insert(iou_id);
+        }
+        self.version = delta.version;
+        if let Some(clock) = delta.clock {
+            self.clock = clock;
+        }
+        self.rebuild_indexes();
+        Ok(())
+    }
+
     /// Get all IOU entries
     pub fn all_entries(&self) -> Vec<&IOUEntry> {
         self.ious.iter().collect()
@@ -351,4 +1220,41 @@ mod tests {
         assert_eq!(result.new_entries, 1);
         assert_eq!(state1.iou_count(), 2);
     }
+
+    #[test]
+    fn test_summary_delta_sync_bytes_scale_with_difference() {
+        let alice = Keypair::generate();
+        let bob = Keypair::generate();
+
+        // Insert the shared entries directly: verifying 10,000 signatures is slow in debug builds
+        let mut behind = MeshState::new(NodeId::generate());
+        for nonce in 0..9_995 {
+            behind.ious.insert(IOUEntry::new(create_test_iou(&alice, &bob, 10, nonce), alice.public_key()));
+        }
+        behind.log_new_entries();
+        behind.rebuild_indexes();
+
+        let mut ahead = MeshState::new(NodeId::generate());
+        ahead.set_merge_policy(MergePolicy::Trust);
+        ahead.merge(&behind);
+        for nonce in 9_995..10_000 {
+            ahead.add_iou(create_test_iou(&alice, &bob, 10, nonce), &alice.public_key()).unwrap();
+        }
+        assert_eq!(ahead.iou_count(), 10_000);
+
+        let summary = behind.summary().to_bytes();
+        let delta = ahead.diff_against_summary(&StateSummary::from_bytes(&summary).unwrap());
+        let delta_bytes = delta.to_bytes();
+        let exchanged = summary.len() + delta_bytes.len();
+
+        // Each missing entry costs at most one bucket of about 16 entries
+        assert!(delta.len() >= 5);
+        assert!(delta.len() <= 5 * 48, "delta has {} entries", delta.len());
+        let full_state = ahead.to_bytes().len();
+        assert!(exchanged * 50 < full_state, "exchanged {} of {} bytes", exchanged, full_state);
+
+        let result = behind.apply_delta(Delta::from_bytes(&delta_bytes).unwrap());
+        assert_eq!(result.new_entries, 5);
+        assert_eq!(behind.merkle_root(), ahead.merkle_root());
+    }
 }
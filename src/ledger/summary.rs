@@ -0,0 +1,202 @@
+// State Summary - Merkle digest of IOU IDs for delta sync
+//
+// IOU IDs are spread over a power-of-two number of buckets by their leading
+// bytes. Each bucket is hashed over its sorted IDs and the root over the
+// bucket hashes. A node sends its summary; the peer compares bucket hashes
+// with its own and returns the entries in buckets that differ, so a sync
+// costs the summary plus a few buckets however large the states are.
+
+use crate::iou::IOUId;
+use crate::ledger::crdt::IOUEntry;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Domain separator for summary hashing
+const SUMMARY_DOMAIN: &[u8] = b"p2pmesh:summary:v1";
+
+/// Bytes kept of each bucket hash
+pub const SUMMARY_BUCKET_HASH_LEN: usize = 8;
+
+/// Upper bound on buckets, so a hostile summary can't make a diff expensive
+pub const MAX_SUMMARY_BUCKETS: usize = 4096;
+
+/// Entries per bucket `MeshState::summary` aims for
+pub const SUMMARY_TARGET_BUCKET_LOAD: usize = 16;
+
+/// Errors from state summary handling
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SummaryError {
+    #[error("Invalid summary parameters: {0}")]
+    InvalidParameters(String),
+
+    #[error("Deserialization failed")]
+    DeserializationFailed,
+}
+
+/// Bucketed Merkle summary of a state's IOU IDs
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSummary {
+    /// Hash over all bucket hashes
+    root: [u8; 32],
+    /// Truncated hash of each bucket's sorted IDs (all zeros when empty)
+    buckets: Vec<[u8; SUMMARY_BUCKET_HASH_LEN]>,
+    /// Number of IDs summarized
+    item_count: u64,
+}
+
+impl StateSummary {
+    /// Summarize `ids` over `bucket_count` buckets, rounded up to a power of two
+    /// and clamped to [1, `MAX_SUMMARY_BUCKETS`]
+    pub fn from_ids<'a>(ids: impl IntoIterator<Item = &'a IOUId>, bucket_count: usize) -> Self {
+        let bucket_count = bucket_count.clamp(1, MAX_SUMMARY_BUCKETS).next_power_of_two();
+        let mut grouped: Vec<Vec<[u8; 32]>> = vec![Vec::new(); bucket_count];
+        let mut item_count = 0;
+        for id in ids {
+            grouped[bucket_of(id, bucket_count)].push(*id.as_bytes());
+            item_count += 1;
+        }
+
+        let buckets: Vec<_> = grouped.into_iter().map(hash_bucket).collect();
+        Self { root: hash_root(&buckets), buckets, item_count }
+    }
+
+    /// Hash over every bucket; equal roots mean equal ID sets
+    pub fn root(&self) -> &[u8; 32] {
+        &self.root
+    }
+
+    /// Number of buckets
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Number of IDs summarized
+    pub fn item_count(&self) -> u64 {
+        self.item_count
+    }
+
+    /// Indexes of the buckets whose hashes differ from `other`'s
+    ///
+    /// Empty when the bucket counts differ, since the buckets don't line up.
+    pub fn differing_buckets(&self, other: &StateSummary) -> Vec<usize> {
+        if self.root == other.root || self.buckets.len() != other.buckets.len() {
+            return Vec::new();
+        }
+        self.buckets
+            .iter()
+            .zip(&other.buckets)
+            .enumerate()
+            .filter(|(_, (ours, theirs))| ours != theirs)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Bucket an ID falls into
+    pub fn bucket_of(&self, iou_id: &IOUId) -> usize {
+        bucket_of(iou_id, self.buckets.len())
+    }
+
+    /// Serialize to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes, rejecting bucket counts that aren't a power of two
+    /// or exceed `MAX_SUMMARY_BUCKETS`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SummaryError> {
+        let summary: StateSummary =
+            postcard::from_bytes(bytes).map_err(|_| SummaryError::DeserializationFailed)?;
+        summary.validate()?;
+        Ok(summary)
+    }
+
+    /// Check the bucket count, for summaries that arrive inside other messages
+    pub fn validate(&self) -> Result<(), SummaryError> {
+        let count = self.buckets.len();
+        if count == 0 || count > MAX_SUMMARY_BUCKETS || !count.is_power_of_two() {
+            return Err(SummaryError::InvalidParameters(format!("{} buckets", count)));
+        }
+        Ok(())
+    }
+}
+
+/// Entries a peer is missing, computed against its [`StateSummary`]
+///
+/// Whole differing buckets are sent, so the delta can include a few entries
+/// the peer already has; merging ignores those.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Delta {
+    entries: Vec<IOUEntry>,
+}
+
+impl Delta {
+    /// Create a delta from entries
+    pub fn new(entries: Vec<IOUEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Entries in the delta
+    pub fn entries(&self) -> &[IOUEntry] {
+        &self.entries
+    }
+
+    /// Take the entries
+    pub fn into_entries(self) -> Vec<IOUEntry> {
+        self.entries
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the peer is missing nothing
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serialize to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap_or_default()
+    }
+
+    /// Deserialize from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SummaryError> {
+        postcard::from_bytes(bytes).map_err(|_| SummaryError::DeserializationFailed)
+    }
+}
+
+/// Bucket from the leading ID bytes (IDs are SHA-256 output, so uniform)
+fn bucket_of(iou_id: &IOUId, bucket_count: usize) -> usize {
+    let bytes = iou_id.as_bytes();
+    let prefix = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    prefix & (bucket_count - 1)
+}
+
+fn hash_bucket(mut ids: Vec<[u8; 32]>) -> [u8; SUMMARY_BUCKET_HASH_LEN] {
+    let mut hash = [0u8; SUMMARY_BUCKET_HASH_LEN];
+    if ids.is_empty() {
+        return hash;
+    }
+    ids.sort_unstable();
+
+    let mut hasher = Sha256::new();
+    hasher.update(SUMMARY_DOMAIN);
+    hasher.update(b"bucket:");
+    for id in &ids {
+        hasher.update(id);
+    }
+    hash.copy_from_slice(&hasher.finalize()[..SUMMARY_BUCKET_HASH_LEN]);
+    hash
+}
+
+fn hash_root(buckets: &[[u8; SUMMARY_BUCKET_HASH_LEN]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SUMMARY_DOMAIN);
+    hasher.update(b"root:");
+    for bucket in buckets {
+        hasher.update(bucket);
+    }
+    hasher.finalize().into()
+}
@@ -0,0 +1,87 @@
+// Version Vector - Per-node counters for causal sync decisions
+//
+// Each node counts the entries it originated. A state's vector holds that count
+// for every node whose entries it has merged, so two peers can tell from their
+// vectors alone whether one already has everything the other does.
+
+use crate::ledger::state::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How two version vectors relate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CausalRelation {
+    /// Has seen everything the other has, and more
+    Ahead,
+    /// The other has seen everything this has, and more
+    Behind,
+    /// Each has seen something the other hasn't
+    Concurrent,
+    /// Both have seen the same
+    Equal,
+}
+
+/// Counter of originated entries per node; missing nodes count as 0
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionVector {
+    counters: HashMap<NodeId, u64>,
+}
+
+impl VersionVector {
+    /// Create an empty vector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counter for `node_id`
+    pub fn get(&self, node_id: &NodeId) -> u64 {
+        self.counters.get(node_id).copied().unwrap_or(0)
+    }
+
+    /// Count one more entry originated by `node_id`, returning the new counter
+    pub fn increment(&mut self, node_id: &NodeId) -> u64 {
+        let counter = self.counters.entry(node_id.clone()).or_default();
+        *counter += 1;
+        *counter
+    }
+
+    /// Take the element-wise maximum with `other`
+    pub fn merge(&mut self, other: &VersionVector) {
+        for (node_id, &counter) in &other.counters {
+            let ours = self.counters.entry(node_id.clone()).or_default();
+            *ours = (*ours).max(counter);
+        }
+    }
+
+    /// How this vector relates to `other`
+    pub fn compare(&self, other: &VersionVector) -> CausalRelation {
+        let mut ahead = false;
+        let mut behind = false;
+        for node_id in self.counters.keys().chain(other.counters.keys()) {
+            let (ours, theirs) = (self.get(node_id), other.get(node_id));
+            ahead |= ours > theirs;
+            behind |= ours < theirs;
+        }
+        match (ahead, behind) {
+            (false, false) => CausalRelation::Equal,
+            (true, false) => CausalRelation::Ahead,
+            (false, true) => CausalRelation::Behind,
+            (true, true) => CausalRelation::Concurrent,
+        }
+    }
+
+    /// Number of nodes with a counter
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Check if no node has a counter
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    /// Iterate over the nodes and their counters
+    pub fn iter(&self) -> impl Iterator<Item = (&NodeId, u64)> {
+        self.counters.iter().map(|(node_id, &counter)| (node_id, counter))
+    }
+}
@@ -1,6 +1,11 @@
 // Storage module - PERSISTENCE
 // Handles persistent key-value storage using sled
 
+mod sealed;
 mod store;
 
-pub use store::{MeshStore, StoreError, StorageStats};
+pub use sealed::{
+    seal, seal_with, sealed_params, unseal, unseal_within, KdfParams, SealError, SEALED_MAGIC,
+    SEALED_NONCE_LEN, SEALED_SALT_LEN, SEALED_VERSION,
+};
+pub use store::{MeshStore, StoreEntry, StoreError, StoreWrite, StorageStats};
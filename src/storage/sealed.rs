@@ -0,0 +1,273 @@
+// Sealed blobs - passphrase-encrypted data for backups and exports
+//
+// Layout (all integers little-endian):
+// [magic:4 "P2PE"][version:1][memory_kib:4][iterations:4][parallelism:4][salt:16][nonce:24][ciphertext+tag]
+//
+// The key is derived with Argon2id over the passphrase and salt; the payload is
+// encrypted with XChaCha20-Poly1305 using the whole header as associated data,
+// so tampering with the KDF parameters fails authentication. Those parameters
+// are read before the key is derived, so `unseal` refuses any above the caller's
+// limit (the default params unless given) instead of spending the work.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Magic bytes at the start of every sealed blob
+pub const SEALED_MAGIC: &[u8; 4] = b"P2PE";
+
+/// Current sealed blob format version
+pub const SEALED_VERSION: u8 = 1;
+
+/// Salt length in bytes
+pub const SEALED_SALT_LEN: usize = 16;
+
+/// XChaCha20 nonce length in bytes
+pub const SEALED_NONCE_LEN: usize = 24;
+
+/// Header length in bytes
+const HEADER_LEN: usize = 4 + 1 + 4 + 4 + 4 + SEALED_SALT_LEN + SEALED_NONCE_LEN;
+
+/// Poly1305 tag length in bytes
+const TAG_LEN: usize = 16;
+
+/// XChaCha20 key length in bytes
+const KEY_LEN: usize = 32;
+
+/// KDF: Argon2id, version 0x13 (RFC 9106)
+const KDF_ALGORITHM: Algorithm = Algorithm::Argon2id;
+const KDF_VERSION: Version = Version::V0x13;
+
+/// Largest accepted memory cost (1 GiB), so a hostile blob can't exhaust memory
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+
+/// Largest accepted iteration count
+const MAX_ITERATIONS: u32 = 64;
+
+/// Largest accepted parallelism
+const MAX_PARALLELISM: u32 = 16;
+
+/// Errors from sealing and unsealing
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SealError {
+    #[error("Invalid sealed blob: {0}")]
+    InvalidFormat(String),
+
+    #[error("Unsupported sealed blob version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Invalid KDF parameters: {0}")]
+    InvalidParams(String),
+
+    #[error("KDF parameters above limit: {0}")]
+    ParamsAboveLimit(String),
+
+    #[error("Decryption failed (wrong passphrase or corrupted data)")]
+    DecryptionFailed,
+}
+
+/// Argon2id cost parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over memory
+    pub iterations: u32,
+    /// Number of lanes
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// 19 MiB, 2 iterations, 1 lane
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Create params with the given memory (KiB) and iterations, single lane
+    pub fn new(memory_kib: u32, iterations: u32) -> Self {
+        Self {
+            memory_kib,
+            iterations,
+            parallelism: 1,
+        }
+    }
+
+    /// Set the number of lanes
+    pub fn with_parallelism(mut self, parallelism: u32) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// Check the params are within accepted bounds
+    pub fn validate(&self) -> Result<(), SealError> {
+        if self.parallelism == 0 || self.parallelism > MAX_PARALLELISM {
+            return Err(SealError::InvalidParams(format!(
+                "parallelism {} (1-{})",
+                self.parallelism, MAX_PARALLELISM
+            )));
+        }
+        if self.iterations == 0 || self.iterations > MAX_ITERATIONS {
+            return Err(SealError::InvalidParams(format!(
+                "iterations {} (1-{})",
+                self.iterations, MAX_ITERATIONS
+            )));
+        }
+        if self.memory_kib < 8 * self.parallelism || self.memory_kib > MAX_MEMORY_KIB {
+            return Err(SealError::InvalidParams(format!(
+                "memory {} KiB ({}-{})",
+                self.memory_kib,
+                8 * self.parallelism,
+                MAX_MEMORY_KIB
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check no cost exceeds the matching one in `max`
+    pub fn check_within(&self, max: &KdfParams) -> Result<(), SealError> {
+        for (name, value, limit) in [
+            ("memory KiB", self.memory_kib, max.memory_kib),
+            ("iterations", self.iterations, max.iterations),
+            ("parallelism", self.parallelism, max.parallelism),
+        ] {
+            if value > limit {
+                return Err(SealError::ParamsAboveLimit(format!("{} {} (max {})", name, value, limit)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encrypt `plaintext` under `passphrase` with a random salt and nonce
+pub fn seal(plaintext: &[u8], passphrase: &[u8], params: &KdfParams) -> Result<Vec<u8>, SealError> {
+    use rand::RngCore;
+    let mut salt = [0u8; SEALED_SALT_LEN];
+    let mut nonce = [0u8; SEALED_NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    seal_with(plaintext, passphrase, params, &salt, &nonce)
+}
+
+/// Encrypt with an explicit salt and nonce.
+/// Only for reproducible test vectors; never reuse a salt/nonce pair in practice.
+pub fn seal_with(
+    plaintext: &[u8],
+    passphrase: &[u8],
+    params: &KdfParams,
+    salt: &[u8; SEALED_SALT_LEN],
+    nonce: &[u8; SEALED_NONCE_LEN],
+) -> Result<Vec<u8>, SealError> {
+    params.validate()?;
+
+    let mut blob = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+    blob.extend_from_slice(SEALED_MAGIC);
+    blob.push(SEALED_VERSION);
+    blob.extend_from_slice(&params.memory_kib.to_le_bytes());
+    blob.extend_from_slice(&params.iterations.to_le_bytes());
+    blob.extend_from_slice(&params.parallelism.to_le_bytes());
+    blob.extend_from_slice(salt);
+    blob.extend_from_slice(nonce);
+
+    let cipher = derive_cipher(passphrase, salt, params);
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(nonce), Payload { msg: plaintext, aad: &blob })
+        .map_err(|_| SealError::InvalidFormat("encryption failed".to_string()))?;
+
+    blob.extend_from_slice(&ciphertext);
+    Ok(blob)
+}
+
+/// Decrypt a sealed blob. A wrong passphrase returns `SealError::DecryptionFailed`.
+///
+/// Blobs whose KDF costs exceed `KdfParams::default()` are refused with
+/// `SealError::ParamsAboveLimit`; use `unseal_within` for blobs sealed with more.
+pub fn unseal(blob: &[u8], passphrase: &[u8]) -> Result<Zeroizing<Vec<u8>>, SealError> {
+    unseal_within(blob, passphrase, &KdfParams::default())
+}
+
+/// Like `unseal`, refusing blobs whose KDF costs exceed `max` before deriving the key
+pub fn unseal_within(blob: &[u8], passphrase: &[u8], max: &KdfParams) -> Result<Zeroizing<Vec<u8>>, SealError> {
+    let params = sealed_params(blob)?;
+    params.check_within(max)?;
+    if blob.len() < HEADER_LEN + TAG_LEN {
+        return Err(SealError::InvalidFormat("truncated ciphertext".to_string()));
+    }
+
+    let (header, ciphertext) = blob.split_at(HEADER_LEN);
+    let salt: [u8; SEALED_SALT_LEN] = header[17..17 + SEALED_SALT_LEN].try_into().unwrap();
+    let nonce = &header[17 + SEALED_SALT_LEN..];
+
+    let cipher = derive_cipher(passphrase, &salt, &params);
+    cipher
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: header })
+        .map(Zeroizing::new)
+        .map_err(|_| SealError::DecryptionFailed)
+}
+
+/// Read and validate the KDF params from a sealed blob's header
+pub fn sealed_params(blob: &[u8]) -> Result<KdfParams, SealError> {
+    if blob.len() < HEADER_LEN {
+        return Err(SealError::InvalidFormat("truncated header".to_string()));
+    }
+    if &blob[0..4] != SEALED_MAGIC {
+        return Err(SealError::InvalidFormat("bad magic".to_string()));
+    }
+    if blob[4] != SEALED_VERSION {
+        return Err(SealError::UnsupportedVersion(blob[4]));
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(blob[offset..offset + 4].try_into().unwrap());
+    let params = KdfParams {
+        memory_kib: read_u32(5),
+        iterations: read_u32(9),
+        parallelism: read_u32(13),
+    };
+    params.validate()?;
+    Ok(params)
+}
+
+fn derive_cipher(passphrase: &[u8], salt: &[u8], params: &KdfParams) -> XChaCha20Poly1305 {
+    // `params` passed `validate`, which is stricter than argon2's own bounds
+    let costs = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(KEY_LEN))
+        .expect("validated KDF params");
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(KDF_ALGORITHM, KDF_VERSION, costs)
+        .hash_password_into(passphrase, salt, key.as_mut())
+        .expect("validated KDF params");
+    XChaCha20Poly1305::new_from_slice(key.as_ref()).expect("32-byte key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argon2::{AssociatedData, ParamsBuilder};
+
+    #[test]
+    fn test_kdf_matches_rfc9106_argon2id_vector() {
+        let params = ParamsBuilder::new()
+            .m_cost(32)
+            .t_cost(3)
+            .p_cost(4)
+            .data(AssociatedData::new(&[0x04; 12]).unwrap())
+            .output_len(KEY_LEN)
+            .build()
+            .unwrap();
+        let kdf = Argon2::new_with_secret(&[0x03; 8], KDF_ALGORITHM, KDF_VERSION, params).unwrap();
+
+        let mut tag = [0u8; KEY_LEN];
+        kdf.hash_password_into(&[0x01; 32], &[0x02; 16], &mut tag).unwrap();
+        assert_eq!(
+            hex::encode(tag),
+            "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659"
+        );
+    }
+}
@@ -7,7 +7,7 @@
 // - Node configuration
 
 use crate::identity::Keypair;
-use crate::ledger::{MeshState, MeshStateError, NodeId};
+use crate::ledger::{MeshState, MeshStateError, NodeId, StateDelta};
 use crate::vault::{Vault, VaultError};
 use std::path::Path;
 use thiserror::Error;
@@ -18,7 +18,9 @@ mod keys {
     pub const IDENTITY_KEYPAIR_PREFIX: &[u8] = b"identity:keypair:";
     pub const VAULT: &[u8] = b"vault:state";
     pub const MESH_STATE: &[u8] = b"ledger:mesh_state";
+    pub const MESH_DELTA_PREFIX: &[u8] = b"ledger:mesh_delta:";
     pub const NODE_ID: &[u8] = b"node:id";
+    pub const NONCE_COUNTER: &[u8] = b"wallet:nonce_counter";
 }
 
 /// Errors from storage operations
@@ -46,6 +48,16 @@ impl From<sled::Error> for StoreError {
     }
 }
 
+/// A stored key and its value
+pub type StoreEntry = (Vec<u8>, Vec<u8>);
+
+/// One write in an atomic batch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoreWrite {
+    Put { key: Vec<u8>, value: Vec<u8> },
+    Delete { key: Vec<u8> },
+}
+
 /// Statistics about the storage
 #[derive(Clone, Debug)]
 pub struct StorageStats {
@@ -55,24 +67,32 @@ pub struct StorageStats {
     pub disk_size_bytes: u64,
 }
 
+/// Name sled gives the default tree (not a user namespace)
+const DEFAULT_TREE: &[u8] = b"__sled__default";
+
 /// Persistent key-value store for mesh data
 ///
 /// Uses sled for crash-safe, embedded storage.
 /// All writes are atomic and durable after flush.
+/// A store opened with `open` uses the default tree; `namespace` returns a
+/// handle to a separate sled tree in the same database with the same typed API.
+#[derive(Clone)]
 pub struct MeshStore {
     db: sled::Db,
+    tree: sled::Tree,
 }
 
 impl MeshStore {
     /// Open or create a store at the given path
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StoreError> {
         let db = sled::open(path).map_err(|e| StoreError::OpenFailed(e.to_string()))?;
-        Ok(Self { db })
+        let tree = (*db).clone();
+        Ok(Self { db, tree })
     }
 
     /// Check if the store is empty
     pub fn is_empty(&self) -> Result<bool, StoreError> {
-        Ok(self.db.is_empty())
+        Ok(self.tree.is_empty())
     }
 
     /// Flush all pending writes to disk
@@ -86,48 +106,121 @@ impl MeshStore {
     /// Get storage statistics
     pub fn stats(&self) -> Result<StorageStats, StoreError> {
         Ok(StorageStats {
-            key_count: self.db.len(),
+            key_count: self.tree.len(),
             disk_size_bytes: self.db.size_on_disk().unwrap_or(0),
         })
     }
 
+    // ========================================================================
+    // NAMESPACES
+    // ========================================================================
+
+    /// Open (or create) a namespace backed by its own sled tree
+    pub fn namespace(&self, name: &str) -> Result<MeshStore, StoreError> {
+        let tree = self.db.open_tree(name)?;
+        Ok(Self {
+            db: self.db.clone(),
+            tree,
+        })
+    }
+
+    /// List namespaces in the database that start with `prefix`
+    pub fn list_namespaces(&self, prefix: &str) -> Vec<String> {
+        self.db
+            .tree_names()
+            .into_iter()
+            .filter(|name| name.as_ref() != DEFAULT_TREE)
+            .filter_map(|name| String::from_utf8(name.to_vec()).ok())
+            .filter(|name| name.starts_with(prefix))
+            .collect()
+    }
+
+    /// Check whether a namespace exists
+    pub fn has_namespace(&self, name: &str) -> bool {
+        self.db
+            .tree_names()
+            .iter()
+            .any(|tree| tree.as_ref() == name.as_bytes())
+    }
+
+    /// Overwrite every value in a namespace with zeros, flush, then drop it.
+    /// Returns false if the namespace did not exist.
+    pub fn wipe_namespace(&self, name: &str) -> Result<bool, StoreError> {
+        if !self.has_namespace(name) {
+            return Ok(false);
+        }
+
+        let tree = self.db.open_tree(name)?;
+        for result in tree.iter() {
+            let (key, value) = result?;
+            tree.insert(key, vec![0u8; value.len()])?;
+        }
+        tree.flush()
+            .map_err(|e| StoreError::FlushFailed(e.to_string()))?;
+
+        Ok(self.db.drop_tree(name)?)
+    }
+
     // ========================================================================
     // RAW KEY-VALUE OPERATIONS
     // ========================================================================
 
     /// Put raw bytes
     pub fn put_raw(&self, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
-        self.db.insert(key, value)?;
+        self.tree.insert(key, value)?;
         Ok(())
     }
 
     /// Get raw bytes
     pub fn get_raw(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
-        Ok(self.db.get(key)?.map(|v| v.to_vec()))
+        Ok(self.tree.get(key)?.map(|v| v.to_vec()))
     }
 
     /// Delete a key
     pub fn delete(&self, key: &[u8]) -> Result<(), StoreError> {
-        self.db.remove(key)?;
+        self.tree.remove(key)?;
         Ok(())
     }
 
     /// List all keys with a given prefix
     pub fn list_keys_with_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, StoreError> {
         let mut keys = Vec::new();
-        for result in self.db.scan_prefix(prefix) {
+        for result in self.tree.scan_prefix(prefix) {
             let (key, _) = result?;
             keys.push(key.to_vec());
         }
         Ok(keys)
     }
 
+    /// List all key-value pairs with a given prefix, in key order
+    pub fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<StoreEntry>, StoreError> {
+        let mut entries = Vec::new();
+        for result in self.tree.scan_prefix(prefix) {
+            let (key, value) = result?;
+            entries.push((key.to_vec(), value.to_vec()));
+        }
+        Ok(entries)
+    }
+
+    /// Apply writes atomically: either all of them land or none do
+    pub fn apply_batch(&self, writes: &[StoreWrite]) -> Result<(), StoreError> {
+        let mut batch = sled::Batch::default();
+        for write in writes {
+            match write {
+                StoreWrite::Put { key, value } => batch.insert(key.as_slice(), value.as_slice()),
+                StoreWrite::Delete { key } => batch.remove(key.as_slice()),
+            }
+        }
+        self.tree.apply_batch(batch)?;
+        Ok(())
+    }
+
     /// Delete all keys with a given prefix
     pub fn delete_with_prefix(&self, prefix: &[u8]) -> Result<usize, StoreError> {
         let mut deleted = 0;
-        for result in self.db.scan_prefix(prefix) {
+        for result in self.tree.scan_prefix(prefix) {
             let (key, _) = result?;
-            self.db.remove(key)?;
+            self.tree.remove(key)?;
             deleted += 1;
         }
         Ok(deleted)
@@ -201,22 +294,56 @@ impl MeshStore {
     // LEDGER STATE PERSISTENCE
     // ========================================================================
 
-    /// Save the mesh state
+    /// Save the whole mesh state, dropping any deltas appended since the last save
+    ///
+    /// Also how the delta log is compacted: the state and the deletions land atomically.
     pub fn save_mesh_state(&self, state: &MeshState) -> Result<(), StoreError> {
-        let bytes = state.to_bytes();
-        self.put_raw(keys::MESH_STATE, &bytes)
+        let mut writes = vec![StoreWrite::Put {
+            key: keys::MESH_STATE.to_vec(),
+            value: state.to_bytes(),
+        }];
+        for key in self.list_keys_with_prefix(keys::MESH_DELTA_PREFIX)? {
+            writes.push(StoreWrite::Delete { key });
+        }
+        self.apply_batch(&writes)
+    }
+
+    /// Append a delta from `MeshState::changes_since` to the saved state, returning its sequence number
+    pub fn append_mesh_delta(&self, delta: &StateDelta) -> Result<u64, StoreError> {
+        let next = match self.list_keys_with_prefix(keys::MESH_DELTA_PREFIX)?.last() {
+            Some(key) => Self::delta_sequence(key)? + 1,
+            None => 0,
+        };
+        let key = [keys::MESH_DELTA_PREFIX, &next.to_be_bytes()].concat();
+        self.put_raw(&key, &delta.to_bytes())?;
+        Ok(next)
     }
 
-    /// Load the mesh state
+    /// Number of deltas appended since the mesh state was last saved whole
+    pub fn mesh_delta_count(&self) -> Result<usize, StoreError> {
+        Ok(self.list_keys_with_prefix(keys::MESH_DELTA_PREFIX)?.len())
+    }
+
+    /// Load the mesh state, replaying any appended deltas in order
     pub fn load_mesh_state(&self) -> Result<Option<MeshState>, StoreError> {
-        match self.get_raw(keys::MESH_STATE)? {
-            Some(bytes) => {
-                let state = MeshState::from_bytes(&bytes)
-                    .map_err(|e: MeshStateError| StoreError::DeserializationFailed(e.to_string()))?;
-                Ok(Some(state))
-            }
-            None => Ok(None),
+        let bytes = match self.get_raw(keys::MESH_STATE)? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let failed = |e: MeshStateError| StoreError::DeserializationFailed(e.to_string());
+        let mut state = MeshState::from_bytes(&bytes).map_err(failed)?;
+        for (_, value) in self.scan_prefix(keys::MESH_DELTA_PREFIX)? {
+            let delta = StateDelta::from_bytes(&value).map_err(failed)?;
+            state.apply_state_delta(delta).map_err(failed)?;
         }
+        Ok(Some(state))
+    }
+
+    fn delta_sequence(key: &[u8]) -> Result<u64, StoreError> {
+        key[keys::MESH_DELTA_PREFIX.len()..]
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| StoreError::DeserializationFailed("malformed delta key".to_string()))
     }
 
     // ========================================================================
@@ -245,6 +372,24 @@ impl MeshStore {
         }
     }
 
+    /// Save the wallet's IOU nonce counter
+    pub fn save_nonce_counter(&self, nonce: u64) -> Result<(), StoreError> {
+        self.put_raw(keys::NONCE_COUNTER, &nonce.to_le_bytes())
+    }
+
+    /// Load the wallet's IOU nonce counter
+    pub fn load_nonce_counter(&self) -> Result<Option<u64>, StoreError> {
+        match self.get_raw(keys::NONCE_COUNTER)? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes.as_slice().try_into().map_err(|_| {
+                    StoreError::DeserializationFailed("Invalid nonce counter length".to_string())
+                })?;
+                Ok(Some(u64::from_le_bytes(arr)))
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Get the node ID, creating one if it doesn't exist
     pub fn get_or_create_node_id(&self) -> Result<NodeId, StoreError> {
         if let Some(node_id) = self.load_node_id()? {
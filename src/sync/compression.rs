@@ -0,0 +1,134 @@
+// Payload Compression - smaller state and delta exchanges
+//
+// Serialized states and deltas are mostly IDs, keys and DIDs that repeat across
+// entries, so they deflate well; over LoRa or a metered link that matters. A
+// packed payload starts with one header byte saying whether the rest is raw or
+// deflated. Payloads below the threshold stay raw, as do those that don't
+// shrink. Nodes that predate the header send bare postcard bytes, so a payload
+// whose first byte is not a header is decoded as unheaded.
+//
+// Gossip messages are packed the same way (see `Message::to_wire_bytes`).
+
+use crate::sync::protocol::ProtocolError;
+use miniz_oxide::inflate::TINFLStatus;
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// Header byte of a packed payload sent uncompressed
+pub const PAYLOAD_RAW: u8 = 0xD0;
+
+/// Header byte of a packed payload deflated after the header
+pub const PAYLOAD_DEFLATE: u8 = 0xD1;
+
+/// How a packed payload is compressed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    #[default]
+    Deflate,
+}
+
+impl Compression {
+    /// Header byte of a payload packed with this compression
+    pub fn header(&self) -> u8 {
+        match self {
+            Compression::None => PAYLOAD_RAW,
+            Compression::Deflate => PAYLOAD_DEFLATE,
+        }
+    }
+
+    /// The compression a header byte stands for
+    pub fn from_header(header: u8) -> Option<Self> {
+        match header {
+            PAYLOAD_RAW => Some(Compression::None),
+            PAYLOAD_DEFLATE => Some(Compression::Deflate),
+            _ => None,
+        }
+    }
+}
+
+/// Largest payload a deflated one may expand to, so a hostile peer can't exhaust memory
+pub const MAX_PAYLOAD_BYTES: usize = 64 * 1024 * 1024;
+
+/// Deflate level; payloads are packed once per exchange, so favour size
+const PAYLOAD_COMPRESSION_LEVEL: u8 = 6;
+
+/// Pack serialized bytes for sending, deflating them if at least `min_bytes` long
+///
+/// A `min_bytes` of 0 disables compression and returns the bytes unheaded, as
+/// nodes that predate the header expect.
+pub fn pack_payload(bytes: &[u8], min_bytes: usize) -> Vec<u8> {
+    if min_bytes == 0 {
+        return bytes.to_vec();
+    }
+    pack_with(bytes, Compression::Deflate, min_bytes)
+}
+
+/// Pack bytes behind a header, compressed with `compression` if at least `min_bytes` long
+pub(crate) fn pack_with(bytes: &[u8], compression: Compression, min_bytes: usize) -> Vec<u8> {
+    if compression != Compression::None && bytes.len() >= min_bytes {
+        let compressed = miniz_oxide::deflate::compress_to_vec(bytes, PAYLOAD_COMPRESSION_LEVEL);
+        if compressed.len() < bytes.len() {
+            let mut packed = Vec::with_capacity(compressed.len() + 1);
+            packed.push(PAYLOAD_DEFLATE);
+            packed.extend_from_slice(&compressed);
+            return packed;
+        }
+    }
+    let mut packed = Vec::with_capacity(bytes.len() + 1);
+    packed.push(PAYLOAD_RAW);
+    packed.extend_from_slice(bytes);
+    packed
+}
+
+/// The serialized bytes inside a packed payload, or `None` if it has no header
+/// or won't inflate
+pub fn unpack_payload(payload: &[u8]) -> Option<Cow<'_, [u8]>> {
+    unpack_with_limit(payload, MAX_PAYLOAD_BYTES)?.ok()
+}
+
+/// Unpack a payload that starts with a header, expanding to at most `limit` bytes
+///
+/// `None` if the payload has no header.
+pub(crate) fn unpack_with_limit(payload: &[u8], limit: usize) -> Option<Result<Cow<'_, [u8]>, ProtocolError>> {
+    let (&header, rest) = payload.split_first()?;
+    let unpacked = match Compression::from_header(header)? {
+        Compression::None if rest.len() > limit => Err(ProtocolError::PayloadTooLarge),
+        Compression::None => Ok(Cow::Borrowed(rest)),
+        Compression::Deflate => miniz_oxide::inflate::decompress_to_vec_with_limit(rest, limit)
+            .map(Cow::Owned)
+            .map_err(|e| match e.status {
+                TINFLStatus::HasMoreOutput => ProtocolError::PayloadTooLarge,
+                _ => ProtocolError::DeserializationFailed,
+            }),
+    };
+    Some(unpacked)
+}
+
+/// Why [`decode_payload`] couldn't read a payload
+#[derive(Error, Debug)]
+pub enum PayloadError<E> {
+    /// The payload has a header but its body won't unpack
+    #[error("Payload won't unpack: {0}")]
+    Unpack(#[from] ProtocolError),
+
+    /// The unpacked bytes don't decode
+    #[error("Payload won't decode: {0}")]
+    Decode(E),
+}
+
+/// Decode a payload from [`pack_payload`] or an unheaded one with `decode`
+///
+/// A payload whose first byte is a header is only ever read as packed: postcard
+/// ignores trailing bytes, so retrying a corrupt packed payload whole could
+/// decode it as the wrong value. An unheaded payload from an older node that
+/// happens to start with a header byte is refused.
+pub fn decode_payload<T, E>(
+    payload: &[u8],
+    decode: impl Fn(&[u8]) -> Result<T, E>,
+) -> Result<T, PayloadError<E>> {
+    match unpack_with_limit(payload, MAX_PAYLOAD_BYTES) {
+        Some(bytes) => decode(&bytes?).map_err(PayloadError::Decode),
+        None => decode(payload).map_err(PayloadError::Decode),
+    }
+}
@@ -4,14 +4,66 @@
 // - Push: Rumor spreading for new IOUs
 // - Pull: Anti-entropy for state reconciliation
 // - Heartbeat: Liveness and version broadcasting
+//
+// `tick` drives the rounds: each one pushes pending announcements and due
+// heartbeats to `fanout` random peers, and every anti-entropy interval does a
+// push-pull sync with one random peer so lost messages are eventually repaired.
+// With adaptive scheduling each peer gets its own interval instead, doubling
+// while syncs with it bring nothing new, and the stalest due peer goes first.
+// `tick_with_registry` takes the peers from a `PeerRegistry` instead, favouring
+// the best-reputed and skipping quarantined ones.
+//
+// Heartbeats also drive liveness: a peer that misses `max_missed` of them is
+// marked unreachable by `check_liveness`, and its next heartbeat brings it back
+// with an anti-entropy sync in the following round to catch up on what it missed.
+//
+// Messages from a peer pass through its token buckets first (see
+// `with_rate_limit`), so a flood of sync requests is dropped rather than each
+// answered with our whole state. A peer that keeps it up is throttled for a
+// while and loses reputation, so it also drops down the gossip order. Outgoing messages can go through a bounded
+// queue that reports `Backpressure` once it fills past its high-water mark.
+// The queue has two levels, urgent and bulk (see `Message::priority`), and
+// always drains urgent first; large sync responses are queued in chunks so a
+// payment announcement can slip in between them instead of waiting out the
+// whole state transfer.
+//
+// Valid IOU announcements from peers are relayed on as `Forward` events, each
+// relay spending one unit of the announcement's TTL; `forward_targets` skips
+// the nodes it has already passed through, and seen-message deduplication stops
+// it coming back round a loop.
+//
+// Peers also spread word of each other: whenever two registry-backed nodes sync,
+// each sends a `PeerAnnouncement` listing the best peers it has recently talked
+// to. Usable addresses land in the receiver's registry as `Discovered`, and with
+// `max_auto_dial` set the next rounds dial a few of them with a Hello.
+//
+// A node given its keypair (`with_keypair`) puts its public key in its Hello and
+// signs what it sends peers that agreed to signed messages. Peers' messages,
+// whether given with a registry or as coming from a peer, are checked against
+// the keys peers announce and their sequence numbers. Unsigned messages are
+// refused unless `accept_unsigned` is set for nodes without keys, and always
+// from a node whose key we know, so a peer can't speak for a node ID it doesn't hold.
+//
+// Events are returned from the methods that produce them, and also pushed to
+// any `subscribe`d consumers so a tokio task can await them instead of polling.
 
-use crate::identity::PublicKey;
+use crate::identity::{Keypair, PublicKey};
 use crate::iou::SignedIOU;
-use crate::ledger::{IOUEntry, MergeResult, MeshState, NodeId};
+use crate::ledger::{CausalRelation, IOUEntry, MergePolicy, MergeResult, MeshState, NodeId, VersionVector};
+use crate::sync::compression::{self, Compression};
+use crate::sync::peer::{PeerEvent, PeerRegistry, PeerState};
+use crate::sync::rate_limit::{Admission, PeerLimits};
+use crate::sync::subscription::{GossipSubscription, Subscribers, DEFAULT_SUBSCRIPTION_CAPACITY};
 use crate::sync::protocol::{
-    Heartbeat, IOUAnnouncement, Message, MessageId, SyncRequest, SyncResponse,
+    Features, Heartbeat, Hello, IOUAnnouncement, KnownPeer, Message, MessageId, PeerAnnouncement,
+    Priority, ProtocolError, SignedMessage, SyncRequest,
+    SyncResponse, DEFAULT_ANNOUNCEMENT_TTL, MAX_MESSAGE_BYTES, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
 };
-use std::collections::HashMap;
+use rand::seq::SliceRandom;
+use std::cmp::Reverse;
+use crate::transport::PeerAddress;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
@@ -26,6 +78,36 @@ pub enum GossipError {
 
     #[error("State error: {0}")]
     StateError(String),
+
+    #[error("Unsupported protocol version: {0}")]
+    UnsupportedVersion(u16),
+
+    #[error("Feature not negotiated with peer: {0}")]
+    FeatureNotNegotiated(Features),
+
+    #[error("Protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+
+    #[error("Outbound queue is full")]
+    OutboundQueueFull,
+
+    #[error("Unsigned message refused")]
+    UnsignedMessage,
+
+    #[error("Signed by a node whose key we don't know")]
+    UnknownSigner,
+
+    #[error("Invalid message signature")]
+    InvalidSignature,
+
+    #[error("Public key does not match the sender's node ID")]
+    KeyMismatch,
+
+    #[error("Message claims a sender other than its signer")]
+    SenderMismatch,
+
+    #[error("Replayed message: sequence {0} is not past the last accepted")]
+    Replayed(u64),
 }
 
 /// Configuration for the gossip engine
@@ -35,12 +117,60 @@ pub struct GossipConfig {
     pub fanout: usize,
     /// Maximum hops for IOU announcements
     pub max_hops: u8,
-    /// Heartbeat interval in seconds
+    /// Whether IOU announcements from peers are relayed on to others
+    pub relay_enabled: bool,
+    /// Most relays an IOU announcement may take; higher TTLs from peers are capped to it
+    pub max_ttl: u8,
+    /// Heartbeat interval in seconds (0 = no heartbeats)
     pub heartbeat_interval_secs: u64,
+    /// Heartbeats a peer may miss before it is marked unreachable (0 = never)
+    pub max_missed: u32,
+    /// Push-pull anti-entropy interval in seconds (0 = disabled)
+    pub anti_entropy_interval_secs: u64,
     /// How long to remember seen messages (seconds)
     pub seen_ttl_secs: u64,
     /// Maximum seen messages to track
     pub max_seen_messages: usize,
+    /// Maximum entries per page of a cursor-based sync
+    pub sync_page_size: usize,
+    /// Longest per-peer anti-entropy interval under adaptive back-off in seconds
+    /// (0 = fixed schedule)
+    pub max_interval_secs: u64,
+    /// Smallest state, delta or message payload to compress, in bytes (0 = never compress)
+    pub compression_min_bytes: usize,
+    /// How messages to peers that agreed to compression are compressed
+    pub compression: Compression,
+    /// Largest a received message may be once decompressed
+    pub max_message_bytes: usize,
+    /// Optional protocol features offered to peers in our Hello
+    pub features: Features,
+    /// Messages per second accepted from each peer (0 = unlimited)
+    pub rate_limit_messages_per_sec: u64,
+    /// Bytes per second accepted from each peer (0 = unlimited)
+    pub rate_limit_bytes_per_sec: u64,
+    /// Messages in a row over the rate limit after which a peer is throttled (0 = never)
+    pub throttle_after: u32,
+    /// How long a throttled peer's messages are all dropped, in seconds
+    pub throttle_secs: u64,
+    /// Most known peers shared with a peer we sync with (0 = don't share)
+    pub share_peers: usize,
+    /// Longest since we last heard from a peer for it to be shared, in seconds
+    pub share_max_age_secs: u64,
+    /// Most discovered peers being dialed at once (0 = never dial)
+    pub max_auto_dial: usize,
+    /// Address we listen on, announced to peers and never dialed
+    pub local_address: Option<SocketAddr>,
+    /// Most messages the outbound queue holds
+    pub outbound_queue_size: usize,
+    /// Queue length above which `Backpressure` is reported
+    pub outbound_high_water: usize,
+    /// Most entries per queued sync response; larger ones are split (0 = never split)
+    pub sync_chunk_entries: usize,
+    /// Whether unsigned messages from peers are accepted, for nodes that predate signing
+    /// (off by default)
+    pub accept_unsigned: bool,
+    /// Events each subscription buffers before dropping its oldest
+    pub subscription_capacity: usize,
 }
 
 impl Default for GossipConfig {
@@ -48,9 +178,32 @@ impl Default for GossipConfig {
         Self {
             fanout: 3,
             max_hops: 6,
+            relay_enabled: true,
+            max_ttl: DEFAULT_ANNOUNCEMENT_TTL,
             heartbeat_interval_secs: 30,
+            max_missed: 3,
+            anti_entropy_interval_secs: 60,
             seen_ttl_secs: 300, // 5 minutes
             max_seen_messages: 10000,
+            sync_page_size: 256,
+            max_interval_secs: 0,
+            compression_min_bytes: 0,
+            compression: Compression::Deflate,
+            max_message_bytes: MAX_MESSAGE_BYTES,
+            features: Features::SUPPORTED,
+            rate_limit_messages_per_sec: 0,
+            rate_limit_bytes_per_sec: 0,
+            throttle_after: 50,
+            throttle_secs: 60,
+            share_peers: 8,
+            share_max_age_secs: 3600,
+            max_auto_dial: 0,
+            local_address: None,
+            outbound_queue_size: 1024,
+            outbound_high_water: 768,
+            sync_chunk_entries: 64,
+            accept_unsigned: false,
+            subscription_capacity: DEFAULT_SUBSCRIPTION_CAPACITY,
         }
     }
 }
@@ -73,11 +226,151 @@ impl GossipConfig {
         self
     }
 
+    /// Enable or disable relaying IOU announcements from peers
+    pub fn with_relay(mut self, enabled: bool) -> Self {
+        self.relay_enabled = enabled;
+        self
+    }
+
+    /// Set the most relays an IOU announcement may take
+    pub fn with_max_ttl(mut self, ttl: u8) -> Self {
+        self.max_ttl = ttl;
+        self
+    }
+
     /// Set heartbeat interval
     pub fn with_heartbeat_interval(mut self, secs: u64) -> Self {
         self.heartbeat_interval_secs = secs;
         self
     }
+
+    /// Set how many heartbeats a peer may miss before it is marked unreachable
+    pub fn with_max_missed(mut self, missed: u32) -> Self {
+        self.max_missed = missed;
+        self
+    }
+
+    /// Set the anti-entropy interval
+    ///
+    /// Shorter intervals converge faster after message loss at the cost of a
+    /// full state exchange with one peer per round.
+    pub fn with_anti_entropy_interval(mut self, secs: u64) -> Self {
+        self.anti_entropy_interval_secs = secs;
+        self
+    }
+
+    /// Set the page size for cursor-based syncs
+    pub fn with_sync_page_size(mut self, size: usize) -> Self {
+        self.sync_page_size = size;
+        self
+    }
+
+    /// Enable adaptive anti-entropy, backing off per peer up to `secs`
+    ///
+    /// Each idle sync with a peer doubles its interval, starting from the
+    /// anti-entropy interval; new local or merged entries reset every peer.
+    pub fn with_max_interval(mut self, secs: u64) -> Self {
+        self.max_interval_secs = secs;
+        self
+    }
+
+    /// Deflate state and delta payloads of at least `min_bytes`
+    ///
+    /// Smaller payloads are sent raw behind the same header, since deflate gains
+    /// little on them; peers must be new enough to read the header.
+    pub fn with_compression(mut self, min_bytes: usize) -> Self {
+        self.compression_min_bytes = min_bytes;
+        self
+    }
+
+    /// Set how messages are compressed once over the compression threshold
+    pub fn with_compression_method(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set the largest a received message may be once decompressed
+    pub fn with_max_message_bytes(mut self, max: usize) -> Self {
+        self.max_message_bytes = max;
+        self
+    }
+
+    /// Set the optional features offered to peers
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Limit what each peer may send us to `messages_per_sec` and `bytes_per_sec`
+    /// (0 leaves that dimension unlimited)
+    ///
+    /// Each peer may burst up to one second's worth; messages over the limit are dropped.
+    pub fn with_rate_limit(mut self, messages_per_sec: u64, bytes_per_sec: u64) -> Self {
+        self.rate_limit_messages_per_sec = messages_per_sec;
+        self.rate_limit_bytes_per_sec = bytes_per_sec;
+        self
+    }
+
+    /// Throttle a peer for `secs` once `after` of its messages in a row are over
+    /// the rate limit (an `after` of 0 never throttles)
+    pub fn with_throttle(mut self, after: u32, secs: u64) -> Self {
+        self.throttle_after = after;
+        self.throttle_secs = secs;
+        self
+    }
+
+    /// Share up to `count` peers heard from within `max_age_secs` with each peer we sync with
+    pub fn with_peer_sharing(mut self, count: usize, max_age_secs: u64) -> Self {
+        self.share_peers = count;
+        self.share_max_age_secs = max_age_secs;
+        self
+    }
+
+    /// Dial up to `max` discovered peers at once
+    pub fn with_max_auto_dial(mut self, max: usize) -> Self {
+        self.max_auto_dial = max;
+        self
+    }
+
+    /// Set the address we listen on
+    pub fn with_local_address(mut self, address: SocketAddr) -> Self {
+        self.local_address = Some(address);
+        self
+    }
+
+    /// Bound the outbound queue to `size` messages, reporting backpressure above `high_water`
+    pub fn with_outbound_queue(mut self, size: usize, high_water: usize) -> Self {
+        self.outbound_queue_size = size;
+        self.outbound_high_water = high_water;
+        self
+    }
+
+    /// Split queued sync responses into chunks of at most `entries` entries
+    pub fn with_sync_chunk_entries(mut self, entries: usize) -> Self {
+        self.sync_chunk_entries = entries;
+        self
+    }
+
+    /// Accept or refuse unsigned messages from peers
+    ///
+    /// Off by default; turn it on to keep talking to nodes without keys. Even
+    /// then, unsigned messages from a sender whose key we know are refused.
+    /// Hellos carrying a key are accepted either way, since they are how we learn it.
+    pub fn with_unsigned_messages(mut self, accept: bool) -> Self {
+        self.accept_unsigned = accept;
+        self
+    }
+
+    /// Buffer up to `capacity` events per subscription (see `GossipEngine::subscribe`)
+    pub fn with_subscription_capacity(mut self, capacity: usize) -> Self {
+        self.subscription_capacity = capacity;
+        self
+    }
+
+    /// Pack serialized state or delta bytes for sending under this config
+    pub fn pack_payload(&self, bytes: &[u8]) -> Vec<u8> {
+        compression::pack_payload(bytes, self.compression_min_bytes)
+    }
 }
 
 /// Events produced by the gossip engine
@@ -91,6 +384,20 @@ pub enum GossipEvent {
     NewIOU(SignedIOU),
     /// State was updated
     StateUpdated(MergeResult),
+    /// A peer missed too many heartbeats and is now unreachable
+    PeerLost(NodeId),
+    /// An unreachable peer sent a heartbeat again
+    PeerRecovered(NodeId),
+    /// The outbound queue passed its high-water mark, holding this many messages;
+    /// hold off creating IOUs until it drains
+    Backpressure(usize),
+    /// A peer flooded us past the hard limit; its messages are dropped for a while
+    PeerThrottled(NodeId),
+    /// Another peer told us of a peer we didn't know
+    PeerDiscovered(NodeId),
+    /// A subscription fell behind and this many of its oldest events were dropped;
+    /// only ever received from a `GossipSubscription`
+    Lagged(u64),
 }
 
 /// Statistics about the gossip engine
@@ -98,10 +405,80 @@ pub enum GossipEvent {
 pub struct GossipStats {
     pub messages_processed: u64,
     pub messages_forwarded: u64,
+    /// IOU announcements from peers passed on to others
+    pub messages_relayed: u64,
     pub ious_received: u64,
     pub ious_rejected: u64,
     pub syncs_initiated: u64,
     pub syncs_completed: u64,
+    pub gossip_rounds: u64,
+    pub anti_entropy_rounds: u64,
+    /// Anti-entropy pushes left out because the peer already had everything we have
+    pub pushes_skipped: u64,
+    /// Anti-entropy rounds with no peer due under adaptive scheduling
+    pub rounds_skipped: u64,
+    /// Mean anti-entropy interval across scheduled peers
+    pub avg_interval_secs: f64,
+    /// Messages of a type too new for us, skipped
+    pub messages_skipped: u64,
+    /// Messages refused for using a feature not agreed with their sender
+    pub messages_refused: u64,
+    /// Peers marked unreachable for missing heartbeats
+    pub peers_lost: u64,
+    /// Unreachable peers that sent a heartbeat again
+    pub peers_recovered: u64,
+    /// Messages dropped for exceeding their sender's rate limit
+    pub rate_limited: u64,
+    /// Times a peer was throttled
+    pub peers_throttled: u64,
+    /// Messages dropped because the outbound queue was full
+    pub outbound_dropped: u64,
+    /// Urgent messages waiting in the outbound queue
+    pub urgent_queued: usize,
+    /// Bulk messages waiting in the outbound queue
+    pub bulk_queued: usize,
+    /// Extra messages queued by splitting large sync responses
+    pub sync_chunks: u64,
+    /// Peers learned of from other peers' announcements
+    pub peers_discovered: u64,
+    /// Discovered peers dialed
+    pub peers_dialed: u64,
+    /// Messages we signed
+    pub messages_signed: u64,
+    /// Signed messages from peers that verified
+    pub signatures_verified: u64,
+    /// Signed messages refused for a bad or unknown signature, sender or sequence
+    pub signatures_rejected: u64,
+    /// Unsigned messages refused
+    pub unsigned_refused: u64,
+}
+
+/// Protocol version and features agreed with one peer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeerProtocol {
+    version: u16,
+    features: Features,
+}
+
+impl PeerProtocol {
+    /// Agreed protocol version (the lower of both sides')
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Agreed features (those both sides support)
+    pub fn features(&self) -> Features {
+        self.features
+    }
+}
+
+/// When to next sync one peer under adaptive scheduling
+#[derive(Clone, Debug)]
+struct PeerSchedule {
+    /// Current interval between syncs
+    interval_secs: u64,
+    /// When we last synced with the peer (ms)
+    last_sync_ms: Option<u64>,
 }
 
 /// The gossip engine - orchestrates state synchronization
@@ -116,6 +493,40 @@ pub struct GossipEngine {
     seen_messages: HashMap<MessageId, u64>, // ID -> timestamp
     /// Pending outgoing IOU announcements
     pending_announcements: Vec<IOUAnnouncement>,
+    /// Last applied sync cursor per peer, in that peer's sequence
+    sync_cursors: HashMap<NodeId, u64>,
+    /// Latest version vector each peer sent us
+    peer_clocks: HashMap<NodeId, VersionVector>,
+    /// When `tick` last sent heartbeats (ms)
+    last_heartbeat_ms: Option<u64>,
+    /// When `tick` last ran anti-entropy (ms)
+    last_anti_entropy_ms: Option<u64>,
+    /// Per-peer anti-entropy schedules under adaptive scheduling
+    schedules: HashMap<NodeId, PeerSchedule>,
+    /// Protocol agreed with each peer that sent us a Hello
+    protocols: HashMap<NodeId, PeerProtocol>,
+    /// Recovered peers to sync with in the next round
+    resyncs: Vec<NodeId>,
+    /// Inbound rate limits per peer
+    limits: HashMap<NodeId, PeerLimits>,
+    /// Throttled peers whose reputation the next registry round lowers
+    penalties: Vec<NodeId>,
+    /// Urgent messages waiting to be sent
+    urgent: VecDeque<(NodeId, Message)>,
+    /// Bulk messages waiting to be sent, behind every urgent one
+    bulk: VecDeque<(NodeId, Message)>,
+    /// Whether `Backpressure` was reported and the queue hasn't drained since
+    backpressured: bool,
+    /// Our node key, if we sign what we send
+    keypair: Option<Keypair>,
+    /// Sequence number of the next message we sign
+    next_sequence: u64,
+    /// Last sequence number accepted from each peer
+    peer_sequences: HashMap<NodeId, u64>,
+    /// Keys peers announced in their Hellos, for paths without a registry
+    peer_keys: HashMap<NodeId, PublicKey>,
+    /// Async consumers of the events we return
+    subscribers: Subscribers,
     /// Statistics
     stats: GossipStats,
 }
@@ -129,10 +540,41 @@ impl GossipEngine {
             config,
             seen_messages: HashMap::new(),
             pending_announcements: Vec::new(),
+            sync_cursors: HashMap::new(),
+            peer_clocks: HashMap::new(),
+            last_heartbeat_ms: None,
+            last_anti_entropy_ms: None,
+            schedules: HashMap::new(),
+            protocols: HashMap::new(),
+            resyncs: Vec::new(),
+            limits: HashMap::new(),
+            penalties: Vec::new(),
+            urgent: VecDeque::new(),
+            bulk: VecDeque::new(),
+            backpressured: false,
+            keypair: None,
+            next_sequence: 0,
+            peer_sequences: HashMap::new(),
+            peer_keys: HashMap::new(),
+            subscribers: Subscribers::default(),
             stats: GossipStats::default(),
         }
     }
 
+    /// Sign what we send with `keypair`, which must derive our node ID
+    ///
+    /// Sequence numbers start from the clock in milliseconds, so after a restart
+    /// they carry on above the last ones peers accepted. A key for another node
+    /// is refused with `KeyMismatch`, since peers would reject everything it signs.
+    pub fn with_keypair(mut self, keypair: Keypair) -> Result<Self, GossipError> {
+        if NodeId::from_public_key(&keypair.public_key()) != self.node_id {
+            return Err(GossipError::KeyMismatch);
+        }
+        self.keypair = Some(keypair);
+        self.next_sequence = Self::now();
+        Ok(self)
+    }
+
     /// Get the current state
     pub fn state(&self) -> &MeshState {
         &self.state
@@ -165,7 +607,9 @@ impl GossipEngine {
     /// Announce a new IOU to the network
     pub fn announce_iou(&mut self, iou: SignedIOU, sender_pubkey: &PublicKey) {
         let announcement = IOUAnnouncement::new(iou, sender_pubkey.clone())
-            .with_max_hops(self.config.max_hops);
+            .with_max_hops(self.config.max_hops)
+            .with_ttl(self.config.max_ttl)
+            .with_seen_by(self.node_id.clone());
 
         // Check if we've already announced this
         let msg_id = announcement.id();
@@ -179,6 +623,7 @@ impl GossipEngine {
 
         // Add to pending
         self.pending_announcements.push(announcement);
+        self.reset_schedules();
     }
 
     /// Handle an incoming IOU announcement
@@ -195,6 +640,7 @@ impl GossipEngine {
             .map_err(|e| GossipError::InvalidIOU(e.to_string()))?;
 
         self.stats.ious_received += 1;
+        self.reset_schedules();
         Ok(())
     }
 
@@ -203,12 +649,29 @@ impl GossipEngine {
     // ========================================================================
 
     /// Handle an incoming sync request
-    pub fn handle_sync_request(&self, _request: &SyncRequest) -> SyncResponse {
+    ///
+    /// Requests with a cursor get one page of at most `sync_page_size` entries;
+    /// requests with a state summary get only the delta against it (a malformed
+    /// summary gets the whole state).
+    pub fn handle_sync_request(&self, request: &SyncRequest) -> SyncResponse {
+        if let Some(summary) = request.summary().filter(|summary| summary.validate().is_ok()) {
+            let delta = self.state.diff_against_summary(summary);
+            return SyncResponse::new(self.node_id.clone(), self.state.version(), delta.into_entries());
+        }
+
+        if let Some(cursor) = request.since_cursor() {
+            let (entries, next_cursor) = self.state.entries_since(cursor, self.config.sync_page_size);
+            return SyncResponse::new(self.node_id.clone(), self.state.version(), entries)
+                .with_next_cursor(next_cursor)
+                .with_has_more(next_cursor < self.state.cursor());
+        }
+
         // Get all entries (in a real implementation, we'd filter by version delta)
         // TODO: Use request.known_version() to send only delta
         let entries: Vec<IOUEntry> = self.state.all_entries().into_iter().cloned().collect();
 
         SyncResponse::new(self.node_id.clone(), self.state.version(), entries)
+            .with_clock(self.state.clock().clone())
     }
 
     /// Apply a sync response to our state
@@ -218,19 +681,38 @@ impl GossipEngine {
     ) -> Result<MergeResult, GossipError> {
         // Create a temporary state from the entries
         let mut temp_state = MeshState::new(response.sender().clone());
+        let mut all_valid = true;
 
         for entry in response.entries() {
-            // Add each entry to temp state (re-validate)
+            // Add each entry to temp state (re-validate, but keep entries that have since expired)
             let iou = entry.iou().clone();
             let pubkey = entry.sender_pubkey().clone();
-            let _ = temp_state.add_iou(iou, &pubkey);
+            all_valid &= temp_state.add_replicated_iou(iou, &pubkey).is_ok();
         }
 
-        // Merge into our state
-        let result = self.state.merge(&temp_state);
+        // The peer's clock only describes us once we hold its whole, valid state
+        let clock = match response.clock() {
+            Some(clock) => {
+                self.peer_clocks.insert(response.sender().clone(), clock.clone());
+                if all_valid { clock.clone() } else { VersionVector::new() }
+            }
+            None => VersionVector::new(),
+        };
+        temp_state.set_clock(clock);
+
+        // Merge into our state; the entries were just validated, so don't verify them again
+        let result = self.state.merge_under(&temp_state, MergePolicy::Trust);
 
         if result.new_entries > 0 {
             self.stats.syncs_completed += 1;
+            self.reset_schedules();
+        } else {
+            self.back_off(response.sender());
+        }
+
+        // Only advance once the page is merged, so an interrupted sync resumes before it
+        if let Some(cursor) = response.next_cursor() {
+            self.sync_cursors.insert(response.sender().clone(), cursor);
         }
 
         Ok(result)
@@ -241,50 +723,575 @@ impl GossipEngine {
         SyncRequest::new(self.node_id.clone(), self.state.version())
     }
 
+    /// Generate a sync request carrying our state summary, so the peer sends only what we lack
+    pub fn generate_summary_sync_request(&self) -> SyncRequest {
+        self.generate_sync_request().with_summary(self.state.summary())
+    }
+
+    /// Generate the anti-entropy sync request for `peer`: with our state summary
+    /// if the peer agreed to summary sync, otherwise for its whole state
+    pub fn generate_sync_request_to(&self, peer: &NodeId) -> SyncRequest {
+        if self.peer_features(peer).contains(Features::SUMMARY_SYNC) {
+            self.generate_summary_sync_request()
+        } else {
+            self.generate_sync_request()
+        }
+    }
+
+    /// Generate a paged sync request, resuming from the last cursor applied from `peer`
+    pub fn generate_sync_request_for(&self, peer: &NodeId) -> SyncRequest {
+        self.generate_sync_request().with_since_cursor(self.sync_cursor(peer))
+    }
+
+    /// Last sync cursor applied from `peer` (0 if never synced)
+    pub fn sync_cursor(&self, peer: &NodeId) -> u64 {
+        self.sync_cursors.get(peer).copied().unwrap_or(0)
+    }
+
+    /// Restore a peer's sync cursor, e.g. one persisted before a restart
+    pub fn set_sync_cursor(&mut self, peer: NodeId, cursor: u64) {
+        self.sync_cursors.insert(peer, cursor);
+    }
+
     // ========================================================================
     // HEARTBEAT
     // ========================================================================
 
     /// Generate a heartbeat message
     pub fn generate_heartbeat(&self) -> Heartbeat {
-        Heartbeat::new(self.node_id.clone(), self.state.version())
+        Heartbeat::new(self.node_id.clone(), self.state.version()).with_clock(self.state.clock().clone())
+    }
+
+    /// Latest version vector `peer` sent us, in a heartbeat or full-state sync response
+    pub fn peer_clock(&self, peer: &NodeId) -> Option<&VersionVector> {
+        self.peer_clocks.get(peer)
+    }
+
+    // ========================================================================
+    // LIVENESS
+    // ========================================================================
+
+    /// Mark peers in `registry` that missed too many heartbeats as unreachable
+    pub fn check_liveness(&mut self, registry: &mut PeerRegistry) -> Vec<GossipEvent> {
+        self.check_liveness_at(registry, Self::now())
+    }
+
+    /// Mark active peers silent for `max_missed` heartbeat intervals at `now_ms`
+    /// as unreachable, with a `PeerLost` event for each
+    ///
+    /// Does nothing if heartbeats or eviction are disabled.
+    pub fn check_liveness_at(&mut self, registry: &mut PeerRegistry, now_ms: u64) -> Vec<GossipEvent> {
+        if self.config.heartbeat_interval_secs == 0 || self.config.max_missed == 0 {
+            return Vec::new();
+        }
+        let timeout_ms = self
+            .config
+            .heartbeat_interval_secs
+            .saturating_mul(self.config.max_missed as u64)
+            .saturating_mul(1000);
+        let lost = registry.mark_unreachable_at(now_ms, timeout_ms);
+        self.stats.peers_lost += lost.len() as u64;
+        let events = lost.into_iter().map(GossipEvent::PeerLost).collect();
+        self.publish(events)
+    }
+
+    /// Process a message, recording heartbeats in `registry`
+    pub fn process_message_with_registry(
+        &mut self,
+        registry: &mut PeerRegistry,
+        msg: Message,
+    ) -> Result<Vec<GossipEvent>, GossipError> {
+        self.process_message_with_registry_at(registry, msg, Self::now())
+    }
+
+    /// Process a message at `now_ms`, recording heartbeats in `registry`
+    ///
+    /// A heartbeat from an unreachable peer reconnects it, leads the events with
+    /// `PeerRecovered` and has the next round sync with it whether or not
+    /// anti-entropy is due. Heartbeats from peers not in `registry` are processed
+    /// as usual.
+    ///
+    /// A `PeerAnnouncement` adds the peers it lists to `registry` (see
+    /// `handle_peer_announcement`) instead of being forwarded, and a sync request
+    /// is answered with our own announcement as well. A Hello from a peer we
+    /// dialed marks it connected.
+    ///
+    /// Signed messages are verified against the key `registry` holds for their
+    /// signer and processed unwrapped; unsigned ones are refused unless
+    /// `accept_unsigned` is set (see `GossipConfig::with_unsigned_messages`).
+    pub fn process_message_with_registry_at(
+        &mut self,
+        registry: &mut PeerRegistry,
+        msg: Message,
+        now_ms: u64,
+    ) -> Result<Vec<GossipEvent>, GossipError> {
+        let events = self.handle_with_registry(registry, msg, now_ms)?;
+        Ok(self.publish(events))
+    }
+
+    fn handle_with_registry(
+        &mut self,
+        registry: &mut PeerRegistry,
+        msg: Message,
+        now_ms: u64,
+    ) -> Result<Vec<GossipEvent>, GossipError> {
+        let msg = self.authenticate(Some(registry), msg)?;
+        let mut events = Vec::new();
+        match &msg {
+            Message::PeerAnnouncement(announcement) => {
+                return Ok(self.add_announced_peers(registry, announcement));
+            }
+            Message::SyncRequest(request) if self.config.share_peers > 0 => {
+                let announcement = self.peer_announcement_at(registry, request.sender(), now_ms);
+                let mut events = self.handle_message(msg)?;
+                events.push(GossipEvent::Forward(Message::PeerAnnouncement(announcement)));
+                return Ok(events);
+            }
+            Message::Hello(hello) => {
+                if let Some(peer) = registry.get_peer_mut(hello.node_id()) {
+                    if matches!(peer.state(), PeerState::Connecting | PeerState::Discovered) {
+                        peer.set_state(PeerState::Connected);
+                        peer.touch();
+                    }
+                }
+            }
+            _ => {}
+        }
+        if let Message::Heartbeat(heartbeat) = &msg {
+            let peer = heartbeat.sender();
+            if registry.record_heartbeat_at(peer, now_ms).unwrap_or(false) {
+                self.stats.peers_recovered += 1;
+                if !self.resyncs.contains(peer) {
+                    self.resyncs.push(peer.clone());
+                }
+                events.push(GossipEvent::PeerRecovered(peer.clone()));
+            }
+        }
+        events.extend(self.handle_message(msg)?);
+        Ok(events)
+    }
+
+    /// Whether `peer` is known to have everything we have, so pushing to it is pointless
+    fn peer_has_our_state(&self, peer: &NodeId) -> bool {
+        self.peer_clocks.get(peer).is_some_and(|clock| {
+            matches!(self.state.clock().compare(clock), CausalRelation::Behind | CausalRelation::Equal)
+        })
+    }
+
+    // ========================================================================
+    // PEER DISCOVERY
+    // ========================================================================
+
+    /// Our announcement for `peer` at `now_ms`
+    ///
+    /// Says where to reach us and lists up to `share_peers` peers from `registry`
+    /// (see `PeerRegistry::shareable_peers_at`), leaving out `peer` itself.
+    pub fn peer_announcement_at(&self, registry: &PeerRegistry, peer: &NodeId, now_ms: u64) -> PeerAnnouncement {
+        let known_peers = registry
+            .shareable_peers_at(self.config.share_peers + 1, now_ms, self.config.share_max_age_secs * 1000)
+            .into_iter()
+            .filter(|info| info.node_id() != peer)
+            .take(self.config.share_peers)
+            .map(|info| KnownPeer::new(info.node_id().clone(), PeerAddress::from_socket_addr(*info.address())))
+            .collect();
+
+        let announcement = match self.config.local_address {
+            Some(address) => {
+                PeerAnnouncement::new(self.node_id.clone(), address.port()).with_address(address.ip().to_string())
+            }
+            None => PeerAnnouncement::new(self.node_id.clone(), 0),
+        };
+        announcement.with_known_peers(known_peers)
+    }
+
+    /// Add the announcing peer and the peers it lists to `registry`
+    ///
+    /// Peers already known are left alone, as are addresses we couldn't dial (see
+    /// `dialable_address`); new ones are added as `Discovered` and reported with
+    /// `PeerDiscovered`.
+    pub fn handle_peer_announcement(
+        &mut self,
+        registry: &mut PeerRegistry,
+        announcement: &PeerAnnouncement,
+    ) -> Vec<GossipEvent> {
+        let events = self.add_announced_peers(registry, announcement);
+        self.publish(events)
+    }
+
+    fn add_announced_peers(&mut self, registry: &mut PeerRegistry, announcement: &PeerAnnouncement) -> Vec<GossipEvent> {
+        let announcer = announcement
+            .address()
+            .map(|host| KnownPeer::new(announcement.node_id().clone(), PeerAddress::tcp(host, announcement.port())));
+
+        let mut events = Vec::new();
+        for known in announcer.iter().chain(announcement.known_peers()) {
+            let Some(address) = self.dialable_address(known.node_id(), known.address()) else {
+                continue;
+            };
+            if registry.add_discovered(known.node_id().clone(), address).unwrap_or(false) {
+                self.stats.peers_discovered += 1;
+                events.push(GossipEvent::PeerDiscovered(known.node_id().clone()));
+            }
+        }
+        events
+    }
+
+    /// The socket address to dial `node_id` at, if `address` is one we can use
+    ///
+    /// Peers are dialed over TCP, so only TCP addresses with an IP host qualify,
+    /// and only in the same IP family as our own address when we have one.
+    /// Our own node and address, unspecified and multicast IPs, and port 0 are
+    /// refused.
+    pub fn dialable_address(&self, node_id: &NodeId, address: &PeerAddress) -> Option<SocketAddr> {
+        if *node_id == self.node_id {
+            return None;
+        }
+        let socket = address.socket_addr()?;
+        let ip = socket.ip();
+        if socket.port() == 0 || ip.is_unspecified() || ip.is_multicast() {
+            return None;
+        }
+        match self.config.local_address {
+            Some(local) if local == socket || local.is_ipv4() != socket.is_ipv4() => None,
+            _ => Some(socket),
+        }
+    }
+
+    /// Dial discovered peers until `max_auto_dial` are being dialed, in node ID order
+    ///
+    /// Each is marked `Connecting` and sent a Hello; its reply connects it.
+    fn dial_discovered(&mut self, registry: &mut PeerRegistry) -> Vec<(NodeId, Message)> {
+        let dialing = registry.peers_by_state(PeerState::Connecting).len();
+        let slots = self.config.max_auto_dial.saturating_sub(dialing);
+        if slots == 0 {
+            return Vec::new();
+        }
+        let mut discovered: Vec<NodeId> = registry
+            .peers_by_state(PeerState::Discovered)
+            .into_iter()
+            .map(|peer| peer.node_id().clone())
+            .collect();
+        discovered.sort_by_key(|id| *id.as_bytes());
+
+        let mut outgoing = Vec::new();
+        for peer in discovered.into_iter().take(slots) {
+            if let Some(info) = registry.get_peer_mut(&peer) {
+                info.set_state(PeerState::Connecting);
+            }
+            self.stats.peers_dialed += 1;
+            outgoing.push((peer, Message::Hello(self.generate_hello())));
+        }
+        outgoing
+    }
+
+    // ========================================================================
+    // HANDSHAKE
+    // ========================================================================
+
+    /// Generate the Hello to send a peer on connection
+    pub fn generate_hello(&self) -> Hello {
+        let hello = Hello::new(self.node_id.clone(), self.config.features);
+        match &self.keypair {
+            Some(keypair) => hello.with_public_key(keypair.public_key()),
+            None => hello,
+        }
+    }
+
+    /// Protocol agreed with `peer`, if it has sent us a Hello
+    pub fn peer_protocol(&self, peer: &NodeId) -> Option<&PeerProtocol> {
+        self.protocols.get(peer)
+    }
+
+    /// Features agreed with `peer`
+    ///
+    /// A peer that never sent a Hello is taken to be a version 1 node, which
+    /// predates the handshake and supports only `Features::LEGACY`.
+    pub fn peer_features(&self, peer: &NodeId) -> Features {
+        match self.protocols.get(peer) {
+            Some(protocol) => protocol.features,
+            None => self.config.features & Features::LEGACY,
+        }
+    }
+
+    /// Record the protocol agreed with the sender of `hello`
+    fn negotiate(&mut self, hello: &Hello) -> Result<PeerProtocol, GossipError> {
+        if hello.version() < MIN_PROTOCOL_VERSION {
+            return Err(GossipError::UnsupportedVersion(hello.version()));
+        }
+        let protocol = PeerProtocol {
+            version: hello.version().min(PROTOCOL_VERSION),
+            features: hello.features() & self.config.features & Features::SUPPORTED,
+        };
+        self.protocols.insert(hello.node_id().clone(), protocol);
+        Ok(protocol)
+    }
+
+    // ========================================================================
+    // SIGNING
+    // ========================================================================
+
+    /// Wrap `msg` in a signed envelope under our node key, or `None` without one
+    pub fn sign_message(&mut self, msg: &Message) -> Option<Message> {
+        let keypair = self.keypair.as_ref()?;
+        let signed = SignedMessage::sign(keypair, msg, self.next_sequence);
+        self.next_sequence += 1;
+        self.stats.messages_signed += 1;
+        Some(Message::Signed(signed))
+    }
+
+    /// Whether what we send `peer` goes out signed: we have a key and it agreed to signatures
+    fn signs_for(&self, peer: &NodeId) -> bool {
+        self.keypair.is_some() && self.peer_features(peer).contains(Features::SIGNED_MESSAGES)
+    }
+
+    /// Check `msg` against the keys we know, unwrapping it if signed
+    ///
+    /// Keys come from `registry` when there is one, else from the Hellos we have
+    /// seen. Hellos carrying a key register it first, signed or not, since the
+    /// key must derive the node ID it claims. Unsigned messages are refused
+    /// unless `accept_unsigned` is set, and even then when their sender has a
+    /// known key, so a signed message can't be passed off stripped of its envelope.
+    fn authenticate(&mut self, mut registry: Option<&mut PeerRegistry>, msg: Message) -> Result<Message, GossipError> {
+        let result = match msg {
+            Message::Signed(signed) => self.open_signed(registry, &signed),
+            Message::Hello(hello) if hello.public_key().is_some() => {
+                self.learn_key(registry.as_deref_mut(), &hello).map(|()| Message::Hello(hello))
+            }
+            msg if self.config.accept_unsigned
+                && msg.sender().is_none_or(|sender| self.known_key(registry.as_deref(), sender).is_none()) =>
+            {
+                return Ok(msg);
+            }
+            _ => {
+                self.stats.unsigned_refused += 1;
+                return Err(GossipError::UnsignedMessage);
+            }
+        };
+        match &result {
+            Ok(_) => self.stats.signatures_verified += 1,
+            Err(_) => self.stats.signatures_rejected += 1,
+        }
+        result
+    }
+
+    /// Verify a signed message and return the one inside
+    ///
+    /// The signer's key must be known, the inner message must not name another
+    /// sender, and the sequence number must be past the last one accepted.
+    fn open_signed(&mut self, mut registry: Option<&mut PeerRegistry>, signed: &SignedMessage) -> Result<Message, GossipError> {
+        let msg = signed.message()?;
+        if let Message::Hello(hello) = &msg {
+            self.learn_key(registry.as_deref_mut(), hello)?;
+        }
+        let key = self.known_key(registry.as_deref(), signed.sender()).ok_or(GossipError::UnknownSigner)?;
+        if !signed.verify(&key) {
+            return Err(GossipError::InvalidSignature);
+        }
+        if msg.sender().is_some_and(|sender| sender != signed.sender()) {
+            return Err(GossipError::SenderMismatch);
+        }
+        if self.peer_sequences.get(signed.sender()).is_some_and(|&last| signed.sequence() <= last) {
+            return Err(GossipError::Replayed(signed.sequence()));
+        }
+        self.peer_sequences.insert(signed.sender().clone(), signed.sequence());
+        Ok(msg)
+    }
+
+    /// The key `node_id` signs with, from `registry` first, then from its Hello
+    fn known_key(&self, registry: Option<&PeerRegistry>, node_id: &NodeId) -> Option<PublicKey> {
+        registry
+            .and_then(|registry| registry.public_key(node_id))
+            .or_else(|| self.peer_keys.get(node_id))
+            .cloned()
+    }
+
+    /// Register the key a Hello announces for its sender, if it has one
+    fn learn_key(&mut self, registry: Option<&mut PeerRegistry>, hello: &Hello) -> Result<(), GossipError> {
+        let Some(key) = hello.public_key() else {
+            return Ok(());
+        };
+        if NodeId::from_public_key(key) != *hello.node_id() {
+            return Err(GossipError::KeyMismatch);
+        }
+        if let Some(registry) = registry {
+            registry
+                .register_public_key(hello.node_id(), key.clone())
+                .map_err(|_| GossipError::KeyMismatch)?;
+        }
+        self.peer_keys.insert(hello.node_id().clone(), key.clone());
+        Ok(())
     }
 
     // ========================================================================
     // MESSAGE PROCESSING
     // ========================================================================
 
+    /// Process an incoming message from `peer`
+    ///
+    /// Like `process_message`, but refuses messages using a feature not agreed
+    /// with `peer` (see `peer_features`), and authenticates them like
+    /// `process_message_with_registry` using the keys peers sent in their Hellos.
+    pub fn process_message_from(&mut self, peer: &NodeId, msg: Message) -> Result<Vec<GossipEvent>, GossipError> {
+        self.process_message_from_at(peer, msg, Self::now())
+    }
+
+    /// Process an incoming message from `peer` at `now_ms`
+    ///
+    /// A message over `peer`'s rate limit, or from a throttled peer, is dropped
+    /// without being processed; the one that gets `peer` throttled returns
+    /// `PeerThrottled`.
+    pub fn process_message_from_at(
+        &mut self,
+        peer: &NodeId,
+        msg: Message,
+        now_ms: u64,
+    ) -> Result<Vec<GossipEvent>, GossipError> {
+        // Only measure the message if bytes are limited
+        let len = if self.config.rate_limit_bytes_per_sec > 0 { msg.to_bytes().len() } else { 0 };
+        let events = match self.admit(peer, len, now_ms) {
+            Some(dropped) => dropped,
+            None => self.process_negotiated(peer, msg)?,
+        };
+        Ok(self.publish(events))
+    }
+
+    /// Process a message from `peer` that uses only features agreed with it
+    ///
+    /// Signed messages must be signed by `peer` itself and are checked against
+    /// the key from its Hello; unsigned ones are subject to `accept_unsigned`.
+    fn process_negotiated(&mut self, peer: &NodeId, msg: Message) -> Result<Vec<GossipEvent>, GossipError> {
+        self.check_negotiated(peer, &msg)?;
+        if let Message::Signed(signed) = &msg {
+            if signed.sender() != peer {
+                self.stats.signatures_rejected += 1;
+                return Err(GossipError::SenderMismatch);
+            }
+        }
+        let msg = self.authenticate(None, msg)?;
+        self.check_negotiated(peer, &msg)?;
+        self.handle_message(msg)
+    }
+
+    /// Refuse `msg` if it uses a feature not agreed with `peer`
+    fn check_negotiated(&mut self, peer: &NodeId, msg: &Message) -> Result<(), GossipError> {
+        let missing = msg.required_features().difference(self.peer_features(peer));
+        if !missing.is_empty() {
+            self.stats.messages_refused += 1;
+            return Err(GossipError::FeatureNotNegotiated(missing));
+        }
+        Ok(())
+    }
+
+    /// Serialize a message for sending to `peer`
+    ///
+    /// Compressed under the config's threshold and method if `peer` agreed to
+    /// compression, otherwise sent plain.
+    pub fn encode_message_for(&self, peer: &NodeId, msg: &Message) -> Vec<u8> {
+        if !self.peer_features(peer).contains(Features::COMPRESSION) {
+            return msg.to_bytes();
+        }
+        msg.to_wire_bytes(self.config.compression, self.config.compression_min_bytes)
+    }
+
+    /// Decode and process a message received from `peer`
+    ///
+    /// Compressed messages are inflated up to `max_message_bytes`; message types
+    /// from a newer protocol version are skipped, not treated as errors.
+    pub fn process_bytes_from(&mut self, peer: &NodeId, bytes: &[u8]) -> Result<Vec<GossipEvent>, GossipError> {
+        self.process_bytes_from_at(peer, bytes, Self::now())
+    }
+
+    /// Decode and process a message received from `peer` at `now_ms`
+    ///
+    /// Bytes over `peer`'s rate limit are dropped before being decoded; the byte
+    /// limit counts them as received, compressed or not.
+    pub fn process_bytes_from_at(
+        &mut self,
+        peer: &NodeId,
+        bytes: &[u8],
+        now_ms: u64,
+    ) -> Result<Vec<GossipEvent>, GossipError> {
+        if let Some(dropped) = self.admit(peer, bytes.len(), now_ms) {
+            return Ok(self.publish(dropped));
+        }
+        let events = match Message::from_wire_bytes_with_limit(bytes, self.config.max_message_bytes) {
+            Ok(msg) => self.process_negotiated(peer, msg)?,
+            Err(ProtocolError::UnknownMessageType(_)) => {
+                self.stats.messages_skipped += 1;
+                vec![]
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(self.publish(events))
+    }
+
+    /// Charge a message of `len` bytes to `peer`'s rate limit
+    ///
+    /// `None` if the message may be processed, otherwise the events for dropping it.
+    fn admit(&mut self, peer: &NodeId, len: usize, now_ms: u64) -> Option<Vec<GossipEvent>> {
+        let (messages, bytes) = (self.config.rate_limit_messages_per_sec, self.config.rate_limit_bytes_per_sec);
+        if messages == 0 && bytes == 0 {
+            return None;
+        }
+        let admission = self
+            .limits
+            .entry(peer.clone())
+            .or_insert_with(|| PeerLimits::new(messages, bytes, now_ms))
+            .check(len as u64, now_ms, self.config.throttle_after, self.config.throttle_secs.saturating_mul(1000));
+        match admission {
+            Admission::Admitted => None,
+            Admission::Dropped => {
+                self.stats.rate_limited += 1;
+                Some(vec![])
+            }
+            Admission::Throttled => {
+                self.stats.rate_limited += 1;
+                self.stats.peers_throttled += 1;
+                self.penalties.push(peer.clone());
+                Some(vec![GossipEvent::PeerThrottled(peer.clone())])
+            }
+        }
+    }
+
     /// Process an incoming message
     pub fn process_message(&mut self, msg: Message) -> Result<Vec<GossipEvent>, GossipError> {
+        let events = self.handle_message(msg)?;
+        Ok(self.publish(events))
+    }
+
+    fn handle_message(&mut self, msg: Message) -> Result<Vec<GossipEvent>, GossipError> {
         self.stats.messages_processed += 1;
 
-        // Check if we've seen this message
-        let msg_id = msg.id();
-        let now = Self::now();
+        // Sync messages and Hellos are answered, never relayed: a request retried after
+        // a lost response must be served again, so only relayed messages are deduplicated
+        if !matches!(msg, Message::SyncRequest(_) | Message::SyncResponse(_) | Message::Hello(_)) {
+            let msg_id = msg.id();
+            if self.seen_messages.contains_key(&msg_id) {
+                return Ok(vec![]); // Already seen, don't process
+            }
 
-        if self.seen_messages.contains_key(&msg_id) {
-            return Ok(vec![]); // Already seen, don't process
+            // Mark as seen
+            self.seen_messages.insert(msg_id, Self::now());
         }
 
-        // Mark as seen
-        self.seen_messages.insert(msg_id, now);
-
         let mut events = Vec::new();
 
         match msg {
-            Message::IOUAnnouncement(mut announcement) => {
-                // Try to add to our state
+            Message::IOUAnnouncement(announcement) => {
+                // Try to add to our state; only valid announcements are relayed
                 match self.handle_iou_announcement(announcement.clone()) {
                     Ok(()) => {
-                        // Forward if not at max hops
-                        if !announcement.should_stop_propagation() {
-                            announcement.increment_hop();
-                            events.push(GossipEvent::Forward(Message::IOUAnnouncement(
-                                announcement.clone(),
-                            )));
-                            events.push(GossipEvent::NewIOU(announcement.iou().clone()));
+                        let relayed = if self.config.relay_enabled {
+                            announcement.relayed_by(&self.node_id, self.config.max_ttl)
+                        } else {
+                            None
+                        };
+                        if let Some(relayed) = relayed {
+                            events.push(GossipEvent::Forward(Message::IOUAnnouncement(relayed)));
                             self.stats.messages_forwarded += 1;
+                            self.stats.messages_relayed += 1;
                         }
+                        events.push(GossipEvent::NewIOU(announcement.iou().clone()));
                     }
                     Err(_) => {
                         self.stats.ious_rejected += 1;
@@ -293,6 +1300,10 @@ impl GossipEngine {
             }
 
             Message::Heartbeat(heartbeat) => {
+                if !heartbeat.clock().is_empty() {
+                    self.peer_clocks.insert(heartbeat.sender().clone(), heartbeat.clock().clone());
+                }
+
                 // If peer has higher version, we might want to sync
                 if heartbeat.version() > self.state.version() {
                     events.push(GossipEvent::RequestSync(heartbeat.sender().clone()));
@@ -307,12 +1318,20 @@ impl GossipEngine {
             }
 
             Message::SyncResponse(response) => {
+                // Ask for the next page of a paged sync
+                let next_page = (response.has_more() && response.next_cursor().is_some())
+                    .then(|| response.sender().clone());
+
                 // Apply the response
                 if let Ok(result) = self.apply_sync_response(response) {
                     if result.new_entries > 0 {
                         events.push(GossipEvent::StateUpdated(result));
                     }
                 }
+                if let Some(peer) = next_page {
+                    events.push(GossipEvent::RequestSync(peer));
+                    self.stats.syncs_initiated += 1;
+                }
             }
 
             Message::PeerAnnouncement(_) => {
@@ -320,11 +1339,133 @@ impl GossipEngine {
                 // Just forward
                 events.push(GossipEvent::Forward(msg));
             }
+
+            Message::Encrypted(_) => {
+                // The engine holds no keys; the application opens envelopes
+                // addressed to it and feeds the inner message back in
+                events.push(GossipEvent::Forward(msg));
+            }
+
+            Message::Signed(_) => {
+                // Only the authenticated entry points know the signers' keys
+                return Err(GossipError::UnknownSigner);
+            }
+
+            Message::Hello(hello) => {
+                self.negotiate(&hello)?;
+                // Answer to the sender (handled by caller)
+                if !hello.is_reply() {
+                    events.push(GossipEvent::Forward(Message::Hello(self.generate_hello().as_reply())));
+                }
+            }
         }
 
         Ok(events)
     }
 
+    // ========================================================================
+    // OUTBOUND QUEUE
+    // ========================================================================
+
+    /// Queue a message for `peer` at its priority
+    ///
+    /// A sync response over `sync_chunk_entries` entries is queued as several
+    /// (see `SyncResponse::into_chunks`), taking a slot each. Reports
+    /// `Backpressure` when the queue first passes its high-water mark, and again
+    /// only after it has drained back to the mark. A full queue drops the
+    /// message, and one without room for every chunk drops them all.
+    pub fn queue_outbound(&mut self, peer: NodeId, msg: Message) -> Result<Vec<GossipEvent>, GossipError> {
+        let messages = match msg {
+            Message::SyncResponse(response) => response
+                .into_chunks(self.config.sync_chunk_entries)
+                .into_iter()
+                .map(Message::SyncResponse)
+                .collect(),
+            msg => vec![msg],
+        };
+        if self.outbound_len() + messages.len() > self.config.outbound_queue_size {
+            self.stats.outbound_dropped += 1;
+            return Err(GossipError::OutboundQueueFull);
+        }
+        self.stats.sync_chunks += messages.len() as u64 - 1;
+        for msg in messages {
+            match msg.priority() {
+                Priority::Urgent => self.urgent.push_back((peer.clone(), msg)),
+                Priority::Bulk => self.bulk.push_back((peer.clone(), msg)),
+            }
+        }
+        self.update_queue_depths();
+
+        let mut events = Vec::new();
+        if self.outbound_len() > self.config.outbound_high_water && !self.backpressured {
+            self.backpressured = true;
+            events.push(GossipEvent::Backpressure(self.outbound_len()));
+        }
+        Ok(self.publish(events))
+    }
+
+    /// Take the next message to send: the oldest urgent one, else the oldest bulk one
+    ///
+    /// Signed on the way out (see `sign_message`) if the peer agreed to signed
+    /// messages. Hellos go out as they are, since the key they carry is what
+    /// lets the peer check the rest.
+    pub fn pop_outbound(&mut self) -> Option<(NodeId, Message)> {
+        let next = self.urgent.pop_front().or_else(|| self.bulk.pop_front());
+        self.update_queue_depths();
+        if self.outbound_len() <= self.config.outbound_high_water {
+            self.backpressured = false;
+        }
+        let (peer, msg) = next?;
+        if self.signs_for(&peer) && !matches!(msg, Message::Hello(_) | Message::Signed(_)) {
+            if let Some(signed) = self.sign_message(&msg) {
+                return Some((peer, signed));
+            }
+        }
+        Some((peer, msg))
+    }
+
+    /// Number of messages waiting in the outbound queue
+    pub fn outbound_len(&self) -> usize {
+        self.urgent.len() + self.bulk.len()
+    }
+
+    /// Number of messages of `priority` waiting in the outbound queue
+    pub fn outbound_depth(&self, priority: Priority) -> usize {
+        match priority {
+            Priority::Urgent => self.urgent.len(),
+            Priority::Bulk => self.bulk.len(),
+        }
+    }
+
+    fn update_queue_depths(&mut self) {
+        self.stats.urgent_queued = self.urgent.len();
+        self.stats.bulk_queued = self.bulk.len();
+    }
+
+    /// Whether the outbound queue is past its high-water mark
+    pub fn is_backpressured(&self) -> bool {
+        self.backpressured
+    }
+
+    /// Peers out of `peers` to forward `msg` to
+    ///
+    /// Up to `fanout` random peers, leaving out ourselves and, for an IOU
+    /// announcement, every node it has already passed through.
+    pub fn forward_targets(&self, msg: &Message, peers: &[NodeId]) -> Vec<NodeId> {
+        let candidates: Vec<&NodeId> = peers
+            .iter()
+            .filter(|peer| **peer != self.node_id)
+            .filter(|peer| match msg {
+                Message::IOUAnnouncement(announcement) => !announcement.was_seen_by(peer),
+                _ => true,
+            })
+            .collect();
+        candidates
+            .choose_multiple(&mut rand::thread_rng(), self.config.fanout)
+            .map(|peer| (*peer).clone())
+            .collect()
+    }
+
     /// Collect outgoing messages to send
     pub fn collect_outgoing_messages(&mut self) -> Vec<Message> {
         let messages: Vec<Message> = self
@@ -336,6 +1477,285 @@ impl GossipEngine {
         messages
     }
 
+    // ========================================================================
+    // SUBSCRIPTIONS
+    // ========================================================================
+
+    /// Subscribe to the events our polling methods return, for async consumers
+    ///
+    /// The subscription sees every event those methods return, in order, and
+    /// buffers up to `subscription_capacity` of them. A consumer that falls
+    /// further behind loses the oldest, reported by a `Lagged` event; the engine
+    /// never waits for it. Dropping the subscription unsubscribes, and dropping
+    /// the engine ends it once the buffered events are received.
+    pub fn subscribe(&mut self) -> GossipSubscription {
+        self.subscribers.subscribe(self.config.subscription_capacity)
+    }
+
+    /// Number of subscriptions still being fed
+    pub fn subscriber_count(&mut self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Copy events to subscribers on their way back to the caller
+    fn publish(&mut self, events: Vec<GossipEvent>) -> Vec<GossipEvent> {
+        self.subscribers.publish(&events);
+        events
+    }
+
+    // ========================================================================
+    // ROUNDS
+    // ========================================================================
+
+    /// Run one gossip round against the currently connected `peers`
+    ///
+    /// Returns the messages to send and who to send them to.
+    pub fn tick(&mut self, peers: &[NodeId]) -> Vec<(NodeId, Message)> {
+        self.tick_at(peers, Self::now())
+    }
+
+    /// Run one gossip round at `now_ms`
+    ///
+    /// Pending announcements, and a heartbeat once per heartbeat interval, go to
+    /// `fanout` random peers. Once per anti-entropy interval one random peer gets
+    /// our full state (push) and a sync request for what we lack (pull), carrying our
+    /// summary if the peer agreed to summary sync. The
+    /// push is skipped if the peer's last version vector shows it already has our state.
+    /// Under adaptive scheduling that peer is the stalest one due (see
+    /// `next_peer_to_sync`), and the round is skipped if no peer is due.
+    pub fn tick_at(&mut self, peers: &[NodeId], now_ms: u64) -> Vec<(NodeId, Message)> {
+        self.round_at(peers, false, now_ms)
+    }
+
+    /// Run one gossip round against the peers in `registry`
+    pub fn tick_with_registry(&mut self, registry: &mut PeerRegistry) -> Vec<(NodeId, Message)> {
+        self.tick_with_registry_at(registry, Self::now())
+    }
+
+    /// Run one gossip round against the peers in `registry` at `now_ms`
+    ///
+    /// Peers throttled since the last round lose reputation first (see
+    /// `PeerEvent::Throttled`), and quarantines that have ended are lifted. Only
+    /// active peers take part, so quarantined, banned and unreachable ones are
+    /// skipped, and pushes go to the `fanout` best peers (see
+    /// `PeerRegistry::best_peers`) instead of random ones. Each peer we sync with
+    /// also gets our `PeerAnnouncement`, and discovered peers are dialed up to
+    /// `max_auto_dial`.
+    pub fn tick_with_registry_at(&mut self, registry: &mut PeerRegistry, now_ms: u64) -> Vec<(NodeId, Message)> {
+        for peer in std::mem::take(&mut self.penalties) {
+            let _ = registry.record_event_at(&peer, PeerEvent::Throttled, now_ms);
+        }
+        registry.release_quarantined_at(now_ms);
+        let peers: Vec<NodeId> = registry
+            .best_peers(usize::MAX)
+            .into_iter()
+            .map(|peer| peer.node_id().clone())
+            .collect();
+        let mut outgoing = self.round_at(&peers, true, now_ms);
+
+        if self.config.share_peers > 0 {
+            let synced: Vec<NodeId> = outgoing
+                .iter()
+                .filter(|(_, msg)| matches!(msg, Message::SyncRequest(_)))
+                .map(|(peer, _)| peer.clone())
+                .collect();
+            for peer in synced {
+                let announcement = self.peer_announcement_at(registry, &peer, now_ms);
+                outgoing.push((peer, Message::PeerAnnouncement(announcement)));
+            }
+        }
+        outgoing.extend(self.dial_discovered(registry));
+        outgoing
+    }
+
+    /// One gossip round; with `ranked`, `peers` are best first and pushes go to the head
+    fn round_at(&mut self, peers: &[NodeId], ranked: bool, now_ms: u64) -> Vec<(NodeId, Message)> {
+        let peers: Vec<&NodeId> = peers.iter().filter(|peer| **peer != self.node_id).collect();
+        if peers.is_empty() {
+            return Vec::new();
+        }
+        self.stats.gossip_rounds += 1;
+
+        let mut rng = rand::thread_rng();
+        let mut round = self.collect_outgoing_messages();
+        if Self::is_due(self.last_heartbeat_ms, self.config.heartbeat_interval_secs, now_ms) {
+            self.last_heartbeat_ms = Some(now_ms);
+            round.push(Message::Heartbeat(self.generate_heartbeat()));
+        }
+
+        let mut outgoing = Vec::new();
+        if !round.is_empty() {
+            let targets: Vec<&&NodeId> = if ranked {
+                peers.iter().take(self.config.fanout).collect()
+            } else {
+                peers.choose_multiple(&mut rng, self.config.fanout).collect()
+            };
+            for msg in &round {
+                for peer in &targets {
+                    outgoing.push(((**peer).clone(), msg.clone()));
+                }
+            }
+            self.stats.messages_forwarded += (round.len() * targets.len()) as u64;
+        }
+
+        if Self::is_due(self.last_anti_entropy_ms, self.config.anti_entropy_interval_secs, now_ms) {
+            self.last_anti_entropy_ms = Some(now_ms);
+            let peer = if self.is_adaptive() {
+                let peer = self.next_peer_to_sync_at(peers.iter().copied(), now_ms);
+                match &peer {
+                    Some(peer) => {
+                        self.schedule_mut(peer).last_sync_ms = Some(now_ms);
+                        self.update_avg_interval();
+                    }
+                    None => self.stats.rounds_skipped += 1,
+                }
+                peer
+            } else {
+                peers.choose(&mut rng).map(|peer| (*peer).clone())
+            };
+            if let Some(peer) = peer {
+                self.resyncs.retain(|resync| *resync != peer);
+                self.anti_entropy_with(peer, &mut outgoing);
+            }
+        }
+
+        // Recovered peers catch up now rather than when their turn comes
+        for peer in std::mem::take(&mut self.resyncs) {
+            if peers.contains(&&peer) {
+                self.anti_entropy_with(peer, &mut outgoing);
+            }
+        }
+
+        outgoing
+    }
+
+    /// Push our state to `peer` unless it already has it, and pull what it has
+    fn anti_entropy_with(&mut self, peer: NodeId, outgoing: &mut Vec<(NodeId, Message)>) {
+        if self.peer_has_our_state(&peer) {
+            self.stats.pushes_skipped += 1;
+        } else {
+            let push = SyncResponse::new(
+                self.node_id.clone(),
+                self.state.version(),
+                self.state.all_entries().into_iter().cloned().collect(),
+            )
+            .with_clock(self.state.clock().clone());
+            outgoing.push((peer.clone(), Message::SyncResponse(push)));
+        }
+        let request = self.generate_sync_request_to(&peer);
+        outgoing.push((peer, Message::SyncRequest(request)));
+        self.stats.anti_entropy_rounds += 1;
+        self.stats.syncs_initiated += 1;
+    }
+
+    // ========================================================================
+    // ADAPTIVE SCHEDULING
+    // ========================================================================
+
+    /// The peer the next anti-entropy round should sync with, out of `peers`
+    pub fn next_peer_to_sync(&self, peers: &[NodeId]) -> Option<NodeId> {
+        self.next_peer_to_sync_at(peers, Self::now())
+    }
+
+    /// The stalest peer due for a sync at `now_ms`
+    ///
+    /// Peers are ranked by how many of our entries their last version vector
+    /// lacks (unknown counts as all), then by the longest time since we synced
+    /// with them. Under a fixed schedule every peer is due.
+    pub fn next_peer_to_sync_at<'a>(
+        &self,
+        peers: impl IntoIterator<Item = &'a NodeId>,
+        now_ms: u64,
+    ) -> Option<NodeId> {
+        peers
+            .into_iter()
+            .filter(|peer| **peer != self.node_id)
+            .filter(|peer| !self.is_adaptive() || self.is_peer_due(peer, now_ms))
+            .max_by_key(|peer| {
+                let last_sync = self.schedules.get(*peer).and_then(|s| s.last_sync_ms);
+                (self.entries_missing_at(peer), Reverse(last_sync), Reverse(*peer.as_bytes()))
+            })
+            .cloned()
+    }
+
+    /// Current anti-entropy interval for `peer` in seconds
+    pub fn peer_interval_secs(&self, peer: &NodeId) -> u64 {
+        self.schedules
+            .get(peer)
+            .map_or(self.config.anti_entropy_interval_secs, |s| s.interval_secs)
+    }
+
+    fn is_adaptive(&self) -> bool {
+        self.config.max_interval_secs > 0 && self.config.anti_entropy_interval_secs > 0
+    }
+
+    fn is_peer_due(&self, peer: &NodeId, now_ms: u64) -> bool {
+        self.schedules.get(peer).is_none_or(|s| Self::is_due(s.last_sync_ms, s.interval_secs, now_ms))
+    }
+
+    /// How many of our entries `peer`'s last version vector lacks (`u64::MAX` if unknown)
+    fn entries_missing_at(&self, peer: &NodeId) -> u64 {
+        match self.peer_clocks.get(peer) {
+            Some(clock) => self
+                .state
+                .clock()
+                .iter()
+                .map(|(node_id, ours)| ours.saturating_sub(clock.get(node_id)))
+                .sum(),
+            None => u64::MAX,
+        }
+    }
+
+    fn schedule_mut(&mut self, peer: &NodeId) -> &mut PeerSchedule {
+        let interval_secs = self.config.anti_entropy_interval_secs;
+        self.schedules.entry(peer.clone()).or_insert(PeerSchedule {
+            interval_secs,
+            last_sync_ms: None,
+        })
+    }
+
+    /// Double `peer`'s interval after a sync that brought nothing new
+    fn back_off(&mut self, peer: &NodeId) {
+        if !self.is_adaptive() {
+            return;
+        }
+        let (base, max) = (self.config.anti_entropy_interval_secs, self.config.max_interval_secs);
+        let schedule = self.schedule_mut(peer);
+        schedule.interval_secs = schedule.interval_secs.saturating_mul(2).min(max).max(base);
+        self.update_avg_interval();
+    }
+
+    /// Make every peer due again at the base interval, as there is new data to spread
+    fn reset_schedules(&mut self) {
+        if !self.is_adaptive() {
+            return;
+        }
+        for schedule in self.schedules.values_mut() {
+            schedule.interval_secs = self.config.anti_entropy_interval_secs;
+            schedule.last_sync_ms = None;
+        }
+        self.update_avg_interval();
+    }
+
+    fn update_avg_interval(&mut self) {
+        let total: u64 = self.schedules.values().map(|s| s.interval_secs).sum();
+        self.stats.avg_interval_secs = match self.schedules.len() {
+            0 => 0.0,
+            count => total as f64 / count as f64,
+        };
+    }
+
+    /// Whether a periodic task last run at `last_ms` is due again (interval 0 = never)
+    fn is_due(last_ms: Option<u64>, interval_secs: u64, now_ms: u64) -> bool {
+        if interval_secs == 0 {
+            return false;
+        }
+        match last_ms {
+            Some(last) => now_ms.saturating_sub(last) >= interval_secs * 1000,
+            None => true,
+        }
+    }
+
     // ========================================================================
     // MAINTENANCE
     // ========================================================================
@@ -1,13 +1,27 @@
 // Sync module - HOW NODES TALK
 // Handles gossip protocol, peer management, and state synchronization
 
+mod compression;
 mod gossip;
 mod peer;
 mod protocol;
+mod rate_limit;
+mod subscription;
 
-pub use gossip::{GossipConfig, GossipEngine, GossipEvent, GossipStats};
-pub use peer::{PeerError, PeerInfo, PeerRegistry, PeerState, PeerStats};
+pub use compression::{
+    decode_payload, pack_payload, unpack_payload, Compression, PayloadError, MAX_PAYLOAD_BYTES,
+    PAYLOAD_DEFLATE, PAYLOAD_RAW,
+};
+pub use gossip::{GossipConfig, GossipEngine, GossipError, GossipEvent, GossipStats, PeerProtocol};
+pub use peer::{
+    PeerError, PeerEvent, PeerInfo, PeerRegistry, PeerState, PeerStats, DEFAULT_BAN_THRESHOLD,
+    DEFAULT_DECAY_SECS, DEFAULT_QUARANTINE_SECS, MAX_REPUTATION, MIN_REPUTATION,
+};
 pub use protocol::{
-    Heartbeat, IOUAnnouncement, Message, MessageId, MessageType, PeerAnnouncement,
-    ProtocolError, SyncRequest, SyncResponse,
+    EncryptedMessage, Features, Heartbeat, Hello, IOUAnnouncement, KnownPeer, Message, MessageId,
+    MessageType, PeerAnnouncement, Priority, ProtocolError, SignedMessage, SyncRequest, SyncResponse,
+    DEFAULT_ANNOUNCEMENT_TTL, MAX_MESSAGE_BYTES, MAX_SEEN_BY, MIN_PROTOCOL_VERSION,
+    PROTOCOL_VERSION,
 };
+pub use rate_limit::TokenBucket;
+pub use subscription::{GossipSubscription, DEFAULT_SUBSCRIPTION_CAPACITY};
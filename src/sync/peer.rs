@@ -3,6 +3,7 @@
 // Manages the registry of known peers, their connection state,
 // and provides selection algorithms for gossip.
 
+use crate::identity::{Did, PublicKey, SignedDidDocument};
 use crate::ledger::NodeId;
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
@@ -22,6 +23,15 @@ pub enum PeerError {
 
     #[error("Deserialization failed")]
     DeserializationFailed,
+
+    #[error("DID document has an invalid signature")]
+    InvalidDocument,
+
+    #[error("DID document has expired")]
+    DocumentExpired,
+
+    #[error("Public key does not match the node ID")]
+    KeyMismatch,
 }
 
 /// State of a peer connection
@@ -39,6 +49,58 @@ pub enum PeerState {
     Disconnected,
     /// Peer misbehaved (bad messages, etc.)
     Banned,
+    /// Reputation fell below the quarantine threshold; skipped until the cool-down ends
+    Quarantined,
+    /// Missed too many heartbeats; connected again on the next one
+    Unreachable,
+    /// Learned of through another peer's announcement; not yet dialed
+    Discovered,
+}
+
+/// Lowest reputation a peer can fall to
+pub const MIN_REPUTATION: i32 = -100;
+
+/// Highest reputation a peer can reach
+pub const MAX_REPUTATION: i32 = 100;
+
+/// Reputation at or below which a registry bans a peer, unless changed with `with_ban_threshold`
+pub const DEFAULT_BAN_THRESHOLD: i32 = -50;
+
+/// How long a quarantine lasts, unless changed with `with_quarantine`
+pub const DEFAULT_QUARANTINE_SECS: u64 = 300;
+
+/// Seconds for a reputation to decay one point towards 0, unless changed with `with_reputation_decay`
+pub const DEFAULT_DECAY_SECS: u64 = 60;
+
+/// Something a peer did that changes its reputation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerEvent {
+    /// Completed a state sync with us
+    SyncSucceeded,
+    /// Sent an IOU that failed validation
+    InvalidIOU,
+    /// Didn't answer in time
+    Timeout,
+    /// Sent a message that decoded and checked out
+    ValidMessage,
+    /// Sent a malformed or invalid message
+    InvalidMessage,
+    /// Flooded us past the hard rate limit
+    Throttled,
+}
+
+impl PeerEvent {
+    /// How much this event moves a peer's reputation
+    pub fn score_delta(&self) -> i32 {
+        match self {
+            PeerEvent::SyncSucceeded => 1,
+            PeerEvent::InvalidIOU => -10,
+            PeerEvent::Timeout => -2,
+            PeerEvent::ValidMessage => 1,
+            PeerEvent::InvalidMessage => -10,
+            PeerEvent::Throttled => -10,
+        }
+    }
 }
 
 /// Statistics about a peer registry
@@ -49,6 +111,11 @@ pub struct PeerStats {
     pub syncing_peers: usize,
     pub disconnected_peers: usize,
     pub banned_peers: usize,
+    pub quarantined_peers: usize,
+    pub unreachable_peers: usize,
+    pub discovered_peers: usize,
+    /// Mean reputation across all peers (0 if there are none)
+    pub average_reputation: f64,
 }
 
 /// Information about a known peer
@@ -68,6 +135,117 @@ pub struct PeerInfo {
     rtt_samples: Vec<u32>,
     /// Number of failed connection attempts
     failed_attempts: u32,
+    /// Score from past behaviour, between MIN_REPUTATION and MAX_REPUTATION
+    reputation: i32,
+    /// When the reputation last changed or decayed (unix timestamp ms)
+    reputation_updated_ms: u64,
+    /// Set while the peer is quarantined
+    quarantine: Option<Quarantine>,
+    /// Last time the peer sent us a heartbeat (unix timestamp ms)
+    last_heartbeat: Option<u64>,
+}
+
+/// A quarantine in force
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct Quarantine {
+    /// When the cool-down ends (unix timestamp ms)
+    until_ms: u64,
+    /// State to return to once released
+    previous_state: PeerState,
+}
+
+/// Peer info as serialized before heartbeats were tracked
+#[derive(Deserialize)]
+struct PreHeartbeatPeerInfo {
+    node_id: NodeId,
+    address: SocketAddr,
+    state: PeerState,
+    known_version: u64,
+    last_seen: u64,
+    rtt_samples: Vec<u32>,
+    failed_attempts: u32,
+    reputation: i32,
+    reputation_updated_ms: u64,
+    quarantine: Option<Quarantine>,
+}
+
+impl From<PreHeartbeatPeerInfo> for PeerInfo {
+    fn from(old: PreHeartbeatPeerInfo) -> Self {
+        Self {
+            node_id: old.node_id,
+            address: old.address,
+            state: old.state,
+            known_version: old.known_version,
+            last_seen: old.last_seen,
+            rtt_samples: old.rtt_samples,
+            failed_attempts: old.failed_attempts,
+            reputation: old.reputation,
+            reputation_updated_ms: old.reputation_updated_ms,
+            quarantine: old.quarantine,
+            last_heartbeat: None,
+        }
+    }
+}
+
+/// Peer info as serialized before quarantines
+#[derive(Deserialize)]
+struct PreQuarantinePeerInfo {
+    node_id: NodeId,
+    address: SocketAddr,
+    state: PeerState,
+    known_version: u64,
+    last_seen: u64,
+    rtt_samples: Vec<u32>,
+    failed_attempts: u32,
+    reputation: i32,
+}
+
+impl From<PreQuarantinePeerInfo> for PeerInfo {
+    fn from(old: PreQuarantinePeerInfo) -> Self {
+        Self {
+            node_id: old.node_id,
+            address: old.address,
+            state: old.state,
+            known_version: old.known_version,
+            last_seen: old.last_seen,
+            rtt_samples: old.rtt_samples,
+            failed_attempts: old.failed_attempts,
+            reputation: old.reputation,
+            reputation_updated_ms: old.last_seen,
+            quarantine: None,
+            last_heartbeat: None,
+        }
+    }
+}
+
+/// Peer info as serialized before reputation was tracked
+#[derive(Deserialize)]
+struct PreReputationPeerInfo {
+    node_id: NodeId,
+    address: SocketAddr,
+    state: PeerState,
+    known_version: u64,
+    last_seen: u64,
+    rtt_samples: Vec<u32>,
+    failed_attempts: u32,
+}
+
+impl From<PreReputationPeerInfo> for PeerInfo {
+    fn from(old: PreReputationPeerInfo) -> Self {
+        Self {
+            node_id: old.node_id,
+            address: old.address,
+            state: old.state,
+            known_version: old.known_version,
+            last_seen: old.last_seen,
+            rtt_samples: old.rtt_samples,
+            failed_attempts: old.failed_attempts,
+            reputation: 0,
+            reputation_updated_ms: old.last_seen,
+            quarantine: None,
+            last_heartbeat: None,
+        }
+    }
 }
 
 impl PeerInfo {
@@ -86,6 +264,10 @@ impl PeerInfo {
             last_seen: now,
             rtt_samples: Vec::new(),
             failed_attempts: 0,
+            reputation: 0,
+            reputation_updated_ms: now,
+            quarantine: None,
+            last_heartbeat: None,
         }
     }
 
@@ -135,6 +317,11 @@ impl PeerInfo {
             .as_millis() as u64;
     }
 
+    /// Last time the peer sent us a heartbeat (unix timestamp ms), if it ever has
+    pub fn last_heartbeat(&self) -> Option<u64> {
+        self.last_heartbeat
+    }
+
     /// Check if peer is stale (not seen in timeout_secs)
     pub fn is_stale(&self, timeout_secs: u64) -> bool {
         let now = SystemTime::now()
@@ -174,6 +361,42 @@ impl PeerInfo {
     pub fn failed_attempts(&self) -> u32 {
         self.failed_attempts
     }
+
+    /// Get the reputation score (new peers start at 0)
+    pub fn reputation(&self) -> i32 {
+        self.reputation
+    }
+
+    /// Adjust reputation for an event, returning the new score
+    pub fn record_event(&mut self, event: PeerEvent) -> i32 {
+        self.reputation = self
+            .reputation
+            .saturating_add(event.score_delta())
+            .clamp(MIN_REPUTATION, MAX_REPUTATION);
+        self.reputation
+    }
+
+    /// When the peer's quarantine ends (unix timestamp ms), if it is quarantined
+    pub fn quarantined_until(&self) -> Option<u64> {
+        self.quarantine.map(|q| q.until_ms)
+    }
+
+    /// Move the reputation one point towards 0 per `secs_per_point` elapsed since it last changed
+    fn decay(&mut self, now_ms: u64, secs_per_point: u64) {
+        let interval_ms = secs_per_point.saturating_mul(1000).max(1);
+        let points = now_ms.saturating_sub(self.reputation_updated_ms) / interval_ms;
+        if points == 0 {
+            return;
+        }
+        let points = points.min(MAX_REPUTATION as u64) as i32;
+        self.reputation -= self.reputation.signum() * points.min(self.reputation.abs());
+        self.reputation_updated_ms += points as u64 * interval_ms;
+    }
+
+    /// Connected or syncing
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, PeerState::Connected | PeerState::Syncing)
+    }
 }
 
 /// Registry of known peers
@@ -183,6 +406,18 @@ pub struct PeerRegistry {
     my_node_id: NodeId,
     /// Map of node ID to peer info
     peers: HashMap<NodeId, PeerInfo>,
+    /// Reputation at or below which peers are banned (None disables banning)
+    ban_threshold: Option<i32>,
+    /// Reputation at or below which peers are quarantined (None disables quarantine)
+    quarantine_threshold: Option<i32>,
+    /// How long a quarantine lasts
+    quarantine_secs: u64,
+    /// Seconds per point of reputation decay (None disables decay)
+    decay_secs: Option<u64>,
+    /// Latest signed DID document per node
+    documents: HashMap<NodeId, SignedDidDocument>,
+    /// Message signing key per node, learned from Hellos and DID documents
+    keys: HashMap<NodeId, PublicKey>,
 }
 
 impl PeerRegistry {
@@ -191,9 +426,46 @@ impl PeerRegistry {
         Self {
             my_node_id,
             peers: HashMap::new(),
+            ban_threshold: Some(DEFAULT_BAN_THRESHOLD),
+            quarantine_threshold: None,
+            quarantine_secs: DEFAULT_QUARANTINE_SECS,
+            decay_secs: Some(DEFAULT_DECAY_SECS),
+            documents: HashMap::new(),
+            keys: HashMap::new(),
         }
     }
 
+    /// Set the reputation at or below which peers are banned (None disables banning)
+    pub fn with_ban_threshold(mut self, threshold: Option<i32>) -> Self {
+        self.ban_threshold = threshold;
+        self
+    }
+
+    /// Get the ban threshold
+    pub fn ban_threshold(&self) -> Option<i32> {
+        self.ban_threshold
+    }
+
+    /// Quarantine peers whose reputation drops to `threshold` for `cooldown_secs`
+    /// (None, the default, disables quarantine). Should sit above the ban threshold.
+    pub fn with_quarantine(mut self, threshold: Option<i32>, cooldown_secs: u64) -> Self {
+        self.quarantine_threshold = threshold;
+        self.quarantine_secs = cooldown_secs;
+        self
+    }
+
+    /// Get the quarantine threshold
+    pub fn quarantine_threshold(&self) -> Option<i32> {
+        self.quarantine_threshold
+    }
+
+    /// Set how many seconds it takes a reputation to decay one point towards 0
+    /// (None disables decay)
+    pub fn with_reputation_decay(mut self, secs_per_point: Option<u64>) -> Self {
+        self.decay_secs = secs_per_point;
+        self
+    }
+
     /// Check if registry is empty
     pub fn is_empty(&self) -> bool {
         self.peers.is_empty()
@@ -229,6 +501,23 @@ impl PeerRegistry {
         Ok(())
     }
 
+    /// Add a peer learned of from another peer, returning whether it was new
+    ///
+    /// Known peers are left as they are, so an announcement can't redirect or
+    /// revive them; new ones start out `Discovered`.
+    pub fn add_discovered(&mut self, node_id: NodeId, address: SocketAddr) -> Result<bool, PeerError> {
+        if node_id == self.my_node_id {
+            return Err(PeerError::CannotAddSelf);
+        }
+        if self.peers.contains_key(&node_id) {
+            return Ok(false);
+        }
+        let mut peer = PeerInfo::new(node_id.clone(), address);
+        peer.set_state(PeerState::Discovered);
+        self.peers.insert(node_id, peer);
+        Ok(true)
+    }
+
     /// Remove a peer
     pub fn remove_peer(&mut self, node_id: &NodeId) {
         self.peers.remove(node_id);
@@ -249,6 +538,77 @@ impl PeerRegistry {
         self.peers.values().collect()
     }
 
+    /// Register a peer's signed DID document so it can be reached by DID
+    pub fn register_document(&mut self, document: SignedDidDocument) -> Result<bool, PeerError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.register_document_at(document, now)
+    }
+
+    /// Register a signed DID document as of `now` (unix seconds).
+    /// The peer is added at the document's first `tcp://` endpoint, if any.
+    /// Returns false, keeping the known document, unless this one has a newer `updated_at`.
+    pub fn register_document_at(&mut self, document: SignedDidDocument, now: u64) -> Result<bool, PeerError> {
+        if !document.verify() {
+            return Err(PeerError::InvalidDocument);
+        }
+        if document.is_expired_at(now) {
+            return Err(PeerError::DocumentExpired);
+        }
+
+        let public_key = document.document().public_key().map_err(|_| PeerError::InvalidDocument)?;
+        let node_id = NodeId::from_public_key(&public_key);
+        if node_id == self.my_node_id {
+            return Err(PeerError::CannotAddSelf);
+        }
+        if self
+            .documents
+            .get(&node_id)
+            .is_some_and(|known| known.updated_at() >= document.updated_at())
+        {
+            return Ok(false);
+        }
+
+        if let Some(address) = document.document().service.iter().find_map(|s| s.socket_addr()) {
+            self.add_peer(node_id.clone(), address)?;
+        }
+        self.keys.insert(node_id.clone(), public_key);
+        self.documents.insert(node_id, document);
+        Ok(true)
+    }
+
+    /// Register the key a node signs its messages with, returning whether it was new
+    ///
+    /// Node IDs derive from keys (`NodeId::from_public_key`), so a key is only
+    /// accepted for the node it derives and can't be claimed for another.
+    pub fn register_public_key(&mut self, node_id: &NodeId, public_key: PublicKey) -> Result<bool, PeerError> {
+        if &NodeId::from_public_key(&public_key) != node_id {
+            return Err(PeerError::KeyMismatch);
+        }
+        Ok(self.keys.insert(node_id.clone(), public_key).is_none())
+    }
+
+    /// Key a node signs its messages with, if we've learned it
+    pub fn public_key(&self, node_id: &NodeId) -> Option<&PublicKey> {
+        self.keys.get(node_id)
+    }
+
+    /// Latest registered DID document for a DID
+    pub fn document(&self, did: &Did) -> Option<&SignedDidDocument> {
+        self.documents.get(&Self::node_id_for(did)?)
+    }
+
+    /// The peer a DID's document routes to
+    pub fn peer_for_did(&self, did: &Did) -> Option<&PeerInfo> {
+        self.peers.get(&Self::node_id_for(did)?)
+    }
+
+    fn node_id_for(did: &Did) -> Option<NodeId> {
+        did.public_key().ok().map(|key| NodeId::from_public_key(&key))
+    }
+
     /// Select random peers for gossip
     pub fn select_random_peers(&self, count: usize) -> Vec<&PeerInfo> {
         let mut rng = rand::thread_rng();
@@ -258,6 +618,159 @@ impl PeerRegistry {
         peers
     }
 
+    /// Adjust a peer's reputation, banning it if the score drops to the ban threshold.
+    /// Returns the peer's state afterwards.
+    pub fn record_event(&mut self, node_id: &NodeId, event: PeerEvent) -> Result<PeerState, PeerError> {
+        self.record_event_at(node_id, event, Self::now_ms())
+    }
+
+    /// Adjust a peer's reputation at `now_ms`, after decaying it for the time passed
+    ///
+    /// A score at or below the ban threshold bans the peer; one at or below the
+    /// quarantine threshold after a penalty quarantines it for the cool-down,
+    /// restarting the cool-down if it is already quarantined. Returns the peer's state afterwards.
+    pub fn record_event_at(&mut self, node_id: &NodeId, event: PeerEvent, now_ms: u64) -> Result<PeerState, PeerError> {
+        let peer = self.peers.get_mut(node_id).ok_or(PeerError::PeerNotFound)?;
+        if let Some(secs) = self.decay_secs {
+            peer.decay(now_ms, secs);
+        }
+        let score = peer.record_event(event);
+        peer.reputation_updated_ms = now_ms;
+
+        if self.ban_threshold.is_some_and(|threshold| score <= threshold) {
+            peer.quarantine = None;
+            peer.set_state(PeerState::Banned);
+        } else if event.score_delta() < 0
+            && peer.state != PeerState::Banned
+            && self.quarantine_threshold.is_some_and(|threshold| score <= threshold)
+        {
+            let previous_state = peer.quarantine.map_or(peer.state, |q| q.previous_state);
+            peer.quarantine = Some(Quarantine {
+                until_ms: now_ms.saturating_add(self.quarantine_secs.saturating_mul(1000)),
+                previous_state,
+            });
+            peer.set_state(PeerState::Quarantined);
+        }
+        Ok(peer.state)
+    }
+
+    /// Credit a peer for a good message
+    pub fn record_valid_message(&mut self, node_id: &NodeId) -> Result<PeerState, PeerError> {
+        self.record_event(node_id, PeerEvent::ValidMessage)
+    }
+
+    /// Penalize a peer for a malformed or invalid message
+    pub fn record_invalid_message(&mut self, node_id: &NodeId) -> Result<PeerState, PeerError> {
+        self.record_event(node_id, PeerEvent::InvalidMessage)
+    }
+
+    /// Penalize a peer for not answering in time
+    pub fn record_timeout(&mut self, node_id: &NodeId) -> Result<PeerState, PeerError> {
+        self.record_event(node_id, PeerEvent::Timeout)
+    }
+
+    /// Release peers whose quarantine has ended
+    pub fn release_quarantined(&mut self) -> Vec<NodeId> {
+        self.release_quarantined_at(Self::now_ms())
+    }
+
+    /// Release peers whose quarantine ended by `now_ms`, returning them to the state
+    /// they were in before. Their reputation is kept (less any decay), so further
+    /// misbehaviour quarantines them again quickly.
+    pub fn release_quarantined_at(&mut self, now_ms: u64) -> Vec<NodeId> {
+        let mut released = Vec::new();
+        for peer in self.peers.values_mut() {
+            let Some(quarantine) = peer.quarantine.filter(|q| q.until_ms <= now_ms) else {
+                continue;
+            };
+            if let Some(secs) = self.decay_secs {
+                peer.decay(now_ms, secs);
+            }
+            peer.quarantine = None;
+            peer.set_state(quarantine.previous_state);
+            released.push(peer.node_id.clone());
+        }
+        released.sort_by_key(|id| *id.as_bytes());
+        released
+    }
+
+    /// Record a heartbeat from a peer, returning whether it was unreachable until now
+    pub fn record_heartbeat(&mut self, node_id: &NodeId) -> Result<bool, PeerError> {
+        self.record_heartbeat_at(node_id, Self::now_ms())
+    }
+
+    /// Record a heartbeat from a peer at `now_ms`
+    ///
+    /// An unreachable peer is connected again; returns whether it was.
+    pub fn record_heartbeat_at(&mut self, node_id: &NodeId, now_ms: u64) -> Result<bool, PeerError> {
+        let peer = self.peers.get_mut(node_id).ok_or(PeerError::PeerNotFound)?;
+        peer.last_heartbeat = Some(now_ms);
+        peer.last_seen = peer.last_seen.max(now_ms);
+        let recovered = peer.state == PeerState::Unreachable;
+        if recovered {
+            peer.set_state(PeerState::Connected);
+        }
+        Ok(recovered)
+    }
+
+    /// Mark active peers with no heartbeat for over `timeout_ms` as unreachable
+    ///
+    /// A peer that never sent a heartbeat is timed from when it was last seen.
+    /// Returns the peers marked, in node ID order.
+    pub fn mark_unreachable_at(&mut self, now_ms: u64, timeout_ms: u64) -> Vec<NodeId> {
+        let mut lost = Vec::new();
+        for peer in self.peers.values_mut().filter(|p| p.is_active()) {
+            let heard = peer.last_heartbeat.unwrap_or(peer.last_seen);
+            if now_ms.saturating_sub(heard) > timeout_ms {
+                peer.set_state(PeerState::Unreachable);
+                lost.push(peer.node_id.clone());
+            }
+        }
+        lost.sort_by_key(|id| *id.as_bytes());
+        lost
+    }
+
+    fn now_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
+    /// Up to `count` active peers, best reputation first; quarantined and banned peers
+    /// are never active.
+    /// Ties go to the lower average RTT, then the lower node ID, so the order is stable.
+    pub fn best_peers(&self, count: usize) -> Vec<&PeerInfo> {
+        let mut peers: Vec<&PeerInfo> = self.peers.values().filter(|p| p.is_active()).collect();
+        peers.sort_by_key(|p| {
+            (
+                std::cmp::Reverse(p.reputation),
+                p.average_rtt().unwrap_or(u32::MAX),
+                *p.node_id.as_bytes(),
+            )
+        });
+        peers.truncate(count);
+        peers
+    }
+
+    /// Up to `count` peers worth telling others about at `now_ms`, best first
+    ///
+    /// Only peers we have talked to (connected, syncing or since disconnected)
+    /// with a reputation of at least 0, seen within the last `max_age_ms`.
+    pub fn shareable_peers_at(&self, count: usize, now_ms: u64, max_age_ms: u64) -> Vec<&PeerInfo> {
+        let mut peers: Vec<&PeerInfo> = self
+            .peers
+            .values()
+            .filter(|p| {
+                matches!(p.state, PeerState::Connected | PeerState::Syncing | PeerState::Disconnected)
+            })
+            .filter(|p| p.reputation >= 0 && now_ms.saturating_sub(p.last_seen) <= max_age_ms)
+            .collect();
+        peers.sort_by_key(|p| (std::cmp::Reverse(p.reputation), std::cmp::Reverse(p.last_seen), *p.node_id.as_bytes()));
+        peers.truncate(count);
+        peers
+    }
+
     /// Get peers by state
     pub fn peers_by_state(&self, state: PeerState) -> Vec<&PeerInfo> {
         self.peers
@@ -298,6 +811,10 @@ impl PeerRegistry {
             syncing_peers: 0,
             disconnected_peers: 0,
             banned_peers: 0,
+            quarantined_peers: 0,
+            unreachable_peers: 0,
+            discovered_peers: 0,
+            average_reputation: 0.0,
         };
 
         for peer in self.peers.values() {
@@ -306,9 +823,16 @@ impl PeerRegistry {
                 PeerState::Syncing => stats.syncing_peers += 1,
                 PeerState::Disconnected => stats.disconnected_peers += 1,
                 PeerState::Banned => stats.banned_peers += 1,
+                PeerState::Quarantined => stats.quarantined_peers += 1,
+                PeerState::Unreachable => stats.unreachable_peers += 1,
+                PeerState::Discovered => stats.discovered_peers += 1,
                 _ => {}
             }
         }
+        if !self.peers.is_empty() {
+            let total: i64 = self.peers.values().map(|p| p.reputation as i64).sum();
+            stats.average_reputation = total as f64 / self.peers.len() as f64;
+        }
 
         stats
     }
@@ -321,9 +845,23 @@ impl PeerRegistry {
     }
 
     /// Deserialize from bytes
+    ///
+    /// Also accepts peer lists saved before heartbeats or quarantines, and before
+    /// reputation was tracked (scores start at 0).
     pub fn from_bytes(bytes: &[u8], my_node_id: NodeId) -> Result<Self, PeerError> {
-        let peers: Vec<PeerInfo> =
-            postcard::from_bytes(bytes).map_err(|_| PeerError::DeserializationFailed)?;
+        let peers: Vec<PeerInfo> = if let Ok(peers) = postcard::from_bytes(bytes) {
+            peers
+        } else if let Ok(peers) = postcard::from_bytes::<Vec<PreHeartbeatPeerInfo>>(bytes) {
+            peers.into_iter().map(PeerInfo::from).collect()
+        } else if let Ok(peers) = postcard::from_bytes::<Vec<PreQuarantinePeerInfo>>(bytes) {
+            peers.into_iter().map(PeerInfo::from).collect()
+        } else {
+            postcard::from_bytes::<Vec<PreReputationPeerInfo>>(bytes)
+                .map_err(|_| PeerError::DeserializationFailed)?
+                .into_iter()
+                .map(PeerInfo::from)
+                .collect()
+        };
 
         let mut registry = Self::new(my_node_id);
         for peer in peers {
@@ -5,16 +5,41 @@
 // - IOUAnnouncement: Push-based IOU propagation
 // - PeerAnnouncement: Peer discovery
 // - Heartbeat: Keep-alive and version broadcast
+// - Encrypted: Any of the above sealed to a single recipient
+// - Hello: Protocol version and feature negotiation on connection
+// - Signed: Any of the above signed with the sender's node key
+//
+// Nodes from before the handshake speak protocol version 1 and never send a
+// Hello. Message types added after a node was built decode as
+// `ProtocolError::UnknownMessageType`, which receivers skip.
 
-use crate::identity::{Did, PublicKey};
+use crate::identity::{
+    decrypt, encrypt_for, Did, EncryptionError, Keypair, PublicKey, SealedEnvelope, Signature, Signer,
+};
 use crate::iou::SignedIOU;
-use crate::ledger::{IOUEntry, NodeId};
+use crate::ledger::{IOUEntry, NodeId, StateSummary, VersionVector};
+use crate::sync::compression::{self, Compression};
+use crate::transport::PeerAddress;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashSet;
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+/// Protocol version this node speaks
+pub const PROTOCOL_VERSION: u16 = 2;
+
+/// Oldest protocol version this node still talks to
+pub const MIN_PROTOCOL_VERSION: u16 = 1;
+
+/// Largest message a compressed one may expand to, so a hostile peer can't exhaust memory
+pub const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Domain separator so a message signature can never be replayed as another kind of signature
+const SIGNED_MESSAGE_DOMAIN: &[u8] = b"p2pmesh:message:v1";
+
 /// Unique identifier for a message (for deduplication)
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct MessageId([u8; 32]);
@@ -39,6 +64,9 @@ pub enum MessageType {
     IOUAnnouncement,
     PeerAnnouncement,
     Heartbeat,
+    Encrypted,
+    Hello,
+    Signed,
 }
 
 /// Protocol errors
@@ -52,9 +80,21 @@ pub enum ProtocolError {
 
     #[error("Message too large")]
     MessageTooLarge,
+
+    #[error("Encryption error: {0}")]
+    Encryption(#[from] EncryptionError),
+
+    #[error("Unknown message type: {0}")]
+    UnknownMessageType(u32),
+
+    #[error("Payload too large once decompressed")]
+    PayloadTooLarge,
 }
 
 /// Wrapper for all message types
+///
+/// New variants must only ever be appended, since the variant index is the
+/// message type on the wire.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Message {
     SyncRequest(SyncRequest),
@@ -62,9 +102,27 @@ pub enum Message {
     IOUAnnouncement(IOUAnnouncement),
     PeerAnnouncement(PeerAnnouncement),
     Heartbeat(Heartbeat),
+    Encrypted(EncryptedMessage),
+    Hello(Hello),
+    Signed(SignedMessage),
+}
+
+/// How urgently an outbound message should be sent; urgent ones go first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Payments, heartbeats and handshakes
+    Urgent,
+    /// State transfers that can wait behind urgent traffic
+    Bulk,
 }
 
 impl Message {
+    /// Number of message types this node knows; higher wire tags are from newer nodes
+    const KNOWN_TYPES: u32 = 8;
+
+    /// Wire tags at or above this are garbage rather than types we don't know yet
+    const MAX_TYPES: u32 = 64;
+
     /// Get the message type
     pub fn message_type(&self) -> MessageType {
         match self {
@@ -73,6 +131,47 @@ impl Message {
             Message::IOUAnnouncement(_) => MessageType::IOUAnnouncement,
             Message::PeerAnnouncement(_) => MessageType::PeerAnnouncement,
             Message::Heartbeat(_) => MessageType::Heartbeat,
+            Message::Encrypted(_) => MessageType::Encrypted,
+            Message::Hello(_) => MessageType::Hello,
+            Message::Signed(_) => MessageType::Signed,
+        }
+    }
+
+    /// The node a message says it comes from, for types that name one
+    ///
+    /// IOU announcements name the IOU's sender rather than the node passing them
+    /// on, and encrypted and signed messages only the recipient or the signer.
+    pub fn sender(&self) -> Option<&NodeId> {
+        match self {
+            Message::SyncRequest(r) => Some(&r.sender),
+            Message::SyncResponse(r) => Some(&r.sender),
+            Message::PeerAnnouncement(a) => Some(&a.node_id),
+            Message::Heartbeat(h) => Some(&h.sender),
+            Message::Hello(h) => Some(&h.node_id),
+            Message::IOUAnnouncement(_) | Message::Encrypted(_) | Message::Signed(_) => None,
+        }
+    }
+
+    /// How urgently the message should be sent
+    ///
+    /// State transfers and peer lists are bulk; everything else, payments and
+    /// heartbeats included, is small and time-sensitive. A signed message goes at
+    /// the priority of the one inside.
+    pub fn priority(&self) -> Priority {
+        match self {
+            Message::SyncResponse(_) | Message::PeerAnnouncement(_) => Priority::Bulk,
+            Message::Signed(signed) => signed.message().map_or(Priority::Urgent, |inner| inner.priority()),
+            _ => Priority::Urgent,
+        }
+    }
+
+    /// Optional features the receiver must have agreed to for this message
+    pub fn required_features(&self) -> Features {
+        match self {
+            Message::Encrypted(_) => Features::ENCRYPTION,
+            Message::Signed(_) => Features::SIGNED_MESSAGES,
+            Message::SyncRequest(r) if r.summary.is_some() => Features::SUMMARY_SYNC,
+            _ => Features::NONE,
         }
     }
 
@@ -86,11 +185,26 @@ impl Message {
                 hasher.update(b"sync_req:");
                 hasher.update(r.sender.as_bytes());
                 hasher.update(r.known_version.to_le_bytes());
+                // Resumed syncs repeat a cursor after a drop, so each request is distinct
+                if let Some(cursor) = r.since_cursor {
+                    hasher.update(b"cursor:");
+                    hasher.update(cursor.to_le_bytes());
+                    hasher.update(r.timestamp.to_le_bytes());
+                }
+                if let Some(summary) = &r.summary {
+                    hasher.update(b"summary:");
+                    hasher.update(summary.root());
+                }
             }
             Message::SyncResponse(r) => {
                 hasher.update(b"sync_resp:");
                 hasher.update(r.sender.as_bytes());
                 hasher.update(r.current_version.to_le_bytes());
+                // Pages of one sync share a version
+                if let Some(cursor) = r.next_cursor {
+                    hasher.update(b"cursor:");
+                    hasher.update(cursor.to_le_bytes());
+                }
             }
             Message::IOUAnnouncement(a) => {
                 hasher.update(b"iou_ann:");
@@ -107,6 +221,23 @@ impl Message {
                 hasher.update(h.version.to_le_bytes());
                 hasher.update(h.timestamp.to_le_bytes());
             }
+            Message::Encrypted(e) => {
+                hasher.update(b"enc:");
+                hasher.update(e.envelope.to_bytes());
+            }
+            Message::Hello(h) => {
+                hasher.update(b"hello:");
+                hasher.update(h.node_id.as_bytes());
+                hasher.update(h.version.to_le_bytes());
+                hasher.update(h.features.bits().to_le_bytes());
+                hasher.update([h.reply as u8]);
+                hasher.update(h.timestamp.to_le_bytes());
+            }
+            Message::Signed(m) => {
+                hasher.update(b"signed:");
+                hasher.update(m.sender.as_bytes());
+                hasher.update(m.sequence.to_le_bytes());
+            }
         }
 
         let result = hasher.finalize();
@@ -115,14 +246,156 @@ impl Message {
         MessageId(bytes)
     }
 
+    /// Seal this message so only `recipient` can read it
+    pub fn encrypt_for(&self, recipient: &PublicKey) -> Result<Message, ProtocolError> {
+        Ok(Message::Encrypted(EncryptedMessage::seal(recipient, self)?))
+    }
+
     /// Serialize to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
         postcard::to_allocvec(self).unwrap_or_default()
     }
 
     /// Deserialize from bytes
+    ///
+    /// Fails with `ProtocolError::UnknownMessageType` for a message type added
+    /// by a newer node, so the caller can skip it rather than drop the peer.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProtocolError> {
-        postcard::from_bytes(bytes).map_err(|_| ProtocolError::DeserializationFailed)
+        postcard::from_bytes(bytes).map_err(|_| match postcard::take_from_bytes::<u32>(bytes) {
+            Ok((tag, _)) if (Self::KNOWN_TYPES..Self::MAX_TYPES).contains(&tag) => {
+                ProtocolError::UnknownMessageType(tag)
+            }
+            _ => ProtocolError::DeserializationFailed,
+        })
+    }
+
+    /// Serialize for the wire, compressed with `compression` if at least `min_bytes` long
+    ///
+    /// Packed messages start with a compression header byte, which no message
+    /// type tag can be. A `min_bytes` of 0 sends the plain `to_bytes` encoding,
+    /// for peers that didn't agree to compression.
+    pub fn to_wire_bytes(&self, compression: Compression, min_bytes: usize) -> Vec<u8> {
+        let bytes = self.to_bytes();
+        if min_bytes == 0 {
+            return bytes;
+        }
+        compression::pack_with(&bytes, compression, min_bytes)
+    }
+
+    /// Deserialize from the wire, decompressing packed messages transparently
+    pub fn from_wire_bytes(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        Self::from_wire_bytes_with_limit(bytes, MAX_MESSAGE_BYTES)
+    }
+
+    /// Deserialize from the wire, refusing messages that expand past `limit` bytes
+    ///
+    /// Fails with `ProtocolError::PayloadTooLarge` as soon as inflating passes the
+    /// limit, so a small compressed bomb never gets fully expanded.
+    pub fn from_wire_bytes_with_limit(bytes: &[u8], limit: usize) -> Result<Self, ProtocolError> {
+        match compression::unpack_with_limit(bytes, limit) {
+            Some(unpacked) => Self::from_bytes(&unpacked?),
+            None if bytes.len() > limit => Err(ProtocolError::PayloadTooLarge),
+            None => Self::from_bytes(bytes),
+        }
+    }
+}
+
+// ============================================================================
+// FEATURES
+// ============================================================================
+
+/// Set of optional protocol features, as bit flags
+///
+/// Bits this node doesn't know are kept when decoding a peer's Hello, but never
+/// survive negotiation, since the agreed set is the intersection of both sides.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Features(u32);
+
+impl Features {
+    /// No optional features
+    pub const NONE: Features = Features(0);
+    /// Deflated state and delta payloads (see `pack_payload`)
+    pub const COMPRESSION: Features = Features(1 << 0);
+    /// Messages sealed to one recipient
+    pub const ENCRYPTION: Features = Features(1 << 1);
+    /// Compact IOU encoding (see `IOUCodec`)
+    pub const COMPACT_IOU: Features = Features(1 << 2);
+    /// Sync requests carrying a state summary, answered with a delta
+    pub const SUMMARY_SYNC: Features = Features(1 << 3);
+    /// Messages signed with the sender's node key (see `SignedMessage`)
+    pub const SIGNED_MESSAGES: Features = Features(1 << 4);
+
+    /// Every feature this node supports
+    pub const SUPPORTED: Features = Features(
+        Self::COMPRESSION.0 | Self::ENCRYPTION.0 | Self::COMPACT_IOU.0 | Self::SUMMARY_SYNC.0 | Self::SIGNED_MESSAGES.0,
+    );
+
+    /// Features assumed of a version 1 node, which predates the handshake
+    pub const LEGACY: Features = Features(Self::ENCRYPTION.0 | Self::SUMMARY_SYNC.0);
+
+    /// Create from raw bits, keeping unknown ones
+    pub fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Get raw bits
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Check if every feature in `other` is in this set
+    pub fn contains(&self, other: Features) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Check if the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Features in this set but not in `other`
+    pub fn difference(&self, other: Features) -> Features {
+        Features(self.0 & !other.0)
+    }
+}
+
+impl BitOr for Features {
+    type Output = Features;
+
+    fn bitor(self, other: Features) -> Features {
+        Features(self.0 | other.0)
+    }
+}
+
+impl BitAnd for Features {
+    type Output = Features;
+
+    fn bitand(self, other: Features) -> Features {
+        Features(self.0 & other.0)
+    }
+}
+
+impl fmt::Display for Features {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NAMES: [(Features, &str); 4] = [
+            (Features::COMPRESSION, "compression"),
+            (Features::ENCRYPTION, "encryption"),
+            (Features::COMPACT_IOU, "compact-iou"),
+            (Features::SUMMARY_SYNC, "summary-sync"),
+        ];
+        let mut names: Vec<String> = NAMES
+            .iter()
+            .filter(|(feature, _)| self.contains(*feature))
+            .map(|(_, name)| name.to_string())
+            .collect();
+        let unknown = self.difference(Features::SUPPORTED);
+        if !unknown.is_empty() {
+            names.push(format!("{:#x}", unknown.0));
+        }
+        if names.is_empty() {
+            return f.write_str("none");
+        }
+        f.write_str(&names.join("|"))
     }
 }
 
@@ -133,13 +406,19 @@ impl Message {
 /// Request for state synchronization
 ///
 /// Sent to request IOUs that the sender doesn't have.
-/// The receiver should respond with entries newer than known_version.
+/// The receiver should respond with entries newer than known_version, with
+/// one page of entries after `since_cursor` when resuming a paged sync, or with
+/// the delta against `summary` when the requester sent its state summary.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SyncRequest {
     /// Node ID of the requester
     sender: NodeId,
     /// The version the requester currently has
     known_version: u64,
+    /// Resume after this cursor in the responder's sequence (None = whole state)
+    since_cursor: Option<u64>,
+    /// Requester's state summary, asking for only the entries it is missing
+    summary: Option<StateSummary>,
     /// Optional filter: only want IOUs from this sender
     sender_filter: Option<Did>,
     /// Optional filter: only want IOUs to this recipient
@@ -159,12 +438,26 @@ impl SyncRequest {
         Self {
             sender,
             known_version,
+            since_cursor: None,
+            summary: None,
             sender_filter: None,
             recipient_filter: None,
             timestamp,
         }
     }
 
+    /// Ask for a page of entries after `cursor` (0 = from the beginning)
+    pub fn with_since_cursor(mut self, cursor: u64) -> Self {
+        self.since_cursor = Some(cursor);
+        self
+    }
+
+    /// Ask for the delta against our state summary instead of the whole state
+    pub fn with_summary(mut self, summary: StateSummary) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
     /// Add a sender filter
     pub fn with_sender_filter(mut self, sender: Did) -> Self {
         self.sender_filter = Some(sender);
@@ -187,6 +480,16 @@ impl SyncRequest {
         self.known_version
     }
 
+    /// Get the cursor to resume after, for a paged sync
+    pub fn since_cursor(&self) -> Option<u64> {
+        self.since_cursor
+    }
+
+    /// Get the requester's state summary, for a delta sync
+    pub fn summary(&self) -> Option<&StateSummary> {
+        self.summary.as_ref()
+    }
+
     /// Get the sender filter
     pub fn sender_filter(&self) -> Option<&Did> {
         self.sender_filter.as_ref()
@@ -220,8 +523,12 @@ pub struct SyncResponse {
     entries: Vec<IOUEntry>,
     /// Whether there are more entries available
     has_more: bool,
+    /// Cursor to request the next page from (paged syncs only)
+    next_cursor: Option<u64>,
     /// Timestamp
     timestamp: u64,
+    /// Responder's version vector, set only when `entries` is its whole state
+    clock: Option<VersionVector>,
 }
 
 impl SyncResponse {
@@ -237,7 +544,9 @@ impl SyncResponse {
             current_version,
             entries,
             has_more: false,
+            next_cursor: None,
             timestamp,
+            clock: None,
         }
     }
 
@@ -247,6 +556,18 @@ impl SyncResponse {
         self
     }
 
+    /// Set the cursor the requester resumes from
+    pub fn with_next_cursor(mut self, cursor: u64) -> Self {
+        self.next_cursor = Some(cursor);
+        self
+    }
+
+    /// Attach the responder's version vector; only for a response carrying its whole state
+    pub fn with_clock(mut self, clock: VersionVector) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     /// Get the sender node ID
     pub fn sender(&self) -> &NodeId {
         &self.sender
@@ -267,19 +588,67 @@ impl SyncResponse {
         self.has_more
     }
 
+    /// Get the cursor to request the next page from
+    pub fn next_cursor(&self) -> Option<u64> {
+        self.next_cursor
+    }
+
+    /// Get the responder's version vector, if the response carries its whole state
+    pub fn clock(&self) -> Option<&VersionVector> {
+        self.clock.as_ref()
+    }
+
     /// Get the timestamp
     pub fn timestamp(&self) -> u64 {
         self.timestamp
     }
+
+    /// Split into responses of at most `max_entries` entries each (0 = don't split)
+    ///
+    /// Sent in order, the chunks add up to this response: only the last carries
+    /// the version vector and paging cursor, so neither is applied before every
+    /// entry has arrived.
+    pub fn into_chunks(self, max_entries: usize) -> Vec<SyncResponse> {
+        if max_entries == 0 || self.entries.len() <= max_entries {
+            return vec![self];
+        }
+        let mut chunks: Vec<SyncResponse> = self
+            .entries
+            .chunks(max_entries)
+            .map(|entries| SyncResponse {
+                sender: self.sender.clone(),
+                current_version: self.current_version,
+                entries: entries.to_vec(),
+                has_more: false,
+                next_cursor: None,
+                timestamp: self.timestamp,
+                clock: None,
+            })
+            .collect();
+        if let Some(last) = chunks.last_mut() {
+            last.has_more = self.has_more;
+            last.next_cursor = self.next_cursor;
+            last.clock = self.clock;
+        }
+        chunks
+    }
 }
 
 // ============================================================================
 // IOU ANNOUNCEMENT
 // ============================================================================
 
+/// Relays an announcement may take by default before it stops spreading
+pub const DEFAULT_ANNOUNCEMENT_TTL: u8 = 6;
+
+/// Most nodes an announcement remembers passing through; the oldest are dropped
+pub const MAX_SEEN_BY: usize = 16;
+
 /// Announcement of a new IOU to the network
 ///
-/// Used for rumor spreading - nodes forward new IOUs to their peers.
+/// Used for rumor spreading - nodes forward new IOUs to their peers. Each relay
+/// spends one unit of TTL and adds the relaying node to `seen_by`, so it isn't
+/// sent back to nodes it has already passed through.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct IOUAnnouncement {
     /// The IOU being announced
@@ -292,6 +661,10 @@ pub struct IOUAnnouncement {
     max_hops: u8,
     /// Timestamp when first announced
     timestamp: u64,
+    /// Relays left before propagation stops
+    ttl: u8,
+    /// Nodes the announcement has passed through, oldest first
+    seen_by: Vec<NodeId>,
 }
 
 impl IOUAnnouncement {
@@ -308,6 +681,8 @@ impl IOUAnnouncement {
             hop_count: 0,
             max_hops: 6, // Default: 6 hops like typical gossip
             timestamp,
+            ttl: DEFAULT_ANNOUNCEMENT_TTL,
+            seen_by: Vec::new(),
         }
     }
 
@@ -317,6 +692,18 @@ impl IOUAnnouncement {
         self
     }
 
+    /// Set the relays left before propagation stops
+    pub fn with_ttl(mut self, ttl: u8) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Record that `node` has seen the announcement
+    pub fn with_seen_by(mut self, node: NodeId) -> Self {
+        self.mark_seen_by(node);
+        self
+    }
+
     /// Get the IOU
     pub fn iou(&self) -> &SignedIOU {
         &self.iou
@@ -339,7 +726,51 @@ impl IOUAnnouncement {
 
     /// Check if propagation should stop
     pub fn should_stop_propagation(&self) -> bool {
-        self.hop_count >= self.max_hops
+        self.hop_count >= self.max_hops || self.ttl == 0
+    }
+
+    /// Get the relays left before propagation stops
+    pub fn ttl(&self) -> u8 {
+        self.ttl
+    }
+
+    /// Get the nodes the announcement has passed through, oldest first
+    pub fn seen_by(&self) -> &[NodeId] {
+        &self.seen_by
+    }
+
+    /// Whether the announcement has passed through `node`
+    pub fn was_seen_by(&self, node: &NodeId) -> bool {
+        self.seen_by.contains(node)
+    }
+
+    /// Record that `node` has seen the announcement, keeping at most `MAX_SEEN_BY` nodes
+    pub fn mark_seen_by(&mut self, node: NodeId) {
+        if self.was_seen_by(&node) {
+            return;
+        }
+        self.seen_by.push(node);
+        if self.seen_by.len() > MAX_SEEN_BY {
+            let excess = self.seen_by.len() - MAX_SEEN_BY;
+            self.seen_by.drain(..excess);
+        }
+    }
+
+    /// The announcement as `relayer` passes it on, or `None` if it should stop here
+    ///
+    /// The TTL is first capped at `max_ttl`, so a peer can't make an announcement
+    /// travel further than we allow; the relay then spends one unit of it, counts a
+    /// hop, and adds `relayer` to `seen_by`.
+    pub fn relayed_by(&self, relayer: &NodeId, max_ttl: u8) -> Option<Self> {
+        let mut relayed = self.clone();
+        relayed.ttl = relayed.ttl.min(max_ttl);
+        if relayed.should_stop_propagation() {
+            return None;
+        }
+        relayed.ttl -= 1;
+        relayed.increment_hop();
+        relayed.mark_seen_by(relayer.clone());
+        Some(relayed)
     }
 
     /// Get timestamp
@@ -363,6 +794,30 @@ impl IOUAnnouncement {
 // PEER ANNOUNCEMENT
 // ============================================================================
 
+/// A peer shared in a `PeerAnnouncement`, and where to reach it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownPeer {
+    node_id: NodeId,
+    address: PeerAddress,
+}
+
+impl KnownPeer {
+    /// Create a known peer
+    pub fn new(node_id: NodeId, address: PeerAddress) -> Self {
+        Self { node_id, address }
+    }
+
+    /// Get node ID
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    /// Get address
+    pub fn address(&self) -> &PeerAddress {
+        &self.address
+    }
+}
+
 /// Announcement of a peer's presence
 ///
 /// Used for peer discovery - nodes announce themselves and share known peers.
@@ -378,6 +833,8 @@ pub struct PeerAnnouncement {
     capabilities: HashSet<String>,
     /// Timestamp
     timestamp: u64,
+    /// Other peers the announcing peer vouches for
+    known_peers: Vec<KnownPeer>,
 }
 
 impl PeerAnnouncement {
@@ -394,6 +851,7 @@ impl PeerAnnouncement {
             address: None,
             capabilities: HashSet::new(),
             timestamp,
+            known_peers: Vec::new(),
         }
     }
 
@@ -429,6 +887,17 @@ impl PeerAnnouncement {
         self.capabilities.contains(capability)
     }
 
+    /// Set the peers shared with the announcement
+    pub fn with_known_peers(mut self, peers: Vec<KnownPeer>) -> Self {
+        self.known_peers = peers;
+        self
+    }
+
+    /// Get the peers shared with the announcement
+    pub fn known_peers(&self) -> &[KnownPeer] {
+        &self.known_peers
+    }
+
     /// Get timestamp
     pub fn timestamp(&self) -> u64 {
         self.timestamp
@@ -450,6 +919,8 @@ pub struct Heartbeat {
     version: u64,
     /// Timestamp
     timestamp: u64,
+    /// Sender's version vector
+    clock: VersionVector,
 }
 
 impl Heartbeat {
@@ -464,9 +935,16 @@ impl Heartbeat {
             sender,
             version,
             timestamp,
+            clock: VersionVector::new(),
         }
     }
 
+    /// Attach the sender's version vector
+    pub fn with_clock(mut self, clock: VersionVector) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Get the sender node ID
     pub fn sender(&self) -> &NodeId {
         &self.sender
@@ -477,12 +955,272 @@ impl Heartbeat {
         self.version
     }
 
+    /// Get the sender's version vector (empty if it didn't send one)
+    pub fn clock(&self) -> &VersionVector {
+        &self.clock
+    }
+
+    /// Get the timestamp
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+// ============================================================================
+// HELLO
+// ============================================================================
+
+/// First message on a connection, announcing the sender's protocol version and features
+///
+/// The receiver records the lower of the two versions and the features both
+/// support, then answers with a reply Hello of its own so the sender can do
+/// the same. Replies are never answered.
+///
+/// A node with a key sends its public key too. The node ID is derived from it
+/// (`NodeId::from_public_key`), so the key needs no signature to be trusted;
+/// the receiver checks the two match and verifies the sender's signed messages
+/// with it from then on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Hello {
+    /// Node ID of the sender
+    node_id: NodeId,
+    /// Highest protocol version the sender speaks
+    version: u16,
+    /// Optional features the sender supports
+    features: Features,
+    /// Whether this answers the receiver's own Hello
+    reply: bool,
+    /// Timestamp
+    timestamp: u64,
+    /// Key the sender signs its messages with
+    public_key: Option<PublicKey>,
+}
+
+impl Hello {
+    /// Create a Hello for this node's protocol version
+    pub fn new(node_id: NodeId, features: Features) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        Self {
+            node_id,
+            version: PROTOCOL_VERSION,
+            features,
+            reply: false,
+            timestamp,
+            public_key: None,
+        }
+    }
+
+    /// Announce the key the sender signs its messages with
+    pub fn with_public_key(mut self, public_key: PublicKey) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    /// Announce a different protocol version, e.g. to stay on an older one during a rollout
+    pub fn with_version(mut self, version: u16) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Mark this Hello as the answer to a peer's
+    pub fn as_reply(mut self) -> Self {
+        self.reply = true;
+        self
+    }
+
+    /// Get the sender node ID
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    /// Get the sender's protocol version
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Get the sender's features
+    pub fn features(&self) -> Features {
+        self.features
+    }
+
+    /// Check if this answers our own Hello
+    pub fn is_reply(&self) -> bool {
+        self.reply
+    }
+
+    /// Get the key the sender signs its messages with, if it sent one
+    pub fn public_key(&self) -> Option<&PublicKey> {
+        self.public_key.as_ref()
+    }
+
+    /// Whether the announced key is the one the sender's node ID derives from
+    pub fn key_matches_node(&self) -> bool {
+        self.public_key
+            .as_ref()
+            .is_some_and(|key| NodeId::from_public_key(key) == self.node_id)
+    }
+
     /// Get the timestamp
     pub fn timestamp(&self) -> u64 {
         self.timestamp
     }
 }
 
+// ============================================================================
+// ENCRYPTED MESSAGE
+// ============================================================================
+
+/// A message sealed to one recipient
+///
+/// Gossiped like any other message; only the recipient can open it. The
+/// recipient DID is in the clear so nodes can tell which envelopes are theirs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EncryptedMessage {
+    /// Intended recipient
+    recipient: Did,
+    /// Sealed inner message
+    envelope: SealedEnvelope,
+}
+
+impl EncryptedMessage {
+    /// Seal `inner` to `recipient`
+    pub fn seal(recipient: &PublicKey, inner: &Message) -> Result<Self, ProtocolError> {
+        let envelope = encrypt_for(recipient, &inner.to_bytes())?;
+        Ok(Self {
+            recipient: Did::from_public_key(recipient),
+            envelope,
+        })
+    }
+
+    /// Get the recipient DID
+    pub fn recipient(&self) -> &Did {
+        &self.recipient
+    }
+
+    /// Get the sealed envelope
+    pub fn envelope(&self) -> &SealedEnvelope {
+        &self.envelope
+    }
+
+    /// Check if this message is addressed to `did`
+    pub fn is_for(&self, did: &Did) -> bool {
+        &self.recipient == did
+    }
+
+    /// Open the envelope with the recipient's keypair
+    ///
+    /// Fails with `ProtocolError::Encryption` for any other keypair.
+    pub fn decrypt(&self, keypair: &Keypair) -> Result<Message, ProtocolError> {
+        let plaintext = decrypt(keypair, &self.envelope)?;
+        Message::from_bytes(&plaintext)
+    }
+}
+
+// ============================================================================
+// SIGNED MESSAGE
+// ============================================================================
+
+/// A message signed with its sender's node key
+///
+/// The signature covers the encoded message, the sender, a timestamp and a
+/// sequence number. Senders number their messages in increasing order, so a
+/// receiver that remembers the last number it accepted from each peer can
+/// refuse a captured message played back later.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedMessage {
+    /// Encoded inner message (`Message::to_bytes`)
+    payload: Vec<u8>,
+    /// Node that signed it
+    sender: NodeId,
+    /// When it was signed (ms)
+    timestamp: u64,
+    /// Position in the sender's sequence of signed messages
+    sequence: u64,
+    signature: Signature,
+}
+
+impl SignedMessage {
+    /// Sign `message` as the node `keypair` belongs to, timestamped now
+    pub fn sign(keypair: &Keypair, message: &Message, sequence: u64) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        Self::sign_at(keypair, message, sequence, timestamp)
+    }
+
+    /// Sign `message` with the given timestamp (ms)
+    pub fn sign_at(keypair: &Keypair, message: &Message, sequence: u64, timestamp: u64) -> Self {
+        let payload = message.to_bytes();
+        let sender = NodeId::from_public_key(&keypair.public_key());
+        let signing_bytes = Self::signing_bytes(&payload, &sender, timestamp, sequence);
+        Self {
+            payload,
+            sender,
+            timestamp,
+            sequence,
+            signature: Signer::sign(keypair, &signing_bytes),
+        }
+    }
+
+    fn signing_bytes(payload: &[u8], sender: &NodeId, timestamp: u64, sequence: u64) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SIGNED_MESSAGE_DOMAIN.len() + 56 + payload.len());
+        bytes.extend_from_slice(SIGNED_MESSAGE_DOMAIN);
+        bytes.extend_from_slice(sender.as_bytes());
+        bytes.extend_from_slice(&timestamp.to_le_bytes());
+        bytes.extend_from_slice(&sequence.to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    /// Get the signing node's ID
+    pub fn sender(&self) -> &NodeId {
+        &self.sender
+    }
+
+    /// Get the signing time (ms)
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Get the sequence number
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Get the encoded inner message
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Get the signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Verify the signature against the sender's public key
+    pub fn verify(&self, public_key: &PublicKey) -> bool {
+        let signing_bytes = Self::signing_bytes(&self.payload, &self.sender, self.timestamp, self.sequence);
+        Signer::verify(public_key, &signing_bytes, &self.signature)
+    }
+
+    /// Decode the inner message
+    ///
+    /// Fails with `ProtocolError::InvalidFormat` for a signed message inside
+    /// another, which no sender produces.
+    pub fn message(&self) -> Result<Message, ProtocolError> {
+        match Message::from_bytes(&self.payload)? {
+            Message::Signed(_) => Err(ProtocolError::InvalidFormat),
+            message => Ok(message),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
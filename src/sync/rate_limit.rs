@@ -0,0 +1,153 @@
+// Rate Limiting - Token buckets for per-peer inbound limits
+//
+// A bucket holds up to one second's worth of tokens and refills continuously
+// at its rate, so a peer can burst up to the rate and then sustain it. Tokens
+// are counted in thousandths so refills are exact integer arithmetic and a
+// given sequence of timestamps always gives the same answers.
+//
+// A peer that keeps sending past its limit is throttled: everything it sends is
+// dropped for a cool-down, however full its buckets are.
+
+/// A token bucket refilling at `rate` tokens per second, up to `rate` tokens
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    /// Tokens added per second, and the most the bucket holds
+    rate: u64,
+    /// Tokens held, in thousandths
+    milli_tokens: u64,
+    /// When the bucket was last refilled (ms)
+    updated_ms: u64,
+}
+
+impl TokenBucket {
+    /// Create a full bucket as of `now_ms`
+    pub fn new(rate: u64, now_ms: u64) -> Self {
+        Self {
+            rate,
+            milli_tokens: rate.saturating_mul(1000),
+            updated_ms: now_ms,
+        }
+    }
+
+    /// Tokens added per second
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// Whole tokens available at `now_ms`
+    pub fn available_at(&mut self, now_ms: u64) -> u64 {
+        self.refill(now_ms);
+        self.milli_tokens / 1000
+    }
+
+    /// Take `amount` tokens at `now_ms` if there are enough
+    ///
+    /// An amount larger than the whole bucket can never be covered, so it is
+    /// let through when the bucket is full and empties it.
+    pub fn try_take_at(&mut self, amount: u64, now_ms: u64) -> bool {
+        self.refill(now_ms);
+        if !self.can_take(amount) {
+            return false;
+        }
+        self.take(amount);
+        true
+    }
+
+    /// Whether `amount` tokens could be taken without refilling first
+    pub(crate) fn can_take(&self, amount: u64) -> bool {
+        self.milli_tokens >= amount.saturating_mul(1000) || self.is_full()
+    }
+
+    pub(crate) fn take(&mut self, amount: u64) {
+        self.milli_tokens = self.milli_tokens.saturating_sub(amount.saturating_mul(1000));
+    }
+
+    pub(crate) fn refill(&mut self, now_ms: u64) {
+        let elapsed = now_ms.saturating_sub(self.updated_ms);
+        self.milli_tokens = self
+            .milli_tokens
+            .saturating_add(elapsed.saturating_mul(self.rate))
+            .min(self.capacity());
+        self.updated_ms = self.updated_ms.max(now_ms);
+    }
+
+    fn capacity(&self) -> u64 {
+        self.rate.saturating_mul(1000)
+    }
+
+    fn is_full(&self) -> bool {
+        self.milli_tokens >= self.capacity()
+    }
+}
+
+/// Message and byte buckets for one peer; a message must fit both to pass
+#[derive(Clone, Debug)]
+pub(crate) struct PeerLimits {
+    messages: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+    /// Messages dropped in a row
+    dropped: u32,
+    /// When a throttle in force ends (ms)
+    throttled_until: Option<u64>,
+}
+
+/// What became of a message put to a peer's limits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Admission {
+    Admitted,
+    Dropped,
+    /// Dropped, and the peer is throttled from now on
+    Throttled,
+}
+
+impl PeerLimits {
+    /// Buckets for the given rates (0 = unlimited), full as of `now_ms`
+    pub(crate) fn new(messages_per_sec: u64, bytes_per_sec: u64, now_ms: u64) -> Self {
+        Self {
+            messages: (messages_per_sec > 0).then(|| TokenBucket::new(messages_per_sec, now_ms)),
+            bytes: (bytes_per_sec > 0).then(|| TokenBucket::new(bytes_per_sec, now_ms)),
+            dropped: 0,
+            throttled_until: None,
+        }
+    }
+
+    /// Admit a message as `admit` does, throttling the peer for `throttle_ms` once
+    /// `throttle_after` messages in a row are dropped (0 = never throttle)
+    pub(crate) fn check(&mut self, len: u64, now_ms: u64, throttle_after: u32, throttle_ms: u64) -> Admission {
+        if self.throttled_until.is_some_and(|until| now_ms < until) {
+            return Admission::Dropped;
+        }
+        self.throttled_until = None;
+        if self.admit(len, now_ms) {
+            self.dropped = 0;
+            return Admission::Admitted;
+        }
+        self.dropped = self.dropped.saturating_add(1);
+        if throttle_after > 0 && self.dropped >= throttle_after {
+            self.dropped = 0;
+            self.throttled_until = Some(now_ms.saturating_add(throttle_ms));
+            return Admission::Throttled;
+        }
+        Admission::Dropped
+    }
+
+    /// Admit one message of `len` bytes at `now_ms`, charging the buckets only if both allow it
+    pub(crate) fn admit(&mut self, len: u64, now_ms: u64) -> bool {
+        let fits = |bucket: &mut Option<TokenBucket>, amount| {
+            bucket.as_mut().is_none_or(|bucket| {
+                bucket.refill(now_ms);
+                bucket.can_take(amount)
+            })
+        };
+        if !(fits(&mut self.messages, 1) && fits(&mut self.bytes, len)) {
+            return false;
+        }
+        if let Some(bucket) = &mut self.messages {
+            bucket.take(1);
+        }
+        if let Some(bucket) = &mut self.bytes {
+            bucket.take(len);
+        }
+        true
+    }
+}
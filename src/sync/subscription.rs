@@ -0,0 +1,161 @@
+// Event Subscriptions - Gossip events pushed to async consumers
+//
+// Every event the engine returns from its polling methods is also copied to each
+// subscription, so a tokio task can await them instead of polling in a loop.
+// A subscription buffers at most its capacity of events. When the consumer lags
+// and the buffer is full, the oldest event is dropped to make room, and the next
+// receive reports how many were lost with `GossipEvent::Lagged` before carrying
+// on with the events still held. The engine never waits on a slow consumer.
+
+use crate::sync::GossipEvent;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+/// Events buffered per subscription unless configured otherwise
+pub const DEFAULT_SUBSCRIPTION_CAPACITY: usize = 256;
+
+/// Buffer shared by the engine and one subscription
+struct Inbox {
+    /// Events not yet received, oldest first
+    events: VecDeque<GossipEvent>,
+    /// Events dropped since the consumer last received
+    lagged: u64,
+    /// Most events held at once
+    capacity: usize,
+    /// The engine is gone, so no more events will arrive
+    closed: bool,
+}
+
+struct Shared {
+    inbox: Mutex<Inbox>,
+    notify: Notify,
+}
+
+impl Shared {
+    fn inbox(&self) -> MutexGuard<'_, Inbox> {
+        self.inbox.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A stream of gossip events, from `GossipEngine::subscribe`
+pub struct GossipSubscription {
+    shared: Arc<Shared>,
+}
+
+impl GossipSubscription {
+    /// Wait for the next event
+    ///
+    /// Returns `Lagged(n)` first if `n` events were dropped while the buffer was
+    /// full, and `None` once the engine is dropped and every held event received.
+    pub async fn recv(&mut self) -> Option<GossipEvent> {
+        loop {
+            let closed = {
+                let mut inbox = self.shared.inbox();
+                if let Some(event) = Self::take(&mut inbox) {
+                    return Some(event);
+                }
+                inbox.closed
+            };
+            if closed {
+                return None;
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Take the next event if one is waiting
+    pub fn try_recv(&mut self) -> Option<GossipEvent> {
+        Self::take(&mut self.shared.inbox())
+    }
+
+    fn take(inbox: &mut Inbox) -> Option<GossipEvent> {
+        if inbox.lagged > 0 {
+            return Some(GossipEvent::Lagged(std::mem::take(&mut inbox.lagged)));
+        }
+        inbox.events.pop_front()
+    }
+
+    /// Events waiting to be received, not counting a pending `Lagged`
+    pub fn len(&self) -> usize {
+        self.shared.inbox().events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Most events buffered before the oldest are dropped
+    pub fn capacity(&self) -> usize {
+        self.shared.inbox().capacity
+    }
+
+    /// Whether the engine is gone (held events can still be received)
+    pub fn is_closed(&self) -> bool {
+        self.shared.inbox().closed
+    }
+}
+
+/// The engine's side of its subscriptions
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    subscribers: Vec<Arc<Shared>>,
+}
+
+impl Subscribers {
+    /// Add a subscription buffering up to `capacity` events (at least one)
+    pub(crate) fn subscribe(&mut self, capacity: usize) -> GossipSubscription {
+        let shared = Arc::new(Shared {
+            inbox: Mutex::new(Inbox {
+                events: VecDeque::new(),
+                lagged: 0,
+                capacity: capacity.max(1),
+                closed: false,
+            }),
+            notify: Notify::new(),
+        });
+        self.subscribers.push(shared.clone());
+        GossipSubscription { shared }
+    }
+
+    /// Number of live subscriptions
+    pub(crate) fn len(&mut self) -> usize {
+        self.prune();
+        self.subscribers.len()
+    }
+
+    /// Copy `events` to every live subscription, dropping the oldest where full
+    pub(crate) fn publish(&mut self, events: &[GossipEvent]) {
+        self.prune();
+        if events.is_empty() {
+            return;
+        }
+        for shared in &self.subscribers {
+            {
+                let mut inbox = shared.inbox();
+                for event in events {
+                    if inbox.events.len() >= inbox.capacity {
+                        inbox.events.pop_front();
+                        inbox.lagged += 1;
+                    }
+                    inbox.events.push_back(event.clone());
+                }
+            }
+            shared.notify.notify_one();
+        }
+    }
+
+    /// Forget subscriptions whose receiving half was dropped
+    fn prune(&mut self) {
+        self.subscribers.retain(|shared| Arc::strong_count(shared) > 1);
+    }
+}
+
+impl Drop for Subscribers {
+    fn drop(&mut self) {
+        for shared in &self.subscribers {
+            shared.inbox().closed = true;
+            shared.notify.notify_one();
+        }
+    }
+}
@@ -121,6 +121,8 @@ pub struct BleTransportConfig {
     pub reconnect_attempts: u32,
     /// Delay between reconnection attempts in ms
     pub reconnect_delay_ms: u32,
+    /// Largest payload accepted for send or reassembly
+    pub max_message_size: usize,
 }
 
 impl Default for BleTransportConfig {
@@ -137,6 +139,7 @@ impl Default for BleTransportConfig {
             auto_reconnect: false,
             reconnect_attempts: 3,
             reconnect_delay_ms: 1000,
+            max_message_size: 64 * 1024,
         }
     }
 }
@@ -200,6 +203,248 @@ impl BleTransportConfig {
         self.reconnect_delay_ms = delay_ms;
         self
     }
+
+    pub fn with_max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+}
+
+// ============================================================================
+// BLE ADAPTER (radio backend)
+// ============================================================================
+
+/// Event reported by a BLE adapter
+#[derive(Debug, Clone, PartialEq)]
+pub enum BleAdapterEvent {
+    /// A device advertising the mesh service was seen
+    Discovered {
+        address: PeerAddress,
+        name: Option<String>,
+        rssi: Option<i8>,
+    },
+    /// A peer wrote or notified one chunk on the data characteristic
+    ChunkReceived { address: PeerAddress, data: Vec<u8> },
+    /// The link to a peer dropped
+    Disconnected { address: PeerAddress, reason: String },
+}
+
+/// Radio backend driven by `BleTransport`.
+///
+/// Implement this over a platform BLE stack (CoreBluetooth, Android GATT); the `ble`
+/// feature provides `BtleplugAdapter` for desktop hosts.
+/// The transport handles connection bookkeeping, fragmentation and reassembly;
+/// the adapter only moves raw chunks.
+#[allow(async_fn_in_trait)]
+pub trait BleAdapter {
+    /// Initialize the radio
+    async fn power_on(&mut self) -> Result<(), TransportError>;
+
+    /// Release the radio
+    async fn power_off(&mut self);
+
+    /// Scan for devices advertising `service_uuid`
+    async fn start_scan(&mut self, service_uuid: &str) -> Result<(), TransportError>;
+
+    /// Stop scanning
+    async fn stop_scan(&mut self);
+
+    /// Advertise `service_uuid` (peripheral mode)
+    async fn start_advertising(&mut self, service_uuid: &str, name: Option<&str>) -> Result<(), TransportError>;
+
+    /// Stop advertising
+    async fn stop_advertising(&mut self);
+
+    /// GATT connect, discover the data characteristic and negotiate the MTU.
+    /// Returns the negotiated MTU.
+    async fn connect(
+        &mut self,
+        address: &PeerAddress,
+        service_uuid: &str,
+        characteristic_uuid: &str,
+        requested_mtu: u16,
+    ) -> Result<u16, TransportError>;
+
+    /// Drop the link to a peer
+    async fn disconnect(&mut self, address: &PeerAddress);
+
+    /// Write one chunk (at most MTU - 3 bytes) to the peer's data characteristic
+    async fn write(&mut self, address: &PeerAddress, chunk: &[u8]) -> Result<(), TransportError>;
+
+    /// Drain pending adapter events (non-blocking)
+    async fn poll(&mut self) -> Vec<BleAdapterEvent>;
+
+    /// Signal strength of a connected peer
+    fn rssi(&self, _address: &PeerAddress) -> Option<i8> {
+        None
+    }
+}
+
+/// Adapter for builds without a BLE backend: the radio always reports `HardwareUnavailable`
+#[derive(Debug, Clone, Default)]
+pub struct UnavailableBleAdapter;
+
+impl BleAdapter for UnavailableBleAdapter {
+    async fn power_on(&mut self) -> Result<(), TransportError> {
+        Err(TransportError::HardwareUnavailable)
+    }
+
+    async fn power_off(&mut self) {}
+
+    async fn start_scan(&mut self, _service_uuid: &str) -> Result<(), TransportError> {
+        Err(TransportError::HardwareUnavailable)
+    }
+
+    async fn stop_scan(&mut self) {}
+
+    async fn start_advertising(&mut self, _service_uuid: &str, _name: Option<&str>) -> Result<(), TransportError> {
+        Err(TransportError::HardwareUnavailable)
+    }
+
+    async fn stop_advertising(&mut self) {}
+
+    async fn connect(
+        &mut self,
+        _address: &PeerAddress,
+        _service_uuid: &str,
+        _characteristic_uuid: &str,
+        _requested_mtu: u16,
+    ) -> Result<u16, TransportError> {
+        Err(TransportError::HardwareUnavailable)
+    }
+
+    async fn disconnect(&mut self, _address: &PeerAddress) {}
+
+    async fn write(&mut self, _address: &PeerAddress, _chunk: &[u8]) -> Result<(), TransportError> {
+        Err(TransportError::HardwareUnavailable)
+    }
+
+    async fn poll(&mut self) -> Vec<BleAdapterEvent> {
+        Vec::new()
+    }
+}
+
+// ============================================================================
+// FRAGMENTATION
+// ============================================================================
+
+/// ATT header bytes taken out of every write
+pub const ATT_HEADER_LEN: usize = 3;
+
+/// Fragment header: [message_id:2][index:2][count:2] (little-endian)
+pub const FRAGMENT_HEADER_LEN: usize = 6;
+
+/// Split a payload into chunks that fit a single characteristic write at `mtu`
+pub fn fragment_payload(data: &[u8], mtu: u16, message_id: u16) -> Result<Vec<Vec<u8>>, TransportError> {
+    let chunk_size = (mtu as usize).saturating_sub(ATT_HEADER_LEN + FRAGMENT_HEADER_LEN);
+    if chunk_size == 0 {
+        return Err(TransportError::InvalidConfig(format!("MTU {} too small", mtu)));
+    }
+
+    let count = data.len().div_ceil(chunk_size).max(1);
+    if count > u16::MAX as usize {
+        return Err(TransportError::PayloadTooLarge);
+    }
+
+    let chunks = if data.is_empty() {
+        vec![data]
+    } else {
+        data.chunks(chunk_size).collect()
+    };
+
+    Ok(chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut fragment = Vec::with_capacity(FRAGMENT_HEADER_LEN + chunk.len());
+            fragment.extend_from_slice(&message_id.to_le_bytes());
+            fragment.extend_from_slice(&(index as u16).to_le_bytes());
+            fragment.extend_from_slice(&(count as u16).to_le_bytes());
+            fragment.extend_from_slice(chunk);
+            fragment
+        })
+        .collect())
+}
+
+#[derive(Debug)]
+struct PartialMessage {
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+    bytes: usize,
+}
+
+/// Reassembles fragmented payloads, per peer and message ID
+#[derive(Debug)]
+pub struct BleReassembler {
+    partial: HashMap<(PeerAddress, u16), PartialMessage>,
+    max_message_size: usize,
+}
+
+impl BleReassembler {
+    pub fn new(max_message_size: usize) -> Self {
+        Self {
+            partial: HashMap::new(),
+            max_message_size,
+        }
+    }
+
+    /// Add a fragment from `from`; returns the full payload once every fragment arrived
+    pub fn push(&mut self, from: &PeerAddress, fragment: &[u8]) -> Result<Option<Vec<u8>>, TransportError> {
+        if fragment.len() < FRAGMENT_HEADER_LEN {
+            return Err(TransportError::ReceiveFailed("Fragment too short".to_string()));
+        }
+
+        let message_id = u16::from_le_bytes([fragment[0], fragment[1]]);
+        let index = u16::from_le_bytes([fragment[2], fragment[3]]) as usize;
+        let count = u16::from_le_bytes([fragment[4], fragment[5]]) as usize;
+        let body = &fragment[FRAGMENT_HEADER_LEN..];
+        let key = (from.clone(), message_id);
+
+        if count == 0 || index >= count {
+            self.partial.remove(&key);
+            return Err(TransportError::ReceiveFailed(format!(
+                "Bad fragment {}/{}",
+                index, count
+            )));
+        }
+
+        let partial = self.partial.entry(key.clone()).or_insert_with(|| PartialMessage {
+            chunks: vec![None; count],
+            received: 0,
+            bytes: 0,
+        });
+        if partial.chunks.len() != count {
+            self.partial.remove(&key);
+            return Err(TransportError::ReceiveFailed("Fragment count changed".to_string()));
+        }
+
+        if partial.chunks[index].is_none() {
+            partial.bytes += body.len();
+            if partial.bytes > self.max_message_size {
+                self.partial.remove(&key);
+                return Err(TransportError::PayloadTooLarge);
+            }
+            partial.chunks[index] = Some(body.to_vec());
+            partial.received += 1;
+        }
+
+        if partial.received < count {
+            return Ok(None);
+        }
+
+        let partial = self.partial.remove(&key).expect("entry exists");
+        Ok(Some(partial.chunks.into_iter().flatten().flatten().collect()))
+    }
+
+    /// Drop incomplete messages from a peer
+    pub fn clear_peer(&mut self, address: &PeerAddress) {
+        self.partial.retain(|(peer, _), _| peer != address);
+    }
+
+    /// Number of incomplete messages
+    pub fn pending(&self) -> usize {
+        self.partial.len()
+    }
 }
 
 // ============================================================================
@@ -218,10 +463,19 @@ struct DiscoveredDevice {
 // ============================================================================
 
 /// BLE transport implementation
-pub struct BleTransport {
+///
+/// Radio access goes through a `BleAdapter`; `BleTransport::new` uses
+/// `UnavailableBleAdapter`, so starting it reports `HardwareUnavailable`
+/// until a platform adapter is supplied with `with_adapter`.
+pub struct BleTransport<A: BleAdapter = UnavailableBleAdapter> {
     config: BleTransportConfig,
+    adapter: A,
     state: TransportState,
     connections: HashMap<ConnectionId, ConnectionInfo>,
+    /// Negotiated MTU per connection
+    mtus: HashMap<ConnectionId, u16>,
+    reassembler: BleReassembler,
+    next_message_id: u16,
     discovered_devices: Vec<DiscoveredDevice>,
     events: Vec<TransportEvent>,
     stats: TransportStats,
@@ -231,10 +485,22 @@ pub struct BleTransport {
 
 impl BleTransport {
     pub fn new(config: BleTransportConfig) -> Self {
+        Self::with_adapter(config, UnavailableBleAdapter)
+    }
+}
+
+impl<A: BleAdapter> BleTransport<A> {
+    /// Create a transport driving the given radio adapter
+    pub fn with_adapter(config: BleTransportConfig, adapter: A) -> Self {
+        let reassembler = BleReassembler::new(config.max_message_size);
         Self {
             config,
+            adapter,
             state: TransportState::Stopped,
             connections: HashMap::new(),
+            mtus: HashMap::new(),
+            reassembler,
+            next_message_id: 0,
             discovered_devices: Vec::new(),
             events: Vec::new(),
             stats: TransportStats::default(),
@@ -243,6 +509,11 @@ impl BleTransport {
         }
     }
 
+    /// Get the radio adapter
+    pub fn adapter(&self) -> &A {
+        &self.adapter
+    }
+
     /// Check if operating as central
     pub fn is_central(&self) -> bool {
         self.config.is_central
@@ -258,6 +529,11 @@ impl BleTransport {
         self.config.mtu
     }
 
+    /// Get the MTU negotiated for a connection
+    pub fn negotiated_mtu(&self, connection_id: &ConnectionId) -> Option<u16> {
+        self.mtus.get(connection_id).copied()
+    }
+
     /// Start scanning for BLE devices (central mode)
     pub async fn start_scan(&mut self) -> Result<(), TransportError> {
         if !self.config.is_central {
@@ -269,17 +545,18 @@ impl BleTransport {
             return Err(TransportError::NotRunning);
         }
 
-        self.is_scanning = true;
         self.discovered_devices.clear();
-
-        // In a real implementation, this would start BLE scanning
-        // For now, we just set the flag
+        self.adapter.start_scan(&self.config.service_uuid).await?;
+        self.is_scanning = true;
 
         Ok(())
     }
 
     /// Stop scanning
     pub async fn stop_scan(&mut self) -> Result<(), TransportError> {
+        if self.is_scanning {
+            self.adapter.stop_scan().await;
+        }
         self.is_scanning = false;
         Ok(())
     }
@@ -295,15 +572,19 @@ impl BleTransport {
             return Err(TransportError::NotRunning);
         }
 
+        self.adapter
+            .start_advertising(&self.config.service_uuid, self.config.advertise_name.as_deref())
+            .await?;
         self.is_advertising = true;
 
-        // In a real implementation, this would start BLE advertising
-
         Ok(())
     }
 
     /// Stop advertising
     pub async fn stop_advertising(&mut self) -> Result<(), TransportError> {
+        if self.is_advertising {
+            self.adapter.stop_advertising().await;
+        }
         self.is_advertising = false;
         Ok(())
     }
@@ -314,18 +595,117 @@ impl BleTransport {
     }
 
     /// Get RSSI for a connection
-    pub fn get_rssi(&self, _connection_id: &ConnectionId) -> Option<i8> {
-        // In a real implementation, this would query the BLE stack
-        None
+    pub fn get_rssi(&self, connection_id: &ConnectionId) -> Option<i8> {
+        let info = self.connections.get(connection_id)?;
+        self.adapter.rssi(info.address())
     }
 
     /// Get required permissions for BLE operations
     pub fn required_permissions(&self) -> Vec<&'static str> {
         vec!["bluetooth"]
     }
+
+    /// Find the connection for a peer address
+    fn connection_for(&self, address: &PeerAddress) -> Option<ConnectionId> {
+        self.connections
+            .iter()
+            .find(|(_, info)| info.address() == address)
+            .map(|(id, _)| id.clone())
+    }
+
+    /// Register a connection and queue a Connected event
+    fn register_connection(&mut self, address: PeerAddress, mtu: u16) -> ConnectionId {
+        let mut info = ConnectionInfo::new(address.clone());
+        let conn_id = info.id().clone();
+        info.set_state(ConnectionState::Connected);
+
+        self.connections.insert(conn_id.clone(), info);
+        self.mtus.insert(conn_id.clone(), mtu);
+        self.stats.connections_active = self.connections.len() as u32;
+        self.stats.connections_total += 1;
+
+        self.events.push(TransportEvent::Connected {
+            connection_id: conn_id.clone(),
+            address,
+        });
+
+        conn_id
+    }
+
+    /// Drop local bookkeeping for a connection
+    fn forget_connection(&mut self, connection_id: &ConnectionId) -> Option<ConnectionInfo> {
+        let info = self.connections.remove(connection_id)?;
+        self.mtus.remove(connection_id);
+        self.reassembler.clear_peer(info.address());
+        self.stats.connections_active = self.connections.len() as u32;
+        Some(info)
+    }
+
+    /// Translate one adapter event into transport events
+    fn handle_adapter_event(&mut self, event: BleAdapterEvent) {
+        match event {
+            BleAdapterEvent::Discovered { address, name, rssi } => {
+                let device = match self.discovered_devices.iter_mut().find(|d| d.address == address) {
+                    Some(device) => {
+                        device.name = name.or(device.name.take());
+                        device.rssi = rssi;
+                        device.clone()
+                    }
+                    None => {
+                        let device = DiscoveredDevice { address, name, rssi };
+                        self.discovered_devices.push(device.clone());
+                        device
+                    }
+                };
+                self.events.push(TransportEvent::DeviceDiscovered {
+                    address: device.address,
+                    rssi: device.rssi,
+                    name: device.name,
+                });
+            }
+            BleAdapterEvent::ChunkReceived { address, data } => {
+                // Inbound links (we are the peripheral) are registered on first data
+                let conn_id = match self.connection_for(&address) {
+                    Some(id) => id,
+                    None => self.register_connection(address.clone(), self.config.mtu),
+                };
+
+                match self.reassembler.push(&address, &data) {
+                    Ok(Some(message)) => {
+                        if let Some(info) = self.connections.get_mut(&conn_id) {
+                            info.record_bytes_received(message.len() as u64);
+                        }
+                        self.stats.bytes_received += message.len() as u64;
+                        self.stats.messages_received += 1;
+                        self.events.push(TransportEvent::MessageReceived {
+                            connection_id: conn_id,
+                            data: message,
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        self.stats.errors += 1;
+                        self.events.push(TransportEvent::Error {
+                            connection_id: Some(conn_id),
+                            error,
+                        });
+                    }
+                }
+            }
+            BleAdapterEvent::Disconnected { address, reason } => {
+                if let Some(conn_id) = self.connection_for(&address) {
+                    self.forget_connection(&conn_id);
+                    self.events.push(TransportEvent::Disconnected {
+                        connection_id: conn_id,
+                        reason,
+                    });
+                }
+            }
+        }
+    }
 }
 
-impl Transport for BleTransport {
+impl<A: BleAdapter> Transport for BleTransport<A> {
     async fn start(&mut self) -> Result<(), TransportError> {
         if self.state.is_running() {
             return Err(TransportError::AlreadyRunning);
@@ -333,14 +713,10 @@ impl Transport for BleTransport {
 
         self.state = TransportState::Starting;
 
-        // In a real implementation, this would:
-        // 1. Initialize BLE adapter
-        // 2. Check permissions
-        // 3. Set up GATT server if peripheral
-        // 4. Start discovery if central
-
-        // For now, simulate successful start
-        // On systems without BLE, we'd return HardwareUnavailable
+        if let Err(e) = self.adapter.power_on().await {
+            self.state = TransportState::Stopped;
+            return Err(e);
+        }
 
         self.state = TransportState::Running;
         Ok(())
@@ -350,17 +726,25 @@ impl Transport for BleTransport {
         if !self.state.is_running() && !matches!(self.state, TransportState::Stopped) {
             return Err(TransportError::NotRunning);
         }
+        if matches!(self.state, TransportState::Stopped) {
+            return Ok(());
+        }
 
         self.state = TransportState::Stopping;
 
         // Stop scanning/advertising
-        self.is_scanning = false;
-        self.is_advertising = false;
+        self.stop_scan().await?;
+        self.stop_advertising().await?;
 
         // Disconnect all
-        self.connections.clear();
-        self.stats.connections_active = 0;
+        let conn_ids: Vec<ConnectionId> = self.connections.keys().cloned().collect();
+        for conn_id in conn_ids {
+            if let Some(info) = self.forget_connection(&conn_id) {
+                self.adapter.disconnect(info.address()).await;
+            }
+        }
 
+        self.adapter.power_off().await;
         self.state = TransportState::Stopped;
         Ok(())
     }
@@ -375,34 +759,33 @@ impl Transport for BleTransport {
             return Err(TransportError::InvalidAddress("Expected BLE address".to_string()));
         }
 
+        if self.connection_for(&address).is_some() {
+            return Err(TransportError::AlreadyConnected);
+        }
+
         // Check max connections
         if self.connections.len() >= self.config.base.max_connections as usize {
             return Err(TransportError::MaxConnectionsReached);
         }
 
-        // In a real implementation, this would initiate BLE connection
-        let mut info = ConnectionInfo::new(address.clone());
-        let conn_id = info.id().clone();
-        info.set_state(ConnectionState::Connected);
-
-        self.connections.insert(conn_id.clone(), info);
-        self.stats.connections_active = self.connections.len() as u32;
-        self.stats.connections_total += 1;
+        let negotiated = self
+            .adapter
+            .connect(
+                &address,
+                &self.config.service_uuid,
+                &self.config.characteristic_uuid,
+                self.config.mtu,
+            )
+            .await?;
 
-        self.events.push(TransportEvent::Connected {
-            connection_id: conn_id.clone(),
-            address,
-        });
-
-        Ok(conn_id)
+        Ok(self.register_connection(address, negotiated.min(self.config.mtu)))
     }
 
     async fn disconnect(&mut self, connection_id: &ConnectionId) -> Result<(), TransportError> {
-        if self.connections.remove(connection_id).is_none() {
-            return Err(TransportError::NotConnected);
-        }
+        let info = self.forget_connection(connection_id)
+            .ok_or(TransportError::NotConnected)?;
 
-        self.stats.connections_active = self.connections.len() as u32;
+        self.adapter.disconnect(info.address()).await;
 
         self.events.push(TransportEvent::Disconnected {
             connection_id: connection_id.clone(),
@@ -413,16 +796,31 @@ impl Transport for BleTransport {
     }
 
     async fn send(&mut self, connection_id: &ConnectionId, data: &[u8]) -> Result<usize, TransportError> {
-        let connection = self.connections.get_mut(connection_id)
-            .ok_or(TransportError::NotConnected)?;
+        let address = self.connections.get(connection_id)
+            .ok_or(TransportError::NotConnected)?
+            .address()
+            .clone();
+        let mtu = self.mtus.get(connection_id).copied().unwrap_or(self.config.mtu);
 
-        // Check MTU
-        if data.len() > self.config.mtu as usize {
+        if data.len() > self.config.max_message_size {
             return Err(TransportError::PayloadTooLarge);
         }
 
-        // In a real implementation, this would write to BLE characteristic
-        connection.record_bytes_sent(data.len() as u64);
+        // Payloads larger than one write are split and reassembled by the peer
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+        let fragments = fragment_payload(data, mtu, message_id)?;
+
+        for fragment in &fragments {
+            if let Err(e) = self.adapter.write(&address, fragment).await {
+                self.stats.errors += 1;
+                return Err(TransportError::SendFailed(e.to_string()));
+            }
+        }
+
+        if let Some(connection) = self.connections.get_mut(connection_id) {
+            connection.record_bytes_sent(data.len() as u64);
+        }
         self.stats.bytes_sent += data.len() as u64;
         self.stats.messages_sent += 1;
 
@@ -444,6 +842,9 @@ impl Transport for BleTransport {
     }
 
     async fn poll_events(&mut self) -> Vec<TransportEvent> {
+        for event in self.adapter.poll().await {
+            self.handle_adapter_event(event);
+        }
         std::mem::take(&mut self.events)
     }
 
@@ -452,7 +853,7 @@ impl Transport for BleTransport {
     }
 
     fn local_address(&self) -> Option<PeerAddress> {
-        // In a real implementation, this would return the local BLE address
+        // Platform BLE stacks generally don't expose the local MAC address
         None
     }
 
@@ -0,0 +1,326 @@
+// btleplug BLE Adapter
+// Drives `BleTransport` over the host Bluetooth stack (BlueZ, CoreBluetooth, WinRT) as a GATT central
+// Only built with the `ble` feature
+
+use crate::transport::{BleAdapter, BleAdapterEvent, PeerAddress, TransportError};
+use btleplug::api::{
+    Central, CentralEvent, CharPropFlags, Characteristic, Manager as _, Peripheral as _, ScanFilter, WriteType,
+};
+use btleplug::platform::{Adapter, Manager, Peripheral, PeripheralId};
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// ATT MTU every BLE link supports before negotiation
+pub const DEFAULT_ATT_MTU: u16 = 23;
+
+/// What the central event pump shares with the adapter
+#[derive(Default)]
+struct ScanState {
+    /// Service being scanned for; discoveries of other devices are dropped
+    service: Option<Uuid>,
+    /// Last seen signal strength by MAC address
+    rssi: HashMap<String, i8>,
+}
+
+/// A connected peer's data characteristic and notification pump
+struct BtleLink {
+    peripheral: Peripheral,
+    characteristic: Characteristic,
+    notifications: JoinHandle<()>,
+}
+
+/// `BleAdapter` over btleplug, as a GATT central
+///
+/// btleplug can't advertise, so peripheral mode reports `HardwareUnavailable`. It
+/// doesn't expose the MTU the OS negotiated either, so `connect` reports the smaller
+/// of the requested MTU and `assumed_mtu` (the 23-byte minimum unless raised with
+/// `with_assumed_mtu`). Peers are addressed by MAC, which CoreBluetooth hides.
+pub struct BtleplugAdapter {
+    adapter_index: usize,
+    assumed_mtu: u16,
+    central: Option<Adapter>,
+    central_events: Option<JoinHandle<()>>,
+    scan: Arc<Mutex<ScanState>>,
+    links: HashMap<String, BtleLink>,
+    events_tx: mpsc::UnboundedSender<BleAdapterEvent>,
+    events_rx: mpsc::UnboundedReceiver<BleAdapterEvent>,
+}
+
+impl Default for BtleplugAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BtleplugAdapter {
+    /// Adapter over the host's first Bluetooth controller
+    pub fn new() -> Self {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        Self {
+            adapter_index: 0,
+            assumed_mtu: DEFAULT_ATT_MTU,
+            central: None,
+            central_events: None,
+            scan: Arc::new(Mutex::new(ScanState::default())),
+            links: HashMap::new(),
+            events_tx,
+            events_rx,
+        }
+    }
+
+    /// Use the host's `index`th Bluetooth controller
+    pub fn with_adapter_index(mut self, index: usize) -> Self {
+        self.adapter_index = index;
+        self
+    }
+
+    /// MTU to assume the OS negotiated, for platforms known to raise it
+    pub fn with_assumed_mtu(mut self, mtu: u16) -> Self {
+        self.assumed_mtu = mtu.max(DEFAULT_ATT_MTU);
+        self
+    }
+
+    fn central(&self) -> Result<&Adapter, TransportError> {
+        self.central.as_ref().ok_or(TransportError::NotRunning)
+    }
+
+    /// Find a known peripheral by MAC address
+    async fn peripheral(&self, mac_address: &str) -> Result<Peripheral, TransportError> {
+        let peripherals = self.central()?.peripherals().await.map_err(connect_error)?;
+        peripherals
+            .into_iter()
+            .find(|p| p.address().to_string().eq_ignore_ascii_case(mac_address))
+            .ok_or_else(|| TransportError::ConnectionFailed(format!("{} not discovered", mac_address)))
+    }
+}
+
+impl Drop for BtleplugAdapter {
+    fn drop(&mut self) {
+        if let Some(pump) = self.central_events.take() {
+            pump.abort();
+        }
+        for link in self.links.values() {
+            link.notifications.abort();
+        }
+    }
+}
+
+impl BleAdapter for BtleplugAdapter {
+    async fn power_on(&mut self) -> Result<(), TransportError> {
+        if self.central.is_some() {
+            return Ok(());
+        }
+        let manager = Manager::new().await.map_err(|_| TransportError::HardwareUnavailable)?;
+        let central = manager
+            .adapters()
+            .await
+            .map_err(|_| TransportError::HardwareUnavailable)?
+            .into_iter()
+            .nth(self.adapter_index)
+            .ok_or(TransportError::HardwareUnavailable)?;
+        let events = central.events().await.map_err(|_| TransportError::HardwareUnavailable)?;
+
+        self.central_events = Some(tokio::spawn(pump_central_events(
+            central.clone(),
+            events,
+            self.scan.clone(),
+            self.events_tx.clone(),
+        )));
+        self.central = Some(central);
+        Ok(())
+    }
+
+    async fn power_off(&mut self) {
+        self.stop_scan().await;
+        let addresses: Vec<PeerAddress> = self.links.keys().map(|mac| PeerAddress::ble(mac)).collect();
+        for address in addresses {
+            self.disconnect(&address).await;
+        }
+        if let Some(pump) = self.central_events.take() {
+            pump.abort();
+        }
+        self.central = None;
+    }
+
+    async fn start_scan(&mut self, service_uuid: &str) -> Result<(), TransportError> {
+        let service = parse_uuid(service_uuid)?;
+        self.scan.lock().unwrap().service = Some(service);
+        self.central()?
+            .start_scan(ScanFilter { services: vec![service] })
+            .await
+            .map_err(|e| TransportError::ConnectionFailed(format!("scan failed: {}", e)))
+    }
+
+    async fn stop_scan(&mut self) {
+        self.scan.lock().unwrap().service = None;
+        if let Some(central) = &self.central {
+            let _ = central.stop_scan().await;
+        }
+    }
+
+    /// btleplug has no peripheral role
+    async fn start_advertising(&mut self, _service_uuid: &str, _name: Option<&str>) -> Result<(), TransportError> {
+        Err(TransportError::HardwareUnavailable)
+    }
+
+    async fn stop_advertising(&mut self) {}
+
+    async fn connect(
+        &mut self,
+        address: &PeerAddress,
+        service_uuid: &str,
+        characteristic_uuid: &str,
+        requested_mtu: u16,
+    ) -> Result<u16, TransportError> {
+        let mac_address = mac_address(address)?;
+        let service = parse_uuid(service_uuid)?;
+        let data_uuid = parse_uuid(characteristic_uuid)?;
+        let peripheral = self.peripheral(mac_address).await?;
+
+        if !peripheral.is_connected().await.map_err(connect_error)? {
+            peripheral.connect().await.map_err(connect_error)?;
+        }
+        peripheral.discover_services().await.map_err(connect_error)?;
+        let characteristic = peripheral
+            .characteristics()
+            .into_iter()
+            .find(|c| c.service_uuid == service && c.uuid == data_uuid)
+            .ok_or_else(|| {
+                TransportError::ConnectionFailed(format!("{} has no mesh data characteristic", mac_address))
+            })?;
+        if characteristic.properties.contains(CharPropFlags::NOTIFY) {
+            peripheral.subscribe(&characteristic).await.map_err(connect_error)?;
+        }
+
+        let mut notifications = peripheral.notifications().await.map_err(connect_error)?;
+        let events_tx = self.events_tx.clone();
+        let from = address.clone();
+        let pump = tokio::spawn(async move {
+            while let Some(notification) = notifications.next().await {
+                if notification.uuid != data_uuid {
+                    continue;
+                }
+                let event = BleAdapterEvent::ChunkReceived { address: from.clone(), data: notification.value };
+                if events_tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let link = BtleLink { peripheral, characteristic, notifications: pump };
+        if let Some(old) = self.links.insert(mac_address.to_string(), link) {
+            old.notifications.abort();
+        }
+        Ok(requested_mtu.min(self.assumed_mtu))
+    }
+
+    async fn disconnect(&mut self, address: &PeerAddress) {
+        let Ok(mac_address) = mac_address(address) else {
+            return;
+        };
+        if let Some(link) = self.links.remove(mac_address) {
+            link.notifications.abort();
+            let _ = link.peripheral.disconnect().await;
+        }
+    }
+
+    async fn write(&mut self, address: &PeerAddress, chunk: &[u8]) -> Result<(), TransportError> {
+        let link = self.links.get(mac_address(address)?).ok_or(TransportError::NotConnected)?;
+        let write_type = if link.characteristic.properties.contains(CharPropFlags::WRITE_WITHOUT_RESPONSE) {
+            WriteType::WithoutResponse
+        } else {
+            WriteType::WithResponse
+        };
+        link.peripheral
+            .write(&link.characteristic, chunk, write_type)
+            .await
+            .map_err(|e| TransportError::SendFailed(e.to_string()))
+    }
+
+    async fn poll(&mut self) -> Vec<BleAdapterEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.events_rx.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    fn rssi(&self, address: &PeerAddress) -> Option<i8> {
+        let mac_address = mac_address(address).ok()?;
+        self.scan.lock().unwrap().rssi.get(mac_address).copied()
+    }
+}
+
+/// Turn central events into discoveries of the scanned service and dropped links
+async fn pump_central_events(
+    central: Adapter,
+    mut events: impl futures_util::Stream<Item = CentralEvent> + Unpin,
+    scan: Arc<Mutex<ScanState>>,
+    events_tx: mpsc::UnboundedSender<BleAdapterEvent>,
+) {
+    while let Some(event) = events.next().await {
+        let event = match event {
+            CentralEvent::DeviceDiscovered(id) | CentralEvent::DeviceUpdated(id) => {
+                discovered(&central, &id, &scan).await
+            }
+            CentralEvent::DeviceDisconnected(id) => match central.peripheral(&id).await {
+                Ok(peripheral) => Some(BleAdapterEvent::Disconnected {
+                    address: PeerAddress::ble(&peripheral.address().to_string()),
+                    reason: "link lost".to_string(),
+                }),
+                Err(_) => None,
+            },
+            _ => None,
+        };
+        if let Some(event) = event {
+            if events_tx.send(event).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Record a device's signal strength and, if it offers the scanned service, report it
+async fn discovered(central: &Adapter, id: &PeripheralId, scan: &Mutex<ScanState>) -> Option<BleAdapterEvent> {
+    let peripheral = central.peripheral(id).await.ok()?;
+    let properties = peripheral.properties().await.ok()??;
+    let address = properties.address.to_string().to_uppercase();
+    let rssi = properties.rssi.map(|rssi| rssi.clamp(i8::MIN as i16, i8::MAX as i16) as i8);
+
+    let mut scan = scan.lock().unwrap();
+    if let Some(rssi) = rssi {
+        scan.rssi.insert(address.clone(), rssi);
+    }
+    let service = scan.service?;
+    if !properties.services.contains(&service) {
+        return None;
+    }
+    Some(BleAdapterEvent::Discovered {
+        address: PeerAddress::ble(&address),
+        name: properties.local_name,
+        rssi,
+    })
+}
+
+fn mac_address(address: &PeerAddress) -> Result<&str, TransportError> {
+    match address {
+        PeerAddress::Ble { mac_address } => Ok(mac_address),
+        other => Err(TransportError::InvalidAddress(format!("not a BLE address: {}", other))),
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, TransportError> {
+    Uuid::parse_str(uuid).map_err(|_| TransportError::InvalidConfig(format!("invalid UUID: {}", uuid)))
+}
+
+fn connect_error(error: btleplug::Error) -> TransportError {
+    match error {
+        btleplug::Error::NotConnected => TransportError::NotConnected,
+        btleplug::Error::TimedOut(_) => TransportError::Timeout,
+        other => TransportError::ConnectionFailed(other.to_string()),
+    }
+}
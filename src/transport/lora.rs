@@ -2,11 +2,11 @@
 // Provides Long Range (LoRa) radio transport for Raspberry Pi and embedded systems
 
 use crate::transport::{
-    ConnectionId, ConnectionInfo, PeerAddress,
+    ConnectionId, ConnectionInfo, PeerAddress, TokenBucket,
     Transport, TransportConfig, TransportError, TransportEvent, TransportState, TransportStats,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 // ============================================================================
 // LORA MODULATION PARAMETERS
@@ -192,13 +192,21 @@ impl Default for LoraModulation {
 // LORA MESH HEADER
 // ============================================================================
 
+/// Bytes taken by `LoraMeshHeader` at the start of every frame
+pub const LORA_HEADER_LEN: usize = 7;
+
 /// Mesh routing header for LoRa packets
+///
+/// Wire layout: [source][destination][flags][hop_count][message_id][fragment_index][fragment_total]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoraMeshHeader {
     source: u8,
     destination: u8,
     flags: u8,
     hop_count: u8,
+    message_id: u8,
+    fragment_index: u8,
+    fragment_total: u8,
 }
 
 impl LoraMeshHeader {
@@ -208,16 +216,22 @@ impl LoraMeshHeader {
             destination,
             flags,
             hop_count,
+            message_id: 0,
+            fragment_index: 0,
+            fragment_total: 1,
         }
     }
 
     pub fn broadcast(source: u8) -> Self {
-        Self {
-            source,
-            destination: 0xFF,
-            flags: 0x01, // Broadcast flag
-            hop_count: 0,
-        }
+        Self::new(source, 0xFF, 0x01, 0) // Broadcast flag
+    }
+
+    /// Mark this header as fragment `index` of `total` for `message_id`
+    pub fn with_fragment(mut self, message_id: u8, index: u8, total: u8) -> Self {
+        self.message_id = message_id;
+        self.fragment_index = index;
+        self.fragment_total = total;
+        self
     }
 
     pub fn source(&self) -> u8 {
@@ -240,23 +254,100 @@ impl LoraMeshHeader {
         self.hop_count = self.hop_count.saturating_add(1);
     }
 
-    pub fn to_bytes(&self) -> [u8; 4] {
-        [self.source, self.destination, self.flags, self.hop_count]
+    pub fn message_id(&self) -> u8 {
+        self.message_id
+    }
+
+    pub fn fragment_index(&self) -> u8 {
+        self.fragment_index
+    }
+
+    pub fn fragment_total(&self) -> u8 {
+        self.fragment_total
+    }
+
+    /// Whether the payload is one part of a larger message
+    pub fn is_fragmented(&self) -> bool {
+        self.fragment_total > 1
+    }
+
+    pub fn to_bytes(&self) -> [u8; LORA_HEADER_LEN] {
+        [
+            self.source,
+            self.destination,
+            self.flags,
+            self.hop_count,
+            self.message_id,
+            self.fragment_index,
+            self.fragment_total,
+        ]
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, TransportError> {
-        if bytes.len() < 4 {
+        if bytes.len() < LORA_HEADER_LEN {
             return Err(TransportError::ReceiveFailed("Header too short".to_string()));
         }
+        let (fragment_index, fragment_total) = (bytes[5], bytes[6]);
+        if fragment_total == 0 || fragment_index >= fragment_total {
+            return Err(TransportError::ReceiveFailed(format!(
+                "Bad fragment {}/{}",
+                fragment_index, fragment_total
+            )));
+        }
         Ok(Self {
             source: bytes[0],
             destination: bytes[1],
             flags: bytes[2],
             hop_count: bytes[3],
+            message_id: bytes[4],
+            fragment_index,
+            fragment_total,
         })
     }
 }
 
+// ============================================================================
+// LORA RADIO
+// ============================================================================
+
+/// A frame picked up by the radio
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoraRadioFrame {
+    /// Raw frame: `LoraMeshHeader` followed by the payload
+    pub data: Vec<u8>,
+    pub rssi: i16,
+    pub snr: f32,
+    pub frequency: u32,
+}
+
+/// Radio backend driven by `LoraTransport`.
+///
+/// Implement this over the SPI driver for the module (SX127x, SX126x).
+/// The transport handles headers, duty cycle, fragmentation and reassembly;
+/// the radio only moves raw frames.
+#[allow(async_fn_in_trait)]
+pub trait LoraRadio {
+    /// Transmit one frame (at most `max_payload_size` bytes) and wait for TX done
+    async fn transmit(&mut self, frequency: u32, frame: &[u8]) -> Result<(), TransportError>;
+
+    /// Drain frames received since the last call (non-blocking)
+    async fn receive(&mut self) -> Vec<LoraRadioFrame>;
+}
+
+/// Radio for builds without a LoRa driver: transmissions are discarded and nothing is received
+#[derive(Debug, Clone, Default)]
+pub struct NullLoraRadio;
+
+impl LoraRadio for NullLoraRadio {
+    async fn transmit(&mut self, _frequency: u32, _frame: &[u8]) -> Result<(), TransportError> {
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Vec<LoraRadioFrame> {
+        Vec::new()
+    }
+}
+
 // ============================================================================
 // LORA TRANSPORT CONFIG
 // ============================================================================
@@ -288,6 +379,8 @@ pub struct LoraTransportConfig {
     pub implicit_header: bool,
     /// Duty cycle percentage (for regulatory compliance)
     pub duty_cycle_percent: f32,
+    /// Sliding window the duty cycle is measured over, in milliseconds
+    pub duty_cycle_window_ms: u64,
     /// SPI device path (for Raspberry Pi)
     pub spi_device: String,
     /// Reset GPIO pin
@@ -296,6 +389,12 @@ pub struct LoraTransportConfig {
     pub dio0_pin: Option<u8>,
     /// Low power mode
     pub low_power_mode: bool,
+    /// How long an incomplete fragmented message is kept before it is dropped
+    pub fragment_timeout_ms: u64,
+    /// Relay packets addressed to other devices (and rebroadcast broadcasts)
+    pub forwarding: bool,
+    /// Highest hop count a relayed packet may carry
+    pub max_hops: u8,
 }
 
 impl Default for LoraTransportConfig {
@@ -313,10 +412,14 @@ impl Default for LoraTransportConfig {
             crc_enabled: true,
             implicit_header: false,
             duty_cycle_percent: 1.0,
+            duty_cycle_window_ms: 3_600_000, // ETSI EN 300 220 observation period
             spi_device: "/dev/spidev0.0".to_string(),
             reset_pin: None,
             dio0_pin: None,
             low_power_mode: false,
+            fragment_timeout_ms: 600_000,
+            forwarding: false,
+            max_hops: 3,
         }
     }
 }
@@ -386,6 +489,11 @@ impl LoraTransportConfig {
         self
     }
 
+    pub fn with_duty_cycle_window_ms(mut self, window_ms: u64) -> Self {
+        self.duty_cycle_window_ms = window_ms;
+        self
+    }
+
     pub fn with_spi_device(mut self, device: &str) -> Self {
         self.spi_device = device.to_string();
         self
@@ -405,6 +513,151 @@ impl LoraTransportConfig {
         self.low_power_mode = enabled;
         self
     }
+
+    pub fn with_fragment_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.fragment_timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn with_forwarding(mut self, enabled: bool) -> Self {
+        self.forwarding = enabled;
+        self
+    }
+
+    pub fn with_max_hops(mut self, hops: u8) -> Self {
+        self.max_hops = hops;
+        self
+    }
+}
+
+// ============================================================================
+// DUTY CYCLE
+// ============================================================================
+
+/// Sliding-window duty-cycle accounting
+///
+/// Each transmission's time-on-air is recorded. A frame may go out only if
+/// the airtime within the last `window_ms`, plus its own, stays within
+/// `duty_cycle_percent` of the window. Times are caller-supplied milliseconds.
+#[derive(Debug, Clone)]
+pub struct DutyCycleTracker {
+    duty_cycle_percent: f32,
+    window_ms: u64,
+    /// (transmission end, airtime), oldest first
+    transmissions: VecDeque<(u64, u64)>,
+}
+
+impl DutyCycleTracker {
+    pub fn new(duty_cycle_percent: f32, window_ms: u64) -> Self {
+        Self {
+            duty_cycle_percent,
+            window_ms,
+            transmissions: VecDeque::new(),
+        }
+    }
+
+    /// Airtime allowed per window
+    pub fn budget_ms(&self) -> u64 {
+        let percent = self.duty_cycle_percent.clamp(0.0, 100.0) as f64;
+        (self.window_ms as f64 * percent / 100.0) as u64
+    }
+
+    /// Record a transmission of `airtime_ms` that ended at `end_ms`
+    pub fn record(&mut self, end_ms: u64, airtime_ms: u64) {
+        let window_start = end_ms.saturating_sub(self.window_ms);
+        while matches!(self.transmissions.front(), Some((end, _)) if *end <= window_start) {
+            self.transmissions.pop_front();
+        }
+        self.transmissions.push_back((end_ms, airtime_ms));
+    }
+
+    /// Airtime spent within the window ending at `now_ms`
+    pub fn used_ms(&self, now_ms: u64) -> u64 {
+        self.live(now_ms).map(|(_, airtime)| airtime).sum()
+    }
+
+    /// How long until a frame of `airtime_ms` fits in the budget.
+    /// A frame larger than the whole budget waits for the window to empty.
+    pub fn time_until_available(&self, now_ms: u64, airtime_ms: u64) -> u64 {
+        let budget = self.budget_ms();
+        let mut used = self.used_ms(now_ms);
+        if used + airtime_ms <= budget {
+            return 0;
+        }
+
+        // Transmissions age out oldest first, each at end + window
+        for (end, airtime) in self.live(now_ms) {
+            used -= airtime;
+            if used + airtime_ms <= budget || used == 0 {
+                return (end + self.window_ms).saturating_sub(now_ms);
+            }
+        }
+        0
+    }
+
+    /// Transmissions still inside the window ending at `now_ms`
+    fn live(&self, now_ms: u64) -> impl Iterator<Item = (u64, u64)> + '_ {
+        let window_start = now_ms.saturating_sub(self.window_ms);
+        self.transmissions
+            .iter()
+            .copied()
+            .filter(move |(end, _)| *end > window_start)
+    }
+}
+
+// ============================================================================
+// FRAGMENTATION
+// ============================================================================
+
+/// Split a payload into frames of at most `max_frame_size` bytes, each carrying a copy of `header`
+fn fragment_frames(
+    header: &LoraMeshHeader,
+    data: &[u8],
+    max_frame_size: usize,
+    message_id: u8,
+) -> Result<Vec<Vec<u8>>, TransportError> {
+    let chunk_size = max_frame_size.saturating_sub(LORA_HEADER_LEN);
+    if chunk_size == 0 {
+        return Err(TransportError::PayloadTooLarge);
+    }
+
+    let total = data.len().div_ceil(chunk_size).max(1);
+    if total > u8::MAX as usize {
+        return Err(TransportError::PayloadTooLarge);
+    }
+
+    let chunks = if data.is_empty() {
+        vec![data]
+    } else {
+        data.chunks(chunk_size).collect()
+    };
+
+    Ok(chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let header = header.clone().with_fragment(message_id, index as u8, total as u8);
+            let mut frame = Vec::with_capacity(LORA_HEADER_LEN + chunk.len());
+            frame.extend_from_slice(&header.to_bytes());
+            frame.extend_from_slice(chunk);
+            frame
+        })
+        .collect())
+}
+
+/// Packets remembered for duplicate suppression. Kept below 256 so a sender's
+/// wrapping message ID can't collide with an entry still in the cache.
+const RECENT_PACKET_CAPACITY: usize = 128;
+
+/// Identifies one frame across relays: (source, destination, message_id, fragment_index)
+type PacketKey = (u8, u8, u8, u8);
+
+/// Fragments received so far for one (source, message_id)
+#[derive(Debug)]
+struct PartialMessage {
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+    last_fragment_at: u64,
 }
 
 // ============================================================================
@@ -412,8 +665,14 @@ impl LoraTransportConfig {
 // ============================================================================
 
 /// LoRa transport implementation
-pub struct LoraTransport {
+///
+/// Radio access goes through a `LoraRadio`; `LoraTransport::new` uses
+/// `NullLoraRadio`, which discards transmissions. Payloads larger than one
+/// frame are fragmented, and the fragments are sent as the duty cycle allows.
+/// With `forwarding` enabled the node also relays frames for other devices.
+pub struct LoraTransport<R: LoraRadio = NullLoraRadio> {
     config: LoraTransportConfig,
+    radio: R,
     state: TransportState,
     connections: HashMap<ConnectionId, ConnectionInfo>,
     events: Vec<TransportEvent>,
@@ -423,14 +682,31 @@ pub struct LoraTransport {
     is_sleeping: bool,
     last_rssi: Option<i16>,
     last_snr: Option<f32>,
-    last_tx_time: Option<u64>,
+    duty_cycle: DutyCycleTracker,
+    /// Frames waiting for the duty cycle: (frequency, frame)
+    tx_queue: VecDeque<(u32, Vec<u8>)>,
+    next_message_id: u8,
+    partial: HashMap<(u8, u8), PartialMessage>,
+    recent_packets: VecDeque<PacketKey>,
+    packets_forwarded: u64,
+    /// Send rate limit per destination, when configured
+    throttles: HashMap<PeerAddress, TokenBucket>,
 }
 
 impl LoraTransport {
     pub fn new(config: LoraTransportConfig) -> Self {
+        Self::with_radio(config, NullLoraRadio)
+    }
+}
+
+impl<R: LoraRadio> LoraTransport<R> {
+    /// Create a transport driving `radio`
+    pub fn with_radio(config: LoraTransportConfig, radio: R) -> Self {
         let freq = config.frequency;
+        let duty_cycle = DutyCycleTracker::new(config.duty_cycle_percent, config.duty_cycle_window_ms);
         Self {
             config,
+            radio,
             state: TransportState::Stopped,
             connections: HashMap::new(),
             events: Vec::new(),
@@ -440,10 +716,21 @@ impl LoraTransport {
             is_sleeping: false,
             last_rssi: None,
             last_snr: None,
-            last_tx_time: None,
+            duty_cycle,
+            tx_queue: VecDeque::new(),
+            next_message_id: 0,
+            partial: HashMap::new(),
+            recent_packets: VecDeque::new(),
+            packets_forwarded: 0,
+            throttles: HashMap::new(),
         }
     }
 
+    /// The radio backend
+    pub fn radio(&self) -> &R {
+        &self.radio
+    }
+
     /// Get current frequency
     pub fn current_frequency(&self) -> u32 {
         self.current_frequency
@@ -511,6 +798,11 @@ impl LoraTransport {
     }
 
     /// Send to a specific address
+    ///
+    /// Payloads larger than one frame are split into fragments. The first
+    /// fragment goes out immediately; the rest are sent from `poll_events`
+    /// as the duty cycle allows. Returns `LoraChannelBusy` while an earlier
+    /// message is still being transmitted.
     pub async fn send_to(&mut self, address: &PeerAddress, data: &[u8]) -> Result<usize, TransportError> {
         if !self.state.is_running() {
             return Err(TransportError::NotRunning);
@@ -521,35 +813,211 @@ impl LoraTransport {
             _ => return Err(TransportError::InvalidAddress("Expected LoRa address".to_string())),
         };
 
-        // Check payload size
-        let modulation = LoraModulation::new(
+        let header = LoraMeshHeader::new(self.config.device_id, device_id, 0, 0);
+        let message_id = self.next_message_id;
+        let frames = fragment_frames(&header, data, self.modulation().max_payload_size(), message_id)?;
+
+        // Check duty cycle
+        if !self.tx_queue.is_empty() || self.wait_for_frame_ms(frames[0].len()) > 0 {
+            return Err(TransportError::LoraChannelBusy);
+        }
+        self.throttle(address, data.len()).await;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+
+        self.tx_queue.extend(frames.into_iter().map(|frame| (frequency, frame)));
+        self.stats.messages_sent += 1;
+        self.transmit_pending().await?;
+
+        Ok(data.len())
+    }
+
+    /// Wait out the destination's rate limit, if configured
+    async fn throttle(&mut self, address: &PeerAddress, bytes: usize) {
+        let Some(rate) = self.config.base.rate_limit_bytes_per_sec else {
+            return;
+        };
+        let now = Self::now();
+        let delay = self
+            .throttles
+            .entry(address.clone())
+            .or_insert_with(|| TokenBucket::new(rate, now))
+            .reserve(now, bytes);
+        if !delay.is_zero() {
+            self.stats.throttled_sends += 1;
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Number of fragments still waiting for the duty cycle
+    pub fn pending_fragments(&self) -> usize {
+        self.tx_queue.len()
+    }
+
+    /// Number of frames relayed for other devices
+    pub fn packets_forwarded(&self) -> u64 {
+        self.packets_forwarded
+    }
+
+    /// Number of incoming messages with fragments still missing
+    pub fn pending_reassembly(&self) -> usize {
+        self.partial.len()
+    }
+
+    fn modulation(&self) -> LoraModulation {
+        LoraModulation::new(
             self.config.spreading_factor,
             self.config.bandwidth,
             self.config.coding_rate,
+        )
+    }
+
+    /// Transmit queued frames while the duty cycle allows.
+    /// A radio failure drops the rest of the message.
+    async fn transmit_pending(&mut self) -> Result<(), TransportError> {
+        while let Some((frequency, frame)) = self.tx_queue.front() {
+            if self.wait_for_frame_ms(frame.len()) > 0 {
+                break;
+            }
+
+            if let Err(e) = self.radio.transmit(*frequency, frame).await {
+                self.tx_queue.clear();
+                return Err(e);
+            }
+
+            self.stats.packets_sent += 1;
+            self.stats.bytes_sent += (frame.len() - LORA_HEADER_LEN) as u64;
+            let airtime = self.airtime_ms(frame.len());
+            self.duty_cycle.record(Self::now(), airtime);
+            self.tx_queue.pop_front();
+        }
+        Ok(())
+    }
+
+    fn airtime_ms(&self, frame_len: usize) -> u64 {
+        self.modulation().time_on_air_ms(frame_len) as u64
+    }
+
+    /// Time until a frame of `frame_len` bytes fits in the duty cycle
+    fn wait_for_frame_ms(&self, frame_len: usize) -> u64 {
+        self.duty_cycle.time_until_available(Self::now(), self.airtime_ms(frame_len))
+    }
+
+    /// Handle one received frame: relay it if needed, and emit
+    /// `LoraPacketReceived` once a message for this device is complete
+    fn handle_frame(&mut self, frame: LoraRadioFrame) {
+        let header = match LoraMeshHeader::from_bytes(&frame.data) {
+            Ok(header) => header,
+            Err(_) => return,
+        };
+        if header.source() == self.config.device_id || !self.remember_packet(&header) {
+            return;
+        }
+
+        let for_us = header.destination() == self.config.device_id;
+        if !for_us && self.config.forwarding {
+            self.forward(header.clone(), &frame);
+        }
+        if !for_us && !header.is_broadcast() {
+            return;
+        }
+
+        let body = &frame.data[LORA_HEADER_LEN..];
+        self.last_rssi = Some(frame.rssi);
+        self.last_snr = Some(frame.snr);
+        self.stats.packets_received += 1;
+        self.stats.bytes_received += body.len() as u64;
+
+        let data = if header.is_fragmented() {
+            match self.reassemble(&header, body) {
+                Some(data) => data,
+                None => return,
+            }
+        } else {
+            body.to_vec()
+        };
+
+        self.stats.messages_received += 1;
+        self.events.push(TransportEvent::LoraPacketReceived {
+            data,
+            rssi: frame.rssi,
+            snr: frame.snr,
+            frequency: frame.frequency,
+        });
+    }
+
+    /// Record a packet as seen; false if it was already seen (via another relay)
+    fn remember_packet(&mut self, header: &LoraMeshHeader) -> bool {
+        let key = (
+            header.source(),
+            header.destination(),
+            header.message_id(),
+            header.fragment_index(),
         );
-        if data.len() > modulation.max_payload_size() {
-            return Err(TransportError::PayloadTooLarge);
+        if self.recent_packets.contains(&key) {
+            return false;
         }
+        if self.recent_packets.len() == RECENT_PACKET_CAPACITY {
+            self.recent_packets.pop_front();
+        }
+        self.recent_packets.push_back(key);
+        true
+    }
 
-        // Check duty cycle
-        if !self.can_transmit() {
-            return Err(TransportError::LoraChannelBusy);
+    /// Queue a received frame for rebroadcast with its hop count bumped,
+    /// unless that would exceed `max_hops`
+    fn forward(&mut self, mut header: LoraMeshHeader, frame: &LoraRadioFrame) {
+        header.increment_hop();
+        if header.hop_count() > self.config.max_hops {
+            return;
         }
 
-        // Create header
-        let header = LoraMeshHeader::new(self.config.device_id, device_id, 0, 0);
-        let header_bytes = header.to_bytes();
+        let mut relayed = frame.data.clone();
+        relayed[..LORA_HEADER_LEN].copy_from_slice(&header.to_bytes());
+        self.tx_queue.push_back((frame.frequency, relayed));
+        self.packets_forwarded += 1;
+    }
+
+    /// Store a fragment; returns the full payload once every fragment arrived
+    fn reassemble(&mut self, header: &LoraMeshHeader, body: &[u8]) -> Option<Vec<u8>> {
+        let key = (header.source(), header.message_id());
+        let total = header.fragment_total() as usize;
+        let now = Self::now();
+
+        let partial = self.partial.entry(key).or_insert_with(|| PartialMessage {
+            chunks: vec![None; total],
+            received: 0,
+            last_fragment_at: now,
+        });
+        if partial.chunks.len() != total {
+            // The sender reused the message ID for a new message
+            *partial = PartialMessage {
+                chunks: vec![None; total],
+                received: 0,
+                last_fragment_at: now,
+            };
+        }
 
-        // In a real implementation, this would:
-        // 1. Set frequency if different
-        // 2. Transmit header + data
-        // 3. Wait for TX done
+        let slot = &mut partial.chunks[header.fragment_index() as usize];
+        if slot.is_none() {
+            *slot = Some(body.to_vec());
+            partial.received += 1;
+        }
+        partial.last_fragment_at = now;
 
-        self.stats.packets_sent += 1;
-        self.stats.bytes_sent += data.len() as u64;
-        self.last_tx_time = Some(Self::now());
+        if partial.received < total {
+            return None;
+        }
 
-        Ok(data.len())
+        let partial = self.partial.remove(&key)?;
+        Some(partial.chunks.into_iter().flatten().flatten().collect())
+    }
+
+    /// Drop incomplete messages that stopped receiving fragments
+    fn expire_partial(&mut self) {
+        let now = Self::now();
+        let timeout = self.config.fragment_timeout_ms;
+        self.partial
+            .retain(|_, partial| now.saturating_sub(partial.last_fragment_at) < timeout);
     }
 
     fn now() -> u64 {
@@ -569,15 +1037,21 @@ impl LoraTransport {
     }
 
     /// Time until next transmit is allowed (duty cycle)
+    ///
+    /// Measured against the airtime spent in the last `duty_cycle_window_ms`,
+    /// for a full-size frame at the current modulation.
     pub fn time_until_transmit_ms(&self) -> u64 {
-        if let Some(last_tx) = self.last_tx_time {
-            let now = Self::now();
-            let elapsed = now.saturating_sub(last_tx);
-            let required_wait = (1000.0 / self.config.duty_cycle_percent * 100.0) as u64;
-            required_wait.saturating_sub(elapsed)
-        } else {
-            0
-        }
+        self.wait_for_frame_ms(self.modulation().max_payload_size())
+    }
+
+    /// Airtime spent within the current duty-cycle window
+    pub fn airtime_used_ms(&self) -> u64 {
+        self.duty_cycle.used_ms(Self::now())
+    }
+
+    /// The duty-cycle accounting
+    pub fn duty_cycle(&self) -> &DutyCycleTracker {
+        &self.duty_cycle
     }
 
     /// Check if can transmit now
@@ -602,7 +1076,7 @@ impl LoraTransport {
     }
 }
 
-impl Transport for LoraTransport {
+impl<R: LoraRadio> Transport for LoraTransport<R> {
     async fn start(&mut self) -> Result<(), TransportError> {
         if self.state.is_running() {
             return Err(TransportError::AlreadyRunning);
@@ -629,6 +1103,9 @@ impl Transport for LoraTransport {
         self.is_receiving = false;
         self.is_sleeping = false;
         self.connections.clear();
+        self.tx_queue.clear();
+        self.partial.clear();
+        self.recent_packets.clear();
         self.state = TransportState::Stopped;
 
         Ok(())
@@ -682,6 +1159,15 @@ impl Transport for LoraTransport {
     }
 
     async fn poll_events(&mut self) -> Vec<TransportEvent> {
+        if self.state.is_running() {
+            for frame in self.radio.receive().await {
+                self.handle_frame(frame);
+            }
+            self.expire_partial();
+            if let Err(error) = self.transmit_pending().await {
+                self.events.push(TransportEvent::Error { connection_id: None, error });
+            }
+        }
         std::mem::take(&mut self.events)
     }
 
@@ -706,6 +1192,14 @@ impl Transport for LoraTransport {
     }
 
     fn stats(&self) -> TransportStats {
-        self.stats.clone()
+        let now = Self::now();
+        let mut stats = self.stats.clone();
+        stats.throttle_delay_ms = self
+            .throttles
+            .values()
+            .map(|t| t.current_delay(now).as_millis() as u64)
+            .max()
+            .unwrap_or(0);
+        stats
     }
 }
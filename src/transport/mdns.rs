@@ -0,0 +1,349 @@
+// mDNS Discovery - find TCP peers on the local network without configured addresses
+// Advertises a `_p2pmesh._tcp.local` service (PTR/SRV/TXT) and surfaces other nodes' records.
+// DNS messages are encoded and parsed with hickory-proto, the codec libp2p's mDNS uses.
+
+use crate::ledger::NodeId;
+use crate::transport::{PeerAddress, TransportError, TransportEvent};
+use hickory_proto::op::{Message, MessageType, Query};
+use hickory_proto::rr::rdata::{PTR, SRV, TXT};
+use hickory_proto::rr::{Name, RData, Record, RecordType};
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+
+/// Service type advertised by p2pmesh nodes
+pub const MDNS_SERVICE_TYPE: &str = "_p2pmesh._tcp.local";
+
+/// IPv4 mDNS multicast group
+pub const MDNS_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+
+/// Standard mDNS port
+pub const MDNS_PORT: u16 = 5353;
+
+/// Longest TXT character-string
+const MAX_TXT_ENTRY: usize = 255;
+
+// ============================================================================
+// MDNS CONFIG
+// ============================================================================
+
+/// Configuration for mDNS peer discovery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MdnsConfig {
+    /// Service type to advertise and browse for
+    pub service_type: String,
+    /// UDP port of the multicast group (5353 outside tests)
+    pub port: u16,
+    /// IPv4 address of the interface to join and send on ("0.0.0.0" = system default)
+    pub interface: String,
+    /// Seconds between unsolicited announcements (0 = only at start and when queried)
+    pub announce_interval_secs: u32,
+    /// Lifetime of our records in other nodes' caches
+    pub ttl_secs: u32,
+}
+
+impl Default for MdnsConfig {
+    fn default() -> Self {
+        Self {
+            service_type: MDNS_SERVICE_TYPE.to_string(),
+            port: MDNS_PORT,
+            interface: Ipv4Addr::UNSPECIFIED.to_string(),
+            announce_interval_secs: 60,
+            ttl_secs: 120,
+        }
+    }
+}
+
+impl MdnsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_service_type(mut self, service_type: &str) -> Self {
+        self.service_type = service_type.to_string();
+        self
+    }
+
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn with_interface(mut self, interface: &str) -> Self {
+        self.interface = interface.to_string();
+        self
+    }
+
+    pub fn with_announce_interval_secs(mut self, secs: u32) -> Self {
+        self.announce_interval_secs = secs;
+        self
+    }
+
+    pub fn with_ttl_secs(mut self, secs: u32) -> Self {
+        self.ttl_secs = secs;
+        self
+    }
+}
+
+// ============================================================================
+// ANNOUNCEMENT
+// ============================================================================
+
+/// A node's advertised service record: who it is and which TCP port it listens on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MdnsAnnouncement {
+    node_id: NodeId,
+    did: Option<String>,
+    port: u16,
+}
+
+impl MdnsAnnouncement {
+    pub fn new(node_id: NodeId, port: u16) -> Self {
+        Self { node_id, did: None, port }
+    }
+
+    pub fn with_did(mut self, did: &str) -> Self {
+        self.did = Some(did.to_string());
+        self
+    }
+
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    pub fn did(&self) -> Option<&str> {
+        self.did.as_deref()
+    }
+
+    /// TCP listen port
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// DID if advertised, otherwise the hex node ID
+    pub fn display_name(&self) -> String {
+        self.did.clone().unwrap_or_else(|| hex::encode(self.node_id.as_bytes()))
+    }
+
+    /// Service instance name; a DNS label holds 63 bytes, so only half the node ID
+    pub fn instance_name(&self, service_type: &str) -> String {
+        format!("{}.{}", hex::encode(&self.node_id.as_bytes()[..16]), service_type)
+    }
+
+    /// Unsolicited response carrying our PTR, SRV and TXT records
+    ///
+    /// Fails if `service_type` is not a valid DNS name.
+    pub fn to_packet(&self, service_type: &str, ttl_secs: u32) -> Result<Vec<u8>, TransportError> {
+        let service = dns_name(service_type)?;
+        let instance = dns_name(&self.instance_name(service_type))?;
+        let host = dns_name(&format!("{}.local", hex::encode(&self.node_id.as_bytes()[..16])))?;
+
+        let mut entries = vec![format!("node={}", hex::encode(self.node_id.as_bytes()))];
+        if let Some(did) = &self.did {
+            entries.push(format!("did={}", did));
+        }
+        let txt = TXT::from_bytes(entries.iter().map(|e| &e.as_bytes()[..e.len().min(MAX_TXT_ENTRY)]).collect());
+
+        // SRV and TXT are unique to this instance, so caches should replace what they hold
+        let mut srv = Record::from_rdata(instance.clone(), ttl_secs, RData::SRV(SRV::new(0, 0, self.port, host)));
+        srv.set_mdns_cache_flush(true);
+        let mut txt = Record::from_rdata(instance.clone(), ttl_secs, RData::TXT(txt));
+        txt.set_mdns_cache_flush(true);
+
+        let mut message = Message::new();
+        message.set_message_type(MessageType::Response).set_authoritative(true);
+        message.add_answer(Record::from_rdata(service, ttl_secs, RData::PTR(PTR(instance))));
+        message.add_answer(srv);
+        message.add_answer(txt);
+        encode(&message)
+    }
+}
+
+// ============================================================================
+// PACKETS
+// ============================================================================
+
+/// What a received mDNS packet means for `service_type`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MdnsMessage {
+    /// The packet asks for the service's instances
+    pub queries_service: bool,
+    /// Complete instances of the service (SRV with a `node=` TXT entry)
+    pub announcements: Vec<MdnsAnnouncement>,
+}
+
+impl MdnsMessage {
+    /// Parse a packet, keeping only what concerns `service_type`.
+    /// Returns None for malformed packets.
+    pub fn parse(packet: &[u8], service_type: &str) -> Option<Self> {
+        let service = Name::from_ascii(service_type).ok()?;
+        let message = Message::from_vec(packet).ok()?;
+
+        let queries_service = message.message_type() == MessageType::Query
+            && message
+                .queries()
+                .iter()
+                .any(|q| q.query_type() == RecordType::PTR && q.name().eq_ignore_root(&service));
+
+        // Instance names are matched case-insensitively, so key them lowercased
+        let mut ports: HashMap<String, u16> = HashMap::new();
+        let mut texts: HashMap<String, &TXT> = HashMap::new();
+        let records = message.answers().iter().chain(message.name_servers()).chain(message.additionals());
+        for record in records {
+            let name = record.name();
+            if name.eq_ignore_root(&service) || !service.zone_of(name) {
+                continue;
+            }
+            let key = name.to_lowercase().to_ascii();
+            match record.data() {
+                RData::SRV(srv) => {
+                    ports.insert(key, srv.port());
+                }
+                RData::TXT(txt) => {
+                    texts.insert(key, txt);
+                }
+                _ => {}
+            }
+        }
+
+        let mut announcements = Vec::new();
+        for (name, port) in ports {
+            let entries: Vec<String> = texts
+                .get(&name)
+                .map(|txt| txt.iter().map(|e| String::from_utf8_lossy(e).into_owned()).collect())
+                .unwrap_or_default();
+            let node_id = entries
+                .iter()
+                .find_map(|e| e.strip_prefix("node="))
+                .and_then(|h| hex::decode(h).ok())
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok());
+            let node_id = match node_id {
+                Some(bytes) => NodeId::from_bytes(bytes),
+                None => continue,
+            };
+            let mut announcement = MdnsAnnouncement::new(node_id, port);
+            if let Some(did) = entries.iter().find_map(|e| e.strip_prefix("did=")) {
+                announcement = announcement.with_did(did);
+            }
+            announcements.push(announcement);
+        }
+        Some(Self { queries_service, announcements })
+    }
+}
+
+/// Multicast query asking every node offering `service_type` to announce itself
+///
+/// Fails if `service_type` is not a valid DNS name.
+pub fn query_packet(service_type: &str) -> Result<Vec<u8>, TransportError> {
+    let mut message = Message::new();
+    message.add_query(Query::query(dns_name(service_type)?, RecordType::PTR));
+    encode(&message)
+}
+
+fn dns_name(name: &str) -> Result<Name, TransportError> {
+    Name::from_ascii(name)
+        .map_err(|e| TransportError::InvalidConfig(format!("mDNS name '{}': {}", name, e)))
+}
+
+fn encode(message: &Message) -> Result<Vec<u8>, TransportError> {
+    message
+        .to_vec()
+        .map_err(|e| TransportError::InvalidConfig(format!("mDNS packet: {}", e)))
+}
+
+// ============================================================================
+// DISCOVERY TASK
+// ============================================================================
+
+/// Bind the multicast socket and spawn the announce/browse loop
+///
+/// Announces and queries once at start, answers queries for the service, and
+/// re-announces on the configured interval. Each newly seen (or moved) node is
+/// reported as a `DeviceDiscovered` event addressed at the packet's source IP
+/// and the advertised port.
+pub(super) fn spawn_discovery(
+    config: &MdnsConfig,
+    announcement: MdnsAnnouncement,
+    event_tx: mpsc::Sender<TransportEvent>,
+) -> Result<JoinHandle<()>, TransportError> {
+    let interface: Ipv4Addr = config.interface.parse().map_err(|_| {
+        TransportError::InvalidAddress(format!("mDNS interface '{}' is not an IPv4 address", config.interface))
+    })?;
+    let socket = bind_multicast(interface, config.port)
+        .map_err(|e| TransportError::ConnectionFailed(format!("mDNS socket: {}", e)))?;
+
+    let group = SocketAddr::V4(SocketAddrV4::new(MDNS_GROUP, config.port));
+    let service_type = config.service_type.clone();
+    let announce = announcement.to_packet(&service_type, config.ttl_secs)?;
+    let query = query_packet(&service_type)?;
+    let announce_every = (config.announce_interval_secs > 0)
+        .then(|| Duration::from_secs(config.announce_interval_secs as u64));
+
+    Ok(tokio::spawn(async move {
+        let _ = socket.send_to(&query, group).await;
+        let _ = socket.send_to(&announce, group).await;
+
+        let mut ticker = interval(announce_every.unwrap_or(Duration::from_secs(3600)));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker.tick().await;
+
+        let mut seen: HashMap<NodeId, PeerAddress> = HashMap::new();
+        let mut buf = vec![0u8; 9000];
+        loop {
+            tokio::select! {
+                received = socket.recv_from(&mut buf) => {
+                    let (len, source) = match received {
+                        Ok(received) => received,
+                        Err(_) => continue,
+                    };
+                    let message = match MdnsMessage::parse(&buf[..len], &service_type) {
+                        Some(message) => message,
+                        None => continue,
+                    };
+                    if message.queries_service {
+                        let _ = socket.send_to(&announce, group).await;
+                    }
+                    for peer in message.announcements {
+                        if peer.node_id() == announcement.node_id() {
+                            continue;
+                        }
+                        let address = PeerAddress::tcp(&source.ip().to_string(), peer.port());
+                        if seen.get(peer.node_id()) == Some(&address) {
+                            continue;
+                        }
+                        seen.insert(peer.node_id().clone(), address.clone());
+                        let _ = event_tx.send(TransportEvent::DeviceDiscovered {
+                            address,
+                            rssi: None,
+                            name: Some(peer.display_name()),
+                        }).await;
+                    }
+                }
+                _ = ticker.tick(), if announce_every.is_some() => {
+                    let _ = socket.send_to(&announce, group).await;
+                }
+            }
+        }
+    }))
+}
+
+/// UDP socket on the group's port, shared with other responders on the host
+fn bind_multicast(interface: Ipv4Addr, port: u16) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port).into())?;
+    socket.join_multicast_v4(&MDNS_GROUP, &interface)?;
+    if !interface.is_unspecified() {
+        socket.set_multicast_if_v4(&interface)?;
+    }
+    socket.set_multicast_loop_v4(true)?;
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
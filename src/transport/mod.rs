@@ -1,10 +1,18 @@
 // Transport module - THE WIRE (abstract)
-// Provides abstract transport layer for TCP, BLE, and LoRa communications
+// Provides abstract transport layer for TCP, WebSocket, BLE, and LoRa communications
 
 mod traits;
 mod tcp;
+mod tls;
+mod throttle;
 mod ble;
 mod lora;
+#[cfg(feature = "websocket")]
+mod ws;
+#[cfg(feature = "mdns")]
+mod mdns;
+#[cfg(feature = "ble")]
+mod btle;
 
 pub use traits::{
     // Core trait
@@ -21,15 +29,34 @@ pub use traits::{
     TransportStats,
 };
 
-pub use tcp::{TcpTransport, TcpTransportConfig};
+pub use tcp::{ReconnectPolicy, TcpTransport, TcpTransportConfig};
+
+pub use tls::TlsConfig;
+
+pub use throttle::TokenBucket;
+
+#[cfg(feature = "websocket")]
+pub use ws::{WsTransport, WsTransportConfig, WsUrl};
+
+#[cfg(feature = "mdns")]
+pub use mdns::{
+    MdnsAnnouncement, MdnsConfig, MdnsMessage, query_packet as mdns_query_packet,
+    MDNS_GROUP, MDNS_PORT, MDNS_SERVICE_TYPE,
+};
 
 pub use ble::{
     BleTransport, BleTransportConfig,
     BleService, BleCharacteristic,
+    BleAdapter, BleAdapterEvent, UnavailableBleAdapter,
+    BleReassembler, fragment_payload, ATT_HEADER_LEN, FRAGMENT_HEADER_LEN,
 };
 
+#[cfg(feature = "ble")]
+pub use btle::{BtleplugAdapter, DEFAULT_ATT_MTU};
+
 pub use lora::{
     LoraTransport, LoraTransportConfig,
     LoraModulation, LoraSpreadingFactor, LoraBandwidth, LoraCodingRate,
-    LoraMeshHeader,
+    LoraMeshHeader, LoraRadio, LoraRadioFrame, NullLoraRadio, LORA_HEADER_LEN,
+    DutyCycleTracker,
 };
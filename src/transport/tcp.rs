@@ -1,16 +1,25 @@
 // TCP Transport Implementation
 // Provides TCP/IP network transport for peer-to-peer communication
 
+#[cfg(feature = "mdns")]
+use crate::identity::Did;
+use crate::ledger::NodeId;
+use crate::sync::{Heartbeat, Message};
+#[cfg(feature = "mdns")]
+use crate::transport::mdns::{spawn_discovery, MdnsAnnouncement, MdnsConfig};
 use crate::transport::{
-    ConnectionId, ConnectionInfo, ConnectionState, PeerAddress,
+    ConnectionId, ConnectionInfo, ConnectionState, PeerAddress, TlsConfig, TokenBucket,
     Transport, TransportConfig, TransportError, TransportEvent, TransportState, TransportStats,
 };
+use futures_rustls::{TlsAcceptor, TlsConnector};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
-use tokio::time::{timeout, Duration};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout, Duration};
+use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
 
 // ============================================================================
 // TCP TRANSPORT CONFIG
@@ -29,8 +38,20 @@ pub struct TcpTransportConfig {
     pub reuse_address: bool,
     /// Enable TCP_NODELAY
     pub nodelay: bool,
-    /// TCP keepalive interval in seconds
+    /// Send a heartbeat on connections idle this many seconds (None = no heartbeats)
     pub keepalive_secs: Option<u32>,
+    /// Drop connections that receive nothing for this many seconds
+    /// (None = three keepalive intervals)
+    #[serde(default)]
+    pub heartbeat_timeout_secs: Option<u32>,
+    /// Wrap connections in TLS (None = plaintext)
+    pub tls: Option<TlsConfig>,
+    /// Re-dial dropped outbound connections (None = no reconnect)
+    pub reconnect: Option<ReconnectPolicy>,
+    /// Advertise and browse for peers over mDNS (None = no discovery)
+    #[cfg(feature = "mdns")]
+    #[serde(default)]
+    pub mdns: Option<MdnsConfig>,
 }
 
 impl Default for TcpTransportConfig {
@@ -42,6 +63,11 @@ impl Default for TcpTransportConfig {
             reuse_address: true,
             nodelay: true,
             keepalive_secs: Some(60),
+            heartbeat_timeout_secs: None,
+            tls: None,
+            reconnect: None,
+            #[cfg(feature = "mdns")]
+            mdns: None,
         }
     }
 }
@@ -80,6 +106,96 @@ impl TcpTransportConfig {
         self.keepalive_secs = secs;
         self
     }
+
+    pub fn with_heartbeat_timeout_secs(mut self, secs: u32) -> Self {
+        self.heartbeat_timeout_secs = Some(secs);
+        self
+    }
+
+    /// Silence after which a connection is considered dead, if heartbeats are on
+    pub fn liveness_timeout_secs(&self) -> Option<u32> {
+        let keepalive = self.keepalive_secs?;
+        Some(self.heartbeat_timeout_secs.unwrap_or(keepalive.saturating_mul(3)))
+    }
+
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    #[cfg(feature = "mdns")]
+    pub fn with_mdns(mut self, mdns: MdnsConfig) -> Self {
+        self.mdns = Some(mdns);
+        self
+    }
+}
+
+// ============================================================================
+// RECONNECT POLICY
+// ============================================================================
+
+/// Exponential backoff for re-dialing dropped outbound connections
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconnectPolicy {
+    /// Delay before the first attempt in milliseconds
+    pub initial_delay_ms: u64,
+    /// Upper bound on the delay between attempts in milliseconds
+    pub max_delay_ms: u64,
+    /// Factor applied to the delay after each failed attempt
+    pub multiplier: f64,
+    /// Attempts before giving up (0 = never reconnect)
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: 500,
+            max_delay_ms: 30_000,
+            multiplier: 2.0,
+            max_attempts: 10,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_initial_delay_ms(mut self, ms: u64) -> Self {
+        self.initial_delay_ms = ms;
+        self
+    }
+
+    pub fn with_max_delay_ms(mut self, ms: u64) -> Self {
+        self.max_delay_ms = ms;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts;
+        self
+    }
+
+    /// Delay before the given attempt (1-based), capped at `max_delay_ms`.
+    /// Multipliers below 1.0 are treated as 1.0.
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let factor = self.multiplier.max(1.0).powi(exponent);
+        let delay = (self.initial_delay_ms as f64 * factor).min(self.max_delay_ms as f64);
+        Duration::from_millis(delay as u64)
+    }
 }
 
 // ============================================================================
@@ -89,6 +205,152 @@ impl TcpTransportConfig {
 struct TcpConnection {
     info: ConnectionInfo,
     writer: mpsc::Sender<Vec<u8>>,
+    /// Dialed by us (only these are re-dialed)
+    outbound: bool,
+    /// Send rate limit, when configured
+    throttle: Option<TokenBucket>,
+    /// When data last arrived (ms), for dead peer detection
+    last_received_ms: u64,
+    /// Reader or reconnect task, aborted when the connection is dropped.
+    /// The writer task drains queued data and exits once `writer` is dropped.
+    task: Option<JoinHandle<()>>,
+}
+
+impl Drop for TcpConnection {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Result of a background reconnect task
+enum ReconnectOutcome {
+    Reconnected {
+        connection_id: ConnectionId,
+        stream: BoxedStream,
+    },
+    GaveUp {
+        connection_id: ConnectionId,
+        attempts: u32,
+    },
+}
+
+/// Wall-clock milliseconds for rate limiting and liveness checks
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Byte stream behind a connection (plain TCP or TLS)
+pub(super) trait ConnectionStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> ConnectionStream for T {}
+
+pub(super) type BoxedStream = Box<dyn ConnectionStream>;
+
+// ============================================================================
+// TLS HANDSHAKE
+// ============================================================================
+
+/// Run the server side of a TLS handshake on an accepted socket
+pub(super) async fn accept_tls(
+    acceptor: Option<TlsAcceptor>,
+    stream: TcpStream,
+    handshake_timeout: Duration,
+) -> Result<BoxedStream, String> {
+    let acceptor = acceptor.ok_or_else(|| "no server certificate configured".to_string())?;
+    let tls_stream = timeout(handshake_timeout, acceptor.accept(stream.compat()))
+        .await
+        .map_err(|_| "handshake timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(Box::new(tls_stream.compat()))
+}
+
+/// Open an outbound stream, wrapped in TLS when configured
+pub(super) async fn dial(
+    host: &str,
+    port: u16,
+    tls: Option<&TlsConfig>,
+    connect_timeout: Duration,
+    nodelay: bool,
+) -> Result<BoxedStream, TransportError> {
+    let addr_str = format!("{}:{}", host, port);
+    let stream = timeout(connect_timeout, TcpStream::connect(&addr_str))
+        .await
+        .map_err(|_| TransportError::Timeout)?
+        .map_err(|e| TransportError::ConnectionFailed(e.to_string()))?;
+    stream.set_nodelay(nodelay).ok();
+
+    match tls {
+        Some(tls) => {
+            let connector = TlsConnector::from(tls.client_config()?);
+            let server_name = tls.server_name_for(host)?;
+            let tls_stream = timeout(connect_timeout, connector.connect(server_name, stream.compat()))
+                .await
+                .map_err(|_| TransportError::ConnectionFailed("TLS handshake timed out".to_string()))?
+                .map_err(|e| TransportError::ConnectionFailed(format!("TLS handshake failed: {}", e)))?;
+            Ok(Box::new(tls_stream.compat()))
+        }
+        None => Ok(Box::new(stream)),
+    }
+}
+
+/// Spawn reader and writer tasks for a stream.
+/// Returns the write channel and the reader task handle.
+fn spawn_io(
+    stream: BoxedStream,
+    conn_id: ConnectionId,
+    event_tx: mpsc::Sender<TransportEvent>,
+) -> (mpsc::Sender<Vec<u8>>, JoinHandle<()>) {
+    // Create write channel
+    let (write_tx, mut write_rx) = mpsc::channel::<Vec<u8>>(100);
+
+    // Split stream
+    let (mut reader, mut writer) = tokio::io::split(stream);
+
+    // Spawn reader task
+    let reader_task = tokio::spawn(async move {
+        let mut buf = vec![0u8; 4096];
+        loop {
+            match reader.read(&mut buf).await {
+                Ok(0) => {
+                    // Connection closed
+                    let _ = event_tx.send(TransportEvent::Disconnected {
+                        connection_id: conn_id.clone(),
+                        reason: "Connection closed".to_string(),
+                    }).await;
+                    break;
+                }
+                Ok(n) => {
+                    let _ = event_tx.send(TransportEvent::MessageReceived {
+                        connection_id: conn_id.clone(),
+                        data: buf[..n].to_vec(),
+                    }).await;
+                }
+                Err(e) => {
+                    let _ = event_tx.send(TransportEvent::Disconnected {
+                        connection_id: conn_id.clone(),
+                        reason: e.to_string(),
+                    }).await;
+                    break;
+                }
+            }
+        }
+    });
+
+    // Spawn writer task
+    tokio::spawn(async move {
+        while let Some(data) = write_rx.recv().await {
+            if writer.write_all(&data).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    (write_tx, reader_task)
 }
 
 // ============================================================================
@@ -107,10 +369,19 @@ pub struct TcpTransport {
     incoming_rx: Option<mpsc::Receiver<IncomingConnection>>,
     event_rx: Option<mpsc::Receiver<TransportEvent>>,
     event_tx: Option<mpsc::Sender<TransportEvent>>,
+    reconnect_rx: Option<mpsc::Receiver<ReconnectOutcome>>,
+    reconnect_tx: Option<mpsc::Sender<ReconnectOutcome>>,
+    /// Sender ID carried by our heartbeats
+    node_id: NodeId,
+    /// DID advertised over mDNS alongside the node ID
+    #[cfg(feature = "mdns")]
+    did: Option<Did>,
+    #[cfg(feature = "mdns")]
+    discovery_handle: Option<JoinHandle<()>>,
 }
 
 struct IncomingConnection {
-    stream: TcpStream,
+    stream: BoxedStream,
     address: PeerAddress,
 }
 
@@ -127,82 +398,204 @@ impl TcpTransport {
             incoming_rx: None,
             event_rx: None,
             event_tx: None,
+            reconnect_rx: None,
+            reconnect_tx: None,
+            node_id: NodeId::generate(),
+            #[cfg(feature = "mdns")]
+            did: None,
+            #[cfg(feature = "mdns")]
+            discovery_handle: None,
         }
     }
 
-    async fn setup_connection(&mut self, stream: TcpStream, address: PeerAddress) -> Result<ConnectionId, TransportError> {
+    /// Set the node ID our heartbeats carry (random by default)
+    pub fn set_node_id(&mut self, node_id: NodeId) {
+        self.node_id = node_id;
+    }
+
+    /// Get the node ID our heartbeats carry
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    /// Set the DID our mDNS record advertises (node ID only by default)
+    #[cfg(feature = "mdns")]
+    pub fn set_did(&mut self, did: Did) {
+        self.did = Some(did);
+    }
+
+    async fn setup_connection(
+        &mut self,
+        stream: BoxedStream,
+        address: PeerAddress,
+        outbound: bool,
+    ) -> Result<ConnectionId, TransportError> {
         // Check max connections
         if self.connections.len() >= self.config.base.max_connections as usize {
             return Err(TransportError::MaxConnectionsReached);
         }
 
-        // Configure socket
-        stream.set_nodelay(self.config.nodelay).ok();
-
         let mut info = ConnectionInfo::new(address.clone());
         let conn_id = info.id().clone();
         info.set_state(ConnectionState::Connected);
 
-        // Create write channel
-        let (write_tx, mut write_rx) = mpsc::channel::<Vec<u8>>(100);
+        let event_tx = self.event_tx.clone().unwrap();
+        let (writer, reader_task) = spawn_io(stream, conn_id.clone(), event_tx);
 
-        // Split stream
-        let (mut reader, mut writer) = stream.into_split();
+        let connection = TcpConnection {
+            info,
+            writer,
+            outbound,
+            throttle: self.config.base.rate_limit_bytes_per_sec.map(|rate| TokenBucket::new(rate, now_ms())),
+            last_received_ms: now_ms(),
+            task: Some(reader_task),
+        };
 
-        // Clone event sender
-        let event_tx = self.event_tx.clone().unwrap();
-        let conn_id_read = conn_id.clone();
-        let conn_id_write = conn_id.clone();
+        self.connections.insert(conn_id.clone(), connection);
+        self.stats.connections_active = self.connections.len() as u32;
+        self.stats.connections_total += 1;
 
-        // Spawn reader task
-        tokio::spawn(async move {
-            let mut buf = vec![0u8; 4096];
-            loop {
-                match reader.read(&mut buf).await {
-                    Ok(0) => {
-                        // Connection closed
-                        let _ = event_tx.send(TransportEvent::Disconnected {
-                            connection_id: conn_id_read.clone(),
-                            reason: "Connection closed".to_string(),
-                        }).await;
-                        break;
-                    }
-                    Ok(n) => {
-                        let _ = event_tx.send(TransportEvent::MessageReceived {
-                            connection_id: conn_id_read.clone(),
-                            data: buf[..n].to_vec(),
-                        }).await;
-                    }
-                    Err(e) => {
-                        let _ = event_tx.send(TransportEvent::Disconnected {
-                            connection_id: conn_id_read.clone(),
-                            reason: e.to_string(),
-                        }).await;
-                        break;
-                    }
+        Ok(conn_id)
+    }
+
+    /// Whether a dropped connection should be re-dialed instead of removed
+    fn should_reconnect(&self, connection_id: &ConnectionId) -> bool {
+        let policy_allows = self.config.reconnect.as_ref().is_some_and(|p| p.max_attempts > 0);
+        let connection_allows = self.connections.get(connection_id).is_some_and(|c| {
+            c.outbound && *c.info.state() == ConnectionState::Connected
+        });
+        policy_allows && connection_allows && self.state.is_running()
+    }
+
+    /// Start a background task re-dialing a dropped outbound connection
+    fn start_reconnect(&mut self, connection_id: &ConnectionId) {
+        let (policy, event_tx, reconnect_tx) = match (
+            self.config.reconnect.clone(),
+            self.event_tx.clone(),
+            self.reconnect_tx.clone(),
+        ) {
+            (Some(policy), Some(event_tx), Some(reconnect_tx)) => (policy, event_tx, reconnect_tx),
+            _ => return,
+        };
+        let connection = match self.connections.get_mut(connection_id) {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        let address = connection.info.address().clone();
+        let (host, port) = match &address {
+            PeerAddress::Tcp { host, port } => (host.clone(), *port),
+            _ => return,
+        };
+        let tls = self.config.tls.clone();
+        let connect_timeout = Duration::from_secs(self.config.base.connection_timeout_secs as u64);
+        let nodelay = self.config.nodelay;
+        let id = connection_id.clone();
+
+        let task = tokio::spawn(async move {
+            for attempt in 1..=policy.max_attempts {
+                let delay = policy.delay_for_attempt(attempt);
+                let _ = event_tx.send(TransportEvent::Reconnecting {
+                    connection_id: id.clone(),
+                    address: address.clone(),
+                    attempt,
+                    delay_ms: delay.as_millis() as u64,
+                }).await;
+                sleep(delay).await;
+
+                if let Ok(stream) = dial(&host, port, tls.as_ref(), connect_timeout, nodelay).await {
+                    let _ = reconnect_tx.send(ReconnectOutcome::Reconnected {
+                        connection_id: id,
+                        stream,
+                    }).await;
+                    return;
                 }
             }
+
+            let _ = reconnect_tx.send(ReconnectOutcome::GaveUp {
+                connection_id: id,
+                attempts: policy.max_attempts,
+            }).await;
         });
 
-        // Spawn writer task
-        tokio::spawn(async move {
-            while let Some(data) = write_rx.recv().await {
-                if writer.write_all(&data).await.is_err() {
-                    break;
+        connection.info.set_state(ConnectionState::Reconnecting);
+        connection.task = Some(task);
+    }
+
+    /// Apply a finished reconnect attempt
+    fn finish_reconnect(&mut self, outcome: ReconnectOutcome) {
+        match outcome {
+            ReconnectOutcome::Reconnected { connection_id, stream } => {
+                // Dropped by a user disconnect() while the dial was in flight
+                let event_tx = match self.event_tx.clone() {
+                    Some(event_tx) => event_tx,
+                    None => return,
+                };
+                let connection = match self.connections.get_mut(&connection_id) {
+                    Some(connection) => connection,
+                    None => return,
+                };
+
+                let (writer, reader_task) = spawn_io(stream, connection_id.clone(), event_tx);
+                connection.writer = writer;
+                connection.task = Some(reader_task);
+                connection.last_received_ms = now_ms();
+                connection.info.set_state(ConnectionState::Connected);
+
+                self.events.push(TransportEvent::Reconnected {
+                    connection_id,
+                    address: connection.info.address().clone(),
+                });
+            }
+            ReconnectOutcome::GaveUp { connection_id, attempts } => {
+                if self.connections.remove(&connection_id).is_none() {
+                    return;
                 }
+                self.stats.connections_active = self.connections.len() as u32;
+
+                self.events.push(TransportEvent::Disconnected {
+                    connection_id,
+                    reason: format!("Reconnect failed after {} attempts", attempts),
+                });
             }
-        });
+        }
+    }
 
-        let connection = TcpConnection {
-            info,
-            writer: write_tx,
+    /// Heartbeat idle connections and find ones whose peer has gone silent
+    ///
+    /// A connection with no traffic for `keepalive_secs` gets a `Heartbeat` (version 0,
+    /// so it never prompts a sync). One that has received nothing, heartbeats included,
+    /// for the liveness timeout is returned as dead.
+    fn check_liveness(&mut self) -> Vec<ConnectionId> {
+        let (keepalive_secs, timeout_secs) = match (
+            self.config.keepalive_secs,
+            self.config.liveness_timeout_secs(),
+        ) {
+            (Some(keepalive), Some(timeout)) => (keepalive as u64, timeout as u64),
+            _ => return Vec::new(),
         };
+        let now = now_ms();
+        let heartbeat = Message::Heartbeat(Heartbeat::new(self.node_id.clone(), 0)).to_bytes();
 
-        self.connections.insert(conn_id.clone(), connection);
-        self.stats.connections_active = self.connections.len() as u32;
-        self.stats.connections_total += 1;
+        let mut dead = Vec::new();
+        for (connection_id, connection) in self.connections.iter_mut() {
+            if *connection.info.state() != ConnectionState::Connected {
+                continue;
+            }
+            if now.saturating_sub(connection.last_received_ms) >= timeout_secs * 1000 {
+                dead.push(connection_id.clone());
+                continue;
+            }
 
-        Ok(conn_id)
+            let last_activity = connection.info.last_activity().unwrap_or(connection.info.created_at());
+            let idle_secs = (now / 1000).saturating_sub(last_activity);
+            if idle_secs >= keepalive_secs && connection.writer.try_send(heartbeat.clone()).is_ok() {
+                connection.info.record_bytes_sent(heartbeat.len() as u64);
+                self.stats.bytes_sent += heartbeat.len() as u64;
+                self.stats.heartbeats_sent += 1;
+            }
+        }
+        dead
     }
 }
 
@@ -212,6 +605,13 @@ impl Transport for TcpTransport {
             return Err(TransportError::AlreadyRunning);
         }
 
+        // Build the TLS acceptor up front so bad cert/key paths fail start()
+        let acceptor = match &self.config.tls {
+            Some(tls) if tls.can_accept() => Some(TlsAcceptor::from(tls.server_config()?)),
+            _ => None,
+        };
+        let tls_enabled = self.config.tls.is_some();
+
         self.state = TransportState::Starting;
 
         // Create event channel
@@ -223,6 +623,11 @@ impl Transport for TcpTransport {
         let (incoming_tx, incoming_rx) = mpsc::channel::<IncomingConnection>(100);
         self.incoming_rx = Some(incoming_rx);
 
+        // Create reconnect outcome channel
+        let (reconnect_tx, reconnect_rx) = mpsc::channel::<ReconnectOutcome>(100);
+        self.reconnect_tx = Some(reconnect_tx);
+        self.reconnect_rx = Some(reconnect_rx);
+
         // Bind listener
         let bind_addr = format!("{}:{}", self.config.bind_address, self.config.bind_port);
         let listener = TcpListener::bind(&bind_addr).await.map_err(|e| {
@@ -246,13 +651,55 @@ impl Transport for TcpTransport {
         };
         let _ = event_tx.send(listening_event).await;
 
+        // Advertise the listen port and start browsing for other nodes
+        #[cfg(feature = "mdns")]
+        if let Some(mdns) = &self.config.mdns {
+            let mut announcement = MdnsAnnouncement::new(self.node_id.clone(), local_addr.port());
+            if let Some(did) = &self.did {
+                announcement = announcement.with_did(&did.to_string());
+            }
+            let handle = spawn_discovery(mdns, announcement, event_tx.clone()).inspect_err(|e| {
+                self.state = TransportState::Error(e.to_string());
+            })?;
+            self.discovery_handle = Some(handle);
+        }
+
         // Spawn listener task
+        let nodelay = self.config.nodelay;
+        let handshake_timeout = Duration::from_secs(self.config.base.connection_timeout_secs as u64);
         let handle = tokio::spawn(async move {
             loop {
                 match listener.accept().await {
                     Ok((stream, addr)) => {
+                        stream.set_nodelay(nodelay).ok();
                         let address = PeerAddress::tcp(&addr.ip().to_string(), addr.port());
-                        let _ = incoming_tx.send(IncomingConnection { stream, address }).await;
+
+                        if !tls_enabled {
+                            let stream: BoxedStream = Box::new(stream);
+                            let _ = incoming_tx.send(IncomingConnection { stream, address }).await;
+                            continue;
+                        }
+
+                        // Handshake off the accept loop so a slow peer can't stall it
+                        let acceptor = acceptor.clone();
+                        let incoming_tx = incoming_tx.clone();
+                        let event_tx = event_tx.clone();
+                        tokio::spawn(async move {
+                            match accept_tls(acceptor, stream, handshake_timeout).await {
+                                Ok(stream) => {
+                                    let _ = incoming_tx.send(IncomingConnection { stream, address }).await;
+                                }
+                                Err(reason) => {
+                                    let _ = event_tx.send(TransportEvent::Error {
+                                        connection_id: None,
+                                        error: TransportError::ConnectionFailed(format!(
+                                            "TLS handshake with {} failed: {}",
+                                            address, reason
+                                        )),
+                                    }).await;
+                                }
+                            }
+                        });
                     }
                     Err(_) => break,
                 }
@@ -276,6 +723,10 @@ impl Transport for TcpTransport {
         if let Some(handle) = self.listener_handle.take() {
             handle.abort();
         }
+        #[cfg(feature = "mdns")]
+        if let Some(handle) = self.discovery_handle.take() {
+            handle.abort();
+        }
 
         // Close all connections
         self.connections.clear();
@@ -285,6 +736,8 @@ impl Transport for TcpTransport {
         self.event_tx = None;
         self.event_rx = None;
         self.incoming_rx = None;
+        self.reconnect_tx = None;
+        self.reconnect_rx = None;
         self.local_address = None;
 
         self.state = TransportState::Stopped;
@@ -310,14 +763,9 @@ impl Transport for TcpTransport {
 
         // Connect with timeout
         let connect_timeout = Duration::from_secs(self.config.base.connection_timeout_secs as u64);
-        let addr_str = format!("{}:{}", host, port);
+        let stream = dial(&host, port, self.config.tls.as_ref(), connect_timeout, self.config.nodelay).await?;
 
-        let stream = timeout(connect_timeout, TcpStream::connect(&addr_str))
-            .await
-            .map_err(|_| TransportError::Timeout)?
-            .map_err(|e| TransportError::ConnectionFailed(e.to_string()))?;
-
-        let conn_id = self.setup_connection(stream, address.clone()).await?;
+        let conn_id = self.setup_connection(stream, address.clone(), true).await?;
 
         // Emit connected event
         self.events.push(TransportEvent::Connected {
@@ -346,6 +794,18 @@ impl Transport for TcpTransport {
     async fn send(&mut self, connection_id: &ConnectionId, data: &[u8]) -> Result<usize, TransportError> {
         let connection = self.connections.get_mut(connection_id)
             .ok_or(TransportError::NotConnected)?;
+        if *connection.info.state() == ConnectionState::Reconnecting {
+            return Err(TransportError::NotConnected);
+        }
+
+        // Wait out the rate limit before queueing, so the link never sees the burst
+        if let Some(throttle) = connection.throttle.as_mut() {
+            let delay = throttle.reserve(now_ms(), data.len());
+            if !delay.is_zero() {
+                self.stats.throttled_sends += 1;
+                sleep(delay).await;
+            }
+        }
 
         connection.writer.send(data.to_vec()).await
             .map_err(|_| TransportError::SendFailed("Channel closed".to_string()))?;
@@ -382,7 +842,7 @@ impl Transport for TcpTransport {
 
         // Process incoming connections
         for incoming in incoming_connections {
-            if let Ok(conn_id) = self.setup_connection(incoming.stream, incoming.address.clone()).await {
+            if let Ok(conn_id) = self.setup_connection(incoming.stream, incoming.address.clone(), false).await {
                 self.events.push(TransportEvent::Connected {
                     connection_id: conn_id,
                     address: incoming.address,
@@ -391,22 +851,66 @@ impl Transport for TcpTransport {
         }
 
         // Collect events from channel
+        let mut channel_events = Vec::new();
         if let Some(ref mut rx) = self.event_rx {
             while let Ok(event) = rx.try_recv() {
-                // Handle disconnection events
-                if let TransportEvent::Disconnected { ref connection_id, .. } = event {
-                    self.connections.remove(connection_id);
-                    self.stats.connections_active = self.connections.len() as u32;
+                channel_events.push(event);
+            }
+        }
+
+        // Heartbeats count as traffic, so check liveness after taking in what arrived
+        for event in &channel_events {
+            if let TransportEvent::MessageReceived { connection_id, .. } = event {
+                if let Some(conn) = self.connections.get_mut(connection_id) {
+                    conn.last_received_ms = now_ms();
                 }
-                if let TransportEvent::MessageReceived { ref connection_id, ref data } = event {
-                    if let Some(conn) = self.connections.get_mut(connection_id) {
-                        conn.info.record_bytes_received(data.len() as u64);
-                    }
-                    self.stats.bytes_received += data.len() as u64;
-                    self.stats.messages_received += 1;
+            }
+        }
+        for connection_id in self.check_liveness() {
+            self.stats.peer_timeouts += 1;
+            channel_events.push(TransportEvent::Disconnected {
+                connection_id,
+                reason: "Peer timed out".to_string(),
+            });
+        }
+
+        for event in channel_events {
+            // Handle disconnection events: re-dial under the reconnect policy, or drop
+            if let TransportEvent::Disconnected { ref connection_id, .. } = event {
+                if self.should_reconnect(connection_id) {
+                    self.start_reconnect(connection_id);
+                    continue;
+                }
+                // A stale close from a link that is already being re-dialed
+                if self.connections.get(connection_id).is_some_and(|c| {
+                    *c.info.state() == ConnectionState::Reconnecting
+                }) {
+                    continue;
+                }
+                if self.connections.remove(connection_id).is_none() {
+                    continue;
+                }
+                self.stats.connections_active = self.connections.len() as u32;
+            }
+            if let TransportEvent::MessageReceived { ref connection_id, ref data } = event {
+                if let Some(conn) = self.connections.get_mut(connection_id) {
+                    conn.info.record_bytes_received(data.len() as u64);
                 }
-                self.events.push(event);
+                self.stats.bytes_received += data.len() as u64;
+                self.stats.messages_received += 1;
             }
+            self.events.push(event);
+        }
+
+        // Apply finished reconnect attempts
+        let mut outcomes = Vec::new();
+        if let Some(ref mut rx) = self.reconnect_rx {
+            while let Ok(outcome) = rx.try_recv() {
+                outcomes.push(outcome);
+            }
+        }
+        for outcome in outcomes {
+            self.finish_reconnect(outcome);
         }
 
         std::mem::take(&mut self.events)
@@ -429,6 +933,15 @@ impl Transport for TcpTransport {
     }
 
     fn stats(&self) -> TransportStats {
-        self.stats.clone()
+        let now = now_ms();
+        let mut stats = self.stats.clone();
+        stats.throttle_delay_ms = self
+            .connections
+            .values()
+            .filter_map(|c| c.throttle.as_ref())
+            .map(|t| t.current_delay(now).as_millis() as u64)
+            .max()
+            .unwrap_or(0);
+        stats
     }
 }
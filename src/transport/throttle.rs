@@ -0,0 +1,61 @@
+// Bandwidth Throttling
+// Token bucket used to cap per-connection throughput on slow or metered links
+
+use std::time::Duration;
+
+/// Token-bucket rate limiter
+///
+/// The bucket holds up to one second of bandwidth and refills continuously.
+/// A send takes its bytes up front and may overdraw the bucket; the caller
+/// waits out the debt before handing the data to the link, so bursts are
+/// smoothed instead of flooding it. Times are caller-supplied milliseconds.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    bytes_per_sec: u64,
+    /// Available bytes; negative while a send is waiting for bandwidth
+    tokens: f64,
+    last_refill_ms: u64,
+}
+
+impl TokenBucket {
+    /// Create a full bucket. A rate of 0 is treated as 1 byte per second.
+    pub fn new(bytes_per_sec: u64, now_ms: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1);
+        Self {
+            bytes_per_sec,
+            tokens: bytes_per_sec as f64,
+            last_refill_ms: now_ms,
+        }
+    }
+
+    /// Configured rate in bytes per second
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+
+    /// Take `bytes` from the bucket, returning how long to wait before sending them
+    pub fn reserve(&mut self, now_ms: u64, bytes: usize) -> Duration {
+        self.tokens = self.tokens_at(now_ms);
+        self.last_refill_ms = self.last_refill_ms.max(now_ms);
+        self.tokens -= bytes as f64;
+        self.delay_for(self.tokens)
+    }
+
+    /// How long a send issued at `now_ms` would wait before any bytes go out
+    pub fn current_delay(&self, now_ms: u64) -> Duration {
+        self.delay_for(self.tokens_at(now_ms))
+    }
+
+    fn tokens_at(&self, now_ms: u64) -> f64 {
+        let elapsed_ms = now_ms.saturating_sub(self.last_refill_ms);
+        let refilled = self.tokens + elapsed_ms as f64 * self.bytes_per_sec as f64 / 1000.0;
+        refilled.min(self.bytes_per_sec as f64)
+    }
+
+    fn delay_for(&self, tokens: f64) -> Duration {
+        if tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(-tokens / self.bytes_per_sec as f64)
+    }
+}
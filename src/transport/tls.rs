@@ -0,0 +1,207 @@
+// TLS configuration for stream transports
+// Loads PEM certificates/keys and builds rustls client and server configs
+
+use crate::transport::TransportError;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, ServerConfig, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// ============================================================================
+// TLS CONFIG
+// ============================================================================
+
+/// TLS settings for a transport.
+///
+/// The certificate and key are presented to inbound peers. Outbound
+/// connections verify the peer against `ca_path` or `pinned_cert_path`;
+/// one of the two is required to connect.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM certificate chain presented when accepting connections
+    pub cert_path: Option<PathBuf>,
+    /// PEM private key for `cert_path`
+    pub key_path: Option<PathBuf>,
+    /// PEM CA certificates trusted when connecting
+    pub ca_path: Option<PathBuf>,
+    /// PEM certificate the peer must present exactly (overrides `ca_path`)
+    pub pinned_cert_path: Option<PathBuf>,
+    /// Server name to verify instead of the connection host
+    pub server_name: Option<String>,
+}
+
+impl TlsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Config that accepts connections with the given certificate and key
+    pub fn server(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self::new().with_identity(cert_path, key_path)
+    }
+
+    pub fn with_identity(mut self, cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        self.cert_path = Some(cert_path.into());
+        self.key_path = Some(key_path.into());
+        self
+    }
+
+    pub fn with_ca(mut self, ca_path: impl Into<PathBuf>) -> Self {
+        self.ca_path = Some(ca_path.into());
+        self
+    }
+
+    pub fn with_pinned_cert(mut self, cert_path: impl Into<PathBuf>) -> Self {
+        self.pinned_cert_path = Some(cert_path.into());
+        self
+    }
+
+    pub fn with_server_name(mut self, name: &str) -> Self {
+        self.server_name = Some(name.to_string());
+        self
+    }
+
+    /// Whether this config can accept inbound TLS connections
+    pub fn can_accept(&self) -> bool {
+        self.cert_path.is_some() && self.key_path.is_some()
+    }
+
+    /// Whether this config can verify outbound TLS connections
+    pub fn can_connect(&self) -> bool {
+        self.ca_path.is_some() || self.pinned_cert_path.is_some()
+    }
+
+    /// Build the rustls server config
+    pub(crate) fn server_config(&self) -> Result<Arc<ServerConfig>, TransportError> {
+        let (cert_path, key_path) = match (&self.cert_path, &self.key_path) {
+            (Some(cert), Some(key)) => (cert, key),
+            _ => return Err(TransportError::InvalidConfig("TLS server requires a certificate and key".to_string())),
+        };
+
+        let certs = load_certs(cert_path)?;
+        let key = PrivateKeyDer::from_pem_file(key_path)
+            .map_err(|e| TransportError::InvalidConfig(format!("{}: {}", key_path.display(), e)))?;
+
+        let config = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| TransportError::InvalidConfig(e.to_string()))?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| TransportError::InvalidConfig(e.to_string()))?;
+
+        Ok(Arc::new(config))
+    }
+
+    /// Build the rustls client config
+    pub(crate) fn client_config(&self) -> Result<Arc<ClientConfig>, TransportError> {
+        let builder = ClientConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| TransportError::InvalidConfig(e.to_string()))?;
+
+        let config = if let Some(pinned_path) = &self.pinned_cert_path {
+            let pinned = load_certs(pinned_path)?.remove(0);
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier::new(pinned)))
+                .with_no_client_auth()
+        } else if let Some(ca_path) = &self.ca_path {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(ca_path)? {
+                roots
+                    .add(cert)
+                    .map_err(|e| TransportError::InvalidConfig(format!("{}: {}", ca_path.display(), e)))?;
+            }
+            builder.with_root_certificates(roots).with_no_client_auth()
+        } else {
+            return Err(TransportError::InvalidConfig(
+                "TLS client requires a CA or pinned certificate".to_string(),
+            ));
+        };
+
+        Ok(Arc::new(config))
+    }
+
+    /// Name to verify for a connection to `host`
+    pub(crate) fn server_name_for(&self, host: &str) -> Result<ServerName<'static>, TransportError> {
+        let name = self.server_name.as_deref().unwrap_or(host);
+        ServerName::try_from(name.to_string())
+            .map_err(|_| TransportError::InvalidAddress(format!("Invalid TLS server name: {}", name)))
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TransportError> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| TransportError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+
+    if certs.is_empty() {
+        return Err(TransportError::InvalidConfig(format!("{}: no certificates found", path.display())));
+    }
+    Ok(certs)
+}
+
+// ============================================================================
+// CERTIFICATE PINNING
+// ============================================================================
+
+/// Accepts only a server presenting exactly the pinned end-entity certificate
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    pinned: CertificateDer<'static>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl PinnedCertVerifier {
+    fn new(pinned: CertificateDer<'static>) -> Self {
+        Self { pinned, provider: provider() }
+    }
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if end_entity.as_ref() == self.pinned.as_ref() {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
@@ -4,6 +4,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, SocketAddr};
 use thiserror::Error;
 
 // ============================================================================
@@ -21,6 +22,9 @@ pub struct TransportConfig {
     pub message_timeout_secs: u32,
     /// Buffer size for read/write operations
     pub buffer_size: usize,
+    /// Per-connection send limit in bytes per second (None = unlimited; TCP and LoRa)
+    #[serde(default)]
+    pub rate_limit_bytes_per_sec: Option<u64>,
 }
 
 impl Default for TransportConfig {
@@ -30,6 +34,7 @@ impl Default for TransportConfig {
             connection_timeout_secs: 30,
             message_timeout_secs: 10,
             buffer_size: 4096,
+            rate_limit_bytes_per_sec: None,
         }
     }
 }
@@ -59,11 +64,20 @@ impl TransportConfig {
         self
     }
 
+    /// Cap each connection's send throughput; oversubscribed sends wait for bandwidth
+    pub fn with_rate_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.rate_limit_bytes_per_sec = Some(bytes_per_sec);
+        self
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<(), TransportError> {
         if self.max_connections == 0 {
             return Err(TransportError::InvalidConfig("max_connections cannot be 0".to_string()));
         }
+        if self.rate_limit_bytes_per_sec == Some(0) {
+            return Err(TransportError::InvalidConfig("rate limit cannot be 0".to_string()));
+        }
         Ok(())
     }
 }
@@ -81,6 +95,8 @@ pub enum PeerAddress {
     Ble { mac_address: String },
     /// LoRa device address
     Lora { device_id: u8, frequency: u32 },
+    /// WebSocket URL (`ws://` or `wss://`)
+    Ws { url: String },
 }
 
 impl PeerAddress {
@@ -104,6 +120,27 @@ impl PeerAddress {
         Self::Lora { device_id, frequency }
     }
 
+    /// Create a WebSocket address from a `ws://` or `wss://` URL
+    pub fn ws(url: &str) -> Self {
+        Self::Ws { url: url.to_string() }
+    }
+
+    /// Create a TCP address from a socket address
+    pub fn from_socket_addr(address: SocketAddr) -> Self {
+        Self::tcp(&address.ip().to_string(), address.port())
+    }
+
+    /// The socket address of a TCP address whose host is an IP, if it is one
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp { host, port } => {
+                let ip: IpAddr = host.trim_start_matches('[').trim_end_matches(']').parse().ok()?;
+                Some(SocketAddr::new(ip, *port))
+            }
+            _ => None,
+        }
+    }
+
     /// Create a LoRa broadcast address
     pub fn lora_broadcast(frequency: u32) -> Self {
         Self::Lora {
@@ -127,6 +164,11 @@ impl PeerAddress {
         matches!(self, Self::Lora { .. })
     }
 
+    /// Check if this is a WebSocket address
+    pub fn is_ws(&self) -> bool {
+        matches!(self, Self::Ws { .. })
+    }
+
     /// Check if this is a broadcast address
     pub fn is_broadcast(&self) -> bool {
         match self {
@@ -144,6 +186,7 @@ impl fmt::Display for PeerAddress {
             Self::Lora { device_id, frequency } => {
                 write!(f, "lora://0x{:02X}@{}Hz", device_id, frequency)
             }
+            Self::Ws { url } => write!(f, "{}", url),
         }
     }
 }
@@ -161,6 +204,7 @@ impl PartialEq for PeerAddress {
                 Self::Lora { device_id: d1, frequency: f1 },
                 Self::Lora { device_id: d2, frequency: f2 },
             ) => d1 == d2 && f1 == f2,
+            (Self::Ws { url: u1 }, Self::Ws { url: u2 }) => u1 == u2,
             _ => false,
         }
     }
@@ -185,6 +229,10 @@ impl Hash for PeerAddress {
                 device_id.hash(state);
                 frequency.hash(state);
             }
+            Self::Ws { url } => {
+                3u8.hash(state);
+                url.hash(state);
+            }
         }
     }
 }
@@ -249,6 +297,8 @@ pub enum ConnectionState {
     Connecting,
     Connected,
     Disconnecting,
+    /// Link dropped; re-dialing under a reconnect policy
+    Reconnecting,
 }
 
 impl Default for ConnectionState {
@@ -267,6 +317,9 @@ impl ConnectionState {
             (Self::Connected, Self::Disconnecting) => true,
             (Self::Connected, Self::Disconnected) => true, // Abrupt disconnect
             (Self::Disconnecting, Self::Disconnected) => true,
+            (Self::Connected, Self::Reconnecting) => true,
+            (Self::Reconnecting, Self::Connected) => true,
+            (Self::Reconnecting, Self::Disconnected) => true, // Gave up
             _ => false,
         }
     }
@@ -478,6 +531,20 @@ pub enum TransportEvent {
         reason: String,
     },
 
+    /// Dropped connection is about to be re-dialed
+    Reconnecting {
+        connection_id: ConnectionId,
+        address: PeerAddress,
+        attempt: u32,
+        delay_ms: u64,
+    },
+
+    /// Dropped connection re-established under its original ID
+    Reconnected {
+        connection_id: ConnectionId,
+        address: PeerAddress,
+    },
+
     /// Message received
     MessageReceived {
         connection_id: ConnectionId,
@@ -646,6 +713,14 @@ pub struct TransportStats {
     pub packets_received: u64,
     /// Errors encountered
     pub errors: u64,
+    /// Sends that had to wait for the rate limit
+    pub throttled_sends: u64,
+    /// Longest wait a send would currently face from the rate limit, in milliseconds
+    pub throttle_delay_ms: u64,
+    /// Keepalive heartbeats sent on idle connections (TCP)
+    pub heartbeats_sent: u64,
+    /// Connections dropped because the peer went silent (TCP)
+    pub peer_timeouts: u64,
 }
 
 // ============================================================================
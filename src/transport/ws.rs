@@ -0,0 +1,543 @@
+// WebSocket Transport Implementation
+// Carries each message as one binary WebSocket frame, for peers behind NAT or in browsers
+// Only built with the `websocket` feature
+
+use super::tcp::{accept_tls, dial, BoxedStream};
+use crate::transport::{
+    ConnectionId, ConnectionInfo, ConnectionState, PeerAddress, TlsConfig,
+    Transport, TransportConfig, TransportError, TransportEvent, TransportState, TransportStats,
+};
+use futures_rustls::TlsAcceptor;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+// ============================================================================
+// WS TRANSPORT CONFIG
+// ============================================================================
+
+/// Configuration for WebSocket transport
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsTransportConfig {
+    /// Base transport configuration
+    pub base: TransportConfig,
+    /// Address to bind to
+    pub bind_address: String,
+    /// Port to bind to (0 for random)
+    pub bind_port: u16,
+    /// Enable TCP_NODELAY
+    pub nodelay: bool,
+    /// Certificate for serving `wss://` and trust roots for dialing it (None = `ws://` only)
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for WsTransportConfig {
+    fn default() -> Self {
+        Self {
+            base: TransportConfig::default(),
+            bind_address: "0.0.0.0".to_string(),
+            bind_port: 0,
+            nodelay: true,
+            tls: None,
+        }
+    }
+}
+
+impl WsTransportConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_base_config(mut self, base: TransportConfig) -> Self {
+        self.base = base;
+        self
+    }
+
+    pub fn with_bind_address(mut self, addr: &str) -> Self {
+        self.bind_address = addr.to_string();
+        self
+    }
+
+    pub fn with_bind_port(mut self, port: u16) -> Self {
+        self.bind_port = port;
+        self
+    }
+
+    pub fn with_nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+}
+
+// ============================================================================
+// WS URL
+// ============================================================================
+
+/// A parsed `ws://` or `wss://` URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsUrl {
+    /// `wss://` (TLS) rather than `ws://`
+    pub secure: bool,
+    /// Host name or IP (IPv6 kept in brackets)
+    pub host: String,
+    /// Explicit port, or 80 / 443 by scheme
+    pub port: u16,
+    /// Request path including any query, at least `/`
+    pub path: String,
+}
+
+impl WsUrl {
+    /// Parse a WebSocket URL. Other schemes and user info are rejected.
+    pub fn parse(url: &str) -> Result<Self, TransportError> {
+        let invalid = |reason: &str| TransportError::InvalidAddress(format!("{}: {}", url, reason));
+
+        let (secure, rest) = if let Some(rest) = url.strip_prefix("wss://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("ws://") {
+            (false, rest)
+        } else {
+            return Err(invalid("expected ws:// or wss://"));
+        };
+
+        let split = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(split);
+        if authority.contains('@') {
+            return Err(invalid("user info is not supported"));
+        }
+
+        let (host, port) = match authority.rsplit_once(':') {
+            // A colon inside brackets is part of an IPv6 address, not a port
+            Some((host, port)) if !authority.ends_with(']') => {
+                let port = port.parse::<u16>().map_err(|_| invalid("invalid port"))?;
+                (host, port)
+            }
+            _ => (authority, if secure { 443 } else { 80 }),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+
+        let path = match path {
+            "" => "/".to_string(),
+            p if p.starts_with('/') => p.to_string(),
+            p => format!("/{}", p),
+        };
+
+        Ok(Self { secure, host: host.to_string(), port, path })
+    }
+
+    fn scheme(&self) -> &'static str {
+        if self.secure { "wss" } else { "ws" }
+    }
+}
+
+impl fmt::Display for WsUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}:{}{}", self.scheme(), self.host, self.port, self.path)
+    }
+}
+
+// ============================================================================
+// INTERNAL CONNECTION STATE
+// ============================================================================
+
+struct WsConnection {
+    info: ConnectionInfo,
+    writer: mpsc::Sender<Vec<u8>>,
+    /// Reader task, aborted when the connection is dropped.
+    /// The writer task sends a close frame and exits once `writer` is dropped.
+    task: Option<JoinHandle<()>>,
+}
+
+impl Drop for WsConnection {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+struct IncomingConnection {
+    socket: WebSocketStream<BoxedStream>,
+    address: PeerAddress,
+}
+
+/// Run the server side of the (optional TLS and) WebSocket handshake on an accepted socket
+async fn accept_ws(
+    acceptor: Option<TlsAcceptor>,
+    stream: tokio::net::TcpStream,
+    handshake_timeout: Duration,
+) -> Result<WebSocketStream<BoxedStream>, String> {
+    let stream: BoxedStream = match acceptor {
+        Some(acceptor) => accept_tls(Some(acceptor), stream, handshake_timeout).await?,
+        None => Box::new(stream),
+    };
+    timeout(handshake_timeout, tokio_tungstenite::accept_async(stream))
+        .await
+        .map_err(|_| "WebSocket handshake timed out".to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Spawn reader and writer tasks for a socket.
+/// Returns the write channel and the reader task handle.
+fn spawn_io(
+    socket: WebSocketStream<BoxedStream>,
+    conn_id: ConnectionId,
+    event_tx: mpsc::Sender<TransportEvent>,
+) -> (mpsc::Sender<Vec<u8>>, JoinHandle<()>) {
+    let (write_tx, mut write_rx) = mpsc::channel::<Vec<u8>>(100);
+    let (mut sink, mut stream) = socket.split();
+
+    // Each binary frame is one message; pings are answered by tungstenite
+    let reader_task = tokio::spawn(async move {
+        let reason = loop {
+            match stream.next().await {
+                Some(Ok(Message::Binary(data))) => {
+                    let _ = event_tx.send(TransportEvent::MessageReceived {
+                        connection_id: conn_id.clone(),
+                        data,
+                    }).await;
+                }
+                Some(Ok(Message::Close(_))) | None => break "Connection closed".to_string(),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => break e.to_string(),
+            }
+        };
+        let _ = event_tx.send(TransportEvent::Disconnected {
+            connection_id: conn_id,
+            reason,
+        }).await;
+    });
+
+    tokio::spawn(async move {
+        while let Some(data) = write_rx.recv().await {
+            if sink.send(Message::binary(data)).await.is_err() {
+                return;
+            }
+        }
+        let _ = sink.close().await;
+    });
+
+    (write_tx, reader_task)
+}
+
+// ============================================================================
+// WS TRANSPORT
+// ============================================================================
+
+/// WebSocket transport implementation
+///
+/// Dials `PeerAddress::Ws` URLs and listens for WebSocket upgrades on any path.
+/// `wss://` uses the config's `TlsConfig` on both sides, like TLS over TCP.
+pub struct WsTransport {
+    config: WsTransportConfig,
+    state: TransportState,
+    local_address: Option<PeerAddress>,
+    connections: HashMap<ConnectionId, WsConnection>,
+    events: Vec<TransportEvent>,
+    stats: TransportStats,
+    listener_handle: Option<JoinHandle<()>>,
+    incoming_rx: Option<mpsc::Receiver<IncomingConnection>>,
+    event_rx: Option<mpsc::Receiver<TransportEvent>>,
+    event_tx: Option<mpsc::Sender<TransportEvent>>,
+}
+
+impl WsTransport {
+    pub fn new(config: WsTransportConfig) -> Self {
+        Self {
+            config,
+            state: TransportState::Stopped,
+            local_address: None,
+            connections: HashMap::new(),
+            events: Vec::new(),
+            stats: TransportStats::default(),
+            listener_handle: None,
+            incoming_rx: None,
+            event_rx: None,
+            event_tx: None,
+        }
+    }
+
+    fn setup_connection(
+        &mut self,
+        socket: WebSocketStream<BoxedStream>,
+        address: PeerAddress,
+    ) -> Result<ConnectionId, TransportError> {
+        if self.connections.len() >= self.config.base.max_connections as usize {
+            return Err(TransportError::MaxConnectionsReached);
+        }
+
+        let mut info = ConnectionInfo::new(address);
+        let conn_id = info.id().clone();
+        info.set_state(ConnectionState::Connected);
+
+        let event_tx = self.event_tx.clone().ok_or(TransportError::NotRunning)?;
+        let (writer, reader_task) = spawn_io(socket, conn_id.clone(), event_tx);
+
+        self.connections.insert(conn_id.clone(), WsConnection {
+            info,
+            writer,
+            task: Some(reader_task),
+        });
+        self.stats.connections_active = self.connections.len() as u32;
+        self.stats.connections_total += 1;
+
+        Ok(conn_id)
+    }
+}
+
+impl Transport for WsTransport {
+    async fn start(&mut self) -> Result<(), TransportError> {
+        if self.state.is_running() {
+            return Err(TransportError::AlreadyRunning);
+        }
+
+        // Build the TLS acceptor up front so bad cert/key paths fail start()
+        let acceptor = match &self.config.tls {
+            Some(tls) if tls.can_accept() => Some(TlsAcceptor::from(tls.server_config()?)),
+            _ => None,
+        };
+
+        self.state = TransportState::Starting;
+
+        let (event_tx, event_rx) = mpsc::channel::<TransportEvent>(1000);
+        self.event_tx = Some(event_tx.clone());
+        self.event_rx = Some(event_rx);
+
+        let (incoming_tx, incoming_rx) = mpsc::channel::<IncomingConnection>(100);
+        self.incoming_rx = Some(incoming_rx);
+
+        let bind_addr = format!("{}:{}", self.config.bind_address, self.config.bind_port);
+        let listener = TcpListener::bind(&bind_addr).await.map_err(|e| {
+            self.state = TransportState::Error(e.to_string());
+            TransportError::ConnectionFailed(e.to_string())
+        })?;
+        let local_addr = listener.local_addr().map_err(|e| {
+            TransportError::ConnectionFailed(e.to_string())
+        })?;
+
+        let scheme = if acceptor.is_some() { "wss" } else { "ws" };
+        let local_address = PeerAddress::ws(&format!("{}://{}/", scheme, local_addr));
+        self.local_address = Some(local_address.clone());
+        let _ = event_tx.send(TransportEvent::Listening { address: local_address }).await;
+
+        // Handshakes run off the accept loop so a slow peer can't stall it
+        let nodelay = self.config.nodelay;
+        let handshake_timeout = Duration::from_secs(self.config.base.connection_timeout_secs as u64);
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, addr)) = listener.accept().await {
+                stream.set_nodelay(nodelay).ok();
+                let address = PeerAddress::ws(&format!("{}://{}/", scheme, addr));
+
+                let acceptor = acceptor.clone();
+                let incoming_tx = incoming_tx.clone();
+                let event_tx = event_tx.clone();
+                tokio::spawn(async move {
+                    match accept_ws(acceptor, stream, handshake_timeout).await {
+                        Ok(socket) => {
+                            let _ = incoming_tx.send(IncomingConnection { socket, address }).await;
+                        }
+                        Err(reason) => {
+                            let _ = event_tx.send(TransportEvent::Error {
+                                connection_id: None,
+                                error: TransportError::ConnectionFailed(format!(
+                                    "WebSocket handshake with {} failed: {}",
+                                    address, reason
+                                )),
+                            }).await;
+                        }
+                    }
+                });
+            }
+        });
+
+        self.listener_handle = Some(handle);
+        self.state = TransportState::Running;
+
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<(), TransportError> {
+        if !self.state.is_running() && !matches!(self.state, TransportState::Stopped) {
+            return Err(TransportError::NotRunning);
+        }
+
+        self.state = TransportState::Stopping;
+
+        if let Some(handle) = self.listener_handle.take() {
+            handle.abort();
+        }
+
+        self.connections.clear();
+        self.stats.connections_active = 0;
+
+        self.event_tx = None;
+        self.event_rx = None;
+        self.incoming_rx = None;
+        self.local_address = None;
+
+        self.state = TransportState::Stopped;
+
+        Ok(())
+    }
+
+    async fn connect(&mut self, address: PeerAddress) -> Result<ConnectionId, TransportError> {
+        if !self.state.is_running() {
+            return Err(TransportError::NotRunning);
+        }
+
+        if self.connections.len() >= self.config.base.max_connections as usize {
+            return Err(TransportError::MaxConnectionsReached);
+        }
+
+        let url = match &address {
+            PeerAddress::Ws { url } => url.clone(),
+            _ => return Err(TransportError::InvalidAddress("Expected WebSocket address".to_string())),
+        };
+        let parsed = WsUrl::parse(&url)?;
+
+        let tls = match (parsed.secure, &self.config.tls) {
+            (true, Some(tls)) => Some(tls),
+            (true, None) => {
+                return Err(TransportError::InvalidConfig("wss:// requires a TLS config".to_string()))
+            }
+            (false, _) => None,
+        };
+
+        let connect_timeout = Duration::from_secs(self.config.base.connection_timeout_secs as u64);
+        let stream = dial(&parsed.host, parsed.port, tls, connect_timeout, self.config.nodelay).await?;
+        let (socket, _) = timeout(connect_timeout, tokio_tungstenite::client_async(url, stream))
+            .await
+            .map_err(|_| TransportError::Timeout)?
+            .map_err(|e| TransportError::ConnectionFailed(format!("WebSocket handshake failed: {}", e)))?;
+
+        let conn_id = self.setup_connection(socket, address.clone())?;
+
+        self.events.push(TransportEvent::Connected {
+            connection_id: conn_id.clone(),
+            address,
+        });
+
+        Ok(conn_id)
+    }
+
+    async fn disconnect(&mut self, connection_id: &ConnectionId) -> Result<(), TransportError> {
+        if self.connections.remove(connection_id).is_none() {
+            return Err(TransportError::NotConnected);
+        }
+
+        self.stats.connections_active = self.connections.len() as u32;
+
+        self.events.push(TransportEvent::Disconnected {
+            connection_id: connection_id.clone(),
+            reason: "Disconnected by local".to_string(),
+        });
+
+        Ok(())
+    }
+
+    async fn send(&mut self, connection_id: &ConnectionId, data: &[u8]) -> Result<usize, TransportError> {
+        let connection = self.connections.get_mut(connection_id)
+            .ok_or(TransportError::NotConnected)?;
+
+        connection.writer.send(data.to_vec()).await
+            .map_err(|_| TransportError::SendFailed("Channel closed".to_string()))?;
+
+        connection.info.record_bytes_sent(data.len() as u64);
+        self.stats.bytes_sent += data.len() as u64;
+        self.stats.messages_sent += 1;
+
+        Ok(data.len())
+    }
+
+    async fn broadcast(&mut self, data: &[u8]) -> Result<u32, TransportError> {
+        let mut count = 0u32;
+
+        let conn_ids: Vec<ConnectionId> = self.connections.keys().cloned().collect();
+        for conn_id in conn_ids {
+            if self.send(&conn_id, data).await.is_ok() {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn poll_events(&mut self) -> Vec<TransportEvent> {
+        // Collect incoming connections first
+        let mut incoming_connections = Vec::new();
+        if let Some(ref mut rx) = self.incoming_rx {
+            while let Ok(incoming) = rx.try_recv() {
+                incoming_connections.push(incoming);
+            }
+        }
+
+        for incoming in incoming_connections {
+            if let Ok(conn_id) = self.setup_connection(incoming.socket, incoming.address.clone()) {
+                self.events.push(TransportEvent::Connected {
+                    connection_id: conn_id,
+                    address: incoming.address,
+                });
+            }
+        }
+
+        let mut channel_events = Vec::new();
+        if let Some(ref mut rx) = self.event_rx {
+            while let Ok(event) = rx.try_recv() {
+                channel_events.push(event);
+            }
+        }
+
+        for event in channel_events {
+            if let TransportEvent::Disconnected { ref connection_id, .. } = event {
+                // Already removed by a local disconnect()
+                if self.connections.remove(connection_id).is_none() {
+                    continue;
+                }
+                self.stats.connections_active = self.connections.len() as u32;
+            }
+            if let TransportEvent::MessageReceived { ref connection_id, ref data } = event {
+                if let Some(conn) = self.connections.get_mut(connection_id) {
+                    conn.info.record_bytes_received(data.len() as u64);
+                }
+                self.stats.bytes_received += data.len() as u64;
+                self.stats.messages_received += 1;
+            }
+            self.events.push(event);
+        }
+
+        std::mem::take(&mut self.events)
+    }
+
+    fn state(&self) -> &TransportState {
+        &self.state
+    }
+
+    fn local_address(&self) -> Option<PeerAddress> {
+        self.local_address.clone()
+    }
+
+    fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    fn connection_info(&self, connection_id: &ConnectionId) -> Option<&ConnectionInfo> {
+        self.connections.get(connection_id).map(|c| &c.info)
+    }
+
+    fn stats(&self) -> TransportStats {
+        self.stats.clone()
+    }
+}
@@ -1,12 +1,21 @@
 // Balance tracking and Vault implementation
 
-use crate::identity::{Did, PublicKey};
-use crate::iou::{IOUId, IOUValidator, SignedIOU, ValidationError};
+use crate::identity::{Did, Keypair, PublicKey};
+use crate::iou::{
+    IOUBuilder, IOUError, IOUId, IOUValidator, PaymentRequestId, SignedCancellation, SignedIOU,
+    SignedRejection, ValidationError, ValidationPolicy,
+};
+use crate::ledger::ConflictDetector;
+use crate::storage::{seal, unseal_within, KdfParams, MeshStore, SealError};
+use crate::vault::export::{self, TransactionExport};
+use crate::vault::history::{BalancePoint, Statement, TransactionIndex, TransactionPage, TransactionQuery, TransactionSource};
+use crate::vault::persistent::{PersistentVault, VaultChange};
 use crate::vault::spending::{SpentOutput, SpentOutputSet};
-use crate::vault::utxo::{LockInfo, UTXOId, UTXOSet, UTXO};
+use crate::vault::utxo::{CoinSelectionStrategy, LockInfo, UTXOId, UTXOSet, UTXOType, UTXO};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
+use zeroize::Zeroizing;
 
 /// Errors that can occur during vault operations
 #[derive(Error, Debug)]
@@ -41,6 +50,9 @@ pub enum VaultError {
     #[error("Reservation not found")]
     ReservationNotFound,
 
+    #[error("IOU amount {requested} exceeds reserved amount {reserved}")]
+    ReservationExceeded { reserved: u64, requested: u64 },
+
     #[error("Invalid amount")]
     InvalidAmount,
 
@@ -49,6 +61,45 @@ pub enum VaultError {
 
     #[error("State export/import error: {0}")]
     StateError(String),
+
+    #[error("Unknown IOU: not sent from this vault")]
+    UnknownIOU,
+
+    #[error("Nothing to consolidate: need at least 2 unlocked UTXOs")]
+    NothingToConsolidate,
+
+    #[error("Failed to build IOU: {0}")]
+    IOUBuildFailed(#[from] IOUError),
+
+    #[error("Credit limit exceeded for {sender}: limit {limit}, outstanding {outstanding}")]
+    CreditLimitExceeded { sender: Did, limit: u64, outstanding: u64 },
+
+    #[error("Unknown IOU: not received by this vault")]
+    UnknownReceivedIOU,
+
+    #[error("IOU already settled: it can no longer be cancelled")]
+    AlreadySettled,
+
+    #[error("Wrong passphrase or tampered encrypted vault")]
+    WrongPassphrase,
+
+    #[error("IOU not held: no hashlocked IOU with this ID is awaiting its preimage")]
+    NotHeld,
+
+    #[error("Wrong preimage: it does not hash to the IOU's hashlock")]
+    WrongPreimage,
+
+    #[error("Hashlock expired: the preimage came too late to release the IOU")]
+    HashlockExpired,
+
+    #[error("IOU not hashlocked: only hashlocked IOUs can be reclaimed")]
+    NotHashlocked,
+
+    #[error("Hashlock still active: the IOU can't be reclaimed until it expires")]
+    HashlockActive,
+
+    #[error("Conflicting spend: UTXO {utxo_id:?} was already spent by IOU {spent_by:?}")]
+    ConflictingSpend { utxo_id: UTXOId, spent_by: IOUId },
 }
 
 /// Transaction record for history tracking
@@ -77,13 +128,35 @@ impl TransactionRecord {
 pub enum TransactionDirection {
     Received,
     Sent,
+    /// A sent IOU returned after the recipient rejected it
+    Refunded,
+    /// A sent IOU its sender withdrew before it was accepted
+    Cancelled,
+    /// A received IOU reversed after it lost a double-spend conflict
+    Invalidated,
+    /// A sent hashlocked IOU taken back after it expired unreleased
+    Reclaimed,
 }
 
-/// Balance reservation for pending transactions
+/// Identifies a reservation made with [`Vault::reserve_for_amount`]
+pub type ReservationId = u64;
+
+/// How long a reservation holds its UTXOs unless changed with `set_reservation_timeout`
+pub const DEFAULT_RESERVATION_TIMEOUT_MS: u64 = 5 * 60 * 1000;
+
+/// UTXOs below this amount count as dust unless changed with `set_dust_threshold`
+/// (0 disables dust handling)
+pub const DEFAULT_DUST_THRESHOLD: u64 = 0;
+
+/// UTXOs held for a pending transaction
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct Reservation {
-    id: u64,
+    id: ReservationId,
     amount: u64,
+    /// Locked UTXOs covering `amount`
+    utxo_ids: Vec<UTXOId>,
+    /// When the hold lapses
+    lock: LockInfo,
 }
 
 /// Vault state for export/import
@@ -96,6 +169,22 @@ pub struct VaultState {
     transactions: Vec<TransactionRecord>,
 }
 
+/// Vault fields outside the keyed collections, persisted as one record by `PersistentVault`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct VaultMeta {
+    owner: PublicKey,
+    reservations: HashMap<ReservationId, Reservation>,
+    next_reservation_id: ReservationId,
+    reservation_timeout_ms: u64,
+    lock_timeouts: HashMap<UTXOId, LockInfo>,
+    coin_selection: CoinSelectionStrategy,
+    last_utxo_sequence: u64,
+    credit_limits: HashMap<Did, u64>,
+    dust_threshold: u64,
+    overdrawn: u64,
+    held_ious: HashSet<IOUId>,
+}
+
 /// Memory statistics for the vault
 #[derive(Clone, Debug)]
 pub struct MemoryStats {
@@ -126,12 +215,34 @@ pub struct Vault {
     processed_ious: HashMap<IOUId, u64>,
     /// Transaction history
     transactions: Vec<TransactionRecord>,
+    /// Time and counterparty indexes over `transactions`
+    transaction_index: TransactionIndex,
     /// Active reservations
-    reservations: HashMap<u64, Reservation>,
+    reservations: HashMap<ReservationId, Reservation>,
     /// Next reservation ID
-    next_reservation_id: u64,
+    next_reservation_id: ReservationId,
+    /// How long new reservations hold their UTXOs
+    reservation_timeout_ms: u64,
     /// Lock timeout tracking: UTXO ID -> LockInfo
     lock_timeouts: HashMap<UTXOId, LockInfo>,
+    /// Strategy used when selecting UTXOs for outgoing payments
+    coin_selection: CoinSelectionStrategy,
+    /// Sender DID -> cap on unsettled value received from them
+    credit_limits: HashMap<Did, u64>,
+    /// Received IOUs their sender has settled
+    settled_ious: HashSet<IOUId>,
+    /// UTXOs below this amount are dust: spent last and swept by `sweep_dust`
+    dust_threshold: u64,
+    /// Value of invalidated IOUs that was already spent and could not be reversed
+    overdrawn: u64,
+    /// Received hashlocked IOUs waiting for their preimage before they become UTXOs
+    held_ious: HashSet<IOUId>,
+    /// Extra rules for received IOUs; deployment config, so not persisted
+    #[serde(skip)]
+    validation_policy: ValidationPolicy,
+    /// Changes not yet written by a `PersistentVault` (None when not attached)
+    #[serde(skip)]
+    journal: Option<Vec<VaultChange>>,
 }
 
 impl Vault {
@@ -143,17 +254,48 @@ impl Vault {
             spent_outputs: SpentOutputSet::new(),
             processed_ious: HashMap::new(),
             transactions: Vec::new(),
+            transaction_index: TransactionIndex::default(),
             reservations: HashMap::new(),
             next_reservation_id: 1,
+            reservation_timeout_ms: DEFAULT_RESERVATION_TIMEOUT_MS,
             lock_timeouts: HashMap::new(),
+            coin_selection: CoinSelectionStrategy::default(),
+            credit_limits: HashMap::new(),
+            settled_ious: HashSet::new(),
+            dust_threshold: DEFAULT_DUST_THRESHOLD,
+            overdrawn: 0,
+            held_ious: HashSet::new(),
+            validation_policy: ValidationPolicy::default(),
+            journal: None,
         }
     }
 
+    /// Create an empty vault that checks received IOUs against `policy`
+    pub fn with_policy(owner: PublicKey, policy: ValidationPolicy) -> Self {
+        let mut vault = Self::new(owner);
+        vault.validation_policy = policy;
+        vault
+    }
+
     /// Get the owner of this vault
     pub fn owner(&self) -> &PublicKey {
         &self.owner
     }
 
+    fn owner_did(&self) -> Did {
+        Did::from_public_key(&self.owner)
+    }
+
+    /// Value a received transaction credited to this vault (its output, for multi-output IOUs)
+    fn received_amount(&self, record: &TransactionRecord) -> u64 {
+        let iou = record.iou.iou();
+        if iou.is_multi_output() {
+            iou.amount_for(&self.owner_did())
+        } else {
+            iou.amount()
+        }
+    }
+
     // ========================================================================
     // BALANCE QUERIES
     // ========================================================================
@@ -163,10 +305,9 @@ impl Vault {
         self.utxos.total_value()
     }
 
-    /// Get the available balance (excluding locked UTXOs and reservations)
+    /// Get the available balance (excluding locked UTXOs, which includes reserved ones)
     pub fn available_balance(&self) -> u64 {
-        let reserved: u64 = self.reservations.values().map(|r| r.amount).sum();
-        self.utxos.unlocked_value().saturating_sub(reserved)
+        self.utxos.unlocked_value()
     }
 
     /// Check if the vault can afford a specific amount
@@ -180,10 +321,77 @@ impl Vault {
             .iter()
             .filter(|t| t.direction == TransactionDirection::Received)
             .filter(|t| t.iou.iou().sender() == sender)
-            .map(|t| t.iou.iou().amount())
+            .map(|t| self.received_amount(t))
             .sum()
     }
 
+    // ========================================================================
+    // CREDIT LIMITS
+    // ========================================================================
+
+    /// Cap the unsettled value this vault accepts from `sender`.
+    /// `receive_iou` refuses IOUs that would push the sender past the limit.
+    pub fn set_credit_limit(&mut self, sender: Did, limit: u64) {
+        self.credit_limits.insert(sender, limit);
+    }
+
+    /// Get the credit limit for a sender, if one is set
+    pub fn credit_limit(&self, sender: &Did) -> Option<u64> {
+        self.credit_limits.get(sender).copied()
+    }
+
+    /// Remove a sender's credit limit, returning it
+    pub fn remove_credit_limit(&mut self, sender: &Did) -> Option<u64> {
+        self.credit_limits.remove(sender)
+    }
+
+    /// Value received from `sender` that has not been marked settled
+    pub fn outstanding_from_sender(&self, sender: &Did) -> u64 {
+        self.transaction_index
+            .with(sender)
+            .map(|position| &self.transactions[position])
+            .filter(|t| t.direction == TransactionDirection::Received)
+            .filter(|t| !self.settled_ious.contains(&t.iou.id()))
+            .fold(0u64, |total, t| total.saturating_add(self.received_amount(t)))
+    }
+
+    /// Mark a received IOU as settled so it no longer counts against its sender's limit.
+    /// Marking an already settled IOU is a no-op.
+    pub fn mark_settled(&mut self, iou_id: &IOUId) -> Result<(), VaultError> {
+        let received = self.transactions
+            .iter()
+            .any(|t| t.direction == TransactionDirection::Received && &t.iou.id() == iou_id);
+        if !received {
+            return Err(VaultError::UnknownReceivedIOU);
+        }
+
+        if self.settled_ious.insert(iou_id.clone()) {
+            self.journal(VaultChange::Settled(iou_id.clone()));
+        }
+        Ok(())
+    }
+
+    /// Mark a sent IOU as settled once its recipient is known to have accepted it.
+    /// A settled IOU can no longer be cancelled. Marking twice is a no-op.
+    pub fn mark_sent_settled(&mut self, iou_id: &IOUId) -> Result<(), VaultError> {
+        let sent = self.transactions
+            .iter()
+            .any(|t| t.direction == TransactionDirection::Sent && &t.iou.id() == iou_id);
+        if !sent {
+            return Err(VaultError::UnknownIOU);
+        }
+
+        if self.settled_ious.insert(iou_id.clone()) {
+            self.journal(VaultChange::Settled(iou_id.clone()));
+        }
+        Ok(())
+    }
+
+    /// Check whether a sent or received IOU has been marked settled
+    pub fn is_settled(&self, iou_id: &IOUId) -> bool {
+        self.settled_ious.contains(iou_id)
+    }
+
     // ========================================================================
     // UTXO OPERATIONS
     // ========================================================================
@@ -220,6 +428,7 @@ impl Vault {
         match self.utxos.get_mut(id) {
             Some(utxo) => {
                 utxo.lock();
+                self.journal(VaultChange::Utxo(id.clone()));
                 Ok(())
             }
             None => Err(VaultError::UTXONotFound),
@@ -231,6 +440,7 @@ impl Vault {
         match self.utxos.get_mut(id) {
             Some(utxo) => {
                 utxo.unlock();
+                self.journal(VaultChange::Utxo(id.clone()));
                 Ok(())
             }
             None => Err(VaultError::UTXONotFound),
@@ -239,7 +449,46 @@ impl Vault {
 
     /// Estimate how many UTXOs would be needed to cover an amount
     pub fn estimate_utxos_needed(&self, amount: u64) -> Option<usize> {
-        self.utxos.select_for_amount(amount).map(|(utxos, _)| utxos.len())
+        self.utxos
+            .select_avoiding_dust(amount, self.coin_selection, self.dust_threshold)
+            .map(|(utxos, _)| utxos.len())
+    }
+
+    /// Get the coin selection strategy used for outgoing payments
+    pub fn coin_selection(&self) -> CoinSelectionStrategy {
+        self.coin_selection
+    }
+
+    /// Set the coin selection strategy used for outgoing payments
+    pub fn set_coin_selection(&mut self, strategy: CoinSelectionStrategy) {
+        self.coin_selection = strategy;
+    }
+
+    /// Get the amount below which UTXOs count as dust
+    pub fn dust_threshold(&self) -> u64 {
+        self.dust_threshold
+    }
+
+    /// Set the amount below which UTXOs count as dust (0 disables dust handling).
+    /// Coin selection only spends dust when the larger UTXOs can't cover a payment.
+    pub fn set_dust_threshold(&mut self, threshold: u64) {
+        self.dust_threshold = threshold;
+    }
+
+    /// UTXOs worth less than the dust threshold, smallest first
+    pub fn dust_utxos(&self) -> Vec<&UTXO> {
+        self.utxos.dust(self.dust_threshold)
+    }
+
+    /// Get the policy received IOUs are checked against
+    pub fn validation_policy(&self) -> &ValidationPolicy {
+        &self.validation_policy
+    }
+
+    /// Set the policy received IOUs are checked against.
+    /// The policy isn't persisted, so set it again after loading a vault.
+    pub fn set_validation_policy(&mut self, policy: ValidationPolicy) {
+        self.validation_policy = policy;
     }
 
     // ========================================================================
@@ -247,7 +496,19 @@ impl Vault {
     // ========================================================================
 
     /// Receive an IOU and add it to the vault
+    ///
+    /// A hashlocked IOU is recorded but held rather than credited: it only becomes a
+    /// UTXO once [`Vault::reveal_preimage`] is called with its preimage.
     pub fn receive_iou(&mut self, signed_iou: SignedIOU, sender_pubkey: &PublicKey) -> Result<(), VaultError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.receive_iou_at(signed_iou, sender_pubkey, now)
+    }
+
+    /// [`Vault::receive_iou`] at the given time (Unix seconds)
+    pub fn receive_iou_at(&mut self, signed_iou: SignedIOU, sender_pubkey: &PublicKey, now: u64) -> Result<(), VaultError> {
         let iou = signed_iou.iou();
         let iou_id = signed_iou.id();
 
@@ -256,54 +517,229 @@ impl Vault {
             return Err(VaultError::DuplicateTransaction);
         }
 
-        // Verify recipient matches vault owner
-        let recipient_pubkey = iou.recipient().public_key()
-            .map_err(|_| VaultError::RecipientMismatch)?;
-        if recipient_pubkey != self.owner {
+        // Verify the vault owner is a recipient; only their output is credited
+        let owner_did = self.owner_did();
+        if !iou.recipients().contains(&&owner_did) {
             return Err(VaultError::RecipientMismatch);
         }
-
-        // Validate the IOU signature
-        IOUValidator::validate(&signed_iou, sender_pubkey)?;
+        let amount = iou.amount_for(&owner_did);
+
+        // Validate the IOU signature and the vault's policy
+        IOUValidator::validate_with_policy_at(&signed_iou, sender_pubkey, &self.validation_policy, now)?;
+
+        // Enforce the sender's credit limit
+        if let Some(&limit) = self.credit_limits.get(iou.sender()) {
+            let outstanding = self.outstanding_from_sender(iou.sender());
+            if outstanding.saturating_add(amount) > limit {
+                return Err(VaultError::CreditLimitExceeded {
+                    sender: iou.sender().clone(),
+                    limit,
+                    outstanding,
+                });
+            }
+        }
 
         // Check for balance overflow
         let _new_balance = self.balance()
-            .checked_add(iou.amount())
+            .checked_add(amount)
             .ok_or(VaultError::BalanceOverflow)?;
 
-        // Create UTXO from this IOU (Received type)
-        let utxo = UTXO::new(self.owner.clone(), iou.amount(), iou_id.clone());
-        self.utxos.add(utxo);
+        // Create UTXO from this IOU (Received type), or hold it until its preimage is known
+        if iou.hashlock().is_some() {
+            self.held_ious.insert(iou_id.clone());
+        } else {
+            let utxo = UTXO::new(self.owner.clone(), amount, iou_id.clone());
+            self.add_utxo(utxo);
+        }
 
         // Mark IOU as processed with timestamp
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        self.processed_ious.insert(iou_id.clone(), timestamp);
+        self.mark_processed(iou_id.clone(), now);
 
         // Record transaction
         let record = TransactionRecord {
             iou: signed_iou,
             direction: TransactionDirection::Received,
-            timestamp,
+            timestamp: now,
         };
-        self.transactions.push(record);
+        self.record_transaction(record);
 
         Ok(())
     }
 
+    /// Receive an IOU unless it double-spends a source already seen spent
+    ///
+    /// `claims` holds the spending claims known to the mesh. If one of the IOU's
+    /// claims is preceded by another IOU's claim on the same UTXO (see
+    /// `ConflictDetector::prior_spend`), nothing is recorded and `ConflictingSpend`
+    /// names the earlier spend. IOUs with no claims are received as usual.
+    pub fn receive_iou_checked(
+        &mut self,
+        signed_iou: SignedIOU,
+        sender_pubkey: &PublicKey,
+        claims: &ConflictDetector,
+    ) -> Result<(), VaultError> {
+        if let Some(prior) = claims.prior_spend(&signed_iou.id()) {
+            return Err(VaultError::ConflictingSpend {
+                utxo_id: prior.utxo_id().clone(),
+                spent_by: prior.spending_iou_id().clone(),
+            });
+        }
+        self.receive_iou(signed_iou, sender_pubkey)
+    }
+
     /// Check if an IOU has already been processed
     pub fn has_processed_iou(&self, iou_id: &IOUId) -> bool {
         self.processed_ious.contains_key(iou_id)
     }
 
+    /// Reverse the credit for a received IOU that lost a double-spend conflict
+    ///
+    /// Meant for the losers reported by `ConflictDetector::losing_iou_ids`. If the
+    /// IOU's UTXO is still unspent it is removed, releasing any reservation holding
+    /// it, and its value is returned. If it was already spent the value can't be
+    /// taken back: it is added to `overdrawn_amount` and 0 is returned. Either way an
+    /// `Invalidated` transaction is recorded.
+    pub fn invalidate_received_iou(&mut self, iou_id: &IOUId) -> Result<u64, VaultError> {
+        let received = self.transactions
+            .iter()
+            .find(|t| t.direction == TransactionDirection::Received && &t.iou.id() == iou_id)
+            .cloned()
+            .ok_or(VaultError::UnknownReceivedIOU)?;
+
+        let invalidated = self.transactions
+            .iter()
+            .any(|t| t.direction == TransactionDirection::Invalidated && &t.iou.id() == iou_id);
+        if invalidated {
+            return Err(VaultError::DuplicateTransaction);
+        }
+
+        let utxo_id = UTXOId::from_iou(iou_id);
+        let reversed = match self.utxos.get(&utxo_id).map(|utxo| utxo.amount()) {
+            // Never credited, so there's nothing to take back
+            None if self.held_ious.remove(iou_id) => 0,
+            Some(amount) => {
+                let holding: Vec<ReservationId> = self.reservations
+                    .values()
+                    .filter(|r| r.utxo_ids.contains(&utxo_id))
+                    .map(|r| r.id)
+                    .collect();
+                for id in holding {
+                    if let Some(reservation) = self.reservations.remove(&id) {
+                        self.unlock_reserved(&reservation);
+                    }
+                }
+                self.lock_timeouts.remove(&utxo_id);
+                self.utxos.remove(&utxo_id);
+                self.journal(VaultChange::Utxo(utxo_id));
+                amount
+            }
+            None => {
+                self.overdrawn = self.overdrawn.saturating_add(self.received_amount(&received));
+                0
+            }
+        };
+
+        let record = TransactionRecord {
+            iou: received.iou,
+            direction: TransactionDirection::Invalidated,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+        self.record_transaction(record);
+
+        Ok(reversed)
+    }
+
+    /// Release a held hashlocked IOU by revealing its preimage
+    ///
+    /// The preimage must hash to the IOU's hashlock and arrive before the IOU expires,
+    /// after which its sender may have reclaimed it. The IOU's value becomes a
+    /// spendable UTXO and is returned. A wrong preimage leaves the IOU held.
+    pub fn reveal_preimage(&mut self, iou_id: &IOUId, preimage: &[u8]) -> Result<u64, VaultError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.reveal_preimage_at(iou_id, preimage, now)
+    }
+
+    /// [`Vault::reveal_preimage`] at the given time (Unix seconds)
+    pub fn reveal_preimage_at(&mut self, iou_id: &IOUId, preimage: &[u8], now: u64) -> Result<u64, VaultError> {
+        if !self.held_ious.contains(iou_id) {
+            return Err(VaultError::NotHeld);
+        }
+        let received = self.transactions
+            .iter()
+            .find(|t| t.direction == TransactionDirection::Received && &t.iou.id() == iou_id)
+            .ok_or(VaultError::NotHeld)?;
+        let iou = received.iou.iou();
+
+        if iou.is_expired_at(now) {
+            return Err(VaultError::HashlockExpired);
+        }
+        if !iou.hashlock().is_some_and(|hashlock| hashlock.is_unlocked_by(preimage)) {
+            return Err(VaultError::WrongPreimage);
+        }
+
+        let amount = self.received_amount(received);
+        self.balance()
+            .checked_add(amount)
+            .ok_or(VaultError::BalanceOverflow)?;
+
+        self.held_ious.remove(iou_id);
+        self.add_utxo(UTXO::new(self.owner.clone(), amount, iou_id.clone()));
+        Ok(amount)
+    }
+
+    /// Whether a received hashlocked IOU is still waiting for its preimage
+    pub fn is_held(&self, iou_id: &IOUId) -> bool {
+        self.held_ious.contains(iou_id)
+    }
+
+    /// Received hashlocked IOUs still waiting for their preimage
+    pub fn held_ious(&self) -> Vec<&SignedIOU> {
+        self.transactions
+            .iter()
+            .filter(|t| t.direction == TransactionDirection::Received && self.held_ious.contains(&t.iou.id()))
+            .map(|t| &t.iou)
+            .collect()
+    }
+
+    /// Total value of held hashlocked IOUs
+    pub fn held_balance(&self) -> u64 {
+        self.transactions
+            .iter()
+            .filter(|t| t.direction == TransactionDirection::Received && self.held_ious.contains(&t.iou.id()))
+            .fold(0u64, |total, t| total.saturating_add(self.received_amount(t)))
+    }
+
+    /// Value of invalidated IOUs that had already been spent
+    pub fn overdrawn_amount(&self) -> u64 {
+        self.overdrawn
+    }
+
+    /// Whether an invalidated IOU's value had already been spent
+    pub fn is_overdrawn(&self) -> bool {
+        self.overdrawn > 0
+    }
+
     // ========================================================================
     // SENDING IOUs
     // ========================================================================
 
     /// Record a sent IOU (deducting from balance)
     pub fn record_sent_iou(&mut self, signed_iou: SignedIOU) -> Result<(), VaultError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.record_sent_iou_at(signed_iou, now)
+    }
+
+    /// [`Vault::record_sent_iou`] at the given time (Unix seconds)
+    pub fn record_sent_iou_at(&mut self, signed_iou: SignedIOU, now: u64) -> Result<(), VaultError> {
         let iou = signed_iou.iou();
         let iou_id = signed_iou.id();
 
@@ -326,7 +762,7 @@ impl Vault {
 
         // Select UTXOs to spend
         let (selected_utxos, change) = self.utxos
-            .select_for_amount(amount)
+            .select_avoiding_dust(amount, self.coin_selection, self.dust_threshold)
             .ok_or(VaultError::InsufficientBalance {
                 available,
                 required: amount,
@@ -334,26 +770,22 @@ impl Vault {
 
         // Remove spent UTXOs and record as spent
         for utxo in &selected_utxos {
-            self.spent_outputs.add_unchecked(SpentOutput::now(utxo.id().clone(), iou_id.clone()));
-            self.utxos.remove(utxo.id());
+            self.spend_utxo(utxo.id(), &iou_id);
         }
 
         // Create change UTXO if needed (using Change type for unique ID)
         if change > 0 {
             let change_utxo = UTXO::new_change(self.owner.clone(), change, iou_id.clone());
-            self.utxos.add(change_utxo);
+            self.add_utxo(change_utxo);
         }
 
         // Record transaction
         let record = TransactionRecord {
             iou: signed_iou,
             direction: TransactionDirection::Sent,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: now,
         };
-        self.transactions.push(record);
+        self.record_transaction(record);
 
         Ok(())
     }
@@ -393,14 +825,13 @@ impl Vault {
 
         // Remove spent UTXOs and record as spent
         for utxo in &selected_utxos {
-            self.spent_outputs.add_unchecked(SpentOutput::now(utxo.id().clone(), iou_id.clone()));
-            self.utxos.remove(utxo.id());
+            self.spend_utxo(utxo.id(), &iou_id);
         }
 
         // Create change UTXO if needed (using Change type for unique ID)
         if change > 0 {
             let change_utxo = UTXO::new_change(self.owner.clone(), change, iou_id.clone());
-            self.utxos.add(change_utxo);
+            self.add_utxo(change_utxo);
         }
 
         // Record transaction
@@ -412,58 +843,367 @@ impl Vault {
                 .unwrap()
                 .as_secs(),
         };
-        self.transactions.push(record);
+        self.record_transaction(record);
 
         Ok(())
     }
 
+    /// Sweep up to `max_inputs` of the smallest unlocked UTXOs into a single output
+    ///
+    /// Builds a self-directed IOU for the swept total, spends the inputs through
+    /// `spend_with_utxos` and credits the total back as one `Consolidated` UTXO. The
+    /// balance is unchanged. Locked UTXOs, including those held by reservations, are
+    /// skipped. The IOU nonce continues the vault's sent
+    /// sequence. Returns the signed IOU so the caller can broadcast it.
+    pub fn consolidate(&mut self, keypair: &Keypair, max_inputs: usize) -> Result<SignedIOU, VaultError> {
+        if keypair.public_key() != self.owner {
+            return Err(VaultError::NotOwner);
+        }
+
+        let mut candidates: Vec<&UTXO> = self.utxos.iter().filter(|u| !u.is_locked()).collect();
+        candidates.sort_by(|a, b| {
+            a.amount()
+                .cmp(&b.amount())
+                .then_with(|| a.id().as_bytes().cmp(b.id().as_bytes()))
+        });
+        let candidates = candidates.into_iter().take(max_inputs).map(|u| u.id().clone()).collect();
+
+        self.consolidate_ids(keypair, candidates)
+    }
+
+    /// Sweep every unlocked dust UTXO into a single output
+    ///
+    /// Works like [`Vault::consolidate`] over the UTXOs below the dust threshold, even
+    /// when they make up the whole balance or the swept output is itself still dust.
+    pub fn sweep_dust(&mut self, keypair: &Keypair) -> Result<SignedIOU, VaultError> {
+        if keypair.public_key() != self.owner {
+            return Err(VaultError::NotOwner);
+        }
+
+        let utxo_ids: Vec<UTXOId> = self
+            .dust_utxos()
+            .into_iter()
+            .filter(|u| !u.is_locked())
+            .map(|u| u.id().clone())
+            .collect();
+
+        self.consolidate_ids(keypair, utxo_ids)
+    }
+
+    /// Merge `utxo_ids`, in order, into one `Consolidated` UTXO via a self-directed IOU.
+    /// Inputs that would overflow the total are left out.
+    fn consolidate_ids(&mut self, keypair: &Keypair, candidates: Vec<UTXOId>) -> Result<SignedIOU, VaultError> {
+        let mut total = 0u64;
+        let mut utxo_ids = Vec::new();
+        for id in candidates {
+            let amount = self.utxos.get(&id).ok_or(VaultError::UTXONotFound)?.amount();
+            match total.checked_add(amount) {
+                Some(next) => {
+                    total = next;
+                    utxo_ids.push(id);
+                }
+                None => break,
+            }
+        }
+
+        if utxo_ids.len() < 2 {
+            return Err(VaultError::NothingToConsolidate);
+        }
+
+        let nonce = self.highest_sent_nonce().map_or(1, |n| n.saturating_add(1));
+        let signed_iou = IOUBuilder::new()
+            .sender(keypair)
+            .recipient(Did::from_public_key(&self.owner))
+            .amount(total)
+            .nonce(nonce)
+            .allow_self_payment()
+            .build()?;
+        let iou_id = signed_iou.id();
+
+        // Spend the inputs exactly (no change), then credit the sweep back
+        self.spend_with_utxos(signed_iou.clone(), utxo_ids)?;
+        self.add_utxo(UTXO::with_type(self.owner.clone(), total, iou_id.clone(), UTXOType::Consolidated));
+
+        // Mark as processed so the self-payment can't be credited again via receive_iou
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.mark_processed(iou_id, timestamp);
+
+        Ok(signed_iou)
+    }
+
+    /// Same as [`Vault::consolidate`]
+    pub fn consolidate_utxos(&mut self, keypair: &Keypair, max_inputs: usize) -> Result<SignedIOU, VaultError> {
+        self.consolidate(keypair, max_inputs)
+    }
+
+    /// Whether the vault holds at least `threshold` unlocked UTXOs (and at least 2),
+    /// i.e. a `consolidate` call would be worthwhile
+    pub fn should_consolidate(&self, threshold: usize) -> bool {
+        let unlocked = self.utxos.iter().filter(|u| !u.is_locked()).count();
+        unlocked >= threshold.max(2)
+    }
+
+    /// Refund a sent IOU that its recipient rejected
+    ///
+    /// The rejection must be signed by the IOU's recipient and reference an IOU this
+    /// vault sent. The IOU amount is restored as a Refund UTXO (change was never given
+    /// away) and a `Refunded` transaction is recorded. Returns the refunded amount.
+    ///
+    /// For a multi-output IOU only the rejecting recipient's output is refunded, and
+    /// only the first rejection is honoured.
+    pub fn process_rejection(&mut self, rejection: &SignedRejection) -> Result<u64, VaultError> {
+        let iou_id = rejection.rejection().iou_id();
+
+        let sent = self.transactions
+            .iter()
+            .find(|t| t.direction == TransactionDirection::Sent && &t.iou.id() == iou_id)
+            .map(|t| t.iou.clone())
+            .ok_or(VaultError::UnknownIOU)?;
+
+        if !sent.iou().recipients().contains(&rejection.rejection().recipient()) {
+            return Err(VaultError::RecipientMismatch);
+        }
+        if !rejection.verify_recipient() {
+            return Err(VaultError::InvalidSignature);
+        }
+
+        if self.is_returned(iou_id) {
+            return Err(VaultError::DuplicateTransaction);
+        }
+
+        let amount = sent.iou().amount_for(rejection.rejection().recipient());
+        self.balance()
+            .checked_add(amount)
+            .ok_or(VaultError::BalanceOverflow)?;
+
+        self.add_utxo(UTXO::with_type(self.owner.clone(), amount, iou_id.clone(), UTXOType::Refund));
+
+        let record = TransactionRecord {
+            iou: sent,
+            direction: TransactionDirection::Refunded,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+        self.record_transaction(record);
+
+        Ok(amount)
+    }
+
+    /// Withdraw an IOU this vault sent before its recipient accepted it
+    ///
+    /// The cancellation must be signed by the IOU's sender. Fails with `AlreadySettled`
+    /// once the IOU has been marked settled, since acceptance seen first wins. The full
+    /// amount comes back as a Refund UTXO and a `Cancelled` transaction is recorded.
+    /// Returns the restored amount.
+    pub fn cancel_sent_iou(&mut self, cancellation: &SignedCancellation) -> Result<u64, VaultError> {
+        let iou_id = cancellation.cancellation().iou_id();
+
+        let sent = self.transactions
+            .iter()
+            .find(|t| t.direction == TransactionDirection::Sent && &t.iou.id() == iou_id)
+            .map(|t| t.iou.clone())
+            .ok_or(VaultError::UnknownIOU)?;
+
+        if !cancellation.cancels(&sent) {
+            return Err(VaultError::SenderMismatch);
+        }
+        if !cancellation.verify_sender() {
+            return Err(VaultError::InvalidSignature);
+        }
+        if self.settled_ious.contains(iou_id) {
+            return Err(VaultError::AlreadySettled);
+        }
+        if self.is_returned(iou_id) {
+            return Err(VaultError::DuplicateTransaction);
+        }
+
+        let amount = sent.iou().amount();
+        self.balance()
+            .checked_add(amount)
+            .ok_or(VaultError::BalanceOverflow)?;
+
+        self.add_utxo(UTXO::with_type(self.owner.clone(), amount, iou_id.clone(), UTXOType::Refund));
+
+        let record = TransactionRecord {
+            iou: sent,
+            direction: TransactionDirection::Cancelled,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+        self.record_transaction(record);
+
+        Ok(amount)
+    }
+
+    /// Take back a sent hashlocked IOU whose recipient never revealed the preimage
+    ///
+    /// Only allowed once the IOU has expired, since until then the recipient may still
+    /// release it. Fails with `AlreadySettled` if the IOU was marked settled. The full
+    /// amount comes back as a Refund UTXO and a `Reclaimed` transaction is recorded.
+    /// Returns the reclaimed amount.
+    pub fn reclaim_hashlocked(&mut self, iou_id: &IOUId) -> Result<u64, VaultError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.reclaim_hashlocked_at(iou_id, now)
+    }
+
+    /// [`Vault::reclaim_hashlocked`] at the given time (Unix seconds)
+    pub fn reclaim_hashlocked_at(&mut self, iou_id: &IOUId, now: u64) -> Result<u64, VaultError> {
+        let sent = self.transactions
+            .iter()
+            .find(|t| t.direction == TransactionDirection::Sent && &t.iou.id() == iou_id)
+            .map(|t| t.iou.clone())
+            .ok_or(VaultError::UnknownIOU)?;
+
+        if sent.hashlock().is_none() {
+            return Err(VaultError::NotHashlocked);
+        }
+        if !sent.iou().is_expired_at(now) {
+            return Err(VaultError::HashlockActive);
+        }
+        if self.settled_ious.contains(iou_id) {
+            return Err(VaultError::AlreadySettled);
+        }
+        if self.is_returned(iou_id) {
+            return Err(VaultError::DuplicateTransaction);
+        }
+
+        let amount = sent.iou().amount();
+        self.balance()
+            .checked_add(amount)
+            .ok_or(VaultError::BalanceOverflow)?;
+
+        self.add_utxo(UTXO::with_type(self.owner.clone(), amount, iou_id.clone(), UTXOType::Refund));
+
+        let record = TransactionRecord {
+            iou: sent,
+            direction: TransactionDirection::Reclaimed,
+            timestamp: now,
+        };
+        self.record_transaction(record);
+
+        Ok(amount)
+    }
+
+    /// Whether a sent IOU was already refunded, cancelled or reclaimed
+    fn is_returned(&self, iou_id: &IOUId) -> bool {
+        self.transactions.iter().any(|t| {
+            matches!(
+                t.direction,
+                TransactionDirection::Refunded | TransactionDirection::Cancelled | TransactionDirection::Reclaimed
+            ) && &t.iou.id() == iou_id
+        })
+    }
+
     // ========================================================================
     // RESERVATION SYSTEM
     // ========================================================================
 
-    /// Reserve balance for a pending transaction
-    pub fn reserve_balance(&mut self, amount: u64) -> Result<u64, VaultError> {
+    /// Select and lock UTXOs covering `amount` for a pending transaction
+    ///
+    /// The UTXOs are chosen with the vault's coin selection strategy and stay locked
+    /// until the reservation is committed, released, or expires (see
+    /// `set_reservation_timeout`). Reserving zero is a no-op that returns ID 0.
+    pub fn reserve_for_amount(&mut self, amount: u64) -> Result<ReservationId, VaultError> {
         if amount == 0 {
             return Ok(0);
         }
 
-        if amount > self.available_balance() {
-            return Err(VaultError::InsufficientBalance {
+        let (selected, _) = self.utxos
+            .select_avoiding_dust(amount, self.coin_selection, self.dust_threshold)
+            .ok_or(VaultError::InsufficientBalance {
                 available: self.available_balance(),
                 required: amount,
-            });
+            })?;
+
+        let utxo_ids: Vec<UTXOId> = selected.iter().map(|u| u.id().clone()).collect();
+        for id in &utxo_ids {
+            self.lock_utxo(id)?;
         }
 
         let id = self.next_reservation_id;
         self.next_reservation_id += 1;
 
-        self.reservations.insert(id, Reservation { id, amount });
+        let lock = LockInfo::with_reason(self.reservation_timeout_ms, format!("reservation {}", id));
+        self.reservations.insert(id, Reservation { id, amount, utxo_ids, lock });
         Ok(id)
     }
 
-    /// Release a reservation without spending
-    pub fn release_reservation(&mut self, reservation_id: u64) -> Result<(), VaultError> {
-        if self.reservations.remove(&reservation_id).is_none() {
-            return Err(VaultError::ReservationNotFound);
-        }
-        Ok(())
+    /// Same as [`Vault::reserve_for_amount`]
+    pub fn reserve_balance(&mut self, amount: u64) -> Result<ReservationId, VaultError> {
+        self.reserve_for_amount(amount)
     }
 
-    /// Commit a reservation (actually spend the reserved amount)
-    pub fn commit_reservation(&mut self, reservation_id: u64) -> Result<u64, VaultError> {
+    /// Release a reservation without spending, unlocking its UTXOs
+    pub fn release_reservation(&mut self, reservation_id: ReservationId) -> Result<(), VaultError> {
         let reservation = self.reservations.remove(&reservation_id)
             .ok_or(VaultError::ReservationNotFound)?;
+        self.unlock_reserved(&reservation);
+        Ok(())
+    }
+
+    /// Commit a reservation by spending it with a signed IOU
+    ///
+    /// The IOU amount must not exceed the reserved amount. Exactly the reserved UTXOs
+    /// are consumed via `spend_with_utxos`, with any excess returned as change. The
+    /// reservation is removed only if that succeeds, so a failed commit leaves both
+    /// the reservation and its UTXOs untouched. Returns the amount spent.
+    pub fn commit_reservation(&mut self, reservation_id: ReservationId, signed_iou: SignedIOU) -> Result<u64, VaultError> {
+        let reservation = self.reservations.get(&reservation_id)
+            .ok_or(VaultError::ReservationNotFound)?;
 
-        // The actual spending would happen here, but since this is a
-        // reservation commit, we just need to update the balance.
-        // In practice, this would be called after record_sent_iou succeeds.
+        let amount = signed_iou.iou().amount();
+        if amount > reservation.amount {
+            return Err(VaultError::ReservationExceeded { reserved: reservation.amount, requested: amount });
+        }
+
+        self.spend_with_utxos(signed_iou, reservation.utxo_ids.clone())?;
+        self.reservations.remove(&reservation_id);
+
+        Ok(amount)
+    }
 
-        // For now, we need to manually adjust - the reservation was for
-        // holding funds. Since record_sent_iou handles the actual UTXO
-        // consumption, committing just means the reservation is done.
-        // We simulate the spend by reducing balance through UTXO consumption.
+    /// UTXOs held by a reservation
+    pub fn reservation_utxos(&self, reservation_id: ReservationId) -> Option<&[UTXOId]> {
+        self.reservations.get(&reservation_id).map(|r| r.utxo_ids.as_slice())
+    }
+
+    /// Lock information for a reservation
+    pub fn reservation_lock(&self, reservation_id: ReservationId) -> Option<&LockInfo> {
+        self.reservations.get(&reservation_id).map(|r| &r.lock)
+    }
 
-        Ok(reservation.amount)
+    /// Get the number of active reservations
+    pub fn active_reservation_count(&self) -> usize {
+        self.reservations.len()
+    }
+
+    /// Set how long new reservations hold their UTXOs (in milliseconds)
+    pub fn set_reservation_timeout(&mut self, timeout_ms: u64) {
+        self.reservation_timeout_ms = timeout_ms;
+    }
+
+    /// How long new reservations hold their UTXOs (in milliseconds)
+    pub fn reservation_timeout_ms(&self) -> u64 {
+        self.reservation_timeout_ms
+    }
+
+    fn unlock_reserved(&mut self, reservation: &Reservation) {
+        for id in &reservation.utxo_ids {
+            if let Some(utxo) = self.utxos.get_mut(id) {
+                utxo.unlock();
+                self.journal(VaultChange::Utxo(id.clone()));
+            }
+        }
     }
 
     // ========================================================================
@@ -475,11 +1215,168 @@ impl Vault {
         self.transactions.len()
     }
 
-    /// Get all transaction history
+    /// Get all transaction history (oldest first)
     pub fn transaction_history(&self) -> Vec<&TransactionRecord> {
         self.transactions.iter().collect()
     }
 
+    /// Up to `limit` transactions after skipping the `offset` newest, newest first
+    pub fn transactions_page(&self, offset: usize, limit: usize) -> Vec<&TransactionRecord> {
+        self.transaction_index
+            .between(0, u64::MAX)
+            .skip(offset)
+            .take(limit)
+            .map(|position| &self.transactions[position])
+            .collect()
+    }
+
+    /// Transactions recorded at or after `timestamp`, newest first
+    pub fn transactions_since(&self, timestamp: u64) -> Vec<&TransactionRecord> {
+        self.transactions_between(timestamp, u64::MAX)
+    }
+
+    /// Transactions recorded between `from_ts` and `to_ts` (inclusive), newest first
+    pub fn transactions_between(&self, from_ts: u64, to_ts: u64) -> Vec<&TransactionRecord> {
+        self.transaction_index
+            .between(from_ts, to_ts)
+            .map(|position| &self.transactions[position])
+            .collect()
+    }
+
+    /// Transactions with `counterparty` (sender of receipts, recipient of sends), newest first
+    pub fn transactions_with(&self, counterparty: &Did) -> Vec<&TransactionRecord> {
+        self.transaction_index
+            .with(counterparty)
+            .map(|position| &self.transactions[position])
+            .collect()
+    }
+
+    /// Transactions passing every filter of `query`, newest first, ignoring its pagination
+    ///
+    /// Lazy, so callers can stop early without visiting the whole log.
+    pub fn iter_transactions<'a>(
+        &'a self,
+        query: &'a TransactionQuery,
+    ) -> impl Iterator<Item = &'a TransactionRecord> + 'a {
+        let (from_ts, to_ts) = query.time_range();
+        self.transaction_index
+            .between(from_ts, to_ts)
+            .map(|position| &self.transactions[position])
+            .filter(|record| query.matches(record))
+    }
+
+    /// One page of transactions matching `query`, newest first
+    pub fn query_transactions(&self, query: &TransactionQuery) -> TransactionPage {
+        let (offset, limit) = query.pagination();
+        let mut total = 0;
+        let mut records = Vec::new();
+        for record in self.iter_transactions(query) {
+            if total >= offset && records.len() < limit {
+                records.push(record.clone());
+            }
+            total += 1;
+        }
+        TransactionPage::new(records, offset, total)
+    }
+
+    /// Running balance after each transaction, oldest first
+    ///
+    /// Records are sorted by timestamp (ties keep log order), so one recorded late
+    /// with an earlier timestamp lands where it belongs. Each point's credit and
+    /// debit are what the transaction did to the vault's current UTXOs: a held
+    /// hashlocked receipt adds nothing yet, and invalidating an IOU whose value was
+    /// already spent takes nothing back. The last point's balance is `balance()`.
+    pub fn balance_history(&self) -> Vec<BalancePoint> {
+        let mut ordered: Vec<&TransactionRecord> = self.transactions.iter().collect();
+        ordered.sort_by_key(|record| record.timestamp);
+
+        let mut balance = 0u64;
+        ordered
+            .into_iter()
+            .map(|record| {
+                let (credit, debit) = self.balance_change(record);
+                balance = balance.saturating_add(credit).saturating_sub(debit);
+                BalancePoint::new(record.clone(), credit, debit, balance)
+            })
+            .collect()
+    }
+
+    /// Itemized statement of transactions between `start` and `end` (inclusive)
+    ///
+    /// The opening balance is the running balance after everything before `start`.
+    pub fn statement(&self, start: u64, end: u64) -> Statement {
+        let mut opening = 0;
+        let mut items = Vec::new();
+        for point in self.balance_history() {
+            if point.timestamp() < start {
+                opening = point.balance();
+            } else if point.timestamp() <= end {
+                items.push(point);
+            }
+        }
+        Statement::new(start, end, opening, items)
+    }
+
+    /// `(credit, debit)` a transaction applied to the balance
+    ///
+    /// A refund of a multi-output IOU whose Refund UTXO has since been spent is
+    /// counted at the IOU's full amount, as the record doesn't say which output
+    /// was returned. A sent IOU debits only what it paid to others, so the
+    /// self-directed merges of `consolidate` and `sweep_dust` change nothing.
+    fn balance_change(&self, record: &TransactionRecord) -> (u64, u64) {
+        let iou_id = record.iou.id();
+        let iou = record.iou.iou();
+        match record.direction {
+            TransactionDirection::Received if self.held_ious.contains(&iou_id) => (0, 0),
+            TransactionDirection::Received => (self.received_amount(record), 0),
+            TransactionDirection::Sent => (0, iou.amount().saturating_sub(iou.amount_for(&self.owner_did()))),
+            TransactionDirection::Refunded => {
+                let refund = UTXOId::from_iou_with_type(&iou_id, UTXOType::Refund);
+                (self.utxos.get(&refund).map_or(iou.amount(), |utxo| utxo.amount()), 0)
+            }
+            TransactionDirection::Cancelled | TransactionDirection::Reclaimed => (iou.amount(), 0),
+            // Spent before the reversal, so it went to `overdrawn` instead
+            TransactionDirection::Invalidated if self.spent_outputs.contains(&UTXOId::from_iou(&iou_id)) => (0, 0),
+            TransactionDirection::Invalidated => (0, self.received_amount(record)),
+        }
+    }
+
+    /// Every transaction as an export row, oldest first
+    pub fn transaction_exports(&self) -> Vec<TransactionExport> {
+        self.transactions
+            .iter()
+            .map(|record| {
+                let amount = match record.direction {
+                    TransactionDirection::Received | TransactionDirection::Invalidated => self.received_amount(record),
+                    _ => record.iou.iou().amount(),
+                };
+                TransactionExport::new(record, amount)
+            })
+            .collect()
+    }
+
+    /// Transaction history as CSV, a header line then one row per transaction
+    ///
+    /// Columns follow `TRANSACTION_EXPORT_COLUMNS`; fields with commas, quotes or
+    /// line breaks (typically memos) are quoted.
+    pub fn export_transactions_csv(&self) -> String {
+        export::to_csv(&self.transaction_exports())
+    }
+
+    /// Transaction history as a JSON array, one object per transaction
+    ///
+    /// Read it back with [`TransactionExport::parse_json`].
+    pub fn export_transactions_json(&self) -> String {
+        export::to_json(&self.transaction_exports())
+    }
+
+    /// Append a record to the history and its indexes
+    fn record_transaction(&mut self, record: TransactionRecord) {
+        self.journal(VaultChange::Transaction(self.transactions.len()));
+        self.transaction_index.insert(self.transactions.len(), &record);
+        self.transactions.push(record);
+    }
+
     /// Get only received transactions
     pub fn received_transactions(&self) -> Vec<&TransactionRecord> {
         self.transactions
@@ -496,6 +1393,28 @@ impl Vault {
             .collect()
     }
 
+    /// Whether this vault has already paid the given payment request
+    ///
+    /// A payment that was later refunded or cancelled doesn't count, so the request can be paid again.
+    pub fn has_paid_request(&self, request_id: &PaymentRequestId) -> bool {
+        self.transactions
+            .iter()
+            .filter(|t| t.direction == TransactionDirection::Sent)
+            .filter(|t| t.iou.iou().request_id() == Some(request_id))
+            .any(|t| !self.is_returned(&t.iou.id()))
+    }
+
+    /// Highest nonce among IOUs this vault has sent, if any
+    ///
+    /// Used to resume a nonce counter after restoring from exported state.
+    pub fn highest_sent_nonce(&self) -> Option<u64> {
+        self.transactions
+            .iter()
+            .filter(|t| t.direction == TransactionDirection::Sent)
+            .map(|t| t.iou.iou().nonce())
+            .max()
+    }
+
     // ========================================================================
     // SPENT OUTPUTS
     // ========================================================================
@@ -534,7 +1453,9 @@ impl Vault {
         self.utxos = state.utxos;
         self.spent_outputs = state.spent_outputs;
         self.processed_ious = state.processed_ious;
+        self.transaction_index = TransactionIndex::build(&state.transactions);
         self.transactions = state.transactions;
+        self.journal(VaultChange::Reset);
 
         Ok(())
     }
@@ -551,6 +1472,7 @@ impl Vault {
             Some(utxo) => {
                 utxo.lock();
                 self.lock_timeouts.insert(id.clone(), LockInfo::new(timeout_ms));
+                self.journal(VaultChange::Utxo(id.clone()));
                 Ok(())
             }
             None => Err(VaultError::UTXONotFound),
@@ -563,6 +1485,7 @@ impl Vault {
             Some(utxo) => {
                 utxo.lock();
                 self.lock_timeouts.insert(id.clone(), LockInfo::with_reason(timeout_ms, reason));
+                self.journal(VaultChange::Utxo(id.clone()));
                 Ok(())
             }
             None => Err(VaultError::UTXONotFound),
@@ -575,6 +1498,7 @@ impl Vault {
     }
 
     /// Cleanup all expired locks, automatically unlocking the UTXOs
+    /// Expired reservations are released too, each counting as one lock.
     /// Returns the number of locks that were cleaned up
     pub fn cleanup_expired_locks(&mut self) -> usize {
         let expired: Vec<UTXOId> = self.lock_timeouts
@@ -583,13 +1507,26 @@ impl Vault {
             .map(|(id, _)| id.clone())
             .collect();
 
-        let count = expired.len();
+        let expired_reservations: Vec<ReservationId> = self.reservations
+            .values()
+            .filter(|r| r.lock.is_expired())
+            .map(|r| r.id)
+            .collect();
+
+        let count = expired.len() + expired_reservations.len();
 
         for id in expired {
             self.lock_timeouts.remove(&id);
             if let Some(utxo) = self.utxos.get_mut(&id) {
                 utxo.unlock();
             }
+            self.journal(VaultChange::Utxo(id));
+        }
+
+        for id in expired_reservations {
+            if let Some(reservation) = self.reservations.remove(&id) {
+                self.unlock_reserved(&reservation);
+            }
         }
 
         count
@@ -615,10 +1552,20 @@ impl Vault {
     /// WARNING: Pruned IOUs can potentially be replayed if they're resubmitted.
     /// Only prune IOUs that are old enough that replay is unlikely or
     /// use other mechanisms (like expiry validation) to prevent replay.
+    /// `receive_iou` refuses expired IOUs, so `prune_expired_processed_ious`
+    /// is always replay-safe.
     pub fn prune_processed_ious_before(&mut self, before_timestamp: u64) -> usize {
-        let before_count = self.processed_ious.len();
-        self.processed_ious.retain(|_, timestamp| *timestamp >= before_timestamp);
-        before_count - self.processed_ious.len()
+        let stale: Vec<IOUId> = self.processed_ious
+            .iter()
+            .filter(|(_, timestamp)| **timestamp < before_timestamp)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in &stale {
+            self.processed_ious.remove(id);
+            self.journal(VaultChange::Processed(id.clone()));
+        }
+        stale.len()
     }
 
     /// Prune processed IOUs to keep only the most recent N entries
@@ -640,11 +1587,42 @@ impl Vault {
         // Remove the oldest entries
         for (id, _) in entries.into_iter().take(to_remove) {
             self.processed_ious.remove(&id);
+            self.journal(VaultChange::Processed(id));
         }
 
         to_remove
     }
 
+    /// Prune processed IOUs whose own expiry has passed
+    /// Returns the number of IOUs pruned
+    ///
+    /// Safe against replay: an expired IOU is rejected by `receive_iou` anyway.
+    /// IOUs without an expiry are kept.
+    pub fn prune_expired_processed_ious(&mut self) -> usize {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.prune_expired_processed_ious_at(now)
+    }
+
+    /// Prune processed IOUs that have expired at `now` (Unix seconds)
+    /// Expiries come from the IOUs stored in the transaction history.
+    pub fn prune_expired_processed_ious_at(&mut self, now: u64) -> usize {
+        let expired: HashSet<IOUId> = self.transactions
+            .iter()
+            .filter(|t| t.iou.iou().is_expired_at(now))
+            .map(|t| t.iou.id())
+            .filter(|id| self.processed_ious.contains_key(id))
+            .collect();
+
+        for id in &expired {
+            self.processed_ious.remove(id);
+            self.journal(VaultChange::Processed(id.clone()));
+        }
+        expired.len()
+    }
+
     /// Get the timestamp when an IOU was processed (if tracked)
     pub fn get_processed_iou_timestamp(&self, iou_id: &IOUId) -> Option<u64> {
         self.processed_ious.get(iou_id).copied()
@@ -686,6 +1664,131 @@ impl Vault {
         }
     }
 
+    // ========================================================================
+    // CHANGE JOURNAL
+    // ========================================================================
+
+    /// Attach this vault to `store`, writing only changes from now on
+    ///
+    /// Same as [`PersistentVault::new`].
+    pub fn with_store(self, store: MeshStore) -> PersistentVault {
+        PersistentVault::new(store, self)
+    }
+
+    /// Note a change for an attached `PersistentVault`
+    fn journal(&mut self, change: VaultChange) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push(change);
+        }
+    }
+
+    fn add_utxo(&mut self, utxo: UTXO) {
+        self.journal(VaultChange::Utxo(utxo.id().clone()));
+        self.utxos.add(utxo);
+    }
+
+    /// Record `utxo_id` as spent by `iou_id` and drop it from the UTXO set
+    fn spend_utxo(&mut self, utxo_id: &UTXOId, iou_id: &IOUId) {
+        self.spent_outputs.add_unchecked(SpentOutput::now(utxo_id.clone(), iou_id.clone()));
+        self.utxos.remove(utxo_id);
+        self.journal(VaultChange::Spent(utxo_id.clone()));
+        self.journal(VaultChange::Utxo(utxo_id.clone()));
+    }
+
+    fn mark_processed(&mut self, iou_id: IOUId, timestamp: u64) {
+        self.processed_ious.insert(iou_id.clone(), timestamp);
+        self.journal(VaultChange::Processed(iou_id));
+    }
+
+    /// Start journaling, with `pending` already queued
+    pub(crate) fn start_journal(&mut self, pending: Vec<VaultChange>) {
+        self.journal = Some(pending);
+    }
+
+    /// Stop journaling and drop pending changes
+    pub(crate) fn stop_journal(&mut self) {
+        self.journal = None;
+    }
+
+    /// Changes since the last `take_journal`
+    pub(crate) fn pending_changes(&self) -> &[VaultChange] {
+        self.journal.as_deref().unwrap_or_default()
+    }
+
+    pub(crate) fn take_journal(&mut self) -> Vec<VaultChange> {
+        self.journal.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    pub(crate) fn transaction_at(&self, position: usize) -> Option<&TransactionRecord> {
+        self.transactions.get(position)
+    }
+
+    pub(crate) fn processed_ious(&self) -> impl Iterator<Item = (&IOUId, u64)> {
+        self.processed_ious.iter().map(|(id, timestamp)| (id, *timestamp))
+    }
+
+    pub(crate) fn settled_ious(&self) -> impl Iterator<Item = &IOUId> {
+        self.settled_ious.iter()
+    }
+
+    pub(crate) fn meta(&self) -> VaultMeta {
+        VaultMeta {
+            owner: self.owner.clone(),
+            reservations: self.reservations.clone(),
+            next_reservation_id: self.next_reservation_id,
+            reservation_timeout_ms: self.reservation_timeout_ms,
+            lock_timeouts: self.lock_timeouts.clone(),
+            coin_selection: self.coin_selection,
+            last_utxo_sequence: self.utxos.last_sequence(),
+            credit_limits: self.credit_limits.clone(),
+            dust_threshold: self.dust_threshold,
+            overdrawn: self.overdrawn,
+            held_ious: self.held_ious.clone(),
+        }
+    }
+
+    /// Rebuild a vault from persisted parts
+    pub(crate) fn from_parts(
+        meta: VaultMeta,
+        utxos: Vec<UTXO>,
+        spent_outputs: Vec<SpentOutput>,
+        processed_ious: HashMap<IOUId, u64>,
+        settled_ious: HashSet<IOUId>,
+        transactions: Vec<TransactionRecord>,
+    ) -> Self {
+        let mut utxo_set = UTXOSet::new();
+        for utxo in utxos {
+            utxo_set.add(utxo);
+        }
+        utxo_set.set_last_sequence(meta.last_utxo_sequence);
+
+        let mut spent_set = SpentOutputSet::new();
+        for spent in spent_outputs {
+            spent_set.add_unchecked(spent);
+        }
+
+        Self {
+            owner: meta.owner,
+            utxos: utxo_set,
+            spent_outputs: spent_set,
+            processed_ious,
+            transaction_index: TransactionIndex::build(&transactions),
+            transactions,
+            reservations: meta.reservations,
+            next_reservation_id: meta.next_reservation_id,
+            reservation_timeout_ms: meta.reservation_timeout_ms,
+            lock_timeouts: meta.lock_timeouts,
+            coin_selection: meta.coin_selection,
+            credit_limits: meta.credit_limits,
+            settled_ious,
+            dust_threshold: meta.dust_threshold,
+            overdrawn: meta.overdrawn,
+            held_ious: meta.held_ious,
+            validation_policy: ValidationPolicy::default(),
+            journal: None,
+        }
+    }
+
     // ========================================================================
     // SERIALIZATION
     // ========================================================================
@@ -700,4 +1803,45 @@ impl Vault {
         postcard::from_bytes(bytes)
             .map_err(|e| VaultError::StateError(e.to_string()))
     }
+
+    /// Serialize the vault encrypted under `passphrase`
+    /// (Argon2id with default params, XChaCha20-Poly1305; see `storage::seal`)
+    pub fn to_encrypted_bytes(&self, passphrase: &str) -> Vec<u8> {
+        self.to_encrypted_bytes_with_params(passphrase, &KdfParams::default())
+            .expect("default KDF params are valid")
+    }
+
+    /// Like `to_encrypted_bytes` with explicit Argon2id params
+    pub fn to_encrypted_bytes_with_params(
+        &self,
+        passphrase: &str,
+        params: &KdfParams,
+    ) -> Result<Vec<u8>, VaultError> {
+        let plaintext = Zeroizing::new(self.to_bytes());
+        seal(&plaintext, passphrase.as_bytes(), params).map_err(|e| VaultError::StateError(e.to_string()))
+    }
+
+    /// Decrypt and deserialize a vault from `to_encrypted_bytes`
+    ///
+    /// A wrong passphrase returns `VaultError::WrongPassphrase`; a malformed
+    /// blob returns `VaultError::StateError`.
+    pub fn from_encrypted_bytes(bytes: &[u8], passphrase: &str) -> Result<Self, VaultError> {
+        Self::from_encrypted_bytes_within(bytes, passphrase, &KdfParams::default())
+    }
+
+    /// Like `from_encrypted_bytes` for blobs sealed with KDF costs up to `max`
+    /// (blobs above it are refused before any key derivation)
+    pub fn from_encrypted_bytes_within(bytes: &[u8], passphrase: &str, max: &KdfParams) -> Result<Self, VaultError> {
+        let plaintext = unseal_within(bytes, passphrase.as_bytes(), max).map_err(|e| match e {
+            SealError::DecryptionFailed => VaultError::WrongPassphrase,
+            other => VaultError::StateError(other.to_string()),
+        })?;
+        Self::from_bytes(&plaintext)
+    }
+}
+
+impl TransactionSource for Vault {
+    fn query_transactions(&self, query: &TransactionQuery) -> TransactionPage {
+        Vault::query_transactions(self, query)
+    }
 }
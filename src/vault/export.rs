@@ -0,0 +1,181 @@
+// Transaction export - CSV and JSON for spreadsheets and accounting tools
+// One row per transaction record, oldest first, with columns in a fixed order
+
+use crate::iou::json::{JsonObject, JsonValue};
+use crate::iou::IOUId;
+use crate::vault::balance::{TransactionDirection, TransactionRecord, VaultError};
+use crate::vault::history::counterparties;
+
+/// CSV header and JSON key order of an exported transaction
+pub const TRANSACTION_EXPORT_COLUMNS: [&str; 6] = ["direction", "counterparty", "amount", "timestamp", "iou_id", "memo"];
+
+/// Separator between the DIDs of a multi-output IOU's counterparties
+pub const COUNTERPARTY_SEPARATOR: char = ';';
+
+/// One transaction as exported by [`Vault::export_transactions_csv`](crate::vault::Vault::export_transactions_csv)
+/// and [`Vault::export_transactions_json`](crate::vault::Vault::export_transactions_json)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionExport {
+    direction: TransactionDirection,
+    counterparty: String,
+    amount: u64,
+    timestamp: u64,
+    iou_id: IOUId,
+    memo: Option<String>,
+}
+
+impl TransactionExport {
+    /// Export `record`, which moved `amount` for this vault
+    pub(crate) fn new(record: &TransactionRecord, amount: u64) -> Self {
+        let iou = record.iou().iou();
+        let counterparty = counterparties(record)
+            .iter()
+            .map(|did| did.to_string())
+            .collect::<Vec<_>>()
+            .join(&COUNTERPARTY_SEPARATOR.to_string());
+        Self {
+            direction: record.direction(),
+            counterparty,
+            amount,
+            timestamp: record.timestamp(),
+            iou_id: record.iou().id(),
+            memo: iou.memo().map(str::to_string),
+        }
+    }
+
+    pub fn direction(&self) -> TransactionDirection {
+        self.direction
+    }
+
+    /// DID of the other party, or of every recipient joined by [`COUNTERPARTY_SEPARATOR`]
+    pub fn counterparty(&self) -> &str {
+        &self.counterparty
+    }
+
+    /// Value moved: this vault's output for receipts, the IOU amount otherwise
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn iou_id(&self) -> &IOUId {
+        &self.iou_id
+    }
+
+    pub fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
+
+    /// Fields in [`TRANSACTION_EXPORT_COLUMNS`] order, unescaped (a missing memo is empty)
+    fn fields(&self) -> [String; 6] {
+        [
+            direction_label(self.direction).to_string(),
+            self.counterparty.clone(),
+            self.amount.to_string(),
+            self.timestamp.to_string(),
+            hex::encode(self.iou_id.as_bytes()),
+            self.memo.clone().unwrap_or_default(),
+        ]
+    }
+
+    /// One CSV line, without its line ending
+    pub fn to_csv_row(&self) -> String {
+        self.fields().iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",")
+    }
+
+    /// One JSON object, keys in [`TRANSACTION_EXPORT_COLUMNS`] order
+    ///
+    /// Amount and timestamp are decimal strings, as in the canonical IOU JSON.
+    pub fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("direction", direction_label(self.direction))
+            .string("counterparty", &self.counterparty)
+            .u64("amount", self.amount)
+            .u64("timestamp", self.timestamp)
+            .string("iou_id", &hex::encode(self.iou_id.as_bytes()))
+            .opt_string("memo", self.memo.as_deref())
+            .finish()
+    }
+
+    /// Parse one object written by [`TransactionExport::to_json`]
+    fn from_json_value(value: &JsonValue) -> Result<Self, String> {
+        let direction = value.str_field("direction")?;
+        let iou_id = hex::decode(value.str_field("iou_id")?)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or("'iou_id' is not 32 hex-encoded bytes")?;
+        Ok(Self {
+            direction: direction_from_label(direction).ok_or_else(|| format!("unknown direction '{}'", direction))?,
+            counterparty: value.str_field("counterparty")?.to_string(),
+            amount: value.u64_field("amount")?,
+            timestamp: value.u64_field("timestamp")?,
+            iou_id: IOUId::from_bytes(iou_id),
+            memo: value.opt_str_field("memo")?.map(str::to_string),
+        })
+    }
+
+    /// Parse an array written by [`Vault::export_transactions_json`](crate::vault::Vault::export_transactions_json)
+    pub fn parse_json(json: &str) -> Result<Vec<Self>, VaultError> {
+        let items = match JsonValue::parse(json).map_err(VaultError::StateError)? {
+            JsonValue::Array(items) => items,
+            _ => return Err(VaultError::StateError("expected a JSON array".to_string())),
+        };
+        items
+            .iter()
+            .map(Self::from_json_value)
+            .collect::<Result<_, _>>()
+            .map_err(VaultError::StateError)
+    }
+}
+
+/// CSV with a header line, one `\r\n`-terminated row per export (RFC 4180)
+pub(crate) fn to_csv(exports: &[TransactionExport]) -> String {
+    let mut csv = TRANSACTION_EXPORT_COLUMNS.join(",");
+    csv.push_str("\r\n");
+    for export in exports {
+        csv.push_str(&export.to_csv_row());
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// JSON array of the exports' objects
+pub(crate) fn to_json(exports: &[TransactionExport]) -> String {
+    let objects: Vec<String> = exports.iter().map(TransactionExport::to_json).collect();
+    format!("[{}]", objects.join(","))
+}
+
+/// Quote a field containing a comma, quote or line break, doubling its quotes
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn direction_label(direction: TransactionDirection) -> &'static str {
+    match direction {
+        TransactionDirection::Received => "received",
+        TransactionDirection::Sent => "sent",
+        TransactionDirection::Refunded => "refunded",
+        TransactionDirection::Cancelled => "cancelled",
+        TransactionDirection::Invalidated => "invalidated",
+        TransactionDirection::Reclaimed => "reclaimed",
+    }
+}
+
+fn direction_from_label(label: &str) -> Option<TransactionDirection> {
+    Some(match label {
+        "received" => TransactionDirection::Received,
+        "sent" => TransactionDirection::Sent,
+        "refunded" => TransactionDirection::Refunded,
+        "cancelled" => TransactionDirection::Cancelled,
+        "invalidated" => TransactionDirection::Invalidated,
+        "reclaimed" => TransactionDirection::Reclaimed,
+        _ => return None,
+    })
+}
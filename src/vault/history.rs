@@ -0,0 +1,371 @@
+// Transaction history indexes
+// Positions into the vault's append-only transaction log, keyed by time and counterparty
+
+use crate::identity::Did;
+use crate::vault::balance::{TransactionDirection, TransactionRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Secondary indexes over a transaction log.
+/// Positions stay valid because records are only ever appended.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct TransactionIndex {
+    /// Timestamp -> positions recorded at that second (ascending)
+    by_time: BTreeMap<u64, Vec<usize>>,
+    /// Counterparty DID -> positions (ascending)
+    by_counterparty: HashMap<Did, Vec<usize>>,
+}
+
+impl TransactionIndex {
+    /// Build an index for an existing log
+    pub(crate) fn build(records: &[TransactionRecord]) -> Self {
+        let mut index = Self::default();
+        for (position, record) in records.iter().enumerate() {
+            index.insert(position, record);
+        }
+        index
+    }
+
+    /// Index the record stored at `position`
+    pub(crate) fn insert(&mut self, position: usize, record: &TransactionRecord) {
+        self.by_time.entry(record.timestamp()).or_default().push(position);
+        for did in counterparties(record) {
+            self.by_counterparty.entry(did.clone()).or_default().push(position);
+        }
+    }
+
+    /// Positions with `from <= timestamp <= to`, newest first
+    pub(crate) fn between(&self, from: u64, to: u64) -> impl Iterator<Item = usize> + '_ {
+        let range = if from <= to { Some(self.by_time.range(from..=to)) } else { None };
+        range
+            .into_iter()
+            .flatten()
+            .rev()
+            .flat_map(|(_, positions)| positions.iter().rev().copied())
+    }
+
+    /// Positions involving `did`, newest first
+    pub(crate) fn with(&self, did: &Did) -> impl Iterator<Item = usize> + '_ {
+        self.by_counterparty
+            .get(did)
+            .into_iter()
+            .flat_map(|positions| positions.iter().rev().copied())
+    }
+}
+
+/// The other parties of a transaction (the sender for receipts and their reversals, every recipient otherwise)
+pub(crate) fn counterparties(record: &TransactionRecord) -> Vec<&Did> {
+    let iou = record.iou().iou();
+    match record.direction() {
+        TransactionDirection::Received | TransactionDirection::Invalidated => vec![iou.sender()],
+        TransactionDirection::Sent
+        | TransactionDirection::Refunded
+        | TransactionDirection::Cancelled
+        | TransactionDirection::Reclaimed => iou.recipients(),
+    }
+}
+
+// ============================================================================
+// QUERIES
+// ============================================================================
+
+/// Page size used when a [`TransactionQuery`] doesn't set a limit
+pub const DEFAULT_TRANSACTION_PAGE_SIZE: usize = 50;
+
+/// Filtered, paginated view over transaction history
+///
+/// Every filter is optional and they combine with AND. Results are newest first.
+///
+/// ```ignore
+/// let page = vault.query_transactions(
+///     &TransactionQuery::new()
+///         .direction(TransactionDirection::Received)
+///         .min_amount(100)
+///         .limit(20),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionQuery {
+    direction: Option<TransactionDirection>,
+    sender: Option<Did>,
+    recipient: Option<Did>,
+    min_amount: Option<u64>,
+    max_amount: Option<u64>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    offset: usize,
+    limit: usize,
+}
+
+impl Default for TransactionQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionQuery {
+    /// Query matching every transaction, first page
+    pub fn new() -> Self {
+        Self {
+            direction: None,
+            sender: None,
+            recipient: None,
+            min_amount: None,
+            max_amount: None,
+            from_ts: None,
+            to_ts: None,
+            offset: 0,
+            limit: DEFAULT_TRANSACTION_PAGE_SIZE,
+        }
+    }
+
+    /// Only transactions in this direction
+    pub fn direction(mut self, direction: TransactionDirection) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Only IOUs issued by `did`
+    pub fn sender(mut self, did: Did) -> Self {
+        self.sender = Some(did);
+        self
+    }
+
+    /// Only IOUs paying `did` (any output of a multi-output IOU)
+    pub fn recipient(mut self, did: Did) -> Self {
+        self.recipient = Some(did);
+        self
+    }
+
+    /// Only IOUs whose total amount is at least `amount`
+    pub fn min_amount(mut self, amount: u64) -> Self {
+        self.min_amount = Some(amount);
+        self
+    }
+
+    /// Only IOUs whose total amount is at most `amount`
+    pub fn max_amount(mut self, amount: u64) -> Self {
+        self.max_amount = Some(amount);
+        self
+    }
+
+    /// Only transactions recorded at or after `timestamp`
+    pub fn since(mut self, timestamp: u64) -> Self {
+        self.from_ts = Some(timestamp);
+        self
+    }
+
+    /// Only transactions recorded at or before `timestamp`
+    pub fn until(mut self, timestamp: u64) -> Self {
+        self.to_ts = Some(timestamp);
+        self
+    }
+
+    /// Only transactions recorded between `from_ts` and `to_ts` (inclusive)
+    pub fn between(self, from_ts: u64, to_ts: u64) -> Self {
+        self.since(from_ts).until(to_ts)
+    }
+
+    /// Skip this many matching transactions
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return at most this many transactions
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Inclusive time bounds, open ends widened to the full range
+    pub fn time_range(&self) -> (u64, u64) {
+        (self.from_ts.unwrap_or(0), self.to_ts.unwrap_or(u64::MAX))
+    }
+
+    /// `(offset, limit)` of the requested page
+    pub fn pagination(&self) -> (usize, usize) {
+        (self.offset, self.limit)
+    }
+
+    /// Whether `record` passes every filter (pagination aside)
+    pub fn matches(&self, record: &TransactionRecord) -> bool {
+        let iou = record.iou().iou();
+        let (from_ts, to_ts) = self.time_range();
+        self.direction.is_none_or(|d| d == record.direction())
+            && self.sender.as_ref().is_none_or(|did| iou.sender() == did)
+            && self.recipient.as_ref().is_none_or(|did| iou.recipients().contains(&did))
+            && self.min_amount.is_none_or(|min| iou.amount() >= min)
+            && self.max_amount.is_none_or(|max| iou.amount() <= max)
+            && (from_ts..=to_ts).contains(&record.timestamp())
+    }
+
+    /// The same filters, advanced to the following page
+    pub fn next_page(&self) -> Self {
+        let mut next = self.clone();
+        next.offset = self.offset.saturating_add(self.limit);
+        next
+    }
+}
+
+/// One page of [`TransactionQuery`] results
+///
+/// Records are owned so a store-backed history can hand out pages without borrowing.
+#[derive(Clone, Debug)]
+pub struct TransactionPage {
+    records: Vec<TransactionRecord>,
+    offset: usize,
+    total: usize,
+}
+
+impl TransactionPage {
+    pub fn new(records: Vec<TransactionRecord>, offset: usize, total: usize) -> Self {
+        Self { records, offset, total }
+    }
+
+    /// Records on this page, newest first
+    pub fn records(&self) -> &[TransactionRecord] {
+        &self.records
+    }
+
+    pub fn into_records(self) -> Vec<TransactionRecord> {
+        self.records
+    }
+
+    /// Position of the first record among all matches
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of transactions matching the filters across all pages
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Whether more matches follow this page
+    pub fn has_more(&self) -> bool {
+        self.offset.saturating_add(self.records.len()) < self.total
+    }
+
+    /// Offset of the next page, if there is one
+    pub fn next_offset(&self) -> Option<usize> {
+        self.has_more().then(|| self.offset + self.records.len())
+    }
+}
+
+/// Anything that can answer [`TransactionQuery`]s
+///
+/// [`Vault`](crate::vault::Vault) answers from its in-memory log; a store-backed history
+/// can implement this to serve the same queries without loading every record.
+pub trait TransactionSource {
+    /// Matching transactions for the query's page, newest first
+    fn query_transactions(&self, query: &TransactionQuery) -> TransactionPage;
+}
+
+// ============================================================================
+// BALANCE OVER TIME
+// ============================================================================
+
+/// A transaction and the balance it left, from [`Vault::balance_history`](crate::vault::Vault::balance_history)
+#[derive(Clone, Debug)]
+pub struct BalancePoint {
+    record: TransactionRecord,
+    credit: u64,
+    debit: u64,
+    balance: u64,
+}
+
+impl BalancePoint {
+    pub(crate) fn new(record: TransactionRecord, credit: u64, debit: u64, balance: u64) -> Self {
+        Self { record, credit, debit, balance }
+    }
+
+    pub fn record(&self) -> &TransactionRecord {
+        &self.record
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.record.timestamp()
+    }
+
+    pub fn direction(&self) -> TransactionDirection {
+        self.record.direction()
+    }
+
+    /// Value the transaction added to the balance
+    pub fn credit(&self) -> u64 {
+        self.credit
+    }
+
+    /// Value the transaction took from the balance
+    pub fn debit(&self) -> u64 {
+        self.debit
+    }
+
+    /// Balance after the transaction
+    pub fn balance(&self) -> u64 {
+        self.balance
+    }
+}
+
+/// Itemized statement for a period, from [`Vault::statement`](crate::vault::Vault::statement)
+#[derive(Clone, Debug)]
+pub struct Statement {
+    start: u64,
+    end: u64,
+    opening_balance: u64,
+    items: Vec<BalancePoint>,
+}
+
+impl Statement {
+    pub(crate) fn new(start: u64, end: u64, opening_balance: u64, items: Vec<BalancePoint>) -> Self {
+        Self { start, end, opening_balance, items }
+    }
+
+    /// First second covered (inclusive)
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Last second covered (inclusive)
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Balance before the first transaction of the period
+    pub fn opening_balance(&self) -> u64 {
+        self.opening_balance
+    }
+
+    /// Balance after the last transaction of the period
+    pub fn closing_balance(&self) -> u64 {
+        self.items.last().map_or(self.opening_balance, BalancePoint::balance)
+    }
+
+    /// Transactions in the period, oldest first
+    pub fn items(&self) -> &[BalancePoint] {
+        &self.items
+    }
+
+    /// Sum of credits over the period
+    pub fn total_credits(&self) -> u64 {
+        self.items.iter().map(BalancePoint::credit).fold(0, u64::saturating_add)
+    }
+
+    /// Sum of debits over the period
+    pub fn total_debits(&self) -> u64 {
+        self.items.iter().map(BalancePoint::debit).fold(0, u64::saturating_add)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
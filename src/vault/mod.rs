@@ -1,9 +1,20 @@
 // Vault module - Tracks what you own (balance, UTXOs)
 
 mod balance;
+mod export;
+mod history;
+mod persistent;
 mod spending;
 mod utxo;
 
-pub use balance::{MemoryStats, TransactionDirection, TransactionRecord, Vault, VaultError, VaultState};
+pub use balance::{
+    MemoryStats, ReservationId, TransactionDirection, TransactionRecord, Vault, VaultError, VaultState,
+    DEFAULT_DUST_THRESHOLD, DEFAULT_RESERVATION_TIMEOUT_MS,
+};
+pub use export::{TransactionExport, COUNTERPARTY_SEPARATOR, TRANSACTION_EXPORT_COLUMNS};
+pub use history::{
+    BalancePoint, Statement, TransactionPage, TransactionQuery, TransactionSource, DEFAULT_TRANSACTION_PAGE_SIZE,
+};
+pub use persistent::PersistentVault;
 pub use spending::{SpentOutput, SpentOutputError, SpentOutputSet};
-pub use utxo::{LockInfo, UTXOId, UTXOSet, UTXOType, UTXO};
+pub use utxo::{CoinSelection, CoinSelectionStrategy, LockInfo, UTXOId, UTXOSet, UTXOType, UTXO};
@@ -0,0 +1,333 @@
+// Incremental vault persistence
+// Stores each vault record under its own key and writes only what changed
+
+use crate::identity::PublicKey;
+use crate::iou::IOUId;
+use crate::storage::{MeshStore, StoreError, StoreWrite};
+use crate::vault::balance::{TransactionRecord, Vault, VaultMeta};
+use crate::vault::spending::SpentOutput;
+use crate::vault::utxo::{UTXOId, UTXO};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::ops::{Deref, DerefMut};
+
+/// Key prefixes for vault records
+mod keys {
+    pub const META: &[u8] = b"vault:meta";
+    pub const UTXO: &[u8] = b"vault:utxo:";
+    pub const SPENT: &[u8] = b"vault:spent:";
+    pub const PROCESSED: &[u8] = b"vault:processed:";
+    pub const SETTLED: &[u8] = b"vault:settled:";
+    pub const TRANSACTION: &[u8] = b"vault:tx:";
+}
+
+/// A vault record touched since the last write
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum VaultChange {
+    /// Rewrite everything (first write, or state was imported)
+    Reset,
+    /// UTXO added, removed, locked or unlocked
+    Utxo(UTXOId),
+    /// Spent output recorded
+    Spent(UTXOId),
+    /// Processed IOU recorded or pruned
+    Processed(IOUId),
+    /// Received IOU marked settled
+    Settled(IOUId),
+    /// Transaction record appended at this position
+    Transaction(usize),
+}
+
+// ============================================================================
+// PERSISTENT VAULT
+// ============================================================================
+
+/// A vault that writes only its changes to a `MeshStore`
+///
+/// UTXOs, spent outputs, processed and settled IOUs and transaction records
+/// each live under their own key, so a payment costs a handful of small writes
+/// instead of re-serializing the whole vault. Dereferences to `Vault`, so it is used
+/// like one; mutate through `update`, or directly followed by `persist`.
+/// Each `persist` is applied as one atomic batch.
+pub struct PersistentVault {
+    vault: Vault,
+    store: MeshStore,
+}
+
+impl PersistentVault {
+    /// Attach `vault` to `store`; the first `persist` writes it in full
+    pub fn new(store: MeshStore, mut vault: Vault) -> Self {
+        vault.start_journal(vec![VaultChange::Reset]);
+        Self { vault, store }
+    }
+
+    /// Load the vault in `store`, or create and write an empty one for `owner`
+    ///
+    /// A stored vault is returned as is, whatever its owner.
+    pub fn open(store: MeshStore, owner: PublicKey) -> Result<Self, StoreError> {
+        if let Some(vault) = Self::load(store.clone())? {
+            return Ok(vault);
+        }
+        let mut vault = Self::new(store, Vault::new(owner));
+        vault.persist()?;
+        Ok(vault)
+    }
+
+    /// Load a vault previously written to `store`, if any
+    pub fn load(store: MeshStore) -> Result<Option<Self>, StoreError> {
+        let meta: VaultMeta = match store.get_raw(keys::META)? {
+            Some(bytes) => decode(&bytes)?,
+            None => return Ok(None),
+        };
+
+        let spent_outputs = store
+            .scan_prefix(keys::SPENT)?
+            .iter()
+            .map(|(_, value)| decode::<SpentOutput>(value))
+            .collect::<Result<Vec<_>, _>>()?;
+        let spent_ids: HashSet<UTXOId> = spent_outputs.iter().map(|s| s.utxo_id().clone()).collect();
+
+        // A UTXO whose removal didn't land is still spent; never load it as spendable
+        let mut utxos = Vec::new();
+        let mut stale = Vec::new();
+        for (_, value) in store.scan_prefix(keys::UTXO)? {
+            let utxo: UTXO = decode(&value)?;
+            if spent_ids.contains(utxo.id()) {
+                stale.push(VaultChange::Utxo(utxo.id().clone()));
+            } else {
+                utxos.push(utxo);
+            }
+        }
+
+        let mut processed_ious = HashMap::new();
+        for (key, value) in store.scan_prefix(keys::PROCESSED)? {
+            let id = iou_id_from_key(&key, keys::PROCESSED)?;
+            let timestamp: [u8; 8] = value
+                .as_slice()
+                .try_into()
+                .map_err(|_| StoreError::DeserializationFailed("Invalid processed IOU timestamp".to_string()))?;
+            processed_ious.insert(IOUId::from_bytes(id), u64::from_le_bytes(timestamp));
+        }
+
+        let mut settled_ious = HashSet::new();
+        for key in store.list_keys_with_prefix(keys::SETTLED)? {
+            settled_ious.insert(IOUId::from_bytes(iou_id_from_key(&key, keys::SETTLED)?));
+        }
+
+        // Keys sort by position; stop at the first gap
+        let mut transactions = Vec::new();
+        for (key, value) in store.scan_prefix(keys::TRANSACTION)? {
+            if key[keys::TRANSACTION.len()..] != transaction_position(transactions.len()) {
+                break;
+            }
+            transactions.push(decode::<TransactionRecord>(&value)?);
+        }
+
+        let mut vault = Vault::from_parts(meta, utxos, spent_outputs, processed_ious, settled_ious, transactions);
+        vault.start_journal(stale);
+        Ok(Some(Self { vault, store }))
+    }
+
+    /// The vault
+    pub fn vault(&self) -> &Vault {
+        &self.vault
+    }
+
+    /// Mutable access; changes are written on the next `persist`
+    pub fn vault_mut(&mut self) -> &mut Vault {
+        &mut self.vault
+    }
+
+    /// The backing store
+    pub fn store(&self) -> &MeshStore {
+        &self.store
+    }
+
+    /// Run `f` against the vault, then persist whatever it changed
+    pub fn update<T>(&mut self, f: impl FnOnce(&mut Vault) -> T) -> Result<T, StoreError> {
+        let result = f(&mut self.vault);
+        self.persist()?;
+        Ok(result)
+    }
+
+    /// Writes the next `persist` will apply, in order
+    pub fn pending_writes(&self) -> Result<Vec<StoreWrite>, StoreError> {
+        let changes = self.vault.pending_changes();
+        if changes.contains(&VaultChange::Reset) {
+            return full_writes(&self.vault, &self.store);
+        }
+        delta_writes(&self.vault, changes)
+    }
+
+    /// Write pending changes as one atomic batch. Returns the number of writes.
+    pub fn persist(&mut self) -> Result<usize, StoreError> {
+        let writes = self.pending_writes()?;
+        self.store.apply_batch(&writes)?;
+        self.vault.take_journal();
+        Ok(writes.len())
+    }
+
+    /// Detach from the store, dropping unpersisted changes
+    pub fn into_inner(mut self) -> Vault {
+        self.vault.stop_journal();
+        self.vault
+    }
+}
+
+impl Deref for PersistentVault {
+    type Target = Vault;
+
+    fn deref(&self) -> &Vault {
+        &self.vault
+    }
+}
+
+/// Changes made through the vault are journaled and written on the next `persist`
+impl DerefMut for PersistentVault {
+    fn deref_mut(&mut self) -> &mut Vault {
+        &mut self.vault
+    }
+}
+
+// ============================================================================
+// WRITE PLANNING
+// ============================================================================
+
+/// Writes for the changed records only.
+/// Spent outputs go before UTXO removals so a torn write never frees spent funds.
+fn delta_writes(vault: &Vault, changes: &[VaultChange]) -> Result<Vec<StoreWrite>, StoreError> {
+    let mut seen = HashSet::new();
+    let mut transactions = Vec::new();
+    let mut spent = Vec::new();
+    let mut processed = Vec::new();
+    let mut settled = Vec::new();
+    let mut utxos = Vec::new();
+
+    for change in changes {
+        if !seen.insert(change) {
+            continue;
+        }
+        match change {
+            VaultChange::Reset => {}
+            VaultChange::Transaction(position) => {
+                let key = transaction_key(*position);
+                transactions.push(match vault.transaction_at(*position) {
+                    Some(record) => put(key, record)?,
+                    None => StoreWrite::Delete { key },
+                });
+            }
+            VaultChange::Spent(id) => {
+                let key = prefixed(keys::SPENT, id.as_bytes());
+                spent.push(match vault.get_spent_output(id) {
+                    Some(output) => put(key, output)?,
+                    None => StoreWrite::Delete { key },
+                });
+            }
+            VaultChange::Processed(id) => {
+                let key = prefixed(keys::PROCESSED, id.as_bytes());
+                processed.push(match vault.get_processed_iou_timestamp(id) {
+                    Some(timestamp) => StoreWrite::Put { key, value: timestamp.to_le_bytes().to_vec() },
+                    None => StoreWrite::Delete { key },
+                });
+            }
+            VaultChange::Settled(id) => {
+                let key = prefixed(keys::SETTLED, id.as_bytes());
+                settled.push(if vault.is_settled(id) {
+                    StoreWrite::Put { key, value: Vec::new() }
+                } else {
+                    StoreWrite::Delete { key }
+                });
+            }
+            VaultChange::Utxo(id) => {
+                let key = prefixed(keys::UTXO, id.as_bytes());
+                utxos.push(match vault.get_utxo(id) {
+                    Some(utxo) => put(key, utxo)?,
+                    None => StoreWrite::Delete { key },
+                });
+            }
+        }
+    }
+
+    let mut writes = vec![put(keys::META.to_vec(), &vault.meta())?];
+    writes.extend(transactions);
+    writes.extend(spent);
+    writes.extend(processed);
+    writes.extend(settled);
+    writes.extend(utxos);
+    Ok(writes)
+}
+
+/// Writes that replace whatever vault records `store` holds with `vault`
+fn full_writes(vault: &Vault, store: &MeshStore) -> Result<Vec<StoreWrite>, StoreError> {
+    let mut writes = vec![put(keys::META.to_vec(), &vault.meta())?];
+
+    let mut position = 0;
+    while let Some(record) = vault.transaction_at(position) {
+        writes.push(put(transaction_key(position), record)?);
+        position += 1;
+    }
+    for output in vault.spent_outputs() {
+        writes.push(put(prefixed(keys::SPENT, output.utxo_id().as_bytes()), output)?);
+    }
+    for (id, timestamp) in vault.processed_ious() {
+        writes.push(StoreWrite::Put {
+            key: prefixed(keys::PROCESSED, id.as_bytes()),
+            value: timestamp.to_le_bytes().to_vec(),
+        });
+    }
+    for id in vault.settled_ious() {
+        writes.push(StoreWrite::Put {
+            key: prefixed(keys::SETTLED, id.as_bytes()),
+            value: Vec::new(),
+        });
+    }
+    for utxo in vault.utxo_set() {
+        writes.push(put(prefixed(keys::UTXO, utxo.id().as_bytes()), utxo)?);
+    }
+
+    let written: HashSet<Vec<u8>> = writes
+        .iter()
+        .filter_map(|write| match write {
+            StoreWrite::Put { key, .. } => Some(key.clone()),
+            StoreWrite::Delete { .. } => None,
+        })
+        .collect();
+    for prefix in [keys::TRANSACTION, keys::SPENT, keys::PROCESSED, keys::SETTLED, keys::UTXO] {
+        for key in store.list_keys_with_prefix(prefix)? {
+            if !written.contains(&key) {
+                writes.push(StoreWrite::Delete { key });
+            }
+        }
+    }
+
+    Ok(writes)
+}
+
+fn put<T: Serialize>(key: Vec<u8>, value: &T) -> Result<StoreWrite, StoreError> {
+    let value = postcard::to_allocvec(value).map_err(|e| StoreError::SerializationFailed(e.to_string()))?;
+    Ok(StoreWrite::Put { key, value })
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, StoreError> {
+    postcard::from_bytes(bytes).map_err(|e| StoreError::DeserializationFailed(e.to_string()))
+}
+
+fn iou_id_from_key(key: &[u8], prefix: &[u8]) -> Result<[u8; 32], StoreError> {
+    key[prefix.len()..]
+        .try_into()
+        .map_err(|_| StoreError::DeserializationFailed("Invalid IOU key".to_string()))
+}
+
+fn prefixed(prefix: &[u8], id: &[u8]) -> Vec<u8> {
+    [prefix, id].concat()
+}
+
+/// Big-endian so keys sort in append order
+fn transaction_position(position: usize) -> [u8; 8] {
+    (position as u64).to_be_bytes()
+}
+
+fn transaction_key(position: usize) -> Vec<u8> {
+    prefixed(keys::TRANSACTION, &transaction_position(position))
+}
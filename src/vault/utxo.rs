@@ -14,8 +14,38 @@ pub enum UTXOType {
     Received,
     /// UTXO from change after sending a payment
     Change,
+    /// UTXO restoring funds of a sent payment the recipient rejected
+    Refund,
+    /// UTXO merging several smaller UTXOs of the same owner
+    Consolidated,
 }
 
+/// Strategy used to pick which UTXOs fund a payment
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoinSelectionStrategy {
+    /// Exact match if available, otherwise spend the largest UTXOs first (default)
+    #[default]
+    LargestFirst,
+    /// Spend the smallest UTXOs first (consolidates dust, more inputs)
+    SmallestFirst,
+    /// Pick the combination that leaves the least change
+    MinimizeChange,
+    /// Use as few UTXOs as possible, preferring the smallest single UTXO that covers the amount
+    MinimizeInputs,
+    /// Branch-and-bound search for an exact match; accepts a selection whose change is at
+    /// most `tolerance`, otherwise falls back to LargestFirst. Any leftover is still returned
+    /// as change, so no value is discarded.
+    BranchAndBoundExactMatch { tolerance: u64 },
+    /// Spend the UTXOs that entered the set earliest
+    OldestFirst,
+}
+
+/// Short name for [`CoinSelectionStrategy`]
+pub type CoinSelection = CoinSelectionStrategy;
+
+/// Upper bound on branches explored by the MinimizeChange and BranchAndBound searches
+const MIN_CHANGE_MAX_TRIES: usize = 100_000;
+
 /// Unique identifier for a UTXO
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct UTXOId([u8; 32]);
@@ -33,6 +63,8 @@ impl UTXOId {
         match utxo_type {
             UTXOType::Received => hasher.update(b"utxo:received:"),
             UTXOType::Change => hasher.update(b"utxo:change:"),
+            UTXOType::Refund => hasher.update(b"utxo:refund:"),
+            UTXOType::Consolidated => hasher.update(b"utxo:consolidated:"),
         }
         hasher.update(iou_id.as_bytes());
         let result = hasher.finalize();
@@ -115,6 +147,8 @@ pub struct UTXO {
     utxo_type: UTXOType,
     /// Whether this UTXO is locked for a pending transaction
     locked: bool,
+    /// Order in which this UTXO entered its set (0 = not yet added)
+    sequence: u64,
 }
 
 impl UTXO {
@@ -133,6 +167,7 @@ impl UTXO {
             source_iou_id,
             utxo_type,
             locked: false,
+            sequence: 0,
         }
     }
 
@@ -141,6 +176,11 @@ impl UTXO {
         Self::with_type(owner, amount, source_iou_id, UTXOType::Change)
     }
 
+    /// Order in which this UTXO entered its set (lower = older, 0 = not yet added)
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
     /// Get the unique ID of this UTXO
     pub fn id(&self) -> &UTXOId {
         &self.id
@@ -186,6 +226,8 @@ impl UTXO {
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct UTXOSet {
     utxos: HashMap<UTXOId, UTXO>,
+    /// Last sequence number handed out by `add`
+    last_sequence: u64,
 }
 
 impl UTXOSet {
@@ -193,14 +235,31 @@ impl UTXOSet {
     pub fn new() -> Self {
         Self {
             utxos: HashMap::new(),
+            last_sequence: 0,
         }
     }
 
-    /// Add a UTXO to the set
-    pub fn add(&mut self, utxo: UTXO) {
+    /// Add a UTXO to the set.
+    /// A UTXO added for the first time is stamped with the next sequence number;
+    /// one that already has a sequence (e.g. re-added after a rollback) keeps it.
+    pub fn add(&mut self, mut utxo: UTXO) {
+        if utxo.sequence == 0 {
+            self.last_sequence += 1;
+            utxo.sequence = self.last_sequence;
+        }
         self.utxos.insert(utxo.id().clone(), utxo);
     }
 
+    /// Last sequence number handed out by `add`
+    pub(crate) fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    /// Resume sequence numbering after a restore
+    pub(crate) fn set_last_sequence(&mut self, sequence: u64) {
+        self.last_sequence = sequence;
+    }
+
     /// Remove a UTXO from the set by ID
     pub fn remove(&mut self, id: &UTXOId) -> Option<UTXO> {
         self.utxos.remove(id)
@@ -292,6 +351,247 @@ impl UTXOSet {
         }
     }
 
+    /// Select UTXOs to cover a specific amount using the given strategy
+    /// Returns (selected UTXOs, change amount) or None if insufficient funds
+    pub fn select_with_strategy(
+        &self,
+        amount: u64,
+        strategy: CoinSelectionStrategy,
+    ) -> Option<(Vec<UTXO>, u64)> {
+        match strategy {
+            CoinSelectionStrategy::LargestFirst => self.select_for_amount(amount),
+            CoinSelectionStrategy::SmallestFirst => self.select_smallest_first(amount),
+            CoinSelectionStrategy::MinimizeChange => self.select_minimize_change(amount),
+            CoinSelectionStrategy::MinimizeInputs => self.select_minimize_inputs(amount),
+            CoinSelectionStrategy::BranchAndBoundExactMatch { tolerance } => {
+                self.select_branch_and_bound(amount, tolerance)
+            }
+            CoinSelectionStrategy::OldestFirst => self.select_oldest_first(amount),
+        }
+    }
+
+    /// Select like [`UTXOSet::select_with_strategy`], but leave UTXOs worth less than
+    /// `dust_threshold` unspent unless the larger ones can't cover the amount alone
+    pub fn select_avoiding_dust(
+        &self,
+        amount: u64,
+        strategy: CoinSelectionStrategy,
+        dust_threshold: u64,
+    ) -> Option<(Vec<UTXO>, u64)> {
+        let has_dust = self.utxos.values().any(|u| !u.is_locked() && u.amount() < dust_threshold);
+        if has_dust {
+            let without_dust = UTXOSet {
+                utxos: self.utxos
+                    .iter()
+                    .filter(|(_, u)| u.amount() >= dust_threshold)
+                    .map(|(id, u)| (id.clone(), u.clone()))
+                    .collect(),
+                last_sequence: self.last_sequence,
+            };
+            if let Some(selection) = without_dust.select_with_strategy(amount, strategy) {
+                return Some(selection);
+            }
+        }
+        self.select_with_strategy(amount, strategy)
+    }
+
+    /// UTXOs worth less than `threshold` (locked ones included), smallest first
+    pub fn dust(&self, threshold: u64) -> Vec<&UTXO> {
+        let mut dust: Vec<_> = self.utxos.values().filter(|u| u.amount() < threshold).collect();
+        dust.sort_by(|a, b| {
+            a.amount()
+                .cmp(&b.amount())
+                .then_with(|| a.id().as_bytes().cmp(b.id().as_bytes()))
+        });
+        dust
+    }
+
+    /// Alias for [`UTXOSet::select_with_strategy`]
+    pub fn select_for_amount_with_strategy(
+        &self,
+        amount: u64,
+        strategy: CoinSelection,
+    ) -> Option<(Vec<UTXO>, u64)> {
+        self.select_with_strategy(amount, strategy)
+    }
+
+    /// Unlocked UTXOs sorted by amount (ascending), ties broken by ID for determinism
+    fn unlocked_sorted(&self) -> Vec<&UTXO> {
+        let mut available: Vec<_> = self.utxos.values().filter(|u| !u.is_locked()).collect();
+        available.sort_by(|a, b| {
+            a.amount()
+                .cmp(&b.amount())
+                .then_with(|| a.id().as_bytes().cmp(b.id().as_bytes()))
+        });
+        available
+    }
+
+    /// Greedily take UTXOs in the given order until the amount is covered
+    fn select_greedy<'a>(
+        candidates: impl Iterator<Item = &'a UTXO>,
+        amount: u64,
+    ) -> Option<(Vec<UTXO>, u64)> {
+        let mut selected = Vec::new();
+        let mut total = 0u64;
+
+        for utxo in candidates {
+            if total >= amount {
+                break;
+            }
+            selected.push(utxo.clone());
+            total = total.saturating_add(utxo.amount());
+        }
+
+        if total >= amount {
+            Some((selected, total - amount))
+        } else {
+            None
+        }
+    }
+
+    fn select_smallest_first(&self, amount: u64) -> Option<(Vec<UTXO>, u64)> {
+        if amount == 0 {
+            return Some((vec![], 0));
+        }
+        Self::select_greedy(self.unlocked_sorted().into_iter(), amount)
+    }
+
+    fn select_minimize_inputs(&self, amount: u64) -> Option<(Vec<UTXO>, u64)> {
+        if amount == 0 {
+            return Some((vec![], 0));
+        }
+
+        let available = self.unlocked_sorted();
+
+        // A single UTXO is the fewest inputs possible - take the smallest that covers
+        if let Some(utxo) = available.iter().find(|u| u.amount() >= amount) {
+            return Some((vec![(*utxo).clone()], utxo.amount() - amount));
+        }
+
+        // Otherwise largest-first gives the fewest inputs
+        Self::select_greedy(available.into_iter().rev(), amount)
+    }
+
+    fn select_oldest_first(&self, amount: u64) -> Option<(Vec<UTXO>, u64)> {
+        if amount == 0 {
+            return Some((vec![], 0));
+        }
+
+        let mut available: Vec<_> = self.utxos.values().filter(|u| !u.is_locked()).collect();
+        available.sort_by(|a, b| {
+            a.sequence()
+                .cmp(&b.sequence())
+                .then_with(|| a.id().as_bytes().cmp(b.id().as_bytes()))
+        });
+        Self::select_greedy(available.into_iter(), amount)
+    }
+
+    fn select_branch_and_bound(&self, amount: u64, tolerance: u64) -> Option<(Vec<UTXO>, u64)> {
+        if amount == 0 {
+            return Some((vec![], 0));
+        }
+
+        match self.search_least_change(amount) {
+            Some((utxos, change)) if change <= tolerance => Some((utxos, change)),
+            _ => self.select_for_amount(amount),
+        }
+    }
+
+    fn select_minimize_change(&self, amount: u64) -> Option<(Vec<UTXO>, u64)> {
+        if amount == 0 {
+            return Some((vec![], 0));
+        }
+
+        self.search_least_change(amount)
+            .or_else(|| self.select_for_amount(amount))
+    }
+
+    /// Bounded search for the selection leaving the least change (fewest inputs on ties).
+    /// Returns None if funds are insufficient or the search budget ran out without a result.
+    fn search_least_change(&self, amount: u64) -> Option<(Vec<UTXO>, u64)> {
+        // Depth-first search over descending amounts with pruning
+        let mut available = self.unlocked_sorted();
+        available.reverse();
+
+        // suffix[i] = sum of available[i..], used to prune unreachable branches
+        let mut suffix = vec![0u64; available.len() + 1];
+        for i in (0..available.len()).rev() {
+            suffix[i] = suffix[i + 1].saturating_add(available[i].amount());
+        }
+        if suffix[0] < amount {
+            return None;
+        }
+
+        let mut best: Option<(Vec<usize>, u64)> = None;
+        let mut current = Vec::new();
+        let mut tries = 0usize;
+
+        Self::search_min_change(
+            &available, &suffix, amount, 0, 0, &mut current, &mut best, &mut tries,
+        );
+
+        best.map(|(indices, change)| {
+            (indices.into_iter().map(|i| available[i].clone()).collect(), change)
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_min_change(
+        available: &[&UTXO],
+        suffix: &[u64],
+        amount: u64,
+        index: usize,
+        total: u64,
+        current: &mut Vec<usize>,
+        best: &mut Option<(Vec<usize>, u64)>,
+        tries: &mut usize,
+    ) {
+        *tries += 1;
+        if *tries > MIN_CHANGE_MAX_TRIES {
+            return;
+        }
+
+        if total >= amount {
+            let change = total - amount;
+            let better = match best {
+                Some((indices, best_change)) => {
+                    change < *best_change
+                        || (change == *best_change && current.len() < indices.len())
+                }
+                None => true,
+            };
+            if better {
+                *best = Some((current.clone(), change));
+            }
+            return;
+        }
+
+        // Prune: not enough left to cover, or already an exact match found
+        if index >= available.len() || total.saturating_add(suffix[index]) < amount {
+            return;
+        }
+        if matches!(best, Some((_, 0))) {
+            return;
+        }
+
+        // Include available[index]
+        current.push(index);
+        Self::search_min_change(
+            available,
+            suffix,
+            amount,
+            index + 1,
+            total.saturating_add(available[index].amount()),
+            current,
+            best,
+            tries,
+        );
+        current.pop();
+
+        // Exclude available[index]
+        Self::search_min_change(available, suffix, amount, index + 1, total, current, best, tries);
+    }
+
     /// Iterate over all UTXOs
     pub fn iter(&self) -> impl Iterator<Item = &UTXO> {
         self.utxos.values()
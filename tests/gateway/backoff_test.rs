@@ -0,0 +1,238 @@
+// Settler Backoff Tests
+// Tests the exponential retry delays, their jitter and cap, and Retry-After hints
+
+use async_trait::async_trait;
+use p2pmesh::gateway::{
+    parse_retry_after, SettlementBatch, SettlementTarget, Settler, SettlerConfig, SettlerError,
+};
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::IOUBuilder;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn secs(s: u64) -> Duration {
+    Duration::from_secs(s)
+}
+
+/// 1s doubling up to 10s, without jitter
+fn doubling() -> SettlerConfig {
+    SettlerConfig::new()
+        .with_retry_delay_secs(1)
+        .with_retry_multiplier(2.0)
+        .with_max_retry_delay_secs(10)
+        .with_retry_jitter(0.0)
+}
+
+fn create_test_batch() -> SettlementBatch {
+    let alice = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(100)
+        .build()
+        .unwrap();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&iou);
+    batch
+}
+
+/// Fails `failures` times asking to retry after 0s, then succeeds; counts hints read
+struct ThrottledTarget {
+    failures: usize,
+    calls: AtomicUsize,
+    hints: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl SettlementTarget for ThrottledTarget {
+    async fn settle(&self, _batch: &SettlementBatch) -> Result<String, String> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err("busy (retry after 0s)".to_string());
+        }
+        Ok("tx-throttled".to_string())
+    }
+
+    fn retry_after(&self, error: &str) -> Option<Duration> {
+        self.hints.fetch_add(1, Ordering::SeqCst);
+        parse_retry_after(error)
+    }
+}
+
+// ============================================================================
+// BACKOFF SEQUENCE
+// ============================================================================
+
+#[test]
+fn test_delay_grows_by_multiplier_up_to_cap() {
+    let config = doubling();
+
+    let delays: Vec<_> = (1..=6).map(|retry| config.backoff_delay(retry)).collect();
+
+    assert_eq!(delays, vec![secs(1), secs(2), secs(4), secs(8), secs(10), secs(10)]);
+}
+
+#[test]
+fn test_multiplier_of_one_keeps_delay_flat() {
+    let config = doubling().with_retry_multiplier(1.0);
+
+    assert!((1..=5).all(|retry| config.backoff_delay(retry) == secs(1)));
+}
+
+#[test]
+fn test_fractional_multiplier() {
+    let config = doubling().with_retry_multiplier(1.5).with_max_retry_delay_secs(60);
+
+    assert_eq!(config.backoff_delay(3), Duration::from_millis(2250));
+}
+
+#[test]
+fn test_cap_below_base_wins() {
+    let config = doubling().with_retry_delay_secs(30);
+
+    assert_eq!(config.backoff_delay(1), secs(10));
+}
+
+#[test]
+fn test_late_retries_saturate_at_cap() {
+    let config = doubling();
+
+    assert_eq!(config.backoff_delay(10_000), secs(10));
+    assert_eq!(config.backoff_delay(u32::MAX), secs(10));
+}
+
+#[test]
+fn test_zero_base_never_waits() {
+    let config = doubling().with_retry_delay_secs(0).with_retry_jitter(0.5);
+    let mut rng = StdRng::seed_from_u64(7);
+
+    assert!((1..=8).all(|retry| config.retry_delay(retry, None, &mut rng).is_zero()));
+}
+
+// ============================================================================
+// JITTER
+// ============================================================================
+
+#[test]
+fn test_jitter_stays_within_fraction() {
+    let config = doubling().with_retry_jitter(0.25);
+    let mut rng = StdRng::seed_from_u64(42);
+
+    for retry in 1..=6 {
+        let backoff = config.backoff_delay(retry);
+        for _ in 0..50 {
+            let delay = config.retry_delay(retry, None, &mut rng);
+            assert!(delay <= backoff);
+            assert!(delay >= backoff.mul_f64(0.75));
+        }
+    }
+}
+
+#[test]
+fn test_jitter_spreads_delays() {
+    let config = doubling().with_retry_jitter(0.5);
+    let mut rng = StdRng::seed_from_u64(1);
+
+    let delays: Vec<_> = (0..20).map(|_| config.retry_delay(4, None, &mut rng)).collect();
+
+    assert!(delays.iter().any(|d| *d != delays[0]));
+}
+
+#[test]
+fn test_no_jitter_is_deterministic() {
+    let config = doubling();
+    let mut rng = StdRng::seed_from_u64(3);
+
+    assert_eq!(config.retry_delay(3, None, &mut rng), secs(4));
+}
+
+// ============================================================================
+// RETRY-AFTER HINTS
+// ============================================================================
+
+#[test]
+fn test_hint_longer_than_backoff_is_honoured() {
+    let config = doubling();
+    let mut rng = StdRng::seed_from_u64(0);
+
+    assert_eq!(config.retry_delay(1, Some(secs(7)), &mut rng), secs(7));
+}
+
+#[test]
+fn test_hint_shorter_than_backoff_keeps_backoff() {
+    let config = doubling();
+    let mut rng = StdRng::seed_from_u64(0);
+
+    assert_eq!(config.retry_delay(4, Some(secs(1)), &mut rng), secs(8));
+}
+
+#[test]
+fn test_hint_is_capped() {
+    let config = doubling();
+    let mut rng = StdRng::seed_from_u64(0);
+
+    assert_eq!(config.retry_delay(1, Some(secs(3600)), &mut rng), secs(10));
+}
+
+#[test]
+fn test_parse_retry_after() {
+    assert_eq!(parse_retry_after("returned 429: {} (retry after 30s)"), Some(secs(30)));
+    assert_eq!(parse_retry_after("Retry After 5s"), Some(secs(5)));
+    assert_eq!(parse_retry_after("returned 500: {}"), None);
+    assert_eq!(parse_retry_after("retry after soon"), None);
+}
+
+#[tokio::test]
+async fn test_process_reads_hint_after_each_failure() {
+    let hints = Arc::new(AtomicUsize::new(0));
+    let target = ThrottledTarget {
+        failures: 2,
+        calls: AtomicUsize::new(0),
+        hints: hints.clone(),
+    };
+    let config = SettlerConfig::new().with_max_retries(3).with_retry_delay_secs(0);
+    let mut settler = Settler::with_target(config, Box::new(target));
+
+    let batch = create_test_batch();
+    let batch_id = batch.id().clone();
+    settler.submit(batch).await.unwrap();
+
+    let result = settler.process(&batch_id).await.unwrap();
+
+    assert!(result.is_success());
+    assert_eq!(result.attempts(), 3);
+    assert_eq!(hints.load(Ordering::SeqCst), 2);
+}
+
+// ============================================================================
+// VALIDATION
+// ============================================================================
+
+#[test]
+fn test_default_backoff_is_valid() {
+    let config = SettlerConfig::default();
+
+    assert!(config.validate().is_ok());
+    assert!(config.retry_multiplier > 1.0);
+    assert!(config.max_retry_delay_secs >= config.retry_delay_secs);
+}
+
+#[test]
+fn test_validate_rejects_shrinking_multiplier() {
+    let config = SettlerConfig::new().with_retry_multiplier(0.5);
+
+    assert!(matches!(config.validate(), Err(SettlerError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validate_rejects_jitter_out_of_range() {
+    assert!(SettlerConfig::new().with_retry_jitter(1.5).validate().is_err());
+    assert!(SettlerConfig::new().with_retry_jitter(-0.1).validate().is_err());
+}
@@ -2,13 +2,14 @@
 // Tests for gathering IOUs for settlement
 
 use p2pmesh::identity::{Did, Keypair};
-use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::iou::{Denomination, IOUBuilder, SignedIOU};
 use p2pmesh::ledger::{MeshState, NodeId};
 use p2pmesh::gateway::{
     Collector, CollectorConfig, CollectorError,
     SettlementBatch, BatchId, BatchStatus,
-    SettlementEntry, NetPosition,
+    SettlementEntry, Transfer,
 };
+use std::collections::HashMap;
 
 // ============================================================================
 // HELPER FUNCTIONS
@@ -42,7 +43,8 @@ fn test_collector_config_default() {
 
     assert!(config.min_batch_size > 0);
     assert!(config.max_batch_size > config.min_batch_size);
-    assert!(config.min_iou_age_secs >= 0);
+    // Unsigned, so the minimum age can never be negative
+    let _: u64 = config.min_iou_age_secs;
 }
 
 #[test]
@@ -403,7 +405,7 @@ fn test_settlement_batch_add_entry() {
     let bob = Keypair::generate();
     let iou = create_test_iou(&alice, &bob, 100, 1);
 
-    let entry = SettlementEntry::from_iou(&iou);
+    let entry = SettlementEntry::from_iou(&iou).remove(0);
     batch.add_entry(entry);
 
     assert_eq!(batch.entries().len(), 1);
@@ -419,7 +421,7 @@ fn test_settlement_batch_multiple_entries() {
 
     for i in 1..=5 {
         let iou = create_test_iou(&alice, &bob, i * 100, i);
-        batch.add_entry(SettlementEntry::from_iou(&iou));
+        batch.add_iou(&iou);
     }
 
     assert_eq!(batch.entries().len(), 5);
@@ -466,7 +468,7 @@ fn test_settlement_entry_from_iou() {
     let bob = Keypair::generate();
     let iou = create_test_iou(&alice, &bob, 500, 1);
 
-    let entry = SettlementEntry::from_iou(&iou);
+    let entry = SettlementEntry::from_iou(&iou).remove(0);
 
     assert_eq!(entry.amount(), 500);
     assert_eq!(entry.sender(), iou.iou().sender());
@@ -474,13 +476,90 @@ fn test_settlement_entry_from_iou() {
     assert_eq!(entry.iou_id(), &iou.id());
 }
 
+#[test]
+fn test_settlement_entry_from_multi_output_iou() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let carol = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .add_output(Did::from_public_key(&bob.public_key()), 60)
+        .add_output(Did::from_public_key(&carol.public_key()), 40)
+        .build()
+        .unwrap();
+
+    let entries = SettlementEntry::from_iou(&iou);
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].recipient(), &Did::from_public_key(&bob.public_key()));
+    assert_eq!(entries[0].amount(), 60);
+    assert_eq!(entries[1].recipient(), &Did::from_public_key(&carol.public_key()));
+    assert_eq!(entries[1].amount(), 40);
+    for entry in &entries {
+        assert_eq!(entry.sender(), iou.iou().sender());
+        assert_eq!(entry.iou_id(), &iou.id());
+    }
+}
+
+#[test]
+fn test_multi_output_iou_net_positions() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let carol = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .add_output(Did::from_public_key(&bob.public_key()), 60)
+        .add_output(Did::from_public_key(&carol.public_key()), 40)
+        .build()
+        .unwrap();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&iou);
+
+    assert_eq!(batch.entries().len(), 2);
+    assert_eq!(batch.total_amount(), 100);
+    let positions = batch.calculate_net_positions();
+    let net = |kp: &Keypair| {
+        let did = Did::from_public_key(&kp.public_key());
+        positions.iter().find(|p| p.party() == &did).unwrap().net_amount()
+    };
+    assert_eq!(net(&alice), -100);
+    assert_eq!(net(&bob), 60);
+    assert_eq!(net(&carol), 40);
+}
+
+#[test]
+fn test_collect_by_recipient_finds_any_output() {
+    let config = CollectorConfig::new().with_min_batch_size(1);
+    let mut collector = Collector::new(config);
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let carol = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .add_output(Did::from_public_key(&bob.public_key()), 60)
+        .add_output(Did::from_public_key(&carol.public_key()), 40)
+        .build()
+        .unwrap();
+    let state = create_mesh_with_ious(NodeId::generate(), vec![(iou, &alice)]);
+
+    let collected = collector
+        .collect_by_recipient(&state, &Did::from_public_key(&carol.public_key()))
+        .unwrap();
+
+    assert_eq!(collected, 1);
+    let batch = collector.create_batch().unwrap();
+    assert_eq!(batch.entries().len(), 2);
+    assert_eq!(batch.total_amount(), 100);
+}
+
 #[test]
 fn test_settlement_entry_serialization() {
     let alice = Keypair::generate();
     let bob = Keypair::generate();
     let iou = create_test_iou(&alice, &bob, 100, 1);
 
-    let entry = SettlementEntry::from_iou(&iou);
+    let entry = SettlementEntry::from_iou(&iou).remove(0);
     let bytes = entry.to_bytes();
 
     assert!(!bytes.is_empty());
@@ -505,8 +584,8 @@ fn test_net_position_single_direction() {
     let mut batch = SettlementBatch::new();
 
     // Alice owes Bob 300 (100 + 200)
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&alice, &bob, 100, 1)));
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&alice, &bob, 200, 2)));
+    batch.add_iou(&create_test_iou(&alice, &bob, 100, 1));
+    batch.add_iou(&create_test_iou(&alice, &bob, 200, 2));
 
     let positions = batch.calculate_net_positions();
 
@@ -528,9 +607,9 @@ fn test_net_position_bidirectional() {
     let mut batch = SettlementBatch::new();
 
     // Alice → Bob: 300
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&alice, &bob, 300, 1)));
+    batch.add_iou(&create_test_iou(&alice, &bob, 300, 1));
     // Bob → Alice: 100
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&bob, &alice, 100, 2)));
+    batch.add_iou(&create_test_iou(&bob, &alice, 100, 2));
 
     let positions = batch.calculate_net_positions();
 
@@ -555,11 +634,11 @@ fn test_net_position_three_parties() {
     let mut batch = SettlementBatch::new();
 
     // Alice → Bob: 100
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&alice, &bob, 100, 1)));
+    batch.add_iou(&create_test_iou(&alice, &bob, 100, 1));
     // Bob → Charlie: 150
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&bob, &charlie, 150, 2)));
+    batch.add_iou(&create_test_iou(&bob, &charlie, 150, 2));
     // Charlie → Alice: 50
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&charlie, &alice, 50, 3)));
+    batch.add_iou(&create_test_iou(&charlie, &alice, 50, 3));
 
     let positions = batch.calculate_net_positions();
 
@@ -590,9 +669,9 @@ fn test_net_position_perfectly_balanced() {
     let mut batch = SettlementBatch::new();
 
     // Alice → Bob: 100
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&alice, &bob, 100, 1)));
+    batch.add_iou(&create_test_iou(&alice, &bob, 100, 1));
     // Bob → Alice: 100
-    batch.add_entry(SettlementEntry::from_iou(&create_test_iou(&bob, &alice, 100, 2)));
+    batch.add_iou(&create_test_iou(&bob, &alice, 100, 2));
 
     let positions = batch.calculate_net_positions();
 
@@ -604,6 +683,114 @@ fn test_net_position_perfectly_balanced() {
     assert_eq!(bob_pos.net_amount(), 0);
 }
 
+// ============================================================================
+// MINIMAL TRANSFERS
+// ============================================================================
+
+/// Assert that applying `transfers` reproduces every net position of `batch`
+fn assert_nets_preserved(batch: &SettlementBatch, transfers: &[Transfer]) {
+    let mut nets: HashMap<Did, i64> = HashMap::new();
+    for transfer in transfers {
+        assert!(transfer.amount() > 0);
+        assert_ne!(transfer.from(), transfer.to());
+        *nets.entry(transfer.from().clone()).or_insert(0) -= transfer.amount() as i64;
+        *nets.entry(transfer.to().clone()).or_insert(0) += transfer.amount() as i64;
+    }
+
+    for position in batch.calculate_net_positions() {
+        let net = nets.get(position.party()).copied().unwrap_or(0);
+        assert_eq!(net, position.net_amount(), "net of {}", position.party());
+    }
+}
+
+#[test]
+fn test_minimal_transfers_three_parties() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_test_iou(&alice, &bob, 100, 1));
+    batch.add_iou(&create_test_iou(&bob, &charlie, 150, 2));
+    batch.add_iou(&create_test_iou(&charlie, &alice, 50, 3));
+
+    let transfers = batch.minimal_transfers();
+
+    // Alice and Bob each pay Charlie 50, instead of the three raw edges
+    assert_eq!(transfers.len(), 2);
+    assert!(transfers.len() < batch.entries().len());
+    let charlie_did = Did::from_public_key(&charlie.public_key());
+    assert!(transfers.iter().all(|t| t.to() == &charlie_did && t.amount() == 50));
+    assert_nets_preserved(&batch, &transfers);
+}
+
+#[test]
+fn test_minimal_transfers_bidirectional() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_test_iou(&alice, &bob, 300, 1));
+    batch.add_iou(&create_test_iou(&bob, &alice, 100, 2));
+
+    let transfers = batch.minimal_transfers();
+
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].from(), &Did::from_public_key(&alice.public_key()));
+    assert_eq!(transfers[0].to(), &Did::from_public_key(&bob.public_key()));
+    assert_eq!(transfers[0].amount(), 200);
+    assert_nets_preserved(&batch, &transfers);
+}
+
+#[test]
+fn test_minimal_transfers_perfectly_balanced_is_empty() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_test_iou(&alice, &bob, 100, 1));
+    batch.add_iou(&create_test_iou(&bob, &alice, 100, 2));
+
+    assert!(batch.minimal_transfers().is_empty());
+    assert!(SettlementBatch::new().minimal_transfers().is_empty());
+}
+
+#[test]
+fn test_minimal_transfers_many_parties() {
+    let parties: Vec<Keypair> = (0..6).map(|_| Keypair::generate()).collect();
+
+    let mut batch = SettlementBatch::new();
+    let mut nonce = 0;
+    for (i, sender) in parties.iter().enumerate() {
+        for (j, recipient) in parties.iter().enumerate() {
+            if i != j {
+                nonce += 1;
+                batch.add_iou(&create_test_iou(sender, recipient, (i * 7 + j * 3 + 1) as u64 * 10, nonce));
+            }
+        }
+    }
+
+    let transfers = batch.minimal_transfers();
+
+    let unsettled = batch.calculate_net_positions().iter().filter(|p| p.net_amount() != 0).count();
+    assert!(transfers.len() < unsettled);
+    assert!(transfers.len() < batch.entries().len());
+    assert_nets_preserved(&batch, &transfers);
+}
+
+#[test]
+fn test_minimal_transfers_deterministic() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_test_iou(&alice, &charlie, 100, 1));
+    batch.add_iou(&create_test_iou(&bob, &charlie, 100, 2));
+
+    assert_eq!(batch.minimal_transfers(), batch.minimal_transfers());
+}
+
 // ============================================================================
 // BATCH MANAGEMENT
 // ============================================================================
@@ -741,6 +928,34 @@ fn test_collector_stats() {
     assert_eq!(stats.batches_created, 1);
 }
 
+#[test]
+fn test_collector_skips_invalid_signatures() {
+    let config = CollectorConfig::new()
+        .with_min_batch_size(1)
+        .with_min_iou_age_secs(0);
+    let mut collector = Collector::new(config);
+
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let good = create_test_iou(&alice, &bob, 100, 1);
+    let bad = create_test_iou(&alice, &bob, 200, 2);
+
+    // Corrupt one signature in the serialized state, as a bad merge could deliver it
+    let state = create_mesh_with_ious(NodeId::generate(), vec![(good, &alice), (bad.clone(), &alice)]);
+    let mut bytes = state.to_bytes();
+    let at = bytes
+        .windows(64)
+        .position(|window| window == bad.signature().as_bytes())
+        .unwrap();
+    bytes[at] ^= 0x01;
+    let state = MeshState::from_bytes(&bytes).unwrap();
+
+    assert_eq!(collector.collect_from_state(&state).unwrap(), 1);
+    assert_eq!(collector.stats().total_collected, 1);
+    assert_eq!(collector.stats().total_amount_collected, 100);
+    assert_eq!(collector.stats().total_invalid, 1);
+}
+
 #[test]
 fn test_collector_reset_stats() {
     let config = CollectorConfig::new()
@@ -763,3 +978,262 @@ fn test_collector_reset_stats() {
     let stats = collector.stats();
     assert_eq!(stats.total_collected, 0);
 }
+
+// ============================================================================
+// JSON EXPORT
+// ============================================================================
+
+#[test]
+fn test_batch_to_json_layout() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = create_test_iou(&alice, &bob, 125, 3);
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&iou);
+
+    let json = batch.to_json();
+    let expected_entry = format!(
+        "{{\"iou_id\":\"{}\",\"sender\":\"{}\",\"recipient\":\"{}\",\"amount\":\"125\",\"timestamp\":\"{}\"}}",
+        base64_url(iou.id().as_bytes()),
+        Did::from_public_key(&alice.public_key()),
+        Did::from_public_key(&bob.public_key()),
+        iou.iou().timestamp()
+    );
+    let expected = format!(
+        "{{\"id\":\"{}\",\"status\":\"pending\",\"created_at\":\"{}\",\"total_amount\":\"125\",\"entries\":[{}]}}",
+        base64_url(batch.id().as_bytes()),
+        batch.created_at(),
+        expected_entry
+    );
+    assert_eq!(json, expected);
+}
+
+#[test]
+fn test_batch_to_json_multiple_entries_and_status() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let carol = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_test_iou(&alice, &bob, 10, 1));
+    batch.add_iou(&create_test_iou(&bob, &carol, 20, 2));
+    batch.set_status(BatchStatus::Submitted);
+
+    let json = batch.to_json();
+    assert!(json.contains("\"status\":\"submitted\""));
+    assert!(json.contains("\"total_amount\":\"30\""));
+    assert_eq!(json.matches("\"iou_id\"").count(), 2);
+    assert_eq!(json, batch.clone().to_json());
+}
+
+#[test]
+fn test_empty_batch_to_json() {
+    let batch = SettlementBatch::new();
+    assert!(batch.to_json().ends_with("\"total_amount\":\"0\",\"entries\":[]}"));
+}
+
+#[test]
+fn test_batch_net_positions_to_json() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let alice_did = Did::from_public_key(&alice.public_key());
+    let bob_did = Did::from_public_key(&bob.public_key());
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_test_iou(&alice, &bob, 100, 1));
+    batch.add_iou(&create_test_iou(&bob, &alice, 30, 2));
+
+    let mut positions = [
+        (alice_did.to_string(), "-70"),
+        (bob_did.to_string(), "70"),
+    ];
+    positions.sort();
+    let expected = format!(
+        "{{\"batch_id\":\"{}\",\"total_amount\":\"130\",\"positions\":[{{\"party\":\"{}\",\"net_amount\":\"{}\"}},{{\"party\":\"{}\",\"net_amount\":\"{}\"}}]}}",
+        base64_url(batch.id().as_bytes()),
+        positions[0].0,
+        positions[0].1,
+        positions[1].0,
+        positions[1].1
+    );
+    assert_eq!(batch.net_positions_to_json(), expected);
+}
+
+// ============================================================================
+// DENOMINATIONS
+// ============================================================================
+
+fn create_denominated_iou(sender: &Keypair, recipient: &Keypair, amount: u64, nonce: u64, code: &str) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(amount)
+        .nonce(nonce)
+        .denomination(Denomination::new(code, 2).unwrap())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_settlement_entry_carries_denomination() {
+    let iou = create_denominated_iou(&Keypair::generate(), &Keypair::generate(), 1250, 1, "USD");
+
+    let entry = SettlementEntry::from_iou(&iou).remove(0);
+
+    assert_eq!(entry.denomination().map(Denomination::code), Some("USD"));
+    assert!(entry.to_json().ends_with(",\"denomination\":{\"code\":\"USD\",\"exponent\":\"2\"}}"));
+
+    let decoded = SettlementEntry::from_bytes(&entry.to_bytes()).unwrap();
+    assert_eq!(decoded.denomination(), entry.denomination());
+}
+
+#[test]
+fn test_batch_shared_denomination() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    assert_eq!(batch.denomination().unwrap(), None);
+
+    batch.try_add_iou(&create_denominated_iou(&alice, &bob, 100, 1, "USD")).unwrap();
+    batch.try_add_iou(&create_denominated_iou(&bob, &alice, 40, 2, "USD")).unwrap();
+
+    assert_eq!(batch.denomination().unwrap().map(Denomination::code), Some("USD"));
+    assert_eq!(batch.total_amount(), 140);
+}
+
+#[test]
+fn test_batch_rejects_mixed_denominations() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    batch.try_add_iou(&create_denominated_iou(&alice, &bob, 100, 1, "USD")).unwrap();
+
+    let eur = create_denominated_iou(&alice, &bob, 100, 2, "EUR");
+    assert!(matches!(batch.try_add_iou(&eur), Err(CollectorError::MixedDenominations)));
+    let entry = SettlementEntry::from_iou(&eur).remove(0);
+    assert!(matches!(batch.try_add_entry(entry), Err(CollectorError::MixedDenominations)));
+
+    // Bare credits don't mix with a denomination either
+    let credits = create_test_iou(&alice, &bob, 100, 3);
+    assert!(matches!(batch.try_add_iou(&credits), Err(CollectorError::MixedDenominations)));
+
+    assert_eq!(batch.entries().len(), 1);
+    assert_eq!(batch.total_amount(), 100);
+}
+
+#[test]
+fn test_unchecked_mixed_batch_fails_assertion() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_denominated_iou(&alice, &bob, 100, 1, "USD"));
+    batch.add_iou(&create_denominated_iou(&alice, &bob, 100, 2, "EUR"));
+
+    assert!(matches!(batch.denomination(), Err(CollectorError::MixedDenominations)));
+}
+
+#[test]
+fn test_net_positions_never_mix_denominations() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let alice_did = Did::from_public_key(&alice.public_key());
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_denominated_iou(&alice, &bob, 100, 1, "USD"));
+    batch.add_iou(&create_denominated_iou(&bob, &alice, 100, 2, "EUR"));
+
+    // Netting across currencies would wrongly give alice zero
+    let mut alice_positions: Vec<(String, i64)> = batch
+        .calculate_net_positions()
+        .into_iter()
+        .filter(|position| position.party() == &alice_did)
+        .map(|position| (position.denomination().unwrap().code().to_string(), position.net_amount()))
+        .collect();
+    alice_positions.sort();
+    assert_eq!(alice_positions, vec![("EUR".to_string(), 100), ("USD".to_string(), -100)]);
+
+    let transfers = batch.minimal_transfers();
+    assert_eq!(transfers.len(), 2);
+    assert_eq!(transfers[0].denomination().map(Denomination::code), Some("EUR"));
+    assert_eq!(transfers[1].denomination().map(Denomination::code), Some("USD"));
+}
+
+#[test]
+fn test_net_positions_to_json_includes_denomination() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&create_denominated_iou(&alice, &bob, 100, 1, "USD"));
+
+    let json = batch.net_positions_to_json();
+    assert_eq!(json.matches("\"denomination\":{\"code\":\"USD\",\"exponent\":\"2\"}").count(), 2);
+}
+
+#[test]
+fn test_collector_batches_one_denomination_at_a_time() {
+    let config = CollectorConfig::new()
+        .with_min_batch_size(1)
+        .with_min_iou_age_secs(0);
+    let mut collector = Collector::new(config);
+
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let state = create_mesh_with_ious(NodeId::generate(), vec![
+        (create_denominated_iou(&alice, &bob, 100, 1, "USD"), &alice),
+        (create_denominated_iou(&alice, &bob, 200, 2, "EUR"), &alice),
+        (create_denominated_iou(&alice, &bob, 300, 3, "USD"), &alice),
+    ]);
+    collector.collect_from_state(&state).unwrap();
+
+    let first = collector.create_batch().unwrap();
+    let second = collector.create_batch().unwrap();
+    assert!(matches!(collector.create_batch(), Err(CollectorError::InsufficientIOUs)));
+
+    let mut batches = [first, second];
+    batches.sort_by_key(|batch| batch.entries().len());
+    assert_eq!(batches[0].total_amount(), 200);
+    assert_eq!(batches[0].denomination().unwrap().map(Denomination::code), Some("EUR"));
+    assert_eq!(batches[1].total_amount(), 400);
+    assert_eq!(batches[1].denomination().unwrap().map(Denomination::code), Some("USD"));
+}
+
+fn base64_url(bytes: &[u8]) -> String {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// ============================================================================
+// CHECKPOINTS
+// ============================================================================
+
+#[test]
+fn test_confirmed_batch_checkpoint_prunes_mesh_state() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let gateway = Keypair::generate();
+
+    let settled = create_test_iou(&alice, &bob, 100, 1);
+    let unsettled = create_test_iou(&alice, &bob, 50, 2);
+    let mut state = create_mesh_with_ious(NodeId::generate(), vec![
+        (settled.clone(), &alice),
+        (unsettled.clone(), &alice),
+    ]);
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&settled);
+    assert!(matches!(batch.checkpoint(&gateway), Err(CollectorError::BatchNotConfirmed)));
+
+    batch.set_status(BatchStatus::Confirmed);
+    let checkpoint = batch.checkpoint(&gateway).unwrap();
+    assert_eq!(checkpoint.checkpoint().iou_ids(), &[settled.id()]);
+
+    assert_eq!(state.apply_checkpoint(&checkpoint).unwrap(), 1);
+    assert!(!state.has_iou(&settled.id()));
+    assert!(state.has_iou(&unsettled.id()));
+}
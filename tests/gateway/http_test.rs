@@ -0,0 +1,274 @@
+// HTTP Settlement Target Tests
+// Tests the reqwest-backed target against a local mock HTTP server
+// Run with `cargo test --features http-settlement`
+
+#![cfg(feature = "http-settlement")]
+
+use p2pmesh::gateway::{
+    HttpSettlementTarget, SettlementBatch, SettlementTarget, Settler, SettlerConfig, SettlerError,
+};
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::IOUBuilder;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// ============================================================================
+// MOCK SERVER
+// ============================================================================
+
+/// What the mock server does with one request
+#[derive(Clone)]
+enum Reply {
+    Respond(u16, &'static str),
+    Throttle(u64),
+    Hang,
+}
+
+/// Minimal HTTP/1.1 server: answers with scripted replies (repeating the last)
+/// and records every request it receives
+struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    async fn start(replies: Vec<Reply>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/settle", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let replies = Arc::new(Mutex::new(VecDeque::from(replies)));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let reply = {
+                    let mut replies = replies.lock().unwrap();
+                    if replies.len() > 1 { replies.pop_front().unwrap() } else { replies[0].clone() }
+                };
+                tokio::spawn(serve(stream, reply, recorded.clone()));
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve(mut stream: TcpStream, reply: Reply, requests: Arc<Mutex<Vec<String>>>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await.unwrap();
+        if n == 0 {
+            return;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf).to_string();
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let content_length = text[..header_end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if buf.len() >= header_end + 4 + content_length {
+                requests.lock().unwrap().push(text);
+                break;
+            }
+        }
+    }
+
+    match reply {
+        Reply::Respond(status, body) => {
+            let response = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+        Reply::Throttle(secs) => {
+            let response = format!(
+                "HTTP/1.1 429 Mock\r\nRetry-After: {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}",
+                secs
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+        Reply::Hang => tokio::time::sleep(Duration::from_secs(30)).await,
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn create_test_batch() -> SettlementBatch {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(100)
+        .build()
+        .unwrap();
+
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&iou);
+    batch
+}
+
+fn config(url: &str) -> SettlerConfig {
+    SettlerConfig::new()
+        .with_endpoint(url)
+        .with_api_key("secret-key")
+        .with_timeout_secs(1)
+        .with_retry_delay_secs(0)
+}
+
+// ============================================================================
+// CONFIG
+// ============================================================================
+
+#[test]
+fn test_from_config_requires_endpoint() {
+    let result = HttpSettlementTarget::from_config(&SettlerConfig::default());
+    assert!(matches!(result, Err(SettlerError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_from_config_uses_endpoint() {
+    let target = HttpSettlementTarget::from_config(&config("http://127.0.0.1:9/settle")).unwrap();
+    assert_eq!(target.endpoint(), "http://127.0.0.1:9/settle");
+}
+
+// ============================================================================
+// SETTLE
+// ============================================================================
+
+#[tokio::test]
+async fn test_settle_success() {
+    let server = MockServer::start(vec![Reply::Respond(200, r#"{"transaction_id":"tx-123"}"#)]).await;
+    let target = HttpSettlementTarget::from_config(&config(&server.url)).unwrap();
+    let batch = create_test_batch();
+
+    let tx_id = target.settle(&batch).await.unwrap();
+    assert_eq!(tx_id, "tx-123");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert!(request.starts_with("POST /settle "));
+    assert!(request.to_ascii_lowercase().contains("authorization: bearer secret-key"));
+    assert!(request.to_ascii_lowercase().contains("content-type: application/json"));
+    let key_header = format!("idempotency-key: {}", batch.idempotency_key());
+    assert!(request.to_ascii_lowercase().contains(&key_header));
+    assert!(request.ends_with(&batch.net_positions_to_json()));
+}
+
+#[tokio::test]
+async fn test_settle_without_api_key_sends_no_authorization() {
+    let server = MockServer::start(vec![Reply::Respond(201, r#"{"transaction_id":"tx-1"}"#)]).await;
+    let target = HttpSettlementTarget::new(&server.url, 5).unwrap();
+
+    assert_eq!(target.settle(&create_test_batch()).await.unwrap(), "tx-1");
+    assert!(!server.requests()[0].to_ascii_lowercase().contains("authorization:"));
+}
+
+#[tokio::test]
+async fn test_settle_server_error() {
+    let server = MockServer::start(vec![Reply::Respond(500, r#"{"error":"down"}"#)]).await;
+    let target = HttpSettlementTarget::from_config(&config(&server.url)).unwrap();
+
+    let error = target.settle(&create_test_batch()).await.unwrap_err();
+    assert!(error.contains("500"), "unexpected error: {}", error);
+}
+
+#[tokio::test]
+async fn test_settle_passes_on_retry_after() {
+    let server = MockServer::start(vec![Reply::Throttle(42)]).await;
+    let target = HttpSettlementTarget::from_config(&config(&server.url)).unwrap();
+
+    let error = target.settle(&create_test_batch()).await.unwrap_err();
+    assert!(error.contains("429"), "unexpected error: {}", error);
+    assert_eq!(target.retry_after(&error), Some(Duration::from_secs(42)));
+}
+
+#[tokio::test]
+async fn test_settle_missing_transaction_id() {
+    let server = MockServer::start(vec![Reply::Respond(200, r#"{"status":"ok"}"#)]).await;
+    let target = HttpSettlementTarget::from_config(&config(&server.url)).unwrap();
+
+    assert!(target.settle(&create_test_batch()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_settle_timeout() {
+    let server = MockServer::start(vec![Reply::Hang]).await;
+    let target = HttpSettlementTarget::from_config(&config(&server.url)).unwrap();
+
+    let error = target.settle(&create_test_batch()).await.unwrap_err();
+    assert_eq!(error, "Timeout");
+}
+
+#[tokio::test]
+async fn test_settle_connection_refused() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/settle", listener.local_addr().unwrap());
+    drop(listener);
+
+    let target = HttpSettlementTarget::from_config(&config(&url)).unwrap();
+    assert!(target.settle(&create_test_batch()).await.is_err());
+}
+
+// ============================================================================
+// SETTLER INTEGRATION
+// ============================================================================
+
+#[tokio::test]
+async fn test_settler_retries_after_server_error() {
+    let server = MockServer::start(vec![
+        Reply::Respond(500, "{}"),
+        Reply::Respond(503, "{}"),
+        Reply::Respond(200, r#"{"transaction_id":"tx-retry"}"#),
+    ])
+    .await;
+    let config = config(&server.url).with_max_retries(3);
+    let target = HttpSettlementTarget::from_config(&config).unwrap();
+    let mut settler = Settler::with_target(config, Box::new(target));
+
+    let batch = create_test_batch();
+    let batch_id = batch.id().clone();
+    settler.submit(batch).await.unwrap();
+
+    let result = settler.process(&batch_id).await.unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.transaction_id(), Some("tx-retry"));
+    assert_eq!(result.attempts(), 3);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_settler_fails_after_timeouts() {
+    let server = MockServer::start(vec![Reply::Hang]).await;
+    let config = config(&server.url).with_max_retries(1);
+    let target = HttpSettlementTarget::from_config(&config).unwrap();
+    let mut settler = Settler::with_target(config, Box::new(target));
+
+    let batch = create_test_batch();
+    let batch_id = batch.id().clone();
+    settler.submit(batch).await.unwrap();
+
+    let result = settler.process(&batch_id).await.unwrap();
+    assert!(!result.is_success());
+    assert_eq!(result.error_message(), Some("Timeout"));
+    assert_eq!(result.attempts(), 2);
+}
@@ -0,0 +1,196 @@
+// Idempotency Tests
+// Tests the idempotency key carried by settlement batches and that resubmitting
+// the same IOUs settles them once
+
+use async_trait::async_trait;
+use p2pmesh::gateway::{
+    BatchStatus, SettlementBatch, SettlementTarget, Settler, SettlerConfig, SettlerError,
+};
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn ious(count: u64) -> Vec<SignedIOU> {
+    let alice = Keypair::generate();
+    let bob = Did::from_public_key(&Keypair::generate().public_key());
+    (0..count)
+        .map(|nonce| {
+            IOUBuilder::new()
+                .sender(&alice)
+                .recipient(bob.clone())
+                .amount(100)
+                .nonce(nonce)
+                .build()
+                .unwrap()
+        })
+        .collect()
+}
+
+fn batch_of<'a>(ious: impl IntoIterator<Item = &'a SignedIOU>) -> SettlementBatch {
+    let mut batch = SettlementBatch::new();
+    for iou in ious {
+        batch.add_iou(iou);
+    }
+    batch
+}
+
+/// A backend that settles each idempotency key once, answering repeats with the
+/// first transaction ID; optionally drops the response to its first call
+#[derive(Clone, Default)]
+struct Ledger {
+    settled: Arc<Mutex<HashMap<String, String>>>,
+    calls: Arc<AtomicUsize>,
+    lose_first_response: bool,
+}
+
+impl Ledger {
+    fn settlements(&self) -> usize {
+        self.settled.lock().unwrap().len()
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl SettlementTarget for Ledger {
+    async fn settle(&self, batch: &SettlementBatch) -> Result<String, String> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        let mut settled = self.settled.lock().unwrap();
+        let count = settled.len();
+        let tx_id = settled
+            .entry(batch.idempotency_key())
+            .or_insert_with(|| format!("tx-{}", count))
+            .clone();
+        if self.lose_first_response && call == 0 {
+            return Err("Timeout".to_string());
+        }
+        Ok(tx_id)
+    }
+}
+
+fn settler(ledger: &Ledger, max_retries: u32) -> Settler {
+    let config = SettlerConfig::new().with_max_retries(max_retries).with_retry_delay_secs(0);
+    Settler::with_target(config, Box::new(ledger.clone()))
+}
+
+// ============================================================================
+// KEY
+// ============================================================================
+
+#[test]
+fn test_key_is_deterministic() {
+    let ious = ious(3);
+    let batch = batch_of(&ious);
+
+    assert_eq!(batch.idempotency_key(), batch.idempotency_key());
+    assert_eq!(batch.idempotency_key().len(), 64);
+    assert!(batch.idempotency_key().chars().all(|c| c.is_ascii_hexdigit()));
+}
+
+#[test]
+fn test_key_depends_only_on_ious() {
+    let ious = ious(3);
+    let first = batch_of(&ious);
+    let mut second = batch_of(ious.iter().rev());
+    second.set_status(BatchStatus::Failed);
+
+    assert_ne!(first.id(), second.id());
+    assert_eq!(first.idempotency_key(), second.idempotency_key());
+}
+
+#[test]
+fn test_key_differs_for_different_ious() {
+    let ious = ious(3);
+
+    assert_ne!(batch_of(&ious).idempotency_key(), batch_of(&ious[..2]).idempotency_key());
+    assert_ne!(batch_of(&ious[..1]).idempotency_key(), batch_of(&ious[1..2]).idempotency_key());
+}
+
+#[test]
+fn test_key_survives_serialization() {
+    let batch = batch_of(&ious(2));
+
+    let restored = SettlementBatch::from_bytes(&batch.to_bytes()).unwrap();
+
+    assert_eq!(restored.idempotency_key(), batch.idempotency_key());
+}
+
+// ============================================================================
+// SETTLER
+// ============================================================================
+
+#[tokio::test]
+async fn test_same_ious_in_another_batch_are_refused() {
+    let ledger = Ledger::default();
+    let mut settler = settler(&ledger, 0);
+    let ious = ious(2);
+    let first = batch_of(&ious);
+    let first_id = first.id().clone();
+
+    settler.submit(first).await.unwrap();
+    assert!(matches!(settler.submit(batch_of(&ious)).await, Err(SettlerError::DuplicateBatch)));
+
+    settler.process(&first_id).await.unwrap();
+    assert!(matches!(settler.submit(batch_of(&ious)).await, Err(SettlerError::DuplicateBatch)));
+    assert_eq!(ledger.settlements(), 1);
+}
+
+#[tokio::test]
+async fn test_confirmed_batch_is_not_reprocessed() {
+    let ledger = Ledger::default();
+    let mut settler = settler(&ledger, 0);
+    let batch = batch_of(&ious(1));
+    let id = batch.id().clone();
+    settler.submit(batch).await.unwrap();
+
+    settler.process(&id).await.unwrap();
+
+    assert!(matches!(settler.process(&id).await, Err(SettlerError::BatchAlreadyProcessed)));
+    assert_eq!(ledger.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_retry_after_lost_response_settles_once() {
+    let ledger = Ledger { lose_first_response: true, ..Ledger::default() };
+    let mut settler = settler(&ledger, 1);
+    let batch = batch_of(&ious(2));
+    let id = batch.id().clone();
+    settler.submit(batch).await.unwrap();
+
+    let result = settler.process(&id).await.unwrap();
+
+    assert!(result.is_success());
+    assert_eq!(result.attempts(), 2);
+    assert_eq!(result.transaction_id(), Some("tx-0"));
+    assert_eq!(ledger.calls(), 2);
+    assert_eq!(ledger.settlements(), 1);
+}
+
+#[tokio::test]
+async fn test_resubmission_after_failure_settles_once() {
+    let ledger = Ledger { lose_first_response: true, ..Ledger::default() };
+    let mut settler = settler(&ledger, 0);
+    let ious = ious(2);
+    let first = batch_of(&ious);
+    let first_id = first.id().clone();
+    settler.submit(first).await.unwrap();
+    assert!(!settler.process(&first_id).await.unwrap().is_success());
+
+    // The failed batch no longer blocks its IOUs
+    let second = batch_of(&ious);
+    let second_id = second.id().clone();
+    settler.submit(second).await.unwrap();
+    let result = settler.process(&second_id).await.unwrap();
+
+    assert_eq!(result.transaction_id(), Some("tx-0"));
+    assert_eq!(ledger.calls(), 2);
+    assert_eq!(ledger.settlements(), 1);
+}
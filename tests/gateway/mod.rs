@@ -3,3 +3,8 @@
 mod collector_test;
 mod settler_test;
 mod edge_cases_test;
+mod recovery_test;
+mod backoff_test;
+mod idempotency_test;
+#[cfg(feature = "http-settlement")]
+mod http_test;
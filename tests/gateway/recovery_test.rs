@@ -0,0 +1,264 @@
+// Settler Recovery Tests
+// Tests persisting batch status changes and results, recovering them after a
+// restart, and reconciling batches that were mid-flight when the settler stopped
+
+use async_trait::async_trait;
+use p2pmesh::gateway::{
+    BatchId, BatchStatus, SettlementBatch, SettlementTarget, Settler, SettlerConfig, SettlerError,
+};
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::IOUBuilder;
+use p2pmesh::storage::MeshStore;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// A target that counts how often it is asked to settle, optionally slowly or failing
+struct CountingTarget {
+    calls: Arc<AtomicUsize>,
+    delay_ms: u64,
+    fail: bool,
+}
+
+#[async_trait]
+impl SettlementTarget for CountingTarget {
+    async fn settle(&self, _batch: &SettlementBatch) -> Result<String, String> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+        if self.fail {
+            return Err("bank offline".to_string());
+        }
+        Ok(format!("tx-{}", call))
+    }
+}
+
+/// A gateway's settler store on disk, reopened on every "restart"
+struct Gateway {
+    dir: TempDir,
+    calls: Arc<AtomicUsize>,
+}
+
+impl Gateway {
+    fn new() -> Self {
+        Self {
+            dir: TempDir::new().unwrap(),
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn settler_with(&self, delay_ms: u64, fail: bool) -> Settler {
+        let config = SettlerConfig::new().with_max_retries(0).with_retry_delay_secs(0);
+        let target = CountingTarget { calls: self.calls.clone(), delay_ms, fail };
+        Settler::with_target(config, Box::new(target)).with_store(self.open_store())
+    }
+
+    /// Reopen the store, waiting out sled's flusher still holding the last handle's lock
+    fn open_store(&self) -> MeshStore {
+        for _ in 0..50 {
+            if let Ok(store) = MeshStore::open(self.dir.path()) {
+                return store;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        MeshStore::open(self.dir.path()).unwrap()
+    }
+
+    /// Start a settler on the store and recover what it holds
+    fn start(&self) -> (Settler, Vec<BatchId>) {
+        let mut settler = self.settler_with(0, false);
+        let in_flight = settler.recover().unwrap();
+        (settler, in_flight)
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+fn batch(amount: u64) -> SettlementBatch {
+    let alice = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    let mut batch = SettlementBatch::new();
+    batch.add_iou(&iou);
+    batch
+}
+
+/// Submit a batch and stop the settler while the target is settling it
+async fn crash_mid_settlement(gateway: &Gateway) -> BatchId {
+    let mut settler = gateway.settler_with(10_000, false);
+    let batch = batch(250);
+    let id = batch.id().clone();
+    settler.submit(batch).await.unwrap();
+
+    let interrupted = tokio::time::timeout(Duration::from_millis(50), settler.process(&id)).await;
+    assert!(interrupted.is_err());
+    id
+}
+
+// ============================================================================
+// RECOVERY
+// ============================================================================
+
+#[tokio::test]
+async fn test_submitted_batch_survives_restart() {
+    let gateway = Gateway::new();
+    let id = {
+        let mut settler = gateway.settler_with(0, false);
+        let batch = batch(100);
+        let id = batch.id().clone();
+        settler.submit(batch).await.unwrap();
+        id
+    };
+
+    let (mut settler, in_flight) = gateway.start();
+
+    assert!(in_flight.is_empty());
+    assert_eq!(settler.get_status(&id), Some(BatchStatus::Pending));
+    assert_eq!(settler.pending_settlements(), 1);
+    assert!(settler.process(&id).await.unwrap().is_success());
+    assert_eq!(gateway.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_confirmed_batch_is_not_resubmitted_after_restart() {
+    let gateway = Gateway::new();
+    let id = {
+        let mut settler = gateway.settler_with(0, false);
+        let batch = batch(300);
+        let id = batch.id().clone();
+        settler.submit(batch).await.unwrap();
+        settler.process(&id).await.unwrap();
+        id
+    };
+
+    let (mut settler, in_flight) = gateway.start();
+
+    assert!(in_flight.is_empty());
+    assert_eq!(settler.get_status(&id), Some(BatchStatus::Confirmed));
+    let result = settler.get_result(&id).unwrap();
+    assert_eq!(result.transaction_id(), Some("tx-0"));
+    assert_eq!(result.receipt().unwrap().amount(), 300);
+    assert_eq!(settler.stats().batches_settled, 1);
+    assert_eq!(settler.stats().total_amount_settled, 300);
+
+    assert!(matches!(settler.process(&id).await, Err(SettlerError::BatchAlreadyProcessed)));
+    assert_eq!(gateway.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_result_survives_restart() {
+    let gateway = Gateway::new();
+    let id = {
+        let mut settler = gateway.settler_with(0, true);
+        let batch = batch(50);
+        let id = batch.id().clone();
+        settler.submit(batch).await.unwrap();
+        settler.process(&id).await.unwrap();
+        id
+    };
+
+    let (settler, _) = gateway.start();
+
+    assert_eq!(settler.get_status(&id), Some(BatchStatus::Failed));
+    assert_eq!(settler.get_result(&id).unwrap().error_message(), Some("bank offline"));
+    assert_eq!(settler.stats().batches_failed, 1);
+}
+
+#[tokio::test]
+async fn test_cancelled_batch_is_gone_after_restart() {
+    let gateway = Gateway::new();
+    {
+        let mut settler = gateway.settler_with(0, false);
+        let batch = batch(10);
+        let id = batch.id().clone();
+        settler.submit(batch).await.unwrap();
+        settler.cancel(&id).unwrap();
+    }
+
+    let (settler, _) = gateway.start();
+
+    assert_eq!(settler.stats().batches_submitted, 0);
+    assert_eq!(settler.pending_settlements(), 0);
+}
+
+#[tokio::test]
+async fn test_settler_without_store_recovers_nothing() {
+    let mut settler = Settler::new(SettlerConfig::default());
+
+    assert!(settler.recover().unwrap().is_empty());
+}
+
+// ============================================================================
+// MID-FLIGHT CRASH
+// ============================================================================
+
+#[tokio::test]
+async fn test_crash_between_submit_and_confirm_leaves_batch_in_flight() {
+    let gateway = Gateway::new();
+    let id = crash_mid_settlement(&gateway).await;
+
+    let (mut settler, in_flight) = gateway.start();
+
+    assert_eq!(in_flight, vec![id.clone()]);
+    assert_eq!(settler.get_status(&id), Some(BatchStatus::Processing));
+    assert_eq!(settler.in_flight().len(), 1);
+    // The target may already have paid it, so it is never sent blindly again
+    assert!(matches!(settler.process(&id).await, Err(SettlerError::BatchInFlight)));
+    assert_eq!(gateway.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_reconciled_batch_stays_confirmed_without_resubmission() {
+    let gateway = Gateway::new();
+    let id = crash_mid_settlement(&gateway).await;
+    {
+        let (mut settler, _) = gateway.start();
+        settler.reconcile(&id, Some("tx-bank-77".to_string())).unwrap();
+        assert_eq!(settler.stats().batches_settled, 1);
+    }
+
+    let (mut settler, in_flight) = gateway.start();
+
+    assert!(in_flight.is_empty());
+    assert_eq!(settler.get_status(&id), Some(BatchStatus::Confirmed));
+    assert_eq!(settler.get_result(&id).unwrap().transaction_id(), Some("tx-bank-77"));
+    assert!(matches!(settler.process(&id).await, Err(SettlerError::BatchAlreadyProcessed)));
+    assert_eq!(gateway.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_batch_the_target_never_got_is_retried() {
+    let gateway = Gateway::new();
+    let id = crash_mid_settlement(&gateway).await;
+    let (mut settler, _) = gateway.start();
+
+    settler.reconcile(&id, None).unwrap();
+
+    assert_eq!(settler.get_status(&id), Some(BatchStatus::Pending));
+    assert!(settler.process(&id).await.unwrap().is_success());
+    assert_eq!(gateway.calls(), 2);
+}
+
+#[tokio::test]
+async fn test_reconcile_rejects_batches_not_in_flight() {
+    let gateway = Gateway::new();
+    let (mut settler, _) = gateway.start();
+    let batch = batch(20);
+    let id = batch.id().clone();
+    settler.submit(batch).await.unwrap();
+
+    assert!(matches!(
+        settler.reconcile(&id, Some("tx".to_string())),
+        Err(SettlerError::BatchAlreadyProcessed)
+    ));
+    assert!(matches!(
+        settler.reconcile(&BatchId::generate(), None),
+        Err(SettlerError::BatchNotFound)
+    ));
+}
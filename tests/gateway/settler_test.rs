@@ -2,10 +2,10 @@
 // Tests for pushing settlements to external systems (bank/blockchain)
 
 use p2pmesh::identity::{Did, Keypair};
-use p2pmesh::iou::IOUBuilder;
+use p2pmesh::iou::{Denomination, IOUBuilder};
 use p2pmesh::gateway::{
     Settler, SettlerConfig, SettlerError, SettlerEvent,
-    SettlementBatch, SettlementEntry, BatchStatus, BatchId,
+    SettlementBatch, BatchStatus, BatchId,
     SettlementResult, SettlementReceipt,
     SettlementTarget, MockSettlementTarget,
 };
@@ -29,7 +29,7 @@ fn create_test_batch(num_entries: usize) -> SettlementBatch {
             .build()
             .unwrap();
 
-        batch.add_entry(SettlementEntry::from_iou(&iou));
+        batch.add_iou(&iou);
     }
 
     batch
@@ -119,7 +119,6 @@ async fn test_settler_submit_batch() {
     let mut settler = Settler::with_target(config, Box::new(target));
 
     let batch = create_test_batch(3);
-    let batch_id = batch.id().clone();
 
     let result = settler.submit(batch).await;
 
@@ -140,6 +139,32 @@ async fn test_settler_submit_empty_batch() {
     assert!(matches!(result, Err(SettlerError::EmptyBatch)));
 }
 
+#[tokio::test]
+async fn test_settler_submit_mixed_denomination_batch() {
+    let config = SettlerConfig::default();
+    let target = MockSettlementTarget::new();
+    let mut settler = Settler::with_target(config, Box::new(target));
+
+    let alice = Keypair::generate();
+    let bob = Did::from_public_key(&Keypair::generate().public_key());
+    let mut batch = SettlementBatch::new();
+    for (nonce, code) in [(1, "USD"), (2, "EUR")] {
+        let iou = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(bob.clone())
+            .amount(100)
+            .nonce(nonce)
+            .denomination(Denomination::new(code, 2).unwrap())
+            .build()
+            .unwrap();
+        batch.add_iou(&iou);
+    }
+
+    let result = settler.submit(batch).await;
+
+    assert!(matches!(result, Err(SettlerError::MixedDenominations)));
+}
+
 #[tokio::test]
 async fn test_settler_submit_without_target() {
     let config = SettlerConfig::default();
@@ -0,0 +1,283 @@
+// Key derivation tests
+// Tests SLIP-0010 Ed25519 derivation of sub-identities from one master key
+
+use p2pmesh::identity::{
+    parse_derivation_path, sub_account_path, DerivationError, Did, Keypair, Signer,
+    HARDENED_OFFSET,
+};
+
+/// SLIP-0010 Ed25519 test vector 1: (path, private key, public key)
+const SLIP10_SEED_1: &str = "000102030405060708090a0b0c0d0e0f";
+const SLIP10_VECTOR_1: &[(&str, &str, &str)] = &[
+    (
+        "m",
+        "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+        "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+    ),
+    (
+        "m/0'",
+        "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+        "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+    ),
+    (
+        "m/0'/1'",
+        "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+        "1932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187",
+    ),
+    (
+        "m/0'/1'/2'",
+        "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+        "ae98736566d30ed0e9d2f4486a64bc95740d89c7db33f52121f8ea8f76ff0fc1",
+    ),
+    (
+        "m/0'/1'/2'/2'",
+        "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+        "8abae2d66361c879b900d204ad2cc4984fa2aa344dd7ddc46007329ac76c429c",
+    ),
+    (
+        "m/0'/1'/2'/2'/1000000000'",
+        "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+        "3c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+    ),
+];
+
+/// SLIP-0010 Ed25519 test vector 2: (path, private key)
+const SLIP10_SEED_2: &str = "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a2\
+                             9f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542";
+const SLIP10_VECTOR_2: &[(&str, &str)] = &[
+    ("m", "171cb88b1b3c1db25add599712e36245d75bc65a1a5c9e18d76f9f2b1eab4012"),
+    ("m/0H", "1559eb2bbec5790b0c65d8693e4d0875b1747f4970ae8b650486ed7470845635"),
+    ("m/0H/2147483647H", "ea4f5bfe8694d8bb74b7b59404632fd5968b774ed545e810de9c32a4fb4192f4"),
+    ("m/0H/2147483647H/1H", "3757c7577170179c7868353ada796c839135b3d30554bbb74a4b1e4a5a58505c"),
+    (
+        "m/0H/2147483647H/1H/2147483646H",
+        "5837736c89570de861ebc173b1086da4f505d4adb387c6a1b1342d5e4ac9ec72",
+    ),
+    (
+        "m/0H/2147483647H/1H/2147483646H/2H",
+        "551d333177df541ad876a60ea71f00447931c0a9da16f227c11ea080d7391b8d",
+    ),
+];
+
+// ============================================================================
+// TEST VECTORS
+// ============================================================================
+
+/// Test: SLIP-0010 vector 1 keys and public keys
+#[test]
+fn test_slip10_vector_1() {
+    let seed = hex::decode(SLIP10_SEED_1).unwrap();
+    for (path, private, public) in SLIP10_VECTOR_1 {
+        let keypair = Keypair::derive_from_seed(&seed, path).unwrap();
+        assert_eq!(hex::encode(keypair.to_bytes().as_slice()), *private, "{}", path);
+        assert_eq!(hex::encode(keypair.public_key().as_bytes()), *public, "{}", path);
+    }
+}
+
+/// Test: SLIP-0010 vector 2 keys, including the largest hardened indexes
+#[test]
+fn test_slip10_vector_2() {
+    let seed = hex::decode(SLIP10_SEED_2).unwrap();
+    for (path, private) in SLIP10_VECTOR_2 {
+        let keypair = Keypair::derive_from_seed(&seed, path).unwrap();
+        assert_eq!(hex::encode(keypair.to_bytes().as_slice()), *private, "{}", path);
+    }
+}
+
+/// Test: Child DIDs from a fixed master are the same on every platform
+#[test]
+fn test_derive_child_did_vector() {
+    let master = Keypair::from_bytes(&[0x80; 32]).unwrap();
+
+    let child = master.derive_child("m/44'/0'/1'").unwrap();
+    let again = Keypair::from_bytes(&[0x80; 32]).unwrap().derive_child("m/44'/0'/1'").unwrap();
+
+    assert_eq!(child.to_bytes(), again.to_bytes());
+    assert_eq!(
+        child.to_bytes(),
+        Keypair::derive_from_seed(&[0x80; 32], "m/44'/0'/1'").unwrap().to_bytes()
+    );
+}
+
+// ============================================================================
+// DERIVATION
+// ============================================================================
+
+/// Test: Different paths give different DIDs
+#[test]
+fn test_distinct_paths_give_distinct_dids() {
+    let master = Keypair::generate();
+    let paths = ["m/44'/0'/0'", "m/44'/0'/1'", "m/44'/1'/0'", "m/0'"];
+
+    let dids: Vec<Did> = paths
+        .iter()
+        .map(|path| Did::from_public_key(&master.derive_child(path).unwrap().public_key()))
+        .collect();
+
+    for (i, did) in dids.iter().enumerate() {
+        assert_ne!(did, &Did::from_public_key(&master.public_key()));
+        assert!(dids[i + 1..].iter().all(|other| other != did));
+    }
+}
+
+/// Test: Derived keys sign and their DIDs round-trip
+#[test]
+fn test_derived_keypair_is_usable_identity() {
+    let child = Keypair::generate().derive_child("m/44'/0'/7'").unwrap();
+
+    let did = Did::from_public_key(&child.public_key());
+    assert_eq!(Did::parse(&did.to_string()).unwrap().public_key().unwrap(), child.public_key());
+
+    let signature = Signer::sign(&child, b"refund");
+    assert!(Signer::verify(&child.public_key(), b"refund", &signature));
+}
+
+/// Test: Children are restorable from the master's mnemonic
+#[test]
+fn test_children_restore_from_master_mnemonic() {
+    let master = Keypair::generate();
+    let restored = Keypair::from_mnemonic(&master.to_mnemonic()).unwrap();
+
+    let path = "m/44'/0'/3'";
+    assert_eq!(
+        master.derive_child(path).unwrap().public_key(),
+        restored.derive_child(path).unwrap().public_key()
+    );
+}
+
+/// Test: Hardened markers ', h and H are interchangeable
+#[test]
+fn test_hardened_markers_equivalent() {
+    let master = Keypair::generate();
+    let expected = master.derive_child("m/44'/0'/1'").unwrap().public_key();
+
+    assert_eq!(master.derive_child("m/44h/0h/1h").unwrap().public_key(), expected);
+    assert_eq!(master.derive_child("m/44H/0'/1h").unwrap().public_key(), expected);
+}
+
+/// Test: Seeds outside 16..=64 bytes are rejected
+#[test]
+fn test_seed_length_checked() {
+    assert_eq!(
+        Keypair::derive_from_seed(&[0; 15], "m").err(),
+        Some(DerivationError::InvalidSeedLength(15))
+    );
+    assert_eq!(
+        Keypair::derive_from_seed(&[0; 65], "m").err(),
+        Some(DerivationError::InvalidSeedLength(65))
+    );
+    assert!(Keypair::derive_from_seed(&[0; 64], "m").is_ok());
+}
+
+// ============================================================================
+// SUB-ACCOUNTS
+// ============================================================================
+
+/// Test: Sub-accounts are deterministic and live at their numbered path
+#[test]
+fn test_sub_account_is_deterministic() {
+    let master = Keypair::generate();
+
+    let savings = master.derive_sub_account(0).unwrap();
+    let again = Keypair::from_mnemonic(&master.to_mnemonic()).unwrap().derive_sub_account(0).unwrap();
+
+    assert_eq!(savings.to_bytes(), again.to_bytes());
+    assert_eq!(sub_account_path(0), "m/44'/0'/0'");
+    assert_eq!(savings.public_key(), master.derive_child("m/44'/0'/0'").unwrap().public_key());
+}
+
+/// Test: Each index gives its own keypair and DID
+#[test]
+fn test_sub_accounts_differ() {
+    let master = Keypair::generate();
+
+    let dids: Vec<Did> = (0..4)
+        .map(|index| Did::from_public_key(&master.derive_sub_account(index).unwrap().public_key()))
+        .collect();
+
+    for (i, did) in dids.iter().enumerate() {
+        assert_ne!(did, &Did::from_public_key(&master.public_key()));
+        assert!(dids[i + 1..].iter().all(|other| other != did));
+    }
+}
+
+/// Test: A sub-account's key doesn't lead back to the master or its siblings
+#[test]
+fn test_master_not_recoverable_from_sub_account() {
+    let master = Keypair::generate();
+    let spending = master.derive_sub_account(1).unwrap();
+
+    assert_ne!(spending.to_bytes(), master.to_bytes());
+    // Deriving from the child only ever reaches the child's own subtree
+    for index in 0..4 {
+        let grandchild = spending.derive_sub_account(index).unwrap();
+        assert_ne!(grandchild.public_key(), master.public_key());
+        assert_ne!(grandchild.public_key(), master.derive_sub_account(index).unwrap().public_key());
+    }
+    assert_ne!(spending.derive_child("m").unwrap().public_key(), master.public_key());
+}
+
+/// Test: Indexes must fit below the hardened offset
+#[test]
+fn test_sub_account_index_out_of_range() {
+    let master = Keypair::generate();
+
+    assert!(master.derive_sub_account(HARDENED_OFFSET - 1).is_ok());
+    assert!(matches!(
+        master.derive_sub_account(HARDENED_OFFSET),
+        Err(DerivationError::IndexOutOfRange(_))
+    ));
+}
+
+// ============================================================================
+// PATH PARSING
+// ============================================================================
+
+/// Test: Paths parse to hardened indexes
+#[test]
+fn test_parse_derivation_path() {
+    assert_eq!(parse_derivation_path("m").unwrap(), Vec::<u32>::new());
+    assert_eq!(
+        parse_derivation_path("m/44'/0'/1'").unwrap(),
+        vec![44 + HARDENED_OFFSET, HARDENED_OFFSET, 1 + HARDENED_OFFSET]
+    );
+    assert_eq!(
+        parse_derivation_path("m/2147483647'").unwrap(),
+        vec![u32::MAX]
+    );
+}
+
+/// Test: Malformed paths are rejected
+#[test]
+fn test_malformed_paths_rejected() {
+    for path in ["", "44'/0'", "M/0'", "m/", "m//0'", "m/abc'", "m/-1'", "m/0'/", "m/0''", "m/ 1'"] {
+        assert!(
+            matches!(parse_derivation_path(path), Err(DerivationError::InvalidPath(_))),
+            "{:?} should be rejected",
+            path
+        );
+        assert!(Keypair::generate().derive_child(path).is_err());
+    }
+}
+
+/// Test: Non-hardened components are rejected (Ed25519 can't derive them)
+#[test]
+fn test_non_hardened_rejected() {
+    assert_eq!(
+        parse_derivation_path("m/44'/0/1'"),
+        Err(DerivationError::NonHardened(0))
+    );
+}
+
+/// Test: Indexes at or above 2^31 are rejected
+#[test]
+fn test_index_out_of_range_rejected() {
+    assert!(matches!(
+        parse_derivation_path("m/2147483648'"),
+        Err(DerivationError::IndexOutOfRange(_))
+    ));
+    assert!(matches!(
+        parse_derivation_path("m/99999999999'"),
+        Err(DerivationError::IndexOutOfRange(_))
+    ));
+}
@@ -1,4 +1,4 @@
-use p2pmesh::identity::{Keypair, Did, PublicKey};
+use p2pmesh::identity::{Keypair, Did, DidFormat, DidParseError};
 
 /// Test: Can create DID from public key
 #[test]
@@ -165,3 +165,190 @@ fn test_did_hashable() {
 
     assert_eq!(map.get(&did), Some(&100), "DID should be usable as HashMap key");
 }
+
+// ============================================================================
+// FORMATS
+// ============================================================================
+
+/// Test: Display is the legacy form
+#[test]
+fn test_did_display_is_legacy_format() {
+    let did = Did::from_public_key(&Keypair::generate().public_key());
+
+    assert_eq!(DidFormat::default(), DidFormat::Legacy);
+    assert_eq!(did.to_string(), did.to_string_as(DidFormat::Legacy));
+    assert_eq!(did.to_string(), format!("did:mesh:{}", did.key_part()));
+}
+
+/// Test: Multibase form is a 'z'-prefixed ed25519-pub multicodec key
+#[test]
+fn test_did_multibase_format() {
+    let keypair = Keypair::generate();
+    let did = Did::from_public_key(&keypair.public_key());
+
+    let multibase = did.to_string_as(DidFormat::Multibase);
+    let encoded = multibase.strip_prefix("did:mesh:z").unwrap();
+    let bytes = bs58::decode(encoded).into_vec().unwrap();
+
+    assert_eq!(&bytes[..2], &[0xed, 0x01]);
+    assert_eq!(&bytes[2..], keypair.public_key().as_bytes());
+}
+
+/// Test: Both formats round-trip to the same DID
+#[test]
+fn test_did_round_trip_both_formats() {
+    for _ in 0..50 {
+        let keypair = Keypair::generate();
+        let did = Did::from_public_key(&keypair.public_key());
+
+        for format in [DidFormat::Legacy, DidFormat::Multibase] {
+            let parsed = Did::parse(&did.to_string_as(format)).unwrap();
+            assert_eq!(parsed, did);
+            assert_eq!(parsed.to_string_as(format), did.to_string_as(format));
+            assert_eq!(parsed.public_key().unwrap().as_bytes(), keypair.public_key().as_bytes());
+        }
+    }
+}
+
+/// Test: Known vector parses in both forms
+#[test]
+fn test_did_known_vector_both_formats() {
+    let legacy = "did:mesh:GSWmWFSy27pcZDVH9NA52BFapq8DUwTyuzT8JsrhctL1";
+    let did = Did::parse(legacy).unwrap();
+    let multibase = did.to_string_as(DidFormat::Multibase);
+
+    assert!(multibase.starts_with("did:mesh:z6Mk"));
+    assert_eq!(Did::parse(&multibase).unwrap().to_string(), legacy);
+}
+
+// ============================================================================
+// STRICT PARSE ERRORS
+// ============================================================================
+
+fn valid_did() -> String {
+    Did::from_public_key(&Keypair::generate().public_key()).to_string()
+}
+
+/// Test: Missing or mis-cased scheme is a bad prefix
+#[test]
+fn test_did_parse_bad_prefix() {
+    let key = valid_did()["did:mesh:".len()..].to_string();
+
+    for input in [
+        String::new(),
+        "did".to_string(),
+        key.clone(),
+        format!("mesh:{}", key),
+        format!("DID:mesh:{}", key),
+        format!("abc:mesh:{}", key),
+        format!(" did:mesh:{}", key),
+        format!("\u{feff}did:mesh:{}", key),
+        format!("ⅾid:mesh:{}", key),
+    ] {
+        assert!(
+            matches!(Did::parse(&input), Err(DidParseError::BadPrefix(_))),
+            "expected BadPrefix for {:?}",
+            input
+        );
+    }
+}
+
+/// Test: Other methods are unsupported, including a mis-cased 'mesh'
+#[test]
+fn test_did_parse_unsupported_method() {
+    let key = valid_did()["did:mesh:".len()..].to_string();
+
+    for (input, method) in [
+        (format!("did:key:{}", key), "key"),
+        (format!("did:MESH:{}", key), "MESH"),
+        (format!("did:Mesh:{}", key), "Mesh"),
+        (format!("did: mesh:{}", key), " mesh"),
+        (format!("did:mésh:{}", key), "mésh"),
+        ("did:".to_string(), ""),
+        ("did::abc".to_string(), ""),
+    ] {
+        assert_eq!(
+            Did::parse(&input),
+            Err(DidParseError::UnsupportedMethod(method.to_string())),
+            "input {:?}",
+            input
+        );
+    }
+}
+
+/// Test: Junk in the key part is a bad encoding
+#[test]
+fn test_did_parse_bad_encoding() {
+    let did = valid_did();
+
+    for input in [
+        "did:mesh".to_string(),
+        "did:mesh:".to_string(),
+        "did:mesh:!!!invalid".to_string(),
+        format!("{} ", did),
+        format!("{}\n", did),
+        format!("{}\t", did),
+        did.replacen("did:mesh:", "did:mesh: ", 1),
+        format!("{}é", did),
+        format!("{}🦀", did),
+        format!("{}:extra", did),
+        format!("{}\u{200b}", did),
+        // 0, O, I and l are not in the base58 alphabet
+        "did:mesh:0OIl".to_string(),
+    ] {
+        assert!(
+            matches!(Did::parse(&input), Err(DidParseError::BadEncoding(_))),
+            "expected BadEncoding for {:?}, got {:?}",
+            input,
+            Did::parse(&input)
+        );
+    }
+}
+
+/// Test: Keys that decode to the wrong size are rejected in both forms
+#[test]
+fn test_did_parse_wrong_key_length() {
+    let short = bs58::encode([7u8; 31]).into_string();
+    let long = bs58::encode([7u8; 33]).into_string();
+    let mut short_multicodec = vec![0xed, 0x01];
+    short_multicodec.extend_from_slice(&[7u8; 16]);
+    let short_multibase = format!("z{}", bs58::encode(short_multicodec).into_string());
+
+    assert_eq!(Did::parse(&format!("did:mesh:{}", short)), Err(DidParseError::WrongKeyLength(31)));
+    assert_eq!(Did::parse(&format!("did:mesh:{}", long)), Err(DidParseError::WrongKeyLength(33)));
+    assert_eq!(Did::parse("did:mesh:abc123"), Err(DidParseError::WrongKeyLength(5)));
+    assert_eq!(
+        Did::parse(&format!("did:mesh:{}", short_multibase)),
+        Err(DidParseError::WrongKeyLength(16))
+    );
+}
+
+/// Test: A multibase value with the wrong multicodec is not taken as a key
+#[test]
+fn test_did_parse_rejects_foreign_multicodec() {
+    // secp256k1-pub multicodec (0xe7) with a 33-byte key
+    let mut bytes = vec![0xe7, 0x01];
+    bytes.extend_from_slice(&[2u8; 33]);
+    let input = format!("did:mesh:z{}", bs58::encode(bytes).into_string());
+
+    assert!(matches!(Did::parse(&input), Err(DidParseError::WrongKeyLength(_))));
+}
+
+/// Test: A legacy key that happens to start with 'z' still parses
+#[test]
+fn test_did_parse_legacy_key_starting_with_z() {
+    let did = (0..)
+        .map(|_| Did::from_public_key(&Keypair::generate().public_key()))
+        .find(|did| did.key_part().starts_with('z'))
+        .unwrap();
+
+    assert_eq!(Did::parse(&did.to_string()).unwrap(), did);
+}
+
+/// Test: Parse errors convert into DidError
+#[test]
+fn test_did_parse_error_converts_to_did_error() {
+    let err: p2pmesh::identity::DidError = Did::parse("did:key:abc").unwrap_err().into();
+
+    assert!(err.to_string().contains("key"));
+}
@@ -0,0 +1,251 @@
+use p2pmesh::identity::{
+    Did, DidDocument, DidError, Keypair, SignedDidDocument, ED25519_VERIFICATION_KEY_2020,
+};
+
+/// Helper to create a DID and its keypair
+fn create_did() -> (Keypair, Did) {
+    let keypair = Keypair::generate();
+    let did = Did::from_public_key(&keypair.public_key());
+    (keypair, did)
+}
+
+/// Test: Document id and references point at the DID
+#[test]
+fn test_document_structure() {
+    let (_, did) = create_did();
+    let doc = did.to_document();
+
+    assert_eq!(doc.id, did.to_string());
+    assert_eq!(doc.verification_method.len(), 1);
+
+    let method = &doc.verification_method[0];
+    assert_eq!(method.id, format!("{}#key-1", did));
+    assert_eq!(method.method_type, ED25519_VERIFICATION_KEY_2020);
+    assert_eq!(method.controller, did.to_string());
+    assert!(method.public_key_multibase.starts_with('z'));
+
+    assert_eq!(doc.authentication, vec![method.id.clone()]);
+    assert_eq!(doc.assertion_method, vec![method.id.clone()]);
+}
+
+/// Test: Public key recovered from the document matches the original
+#[test]
+fn test_document_public_key_matches() {
+    let (keypair, did) = create_did();
+    let doc = did.to_document();
+
+    let recovered = doc.public_key().expect("Should recover public key");
+    assert_eq!(recovered, keypair.public_key());
+}
+
+/// Test: JSON round-trip preserves the document and its key
+#[test]
+fn test_document_json_roundtrip() {
+    let (keypair, did) = create_did();
+    let doc = did.to_document();
+
+    let json = doc.to_json();
+    let parsed = DidDocument::from_json(&json).expect("Should parse own JSON");
+
+    assert_eq!(parsed, doc);
+    assert_eq!(parsed.public_key().unwrap(), keypair.public_key());
+    assert_eq!(parsed.did().unwrap(), did);
+}
+
+/// Test: JSON uses the standard DID document field names
+#[test]
+fn test_document_json_field_names() {
+    let (_, did) = create_did();
+    let json = did.to_document().to_json();
+
+    for field in ["\"@context\"", "\"verificationMethod\"", "\"authentication\"",
+                  "\"publicKeyMultibase\"", "\"Ed25519VerificationKey2020\""] {
+        assert!(json.contains(field), "JSON should contain {}", field);
+    }
+}
+
+/// Test: Externally formatted JSON (whitespace, extra fields) is accepted
+#[test]
+fn test_document_from_pretty_json() {
+    let (keypair, did) = create_did();
+    let method = did.to_document().verification_method[0].clone();
+
+    let json = format!(
+        r#"{{
+            "@context": "https://www.w3.org/ns/did/v1",
+            "id": "{did}",
+            "created": 1703612400,
+            "deactivated": false,
+            "verificationMethod": [{{
+                "id": "{kid}",
+                "type": "Ed25519VerificationKey2020",
+                "controller": "{did}",
+                "publicKeyMultibase": "{mb}"
+            }}],
+            "authentication": ["{kid}"]
+        }}"#,
+        did = did,
+        kid = method.id,
+        mb = method.public_key_multibase,
+    );
+
+    let doc = DidDocument::from_json(&json).expect("Should parse pretty JSON");
+    assert_eq!(doc.public_key().unwrap(), keypair.public_key());
+    assert!(doc.assertion_method.is_empty());
+}
+
+/// Test: Document claiming a key that isn't its own is rejected
+#[test]
+fn test_document_with_foreign_key_rejected() {
+    let (_, did) = create_did();
+    let (_, other) = create_did();
+
+    let mut doc = did.to_document();
+    doc.verification_method[0].public_key_multibase =
+        other.to_document().verification_method[0].public_key_multibase.clone();
+
+    assert!(matches!(doc.public_key(), Err(DidError::InvalidDocument(_))));
+}
+
+/// Test: Malformed JSON is rejected
+#[test]
+fn test_document_malformed_json_rejected() {
+    for input in ["", "{", "[]", "{\"id\": 5}", "{\"id\":\"did:mesh:x\"} trailing"] {
+        assert!(
+            matches!(DidDocument::from_json(input), Err(DidError::InvalidDocument(_))),
+            "Should reject {:?}",
+            input
+        );
+    }
+}
+
+/// Test: Missing required fields are rejected
+#[test]
+fn test_document_missing_fields_rejected() {
+    let (_, did) = create_did();
+    let json = format!(r#"{{"id":"{}","authentication":[]}}"#, did);
+
+    assert!(matches!(DidDocument::from_json(&json), Err(DidError::InvalidDocument(_))));
+}
+
+/// Test: Unsupported key type is rejected
+#[test]
+fn test_document_unsupported_key_type_rejected() {
+    let (_, did) = create_did();
+    let mut doc = did.to_document();
+    doc.verification_method[0].method_type = "JsonWebKey2020".to_string();
+
+    assert!(matches!(doc.public_key(), Err(DidError::InvalidDocument(_))));
+}
+
+/// Test: Escaped strings survive the JSON round-trip
+#[test]
+fn test_document_json_escaping() {
+    let (_, did) = create_did();
+    let mut doc = did.to_document();
+    doc.context.push("quote \" backslash \\ newline \n tab \t".to_string());
+
+    let parsed = DidDocument::from_json(&doc.to_json()).unwrap();
+    assert_eq!(parsed.context, doc.context);
+}
+
+// ============================================================================
+// SERVICE ENDPOINTS AND SIGNED DOCUMENTS
+// ============================================================================
+
+/// Test: Services and extra keys get numbered fragment ids
+#[test]
+fn test_document_services_and_keys() {
+    let (keypair, did) = create_did();
+    let extra = Keypair::generate();
+    let doc = did
+        .to_document()
+        .with_key(&extra.public_key())
+        .with_service("MeshTransport", "tcp://1.2.3.4:9000")
+        .with_service("MeshTransport", "lora://0x42@915MHz");
+
+    assert_eq!(doc.verification_method[1].id, format!("{}#key-2", did));
+    assert_eq!(doc.keys().unwrap(), vec![keypair.public_key(), extra.public_key()]);
+    assert_eq!(doc.public_key().unwrap(), keypair.public_key());
+
+    assert_eq!(doc.service.len(), 2);
+    assert_eq!(doc.service[1].id, format!("{}#service-2", did));
+    assert_eq!(doc.service[0].socket_addr(), Some("1.2.3.4:9000".parse().unwrap()));
+    assert_eq!(doc.service[1].socket_addr(), None);
+}
+
+/// Test: Services survive a JSON round-trip and are omitted when empty
+#[test]
+fn test_document_service_json_roundtrip() {
+    let (_, did) = create_did();
+    assert!(!did.to_document().to_json().contains("\"service\""));
+
+    let doc = did.to_document().with_service("MeshTransport", "tcp://1.2.3.4:9000");
+    let json = doc.to_json();
+    assert!(json.contains("\"serviceEndpoint\":\"tcp://1.2.3.4:9000\""));
+    assert_eq!(DidDocument::from_json(&json).unwrap(), doc);
+}
+
+/// Test: Signed document verifies and round-trips through bytes
+#[test]
+fn test_signed_document_roundtrip() {
+    let (keypair, did) = create_did();
+    let signed = did
+        .to_document()
+        .with_service("MeshTransport", "tcp://1.2.3.4:9000")
+        .sign(&keypair, 1703612400, Some(1703698800))
+        .unwrap();
+
+    assert!(signed.verify());
+    assert_eq!(signed.did().unwrap(), did);
+    assert_eq!(signed.updated_at(), 1703612400);
+
+    let parsed = SignedDidDocument::from_bytes(&signed.to_bytes()).unwrap();
+    assert_eq!(parsed, signed);
+    assert!(parsed.verify());
+}
+
+/// Test: Only the controlling key can sign
+#[test]
+fn test_sign_document_with_foreign_key_fails() {
+    let (_, did) = create_did();
+    let result = did.to_document().sign(&Keypair::generate(), 1, None);
+
+    assert!(matches!(result, Err(DidError::InvalidDocument(_))));
+}
+
+/// Test: Tampering with a signed document breaks verification
+#[test]
+fn test_signed_document_tampered_fails() {
+    let (keypair, did) = create_did();
+    let signed = did.to_document().with_service("MeshTransport", "tcp://1.2.3.4:9000")
+        .sign(&keypair, 1, None)
+        .unwrap();
+
+    let bytes = signed.to_bytes();
+    let needle = b"1.2.3.4";
+    let at = bytes.windows(needle.len()).position(|w| w == needle).unwrap();
+    let mut tampered = bytes.clone();
+    tampered[at] = b'9';
+
+    let parsed = SignedDidDocument::from_bytes(&tampered).unwrap();
+    assert!(!parsed.verify());
+}
+
+/// Test: Expiry is exclusive of the expiry time and absent means never
+#[test]
+fn test_signed_document_expiry() {
+    let (keypair, did) = create_did();
+    let expiring = did.to_document().sign(&keypair, 1, Some(100)).unwrap();
+    let forever = did.to_document().sign(&keypair, 1, None).unwrap();
+
+    assert!(!expiring.is_expired_at(99));
+    assert!(expiring.is_expired_at(100));
+    assert!(!forever.is_expired_at(u64::MAX));
+}
+
+/// Test: Garbage bytes are rejected
+#[test]
+fn test_signed_document_from_garbage() {
+    assert!(SignedDidDocument::from_bytes(&[0xff; 8]).is_err());
+}
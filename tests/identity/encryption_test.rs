@@ -0,0 +1,185 @@
+// Encryption tests
+// Tests sealed envelopes encrypted to Ed25519 identities via X25519
+
+use p2pmesh::identity::{
+    decrypt, derive_shared_secret, encrypt_for, encrypt_for_with, EncryptionError, Keypair,
+    SealedEnvelope, ENVELOPE_VERSION,
+};
+
+// ============================================================================
+// SHARED SECRET
+// ============================================================================
+
+#[test]
+fn test_shared_secret_is_symmetric() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let ab = derive_shared_secret(&alice, &bob.public_key()).unwrap();
+    let ba = derive_shared_secret(&bob, &alice.public_key()).unwrap();
+
+    assert_eq!(*ab, *ba);
+}
+
+#[test]
+fn test_shared_secret_differs_per_peer() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let carol = Keypair::generate();
+
+    let ab = derive_shared_secret(&alice, &bob.public_key()).unwrap();
+    let ac = derive_shared_secret(&alice, &carol.public_key()).unwrap();
+
+    assert_ne!(*ab, *ac);
+}
+
+// ============================================================================
+// ENCRYPT / DECRYPT
+// ============================================================================
+
+#[test]
+fn test_encrypt_decrypt_roundtrip() {
+    let bob = Keypair::generate();
+
+    let envelope = encrypt_for(&bob.public_key(), b"private payload").unwrap();
+
+    assert_eq!(envelope.version(), ENVELOPE_VERSION);
+    assert_eq!(decrypt(&bob, &envelope).unwrap(), b"private payload");
+}
+
+#[test]
+fn test_encrypt_empty_plaintext() {
+    let bob = Keypair::generate();
+
+    let envelope = encrypt_for(&bob.public_key(), b"").unwrap();
+
+    assert!(decrypt(&bob, &envelope).unwrap().is_empty());
+}
+
+#[test]
+fn test_ciphertext_hides_plaintext() {
+    let bob = Keypair::generate();
+    let plaintext = b"a fairly recognisable plaintext";
+
+    let envelope = encrypt_for(&bob.public_key(), plaintext).unwrap();
+
+    assert!(!envelope
+        .to_bytes()
+        .windows(plaintext.len())
+        .any(|w| w == plaintext));
+}
+
+#[test]
+fn test_wrong_recipient_fails() {
+    let bob = Keypair::generate();
+    let eve = Keypair::generate();
+
+    let envelope = encrypt_for(&bob.public_key(), b"for bob only").unwrap();
+
+    assert_eq!(decrypt(&eve, &envelope), Err(EncryptionError::DecryptionFailed));
+}
+
+#[test]
+fn test_fresh_ephemeral_key_per_envelope() {
+    let bob = Keypair::generate();
+
+    let first = encrypt_for(&bob.public_key(), b"same").unwrap();
+    let second = encrypt_for(&bob.public_key(), b"same").unwrap();
+
+    assert_ne!(first.ephemeral_public(), second.ephemeral_public());
+    assert_ne!(first.ciphertext(), second.ciphertext());
+}
+
+#[test]
+fn test_encrypt_with_is_deterministic() {
+    let bob = Keypair::from_bytes(&[7u8; 32]).unwrap();
+
+    let first = encrypt_for_with(&bob.public_key(), b"vector", &[1u8; 32], &[2u8; 24]).unwrap();
+    let second = encrypt_for_with(&bob.public_key(), b"vector", &[1u8; 32], &[2u8; 24]).unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(decrypt(&bob, &first).unwrap(), b"vector");
+}
+
+// ============================================================================
+// TAMPERING
+// ============================================================================
+
+#[test]
+fn test_tampered_ciphertext_fails() {
+    let bob = Keypair::generate();
+    let mut bytes = encrypt_for(&bob.public_key(), b"payload").unwrap().to_bytes();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x01;
+
+    let envelope = SealedEnvelope::from_bytes(&bytes).unwrap();
+
+    assert_eq!(decrypt(&bob, &envelope), Err(EncryptionError::DecryptionFailed));
+}
+
+#[test]
+fn test_tampered_ephemeral_key_fails() {
+    let bob = Keypair::generate();
+    let mut bytes = encrypt_for(&bob.public_key(), b"payload").unwrap().to_bytes();
+    bytes[1] ^= 0x01;
+
+    let envelope = SealedEnvelope::from_bytes(&bytes).unwrap();
+
+    assert_eq!(decrypt(&bob, &envelope), Err(EncryptionError::DecryptionFailed));
+}
+
+#[test]
+fn test_low_order_ephemeral_key_fails_cleanly() {
+    let bob = Keypair::generate();
+    let mut bytes = encrypt_for(&bob.public_key(), b"payload").unwrap().to_bytes();
+    bytes[1..33].copy_from_slice(&[0u8; 32]);
+
+    let envelope = SealedEnvelope::from_bytes(&bytes).unwrap();
+
+    assert_eq!(decrypt(&bob, &envelope), Err(EncryptionError::DecryptionFailed));
+}
+
+// ============================================================================
+// WIRE FORMAT
+// ============================================================================
+
+#[test]
+fn test_envelope_bytes_roundtrip() {
+    let bob = Keypair::generate();
+    let envelope = encrypt_for(&bob.public_key(), b"payload").unwrap();
+
+    let bytes = envelope.to_bytes();
+    let restored = SealedEnvelope::from_bytes(&bytes).unwrap();
+
+    assert_eq!(bytes[0], ENVELOPE_VERSION);
+    assert_eq!(bytes.len(), 1 + 32 + 24 + b"payload".len() + 16);
+    assert_eq!(restored, envelope);
+    assert_eq!(decrypt(&bob, &restored).unwrap(), b"payload");
+}
+
+#[test]
+fn test_unsupported_version_rejected() {
+    let bob = Keypair::generate();
+    let mut bytes = encrypt_for(&bob.public_key(), b"payload").unwrap().to_bytes();
+    bytes[0] = 99;
+
+    assert_eq!(
+        SealedEnvelope::from_bytes(&bytes),
+        Err(EncryptionError::UnsupportedVersion(99))
+    );
+}
+
+#[test]
+fn test_truncated_envelope_rejected() {
+    let bob = Keypair::generate();
+    let bytes = encrypt_for(&bob.public_key(), b"").unwrap().to_bytes();
+
+    assert!(matches!(
+        SealedEnvelope::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(EncryptionError::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        SealedEnvelope::from_bytes(&[]),
+        Err(EncryptionError::InvalidFormat(_))
+    ));
+}
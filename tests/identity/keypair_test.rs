@@ -1,4 +1,4 @@
-use p2pmesh::identity::{Keypair, PublicKey, SecretKey};
+use p2pmesh::identity::{Did, Keypair, KeypairError, PublicKey, SecretKey, MNEMONIC_WORD_COUNT};
 
 /// Test: Can generate a new keypair
 #[test]
@@ -94,7 +94,7 @@ fn test_keypair_from_secret_key() {
     let original = Keypair::generate();
     let secret_bytes = original.secret_key().to_bytes();
 
-    let secret = SecretKey::from_bytes(&secret_bytes)
+    let secret = SecretKey::from_bytes(secret_bytes.as_slice())
         .expect("Should create secret key from bytes");
     let restored = Keypair::from_secret_key(secret);
 
@@ -104,3 +104,177 @@ fn test_keypair_from_secret_key() {
         "Keypair from same secret should have same public key"
     );
 }
+
+/// Test: Mnemonic backup has 24 words
+#[test]
+fn test_to_mnemonic_has_24_words() {
+    let keypair = Keypair::generate();
+    let phrase = keypair.to_mnemonic();
+
+    assert_eq!(phrase.split_whitespace().count(), MNEMONIC_WORD_COUNT);
+}
+
+/// Test: Keypair round-trips through its mnemonic
+#[test]
+fn test_mnemonic_roundtrip() {
+    let original = Keypair::generate();
+    let restored = Keypair::from_mnemonic(&original.to_mnemonic())
+        .expect("Mnemonic should restore keypair");
+
+    assert_eq!(original.to_bytes(), restored.to_bytes());
+    assert_eq!(original.public_key(), restored.public_key());
+}
+
+/// Test: Mnemonic parsing normalizes whitespace and case
+#[test]
+fn test_mnemonic_whitespace_and_case_normalized() {
+    let original = Keypair::generate();
+    let messy = format!(
+        "  {}\n",
+        original
+            .to_mnemonic()
+            .to_uppercase()
+            .split(' ')
+            .collect::<Vec<_>>()
+            .join(" \t ")
+    );
+
+    let restored = Keypair::from_mnemonic(&messy).expect("Messy phrase should still parse");
+    assert_eq!(original.public_key(), restored.public_key());
+}
+
+/// Test: Wrong word count is rejected
+#[test]
+fn test_mnemonic_wrong_word_count_fails() {
+    let phrase = Keypair::generate().to_mnemonic();
+    let truncated: Vec<&str> = phrase.split_whitespace().take(12).collect();
+
+    let result = Keypair::from_mnemonic(&truncated.join(" "));
+    assert!(matches!(result, Err(KeypairError::InvalidMnemonic(_))));
+}
+
+/// Test: Invalid checksum is rejected
+#[test]
+fn test_mnemonic_invalid_checksum_fails() {
+    let phrase = Keypair::generate().to_mnemonic();
+    let mut words: Vec<&str> = phrase.split_whitespace().collect();
+
+    // Swapping the last word almost always breaks the checksum; try until it does
+    let replacements = ["abandon", "ability", "able", "about"];
+    let last = words[23];
+    let mut rejected = false;
+    for replacement in replacements.iter().filter(|w| **w != last) {
+        words[23] = replacement;
+        if Keypair::from_mnemonic(&words.join(" ")).is_err() {
+            rejected = true;
+            break;
+        }
+    }
+
+    assert!(rejected, "Altered phrase should fail checksum validation");
+}
+
+/// Mnemonic test vectors: (secret key hex, phrase, DID)
+///
+/// The phrases are the 256-bit vectors from the BIP39 reference test suite, and the
+/// last secret is RFC 8032 test 1. Mobile clients must derive the same DIDs.
+const MNEMONIC_VECTORS: &[(&str, &str, &str)] = &[
+    (
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+         abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+        "did:mesh:4zvwRjXUKGfvwnParsHAS3HuSVzV5cA4McphgmoCtajS",
+    ),
+    (
+        "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+        "legal winner thank year wave sausage worth useful legal winner thank year \
+         wave sausage worth useful legal winner thank year wave sausage worth title",
+        "did:mesh:D2RGSD4paRbDANmHeUC2L9Ljaon7vXy6HzaNeHnoQ7Tg",
+    ),
+    (
+        "8080808080808080808080808080808080808080808080808080808080808080",
+        "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd \
+         amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless",
+        "did:mesh:GSWmWFSy27pcZDVH9NA52BFapq8DUwTyuzT8JsrhctL1",
+    ),
+    (
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+        "did:mesh:8z5oiZDBaCrP7ZCP1vQZbxkUt2eevdpPnyvpQAvAYuiL",
+    ),
+    (
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "output assault guess that stick core tube matter virus number arctic mass \
+         duty tired planet green harbor slide auction fix crack fire work arrive",
+        "did:mesh:FVen3X669xLzsi6N2V91DoiyzHzg1uAgqiT8jZ9nS96Z",
+    ),
+];
+
+/// Test: Secret keys encode to the expected phrases
+#[test]
+fn test_mnemonic_vectors_encode() {
+    for (secret_hex, phrase, _) in MNEMONIC_VECTORS {
+        let keypair = Keypair::from_bytes(&hex::decode(secret_hex).unwrap()).unwrap();
+        assert_eq!(keypair.to_mnemonic(), *phrase);
+    }
+}
+
+/// Test: Phrases decode to the expected secret keys and DIDs
+#[test]
+fn test_mnemonic_vectors_decode() {
+    for (secret_hex, phrase, did) in MNEMONIC_VECTORS {
+        let keypair = Keypair::from_mnemonic(phrase).unwrap();
+        assert_eq!(hex::encode(keypair.to_bytes().as_slice()), *secret_hex);
+        assert_eq!(Did::from_public_key(&keypair.public_key()).to_string(), *did);
+
+        let shouted = Keypair::from_mnemonic(&phrase.to_uppercase()).unwrap();
+        assert_eq!(shouted.public_key(), keypair.public_key());
+    }
+}
+
+/// Test: Unknown words are rejected
+#[test]
+fn test_mnemonic_unknown_word_fails() {
+    let phrase = Keypair::generate().to_mnemonic();
+    let mut words: Vec<&str> = phrase.split_whitespace().collect();
+    words[0] = "notaword";
+
+    let result = Keypair::from_mnemonic(&words.join(" "));
+    assert!(matches!(result, Err(KeypairError::InvalidMnemonic(_))));
+}
+
+/// Test: Secret key types wipe their memory on drop
+#[test]
+fn test_secret_types_are_zeroize_on_drop() {
+    fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
+
+    assert_zeroize_on_drop::<Keypair>();
+    assert_zeroize_on_drop::<SecretKey>();
+    assert_zeroize_on_drop::<zeroize::Zeroizing<Vec<u8>>>();
+}
+
+/// Test: Exported secret key buffer is cleared after drop
+#[test]
+fn test_secret_key_bytes_cleared_after_drop() {
+    use std::mem::ManuallyDrop;
+
+    let keypair = Keypair::generate();
+    let mut bytes = ManuallyDrop::new(keypair.secret_key().to_bytes());
+    assert!(bytes.iter().any(|b| *b != 0), "Fresh key should not be all zeros");
+
+    // Run the destructor in place; the array lives inline so its storage stays readable
+    let ptr: *const [u8; 32] = &**bytes;
+    unsafe {
+        ManuallyDrop::drop(&mut bytes);
+        assert_eq!(std::ptr::read_volatile(ptr), [0u8; 32]);
+    }
+}
+
+/// Test: Byte-returning accessors keep their values
+#[test]
+fn test_zeroizing_accessors_return_same_bytes() {
+    let keypair = Keypair::generate();
+
+    assert_eq!(keypair.to_bytes().as_slice(), keypair.secret_key().to_bytes().as_slice());
+    assert_eq!(keypair.to_bytes().len(), 32);
+}
@@ -1,3 +1,6 @@
 mod keypair_test;
 mod did_test;
 mod signer_test;
+mod document_test;
+mod derivation_test;
+mod encryption_test;
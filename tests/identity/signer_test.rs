@@ -1,4 +1,4 @@
-use p2pmesh::identity::{Keypair, Signature, Signer};
+use p2pmesh::identity::{KeySigner, Keypair, PublicKey, Signature, Signer};
 
 /// Test: Can sign a message
 #[test]
@@ -210,3 +210,44 @@ fn test_sign_binary_data() {
 
     assert!(is_valid, "Binary data should be signable and verifiable");
 }
+
+// ============================================================================
+// KEY SIGNER TESTS
+// ============================================================================
+
+/// Signer that only ever exposes the keypair through the trait
+struct RemoteKey(Keypair);
+
+impl KeySigner for RemoteKey {
+    fn public_key(&self) -> PublicKey {
+        self.0.public_key()
+    }
+
+    fn sign(&self, message: &[u8]) -> Signature {
+        Signer::sign(&self.0, message)
+    }
+}
+
+/// Test: Keypair signs the same through the trait as through Signer
+#[test]
+fn test_keypair_key_signer_matches_signer() {
+    let keypair = Keypair::generate();
+    let message = b"Hello, P2P Mesh!";
+
+    let signer: &dyn KeySigner = &keypair;
+
+    assert_eq!(signer.public_key(), keypair.public_key());
+    assert_eq!(signer.sign(message), Signer::sign(&keypair, message));
+}
+
+/// Test: Custom signers produce signatures the usual verification accepts
+#[test]
+fn test_custom_key_signer_verifies() {
+    let signer = RemoteKey(Keypair::generate());
+    let message = b"signed elsewhere";
+
+    let signature = signer.sign(message);
+
+    assert!(Signer::verify(&signer.public_key(), message, &signature));
+    assert!(!Signer::verify(&Keypair::generate().public_key(), message, &signature));
+}
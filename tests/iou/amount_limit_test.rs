@@ -0,0 +1,111 @@
+// Amount Limit Tests
+// Tests the builder's configurable maximum amount and the net-position-safe bound
+
+use p2pmesh::gateway::SettlementBatch;
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, IOUError, MAX_SAFE_AMOUNT};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn bounded(sender: &Keypair, amount: u64) -> Result<p2pmesh::iou::SignedIOU, IOUError> {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(did(&Keypair::generate()))
+        .amount(amount)
+        .max_amount(MAX_SAFE_AMOUNT)
+        .build()
+}
+
+// ============================================================================
+// BUILDER LIMITS
+// ============================================================================
+
+#[test]
+fn test_zero_amount_fails_with_limit_set() {
+    let result = bounded(&Keypair::generate(), 0);
+
+    assert!(matches!(result, Err(IOUError::InvalidAmount(_))));
+}
+
+#[test]
+fn test_amount_at_limit_builds() {
+    let signed = bounded(&Keypair::generate(), MAX_SAFE_AMOUNT).unwrap();
+
+    assert_eq!(signed.iou().amount(), MAX_SAFE_AMOUNT);
+}
+
+#[test]
+fn test_amount_over_limit_fails() {
+    let result = bounded(&Keypair::generate(), MAX_SAFE_AMOUNT + 1);
+
+    assert!(matches!(
+        result,
+        Err(IOUError::AmountTooLarge { amount, max }) if amount == MAX_SAFE_AMOUNT + 1 && max == MAX_SAFE_AMOUNT
+    ));
+    assert!(matches!(bounded(&Keypair::generate(), u64::MAX), Err(IOUError::AmountTooLarge { .. })));
+}
+
+#[test]
+fn test_custom_limit_applies_to_output_total() {
+    let alice = Keypair::generate();
+
+    // Each output is under the limit, but together they exceed it
+    let result = IOUBuilder::new()
+        .sender(&alice)
+        .add_output(did(&Keypair::generate()), 60)
+        .add_output(did(&Keypair::generate()), 60)
+        .max_amount(100)
+        .build();
+
+    assert!(matches!(result, Err(IOUError::AmountTooLarge { amount: 120, max: 100 })));
+}
+
+#[test]
+fn test_no_limit_by_default() {
+    let result = IOUBuilder::new()
+        .sender(&Keypair::generate())
+        .recipient(did(&Keypair::generate()))
+        .amount(u64::MAX)
+        .build();
+
+    assert!(result.is_ok());
+}
+
+// ============================================================================
+// NET-POSITION SAFETY
+// ============================================================================
+
+#[test]
+fn test_max_batch_of_safe_amounts_fits_i64() {
+    let total = (u32::MAX as i64).checked_mul(MAX_SAFE_AMOUNT as i64);
+
+    assert!(total.is_some());
+    assert!(MAX_SAFE_AMOUNT <= i64::MAX as u64);
+}
+
+#[test]
+fn test_net_positions_of_max_amounts_do_not_overflow() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut batch = SettlementBatch::new();
+    for nonce in 0..1_000 {
+        let iou = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(did(&bob))
+            .amount(MAX_SAFE_AMOUNT)
+            .max_amount(MAX_SAFE_AMOUNT)
+            .nonce(nonce)
+            .build()
+            .unwrap();
+        batch.add_iou(&iou);
+    }
+
+    let positions = batch.calculate_net_positions();
+    let net = |party: &Did| positions.iter().find(|p| p.party() == party).unwrap().net_amount();
+
+    let expected = 1_000 * MAX_SAFE_AMOUNT as i64;
+    assert_eq!(net(&did(&alice)), -expected);
+    assert_eq!(net(&did(&bob)), expected);
+}
@@ -0,0 +1,232 @@
+// Batch signing tests
+// Tests building many IOUs at once and verifying them with batch verification
+
+use p2pmesh::identity::{Did, Keypair, PublicKey, Signer};
+use p2pmesh::iou::{IOUBuilder, IOUError, IOUValidator, NonceError, SignedIOU};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn items(count: usize) -> Vec<(Did, u64)> {
+    (0..count).map(|i| (did(&Keypair::generate()), i as u64 + 1)).collect()
+}
+
+// ============================================================================
+// BUILD BATCH
+// ============================================================================
+
+#[test]
+fn test_build_batch_signs_every_item() {
+    let merchant = Keypair::generate();
+    let items = items(5);
+
+    let ious = IOUBuilder::build_batch(&items, &merchant, 1).unwrap();
+
+    assert_eq!(ious.len(), 5);
+    for (signed, (recipient, amount)) in ious.iter().zip(&items) {
+        assert_eq!(signed.iou().sender(), &did(&merchant));
+        assert_eq!(signed.iou().recipient(), recipient);
+        assert_eq!(signed.iou().amount(), *amount);
+        assert!(signed.verify(&merchant.public_key()));
+    }
+}
+
+#[test]
+fn test_build_batch_assigns_sequential_nonces() {
+    let merchant = Keypair::generate();
+
+    let ious = IOUBuilder::build_batch(&items(4), &merchant, 10).unwrap();
+
+    let nonces: Vec<u64> = ious.iter().map(|signed| signed.iou().nonce()).collect();
+    assert_eq!(nonces, vec![10, 11, 12, 13]);
+}
+
+#[test]
+fn test_build_batch_shares_timestamp() {
+    let merchant = Keypair::generate();
+
+    let ious = IOUBuilder::build_batch(&items(3), &merchant, 1).unwrap();
+
+    let timestamp = ious[0].iou().timestamp();
+    assert!(ious.iter().all(|signed| signed.iou().timestamp() == timestamp));
+}
+
+#[test]
+fn test_build_batch_empty() {
+    let merchant = Keypair::generate();
+    assert!(IOUBuilder::build_batch(&[], &merchant, 1).unwrap().is_empty());
+}
+
+#[test]
+fn test_build_batch_rejects_zero_amount() {
+    let merchant = Keypair::generate();
+    let mut items = items(3);
+    items[2].1 = 0;
+
+    let result = IOUBuilder::build_batch(&items, &merchant, 1);
+    assert!(matches!(result, Err(IOUError::InvalidAmount(_))));
+}
+
+#[test]
+fn test_build_batch_rejects_self_payment() {
+    let merchant = Keypair::generate();
+    let mut items = items(3);
+    items[1].0 = did(&merchant);
+
+    let result = IOUBuilder::build_batch(&items, &merchant, 1);
+    assert!(matches!(result, Err(IOUError::SelfPayment)));
+}
+
+#[test]
+fn test_build_batch_rejects_nonce_overflow() {
+    let merchant = Keypair::generate();
+
+    let result = IOUBuilder::build_batch(&items(3), &merchant, u64::MAX - 1);
+    assert!(matches!(result, Err(IOUError::Nonce(NonceError::Exhausted(_)))));
+
+    // Exactly reaching u64::MAX is fine
+    let ious = IOUBuilder::build_batch(&items(2), &merchant, u64::MAX - 1).unwrap();
+    assert_eq!(ious[1].iou().nonce(), u64::MAX);
+}
+
+// ============================================================================
+// VERIFY BATCH
+// ============================================================================
+
+#[test]
+fn test_verify_batch_all_valid() {
+    let merchant = Keypair::generate();
+    let ious = IOUBuilder::build_batch(&items(20), &merchant, 1).unwrap();
+
+    let results = SignedIOU::verify_batch(&ious, &merchant.public_key());
+    assert_eq!(results, vec![true; 20]);
+}
+
+#[test]
+fn test_verify_batch_empty() {
+    let merchant = Keypair::generate();
+    assert!(SignedIOU::verify_batch(&[], &merchant.public_key()).is_empty());
+}
+
+#[test]
+fn test_verify_batch_flags_bad_signatures() {
+    let merchant = Keypair::generate();
+    let forger = Keypair::generate();
+    let mut ious = IOUBuilder::build_batch(&items(5), &merchant, 1).unwrap();
+
+    // Replace one IOU with an identical one signed by another key
+    let forged = IOUBuilder::build_batch(&[(ious[3].iou().recipient().clone(), 4)], &forger, 4).unwrap();
+    ious[3] = SignedIOU::from_parts(ious[3].iou().clone(), forged[0].signature().clone());
+
+    let results = SignedIOU::verify_batch(&ious, &merchant.public_key());
+    assert_eq!(results, vec![true, true, true, false, true]);
+}
+
+#[test]
+fn test_verify_batch_wrong_key() {
+    let merchant = Keypair::generate();
+    let other = Keypair::generate();
+    let ious = IOUBuilder::build_batch(&items(3), &merchant, 1).unwrap();
+
+    let results = SignedIOU::verify_batch(&ious, &other.public_key());
+    assert_eq!(results, vec![false; 3]);
+}
+
+#[test]
+fn test_verify_batch_matches_single_verify() {
+    let merchant = Keypair::generate();
+    let other = Keypair::generate();
+    let mut ious = IOUBuilder::build_batch(&items(4), &merchant, 1).unwrap();
+    ious.extend(IOUBuilder::build_batch(&items(2), &other, 1).unwrap());
+
+    let expected: Vec<bool> = ious.iter().map(|signed| signed.verify(&merchant.public_key())).collect();
+    assert_eq!(SignedIOU::verify_batch(&ious, &merchant.public_key()), expected);
+}
+
+// ============================================================================
+// VALIDATOR BATCH VERIFICATION
+// ============================================================================
+
+/// One IOU from each of `count` senders, paired with the sender's key
+fn entries(count: usize) -> Vec<(SignedIOU, PublicKey)> {
+    (0..count)
+        .map(|i| {
+            let sender = Keypair::generate();
+            let signed = IOUBuilder::new()
+                .sender(&sender)
+                .recipient(did(&Keypair::generate()))
+                .amount(i as u64 + 1)
+                .build()
+                .unwrap();
+            (signed, sender.public_key())
+        })
+        .collect()
+}
+
+/// Replace entry `index`'s signature with one over different content
+fn tamper(entries: &mut [(SignedIOU, PublicKey)], index: usize) {
+    let original = entries[index].0.iou().clone();
+    let forged = Signer::sign(&Keypair::generate(), &original.to_signing_bytes());
+    entries[index].0 = SignedIOU::from_parts(original, forged);
+}
+
+#[test]
+fn test_validator_verify_batch_all_valid() {
+    let entries = entries(16);
+    assert_eq!(IOUValidator::verify_batch(&entries), vec![true; 16]);
+    assert_eq!(IOUValidator::first_invalid(&entries), None);
+}
+
+#[test]
+fn test_validator_verify_batch_empty() {
+    assert!(IOUValidator::verify_batch(&[]).is_empty());
+    assert_eq!(IOUValidator::first_invalid(&[]), None);
+}
+
+#[test]
+fn test_validator_verify_batch_reports_tampered_entry() {
+    let mut entries = entries(10);
+    tamper(&mut entries, 6);
+
+    let results = IOUValidator::verify_batch(&entries);
+
+    let invalid: Vec<usize> = results.iter().enumerate().filter(|(_, ok)| !**ok).map(|(i, _)| i).collect();
+    assert_eq!(invalid, vec![6]);
+}
+
+#[test]
+fn test_validator_verify_batch_detects_wrong_key() {
+    let mut entries = entries(4);
+    entries[1].1 = Keypair::generate().public_key();
+
+    assert_eq!(IOUValidator::verify_batch(&entries), vec![true, false, true, true]);
+}
+
+#[test]
+fn test_validator_first_invalid_finds_tampered_entry() {
+    for index in [0, 1, 7, 12] {
+        let mut entries = entries(13);
+        tamper(&mut entries, index);
+        assert_eq!(IOUValidator::first_invalid(&entries), Some(index));
+    }
+}
+
+#[test]
+fn test_validator_first_invalid_returns_earliest() {
+    let mut entries = entries(9);
+    tamper(&mut entries, 5);
+    tamper(&mut entries, 2);
+    tamper(&mut entries, 8);
+
+    assert_eq!(IOUValidator::first_invalid(&entries), Some(2));
+}
+
+#[test]
+fn test_validator_first_invalid_single_entry() {
+    let mut entries = entries(1);
+    assert_eq!(IOUValidator::first_invalid(&entries), None);
+
+    tamper(&mut entries, 0);
+    assert_eq!(IOUValidator::first_invalid(&entries), Some(0));
+}
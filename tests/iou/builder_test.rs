@@ -1,5 +1,5 @@
-use p2pmesh::identity::{Keypair, Did};
-use p2pmesh::iou::{IOUBuilder, SignedIOU, IOUError};
+use p2pmesh::identity::{Did, KeySigner, Keypair, PublicKey, Signature, Signer};
+use p2pmesh::iou::{IOUBuilder, IOUError, MAX_MEMO_BYTES};
 
 // ============================================================================
 // IOU BUILDER TESTS
@@ -207,6 +207,24 @@ fn test_builder_rejects_self_payment() {
     }
 }
 
+/// Test: Builder accepts self-payment when explicitly allowed
+#[test]
+fn test_builder_allows_self_payment_when_opted_in() {
+    let sender_kp = Keypair::generate();
+    let sender_did = Did::from_public_key(&sender_kp.public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(sender_did.clone())
+        .amount(100)
+        .allow_self_payment()
+        .build()
+        .expect("Self-payment should be allowed when opted in");
+
+    assert_eq!(signed_iou.iou().recipient(), &sender_did);
+    assert!(signed_iou.verify(&sender_kp.public_key()));
+}
+
 /// Test: Built IOU has correct sender DID derived from keypair
 #[test]
 fn test_builder_derives_sender_did() {
@@ -367,3 +385,342 @@ fn test_builder_chainable() {
 
     assert!(result.is_ok());
 }
+
+// ============================================================================
+// EXPIRY TESTS
+// ============================================================================
+
+/// Test: IOUs have no expiry by default
+#[test]
+fn test_builder_no_expiry_by_default() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&recipient_kp.public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .build()
+        .unwrap();
+
+    assert_eq!(signed_iou.iou().expiry(), None);
+}
+
+/// Test: Absolute expiry is stored on the IOU
+#[test]
+fn test_builder_with_expiry() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&recipient_kp.public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .timestamp(1703612400)
+        .expiry(1703616000)
+        .build()
+        .unwrap();
+
+    assert_eq!(signed_iou.iou().expiry(), Some(1703616000));
+    assert!(signed_iou.verify(&sender_kp.public_key()));
+}
+
+/// Test: TTL is resolved relative to the IOU timestamp
+#[test]
+fn test_builder_with_ttl() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&recipient_kp.public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .timestamp(1703612400)
+        .ttl_secs(3600)
+        .build()
+        .unwrap();
+
+    assert_eq!(signed_iou.iou().expiry(), Some(1703612400 + 3600));
+}
+
+/// Test: Expiry at or before the timestamp is rejected
+#[test]
+fn test_builder_expiry_before_timestamp_fails() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&recipient_kp.public_key());
+
+    let result = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .timestamp(1703612400)
+        .expiry(1703612400)
+        .build();
+
+    assert!(matches!(result, Err(IOUError::InvalidExpiry(_))));
+}
+
+/// Test: Expiry changes the IOU ID (it is part of the signed payload)
+#[test]
+fn test_builder_expiry_changes_id() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&recipient_kp.public_key());
+
+    let without = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient.clone())
+        .amount(100)
+        .nonce(1)
+        .timestamp(1703612400)
+        .build()
+        .unwrap();
+
+    let with = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .nonce(1)
+        .timestamp(1703612400)
+        .expiry(1703616000)
+        .build()
+        .unwrap();
+
+    assert_ne!(without.id(), with.id());
+}
+
+/// Test: expires_at sets an absolute expiry
+#[test]
+fn test_builder_expires_at() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&recipient_kp.public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .timestamp(1703612400)
+        .expires_at(1703616000)
+        .build()
+        .unwrap();
+
+    assert_eq!(signed_iou.iou().expiry(), Some(1703616000));
+}
+
+/// Test: expires_in_secs is relative to the IOU timestamp
+#[test]
+fn test_builder_expires_in_secs() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&recipient_kp.public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .timestamp(1703612400)
+        .expires_in_secs(600)
+        .build()
+        .unwrap();
+
+    assert_eq!(signed_iou.iou().expiry(), Some(1703612400 + 600));
+    assert!(!signed_iou.iou().is_expired_at(1703612400 + 599));
+    assert!(signed_iou.iou().is_expired_at(1703612400 + 600));
+}
+
+/// Test: IOUs have no memo by default
+#[test]
+fn test_builder_no_memo_by_default() {
+    let sender_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .build()
+        .unwrap();
+
+    assert_eq!(signed_iou.memo(), None);
+}
+
+/// Test: Memo is stored on the IOU
+#[test]
+fn test_builder_with_memo() {
+    let sender_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .memo("order #4711")
+        .build()
+        .unwrap();
+
+    assert_eq!(signed_iou.memo(), Some("order #4711"));
+    assert_eq!(signed_iou.iou().memo(), Some("order #4711"));
+    assert!(signed_iou.verify(&sender_kp.public_key()));
+}
+
+/// Test: A memo at the limit is accepted, one byte over is rejected
+#[test]
+fn test_builder_memo_length_limit() {
+    let sender_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+
+    let at_limit = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient.clone())
+        .amount(100)
+        .memo("a".repeat(MAX_MEMO_BYTES))
+        .build();
+    assert!(at_limit.is_ok());
+
+    let over_limit = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .memo("a".repeat(MAX_MEMO_BYTES + 1))
+        .build();
+    assert!(matches!(
+        over_limit,
+        Err(IOUError::MemoTooLong { len, max }) if len == MAX_MEMO_BYTES + 1 && max == MAX_MEMO_BYTES
+    ));
+}
+
+/// Test: The memo limit counts UTF-8 bytes, not characters
+#[test]
+fn test_builder_memo_limit_counts_bytes() {
+    let sender_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+
+    // 71 two-byte characters = 142 bytes
+    let result = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .memo("é".repeat(71))
+        .build();
+
+    assert!(matches!(result, Err(IOUError::MemoTooLong { len: 142, .. })));
+}
+
+/// Test: Memo changes the IOU ID (it is part of the signed payload)
+#[test]
+fn test_builder_memo_changes_id() {
+    let sender_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+    let build = |memo: Option<&str>| {
+        let mut builder = IOUBuilder::new()
+            .sender(&sender_kp)
+            .recipient(recipient.clone())
+            .amount(100)
+            .nonce(1)
+            .timestamp(1703612400);
+        if let Some(memo) = memo {
+            builder = builder.memo(memo);
+        }
+        builder.build().unwrap()
+    };
+
+    let without = build(None);
+    let first = build(Some("order 1"));
+    let second = build(Some("order 2"));
+
+    assert_ne!(without.id(), first.id());
+    assert_ne!(first.id(), second.id());
+}
+
+// ============================================================================
+// EXTERNAL SIGNER TESTS
+// ============================================================================
+
+/// Signer standing in for a key held in a platform keystore
+struct KeystoreSigner(Keypair);
+
+impl KeySigner for KeystoreSigner {
+    fn public_key(&self) -> PublicKey {
+        self.0.public_key()
+    }
+
+    fn sign(&self, message: &[u8]) -> Signature {
+        Signer::sign(&self.0, message)
+    }
+}
+
+/// Test: IOUs signed through sender_signer verify like any other
+#[test]
+fn test_builder_sender_signer_verifies() {
+    let signer = KeystoreSigner(Keypair::generate());
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender_signer(&signer)
+        .recipient(recipient)
+        .amount(100)
+        .build()
+        .expect("Should build with an external signer");
+
+    assert_eq!(signed_iou.iou().sender(), &Did::from_public_key(&signer.public_key()));
+    assert!(signed_iou.verify(&signer.public_key()));
+}
+
+/// Test: sender_signer and sender give the same IOU for the same key
+#[test]
+fn test_builder_sender_signer_matches_keypair() {
+    let keypair = Keypair::generate();
+    let signer = KeystoreSigner(Keypair::from_bytes(&keypair.to_bytes()).unwrap());
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+    let build = |builder: IOUBuilder| {
+        builder
+            .recipient(recipient.clone())
+            .amount(100)
+            .nonce(1)
+            .timestamp(1703612400)
+            .build()
+            .unwrap()
+    };
+
+    let direct = build(IOUBuilder::new().sender(&keypair));
+    let external = build(IOUBuilder::new().sender_signer(&signer));
+
+    assert_eq!(direct.id(), external.id());
+    assert_eq!(direct.signature(), external.signature());
+}
+
+/// Test: A signer with the wrong key yields an IOU that fails verification
+#[test]
+fn test_builder_sender_signer_wrong_key_fails_verify() {
+    struct Mismatched {
+        claimed: Keypair,
+        actual: Keypair,
+    }
+
+    impl KeySigner for Mismatched {
+        fn public_key(&self) -> PublicKey {
+            self.claimed.public_key()
+        }
+
+        fn sign(&self, message: &[u8]) -> Signature {
+            Signer::sign(&self.actual, message)
+        }
+    }
+
+    let signer = Mismatched { claimed: Keypair::generate(), actual: Keypair::generate() };
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+
+    let signed_iou = IOUBuilder::new()
+        .sender_signer(&signer)
+        .recipient(recipient)
+        .amount(100)
+        .build()
+        .unwrap();
+
+    assert!(!signed_iou.verify(&signer.public_key()));
+}
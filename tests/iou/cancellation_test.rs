@@ -0,0 +1,94 @@
+use p2pmesh::identity::{Did, Keypair, Signer};
+use p2pmesh::iou::{Cancellation, IOUBuilder, SignedCancellation, SignedIOU};
+
+// ============================================================================
+// CANCELLATION TESTS
+// ============================================================================
+
+/// Helper to create an IOU from `sender` to a fresh recipient
+fn create_iou_from(sender: &Keypair) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(100)
+        .build()
+        .unwrap()
+}
+
+/// Test: Signed cancellation carries the IOU id and sender
+#[test]
+fn test_cancellation_fields() {
+    let sender = Keypair::generate();
+    let iou = create_iou_from(&sender);
+
+    let cancellation = SignedCancellation::sign(&sender, iou.id());
+
+    assert_eq!(cancellation.cancellation().iou_id(), &iou.id());
+    assert_eq!(cancellation.cancellation().sender(), &Did::from_public_key(&sender.public_key()));
+    assert!(cancellation.cancellation().timestamp() > 0);
+}
+
+/// Test: Cancellation signature verifies against the sender
+#[test]
+fn test_cancellation_signature_verifies() {
+    let sender = Keypair::generate();
+    let iou = create_iou_from(&sender);
+
+    let cancellation = SignedCancellation::sign(&sender, iou.id());
+
+    assert!(cancellation.verify(&sender.public_key()));
+    assert!(cancellation.verify_sender());
+    assert!(!cancellation.verify(&Keypair::generate().public_key()));
+}
+
+/// Test: Cancellation only cancels the sender's own IOU
+#[test]
+fn test_cancels_only_own_iou() {
+    let sender = Keypair::generate();
+    let mallory = Keypair::generate();
+    let iou = create_iou_from(&sender);
+    let other = create_iou_from(&sender);
+
+    assert!(SignedCancellation::sign(&sender, iou.id()).cancels(&iou));
+    assert!(!SignedCancellation::sign(&sender, iou.id()).cancels(&other));
+    assert!(!SignedCancellation::sign(&mallory, iou.id()).cancels(&iou));
+}
+
+/// Test: Claiming to be the sender without their key fails verification
+#[test]
+fn test_forged_cancellation_fails() {
+    let sender = Keypair::generate();
+    let mallory = Keypair::generate();
+    let iou = create_iou_from(&sender);
+
+    let claimed = Cancellation::new(iou.id(), Did::from_public_key(&sender.public_key()), 1_000);
+    let signature = Signer::sign(&mallory, &claimed.to_signing_bytes());
+    let forged = SignedCancellation::from_parts(claimed, signature);
+
+    assert!(forged.cancels(&iou));
+    assert!(!forged.verify_sender());
+}
+
+/// Test: Cancellation survives a bytes round-trip
+#[test]
+fn test_cancellation_bytes_roundtrip() {
+    let sender = Keypair::generate();
+    let iou = create_iou_from(&sender);
+    let cancellation = SignedCancellation::sign(&sender, iou.id());
+
+    let decoded = SignedCancellation::from_bytes(&cancellation.to_bytes()).unwrap();
+
+    assert_eq!(decoded.cancellation(), cancellation.cancellation());
+    assert!(decoded.verify_sender());
+    assert!(SignedCancellation::from_bytes(&[0xFF; 4]).is_err());
+}
+
+/// Test: Cancellation signatures can't be replayed as IOU signatures
+#[test]
+fn test_cancellation_signing_bytes_domain_separated() {
+    let sender = Keypair::generate();
+    let iou = create_iou_from(&sender);
+    let cancellation = SignedCancellation::sign(&sender, iou.id());
+
+    assert!(!Signer::verify(&sender.public_key(), &iou.iou().to_signing_bytes(), cancellation.signature()));
+}
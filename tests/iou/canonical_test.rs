@@ -0,0 +1,204 @@
+// Canonical encoding tests
+// Tests the fixed byte layout IOU IDs and signatures are computed over
+
+use p2pmesh::identity::{Did, Keypair, Signer};
+use p2pmesh::iou::{
+    CodecError, IOUBuilder, IOUCodec, PaymentRequestId, SignedIOU, CANONICAL_VERSION, IOU,
+};
+use sha2::{Digest, Sha256};
+
+fn fixed_keypair(byte: u8) -> Keypair {
+    Keypair::from_bytes(&[byte; 32]).unwrap()
+}
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+/// IOU with fixed keys and fields, so its encoding is the same on every run
+fn fixed_iou() -> SignedIOU {
+    IOUBuilder::new()
+        .sender(&fixed_keypair(0x11))
+        .recipient(did(&fixed_keypair(0x22)))
+        .amount(1500)
+        .nonce(7)
+        .timestamp(1_700_000_000)
+        .build()
+        .unwrap()
+}
+
+/// IOU using every optional field
+fn full_iou() -> SignedIOU {
+    IOUBuilder::new()
+        .sender(&fixed_keypair(0x11))
+        .add_output(did(&fixed_keypair(0x22)), 1000)
+        .add_output(did(&fixed_keypair(0x33)), 500)
+        .nonce(8)
+        .timestamp(1_700_000_000)
+        .expiry(1_700_086_400)
+        .memo("order #1234")
+        .request_id(PaymentRequestId::from_bytes([0x44; 32]))
+        .build()
+        .unwrap()
+}
+
+// ============================================================================
+// ROUNDTRIP
+// ============================================================================
+
+#[test]
+fn test_reencoding_decoded_iou_is_identical() {
+    for signed in [fixed_iou(), full_iou()] {
+        let bytes = signed.to_canonical_bytes().unwrap();
+        let decoded = SignedIOU::from_canonical_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded, signed);
+        assert_eq!(decoded.to_canonical_bytes().unwrap(), bytes);
+        assert_eq!(decoded.id(), signed.id());
+    }
+}
+
+#[test]
+fn test_unsigned_iou_roundtrip() {
+    let iou = full_iou().iou().clone();
+    let bytes = iou.to_canonical_bytes().unwrap();
+
+    let decoded = IOU::from_canonical_bytes(&bytes).unwrap();
+    assert_eq!(decoded, iou);
+    assert_eq!(decoded.to_canonical_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_canonical_bytes_survive_other_codecs() {
+    let signed = full_iou();
+    let canonical = signed.to_canonical_bytes().unwrap();
+
+    let via_postcard = IOUCodec::decode(&IOUCodec::encode(&signed)).unwrap();
+    let via_json = IOUCodec::from_json(&IOUCodec::to_json(&signed)).unwrap();
+
+    assert_eq!(via_postcard.to_canonical_bytes().unwrap(), canonical);
+    assert_eq!(via_json.to_canonical_bytes().unwrap(), canonical);
+}
+
+// ============================================================================
+// IDS AND SIGNATURES
+// ============================================================================
+
+#[test]
+fn test_id_is_hash_of_canonical_iou() {
+    let signed = full_iou();
+    let hash = Sha256::digest(signed.iou().to_canonical_bytes().unwrap());
+
+    assert_eq!(signed.id().as_bytes().as_slice(), hash.as_slice());
+}
+
+#[test]
+fn test_signature_covers_canonical_iou() {
+    let signed = full_iou();
+    let bytes = signed.iou().to_canonical_bytes().unwrap();
+
+    assert_eq!(signed.iou().to_signing_bytes(), bytes);
+    assert!(Signer::verify(&fixed_keypair(0x11).public_key(), &bytes, signed.signature()));
+}
+
+#[test]
+fn test_id_is_stable_across_runs() {
+    let signed = fixed_iou();
+
+    assert_eq!(
+        hex::encode(signed.id().as_bytes()),
+        "783faa208de0f985426355700784279f2836d9ff2ae8b82974dac1e687ad50c5"
+    );
+    assert_eq!(
+        hex::encode(signed.iou().to_canonical_bytes().unwrap()),
+        "35000000\
+         6469643a6d6573683a463235733344646a5864437859426868327a384642757356454d5434623962474e46564b4a693377466f4634\
+         35000000\
+         6469643a6d6573683a426f773143474b474442396d4e7865576477383545326143746851316f5a58346f4645653766595431376577\
+         dc05000000000000\
+         0700000000000000\
+         00f1536500000000"
+    );
+    // Ed25519 signatures are deterministic, so the signed encoding is fixed too
+    assert_eq!(
+        hex::encode(Sha256::digest(signed.to_canonical_bytes().unwrap())),
+        "0018323bd22b5775fb3c86e9894d635edfead28147cf9992dc63fc17eae743bd"
+    );
+}
+
+#[test]
+fn test_signed_layout() {
+    let signed = fixed_iou();
+    let iou = signed.iou().to_canonical_bytes().unwrap();
+    let bytes = signed.to_canonical_bytes().unwrap();
+
+    assert_eq!(bytes[0], CANONICAL_VERSION);
+    assert_eq!(bytes[1..5], (iou.len() as u32).to_le_bytes());
+    assert_eq!(bytes[5..5 + iou.len()], iou[..]);
+    assert_eq!(bytes[5 + iou.len()..], signed.signature().as_bytes()[..]);
+}
+
+// ============================================================================
+// REJECTION
+// ============================================================================
+
+#[test]
+fn test_rejects_unsupported_version() {
+    let mut bytes = fixed_iou().to_canonical_bytes().unwrap();
+    bytes[0] = CANONICAL_VERSION + 1;
+
+    let result = SignedIOU::from_canonical_bytes(&bytes);
+    assert!(matches!(result, Err(CodecError::UnsupportedVersion(_))));
+}
+
+#[test]
+fn test_rejects_truncated_and_trailing_bytes() {
+    let bytes = full_iou().to_canonical_bytes().unwrap();
+
+    for len in 0..bytes.len() {
+        assert!(SignedIOU::from_canonical_bytes(&bytes[..len]).is_err(), "length {}", len);
+    }
+
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert!(matches!(
+        SignedIOU::from_canonical_bytes(&extended),
+        Err(CodecError::DecodeError(_))
+    ));
+}
+
+#[test]
+fn test_rejects_optional_fields_out_of_order() {
+    let base = fixed_iou().iou().to_canonical_bytes().unwrap();
+
+    // Memo before expiry
+    let mut bytes = base.clone();
+    bytes.push(0x02);
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.push(b'x');
+    bytes.push(0x01);
+    bytes.extend_from_slice(&2_000_000_000u64.to_le_bytes());
+    assert!(IOU::from_canonical_bytes(&bytes).is_err());
+
+    // Expiry twice
+    let mut bytes = base;
+    for _ in 0..2 {
+        bytes.push(0x01);
+        bytes.extend_from_slice(&2_000_000_000u64.to_le_bytes());
+    }
+    assert!(IOU::from_canonical_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_rejects_unknown_tag_and_empty_outputs() {
+    let base = fixed_iou().iou().to_canonical_bytes().unwrap();
+
+    let mut unknown = base.clone();
+    unknown.push(0x09);
+    assert!(IOU::from_canonical_bytes(&unknown).is_err());
+
+    let mut empty_outputs = base;
+    empty_outputs.push(0x04);
+    empty_outputs.extend_from_slice(&0u32.to_le_bytes());
+    assert!(IOU::from_canonical_bytes(&empty_outputs).is_err());
+}
@@ -1,5 +1,7 @@
 use p2pmesh::identity::{Keypair, Did};
-use p2pmesh::iou::{IOUBuilder, SignedIOU, IOUCodec};
+use p2pmesh::iou::{CodecError, Denomination, IOUBuilder, PaymentRequestId, SignedIOU, IOUCodec, QR_PREFIX};
+use p2pmesh::identity::Signature;
+use p2pmesh::iou::IOU;
 
 // ============================================================================
 // IOU CODEC (SERIALIZATION) TESTS
@@ -355,3 +357,350 @@ fn test_serialization_preserves_signature() {
         "Deserialized IOU should still have valid signature"
     );
 }
+
+// ============================================================================
+// QR STRING TESTS
+// ============================================================================
+
+/// Test: QR string has prefix and version
+#[test]
+fn test_encode_qr_has_prefix_and_version() {
+    let qr = IOUCodec::encode_qr(&create_signed_iou());
+
+    assert!(qr.starts_with(QR_PREFIX));
+    assert!(qr.starts_with("p2pm:1:"));
+}
+
+/// Test: QR string round-trips
+#[test]
+fn test_qr_roundtrip() {
+    let original = create_signed_iou();
+    let qr = IOUCodec::encode_qr(&original);
+
+    let decoded = IOUCodec::decode_qr(&qr).expect("Should decode valid QR string");
+
+    assert_eq!(original.id(), decoded.id());
+    assert_eq!(original.signature(), decoded.signature());
+}
+
+/// Test: Surrounding whitespace from scanners is tolerated
+#[test]
+fn test_qr_decode_trims_whitespace() {
+    let original = create_signed_iou();
+    let qr = format!("  {}\n", IOUCodec::encode_qr(&original));
+
+    let decoded = IOUCodec::decode_qr(&qr).expect("Should decode trimmed QR string");
+    assert_eq!(original.id(), decoded.id());
+}
+
+/// Test: Missing prefix is rejected
+#[test]
+fn test_qr_missing_prefix_fails() {
+    let qr = IOUCodec::encode_qr(&create_signed_iou());
+    let stripped = qr.trim_start_matches(QR_PREFIX);
+
+    assert!(matches!(IOUCodec::decode_qr(stripped), Err(CodecError::InvalidQr(_))));
+}
+
+/// Test: Unknown version is rejected
+#[test]
+fn test_qr_unknown_version_fails() {
+    let qr = IOUCodec::encode_qr(&create_signed_iou());
+    let bumped = qr.replacen("p2pm:1:", "p2pm:2:", 1);
+
+    assert!(matches!(IOUCodec::decode_qr(&bumped), Err(CodecError::UnsupportedVersion(_))));
+}
+
+/// Test: Tampered payload fails the checksum
+#[test]
+fn test_qr_tampered_payload_fails_checksum() {
+    let qr = IOUCodec::encode_qr(&create_signed_iou());
+    let prefix_len = "p2pm:1:".len();
+
+    // Flip one base64 character in the middle of the payload
+    let mut chars: Vec<char> = qr.chars().collect();
+    let idx = prefix_len + (chars.len() - prefix_len) / 2;
+    chars[idx] = if chars[idx] == 'A' { 'B' } else { 'A' };
+    let tampered: String = chars.into_iter().collect();
+
+    assert!(matches!(IOUCodec::decode_qr(&tampered), Err(CodecError::ChecksumMismatch)));
+}
+
+/// Test: Truncated QR payload is rejected
+#[test]
+fn test_qr_truncated_payload_fails() {
+    let qr = IOUCodec::encode_qr(&create_signed_iou());
+    let truncated = &qr[..qr.len() - 10];
+
+    assert!(IOUCodec::decode_qr(truncated).is_err());
+}
+
+/// Test: Invalid base64 characters are rejected
+#[test]
+fn test_qr_invalid_base64_fails() {
+    let result = IOUCodec::decode_qr("p2pm:1:!!!not-base64!!!");
+    assert!(matches!(result, Err(CodecError::InvalidBase64(_))));
+}
+
+// ============================================================================
+// EXPIRY COMPATIBILITY TESTS
+// ============================================================================
+
+/// Test: Expiry survives an encode/decode roundtrip
+#[test]
+fn test_roundtrip_with_expiry() {
+    let sender_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+    let original = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .timestamp(1703612400)
+        .expires_in_secs(3600)
+        .build()
+        .unwrap();
+
+    let decoded = IOUCodec::decode(&IOUCodec::encode(&original)).unwrap();
+
+    assert_eq!(decoded, original);
+    assert_eq!(decoded.iou().expiry(), Some(1703612400 + 3600));
+    assert!(decoded.verify(&sender_kp.public_key()));
+}
+
+/// Test: IOUs encoded before the expiry field existed still decode
+#[test]
+fn test_decode_legacy_iou_without_expiry_field() {
+    let sender_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+    let original = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .build()
+        .unwrap();
+
+    // Current layout: ... timestamp, expiry tag (0 = None), signature (len 64 + 64 bytes),
+    // memo tag, request ID tag, output count, denomination tag, hashlock tag
+    let mut legacy = IOUCodec::encode(&original);
+    for _ in 0..5 {
+        assert_eq!(legacy.pop(), Some(0));
+    }
+    let tag = legacy.len() - 66;
+    assert_eq!(legacy[tag], 0);
+    legacy.remove(tag);
+
+    let decoded = IOUCodec::decode(&legacy).unwrap();
+
+    assert_eq!(decoded, original);
+    assert_eq!(decoded.id(), original.id());
+    assert_eq!(decoded.iou().expiry(), None);
+    assert!(decoded.verify(&sender_kp.public_key()));
+}
+
+/// Test: Garbage still fails to decode
+#[test]
+fn test_decode_garbage_fails() {
+    let result = IOUCodec::decode(&[0xFF; 10]);
+    assert!(matches!(result, Err(CodecError::DecodeError(_))));
+}
+
+// ============================================================================
+// HISTORICAL LAYOUT TESTS
+// ============================================================================
+
+/// Encode `signed` in the layout that ended `dropped` trailing fields early,
+/// checking that each dropped field was unset
+fn encode_without_trailing(signed: &SignedIOU, dropped: usize) -> Vec<u8> {
+    let mut bytes = IOUCodec::encode(signed);
+    for _ in 0..dropped {
+        assert_eq!(bytes.pop(), Some(0));
+    }
+    bytes
+}
+
+fn assert_decodes_unchanged(bytes: &[u8], original: &SignedIOU, sender_kp: &Keypair) {
+    let decoded = IOUCodec::decode(bytes).unwrap();
+    assert_eq!(&decoded, original);
+    assert_eq!(decoded.id(), original.id());
+    assert!(decoded.verify(&sender_kp.public_key()));
+}
+
+/// Test: The memo-era layout (memo, nothing after) decodes with its memo
+#[test]
+fn test_decode_memo_layout() {
+    let sender_kp = Keypair::generate();
+    let original = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(100)
+        .expires_in_secs(3600)
+        .memo("order 7")
+        .build()
+        .unwrap();
+
+    let bytes = encode_without_trailing(&original, 4);
+
+    assert_decodes_unchanged(&bytes, &original, &sender_kp);
+}
+
+/// Test: The request-era layout (memo, request ID) decodes with both
+#[test]
+fn test_decode_request_layout() {
+    let sender_kp = Keypair::generate();
+    let original = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(100)
+        .memo("invoice 3")
+        .request_id(PaymentRequestId::from_bytes([0x21; 32]))
+        .build()
+        .unwrap();
+
+    let bytes = encode_without_trailing(&original, 3);
+
+    assert_decodes_unchanged(&bytes, &original, &sender_kp);
+    assert_eq!(IOUCodec::decode(&bytes).unwrap().iou().request_id(), original.iou().request_id());
+}
+
+/// Test: The outputs-era layout (memo, request ID, outputs) decodes with its outputs
+#[test]
+fn test_decode_outputs_layout() {
+    let sender_kp = Keypair::generate();
+    let original = IOUBuilder::new()
+        .sender(&sender_kp)
+        .add_output(Did::from_public_key(&Keypair::generate().public_key()), 30)
+        .add_output(Did::from_public_key(&Keypair::generate().public_key()), 70)
+        .memo("split")
+        .build()
+        .unwrap();
+
+    let bytes = encode_without_trailing(&original, 2);
+
+    assert_decodes_unchanged(&bytes, &original, &sender_kp);
+    assert_eq!(IOUCodec::decode(&bytes).unwrap().iou().outputs(), original.iou().outputs());
+}
+
+/// Test: The denomination-era layout (everything but the hashlock) decodes with its denomination
+#[test]
+fn test_decode_denomination_layout() {
+    let sender_kp = Keypair::generate();
+    let original = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(250)
+        .memo("lunch")
+        .request_id(PaymentRequestId::from_bytes([0x33; 32]))
+        .denomination(Denomination::new("EUR", 2).unwrap())
+        .build()
+        .unwrap();
+
+    let bytes = encode_without_trailing(&original, 1);
+
+    assert_decodes_unchanged(&bytes, &original, &sender_kp);
+    assert_eq!(IOUCodec::decode(&bytes).unwrap().iou().denomination(), original.iou().denomination());
+}
+
+/// Test: Every layout ending at a field boundary decodes to the same plain IOU
+#[test]
+fn test_decode_every_trailing_boundary() {
+    let sender_kp = Keypair::generate();
+    let original = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(100)
+        .build()
+        .unwrap();
+
+    for dropped in 0..=5 {
+        assert_decodes_unchanged(&encode_without_trailing(&original, dropped), &original, &sender_kp);
+    }
+}
+
+/// Test: An encoding cut off inside a trailing field fails instead of dropping it
+#[test]
+fn test_decode_cut_inside_trailing_field_fails() {
+    let (original, _) = signed_iou_with_memo("a memo that gets cut");
+    let mut bytes = encode_without_trailing(&original, 4);
+    bytes.truncate(bytes.len() - 3);
+
+    assert!(matches!(IOUCodec::decode(&bytes), Err(CodecError::DecodeError(_))));
+}
+
+// ============================================================================
+// MEMO COMPATIBILITY TESTS
+// ============================================================================
+
+fn signed_iou_with_memo(memo: &str) -> (SignedIOU, Keypair) {
+    let sender_kp = Keypair::generate();
+    let recipient = Did::from_public_key(&Keypair::generate().public_key());
+    let signed = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(recipient)
+        .amount(100)
+        .memo(memo)
+        .build()
+        .unwrap();
+    (signed, sender_kp)
+}
+
+/// Test: Memo survives an encode/decode roundtrip
+#[test]
+fn test_roundtrip_with_memo() {
+    let (original, sender_kp) = signed_iou_with_memo("order #4711");
+
+    let decoded = IOUCodec::decode(&IOUCodec::encode(&original)).unwrap();
+
+    assert_eq!(decoded, original);
+    assert_eq!(decoded.memo(), Some("order #4711"));
+    assert!(decoded.verify(&sender_kp.public_key()));
+}
+
+/// Test: Memo survives the QR encoding
+#[test]
+fn test_qr_roundtrip_with_memo() {
+    let (original, _) = signed_iou_with_memo("café ☕");
+
+    let decoded = IOUCodec::decode_qr(&IOUCodec::encode_qr(&original)).unwrap();
+
+    assert_eq!(decoded.memo(), Some("café ☕"));
+}
+
+/// Test: IOUs encoded before the memo field existed still decode
+#[test]
+fn test_decode_pre_memo_iou() {
+    let original = create_signed_iou();
+
+    // Pre-memo layout is the current one without the trailing memo, request ID, outputs,
+    // denomination and hashlock
+    let mut pre_memo = IOUCodec::encode(&original);
+    for _ in 0..5 {
+        assert_eq!(pre_memo.pop(), Some(0));
+    }
+
+    let decoded = IOUCodec::decode(&pre_memo).unwrap();
+
+    assert_eq!(decoded, original);
+    assert_eq!(decoded.memo(), None);
+}
+
+/// Test: A node that predates the memo can still read the IOU it precedes
+#[test]
+fn test_pre_memo_node_reads_new_encoding() {
+    #[derive(serde::Deserialize)]
+    struct PreMemoSignedIOU {
+        iou: IOU,
+        signature: Signature,
+    }
+
+    let original = create_signed_iou();
+    let (with_memo, _) = signed_iou_with_memo("invoice 12");
+
+    let old: PreMemoSignedIOU = postcard::from_bytes(&IOUCodec::encode(&original)).unwrap();
+    assert_eq!(&old.iou, original.iou());
+    assert_eq!(&old.signature, original.signature());
+
+    // The memo is lost on such a node, so the IOU no longer matches its signature
+    let old: PreMemoSignedIOU = postcard::from_bytes(&IOUCodec::encode(&with_memo)).unwrap();
+    assert_eq!(old.iou.amount(), 100);
+    assert_eq!(old.iou.memo(), None);
+}
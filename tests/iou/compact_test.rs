@@ -0,0 +1,280 @@
+// Compact encoding tests
+// Tests the LoRa-sized framing of signed IOUs and its reassembly
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{
+    CodecError, CompactDecoder, CompactIOU, DidResolver, DidTable, Frame, IOUBuilder, IOUCodec,
+    IOUValidator, KeyHash, PaymentRequestId, SignedIOU, MAX_COMPACT_FRAME_LEN,
+    MAX_PENDING_COMPACT_IOUS,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// A typical payment: random nonce, current timestamp, no optional fields
+fn typical_iou(sender: &Keypair, recipient: &Keypair) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(did(recipient))
+        .amount(1_500)
+        .build()
+        .unwrap()
+}
+
+fn table(keypairs: &[&Keypair]) -> DidTable {
+    let mut table = DidTable::new();
+    for keypair in keypairs {
+        table.insert(did(keypair));
+    }
+    table
+}
+
+fn decode_all(decoder: &mut CompactDecoder<DidTable>, frames: &[Frame]) -> Option<SignedIOU> {
+    let mut result = None;
+    for frame in frames {
+        if let Some(iou) = decoder.push(&frame.to_bytes()).unwrap() {
+            result = Some(iou);
+        }
+    }
+    result
+}
+
+// ============================================================================
+// SIZE
+// ============================================================================
+
+#[test]
+fn test_typical_iou_fits_lora_frames() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let signed = typical_iou(&sender, &recipient);
+
+    let frames = IOUCodec::encode_compact(&signed).unwrap();
+
+    for frame in &frames {
+        assert!(frame.to_bytes().len() <= MAX_COMPACT_FRAME_LEN);
+        assert!(frame.to_bytes().len() <= 51);
+    }
+    assert!(frames.len() <= 3, "typical IOU took {} frames", frames.len());
+
+    let compact_len = CompactIOU::from_signed(&signed).to_bytes().len();
+    assert!(compact_len < IOUCodec::encode(&signed).len());
+    assert!(compact_len <= 110, "compact IOU is {} bytes", compact_len);
+}
+
+#[test]
+fn test_large_fields_still_fit_frames() {
+    let sender = Keypair::generate();
+    let signed = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&Keypair::generate()), u64::MAX / 4)
+        .add_output(did(&Keypair::generate()), u64::MAX / 4)
+        .nonce(u64::MAX)
+        .expires_in_secs(3600)
+        .memo("x".repeat(140))
+        .build()
+        .unwrap();
+
+    let frames = IOUCodec::encode_compact(&signed).unwrap();
+    assert!(frames.len() > 3);
+    for (i, frame) in frames.iter().enumerate() {
+        assert!(frame.len() <= MAX_COMPACT_FRAME_LEN);
+        assert_eq!(frame.index() as usize, i);
+        assert_eq!(frame.total() as usize, frames.len());
+    }
+}
+
+// ============================================================================
+// ROUND TRIPS
+// ============================================================================
+
+#[test]
+fn test_compact_round_trip_verifies() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let signed = typical_iou(&sender, &recipient);
+
+    let frames = IOUCodec::encode_compact(&signed).unwrap();
+    let mut decoder = CompactDecoder::new(table(&[&sender, &recipient]));
+    let decoded = decode_all(&mut decoder, &frames).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert!(IOUValidator::validate(&decoded, &sender.public_key()).is_ok());
+    assert_eq!(decoder.pending_count(), 0);
+}
+
+#[test]
+fn test_compact_round_trip_all_optional_fields() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let signed = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), 30)
+        .add_output(did(&bob), 70)
+        .expires_at(now() + 600)
+        .memo("coffee \u{2615}")
+        .request_id(PaymentRequestId::from_bytes([3u8; 32]))
+        .build()
+        .unwrap();
+
+    let frames = IOUCodec::encode_compact(&signed).unwrap();
+    let mut decoder = CompactDecoder::new(table(&[&sender, &alice, &bob]));
+    let decoded = decode_all(&mut decoder, &frames).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert_eq!(decoded.iou().outputs(), signed.iou().outputs());
+    assert!(IOUValidator::validate(&decoded, &sender.public_key()).is_ok());
+}
+
+#[test]
+fn test_compact_bytes_round_trip() {
+    let signed = typical_iou(&Keypair::generate(), &Keypair::generate());
+    let compact = CompactIOU::from_signed(&signed);
+
+    assert_eq!(CompactIOU::from_bytes(&compact.to_bytes()).unwrap(), compact);
+    assert_eq!(compact.sender(), &KeyHash::of(signed.iou().sender()));
+    assert_eq!(compact.recipient(), &KeyHash::of(signed.iou().recipient()));
+}
+
+// ============================================================================
+// REASSEMBLY
+// ============================================================================
+
+#[test]
+fn test_frames_out_of_order() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let signed = typical_iou(&sender, &recipient);
+
+    let mut frames = IOUCodec::encode_compact(&signed).unwrap();
+    frames.reverse();
+
+    let mut decoder = CompactDecoder::new(table(&[&sender, &recipient]));
+    assert_eq!(decode_all(&mut decoder, &frames).unwrap(), signed);
+}
+
+#[test]
+fn test_interleaved_messages() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let first = typical_iou(&sender, &recipient);
+    let second = typical_iou(&sender, &recipient);
+
+    let a = IOUCodec::encode_compact(&first).unwrap();
+    let b = IOUCodec::encode_compact(&second).unwrap();
+    assert_ne!(a[0].message_id(), b[0].message_id());
+
+    let mut decoder = CompactDecoder::new(table(&[&sender, &recipient]));
+    let mut decoded = Vec::new();
+    for (x, y) in a.iter().zip(b.iter()) {
+        decoded.extend(decoder.push_frame(x.clone()).unwrap());
+        decoded.extend(decoder.push_frame(y.clone()).unwrap());
+    }
+
+    assert_eq!(decoded, vec![first, second]);
+}
+
+#[test]
+fn test_duplicate_frame_ignored() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let signed = typical_iou(&sender, &recipient);
+    let frames = IOUCodec::encode_compact(&signed).unwrap();
+
+    let mut decoder = CompactDecoder::new(table(&[&sender, &recipient]));
+    assert!(decoder.push_frame(frames[0].clone()).unwrap().is_none());
+    assert!(decoder.push_frame(frames[0].clone()).unwrap().is_none());
+    assert_eq!(decoder.pending_count(), 1);
+
+    assert_eq!(decode_all(&mut decoder, &frames[1..]).unwrap(), signed);
+}
+
+#[test]
+fn test_oldest_pending_message_dropped() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let mut decoder = CompactDecoder::new(table(&[&sender, &recipient]));
+
+    let first = typical_iou(&sender, &recipient);
+    let first_frames = IOUCodec::encode_compact(&first).unwrap();
+    decoder.push_frame(first_frames[0].clone()).unwrap();
+
+    for _ in 0..MAX_PENDING_COMPACT_IOUS {
+        let frames = IOUCodec::encode_compact(&typical_iou(&sender, &recipient)).unwrap();
+        decoder.push_frame(frames[0].clone()).unwrap();
+    }
+    assert_eq!(decoder.pending_count(), MAX_PENDING_COMPACT_IOUS);
+
+    // The first message's opening frame was evicted, so the rest can't complete it
+    assert!(decode_all(&mut decoder, &first_frames[1..]).is_none());
+}
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[test]
+fn test_unknown_did_rejected() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let signed = typical_iou(&sender, &recipient);
+    let frames = IOUCodec::encode_compact(&signed).unwrap();
+
+    let mut decoder = CompactDecoder::new(table(&[&sender]));
+    let mut result = Ok(None);
+    for frame in &frames {
+        result = decoder.push_frame(frame.clone());
+    }
+
+    match result {
+        Err(CodecError::UnresolvedDid(hash)) => assert_eq!(hash, KeyHash::of(&did(&recipient))),
+        other => panic!("Expected UnresolvedDid, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_wrong_resolution_fails_signature() {
+    struct Liar(Did);
+    impl DidResolver for Liar {
+        fn resolve(&self, _hash: &KeyHash) -> Option<Did> {
+            Some(self.0.clone())
+        }
+    }
+
+    let sender = Keypair::generate();
+    let signed = typical_iou(&sender, &Keypair::generate());
+    let compact = CompactIOU::from_signed(&signed);
+
+    let decoded = compact.resolve(&Liar(did(&Keypair::generate()))).unwrap();
+    assert!(IOUValidator::validate(&decoded, &sender.public_key()).is_err());
+}
+
+#[test]
+fn test_malformed_frames_rejected() {
+    let mut decoder = CompactDecoder::new(DidTable::new());
+
+    assert!(matches!(decoder.push(&[0, 1, 0]), Err(CodecError::InvalidFrame(_))));
+    assert!(matches!(decoder.push(&[0, 1, 2, 2, 9]), Err(CodecError::InvalidFrame(_))));
+    assert!(matches!(decoder.push(&[0, 1, 0, 0]), Err(CodecError::InvalidFrame(_))));
+    assert!(matches!(decoder.push(&[0u8; MAX_COMPACT_FRAME_LEN + 1]), Err(CodecError::InvalidFrame(_))));
+}
+
+#[test]
+fn test_truncated_compact_bytes_rejected() {
+    let signed = typical_iou(&Keypair::generate(), &Keypair::generate());
+    let bytes = CompactIOU::from_signed(&signed).to_bytes();
+
+    assert!(CompactIOU::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(CompactIOU::from_bytes(&[]).is_err());
+
+    let mut wrong_version = bytes.clone();
+    wrong_version[0] = 99;
+    assert!(matches!(CompactIOU::from_bytes(&wrong_version), Err(CodecError::UnsupportedVersion(_))));
+}
@@ -0,0 +1,230 @@
+// Denomination tests
+// Tests the currency code and decimal exponent IOU amounts can carry
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{
+    CompactIOU, Denomination, DenominationError, DidTable, IOUBuilder, IOUCodec, IOUValidator,
+    SignedIOU, IOU, MAX_DENOMINATION_EXPONENT,
+};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn usd() -> Denomination {
+    Denomination::new("USD", 2).unwrap()
+}
+
+fn denominated_iou(sender: &Keypair, recipient: &Keypair) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(did(recipient))
+        .amount(1250)
+        .denomination(usd())
+        .build()
+        .unwrap()
+}
+
+// ============================================================================
+// FORMATTING
+// ============================================================================
+
+#[test]
+fn test_format_amount_with_exponent() {
+    assert_eq!(usd().format_amount(1250), "12.50 USD");
+    assert_eq!(usd().format_amount(100), "1.00 USD");
+}
+
+#[test]
+fn test_format_amount_below_one_unit() {
+    assert_eq!(usd().format_amount(5), "0.05 USD");
+    assert_eq!(usd().format_amount(0), "0.00 USD");
+}
+
+#[test]
+fn test_format_amount_without_exponent() {
+    let jpy = Denomination::new("JPY", 0).unwrap();
+    assert_eq!(jpy.format_amount(1250), "1250 JPY");
+}
+
+#[test]
+fn test_format_amount_large_exponent() {
+    let bhd = Denomination::new("BHD", 3).unwrap();
+    assert_eq!(bhd.format_amount(1_234_567), "1234.567 BHD");
+
+    let wei = Denomination::new("ETH", MAX_DENOMINATION_EXPONENT).unwrap();
+    assert_eq!(wei.format_amount(u64::MAX), "18.446744073709551615 ETH");
+}
+
+#[test]
+fn test_display_is_code() {
+    assert_eq!(usd().to_string(), "USD");
+}
+
+// ============================================================================
+// VALIDATION
+// ============================================================================
+
+#[test]
+fn test_new_accepts_valid_codes() {
+    assert!(Denomination::new("EUR", 2).is_ok());
+    assert!(Denomination::new("USDC", 6).is_ok());
+    assert!(Denomination::new("POINTS25", 0).is_ok());
+}
+
+#[test]
+fn test_new_rejects_bad_codes() {
+    for code in ["", "US", "usd", "US D", "TOOLONGCODE", "€UR"] {
+        assert_eq!(
+            Denomination::new(code, 2),
+            Err(DenominationError::InvalidCode(code.to_string())),
+            "{:?}",
+            code
+        );
+    }
+}
+
+#[test]
+fn test_new_rejects_large_exponent() {
+    assert_eq!(
+        Denomination::new("USD", MAX_DENOMINATION_EXPONENT + 1),
+        Err(DenominationError::ExponentTooLarge(MAX_DENOMINATION_EXPONENT + 1))
+    );
+}
+
+// ============================================================================
+// SIGNED MODEL
+// ============================================================================
+
+#[test]
+fn test_builder_sets_denomination() {
+    let sender = Keypair::generate();
+    let signed = denominated_iou(&sender, &Keypair::generate());
+
+    assert_eq!(signed.iou().denomination(), Some(&usd()));
+    assert_eq!(signed.denomination(), Some(&usd()));
+    assert!(IOUValidator::validate(&signed, &sender.public_key()).is_ok());
+}
+
+#[test]
+fn test_iou_without_denomination_is_bare_credits() {
+    let signed = IOUBuilder::new()
+        .sender(&Keypair::generate())
+        .recipient(did(&Keypair::generate()))
+        .amount(10)
+        .build()
+        .unwrap();
+
+    assert_eq!(signed.denomination(), None);
+}
+
+#[test]
+fn test_denomination_is_signed() {
+    let sender = Keypair::generate();
+    let signed = denominated_iou(&sender, &Keypair::generate());
+
+    // Swapping the unit invalidates the signature and changes the ID
+    let eur = Denomination::new("EUR", 2).unwrap();
+    let swapped = SignedIOU::from_parts(signed.iou().clone().with_denomination(eur), signed.signature().clone());
+    assert!(!swapped.verify(&sender.public_key()));
+    assert_ne!(swapped.id(), signed.id());
+}
+
+// ============================================================================
+// CODECS
+// ============================================================================
+
+#[test]
+fn test_postcard_round_trip() {
+    let sender = Keypair::generate();
+    let signed = denominated_iou(&sender, &Keypair::generate());
+
+    let decoded = IOUCodec::decode(&IOUCodec::encode(&signed)).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert_eq!(decoded.denomination(), Some(&usd()));
+    assert!(decoded.verify(&sender.public_key()));
+}
+
+#[test]
+fn test_pre_denomination_bytes_still_decode() {
+    let sender = Keypair::generate();
+    let signed = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&Keypair::generate()), 3)
+        .add_output(did(&Keypair::generate()), 4)
+        .memo("split")
+        .build()
+        .unwrap();
+
+    // Drop the trailing hashlock and denomination tags
+    let mut bytes = IOUCodec::encode(&signed);
+    assert_eq!(bytes.pop(), Some(0));
+    assert_eq!(bytes.pop(), Some(0));
+
+    let decoded = IOUCodec::decode(&bytes).unwrap();
+    assert_eq!(decoded, signed);
+    assert_eq!(decoded.iou().outputs().len(), 2);
+    assert!(decoded.verify(&sender.public_key()));
+}
+
+#[test]
+fn test_canonical_round_trip() {
+    let signed = denominated_iou(&Keypair::generate(), &Keypair::generate());
+
+    let bytes = signed.iou().to_canonical_bytes().unwrap();
+    let decoded = IOU::from_canonical_bytes(&bytes).unwrap();
+
+    assert_eq!(&decoded, signed.iou());
+    assert_eq!(decoded.denomination(), Some(&usd()));
+}
+
+#[test]
+fn test_canonical_rejects_invalid_denomination() {
+    let signed = denominated_iou(&Keypair::generate(), &Keypair::generate());
+    let mut bytes = signed.iou().to_canonical_bytes().unwrap();
+
+    // Denomination is the last field: [0x05][len:4]["USD"][exponent]
+    let exponent = bytes.len() - 1;
+    bytes[exponent] = MAX_DENOMINATION_EXPONENT + 1;
+
+    assert!(IOU::from_canonical_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_json_round_trip() {
+    let sender = Keypair::generate();
+    let signed = denominated_iou(&sender, &Keypair::generate());
+
+    let json = IOUCodec::to_json(&signed);
+    assert!(json.contains(r#""denomination":{"code":"USD","exponent":"2"}"#));
+
+    let decoded = IOUCodec::from_json(&json).unwrap();
+    assert_eq!(decoded, signed);
+    assert!(decoded.verify(&sender.public_key()));
+}
+
+#[test]
+fn test_json_rejects_invalid_denomination() {
+    let signed = denominated_iou(&Keypair::generate(), &Keypair::generate());
+    let json = IOUCodec::to_json(&signed).replace(r#""code":"USD""#, r#""code":"usd""#);
+
+    assert!(IOUCodec::from_json(&json).is_err());
+}
+
+#[test]
+fn test_compact_round_trip() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let signed = denominated_iou(&sender, &recipient);
+
+    let mut table = DidTable::new();
+    table.insert(did(&sender));
+    table.insert(did(&recipient));
+
+    let compact = CompactIOU::from_bytes(&CompactIOU::from_signed(&signed).to_bytes()).unwrap();
+    let decoded = compact.resolve(&table).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert!(decoded.verify(&sender.public_key()));
+}
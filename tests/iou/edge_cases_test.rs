@@ -1,4 +1,4 @@
-use p2pmesh::identity::{Keypair, Did, Signer, Signature};
+use p2pmesh::identity::{Keypair, Did, Signature};
 use p2pmesh::iou::{IOU, IOUBuilder, SignedIOU, IOUValidator, IOUCodec, IOUId};
 use std::collections::HashSet;
 
@@ -428,7 +428,7 @@ fn test_large_batch_handling() {
 
     // Serialize all
     let serialized: Vec<Vec<u8>> = ious.iter()
-        .map(|iou| IOUCodec::encode(iou))
+        .map(IOUCodec::encode)
         .collect();
 
     assert_eq!(serialized.len(), 1000);
@@ -0,0 +1,167 @@
+// Hashlock tests
+// Tests IOUs held until the preimage of a committed hash is revealed
+
+use p2pmesh::identity::{Did, Keypair, Signer};
+use p2pmesh::iou::{
+    CompactIOU, DidTable, Hashlock, IOUBuilder, IOUCodec, IOUError, IOUValidator, SignedIOU,
+    ValidationError, IOU,
+};
+
+const PREIMAGE: &[u8] = b"swap secret";
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn hashlocked_iou(sender: &Keypair, recipient: &Keypair) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(did(recipient))
+        .amount(300)
+        .hashlock(Hashlock::from_preimage(PREIMAGE))
+        .ttl_secs(3600)
+        .build()
+        .unwrap()
+}
+
+// ============================================================================
+// HASHLOCK
+// ============================================================================
+
+#[test]
+fn test_hashlock_unlocked_by_preimage() {
+    let lock = Hashlock::from_preimage(PREIMAGE);
+
+    assert!(lock.is_unlocked_by(PREIMAGE));
+    assert!(!lock.is_unlocked_by(b"wrong secret"));
+    assert_eq!(Hashlock::from_bytes(*lock.as_bytes()), lock);
+}
+
+// ============================================================================
+// BUILDER AND VALIDATION
+// ============================================================================
+
+#[test]
+fn test_builder_sets_hashlock() {
+    let sender = Keypair::generate();
+    let signed = hashlocked_iou(&sender, &Keypair::generate());
+
+    assert_eq!(signed.hashlock(), Some(&Hashlock::from_preimage(PREIMAGE)));
+    assert!(signed.iou().expiry().is_some());
+    assert!(IOUValidator::validate(&signed, &sender.public_key()).is_ok());
+}
+
+#[test]
+fn test_builder_requires_expiry_with_hashlock() {
+    let result = IOUBuilder::new()
+        .sender(&Keypair::generate())
+        .recipient(did(&Keypair::generate()))
+        .amount(300)
+        .hashlock(Hashlock::from_preimage(PREIMAGE))
+        .build();
+
+    assert!(matches!(result, Err(IOUError::InvalidExpiry(_))));
+}
+
+#[test]
+fn test_validator_rejects_hashlock_without_expiry() {
+    let sender = Keypair::generate();
+    let iou = IOU::new(did(&sender), did(&Keypair::generate()), 300, 1, 1_700_000_000)
+        .with_hashlock(Hashlock::from_preimage(PREIMAGE));
+    let signature = Signer::sign(&sender, &iou.to_signing_bytes());
+    let signed = SignedIOU::from_parts(iou, signature);
+
+    let result = IOUValidator::validate(&signed, &sender.public_key());
+
+    assert!(matches!(result, Err(ValidationError::HashlockWithoutExpiry)));
+}
+
+#[test]
+fn test_hashlock_is_signed() {
+    let sender = Keypair::generate();
+    let signed = hashlocked_iou(&sender, &Keypair::generate());
+
+    // Swapping the lock invalidates the signature and changes the ID
+    let swapped = SignedIOU::from_parts(
+        signed.iou().clone().with_hashlock(Hashlock::from_preimage(b"other")),
+        signed.signature().clone(),
+    );
+    assert!(!swapped.verify(&sender.public_key()));
+    assert_ne!(swapped.id(), signed.id());
+}
+
+// ============================================================================
+// CODECS
+// ============================================================================
+
+#[test]
+fn test_postcard_round_trip() {
+    let sender = Keypair::generate();
+    let signed = hashlocked_iou(&sender, &Keypair::generate());
+
+    let decoded = IOUCodec::decode(&IOUCodec::encode(&signed)).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert_eq!(decoded.hashlock(), signed.hashlock());
+    assert!(decoded.verify(&sender.public_key()));
+}
+
+#[test]
+fn test_pre_hashlock_bytes_still_decode() {
+    let sender = Keypair::generate();
+    let signed = IOUBuilder::new()
+        .sender(&sender)
+        .recipient(did(&Keypair::generate()))
+        .amount(300)
+        .memo("before hashlocks")
+        .build()
+        .unwrap();
+
+    // Drop the trailing hashlock tag
+    let mut bytes = IOUCodec::encode(&signed);
+    assert_eq!(bytes.pop(), Some(0));
+
+    let decoded = IOUCodec::decode(&bytes).unwrap();
+    assert_eq!(decoded, signed);
+    assert!(decoded.verify(&sender.public_key()));
+}
+
+#[test]
+fn test_canonical_round_trip() {
+    let signed = hashlocked_iou(&Keypair::generate(), &Keypair::generate());
+
+    let decoded = SignedIOU::from_canonical_bytes(&signed.to_canonical_bytes().unwrap()).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert_eq!(decoded.hashlock(), signed.hashlock());
+}
+
+#[test]
+fn test_json_round_trip() {
+    let sender = Keypair::generate();
+    let signed = hashlocked_iou(&sender, &Keypair::generate());
+
+    let json = IOUCodec::to_json(&signed);
+    assert!(json.contains(r#""hashlock":""#));
+
+    let decoded = IOUCodec::from_json(&json).unwrap();
+    assert_eq!(decoded, signed);
+    assert!(decoded.verify(&sender.public_key()));
+}
+
+#[test]
+fn test_compact_round_trip() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let signed = hashlocked_iou(&sender, &recipient);
+
+    let mut table = DidTable::new();
+    table.insert(did(&sender));
+    table.insert(did(&recipient));
+
+    let compact = CompactIOU::from_bytes(&CompactIOU::from_signed(&signed).to_bytes()).unwrap();
+    let decoded = compact.resolve(&table).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert!(decoded.verify(&sender.public_key()));
+}
@@ -0,0 +1,184 @@
+// JSON codec tests
+// Tests the canonical JSON form of signed IOUs used by non-Rust clients
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{
+    CodecError, IOUBuilder, IOUCodec, IOUValidator, PaymentRequestId, SignedIOU,
+};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn simple_iou(sender: &Keypair) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(did(&Keypair::generate()))
+        .amount(250)
+        .nonce(7)
+        .timestamp(1_700_000_000)
+        .build()
+        .unwrap()
+}
+
+fn full_iou(sender: &Keypair) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .add_output(did(&Keypair::generate()), 60)
+        .add_output(did(&Keypair::generate()), 40)
+        .nonce(u64::MAX)
+        .expires_in_secs(3600)
+        .memo("order \"42\"\n\u{e9}t\u{e9} \u{1f600}")
+        .request_id(PaymentRequestId::from_bytes([9u8; 32]))
+        .build()
+        .unwrap()
+}
+
+// ============================================================================
+// ROUND TRIPS
+// ============================================================================
+
+#[test]
+fn test_json_round_trip_simple() {
+    let sender = Keypair::generate();
+    let signed = simple_iou(&sender);
+
+    let decoded = IOUCodec::from_json(&IOUCodec::to_json(&signed)).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert_eq!(decoded.id(), signed.id());
+    assert!(IOUValidator::validate(&decoded, &sender.public_key()).is_ok());
+}
+
+#[test]
+fn test_json_round_trip_all_optional_fields() {
+    let sender = Keypair::generate();
+    let signed = full_iou(&sender);
+
+    let decoded = IOUCodec::from_json(&IOUCodec::to_json(&signed)).unwrap();
+
+    assert_eq!(decoded, signed);
+    assert_eq!(decoded.iou().memo(), signed.iou().memo());
+    assert_eq!(decoded.iou().outputs(), signed.iou().outputs());
+    assert!(IOUValidator::validate(&decoded, &sender.public_key()).is_ok());
+}
+
+#[test]
+fn test_postcard_to_json_to_postcard() {
+    let sender = Keypair::generate();
+    let signed = full_iou(&sender);
+
+    let binary = IOUCodec::encode(&signed);
+    let from_binary = IOUCodec::decode(&binary).unwrap();
+    let json = IOUCodec::to_json(&from_binary);
+    let from_json = IOUCodec::from_json(&json).unwrap();
+
+    let back_to_binary = IOUCodec::encode(&from_json);
+    assert_eq!(back_to_binary, binary);
+
+    let final_iou = IOUCodec::decode(&back_to_binary).unwrap();
+    assert!(IOUValidator::validate(&final_iou, &sender.public_key()).is_ok());
+}
+
+// ============================================================================
+// CANONICAL FORM
+// ============================================================================
+
+#[test]
+fn test_json_field_order_and_number_format() {
+    let sender = Keypair::generate();
+    let signed = simple_iou(&sender);
+    let iou = signed.iou();
+
+    let expected_prefix = format!(
+        "{{\"sender\":\"{}\",\"recipient\":\"{}\",\"amount\":\"250\",\"nonce\":\"7\",\"timestamp\":\"1700000000\",\"signature\":\"",
+        iou.sender(),
+        iou.recipient()
+    );
+    let json = IOUCodec::to_json(&signed);
+    assert!(json.starts_with(&expected_prefix), "unexpected JSON: {}", json);
+    assert!(json.ends_with("\"}"));
+}
+
+#[test]
+fn test_json_is_deterministic() {
+    let signed = full_iou(&Keypair::generate());
+    let json = IOUCodec::to_json(&signed);
+
+    assert_eq!(IOUCodec::to_json(&IOUCodec::from_json(&json).unwrap()), json);
+}
+
+#[test]
+fn test_json_uses_base64url_without_padding() {
+    let signed = full_iou(&Keypair::generate());
+    let json = IOUCodec::to_json(&signed);
+
+    assert!(json.contains("\"request_id\":\"CQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQk\""));
+    assert!(!json.contains('='));
+}
+
+#[test]
+fn test_json_reader_ignores_whitespace_and_key_order() {
+    let sender = Keypair::generate();
+    let signed = simple_iou(&sender);
+    let iou = signed.iou();
+    let canonical = IOUCodec::to_json(&signed);
+    let signature = canonical.rsplit("\"signature\":\"").next().unwrap().trim_end_matches("\"}");
+
+    let reordered = format!(
+        "{{\n  \"signature\": \"{}\",\n  \"timestamp\": 1700000000,\n  \"nonce\": \"7\",\n  \"amount\": \"250\",\n  \"recipient\": \"{}\",\n  \"sender\": \"{}\",\n  \"extra\": [true, null]\n}}",
+        signature,
+        iou.recipient(),
+        iou.sender()
+    );
+
+    let decoded = IOUCodec::from_json(&reordered).unwrap();
+    assert_eq!(decoded, signed);
+}
+
+// ============================================================================
+// TAMPERING AND ERRORS
+// ============================================================================
+
+#[test]
+fn test_tampered_json_fails_signature() {
+    let sender = Keypair::generate();
+    let signed = simple_iou(&sender);
+    let tampered = IOUCodec::to_json(&signed).replace("\"amount\":\"250\"", "\"amount\":\"2500\"");
+
+    let decoded = IOUCodec::from_json(&tampered).unwrap();
+    assert!(IOUValidator::validate(&decoded, &sender.public_key()).is_err());
+}
+
+#[test]
+fn test_invalid_json_rejected() {
+    let signed = simple_iou(&Keypair::generate());
+    let json = IOUCodec::to_json(&signed);
+
+    for bad in [
+        "",
+        "not json",
+        "[]",
+        &json[..json.len() - 1],
+        &format!("{} trailing", json),
+        &json.replace("\"nonce\":\"7\"", "\"nonce\":\"-7\""),
+        &json.replace("\"nonce\":\"7\",", ""),
+        &json.replace("\"nonce\":\"7\"", "\"nonce\":\"7\",\"nonce\":\"8\""),
+    ] {
+        assert!(
+            matches!(IOUCodec::from_json(bad), Err(CodecError::InvalidJson(_))),
+            "should reject {:?}",
+            bad
+        );
+    }
+}
+
+#[test]
+fn test_invalid_signature_bytes_rejected() {
+    let signed = simple_iou(&Keypair::generate());
+    let json = IOUCodec::to_json(&signed);
+    let start = json.find("\"signature\":\"").unwrap();
+    let truncated = format!("{}\"signature\":\"AAAA\"}}", &json[..start]);
+
+    assert!(matches!(IOUCodec::from_json(&truncated), Err(CodecError::InvalidJson(_))));
+}
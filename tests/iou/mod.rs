@@ -3,3 +3,17 @@ mod builder_test;
 mod validator_test;
 mod codec_test;
 mod edge_cases_test;
+mod rejection_test;
+mod request_test;
+mod outputs_test;
+mod cancellation_test;
+mod policy_test;
+mod json_test;
+mod compact_test;
+mod nonce_test;
+mod batch_test;
+mod canonical_test;
+mod denomination_test;
+mod multisig_test;
+mod hashlock_test;
+mod amount_limit_test;
@@ -0,0 +1,355 @@
+use p2pmesh::identity::{Did, Keypair, Signer};
+use p2pmesh::iou::{MultiSigError, MultiSigIOU, MultiSigIOUBuilder, MultiSigPolicy, MultiSigValidator};
+
+// ============================================================================
+// MULTI-SIGNATURE TESTS
+// ============================================================================
+
+/// Three co-signers of a shared wallet
+fn signers() -> [Keypair; 3] {
+    [Keypair::generate(), Keypair::generate(), Keypair::generate()]
+}
+
+fn policy(threshold: u32, signers: &[Keypair]) -> MultiSigPolicy {
+    MultiSigPolicy::new(threshold, signers.iter().map(|k| k.public_key()).collect()).unwrap()
+}
+
+fn wallet_did() -> Did {
+    Did::from_public_key(&Keypair::generate().public_key())
+}
+
+/// Helper to build an IOU under `policy`, signed by `signed_by`
+fn build_iou(policy: MultiSigPolicy, signed_by: &[&Keypair]) -> MultiSigIOU {
+    let mut builder = MultiSigIOUBuilder::new(policy)
+        .sender(wallet_did())
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(500)
+        .nonce(1);
+    for keypair in signed_by {
+        builder = builder.sign_with(keypair);
+    }
+    builder.build().unwrap()
+}
+
+/// Test: 2 of 3 configured signers is enough
+#[test]
+fn test_two_of_three_validates() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol.clone()]);
+    let iou = build_iou(policy.clone(), &[&alice, &carol]);
+
+    let validator = MultiSigValidator::new(policy);
+
+    assert!(iou.has_threshold());
+    assert!(validator.validate(&iou).is_ok());
+}
+
+/// Test: 1 signature under a 2-of-3 policy fails
+#[test]
+fn test_one_of_three_fails() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol.clone()]);
+    let iou = build_iou(policy.clone(), &[&bob]);
+
+    let result = MultiSigValidator::new(policy).validate(&iou);
+
+    assert!(!iou.has_threshold());
+    assert_eq!(result, Err(MultiSigError::InsufficientSignatures { valid: 1, required: 2 }));
+}
+
+/// Test: A partly signed IOU can be finished by another co-signer
+#[test]
+fn test_partly_signed_iou_can_be_completed() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol.clone()]);
+    let mut iou = build_iou(policy.clone(), &[&alice]);
+    let validator = MultiSigValidator::new(policy);
+    assert!(!validator.is_valid(&iou));
+
+    // Passed to Bob's device over the wire
+    let mut received = MultiSigIOU::from_bytes(&iou.to_bytes()).unwrap();
+    received.sign(&bob).unwrap();
+    iou.sign(&bob).unwrap();
+
+    assert!(validator.is_valid(&received));
+    assert!(validator.is_valid(&iou));
+    assert_eq!(received.id(), iou.id());
+}
+
+/// Test: Signer order doesn't change the policy
+#[test]
+fn test_policy_ignores_signer_order() {
+    let [alice, bob, carol] = signers();
+
+    let forward = policy(2, &[alice.clone(), bob.clone(), carol.clone()]);
+    let backward = policy(2, &[carol, bob, alice]);
+
+    assert_eq!(forward, backward);
+}
+
+/// Test: Invalid thresholds are rejected
+#[test]
+fn test_policy_rejects_invalid_threshold() {
+    let keys: Vec<_> = signers().iter().map(|k| k.public_key()).collect();
+
+    assert_eq!(
+        MultiSigPolicy::new(0, keys.clone()),
+        Err(MultiSigError::InvalidThreshold { threshold: 0, signers: 3 })
+    );
+    assert_eq!(
+        MultiSigPolicy::new(4, keys),
+        Err(MultiSigError::InvalidThreshold { threshold: 4, signers: 3 })
+    );
+}
+
+/// Test: A signer listed twice in the policy is rejected
+#[test]
+fn test_policy_rejects_duplicate_signer() {
+    let [alice, bob, _] = signers();
+
+    let result = MultiSigPolicy::new(2, vec![alice.public_key(), bob.public_key(), alice.public_key()]);
+
+    assert_eq!(result, Err(MultiSigError::DuplicateSigner));
+}
+
+/// Test: The same signer can't count twice
+#[test]
+fn test_duplicate_signatures_fail() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol.clone()]);
+    let mut iou = build_iou(policy.clone(), &[&alice]);
+
+    assert_eq!(iou.sign(&alice), Err(MultiSigError::DuplicateSigner));
+
+    // Forged by repeating Alice's signature
+    let mut signatures = iou.signatures().to_vec();
+    signatures.push(signatures[0].clone());
+    let forged = MultiSigIOU::from_parts(iou.iou().clone(), policy.clone(), signatures);
+
+    assert_eq!(MultiSigValidator::new(policy).validate(&forged), Err(MultiSigError::DuplicateSigner));
+}
+
+/// Test: Signing twice through the builder fails too
+#[test]
+fn test_builder_rejects_duplicate_signer() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob, carol]);
+
+    let result = MultiSigIOUBuilder::new(policy)
+        .sender(wallet_did())
+        .recipient(wallet_did())
+        .amount(500)
+        .sign_with(&alice)
+        .sign_with(&alice)
+        .build();
+
+    assert!(matches!(result, Err(MultiSigError::DuplicateSigner)));
+}
+
+/// Test: Keys outside the signer set can't sign
+#[test]
+fn test_unknown_signer_fails() {
+    let [alice, bob, carol] = signers();
+    let mallory = Keypair::generate();
+    let policy = policy(2, &[alice.clone(), bob, carol]);
+    let mut iou = build_iou(policy.clone(), &[&alice]);
+
+    assert_eq!(iou.sign(&mallory), Err(MultiSigError::UnknownSigner));
+
+    let mut signatures = iou.signatures().to_vec();
+    signatures.push((mallory.public_key(), Signer::sign(&mallory, &iou.to_signing_bytes())));
+    let forged = MultiSigIOU::from_parts(iou.iou().clone(), policy.clone(), signatures);
+
+    assert_eq!(MultiSigValidator::new(policy).validate(&forged), Err(MultiSigError::UnknownSigner));
+}
+
+/// Test: Signatures over a different IOU fail
+#[test]
+fn test_signatures_over_mismatched_payload_fail() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol]);
+    let signed = build_iou(policy.clone(), &[&alice, &bob]);
+    let other = build_iou(policy.clone(), &[]);
+
+    // Move the signatures onto a different payment
+    let forged = MultiSigIOU::from_parts(other.iou().clone(), policy.clone(), signed.signatures().to_vec());
+
+    assert_eq!(MultiSigValidator::new(policy).validate(&forged), Err(MultiSigError::InvalidSignature));
+}
+
+/// Test: The payload commits to the threshold, so signatures can't be moved to a weaker policy
+#[test]
+fn test_signatures_commit_to_threshold() {
+    let [alice, bob, carol] = signers();
+    let strict = policy(3, &[alice.clone(), bob.clone(), carol.clone()]);
+    let lax = policy(1, &[alice.clone(), bob.clone(), carol.clone()]);
+    let signed = build_iou(strict.clone(), &[&alice]);
+
+    let downgraded = MultiSigIOU::from_parts(signed.iou().clone(), lax.clone(), signed.signatures().to_vec());
+
+    assert_ne!(downgraded.to_signing_bytes(), signed.to_signing_bytes());
+    assert_eq!(MultiSigValidator::new(lax).validate(&downgraded), Err(MultiSigError::InvalidSignature));
+    assert_eq!(MultiSigValidator::new(strict).validate(&downgraded), Err(MultiSigError::PolicyMismatch));
+}
+
+/// Test: The payload commits to the signer set
+#[test]
+fn test_signatures_commit_to_signer_set() {
+    let [alice, bob, carol] = signers();
+    let dave = Keypair::generate();
+    let original = policy(2, &[alice.clone(), bob.clone(), carol.clone()]);
+    let swapped = policy(2, &[alice.clone(), bob.clone(), dave]);
+    let signed = build_iou(original, &[&alice, &bob]);
+
+    let moved = MultiSigIOU::from_parts(signed.iou().clone(), swapped.clone(), signed.signatures().to_vec());
+
+    assert_eq!(MultiSigValidator::new(swapped).validate(&moved), Err(MultiSigError::InvalidSignature));
+}
+
+/// Test: An IOU must carry the validator's policy
+#[test]
+fn test_validator_rejects_other_policy() {
+    let [alice, bob, carol] = signers();
+    let expected = policy(2, &[alice.clone(), bob.clone(), carol.clone()]);
+    let own = policy(1, std::slice::from_ref(&alice));
+    let iou = build_iou(own, &[&alice]);
+
+    let result = MultiSigValidator::new(expected).validate(&iou);
+
+    assert_eq!(result, Err(MultiSigError::PolicyMismatch));
+}
+
+/// Test: Validator built from raw keys matches one built from a policy
+#[test]
+fn test_validator_with_signers() {
+    let [alice, bob, carol] = signers();
+    let keys = vec![alice.public_key(), bob.public_key(), carol.public_key()];
+    let validator = MultiSigValidator::with_signers(2, keys).unwrap();
+    let iou = build_iou(policy(2, &[alice.clone(), bob.clone(), carol]), &[&bob, &alice]);
+
+    assert!(validator.is_valid(&iou));
+    assert!(MultiSigValidator::with_signers(2, vec![alice.public_key()]).is_err());
+}
+
+/// Test: Builder requires sender, recipient and a non-zero amount
+#[test]
+fn test_builder_requires_fields() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice, bob, carol]);
+    let recipient = wallet_did();
+
+    let missing_sender = MultiSigIOUBuilder::new(policy.clone()).recipient(recipient.clone()).amount(5).build();
+    let zero = MultiSigIOUBuilder::new(policy.clone())
+        .sender(wallet_did())
+        .recipient(recipient.clone())
+        .amount(0)
+        .build();
+    let to_self = MultiSigIOUBuilder::new(policy)
+        .sender(recipient.clone())
+        .recipient(recipient)
+        .amount(5)
+        .build();
+
+    assert!(matches!(missing_sender, Err(MultiSigError::MissingField("sender"))));
+    assert!(matches!(zero, Err(MultiSigError::InvalidAmount)));
+    assert!(matches!(to_self, Err(MultiSigError::SelfPayment)));
+}
+
+/// Test: Serialization roundtrip keeps the IOU valid
+#[test]
+fn test_multisig_serialization_roundtrip() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol]);
+    let iou = build_iou(policy.clone(), &[&alice, &bob]);
+
+    let restored = MultiSigIOU::from_bytes(&iou.to_bytes()).unwrap();
+
+    assert_eq!(restored.id(), iou.id());
+    assert_eq!(restored.iou(), iou.iou());
+    assert!(MultiSigValidator::new(policy).is_valid(&restored));
+}
+
+/// Test: Memo and expiry survive a serialization roundtrip, so the signatures still verify
+#[test]
+fn test_multisig_roundtrip_keeps_memo_and_expiry() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol]);
+    let unsigned = MultiSigIOUBuilder::new(policy.clone())
+        .sender(wallet_did())
+        .recipient(wallet_did())
+        .amount(500)
+        .ttl_secs(3600)
+        .build()
+        .unwrap();
+    let with_memo = unsigned.iou().clone().with_memo("rent".to_string());
+    let mut iou = MultiSigIOU::from_parts(with_memo, policy.clone(), Vec::new());
+    iou.sign(&alice).unwrap();
+    iou.sign(&bob).unwrap();
+
+    let restored = MultiSigIOU::from_bytes(&iou.to_bytes()).unwrap();
+
+    assert_eq!(restored.iou().memo(), Some("rent"));
+    assert_eq!(restored.iou().expiry(), iou.iou().expiry());
+    assert_eq!(restored.id(), iou.id());
+    assert!(MultiSigValidator::new(policy).validate(&restored).is_ok());
+}
+
+/// Test: Encodings from before the trailing IOU fields still decode
+#[test]
+fn test_multisig_without_trailing_fields_decodes() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol]);
+    let iou = build_iou(policy.clone(), &[&alice, &bob]);
+
+    // Drop the memo, request ID, outputs, denomination and hashlock tags
+    let mut bytes = iou.to_bytes();
+    for _ in 0..5 {
+        assert_eq!(bytes.pop(), Some(0));
+    }
+
+    let restored = MultiSigIOU::from_bytes(&bytes).unwrap();
+    assert_eq!(restored.iou(), iou.iou());
+    assert!(MultiSigValidator::new(policy).is_valid(&restored));
+}
+
+/// Test: Builder sets an absolute expiry or one relative to the timestamp
+#[test]
+fn test_builder_sets_expiry() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice, bob, carol]);
+    let builder = || {
+        MultiSigIOUBuilder::new(policy.clone())
+            .sender(wallet_did())
+            .recipient(wallet_did())
+            .amount(5)
+            .timestamp(1000)
+    };
+
+    assert_eq!(builder().expiry(1500).build().unwrap().iou().expiry(), Some(1500));
+    assert_eq!(builder().ttl_secs(60).build().unwrap().iou().expiry(), Some(1060));
+    assert_eq!(builder().build().unwrap().iou().expiry(), None);
+    assert!(matches!(builder().expiry(1000).build(), Err(MultiSigError::InvalidExpiry(_))));
+    assert!(matches!(builder().ttl_secs(u64::MAX).build(), Err(MultiSigError::InvalidExpiry(_))));
+}
+
+/// Test: Validator rejects an IOU past its expiry, however many signed it
+#[test]
+fn test_validator_rejects_expired_iou() {
+    let [alice, bob, carol] = signers();
+    let policy = policy(2, &[alice.clone(), bob.clone(), carol]);
+    let iou = MultiSigIOUBuilder::new(policy.clone())
+        .sender(wallet_did())
+        .recipient(wallet_did())
+        .amount(5)
+        .timestamp(1000)
+        .expiry(1060)
+        .sign_with(&alice)
+        .sign_with(&bob)
+        .build()
+        .unwrap();
+    let validator = MultiSigValidator::new(policy);
+
+    assert!(validator.validate_at(&iou, 1030).is_ok());
+    assert_eq!(validator.validate_at(&iou, 1060), Err(MultiSigError::Expired));
+    assert_eq!(validator.validate(&iou), Err(MultiSigError::Expired));
+}
@@ -0,0 +1,248 @@
+// Nonce manager tests
+// Tests per-sender nonce allocation, regression detection and persistence
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, IOUError, NonceError, NonceManager};
+use p2pmesh::vault::Vault;
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+// ============================================================================
+// ALLOCATION
+// ============================================================================
+
+#[test]
+fn test_next_starts_at_one_and_increments() {
+    let sender = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+
+    assert_eq!(nonces.peek(&sender), 1);
+    assert_eq!(nonces.next(&sender).unwrap(), 1);
+    assert_eq!(nonces.next(&sender).unwrap(), 2);
+    assert_eq!(nonces.last_used(&sender), Some(2));
+}
+
+#[test]
+fn test_peek_does_not_allocate() {
+    let sender = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+
+    assert_eq!(nonces.peek(&sender), 1);
+    assert_eq!(nonces.peek(&sender), 1);
+    assert_eq!(nonces.last_used(&sender), None);
+    assert_eq!(nonces.next(&sender).unwrap(), 1);
+}
+
+#[test]
+fn test_senders_counted_independently() {
+    let alice = did(&Keypair::generate());
+    let bob = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+
+    nonces.next(&alice).unwrap();
+    nonces.next(&alice).unwrap();
+
+    assert_eq!(nonces.next(&bob).unwrap(), 1);
+    assert_eq!(nonces.peek(&alice), 3);
+    assert_eq!(nonces.len(), 2);
+}
+
+#[test]
+fn test_next_fails_when_exhausted() {
+    let sender = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+    nonces.seed(&sender, u64::MAX);
+
+    assert!(matches!(nonces.next(&sender), Err(NonceError::Exhausted(_))));
+    assert_eq!(nonces.last_used(&sender), Some(u64::MAX));
+}
+
+// ============================================================================
+// REGRESSION
+// ============================================================================
+
+#[test]
+fn test_observe_moves_counter_forward() {
+    let sender = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+
+    nonces.observe(&sender, 5).unwrap();
+    assert_eq!(nonces.peek(&sender), 6);
+}
+
+#[test]
+fn test_observe_detects_regression() {
+    let sender = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+    nonces.observe(&sender, 5).unwrap();
+
+    let result = nonces.observe(&sender, 5);
+    assert_eq!(
+        result,
+        Err(NonceError::Regression { sender: sender.clone(), nonce: 5, last_used: 5 })
+    );
+    assert!(nonces.observe(&sender, 3).is_err());
+    assert_eq!(nonces.last_used(&sender), Some(5));
+}
+
+#[test]
+fn test_seed_never_lowers() {
+    let sender = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+
+    nonces.seed(&sender, 10);
+    nonces.seed(&sender, 4);
+    assert_eq!(nonces.last_used(&sender), Some(10));
+}
+
+#[test]
+fn test_merge_takes_higher_counters() {
+    let alice = did(&Keypair::generate());
+    let bob = did(&Keypair::generate());
+
+    let mut local = NonceManager::new();
+    local.seed(&alice, 7);
+    local.seed(&bob, 2);
+
+    let mut other = NonceManager::new();
+    other.seed(&alice, 3);
+    other.seed(&bob, 9);
+
+    local.merge(&other);
+    assert_eq!(local.last_used(&alice), Some(7));
+    assert_eq!(local.last_used(&bob), Some(9));
+}
+
+// ============================================================================
+// VAULT SEEDING
+// ============================================================================
+
+#[test]
+fn test_seed_from_vault_continues_after_sent_history() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+
+    let incoming = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(did(&alice))
+        .amount(100)
+        .build()
+        .unwrap();
+    vault.receive_iou(incoming, &bob.public_key()).unwrap();
+
+    let outgoing = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(did(&bob))
+        .amount(30)
+        .nonce(41)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(outgoing).unwrap();
+
+    let nonces = NonceManager::from_vault(&vault);
+    assert_eq!(nonces.peek(&did(&alice)), 42);
+    assert_eq!(nonces.last_used(&did(&bob)), None);
+}
+
+#[test]
+fn test_from_vault_without_sent_ious_is_empty() {
+    let vault = Vault::new(Keypair::generate().public_key());
+    assert!(NonceManager::from_vault(&vault).is_empty());
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_bytes_roundtrip() {
+    let alice = did(&Keypair::generate());
+    let bob = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+    nonces.seed(&alice, 12);
+    nonces.next(&bob).unwrap();
+
+    let restored = NonceManager::from_bytes(&nonces.to_bytes()).unwrap();
+    assert_eq!(restored, nonces);
+    assert_eq!(restored.peek(&alice), 13);
+}
+
+#[test]
+fn test_from_bytes_rejects_garbage() {
+    let result = NonceManager::from_bytes(&[0xFF, 0xFF, 0xFF]);
+    assert_eq!(result, Err(NonceError::DeserializationFailed));
+}
+
+// ============================================================================
+// BUILDER INTEGRATION
+// ============================================================================
+
+#[test]
+fn test_builder_nonce_from_allocates() {
+    let alice = Keypair::generate();
+    let recipient = did(&Keypair::generate());
+    let mut nonces = NonceManager::new();
+
+    for expected in 1..=3 {
+        let signed = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(recipient.clone())
+            .amount(10)
+            .nonce_from(&mut nonces)
+            .build()
+            .unwrap();
+        assert_eq!(signed.iou().nonce(), expected);
+    }
+    assert_eq!(nonces.last_used(&did(&alice)), Some(3));
+}
+
+#[test]
+fn test_builder_failed_build_does_not_allocate() {
+    let alice = Keypair::generate();
+    let mut nonces = NonceManager::new();
+
+    let result = IOUBuilder::new()
+        .sender(&alice)
+        .amount(10)
+        .nonce_from(&mut nonces)
+        .build();
+
+    assert!(result.is_err());
+    assert!(nonces.is_empty());
+}
+
+#[test]
+fn test_builder_exhausted_nonces_fail_build() {
+    let alice = Keypair::generate();
+    let mut nonces = NonceManager::new();
+    nonces.seed(&did(&alice), u64::MAX);
+
+    let result = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(did(&Keypair::generate()))
+        .amount(10)
+        .nonce_from(&mut nonces)
+        .build();
+
+    assert!(matches!(result, Err(IOUError::Nonce(NonceError::Exhausted(_)))));
+}
+
+#[test]
+fn test_builder_explicit_nonce_overrides_source() {
+    let alice = Keypair::generate();
+    let mut nonces = NonceManager::new();
+
+    let signed = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(did(&Keypair::generate()))
+        .amount(10)
+        .nonce_from(&mut nonces)
+        .nonce(99)
+        .build()
+        .unwrap();
+
+    assert_eq!(signed.iou().nonce(), 99);
+}
@@ -0,0 +1,259 @@
+use p2pmesh::identity::{Did, Keypair, Signer};
+use p2pmesh::iou::{
+    IOUBuilder, IOUCodec, IOUError, IOUOutput, IOUValidator, SignedIOU, ValidationError, IOU,
+};
+
+// ============================================================================
+// MULTI-OUTPUT IOU TESTS
+// ============================================================================
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+/// Sign an IOU assembled by hand, bypassing the builder's checks
+fn sign_raw(sender: &Keypair, recipient: Did, amount: u64, outputs: Vec<IOUOutput>) -> SignedIOU {
+    let iou = IOU::new(did(sender), recipient, amount, 1, 1_000).with_outputs(outputs);
+    let signature = Signer::sign(sender, &iou.to_signing_bytes());
+    SignedIOU::from_parts(iou, signature)
+}
+
+/// Test: Builder sums outputs into the total and keeps each share
+#[test]
+fn test_split_payment_outputs() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let carol = Keypair::generate();
+
+    let iou = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), 30)
+        .add_output(did(&bob), 25)
+        .add_output(did(&carol), 45)
+        .build()
+        .unwrap();
+
+    let inner = iou.iou();
+    assert!(inner.is_multi_output());
+    assert_eq!(inner.amount(), 100);
+    assert_eq!(inner.recipient(), &did(&alice));
+    assert_eq!(inner.recipients(), vec![&did(&alice), &did(&bob), &did(&carol)]);
+    assert_eq!(inner.amount_for(&did(&bob)), 25);
+    assert_eq!(inner.amount_for(&did(&sender)), 0);
+    assert!(IOUValidator::validate(&iou, &sender.public_key()).is_ok());
+}
+
+/// Test: A lone output builds an ordinary single-recipient IOU
+#[test]
+fn test_single_output_collapses_to_recipient() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+
+    let via_output = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), 40)
+        .nonce(7)
+        .timestamp(1_000)
+        .build()
+        .unwrap();
+    let via_recipient = IOUBuilder::new()
+        .sender(&sender)
+        .recipient(did(&alice))
+        .amount(40)
+        .nonce(7)
+        .timestamp(1_000)
+        .build()
+        .unwrap();
+
+    assert!(!via_output.iou().is_multi_output());
+    assert_eq!(via_output.id(), via_recipient.id());
+}
+
+/// Test: Single-recipient IOUs report their one recipient
+#[test]
+fn test_single_recipient_outputs_view() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let iou = IOUBuilder::new().sender(&sender).recipient(did(&alice)).amount(10).build().unwrap();
+
+    assert!(iou.iou().outputs().is_empty());
+    assert_eq!(iou.iou().recipients(), vec![&did(&alice)]);
+    assert_eq!(iou.iou().amount_for(&did(&alice)), 10);
+}
+
+/// Test: Builder rejects zero outputs, repeated recipients and conflicting fields
+#[test]
+fn test_builder_rejects_bad_outputs() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    let zero = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), 10)
+        .add_output(did(&bob), 0)
+        .build();
+    assert!(matches!(zero, Err(IOUError::InvalidAmount(_))));
+
+    let duplicate = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), 10)
+        .add_output(did(&alice), 5)
+        .build();
+    assert!(matches!(duplicate, Err(IOUError::InvalidOutputs(_))));
+
+    let with_recipient = IOUBuilder::new()
+        .sender(&sender)
+        .recipient(did(&bob))
+        .add_output(did(&alice), 10)
+        .build();
+    assert!(matches!(with_recipient, Err(IOUError::InvalidOutputs(_))));
+
+    let wrong_total = IOUBuilder::new()
+        .sender(&sender)
+        .amount(20)
+        .add_output(did(&alice), 10)
+        .add_output(did(&bob), 5)
+        .build();
+    assert!(matches!(wrong_total, Err(IOUError::InvalidAmount(_))));
+
+    let overflow = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), u64::MAX)
+        .add_output(did(&bob), 1)
+        .build();
+    assert!(matches!(overflow, Err(IOUError::InvalidAmount(_))));
+}
+
+/// Test: Paying yourself through an output is a self-payment
+#[test]
+fn test_builder_rejects_output_to_sender() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+
+    let result = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), 10)
+        .add_output(did(&sender), 10)
+        .build();
+
+    assert!(matches!(result, Err(IOUError::SelfPayment)));
+}
+
+/// Test: Validator rejects signed IOUs with zero-amount outputs
+#[test]
+fn test_validator_rejects_zero_output() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let outputs = vec![IOUOutput::new(did(&alice), 10), IOUOutput::new(did(&bob), 0)];
+
+    let iou = sign_raw(&sender, did(&alice), 10, outputs);
+
+    let result = IOUValidator::validate(&iou, &sender.public_key());
+    assert!(matches!(result, Err(ValidationError::ZeroAmountOutput)));
+}
+
+/// Test: Validator rejects signed IOUs that pay a recipient twice
+#[test]
+fn test_validator_rejects_duplicate_recipient() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let outputs = vec![IOUOutput::new(did(&alice), 10), IOUOutput::new(did(&alice), 10)];
+
+    let iou = sign_raw(&sender, did(&alice), 20, outputs);
+
+    let result = IOUValidator::validate(&iou, &sender.public_key());
+    assert!(matches!(result, Err(ValidationError::DuplicateOutputRecipient)));
+}
+
+/// Test: Validator rejects a total that isn't the sum of outputs
+#[test]
+fn test_validator_rejects_total_mismatch() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let outputs = vec![IOUOutput::new(did(&alice), 10), IOUOutput::new(did(&bob), 10)];
+
+    let short = sign_raw(&sender, did(&alice), 15, outputs.clone());
+    let wrong_head = sign_raw(&sender, did(&bob), 20, outputs);
+
+    assert!(matches!(
+        IOUValidator::validate(&short, &sender.public_key()),
+        Err(ValidationError::OutputMismatch)
+    ));
+    assert!(matches!(
+        IOUValidator::validate(&wrong_head, &sender.public_key()),
+        Err(ValidationError::OutputMismatch)
+    ));
+}
+
+/// Test: Outputs are signed, so moving value between them breaks the signature
+#[test]
+fn test_tampered_outputs_fail_signature() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), 10)
+        .add_output(did(&bob), 90)
+        .build()
+        .unwrap();
+
+    let tampered = iou
+        .iou()
+        .clone()
+        .with_outputs(vec![IOUOutput::new(did(&alice), 90), IOUOutput::new(did(&bob), 10)]);
+    let forged = SignedIOU::from_parts(tampered, iou.signature().clone());
+
+    let result = IOUValidator::validate(&forged, &sender.public_key());
+    assert!(matches!(result, Err(ValidationError::InvalidSignature)));
+}
+
+/// Test: Outputs survive the binary and QR codecs
+#[test]
+fn test_outputs_survive_codec() {
+    let sender = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&sender)
+        .add_output(did(&alice), 12)
+        .add_output(did(&bob), 8)
+        .memo("dinner")
+        .build()
+        .unwrap();
+
+    let decoded = IOUCodec::decode(&IOUCodec::encode(&iou)).unwrap();
+    let from_qr = IOUCodec::decode_qr(&IOUCodec::encode_qr(&iou)).unwrap();
+
+    assert_eq!(decoded, iou);
+    assert_eq!(from_qr, iou);
+    assert_eq!(decoded.iou().outputs(), iou.iou().outputs());
+    assert!(decoded.verify(&sender.public_key()));
+}
+
+/// Test: IOUs from before the outputs field still decode
+#[test]
+fn test_pre_outputs_bytes_still_decode() {
+    let sender = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&sender)
+        .recipient(did(&Keypair::generate()))
+        .amount(5)
+        .memo("coffee")
+        .build()
+        .unwrap();
+
+    // Drop the trailing hashlock tag, denomination tag and output count
+    let mut bytes = IOUCodec::encode(&iou);
+    assert_eq!(bytes.pop(), Some(0));
+    assert_eq!(bytes.pop(), Some(0));
+    assert_eq!(bytes.pop(), Some(0));
+
+    let decoded = IOUCodec::decode(&bytes).unwrap();
+    assert_eq!(decoded, iou);
+    assert_eq!(decoded.memo(), Some("coffee"));
+}
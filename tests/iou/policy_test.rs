@@ -0,0 +1,188 @@
+// Validation policy tests
+// Tests deployment-configurable rules layered on top of IOUValidator
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, IOUValidator, SignedIOU, ValidationError, ValidationPolicy};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn iou(from: &Keypair, amount: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(from)
+        .recipient(did(&Keypair::generate()))
+        .amount(amount)
+        .build()
+        .unwrap()
+}
+
+// ============================================================================
+// DEFAULT POLICY
+// ============================================================================
+
+#[test]
+fn test_default_policy_adds_no_rules() {
+    let sender = Keypair::generate();
+    let signed = iou(&sender, u64::MAX / 2);
+
+    let result = IOUValidator::validate_with_policy(&signed, &sender.public_key(), &ValidationPolicy::new());
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_policy_runs_after_builtin_rules() {
+    let sender = Keypair::generate();
+    let other = Keypair::generate();
+    let signed = iou(&sender, 5);
+    let policy = ValidationPolicy::new().with_min_amount(10);
+
+    let result = IOUValidator::validate_with_policy(&signed, &other.public_key(), &policy);
+    assert!(matches!(result, Err(ValidationError::SenderMismatch)));
+}
+
+// ============================================================================
+// AMOUNT LIMITS
+// ============================================================================
+
+#[test]
+fn test_min_amount() {
+    let sender = Keypair::generate();
+    let policy = ValidationPolicy::new().with_min_amount(10);
+
+    let result = IOUValidator::validate_with_policy(&iou(&sender, 9), &sender.public_key(), &policy);
+    assert!(matches!(result, Err(ValidationError::AmountBelowMinimum { amount: 9, min: 10 })));
+
+    assert!(IOUValidator::validate_with_policy(&iou(&sender, 10), &sender.public_key(), &policy).is_ok());
+}
+
+#[test]
+fn test_max_amount() {
+    let sender = Keypair::generate();
+    let policy = ValidationPolicy::new().with_max_amount(1000);
+
+    let result = IOUValidator::validate_with_policy(&iou(&sender, 1001), &sender.public_key(), &policy);
+    assert!(matches!(result, Err(ValidationError::AmountAboveMaximum { amount: 1001, max: 1000 })));
+
+    assert!(IOUValidator::validate_with_policy(&iou(&sender, 1000), &sender.public_key(), &policy).is_ok());
+}
+
+// ============================================================================
+// TIME RULES
+// ============================================================================
+
+#[test]
+fn test_max_clock_skew() {
+    let sender = Keypair::generate();
+    let policy = ValidationPolicy::new().with_max_clock_skew_secs(60);
+    let at = |timestamp: u64| {
+        IOUBuilder::new()
+            .sender(&sender)
+            .recipient(did(&Keypair::generate()))
+            .amount(10)
+            .timestamp(timestamp)
+            .build()
+            .unwrap()
+    };
+
+    let result = IOUValidator::validate_with_policy(&at(now() + 3600), &sender.public_key(), &policy);
+    assert!(matches!(result, Err(ValidationError::FutureTimestamp)));
+
+    assert!(IOUValidator::validate_with_policy(&at(now() + 10), &sender.public_key(), &policy).is_ok());
+}
+
+#[test]
+fn test_require_expiry() {
+    let sender = Keypair::generate();
+    let policy = ValidationPolicy::new().with_require_expiry(true);
+
+    let result = IOUValidator::validate_with_policy(&iou(&sender, 10), &sender.public_key(), &policy);
+    assert!(matches!(result, Err(ValidationError::MissingExpiry)));
+
+    let expiring = IOUBuilder::new()
+        .sender(&sender)
+        .recipient(did(&Keypair::generate()))
+        .amount(10)
+        .expires_in_secs(3600)
+        .build()
+        .unwrap();
+    assert!(IOUValidator::validate_with_policy(&expiring, &sender.public_key(), &policy).is_ok());
+}
+
+// ============================================================================
+// SENDER LISTS
+// ============================================================================
+
+#[test]
+fn test_allow_list() {
+    let trusted = Keypair::generate();
+    let stranger = Keypair::generate();
+    let policy = ValidationPolicy::new().allow_sender(did(&trusted));
+
+    assert!(IOUValidator::validate_with_policy(&iou(&trusted, 10), &trusted.public_key(), &policy).is_ok());
+
+    let result = IOUValidator::validate_with_policy(&iou(&stranger, 10), &stranger.public_key(), &policy);
+    match result {
+        Err(ValidationError::SenderNotAllowed(sender)) => assert_eq!(sender, did(&stranger)),
+        other => panic!("Expected SenderNotAllowed, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_deny_list() {
+    let banned = Keypair::generate();
+    let anyone = Keypair::generate();
+    let policy = ValidationPolicy::new().deny_sender(did(&banned));
+
+    assert!(IOUValidator::validate_with_policy(&iou(&anyone, 10), &anyone.public_key(), &policy).is_ok());
+
+    let result = IOUValidator::validate_with_policy(&iou(&banned, 10), &banned.public_key(), &policy);
+    assert!(matches!(result, Err(ValidationError::SenderDenied(_))));
+}
+
+#[test]
+fn test_deny_list_overrides_allow_list() {
+    let sender = Keypair::generate();
+    let policy = ValidationPolicy::new()
+        .allow_sender(did(&sender))
+        .deny_sender(did(&sender));
+
+    assert!(!policy.is_sender_allowed(&did(&sender)));
+    let result = IOUValidator::validate_with_policy(&iou(&sender, 10), &sender.public_key(), &policy);
+    assert!(matches!(result, Err(ValidationError::SenderDenied(_))));
+}
+
+// ============================================================================
+// VALIDATOR INSTANCE
+// ============================================================================
+
+#[test]
+fn test_validator_with_policy() {
+    let sender = Keypair::generate();
+    let validator = IOUValidator::with_policy(ValidationPolicy::new().with_max_amount(50));
+
+    assert_eq!(validator.policy().max_amount(), Some(50));
+    assert!(validator.check(&iou(&sender, 50), &sender.public_key()).is_ok());
+    assert!(matches!(
+        validator.check(&iou(&sender, 51), &sender.public_key()),
+        Err(ValidationError::AmountAboveMaximum { .. })
+    ));
+}
+
+#[test]
+fn test_default_validator_matches_validate() {
+    let sender = Keypair::generate();
+    let signed = iou(&sender, 10);
+
+    let validator = IOUValidator::default();
+    assert_eq!(validator.policy(), &ValidationPolicy::default());
+    assert_eq!(
+        validator.check(&signed, &sender.public_key()).unwrap(),
+        IOUValidator::validate(&signed, &sender.public_key()).unwrap()
+    );
+}
@@ -0,0 +1,86 @@
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, RejectionReason, SignedIOU, SignedRejection};
+
+// ============================================================================
+// REJECTION TESTS
+// ============================================================================
+
+/// Helper to create an IOU from a fresh sender to `recipient`
+fn create_iou_for(recipient: &Keypair) -> SignedIOU {
+    let sender = Keypair::generate();
+    IOUBuilder::new()
+        .sender(&sender)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(100)
+        .build()
+        .unwrap()
+}
+
+/// Test: Signed rejection carries the IOU id, recipient and reason
+#[test]
+fn test_rejection_fields() {
+    let recipient = Keypair::generate();
+    let iou = create_iou_for(&recipient);
+
+    let rejection = SignedRejection::sign(&recipient, iou.id(), RejectionReason::WrongAmount);
+
+    assert_eq!(rejection.rejection().iou_id(), &iou.id());
+    assert_eq!(rejection.rejection().recipient(), &Did::from_public_key(&recipient.public_key()));
+    assert_eq!(rejection.rejection().reason(), RejectionReason::WrongAmount);
+    assert!(rejection.rejection().timestamp() > 0);
+}
+
+/// Test: Rejection signature verifies against the recipient
+#[test]
+fn test_rejection_signature_verifies() {
+    let recipient = Keypair::generate();
+    let iou = create_iou_for(&recipient);
+
+    let rejection = SignedRejection::sign(&recipient, iou.id(), RejectionReason::Declined);
+
+    assert!(rejection.verify(&recipient.public_key()));
+    assert!(rejection.verify_recipient());
+    assert!(!rejection.verify(&Keypair::generate().public_key()));
+}
+
+/// Test: Rejection survives a bytes round-trip
+#[test]
+fn test_rejection_bytes_roundtrip() {
+    let recipient = Keypair::generate();
+    let iou = create_iou_for(&recipient);
+    let rejection = SignedRejection::sign(&recipient, iou.id(), RejectionReason::Expired);
+
+    let decoded = SignedRejection::from_bytes(&rejection.to_bytes()).unwrap();
+
+    assert_eq!(decoded.rejection(), rejection.rejection());
+    assert!(decoded.verify_recipient());
+}
+
+/// Test: Rejection signature does not verify as an IOU signature
+#[test]
+fn test_rejection_signature_is_domain_separated() {
+    let recipient = Keypair::generate();
+    let iou = create_iou_for(&recipient);
+    let rejection = SignedRejection::sign(&recipient, iou.id(), RejectionReason::Declined);
+
+    assert_ne!(
+        rejection.rejection().to_signing_bytes(),
+        iou.iou().to_signing_bytes()
+    );
+    assert!(rejection.rejection().to_signing_bytes().starts_with(b"p2pmesh:rejection:v1"));
+}
+
+/// Test: Reason codes round-trip and unknown codes map to Other
+#[test]
+fn test_rejection_reason_codes() {
+    for reason in [
+        RejectionReason::Declined,
+        RejectionReason::WrongAmount,
+        RejectionReason::Expired,
+        RejectionReason::UnknownSender,
+        RejectionReason::Other,
+    ] {
+        assert_eq!(RejectionReason::from_code(reason.code()), reason);
+    }
+    assert_eq!(RejectionReason::from_code(42), RejectionReason::Other);
+}
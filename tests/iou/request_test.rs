@@ -0,0 +1,248 @@
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{
+    IOUBuilder, IOUCodec, IOUError, PaymentRequestBuilder, SignedPaymentRequest, MAX_MEMO_BYTES,
+};
+
+// ============================================================================
+// PAYMENT REQUEST TESTS
+// ============================================================================
+
+/// Helper to create a signed request for `amount` from `requester`
+fn create_request(requester: &Keypair, amount: u64) -> SignedPaymentRequest {
+    PaymentRequestBuilder::new()
+        .requester(requester)
+        .amount(amount)
+        .build()
+        .unwrap()
+}
+
+/// Test: Request carries the requester DID, amount, memo and expiry
+#[test]
+fn test_request_fields() {
+    let merchant = Keypair::generate();
+
+    let request = PaymentRequestBuilder::new()
+        .requester(&merchant)
+        .amount(250)
+        .memo("order #4711")
+        .timestamp(1_000)
+        .ttl_secs(600)
+        .build()
+        .unwrap();
+
+    let inner = request.request();
+    assert_eq!(inner.recipient(), &Did::from_public_key(&merchant.public_key()));
+    assert_eq!(inner.amount(), 250);
+    assert_eq!(inner.memo(), Some("order #4711"));
+    assert_eq!(inner.timestamp(), 1_000);
+    assert_eq!(inner.expiry(), Some(1_600));
+}
+
+/// Test: Request signature verifies against the requester only
+#[test]
+fn test_request_signature_verifies() {
+    let merchant = Keypair::generate();
+    let request = create_request(&merchant, 100);
+
+    assert!(request.verify(&merchant.public_key()));
+    assert!(request.verify_requester());
+    assert!(!request.verify(&Keypair::generate().public_key()));
+}
+
+/// Test: Request survives a bytes round-trip with the same ID
+#[test]
+fn test_request_bytes_roundtrip() {
+    let merchant = Keypair::generate();
+    let request = PaymentRequestBuilder::new()
+        .requester(&merchant)
+        .amount(100)
+        .memo("table 7")
+        .ttl_secs(60)
+        .build()
+        .unwrap();
+
+    let decoded = SignedPaymentRequest::from_bytes(&request.to_bytes()).unwrap();
+
+    assert_eq!(decoded.request(), request.request());
+    assert_eq!(decoded.id(), request.id());
+    assert!(decoded.verify_requester());
+}
+
+/// Test: Requests with the same amount get distinct IDs
+#[test]
+fn test_request_ids_are_unique() {
+    let merchant = Keypair::generate();
+
+    assert_ne!(create_request(&merchant, 100).id(), create_request(&merchant, 100).id());
+}
+
+/// Test: Builder validates its fields like `IOUBuilder`
+#[test]
+fn test_request_builder_validation() {
+    let merchant = Keypair::generate();
+
+    let missing = PaymentRequestBuilder::new().amount(10).build();
+    assert!(matches!(missing, Err(IOUError::MissingRequester)));
+
+    let zero = PaymentRequestBuilder::new().requester(&merchant).amount(0).build();
+    assert!(matches!(zero, Err(IOUError::InvalidAmount(_))));
+
+    let long_memo = PaymentRequestBuilder::new()
+        .requester(&merchant)
+        .amount(10)
+        .memo("x".repeat(MAX_MEMO_BYTES + 1))
+        .build();
+    assert!(matches!(long_memo, Err(IOUError::MemoTooLong { .. })));
+
+    let past_expiry = PaymentRequestBuilder::new()
+        .requester(&merchant)
+        .amount(10)
+        .timestamp(1_000)
+        .expiry(1_000)
+        .build();
+    assert!(matches!(past_expiry, Err(IOUError::InvalidExpiry(_))));
+}
+
+/// Test: Request expiry is checked against the given time
+#[test]
+fn test_request_expiry() {
+    let merchant = Keypair::generate();
+    let request = PaymentRequestBuilder::new()
+        .requester(&merchant)
+        .amount(10)
+        .timestamp(1_000)
+        .expiry(2_000)
+        .build()
+        .unwrap();
+
+    assert!(!request.request().is_expired_at(1_999));
+    assert!(request.request().is_expired_at(2_000));
+    assert!(!create_request(&merchant, 10).request().is_expired_at(u64::MAX));
+}
+
+/// Test: Tampering with the request breaks its signature
+#[test]
+fn test_tampered_request_fails_verification() {
+    let merchant = Keypair::generate();
+    let request = create_request(&merchant, 100);
+    let other = create_request(&merchant, 1_000);
+
+    let forged = SignedPaymentRequest::from_parts(other.request().clone(), request.signature().clone());
+
+    assert!(!forged.verify_requester());
+}
+
+// ============================================================================
+// FULFILLMENT TESTS
+// ============================================================================
+
+/// Test: IOU built for a request fulfills it and carries its memo
+#[test]
+fn test_iou_for_request_fulfills_it() {
+    let merchant = Keypair::generate();
+    let customer = Keypair::generate();
+    let request = PaymentRequestBuilder::new()
+        .requester(&merchant)
+        .amount(75)
+        .memo("invoice 12")
+        .build()
+        .unwrap();
+
+    let iou = IOUBuilder::new()
+        .sender(&customer)
+        .for_request(request.request())
+        .build()
+        .unwrap();
+
+    assert!(iou.fulfills(request.request()));
+    assert_eq!(iou.iou().request_id(), Some(&request.id()));
+    assert_eq!(iou.iou().recipient(), request.request().recipient());
+    assert_eq!(iou.iou().amount(), 75);
+    assert_eq!(iou.memo(), Some("invoice 12"));
+    assert!(iou.verify(&customer.public_key()));
+}
+
+/// Test: Plain IOU with matching recipient and amount doesn't fulfill a request
+#[test]
+fn test_iou_without_request_id_does_not_fulfill() {
+    let merchant = Keypair::generate();
+    let customer = Keypair::generate();
+    let request = create_request(&merchant, 75);
+
+    let iou = IOUBuilder::new()
+        .sender(&customer)
+        .recipient(Did::from_public_key(&merchant.public_key()))
+        .amount(75)
+        .build()
+        .unwrap();
+
+    assert!(!iou.fulfills(request.request()));
+}
+
+/// Test: Wrong amount or a different request is not a fulfillment
+#[test]
+fn test_mismatched_iou_does_not_fulfill() {
+    let merchant = Keypair::generate();
+    let customer = Keypair::generate();
+    let request = create_request(&merchant, 75);
+    let other_request = create_request(&merchant, 75);
+
+    let short = IOUBuilder::new()
+        .sender(&customer)
+        .for_request(request.request())
+        .amount(74)
+        .build()
+        .unwrap();
+    let other = IOUBuilder::new()
+        .sender(&customer)
+        .for_request(other_request.request())
+        .build()
+        .unwrap();
+
+    assert!(!short.fulfills(request.request()));
+    assert!(!other.fulfills(request.request()));
+}
+
+/// Test: Request ID is signed and survives the codec
+#[test]
+fn test_request_id_survives_codec() {
+    let merchant = Keypair::generate();
+    let customer = Keypair::generate();
+    let request = create_request(&merchant, 40);
+    let iou = IOUBuilder::new()
+        .sender(&customer)
+        .for_request(request.request())
+        .build()
+        .unwrap();
+
+    let decoded = IOUCodec::decode(&IOUCodec::encode(&iou)).unwrap();
+
+    assert_eq!(decoded.id(), iou.id());
+    assert!(decoded.fulfills(request.request()));
+    assert!(decoded.verify(&customer.public_key()));
+}
+
+/// Test: IOUs from before the request ID field still decode
+#[test]
+fn test_pre_request_bytes_still_decode() {
+    let customer = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&customer)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(40)
+        .memo("lunch")
+        .build()
+        .unwrap();
+
+    // Drop the trailing hashlock tag, denomination tag, output count and request ID tag
+    let mut bytes = IOUCodec::encode(&iou);
+    assert_eq!(bytes.pop(), Some(0));
+    assert_eq!(bytes.pop(), Some(0));
+    assert_eq!(bytes.pop(), Some(0));
+    assert_eq!(bytes.pop(), Some(0));
+
+    let decoded = IOUCodec::decode(&bytes).unwrap();
+    assert_eq!(decoded.id(), iou.id());
+    assert_eq!(decoded.memo(), Some("lunch"));
+    assert!(decoded.iou().request_id().is_none());
+}
@@ -1,5 +1,5 @@
 use p2pmesh::identity::{Keypair, Did, Signer, Signature};
-use p2pmesh::iou::{IOU, SignedIOU, IOUBuilder, IOUValidator, ValidationError};
+use p2pmesh::iou::{IOU, SignedIOU, IOUBuilder, IOUValidator, ValidationError, ValidationPolicy, MAX_MEMO_BYTES};
 
 // ============================================================================
 // IOU VALIDATOR TESTS
@@ -33,7 +33,7 @@ fn test_valid_iou_passes() {
 /// Test: Tampered amount fails validation
 #[test]
 fn test_tampered_amount_fails() {
-    let (signed_iou, sender_kp, recipient_kp) = create_valid_signed_iou();
+    let (signed_iou, sender_kp, _) = create_valid_signed_iou();
 
     // Create a new IOU with different amount but same signature
     let tampered_iou = IOU::new(
@@ -399,3 +399,208 @@ fn test_zero_amount_fails_validation() {
         _ => panic!("Expected InvalidAmount error"),
     }
 }
+
+// ============================================================================
+// EXPIRY TESTS
+// ============================================================================
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Test: Expired IOU fails validation
+#[test]
+fn test_expired_iou_fails_validation() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let now = now_secs();
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&recipient_kp.public_key()))
+        .amount(100)
+        .timestamp(now - 7200)
+        .expiry(now - 3600)
+        .build()
+        .unwrap();
+
+    let result = IOUValidator::validate(&signed_iou, &sender_kp.public_key());
+    assert!(matches!(result, Err(ValidationError::Expired)));
+}
+
+/// Test: IOU with a future expiry passes validation
+#[test]
+fn test_unexpired_iou_passes_validation() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&recipient_kp.public_key()))
+        .amount(100)
+        .ttl_secs(3600)
+        .build()
+        .unwrap();
+
+    let result = IOUValidator::validate(&signed_iou, &sender_kp.public_key());
+    assert!(result.is_ok());
+}
+
+/// Test: Tampering with the expiry invalidates the signature
+#[test]
+fn test_tampered_expiry_fails() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let now = now_secs();
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&recipient_kp.public_key()))
+        .amount(100)
+        .timestamp(now)
+        .expiry(now + 60)
+        .build()
+        .unwrap();
+
+    let original = signed_iou.iou();
+    let tampered_iou = IOU::new(
+        original.sender().clone(),
+        original.recipient().clone(),
+        original.amount(),
+        original.nonce(),
+        original.timestamp(),
+    )
+    .with_expiry(now + 86400);
+
+    let tampered = SignedIOU::from_parts(tampered_iou, signed_iou.signature().clone());
+
+    let result = IOUValidator::validate(&tampered, &sender_kp.public_key());
+    assert!(matches!(result, Err(ValidationError::InvalidSignature)));
+}
+
+/// Test: validate_at checks expiry against the given clock, not the wall clock
+#[test]
+fn test_validate_at_uses_given_time() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&recipient_kp.public_key()))
+        .amount(100)
+        .timestamp(1000)
+        .expiry(1060)
+        .build()
+        .unwrap();
+
+    assert!(IOUValidator::validate_at(&signed_iou, &sender_kp.public_key(), 1030).is_ok());
+    let result = IOUValidator::validate_at(&signed_iou, &sender_kp.public_key(), 2000);
+    assert!(matches!(result, Err(ValidationError::Expired)));
+    let result = IOUValidator::validate(&signed_iou, &sender_kp.public_key());
+    assert!(matches!(result, Err(ValidationError::Expired)));
+}
+
+/// Test: validate_with_policy_at checks expiry against the given clock
+#[test]
+fn test_validate_with_policy_at_uses_given_time() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+    let now = now_secs();
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&recipient_kp.public_key()))
+        .amount(100)
+        .timestamp(now)
+        .expiry(now + 60)
+        .build()
+        .unwrap();
+    let policy = ValidationPolicy::default();
+
+    assert!(IOUValidator::validate_with_policy_at(&signed_iou, &sender_kp.public_key(), &policy, now).is_ok());
+    let result = IOUValidator::validate_with_policy_at(&signed_iou, &sender_kp.public_key(), &policy, now + 61);
+    assert!(matches!(result, Err(ValidationError::Expired)));
+}
+
+/// Test: Tampering with the memo invalidates the signature
+#[test]
+fn test_tampered_memo_fails() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&recipient_kp.public_key()))
+        .amount(100)
+        .memo("order 1")
+        .build()
+        .unwrap();
+
+    let original = signed_iou.iou();
+    let tampered_iou = IOU::new(
+        original.sender().clone(),
+        original.recipient().clone(),
+        original.amount(),
+        original.nonce(),
+        original.timestamp(),
+    )
+    .with_memo("order 2".to_string());
+
+    let tampered = SignedIOU::from_parts(tampered_iou, signed_iou.signature().clone());
+
+    let result = IOUValidator::validate(&tampered, &sender_kp.public_key());
+    assert!(matches!(result, Err(ValidationError::InvalidSignature)));
+}
+
+/// Test: Stripping the memo invalidates the signature
+#[test]
+fn test_stripped_memo_fails() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+
+    let signed_iou = IOUBuilder::new()
+        .sender(&sender_kp)
+        .recipient(Did::from_public_key(&recipient_kp.public_key()))
+        .amount(100)
+        .memo("order 1")
+        .build()
+        .unwrap();
+
+    let original = signed_iou.iou();
+    let stripped_iou = IOU::new(
+        original.sender().clone(),
+        original.recipient().clone(),
+        original.amount(),
+        original.nonce(),
+        original.timestamp(),
+    );
+    let stripped = SignedIOU::from_parts(stripped_iou, signed_iou.signature().clone());
+
+    let result = IOUValidator::validate(&stripped, &sender_kp.public_key());
+    assert!(matches!(result, Err(ValidationError::InvalidSignature)));
+}
+
+/// Test: A signed IOU with an oversized memo is rejected
+#[test]
+fn test_memo_too_long_fails() {
+    let sender_kp = Keypair::generate();
+    let recipient_kp = Keypair::generate();
+
+    // Bypass the builder's check by signing the IOU directly
+    let iou = IOU::new(
+        Did::from_public_key(&sender_kp.public_key()),
+        Did::from_public_key(&recipient_kp.public_key()),
+        100,
+        1,
+        now_secs(),
+    )
+    .with_memo("x".repeat(MAX_MEMO_BYTES + 1));
+    let signature = Signer::sign(&sender_kp, &iou.to_signing_bytes());
+    let signed_iou = SignedIOU::from_parts(iou, signature);
+
+    let result = IOUValidator::validate(&signed_iou, &sender_kp.public_key());
+    assert!(matches!(result, Err(ValidationError::MemoTooLong)));
+}
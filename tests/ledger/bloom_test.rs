@@ -0,0 +1,187 @@
+// Bloom Summary Tests
+// Tests for bloom-filter-based delta exchange between mesh states
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{BloomError, BloomSummary, MeshState, NodeId, MAX_BLOOM_HASHES};
+
+fn create_test_iou(sender: &Keypair, recipient: &Keypair, amount: u64, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(amount)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+/// Two states sharing `shared` IOUs, where the first also has `extra` more
+fn create_diverged_states(shared: u64, extra: u64) -> (MeshState, MeshState) {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut ahead = MeshState::new(NodeId::generate());
+    let mut behind = MeshState::new(NodeId::generate());
+
+    for nonce in 0..shared + extra {
+        let iou = create_test_iou(&alice, &bob, 10 + nonce, nonce);
+        if nonce < shared {
+            behind.add_iou(iou.clone(), &alice.public_key()).unwrap();
+        }
+        ahead.add_iou(iou, &alice.public_key()).unwrap();
+    }
+
+    (ahead, behind)
+}
+
+// ============================================================================
+// BLOOM FILTER BASICS
+// ============================================================================
+
+#[test]
+fn test_bloom_contains_inserted_ids() {
+    let (state, _) = create_diverged_states(50, 0);
+    let summary = state.bloom_summary(0.01);
+
+    assert_eq!(summary.item_count(), 50);
+    for entry in state.all_entries() {
+        assert!(summary.contains(&entry.id()), "No false negatives allowed");
+    }
+}
+
+#[test]
+fn test_bloom_empty_state() {
+    let state = MeshState::new(NodeId::generate());
+    let summary = state.bloom_summary(0.01);
+
+    assert_eq!(summary.item_count(), 0);
+    assert!(summary.num_bits() >= 8);
+    assert!(summary.num_hashes() >= 1);
+}
+
+#[test]
+fn test_bloom_fp_rate_is_clamped() {
+    let loose = BloomSummary::with_capacity(100, 5.0);
+    let nan = BloomSummary::with_capacity(100, f64::NAN);
+    let tight = BloomSummary::with_capacity(100, 0.0);
+
+    assert!(loose.num_bits() > 0);
+    assert!(nan.num_bits() > 0);
+    assert!(tight.num_hashes() <= MAX_BLOOM_HASHES);
+}
+
+#[test]
+fn test_lower_fp_rate_uses_more_bits() {
+    let coarse = BloomSummary::with_capacity(1000, 0.1);
+    let fine = BloomSummary::with_capacity(1000, 0.001);
+
+    assert!(fine.num_bits() > coarse.num_bits());
+}
+
+// ============================================================================
+// DELTA AGAINST BLOOM
+// ============================================================================
+
+#[test]
+fn test_delta_against_bloom_finds_missing_entries() {
+    let (ahead, behind) = create_diverged_states(100, 10);
+
+    let summary = behind.bloom_summary(0.001);
+    let delta = ahead.delta_against_bloom(&summary);
+
+    // Everything returned is genuinely missing; false positives can only hide entries
+    for entry in &delta {
+        assert!(!behind.has_iou(&entry.id()));
+    }
+    assert!(delta.len() >= 9, "At most a rare false positive at 0.1%");
+}
+
+#[test]
+fn test_delta_against_bloom_of_superset_is_empty() {
+    let (ahead, behind) = create_diverged_states(20, 5);
+
+    let summary = ahead.bloom_summary(0.01);
+    assert!(behind.delta_against_bloom(&summary).is_empty());
+}
+
+#[test]
+fn test_bloom_sync_converges() {
+    let (ahead, mut behind) = create_diverged_states(30, 10);
+
+    // Repeat rounds until nothing is left to send
+    for _ in 0..5 {
+        let delta = ahead.delta_against_bloom(&behind.bloom_summary(0.01));
+        if delta.is_empty() {
+            break;
+        }
+        for entry in delta {
+            behind
+                .add_iou(entry.iou().clone(), entry.sender_pubkey())
+                .unwrap();
+        }
+    }
+
+    assert_eq!(behind.iou_count(), ahead.iou_count());
+}
+
+// ============================================================================
+// BYTES ON WIRE
+// ============================================================================
+
+#[test]
+fn test_bloom_exchange_is_much_smaller_than_full_state() {
+    let (ahead, behind) = create_diverged_states(1000, 10);
+
+    // Full-state path: peer ships its whole state, we reply with the exact delta
+    let full_request = behind.to_bytes();
+    let full_reply = postcard::to_allocvec(&ahead.delta(&behind)).unwrap();
+    let full_bytes = full_request.len() + full_reply.len();
+
+    // Bloom path: peer ships a filter, we reply with the probable delta
+    let bloom_request = behind.bloom_summary(0.01).to_bytes();
+    let bloom_reply = postcard::to_allocvec(&ahead.delta_against_bloom(
+        &BloomSummary::from_bytes(&bloom_request).unwrap(),
+    ))
+    .unwrap();
+    let bloom_bytes = bloom_request.len() + bloom_reply.len();
+
+    assert!(
+        bloom_bytes * 10 < full_bytes,
+        "bloom {} bytes vs full {} bytes",
+        bloom_bytes,
+        full_bytes
+    );
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_bloom_serialization_roundtrip() {
+    let (state, _) = create_diverged_states(25, 0);
+    let summary = state.bloom_summary(0.01);
+
+    let restored = BloomSummary::from_bytes(&summary.to_bytes()).unwrap();
+
+    assert_eq!(restored, summary);
+}
+
+#[test]
+fn test_bloom_from_garbage_fails() {
+    let result = BloomSummary::from_bytes(&[0xff, 0xff, 0xff]);
+    assert_eq!(result, Err(BloomError::DeserializationFailed));
+}
+
+#[test]
+fn test_bloom_rejects_empty_filter() {
+    // Empty bit array, 3 hashes, 0 items
+    let result = BloomSummary::from_bytes(&[0x00, 0x03, 0x00]);
+    assert!(matches!(result, Err(BloomError::InvalidParameters(_))));
+}
+
+#[test]
+fn test_bloom_rejects_excessive_hashes() {
+    // One byte of bits, 200 hashes, 0 items
+    let result = BloomSummary::from_bytes(&[0x01, 0x00, 0xc8, 0x01, 0x00]);
+    assert!(matches!(result, Err(BloomError::InvalidParameters(_))));
+}
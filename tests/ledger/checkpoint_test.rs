@@ -0,0 +1,236 @@
+// Checkpoint Tests
+// Tests for pruning settled IOUs from mesh state without breaking convergence
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{
+    Checkpoint, MeshState, MeshStateError, NodeId, SignedCheckpoint,
+};
+
+fn create_test_iou(sender: &Keypair, recipient: &Keypair, amount: u64, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(amount)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+/// A state holding `count` IOUs from Alice, and the IOUs
+fn state_with_ious(count: u64) -> (MeshState, Vec<SignedIOU>, Keypair) {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut state = MeshState::new(NodeId::generate());
+    let ious: Vec<SignedIOU> = (0..count).map(|n| create_test_iou(&alice, &bob, 10 + n, n)).collect();
+    for iou in &ious {
+        state.add_iou(iou.clone(), &alice.public_key()).unwrap();
+    }
+    (state, ious, alice)
+}
+
+fn replica(state: &MeshState) -> MeshState {
+    let mut copy = MeshState::new(NodeId::generate());
+    copy.merge(state);
+    copy
+}
+
+// ============================================================================
+// CHECKPOINT BASICS
+// ============================================================================
+
+#[test]
+fn test_checkpoint_ids_sorted_and_deduplicated() {
+    let (_, ious, _) = state_with_ious(3);
+    let gateway = Keypair::generate();
+    let ids = vec![ious[2].id(), ious[0].id(), ious[2].id(), ious[1].id()];
+
+    let checkpoint = SignedCheckpoint::sign(&gateway, ids);
+
+    let mut expected: Vec<_> = ious.iter().map(|iou| iou.id()).collect();
+    expected.sort_by_key(|id| *id.as_bytes());
+    assert_eq!(checkpoint.checkpoint().iou_ids(), expected.as_slice());
+    assert!(checkpoint.verify_issuer());
+}
+
+#[test]
+fn test_checkpoint_id_independent_of_input_order() {
+    let (_, ious, _) = state_with_ious(3);
+    let issuer = Did::from_public_key(&Keypair::generate().public_key());
+
+    let forward = Checkpoint::new(ious.iter().map(|iou| iou.id()), issuer.clone(), 100);
+    let reverse = Checkpoint::new(ious.iter().rev().map(|iou| iou.id()), issuer, 100);
+
+    assert_eq!(forward.id(), reverse.id());
+}
+
+#[test]
+fn test_checkpoint_round_trip_bytes() {
+    let (_, ious, _) = state_with_ious(2);
+    let checkpoint = SignedCheckpoint::sign(&Keypair::generate(), ious.iter().map(|iou| iou.id()));
+
+    let restored = SignedCheckpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+
+    assert_eq!(restored.id(), checkpoint.id());
+    assert!(restored.verify_issuer());
+}
+
+// ============================================================================
+// APPLYING CHECKPOINTS
+// ============================================================================
+
+#[test]
+fn test_apply_checkpoint_prunes_settled_entries() {
+    let (mut state, ious, _) = state_with_ious(5);
+    let checkpoint = SignedCheckpoint::sign(&Keypair::generate(), ious[..3].iter().map(|iou| iou.id()));
+
+    let removed = state.apply_checkpoint(&checkpoint).unwrap();
+
+    assert_eq!(removed, 3);
+    assert_eq!(state.iou_count(), 2);
+    assert!(state.is_pruned(&ious[0].id()));
+    assert!(state.get_iou(&ious[0].id()).is_none());
+    assert!(state.has_checkpoint(&checkpoint.id()));
+}
+
+#[test]
+fn test_apply_checkpoint_is_idempotent() {
+    let (mut state, ious, _) = state_with_ious(3);
+    let checkpoint = SignedCheckpoint::sign(&Keypair::generate(), vec![ious[0].id()]);
+
+    assert_eq!(state.apply_checkpoint(&checkpoint).unwrap(), 1);
+    let version = state.version();
+
+    assert_eq!(state.apply_checkpoint(&checkpoint).unwrap(), 0);
+    assert_eq!(state.version(), version);
+    assert_eq!(state.checkpoint_count(), 1);
+}
+
+#[test]
+fn test_apply_forged_checkpoint_rejected() {
+    let (mut state, ious, _) = state_with_ious(2);
+    let gateway = Keypair::generate();
+    let checkpoint = SignedCheckpoint::sign(&gateway, vec![ious[0].id()]);
+
+    // Re-sign the same checkpoint with a key that isn't the issuer's
+    let forged = SignedCheckpoint::from_parts(
+        checkpoint.checkpoint().clone(),
+        SignedCheckpoint::sign(&Keypair::generate(), vec![ious[0].id()]).signature().clone(),
+    );
+
+    assert!(matches!(state.apply_checkpoint(&forged), Err(MeshStateError::InvalidCheckpoint)));
+    assert_eq!(state.iou_count(), 2);
+}
+
+#[test]
+fn test_pruned_iou_cannot_be_re_added() {
+    let (mut state, ious, alice) = state_with_ious(2);
+    state.apply_checkpoint(&SignedCheckpoint::sign(&Keypair::generate(), vec![ious[0].id()])).unwrap();
+
+    let result = state.add_iou(ious[0].clone(), &alice.public_key());
+
+    assert!(matches!(result, Err(MeshStateError::Pruned)));
+}
+
+#[test]
+fn test_merge_with_unpruned_peer_prevents_resurrection() {
+    let (mut pruned, ious, _) = state_with_ious(4);
+    let unpruned = replica(&pruned);
+    pruned.apply_checkpoint(&SignedCheckpoint::sign(&Keypair::generate(), ious[..2].iter().map(|iou| iou.id()))).unwrap();
+
+    let result = pruned.merge(&unpruned);
+
+    assert_eq!(result.new_entries, 0);
+    assert_eq!(result.resurrections_prevented, 2);
+    assert_eq!(result.total_after_merge, 2);
+    assert_eq!(pruned.iou_count(), 2);
+}
+
+#[test]
+fn test_merge_reports_new_entries_alongside_prevented_ones() {
+    let (mut pruned, ious, alice) = state_with_ious(2);
+    let mut unpruned = replica(&pruned);
+    pruned.apply_checkpoint(&SignedCheckpoint::sign(&Keypair::generate(), vec![ious[0].id()])).unwrap();
+    let fresh = create_test_iou(&alice, &Keypair::generate(), 5, 99);
+    unpruned.add_iou(fresh.clone(), &alice.public_key()).unwrap();
+
+    let result = pruned.merge(&unpruned);
+
+    assert_eq!(result.new_entries, 1);
+    assert_eq!(result.resurrections_prevented, 1);
+    assert!(pruned.has_iou(&fresh.id()));
+}
+
+#[test]
+fn test_pruning_survives_serialization() {
+    let (mut state, ious, _) = state_with_ious(3);
+    let unpruned = replica(&state);
+    let checkpoint = SignedCheckpoint::sign(&Keypair::generate(), vec![ious[0].id()]);
+    state.apply_checkpoint(&checkpoint).unwrap();
+
+    let mut restored = MeshState::from_bytes(&state.to_bytes()).unwrap();
+
+    assert!(restored.has_checkpoint(&checkpoint.id()));
+    assert_eq!(restored.merge(&unpruned).resurrections_prevented, 1);
+    assert_eq!(restored.iou_count(), 2);
+}
+
+// ============================================================================
+// CONVERGENCE
+// ============================================================================
+
+#[test]
+fn test_three_nodes_pruning_at_different_times_converge() {
+    let (mut node_a, ious, alice) = state_with_ious(6);
+    let mut node_b = replica(&node_a);
+    let mut node_c = replica(&node_a);
+    let checkpoint = SignedCheckpoint::sign(&Keypair::generate(), ious[..4].iter().map(|iou| iou.id()));
+
+    // A prunes first, then gossips with B, which hasn't
+    node_a.apply_checkpoint(&checkpoint).unwrap();
+    node_a.merge(&node_b);
+    node_b.merge(&node_a);
+    assert_eq!(node_a.iou_count(), 2);
+    assert_eq!(node_b.iou_count(), 6);
+
+    // New activity on C meanwhile, then B prunes and syncs with C
+    let fresh = create_test_iou(&alice, &Keypair::generate(), 1, 100);
+    node_c.add_iou(fresh, &alice.public_key()).unwrap();
+    node_b.apply_checkpoint(&checkpoint).unwrap();
+    node_b.merge(&node_c);
+    node_c.merge(&node_b);
+
+    // C prunes last; a final round of merges settles everyone
+    node_c.apply_checkpoint(&checkpoint).unwrap();
+    for _ in 0..2 {
+        node_a.merge(&node_b);
+        node_a.merge(&node_c);
+        node_b.merge(&node_a);
+        node_b.merge(&node_c);
+        node_c.merge(&node_a);
+        node_c.merge(&node_b);
+    }
+
+    assert_eq!(node_a.iou_count(), 3);
+    assert_eq!(node_a.merkle_root(), node_b.merkle_root());
+    assert_eq!(node_b.merkle_root(), node_c.merkle_root());
+    assert!(ious[..4].iter().all(|iou| !node_c.has_iou(&iou.id())));
+}
+
+#[test]
+fn test_checkpoint_order_does_not_matter() {
+    let (state, ious, _) = state_with_ious(6);
+    let gateway = Keypair::generate();
+    let first = SignedCheckpoint::sign(&gateway, ious[..2].iter().map(|iou| iou.id()));
+    let second = SignedCheckpoint::sign(&gateway, ious[1..4].iter().map(|iou| iou.id()));
+
+    let mut forward = replica(&state);
+    forward.apply_checkpoint(&first).unwrap();
+    forward.apply_checkpoint(&second).unwrap();
+    let mut reverse = replica(&state);
+    reverse.apply_checkpoint(&second).unwrap();
+    reverse.apply_checkpoint(&first).unwrap();
+
+    assert_eq!(forward.iou_count(), 2);
+    assert_eq!(forward.merkle_root(), reverse.merkle_root());
+}
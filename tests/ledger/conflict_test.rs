@@ -4,10 +4,10 @@
 use p2pmesh::identity::{Did, Keypair};
 use p2pmesh::iou::IOUBuilder;
 use p2pmesh::ledger::{
-    ConflictDetector, ConflictError, ConflictType, MeshState, NodeId,
-    SpendingClaim, ConflictResolution,
+    ConflictDetector, ConflictError, ConflictPolicy, ConflictType, DropReason, MeshState, NodeId,
+    SpendingClaim, ConflictResolution, ResolutionStrategy,
 };
-use p2pmesh::vault::{Vault, UTXOId};
+use p2pmesh::vault::UTXOId;
 
 // ============================================================================
 // SPENDING CLAIM BASICS
@@ -209,6 +209,26 @@ fn test_get_conflicting_claims() {
     assert_eq!(conflicts.len(), 2);
 }
 
+#[test]
+fn test_prior_spend_finds_earlier_claim() {
+    let mut detector = ConflictDetector::new();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let utxo_id = UTXOId::from_bytes([1u8; 32]);
+
+    let iou1 = create_test_iou(&alice, &bob, 100, 1);
+    let iou2 = create_test_iou(&alice, &bob, 100, 2);
+    let unclaimed = create_test_iou(&alice, &bob, 100, 3);
+
+    // Registered out of order: the earlier timestamp still counts as first
+    let _ = detector.register_claim(SpendingClaim::with_timestamp(utxo_id.clone(), iou2.id(), alice.public_key(), 2000));
+    let _ = detector.register_claim(SpendingClaim::with_timestamp(utxo_id.clone(), iou1.id(), alice.public_key(), 1000));
+
+    assert!(detector.prior_spend(&iou1.id()).is_none());
+    assert_eq!(detector.prior_spend(&iou2.id()).unwrap().spending_iou_id(), &iou1.id());
+    assert!(detector.prior_spend(&unclaimed.id()).is_none());
+}
+
 // ============================================================================
 // CONFLICT RESOLUTION
 // ============================================================================
@@ -375,6 +395,381 @@ fn test_merge_detectors_finds_conflict() {
     assert_eq!(detector1.conflict_count(), 1);
 }
 
+// ============================================================================
+// CONFLICT POLICIES
+// ============================================================================
+
+/// Two IOUs from alice spending the same UTXO:
+/// `early` is signed first but uses nonce 5, `low` is signed later with nonce 2
+struct DoubleSpend {
+    alice: Keypair,
+    early: p2pmesh::iou::SignedIOU,
+    low: p2pmesh::iou::SignedIOU,
+    claims: ConflictDetector,
+}
+
+fn double_spend() -> DoubleSpend {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+    let utxo_id = UTXOId::from_bytes([7u8; 32]);
+
+    let early = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(100)
+        .nonce(5)
+        .timestamp(1_000)
+        .build()
+        .unwrap();
+    let low = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&charlie.public_key()))
+        .amount(100)
+        .nonce(2)
+        .timestamp(2_000)
+        .build()
+        .unwrap();
+
+    // Claimed in the opposite order to the signed timestamps
+    let mut claims = ConflictDetector::new();
+    claims.register_claim(SpendingClaim::new(utxo_id.clone(), low.id(), alice.public_key())).unwrap();
+    let _ = claims.register_claim(SpendingClaim::new(utxo_id, early.id(), alice.public_key()));
+
+    DoubleSpend { alice, early, low, claims }
+}
+
+/// Node states holding one side of the double spend each
+fn split_nodes(ds: &DoubleSpend) -> (MeshState, MeshState) {
+    let mut node_a = MeshState::new(NodeId::generate());
+    let mut node_b = MeshState::new(NodeId::generate());
+    node_a.add_iou(ds.early.clone(), &ds.alice.public_key()).unwrap();
+    node_b.add_iou(ds.low.clone(), &ds.alice.public_key()).unwrap();
+    (node_a, node_b)
+}
+
+/// Merge in both directions and check the nodes agree
+fn merge_both_ways(ds: &DoubleSpend, policy: ConflictPolicy) -> MeshState {
+    let (mut node_a, mut node_b) = split_nodes(ds);
+    let (a_view, b_view) = (node_a.clone(), node_b.clone());
+
+    node_a.merge_with_policy(&b_view, &ds.claims, policy);
+    node_b.merge_with_policy(&a_view, &ds.claims, policy);
+
+    assert_eq!(node_a.has_iou(&ds.early.id()), node_b.has_iou(&ds.early.id()));
+    assert_eq!(node_a.has_iou(&ds.low.id()), node_b.has_iou(&ds.low.id()));
+    assert_eq!(node_a.iou_count(), node_b.iou_count());
+    node_a
+}
+
+#[test]
+fn test_first_seen_wins_keeps_earliest_signed() {
+    let ds = double_spend();
+
+    let state = merge_both_ways(&ds, ConflictPolicy::FirstSeenWins);
+
+    assert!(state.has_iou(&ds.early.id()));
+    assert!(!state.has_iou(&ds.low.id()));
+}
+
+#[test]
+fn test_lowest_nonce_wins_keeps_lowest_nonce() {
+    let ds = double_spend();
+
+    let state = merge_both_ways(&ds, ConflictPolicy::LowestNonceWins);
+
+    assert!(state.has_iou(&ds.low.id()));
+    assert!(!state.has_iou(&ds.early.id()));
+}
+
+#[test]
+fn test_reject_both_drops_every_conflicting_iou() {
+    let ds = double_spend();
+
+    let state = merge_both_ways(&ds, ConflictPolicy::RejectBoth);
+
+    assert!(!state.has_iou(&ds.early.id()));
+    assert!(!state.has_iou(&ds.low.id()));
+    assert!(state.is_empty());
+}
+
+#[test]
+fn test_policy_merge_reports_dropped_entries() {
+    let ds = double_spend();
+    let (mut node_a, node_b) = split_nodes(&ds);
+
+    let result = node_a.merge_with_policy(&node_b, &ds.claims, ConflictPolicy::FirstSeenWins);
+
+    assert_eq!(result.new_entries, 0);
+    assert_eq!(result.total_after_merge, 1);
+    assert_eq!(result.dropped.len(), 1);
+    assert_eq!(result.dropped[0].iou_id, ds.low.id());
+    assert_eq!(result.dropped[0].utxo_id, UTXOId::from_bytes([7u8; 32]));
+    assert_eq!(result.dropped[0].reason, DropReason::Superseded { winner: ds.early.id() });
+}
+
+#[test]
+fn test_policy_merge_drops_locally_held_loser() {
+    let ds = double_spend();
+    let (mut node_a, node_b) = split_nodes(&ds);
+
+    // node_a held `early`, which loses on nonce
+    let result = node_a.merge_with_policy(&node_b, &ds.claims, ConflictPolicy::LowestNonceWins);
+
+    assert_eq!(result.new_entries, 1);
+    assert_eq!(result.dropped[0].iou_id, ds.early.id());
+    assert!(node_a.get_ious_by_sender(&Did::from_public_key(&ds.alice.public_key()))
+        .iter()
+        .all(|entry| entry.id() == ds.low.id()));
+}
+
+#[test]
+fn test_reject_both_reports_each_iou() {
+    let ds = double_spend();
+    let (mut node_a, node_b) = split_nodes(&ds);
+
+    let result = node_a.merge_with_policy(&node_b, &ds.claims, ConflictPolicy::RejectBoth);
+
+    let mut dropped: Vec<_> = result.dropped.iter().map(|d| d.iou_id.clone()).collect();
+    dropped.sort_by_key(|id| *id.as_bytes());
+    let mut expected = vec![ds.early.id(), ds.low.id()];
+    expected.sort_by_key(|id| *id.as_bytes());
+    assert_eq!(dropped, expected);
+    assert!(result.dropped.iter().all(|d| d.reason == DropReason::Rejected));
+}
+
+#[test]
+fn test_policy_merge_without_conflicts_is_plain_merge() {
+    let ds = double_spend();
+    let (mut node_a, node_b) = split_nodes(&ds);
+
+    let result = node_a.merge_with_policy(&node_b, &ConflictDetector::new(), ConflictPolicy::RejectBoth);
+
+    assert_eq!(result.new_entries, 1);
+    assert!(result.dropped.is_empty());
+    assert_eq!(node_a.iou_count(), 2);
+}
+
+#[test]
+fn test_policy_ignores_claims_for_unknown_ious() {
+    let ds = double_spend();
+    let mut node = MeshState::new(NodeId::generate());
+    node.add_iou(ds.low.clone(), &ds.alice.public_key()).unwrap();
+
+    // Only one side of the conflict is known, so nothing to resolve yet
+    let result = node.merge_with_policy(&MeshState::new(NodeId::generate()), &ds.claims, ConflictPolicy::RejectBoth);
+
+    assert!(result.dropped.is_empty());
+    assert!(node.has_iou(&ds.low.id()));
+}
+
+#[test]
+fn test_policy_tie_broken_by_iou_id() {
+    let alice = Keypair::generate();
+    let utxo_id = UTXOId::from_bytes([9u8; 32]);
+    let ious: Vec<_> = (0..2)
+        .map(|_| {
+            IOUBuilder::new()
+                .sender(&alice)
+                .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+                .amount(10)
+                .nonce(1)
+                .timestamp(1_000)
+                .build()
+                .unwrap()
+        })
+        .collect();
+    let mut claims = ConflictDetector::new();
+    for iou in &ious {
+        let _ = claims.register_claim(SpendingClaim::new(utxo_id.clone(), iou.id(), alice.public_key()));
+    }
+    let expected = ious.iter().map(|iou| iou.id()).min_by_key(|id| *id.as_bytes()).unwrap();
+
+    for policy in [ConflictPolicy::FirstSeenWins, ConflictPolicy::LowestNonceWins] {
+        let mut node_a = MeshState::new(NodeId::generate());
+        let mut node_b = MeshState::new(NodeId::generate());
+        node_a.add_iou(ious[0].clone(), &alice.public_key()).unwrap();
+        node_b.add_iou(ious[1].clone(), &alice.public_key()).unwrap();
+        let a_view = node_a.clone();
+
+        node_a.merge_with_policy(&node_b, &claims, policy);
+        node_b.merge_with_policy(&a_view, &claims, policy);
+
+        assert!(node_a.has_iou(&expected) && node_b.has_iou(&expected));
+        assert_eq!(node_a.iou_count(), 1);
+        assert_eq!(node_b.iou_count(), 1);
+    }
+}
+
+// ============================================================================
+// RESOLUTION STRATEGIES
+// ============================================================================
+
+/// A detector with one UTXO claimed by three IOUs at the given timestamps
+fn contested_detector(
+    strategy: ResolutionStrategy,
+    timestamps: [u64; 3],
+) -> (ConflictDetector, UTXOId, Vec<p2pmesh::iou::IOUId>) {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let utxo_id = UTXOId::from_bytes([1u8; 32]);
+    let ids: Vec<_> = (1..=3).map(|nonce| create_test_iou(&alice, &bob, 100, nonce).id()).collect();
+
+    let mut detector = ConflictDetector::with_strategy(strategy);
+    for (id, timestamp) in ids.iter().zip(timestamps) {
+        let _ = detector.register_claim(SpendingClaim::with_timestamp(
+            utxo_id.clone(),
+            id.clone(),
+            alice.public_key(),
+            timestamp,
+        ));
+    }
+    (detector, utxo_id, ids)
+}
+
+#[test]
+fn test_resolve_all_first_timestamp_wins() {
+    let (detector, utxo_id, ids) = contested_detector(ResolutionStrategy::FirstTimestampWins, [300, 100, 200]);
+
+    let resolved = detector.resolve_all();
+
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].utxo_id, utxo_id);
+    assert_eq!(resolved[0].winner, Some(ids[1].clone()));
+    assert_eq!(resolved[0].losers.len(), 2);
+    assert!(resolved[0].losers.contains(&ids[0]) && resolved[0].losers.contains(&ids[2]));
+}
+
+#[test]
+fn test_resolve_all_lowest_iou_id_wins() {
+    let (detector, _, ids) = contested_detector(ResolutionStrategy::LowestIOUIdWins, [300, 100, 200]);
+    let lowest = ids.iter().min_by_key(|id| *id.as_bytes()).unwrap();
+
+    let resolved = detector.resolve_all();
+
+    assert_eq!(resolved[0].winner.as_ref(), Some(lowest));
+    assert!(!resolved[0].losers.contains(lowest));
+}
+
+#[test]
+fn test_resolve_all_prefers_settled_iou() {
+    let (mut detector, _, ids) = contested_detector(ResolutionStrategy::PreferSettled, [100, 200, 300]);
+    assert_eq!(detector.resolve_all()[0].winner, Some(ids[0].clone()));
+
+    detector.mark_settled(ids[2].clone());
+
+    assert_eq!(detector.resolve_all()[0].winner, Some(ids[2].clone()));
+    assert_eq!(detector.losing_iou_ids().len(), 2);
+}
+
+#[test]
+fn test_resolve_all_manual_picks_no_winner() {
+    let (detector, utxo_id, _) = contested_detector(ResolutionStrategy::Manual, [100, 200, 300]);
+
+    let resolved = detector.resolve_all();
+
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].utxo_id, utxo_id);
+    assert!(resolved[0].winner.is_none());
+    assert!(detector.losing_iou_ids().is_empty());
+}
+
+#[test]
+fn test_resolve_all_ties_break_by_iou_id() {
+    let (detector, _, ids) = contested_detector(ResolutionStrategy::FirstTimestampWins, [100, 100, 100]);
+    let lowest = ids.iter().min_by_key(|id| *id.as_bytes()).unwrap();
+
+    assert_eq!(detector.resolve_all()[0].winner.as_ref(), Some(lowest));
+}
+
+#[test]
+fn test_resolve_all_skips_uncontested_utxos() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut detector = ConflictDetector::new();
+    let iou = create_test_iou(&alice, &bob, 100, 1);
+    detector
+        .register_claim(SpendingClaim::new(UTXOId::from_bytes([1u8; 32]), iou.id(), alice.public_key()))
+        .unwrap();
+
+    assert!(detector.resolve_all().is_empty());
+    assert!(detector.losing_iou_ids().is_empty());
+}
+
+#[test]
+fn test_resolve_all_independent_of_observation_order() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let claims: Vec<SpendingClaim> = (0..6u8)
+        .map(|i| {
+            let iou = create_test_iou(&alice, &bob, 100, i as u64);
+            let utxo_id = UTXOId::from_bytes([i % 2; 32]);
+            SpendingClaim::with_timestamp(utxo_id, iou.id(), alice.public_key(), 1000 - (i as u64 % 3))
+        })
+        .collect();
+
+    for strategy in [
+        ResolutionStrategy::FirstTimestampWins,
+        ResolutionStrategy::LowestIOUIdWins,
+        ResolutionStrategy::PreferSettled,
+    ] {
+        let mut forward = ConflictDetector::with_strategy(strategy);
+        for claim in &claims {
+            let _ = forward.register_claim(claim.clone());
+        }
+
+        // Node B hears half the claims itself and the rest by gossip, in reverse
+        let mut gossiped = ConflictDetector::new();
+        for claim in claims.iter().step_by(2) {
+            let _ = gossiped.register_claim(claim.clone());
+        }
+        let mut backward = ConflictDetector::with_strategy(strategy);
+        for claim in claims.iter().rev().step_by(2) {
+            let _ = backward.register_claim(claim.clone());
+        }
+        backward.merge(&gossiped);
+
+        assert_eq!(forward.resolve_all(), backward.resolve_all());
+        assert_eq!(forward.losing_iou_ids(), backward.losing_iou_ids());
+    }
+}
+
+#[test]
+fn test_merge_keeps_earliest_claim_timestamp() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let utxo_id = UTXOId::from_bytes([1u8; 32]);
+    let early = create_test_iou(&alice, &bob, 100, 1);
+    let late = create_test_iou(&alice, &bob, 100, 2);
+
+    // Each node saw the other's IOU first
+    let mut node_a = ConflictDetector::new();
+    node_a.register_claim(SpendingClaim::with_timestamp(utxo_id.clone(), early.id(), alice.public_key(), 100)).unwrap();
+    let _ = node_a.register_claim(SpendingClaim::with_timestamp(utxo_id.clone(), late.id(), alice.public_key(), 500));
+    let mut node_b = ConflictDetector::new();
+    node_b.register_claim(SpendingClaim::with_timestamp(utxo_id.clone(), late.id(), alice.public_key(), 200)).unwrap();
+    let _ = node_b.register_claim(SpendingClaim::with_timestamp(utxo_id, early.id(), alice.public_key(), 600));
+
+    let a_view = node_a.clone();
+    node_a.merge(&node_b);
+    node_b.merge(&a_view);
+
+    assert_eq!(node_a.resolve_all(), node_b.resolve_all());
+    assert_eq!(node_a.resolve_all()[0].winner, Some(early.id()));
+}
+
+#[test]
+fn test_merge_shares_settled_ious() {
+    let (mut node_a, _, ids) = contested_detector(ResolutionStrategy::PreferSettled, [100, 200, 300]);
+    let mut node_b = node_a.clone();
+    node_b.mark_settled(ids[1].clone());
+
+    node_a.merge(&node_b);
+
+    assert!(node_a.is_settled(&ids[1]));
+    assert_eq!(node_a.resolve_all()[0].winner, Some(ids[1].clone()));
+}
+
 // ============================================================================
 // CONFLICT TYPES
 // ============================================================================
@@ -405,6 +800,31 @@ fn test_conflict_type_double_spend() {
     }
 }
 
+#[test]
+fn test_double_spend_error_carries_both_claims() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+    let mut detector = ConflictDetector::new();
+    let utxo_id = UTXOId::from_bytes([2u8; 32]);
+
+    let iou1 = create_test_iou(&alice, &bob, 100, 1);
+    let iou2 = create_test_iou(&alice, &charlie, 100, 2);
+    detector
+        .register_claim(SpendingClaim::new(utxo_id.clone(), iou1.id(), alice.public_key()))
+        .unwrap();
+    let result = detector.register_claim(SpendingClaim::new(utxo_id.clone(), iou2.id(), alice.public_key()));
+
+    match result {
+        Err(ConflictError::DoubleSpend { utxo_id: conflicted, first_claim, second_claim, .. }) => {
+            assert_eq!(conflicted, utxo_id);
+            assert_eq!(first_claim.spending_iou_id(), &iou1.id());
+            assert_eq!(second_claim.spending_iou_id(), &iou2.id());
+        }
+        other => panic!("Expected DoubleSpend error, got {:?}", other),
+    }
+}
+
 // ============================================================================
 // SERIALIZATION
 // ============================================================================
@@ -431,6 +851,18 @@ fn test_conflict_detector_serialization() {
     assert_eq!(restored.claim_count(), 1);
 }
 
+#[test]
+fn test_conflict_detector_serialization_keeps_settled_not_strategy() {
+    let (mut detector, _, ids) = contested_detector(ResolutionStrategy::Manual, [100, 200, 300]);
+    detector.mark_settled(ids[2].clone());
+
+    let restored = ConflictDetector::from_bytes(&detector.to_bytes()).unwrap();
+
+    assert!(restored.is_settled(&ids[2]));
+    assert_eq!(restored.strategy(), ResolutionStrategy::FirstTimestampWins);
+    assert_eq!(restored.conflict_count(), 2);
+}
+
 #[test]
 fn test_spending_claim_serialization() {
     let alice = Keypair::generate();
@@ -3,7 +3,7 @@
 
 use p2pmesh::identity::{Did, Keypair};
 use p2pmesh::iou::{IOUBuilder, SignedIOU};
-use p2pmesh::ledger::{GSet, IOUEntry, MergeResult};
+use p2pmesh::ledger::{GSet, IOUEntry};
 
 // ============================================================================
 // G-SET BASIC OPERATIONS
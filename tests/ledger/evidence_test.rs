@@ -0,0 +1,225 @@
+// Conflict Evidence Tests
+// Tests exporting signed double-spend evidence and verifying it independently
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{
+    nonce_slot, verify_evidence, ConflictDetector, ConflictError, ConflictEvidence, IOUEntry,
+    SpendingClaim,
+};
+use p2pmesh::vault::UTXOId;
+
+fn iou(sender: &Keypair, nonce: u64, amount: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(amount)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+fn entry(sender: &Keypair, nonce: u64, amount: u64, received_at: u64) -> IOUEntry {
+    IOUEntry::with_timestamp(iou(sender, nonce, amount), sender.public_key(), received_at)
+}
+
+/// A detector holding two IOUs from `alice` that reuse nonce 7
+fn nonce_reuse(alice: &Keypair) -> (ConflictDetector, IOUEntry, IOUEntry) {
+    let first = entry(alice, 7, 100, 1_000);
+    let second = entry(alice, 7, 250, 2_000);
+    let mut detector = ConflictDetector::new();
+    // Registered out of order; evidence still lists the first-seen IOU first
+    detector.register_entry(&second).unwrap();
+    detector.register_entry(&first).unwrap_err();
+    (detector, first, second)
+}
+
+// ============================================================================
+// EXPORT
+// ============================================================================
+
+#[test]
+fn test_export_nonce_reuse_orders_by_first_seen() {
+    let alice = Keypair::generate();
+    let detector_key = Keypair::generate();
+    let (detector, first, second) = nonce_reuse(&alice);
+    let slot = nonce_slot(&Did::from_public_key(&alice.public_key()), 7);
+
+    let evidence = detector.export_evidence(&slot, &detector_key).unwrap();
+
+    assert_eq!(evidence.first().id(), first.id());
+    assert_eq!(evidence.second().id(), second.id());
+    assert_eq!((evidence.first_seen(), evidence.second_seen()), (1_000, 2_000));
+    assert_eq!(evidence.detector(), &detector_key.public_key());
+    assert!(evidence.is_nonce_reuse());
+    assert!(verify_evidence(&evidence, &alice.public_key()).is_ok());
+}
+
+#[test]
+fn test_export_utxo_conflict() {
+    let alice = Keypair::generate();
+    let utxo_id = UTXOId::from_bytes([9u8; 32]);
+    let (a, b) = (iou(&alice, 1, 100), iou(&alice, 2, 100));
+    let mut detector = ConflictDetector::new();
+    let claim = |signed: &SignedIOU, ts| {
+        SpendingClaim::with_timestamp(utxo_id.clone(), signed.id(), alice.public_key(), ts)
+    };
+    detector.register_signed_claim(claim(&a, 10), a.clone()).unwrap();
+    detector.register_signed_claim(claim(&b, 20), b.clone()).unwrap_err();
+
+    let evidence = detector.export_evidence(&utxo_id, &Keypair::generate()).unwrap();
+
+    assert_eq!(evidence.utxo_id(), &utxo_id);
+    assert!(!evidence.is_nonce_reuse());
+    assert!(verify_evidence(&evidence, &alice.public_key()).is_ok());
+}
+
+#[test]
+fn test_export_without_conflict_or_ious_fails() {
+    let alice = Keypair::generate();
+    let utxo_id = UTXOId::from_bytes([9u8; 32]);
+    let mut detector = ConflictDetector::new();
+    let signer = Keypair::generate();
+
+    assert!(matches!(
+        detector.export_evidence(&utxo_id, &signer),
+        Err(ConflictError::NoConflict(_))
+    ));
+
+    // Claims registered without their IOUs can't back evidence
+    for nonce in 1..=2 {
+        let claim = SpendingClaim::new(utxo_id.clone(), iou(&alice, nonce, 5).id(), alice.public_key());
+        let _ = detector.register_claim(claim);
+    }
+    assert!(matches!(
+        detector.export_evidence(&utxo_id, &signer),
+        Err(ConflictError::MissingIOU(_))
+    ));
+    assert!(detector.export_all_evidence(&signer).is_empty());
+}
+
+#[test]
+fn test_merged_detector_carries_ious() {
+    let alice = Keypair::generate();
+    let (a, b) = (entry(&alice, 3, 10, 1), entry(&alice, 3, 20, 2));
+    let mut left = ConflictDetector::new();
+    let mut right = ConflictDetector::new();
+    left.register_entry(&a).unwrap();
+    right.register_entry(&b).unwrap();
+
+    left.merge(&right);
+
+    assert_eq!(left.export_all_evidence(&Keypair::generate()).len(), 1);
+}
+
+// ============================================================================
+// VERIFICATION
+// ============================================================================
+
+#[test]
+fn test_verify_rejects_wrong_sender() {
+    let alice = Keypair::generate();
+    let (detector, _, _) = nonce_reuse(&alice);
+    let evidence = detector.export_all_evidence(&Keypair::generate()).remove(0);
+
+    let result = verify_evidence(&evidence, &Keypair::generate().public_key());
+
+    assert!(matches!(result, Err(ConflictError::InvalidEvidence(_))));
+}
+
+#[test]
+fn test_verify_rejects_tampered_detector_signature() {
+    let alice = Keypair::generate();
+    let (detector, _, _) = nonce_reuse(&alice);
+    let mut bytes = detector.export_all_evidence(&Keypair::generate()).remove(0).to_bytes();
+
+    // The detector signature is the last field
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x01;
+    let evidence = ConflictEvidence::from_bytes(&bytes).unwrap();
+
+    assert!(matches!(
+        verify_evidence(&evidence, &alice.public_key()),
+        Err(ConflictError::InvalidEvidence("detector signature does not verify"))
+    ));
+}
+
+#[test]
+fn test_verify_rejects_forged_nonce_slot() {
+    let alice = Keypair::generate();
+    let (a, b) = (iou(&alice, 1, 10), iou(&alice, 2, 10));
+    let slot = nonce_slot(&Did::from_public_key(&alice.public_key()), 1);
+
+    // Even a correctly signed detector can't claim different nonces share a slot
+    let evidence = ConflictEvidence::sign(slot, a, 1, b, 2, &Keypair::generate());
+
+    assert!(matches!(
+        verify_evidence(&evidence, &alice.public_key()),
+        Err(ConflictError::InvalidEvidence(_))
+    ));
+}
+
+#[test]
+fn test_verify_rejects_same_iou_twice() {
+    let alice = Keypair::generate();
+    let a = iou(&alice, 1, 10);
+    let evidence = ConflictEvidence::sign(UTXOId::from_bytes([1u8; 32]), a.clone(), 1, a, 2, &alice);
+
+    assert!(verify_evidence(&evidence, &alice.public_key()).is_err());
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_evidence_round_trips_and_verifies() {
+    let alice = Keypair::generate();
+    let (detector, first, _) = nonce_reuse(&alice);
+    let evidence = detector.export_all_evidence(&Keypair::generate()).remove(0);
+
+    let restored = ConflictEvidence::from_bytes(&evidence.to_bytes()).unwrap();
+
+    assert_eq!(restored.first().id(), first.id());
+    assert!(verify_evidence(&restored, &alice.public_key()).is_ok());
+}
+
+#[test]
+fn test_evidence_json_lists_both_ious() {
+    let alice = Keypair::generate();
+    let (detector, first, second) = nonce_reuse(&alice);
+    let evidence = detector.export_all_evidence(&Keypair::generate()).remove(0);
+
+    let json = evidence.to_json();
+
+    assert!(json.starts_with('{') && json.ends_with('}'));
+    assert!(json.contains(r#""nonceReuse":true"#));
+    assert!(json.contains(&hex::encode(first.id().as_bytes())));
+    assert!(json.contains(&hex::encode(second.id().as_bytes())));
+    assert!(json.contains(r#""firstSeen":1000"#));
+}
+
+#[test]
+fn test_detector_keeps_ious_across_bytes() {
+    let alice = Keypair::generate();
+    let (detector, _, _) = nonce_reuse(&alice);
+
+    let restored = ConflictDetector::from_bytes(&detector.to_bytes()).unwrap();
+
+    assert_eq!(restored.export_all_evidence(&Keypair::generate()).len(), 1);
+}
+
+#[test]
+fn test_detector_from_before_evidence_still_decodes() {
+    let alice = Keypair::generate();
+    let utxo_id = UTXOId::from_bytes([4u8; 32]);
+    let mut detector = ConflictDetector::new();
+    detector.register_claim(SpendingClaim::new(utxo_id.clone(), iou(&alice, 1, 5).id(), alice.public_key())).unwrap();
+
+    // Drop the empty IOU map (one byte)
+    let mut bytes = detector.to_bytes();
+    bytes.pop();
+
+    let restored = ConflictDetector::from_bytes(&bytes).unwrap();
+    assert_eq!(restored.claim_count(), 1);
+}
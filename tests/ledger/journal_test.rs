@@ -0,0 +1,182 @@
+// Journal Tests
+// Tests persisting a mesh state as incremental deltas from save points
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedCancellation, SignedIOU};
+use p2pmesh::ledger::{MeshState, MeshStateError, NodeId, SignedCheckpoint, StateDelta};
+
+fn iou(sender: &Keypair, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(10 + nonce)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+fn add(state: &mut MeshState, sender: &Keypair, nonce: u64) -> SignedIOU {
+    let iou = iou(sender, nonce);
+    state.add_iou(iou.clone(), &sender.public_key()).unwrap();
+    iou
+}
+
+/// Everything persisted about a state, in a comparable form
+fn shape(state: &MeshState) -> (Vec<String>, [u8; 32], u64, usize, usize, usize) {
+    let (entries, _) = state.entries_since(0, usize::MAX);
+    (
+        entries.iter().map(|e| hex::encode(e.id().as_bytes())).collect(),
+        state.merkle_root(),
+        state.version(),
+        state.iou_count(),
+        state.cancellation_count(),
+        state.checkpoint_count(),
+    )
+}
+
+// ============================================================================
+// REPLAY
+// ============================================================================
+
+#[test]
+fn test_replayed_deltas_rebuild_the_state() {
+    let alice = Keypair::generate();
+    let gateway = Keypair::generate();
+    let mut state = MeshState::new(NodeId::generate());
+    let first = add(&mut state, &alice, 1);
+    let second = add(&mut state, &alice, 2);
+
+    // The base write, then a delta after each round of changes
+    let base = state.to_bytes();
+    let mut point = state.save_point();
+    let mut deltas: Vec<Vec<u8>> = Vec::new();
+    let mut persist = |state: &MeshState, point: &mut _| {
+        deltas.push(state.changes_since(point).to_bytes());
+        *point = state.save_point();
+    };
+
+    add(&mut state, &alice, 3);
+    state.mark_settled(&first.id());
+    persist(&state, &mut point);
+
+    let cancelled = iou(&alice, 4);
+    state.add_cancellation(SignedCancellation::sign(&alice, cancelled.id())).unwrap();
+    let mut peer = MeshState::new(NodeId::generate());
+    add(&mut peer, &Keypair::generate(), 1);
+    state.merge(&peer);
+    persist(&state, &mut point);
+
+    state.apply_checkpoint(&SignedCheckpoint::sign(&gateway, vec![first.id(), second.id()])).unwrap();
+    persist(&state, &mut point);
+
+    let mut restored = MeshState::from_bytes(&base).unwrap();
+    for bytes in &deltas {
+        restored.apply_state_delta(StateDelta::from_bytes(bytes).unwrap()).unwrap();
+    }
+
+    assert_eq!(shape(&restored), shape(&state));
+    assert_eq!(restored.clock(), state.clock());
+    assert!(restored.is_pruned(&first.id()));
+    assert!(!restored.is_settled(&first.id()));
+    assert!(restored.is_cancelled(&cancelled.id()));
+}
+
+#[test]
+fn test_delta_carries_only_changes() {
+    let alice = Keypair::generate();
+    let mut state = MeshState::new(NodeId::generate());
+    for nonce in 0..50 {
+        add(&mut state, &alice, nonce);
+    }
+    let point = state.save_point();
+    add(&mut state, &alice, 50);
+
+    let delta = state.changes_since(&point);
+
+    assert_eq!(delta.entries().len(), 1);
+    assert!(delta.to_bytes().len() * 10 < state.to_bytes().len());
+    assert_eq!(delta.root(), state.merkle_root());
+}
+
+#[test]
+fn test_delta_reports_frozen_entries_as_removed() {
+    let alice = Keypair::generate();
+    let bob = Did::from_public_key(&Keypair::generate().public_key());
+    let mut state = MeshState::new(NodeId::generate());
+    for day in 0..4u64 {
+        let iou = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(bob.clone())
+            .amount(5)
+            .nonce(day)
+            .timestamp(1_700_000_000 + day * 86_400)
+            .build()
+            .unwrap();
+        state.add_iou(iou, &alice.public_key()).unwrap();
+    }
+    let base = state.clone();
+    let point = state.save_point();
+
+    state.snapshot(1_700_000_000 + 2 * 86_400);
+    let delta = state.changes_since(&point);
+
+    assert_eq!(delta.removed().len(), 2);
+    let mut restored = base;
+    restored.apply_state_delta(delta).unwrap();
+    assert_eq!(restored.merkle_root(), state.merkle_root());
+}
+
+// ============================================================================
+// EDGE CASES
+// ============================================================================
+
+#[test]
+fn test_unchanged_state_gives_empty_delta() {
+    let mut state = MeshState::new(NodeId::generate());
+    add(&mut state, &Keypair::generate(), 1);
+
+    let delta = state.changes_since(&state.save_point());
+
+    assert!(delta.is_empty());
+}
+
+#[test]
+fn test_settling_alone_is_a_change() {
+    let mut state = MeshState::new(NodeId::generate());
+    let iou = add(&mut state, &Keypair::generate(), 1);
+    let point = state.save_point();
+
+    state.mark_settled(&iou.id());
+
+    assert!(!state.changes_since(&point).is_empty());
+}
+
+#[test]
+fn test_out_of_order_delta_is_refused() {
+    let alice = Keypair::generate();
+    let mut state = MeshState::new(NodeId::generate());
+    let base = state.clone();
+    let first_point = state.save_point();
+    add(&mut state, &alice, 1);
+    let first = state.changes_since(&first_point);
+    let second_point = state.save_point();
+    add(&mut state, &alice, 2);
+    let second = state.changes_since(&second_point);
+
+    let mut restored = base;
+    let result = restored.apply_state_delta(second.clone());
+
+    assert!(matches!(result, Err(MeshStateError::DeltaBaseMismatch { expected: 1, actual: 0 })));
+    assert!(restored.is_empty());
+    restored.apply_state_delta(first).unwrap();
+    restored.apply_state_delta(second).unwrap();
+    assert_eq!(restored.merkle_root(), state.merkle_root());
+}
+
+#[test]
+fn test_delta_from_garbage_fails() {
+    assert!(matches!(
+        StateDelta::from_bytes(&[0xff, 0xff, 0xff]),
+        Err(MeshStateError::DeserializationFailed)
+    ));
+}
@@ -0,0 +1,255 @@
+// Merge Verification Tests
+// Tests that merges re-verify incoming entries instead of trusting the peer
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{
+    ConflictDetector, ConflictPolicy, EntryRejection, MergePolicy, MeshState, NodeId,
+};
+
+fn iou(sender: &Keypair, recipient: &Keypair, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(10)
+        .nonce(nonce)
+        .timestamp(1_700_000_000 + nonce)
+        .build()
+        .unwrap()
+}
+
+/// A state holding one IOU from `sender` per nonce
+fn state_with(sender: &Keypair, recipient: &Keypair, nonces: std::ops::Range<u64>) -> MeshState {
+    let mut state = MeshState::new(NodeId::generate());
+    for nonce in nonces {
+        state.add_iou(iou(sender, recipient, nonce), &sender.public_key()).unwrap();
+    }
+    state
+}
+
+/// Rewrite every occurrence of `find` in the serialized state, as a malicious peer could
+fn forge(state: &MeshState, find: &[u8], replace: &[u8]) -> MeshState {
+    assert_eq!(find.len(), replace.len());
+    let mut bytes = state.to_bytes();
+    let mut found = false;
+    let mut i = 0;
+    while i + find.len() <= bytes.len() {
+        if &bytes[i..i + find.len()] == find {
+            bytes[i..i + find.len()].copy_from_slice(replace);
+            found = true;
+            i += find.len();
+        } else {
+            i += 1;
+        }
+    }
+    assert!(found, "pattern not in serialized state");
+    MeshState::from_bytes(&bytes).unwrap()
+}
+
+/// `state` with the signature of `target` corrupted
+fn with_bad_signature(state: &MeshState, target: &SignedIOU) -> MeshState {
+    let signature = target.signature().as_bytes().to_vec();
+    let mut corrupted = signature.clone();
+    corrupted[0] ^= 0x01;
+    forge(state, &signature, &corrupted)
+}
+
+// ============================================================================
+// VERIFY NEW (DEFAULT)
+// ============================================================================
+
+#[test]
+fn test_default_policy_is_verify_new() {
+    let state = MeshState::new(NodeId::generate());
+
+    assert_eq!(state.merge_policy(), MergePolicy::VerifyNew);
+}
+
+#[test]
+fn test_valid_entries_merge_and_are_verified() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let remote = state_with(&alice, &bob, 0..4);
+    let mut local = MeshState::new(NodeId::generate());
+
+    let result = local.merge(&remote);
+
+    assert_eq!(result.new_entries, 4);
+    assert_eq!(result.verified_entries, 4);
+    assert_eq!(result.rejected_entries, 0);
+    assert!(result.rejected.is_empty());
+    assert_eq!(local.iou_count(), 4);
+}
+
+#[test]
+fn test_forged_signature_is_rejected() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let honest = state_with(&alice, &bob, 0..3);
+    let target = honest.all_entries().first().unwrap().iou().clone();
+    let remote = with_bad_signature(&honest, &target);
+    let mut local = MeshState::new(NodeId::generate());
+
+    let result = local.merge(&remote);
+
+    assert_eq!(result.new_entries, 2);
+    assert_eq!(result.rejected_entries, 1);
+    assert_eq!(result.rejected[0].iou_id, target.id());
+    assert_eq!(result.rejected[0].reason, EntryRejection::InvalidSignature);
+    assert!(!local.has_iou(&target.id()));
+    assert_eq!(local.iou_count(), 2);
+}
+
+#[test]
+fn test_sender_key_mismatch_is_rejected() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mallory = Keypair::generate();
+    let honest = state_with(&alice, &bob, 0..2);
+
+    // Attach Mallory's key to Alice's IOUs
+    let remote = forge(&honest, alice.public_key().as_bytes(), mallory.public_key().as_bytes());
+    let mut local = MeshState::new(NodeId::generate());
+
+    let result = local.merge(&remote);
+
+    assert_eq!(result.new_entries, 0);
+    assert_eq!(result.rejected_entries, 2);
+    assert!(result.rejected.iter().all(|r| r.reason == EntryRejection::SenderKeyMismatch));
+    assert!(local.is_empty());
+}
+
+#[test]
+fn test_rejected_entries_are_in_id_order() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mallory = Keypair::generate();
+    let honest = state_with(&alice, &bob, 0..5);
+    let remote = forge(&honest, alice.public_key().as_bytes(), mallory.public_key().as_bytes());
+
+    let result = MeshState::new(NodeId::generate()).merge(&remote);
+
+    let ids: Vec<_> = result.rejected.iter().map(|r| *r.iou_id.as_bytes()).collect();
+    let mut sorted = ids.clone();
+    sorted.sort();
+    assert_eq!(ids, sorted);
+}
+
+#[test]
+fn test_verify_new_skips_entries_already_held() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut local = state_with(&alice, &bob, 0..6);
+    let remote = state_with(&alice, &bob, 0..8);
+
+    let result = local.merge(&remote);
+
+    assert_eq!(result.new_entries, 2);
+    assert_eq!(result.verified_entries, 2);
+}
+
+#[test]
+fn test_verify_new_cost_scales_with_delta_not_total() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+
+    // Same 3-entry delta on top of small and large shared histories
+    let verified: Vec<usize> = [5u64, 60]
+        .iter()
+        .map(|&shared| {
+            let mut local = state_with(&alice, &bob, 0..shared);
+            let remote = state_with(&alice, &bob, 0..shared + 3);
+            local.merge(&remote).verified_entries
+        })
+        .collect();
+
+    assert_eq!(verified, vec![3, 3]);
+}
+
+// ============================================================================
+// VERIFY ALL AND TRUST
+// ============================================================================
+
+#[test]
+fn test_verify_all_checks_entries_already_held() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut local = state_with(&alice, &bob, 0..3);
+    local.set_merge_policy(MergePolicy::VerifyAll);
+    let remote = state_with(&alice, &bob, 0..4);
+
+    let result = local.merge(&remote);
+
+    assert_eq!(result.new_entries, 1);
+    assert_eq!(result.verified_entries, 4);
+}
+
+#[test]
+fn test_verify_all_reports_forged_copy_of_held_entry() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut local = state_with(&alice, &bob, 0..2);
+    let target = local.all_entries().first().unwrap().iou().clone();
+    let remote = with_bad_signature(&local, &target);
+
+    local.set_merge_policy(MergePolicy::VerifyNew);
+    assert_eq!(local.merge(&remote).rejected_entries, 0);
+
+    local.set_merge_policy(MergePolicy::VerifyAll);
+    let result = local.merge(&remote);
+
+    assert_eq!(result.rejected_entries, 1);
+    assert_eq!(result.rejected[0].iou_id, target.id());
+    // Our own, valid copy stays
+    assert!(local.has_iou(&target.id()));
+    assert!(local.get_iou(&target.id()).unwrap().verify());
+}
+
+#[test]
+fn test_trust_accepts_unverified_entries() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let honest = state_with(&alice, &bob, 0..2);
+    let target = honest.all_entries().first().unwrap().iou().clone();
+    let remote = with_bad_signature(&honest, &target);
+    let mut local = MeshState::new(NodeId::generate());
+    local.set_merge_policy(MergePolicy::Trust);
+
+    let result = local.merge(&remote);
+
+    assert_eq!(result.new_entries, 2);
+    assert_eq!(result.verified_entries, 0);
+    assert_eq!(result.rejected_entries, 0);
+    assert!(local.has_iou(&target.id()));
+}
+
+#[test]
+fn test_merge_policy_not_persisted() {
+    let mut state = MeshState::new(NodeId::generate());
+    state.set_merge_policy(MergePolicy::Trust);
+
+    let restored = MeshState::from_bytes(&state.to_bytes()).unwrap();
+
+    assert_eq!(restored.merge_policy(), MergePolicy::VerifyNew);
+}
+
+// ============================================================================
+// POLICY MERGES
+// ============================================================================
+
+#[test]
+fn test_merge_with_policy_rejects_forged_entries() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let honest = state_with(&alice, &bob, 0..3);
+    let target = honest.all_entries().first().unwrap().iou().clone();
+    let remote = with_bad_signature(&honest, &target);
+    let mut local = MeshState::new(NodeId::generate());
+
+    let result = local.merge_with_policy(&remote, &ConflictDetector::new(), ConflictPolicy::FirstSeenWins);
+
+    assert_eq!(result.new_entries, 2);
+    assert_eq!(result.rejected.len(), 1);
+    assert_eq!(result.rejected[0].reason, EntryRejection::InvalidSignature);
+    assert!(!local.has_iou(&target.id()));
+}
@@ -1,3 +1,12 @@
+mod bloom_test;
+mod checkpoint_test;
 mod conflict_test;
 mod crdt_test;
 mod state_test;
+mod summary_test;
+mod query_test;
+mod merge_verification_test;
+mod version_vector_test;
+mod snapshot_test;
+mod evidence_test;
+mod journal_test;
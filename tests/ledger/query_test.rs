@@ -0,0 +1,407 @@
+// Mesh Query Tests
+// Tests for filtered IOU queries and the indexes backing them
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, IOUId, SignedIOU};
+use p2pmesh::ledger::{IOUEntry, MeshQuery, MeshState, NodeId, PartyTotals, SignedCheckpoint};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn iou_at(sender: &Keypair, recipient: &Keypair, amount: u64, timestamp: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(did(recipient))
+        .amount(amount)
+        .nonce(timestamp)
+        .timestamp(timestamp)
+        .build()
+        .unwrap()
+}
+
+/// Alice, Bob and Carol paying each other at timestamps 1000..1012
+struct Fixture {
+    alice: Keypair,
+    bob: Keypair,
+    carol: Keypair,
+    ious: Vec<(SignedIOU, Keypair)>,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let alice = Keypair::generate();
+        let bob = Keypair::generate();
+        let carol = Keypair::generate();
+        let mut ious = Vec::new();
+        for i in 0..12u64 {
+            let (sender, recipient) = match i % 3 {
+                0 => (&alice, &bob),
+                1 => (&bob, &carol),
+                _ => (&alice, &carol),
+            };
+            ious.push((iou_at(sender, recipient, 10 * (i + 1), 1000 + i), sender.clone()));
+        }
+        Self { alice, bob, carol, ious }
+    }
+
+    fn state(&self) -> MeshState {
+        self.state_with(|_| true)
+    }
+
+    fn state_with(&self, keep: impl Fn(usize) -> bool) -> MeshState {
+        let mut state = MeshState::new(NodeId::generate());
+        for (i, (iou, sender)) in self.ious.iter().enumerate() {
+            if keep(i) {
+                state.add_iou(iou.clone(), &sender.public_key()).unwrap();
+            }
+        }
+        state
+    }
+}
+
+fn ids<'a>(entries: impl IntoIterator<Item = &'a IOUEntry>) -> Vec<IOUId> {
+    entries.into_iter().map(|entry| entry.id()).collect()
+}
+
+/// What a query should return, found by scanning every entry
+fn scan(state: &MeshState, keep: impl Fn(&IOUEntry) -> bool) -> Vec<IOUId> {
+    let mut entries: Vec<&IOUEntry> = state.all_entries().into_iter().filter(|e| keep(e)).collect();
+    entries.sort_by_key(|e| (e.iou().iou().timestamp(), *e.id().as_bytes()));
+    ids(entries)
+}
+
+/// Run a spread of queries against `state` and check each against a full scan
+fn assert_queries_match_scan(state: &MeshState, fixture: &Fixture) {
+    for party in [&fixture.alice, &fixture.bob, &fixture.carol] {
+        let party = did(party);
+        assert_eq!(
+            ids(state.query().sender(party.clone())),
+            scan(state, |e| e.iou().iou().sender() == &party)
+        );
+        assert_eq!(
+            ids(state.query().recipient(party.clone())),
+            scan(state, |e| e.iou().iou().recipients().contains(&&party))
+        );
+        assert_eq!(
+            ids(state.query().sender(party.clone()).after(1003).before(1009)),
+            scan(state, |e| {
+                let iou = e.iou().iou();
+                iou.sender() == &party && (1003..1009).contains(&iou.timestamp())
+            })
+        );
+    }
+    assert_eq!(
+        ids(state.query().after(1004).before(1010)),
+        scan(state, |e| (1004..1010).contains(&e.iou().iou().timestamp()))
+    );
+    assert_eq!(
+        ids(state.query().min_amount(60)),
+        scan(state, |e| e.iou().iou().amount() >= 60)
+    );
+    assert_eq!(
+        ids(state.query().unsettled_only()),
+        scan(state, |e| !state.is_settled(&e.id()))
+    );
+}
+
+// ============================================================================
+// FILTERS
+// ============================================================================
+
+#[test]
+fn test_query_without_filters_returns_everything_oldest_first() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+
+    let expected: Vec<IOUId> = fixture.ious.iter().map(|(iou, _)| iou.id()).collect();
+    assert_eq!(ids(state.query()), expected);
+}
+
+#[test]
+fn test_query_by_sender() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+
+    let entries: Vec<&IOUEntry> = state.query().sender(did(&fixture.bob)).into_iter().collect();
+
+    assert_eq!(entries.len(), 4);
+    assert!(entries.iter().all(|e| e.iou().iou().sender() == &did(&fixture.bob)));
+}
+
+#[test]
+fn test_query_by_recipient() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+
+    assert_eq!(state.query().recipient(did(&fixture.carol)).into_iter().count(), 8);
+    assert_eq!(state.query().recipient(did(&fixture.alice)).into_iter().count(), 0);
+}
+
+#[test]
+fn test_query_by_sender_and_recipient() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+
+    let entries: Vec<&IOUEntry> = state
+        .query()
+        .sender(did(&fixture.alice))
+        .recipient(did(&fixture.carol))
+        .into_iter()
+        .collect();
+
+    assert_eq!(entries.len(), 4);
+    assert!(entries.iter().all(|e| (e.iou().iou().timestamp() - 1000) % 3 == 2));
+}
+
+#[test]
+fn test_query_time_range_is_half_open() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+
+    let timestamps: Vec<u64> = state
+        .query()
+        .after(1002)
+        .before(1005)
+        .into_iter()
+        .map(|e| e.iou().iou().timestamp())
+        .collect();
+
+    assert_eq!(timestamps, vec![1002, 1003, 1004]);
+    assert_eq!(state.query().after(1011).into_iter().count(), 1);
+    assert_eq!(state.query().before(1000).into_iter().count(), 0);
+}
+
+#[test]
+fn test_query_empty_time_range() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+
+    assert_eq!(state.query().after(1005).before(1005).into_iter().count(), 0);
+    assert_eq!(state.query().after(1008).before(1002).into_iter().count(), 0);
+}
+
+#[test]
+fn test_query_min_amount() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+
+    let amounts: Vec<u64> = state.query().min_amount(100).into_iter().map(|e| e.iou().iou().amount()).collect();
+
+    assert_eq!(amounts, vec![100, 110, 120]);
+}
+
+#[test]
+fn test_query_min_amount_uses_recipient_output() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let carol = Keypair::generate();
+    let split = IOUBuilder::new()
+        .sender(&alice)
+        .add_output(did(&bob), 20)
+        .add_output(did(&carol), 80)
+        .build()
+        .unwrap();
+    let mut state = MeshState::new(NodeId::generate());
+    state.add_iou(split, &alice.public_key()).unwrap();
+
+    assert_eq!(state.query().min_amount(50).into_iter().count(), 1);
+    assert_eq!(state.query().recipient(did(&bob)).min_amount(50).into_iter().count(), 0);
+    assert_eq!(state.query().recipient(did(&carol)).min_amount(50).into_iter().count(), 1);
+}
+
+#[test]
+fn test_query_unsettled_only() {
+    let fixture = Fixture::new();
+    let mut state = fixture.state();
+    let settled = fixture.ious[0].0.id();
+
+    assert!(state.mark_settled(&settled));
+    assert!(!state.mark_settled(&settled));
+
+    let unsettled = ids(state.query().sender(did(&fixture.alice)).unsettled_only());
+    assert_eq!(unsettled.len(), 7);
+    assert!(!unsettled.contains(&settled));
+    assert_eq!(state.query().sender(did(&fixture.alice)).into_iter().count(), 8);
+}
+
+#[test]
+fn test_query_combined_filters() {
+    let fixture = Fixture::new();
+    let mut state = fixture.state();
+    state.mark_settled(&fixture.ious[5].0.id());
+
+    let query: MeshQuery = state
+        .query()
+        .sender(did(&fixture.alice))
+        .after(1003)
+        .before(1011)
+        .min_amount(50)
+        .unsettled_only();
+
+    // Alice sent at 1003, 1005, 1006, 1008, 1009; 1005 is settled
+    let timestamps: Vec<u64> = query.into_iter().map(|e| e.iou().iou().timestamp()).collect();
+    assert_eq!(timestamps, vec![1006, 1008, 1009]);
+}
+
+#[test]
+fn test_query_unknown_party_is_empty() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+    let stranger = did(&Keypair::generate());
+
+    assert_eq!(state.query().sender(stranger.clone()).into_iter().count(), 0);
+    assert_eq!(state.query().recipient(stranger).into_iter().count(), 0);
+}
+
+// ============================================================================
+// INDEX CONSISTENCY
+// ============================================================================
+
+#[test]
+fn test_indexes_consistent_after_add() {
+    let fixture = Fixture::new();
+    let mut state = fixture.state();
+    state.mark_settled(&fixture.ious[3].0.id());
+
+    assert_queries_match_scan(&state, &fixture);
+}
+
+#[test]
+fn test_indexes_consistent_after_from_bytes() {
+    let fixture = Fixture::new();
+    let mut state = fixture.state();
+    state.mark_settled(&fixture.ious[3].0.id());
+
+    let restored = MeshState::from_bytes(&state.to_bytes()).unwrap();
+
+    assert!(restored.is_settled(&fixture.ious[3].0.id()));
+    assert_queries_match_scan(&restored, &fixture);
+    assert_eq!(ids(restored.query().after(1001)), ids(state.query().after(1001)));
+}
+
+#[test]
+fn test_indexes_consistent_after_merge() {
+    let fixture = Fixture::new();
+    let mut evens = fixture.state_with(|i| i % 2 == 0);
+    let odds = fixture.state_with(|i| i % 2 == 1);
+
+    evens.merge(&odds);
+
+    assert_eq!(evens.iou_count(), 12);
+    assert_queries_match_scan(&evens, &fixture);
+    assert_eq!(ids(evens.query()), ids(fixture.state().query()));
+}
+
+#[test]
+fn test_indexes_consistent_after_repeated_merges() {
+    let fixture = Fixture::new();
+    let mut node = fixture.state_with(|i| i < 4);
+    let middle = fixture.state_with(|i| (2..8).contains(&i));
+    let all = fixture.state();
+
+    node.merge(&middle);
+    node.merge(&middle);
+    node.merge(&all);
+
+    assert_eq!(node.iou_count(), 12);
+    assert_eq!(node.cursor(), 12);
+    assert_queries_match_scan(&node, &fixture);
+}
+
+#[test]
+fn test_indexes_consistent_after_merge_with_pruned_entries() {
+    let fixture = Fixture::new();
+    let gateway = Keypair::generate();
+    let all = fixture.state();
+    let mut node = fixture.state_with(|i| i < 6);
+    let pruned = vec![fixture.ious[1].0.id(), fixture.ious[9].0.id()];
+    node.apply_checkpoint(&SignedCheckpoint::sign(&gateway, pruned.clone())).unwrap();
+
+    let result = node.merge(&all);
+
+    assert_eq!(result.new_entries, 5);
+    assert_eq!(node.iou_count(), 10);
+    assert!(pruned.iter().all(|id| node.query().into_iter().all(|e| &e.id() != id)));
+    assert_queries_match_scan(&node, &fixture);
+    // IOUs learned in the merge are still reported to cursor-based sync
+    assert_eq!(node.entries_since(6, 100).0.len(), 5);
+}
+
+#[test]
+fn test_settled_marks_are_not_merged() {
+    let fixture = Fixture::new();
+    let mut node_a = fixture.state();
+    node_a.mark_settled(&fixture.ious[0].0.id());
+    let mut node_b = MeshState::new(NodeId::generate());
+
+    node_b.merge(&node_a);
+
+    assert!(!node_b.is_settled(&fixture.ious[0].0.id()));
+    assert_eq!(node_b.query().unsettled_only().into_iter().count(), 12);
+}
+
+#[test]
+fn test_checkpoint_clears_settled_mark() {
+    let fixture = Fixture::new();
+    let gateway = Keypair::generate();
+    let mut state = fixture.state();
+    let id = fixture.ious[0].0.id();
+    state.mark_settled(&id);
+
+    state.apply_checkpoint(&SignedCheckpoint::sign(&gateway, [id.clone()])).unwrap();
+
+    assert!(!state.is_settled(&id));
+    assert!(!state.mark_settled(&id));
+}
+
+// ============================================================================
+// PER-PARTY STATISTICS
+// ============================================================================
+
+#[test]
+fn test_statistics_party_totals() {
+    let fixture = Fixture::new();
+    let state = fixture.state();
+
+    let stats = state.statistics();
+
+    // Amounts are 10 * (i + 1): Alice->Bob at i % 3 == 0, Bob->Carol at 1, Alice->Carol at 2
+    let alice_to_bob: u64 = [10, 40, 70, 100].iter().sum();
+    let bob_to_carol: u64 = [20, 50, 80, 110].iter().sum();
+    let alice_to_carol: u64 = [30, 60, 90, 120].iter().sum();
+    assert_eq!(stats.party_totals.len(), 3);
+    assert_eq!(
+        stats.party_totals[&did(&fixture.alice)],
+        PartyTotals { sent: alice_to_bob + alice_to_carol, received: 0 }
+    );
+    assert_eq!(
+        stats.party_totals[&did(&fixture.bob)],
+        PartyTotals { sent: bob_to_carol, received: alice_to_bob }
+    );
+    assert_eq!(
+        stats.party_totals[&did(&fixture.carol)],
+        PartyTotals { sent: 0, received: bob_to_carol + alice_to_carol }
+    );
+}
+
+#[test]
+fn test_statistics_party_totals_split_multi_output() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let carol = Keypair::generate();
+    let split = IOUBuilder::new()
+        .sender(&alice)
+        .add_output(did(&bob), 20)
+        .add_output(did(&carol), 80)
+        .build()
+        .unwrap();
+    let mut state = MeshState::new(NodeId::generate());
+    state.add_iou(split, &alice.public_key()).unwrap();
+
+    let totals = state.statistics().party_totals;
+
+    assert_eq!(totals[&did(&alice)].sent, 100);
+    assert_eq!(totals[&did(&bob)].received, 20);
+    assert_eq!(totals[&did(&carol)].received, 80);
+}
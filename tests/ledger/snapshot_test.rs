@@ -0,0 +1,214 @@
+// Snapshot Tests
+// Tests freezing old entries into compressed, hash-chained segments
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{MeshState, MeshStateError, NodeId, SnapshotError, StateSnapshot};
+
+const DAY: u64 = 24 * 60 * 60;
+const START: u64 = 1_700_000_000;
+
+fn iou(sender: &Keypair, recipient: &Did, day: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(recipient.clone())
+        .amount(10 + day)
+        .nonce(day)
+        .timestamp(START + day * DAY)
+        .build()
+        .unwrap()
+}
+
+/// A state with one IOU per day for `days` days, all between the same two parties
+fn state_with_days(days: u64) -> (MeshState, Keypair, Did) {
+    let alice = Keypair::generate();
+    let bob = Did::from_public_key(&Keypair::generate().public_key());
+    let mut state = MeshState::new(NodeId::generate());
+    for day in 0..days {
+        state.add_iou(iou(&alice, &bob, day), &alice.public_key()).unwrap();
+    }
+    (state, alice, bob)
+}
+
+fn day(n: u64) -> u64 {
+    START + n * DAY
+}
+
+// ============================================================================
+// FREEZING
+// ============================================================================
+
+#[test]
+fn test_snapshot_freezes_entries_older_than_cutoff() {
+    let (mut state, _, _) = state_with_days(30);
+
+    let snapshot = state.snapshot(day(20));
+
+    assert_eq!(snapshot.segments().len(), 1);
+    assert_eq!(snapshot.entry_count(), 20);
+    assert_eq!(state.iou_count(), 10);
+    assert!(state.all_entries().iter().all(|e| e.iou().iou().timestamp() >= day(20)));
+    assert_eq!(state.frozen(), &snapshot);
+}
+
+#[test]
+fn test_snapshot_entries_round_trip() {
+    let (mut state, _, _) = state_with_days(12);
+    let mut expected: Vec<_> = state.all_entries().iter().map(|e| e.id()).collect();
+    expected.sort_by_key(|id| *id.as_bytes());
+
+    let snapshot = state.snapshot(day(12));
+    let mut frozen: Vec<_> = snapshot.entries().unwrap().iter().map(|e| e.id()).collect();
+    frozen.sort_by_key(|id| *id.as_bytes());
+
+    assert_eq!(frozen, expected);
+    assert!(state.is_empty());
+}
+
+#[test]
+fn test_snapshot_without_old_entries_adds_no_segment() {
+    let (mut state, _, _) = state_with_days(5);
+
+    let snapshot = state.snapshot(day(0));
+
+    assert!(snapshot.is_empty());
+    assert_eq!(state.iou_count(), 5);
+}
+
+#[test]
+fn test_segments_are_hash_chained() {
+    let (mut state, _, _) = state_with_days(30);
+
+    state.snapshot(day(10));
+    let snapshot = state.snapshot(day(20));
+
+    let segments = snapshot.segments();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].prev_hash(), &[0u8; 32]);
+    assert_eq!(segments[1].prev_hash(), segments[0].hash());
+    assert_eq!(snapshot.head_hash(), *segments[1].hash());
+    assert!(snapshot.verify_chain().is_ok());
+}
+
+// ============================================================================
+// RELOADING
+// ============================================================================
+
+#[test]
+fn test_from_snapshot_restores_live_and_frozen() {
+    let (mut state, _, _) = state_with_days(30);
+    let snapshot = state.snapshot(day(20));
+    let live_bytes = state.to_bytes();
+
+    let snapshot = StateSnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+    let restored = MeshState::from_snapshot(snapshot.clone(), &live_bytes).unwrap();
+
+    assert_eq!(restored.iou_count(), 10);
+    assert_eq!(restored.frozen(), &snapshot);
+    assert_eq!(restored.compaction_stats().frozen_entries, 20);
+}
+
+/// Zero the `nth` occurrence of `find` in the serialized snapshot
+fn tamper(snapshot: &StateSnapshot, find: &[u8; 32], nth: usize) -> StateSnapshot {
+    let mut bytes = snapshot.to_bytes();
+    let at = (0..bytes.len() - 31)
+        .filter(|&i| &bytes[i..i + 32] == find)
+        .nth(nth)
+        .unwrap();
+    bytes[at..at + 32].copy_from_slice(&[0u8; 32]);
+    StateSnapshot::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn test_from_snapshot_rejects_broken_chain() {
+    let (mut state, _, _) = state_with_days(30);
+    state.snapshot(day(10));
+    let snapshot = state.snapshot(day(20));
+    let first_hash = *snapshot.segments()[0].hash();
+
+    // The first occurrence is segment 0's own hash, the second segment 1's link to it
+    let bad_hash = tamper(&snapshot, &first_hash, 0);
+    let bad_link = tamper(&snapshot, &first_hash, 1);
+
+    assert!(matches!(
+        MeshState::from_snapshot(bad_hash, &state.to_bytes()),
+        Err(MeshStateError::InvalidSnapshot(SnapshotError::BrokenChain(0)))
+    ));
+    assert!(matches!(
+        MeshState::from_snapshot(bad_link, &state.to_bytes()),
+        Err(MeshStateError::InvalidSnapshot(SnapshotError::BrokenChain(1)))
+    ));
+}
+
+// ============================================================================
+// MERGING AGAINST FROZEN ENTRIES
+// ============================================================================
+
+#[test]
+fn test_merge_does_not_re_add_frozen_entries() {
+    let (mut state, alice, bob) = state_with_days(30);
+    let peer = state.clone();
+    state.snapshot(day(20));
+
+    let result = state.merge(&peer);
+
+    assert_eq!(result.new_entries, 0);
+    assert_eq!(state.iou_count(), 10);
+
+    // Genuinely new entries, old or recent, still merge
+    let mut newer = MeshState::new(NodeId::generate());
+    newer.add_iou(iou(&alice, &bob, 40), &alice.public_key()).unwrap();
+    let late = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(bob.clone())
+        .amount(7)
+        .nonce(1_000)
+        .timestamp(day(3))
+        .build()
+        .unwrap();
+    newer.add_iou(late, &alice.public_key()).unwrap();
+    assert_eq!(state.merge(&newer).new_entries, 2);
+}
+
+#[test]
+fn test_add_iou_rejects_frozen_duplicate() {
+    let (mut state, alice, bob) = state_with_days(30);
+    state.snapshot(day(20));
+
+    let result = state.add_iou(iou(&alice, &bob, 5), &alice.public_key());
+
+    assert!(matches!(result, Err(MeshStateError::DuplicateIOU)));
+}
+
+// ============================================================================
+// COMPACTION STATS
+// ============================================================================
+
+#[test]
+fn test_compaction_stats_report_savings() {
+    let (mut state, _, _) = state_with_days(200);
+    let full_bytes = state.to_bytes().len() as u64;
+
+    state.snapshot(day(190));
+    let stats = state.compaction_stats();
+
+    assert_eq!(stats.segments, 1);
+    assert_eq!(stats.frozen_entries, 190);
+    assert_eq!(stats.live_entries, 10);
+    assert!(stats.compressed_bytes < stats.raw_bytes);
+    assert_eq!(stats.bytes_saved(), stats.raw_bytes - stats.compressed_bytes);
+    assert_eq!(stats.live_bytes, state.to_bytes().len() as u64);
+    assert!(stats.live_bytes + state.frozen().to_bytes().len() as u64 <= full_bytes);
+}
+
+#[test]
+fn test_compaction_stats_empty_without_snapshot() {
+    let (state, _, _) = state_with_days(3);
+
+    let stats = state.compaction_stats();
+
+    assert_eq!(stats.segments, 0);
+    assert_eq!(stats.frozen_entries, 0);
+    assert_eq!(stats.bytes_saved(), 0);
+    assert_eq!(stats.live_entries, 3);
+}
@@ -2,8 +2,8 @@
 // Tests for tracking the current state of the mesh network
 
 use p2pmesh::identity::{Did, Keypair};
-use p2pmesh::iou::IOUBuilder;
-use p2pmesh::ledger::{MeshState, MeshStateError, NodeId};
+use p2pmesh::iou::{IOUBuilder, SignedCancellation};
+use p2pmesh::ledger::{MeshState, MeshStateError, NodeId, STATE_MAGIC, STATE_VERSION};
 
 // ============================================================================
 // MESH STATE CREATION
@@ -117,6 +117,48 @@ fn test_add_multiple_ious() {
     assert_eq!(state.iou_count(), 10);
 }
 
+/// An IOU whose expiry passed long ago
+fn create_expired_iou(sender: &Keypair, recipient: &Keypair) -> p2pmesh::iou::SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(100)
+        .timestamp(1_000)
+        .expiry(1_060)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_add_expired_iou_fails() {
+    let mut state = MeshState::new(NodeId::generate());
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+
+    let result = state.add_iou(create_expired_iou(&alice, &bob), &alice.public_key());
+
+    assert!(matches!(result, Err(MeshStateError::ValidationFailed(_))));
+}
+
+#[test]
+fn test_replicated_expired_iou_is_kept() {
+    let mut state = MeshState::new(NodeId::generate());
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+
+    state.add_replicated_iou(create_expired_iou(&alice, &bob), &alice.public_key()).unwrap();
+
+    assert_eq!(state.iou_count(), 1);
+}
+
+#[test]
+fn test_replicated_iou_still_needs_valid_signature() {
+    let mut state = MeshState::new(NodeId::generate());
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+
+    let result = state.add_replicated_iou(create_expired_iou(&alice, &bob), &bob.public_key());
+
+    assert!(matches!(result, Err(MeshStateError::ValidationFailed(_))));
+}
+
 // ============================================================================
 // QUERYING MESH STATE
 // ============================================================================
@@ -291,6 +333,31 @@ fn test_state_serialization_roundtrip() {
     assert_eq!(restored.iou_count(), 1);
 }
 
+#[test]
+fn test_state_bytes_start_with_magic_and_version() {
+    let bytes = MeshState::new(NodeId::generate()).to_bytes();
+
+    assert_eq!(&bytes[..4], STATE_MAGIC);
+    assert_eq!(bytes[4], STATE_VERSION);
+}
+
+#[test]
+fn test_state_with_unknown_version_is_rejected() {
+    let mut bytes = MeshState::new(NodeId::generate()).to_bytes();
+    bytes[4] = STATE_VERSION + 1;
+
+    assert!(matches!(
+        MeshState::from_bytes(&bytes),
+        Err(MeshStateError::UnsupportedVersion(v)) if v == STATE_VERSION + 1
+    ));
+    assert!(matches!(MeshState::from_bytes(STATE_MAGIC), Err(MeshStateError::DeserializationFailed)));
+}
+
+/// A state's bytes without the magic and version, as written before they were
+fn unversioned_bytes(state: &MeshState) -> Vec<u8> {
+    state.to_bytes().split_off(STATE_MAGIC.len() + 1)
+}
+
 // ============================================================================
 // STATE VERSION/CLOCK
 // ============================================================================
@@ -363,6 +430,32 @@ fn test_compute_balance_for_did() {
     assert_eq!(sent, 300);
 }
 
+#[test]
+fn test_multi_output_iou_credits_each_recipient() {
+    let node_id = NodeId::generate();
+    let mut state = MeshState::new(node_id);
+
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+    let bob_did = Did::from_public_key(&bob.public_key());
+    let charlie_did = Did::from_public_key(&charlie.public_key());
+
+    // Alice splits 100 between Bob and Charlie in one IOU
+    let split = IOUBuilder::new()
+        .sender(&alice)
+        .add_output(bob_did.clone(), 70)
+        .add_output(charlie_did.clone(), 30)
+        .build()
+        .unwrap();
+    state.add_iou(split, &alice.public_key()).unwrap();
+
+    assert_eq!(state.get_ious_by_recipient(&charlie_did).len(), 1);
+    assert_eq!(state.total_received(&bob_did), 70);
+    assert_eq!(state.total_received(&charlie_did), 30);
+    assert_eq!(state.total_sent(&Did::from_public_key(&alice.public_key())), 100);
+}
+
 // ============================================================================
 // MESH STATE STATISTICS
 // ============================================================================
@@ -388,3 +481,351 @@ fn test_state_statistics() {
     assert_eq!(stats.unique_recipients, 3);
     assert_eq!(stats.total_value, 175);
 }
+
+// ============================================================================
+// CANCELLATIONS
+// ============================================================================
+
+#[test]
+fn test_cancelled_iou_cannot_be_added() {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = create_test_iou(&alice, &bob, 100, 1);
+
+    assert!(state.add_cancellation(SignedCancellation::sign(&alice, iou.id())).unwrap());
+    let result = state.add_iou(iou.clone(), &alice.public_key());
+
+    assert!(matches!(result, Err(MeshStateError::Cancelled)));
+    assert!(state.is_cancelled(&iou.id()));
+    assert!(!state.has_iou(&iou.id()));
+}
+
+#[test]
+fn test_cancellation_after_acceptance_keeps_iou() {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = create_test_iou(&alice, &bob, 100, 1);
+    state.add_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    state.add_cancellation(SignedCancellation::sign(&alice, iou.id())).unwrap();
+
+    assert!(state.has_iou(&iou.id()));
+    assert!(state.is_cancelled(&iou.id()));
+}
+
+#[test]
+fn test_add_cancellation_is_idempotent() {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let iou = create_test_iou(&alice, &Keypair::generate(), 100, 1);
+
+    assert!(state.add_cancellation(SignedCancellation::sign(&alice, iou.id())).unwrap());
+    let version = state.version();
+    assert!(!state.add_cancellation(SignedCancellation::sign(&alice, iou.id())).unwrap());
+
+    assert_eq!(state.cancellation_count(), 1);
+    assert_eq!(state.version(), version);
+}
+
+#[test]
+fn test_cancellation_from_non_sender_rejected() {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let mallory = Keypair::generate();
+    let iou = create_test_iou(&alice, &Keypair::generate(), 100, 1);
+    state.add_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let result = state.add_cancellation(SignedCancellation::sign(&mallory, iou.id()));
+
+    assert!(matches!(result, Err(MeshStateError::CancellationSenderMismatch)));
+    assert!(!state.is_cancelled(&iou.id()));
+}
+
+#[test]
+fn test_forged_cancellation_rejected() {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let iou = create_test_iou(&alice, &Keypair::generate(), 100, 1);
+    let genuine = SignedCancellation::sign(&alice, iou.id());
+    let other = SignedCancellation::sign(&alice, create_test_iou(&alice, &Keypair::generate(), 5, 2).id());
+
+    let forged = SignedCancellation::from_parts(genuine.cancellation().clone(), other.signature().clone());
+
+    assert!(matches!(state.add_cancellation(forged), Err(MeshStateError::InvalidCancellation)));
+}
+
+#[test]
+fn test_cancellations_propagate_on_merge() {
+    let mut sender_node = MeshState::new(NodeId::generate());
+    let mut recipient_node = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = create_test_iou(&alice, &bob, 100, 1);
+    sender_node.add_cancellation(SignedCancellation::sign(&alice, iou.id())).unwrap();
+    let version = recipient_node.version();
+
+    recipient_node.merge(&sender_node);
+
+    assert!(recipient_node.is_cancelled(&iou.id()));
+    assert!(recipient_node.version() > version);
+    assert!(matches!(
+        recipient_node.add_iou(iou, &alice.public_key()),
+        Err(MeshStateError::Cancelled)
+    ));
+}
+
+#[test]
+fn test_cancellations_survive_serialization() {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let iou = create_test_iou(&alice, &Keypair::generate(), 100, 1);
+    state.add_cancellation(SignedCancellation::sign(&alice, iou.id())).unwrap();
+
+    let restored = MeshState::from_bytes(&state.to_bytes()).unwrap();
+
+    assert!(restored.is_cancelled(&iou.id()));
+    assert!(restored.get_cancellation(&iou.id()).unwrap().verify_sender());
+}
+
+#[test]
+fn test_state_from_before_cancellations_still_decodes() {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let iou = create_test_iou(&alice, &Keypair::generate(), 100, 1);
+    state.add_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    // Drop the version vector (one 34-byte counter), the (empty) checkpoint and settled
+    // sets, the sync log (one 32-byte ID) and the (empty) cancellation map
+    let mut bytes = unversioned_bytes(&state);
+    bytes.truncate(bytes.len() - 70);
+    assert_eq!(bytes.pop(), Some(0));
+
+    let restored = MeshState::from_bytes(&bytes).unwrap();
+    assert!(restored.has_iou(&iou.id()));
+    assert_eq!(restored.cancellation_count(), 0);
+    assert_eq!(restored.version(), state.version());
+}
+
+// ============================================================================
+// SYNC CURSORS
+// ============================================================================
+
+fn state_with_ious(count: u64) -> (MeshState, Vec<p2pmesh::iou::IOUId>) {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut ids = Vec::new();
+    for nonce in 1..=count {
+        let iou = create_test_iou(&alice, &bob, 100, nonce);
+        ids.push(iou.id());
+        state.add_iou(iou, &alice.public_key()).unwrap();
+    }
+    (state, ids)
+}
+
+#[test]
+fn test_cursor_counts_added_ious() {
+    let (state, _) = state_with_ious(3);
+
+    assert_eq!(MeshState::new(NodeId::generate()).cursor(), 0);
+    assert_eq!(state.cursor(), 3);
+}
+
+#[test]
+fn test_entries_since_pages_in_arrival_order() {
+    let (state, ids) = state_with_ious(5);
+
+    let (first, cursor) = state.entries_since(0, 2);
+    let (second, cursor2) = state.entries_since(cursor, 2);
+    let (third, cursor3) = state.entries_since(cursor2, 2);
+    let (empty, cursor4) = state.entries_since(cursor3, 2);
+
+    let paged: Vec<_> = first.iter().chain(&second).chain(&third).map(|e| e.id()).collect();
+    assert_eq!(paged, ids);
+    assert_eq!((cursor, cursor2, cursor3, cursor4), (2, 4, 5, 5));
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_entries_since_bounds() {
+    let (state, ids) = state_with_ious(3);
+
+    // A limit of 0 still makes progress; a cursor past the end returns nothing
+    let (one, cursor) = state.entries_since(0, 0);
+    assert_eq!(one.len(), 1);
+    assert_eq!(one[0].id(), ids[0]);
+    assert_eq!(cursor, 1);
+
+    let (none, cursor) = state.entries_since(99, 10);
+    assert!(none.is_empty());
+    assert_eq!(cursor, 3);
+}
+
+#[test]
+fn test_merge_logs_new_entries_after_existing() {
+    let (mut state, ids) = state_with_ious(2);
+    let (other, other_ids) = state_with_ious(3);
+
+    state.merge(&other);
+    state.merge(&other);
+
+    assert_eq!(state.cursor(), 5);
+    let (entries, _) = state.entries_since(2, 10);
+    let mut merged: Vec<_> = entries.iter().map(|e| e.id()).collect();
+    let mut expected = other_ids.clone();
+    merged.sort_by_key(|id| *id.as_bytes());
+    expected.sort_by_key(|id| *id.as_bytes());
+    assert_eq!(merged, expected);
+    assert_eq!(state.entries_since(0, 2).0.iter().map(|e| e.id()).collect::<Vec<_>>(), ids);
+}
+
+#[test]
+fn test_cursors_survive_serialization() {
+    let (state, ids) = state_with_ious(4);
+
+    let restored = MeshState::from_bytes(&state.to_bytes()).unwrap();
+
+    assert_eq!(restored.cursor(), 4);
+    let (entries, _) = restored.entries_since(1, 10);
+    assert_eq!(entries.iter().map(|e| e.id()).collect::<Vec<_>>(), ids[1..].to_vec());
+}
+
+// ============================================================================
+// MERKLE ROOT
+// ============================================================================
+
+#[test]
+fn test_merkle_root_empty_state() {
+    let state = MeshState::new(NodeId::generate());
+
+    assert_eq!(state.merkle_root(), [0u8; 32]);
+}
+
+#[test]
+fn test_merkle_root_independent_of_arrival_order() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let ious: Vec<_> = (1..=5).map(|n| create_test_iou(&alice, &bob, 100, n)).collect();
+
+    let mut forward = MeshState::new(NodeId::generate());
+    let mut backward = MeshState::new(NodeId::generate());
+    for iou in &ious {
+        forward.add_iou(iou.clone(), &alice.public_key()).unwrap();
+    }
+    for iou in ious.iter().rev() {
+        backward.add_iou(iou.clone(), &alice.public_key()).unwrap();
+    }
+
+    assert_eq!(forward.merkle_root(), backward.merkle_root());
+}
+
+#[test]
+fn test_merkle_root_changes_with_content() {
+    let (mut state, _) = state_with_ious(3);
+    let before = state.merkle_root();
+
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = create_test_iou(&alice, &bob, 100, 1);
+    state.add_iou(iou.clone(), &alice.public_key()).unwrap();
+    let with_iou = state.merkle_root();
+
+    state.add_cancellation(SignedCancellation::sign(&alice, iou.id())).unwrap();
+
+    assert_ne!(before, with_iou);
+    assert_ne!(with_iou, state.merkle_root());
+}
+
+#[test]
+fn test_merkle_root_matches_after_merge() {
+    let (mut state1, _) = state_with_ious(3);
+    let (mut state2, _) = state_with_ious(4);
+    assert_ne!(state1.merkle_root(), state2.merkle_root());
+
+    state1.merge(&state2);
+    state2.merge(&state1);
+
+    assert_eq!(state1.merkle_root(), state2.merkle_root());
+}
+
+// ============================================================================
+// TIME-RANGE QUERIES
+// ============================================================================
+
+fn create_iou_at(sender: &Keypair, recipient: &Keypair, nonce: u64, timestamp: u64) -> p2pmesh::iou::SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(10)
+        .nonce(nonce)
+        .timestamp(timestamp)
+        .build()
+        .unwrap()
+}
+
+/// Alice sends IOUs at 1000, 2000, 3000 and 4000; Bob sends one at 2000
+fn state_with_timestamps() -> (MeshState, Keypair, Keypair) {
+    let mut state = MeshState::new(NodeId::generate());
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    for (nonce, timestamp) in [(1, 3000), (2, 1000), (3, 4000), (4, 2000)] {
+        state.add_iou(create_iou_at(&alice, &bob, nonce, timestamp), &alice.public_key()).unwrap();
+    }
+    state.add_iou(create_iou_at(&bob, &alice, 1, 2000), &bob.public_key()).unwrap();
+    (state, alice, bob)
+}
+
+fn timestamps(entries: &[&p2pmesh::ledger::IOUEntry]) -> Vec<u64> {
+    entries.iter().map(|e| e.iou().iou().timestamp()).collect()
+}
+
+#[test]
+fn test_ious_between_start_inclusive_end_exclusive() {
+    let (state, _, _) = state_with_timestamps();
+
+    assert_eq!(timestamps(&state.ious_between(2000, 4000)), vec![2000, 2000, 3000]);
+    assert_eq!(timestamps(&state.ious_between(1000, 1001)), vec![1000]);
+    assert!(state.ious_between(1001, 2000).is_empty());
+}
+
+#[test]
+fn test_ious_between_adjacent_windows_partition() {
+    let (state, _, _) = state_with_timestamps();
+
+    let first = state.ious_between(0, 2500).len();
+    let second = state.ious_between(2500, u64::MAX).len();
+
+    assert_eq!(first + second, state.iou_count());
+}
+
+#[test]
+fn test_ious_between_empty_or_inverted_window() {
+    let (state, _, _) = state_with_timestamps();
+
+    assert!(state.ious_between(3000, 3000).is_empty());
+    assert!(state.ious_between(4000, 1000).is_empty());
+}
+
+#[test]
+fn test_ious_by_sender_between() {
+    let (state, alice, bob) = state_with_timestamps();
+    let alice_did = Did::from_public_key(&alice.public_key());
+    let bob_did = Did::from_public_key(&bob.public_key());
+
+    assert_eq!(timestamps(&state.ious_by_sender_between(&alice_did, 1000, 3000)), vec![1000, 2000]);
+    assert_eq!(timestamps(&state.ious_by_sender_between(&bob_did, 0, u64::MAX)), vec![2000]);
+    assert!(state.ious_by_sender_between(&bob_did, 2001, 5000).is_empty());
+}
+
+#[test]
+fn test_ious_between_after_merge_and_serialization() {
+    let (state, _, _) = state_with_timestamps();
+    let mut merged = MeshState::new(NodeId::generate());
+    merged.merge(&state);
+    let restored = MeshState::from_bytes(&state.to_bytes()).unwrap();
+
+    assert_eq!(timestamps(&merged.ious_between(1500, 3500)), vec![2000, 2000, 3000]);
+    assert_eq!(timestamps(&restored.ious_between(1500, 3500)), vec![2000, 2000, 3000]);
+}
@@ -0,0 +1,228 @@
+// State Summary Tests
+// Tests for Merkle-summary-based delta sync between mesh states
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{
+    Delta, IOUEntry, MeshState, NodeId, StateSummary, SummaryError, MAX_SUMMARY_BUCKETS,
+};
+
+fn create_test_iou(sender: &Keypair, recipient: &Keypair, amount: u64, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(amount)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+/// Two states sharing `shared` IOUs, where the first also has `extra` more
+fn create_diverged_states(shared: u64, extra: u64) -> (MeshState, MeshState) {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut behind = MeshState::new(NodeId::generate());
+    for nonce in 0..shared {
+        behind.add_iou(create_test_iou(&alice, &bob, 10 + nonce, nonce), &alice.public_key()).unwrap();
+    }
+
+    let mut ahead = MeshState::new(NodeId::generate());
+    ahead.merge(&behind);
+    for nonce in shared..shared + extra {
+        ahead.add_iou(create_test_iou(&alice, &bob, 10 + nonce, nonce), &alice.public_key()).unwrap();
+    }
+
+    (ahead, behind)
+}
+
+// ============================================================================
+// SUMMARY BASICS
+// ============================================================================
+
+#[test]
+fn test_summary_of_equal_states_match() {
+    let (ahead, behind) = create_diverged_states(40, 0);
+
+    assert_eq!(ahead.summary(), behind.summary());
+    assert!(ahead.summary().differing_buckets(&behind.summary()).is_empty());
+}
+
+#[test]
+fn test_summary_is_independent_of_arrival_order() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let ious: Vec<SignedIOU> = (0..20).map(|n| create_test_iou(&alice, &bob, 5, n)).collect();
+
+    let mut forward = MeshState::new(NodeId::generate());
+    let mut reverse = MeshState::new(NodeId::generate());
+    for iou in &ious {
+        forward.add_iou(iou.clone(), &alice.public_key()).unwrap();
+    }
+    for iou in ious.iter().rev() {
+        reverse.add_iou(iou.clone(), &alice.public_key()).unwrap();
+    }
+
+    assert_eq!(forward.summary().root(), reverse.summary().root());
+}
+
+#[test]
+fn test_summary_bucket_count_scales_with_state() {
+    let empty = MeshState::new(NodeId::generate());
+    assert_eq!(empty.summary().bucket_count(), 1);
+    assert_eq!(empty.summary().item_count(), 0);
+
+    let (state, _) = create_diverged_states(100, 0);
+    let summary = state.summary();
+    assert_eq!(summary.bucket_count(), 8);
+    assert_eq!(summary.item_count(), 100);
+}
+
+#[test]
+fn test_summary_bucket_count_is_clamped_power_of_two() {
+    let (state, _) = create_diverged_states(3, 0);
+
+    assert_eq!(state.summary_with_buckets(0).bucket_count(), 1);
+    assert_eq!(state.summary_with_buckets(100).bucket_count(), 128);
+    assert_eq!(state.summary_with_buckets(usize::MAX).bucket_count(), MAX_SUMMARY_BUCKETS);
+}
+
+#[test]
+fn test_differing_buckets_locate_missing_entries() {
+    let (ahead, behind) = create_diverged_states(50, 1);
+    let ours = ahead.summary_with_buckets(16);
+    let theirs = behind.summary_with_buckets(16);
+
+    let missing = ahead.all_entries().into_iter().find(|entry| !behind.has_iou(&entry.id())).unwrap();
+    assert_eq!(theirs.differing_buckets(&ours), vec![ours.bucket_of(&missing.id())]);
+}
+
+#[test]
+fn test_differing_buckets_needs_matching_bucket_counts() {
+    let (ahead, behind) = create_diverged_states(10, 5);
+
+    assert!(ahead.summary_with_buckets(4).differing_buckets(&behind.summary_with_buckets(8)).is_empty());
+}
+
+// ============================================================================
+// DELTAS
+// ============================================================================
+
+#[test]
+fn test_delta_against_summary_contains_missing_entries() {
+    let (ahead, behind) = create_diverged_states(30, 4);
+
+    let delta = ahead.diff_against_summary(&behind.summary());
+
+    for entry in ahead.all_entries() {
+        if !behind.has_iou(&entry.id()) {
+            assert!(delta.entries().iter().any(|e| e.id() == entry.id()));
+        }
+    }
+}
+
+#[test]
+fn test_delta_against_equal_summary_is_empty() {
+    let (ahead, behind) = create_diverged_states(30, 0);
+
+    assert!(ahead.diff_against_summary(&behind.summary()).is_empty());
+}
+
+#[test]
+fn test_delta_for_state_ahead_adds_nothing() {
+    // The summary owner already has everything we have
+    let (mut ahead, behind) = create_diverged_states(30, 4);
+
+    let delta = behind.diff_against_summary(&ahead.summary());
+
+    assert!(delta.entries().iter().all(|entry| ahead.has_iou(&entry.id())));
+    assert_eq!(ahead.apply_delta(delta).new_entries, 0);
+}
+
+#[test]
+fn test_delta_with_expired_iou_converges() {
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+    let expired = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(100)
+        .timestamp(1_000)
+        .expiry(1_060)
+        .build()
+        .unwrap();
+    let (mut ahead, mut behind) = create_diverged_states(5, 0);
+    ahead.add_replicated_iou(expired, &alice.public_key()).unwrap();
+
+    let delta = ahead.diff_against_summary(&behind.summary());
+    assert_eq!(behind.apply_delta(delta).new_entries, 1);
+
+    assert_eq!(behind.merkle_root(), ahead.merkle_root());
+    assert!(ahead.diff_against_summary(&behind.summary()).is_empty());
+}
+
+#[test]
+fn test_apply_delta_converges() {
+    let (ahead, mut behind) = create_diverged_states(60, 6);
+
+    let result = behind.apply_delta(ahead.diff_against_summary(&behind.summary()));
+
+    assert_eq!(result.new_entries, 6);
+    assert_eq!(behind.merkle_root(), ahead.merkle_root());
+    assert!(ahead.diff_against_summary(&behind.summary()).is_empty());
+}
+
+#[test]
+fn test_apply_delta_skips_invalid_entries() {
+    let (ahead, mut behind) = create_diverged_states(0, 2);
+
+    // Pair each IOU with the wrong sender key
+    let entries = ahead
+        .all_entries()
+        .into_iter()
+        .map(|entry| IOUEntry::new(entry.iou().clone(), Keypair::generate().public_key()))
+        .collect();
+
+    let result = behind.apply_delta(Delta::new(entries));
+
+    assert_eq!(result.new_entries, 0);
+    assert!(behind.is_empty());
+}
+
+#[test]
+fn test_delta_round_trip_bytes() {
+    let (ahead, behind) = create_diverged_states(10, 3);
+    let delta = ahead.diff_against_summary(&behind.summary());
+
+    let restored = Delta::from_bytes(&delta.to_bytes()).unwrap();
+
+    assert_eq!(restored.len(), delta.len());
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_summary_round_trip_bytes() {
+    let (state, _) = create_diverged_states(40, 0);
+    let summary = state.summary();
+
+    assert_eq!(StateSummary::from_bytes(&summary.to_bytes()).unwrap(), summary);
+}
+
+#[test]
+fn test_summary_from_garbage_fails() {
+    assert_eq!(StateSummary::from_bytes(&[0xff, 0x01]), Err(SummaryError::DeserializationFailed));
+}
+
+#[test]
+fn test_summary_rejects_bad_bucket_counts() {
+    let (state, _) = create_diverged_states(3, 0);
+    let mut bytes = state.summary_with_buckets(4).to_bytes();
+
+    // Layout: root (32 bytes), bucket count varint, buckets, item count.
+    // Drop the last bucket to leave three.
+    bytes[32] = 3;
+    bytes.drain(33 + 3 * 8..33 + 4 * 8);
+
+    assert!(matches!(StateSummary::from_bytes(&bytes), Err(SummaryError::InvalidParameters(_))));
+}
@@ -0,0 +1,183 @@
+// Version Vector Tests
+// Tests per-node counters that tell whether a state is ahead, behind or concurrent
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{CausalRelation, MeshState, NodeId, VersionVector};
+
+fn iou(sender: &Keypair, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(10)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+/// A fresh state with `count` IOUs added locally
+fn state_with(count: u64) -> MeshState {
+    let alice = Keypair::generate();
+    let mut state = MeshState::new(NodeId::generate());
+    for nonce in 0..count {
+        state.add_iou(iou(&alice, nonce), &alice.public_key()).unwrap();
+    }
+    state
+}
+
+fn vector(counters: &[(&NodeId, u64)]) -> VersionVector {
+    let mut vector = VersionVector::new();
+    for (node_id, count) in counters {
+        for _ in 0..*count {
+            vector.increment(node_id);
+        }
+    }
+    vector
+}
+
+// ============================================================================
+// VERSION VECTOR
+// ============================================================================
+
+#[test]
+fn test_increment_counts_per_node() {
+    let a = NodeId::generate();
+    let b = NodeId::generate();
+    let mut clock = VersionVector::new();
+
+    assert_eq!(clock.increment(&a), 1);
+    assert_eq!(clock.increment(&a), 2);
+
+    assert_eq!(clock.get(&a), 2);
+    assert_eq!(clock.get(&b), 0);
+    assert_eq!(clock.len(), 1);
+}
+
+#[test]
+fn test_compare_all_relations() {
+    let a = NodeId::generate();
+    let b = NodeId::generate();
+
+    let base = vector(&[(&a, 2), (&b, 1)]);
+    let ahead = vector(&[(&a, 3), (&b, 1)]);
+    let concurrent = vector(&[(&a, 1), (&b, 2)]);
+
+    assert_eq!(base.compare(&base.clone()), CausalRelation::Equal);
+    assert_eq!(ahead.compare(&base), CausalRelation::Ahead);
+    assert_eq!(base.compare(&ahead), CausalRelation::Behind);
+    assert_eq!(base.compare(&concurrent), CausalRelation::Concurrent);
+    assert_eq!(concurrent.compare(&base), CausalRelation::Concurrent);
+}
+
+#[test]
+fn test_missing_node_counts_as_zero() {
+    let a = NodeId::generate();
+    let b = NodeId::generate();
+
+    assert_eq!(vector(&[(&a, 1), (&b, 0)]).compare(&vector(&[(&a, 1)])), CausalRelation::Equal);
+    assert_eq!(vector(&[(&b, 1)]).compare(&VersionVector::new()), CausalRelation::Ahead);
+}
+
+#[test]
+fn test_merge_takes_element_wise_max() {
+    let a = NodeId::generate();
+    let b = NodeId::generate();
+    let c = NodeId::generate();
+    let mut clock = vector(&[(&a, 3), (&b, 1)]);
+
+    clock.merge(&vector(&[(&a, 1), (&b, 4), (&c, 2)]));
+
+    assert_eq!(clock.get(&a), 3);
+    assert_eq!(clock.get(&b), 4);
+    assert_eq!(clock.get(&c), 2);
+}
+
+// ============================================================================
+// MESH STATE
+// ============================================================================
+
+#[test]
+fn test_add_iou_increments_own_counter() {
+    let state = state_with(3);
+
+    assert_eq!(state.clock().get(state.node_id()), 3);
+    assert_eq!(state.clock().len(), 1);
+}
+
+#[test]
+fn test_state_compare_all_relations() {
+    let mut a = MeshState::new(NodeId::generate());
+    let b = MeshState::new(NodeId::generate());
+    assert_eq!(a.compare(&b), CausalRelation::Equal);
+
+    let mut a_ahead = state_with(2);
+    let b_behind = MeshState::new(NodeId::generate());
+    assert_eq!(a_ahead.compare(&b_behind), CausalRelation::Ahead);
+    assert_eq!(b_behind.compare(&a_ahead), CausalRelation::Behind);
+
+    let concurrent = state_with(1);
+    assert_eq!(a_ahead.compare(&concurrent), CausalRelation::Concurrent);
+
+    // Exchanging states makes them equal again
+    a_ahead.merge(&concurrent);
+    a.merge(&a_ahead);
+    assert_eq!(a.compare(&a_ahead), CausalRelation::Equal);
+}
+
+#[test]
+fn test_merge_takes_max_of_state_clocks() {
+    let mut local = state_with(2);
+    let remote = state_with(3);
+
+    local.merge(&remote);
+
+    assert_eq!(local.clock().get(local.node_id()), 2);
+    assert_eq!(local.clock().get(remote.node_id()), 3);
+    assert_eq!(local.compare(&remote), CausalRelation::Ahead);
+}
+
+#[test]
+fn test_merge_with_rejected_entries_keeps_clock() {
+    let mut remote = state_with(1);
+    let entry = remote.all_entries()[0].iou().clone();
+
+    // Corrupt the signature so the entry fails verification
+    let signature = entry.signature().as_bytes().to_vec();
+    let mut bytes = remote.to_bytes();
+    let at = bytes.windows(signature.len()).position(|w| w == signature.as_slice()).unwrap();
+    bytes[at] ^= 0x01;
+    remote = MeshState::from_bytes(&bytes).unwrap();
+
+    let mut local = MeshState::new(NodeId::generate());
+    let result = local.merge(&remote);
+
+    assert_eq!(result.rejected_entries, 1);
+    assert!(local.clock().is_empty());
+    assert_eq!(local.compare(&remote), CausalRelation::Behind);
+}
+
+#[test]
+fn test_apply_delta_does_not_count_as_local() {
+    let remote = state_with(2);
+    let mut local = MeshState::new(NodeId::generate());
+
+    local.apply_delta(remote.diff_against_summary(&local.summary()));
+
+    assert_eq!(local.iou_count(), 2);
+    assert_eq!(local.clock().get(local.node_id()), 0);
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_clock_survives_to_bytes() {
+    let mut state = state_with(2);
+    state.merge(&state_with(1));
+
+    let restored = MeshState::from_bytes(&state.to_bytes()).unwrap();
+
+    assert_eq!(restored.clock(), state.clock());
+    assert_eq!(restored.compare(&state), CausalRelation::Equal);
+}
@@ -1,3 +1,4 @@
 // Storage test modules
 
+mod sealed_test;
 mod store_test;
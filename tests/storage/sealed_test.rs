@@ -0,0 +1,218 @@
+// Sealed Blob Tests
+// Tests for passphrase encryption (Argon2id + XChaCha20-Poly1305)
+
+use p2pmesh::storage::{
+    seal, seal_with, sealed_params, unseal, unseal_within, KdfParams, SealError, SEALED_VERSION,
+};
+use std::time::{Duration, Instant};
+
+/// Cheap params so tests stay fast
+fn test_params() -> KdfParams {
+    KdfParams::new(64, 1)
+}
+
+// ============================================================================
+// ROUNDTRIP
+// ============================================================================
+
+#[test]
+fn test_seal_unseal_roundtrip() {
+    let blob = seal(b"secret data", b"passphrase", &test_params()).unwrap();
+    let plaintext = unseal(&blob, b"passphrase").unwrap();
+
+    assert_eq!(plaintext.as_slice(), b"secret data");
+}
+
+#[test]
+fn test_seal_is_randomized() {
+    let a = seal(b"secret data", b"passphrase", &test_params()).unwrap();
+    let b = seal(b"secret data", b"passphrase", &test_params()).unwrap();
+
+    assert_ne!(a, b, "Fresh salt and nonce every time");
+}
+
+#[test]
+fn test_seal_empty_plaintext() {
+    let blob = seal(b"", b"passphrase", &test_params()).unwrap();
+    assert!(unseal(&blob, b"passphrase").unwrap().is_empty());
+}
+
+// ============================================================================
+// FAILURES
+// ============================================================================
+
+#[test]
+fn test_wrong_passphrase_fails() {
+    let blob = seal(b"secret data", b"passphrase", &test_params()).unwrap();
+
+    assert_eq!(unseal(&blob, b"wrong").unwrap_err(), SealError::DecryptionFailed);
+}
+
+#[test]
+fn test_tampered_ciphertext_fails() {
+    let mut blob = seal(b"secret data", b"passphrase", &test_params()).unwrap();
+    let last = blob.len() - 1;
+    blob[last] ^= 0x01;
+
+    assert_eq!(unseal(&blob, b"passphrase").unwrap_err(), SealError::DecryptionFailed);
+}
+
+#[test]
+fn test_tampered_params_fail_authentication() {
+    let mut blob = seal(b"secret data", b"passphrase", &KdfParams::new(64, 2)).unwrap();
+    // Drop iterations from 2 to 1
+    blob[9] = 1;
+
+    assert_eq!(unseal(&blob, b"passphrase").unwrap_err(), SealError::DecryptionFailed);
+}
+
+#[test]
+fn test_bad_magic_fails() {
+    let mut blob = seal(b"secret data", b"passphrase", &test_params()).unwrap();
+    blob[0] = b'X';
+
+    assert!(matches!(unseal(&blob, b"passphrase"), Err(SealError::InvalidFormat(_))));
+}
+
+#[test]
+fn test_unsupported_version_fails() {
+    let mut blob = seal(b"secret data", b"passphrase", &test_params()).unwrap();
+    blob[4] = SEALED_VERSION + 1;
+
+    assert_eq!(
+        unseal(&blob, b"passphrase").unwrap_err(),
+        SealError::UnsupportedVersion(SEALED_VERSION + 1)
+    );
+}
+
+#[test]
+fn test_truncated_blob_fails() {
+    let blob = seal(b"secret data", b"passphrase", &test_params()).unwrap();
+
+    assert!(matches!(unseal(&blob[..20], b"passphrase"), Err(SealError::InvalidFormat(_))));
+    assert!(matches!(unseal(&blob[..60], b"passphrase"), Err(SealError::InvalidFormat(_))));
+}
+
+// ============================================================================
+// KDF PARAMS
+// ============================================================================
+
+#[test]
+fn test_params_are_embedded_in_header() {
+    let params = KdfParams::new(128, 3).with_parallelism(2);
+    let blob = seal(b"data", b"passphrase", &params).unwrap();
+
+    assert_eq!(sealed_params(&blob).unwrap(), params);
+    assert_eq!(unseal_within(&blob, b"passphrase", &params).unwrap().as_slice(), b"data");
+}
+
+#[test]
+fn test_invalid_params_rejected() {
+    assert!(matches!(
+        seal(b"data", b"pw", &KdfParams::new(64, 0)),
+        Err(SealError::InvalidParams(_))
+    ));
+    assert!(matches!(
+        seal(b"data", b"pw", &KdfParams::new(4, 1)),
+        Err(SealError::InvalidParams(_))
+    ));
+    assert!(matches!(
+        seal(b"data", b"pw", &KdfParams::new(64, 1).with_parallelism(0)),
+        Err(SealError::InvalidParams(_))
+    ));
+}
+
+#[test]
+fn test_hostile_memory_cost_rejected_before_kdf() {
+    let mut blob = seal(b"data", b"passphrase", &test_params()).unwrap();
+    blob[5..9].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    assert!(matches!(unseal(&blob, b"passphrase"), Err(SealError::InvalidParams(_))));
+}
+
+#[test]
+fn test_default_params_are_valid() {
+    let params = KdfParams::default();
+    assert!(params.validate().is_ok());
+    assert!(params.memory_kib >= 19 * 1024);
+}
+
+// ============================================================================
+// COST LIMITS
+// ============================================================================
+
+#[test]
+fn test_unseal_refuses_costs_above_default() {
+    let params = KdfParams::new(64, KdfParams::default().iterations + 1);
+    let blob = seal(b"data", b"passphrase", &params).unwrap();
+
+    assert!(matches!(unseal(&blob, b"passphrase"), Err(SealError::ParamsAboveLimit(_))));
+    assert_eq!(unseal_within(&blob, b"passphrase", &params).unwrap().as_slice(), b"data");
+}
+
+#[test]
+fn test_unseal_refuses_parallelism_above_limit() {
+    let blob = seal(b"data", b"passphrase", &test_params().with_parallelism(4)).unwrap();
+
+    let max = KdfParams::new(1024, 1).with_parallelism(2);
+    assert!(matches!(unseal_within(&blob, b"passphrase", &max), Err(SealError::ParamsAboveLimit(_))));
+}
+
+#[test]
+fn test_over_cost_header_rejected_before_kdf() {
+    // 1 GiB and 64 passes pass validation but would take minutes to derive
+    let mut blob = seal(b"data", b"passphrase", &test_params()).unwrap();
+    blob[5..9].copy_from_slice(&(1024u32 * 1024).to_le_bytes());
+    blob[9..13].copy_from_slice(&64u32.to_le_bytes());
+    assert!(sealed_params(&blob).is_ok());
+
+    let started = Instant::now();
+    let result = unseal(&blob, b"passphrase");
+
+    assert!(matches!(result, Err(SealError::ParamsAboveLimit(_))));
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_check_within() {
+    let max = KdfParams::default();
+
+    assert!(max.check_within(&max).is_ok());
+    assert!(test_params().check_within(&max).is_ok());
+    assert!(KdfParams::new(max.memory_kib + 1, 1).check_within(&max).is_err());
+    assert!(KdfParams::new(64, max.iterations + 1).check_within(&max).is_err());
+    assert!(test_params().with_parallelism(2).check_within(&max).is_err());
+}
+
+// ============================================================================
+// CROSS-PLATFORM VECTORS
+// ============================================================================
+
+/// Vector for Kotlin/Swift implementations:
+/// passphrase "correct horse battery staple", memory 64 KiB, 1 iteration, 1 lane,
+/// salt 16 x 0x02, nonce 24 x 0x03, plaintext "p2pmesh sealed test vector"
+const VECTOR_BLOB: &str = "503250450140000000010000000100000002020202020202020202020202020202\
+    03030303030303030303030303030303030303030303030392eea75921c0e29e478bfb1d13015ee2\
+    21e59d78d2be6066d01f32a53a59a86e7d9bdf09b78e96f3778d";
+
+#[test]
+fn test_vector_seal_matches() {
+    let blob = seal_with(
+        b"p2pmesh sealed test vector",
+        b"correct horse battery staple",
+        &KdfParams::new(64, 1),
+        &[0x02; 16],
+        &[0x03; 24],
+    )
+    .unwrap();
+
+    assert_eq!(hex::encode(blob), VECTOR_BLOB);
+}
+
+#[test]
+fn test_vector_unseal_matches() {
+    let blob = hex::decode(VECTOR_BLOB).unwrap();
+    let plaintext = unseal(&blob, b"correct horse battery staple").unwrap();
+
+    assert_eq!(plaintext.as_slice(), b"p2pmesh sealed test vector");
+}
@@ -315,6 +315,54 @@ fn test_mesh_state_persists_across_reopens() {
     }
 }
 
+fn add_iou(state: &mut MeshState, sender: &Keypair, nonce: u64) {
+    let iou = IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(7)
+        .nonce(nonce)
+        .build()
+        .unwrap();
+    state.add_iou(iou, &sender.public_key()).unwrap();
+}
+
+#[test]
+fn test_mesh_deltas_replayed_on_load() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = MeshStore::open(temp_dir.path()).unwrap();
+    let (mut state, alice, _) = create_mesh_state_with_ious();
+    store.save_mesh_state(&state).unwrap();
+    let mut point = state.save_point();
+
+    for nonce in 10..13 {
+        add_iou(&mut state, &alice, nonce);
+        assert_eq!(store.append_mesh_delta(&state.changes_since(&point)).unwrap(), nonce - 10);
+        point = state.save_point();
+    }
+    let loaded = store.load_mesh_state().unwrap().unwrap();
+
+    assert_eq!(store.mesh_delta_count().unwrap(), 3);
+    assert_eq!(loaded.iou_count(), 8);
+    assert_eq!(loaded.merkle_root(), state.merkle_root());
+}
+
+#[test]
+fn test_save_mesh_state_compacts_deltas() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = MeshStore::open(temp_dir.path()).unwrap();
+    let (mut state, alice, _) = create_mesh_state_with_ious();
+    store.save_mesh_state(&state).unwrap();
+    let point = state.save_point();
+    add_iou(&mut state, &alice, 10);
+    store.append_mesh_delta(&state.changes_since(&point)).unwrap();
+
+    store.save_mesh_state(&state).unwrap();
+
+    assert_eq!(store.mesh_delta_count().unwrap(), 0);
+    let loaded = store.load_mesh_state().unwrap().unwrap();
+    assert_eq!(loaded.merkle_root(), state.merkle_root());
+}
+
 #[test]
 fn test_mesh_state_indexes_rebuilt_on_load() {
     let temp_dir = TempDir::new().unwrap();
@@ -345,7 +393,7 @@ fn test_save_load_node_id() {
     let store = MeshStore::open(temp_dir.path()).unwrap();
 
     let node_id = NodeId::generate();
-    let original_bytes = node_id.as_bytes().clone();
+    let original_bytes = *node_id.as_bytes();
 
     store.save_node_id(&node_id).unwrap();
     let loaded = store.load_node_id().unwrap().unwrap();
@@ -367,6 +415,28 @@ fn test_get_or_create_node_id() {
     assert_eq!(node_id1.as_bytes(), node_id2.as_bytes());
 }
 
+#[test]
+fn test_save_load_nonce_counter() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = MeshStore::open(temp_dir.path()).unwrap();
+
+    assert_eq!(store.load_nonce_counter().unwrap(), None);
+
+    store.save_nonce_counter(42).unwrap();
+    assert_eq!(store.load_nonce_counter().unwrap(), Some(42));
+}
+
+#[test]
+fn test_corrupted_nonce_counter_returns_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = MeshStore::open(temp_dir.path()).unwrap();
+
+    store.put_raw(b"wallet:nonce_counter", b"bad").unwrap();
+
+    let result = store.load_nonce_counter();
+    assert!(matches!(result, Err(StoreError::DeserializationFailed(_))));
+}
+
 // ============================================================================
 // ATOMIC OPERATIONS
 // ============================================================================
@@ -448,6 +518,83 @@ fn test_delete_with_prefix() {
     assert!(store.get_raw(b"keep:1").unwrap().is_some());
 }
 
+// ============================================================================
+// NAMESPACES
+// ============================================================================
+
+#[test]
+fn test_namespaces_are_isolated() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = MeshStore::open(temp_dir.path()).unwrap();
+
+    let alice = store.namespace("wallet:alice").unwrap();
+    let bob = store.namespace("wallet:bob").unwrap();
+
+    let alice_keypair = Keypair::generate();
+    let bob_keypair = Keypair::generate();
+    alice.save_keypair(&alice_keypair).unwrap();
+    bob.save_keypair(&bob_keypair).unwrap();
+
+    assert_eq!(
+        alice.load_keypair().unwrap().unwrap().public_key(),
+        alice_keypair.public_key()
+    );
+    assert_eq!(
+        bob.load_keypair().unwrap().unwrap().public_key(),
+        bob_keypair.public_key()
+    );
+    assert!(store.load_keypair().unwrap().is_none(), "Default tree untouched");
+}
+
+#[test]
+fn test_list_namespaces_with_prefix() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = MeshStore::open(temp_dir.path()).unwrap();
+
+    store.namespace("wallet:alice").unwrap();
+    store.namespace("wallet:bob").unwrap();
+    store.namespace("cache:peers").unwrap();
+
+    let mut wallets = store.list_namespaces("wallet:");
+    wallets.sort();
+
+    assert_eq!(wallets, vec!["wallet:alice", "wallet:bob"]);
+    assert!(store.has_namespace("cache:peers"));
+    assert!(!store.has_namespace("wallet:carol"));
+}
+
+#[test]
+fn test_namespace_persists_across_reopen() {
+    let temp_dir = TempDir::new().unwrap();
+    let (vault, _, _) = create_funded_vault();
+
+    {
+        let store = MeshStore::open(temp_dir.path()).unwrap();
+        store.namespace("wallet:main").unwrap().save_vault(&vault).unwrap();
+        store.flush().unwrap();
+    }
+
+    let store = MeshStore::open(temp_dir.path()).unwrap();
+    let loaded = store.namespace("wallet:main").unwrap().load_vault().unwrap().unwrap();
+    assert_eq!(loaded.balance(), vault.balance());
+}
+
+#[test]
+fn test_wipe_namespace_removes_data() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = MeshStore::open(temp_dir.path()).unwrap();
+
+    let ns = store.namespace("wallet:old").unwrap();
+    ns.save_keypair(&Keypair::generate()).unwrap();
+
+    assert!(store.wipe_namespace("wallet:old").unwrap());
+    assert!(!store.has_namespace("wallet:old"));
+
+    let reopened = store.namespace("wallet:old").unwrap();
+    assert!(reopened.load_keypair().unwrap().is_none());
+    assert!(!store.wipe_namespace("wallet:missing").unwrap());
+}
+
 // ============================================================================
 // ERROR HANDLING
 // ============================================================================
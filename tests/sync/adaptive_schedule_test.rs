@@ -0,0 +1,260 @@
+// Adaptive Scheduling Tests
+// Tests per-peer anti-entropy back-off, resets on new data, stalest-peer selection,
+// and a three-peer simulation comparing message counts with the fixed schedule
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{GossipConfig, GossipEngine, GossipEvent, Message};
+
+const BASE_SECS: u64 = 10;
+const MAX_SECS: u64 = 80;
+
+fn fixed() -> GossipConfig {
+    GossipConfig::new()
+        .with_heartbeat_interval(0)
+        .with_anti_entropy_interval(BASE_SECS)
+}
+
+fn adaptive() -> GossipConfig {
+    fixed().with_max_interval(MAX_SECS)
+}
+
+fn engine(config: GossipConfig) -> GossipEngine {
+    let id = NodeId::generate();
+    GossipEngine::new(id.clone(), MeshState::new(id), config)
+}
+
+fn iou(sender: &Keypair, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(10)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+/// Create an IOU locally and announce it, as an app would
+fn create_iou(engine: &mut GossipEngine, sender: &Keypair, nonce: u64) {
+    let iou = iou(sender, nonce);
+    engine.state_mut().add_iou(iou.clone(), &sender.public_key()).unwrap();
+    engine.announce_iou(iou, &sender.public_key());
+}
+
+/// One node under test and mock peers that only answer it
+struct Sim {
+    node: GossipEngine,
+    peers: Vec<GossipEngine>,
+    /// Messages the node sent
+    sent: usize,
+}
+
+impl Sim {
+    fn new(config: GossipConfig, peer_count: usize) -> Self {
+        Self {
+            node: engine(config),
+            peers: (0..peer_count).map(|_| engine(fixed())).collect(),
+            sent: 0,
+        }
+    }
+
+    fn peer_ids(&self) -> Vec<NodeId> {
+        self.peers.iter().map(|peer| peer.state().node_id().clone()).collect()
+    }
+
+    fn peer_mut(&mut self, id: &NodeId) -> &mut GossipEngine {
+        self.peers.iter_mut().find(|peer| peer.state().node_id() == id).unwrap()
+    }
+
+    /// Tick the node and deliver everything, routing replies back to it
+    fn round(&mut self, now_ms: u64) {
+        let outgoing = self.node.tick_at(&self.peer_ids(), now_ms);
+        self.sent += outgoing.len();
+        for (to, msg) in outgoing {
+            let replies: Vec<Message> = self
+                .peer_mut(&to)
+                .process_message(msg)
+                .unwrap()
+                .into_iter()
+                .filter_map(|event| match event {
+                    GossipEvent::Forward(reply @ Message::SyncResponse(_)) => Some(reply),
+                    _ => None,
+                })
+                .collect();
+            for reply in replies {
+                self.node.process_message(reply).unwrap();
+            }
+        }
+    }
+
+    fn run(&mut self, from_secs: u64, to_secs: u64) {
+        for secs in (from_secs..to_secs).step_by(BASE_SECS as usize) {
+            self.round(secs * 1000);
+        }
+    }
+}
+
+// ============================================================================
+// BACK-OFF
+// ============================================================================
+
+#[test]
+fn test_idle_syncs_double_interval_up_to_max() {
+    let mut sim = Sim::new(adaptive(), 1);
+    let peer = sim.peer_ids()[0].clone();
+
+    let mut intervals = Vec::new();
+    let mut now = 0;
+    for _ in 0..5 {
+        sim.round(now * 1000);
+        intervals.push(sim.node.peer_interval_secs(&peer));
+        now += sim.node.peer_interval_secs(&peer);
+    }
+
+    assert_eq!(intervals, vec![20, 40, 80, 80, 80]);
+    assert_eq!(sim.node.stats().avg_interval_secs, 80.0);
+}
+
+#[test]
+fn test_rounds_skipped_while_no_peer_is_due() {
+    let mut sim = Sim::new(adaptive(), 1);
+
+    sim.round(0);
+    sim.round(10_000);
+    sim.round(19_999);
+
+    // The only peer backed off to 20s after the first idle sync
+    assert_eq!(sim.node.stats().anti_entropy_rounds, 1);
+    assert_eq!(sim.node.stats().rounds_skipped, 1);
+    sim.round(20_000);
+    assert_eq!(sim.node.stats().anti_entropy_rounds, 2);
+}
+
+#[test]
+fn test_fixed_schedule_never_backs_off() {
+    let mut sim = Sim::new(fixed(), 1);
+    let peer = sim.peer_ids()[0].clone();
+
+    sim.run(0, 60);
+
+    assert_eq!(sim.node.peer_interval_secs(&peer), BASE_SECS);
+    assert_eq!(sim.node.stats().anti_entropy_rounds, 6);
+    assert_eq!(sim.node.stats().rounds_skipped, 0);
+}
+
+// ============================================================================
+// RESETS
+// ============================================================================
+
+#[test]
+fn test_local_iou_resets_back_off() {
+    let mut sim = Sim::new(adaptive(), 1);
+    let peer = sim.peer_ids()[0].clone();
+    sim.run(0, 100);
+    assert!(sim.node.peer_interval_secs(&peer) > BASE_SECS);
+
+    create_iou(&mut sim.node, &Keypair::generate(), 1);
+
+    assert_eq!(sim.node.peer_interval_secs(&peer), BASE_SECS);
+    assert_eq!(sim.node.next_peer_to_sync_at(std::slice::from_ref(&peer), 100_000), Some(peer));
+}
+
+#[test]
+fn test_merge_with_new_entries_resets_back_off() {
+    let mut sim = Sim::new(adaptive(), 2);
+    let peers = sim.peer_ids();
+    sim.run(0, 100);
+    assert!(peers.iter().all(|peer| sim.node.peer_interval_secs(peer) > BASE_SECS));
+
+    // One peer gains an entry; the next pull from it resets every peer
+    let alice = Keypair::generate();
+    sim.peer_mut(&peers[0]).state_mut().add_iou(iou(&alice, 1), &alice.public_key()).unwrap();
+    let mut now = 100;
+    while sim.node.state().iou_count() == 0 {
+        sim.round(now * 1000);
+        now += BASE_SECS;
+    }
+
+    assert!(peers.iter().all(|peer| sim.node.peer_interval_secs(peer) == BASE_SECS));
+}
+
+// ============================================================================
+// PEER SELECTION
+// ============================================================================
+
+#[test]
+fn test_next_peer_prefers_unknown_then_most_behind() {
+    let mut node = engine(adaptive());
+    let alice = Keypair::generate();
+    for nonce in 1..=3 {
+        create_iou(&mut node, &alice, nonce);
+    }
+    let mut behind = engine(fixed());
+    let mut closer = engine(fixed());
+    behind.state_mut().add_iou(iou(&alice, 9), &alice.public_key()).unwrap();
+    closer.state_mut().merge(node.state());
+    closer.state_mut().add_iou(iou(&alice, 8), &alice.public_key()).unwrap();
+    let unknown = NodeId::generate();
+    let (behind_id, closer_id) = (behind.state().node_id().clone(), closer.state().node_id().clone());
+
+    node.process_message(Message::Heartbeat(behind.generate_heartbeat())).unwrap();
+    node.process_message(Message::Heartbeat(closer.generate_heartbeat())).unwrap();
+
+    let all = [closer_id.clone(), behind_id.clone(), unknown.clone()];
+    assert_eq!(node.next_peer_to_sync(&all), Some(unknown));
+    assert_eq!(node.next_peer_to_sync(&all[..2]), Some(behind_id));
+    // A peer with all of our entries is still picked when it's the only one
+    assert_eq!(node.next_peer_to_sync(&all[..1]), Some(closer_id));
+}
+
+#[test]
+fn test_next_peer_rotates_through_equally_stale_peers() {
+    let mut sim = Sim::new(fixed().with_max_interval(MAX_SECS), 3);
+
+    let mut synced = Vec::new();
+    for round in 0..3 {
+        let next = sim.node.next_peer_to_sync_at(&sim.peer_ids(), round * 10_000).unwrap();
+        sim.round(round * 10_000);
+        synced.push(next);
+    }
+
+    synced.sort_by_key(|id| *id.as_bytes());
+    synced.dedup();
+    assert_eq!(synced.len(), 3);
+}
+
+// ============================================================================
+// SIMULATION
+// ============================================================================
+
+/// An hour of 10s ticks against three mock peers, with a few IOUs mid-way
+fn simulate(config: GossipConfig) -> Sim {
+    let mut sim = Sim::new(config, 3);
+    let alice = Keypair::generate();
+    sim.run(0, 1800);
+    for nonce in 1..=2 {
+        create_iou(&mut sim.node, &alice, nonce);
+    }
+    sim.run(1800, 3600);
+    sim
+}
+
+#[test]
+fn test_adaptive_schedule_sends_fewer_messages_than_fixed() {
+    let fixed = simulate(fixed());
+    let adaptive = simulate(adaptive());
+
+    assert!(
+        adaptive.sent * 2 < fixed.sent,
+        "adaptive sent {}, fixed sent {}",
+        adaptive.sent,
+        fixed.sent
+    );
+    assert!(adaptive.node.stats().rounds_skipped > 0);
+
+    // Both still deliver the new IOUs to every peer
+    for sim in [&fixed, &adaptive] {
+        assert!(sim.peers.iter().all(|peer| peer.state().iou_count() == 2));
+    }
+}
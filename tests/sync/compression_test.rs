@@ -0,0 +1,265 @@
+// Payload Compression Tests
+// Tests packing state and delta payloads, the raw/deflate header, decoding
+// unheaded payloads from older nodes, the lossless round trip, and compressed
+// gossip messages with their decompressed-size limit
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::IOUBuilder;
+use p2pmesh::ledger::{Delta, IOUEntry, MeshState, MeshStateError, NodeId};
+use p2pmesh::sync::{
+    decode_payload, pack_payload, unpack_payload, Compression, Features, GossipConfig, GossipEngine,
+    GossipError, Hello, Message, PayloadError, ProtocolError, SyncResponse, MAX_MESSAGE_BYTES,
+    PAYLOAD_DEFLATE, PAYLOAD_RAW,
+};
+use std::cell::RefCell;
+
+/// A state of `count` IOUs from one sender to a handful of recipients
+fn state_with(count: u64) -> MeshState {
+    let sender = Keypair::generate();
+    let recipients: Vec<Did> = (0..4)
+        .map(|_| Did::from_public_key(&Keypair::generate().public_key()))
+        .collect();
+    let mut state = MeshState::new(NodeId::generate());
+    for nonce in 1..=count {
+        let iou = IOUBuilder::new()
+            .sender(&sender)
+            .recipient(recipients[nonce as usize % recipients.len()].clone())
+            .amount(nonce * 10)
+            .nonce(nonce)
+            .build()
+            .unwrap();
+        state.add_iou(iou, &sender.public_key()).unwrap();
+    }
+    state
+}
+
+fn decode_state(payload: &[u8]) -> MeshState {
+    decode_payload(payload, MeshState::from_bytes).unwrap()
+}
+
+fn ids(state: &MeshState) -> Vec<String> {
+    let mut ids: Vec<String> = state.all_entries().iter().map(|e| hex::encode(e.id().as_bytes())).collect();
+    ids.sort();
+    ids
+}
+
+// ============================================================================
+// PACKING
+// ============================================================================
+
+#[test]
+fn test_large_state_compresses_significantly() {
+    let bytes = state_with(500).to_bytes();
+
+    let packed = pack_payload(&bytes, 1024);
+
+    assert_eq!(packed[0], PAYLOAD_DEFLATE);
+    assert!(
+        packed.len() * 10 < bytes.len() * 7,
+        "{} bytes packed to {}",
+        bytes.len(),
+        packed.len()
+    );
+}
+
+#[test]
+fn test_payload_below_threshold_is_sent_raw() {
+    let bytes = state_with(1).to_bytes();
+
+    let packed = pack_payload(&bytes, bytes.len() + 1);
+
+    assert_eq!(packed[0], PAYLOAD_RAW);
+    assert_eq!(&packed[1..], &bytes[..]);
+}
+
+#[test]
+fn test_incompressible_payload_is_sent_raw() {
+    let bytes: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+
+    let packed = pack_payload(&bytes, 1);
+
+    assert!(packed.len() <= bytes.len() + 1);
+    assert_eq!(unpack_payload(&packed).unwrap().as_ref(), &bytes[..]);
+}
+
+#[test]
+fn test_disabled_compression_leaves_bytes_unheaded() {
+    let bytes = state_with(3).to_bytes();
+
+    assert_eq!(pack_payload(&bytes, 0), bytes);
+    assert_eq!(GossipConfig::default().pack_payload(&bytes), bytes);
+}
+
+#[test]
+fn test_config_threshold_controls_compression() {
+    let bytes = state_with(200).to_bytes();
+
+    let small = GossipConfig::new().with_compression(bytes.len() + 1).pack_payload(&bytes);
+    let large = GossipConfig::new().with_compression(512).pack_payload(&bytes);
+
+    assert_eq!(small[0], PAYLOAD_RAW);
+    assert_eq!(large[0], PAYLOAD_DEFLATE);
+}
+
+// ============================================================================
+// ROUND TRIP
+// ============================================================================
+
+#[test]
+fn test_state_round_trip_is_lossless_with_and_without_compression() {
+    let state = state_with(300);
+    let bytes = state.to_bytes();
+
+    for min_bytes in [0, 1, bytes.len() + 1] {
+        let restored = decode_state(&pack_payload(&bytes, min_bytes));
+
+        assert_eq!(ids(&restored), ids(&state), "min_bytes {min_bytes}");
+        assert_eq!(restored.version(), state.version());
+        assert_eq!(restored.merkle_root(), state.merkle_root());
+    }
+}
+
+#[test]
+fn test_delta_round_trip_is_lossless() {
+    let ahead = state_with(100);
+    let behind = MeshState::new(NodeId::generate());
+    let bytes = ahead.diff_against_summary(&behind.summary()).to_bytes();
+
+    let delta = decode_payload(&pack_payload(&bytes, 64), Delta::from_bytes).unwrap();
+
+    assert_eq!(delta.entries().len(), 100);
+    assert_eq!(delta.to_bytes(), bytes);
+}
+
+#[test]
+fn test_unheaded_payload_from_older_node_still_decodes() {
+    let state = state_with(5);
+
+    let restored = decode_state(&state.to_bytes());
+
+    assert_eq!(restored.iou_count(), 5);
+}
+
+#[test]
+fn test_headed_payload_is_never_retried_whole() {
+    let payload = [PAYLOAD_RAW, 1, 2, 3];
+    let attempts = RefCell::new(Vec::new());
+
+    let result: Result<(), PayloadError<&str>> = decode_payload(&payload, |bytes| {
+        attempts.borrow_mut().push(bytes.to_vec());
+        Err("undecodable")
+    });
+
+    assert!(matches!(result, Err(PayloadError::Decode("undecodable"))));
+    assert_eq!(attempts.into_inner(), vec![vec![1, 2, 3]]);
+}
+
+#[test]
+fn test_unknown_first_byte_is_decoded_unheaded() {
+    let payload = [0x01, 2, 3];
+
+    let decoded = decode_payload(&payload, |bytes| Ok::<_, ()>(bytes.to_vec())).unwrap();
+
+    assert_eq!(decoded, payload);
+}
+
+#[test]
+fn test_corrupt_compressed_payload_fails() {
+    let mut packed = pack_payload(&state_with(50).to_bytes(), 1);
+    packed.truncate(packed.len() / 2);
+
+    let result: Result<MeshState, PayloadError<MeshStateError>> = decode_payload(&packed, MeshState::from_bytes);
+
+    assert!(matches!(result, Err(PayloadError::Unpack(_))));
+    assert!(unpack_payload(&packed).is_none());
+}
+
+// ============================================================================
+// MESSAGES
+// ============================================================================
+
+fn full_response(count: u64) -> Message {
+    let state = state_with(count);
+    let entries: Vec<IOUEntry> = state.all_entries().into_iter().cloned().collect();
+    Message::SyncResponse(SyncResponse::new(state.node_id().clone(), state.version(), entries))
+}
+
+/// A deflated message claiming to expand to `len` zero bytes
+fn bomb(len: usize) -> Vec<u8> {
+    let mut bytes = vec![PAYLOAD_DEFLATE];
+    bytes.extend(miniz_oxide::deflate::compress_to_vec(&vec![0u8; len], 9));
+    bytes
+}
+
+#[test]
+fn test_thousand_entry_response_shrinks_by_half() {
+    let msg = full_response(1000);
+    let plain = msg.to_bytes();
+
+    let packed = msg.to_wire_bytes(Compression::Deflate, 1024);
+
+    assert_eq!(packed[0], PAYLOAD_DEFLATE);
+    assert!(packed.len() * 2 <= plain.len(), "{} bytes packed to {}", plain.len(), packed.len());
+    match Message::from_wire_bytes(&packed).unwrap() {
+        Message::SyncResponse(response) => assert_eq!(response.entries().len(), 1000),
+        other => panic!("unexpected message: {:?}", other.message_type()),
+    }
+}
+
+#[test]
+fn test_small_or_uncompressed_messages_stay_readable() {
+    let msg = full_response(2);
+    let plain = msg.to_bytes();
+
+    let off = msg.to_wire_bytes(Compression::Deflate, 0);
+    let below = msg.to_wire_bytes(Compression::Deflate, plain.len() + 1);
+    let none = msg.to_wire_bytes(Compression::None, 1);
+
+    assert_eq!(off, plain);
+    assert_eq!(below[0], PAYLOAD_RAW);
+    assert_eq!(none[0], PAYLOAD_RAW);
+    for bytes in [off, below, none] {
+        assert!(matches!(Message::from_wire_bytes(&bytes).unwrap(), Message::SyncResponse(_)));
+    }
+}
+
+#[test]
+fn test_over_inflating_message_is_rejected() {
+    let bytes = bomb(MAX_MESSAGE_BYTES + 1);
+    assert!(bytes.len() < 64 * 1024);
+
+    assert!(matches!(Message::from_wire_bytes(&bytes), Err(ProtocolError::PayloadTooLarge)));
+    assert!(matches!(
+        Message::from_wire_bytes_with_limit(&bomb(4096), 1024),
+        Err(ProtocolError::PayloadTooLarge)
+    ));
+}
+
+#[test]
+fn test_engine_compresses_only_for_peers_that_agreed() {
+    let id = NodeId::generate();
+    let mut engine = GossipEngine::new(id.clone(), MeshState::new(id), GossipConfig::new().with_compression(256).with_unsigned_messages(true));
+    let (modern, legacy) = (NodeId::generate(), NodeId::generate());
+    let hello = Hello::new(modern.clone(), Features::SUPPORTED);
+    engine.process_message_from(&modern, Message::Hello(hello)).unwrap();
+    let msg = full_response(50);
+
+    assert_eq!(engine.encode_message_for(&modern, &msg)[0], PAYLOAD_DEFLATE);
+    assert_eq!(engine.encode_message_for(&legacy, &msg), msg.to_bytes());
+}
+
+#[test]
+fn test_engine_merges_compressed_response_and_rejects_bomb() {
+    let id = NodeId::generate();
+    let config = GossipConfig::new().with_max_message_bytes(1024 * 1024).with_unsigned_messages(true);
+    let mut engine = GossipEngine::new(id.clone(), MeshState::new(id), config);
+    let peer = NodeId::generate();
+
+    engine
+        .process_bytes_from(&peer, &full_response(30).to_wire_bytes(Compression::Deflate, 1))
+        .unwrap();
+    let result = engine.process_bytes_from(&peer, &bomb(2 * 1024 * 1024));
+
+    assert_eq!(engine.state().iou_count(), 30);
+    assert!(matches!(result, Err(GossipError::Protocol(ProtocolError::PayloadTooLarge))));
+}
@@ -0,0 +1,362 @@
+// Peer Discovery Tests
+// Tests sharing known peers on sync, adding announced peers as discovered,
+// refusing unusable or self addresses, and auto-dialing discovered peers
+
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    GossipConfig, GossipEngine, GossipEvent, KnownPeer, Message, PeerAnnouncement, PeerEvent,
+    PeerRegistry, PeerState,
+};
+use p2pmesh::transport::PeerAddress;
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HOUR: u64 = 3600 * 1000;
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+fn addr(last_octet: u8) -> SocketAddr {
+    format!("10.4.0.{}:7000", last_octet).parse().unwrap()
+}
+
+/// A node listening on `addr`, syncing every round and dialing up to `max_dial` peers
+fn node(address: SocketAddr, max_dial: usize) -> (GossipEngine, PeerRegistry) {
+    let id = NodeId::generate();
+    let config = GossipConfig::new()
+        .with_heartbeat_interval(0)
+        .with_anti_entropy_interval(1)
+        .with_local_address(address)
+        .with_max_auto_dial(max_dial)
+        .with_unsigned_messages(true);
+    let registry = PeerRegistry::new(id.clone());
+    (GossipEngine::new(id.clone(), MeshState::new(id), config), registry)
+}
+
+fn node_id(engine: &GossipEngine) -> NodeId {
+    engine.state().node_id().clone()
+}
+
+fn connect(registry: &mut PeerRegistry, peer: &NodeId, address: SocketAddr) {
+    registry.add_peer(peer.clone(), address).unwrap();
+    registry.get_peer_mut(peer).unwrap().set_state(PeerState::Connected);
+}
+
+fn state_of(registry: &PeerRegistry, peer: &NodeId) -> Option<PeerState> {
+    registry.get_peer(peer).map(|info| info.state())
+}
+
+fn announcements(events: &[GossipEvent]) -> Vec<PeerAnnouncement> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            GossipEvent::Forward(Message::PeerAnnouncement(announcement)) => Some(announcement.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn discovered(events: &[GossipEvent]) -> Vec<NodeId> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            GossipEvent::PeerDiscovered(peer) => Some(peer.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// An announcement from a stranger listing `peers`
+fn listing(peers: Vec<KnownPeer>) -> PeerAnnouncement {
+    PeerAnnouncement::new(NodeId::generate(), 0).with_known_peers(peers)
+}
+
+// ============================================================================
+// BOOTSTRAP THROUGH A PEER
+// ============================================================================
+
+#[test]
+fn test_node_learns_of_peer_through_intermediary() {
+    let (mut a, mut registry_a) = node(addr(1), 1);
+    let (mut b, mut registry_b) = node(addr(2), 1);
+    let (mut c, mut registry_c) = node(addr(3), 1);
+    let (a_id, b_id, c_id) = (node_id(&a), node_id(&b), node_id(&c));
+
+    // A and C only know B; B knows both
+    connect(&mut registry_a, &b_id, addr(2));
+    connect(&mut registry_b, &a_id, addr(1));
+    connect(&mut registry_b, &c_id, addr(3));
+    connect(&mut registry_c, &b_id, addr(2));
+    let now = now_ms();
+
+    // C syncs with B, sending its announcement along
+    let to_b = c.tick_with_registry_at(&mut registry_c, now);
+    assert!(to_b.iter().all(|(peer, _)| *peer == b_id));
+    assert!(to_b.iter().any(|(_, msg)| matches!(msg, Message::PeerAnnouncement(_))));
+
+    // B answers the sync request with its own, listing A
+    let mut replies = Vec::new();
+    for (_, msg) in to_b {
+        replies.extend(b.process_message_with_registry_at(&mut registry_b, msg, now).unwrap());
+    }
+    let shared = announcements(&replies);
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].node_id(), &b_id);
+
+    let events = c
+        .process_message_with_registry_at(&mut registry_c, Message::PeerAnnouncement(shared[0].clone()), now)
+        .unwrap();
+    assert_eq!(discovered(&events), vec![a_id.clone()]);
+    assert_eq!(state_of(&registry_c, &a_id), Some(PeerState::Discovered));
+    assert_eq!(registry_c.get_peer(&a_id).unwrap().address(), &addr(1));
+
+    // C's next round dials A, and A's reply connects it
+    let dials: Vec<Message> = c
+        .tick_with_registry_at(&mut registry_c, now + 2000)
+        .into_iter()
+        .filter(|(peer, _)| *peer == a_id)
+        .map(|(_, msg)| msg)
+        .collect();
+    assert!(matches!(dials.as_slice(), [Message::Hello(_)]));
+    assert_eq!(state_of(&registry_c, &a_id), Some(PeerState::Connecting));
+
+    let reply = a.process_message_with_registry_at(&mut registry_a, dials[0].clone(), now).unwrap();
+    let Some(GossipEvent::Forward(hello)) = reply.into_iter().next() else {
+        panic!("expected a Hello reply");
+    };
+    c.process_message_with_registry_at(&mut registry_c, hello, now).unwrap();
+
+    assert_eq!(state_of(&registry_c, &a_id), Some(PeerState::Connected));
+    assert_eq!(c.stats().peers_discovered, 1);
+    assert_eq!(c.stats().peers_dialed, 1);
+}
+
+#[test]
+fn test_announcement_leaves_out_recipient() {
+    let (b, mut registry_b) = node(addr(2), 0);
+    let (a_id, c_id) = (NodeId::generate(), NodeId::generate());
+    connect(&mut registry_b, &a_id, addr(1));
+    connect(&mut registry_b, &c_id, addr(3));
+
+    let announcement = b.peer_announcement_at(&registry_b, &c_id, now_ms());
+
+    let listed: Vec<&NodeId> = announcement.known_peers().iter().map(|p| p.node_id()).collect();
+    assert_eq!(listed, vec![&a_id]);
+    assert_eq!(announcement.address().map(String::as_str), Some("10.4.0.2"));
+    assert_eq!(announcement.port(), 7000);
+}
+
+#[test]
+fn test_sharing_disabled_sends_no_announcement() {
+    let id = NodeId::generate();
+    let config = GossipConfig::new()
+        .with_heartbeat_interval(0)
+        .with_anti_entropy_interval(1)
+        .with_peer_sharing(0, 3600)
+        .with_unsigned_messages(true);
+    let mut engine = GossipEngine::new(id.clone(), MeshState::new(id.clone()), config);
+    let mut registry = PeerRegistry::new(id);
+    connect(&mut registry, &NodeId::generate(), addr(9));
+
+    let outgoing = engine.tick_with_registry_at(&mut registry, now_ms());
+
+    assert!(outgoing.iter().any(|(_, msg)| matches!(msg, Message::SyncRequest(_))));
+    assert!(!outgoing.iter().any(|(_, msg)| matches!(msg, Message::PeerAnnouncement(_))));
+}
+
+// ============================================================================
+// WHAT GETS SHARED
+// ============================================================================
+
+#[test]
+fn test_shareable_peers_are_known_good_and_recent() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let good = NodeId::generate();
+    let disconnected = NodeId::generate();
+    let disliked = NodeId::generate();
+    let banned = NodeId::generate();
+    let hearsay = NodeId::generate();
+    connect(&mut registry, &good, addr(1));
+    connect(&mut registry, &disconnected, addr(2));
+    registry.get_peer_mut(&disconnected).unwrap().set_state(PeerState::Disconnected);
+    connect(&mut registry, &disliked, addr(3));
+    registry.record_event(&disliked, PeerEvent::Timeout).unwrap();
+    connect(&mut registry, &banned, addr(4));
+    registry.get_peer_mut(&banned).unwrap().set_state(PeerState::Banned);
+    registry.add_discovered(hearsay, addr(5)).unwrap();
+    registry.record_event(&good, PeerEvent::SyncSucceeded).unwrap();
+    let now = now_ms();
+
+    let shared: Vec<&NodeId> = registry.shareable_peers_at(10, now, HOUR).iter().map(|p| p.node_id()).collect();
+
+    // Best reputation first
+    assert_eq!(shared, vec![&good, &disconnected]);
+    assert_eq!(registry.shareable_peers_at(1, now, HOUR).len(), 1);
+    // Nobody heard from within the last hour
+    assert!(registry.shareable_peers_at(10, now + 2 * HOUR, HOUR).is_empty());
+}
+
+// ============================================================================
+// VALIDATING ANNOUNCED PEERS
+// ============================================================================
+
+#[test]
+fn test_self_is_never_discovered() {
+    let (mut engine, mut registry) = node(addr(1), 0);
+    let me = node_id(&engine);
+    let announcement = listing(vec![
+        KnownPeer::new(me, PeerAddress::from_socket_addr(addr(9))),
+        KnownPeer::new(NodeId::generate(), PeerAddress::from_socket_addr(addr(1))),
+    ]);
+
+    let events = engine.handle_peer_announcement(&mut registry, &announcement);
+
+    assert!(events.is_empty());
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_incompatible_addresses_are_refused() {
+    let (mut engine, mut registry) = node(addr(1), 0);
+    let unusable = [
+        PeerAddress::ble("AA:BB:CC:DD:EE:FF"),
+        PeerAddress::lora(7, 868_000_000),
+        PeerAddress::ws("ws://10.4.0.8:7000"),
+        PeerAddress::tcp("node.example", 7000),
+        PeerAddress::tcp("10.4.0.8", 0),
+        PeerAddress::tcp("0.0.0.0", 7000),
+        PeerAddress::tcp("224.0.0.1", 7000),
+        // We listen on IPv4 only
+        PeerAddress::tcp("::1", 7000),
+    ];
+    let peers = unusable.into_iter().map(|address| KnownPeer::new(NodeId::generate(), address)).collect();
+
+    let events = engine.handle_peer_announcement(&mut registry, &listing(peers));
+
+    assert!(events.is_empty());
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_ipv6_peer_accepted_by_ipv6_node() {
+    let local: SocketAddr = "[fd00::1]:7000".parse().unwrap();
+    let (mut engine, mut registry) = node(local, 0);
+    let peer = NodeId::generate();
+    let address: SocketAddr = "[fd00::2]:7000".parse().unwrap();
+
+    let events = engine.handle_peer_announcement(
+        &mut registry,
+        &listing(vec![KnownPeer::new(peer.clone(), PeerAddress::from_socket_addr(address))]),
+    );
+
+    assert_eq!(discovered(&events), vec![peer.clone()]);
+    assert_eq!(registry.get_peer(&peer).unwrap().address(), &address);
+}
+
+#[test]
+fn test_known_peers_are_not_overwritten() {
+    let (mut engine, mut registry) = node(addr(1), 0);
+    let banned = NodeId::generate();
+    connect(&mut registry, &banned, addr(2));
+    registry.get_peer_mut(&banned).unwrap().set_state(PeerState::Banned);
+
+    let events = engine.handle_peer_announcement(
+        &mut registry,
+        &listing(vec![KnownPeer::new(banned.clone(), PeerAddress::from_socket_addr(addr(66)))]),
+    );
+
+    assert!(events.is_empty());
+    assert_eq!(state_of(&registry, &banned), Some(PeerState::Banned));
+    assert_eq!(registry.get_peer(&banned).unwrap().address(), &addr(2));
+}
+
+#[test]
+fn test_announcer_itself_is_discovered() {
+    let (mut engine, mut registry) = node(addr(1), 0);
+    let announcer = NodeId::generate();
+    let announcement = PeerAnnouncement::new(announcer.clone(), 7000).with_address("10.4.0.5".to_string());
+
+    let events = engine
+        .process_message_with_registry_at(&mut registry, Message::PeerAnnouncement(announcement), now_ms())
+        .unwrap();
+
+    // Handled rather than forwarded on
+    assert_eq!(discovered(&events), vec![announcer.clone()]);
+    assert_eq!(events.len(), 1);
+    assert_eq!(registry.stats().discovered_peers, 1);
+}
+
+// ============================================================================
+// AUTO-DIAL
+// ============================================================================
+
+fn with_discovered(engine: &mut GossipEngine, registry: &mut PeerRegistry, count: u8) -> Vec<NodeId> {
+    let peers: Vec<NodeId> = (0..count).map(|_| NodeId::generate()).collect();
+    let known = peers
+        .iter()
+        .enumerate()
+        .map(|(i, peer)| KnownPeer::new(peer.clone(), PeerAddress::from_socket_addr(addr(10 + i as u8))))
+        .collect();
+    engine.handle_peer_announcement(registry, &listing(known));
+    peers
+}
+
+fn dialed(outgoing: &[(NodeId, Message)]) -> usize {
+    outgoing.iter().filter(|(_, msg)| matches!(msg, Message::Hello(_))).count()
+}
+
+#[test]
+fn test_auto_dial_disabled_by_default() {
+    let id = NodeId::generate();
+    let config = GossipConfig::default().with_unsigned_messages(true);
+    let mut engine = GossipEngine::new(id.clone(), MeshState::new(id.clone()), config);
+    let mut registry = PeerRegistry::new(id);
+    with_discovered(&mut engine, &mut registry, 2);
+
+    let outgoing = engine.tick_with_registry_at(&mut registry, now_ms());
+
+    assert_eq!(dialed(&outgoing), 0);
+    assert_eq!(registry.stats().discovered_peers, 2);
+}
+
+#[test]
+fn test_auto_dial_limits_peers_being_dialed() {
+    let (mut engine, mut registry) = node(addr(1), 2);
+    let peers = with_discovered(&mut engine, &mut registry, 3);
+    let now = now_ms();
+
+    assert_eq!(dialed(&engine.tick_with_registry_at(&mut registry, now)), 2);
+    assert_eq!(dialed(&engine.tick_with_registry_at(&mut registry, now + 2000)), 0);
+
+    // One connects, freeing a slot for the last
+    let connecting = registry.peers_by_state(PeerState::Connecting)[0].node_id().clone();
+    registry.get_peer_mut(&connecting).unwrap().set_state(PeerState::Connected);
+    assert_eq!(dialed(&engine.tick_with_registry_at(&mut registry, now + 4000)), 1);
+    assert!(peers.iter().all(|peer| state_of(&registry, peer) != Some(PeerState::Discovered)));
+    assert_eq!(engine.stats().peers_dialed, 3);
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_known_peers_survive_serialization() {
+    let peer = KnownPeer::new(NodeId::generate(), PeerAddress::tcp("10.4.0.1", 7000));
+    let msg = Message::PeerAnnouncement(listing(vec![peer.clone()]));
+
+    match Message::from_bytes(&msg.to_bytes()).unwrap() {
+        Message::PeerAnnouncement(restored) => assert_eq!(restored.known_peers(), &[peer][..]),
+        other => panic!("expected a peer announcement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_peer_address_socket_round_trip() {
+    for address in ["10.4.0.1:7000", "[fd00::1]:7000"] {
+        let socket: SocketAddr = address.parse().unwrap();
+        assert_eq!(PeerAddress::from_socket_addr(socket).socket_addr(), Some(socket));
+    }
+    assert_eq!(PeerAddress::ble("AA:BB:CC:DD:EE:FF").socket_addr(), None);
+}
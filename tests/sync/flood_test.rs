@@ -0,0 +1,175 @@
+// Flood Protection Tests
+// Tests that a burst from one peer engages its rate limit and then a throttle,
+// costs it reputation, and leaves other peers unaffected
+
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    GossipConfig, GossipEngine, GossipEvent, Message, PeerRegistry, PeerState, SyncRequest,
+};
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SEC: u64 = 1000;
+const RATE: u64 = 10;
+const THROTTLE_AFTER: u32 = 20;
+const THROTTLE_SECS: u64 = 30;
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+/// 10 messages/s per peer, throttled for 30s after 20 drops in a row
+fn engine() -> GossipEngine {
+    let id = NodeId::generate();
+    let config = GossipConfig::new()
+        .with_rate_limit(RATE, 0)
+        .with_throttle(THROTTLE_AFTER, THROTTLE_SECS)
+        .with_heartbeat_interval(0)
+        .with_anti_entropy_interval(0)
+        .with_unsigned_messages(true);
+    GossipEngine::new(id.clone(), MeshState::new(id), config)
+}
+
+fn connected_peer(registry: &mut PeerRegistry, last_octet: u8) -> NodeId {
+    let peer_id = NodeId::generate();
+    let addr: SocketAddr = format!("10.3.0.{}:8080", last_octet).parse().unwrap();
+    registry.add_peer(peer_id.clone(), addr).unwrap();
+    registry.get_peer_mut(&peer_id).unwrap().set_state(PeerState::Connected);
+    peer_id
+}
+
+/// Outcome of a burst: messages answered and the events from dropped ones
+struct Burst {
+    answered: usize,
+    throttled: Vec<NodeId>,
+}
+
+fn burst(engine: &mut GossipEngine, peer: &NodeId, count: usize, now: u64) -> Burst {
+    let mut result = Burst { answered: 0, throttled: Vec::new() };
+    for _ in 0..count {
+        let request = Message::SyncRequest(SyncRequest::new(peer.clone(), 0));
+        for event in engine.process_message_from_at(peer, request, now).unwrap() {
+            match event {
+                GossipEvent::PeerThrottled(throttled) => result.throttled.push(throttled),
+                _ => result.answered += 1,
+            }
+        }
+    }
+    result
+}
+
+// ============================================================================
+// BURSTS
+// ============================================================================
+
+#[test]
+fn test_burst_engages_limit_for_sender_only() {
+    let mut engine = engine();
+    let (flooder, neighbour) = (NodeId::generate(), NodeId::generate());
+
+    let flood = burst(&mut engine, &flooder, 15, 0);
+    let normal = burst(&mut engine, &neighbour, 10, 0);
+
+    assert_eq!(flood.answered, RATE as usize);
+    assert!(flood.throttled.is_empty());
+    assert_eq!(normal.answered, 10);
+    assert_eq!(engine.stats().rate_limited, 5);
+}
+
+#[test]
+fn test_sustained_flood_throttles_once() {
+    let mut engine = engine();
+    let flooder = NodeId::generate();
+
+    let flood = burst(&mut engine, &flooder, RATE as usize + 100, 0);
+
+    assert_eq!(flood.answered, RATE as usize);
+    assert_eq!(flood.throttled, vec![flooder]);
+    assert_eq!(engine.stats().peers_throttled, 1);
+    assert_eq!(engine.stats().rate_limited, 100);
+}
+
+#[test]
+fn test_throttled_peer_is_ignored_until_cooldown_ends() {
+    let mut engine = engine();
+    let (flooder, neighbour) = (NodeId::generate(), NodeId::generate());
+    burst(&mut engine, &flooder, RATE as usize + THROTTLE_AFTER as usize, 0);
+
+    // Its bucket has long refilled, but the throttle holds
+    assert_eq!(burst(&mut engine, &flooder, 1, 10 * SEC).answered, 0);
+    assert_eq!(burst(&mut engine, &neighbour, 1, 10 * SEC).answered, 1);
+
+    assert_eq!(burst(&mut engine, &flooder, 1, THROTTLE_SECS * SEC).answered, 1);
+}
+
+#[test]
+fn test_admitted_message_resets_drop_count() {
+    let mut engine = engine();
+    let peer = NodeId::generate();
+    let mut now = 0;
+    burst(&mut engine, &peer, RATE as usize, now);
+
+    // Sending twice the rate: every other message gets a refilled token
+    for _ in 0..4 * THROTTLE_AFTER {
+        now += 50;
+        burst(&mut engine, &peer, 1, now);
+    }
+
+    assert_eq!(engine.stats().peers_throttled, 0);
+    assert!(engine.stats().rate_limited > THROTTLE_AFTER as u64);
+}
+
+#[test]
+fn test_zero_throttle_after_never_throttles() {
+    let id = NodeId::generate();
+    let config = GossipConfig::new()
+        .with_rate_limit(RATE, 0)
+        .with_throttle(0, THROTTLE_SECS)
+        .with_unsigned_messages(true);
+    let mut engine = GossipEngine::new(id.clone(), MeshState::new(id), config);
+    let flooder = NodeId::generate();
+
+    let flood = burst(&mut engine, &flooder, 1_000, 0);
+
+    assert!(flood.throttled.is_empty());
+    assert_eq!(burst(&mut engine, &flooder, 1, SEC).answered, 1);
+}
+
+// ============================================================================
+// REPUTATION
+// ============================================================================
+
+#[test]
+fn test_throttled_peer_loses_reputation_and_rank() {
+    let now = now_ms() + SEC;
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let flooder = connected_peer(&mut registry, 1);
+    let neighbour = connected_peer(&mut registry, 2);
+    let mut engine = engine();
+    burst(&mut engine, &flooder, RATE as usize + THROTTLE_AFTER as usize, now);
+    burst(&mut engine, &neighbour, 1, now);
+
+    engine.tick_with_registry_at(&mut registry, now);
+
+    assert_eq!(registry.get_peer(&flooder).unwrap().reputation(), -10);
+    assert_eq!(registry.get_peer(&neighbour).unwrap().reputation(), 0);
+    let ranked: Vec<&NodeId> = registry.best_peers(2).into_iter().map(|p| p.node_id()).collect();
+    assert_eq!(ranked, vec![&neighbour, &flooder]);
+
+    // Only charged once
+    engine.tick_with_registry_at(&mut registry, now);
+    assert_eq!(registry.get_peer(&flooder).unwrap().reputation(), -10);
+}
+
+#[test]
+fn test_throttle_can_quarantine_flooder() {
+    let now = now_ms() + SEC;
+    let mut registry = PeerRegistry::new(NodeId::generate()).with_quarantine(Some(-10), 60);
+    let flooder = connected_peer(&mut registry, 1);
+    let mut engine = engine();
+    burst(&mut engine, &flooder, RATE as usize + THROTTLE_AFTER as usize, now);
+
+    engine.tick_with_registry_at(&mut registry, now);
+
+    assert_eq!(registry.get_peer(&flooder).unwrap().state(), PeerState::Quarantined);
+}
@@ -29,6 +29,7 @@ fn test_gossip_config_defaults() {
     assert!(config.fanout > 0);
     assert!(config.max_hops > 0);
     assert!(config.heartbeat_interval_secs > 0);
+    assert!(config.anti_entropy_interval_secs > 0);
 }
 
 #[test]
@@ -36,11 +37,13 @@ fn test_gossip_config_custom() {
     let config = GossipConfig::new()
         .with_fanout(5)
         .with_max_hops(10)
-        .with_heartbeat_interval(30);
+        .with_heartbeat_interval(30)
+        .with_anti_entropy_interval(15);
 
     assert_eq!(config.fanout, 5);
     assert_eq!(config.max_hops, 10);
     assert_eq!(config.heartbeat_interval_secs, 30);
+    assert_eq!(config.anti_entropy_interval_secs, 15);
 }
 
 // ============================================================================
@@ -265,6 +268,25 @@ fn test_gossip_process_heartbeat() {
     assert!(events.is_empty() || !events.is_empty());
 }
 
+#[test]
+fn test_gossip_forwards_encrypted_message() {
+    let node_id = NodeId::generate();
+    let state = MeshState::new(node_id.clone());
+    let mut engine = GossipEngine::new(node_id, state, GossipConfig::default());
+
+    let bob = Keypair::generate();
+    let inner = Message::Heartbeat(p2pmesh::sync::Heartbeat::new(NodeId::generate(), 100));
+    let msg = inner.encrypt_for(&bob.public_key()).unwrap();
+
+    let events = engine.process_message(msg.clone()).unwrap();
+
+    // Opaque to the engine: relayed as-is, never acted on
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0], GossipEvent::Forward(Message::Encrypted(_))));
+    // A second copy is deduplicated
+    assert!(engine.process_message(msg).unwrap().is_empty());
+}
+
 // ============================================================================
 // GOSSIP EVENTS
 // ============================================================================
@@ -412,3 +434,456 @@ fn test_gossip_stats() {
     assert_eq!(stats.messages_processed, 0);
     assert_eq!(stats.messages_forwarded, 0);
 }
+
+// ============================================================================
+// CURSOR-BASED SYNC
+// ============================================================================
+
+fn engine_with_ious(count: u64, page_size: usize) -> GossipEngine {
+    engine_with_config(count, GossipConfig::new().with_sync_page_size(page_size))
+}
+
+fn engine_with_config(count: u64, config: GossipConfig) -> GossipEngine {
+    let node_id = NodeId::generate();
+    let mut state = MeshState::new(node_id.clone());
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    for nonce in 1..=count {
+        let iou = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(Did::from_public_key(&bob.public_key()))
+            .amount(100)
+            .nonce(nonce)
+            .build()
+            .unwrap();
+        state.add_iou(iou, &alice.public_key()).unwrap();
+    }
+    GossipEngine::new(node_id, state, config)
+}
+
+fn empty_engine() -> GossipEngine {
+    let node_id = NodeId::generate();
+    GossipEngine::new(node_id.clone(), MeshState::new(node_id), GossipConfig::default())
+}
+
+#[test]
+fn test_gossip_cursor_request_returns_bounded_page() {
+    let responder = engine_with_ious(25, 10);
+    let request = SyncRequest::new(NodeId::generate(), 0).with_since_cursor(0);
+
+    let response = responder.handle_sync_request(&request);
+
+    assert_eq!(response.entries().len(), 10);
+    assert_eq!(response.next_cursor(), Some(10));
+    assert!(response.has_more());
+}
+
+#[test]
+fn test_gossip_multi_page_sync() {
+    let responder = engine_with_ious(25, 10);
+    let peer = responder.state().node_id().clone();
+    let mut requester = empty_engine();
+
+    let mut pages = 0;
+    loop {
+        let request = requester.generate_sync_request_for(&peer);
+        let response = responder.handle_sync_request(&request);
+        pages += 1;
+
+        let events = requester.process_message(Message::SyncResponse(response)).unwrap();
+        let wants_more = events.iter().any(|e| matches!(e, GossipEvent::RequestSync(id) if *id == peer));
+        if !wants_more {
+            break;
+        }
+    }
+
+    assert_eq!(pages, 3);
+    assert_eq!(requester.state().iou_count(), 25);
+    assert_eq!(requester.sync_cursor(&peer), 25);
+}
+
+#[test]
+fn test_gossip_sync_resumes_after_interruption() {
+    let responder = engine_with_ious(25, 10);
+    let peer = responder.state().node_id().clone();
+    let mut requester = empty_engine();
+
+    // First page arrives
+    let first = responder.handle_sync_request(&requester.generate_sync_request_for(&peer));
+    requester.apply_sync_response(first).unwrap();
+    assert_eq!(requester.sync_cursor(&peer), 10);
+
+    // Second page is lost when the connection drops
+    let lost = responder.handle_sync_request(&requester.generate_sync_request_for(&peer));
+    drop(lost);
+
+    // After reconnecting, the requester picks up where it left off
+    let resumed = requester.generate_sync_request_for(&peer);
+    assert_eq!(resumed.since_cursor(), Some(10));
+
+    let mut fetched = 0;
+    let mut request = resumed;
+    loop {
+        let response = responder.handle_sync_request(&request);
+        fetched += response.entries().len();
+        let has_more = response.has_more();
+        requester.apply_sync_response(response).unwrap();
+        if !has_more {
+            break;
+        }
+        request = requester.generate_sync_request_for(&peer);
+    }
+
+    assert_eq!(fetched, 15, "only the remaining entries are re-fetched");
+    assert_eq!(requester.state().iou_count(), 25);
+}
+
+#[test]
+fn test_gossip_sync_after_completion_fetches_only_new() {
+    let mut responder = engine_with_ious(5, 10);
+    let peer = responder.state().node_id().clone();
+    let mut requester = empty_engine();
+
+    let response = responder.handle_sync_request(&requester.generate_sync_request_for(&peer));
+    assert!(!response.has_more());
+    requester.apply_sync_response(response).unwrap();
+
+    let alice = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(7)
+        .build()
+        .unwrap();
+    responder.state_mut().add_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let response = responder.handle_sync_request(&requester.generate_sync_request_for(&peer));
+
+    assert_eq!(response.entries().len(), 1);
+    assert_eq!(response.entries()[0].id(), iou.id());
+    assert_eq!(response.next_cursor(), Some(6));
+}
+
+#[test]
+fn test_gossip_restored_sync_cursor() {
+    let responder = engine_with_ious(12, 10);
+    let peer = responder.state().node_id().clone();
+    let mut requester = empty_engine();
+
+    requester.set_sync_cursor(peer.clone(), 10);
+    let response = responder.handle_sync_request(&requester.generate_sync_request_for(&peer));
+
+    assert_eq!(response.entries().len(), 2);
+    assert!(!response.has_more());
+}
+
+#[test]
+fn test_gossip_request_without_cursor_returns_everything() {
+    let responder = engine_with_ious(25, 10);
+
+    let response = responder.handle_sync_request(&SyncRequest::new(NodeId::generate(), 0));
+
+    assert_eq!(response.entries().len(), 25);
+    assert_eq!(response.next_cursor(), None);
+}
+
+// ============================================================================
+// SUMMARY-BASED SYNC
+// ============================================================================
+
+#[test]
+fn test_gossip_summary_request_returns_only_missing() {
+    let mut responder = engine_with_ious(40, 10);
+    let mut requester = empty_engine();
+    let full = responder.handle_sync_request(&SyncRequest::new(NodeId::generate(), 0));
+    requester.apply_sync_response(full).unwrap();
+
+    let alice = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(7)
+        .build()
+        .unwrap();
+    responder.state_mut().add_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let request = requester.generate_summary_sync_request();
+    assert!(request.summary().is_some());
+    let response = responder.handle_sync_request(&request);
+
+    assert!(response.entries().len() < 40);
+    assert!(response.entries().iter().any(|entry| entry.id() == iou.id()));
+    requester.apply_sync_response(response).unwrap();
+    assert_eq!(requester.state().merkle_root(), responder.state().merkle_root());
+}
+
+#[test]
+fn test_gossip_summary_request_when_in_sync_is_empty() {
+    let responder = engine_with_ious(20, 10);
+    let mut requester = empty_engine();
+    let full = responder.handle_sync_request(&SyncRequest::new(NodeId::generate(), 0));
+    requester.apply_sync_response(full).unwrap();
+
+    let response = responder.handle_sync_request(&requester.generate_summary_sync_request());
+
+    assert!(response.entries().is_empty());
+}
+
+// ============================================================================
+// GOSSIP ROUNDS
+// ============================================================================
+
+fn peer_ids(count: usize) -> Vec<NodeId> {
+    (0..count).map(|_| NodeId::generate()).collect()
+}
+
+#[test]
+fn test_tick_without_peers_sends_nothing() {
+    let mut engine = engine_with_ious(2, 10);
+
+    assert!(engine.tick_at(&[], 0).is_empty());
+    // Our own ID is never a target
+    let own = engine.state().node_id().clone();
+    assert!(engine.tick_at(&[own], 0).is_empty());
+}
+
+#[test]
+fn test_tick_sends_announcements_to_fanout_peers() {
+    let config = GossipConfig::new()
+        .with_fanout(2)
+        .with_heartbeat_interval(0)
+        .with_anti_entropy_interval(0);
+    let mut engine = GossipEngine::new(NodeId::generate(), MeshState::new(NodeId::generate()), config);
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(100)
+        .build()
+        .unwrap();
+    engine.announce_iou(iou, &alice.public_key());
+
+    let peers = peer_ids(5);
+    let outgoing = engine.tick_at(&peers, 0);
+
+    assert_eq!(outgoing.len(), 2);
+    assert_ne!(outgoing[0].0, outgoing[1].0);
+    assert!(outgoing.iter().all(|(peer, msg)| {
+        peers.contains(peer) && matches!(msg, Message::IOUAnnouncement(_))
+    }));
+    assert_eq!(engine.pending_announcements(), 0);
+    assert!(engine.tick_at(&peers, 1).is_empty());
+}
+
+#[test]
+fn test_tick_fanout_capped_by_peer_count() {
+    let config = GossipConfig::new().with_fanout(10).with_anti_entropy_interval(0);
+    let mut engine = GossipEngine::new(NodeId::generate(), MeshState::new(NodeId::generate()), config);
+
+    let outgoing = engine.tick_at(&peer_ids(3), 0);
+
+    assert_eq!(outgoing.len(), 3);
+    assert!(outgoing.iter().all(|(_, msg)| matches!(msg, Message::Heartbeat(_))));
+}
+
+#[test]
+fn test_tick_heartbeat_interval() {
+    let config = GossipConfig::new()
+        .with_fanout(1)
+        .with_heartbeat_interval(30)
+        .with_anti_entropy_interval(0);
+    let mut engine = GossipEngine::new(NodeId::generate(), MeshState::new(NodeId::generate()), config);
+    let peers = peer_ids(3);
+
+    assert_eq!(engine.tick_at(&peers, 1_000).len(), 1);
+    assert!(engine.tick_at(&peers, 30_999).is_empty());
+    assert_eq!(engine.tick_at(&peers, 31_000).len(), 1);
+}
+
+#[test]
+fn test_tick_anti_entropy_push_pull() {
+    let config = GossipConfig::new()
+        .with_heartbeat_interval(0)
+        .with_anti_entropy_interval(60);
+    let mut engine = engine_with_config(3, config);
+    let peers = peer_ids(4);
+
+    let outgoing = engine.tick_at(&peers, 0);
+
+    assert_eq!(outgoing.len(), 2);
+    assert_eq!(outgoing[0].0, outgoing[1].0);
+    let Message::SyncResponse(push) = &outgoing[0].1 else {
+        panic!("expected a state push");
+    };
+    assert_eq!(push.entries().len(), 3);
+    assert!(matches!(outgoing[1].1, Message::SyncRequest(_)));
+    assert_eq!(engine.stats().anti_entropy_rounds, 1);
+
+    assert!(engine.tick_at(&peers, 59_999).is_empty());
+    assert_eq!(engine.tick_at(&peers, 60_000).len(), 2);
+}
+
+#[test]
+fn test_retried_sync_request_is_served_again() {
+    let mut responder = engine_with_ious(3, 10);
+    let request = Message::SyncRequest(SyncRequest::new(NodeId::generate(), 0));
+
+    // The first response was lost; the identical retry still gets an answer
+    assert_eq!(responder.process_message(request.clone()).unwrap().len(), 1);
+    assert_eq!(responder.process_message(request).unwrap().len(), 1);
+}
+
+// ============================================================================
+// VERSION VECTORS
+// ============================================================================
+
+fn anti_entropy_only() -> GossipConfig {
+    GossipConfig::new()
+        .with_heartbeat_interval(0)
+        .with_anti_entropy_interval(60)
+}
+
+/// An engine whose state holds `other`'s state plus `extra` IOUs of its own
+fn engine_ahead_of(other: &GossipEngine, extra: u64) -> GossipEngine {
+    let mut engine = engine_with_config(extra, anti_entropy_only());
+    engine.state_mut().merge(other.state());
+    engine
+}
+
+#[test]
+fn test_heartbeat_carries_version_vector() {
+    let sender = engine_with_ious(3, 10);
+    let mut receiver = engine_with_ious(0, 10);
+    let sender_id = sender.state().node_id().clone();
+
+    let heartbeat = sender.generate_heartbeat();
+    assert_eq!(heartbeat.clock(), sender.state().clock());
+
+    receiver.process_message(Message::Heartbeat(heartbeat)).unwrap();
+    assert_eq!(receiver.peer_clock(&sender_id), Some(sender.state().clock()));
+}
+
+#[test]
+fn test_push_skipped_for_peer_already_ahead() {
+    let mut engine = engine_with_config(3, anti_entropy_only());
+    let peer = engine_ahead_of(&engine, 1);
+    let peer_id = peer.state().node_id().clone();
+    engine.process_message(Message::Heartbeat(peer.generate_heartbeat())).unwrap();
+
+    let outgoing = engine.tick_at(&[peer_id], 0);
+
+    // Only the pull goes out
+    assert_eq!(outgoing.len(), 1);
+    assert!(matches!(outgoing[0].1, Message::SyncRequest(_)));
+    assert_eq!(engine.stats().pushes_skipped, 1);
+}
+
+#[test]
+fn test_push_sent_to_concurrent_peer() {
+    let mut engine = engine_with_config(3, anti_entropy_only());
+    let peer = engine_with_config(2, anti_entropy_only());
+    let peer_id = peer.state().node_id().clone();
+    engine.process_message(Message::Heartbeat(peer.generate_heartbeat())).unwrap();
+
+    let outgoing = engine.tick_at(&[peer_id], 0);
+
+    assert_eq!(outgoing.len(), 2);
+    let Message::SyncResponse(push) = &outgoing[0].1 else {
+        panic!("expected a state push");
+    };
+    assert_eq!(push.clock(), Some(engine.state().clock()));
+    assert_eq!(engine.stats().pushes_skipped, 0);
+}
+
+#[test]
+fn test_full_sync_response_merges_version_vector() {
+    let responder = engine_with_ious(3, 10);
+    let mut requester = engine_with_ious(0, 10);
+
+    let response = responder.handle_sync_request(&requester.generate_sync_request());
+    requester.apply_sync_response(response).unwrap();
+
+    assert_eq!(requester.state().clock(), responder.state().clock());
+    assert_eq!(
+        requester.state().compare(responder.state()),
+        p2pmesh::ledger::CausalRelation::Equal
+    );
+}
+
+#[test]
+fn test_paged_sync_response_leaves_version_vector() {
+    let responder = engine_with_ious(3, 2);
+    let mut requester = engine_with_ious(0, 2);
+    let responder_id = responder.state().node_id().clone();
+
+    let response = responder.handle_sync_request(&requester.generate_sync_request_for(&responder_id));
+    assert!(response.clock().is_none());
+    requester.apply_sync_response(response).unwrap();
+
+    assert_eq!(requester.state().iou_count(), 2);
+    assert!(requester.state().clock().is_empty());
+}
+
+// ============================================================================
+// EXPIRED IOUS
+// ============================================================================
+
+/// An IOU that expired a minute ago, as if accepted before then
+fn expired_iou(alice: &Keypair, bob: &Keypair) -> p2pmesh::iou::SignedIOU {
+    IOUBuilder::new()
+        .sender(alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(100)
+        .timestamp(1_000)
+        .expiry(1_060)
+        .build()
+        .unwrap()
+}
+
+fn engine_holding_expired_iou() -> GossipEngine {
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+    let id = NodeId::generate();
+    let mut state = MeshState::new(id.clone());
+    state.add_replicated_iou(expired_iou(&alice, &bob), &alice.public_key()).unwrap();
+    GossipEngine::new(id, state, GossipConfig::default())
+}
+
+#[test]
+fn test_full_sync_replicates_expired_iou() {
+    let ahead = engine_holding_expired_iou();
+    let id = NodeId::generate();
+    let mut behind = GossipEngine::new(id.clone(), MeshState::new(id), GossipConfig::default());
+
+    let response = ahead.handle_sync_request(&behind.generate_sync_request());
+    let result = behind.apply_sync_response(response).unwrap();
+
+    assert_eq!(result.new_entries, 1);
+    assert_eq!(behind.state().merkle_root(), ahead.state().merkle_root());
+}
+
+#[test]
+fn test_summary_sync_with_expired_iou_converges() {
+    let ahead = engine_holding_expired_iou();
+    let id = NodeId::generate();
+    let mut behind = GossipEngine::new(id.clone(), MeshState::new(id), GossipConfig::default());
+
+    let response = ahead.handle_sync_request(&behind.generate_summary_sync_request());
+    assert_eq!(behind.apply_sync_response(response).unwrap().new_entries, 1);
+
+    // Converged: the next round has nothing left to send
+    let response = ahead.handle_sync_request(&behind.generate_summary_sync_request());
+    assert!(response.entries().is_empty());
+    assert_eq!(behind.state().merkle_root(), ahead.state().merkle_root());
+}
+
+#[test]
+fn test_expired_iou_announcement_is_refused() {
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+    let id = NodeId::generate();
+    let mut engine = GossipEngine::new(id.clone(), MeshState::new(id), GossipConfig::default());
+    let announcement = IOUAnnouncement::new(expired_iou(&alice, &bob), alice.public_key());
+
+    assert!(engine.handle_iou_announcement(announcement).is_err());
+    assert_eq!(engine.state().iou_count(), 0);
+}
@@ -0,0 +1,337 @@
+// Handshake Tests
+// Tests Hello version and feature negotiation, refusing messages that use
+// unnegotiated features, skipping unknown message types, and a v1 node syncing
+// with a v2 node
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::IOUBuilder;
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    Features, GossipConfig, GossipEngine, GossipError, GossipEvent, Heartbeat, Hello, Message,
+    ProtocolError, PROTOCOL_VERSION,
+};
+
+/// An engine without a key, accepting unsigned messages from peers like it
+fn engine(config: GossipConfig) -> GossipEngine {
+    let id = NodeId::generate();
+    GossipEngine::new(id.clone(), MeshState::new(id), config.with_unsigned_messages(true))
+}
+
+fn node_id(engine: &GossipEngine) -> NodeId {
+    engine.state().node_id().clone()
+}
+
+/// A node speaking protocol version 1, without summary sync
+struct V1 {
+    engine: GossipEngine,
+}
+
+impl V1 {
+    fn new() -> Self {
+        Self::with_config(GossipConfig::new())
+    }
+
+    fn with_config(config: GossipConfig) -> Self {
+        Self { engine: engine(config.with_features(Features::ENCRYPTION)) }
+    }
+
+    fn hello(&self) -> Hello {
+        self.engine.generate_hello().with_version(1)
+    }
+}
+
+/// Deliver `from`'s Hello to `to`, and the reply back
+fn handshake(from: &mut GossipEngine, from_hello: Hello, to: &mut GossipEngine) {
+    let from_id = node_id(from);
+    let events = to.process_message_from(&from_id, Message::Hello(from_hello)).unwrap();
+    let to_id = node_id(to);
+    for event in events {
+        if let GossipEvent::Forward(reply) = event {
+            assert!(from.process_message_from(&to_id, reply).unwrap().is_empty());
+        }
+    }
+}
+
+fn add_iou(engine: &mut GossipEngine, sender: &Keypair) {
+    let iou = IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(10)
+        .build()
+        .unwrap();
+    engine.state_mut().add_iou(iou, &sender.public_key()).unwrap();
+}
+
+/// Bytes of a message type from a newer protocol version
+fn future_message_bytes() -> Vec<u8> {
+    let mut bytes = postcard::to_allocvec(&42u32).unwrap();
+    bytes.extend_from_slice(&[1, 2, 3, 4]);
+    bytes
+}
+
+// ============================================================================
+// NEGOTIATION
+// ============================================================================
+
+#[test]
+fn test_hello_is_answered_once_and_both_sides_agree() {
+    let mut alice = engine(GossipConfig::new());
+    let mut bob = engine(GossipConfig::new());
+    let hello = alice.generate_hello();
+
+    handshake(&mut alice, hello, &mut bob);
+
+    for (engine, peer) in [(&alice, node_id(&bob)), (&bob, node_id(&alice))] {
+        let protocol = engine.peer_protocol(&peer).unwrap();
+        assert_eq!(protocol.version(), PROTOCOL_VERSION);
+        assert_eq!(protocol.features(), Features::SUPPORTED);
+    }
+}
+
+#[test]
+fn test_agreed_features_are_the_intersection() {
+    let mut v1 = V1::new();
+    let mut v2 = engine(GossipConfig::new());
+    let hello = v1.hello();
+
+    handshake(&mut v1.engine, hello, &mut v2);
+
+    let v1_id = node_id(&v1.engine);
+    let protocol = v2.peer_protocol(&v1_id).unwrap();
+    assert_eq!(protocol.version(), 1);
+    assert_eq!(protocol.features(), Features::ENCRYPTION);
+    // The reply announces v2, but v1 only keeps what it supports
+    assert_eq!(v1.engine.peer_features(&node_id(&v2)), Features::ENCRYPTION);
+}
+
+#[test]
+fn test_newer_peer_is_downgraded_to_our_version() {
+    let mut node = engine(GossipConfig::new());
+    let future = NodeId::generate();
+    let unknown_feature = Features::from_bits(1 << 20);
+    let hello = Hello::new(future.clone(), Features::SUPPORTED | unknown_feature)
+        .with_version(PROTOCOL_VERSION + 1);
+
+    node.process_message_from(&future, Message::Hello(hello)).unwrap();
+
+    let protocol = node.peer_protocol(&future).unwrap();
+    assert_eq!(protocol.version(), PROTOCOL_VERSION);
+    assert_eq!(protocol.features(), Features::SUPPORTED);
+}
+
+#[test]
+fn test_hello_below_minimum_version_is_rejected() {
+    let mut node = engine(GossipConfig::new());
+    let peer = NodeId::generate();
+    let hello = Hello::new(peer.clone(), Features::SUPPORTED).with_version(0);
+
+    let result = node.process_message_from(&peer, Message::Hello(hello));
+
+    assert!(matches!(result, Err(GossipError::UnsupportedVersion(0))));
+    assert!(node.peer_protocol(&peer).is_none());
+}
+
+#[test]
+fn test_peer_without_hello_is_treated_as_legacy() {
+    let node = engine(GossipConfig::new());
+    let peer = NodeId::generate();
+
+    assert!(node.peer_protocol(&peer).is_none());
+    assert_eq!(node.peer_features(&peer), Features::LEGACY);
+}
+
+#[test]
+fn test_repeated_hello_renegotiates() {
+    let mut node = engine(GossipConfig::new());
+    let peer = NodeId::generate();
+    node.process_message_from(&peer, Message::Hello(Hello::new(peer.clone(), Features::SUPPORTED)))
+        .unwrap();
+
+    // The peer restarted with summary sync switched off
+    let events = node
+        .process_message_from(&peer, Message::Hello(Hello::new(peer.clone(), Features::ENCRYPTION)))
+        .unwrap();
+
+    assert_eq!(node.peer_features(&peer), Features::ENCRYPTION);
+    assert_eq!(events.len(), 1);
+}
+
+// ============================================================================
+// REFUSAL
+// ============================================================================
+
+#[test]
+fn test_summary_request_refused_without_summary_sync() {
+    let mut v1 = V1::new();
+    let mut v2 = engine(GossipConfig::new());
+    let hello = v1.hello();
+    handshake(&mut v1.engine, hello, &mut v2);
+    let v1_id = node_id(&v1.engine);
+
+    let summary = Message::SyncRequest(v1.engine.generate_summary_sync_request());
+    let plain = Message::SyncRequest(v1.engine.generate_sync_request());
+
+    assert!(matches!(
+        v2.process_message_from(&v1_id, summary),
+        Err(GossipError::FeatureNotNegotiated(f)) if f == Features::SUMMARY_SYNC
+    ));
+    assert_eq!(v2.process_message_from(&v1_id, plain).unwrap().len(), 1);
+    assert_eq!(v2.stats().messages_refused, 1);
+}
+
+#[test]
+fn test_encrypted_message_refused_without_encryption() {
+    let mut node = engine(GossipConfig::new());
+    let peer = NodeId::generate();
+    node.process_message_from(&peer, Message::Hello(Hello::new(peer.clone(), Features::SUMMARY_SYNC)))
+        .unwrap();
+    let recipient = Keypair::generate();
+    let inner = Message::Heartbeat(Heartbeat::new(peer.clone(), 1));
+
+    let sealed = inner.encrypt_for(&recipient.public_key()).unwrap();
+
+    assert!(matches!(
+        node.process_message_from(&peer, sealed),
+        Err(GossipError::FeatureNotNegotiated(f)) if f == Features::ENCRYPTION
+    ));
+}
+
+#[test]
+fn test_legacy_peer_may_still_send_summary_requests() {
+    let mut node = engine(GossipConfig::new());
+    let legacy = engine(GossipConfig::new());
+
+    let request = Message::SyncRequest(legacy.generate_summary_sync_request());
+
+    assert_eq!(node.process_message_from(&node_id(&legacy), request).unwrap().len(), 1);
+}
+
+// ============================================================================
+// UNKNOWN MESSAGE TYPES
+// ============================================================================
+
+#[test]
+fn test_unknown_message_type_decodes_as_unknown() {
+    assert!(matches!(
+        Message::from_bytes(&future_message_bytes()),
+        Err(ProtocolError::UnknownMessageType(42))
+    ));
+    assert!(matches!(Message::from_bytes(&[0xff]), Err(ProtocolError::DeserializationFailed)));
+}
+
+#[test]
+fn test_unknown_message_type_is_skipped() {
+    let mut node = engine(GossipConfig::new());
+    let peer = NodeId::generate();
+
+    let events = node.process_bytes_from(&peer, &future_message_bytes()).unwrap();
+
+    assert!(events.is_empty());
+    assert_eq!(node.stats().messages_skipped, 1);
+    // The peer can carry on with known messages
+    let heartbeat = Message::Heartbeat(Heartbeat::new(peer.clone(), 0)).to_bytes();
+    assert!(node.process_bytes_from(&peer, &heartbeat).is_ok());
+}
+
+#[test]
+fn test_malformed_known_message_is_an_error() {
+    let mut node = engine(GossipConfig::new());
+    let mut bytes = Message::Heartbeat(Heartbeat::new(NodeId::generate(), 3)).to_bytes();
+    bytes.truncate(4);
+
+    let result = node.process_bytes_from(&NodeId::generate(), &bytes);
+
+    assert!(matches!(result, Err(GossipError::Protocol(ProtocolError::DeserializationFailed))));
+}
+
+// ============================================================================
+// MIXED-VERSION MESH
+// ============================================================================
+
+#[test]
+fn test_v2_sends_v1_plain_sync_requests() {
+    let mut v1 = V1::new();
+    let config = GossipConfig::new().with_heartbeat_interval(0).with_anti_entropy_interval(10);
+    let mut v2 = engine(config);
+    let hello = v1.hello();
+    handshake(&mut v1.engine, hello, &mut v2);
+    let v1_id = node_id(&v1.engine);
+    let other_v2 = NodeId::generate();
+
+    let requests: Vec<Message> = v2
+        .tick_at(std::slice::from_ref(&v1_id), 0)
+        .into_iter()
+        .map(|(_, msg)| msg)
+        .filter(|msg| matches!(msg, Message::SyncRequest(_)))
+        .collect();
+
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].required_features().is_empty());
+    assert!(v2.generate_sync_request_to(&other_v2).summary().is_some());
+}
+
+#[test]
+fn test_v1_and_v2_nodes_converge() {
+    let config = GossipConfig::new().with_heartbeat_interval(0).with_anti_entropy_interval(10);
+    let mut v1 = V1::with_config(config.clone());
+    let mut v2 = engine(config);
+    let hello = v1.hello();
+    handshake(&mut v1.engine, hello, &mut v2);
+    let (v1_id, v2_id) = (node_id(&v1.engine), node_id(&v2));
+    add_iou(&mut v1.engine, &Keypair::generate());
+    add_iou(&mut v2, &Keypair::generate());
+    add_iou(&mut v2, &Keypair::generate());
+
+    for now in [0, 10_000] {
+        let from_v2 = v2.tick_at(std::slice::from_ref(&v1_id), now);
+        let from_v1 = v1.engine.tick_at(std::slice::from_ref(&v2_id), now);
+        for (_, msg) in from_v2 {
+            for event in v1.engine.process_message_from(&v2_id, msg).unwrap() {
+                if let GossipEvent::Forward(reply) = event {
+                    v2.process_message_from(&v1_id, reply).unwrap();
+                }
+            }
+        }
+        for (_, msg) in from_v1 {
+            for event in v2.process_message_from(&v1_id, msg).unwrap() {
+                if let GossipEvent::Forward(reply) = event {
+                    v1.engine.process_message_from(&v2_id, reply).unwrap();
+                }
+            }
+        }
+    }
+
+    assert_eq!(v1.engine.state().iou_count(), 3);
+    assert_eq!(v2.state().iou_count(), 3);
+    assert_eq!(v2.stats().messages_refused, 0);
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_hello_round_trips() {
+    let id = NodeId::generate();
+    let hello = Hello::new(id.clone(), Features::COMPRESSION | Features::COMPACT_IOU).as_reply();
+
+    match Message::from_bytes(&Message::Hello(hello).to_bytes()).unwrap() {
+        Message::Hello(restored) => {
+            assert_eq!(restored.node_id(), &id);
+            assert_eq!(restored.version(), PROTOCOL_VERSION);
+            assert_eq!(restored.features(), Features::COMPRESSION | Features::COMPACT_IOU);
+            assert!(restored.is_reply());
+        }
+        other => panic!("unexpected message: {:?}", other.message_type()),
+    }
+}
+
+#[test]
+fn test_features_display() {
+    assert_eq!(Features::NONE.to_string(), "none");
+    assert_eq!(Features::LEGACY.to_string(), "encryption|summary-sync");
+    assert_eq!(
+        (Features::COMPRESSION | Features::from_bits(1 << 8)).to_string(),
+        "compression|0x100"
+    );
+}
@@ -0,0 +1,281 @@
+// Liveness Tests
+// Tests heartbeat tracking, marking silent peers unreachable, and recovering
+// them with an immediate anti-entropy sync, all against a fake clock
+
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    GossipConfig, GossipEngine, GossipEvent, Heartbeat, Message, PeerRegistry, PeerState,
+};
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SEC: u64 = 1000;
+const INTERVAL_SECS: u64 = 10;
+const MAX_MISSED: u32 = 3;
+/// How long a peer may stay silent before it is lost
+const TIMEOUT: u64 = INTERVAL_SECS * MAX_MISSED as u64 * SEC;
+
+/// A clock that only moves when told to, starting just after peers are added
+struct FakeClock(u64);
+
+impl FakeClock {
+    fn new() -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        Self(now + SEC)
+    }
+
+    fn now(&self) -> u64 {
+        self.0
+    }
+
+    fn advance(&mut self, ms: u64) -> u64 {
+        self.0 += ms;
+        self.0
+    }
+}
+
+fn connected_peer(registry: &mut PeerRegistry, last_octet: u8) -> NodeId {
+    let peer_id = NodeId::generate();
+    let addr: SocketAddr = format!("10.2.0.{}:8080", last_octet).parse().unwrap();
+    registry.add_peer(peer_id.clone(), addr).unwrap();
+    registry.get_peer_mut(&peer_id).unwrap().set_state(PeerState::Connected);
+    peer_id
+}
+
+/// An engine heartbeating every 10s and losing peers after 3 missed heartbeats
+fn engine() -> GossipEngine {
+    let id = NodeId::generate();
+    let config = GossipConfig::new()
+        .with_heartbeat_interval(INTERVAL_SECS)
+        .with_max_missed(MAX_MISSED)
+        .with_anti_entropy_interval(60)
+        .with_unsigned_messages(true);
+    GossipEngine::new(id.clone(), MeshState::new(id), config)
+}
+
+fn heartbeat_from(peer: &NodeId) -> Message {
+    Message::Heartbeat(Heartbeat::new(peer.clone(), 0))
+}
+
+fn state_of(registry: &PeerRegistry, peer: &NodeId) -> PeerState {
+    registry.get_peer(peer).unwrap().state()
+}
+
+// ============================================================================
+// REGISTRY
+// ============================================================================
+
+#[test]
+fn test_heartbeat_is_recorded() {
+    let clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let peer = connected_peer(&mut registry, 1);
+
+    let recovered = registry.record_heartbeat_at(&peer, clock.now()).unwrap();
+
+    assert!(!recovered);
+    assert_eq!(registry.get_peer(&peer).unwrap().last_heartbeat(), Some(clock.now()));
+    assert_eq!(registry.get_peer(&peer).unwrap().last_seen(), clock.now());
+}
+
+#[test]
+fn test_heartbeat_from_unknown_peer_fails() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+
+    assert!(registry.record_heartbeat_at(&NodeId::generate(), 0).is_err());
+}
+
+#[test]
+fn test_silent_peer_is_marked_unreachable() {
+    let mut clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let silent = connected_peer(&mut registry, 1);
+    let chatty = connected_peer(&mut registry, 2);
+    registry.record_heartbeat_at(&silent, clock.now()).unwrap();
+
+    let now = clock.advance(TIMEOUT);
+    registry.record_heartbeat_at(&chatty, now).unwrap();
+    assert!(registry.mark_unreachable_at(now, TIMEOUT).is_empty());
+
+    let now = clock.advance(1);
+    assert_eq!(registry.mark_unreachable_at(now, TIMEOUT), vec![silent.clone()]);
+    assert_eq!(state_of(&registry, &silent), PeerState::Unreachable);
+    assert_eq!(state_of(&registry, &chatty), PeerState::Connected);
+    assert_eq!(registry.stats().unreachable_peers, 1);
+}
+
+#[test]
+fn test_inactive_peers_are_not_marked() {
+    let mut clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let banned = connected_peer(&mut registry, 1);
+    let disconnected = connected_peer(&mut registry, 2);
+    registry.get_peer_mut(&banned).unwrap().set_state(PeerState::Banned);
+    registry.get_peer_mut(&disconnected).unwrap().set_state(PeerState::Disconnected);
+
+    let now = clock.advance(10 * TIMEOUT);
+
+    assert!(registry.mark_unreachable_at(now, TIMEOUT).is_empty());
+    assert_eq!(state_of(&registry, &banned), PeerState::Banned);
+}
+
+#[test]
+fn test_heartbeat_reconnects_unreachable_peer() {
+    let mut clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let peer = connected_peer(&mut registry, 1);
+    registry.mark_unreachable_at(clock.advance(TIMEOUT + 1), TIMEOUT);
+
+    assert!(registry.record_heartbeat_at(&peer, clock.advance(SEC)).unwrap());
+    assert_eq!(state_of(&registry, &peer), PeerState::Connected);
+    assert!(!registry.record_heartbeat_at(&peer, clock.advance(SEC)).unwrap());
+}
+
+#[test]
+fn test_last_heartbeat_survives_serialization() {
+    let clock = FakeClock::new();
+    let my_node_id = NodeId::generate();
+    let mut registry = PeerRegistry::new(my_node_id.clone());
+    let peer = connected_peer(&mut registry, 1);
+    registry.record_heartbeat_at(&peer, clock.now()).unwrap();
+
+    let restored = PeerRegistry::from_bytes(&registry.to_bytes(), my_node_id).unwrap();
+
+    assert_eq!(restored.get_peer(&peer).unwrap().last_heartbeat(), Some(clock.now()));
+}
+
+#[test]
+fn test_registry_from_before_heartbeats_still_decodes() {
+    let my_node_id = NodeId::generate();
+    let mut registry = PeerRegistry::new(my_node_id.clone());
+    let peer = connected_peer(&mut registry, 1);
+
+    // Drop the trailing empty last_heartbeat (one byte)
+    let mut bytes = registry.to_bytes();
+    bytes.pop();
+
+    let restored = PeerRegistry::from_bytes(&bytes, my_node_id).unwrap();
+    assert_eq!(restored.get_peer(&peer).unwrap().last_heartbeat(), None);
+    assert_eq!(state_of(&restored, &peer), PeerState::Connected);
+}
+
+// ============================================================================
+// ENGINE
+// ============================================================================
+
+#[test]
+fn test_engine_heartbeats_once_per_interval() {
+    let mut clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    connected_peer(&mut registry, 1);
+    let mut engine = engine();
+    let heartbeats = |outgoing: &[(NodeId, Message)]| {
+        outgoing.iter().filter(|(_, msg)| matches!(msg, Message::Heartbeat(_))).count()
+    };
+
+    assert_eq!(heartbeats(&engine.tick_with_registry_at(&mut registry, clock.now())), 1);
+    assert_eq!(heartbeats(&engine.tick_with_registry_at(&mut registry, clock.advance(5 * SEC))), 0);
+    assert_eq!(heartbeats(&engine.tick_with_registry_at(&mut registry, clock.advance(5 * SEC))), 1);
+}
+
+#[test]
+fn test_peer_lost_after_max_missed_heartbeats() {
+    let mut clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let peer = connected_peer(&mut registry, 1);
+    let mut engine = engine();
+    engine.process_message_with_registry_at(&mut registry, heartbeat_from(&peer), clock.now()).unwrap();
+
+    // Two missed heartbeats are tolerated
+    assert!(engine.check_liveness_at(&mut registry, clock.advance(2 * INTERVAL_SECS * SEC)).is_empty());
+
+    let events = engine.check_liveness_at(&mut registry, clock.advance(INTERVAL_SECS * SEC + 1));
+    assert!(matches!(events.as_slice(), [GossipEvent::PeerLost(lost)] if *lost == peer));
+    assert_eq!(state_of(&registry, &peer), PeerState::Unreachable);
+    assert_eq!(engine.stats().peers_lost, 1);
+
+    // Already lost, so not reported again
+    assert!(engine.check_liveness_at(&mut registry, clock.advance(TIMEOUT)).is_empty());
+}
+
+#[test]
+fn test_liveness_disabled_without_heartbeats_or_threshold() {
+    for config in [
+        GossipConfig::new().with_heartbeat_interval(0),
+        GossipConfig::new().with_max_missed(0),
+    ] {
+        let mut clock = FakeClock::new();
+        let mut registry = PeerRegistry::new(NodeId::generate());
+        let peer = connected_peer(&mut registry, 1);
+        let id = NodeId::generate();
+        let mut engine = GossipEngine::new(id.clone(), MeshState::new(id), config.with_unsigned_messages(true));
+
+        assert!(engine.check_liveness_at(&mut registry, clock.advance(3600 * SEC)).is_empty());
+        assert_eq!(state_of(&registry, &peer), PeerState::Connected);
+    }
+}
+
+#[test]
+fn test_unreachable_peer_is_left_out_of_rounds() {
+    let mut clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let lost = connected_peer(&mut registry, 1);
+    let alive = connected_peer(&mut registry, 2);
+    let mut engine = engine();
+    let now = clock.advance(TIMEOUT);
+    engine.process_message_with_registry_at(&mut registry, heartbeat_from(&alive), now).unwrap();
+    engine.check_liveness_at(&mut registry, clock.advance(1));
+
+    let outgoing = engine.tick_with_registry_at(&mut registry, clock.now());
+
+    assert!(!outgoing.is_empty());
+    assert!(outgoing.iter().all(|(peer, _)| *peer != lost));
+}
+
+#[test]
+fn test_recovered_peer_is_reported_and_synced_immediately() {
+    let mut clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let peer = connected_peer(&mut registry, 1);
+    let mut engine = engine();
+    // The scheduled anti-entropy round happens now, so the next isn't due for a minute
+    engine.tick_with_registry_at(&mut registry, clock.now());
+    engine.check_liveness_at(&mut registry, clock.advance(TIMEOUT + 1));
+
+    let events = engine
+        .process_message_with_registry_at(&mut registry, heartbeat_from(&peer), clock.advance(SEC))
+        .unwrap();
+
+    assert!(matches!(events.first(), Some(GossipEvent::PeerRecovered(recovered)) if *recovered == peer));
+    assert_eq!(state_of(&registry, &peer), PeerState::Connected);
+    assert_eq!(engine.stats().peers_recovered, 1);
+
+    let outgoing = engine.tick_with_registry_at(&mut registry, clock.advance(SEC));
+    assert!(outgoing
+        .iter()
+        .any(|(to, msg)| *to == peer && matches!(msg, Message::SyncRequest(_))));
+    assert_eq!(engine.stats().anti_entropy_rounds, 2);
+
+    // Only once
+    let outgoing = engine.tick_with_registry_at(&mut registry, clock.advance(SEC));
+    assert!(outgoing.iter().all(|(_, msg)| !matches!(msg, Message::SyncRequest(_))));
+}
+
+#[test]
+fn test_heartbeat_from_live_peer_reports_nothing_new() {
+    let clock = FakeClock::new();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let peer = connected_peer(&mut registry, 1);
+    let stranger = NodeId::generate();
+    let mut engine = engine();
+
+    let events = engine.process_message_with_registry_at(&mut registry, heartbeat_from(&peer), clock.now()).unwrap();
+    assert!(events.is_empty());
+
+    // Unknown to the registry, but still processed
+    let events = engine
+        .process_message_with_registry_at(&mut registry, heartbeat_from(&stranger), clock.now())
+        .unwrap();
+    assert!(events.is_empty());
+    assert_eq!(engine.stats().messages_processed, 2);
+}
@@ -3,3 +3,16 @@
 mod peer_test;
 mod protocol_test;
 mod gossip_test;
+mod convergence_test;
+mod adaptive_schedule_test;
+mod quarantine_test;
+mod compression_test;
+mod handshake_test;
+mod liveness_test;
+mod rate_limit_test;
+mod flood_test;
+mod relay_test;
+mod discovery_test;
+mod priority_test;
+mod signing_test;
+mod subscription_test;
@@ -1,8 +1,11 @@
 // Peer Tests
 // Tests for peer management and registry
 
+use p2pmesh::identity::{Did, Keypair, SignedDidDocument};
 use p2pmesh::ledger::NodeId;
-use p2pmesh::sync::{PeerInfo, PeerRegistry, PeerState, PeerError};
+use p2pmesh::sync::{
+    PeerError, PeerEvent, PeerInfo, PeerRegistry, PeerState, DEFAULT_BAN_THRESHOLD, MIN_REPUTATION,
+};
 use std::net::SocketAddr;
 
 // ============================================================================
@@ -80,7 +83,7 @@ fn test_peer_info_rtt_tracking() {
     // Should have an average RTT
     assert!(peer.average_rtt().is_some());
     let avg = peer.average_rtt().unwrap();
-    assert!(avg >= 50 && avg <= 100);
+    assert!((50..=100).contains(&avg));
 }
 
 // ============================================================================
@@ -337,3 +340,262 @@ fn test_peer_registry_stats() {
     assert_eq!(stats.total_peers, 2);
     assert_eq!(stats.connected_peers, 1);
 }
+
+// ============================================================================
+// PEER REPUTATION
+// ============================================================================
+
+fn connected_peer(registry: &mut PeerRegistry, last_octet: u8) -> NodeId {
+    let peer_id = NodeId::generate();
+    let addr: SocketAddr = format!("192.168.1.{}:8080", last_octet).parse().unwrap();
+    registry.add_peer(peer_id.clone(), addr).unwrap();
+    registry.get_peer_mut(&peer_id).unwrap().set_state(PeerState::Connected);
+    peer_id
+}
+
+#[test]
+fn test_peer_info_reputation_follows_events() {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let mut peer = PeerInfo::new(NodeId::generate(), addr);
+    assert_eq!(peer.reputation(), 0);
+
+    peer.record_event(PeerEvent::SyncSucceeded);
+    peer.record_event(PeerEvent::SyncSucceeded);
+    assert_eq!(peer.reputation(), 2);
+
+    peer.record_event(PeerEvent::Timeout);
+    assert_eq!(peer.reputation(), 0);
+
+    peer.record_event(PeerEvent::InvalidIOU);
+    assert_eq!(peer.reputation(), -10);
+}
+
+#[test]
+fn test_peer_info_reputation_is_clamped() {
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let mut peer = PeerInfo::new(NodeId::generate(), addr);
+
+    for _ in 0..50 {
+        peer.record_event(PeerEvent::InvalidIOU);
+    }
+    assert_eq!(peer.reputation(), MIN_REPUTATION);
+}
+
+#[test]
+fn test_best_peers_orders_by_reputation() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let reliable = connected_peer(&mut registry, 1);
+    let flaky = connected_peer(&mut registry, 2);
+    let average = connected_peer(&mut registry, 3);
+
+    for _ in 0..5 {
+        registry.record_event(&reliable, PeerEvent::SyncSucceeded).unwrap();
+    }
+    registry.record_event(&average, PeerEvent::SyncSucceeded).unwrap();
+    registry.record_event(&flaky, PeerEvent::SyncSucceeded).unwrap();
+    registry.record_event(&flaky, PeerEvent::Timeout).unwrap();
+
+    let best: Vec<&NodeId> = registry.best_peers(3).iter().map(|p| p.node_id()).collect();
+    assert_eq!(best, vec![&reliable, &average, &flaky]);
+
+    let top: Vec<&NodeId> = registry.best_peers(1).iter().map(|p| p.node_id()).collect();
+    assert_eq!(top, vec![&reliable]);
+}
+
+#[test]
+fn test_best_peers_skips_inactive_peers() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let active = connected_peer(&mut registry, 1);
+    let offline = connected_peer(&mut registry, 2);
+
+    for _ in 0..3 {
+        registry.record_event(&offline, PeerEvent::SyncSucceeded).unwrap();
+    }
+    registry.get_peer_mut(&offline).unwrap().set_state(PeerState::Disconnected);
+
+    let best = registry.best_peers(5);
+    assert_eq!(best.len(), 1);
+    assert_eq!(best[0].node_id(), &active);
+}
+
+#[test]
+fn test_best_peers_breaks_ties_by_rtt() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let slow = connected_peer(&mut registry, 1);
+    let fast = connected_peer(&mut registry, 2);
+
+    registry.get_peer_mut(&slow).unwrap().record_rtt(200);
+    registry.get_peer_mut(&fast).unwrap().record_rtt(20);
+
+    let best: Vec<&NodeId> = registry.best_peers(2).iter().map(|p| p.node_id()).collect();
+    assert_eq!(best, vec![&fast, &slow]);
+}
+
+#[test]
+fn test_misbehaving_peer_is_banned() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let good = connected_peer(&mut registry, 1);
+    let malicious = connected_peer(&mut registry, 2);
+    assert_eq!(registry.ban_threshold(), Some(DEFAULT_BAN_THRESHOLD));
+
+    registry.record_event(&good, PeerEvent::Timeout).unwrap();
+    registry.record_event(&good, PeerEvent::SyncSucceeded).unwrap();
+
+    for _ in 0..4 {
+        let state = registry.record_event(&malicious, PeerEvent::InvalidIOU).unwrap();
+        assert_eq!(state, PeerState::Connected);
+    }
+    let state = registry.record_event(&malicious, PeerEvent::InvalidIOU).unwrap();
+    assert_eq!(state, PeerState::Banned);
+
+    assert_eq!(registry.get_peer(&good).unwrap().state(), PeerState::Connected);
+    let best: Vec<&NodeId> = registry.best_peers(5).iter().map(|p| p.node_id()).collect();
+    assert_eq!(best, vec![&good]);
+    assert_eq!(registry.stats().banned_peers, 1);
+}
+
+#[test]
+fn test_custom_ban_threshold() {
+    let mut registry = PeerRegistry::new(NodeId::generate()).with_ban_threshold(Some(-4));
+    let peer = connected_peer(&mut registry, 1);
+
+    registry.record_event(&peer, PeerEvent::Timeout).unwrap();
+    let state = registry.record_event(&peer, PeerEvent::Timeout).unwrap();
+    assert_eq!(state, PeerState::Banned);
+}
+
+#[test]
+fn test_banning_can_be_disabled() {
+    let mut registry = PeerRegistry::new(NodeId::generate()).with_ban_threshold(None);
+    let peer = connected_peer(&mut registry, 1);
+
+    for _ in 0..20 {
+        registry.record_event(&peer, PeerEvent::InvalidIOU).unwrap();
+    }
+    assert_eq!(registry.get_peer(&peer).unwrap().state(), PeerState::Connected);
+    assert_eq!(registry.get_peer(&peer).unwrap().reputation(), MIN_REPUTATION);
+}
+
+#[test]
+fn test_record_event_unknown_peer() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let result = registry.record_event(&NodeId::generate(), PeerEvent::SyncSucceeded);
+    assert!(matches!(result, Err(PeerError::PeerNotFound)));
+}
+
+#[test]
+fn test_reputation_survives_serialization() {
+    let my_node_id = NodeId::generate();
+    let mut registry = PeerRegistry::new(my_node_id.clone());
+    let peer = connected_peer(&mut registry, 1);
+    registry.record_event(&peer, PeerEvent::SyncSucceeded).unwrap();
+    registry.record_event(&peer, PeerEvent::SyncSucceeded).unwrap();
+
+    let restored = PeerRegistry::from_bytes(&registry.to_bytes(), my_node_id).unwrap();
+    assert_eq!(restored.get_peer(&peer).unwrap().reputation(), 2);
+}
+
+// ============================================================================
+// DID DOCUMENTS
+// ============================================================================
+
+fn signed_document(keypair: &Keypair, endpoint: &str, updated_at: u64, expires_at: Option<u64>) -> SignedDidDocument {
+    Did::from_public_key(&keypair.public_key())
+        .to_document()
+        .with_service("MeshTransport", endpoint)
+        .sign(keypair, updated_at, expires_at)
+        .unwrap()
+}
+
+#[test]
+fn test_register_document_routes_did() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let keypair = Keypair::generate();
+    let did = Did::from_public_key(&keypair.public_key());
+
+    let stored = registry
+        .register_document_at(signed_document(&keypair, "tcp://10.0.0.1:9000", 10, Some(200)), 100)
+        .unwrap();
+
+    assert!(stored);
+    let peer = registry.peer_for_did(&did).expect("DID should route to a peer");
+    assert_eq!(peer.node_id(), &NodeId::from_public_key(&keypair.public_key()));
+    assert_eq!(peer.address(), &"10.0.0.1:9000".parse::<SocketAddr>().unwrap());
+    assert_eq!(registry.document(&did).unwrap().updated_at(), 10);
+}
+
+#[test]
+fn test_register_document_without_tcp_endpoint() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let keypair = Keypair::generate();
+    let did = Did::from_public_key(&keypair.public_key());
+
+    registry
+        .register_document_at(signed_document(&keypair, "lora://0x42@915MHz", 10, None), 100)
+        .unwrap();
+
+    assert!(registry.document(&did).is_some());
+    assert!(registry.peer_for_did(&did).is_none());
+}
+
+#[test]
+fn test_register_document_rejects_bad_signature() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let keypair = Keypair::generate();
+    let signed = signed_document(&keypair, "tcp://10.0.0.1:9000", 10, None);
+
+    // Same document and signature, claiming a later update.
+    // Postcard tail: updated_at (1 byte), expires_at None (1), signature length (1) + 64
+    let mut bytes = signed.to_bytes();
+    let pos = bytes.len() - 64 - 3;
+    bytes[pos] = 11;
+    let forged = SignedDidDocument::from_bytes(&bytes).unwrap();
+    assert_eq!(forged.updated_at(), 11);
+
+    let result = registry.register_document_at(forged, 100);
+
+    assert!(matches!(result, Err(PeerError::InvalidDocument)));
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_register_document_rejects_expired() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let keypair = Keypair::generate();
+
+    let result = registry.register_document_at(signed_document(&keypair, "tcp://10.0.0.1:9000", 10, Some(100)), 100);
+
+    assert!(matches!(result, Err(PeerError::DocumentExpired)));
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_register_document_newer_supersedes_older() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let keypair = Keypair::generate();
+    let did = Did::from_public_key(&keypair.public_key());
+
+    registry.register_document_at(signed_document(&keypair, "tcp://10.0.0.1:9000", 10, None), 100).unwrap();
+    let newer = registry
+        .register_document_at(signed_document(&keypair, "tcp://10.0.0.2:9000", 20, None), 100)
+        .unwrap();
+    let older = registry
+        .register_document_at(signed_document(&keypair, "tcp://10.0.0.3:9000", 15, None), 100)
+        .unwrap();
+
+    assert!(newer);
+    assert!(!older);
+    assert_eq!(registry.document(&did).unwrap().updated_at(), 20);
+    assert_eq!(registry.peer_for_did(&did).unwrap().address(), &"10.0.0.2:9000".parse::<SocketAddr>().unwrap());
+    assert_eq!(registry.peer_count(), 1);
+}
+
+#[test]
+fn test_register_own_document_fails() {
+    let keypair = Keypair::generate();
+    let mut registry = PeerRegistry::new(NodeId::from_public_key(&keypair.public_key()));
+
+    let result = registry.register_document_at(signed_document(&keypair, "tcp://10.0.0.1:9000", 10, None), 100);
+
+    assert!(matches!(result, Err(PeerError::CannotAddSelf)));
+}
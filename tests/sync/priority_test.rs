@@ -0,0 +1,288 @@
+// Outbound Priority Tests
+// Tests the two-level outbound queue: urgent messages drain before bulk ones,
+// large sync responses are chunked, and a payment overtakes a state transfer on
+// a slow link
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    GossipConfig, GossipEngine, GossipError, GossipEvent, Message, Priority, SyncResponse,
+};
+
+fn engine(config: GossipConfig) -> GossipEngine {
+    let id = NodeId::generate();
+    GossipEngine::new(id.clone(), MeshState::new(id), config)
+}
+
+fn iou(sender: &Keypair, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(10)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+/// An engine holding `count` IOUs from one sender
+fn engine_with_ious(config: GossipConfig, count: u64) -> GossipEngine {
+    let alice = Keypair::generate();
+    let mut engine = engine(config);
+    for nonce in 1..=count {
+        engine.state_mut().add_iou(iou(&alice, nonce), &alice.public_key()).unwrap();
+    }
+    engine
+}
+
+/// `from`'s whole state, as it would answer a sync request from `to`
+fn full_sync(from: &GossipEngine, to: &GossipEngine) -> Message {
+    Message::SyncResponse(from.handle_sync_request(&to.generate_sync_request()))
+}
+
+fn heartbeat(engine: &GossipEngine) -> Message {
+    Message::Heartbeat(engine.generate_heartbeat())
+}
+
+fn is_sync_response(msg: &Message) -> bool {
+    matches!(msg, Message::SyncResponse(_))
+}
+
+/// A link that moves `bytes_per_tick` bytes a tick, one whole message at a time
+struct SlowLink {
+    bytes_per_tick: usize,
+    /// Message being transmitted and its bytes still to go
+    in_flight: Option<(Message, usize)>,
+}
+
+impl SlowLink {
+    fn new(bytes_per_tick: usize) -> Self {
+        Self { bytes_per_tick, in_flight: None }
+    }
+
+    /// Transmit for one tick, returning the messages fully delivered
+    fn tick(&mut self, sender: &mut GossipEngine) -> Vec<Message> {
+        let mut budget = self.bytes_per_tick;
+        let mut delivered = Vec::new();
+        loop {
+            if self.in_flight.is_none() {
+                let Some((_, msg)) = sender.pop_outbound() else { break };
+                let len = msg.to_bytes().len();
+                self.in_flight = Some((msg, len));
+            }
+            let (_, remaining) = self.in_flight.as_mut().unwrap();
+            if *remaining > budget {
+                *remaining -= budget;
+                break;
+            }
+            budget -= *remaining;
+            delivered.push(self.in_flight.take().unwrap().0);
+        }
+        delivered
+    }
+}
+
+/// Ticks until a payment sent mid-sync, and until the sync, complete at the receiver
+struct Arrival {
+    payment: usize,
+    sync: usize,
+}
+
+/// Alice's node starts sending Bob its 128 IOUs, then pays Bob one tick later
+fn pay_during_sync(chunk_entries: usize) -> Arrival {
+    let config = GossipConfig::new().with_sync_chunk_entries(chunk_entries);
+    let mut alice_node = engine_with_ious(config, 128);
+    let mut bob_node = engine(GossipConfig::new());
+    let bob_id = bob_node.state().node_id().clone();
+    let mut link = SlowLink::new(2_000);
+
+    let sync = full_sync(&alice_node, &bob_node);
+    alice_node.queue_outbound(bob_id.clone(), sync).unwrap();
+
+    let mut arrival = Arrival { payment: 0, sync: 0 };
+    for tick in 1..=1_000 {
+        if tick == 2 {
+            let alice = Keypair::generate();
+            alice_node.announce_iou(iou(&alice, 1), &alice.public_key());
+            for msg in alice_node.collect_outgoing_messages() {
+                alice_node.queue_outbound(bob_id.clone(), msg).unwrap();
+            }
+        }
+        for msg in link.tick(&mut alice_node) {
+            for event in bob_node.process_message(msg).unwrap() {
+                if matches!(event, GossipEvent::NewIOU(_)) {
+                    arrival.payment = tick;
+                }
+            }
+        }
+        if arrival.sync == 0 && bob_node.state().iou_count() >= 128 + usize::from(arrival.payment > 0) {
+            arrival.sync = tick;
+        }
+        if arrival.payment > 0 && arrival.sync > 0 {
+            break;
+        }
+    }
+    arrival
+}
+
+// ============================================================================
+// SLOW LINK
+// ============================================================================
+
+#[test]
+fn test_payment_overtakes_bulk_sync_on_slow_link() {
+    let arrival = pay_during_sync(16);
+
+    assert!(arrival.payment > 0 && arrival.sync > 0);
+    assert!(
+        arrival.payment < arrival.sync,
+        "payment at tick {} should beat the sync at tick {}",
+        arrival.payment,
+        arrival.sync
+    );
+}
+
+#[test]
+fn test_unchunked_sync_holds_payment_back() {
+    // One response carrying the whole state goes out whole, payment or not
+    let arrival = pay_during_sync(0);
+
+    assert!(arrival.payment >= arrival.sync);
+}
+
+// ============================================================================
+// TWO-LEVEL QUEUE
+// ============================================================================
+
+#[test]
+fn test_urgent_drains_before_bulk() {
+    let mut node = engine_with_ious(GossipConfig::new(), 3);
+    let peer = engine(GossipConfig::new());
+    let peer_id = peer.state().node_id().clone();
+
+    node.queue_outbound(peer_id.clone(), full_sync(&node, &peer)).unwrap();
+    node.queue_outbound(peer_id.clone(), heartbeat(&node)).unwrap();
+
+    assert!(matches!(node.pop_outbound().unwrap().1, Message::Heartbeat(_)));
+    assert!(is_sync_response(&node.pop_outbound().unwrap().1));
+    assert!(node.pop_outbound().is_none());
+}
+
+#[test]
+fn test_each_level_stays_in_order() {
+    let mut node = engine(GossipConfig::new());
+    let (first, second) = (NodeId::generate(), NodeId::generate());
+
+    node.queue_outbound(first.clone(), heartbeat(&node)).unwrap();
+    node.queue_outbound(second.clone(), heartbeat(&node)).unwrap();
+
+    assert_eq!(node.pop_outbound().unwrap().0, first);
+    assert_eq!(node.pop_outbound().unwrap().0, second);
+}
+
+#[test]
+fn test_queue_depths_in_stats() {
+    let mut node = engine_with_ious(GossipConfig::new().with_sync_chunk_entries(4), 10);
+    let peer = engine(GossipConfig::new());
+    let peer_id = peer.state().node_id().clone();
+
+    node.queue_outbound(peer_id.clone(), full_sync(&node, &peer)).unwrap();
+    node.queue_outbound(peer_id.clone(), heartbeat(&node)).unwrap();
+
+    assert_eq!((node.stats().urgent_queued, node.stats().bulk_queued), (1, 3));
+    assert_eq!(node.outbound_depth(Priority::Bulk), 3);
+    assert_eq!(node.stats().sync_chunks, 2);
+
+    node.pop_outbound();
+    node.pop_outbound();
+    assert_eq!((node.stats().urgent_queued, node.stats().bulk_queued), (0, 2));
+    assert_eq!(node.outbound_len(), 2);
+}
+
+#[test]
+fn test_response_without_room_for_every_chunk_is_dropped() {
+    let config = GossipConfig::new().with_sync_chunk_entries(2).with_outbound_queue(4, 4);
+    let mut node = engine_with_ious(config, 10);
+    let peer = engine(GossipConfig::new());
+
+    let result = node.queue_outbound(peer.state().node_id().clone(), full_sync(&node, &peer));
+
+    assert!(matches!(result, Err(GossipError::OutboundQueueFull)));
+    assert_eq!(node.outbound_len(), 0);
+    assert_eq!(node.stats().outbound_dropped, 1);
+}
+
+#[test]
+fn test_message_priorities() {
+    let node = engine_with_ious(GossipConfig::new(), 1);
+    let peer = engine(GossipConfig::new());
+
+    assert_eq!(heartbeat(&node).priority(), Priority::Urgent);
+    assert_eq!(Message::SyncRequest(node.generate_sync_request()).priority(), Priority::Urgent);
+    assert_eq!(full_sync(&node, &peer).priority(), Priority::Bulk);
+}
+
+// ============================================================================
+// CHUNKING
+// ============================================================================
+
+fn chunks_of(node: &GossipEngine, peer: &GossipEngine, max_entries: usize) -> Vec<SyncResponse> {
+    match full_sync(node, peer) {
+        Message::SyncResponse(response) => response.into_chunks(max_entries),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_chunks_split_entries_and_keep_clock_for_last() {
+    let node = engine_with_ious(GossipConfig::new(), 10);
+    let peer = engine(GossipConfig::new());
+
+    let chunks = chunks_of(&node, &peer, 4);
+
+    let sizes: Vec<usize> = chunks.iter().map(|chunk| chunk.entries().len()).collect();
+    assert_eq!(sizes, vec![4, 4, 2]);
+    assert!(chunks[..2].iter().all(|chunk| chunk.clock().is_none()));
+    assert_eq!(chunks[2].clock(), Some(node.state().clock()));
+}
+
+#[test]
+fn test_small_or_unlimited_response_is_not_split() {
+    let node = engine_with_ious(GossipConfig::new(), 10);
+    let peer = engine(GossipConfig::new());
+
+    assert_eq!(chunks_of(&node, &peer, 10).len(), 1);
+    assert_eq!(chunks_of(&node, &peer, 0).len(), 1);
+}
+
+#[test]
+fn test_paged_response_cursor_only_on_last_chunk() {
+    let node = engine_with_ious(GossipConfig::new(), 5);
+    let peer = engine(GossipConfig::new());
+    let entries = chunks_of(&node, &peer, 0).remove(0).entries().to_vec();
+    let response = SyncResponse::new(NodeId::generate(), 3, entries)
+        .with_next_cursor(42)
+        .with_has_more(true);
+
+    let chunks = response.into_chunks(2);
+
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks[..2].iter().all(|chunk| !chunk.has_more() && chunk.next_cursor().is_none()));
+    assert!(chunks[2].has_more());
+    assert_eq!(chunks[2].next_cursor(), Some(42));
+}
+
+#[test]
+fn test_chunks_rebuild_whole_state_at_receiver() {
+    let mut node = engine_with_ious(GossipConfig::new().with_sync_chunk_entries(3), 10);
+    let mut peer = engine(GossipConfig::new());
+    let peer_id = peer.state().node_id().clone();
+
+    node.queue_outbound(peer_id, full_sync(&node, &peer)).unwrap();
+    while let Some((_, msg)) = node.pop_outbound() {
+        peer.process_message(msg).unwrap();
+    }
+
+    assert_eq!(peer.state().iou_count(), 10);
+    assert_eq!(peer.state().merkle_root(), node.state().merkle_root());
+}
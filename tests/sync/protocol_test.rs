@@ -6,7 +6,7 @@ use p2pmesh::iou::IOUBuilder;
 use p2pmesh::ledger::NodeId;
 use p2pmesh::sync::{
     Message, MessageType, SyncRequest, SyncResponse, IOUAnnouncement,
-    PeerAnnouncement, Heartbeat, ProtocolError,
+    PeerAnnouncement, Heartbeat, ProtocolError, EncryptedMessage,
 };
 
 // ============================================================================
@@ -92,10 +92,50 @@ fn test_sync_request_with_filter() {
     assert_eq!(request.sender_filter(), Some(&alice_did));
 }
 
+#[test]
+fn test_sync_request_with_since_cursor() {
+    let request = SyncRequest::new(NodeId::generate(), 0);
+    assert_eq!(request.since_cursor(), None);
+
+    let request = request.with_since_cursor(128);
+    assert_eq!(request.since_cursor(), Some(128));
+
+    let restored = Message::from_bytes(&Message::SyncRequest(request).to_bytes()).unwrap();
+    match restored {
+        Message::SyncRequest(r) => assert_eq!(r.since_cursor(), Some(128)),
+        _ => panic!("Expected SyncRequest"),
+    }
+}
+
 // ============================================================================
 // SYNC RESPONSE
 // ============================================================================
 
+#[test]
+fn test_sync_response_next_cursor() {
+    let response = SyncResponse::new(NodeId::generate(), 7, vec![]);
+    assert_eq!(response.next_cursor(), None);
+
+    let response = response.with_next_cursor(64).with_has_more(true);
+    let restored = Message::from_bytes(&Message::SyncResponse(response).to_bytes()).unwrap();
+    match restored {
+        Message::SyncResponse(r) => {
+            assert_eq!(r.next_cursor(), Some(64));
+            assert!(r.has_more());
+        }
+        _ => panic!("Expected SyncResponse"),
+    }
+}
+
+#[test]
+fn test_sync_response_pages_have_distinct_ids() {
+    let node_id = NodeId::generate();
+    let page1 = Message::SyncResponse(SyncResponse::new(node_id.clone(), 7, vec![]).with_next_cursor(10));
+    let page2 = Message::SyncResponse(SyncResponse::new(node_id, 7, vec![]).with_next_cursor(20));
+
+    assert_ne!(page1.id(), page2.id());
+}
+
 #[test]
 fn test_sync_response_empty() {
     let node_id = NodeId::generate();
@@ -356,3 +396,49 @@ fn test_same_content_same_id() {
     // Same IOU should produce same announcement ID (for deduplication)
     assert_eq!(ann1.id(), ann2.id());
 }
+
+// ============================================================================
+// ENCRYPTED MESSAGES
+// ============================================================================
+
+#[test]
+fn test_encrypted_message_roundtrip() {
+    let bob = Keypair::generate();
+    let inner = Message::Heartbeat(Heartbeat::new(NodeId::generate(), 7));
+
+    let msg = inner.encrypt_for(&bob.public_key()).unwrap();
+    assert_eq!(msg.message_type(), MessageType::Encrypted);
+
+    let restored = Message::from_bytes(&msg.to_bytes()).unwrap();
+    let Message::Encrypted(sealed) = restored else {
+        panic!("expected an encrypted message");
+    };
+    assert!(sealed.is_for(&Did::from_public_key(&bob.public_key())));
+
+    let opened = sealed.decrypt(&bob).unwrap();
+    assert_eq!(opened.id(), inner.id());
+}
+
+#[test]
+fn test_encrypted_message_wrong_recipient() {
+    let bob = Keypair::generate();
+    let eve = Keypair::generate();
+    let inner = Message::Heartbeat(Heartbeat::new(NodeId::generate(), 7));
+
+    let sealed = EncryptedMessage::seal(&bob.public_key(), &inner).unwrap();
+
+    assert!(!sealed.is_for(&Did::from_public_key(&eve.public_key())));
+    assert!(matches!(sealed.decrypt(&eve), Err(ProtocolError::Encryption(_))));
+}
+
+#[test]
+fn test_encrypted_message_ids_differ_per_envelope() {
+    let bob = Keypair::generate();
+    let inner = Message::Heartbeat(Heartbeat::new(NodeId::generate(), 7));
+
+    let first = inner.encrypt_for(&bob.public_key()).unwrap();
+    let second = inner.encrypt_for(&bob.public_key()).unwrap();
+
+    assert_ne!(first.id(), second.id());
+    assert_eq!(first.id(), Message::from_bytes(&first.to_bytes()).unwrap().id());
+}
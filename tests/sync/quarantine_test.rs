@@ -0,0 +1,236 @@
+// Quarantine Tests
+// Tests reputation decay, quarantining misbehaving peers, recovery after the
+// cool-down, and gossip skipping quarantined peers
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::IOUBuilder;
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{GossipConfig, GossipEngine, PeerEvent, PeerRegistry, PeerState};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const COOLDOWN_SECS: u64 = 60;
+const SEC: u64 = 1000;
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+/// A registry quarantining at -20 for a minute, with peers added just before the returned time
+fn registry() -> (PeerRegistry, u64) {
+    let registry = PeerRegistry::new(NodeId::generate()).with_quarantine(Some(-20), COOLDOWN_SECS);
+    (registry, now_ms() + SEC)
+}
+
+fn connected_peer(registry: &mut PeerRegistry, last_octet: u8) -> NodeId {
+    let peer_id = NodeId::generate();
+    let addr: SocketAddr = format!("10.1.0.{}:8080", last_octet).parse().unwrap();
+    registry.add_peer(peer_id.clone(), addr).unwrap();
+    registry.get_peer_mut(&peer_id).unwrap().set_state(PeerState::Connected);
+    peer_id
+}
+
+/// Two invalid messages take a fresh peer to -20
+fn misbehave(registry: &mut PeerRegistry, peer: &NodeId, now: u64) -> PeerState {
+    registry.record_event_at(peer, PeerEvent::InvalidMessage, now).unwrap();
+    registry.record_event_at(peer, PeerEvent::InvalidMessage, now).unwrap()
+}
+
+// ============================================================================
+// QUARANTINE
+// ============================================================================
+
+#[test]
+fn test_quarantine_is_off_by_default() {
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let peer = connected_peer(&mut registry, 1);
+
+    for _ in 0..4 {
+        registry.record_invalid_message(&peer).unwrap();
+    }
+
+    assert_eq!(registry.quarantine_threshold(), None);
+    assert_eq!(registry.get_peer(&peer).unwrap().state(), PeerState::Connected);
+}
+
+#[test]
+fn test_peer_at_threshold_is_quarantined() {
+    let (mut registry, now) = registry();
+    let peer = connected_peer(&mut registry, 1);
+
+    registry.record_event_at(&peer, PeerEvent::InvalidMessage, now).unwrap();
+    assert_eq!(registry.get_peer(&peer).unwrap().state(), PeerState::Connected);
+    let state = registry.record_event_at(&peer, PeerEvent::InvalidMessage, now).unwrap();
+
+    assert_eq!(state, PeerState::Quarantined);
+    let info = registry.get_peer(&peer).unwrap();
+    assert_eq!(info.quarantined_until(), Some(now + COOLDOWN_SECS * SEC));
+    assert!(!info.is_active());
+    let stats = registry.stats();
+    assert_eq!(stats.quarantined_peers, 1);
+    assert_eq!(stats.average_reputation, -20.0);
+}
+
+#[test]
+fn test_valid_message_does_not_extend_quarantine() {
+    let (mut registry, now) = registry();
+    let peer = connected_peer(&mut registry, 1);
+    misbehave(&mut registry, &peer, now);
+
+    registry.record_event_at(&peer, PeerEvent::ValidMessage, now + 10 * SEC).unwrap();
+    assert_eq!(registry.get_peer(&peer).unwrap().quarantined_until(), Some(now + COOLDOWN_SECS * SEC));
+
+    // Another penalty restarts the cool-down
+    registry.record_event_at(&peer, PeerEvent::Timeout, now + 20 * SEC).unwrap();
+    assert_eq!(registry.get_peer(&peer).unwrap().quarantined_until(), Some(now + 80 * SEC));
+}
+
+#[test]
+fn test_ban_overrides_quarantine() {
+    let (mut registry, now) = registry();
+    let peer = connected_peer(&mut registry, 1);
+
+    for _ in 0..5 {
+        registry.record_event_at(&peer, PeerEvent::InvalidMessage, now).unwrap();
+    }
+
+    let info = registry.get_peer(&peer).unwrap();
+    assert_eq!(info.state(), PeerState::Banned);
+    assert_eq!(info.quarantined_until(), None);
+    assert!(registry.release_quarantined_at(now + 3600 * SEC).is_empty());
+}
+
+// ============================================================================
+// RECOVERY
+// ============================================================================
+
+#[test]
+fn test_peer_recovers_after_cooldown() {
+    let (mut registry, now) = registry();
+    let peer = connected_peer(&mut registry, 1);
+    misbehave(&mut registry, &peer, now);
+
+    assert!(registry.release_quarantined_at(now + 59 * SEC).is_empty());
+    let released = registry.release_quarantined_at(now + COOLDOWN_SECS * SEC);
+
+    assert_eq!(released, vec![peer.clone()]);
+    let info = registry.get_peer(&peer).unwrap();
+    assert_eq!(info.state(), PeerState::Connected);
+    assert_eq!(info.quarantined_until(), None);
+    // One minute of decay at the default rate
+    assert_eq!(info.reputation(), -19);
+    assert_eq!(registry.best_peers(1)[0].node_id(), &peer);
+}
+
+#[test]
+fn test_released_peer_is_quarantined_again_on_misbehaviour() {
+    let (mut registry, now) = registry();
+    let peer = connected_peer(&mut registry, 1);
+    misbehave(&mut registry, &peer, now);
+    registry.release_quarantined_at(now + COOLDOWN_SECS * SEC);
+
+    let state = registry.record_event_at(&peer, PeerEvent::Timeout, now + 61 * SEC).unwrap();
+
+    assert_eq!(state, PeerState::Quarantined);
+}
+
+#[test]
+fn test_reputation_decays_towards_zero() {
+    let (mut registry, now) = registry();
+    let bad = connected_peer(&mut registry, 1);
+    let good = connected_peer(&mut registry, 2);
+    registry.record_event_at(&bad, PeerEvent::InvalidMessage, now).unwrap();
+    for _ in 0..3 {
+        registry.record_event_at(&good, PeerEvent::ValidMessage, now).unwrap();
+    }
+
+    // Five minutes later each has moved five points towards zero before the new event
+    registry.record_event_at(&bad, PeerEvent::ValidMessage, now + 300 * SEC).unwrap();
+    registry.record_event_at(&good, PeerEvent::ValidMessage, now + 300 * SEC).unwrap();
+
+    assert_eq!(registry.get_peer(&bad).unwrap().reputation(), -4);
+    assert_eq!(registry.get_peer(&good).unwrap().reputation(), 1);
+}
+
+#[test]
+fn test_decay_can_be_disabled() {
+    let (registry, now) = registry();
+    let mut registry = registry.with_reputation_decay(None);
+    let peer = connected_peer(&mut registry, 1);
+    registry.record_event_at(&peer, PeerEvent::InvalidMessage, now).unwrap();
+
+    registry.record_event_at(&peer, PeerEvent::ValidMessage, now + 3600 * SEC).unwrap();
+
+    assert_eq!(registry.get_peer(&peer).unwrap().reputation(), -9);
+}
+
+#[test]
+fn test_quarantine_survives_serialization() {
+    let my_node_id = NodeId::generate();
+    let mut registry = PeerRegistry::new(my_node_id.clone()).with_quarantine(Some(-20), COOLDOWN_SECS);
+    let peer = connected_peer(&mut registry, 1);
+    let now = now_ms() + SEC;
+    misbehave(&mut registry, &peer, now);
+
+    let mut restored = PeerRegistry::from_bytes(&registry.to_bytes(), my_node_id).unwrap();
+
+    assert_eq!(restored.get_peer(&peer).unwrap().quarantined_until(), Some(now + COOLDOWN_SECS * SEC));
+    assert_eq!(restored.release_quarantined_at(now + COOLDOWN_SECS * SEC), vec![peer.clone()]);
+    assert_eq!(restored.get_peer(&peer).unwrap().state(), PeerState::Connected);
+}
+
+// ============================================================================
+// GOSSIP
+// ============================================================================
+
+fn engine_with_announcement(fanout: usize) -> GossipEngine {
+    let id = NodeId::generate();
+    let config = GossipConfig::new()
+        .with_fanout(fanout)
+        .with_heartbeat_interval(0)
+        .with_anti_entropy_interval(0);
+    let mut engine = GossipEngine::new(id.clone(), MeshState::new(id), config);
+    let sender = Keypair::generate();
+    let iou = IOUBuilder::new()
+        .sender(&sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(5)
+        .build()
+        .unwrap();
+    engine.announce_iou(iou, &sender.public_key());
+    engine
+}
+
+#[test]
+fn test_gossip_pushes_to_best_peers() {
+    let (mut registry, now) = registry();
+    let peers: Vec<NodeId> = (1..=4).map(|i| connected_peer(&mut registry, i)).collect();
+    for _ in 0..3 {
+        registry.record_event_at(&peers[2], PeerEvent::ValidMessage, now).unwrap();
+    }
+    registry.record_event_at(&peers[0], PeerEvent::ValidMessage, now).unwrap();
+    let mut engine = engine_with_announcement(2);
+
+    let outgoing = engine.tick_with_registry_at(&mut registry, now);
+
+    let targets: HashSet<&NodeId> = outgoing.iter().map(|(peer, _)| peer).collect();
+    assert_eq!(targets, HashSet::from([&peers[2], &peers[0]]));
+}
+
+#[test]
+fn test_gossip_skips_quarantined_peer_until_cooldown_ends() {
+    let (mut registry, now) = registry();
+    let good = connected_peer(&mut registry, 1);
+    let bad = connected_peer(&mut registry, 2);
+    misbehave(&mut registry, &bad, now);
+
+    let mut engine = engine_with_announcement(5);
+    let outgoing = engine.tick_with_registry_at(&mut registry, now + SEC);
+    assert!(outgoing.iter().all(|(peer, _)| *peer == good));
+
+    let mut engine = engine_with_announcement(5);
+    let outgoing = engine.tick_with_registry_at(&mut registry, now + COOLDOWN_SECS * SEC);
+    assert!(outgoing.iter().any(|(peer, _)| *peer == bad));
+    assert_eq!(registry.get_peer(&bad).unwrap().state(), PeerState::Connected);
+}
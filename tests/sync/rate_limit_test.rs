@@ -0,0 +1,243 @@
+// Rate Limit Tests
+// Tests token bucket refills, per-peer inbound limits in the gossip engine and
+// the bounded outbound queue, all against an injected clock
+
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    GossipConfig, GossipEngine, GossipError, GossipEvent, Heartbeat, Message, SyncRequest,
+    TokenBucket,
+};
+
+const START: u64 = 1_000_000;
+
+/// An engine without a key, accepting unsigned messages from peers like it
+fn engine(config: GossipConfig) -> GossipEngine {
+    let id = NodeId::generate();
+    GossipEngine::new(id.clone(), MeshState::new(id), config.with_unsigned_messages(true))
+}
+
+fn sync_request(peer: &NodeId) -> Message {
+    Message::SyncRequest(SyncRequest::new(peer.clone(), 0))
+}
+
+/// Send `count` sync requests from `peer` at `now`, returning how many were answered
+fn flood(engine: &mut GossipEngine, peer: &NodeId, count: usize, now: u64) -> usize {
+    (0..count)
+        .filter(|_| !engine.process_message_from_at(peer, sync_request(peer), now).unwrap().is_empty())
+        .count()
+}
+
+// ============================================================================
+// TOKEN BUCKET
+// ============================================================================
+
+#[test]
+fn test_bucket_starts_full() {
+    let mut bucket = TokenBucket::new(10, START);
+
+    assert_eq!(bucket.rate(), 10);
+    assert_eq!(bucket.available_at(START), 10);
+}
+
+#[test]
+fn test_bucket_refuses_once_drained() {
+    let mut bucket = TokenBucket::new(3, START);
+
+    assert!((0..3).all(|_| bucket.try_take_at(1, START)));
+    assert!(!bucket.try_take_at(1, START));
+    assert_eq!(bucket.available_at(START), 0);
+}
+
+#[test]
+fn test_bucket_refills_at_rate() {
+    let mut bucket = TokenBucket::new(10, START);
+    assert!(bucket.try_take_at(10, START));
+
+    assert_eq!(bucket.available_at(START + 100), 1);
+    assert_eq!(bucket.available_at(START + 550), 5);
+    assert_eq!(bucket.available_at(START + 1_000), 10);
+}
+
+#[test]
+fn test_bucket_never_exceeds_one_second() {
+    let mut bucket = TokenBucket::new(10, START);
+
+    assert_eq!(bucket.available_at(START + 3_600_000), 10);
+    assert!(bucket.try_take_at(10, START + 3_600_000));
+    assert!(!bucket.try_take_at(1, START + 3_600_000));
+}
+
+#[test]
+fn test_bucket_accumulates_partial_tokens() {
+    let mut bucket = TokenBucket::new(3, START);
+    assert!(bucket.try_take_at(3, START));
+
+    // 3 tokens/s is one every 333.3ms
+    assert_eq!(bucket.available_at(START + 333), 0);
+    assert!(!bucket.try_take_at(1, START + 333));
+    assert!(bucket.try_take_at(1, START + 334));
+}
+
+#[test]
+fn test_bucket_ignores_clock_going_back() {
+    let mut bucket = TokenBucket::new(5, START);
+    assert!(bucket.try_take_at(5, START));
+
+    assert_eq!(bucket.available_at(START - 10_000), 0);
+    assert_eq!(bucket.available_at(START + 200), 1);
+}
+
+#[test]
+fn test_oversized_take_passes_only_when_full() {
+    let mut bucket = TokenBucket::new(100, START);
+
+    assert!(bucket.try_take_at(250, START));
+    assert_eq!(bucket.available_at(START), 0);
+    assert!(!bucket.try_take_at(250, START + 500));
+    assert!(bucket.try_take_at(250, START + 1_000));
+}
+
+// ============================================================================
+// INBOUND LIMITS
+// ============================================================================
+
+#[test]
+fn test_unlimited_by_default() {
+    let mut engine = engine(GossipConfig::new());
+    let peer = NodeId::generate();
+
+    assert_eq!(flood(&mut engine, &peer, 100, START), 100);
+    assert_eq!(engine.stats().rate_limited, 0);
+}
+
+#[test]
+fn test_sync_request_flood_is_cut_off() {
+    let mut engine = engine(GossipConfig::new().with_rate_limit(5, 0));
+    let peer = NodeId::generate();
+
+    assert_eq!(flood(&mut engine, &peer, 20, START), 5);
+    assert_eq!(engine.stats().rate_limited, 15);
+    assert_eq!(engine.stats().messages_processed, 5);
+}
+
+#[test]
+fn test_limit_refills_over_time() {
+    let mut engine = engine(GossipConfig::new().with_rate_limit(5, 0));
+    let peer = NodeId::generate();
+    flood(&mut engine, &peer, 5, START);
+
+    assert_eq!(flood(&mut engine, &peer, 5, START + 200), 1);
+    assert_eq!(flood(&mut engine, &peer, 5, START + 600), 2);
+    assert_eq!(flood(&mut engine, &peer, 10, START + 10_000), 5);
+}
+
+#[test]
+fn test_limits_are_per_peer() {
+    let mut engine = engine(GossipConfig::new().with_rate_limit(2, 0));
+    let (noisy, quiet) = (NodeId::generate(), NodeId::generate());
+
+    assert_eq!(flood(&mut engine, &noisy, 10, START), 2);
+    assert_eq!(flood(&mut engine, &quiet, 2, START), 2);
+}
+
+#[test]
+fn test_byte_limit_counts_received_bytes() {
+    let mut engine = engine(GossipConfig::new().with_rate_limit(0, 1_000));
+    let peer = NodeId::generate();
+    let bytes = sync_request(&peer).to_bytes();
+    let fit = 1_000 / bytes.len();
+
+    let answered = (0..fit + 3)
+        .filter(|_| !engine.process_bytes_from_at(&peer, &bytes, START).unwrap().is_empty())
+        .count();
+
+    assert_eq!(answered, fit);
+    assert_eq!(engine.stats().rate_limited, 3);
+}
+
+#[test]
+fn test_both_limits_must_allow() {
+    let mut engine = engine(GossipConfig::new().with_rate_limit(100, 1));
+    let peer = NodeId::generate();
+
+    // The first message is oversized but drains a full byte bucket
+    assert_eq!(flood(&mut engine, &peer, 3, START), 1);
+    // Refused messages weren't charged to the message bucket
+    assert_eq!(flood(&mut engine, &peer, 1, START + 1_000), 1);
+}
+
+#[test]
+fn test_over_limit_bytes_are_not_decoded() {
+    let mut engine = engine(GossipConfig::new().with_rate_limit(1, 0));
+    let peer = NodeId::generate();
+    let heartbeat = Message::Heartbeat(Heartbeat::new(peer.clone(), 0)).to_bytes();
+    engine.process_bytes_from_at(&peer, &heartbeat, START).unwrap();
+
+    // Garbage would fail to decode, but is dropped first
+    let events = engine.process_bytes_from_at(&peer, b"garbage", START).unwrap();
+
+    assert!(events.is_empty());
+    assert_eq!(engine.stats().rate_limited, 1);
+}
+
+// ============================================================================
+// OUTBOUND QUEUE
+// ============================================================================
+
+#[test]
+fn test_backpressure_reported_once_above_high_water() {
+    let mut engine = engine(GossipConfig::new().with_outbound_queue(10, 3));
+    let peer = NodeId::generate();
+
+    let mut reported = Vec::new();
+    for _ in 0..6 {
+        reported.extend(engine.queue_outbound(peer.clone(), sync_request(&peer)).unwrap());
+    }
+
+    assert!(matches!(reported.as_slice(), [GossipEvent::Backpressure(4)]));
+    assert!(engine.is_backpressured());
+    assert_eq!(engine.outbound_len(), 6);
+}
+
+#[test]
+fn test_backpressure_clears_once_drained_to_high_water() {
+    let mut engine = engine(GossipConfig::new().with_outbound_queue(10, 3));
+    let peer = NodeId::generate();
+    for _ in 0..5 {
+        engine.queue_outbound(peer.clone(), sync_request(&peer)).unwrap();
+    }
+
+    engine.pop_outbound();
+    assert!(engine.is_backpressured());
+    engine.pop_outbound();
+    assert!(!engine.is_backpressured());
+
+    let events = engine.queue_outbound(peer.clone(), sync_request(&peer)).unwrap();
+    assert!(matches!(events.as_slice(), [GossipEvent::Backpressure(4)]));
+}
+
+#[test]
+fn test_outbound_queue_is_fifo() {
+    let mut engine = engine(GossipConfig::new());
+    let (first, second) = (NodeId::generate(), NodeId::generate());
+    engine.queue_outbound(first.clone(), sync_request(&first)).unwrap();
+    engine.queue_outbound(second.clone(), sync_request(&second)).unwrap();
+
+    assert_eq!(engine.pop_outbound().unwrap().0, first);
+    assert_eq!(engine.pop_outbound().unwrap().0, second);
+    assert!(engine.pop_outbound().is_none());
+}
+
+#[test]
+fn test_full_queue_drops_message() {
+    let mut engine = engine(GossipConfig::new().with_outbound_queue(2, 1));
+    let peer = NodeId::generate();
+    engine.queue_outbound(peer.clone(), sync_request(&peer)).unwrap();
+    engine.queue_outbound(peer.clone(), sync_request(&peer)).unwrap();
+
+    let result = engine.queue_outbound(peer.clone(), sync_request(&peer));
+
+    assert!(matches!(result, Err(GossipError::OutboundQueueFull)));
+    assert_eq!(engine.outbound_len(), 2);
+    assert_eq!(engine.stats().outbound_dropped, 1);
+}
@@ -0,0 +1,273 @@
+// Relay Tests
+// Tests multi-hop forwarding of IOU announcements: TTL spending, loop prevention
+// through seen_by and deduplication, and that invalid announcements go no further
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    GossipConfig, GossipEngine, GossipEvent, IOUAnnouncement, Message, MAX_SEEN_BY,
+};
+
+fn node(config: GossipConfig) -> GossipEngine {
+    let id = NodeId::generate();
+    let config = config.with_heartbeat_interval(0).with_anti_entropy_interval(0).with_unsigned_messages(true);
+    GossipEngine::new(id.clone(), MeshState::new(id), config)
+}
+
+fn iou(sender: &Keypair, amount: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(sender)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(amount)
+        .build()
+        .unwrap()
+}
+
+fn node_id(engine: &GossipEngine) -> NodeId {
+    engine.state().node_id().clone()
+}
+
+/// The announcement `engine` forwards after processing `msg` from `peer`, if any
+fn relay(engine: &mut GossipEngine, peer: &NodeId, msg: Message) -> Option<IOUAnnouncement> {
+    engine
+        .process_message_from(peer, msg)
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            GossipEvent::Forward(Message::IOUAnnouncement(announcement)) => Some(announcement),
+            _ => None,
+        })
+}
+
+fn received_new_iou(events: &[GossipEvent]) -> bool {
+    events.iter().any(|event| matches!(event, GossipEvent::NewIOU(_)))
+}
+
+// ============================================================================
+// MULTI-HOP FORWARDING
+// ============================================================================
+
+#[test]
+fn test_chain_delivers_with_one_relay_hop() {
+    let alice = Keypair::generate();
+    let mut a = node(GossipConfig::new());
+    let mut b = node(GossipConfig::new());
+    let mut c = node(GossipConfig::new());
+    let (a_id, b_id, c_id) = (node_id(&a), node_id(&b), node_id(&c));
+
+    // A only knows B, and B knows both A and C
+    let signed = iou(&alice, 100);
+    a.announce_iou(signed.clone(), &alice.public_key());
+    let (to, msg) = a.tick(std::slice::from_ref(&b_id)).remove(0);
+    assert_eq!(to, b_id);
+
+    let relayed = relay(&mut b, &a_id, msg).unwrap();
+    let targets = b.forward_targets(&Message::IOUAnnouncement(relayed.clone()), &[a_id.clone(), c_id.clone()]);
+    assert_eq!(targets, vec![c_id]);
+
+    let events = c.process_message_from(&b_id, Message::IOUAnnouncement(relayed.clone())).unwrap();
+
+    assert!(received_new_iou(&events));
+    assert!(c.state().has_iou(&signed.id()));
+    assert_eq!(relayed.hop_count(), 1);
+    assert_eq!(relayed.seen_by(), &[a_id, b_id][..]);
+    assert_eq!(b.stats().messages_relayed, 1);
+}
+
+#[test]
+fn test_relay_spends_one_ttl() {
+    let alice = Keypair::generate();
+    let mut b = node(GossipConfig::new());
+    let peer = NodeId::generate();
+    let announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key()).with_ttl(3);
+
+    let relayed = relay(&mut b, &peer, Message::IOUAnnouncement(announcement)).unwrap();
+
+    assert_eq!(relayed.ttl(), 2);
+    assert!(relayed.was_seen_by(&node_id(&b)));
+}
+
+#[test]
+fn test_exhausted_ttl_is_not_relayed() {
+    let alice = Keypair::generate();
+    let mut b = node(GossipConfig::new());
+    let peer = NodeId::generate();
+    let announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key()).with_ttl(0);
+
+    let events = b.process_message_from(&peer, Message::IOUAnnouncement(announcement)).unwrap();
+
+    // Still accepted locally, just not passed on
+    assert!(received_new_iou(&events));
+    assert!(!events.iter().any(|event| matches!(event, GossipEvent::Forward(_))));
+    assert_eq!(b.stats().messages_relayed, 0);
+}
+
+#[test]
+fn test_announcement_stops_after_ttl_relays() {
+    let alice = Keypair::generate();
+    let announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key()).with_ttl(2);
+    let mut msg = Message::IOUAnnouncement(announcement);
+    let mut from = NodeId::generate();
+
+    let mut relays = 0;
+    for _ in 0..5 {
+        let mut hop = node(GossipConfig::new());
+        match relay(&mut hop, &from, msg.clone()) {
+            Some(relayed) => {
+                relays += 1;
+                from = node_id(&hop);
+                msg = Message::IOUAnnouncement(relayed);
+            }
+            None => break,
+        }
+    }
+
+    assert_eq!(relays, 2);
+}
+
+#[test]
+fn test_peer_ttl_is_capped_at_max_ttl() {
+    let alice = Keypair::generate();
+    let mut b = node(GossipConfig::new().with_max_ttl(2));
+    let peer = NodeId::generate();
+    let announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key()).with_ttl(u8::MAX);
+
+    let relayed = relay(&mut b, &peer, Message::IOUAnnouncement(announcement)).unwrap();
+
+    assert_eq!(relayed.ttl(), 1);
+}
+
+#[test]
+fn test_announce_uses_max_ttl_and_marks_self() {
+    let alice = Keypair::generate();
+    let mut a = node(GossipConfig::new().with_max_ttl(4));
+    let peer = NodeId::generate();
+    a.announce_iou(iou(&alice, 5), &alice.public_key());
+
+    let (_, msg) = a.tick(&[peer]).remove(0);
+
+    match msg {
+        Message::IOUAnnouncement(announcement) => {
+            assert_eq!(announcement.ttl(), 4);
+            assert_eq!(announcement.seen_by(), &[node_id(&a)][..]);
+        }
+        other => panic!("expected an IOU announcement, got {:?}", other),
+    }
+}
+
+// ============================================================================
+// RELAY DISABLED AND INVALID MESSAGES
+// ============================================================================
+
+#[test]
+fn test_relay_disabled_accepts_without_forwarding() {
+    let alice = Keypair::generate();
+    let mut b = node(GossipConfig::new().with_relay(false));
+    let peer = NodeId::generate();
+    let announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key());
+
+    let events = b.process_message_from(&peer, Message::IOUAnnouncement(announcement)).unwrap();
+
+    assert!(received_new_iou(&events));
+    assert_eq!(events.len(), 1);
+    assert_eq!(b.stats().messages_relayed, 0);
+}
+
+#[test]
+fn test_invalid_announcement_is_not_relayed() {
+    let alice = Keypair::generate();
+    let mallory = Keypair::generate();
+    let mut b = node(GossipConfig::new());
+    let peer = NodeId::generate();
+    // Claims Mallory sent an IOU Alice signed
+    let forged = IOUAnnouncement::new(iou(&alice, 5), mallory.public_key());
+
+    let events = b.process_message_from(&peer, Message::IOUAnnouncement(forged)).unwrap();
+
+    assert!(events.is_empty());
+    assert_eq!(b.stats().ious_rejected, 1);
+    assert_eq!(b.stats().messages_relayed, 0);
+    assert_eq!(b.state().iou_count(), 0);
+}
+
+// ============================================================================
+// LOOP PREVENTION
+// ============================================================================
+
+#[test]
+fn test_forward_targets_skip_seen_by() {
+    let alice = Keypair::generate();
+    let b = node(GossipConfig::new().with_fanout(10));
+    let (x, y, z) = (NodeId::generate(), NodeId::generate(), NodeId::generate());
+    let announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key())
+        .with_seen_by(x.clone())
+        .with_seen_by(y.clone());
+
+    let peers = [x, y, z.clone(), node_id(&b)];
+    let targets = b.forward_targets(&Message::IOUAnnouncement(announcement), &peers);
+
+    assert_eq!(targets, vec![z]);
+}
+
+#[test]
+fn test_forward_targets_respect_fanout() {
+    let alice = Keypair::generate();
+    let b = node(GossipConfig::new().with_fanout(2));
+    let peers: Vec<NodeId> = (0..5).map(|_| NodeId::generate()).collect();
+    let msg = Message::IOUAnnouncement(IOUAnnouncement::new(iou(&alice, 5), alice.public_key()));
+
+    assert_eq!(b.forward_targets(&msg, &peers).len(), 2);
+}
+
+#[test]
+fn test_announcement_back_round_a_loop_is_dropped() {
+    let alice = Keypair::generate();
+    let mut b = node(GossipConfig::new());
+    let mut c = node(GossipConfig::new());
+    let (b_id, c_id) = (node_id(&b), node_id(&c));
+    let announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key());
+
+    let from_b = relay(&mut b, &NodeId::generate(), Message::IOUAnnouncement(announcement)).unwrap();
+    let back_to_b = relay(&mut c, &b_id, Message::IOUAnnouncement(from_b)).unwrap();
+    let events = b.process_message_from(&c_id, Message::IOUAnnouncement(back_to_b)).unwrap();
+
+    assert!(events.is_empty());
+    assert_eq!(b.stats().messages_relayed, 1);
+}
+
+#[test]
+fn test_seen_by_keeps_most_recent_nodes() {
+    let alice = Keypair::generate();
+    let nodes: Vec<NodeId> = (0..MAX_SEEN_BY + 3).map(|_| NodeId::generate()).collect();
+    let mut announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key());
+
+    for node in &nodes {
+        announcement.mark_seen_by(node.clone());
+    }
+    announcement.mark_seen_by(nodes.last().unwrap().clone());
+
+    assert_eq!(announcement.seen_by(), &nodes[3..]);
+}
+
+#[test]
+fn test_relay_fields_survive_serialization() {
+    let alice = Keypair::generate();
+    let relayer = NodeId::generate();
+    let announcement = IOUAnnouncement::new(iou(&alice, 5), alice.public_key())
+        .with_seen_by(NodeId::generate())
+        .relayed_by(&relayer, 6)
+        .unwrap();
+
+    let bytes = Message::IOUAnnouncement(announcement).to_bytes();
+
+    match Message::from_bytes(&bytes).unwrap() {
+        Message::IOUAnnouncement(restored) => {
+            assert_eq!(restored.ttl(), 5);
+            assert_eq!(restored.hop_count(), 1);
+            assert_eq!(restored.seen_by().len(), 2);
+            assert!(restored.was_seen_by(&relayer));
+        }
+        other => panic!("expected an IOU announcement, got {:?}", other),
+    }
+}
@@ -0,0 +1,384 @@
+// Signed Message Tests
+// Tests signed envelopes: learning keys from Hellos, verifying signatures and
+// senders, refusing replays, and the compatibility switch for unsigned peers
+
+use p2pmesh::identity::Keypair;
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    Features, GossipConfig, GossipEngine, GossipError, GossipEvent, Heartbeat, Hello, Message, PeerRegistry,
+    SignedMessage,
+};
+
+/// A node whose ID derives from its own key, with its registry
+struct Node {
+    keypair: Keypair,
+    engine: GossipEngine,
+    registry: PeerRegistry,
+}
+
+impl Node {
+    fn new(config: GossipConfig) -> Self {
+        let keypair = Keypair::generate();
+        let id = NodeId::from_public_key(&keypair.public_key());
+        let config = config.with_heartbeat_interval(0).with_anti_entropy_interval(0);
+        let engine = GossipEngine::new(id.clone(), MeshState::new(id.clone()), config).with_keypair(keypair.clone()).unwrap();
+        Self { keypair, engine, registry: PeerRegistry::new(id) }
+    }
+
+    /// A node that refuses unsigned messages
+    fn strict() -> Self {
+        Self::new(GossipConfig::new().with_unsigned_messages(false))
+    }
+
+    fn id(&self) -> NodeId {
+        self.engine.state().node_id().clone()
+    }
+
+    fn receive(&mut self, msg: Message) -> Result<Vec<GossipEvent>, GossipError> {
+        self.engine.process_message_with_registry(&mut self.registry, msg)
+    }
+
+    fn heartbeat(&self) -> Message {
+        Message::Heartbeat(self.engine.generate_heartbeat())
+    }
+
+    /// `msg` signed by this node with sequence number `sequence`
+    fn signed(&self, msg: &Message, sequence: u64) -> Message {
+        Message::Signed(SignedMessage::sign(&self.keypair, msg, sequence))
+    }
+
+    /// What this node sends `to` for `msg` through its outbound queue
+    fn send(&mut self, to: &NodeId, msg: Message) -> Message {
+        self.engine.queue_outbound(to.clone(), msg).unwrap();
+        self.engine.pop_outbound().unwrap().1
+    }
+}
+
+/// Exchange Hellos both ways
+fn handshake(a: &mut Node, b: &mut Node) {
+    let events = b.receive(Message::Hello(a.engine.generate_hello())).unwrap();
+    for event in events {
+        if let GossipEvent::Forward(reply @ Message::Hello(_)) = event {
+            a.receive(reply).unwrap();
+        }
+    }
+}
+
+// ============================================================================
+// KEYS FROM HELLOS
+// ============================================================================
+
+#[test]
+fn test_hello_carries_our_key() {
+    let a = Node::strict();
+
+    let hello = a.engine.generate_hello();
+
+    assert_eq!(hello.public_key(), Some(&a.keypair.public_key()));
+    assert!(hello.key_matches_node());
+}
+
+#[test]
+fn test_handshake_registers_both_keys() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+
+    handshake(&mut a, &mut b);
+
+    assert_eq!(b.registry.public_key(&a.id()), Some(&a.keypair.public_key()));
+    assert_eq!(a.registry.public_key(&b.id()), Some(&b.keypair.public_key()));
+    assert!(a.engine.peer_features(&b.id()).contains(Features::SIGNED_MESSAGES));
+}
+
+#[test]
+fn test_hello_with_someone_elses_key_is_refused() {
+    let (victim, mallory) = (Node::strict(), Keypair::generate());
+    let mut b = Node::strict();
+    let forged = Hello::new(victim.id(), Features::SUPPORTED).with_public_key(mallory.public_key());
+
+    let result = b.receive(Message::Hello(forged));
+
+    assert!(matches!(result, Err(GossipError::KeyMismatch)));
+    assert!(b.registry.public_key(&victim.id()).is_none());
+}
+
+#[test]
+fn test_signed_hello_introduces_its_signer() {
+    let (a, mut b) = (Node::strict(), Node::strict());
+    let hello = Message::Hello(a.engine.generate_hello());
+
+    b.receive(a.signed(&hello, 1)).unwrap();
+
+    assert!(b.registry.public_key(&a.id()).is_some());
+    assert_eq!(b.engine.stats().signatures_verified, 1);
+}
+
+// ============================================================================
+// VERIFICATION
+// ============================================================================
+
+#[test]
+fn test_queued_messages_go_out_signed_and_verify() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake(&mut a, &mut b);
+
+    let heartbeat = a.heartbeat();
+    let sent = a.send(&b.id(), heartbeat);
+
+    assert!(matches!(sent, Message::Signed(_)));
+    b.receive(sent).unwrap();
+    assert_eq!(b.engine.stats().signatures_verified, 2); // the Hello, then the heartbeat
+    assert_eq!(a.engine.stats().messages_signed, 1);
+}
+
+#[test]
+fn test_spoofed_unsigned_heartbeat_is_refused() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake(&mut a, &mut b);
+
+    // Anyone can put a's node ID in a heartbeat
+    let spoofed = Message::Heartbeat(Heartbeat::new(a.id(), 99));
+    let result = b.receive(spoofed);
+
+    assert!(matches!(result, Err(GossipError::UnsignedMessage)));
+    assert_eq!(b.engine.stats().unsigned_refused, 1);
+}
+
+#[test]
+fn test_message_naming_another_sender_is_refused() {
+    let (mut a, mut b, mut mallory) = (Node::strict(), Node::strict(), Node::strict());
+    handshake(&mut a, &mut b);
+    handshake(&mut mallory, &mut b);
+
+    // Mallory signs, as herself, a heartbeat claiming to be a's
+    let claim = Message::Heartbeat(Heartbeat::new(a.id(), 99));
+    let result = b.receive(mallory.signed(&claim, 1));
+
+    assert!(matches!(result, Err(GossipError::SenderMismatch)));
+    assert_eq!(b.engine.stats().signatures_rejected, 1);
+}
+
+#[test]
+fn test_unknown_signer_is_refused() {
+    let (a, mut b) = (Node::strict(), Node::strict());
+
+    let result = b.receive(a.signed(&a.heartbeat(), 1));
+
+    assert!(matches!(result, Err(GossipError::UnknownSigner)));
+}
+
+#[test]
+fn test_tampered_signature_is_refused() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake(&mut a, &mut b);
+    let mut bytes = a.signed(&a.heartbeat(), 1).to_bytes();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x01;
+
+    let result = b.receive(Message::from_bytes(&bytes).unwrap());
+
+    assert!(matches!(result, Err(GossipError::InvalidSignature)));
+}
+
+#[test]
+fn test_signature_survives_serialization() {
+    let a = Node::strict();
+    let heartbeat = a.heartbeat();
+
+    match Message::from_bytes(&a.signed(&heartbeat, 7).to_bytes()).unwrap() {
+        Message::Signed(signed) => {
+            assert!(signed.verify(&a.keypair.public_key()));
+            assert!(!signed.verify(&Keypair::generate().public_key()));
+            assert_eq!(signed.sender(), &a.id());
+            assert_eq!(signed.sequence(), 7);
+            assert_eq!(signed.message().unwrap().id(), heartbeat.id());
+        }
+        other => panic!("expected a signed message, got {:?}", other),
+    }
+}
+
+// ============================================================================
+// REPLAY PROTECTION
+// ============================================================================
+
+#[test]
+fn test_replayed_message_is_refused() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake(&mut a, &mut b);
+    let heartbeat = a.heartbeat();
+    let sent = a.send(&b.id(), heartbeat);
+
+    b.receive(sent.clone()).unwrap();
+    let result = b.receive(sent);
+
+    assert!(matches!(result, Err(GossipError::Replayed(_))));
+}
+
+#[test]
+fn test_sequence_must_increase() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake(&mut a, &mut b);
+
+    b.receive(a.signed(&a.heartbeat(), 10)).unwrap();
+    let result = b.receive(a.signed(&Message::Heartbeat(Heartbeat::new(a.id(), 1)), 5));
+
+    assert!(matches!(result, Err(GossipError::Replayed(5))));
+    b.receive(a.signed(&Message::Heartbeat(Heartbeat::new(a.id(), 2)), 11)).unwrap();
+}
+
+#[test]
+fn test_sequences_are_tracked_per_peer() {
+    let (mut a, mut b, mut c) = (Node::strict(), Node::strict(), Node::strict());
+    handshake(&mut a, &mut b);
+    handshake(&mut c, &mut b);
+
+    b.receive(a.signed(&a.heartbeat(), 100)).unwrap();
+    b.receive(c.signed(&c.heartbeat(), 1)).unwrap();
+}
+
+// ============================================================================
+// COMPATIBILITY
+// ============================================================================
+
+#[test]
+fn test_unsigned_messages_refused_by_default() {
+    let legacy = NodeId::generate();
+    let mut b = Node::new(GossipConfig::new());
+
+    let result = b.receive(Message::Heartbeat(Heartbeat::new(legacy, 1)));
+
+    assert!(matches!(result, Err(GossipError::UnsignedMessage)));
+    assert_eq!(b.engine.stats().unsigned_refused, 1);
+}
+
+#[test]
+fn test_unsigned_messages_accepted_in_compatibility_mode() {
+    let legacy = NodeId::generate();
+    let mut b = Node::new(GossipConfig::new().with_unsigned_messages(true));
+
+    b.receive(Message::Heartbeat(Heartbeat::new(legacy, 1))).unwrap();
+
+    assert_eq!(b.engine.stats().unsigned_refused, 0);
+    assert_eq!(b.engine.stats().messages_processed, 1);
+}
+
+#[test]
+fn test_stripped_message_from_known_signer_is_refused() {
+    let (mut a, mut b) = (Node::strict(), Node::new(GossipConfig::new().with_unsigned_messages(true)));
+    handshake(&mut a, &mut b);
+
+    let result = b.receive(a.heartbeat());
+
+    assert!(matches!(result, Err(GossipError::UnsignedMessage)));
+    assert_eq!(b.engine.stats().unsigned_refused, 1);
+}
+
+#[test]
+fn test_peer_without_signing_gets_plain_messages() {
+    let mut a = Node::new(GossipConfig::new().with_unsigned_messages(true));
+    let b = Node::new(GossipConfig::new());
+    // b's Hello doesn't offer signed messages
+    a.receive(Message::Hello(Hello::new(b.id(), Features::LEGACY))).unwrap();
+
+    let heartbeat = a.heartbeat();
+    assert!(matches!(a.send(&b.id(), heartbeat), Message::Heartbeat(_)));
+}
+
+#[test]
+fn test_engine_without_keypair_cannot_sign() {
+    let id = NodeId::generate();
+    let mut engine = GossipEngine::new(id.clone(), MeshState::new(id.clone()), GossipConfig::new());
+
+    assert!(engine.sign_message(&Message::Heartbeat(Heartbeat::new(id, 1))).is_none());
+    assert!(engine.generate_hello().public_key().is_none());
+}
+
+#[test]
+fn test_keypair_must_match_node_id() {
+    let id = NodeId::generate();
+    let engine = GossipEngine::new(id.clone(), MeshState::new(id), GossipConfig::new());
+
+    assert!(matches!(engine.with_keypair(Keypair::generate()), Err(GossipError::KeyMismatch)));
+}
+
+#[test]
+fn test_signed_message_needs_an_authenticated_path() {
+    let a = Node::strict();
+    let mut b = Node::strict();
+
+    let result = b.engine.process_message(a.signed(&a.heartbeat(), 1));
+
+    assert!(matches!(result, Err(GossipError::UnknownSigner)));
+}
+
+// ============================================================================
+// PEER PATH
+// ============================================================================
+
+/// Exchange Hellos both ways through `process_message_from`, without registries
+fn handshake_from(a: &mut Node, b: &mut Node) {
+    let events = b.engine.process_message_from(&a.id(), Message::Hello(a.engine.generate_hello())).unwrap();
+    for event in events {
+        if let GossipEvent::Forward(reply @ Message::Hello(_)) = event {
+            a.engine.process_message_from(&b.id(), reply).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_signed_message_accepted_from_peer() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake_from(&mut a, &mut b);
+
+    let heartbeat = a.heartbeat();
+    let signed = a.send(&b.id(), heartbeat);
+    assert!(matches!(signed, Message::Signed(_)));
+    b.engine.process_message_from(&a.id(), signed).unwrap();
+
+    assert_eq!(b.engine.stats().signatures_verified, 2);
+    assert_eq!(b.engine.stats().signatures_rejected, 0);
+}
+
+#[test]
+fn test_signed_bytes_accepted_from_peer() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake_from(&mut a, &mut b);
+
+    let heartbeat = a.heartbeat();
+    let signed = a.send(&b.id(), heartbeat);
+    let bytes = a.engine.encode_message_for(&b.id(), &signed);
+
+    assert!(b.engine.process_bytes_from(&a.id(), &bytes).is_ok());
+}
+
+#[test]
+fn test_unsigned_message_from_peer_refused_by_strict_node() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake_from(&mut a, &mut b);
+
+    let result = b.engine.process_message_from(&a.id(), a.heartbeat());
+
+    assert!(matches!(result, Err(GossipError::UnsignedMessage)));
+}
+
+#[test]
+fn test_replay_from_peer_is_refused() {
+    let (mut a, mut b) = (Node::strict(), Node::strict());
+    handshake_from(&mut a, &mut b);
+    let signed = a.signed(&a.heartbeat(), 5);
+
+    b.engine.process_message_from(&a.id(), signed.clone()).unwrap();
+    let result = b.engine.process_message_from(&a.id(), signed);
+
+    assert!(matches!(result, Err(GossipError::Replayed(5))));
+}
+
+#[test]
+fn test_message_signed_by_another_node_is_refused_from_peer() {
+    let (mut a, mut b, mut c) = (Node::strict(), Node::strict(), Node::strict());
+    handshake_from(&mut a, &mut b);
+    handshake_from(&mut c, &mut b);
+
+    let result = b.engine.process_message_from(&c.id(), a.signed(&a.heartbeat(), 1));
+
+    assert!(matches!(result, Err(GossipError::SenderMismatch)));
+}
@@ -0,0 +1,306 @@
+// Subscription Tests
+// Tests the async event stream: that it sees exactly what the polling methods
+// return, and how it drops events for a consumer that falls behind
+
+use p2pmesh::ledger::{MeshState, NodeId};
+use p2pmesh::sync::{
+    GossipConfig, GossipEngine, GossipEvent, GossipSubscription, Heartbeat, Message, PeerRegistry, PeerState,
+    DEFAULT_SUBSCRIPTION_CAPACITY,
+};
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{sleep, timeout, Duration};
+
+fn engine_with(config: GossipConfig) -> GossipEngine {
+    let id = NodeId::generate();
+    GossipEngine::new(id.clone(), MeshState::new(id), config.with_unsigned_messages(true))
+}
+
+fn engine() -> GossipEngine {
+    engine_with(GossipConfig::new())
+}
+
+/// A heartbeat from a new peer that is ahead of us, so it yields one `RequestSync`
+fn heartbeat_ahead() -> (NodeId, Message) {
+    let peer = NodeId::generate();
+    (peer.clone(), Message::Heartbeat(Heartbeat::new(peer, 1)))
+}
+
+/// Feed `count` heartbeats, returning the peers whose `RequestSync` they produce
+fn produce(engine: &mut GossipEngine, count: usize) -> Vec<NodeId> {
+    (0..count)
+        .map(|_| {
+            let (peer, msg) = heartbeat_ahead();
+            engine.process_message(msg).unwrap();
+            peer
+        })
+        .collect()
+}
+
+fn drain(subscription: &mut GossipSubscription) -> Vec<GossipEvent> {
+    std::iter::from_fn(|| subscription.try_recv()).collect()
+}
+
+fn sync_target(event: &GossipEvent) -> Option<&NodeId> {
+    match event {
+        GossipEvent::RequestSync(peer) => Some(peer),
+        _ => None,
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+// ============================================================================
+// SAME EVENTS AS POLLING
+// ============================================================================
+
+#[test]
+fn test_subscription_sees_returned_events() {
+    let mut engine = engine();
+    let mut subscription = engine.subscribe();
+    let (peer, msg) = heartbeat_ahead();
+
+    let returned = engine.process_message(msg).unwrap();
+
+    assert_eq!(returned.len(), 1);
+    assert!(matches!(&returned[0], GossipEvent::RequestSync(p) if *p == peer));
+    let received = drain(&mut subscription);
+    assert_eq!(received.len(), 1);
+    assert_eq!(sync_target(&received[0]), Some(&peer));
+}
+
+#[test]
+fn test_every_subscriber_gets_every_event() {
+    let mut engine = engine();
+    let mut first = engine.subscribe();
+    let mut second = engine.subscribe();
+
+    let peers = produce(&mut engine, 3);
+
+    for subscription in [&mut first, &mut second] {
+        let targets: Vec<NodeId> = drain(subscription).iter().filter_map(sync_target).cloned().collect();
+        assert_eq!(targets, peers);
+    }
+}
+
+#[test]
+fn test_registry_path_publishes_each_event_once() {
+    let mut engine = engine();
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let mut subscription = engine.subscribe();
+    let (peer, msg) = heartbeat_ahead();
+
+    let returned = engine.process_message_with_registry(&mut registry, msg).unwrap();
+    let received = drain(&mut subscription);
+
+    assert_eq!(returned.len(), 1);
+    assert_eq!(received.len(), 1);
+    assert_eq!(sync_target(&received[0]), Some(&peer));
+}
+
+#[test]
+fn test_bytes_path_publishes_each_event_once() {
+    let mut engine = engine();
+    let mut subscription = engine.subscribe();
+    let (peer, msg) = heartbeat_ahead();
+
+    let returned = engine.process_bytes_from(&peer, &msg.to_bytes()).unwrap();
+
+    assert_eq!(returned.len(), 1);
+    assert_eq!(drain(&mut subscription).len(), 1);
+}
+
+#[test]
+fn test_liveness_events_are_published() {
+    let mut engine = engine_with(GossipConfig::new().with_heartbeat_interval(10).with_max_missed(3));
+    let mut registry = PeerRegistry::new(NodeId::generate());
+    let peer = NodeId::generate();
+    let addr: SocketAddr = "10.3.0.1:8080".parse().unwrap();
+    registry.add_peer(peer.clone(), addr).unwrap();
+    registry.get_peer_mut(&peer).unwrap().set_state(PeerState::Connected);
+    let mut subscription = engine.subscribe();
+
+    let returned = engine.check_liveness_at(&mut registry, now_ms() + 60_000);
+
+    assert_eq!(returned.len(), 1);
+    let received = drain(&mut subscription);
+    assert!(matches!(&received[..], [GossipEvent::PeerLost(p)] if *p == peer));
+}
+
+#[test]
+fn test_polling_is_unchanged_without_subscribers() {
+    let mut engine = engine();
+    let (_, msg) = heartbeat_ahead();
+
+    assert_eq!(engine.process_message(msg).unwrap().len(), 1);
+    assert_eq!(engine.subscriber_count(), 0);
+}
+
+#[test]
+fn test_events_before_subscribing_are_not_replayed() {
+    let mut engine = engine();
+    produce(&mut engine, 2);
+
+    let mut subscription = engine.subscribe();
+
+    assert!(subscription.try_recv().is_none());
+}
+
+// ============================================================================
+// SLOW CONSUMERS
+// ============================================================================
+
+#[test]
+fn test_default_capacity() {
+    let mut engine = engine();
+
+    assert_eq!(engine.subscribe().capacity(), DEFAULT_SUBSCRIPTION_CAPACITY);
+}
+
+#[test]
+fn test_zero_capacity_still_holds_one_event() {
+    let mut engine = engine_with(GossipConfig::new().with_subscription_capacity(0));
+    let mut subscription = engine.subscribe();
+
+    let peers = produce(&mut engine, 3);
+
+    assert_eq!(subscription.capacity(), 1);
+    let received = drain(&mut subscription);
+    assert!(matches!(received[0], GossipEvent::Lagged(2)));
+    assert_eq!(sync_target(&received[1]), peers.last());
+}
+
+#[test]
+fn test_lagging_consumer_loses_oldest_events() {
+    let mut engine = engine_with(GossipConfig::new().with_subscription_capacity(4));
+    let mut subscription = engine.subscribe();
+
+    let peers = produce(&mut engine, 10);
+
+    assert_eq!(subscription.len(), 4);
+    let received = drain(&mut subscription);
+    assert!(matches!(received[0], GossipEvent::Lagged(6)));
+    let targets: Vec<NodeId> = received[1..].iter().filter_map(sync_target).cloned().collect();
+    assert_eq!(targets, peers[6..]);
+}
+
+#[test]
+fn test_lagged_is_reported_once_per_gap() {
+    let mut engine = engine_with(GossipConfig::new().with_subscription_capacity(2));
+    let mut subscription = engine.subscribe();
+
+    produce(&mut engine, 5);
+    assert!(matches!(subscription.try_recv(), Some(GossipEvent::Lagged(3))));
+    drain(&mut subscription);
+
+    let peers = produce(&mut engine, 2);
+    let received = drain(&mut subscription);
+    assert!(!received.iter().any(|e| matches!(e, GossipEvent::Lagged(_))));
+    assert_eq!(received.iter().filter_map(sync_target).cloned().collect::<Vec<_>>(), peers);
+}
+
+#[test]
+fn test_consumer_that_keeps_up_never_lags() {
+    let mut engine = engine_with(GossipConfig::new().with_subscription_capacity(2));
+    let mut subscription = engine.subscribe();
+
+    for _ in 0..10 {
+        produce(&mut engine, 2);
+        let received = drain(&mut subscription);
+        assert_eq!(received.len(), 2);
+        assert!(received.iter().all(|e| sync_target(e).is_some()));
+    }
+}
+
+#[test]
+fn test_slow_subscriber_does_not_affect_others() {
+    let mut engine = engine_with(GossipConfig::new().with_subscription_capacity(3));
+    let mut slow = engine.subscribe();
+    let mut fast = engine.subscribe();
+
+    let mut fast_targets = Vec::new();
+    let mut peers = Vec::new();
+    for _ in 0..3 {
+        peers.extend(produce(&mut engine, 3));
+        fast_targets.extend(drain(&mut fast).iter().filter_map(sync_target).cloned());
+    }
+
+    assert_eq!(fast_targets, peers);
+    assert!(matches!(slow.try_recv(), Some(GossipEvent::Lagged(6))));
+}
+
+#[tokio::test]
+async fn test_slow_async_consumer_accounts_for_every_event() {
+    const PRODUCED: usize = 200;
+    let mut engine = engine_with(GossipConfig::new().with_subscription_capacity(8));
+    let mut subscription = engine.subscribe();
+
+    let consumer = tokio::spawn(async move {
+        let (mut received, mut lagged) = (0u64, 0u64);
+        while let Some(event) = subscription.recv().await {
+            match event {
+                GossipEvent::Lagged(n) => lagged += n,
+                _ => received += 1,
+            }
+            sleep(Duration::from_millis(1)).await;
+        }
+        (received, lagged)
+    });
+
+    for _ in 0..PRODUCED {
+        produce(&mut engine, 1);
+        tokio::task::yield_now().await;
+    }
+    drop(engine);
+
+    let (received, lagged) = timeout(Duration::from_secs(10), consumer).await.unwrap().unwrap();
+    assert_eq!(received + lagged, PRODUCED as u64);
+    assert!(lagged > 0, "a consumer sleeping per event should fall behind");
+}
+
+// ============================================================================
+// ASYNC RECEIVE
+// ============================================================================
+
+#[tokio::test]
+async fn test_recv_wakes_when_an_event_arrives() {
+    let mut engine = engine();
+    let mut subscription = engine.subscribe();
+
+    let waiter = tokio::spawn(async move { subscription.recv().await });
+    sleep(Duration::from_millis(20)).await;
+    let peers = produce(&mut engine, 1);
+
+    let event = timeout(Duration::from_secs(5), waiter).await.unwrap().unwrap().unwrap();
+    assert_eq!(sync_target(&event), Some(&peers[0]));
+}
+
+#[tokio::test]
+async fn test_recv_drains_then_ends_when_engine_dropped() {
+    let mut engine = engine();
+    let mut subscription = engine.subscribe();
+    produce(&mut engine, 2);
+
+    drop(engine);
+
+    assert!(subscription.is_closed());
+    assert!(subscription.recv().await.is_some());
+    assert!(subscription.recv().await.is_some());
+    assert!(subscription.recv().await.is_none());
+}
+
+#[test]
+fn test_dropped_subscription_is_forgotten() {
+    let mut engine = engine();
+    let kept = engine.subscribe();
+    let dropped = engine.subscribe();
+    assert_eq!(engine.subscriber_count(), 2);
+
+    drop(dropped);
+    produce(&mut engine, 1);
+
+    assert_eq!(engine.subscriber_count(), 1);
+    assert_eq!(kept.len(), 1);
+}
@@ -0,0 +1,58 @@
+// btleplug BLE Adapter Tests
+// Tests for the host-radio BleAdapter that need no Bluetooth controller
+// Run with `cargo test --features ble`
+
+#![cfg(feature = "ble")]
+
+use p2pmesh::transport::{BleAdapter, BtleplugAdapter, PeerAddress, TransportError};
+
+const SERVICE: &str = "6e400001-b5a3-f393-e0a9-e50e24dcca9e";
+const CHARACTERISTIC: &str = "6e400002-b5a3-f393-e0a9-e50e24dcca9e";
+
+// ============================================================================
+// WITHOUT A RADIO
+// ============================================================================
+
+#[tokio::test]
+async fn test_btleplug_adapter_needs_power_on() {
+    let mut adapter = BtleplugAdapter::new();
+    let peer = PeerAddress::ble("AA:BB:CC:DD:EE:FF");
+
+    assert!(matches!(adapter.start_scan(SERVICE).await, Err(TransportError::NotRunning)));
+    assert!(matches!(
+        adapter.connect(&peer, SERVICE, CHARACTERISTIC, 247).await,
+        Err(TransportError::NotRunning)
+    ));
+    assert!(adapter.poll().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_btleplug_adapter_cannot_advertise() {
+    let mut adapter = BtleplugAdapter::new();
+
+    assert!(matches!(
+        adapter.start_advertising(SERVICE, Some("mesh")).await,
+        Err(TransportError::HardwareUnavailable)
+    ));
+}
+
+#[tokio::test]
+async fn test_btleplug_adapter_rejects_non_ble_addresses_and_bad_uuids() {
+    let mut adapter = BtleplugAdapter::new();
+
+    assert!(matches!(
+        adapter.write(&PeerAddress::tcp("127.0.0.1", 9000), b"chunk").await,
+        Err(TransportError::InvalidAddress(_))
+    ));
+    assert!(matches!(adapter.start_scan("not-a-uuid").await, Err(TransportError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_btleplug_adapter_write_needs_a_link() {
+    let mut adapter = BtleplugAdapter::new();
+    let peer = PeerAddress::ble("AA:BB:CC:DD:EE:FF");
+
+    assert!(matches!(adapter.write(&peer, b"chunk").await, Err(TransportError::NotConnected)));
+    assert_eq!(adapter.rssi(&peer), None);
+}
+
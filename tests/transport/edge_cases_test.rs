@@ -5,8 +5,6 @@ use p2pmesh::transport::{
     Transport, TransportConfig, TransportError, TransportEvent, TransportState,
     ConnectionId, ConnectionInfo, PeerAddress, TcpTransport, TcpTransportConfig,
 };
-use p2pmesh::sync::Message;
-use p2pmesh::ledger::NodeId;
 use std::time::Duration;
 
 // ============================================================================
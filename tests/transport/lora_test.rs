@@ -5,9 +5,10 @@
 use p2pmesh::transport::{
     LoraTransport, LoraTransportConfig, LoraModulation, LoraSpreadingFactor,
     LoraBandwidth, LoraCodingRate, Transport, TransportConfig, TransportError,
-    TransportEvent, TransportState, PeerAddress, ConnectionId,
+    TransportEvent, TransportState, PeerAddress,
+    LoraMeshHeader, LoraRadio, LoraRadioFrame, LORA_HEADER_LEN, DutyCycleTracker,
 };
-use p2pmesh::ledger::NodeId;
+use std::sync::{Arc, Mutex};
 
 // ============================================================================
 // LORA TRANSPORT CONFIG
@@ -18,7 +19,8 @@ fn test_lora_config_default() {
     let config = LoraTransportConfig::default();
 
     assert!(config.frequency > 0);
-    assert!(config.device_id > 0 || config.device_id == 0);
+    // Unsigned, so every default device id is in range
+    let _: u8 = config.device_id;
     assert!(config.spreading_factor.is_valid());
     assert!(config.bandwidth.is_valid());
 }
@@ -477,7 +479,8 @@ async fn test_lora_transport_send_exceeds_max_payload() {
 
     if transport.start().await.is_ok() {
         let addr = PeerAddress::lora(0x01, 915_000_000);
-        let large_data = vec![0u8; 300]; // Exceeds typical 255 byte limit
+        // More than 255 fragments of (255 - header) bytes
+        let large_data = vec![0u8; 255 * (255 - LORA_HEADER_LEN) + 1];
 
         let result = transport.send_to(&addr, &large_data).await;
 
@@ -604,6 +607,112 @@ async fn test_lora_transport_can_transmit() {
     assert!(transport.can_transmit());
 }
 
+fn frame_airtime(config: &LoraTransportConfig, frame_len: usize) -> u64 {
+    LoraModulation::new(config.spreading_factor, config.bandwidth, config.coding_rate)
+        .time_on_air_ms(frame_len) as u64
+}
+
+#[test]
+fn test_duty_cycle_budget() {
+    // ETSI 1% over an hour = 36 s; 10% = 360 s
+    assert_eq!(DutyCycleTracker::new(1.0, 3_600_000).budget_ms(), 36_000);
+    assert_eq!(DutyCycleTracker::new(10.0, 3_600_000).budget_ms(), 360_000);
+    assert_eq!(DutyCycleTracker::new(100.0, 10_000).budget_ms(), 10_000);
+}
+
+#[test]
+fn test_duty_cycle_one_percent_window() {
+    let mut tracker = DutyCycleTracker::new(1.0, 3_600_000);
+
+    // 35 one-second packets leave room for exactly one more
+    for i in 1..=35 {
+        tracker.record(i * 1_000, 1_000);
+    }
+    assert_eq!(tracker.used_ms(35_000), 35_000);
+    assert_eq!(tracker.time_until_available(35_000, 1_000), 0);
+
+    tracker.record(36_000, 1_000);
+    assert_eq!(tracker.used_ms(36_000), 36_000);
+
+    // Next packet waits until the first one (ended at 1 s) leaves the window
+    assert_eq!(tracker.time_until_available(36_000, 1_000), 3_601_000 - 36_000);
+    assert_eq!(tracker.time_until_available(3_601_000, 1_000), 0);
+}
+
+#[test]
+fn test_duty_cycle_ten_percent_window() {
+    let mut tracker = DutyCycleTracker::new(10.0, 10_000);
+
+    // 10% of 10 s = 1 s: ten 100 ms packets
+    for i in 1..=10 {
+        assert_eq!(tracker.time_until_available(i * 100 - 100, 100), 0);
+        tracker.record(i * 100, 100);
+    }
+    assert_eq!(tracker.used_ms(1_000), 1_000);
+
+    // The 11th waits for the first packet (ended at 100 ms) to age out
+    assert_eq!(tracker.time_until_available(1_000, 100), 100 + 10_000 - 1_000);
+
+    // A 250 ms packet needs three packets to age out
+    assert_eq!(tracker.time_until_available(1_000, 250), 300 + 10_000 - 1_000);
+}
+
+#[test]
+fn test_duty_cycle_window_slides() {
+    let mut tracker = DutyCycleTracker::new(10.0, 10_000);
+    tracker.record(1_000, 500);
+    tracker.record(6_000, 500);
+
+    assert_eq!(tracker.used_ms(6_000), 1_000);
+    assert_eq!(tracker.used_ms(11_000), 500);
+    assert_eq!(tracker.used_ms(16_000), 0);
+}
+
+#[test]
+fn test_duty_cycle_oversized_frame_waits_for_empty_window() {
+    let mut tracker = DutyCycleTracker::new(1.0, 10_000);
+    tracker.record(500, 50);
+
+    // 200 ms exceeds the 100 ms budget; best effort is an empty window
+    assert_eq!(tracker.time_until_available(1_000, 200), 500 + 10_000 - 1_000);
+    assert_eq!(DutyCycleTracker::new(1.0, 10_000).time_until_available(0, 200), 0);
+}
+
+#[tokio::test]
+async fn test_lora_transport_tracks_airtime() {
+    let config = LoraTransportConfig::new().with_duty_cycle_percent(100.0);
+    let airtime = frame_airtime(&config, LORA_HEADER_LEN + 10);
+    let mut transport = LoraTransport::new(config);
+    transport.start().await.unwrap();
+
+    assert_eq!(transport.airtime_used_ms(), 0);
+    transport.send_to(&PeerAddress::lora(0x02, 915_000_000), &[0u8; 10]).await.unwrap();
+
+    assert_eq!(transport.airtime_used_ms(), airtime);
+}
+
+#[tokio::test]
+async fn test_lora_transport_budget_exhausts() {
+    let config = LoraTransportConfig::new()
+        .with_duty_cycle_percent(10.0)
+        .with_duty_cycle_window_ms(10_000);
+    let airtime = frame_airtime(&config, LORA_HEADER_LEN + 10);
+    let mut transport = LoraTransport::new(config);
+    transport.start().await.unwrap();
+    let addr = PeerAddress::lora(0x02, 915_000_000);
+
+    let mut sent = 0;
+    while transport.send_to(&addr, &[0u8; 10]).await.is_ok() {
+        sent += 1;
+    }
+
+    // Exactly as many packets as fit in 1 s of airtime
+    assert_eq!(sent, 1_000 / airtime);
+    assert_eq!(transport.airtime_used_ms(), sent * airtime);
+    assert!(!transport.can_transmit());
+    assert!(transport.time_until_transmit_ms() <= 10_000);
+}
+
 // ============================================================================
 // LORA TRANSPORT CAD (CHANNEL ACTIVITY DETECTION)
 // ============================================================================
@@ -729,3 +838,484 @@ fn test_lora_transport_error_channel_busy() {
     assert!(matches!(error, TransportError::LoraChannelBusy));
     assert!(error.is_retryable());
 }
+
+// ============================================================================
+// LORA FRAGMENTATION
+// ============================================================================
+
+/// Transmitted frames with the frequency each was sent on
+type SentFrames = Arc<Mutex<Vec<(u32, Vec<u8>)>>>;
+
+/// Radio that records transmissions and delivers frames pushed into its inbox
+#[derive(Clone, Default)]
+struct MockRadio {
+    sent: SentFrames,
+    inbox: Arc<Mutex<Vec<LoraRadioFrame>>>,
+}
+
+impl MockRadio {
+    fn sent_frames(&self) -> Vec<Vec<u8>> {
+        self.sent.lock().unwrap().iter().map(|(_, frame)| frame.clone()).collect()
+    }
+
+    /// Frames transmitted since the last call
+    fn take_sent(&self) -> Vec<Vec<u8>> {
+        self.sent.lock().unwrap().drain(..).map(|(_, frame)| frame).collect()
+    }
+
+    fn deliver(&self, frame: &[u8]) {
+        self.inbox.lock().unwrap().push(LoraRadioFrame {
+            data: frame.to_vec(),
+            rssi: -90,
+            snr: 7.5,
+            frequency: 915_000_000,
+        });
+    }
+}
+
+impl LoraRadio for MockRadio {
+    async fn transmit(&mut self, frequency: u32, frame: &[u8]) -> Result<(), TransportError> {
+        self.sent.lock().unwrap().push((frequency, frame.to_vec()));
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Vec<LoraRadioFrame> {
+        std::mem::take(&mut *self.inbox.lock().unwrap())
+    }
+}
+
+fn sf12_config(device_id: u8) -> LoraTransportConfig {
+    LoraTransportConfig::new()
+        .with_device_id(device_id)
+        .with_spreading_factor(LoraSpreadingFactor::SF12)
+        .with_duty_cycle_percent(100.0)
+}
+
+async fn started(config: LoraTransportConfig) -> (LoraTransport<MockRadio>, MockRadio) {
+    let radio = MockRadio::default();
+    let mut transport = LoraTransport::with_radio(config, radio.clone());
+    transport.start().await.unwrap();
+    (transport, radio)
+}
+
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+fn received_payloads(events: &[TransportEvent]) -> Vec<Vec<u8>> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            TransportEvent::LoraPacketReceived { data, .. } => Some(data.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_lora_mesh_header_fragment_fields() {
+    let header = LoraMeshHeader::new(0x01, 0x02, 0x00, 0).with_fragment(7, 2, 5);
+
+    let restored = LoraMeshHeader::from_bytes(&header.to_bytes()).unwrap();
+
+    assert_eq!(restored.message_id(), 7);
+    assert_eq!(restored.fragment_index(), 2);
+    assert_eq!(restored.fragment_total(), 5);
+    assert!(restored.is_fragmented());
+}
+
+#[test]
+fn test_lora_mesh_header_defaults_to_single_fragment() {
+    let header = LoraMeshHeader::new(0x01, 0x02, 0x00, 0);
+
+    assert_eq!(header.fragment_index(), 0);
+    assert_eq!(header.fragment_total(), 1);
+    assert!(!header.is_fragmented());
+}
+
+#[test]
+fn test_lora_mesh_header_rejects_bad_fragment_index() {
+    let mut bytes = LoraMeshHeader::new(0x01, 0x02, 0x00, 0).to_bytes();
+    bytes[5] = 3; // index
+    bytes[6] = 3; // total
+
+    assert!(LoraMeshHeader::from_bytes(&bytes).is_err());
+}
+
+#[tokio::test]
+async fn test_lora_fragments_fit_sf12_frames() {
+    let (mut sender, radio) = started(sf12_config(0x01)).await;
+    let max = LoraModulation::new(
+        LoraSpreadingFactor::SF12,
+        LoraBandwidth::BW125,
+        LoraCodingRate::CR4_5,
+    )
+    .max_payload_size();
+
+    let sent = sender.send_to(&PeerAddress::lora(0x02, 915_000_000), &payload(200)).await.unwrap();
+
+    let frames = radio.sent_frames();
+    assert_eq!(sent, 200);
+    assert_eq!(frames.len(), 200usize.div_ceil(max - LORA_HEADER_LEN));
+    assert!(frames.iter().all(|frame| frame.len() <= max));
+    assert_eq!(sender.stats().packets_sent, frames.len() as u64);
+    assert_eq!(sender.stats().bytes_sent, 200);
+}
+
+#[tokio::test]
+async fn test_lora_reassembles_200_byte_payload_at_sf12() {
+    let (mut sender, sender_radio) = started(sf12_config(0x01)).await;
+    let (mut receiver, receiver_radio) = started(sf12_config(0x02)).await;
+    let data = payload(200);
+
+    sender.send_to(&PeerAddress::lora(0x02, 915_000_000), &data).await.unwrap();
+    for frame in sender_radio.sent_frames() {
+        receiver_radio.deliver(&frame);
+    }
+
+    let events = receiver.poll_events().await;
+
+    assert_eq!(received_payloads(&events), vec![data]);
+    assert_eq!(receiver.pending_reassembly(), 0);
+    assert_eq!(receiver.last_rssi(), Some(-90));
+    assert_eq!(receiver.stats().messages_received, 1);
+}
+
+#[tokio::test]
+async fn test_lora_reassembles_out_of_order_fragments() {
+    let (mut sender, sender_radio) = started(sf12_config(0x01)).await;
+    let (mut receiver, receiver_radio) = started(sf12_config(0x02)).await;
+    let data = payload(200);
+
+    sender.send_to(&PeerAddress::lora(0x02, 915_000_000), &data).await.unwrap();
+    let mut frames = sender_radio.sent_frames();
+    frames.reverse();
+
+    let mut delivered = Vec::new();
+    for frame in frames {
+        receiver_radio.deliver(&frame);
+        delivered.extend(received_payloads(&receiver.poll_events().await));
+    }
+
+    assert_eq!(delivered, vec![data]);
+}
+
+#[tokio::test]
+async fn test_lora_duplicate_fragment_ignored() {
+    let (mut sender, sender_radio) = started(sf12_config(0x01)).await;
+    let (mut receiver, receiver_radio) = started(sf12_config(0x02)).await;
+    let data = payload(200);
+
+    sender.send_to(&PeerAddress::lora(0x02, 915_000_000), &data).await.unwrap();
+    let frames = sender_radio.sent_frames();
+    receiver_radio.deliver(&frames[0]);
+    for frame in &frames {
+        receiver_radio.deliver(frame);
+    }
+
+    let events = receiver.poll_events().await;
+
+    assert_eq!(received_payloads(&events), vec![data]);
+}
+
+#[tokio::test]
+async fn test_lora_small_payload_single_frame() {
+    let (mut sender, sender_radio) = started(sf12_config(0x01)).await;
+    let (mut receiver, receiver_radio) = started(sf12_config(0x02)).await;
+
+    sender.send_to(&PeerAddress::lora(0x02, 915_000_000), b"hello").await.unwrap();
+    let frames = sender_radio.sent_frames();
+    assert_eq!(frames.len(), 1);
+    receiver_radio.deliver(&frames[0]);
+
+    let events = receiver.poll_events().await;
+
+    assert_eq!(received_payloads(&events), vec![b"hello".to_vec()]);
+}
+
+#[tokio::test]
+async fn test_lora_fragments_respect_duty_cycle() {
+    // 1% of 10 minutes = 6 s of airtime, enough for two full SF12 frames
+    let config = sf12_config(0x01)
+        .with_duty_cycle_percent(1.0)
+        .with_duty_cycle_window_ms(600_000);
+    let (mut sender, radio) = started(config).await;
+    let addr = PeerAddress::lora(0x02, 915_000_000);
+
+    sender.send_to(&addr, &payload(200)).await.unwrap();
+
+    // Only the first fragments fit in the duty cycle; the rest wait
+    assert_eq!(radio.sent_frames().len(), 2);
+    assert!(sender.pending_fragments() > 0);
+    assert!(!sender.can_transmit());
+
+    sender.poll_events().await;
+    assert_eq!(radio.sent_frames().len(), 2);
+
+    let result = sender.send_to(&addr, b"next").await;
+    assert!(matches!(result, Err(TransportError::LoraChannelBusy)));
+}
+
+#[tokio::test]
+async fn test_lora_missing_fragment_times_out() {
+    let (mut sender, sender_radio) = started(sf12_config(0x01)).await;
+    let (mut receiver, receiver_radio) = started(sf12_config(0x02).with_fragment_timeout_ms(50)).await;
+
+    sender.send_to(&PeerAddress::lora(0x02, 915_000_000), &payload(200)).await.unwrap();
+    let frames = sender_radio.sent_frames();
+    for frame in &frames[..frames.len() - 1] {
+        receiver_radio.deliver(frame);
+    }
+
+    let events = receiver.poll_events().await;
+    assert!(received_payloads(&events).is_empty());
+    assert_eq!(receiver.pending_reassembly(), 1);
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    receiver.poll_events().await;
+    assert_eq!(receiver.pending_reassembly(), 0);
+
+    // The late fragment alone can't complete the dropped message
+    receiver_radio.deliver(&frames[frames.len() - 1]);
+    let events = receiver.poll_events().await;
+    assert!(received_payloads(&events).is_empty());
+}
+
+#[tokio::test]
+async fn test_lora_ignores_frames_for_other_devices() {
+    let (mut sender, sender_radio) = started(sf12_config(0x01)).await;
+    let (mut receiver, receiver_radio) = started(sf12_config(0x03)).await;
+
+    sender.send_to(&PeerAddress::lora(0x02, 915_000_000), b"not for 0x03").await.unwrap();
+    for frame in sender_radio.sent_frames() {
+        receiver_radio.deliver(&frame);
+    }
+
+    let events = receiver.poll_events().await;
+
+    assert!(received_payloads(&events).is_empty());
+    assert_eq!(receiver.stats().packets_received, 0);
+}
+
+#[tokio::test]
+async fn test_lora_broadcast_fragments_reassembled() {
+    let (mut sender, sender_radio) = started(sf12_config(0x01)).await;
+    let (mut receiver, receiver_radio) = started(sf12_config(0x03)).await;
+    let data = payload(120);
+
+    sender.broadcast(&data).await.unwrap();
+    for frame in sender_radio.sent_frames() {
+        receiver_radio.deliver(&frame);
+    }
+
+    let events = receiver.poll_events().await;
+
+    assert_eq!(received_payloads(&events), vec![data]);
+}
+
+// ============================================================================
+// LORA MULTI-HOP FORWARDING
+// ============================================================================
+
+/// Move everything `from` transmitted to the radios in range
+fn air(from: &MockRadio, in_range: &[&MockRadio]) -> usize {
+    let frames = from.take_sent();
+    for frame in &frames {
+        for radio in in_range {
+            radio.deliver(frame);
+        }
+    }
+    frames.len()
+}
+
+fn relay_config(device_id: u8) -> LoraTransportConfig {
+    sf12_config(device_id).with_forwarding(true)
+}
+
+#[test]
+fn test_lora_config_forwarding() {
+    let config = LoraTransportConfig::new().with_forwarding(true).with_max_hops(5);
+
+    assert!(config.forwarding);
+    assert_eq!(config.max_hops, 5);
+}
+
+#[test]
+fn test_lora_config_forwarding_disabled_by_default() {
+    let config = LoraTransportConfig::default();
+
+    assert!(!config.forwarding);
+    assert_eq!(config.max_hops, 3);
+}
+
+#[tokio::test]
+async fn test_lora_line_topology_reaches_node_two_hops_away() {
+    // A <-> B <-> C: A and C are out of range of each other
+    let (mut a, radio_a) = started(sf12_config(0x0A)).await;
+    let (mut b, radio_b) = started(relay_config(0x0B)).await;
+    let (mut c, radio_c) = started(sf12_config(0x0C)).await;
+    let data = payload(200);
+
+    a.send_to(&PeerAddress::lora(0x0C, 915_000_000), &data).await.unwrap();
+    air(&radio_a, &[&radio_b]);
+
+    let relay_events = b.poll_events().await;
+    assert!(received_payloads(&relay_events).is_empty());
+    assert!(air(&radio_b, &[&radio_a, &radio_c]) > 0);
+
+    let events = c.poll_events().await;
+    assert_eq!(received_payloads(&events), vec![data]);
+    assert!(received_payloads(&a.poll_events().await).is_empty());
+}
+
+#[tokio::test]
+async fn test_lora_line_topology_without_relay() {
+    let (mut a, radio_a) = started(sf12_config(0x0A)).await;
+    let (mut b, radio_b) = started(sf12_config(0x0B)).await;
+    let (mut c, radio_c) = started(sf12_config(0x0C)).await;
+
+    a.send_to(&PeerAddress::lora(0x0C, 915_000_000), b"hello").await.unwrap();
+    air(&radio_a, &[&radio_b]);
+    b.poll_events().await;
+
+    assert_eq!(air(&radio_b, &[&radio_a, &radio_c]), 0);
+    assert!(received_payloads(&c.poll_events().await).is_empty());
+}
+
+#[tokio::test]
+async fn test_lora_forwarded_frame_increments_hop_count() {
+    let (mut a, radio_a) = started(sf12_config(0x0A)).await;
+    let (mut b, radio_b) = started(relay_config(0x0B)).await;
+
+    a.send_to(&PeerAddress::lora(0x0C, 915_000_000), b"hop").await.unwrap();
+    air(&radio_a, &[&radio_b]);
+    b.poll_events().await;
+
+    let relayed = radio_b.take_sent();
+    assert_eq!(relayed.len(), 1);
+    let header = LoraMeshHeader::from_bytes(&relayed[0]).unwrap();
+    assert_eq!(header.source(), 0x0A);
+    assert_eq!(header.destination(), 0x0C);
+    assert_eq!(header.hop_count(), 1);
+    assert_eq!(&relayed[0][LORA_HEADER_LEN..], b"hop");
+    assert_eq!(b.packets_forwarded(), 1);
+}
+
+#[tokio::test]
+async fn test_lora_forwarding_respects_max_hops() {
+    // A <-> B <-> C <-> D with max_hops 1: B may relay, C may not
+    let (mut a, radio_a) = started(sf12_config(0x0A)).await;
+    let (mut b, radio_b) = started(relay_config(0x0B).with_max_hops(1)).await;
+    let (mut c, radio_c) = started(relay_config(0x0C).with_max_hops(1)).await;
+    let (mut d, radio_d) = started(sf12_config(0x0D)).await;
+
+    a.send_to(&PeerAddress::lora(0x0D, 915_000_000), b"far away").await.unwrap();
+    air(&radio_a, &[&radio_b]);
+    b.poll_events().await;
+    assert_eq!(air(&radio_b, &[&radio_a, &radio_c]), 1);
+
+    c.poll_events().await;
+    assert_eq!(air(&radio_c, &[&radio_b, &radio_d]), 0);
+    assert!(received_payloads(&d.poll_events().await).is_empty());
+}
+
+#[tokio::test]
+async fn test_lora_relay_drops_duplicate_packets() {
+    let (mut a, radio_a) = started(sf12_config(0x0A)).await;
+    let (mut b, radio_b) = started(relay_config(0x0B)).await;
+
+    a.send_to(&PeerAddress::lora(0x0C, 915_000_000), b"once").await.unwrap();
+    let frame = radio_a.take_sent().remove(0);
+    radio_b.deliver(&frame);
+    radio_b.deliver(&frame);
+    b.poll_events().await;
+
+    assert_eq!(radio_b.take_sent().len(), 1);
+    assert_eq!(b.packets_forwarded(), 1);
+}
+
+#[tokio::test]
+async fn test_lora_relays_do_not_ping_pong() {
+    // Two relays in range of each other must not bounce a packet forever
+    let (mut a, radio_a) = started(sf12_config(0x0A)).await;
+    let (mut b, radio_b) = started(relay_config(0x0B).with_max_hops(10)).await;
+    let (mut c, radio_c) = started(relay_config(0x0C).with_max_hops(10)).await;
+
+    a.send_to(&PeerAddress::lora(0x0D, 915_000_000), b"loop").await.unwrap();
+    air(&radio_a, &[&radio_b]);
+
+    let mut transmissions = 0;
+    for _ in 0..5 {
+        b.poll_events().await;
+        transmissions += air(&radio_b, &[&radio_c]);
+        c.poll_events().await;
+        transmissions += air(&radio_c, &[&radio_b]);
+    }
+
+    assert_eq!(transmissions, 2);
+}
+
+#[tokio::test]
+async fn test_lora_relay_delivers_and_rebroadcasts_broadcasts() {
+    let (mut a, radio_a) = started(sf12_config(0x0A)).await;
+    let (mut b, radio_b) = started(relay_config(0x0B)).await;
+    let (mut c, radio_c) = started(sf12_config(0x0C)).await;
+    let data = payload(120);
+
+    a.broadcast(&data).await.unwrap();
+    air(&radio_a, &[&radio_b]);
+
+    let relay_events = b.poll_events().await;
+    assert_eq!(received_payloads(&relay_events), vec![data.clone()]);
+
+    air(&radio_b, &[&radio_a, &radio_c]);
+    assert_eq!(received_payloads(&c.poll_events().await), vec![data]);
+}
+
+#[tokio::test]
+async fn test_lora_relay_does_not_forward_packets_for_itself() {
+    let (mut a, radio_a) = started(sf12_config(0x0A)).await;
+    let (mut b, radio_b) = started(relay_config(0x0B)).await;
+
+    a.send_to(&PeerAddress::lora(0x0B, 915_000_000), b"direct").await.unwrap();
+    air(&radio_a, &[&radio_b]);
+
+    let events = b.poll_events().await;
+
+    assert_eq!(received_payloads(&events), vec![b"direct".to_vec()]);
+    assert!(radio_b.take_sent().is_empty());
+}
+
+// ============================================================================
+// LORA RATE LIMIT
+// ============================================================================
+
+#[tokio::test]
+async fn test_lora_rate_limit_delays_large_payload() {
+    let config = sf12_config(0x01).with_base_config(TransportConfig::new().with_rate_limit(100));
+    let (mut sender, radio) = started(config).await;
+    let data = payload(200);
+
+    let started = std::time::Instant::now();
+    sender.send_to(&PeerAddress::lora(0x02, 915_000_000), &data).await.unwrap();
+    let elapsed = started.elapsed();
+
+    // One second of burst, then 100 bytes at 100 B/s
+    assert!(elapsed >= std::time::Duration::from_millis(990), "sent in {:?}", elapsed);
+    assert_eq!(sender.stats().throttled_sends, 1);
+    assert!(!radio.sent_frames().is_empty());
+}
+
+#[tokio::test]
+async fn test_lora_rate_limit_per_destination() {
+    let config = sf12_config(0x01).with_base_config(TransportConfig::new().with_rate_limit(200));
+    let (mut sender, _radio) = started(config).await;
+
+    let started = std::time::Instant::now();
+    sender.send_to(&PeerAddress::lora(0x02, 915_000_000), &payload(200)).await.unwrap();
+    sender.send_to(&PeerAddress::lora(0x03, 915_000_000), &payload(200)).await.unwrap();
+
+    assert!(started.elapsed() < std::time::Duration::from_millis(500));
+    assert_eq!(sender.stats().throttled_sends, 0);
+    assert_eq!(sender.stats().throttle_delay_ms, 0);
+}
@@ -0,0 +1,298 @@
+// mDNS Discovery Tests
+// Tests for service record encoding and LAN discovery of TCP transports
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::ledger::NodeId;
+use p2pmesh::transport::{
+    mdns_query_packet, MdnsAnnouncement, MdnsConfig, MdnsMessage, PeerAddress, TcpTransport,
+    TcpTransportConfig, Transport, TransportEvent, MDNS_PORT, MDNS_SERVICE_TYPE,
+};
+use tokio::time::{sleep, Duration};
+
+const TEST_SERVICE: &str = "_p2pmesh-test._tcp.local";
+
+fn test_mdns_config(port: u16) -> MdnsConfig {
+    MdnsConfig::new()
+        .with_service_type(TEST_SERVICE)
+        .with_port(port)
+        .with_announce_interval_secs(1)
+}
+
+/// Poll until a `DeviceDiscovered` event names `name`, or give up after ~5s
+async fn wait_for_discovery(transport: &mut TcpTransport, name: &str) -> Option<PeerAddress> {
+    for _ in 0..100 {
+        for event in transport.poll_events().await {
+            if let TransportEvent::DeviceDiscovered { address, name: Some(found), .. } = event {
+                if found == name {
+                    return Some(address);
+                }
+            }
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    None
+}
+
+// ============================================================================
+// MDNS CONFIG
+// ============================================================================
+
+#[test]
+fn test_mdns_config_default() {
+    let config = MdnsConfig::default();
+
+    assert_eq!(config.service_type, MDNS_SERVICE_TYPE);
+    assert_eq!(config.service_type, "_p2pmesh._tcp.local");
+    assert_eq!(config.port, MDNS_PORT);
+    assert_eq!(config.interface, "0.0.0.0");
+}
+
+#[test]
+fn test_tcp_config_mdns_disabled_by_default() {
+    assert!(TcpTransportConfig::default().mdns.is_none());
+    assert!(TcpTransportConfig::new().with_mdns(MdnsConfig::new()).mdns.is_some());
+}
+
+// ============================================================================
+// RECORD ENCODING
+// ============================================================================
+
+#[test]
+fn test_announcement_round_trips() {
+    let node_id = NodeId::generate();
+    let announcement = MdnsAnnouncement::new(node_id.clone(), 9000);
+
+    let packet = announcement.to_packet(MDNS_SERVICE_TYPE, 120).unwrap();
+    let message = MdnsMessage::parse(&packet, MDNS_SERVICE_TYPE).unwrap();
+
+    assert!(!message.queries_service);
+    assert_eq!(message.announcements, vec![announcement]);
+    assert_eq!(message.announcements[0].node_id(), &node_id);
+    assert_eq!(message.announcements[0].port(), 9000);
+    assert_eq!(message.announcements[0].did(), None);
+}
+
+#[test]
+fn test_announcement_carries_did() {
+    let did = Did::from_public_key(&Keypair::generate().public_key());
+    let announcement = MdnsAnnouncement::new(NodeId::generate(), 7000).with_did(&did.to_string());
+
+    let packet = announcement.to_packet(MDNS_SERVICE_TYPE, 120).unwrap();
+    let parsed = &MdnsMessage::parse(&packet, MDNS_SERVICE_TYPE).unwrap().announcements[0];
+
+    assert_eq!(parsed.did(), Some(did.to_string().as_str()));
+    assert_eq!(parsed.display_name(), did.to_string());
+}
+
+#[test]
+fn test_display_name_falls_back_to_node_id() {
+    let node_id = NodeId::generate();
+    let announcement = MdnsAnnouncement::new(node_id.clone(), 7000);
+
+    assert_eq!(announcement.display_name(), hex::encode(node_id.as_bytes()));
+}
+
+#[test]
+fn test_instance_name_fits_dns_label() {
+    let announcement = MdnsAnnouncement::new(NodeId::generate(), 7000);
+    let instance = announcement.instance_name(MDNS_SERVICE_TYPE);
+
+    let label = instance.split('.').next().unwrap();
+    assert!(label.len() <= 63);
+    assert!(instance.ends_with(MDNS_SERVICE_TYPE));
+}
+
+#[test]
+fn test_other_service_is_ignored() {
+    let packet = MdnsAnnouncement::new(NodeId::generate(), 9000).to_packet("_other._tcp.local", 120).unwrap();
+    let message = MdnsMessage::parse(&packet, MDNS_SERVICE_TYPE).unwrap();
+
+    assert!(message.announcements.is_empty());
+}
+
+#[test]
+fn test_query_packet_asks_for_service() {
+    let query = mdns_query_packet(MDNS_SERVICE_TYPE).unwrap();
+
+    assert!(MdnsMessage::parse(&query, MDNS_SERVICE_TYPE).unwrap().queries_service);
+    assert!(!MdnsMessage::parse(&query, "_other._tcp.local").unwrap().queries_service);
+}
+
+#[test]
+fn test_truncated_packet_is_rejected() {
+    let packet = MdnsAnnouncement::new(NodeId::generate(), 9000).to_packet(MDNS_SERVICE_TYPE, 120).unwrap();
+
+    assert!(MdnsMessage::parse(&packet[..packet.len() - 5], MDNS_SERVICE_TYPE).is_none());
+    assert!(MdnsMessage::parse(&[0, 1], MDNS_SERVICE_TYPE).is_none());
+}
+
+#[test]
+fn test_compressed_names_are_followed() {
+    let node_id = NodeId::generate();
+    let label = hex::encode(&node_id.as_bytes()[..16]);
+    let txt = format!("node={}", hex::encode(node_id.as_bytes()));
+
+    // Header: one answer (SRV) and one additional record (TXT)
+    let mut packet = vec![0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 1];
+    let instance_offset = packet.len() as u8;
+    packet.push(label.len() as u8);
+    packet.extend_from_slice(label.as_bytes());
+    for part in ["_p2pmesh", "_tcp", "local"] {
+        packet.push(part.len() as u8);
+        packet.extend_from_slice(part.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&[0, 33, 0x80, 1, 0, 0, 0, 120, 0, 8, 0, 0, 0, 0, 0x1F, 0x90, 0xC0, instance_offset]);
+    // TXT record named by a pointer to the SRV's owner name
+    packet.extend_from_slice(&[0xC0, instance_offset, 0, 16, 0x80, 1, 0, 0, 0, 120, 0, txt.len() as u8 + 1]);
+    packet.push(txt.len() as u8);
+    packet.extend_from_slice(txt.as_bytes());
+
+    let message = MdnsMessage::parse(&packet, MDNS_SERVICE_TYPE).unwrap();
+    assert_eq!(message.announcements, vec![MdnsAnnouncement::new(node_id, 8080)]);
+}
+
+#[test]
+fn test_pointer_loop_is_rejected() {
+    // The answer's name points at itself
+    let packet = vec![0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0xC0, 12];
+
+    assert!(MdnsMessage::parse(&packet, MDNS_SERVICE_TYPE).is_none());
+}
+
+#[test]
+fn test_forward_pointer_is_rejected() {
+    // The answer's name points past itself, into bytes that are not a name yet
+    let packet = vec![0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0xC0, 14, 0, 0];
+
+    assert!(MdnsMessage::parse(&packet, MDNS_SERVICE_TYPE).is_none());
+}
+
+#[test]
+fn test_two_name_pointer_loop_is_rejected() {
+    // Two answers whose names point at each other
+    let packet = vec![0, 0, 0x84, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0xC0, 14, 0xC0, 12];
+
+    assert!(MdnsMessage::parse(&packet, MDNS_SERVICE_TYPE).is_none());
+}
+
+#[test]
+fn test_invalid_service_type_fails_to_encode() {
+    let long_label = format!("{}._tcp.local", "a".repeat(64));
+
+    assert!(MdnsAnnouncement::new(NodeId::generate(), 9000).to_packet(&long_label, 120).is_err());
+    assert!(mdns_query_packet(&long_label).is_err());
+}
+
+// ============================================================================
+// MALFORMED PACKETS
+// ============================================================================
+
+/// Deterministic xorshift so failures reproduce
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+fn announcement_packet() -> Vec<u8> {
+    let did = Did::from_public_key(&Keypair::generate().public_key());
+    MdnsAnnouncement::new(NodeId::generate(), 9000)
+        .with_did(&did.to_string())
+        .to_packet(MDNS_SERVICE_TYPE, 120)
+        .unwrap()
+}
+
+#[test]
+fn test_every_truncation_is_rejected() {
+    let packet = announcement_packet();
+
+    for len in 0..packet.len() {
+        assert!(MdnsMessage::parse(&packet[..len], MDNS_SERVICE_TYPE).is_none(), "truncated to {}", len);
+    }
+}
+
+#[test]
+fn test_random_packets_never_panic() {
+    let mut state = 0x9E37_79B9_7F4A_7C15;
+
+    for _ in 0..2000 {
+        let len = (next_random(&mut state) % 512) as usize;
+        let packet: Vec<u8> = (0..len).map(|_| next_random(&mut state) as u8).collect();
+        let _ = MdnsMessage::parse(&packet, MDNS_SERVICE_TYPE);
+    }
+}
+
+#[test]
+fn test_mutated_packets_never_panic() {
+    let packet = announcement_packet();
+    let mut state = 0x2545_F491_4F6C_DD1D;
+
+    for index in 0..packet.len() {
+        for _ in 0..8 {
+            let mut mutated = packet.clone();
+            mutated[index] = next_random(&mut state) as u8;
+            let _ = MdnsMessage::parse(&mutated, MDNS_SERVICE_TYPE);
+        }
+    }
+}
+
+// ============================================================================
+// LAN DISCOVERY
+// ============================================================================
+
+#[tokio::test]
+async fn test_transport_discovers_and_connects_to_advertised_peer() {
+    let mut advertiser = TcpTransport::new(TcpTransportConfig::new().with_mdns(test_mdns_config(25353)));
+    let mut browser = TcpTransport::new(TcpTransportConfig::new().with_mdns(test_mdns_config(25353)));
+    let did = Did::from_public_key(&Keypair::generate().public_key());
+    advertiser.set_did(did.clone());
+
+    advertiser.start().await.unwrap();
+    browser.start().await.unwrap();
+
+    let address = wait_for_discovery(&mut browser, &did.to_string())
+        .await
+        .expect("browser should discover the advertised record");
+    let advertised_port = match advertiser.local_address().unwrap() {
+        PeerAddress::Tcp { port, .. } => port,
+        other => panic!("unexpected local address {:?}", other),
+    };
+    assert!(matches!(&address, PeerAddress::Tcp { port, .. } if *port == advertised_port));
+
+    browser.connect(address).await.unwrap();
+    sleep(Duration::from_millis(100)).await;
+    let events = advertiser.poll_events().await;
+    assert!(events.iter().any(|e| matches!(e, TransportEvent::Connected { .. })));
+
+    browser.stop().await.unwrap();
+    advertiser.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_transport_ignores_its_own_record() {
+    let mut transport = TcpTransport::new(TcpTransportConfig::new().with_mdns(test_mdns_config(25354)));
+    let own_name = hex::encode(transport.node_id().as_bytes());
+
+    transport.start().await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+
+    let events = transport.poll_events().await;
+    assert!(!events.iter().any(|e| matches!(
+        e,
+        TransportEvent::DeviceDiscovered { name: Some(name), .. } if *name == own_name
+    )));
+
+    transport.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_invalid_interface_fails_start() {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_mdns(test_mdns_config(25355).with_interface("not-an-ip"));
+    let mut transport = TcpTransport::new(config);
+
+    assert!(transport.start().await.is_err());
+}
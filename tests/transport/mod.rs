@@ -5,3 +5,10 @@ mod tcp_test;
 mod ble_test;
 mod lora_test;
 mod edge_cases_test;
+mod throttle_test;
+#[cfg(feature = "websocket")]
+mod ws_test;
+#[cfg(feature = "mdns")]
+mod mdns_test;
+#[cfg(feature = "ble")]
+mod btle_test;
@@ -2,11 +2,12 @@
 // Tests for the TCP implementation of the Transport trait
 
 use p2pmesh::transport::{
-    TcpTransport, TcpTransportConfig, Transport, TransportConfig, TransportError,
+    ReconnectPolicy, TcpTransport, TcpTransportConfig, TlsConfig, Transport, TransportConfig, TransportError,
     TransportEvent, TransportState, PeerAddress, ConnectionId,
 };
 use p2pmesh::sync::Message;
-use p2pmesh::ledger::NodeId;
+use std::path::PathBuf;
+use tempfile::TempDir;
 
 // ============================================================================
 // TCP TRANSPORT CONFIG
@@ -16,7 +17,7 @@ use p2pmesh::ledger::NodeId;
 fn test_tcp_config_default() {
     let config = TcpTransportConfig::default();
 
-    assert!(config.bind_port > 0 || config.bind_port == 0); // 0 = random port
+    let _: u16 = config.bind_port; // 0 = random port
     assert!(!config.bind_address.is_empty());
 }
 
@@ -51,6 +52,21 @@ fn test_tcp_config_with_keepalive() {
     assert_eq!(config.keepalive_secs, Some(30));
 }
 
+#[test]
+fn test_tcp_config_liveness_timeout() {
+    assert_eq!(TcpTransportConfig::new().with_keepalive_secs(Some(30)).liveness_timeout_secs(), Some(90));
+
+    let explicit = TcpTransportConfig::new()
+        .with_keepalive_secs(Some(30))
+        .with_heartbeat_timeout_secs(45);
+    assert_eq!(explicit.liveness_timeout_secs(), Some(45));
+
+    let disabled = TcpTransportConfig::new()
+        .with_keepalive_secs(None)
+        .with_heartbeat_timeout_secs(45);
+    assert_eq!(disabled.liveness_timeout_secs(), None);
+}
+
 #[test]
 fn test_tcp_config_base_config() {
     let base = TransportConfig::new().with_max_connections(100);
@@ -212,7 +228,6 @@ async fn test_tcp_transport_connect() {
     let result = client.connect(server_addr).await;
 
     assert!(result.is_ok());
-    let conn_id = result.unwrap();
     assert_eq!(client.connection_count(), 1);
 
     // Cleanup
@@ -367,10 +382,9 @@ async fn test_tcp_transport_send_empty_message() {
     // Send empty data
     let result = client.send(&conn_id, &[]).await;
 
-    // Should succeed with 0 bytes or return error
-    match result {
-        Ok(bytes) => assert_eq!(bytes, 0),
-        Err(_) => {} // Error is also acceptable for empty sends
+    // Should succeed with 0 bytes or return error (also acceptable for empty sends)
+    if let Ok(bytes) = result {
+        assert_eq!(bytes, 0);
     }
 
     client.stop().await.unwrap();
@@ -430,7 +444,7 @@ async fn test_tcp_transport_receive_message() {
     let mut client = TcpTransport::new(client_config);
     client.start().await.unwrap();
 
-    let conn_id = client.connect(server_addr).await.unwrap();
+    client.connect(server_addr).await.unwrap();
 
     // Wait for server to accept connection
     sleep(Duration::from_millis(50)).await;
@@ -705,3 +719,606 @@ async fn test_tcp_transport_broadcast_no_connections() {
 
     transport.stop().await.unwrap();
 }
+
+// ============================================================================
+// TCP TRANSPORT TLS
+// ============================================================================
+
+/// Self-signed certificate written to a temp dir
+struct TestCert {
+    _dir: TempDir,
+    cert: PathBuf,
+    key: PathBuf,
+}
+
+fn self_signed_cert() -> TestCert {
+    let dir = TempDir::new().unwrap();
+    let certified = rcgen::generate_simple_self_signed(vec![
+        "localhost".to_string(),
+        "127.0.0.1".to_string(),
+    ])
+    .unwrap();
+
+    let cert = dir.path().join("cert.pem");
+    let key = dir.path().join("key.pem");
+    std::fs::write(&cert, certified.cert.pem()).unwrap();
+    std::fs::write(&key, certified.key_pair.serialize_pem()).unwrap();
+
+    TestCert { _dir: dir, cert, key }
+}
+
+async fn start_tls_server(cert: &TestCert) -> TcpTransport {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_tls(TlsConfig::server(&cert.cert, &cert.key));
+    let mut server = TcpTransport::new(config);
+    server.start().await.unwrap();
+    server
+}
+
+async fn start_client(tls: Option<TlsConfig>) -> TcpTransport {
+    let mut config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_base_config(TransportConfig::new().with_connection_timeout(2));
+    if let Some(tls) = tls {
+        config = config.with_tls(tls);
+    }
+    let mut client = TcpTransport::new(config);
+    client.start().await.unwrap();
+    client
+}
+
+/// Poll until an event matches or ~2s pass, returning everything seen
+async fn poll_until(
+    transport: &mut TcpTransport,
+    predicate: impl Fn(&TransportEvent) -> bool,
+) -> Vec<TransportEvent> {
+    let mut seen = Vec::new();
+    for _ in 0..100 {
+        let events = transport.poll_events().await;
+        let done = events.iter().any(&predicate);
+        seen.extend(events);
+        if done {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    seen
+}
+
+fn received(events: &[TransportEvent]) -> Vec<u8> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            TransportEvent::MessageReceived { data, .. } => Some(data.clone()),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+#[test]
+fn test_tcp_config_with_tls() {
+    let tls = TlsConfig::server("cert.pem", "key.pem").with_ca("ca.pem");
+    let config = TcpTransportConfig::new().with_tls(tls.clone());
+
+    assert_eq!(config.tls, Some(tls));
+    assert!(TcpTransportConfig::default().tls.is_none());
+}
+
+#[test]
+fn test_tls_config_capabilities() {
+    let server = TlsConfig::server("cert.pem", "key.pem");
+    let client = TlsConfig::new().with_pinned_cert("peer.pem");
+
+    assert!(server.can_accept());
+    assert!(!server.can_connect());
+    assert!(!client.can_accept());
+    assert!(client.can_connect());
+}
+
+#[tokio::test]
+async fn test_tcp_tls_start_with_missing_cert_fails() {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_tls(TlsConfig::server("/nonexistent/cert.pem", "/nonexistent/key.pem"));
+    let mut transport = TcpTransport::new(config);
+
+    let result = transport.start().await;
+
+    assert!(matches!(result, Err(TransportError::InvalidConfig(_))));
+    assert!(!transport.state().is_running());
+}
+
+#[tokio::test]
+async fn test_tcp_tls_roundtrip_with_pinned_cert() {
+    let cert = self_signed_cert();
+    let mut server = start_tls_server(&cert).await;
+    let mut client = start_client(Some(TlsConfig::new().with_pinned_cert(&cert.cert))).await;
+
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    client.send(&conn_id, b"encrypted iou").await.unwrap();
+
+    let server_events = poll_until(&mut server, |e| matches!(e, TransportEvent::MessageReceived { .. })).await;
+    let server_conn = server_events
+        .iter()
+        .find_map(|e| match e {
+            TransportEvent::Connected { connection_id, .. } => Some(connection_id.clone()),
+            _ => None,
+        })
+        .expect("TLS connection accepted");
+    assert_eq!(received(&server_events), b"encrypted iou");
+
+    // Reply over the same session
+    server.send(&server_conn, b"ack").await.unwrap();
+    let client_events = poll_until(&mut client, |e| matches!(e, TransportEvent::MessageReceived { .. })).await;
+    assert_eq!(received(&client_events), b"ack");
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_tls_roundtrip_with_ca() {
+    let cert = self_signed_cert();
+    let mut server = start_tls_server(&cert).await;
+    let mut client = start_client(Some(TlsConfig::new().with_ca(&cert.cert))).await;
+
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    client.send(&conn_id, b"hello").await.unwrap();
+
+    let events = poll_until(&mut server, |e| matches!(e, TransportEvent::MessageReceived { .. })).await;
+    assert_eq!(received(&events), b"hello");
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_tls_pinned_cert_mismatch_fails() {
+    let server_cert = self_signed_cert();
+    let other_cert = self_signed_cert();
+    let mut server = start_tls_server(&server_cert).await;
+    let mut client = start_client(Some(TlsConfig::new().with_pinned_cert(&other_cert.cert))).await;
+
+    let result = client.connect(server.local_address().unwrap()).await;
+
+    assert!(matches!(result, Err(TransportError::ConnectionFailed(_))));
+    assert_eq!(client.connection_count(), 0);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_tls_client_without_trust_anchor_fails() {
+    let cert = self_signed_cert();
+    let mut server = start_tls_server(&cert).await;
+    let mut client = start_client(Some(TlsConfig::new())).await;
+
+    let result = client.connect(server.local_address().unwrap()).await;
+
+    assert!(matches!(result, Err(TransportError::InvalidConfig(_))));
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_tls_listener_rejects_plaintext_peer() {
+    let cert = self_signed_cert();
+    let mut server = start_tls_server(&cert).await;
+    let mut client = start_client(None).await;
+
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    client.send(&conn_id, b"not a client hello").await.unwrap();
+
+    let events = poll_until(&mut server, |e| matches!(e, TransportEvent::Error { .. })).await;
+
+    assert!(events.iter().any(|e| matches!(
+        e,
+        TransportEvent::Error { connection_id: None, error: TransportError::ConnectionFailed(_) }
+    )));
+    assert!(!events.iter().any(|e| matches!(e, TransportEvent::Connected { .. })));
+    assert_eq!(server.connection_count(), 0);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_tls_client_to_plaintext_listener_fails() {
+    let cert = self_signed_cert();
+    let mut server = start_client(None).await;
+    let mut client = start_client(Some(TlsConfig::new().with_pinned_cert(&cert.cert))).await;
+
+    let result = client.connect(server.local_address().unwrap()).await;
+
+    assert!(matches!(result, Err(TransportError::ConnectionFailed(_))));
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+// ============================================================================
+// TCP TRANSPORT RECONNECT
+// ============================================================================
+
+fn fast_reconnect(max_attempts: u32) -> ReconnectPolicy {
+    ReconnectPolicy::new()
+        .with_initial_delay_ms(20)
+        .with_max_delay_ms(100)
+        .with_multiplier(2.0)
+        .with_max_attempts(max_attempts)
+}
+
+async fn start_server_on(port: u16) -> TcpTransport {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_bind_port(port);
+    let mut server = TcpTransport::new(config);
+    server.start().await.unwrap();
+    server
+}
+
+async fn start_reconnecting_client(policy: ReconnectPolicy) -> TcpTransport {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_reconnect(policy);
+    let mut client = TcpTransport::new(config);
+    client.start().await.unwrap();
+    client
+}
+
+fn reconnect_attempts(events: &[TransportEvent]) -> usize {
+    events
+        .iter()
+        .filter(|e| matches!(e, TransportEvent::Reconnecting { .. }))
+        .count()
+}
+
+#[test]
+fn test_reconnect_policy_default() {
+    let policy = ReconnectPolicy::default();
+
+    assert!(policy.initial_delay_ms > 0);
+    assert!(policy.max_delay_ms >= policy.initial_delay_ms);
+    assert!(policy.multiplier >= 1.0);
+    assert!(policy.max_attempts > 0);
+    assert!(TcpTransportConfig::default().reconnect.is_none());
+}
+
+#[test]
+fn test_reconnect_policy_backoff_is_exponential_and_capped() {
+    let policy = ReconnectPolicy::new()
+        .with_initial_delay_ms(100)
+        .with_max_delay_ms(1000)
+        .with_multiplier(2.0);
+
+    let delays: Vec<u128> = (1..=6).map(|a| policy.delay_for_attempt(a).as_millis()).collect();
+
+    assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+}
+
+#[test]
+fn test_reconnect_policy_multiplier_below_one_is_constant() {
+    let policy = ReconnectPolicy::new().with_initial_delay_ms(50).with_multiplier(0.5);
+
+    assert_eq!(policy.delay_for_attempt(1), policy.delay_for_attempt(5));
+}
+
+#[tokio::test]
+async fn test_tcp_reconnects_after_server_restart() {
+    let mut server = start_server_on(0).await;
+    let server_addr = server.local_address().unwrap();
+    let port = match &server_addr {
+        PeerAddress::Tcp { port, .. } => *port,
+        _ => unreachable!(),
+    };
+    let mut client = start_reconnecting_client(fast_reconnect(20)).await;
+
+    let conn_id = client.connect(server_addr).await.unwrap();
+    poll_until(&mut server, |e| matches!(e, TransportEvent::Connected { .. })).await;
+
+    // Server goes away: the client starts re-dialing instead of dropping the link
+    server.stop().await.unwrap();
+    let events = poll_until(&mut client, |e| matches!(e, TransportEvent::Reconnecting { .. })).await;
+    assert!(reconnect_attempts(&events) >= 1);
+    assert!(!events.iter().any(|e| matches!(e, TransportEvent::Disconnected { .. })));
+    assert_eq!(client.connection_count(), 1);
+    assert!(matches!(client.send(&conn_id, b"early").await, Err(TransportError::NotConnected)));
+
+    // Server comes back on the same port
+    let mut server = start_server_on(port).await;
+    let events = poll_until(&mut client, |e| matches!(e, TransportEvent::Reconnected { .. })).await;
+    assert!(events.iter().any(|e| matches!(
+        e,
+        TransportEvent::Reconnected { connection_id, .. } if *connection_id == conn_id
+    )));
+
+    // The original connection ID carries traffic again
+    client.send(&conn_id, b"after restart").await.unwrap();
+    let server_events = poll_until(&mut server, |e| matches!(e, TransportEvent::MessageReceived { .. })).await;
+    assert_eq!(received(&server_events), b"after restart");
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_reconnect_gives_up_after_max_attempts() {
+    let mut server = start_server_on(0).await;
+    let mut client = start_reconnecting_client(fast_reconnect(3)).await;
+
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    server.stop().await.unwrap();
+
+    let events = poll_until(&mut client, |e| matches!(e, TransportEvent::Disconnected { .. })).await;
+
+    assert_eq!(reconnect_attempts(&events), 3);
+    assert!(events.iter().any(|e| matches!(
+        e,
+        TransportEvent::Disconnected { connection_id, reason }
+            if *connection_id == conn_id && reason.contains("3 attempts")
+    )));
+    assert_eq!(client.connection_count(), 0);
+
+    client.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_user_disconnect_disables_reconnect() {
+    let mut server = start_server_on(0).await;
+    let mut client = start_reconnecting_client(fast_reconnect(20)).await;
+
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    client.disconnect(&conn_id).await.unwrap();
+    server.stop().await.unwrap();
+
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let events = client.poll_events().await;
+
+    assert_eq!(reconnect_attempts(&events), 0);
+    assert_eq!(client.connection_count(), 0);
+
+    client.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_inbound_connections_are_not_redialed() {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_reconnect(fast_reconnect(20));
+    let mut server = TcpTransport::new(config);
+    server.start().await.unwrap();
+    let mut client = start_client(None).await;
+
+    client.connect(server.local_address().unwrap()).await.unwrap();
+    poll_until(&mut server, |e| matches!(e, TransportEvent::Connected { .. })).await;
+    client.stop().await.unwrap();
+
+    let events = poll_until(&mut server, |e| matches!(e, TransportEvent::Disconnected { .. })).await;
+
+    assert!(events.iter().any(|e| matches!(e, TransportEvent::Disconnected { .. })));
+    assert_eq!(reconnect_attempts(&events), 0);
+    assert_eq!(server.connection_count(), 0);
+
+    server.stop().await.unwrap();
+}
+
+// ============================================================================
+// TCP TRANSPORT RATE LIMIT
+// ============================================================================
+
+async fn start_limited_client(bytes_per_sec: u64) -> TcpTransport {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_base_config(TransportConfig::new().with_rate_limit(bytes_per_sec));
+    let mut client = TcpTransport::new(config);
+    client.start().await.unwrap();
+    client
+}
+
+#[tokio::test]
+async fn test_tcp_rate_limit_delays_large_payload() {
+    let mut server = start_server_on(0).await;
+    let mut client = start_limited_client(10_000).await;
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    let data = vec![7u8; 30_000];
+
+    let started = std::time::Instant::now();
+    client.send(&conn_id, &data).await.unwrap();
+    let elapsed = started.elapsed();
+
+    // One second of burst, then 20KB at 10KB/s (allowing for millisecond clock rounding)
+    assert!(elapsed >= std::time::Duration::from_millis(1990), "sent in {:?}", elapsed);
+    assert_eq!(client.stats().throttled_sends, 1);
+
+    let mut got = Vec::new();
+    for _ in 0..100 {
+        got.extend(received(&server.poll_events().await));
+        if got.len() >= data.len() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(got, data);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_rate_limit_smooths_bursts() {
+    let mut server = start_server_on(0).await;
+    let mut client = start_limited_client(20_000).await;
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+
+    let started = std::time::Instant::now();
+    for _ in 0..6 {
+        client.send(&conn_id, &[1u8; 5_000]).await.unwrap();
+    }
+    let elapsed = started.elapsed();
+
+    // 30KB against a 20KB burst: the last 10KB wait half a second
+    assert!(elapsed >= std::time::Duration::from_millis(490), "sent in {:?}", elapsed);
+    assert_eq!(client.stats().throttled_sends, 2);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_rate_limit_is_per_connection() {
+    let mut first = start_server_on(0).await;
+    let mut second = start_server_on(0).await;
+    let mut client = start_limited_client(10_000).await;
+    let a = client.connect(first.local_address().unwrap()).await.unwrap();
+    let b = client.connect(second.local_address().unwrap()).await.unwrap();
+
+    let started = std::time::Instant::now();
+    client.send(&a, &[1u8; 10_000]).await.unwrap();
+    client.send(&b, &[1u8; 10_000]).await.unwrap();
+
+    // Each connection spends only its own burst
+    assert!(started.elapsed() < std::time::Duration::from_millis(500));
+    assert_eq!(client.stats().throttled_sends, 0);
+
+    client.stop().await.unwrap();
+    first.stop().await.unwrap();
+    second.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_stats_report_throttle_delay() {
+    let mut server = start_server_on(0).await;
+    let mut client = start_limited_client(10_000).await;
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    assert_eq!(client.stats().throttle_delay_ms, 0);
+
+    // Give up on a send while it waits for two seconds of bandwidth
+    let send = client.send(&conn_id, &[1u8; 30_000]);
+    assert!(tokio::time::timeout(std::time::Duration::from_millis(100), send).await.is_err());
+
+    let stats = client.stats();
+    assert_eq!(stats.throttled_sends, 1);
+    assert!(
+        (1500..=2000).contains(&stats.throttle_delay_ms),
+        "delay {}ms",
+        stats.throttle_delay_ms
+    );
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+// ============================================================================
+// TCP TRANSPORT HEARTBEATS
+// ============================================================================
+
+async fn start_heartbeat_transport(keepalive_secs: u32, timeout_secs: u32) -> TcpTransport {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_keepalive_secs(Some(keepalive_secs))
+        .with_heartbeat_timeout_secs(timeout_secs);
+    let mut transport = TcpTransport::new(config);
+    transport.start().await.unwrap();
+    transport
+}
+
+#[tokio::test]
+async fn test_tcp_detects_silent_peer() {
+    use tokio::io::AsyncReadExt;
+
+    // A peer that accepts and then never sends a byte
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let silent_peer = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0u8; 1024];
+        let n = socket.read(&mut buf).await.unwrap();
+        buf.truncate(n);
+        // Hold the socket open so only the silence gives the peer away
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        buf
+    });
+
+    let mut client = start_heartbeat_transport(1, 2).await;
+    let started = std::time::Instant::now();
+    let conn_id = client.connect(PeerAddress::tcp("127.0.0.1", port)).await.unwrap();
+
+    let mut timed_out = false;
+    while started.elapsed() < std::time::Duration::from_secs(4) && !timed_out {
+        timed_out = client.poll_events().await.iter().any(|e| matches!(
+            e,
+            TransportEvent::Disconnected { connection_id, reason }
+                if *connection_id == conn_id && reason.contains("timed out")
+        ));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    assert!(timed_out, "silent peer was not detected");
+    assert!(started.elapsed() < std::time::Duration::from_millis(3000));
+    assert_eq!(client.connection_count(), 0);
+    assert_eq!(client.stats().peer_timeouts, 1);
+    assert!(client.stats().heartbeats_sent >= 1);
+
+    // What the idle client sent was a heartbeat from its node ID
+    let heartbeat = match Message::from_bytes(&silent_peer.await.unwrap()).unwrap() {
+        Message::Heartbeat(heartbeat) => heartbeat,
+        other => panic!("expected a heartbeat, got {:?}", other),
+    };
+    assert_eq!(heartbeat.sender(), client.node_id());
+
+    client.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_heartbeats_keep_idle_connection_alive() {
+    let mut server = start_heartbeat_transport(1, 2).await;
+    let mut client = start_heartbeat_transport(1, 2).await;
+    client.connect(server.local_address().unwrap()).await.unwrap();
+
+    // Both sides only exchange heartbeats for longer than the timeout
+    let started = std::time::Instant::now();
+    let mut disconnected = false;
+    while started.elapsed() < std::time::Duration::from_millis(3500) {
+        let events: Vec<TransportEvent> = client
+            .poll_events()
+            .await
+            .into_iter()
+            .chain(server.poll_events().await)
+            .collect();
+        disconnected |= events.iter().any(|e| matches!(e, TransportEvent::Disconnected { .. }));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    assert!(!disconnected);
+    assert_eq!(client.connection_count(), 1);
+    assert_eq!(server.connection_count(), 1);
+    assert!(client.stats().heartbeats_sent >= 1);
+    assert!(server.stats().heartbeats_sent >= 1);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tcp_no_heartbeats_without_keepalive() {
+    let config = TcpTransportConfig::new()
+        .with_bind_address("127.0.0.1")
+        .with_keepalive_secs(None);
+    let mut client = TcpTransport::new(config);
+    client.start().await.unwrap();
+    let mut server = start_server_on(0).await;
+    client.connect(server.local_address().unwrap()).await.unwrap();
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    client.poll_events().await;
+
+    assert_eq!(client.stats().heartbeats_sent, 0);
+    assert_eq!(client.connection_count(), 1);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
@@ -0,0 +1,65 @@
+// Throttle Tests
+// Tests for the token bucket behind per-connection rate limits
+
+use p2pmesh::transport::TokenBucket;
+use std::time::Duration;
+
+#[test]
+fn test_token_bucket_allows_initial_burst() {
+    let mut bucket = TokenBucket::new(1000, 0);
+
+    assert_eq!(bucket.reserve(0, 600), Duration::ZERO);
+    assert_eq!(bucket.reserve(0, 400), Duration::ZERO);
+    assert_eq!(bucket.current_delay(0), Duration::ZERO);
+}
+
+#[test]
+fn test_token_bucket_overdraw_waits_for_debt() {
+    let mut bucket = TokenBucket::new(1000, 0);
+
+    // 2.5 seconds of data against a 1 second burst
+    let delay = bucket.reserve(0, 2500);
+
+    assert_eq!(delay, Duration::from_millis(1500));
+    assert_eq!(bucket.current_delay(500), Duration::from_millis(1000));
+    assert_eq!(bucket.current_delay(1500), Duration::ZERO);
+}
+
+#[test]
+fn test_token_bucket_queued_sends_accumulate() {
+    let mut bucket = TokenBucket::new(1000, 0);
+
+    bucket.reserve(0, 1000);
+    let first = bucket.reserve(0, 500);
+    let second = bucket.reserve(0, 500);
+
+    assert_eq!(first, Duration::from_millis(500));
+    assert_eq!(second, Duration::from_millis(1000));
+}
+
+#[test]
+fn test_token_bucket_refills_over_time() {
+    let mut bucket = TokenBucket::new(1000, 0);
+
+    bucket.reserve(0, 1000);
+
+    assert_eq!(bucket.reserve(250, 250), Duration::ZERO);
+    assert_eq!(bucket.reserve(250, 250), Duration::from_millis(250));
+}
+
+#[test]
+fn test_token_bucket_refill_capped_at_one_second() {
+    let mut bucket = TokenBucket::new(1000, 0);
+
+    // Idle for a minute still only banks one second of bandwidth
+    assert_eq!(bucket.reserve(60_000, 1000), Duration::ZERO);
+    assert_eq!(bucket.reserve(60_000, 1000), Duration::from_secs(1));
+}
+
+#[test]
+fn test_token_bucket_zero_rate_treated_as_one() {
+    let mut bucket = TokenBucket::new(0, 0);
+
+    assert_eq!(bucket.bytes_per_sec(), 1);
+    assert_eq!(bucket.reserve(0, 3), Duration::from_secs(2));
+}
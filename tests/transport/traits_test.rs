@@ -2,10 +2,9 @@
 // Tests for the abstract Transport trait and related types
 
 use p2pmesh::transport::{
-    Transport, TransportConfig, TransportError, TransportEvent, TransportState,
+    TransportConfig, TransportError, TransportEvent, TransportState,
     ConnectionId, ConnectionInfo, ConnectionState, PeerAddress,
 };
-use p2pmesh::sync::Message;
 use p2pmesh::ledger::NodeId;
 
 // ============================================================================
@@ -49,6 +48,17 @@ fn test_transport_config_validation() {
     assert!(valid_config.validate().is_ok());
 }
 
+#[test]
+fn test_transport_config_rate_limit() {
+    assert_eq!(TransportConfig::default().rate_limit_bytes_per_sec, None);
+
+    let config = TransportConfig::new().with_rate_limit(2048);
+    assert_eq!(config.rate_limit_bytes_per_sec, Some(2048));
+    assert!(config.validate().is_ok());
+
+    assert!(TransportConfig::new().with_rate_limit(0).validate().is_err());
+}
+
 // ============================================================================
 // PEER ADDRESS
 // ============================================================================
@@ -80,6 +90,17 @@ fn test_peer_address_lora_creation() {
     assert!(!addr.is_ble());
 }
 
+#[test]
+fn test_peer_address_ws_creation() {
+    let addr = PeerAddress::ws("wss://relay.example.com/mesh");
+
+    assert!(addr.is_ws());
+    assert!(!addr.is_tcp());
+    assert!(!addr.is_broadcast());
+    assert_eq!(addr.to_string(), "wss://relay.example.com/mesh");
+    assert_ne!(addr, PeerAddress::ws("ws://relay.example.com/mesh"));
+}
+
 #[test]
 fn test_peer_address_display() {
     let tcp_addr = PeerAddress::tcp("192.168.1.100", 9000);
@@ -183,6 +204,15 @@ fn test_connection_state_transitions() {
     assert!(!ConnectionState::Connected.can_transition_to(&ConnectionState::Connecting));
 }
 
+#[test]
+fn test_connection_state_reconnecting_transitions() {
+    assert!(ConnectionState::Connected.can_transition_to(&ConnectionState::Reconnecting));
+    assert!(ConnectionState::Reconnecting.can_transition_to(&ConnectionState::Connected));
+    assert!(ConnectionState::Reconnecting.can_transition_to(&ConnectionState::Disconnected));
+    assert!(!ConnectionState::Disconnected.can_transition_to(&ConnectionState::Reconnecting));
+    assert!(ConnectionState::Reconnecting.is_active());
+}
+
 #[test]
 fn test_connection_state_is_active() {
     assert!(!ConnectionState::Disconnected.is_active());
@@ -467,13 +497,15 @@ mod mock_transport {
     use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
 
+    type SentMessages = Arc<Mutex<Vec<(ConnectionId, Vec<u8>)>>>;
+
     // Mock transport for testing trait interface
     pub struct MockTransport {
         state: TransportState,
         connections: HashMap<ConnectionId, ConnectionInfo>,
         config: TransportConfig,
         events: Vec<TransportEvent>,
-        sent_messages: Arc<Mutex<Vec<(ConnectionId, Vec<u8>)>>>,
+        sent_messages: SentMessages,
     }
 
     impl MockTransport {
@@ -0,0 +1,396 @@
+// WebSocket Transport Tests
+// Tests for the WebSocket implementation of the Transport trait
+// Run with `cargo test --features websocket`
+
+#![cfg(feature = "websocket")]
+
+use p2pmesh::transport::{
+    ConnectionId, PeerAddress, TlsConfig, Transport, TransportConfig, TransportError, TransportEvent,
+    TransportState, WsTransport, WsTransportConfig, WsUrl,
+};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+async fn start_transport(config: WsTransportConfig) -> WsTransport {
+    let mut transport = WsTransport::new(
+        config
+            .with_bind_address("127.0.0.1")
+            .with_base_config(TransportConfig::new().with_connection_timeout(2)),
+    );
+    transport.start().await.unwrap();
+    transport
+}
+
+/// Poll until an event matches or ~2s pass, returning everything seen
+async fn poll_until(
+    transport: &mut WsTransport,
+    predicate: impl Fn(&TransportEvent) -> bool,
+) -> Vec<TransportEvent> {
+    let mut seen = Vec::new();
+    for _ in 0..100 {
+        let events = transport.poll_events().await;
+        let done = events.iter().any(&predicate);
+        seen.extend(events);
+        if done {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    seen
+}
+
+/// Poll until `count` messages have arrived, returning them in order
+async fn receive_messages(transport: &mut WsTransport, count: usize) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    for _ in 0..100 {
+        for event in transport.poll_events().await {
+            if let TransportEvent::MessageReceived { data, .. } = event {
+                messages.push(data);
+            }
+        }
+        if messages.len() >= count {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    messages
+}
+
+fn connected_id(events: &[TransportEvent]) -> Option<ConnectionId> {
+    events.iter().find_map(|e| match e {
+        TransportEvent::Connected { connection_id, .. } => Some(connection_id.clone()),
+        _ => None,
+    })
+}
+
+// ============================================================================
+// URL PARSING
+// ============================================================================
+
+#[test]
+fn test_ws_url_defaults() {
+    let url = WsUrl::parse("ws://example.com").unwrap();
+    assert!(!url.secure);
+    assert_eq!(url.host, "example.com");
+    assert_eq!(url.port, 80);
+    assert_eq!(url.path, "/");
+
+    let url = WsUrl::parse("wss://example.com").unwrap();
+    assert!(url.secure);
+    assert_eq!(url.port, 443);
+}
+
+#[test]
+fn test_ws_url_port_path_and_query() {
+    let url = WsUrl::parse("wss://relay.example.com:8443/mesh?node=7").unwrap();
+
+    assert_eq!(url.host, "relay.example.com");
+    assert_eq!(url.port, 8443);
+    assert_eq!(url.path, "/mesh?node=7");
+    assert_eq!(url.to_string(), "wss://relay.example.com:8443/mesh?node=7");
+}
+
+#[test]
+fn test_ws_url_ipv6() {
+    let url = WsUrl::parse("ws://[::1]:9000/").unwrap();
+    assert_eq!(url.host, "[::1]");
+    assert_eq!(url.port, 9000);
+
+    let url = WsUrl::parse("ws://[::1]").unwrap();
+    assert_eq!(url.host, "[::1]");
+    assert_eq!(url.port, 80);
+}
+
+#[test]
+fn test_ws_url_rejects_malformed() {
+    for url in [
+        "http://example.com",
+        "tcp://127.0.0.1:80",
+        "ws://",
+        "ws://:80",
+        "ws://host:notaport",
+        "ws://host:70000",
+        "ws://user@host",
+    ] {
+        assert!(
+            matches!(WsUrl::parse(url), Err(TransportError::InvalidAddress(_))),
+            "{:?} should be rejected",
+            url
+        );
+    }
+}
+
+// ============================================================================
+// LIFECYCLE
+// ============================================================================
+
+#[tokio::test]
+async fn test_ws_transport_start_and_stop() {
+    let mut transport = start_transport(WsTransportConfig::new()).await;
+
+    assert!(matches!(transport.state(), TransportState::Running));
+    let address = transport.local_address().unwrap();
+    assert!(address.is_ws());
+    assert!(address.to_string().starts_with("ws://127.0.0.1:"));
+
+    let events = transport.poll_events().await;
+    assert!(events.iter().any(|e| matches!(e, TransportEvent::Listening { .. })));
+
+    transport.stop().await.unwrap();
+    assert!(matches!(transport.state(), TransportState::Stopped));
+    assert!(transport.local_address().is_none());
+}
+
+#[tokio::test]
+async fn test_ws_transport_start_twice_fails() {
+    let mut transport = start_transport(WsTransportConfig::new()).await;
+
+    let result = transport.start().await;
+    assert!(matches!(result, Err(TransportError::AlreadyRunning)));
+
+    transport.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ws_connect_requires_running() {
+    let mut transport = WsTransport::new(WsTransportConfig::new());
+
+    let result = transport.connect(PeerAddress::ws("ws://127.0.0.1:1/")).await;
+    assert!(matches!(result, Err(TransportError::NotRunning)));
+}
+
+#[tokio::test]
+async fn test_ws_connect_rejects_non_ws_address() {
+    let mut transport = start_transport(WsTransportConfig::new()).await;
+
+    let result = transport.connect(PeerAddress::tcp("127.0.0.1", 8080)).await;
+    assert!(matches!(result, Err(TransportError::InvalidAddress(_))));
+
+    let result = transport.connect(PeerAddress::ws("http://127.0.0.1:8080/")).await;
+    assert!(matches!(result, Err(TransportError::InvalidAddress(_))));
+
+    transport.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ws_connect_to_closed_port_fails() {
+    let mut transport = start_transport(WsTransportConfig::new()).await;
+
+    let result = transport.connect(PeerAddress::ws("ws://127.0.0.1:1/")).await;
+    assert!(result.is_err());
+    assert_eq!(transport.connection_count(), 0);
+
+    transport.stop().await.unwrap();
+}
+
+// ============================================================================
+// MESSAGING
+// ============================================================================
+
+#[tokio::test]
+async fn test_ws_roundtrip() {
+    let mut server = start_transport(WsTransportConfig::new()).await;
+    let mut client = start_transport(WsTransportConfig::new()).await;
+
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    assert_eq!(client.connection_count(), 1);
+    assert!(client.connection_info(&conn_id).unwrap().address().is_ws());
+
+    client.send(&conn_id, b"signed iou").await.unwrap();
+
+    let server_events = poll_until(&mut server, |e| matches!(e, TransportEvent::MessageReceived { .. })).await;
+    let server_conn = connected_id(&server_events).expect("connection accepted");
+    let message = server_events.iter().find_map(|e| match e {
+        TransportEvent::MessageReceived { connection_id, data } => Some((connection_id.clone(), data.clone())),
+        _ => None,
+    });
+    assert_eq!(message, Some((server_conn.clone(), b"signed iou".to_vec())));
+
+    // Reply over the same connection
+    server.send(&server_conn, b"ack").await.unwrap();
+    assert_eq!(receive_messages(&mut client, 1).await, vec![b"ack".to_vec()]);
+
+    assert_eq!(client.stats().messages_sent, 1);
+    assert_eq!(server.stats().messages_received, 1);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ws_preserves_message_boundaries() {
+    let mut server = start_transport(WsTransportConfig::new()).await;
+    let mut client = start_transport(WsTransportConfig::new()).await;
+
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    let large = vec![0xAB; 200_000];
+    client.send(&conn_id, b"first").await.unwrap();
+    client.send(&conn_id, &large).await.unwrap();
+    client.send(&conn_id, b"").await.unwrap();
+    client.send(&conn_id, b"last").await.unwrap();
+
+    // One event per message, however the bytes were split on the wire
+    let messages = receive_messages(&mut server, 4).await;
+    assert_eq!(messages, vec![b"first".to_vec(), large, Vec::new(), b"last".to_vec()]);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ws_broadcast() {
+    let mut server = start_transport(WsTransportConfig::new()).await;
+    let mut first = start_transport(WsTransportConfig::new()).await;
+    let mut second = start_transport(WsTransportConfig::new()).await;
+
+    first.connect(server.local_address().unwrap()).await.unwrap();
+    second.connect(server.local_address().unwrap()).await.unwrap();
+    for _ in 0..100 {
+        server.poll_events().await;
+        if server.connection_count() == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let sent = server.broadcast(b"gossip").await.unwrap();
+    assert_eq!(sent, 2);
+    assert_eq!(receive_messages(&mut first, 1).await, vec![b"gossip".to_vec()]);
+    assert_eq!(receive_messages(&mut second, 1).await, vec![b"gossip".to_vec()]);
+
+    first.stop().await.unwrap();
+    second.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ws_disconnect_is_seen_by_peer() {
+    let mut server = start_transport(WsTransportConfig::new()).await;
+    let mut client = start_transport(WsTransportConfig::new()).await;
+
+    let conn_id = client.connect(server.local_address().unwrap()).await.unwrap();
+    let events = poll_until(&mut server, |e| matches!(e, TransportEvent::Connected { .. })).await;
+    let server_conn = connected_id(&events).unwrap();
+
+    client.disconnect(&conn_id).await.unwrap();
+    assert_eq!(client.connection_count(), 0);
+    assert!(matches!(client.send(&conn_id, b"late").await, Err(TransportError::NotConnected)));
+
+    let events = poll_until(&mut server, |e| matches!(e, TransportEvent::Disconnected { .. })).await;
+    assert!(events.iter().any(|e| matches!(
+        e,
+        TransportEvent::Disconnected { connection_id, .. } if *connection_id == server_conn
+    )));
+    assert_eq!(server.connection_count(), 0);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ws_max_connections() {
+    let mut server = start_transport(WsTransportConfig::new()).await;
+    let mut other = start_transport(WsTransportConfig::new()).await;
+    let mut client = WsTransport::new(
+        WsTransportConfig::new()
+            .with_bind_address("127.0.0.1")
+            .with_base_config(TransportConfig::new().with_max_connections(1)),
+    );
+    client.start().await.unwrap();
+
+    client.connect(server.local_address().unwrap()).await.unwrap();
+    let result = client.connect(other.local_address().unwrap()).await;
+    assert!(matches!(result, Err(TransportError::MaxConnectionsReached)));
+
+    client.stop().await.unwrap();
+    other.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+// ============================================================================
+// WSS
+// ============================================================================
+
+/// Self-signed certificate written to a temp dir
+struct TestCert {
+    _dir: TempDir,
+    cert: PathBuf,
+    key: PathBuf,
+}
+
+fn self_signed_cert() -> TestCert {
+    let dir = TempDir::new().unwrap();
+    let certified = rcgen::generate_simple_self_signed(vec![
+        "localhost".to_string(),
+        "127.0.0.1".to_string(),
+    ])
+    .unwrap();
+
+    let cert = dir.path().join("cert.pem");
+    let key = dir.path().join("key.pem");
+    std::fs::write(&cert, certified.cert.pem()).unwrap();
+    std::fs::write(&key, certified.key_pair.serialize_pem()).unwrap();
+
+    TestCert { _dir: dir, cert, key }
+}
+
+#[tokio::test]
+async fn test_wss_roundtrip_with_pinned_cert() {
+    let cert = self_signed_cert();
+    let mut server = start_transport(
+        WsTransportConfig::new().with_tls(TlsConfig::server(&cert.cert, &cert.key)),
+    )
+    .await;
+    let mut client = start_transport(
+        WsTransportConfig::new().with_tls(TlsConfig::new().with_pinned_cert(&cert.cert)),
+    )
+    .await;
+
+    let address = server.local_address().unwrap();
+    assert!(address.to_string().starts_with("wss://"));
+
+    let conn_id = client.connect(address).await.unwrap();
+    client.send(&conn_id, b"encrypted iou").await.unwrap();
+
+    assert_eq!(receive_messages(&mut server, 1).await, vec![b"encrypted iou".to_vec()]);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wss_requires_tls_config() {
+    let cert = self_signed_cert();
+    let mut server = start_transport(
+        WsTransportConfig::new().with_tls(TlsConfig::server(&cert.cert, &cert.key)),
+    )
+    .await;
+    let mut client = start_transport(WsTransportConfig::new()).await;
+
+    let result = client.connect(server.local_address().unwrap()).await;
+    assert!(matches!(result, Err(TransportError::InvalidConfig(_))));
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wss_rejects_untrusted_server() {
+    let cert = self_signed_cert();
+    let other = self_signed_cert();
+    let mut server = start_transport(
+        WsTransportConfig::new().with_tls(TlsConfig::server(&cert.cert, &cert.key)),
+    )
+    .await;
+    let mut client = start_transport(
+        WsTransportConfig::new().with_tls(TlsConfig::new().with_pinned_cert(&other.cert)),
+    )
+    .await;
+
+    let result = client.connect(server.local_address().unwrap()).await;
+    assert!(matches!(result, Err(TransportError::ConnectionFailed(_))));
+    assert_eq!(client.connection_count(), 0);
+
+    client.stop().await.unwrap();
+    server.stop().await.unwrap();
+}
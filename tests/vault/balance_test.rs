@@ -1,7 +1,7 @@
 // Balance tracking tests for the vault module
 
 use p2pmesh::identity::{Did, Keypair};
-use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::iou::IOUBuilder;
 use p2pmesh::vault::{Vault, VaultError};
 
 // ============================================================================
@@ -167,7 +167,6 @@ fn test_receive_duplicate_iou_fails() {
 fn test_receive_iou_zero_amount_fails() {
     let alice = Keypair::generate();
     let bob = Keypair::generate();
-    let mut vault = Vault::new(bob.public_key());
 
     // Zero amount should fail at builder level, but vault should also reject
     let result = IOUBuilder::new()
@@ -301,15 +300,16 @@ fn test_available_balance_excludes_pending() {
     let bob = Keypair::generate();
     let mut vault = Vault::new(alice.public_key());
 
-    // Receive funds
-    let incoming = IOUBuilder::new()
-        .sender(&bob)
-        .recipient(Did::from_public_key(&alice.public_key()))
-        .amount(100)
-        .build()
-        .unwrap();
-
-    vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    // Receive 100 as two UTXOs so a reservation can hold one of them
+    for amount in [70, 30] {
+        let incoming = IOUBuilder::new()
+            .sender(&bob)
+            .recipient(Did::from_public_key(&alice.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    }
 
     // Reserve some for pending transaction
     vault.reserve_balance(30).unwrap();
@@ -346,15 +346,16 @@ fn test_release_reserved_balance() {
     let bob = Keypair::generate();
     let mut vault = Vault::new(alice.public_key());
 
-    // Receive funds
-    let incoming = IOUBuilder::new()
-        .sender(&bob)
-        .recipient(Did::from_public_key(&alice.public_key()))
-        .amount(100)
-        .build()
-        .unwrap();
-
-    vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    // Receive 100 as two UTXOs so a reservation can hold one of them
+    for amount in [70, 30] {
+        let incoming = IOUBuilder::new()
+            .sender(&bob)
+            .recipient(Did::from_public_key(&alice.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    }
 
     // Reserve and then release
     let reservation_id = vault.reserve_balance(30).unwrap();
@@ -365,32 +366,136 @@ fn test_release_reserved_balance() {
 }
 
 #[test]
-fn test_commit_reservation_releases_hold() {
+fn test_commit_reservation_spends_reserved_amount() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+
+    // Receive 100 as two UTXOs so a reservation can hold one of them
+    for amount in [70, 30] {
+        let incoming = IOUBuilder::new()
+            .sender(&bob)
+            .recipient(Did::from_public_key(&alice.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    }
+
+    let reservation_id = vault.reserve_balance(30).unwrap();
+    assert_eq!(vault.available_balance(), 70); // 30 is reserved
+
+    let outgoing = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(30)
+        .build()
+        .unwrap();
+
+    let committed_amount = vault.commit_reservation(reservation_id, outgoing).unwrap();
+
+    // Balance decreases by exactly the committed amount and the hold is gone
+    assert_eq!(committed_amount, 30);
+    assert_eq!(vault.balance(), 70);
+    assert_eq!(vault.available_balance(), 70);
+    assert_eq!(vault.sent_transactions().len(), 1);
+}
+
+#[test]
+fn test_commit_reservation_with_smaller_iou_releases_remainder() {
     let alice = Keypair::generate();
     let bob = Keypair::generate();
     let mut vault = Vault::new(alice.public_key());
 
-    // Receive funds
     let incoming = IOUBuilder::new()
         .sender(&bob)
         .recipient(Did::from_public_key(&alice.public_key()))
         .amount(100)
         .build()
         .unwrap();
-
     vault.receive_iou(incoming, &bob.public_key()).unwrap();
 
-    // Reserve and commit - commit just releases the hold
-    // The actual balance reduction happens via record_sent_iou
+    let reservation_id = vault.reserve_balance(50).unwrap();
+
+    let outgoing = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(20)
+        .build()
+        .unwrap();
+
+    assert_eq!(vault.commit_reservation(reservation_id, outgoing).unwrap(), 20);
+    assert_eq!(vault.balance(), 80);
+    assert_eq!(vault.available_balance(), 80);
+}
+
+#[test]
+fn test_commit_reservation_exceeding_reserved_amount_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+
+    // Receive 100 as two UTXOs so a reservation can hold one of them
+    for amount in [70, 30] {
+        let incoming = IOUBuilder::new()
+            .sender(&bob)
+            .recipient(Did::from_public_key(&alice.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    }
+
     let reservation_id = vault.reserve_balance(30).unwrap();
-    assert_eq!(vault.available_balance(), 70); // 30 is reserved
 
-    let committed_amount = vault.commit_reservation(reservation_id).unwrap();
+    let outgoing = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(40)
+        .build()
+        .unwrap();
 
-    assert_eq!(committed_amount, 30);
-    // After commit, reservation is released - full balance available again
-    // (In practice, record_sent_iou would be called to actually spend)
+    let result = vault.commit_reservation(reservation_id, outgoing);
+    assert!(matches!(result, Err(VaultError::ReservationExceeded { reserved: 30, requested: 40 })));
+
+    // Nothing spent, reservation still held
+    assert_eq!(vault.balance(), 100);
+    assert_eq!(vault.available_balance(), 70);
+}
+
+#[test]
+fn test_failed_commit_keeps_reservation() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+
+    // Receive 100 as two UTXOs so a reservation can hold one of them
+    for amount in [70, 30] {
+        let incoming = IOUBuilder::new()
+            .sender(&bob)
+            .recipient(Did::from_public_key(&alice.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    }
+
+    let reservation_id = vault.reserve_balance(30).unwrap();
+
+    // IOU signed by someone else is not ours to spend
+    let foreign = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&alice.public_key()))
+        .amount(30)
+        .build()
+        .unwrap();
+
+    let result = vault.commit_reservation(reservation_id, foreign);
+    assert!(matches!(result, Err(VaultError::NotOwner)));
     assert_eq!(vault.balance(), 100);
+    assert_eq!(vault.available_balance(), 70);
+
+    vault.release_reservation(reservation_id).unwrap();
     assert_eq!(vault.available_balance(), 100);
 }
 
@@ -495,6 +600,46 @@ fn test_get_sent_transactions_only() {
     assert_eq!(sent.len(), 1);
 }
 
+
+#[test]
+fn test_highest_sent_nonce_empty_vault() {
+    let alice = Keypair::generate();
+    let vault = Vault::new(alice.public_key());
+
+    assert_eq!(vault.highest_sent_nonce(), None);
+}
+
+#[test]
+fn test_highest_sent_nonce_tracks_sent_ious_only() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+
+    // Received IOU with a large nonce must not count
+    let incoming = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&alice.public_key()))
+        .amount(100)
+        .nonce(1_000)
+        .build()
+        .unwrap();
+    vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    assert_eq!(vault.highest_sent_nonce(), None);
+
+    for nonce in [3, 7, 5] {
+        let outgoing = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(Did::from_public_key(&bob.public_key()))
+            .amount(10)
+            .nonce(nonce)
+            .build()
+            .unwrap();
+        vault.record_sent_iou(outgoing).unwrap();
+    }
+
+    assert_eq!(vault.highest_sent_nonce(), Some(7));
+}
+
 // ============================================================================
 // BALANCE BY SENDER TESTS
 // ============================================================================
@@ -534,7 +679,6 @@ fn test_balance_from_specific_sender() {
 
 #[test]
 fn test_balance_from_unknown_sender_is_zero() {
-    let alice = Keypair::generate();
     let bob = Keypair::generate();
     let charlie = Keypair::generate();
     let vault = Vault::new(bob.public_key());
@@ -0,0 +1,179 @@
+// Cancellation tests for the vault module
+// Tests that a sender can withdraw an unaccepted IOU and get their funds back
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, PaymentRequestBuilder, RejectionReason, SignedCancellation, SignedIOU, SignedRejection};
+use p2pmesh::vault::{TransactionDirection, Vault, VaultError};
+
+/// Fund `owner`'s vault with 100 and send `amount` to `recipient`
+fn vault_with_sent_iou(owner: &Keypair, recipient: &Keypair, amount: u64) -> (Vault, SignedIOU) {
+    let funder = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+
+    let incoming = IOUBuilder::new()
+        .sender(&funder)
+        .recipient(Did::from_public_key(&owner.public_key()))
+        .amount(100)
+        .build()
+        .unwrap();
+    vault.receive_iou(incoming, &funder.public_key()).unwrap();
+
+    let outgoing = IOUBuilder::new()
+        .sender(owner)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(outgoing.clone()).unwrap();
+
+    (vault, outgoing)
+}
+
+// ============================================================================
+// CANCEL TESTS
+// ============================================================================
+
+#[test]
+fn test_cancel_restores_balance() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+    assert_eq!(vault.balance(), 70);
+
+    let restored = vault.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id())).unwrap();
+
+    assert_eq!(restored, 30);
+    assert_eq!(vault.balance(), 100);
+    assert_eq!(vault.available_balance(), 100);
+}
+
+#[test]
+fn test_cancel_records_cancelled_transaction() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+
+    vault.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id())).unwrap();
+
+    let last = vault.transaction_history().last().copied().unwrap();
+    assert_eq!(last.direction(), TransactionDirection::Cancelled);
+    assert_eq!(last.iou().id(), iou.id());
+    assert_eq!(vault.transactions_with(&Did::from_public_key(&bob.public_key())).len(), 2);
+}
+
+#[test]
+fn test_cancel_settled_iou_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+
+    // Acceptance seen before the cancellation wins
+    vault.mark_sent_settled(&iou.id()).unwrap();
+    let result = vault.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id()));
+
+    assert!(matches!(result, Err(VaultError::AlreadySettled)));
+    assert!(vault.is_settled(&iou.id()));
+    assert_eq!(vault.balance(), 70);
+}
+
+#[test]
+fn test_mark_sent_settled_requires_sent_iou() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, _) = vault_with_sent_iou(&alice, &bob, 30);
+    let received = vault.received_transactions()[0].iou().id();
+
+    assert!(matches!(vault.mark_sent_settled(&received), Err(VaultError::UnknownIOU)));
+}
+
+#[test]
+fn test_cancel_twice_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+    vault.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id())).unwrap();
+
+    let result = vault.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id()));
+
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+    assert_eq!(vault.balance(), 100);
+}
+
+#[test]
+fn test_cancel_unknown_iou_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, _) = vault_with_sent_iou(&alice, &bob, 30);
+    let (_, other) = vault_with_sent_iou(&alice, &bob, 10);
+
+    let result = vault.cancel_sent_iou(&SignedCancellation::sign(&alice, other.id()));
+
+    assert!(matches!(result, Err(VaultError::UnknownIOU)));
+}
+
+#[test]
+fn test_cancel_by_someone_else_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+
+    let result = vault.cancel_sent_iou(&SignedCancellation::sign(&bob, iou.id()));
+
+    assert!(matches!(result, Err(VaultError::SenderMismatch)));
+    assert_eq!(vault.balance(), 70);
+}
+
+#[test]
+fn test_cancel_after_rejection_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+    vault.process_rejection(&SignedRejection::sign(&bob, iou.id(), RejectionReason::Declined)).unwrap();
+
+    let result = vault.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id()));
+
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+    assert_eq!(vault.balance(), 100);
+}
+
+#[test]
+fn test_rejection_after_cancel_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+    vault.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id())).unwrap();
+
+    let result = vault.process_rejection(&SignedRejection::sign(&bob, iou.id(), RejectionReason::Declined));
+
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+    assert_eq!(vault.balance(), 100);
+}
+
+#[test]
+fn test_cancelled_request_payment_can_be_repaid() {
+    let alice = Keypair::generate();
+    let merchant = Keypair::generate();
+    let (mut vault, _) = vault_with_sent_iou(&alice, &merchant, 10);
+    let request = PaymentRequestBuilder::new().requester(&merchant).amount(20).build().unwrap();
+    let payment = IOUBuilder::new().sender(&alice).for_request(request.request()).build().unwrap();
+    vault.record_sent_iou(payment.clone()).unwrap();
+    assert!(vault.has_paid_request(&request.id()));
+
+    vault.cancel_sent_iou(&SignedCancellation::sign(&alice, payment.id())).unwrap();
+
+    assert!(!vault.has_paid_request(&request.id()));
+}
+
+#[test]
+fn test_cancel_survives_serialization() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+    vault.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id())).unwrap();
+
+    let mut restored = Vault::from_bytes(&vault.to_bytes()).unwrap();
+
+    assert_eq!(restored.balance(), 100);
+    let result = restored.cancel_sent_iou(&SignedCancellation::sign(&alice, iou.id()));
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+}
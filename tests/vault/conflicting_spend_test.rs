@@ -0,0 +1,123 @@
+// Conflicting spend tests for the vault module
+// Tests rejecting received IOUs that spend a source output already seen spent
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{ConflictDetector, SpendingClaim};
+use p2pmesh::vault::{UTXOId, Vault, VaultError};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn iou(from: &Keypair, to: &Keypair, amount: u64, nonce: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(from)
+        .recipient(did(to))
+        .amount(amount)
+        .nonce(nonce)
+        .build()
+        .unwrap()
+}
+
+/// Alice's vault holding a single 500 UTXO, and that UTXO's ID
+fn funded_sender(alice: &Keypair) -> (Vault, UTXOId) {
+    let faucet = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    vault.receive_iou(iou(&faucet, alice, 500, 1), &faucet.public_key()).unwrap();
+    let source = vault.utxo_set()[0].id().clone();
+    (vault, source)
+}
+
+/// Alice spends the same source twice from two copies of her vault
+fn double_spend(alice: &Keypair, bob: &Keypair) -> (SignedIOU, SignedIOU, UTXOId) {
+    let (mut vault, source) = funded_sender(alice);
+    let mut fork = vault.clone();
+    let first = iou(alice, bob, 300, 1);
+    let second = iou(alice, bob, 400, 2);
+
+    vault.spend_with_utxos(first.clone(), vec![source.clone()]).unwrap();
+    fork.spend_with_utxos(second.clone(), vec![source.clone()]).unwrap();
+
+    assert_eq!(vault.get_spent_output(&source).unwrap().spending_iou_id(), &first.id());
+    assert_eq!(fork.get_spent_output(&source).unwrap().spending_iou_id(), &second.id());
+    (first, second, source)
+}
+
+fn claims_for(alice: &Keypair, source: &UTXOId, spends: &[(&SignedIOU, u64)]) -> ConflictDetector {
+    let mut detector = ConflictDetector::new();
+    for (spend, timestamp) in spends {
+        let claim = SpendingClaim::with_timestamp(source.clone(), spend.id(), alice.public_key(), *timestamp);
+        let _ = detector.register_claim(claim);
+    }
+    detector
+}
+
+// ============================================================================
+// CHECKED RECEIVE
+// ============================================================================
+
+#[test]
+fn test_second_spend_of_same_source_is_flagged() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (first, second, source) = double_spend(&alice, &bob);
+    let claims = claims_for(&alice, &source, &[(&first, 1_000), (&second, 2_000)]);
+    let mut vault = Vault::new(bob.public_key());
+
+    vault.receive_iou_checked(first.clone(), &alice.public_key(), &claims).unwrap();
+    let result = vault.receive_iou_checked(second.clone(), &alice.public_key(), &claims);
+
+    match result {
+        Err(VaultError::ConflictingSpend { utxo_id, spent_by }) => {
+            assert_eq!(utxo_id, source);
+            assert_eq!(spent_by, first.id());
+        }
+        other => panic!("expected ConflictingSpend, got {other:?}"),
+    }
+    assert_eq!(vault.balance(), 300);
+    assert!(!vault.has_processed_iou(&second.id()));
+}
+
+#[test]
+fn test_later_spend_is_flagged_even_if_it_arrives_first() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (first, second, source) = double_spend(&alice, &bob);
+    let claims = claims_for(&alice, &source, &[(&second, 2_000), (&first, 1_000)]);
+    let mut vault = Vault::new(bob.public_key());
+
+    let result = vault.receive_iou_checked(second, &alice.public_key(), &claims);
+
+    assert!(matches!(result, Err(VaultError::ConflictingSpend { .. })));
+    assert_eq!(vault.balance(), 0);
+    vault.receive_iou_checked(first, &alice.public_key(), &claims).unwrap();
+    assert_eq!(vault.balance(), 300);
+}
+
+#[test]
+fn test_unclaimed_and_sole_spends_are_received() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (first, second, source) = double_spend(&alice, &bob);
+    let claims = claims_for(&alice, &source, &[(&first, 1_000)]);
+    let mut vault = Vault::new(bob.public_key());
+
+    vault.receive_iou_checked(first, &alice.public_key(), &claims).unwrap();
+    vault.receive_iou_checked(second, &alice.public_key(), &ConflictDetector::new()).unwrap();
+
+    assert_eq!(vault.balance(), 700);
+}
+
+#[test]
+fn test_unchecked_receive_accepts_double_spend() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (first, second, _) = double_spend(&alice, &bob);
+    let mut vault = Vault::new(bob.public_key());
+
+    vault.receive_iou(first, &alice.public_key()).unwrap();
+    vault.receive_iou(second, &alice.public_key()).unwrap();
+
+    assert_eq!(vault.balance(), 700);
+}
@@ -0,0 +1,263 @@
+// Credit limit tests for the vault module
+// Tests per-sender caps on unsettled received value
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::vault::{Vault, VaultError};
+
+fn iou(from: &Keypair, to: &Keypair, amount: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(from)
+        .recipient(Did::from_public_key(&to.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap()
+}
+
+fn receive(vault: &mut Vault, owner: &Keypair, from: &Keypair, amount: u64) -> Result<SignedIOU, VaultError> {
+    let iou = iou(from, owner, amount);
+    vault.receive_iou(iou.clone(), &from.public_key())?;
+    Ok(iou)
+}
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+// ============================================================================
+// OUTSTANDING BALANCE
+// ============================================================================
+
+#[test]
+fn test_outstanding_from_sender_sums_received() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+
+    receive(&mut vault, &owner, &alice, 30).unwrap();
+    receive(&mut vault, &owner, &alice, 20).unwrap();
+    receive(&mut vault, &owner, &bob, 7).unwrap();
+
+    assert_eq!(vault.outstanding_from_sender(&did(&alice)), 50);
+    assert_eq!(vault.outstanding_from_sender(&did(&bob)), 7);
+    assert_eq!(vault.outstanding_from_sender(&did(&Keypair::generate())), 0);
+}
+
+#[test]
+fn test_outstanding_ignores_payments_we_sent() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &alice, 100).unwrap();
+
+    vault.record_sent_iou(iou(&owner, &alice, 40)).unwrap();
+
+    assert_eq!(vault.outstanding_from_sender(&did(&alice)), 100);
+}
+
+#[test]
+fn test_mark_settled_reduces_outstanding() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    let first = receive(&mut vault, &owner, &alice, 30).unwrap();
+    receive(&mut vault, &owner, &alice, 20).unwrap();
+
+    vault.mark_settled(&first.id()).unwrap();
+
+    assert!(vault.is_settled(&first.id()));
+    assert_eq!(vault.outstanding_from_sender(&did(&alice)), 20);
+    // Settling doesn't touch the balance
+    assert_eq!(vault.balance(), 50);
+}
+
+#[test]
+fn test_mark_settled_twice_is_noop() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    let received = receive(&mut vault, &owner, &alice, 30).unwrap();
+
+    vault.mark_settled(&received.id()).unwrap();
+    vault.mark_settled(&received.id()).unwrap();
+
+    assert_eq!(vault.outstanding_from_sender(&did(&alice)), 0);
+}
+
+#[test]
+fn test_mark_settled_unknown_iou_fails() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    let never_received = iou(&alice, &owner, 10);
+
+    let result = vault.mark_settled(&never_received.id());
+
+    assert!(matches!(result, Err(VaultError::UnknownReceivedIOU)));
+}
+
+#[test]
+fn test_mark_settled_rejects_sent_iou() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &alice, 100).unwrap();
+    let sent = iou(&owner, &alice, 40);
+    vault.record_sent_iou(sent.clone()).unwrap();
+
+    let result = vault.mark_settled(&sent.id());
+
+    assert!(matches!(result, Err(VaultError::UnknownReceivedIOU)));
+}
+
+// ============================================================================
+// CREDIT LIMITS
+// ============================================================================
+
+#[test]
+fn test_no_credit_limit_by_default() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+
+    assert_eq!(vault.credit_limit(&did(&alice)), None);
+    assert!(receive(&mut vault, &owner, &alice, 1_000_000).is_ok());
+}
+
+#[test]
+fn test_receive_up_to_credit_limit() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_credit_limit(did(&alice), 100);
+
+    receive(&mut vault, &owner, &alice, 60).unwrap();
+    receive(&mut vault, &owner, &alice, 40).unwrap();
+
+    assert_eq!(vault.outstanding_from_sender(&did(&alice)), 100);
+}
+
+#[test]
+fn test_receive_over_credit_limit_fails() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_credit_limit(did(&alice), 100);
+    receive(&mut vault, &owner, &alice, 60).unwrap();
+
+    let result = receive(&mut vault, &owner, &alice, 41);
+
+    match result {
+        Err(VaultError::CreditLimitExceeded { sender, limit, outstanding }) => {
+            assert_eq!(sender, did(&alice));
+            assert_eq!(limit, 100);
+            assert_eq!(outstanding, 60);
+        }
+        other => panic!("Expected CreditLimitExceeded, got {:?}", other.map(|_| ())),
+    }
+    assert_eq!(vault.balance(), 60);
+    assert_eq!(vault.transaction_count(), 1);
+}
+
+#[test]
+fn test_refused_iou_can_be_received_after_settlement() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_credit_limit(did(&alice), 100);
+    let first = receive(&mut vault, &owner, &alice, 80).unwrap();
+    let second = iou(&alice, &owner, 50);
+    assert!(vault.receive_iou(second.clone(), &alice.public_key()).is_err());
+
+    vault.mark_settled(&first.id()).unwrap();
+
+    assert!(vault.receive_iou(second, &alice.public_key()).is_ok());
+    assert_eq!(vault.outstanding_from_sender(&did(&alice)), 50);
+}
+
+#[test]
+fn test_credit_limit_is_per_sender() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_credit_limit(did(&alice), 10);
+    receive(&mut vault, &owner, &alice, 10).unwrap();
+
+    assert!(receive(&mut vault, &owner, &bob, 500).is_ok());
+    assert!(receive(&mut vault, &owner, &alice, 1).is_err());
+}
+
+#[test]
+fn test_zero_credit_limit_refuses_sender() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_credit_limit(did(&alice), 0);
+
+    let result = receive(&mut vault, &owner, &alice, 1);
+
+    assert!(matches!(result, Err(VaultError::CreditLimitExceeded { .. })));
+}
+
+#[test]
+fn test_lowering_limit_below_outstanding_blocks_new_ious() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &alice, 70).unwrap();
+
+    vault.set_credit_limit(did(&alice), 50);
+
+    assert!(receive(&mut vault, &owner, &alice, 1).is_err());
+    assert_eq!(vault.balance(), 70);
+}
+
+#[test]
+fn test_remove_credit_limit() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_credit_limit(did(&alice), 10);
+
+    assert_eq!(vault.remove_credit_limit(&did(&alice)), Some(10));
+    assert_eq!(vault.credit_limit(&did(&alice)), None);
+    assert!(receive(&mut vault, &owner, &alice, 100).is_ok());
+}
+
+#[test]
+fn test_invalid_signature_checked_before_credit_limit() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mallory = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_credit_limit(did(&alice), 0);
+
+    let result = vault.receive_iou(iou(&alice, &owner, 5), &mallory.public_key());
+
+    assert!(!matches!(result, Err(VaultError::CreditLimitExceeded { .. })));
+    assert!(result.is_err());
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_credit_limits_and_settlements_serialize() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_credit_limit(did(&alice), 100);
+    let first = receive(&mut vault, &owner, &alice, 80).unwrap();
+    vault.mark_settled(&first.id()).unwrap();
+
+    let mut restored = Vault::from_bytes(&vault.to_bytes()).unwrap();
+
+    assert_eq!(restored.credit_limit(&did(&alice)), Some(100));
+    assert!(restored.is_settled(&first.id()));
+    assert_eq!(restored.outstanding_from_sender(&did(&alice)), 0);
+    assert!(receive(&mut restored, &owner, &alice, 100).is_ok());
+    assert!(receive(&mut restored, &owner, &alice, 1).is_err());
+}
@@ -4,7 +4,7 @@
 // 3. Memory growth in processed_ious
 
 use p2pmesh::identity::{Did, Keypair};
-use p2pmesh::iou::IOUBuilder;
+use p2pmesh::iou::{IOUBuilder, ValidationError};
 use p2pmesh::vault::{Vault, VaultError, UTXOType};
 use std::thread;
 use std::time::Duration;
@@ -414,6 +414,114 @@ fn test_recent_iou_protected_from_prune() {
     assert_eq!(duplicates, 3);
 }
 
+/// Test: Expired IOUs are refused by the vault
+#[test]
+fn test_receive_expired_iou_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let expired = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&alice.public_key()))
+        .amount(10)
+        .timestamp(now - 120)
+        .expires_at(now - 60)
+        .build()
+        .unwrap();
+
+    let result = vault.receive_iou(expired.clone(), &bob.public_key());
+
+    assert!(matches!(result, Err(VaultError::ValidationFailed(ValidationError::Expired))));
+    assert_eq!(vault.balance(), 0);
+    assert!(!vault.has_processed_iou(&expired.id()));
+}
+
+/// Test: Only processed IOUs past their expiry are pruned
+#[test]
+fn test_prune_expired_processed_ious() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let short = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&alice.public_key()))
+        .amount(10)
+        .expires_at(now + 60)
+        .build()
+        .unwrap();
+    let long = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&alice.public_key()))
+        .amount(20)
+        .expires_at(now + 3600)
+        .build()
+        .unwrap();
+    let forever = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&alice.public_key()))
+        .amount(30)
+        .build()
+        .unwrap();
+    for iou in [&short, &long, &forever] {
+        vault.receive_iou(iou.clone(), &bob.public_key()).unwrap();
+    }
+
+    // Nothing has expired yet
+    assert_eq!(vault.prune_expired_processed_ious(), 0);
+
+    let pruned = vault.prune_expired_processed_ious_at(now + 60);
+
+    assert_eq!(pruned, 1);
+    assert!(!vault.has_processed_iou(&short.id()));
+    assert!(vault.has_processed_iou(&long.id()));
+    assert!(vault.has_processed_iou(&forever.id()));
+
+    // Years later, only the IOU without an expiry is still tracked
+    assert_eq!(vault.prune_expired_processed_ious_at(now + 365 * 86400), 1);
+    assert_eq!(vault.processed_iou_count(), 1);
+    assert!(vault.has_processed_iou(&forever.id()));
+}
+
+/// Test: A pruned expired IOU still can't be replayed
+#[test]
+fn test_pruned_expired_iou_cannot_be_replayed() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let iou = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&alice.public_key()))
+        .amount(10)
+        .timestamp(now - 10)
+        .expires_at(now + 2)
+        .build()
+        .unwrap();
+    vault.receive_iou(iou.clone(), &bob.public_key()).unwrap();
+
+    thread::sleep(Duration::from_secs(3));
+    assert_eq!(vault.prune_expired_processed_ious(), 1);
+
+    let replay = vault.receive_iou(iou, &bob.public_key());
+
+    assert!(matches!(replay, Err(VaultError::ValidationFailed(ValidationError::Expired))));
+    assert_eq!(vault.balance(), 10);
+}
+
 /// Test: Memory stats are available
 #[test]
 fn test_memory_stats() {
@@ -0,0 +1,269 @@
+// Dust tests
+// Tests the dust threshold, dust-aware coin selection and dust sweeping
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, IOUId};
+use p2pmesh::vault::{
+    CoinSelection, Vault, VaultError, UTXOSet, UTXOType, DEFAULT_DUST_THRESHOLD, UTXO,
+};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+/// Vault for `owner` holding one received UTXO per amount
+fn vault_with_utxos(owner: &Keypair, amounts: &[u64]) -> Vault {
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+
+    for (nonce, amount) in amounts.iter().enumerate() {
+        let iou = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(did(owner))
+            .amount(*amount)
+            .nonce(nonce as u64 + 1)
+            .build()
+            .unwrap();
+        vault.receive_iou(iou, &alice.public_key()).unwrap();
+    }
+    vault
+}
+
+/// 50 UTXOs of 1 and 2 (total 75) plus the given larger amounts
+fn dusty_amounts(large: &[u64]) -> Vec<u64> {
+    let mut amounts: Vec<u64> = (0..50).map(|i| 1 + i % 2).collect();
+    amounts.extend_from_slice(large);
+    amounts
+}
+
+fn pay(vault: &mut Vault, sender: &Keypair, amount: u64) -> Result<(), VaultError> {
+    let iou = IOUBuilder::new()
+        .sender(sender)
+        .recipient(did(&Keypair::generate()))
+        .amount(amount)
+        .nonce(vault.highest_sent_nonce().map_or(1, |n| n + 1))
+        .build()
+        .unwrap();
+    vault.record_sent_iou(iou)
+}
+
+fn amounts(utxos: &[&UTXO]) -> Vec<u64> {
+    utxos.iter().map(|u| u.amount()).collect()
+}
+
+// ============================================================================
+// THRESHOLD
+// ============================================================================
+
+#[test]
+fn test_dust_disabled_by_default() {
+    let bob = Keypair::generate();
+    let vault = vault_with_utxos(&bob, &[1, 2, 3]);
+
+    assert_eq!(vault.dust_threshold(), DEFAULT_DUST_THRESHOLD);
+    assert!(vault.dust_utxos().is_empty());
+}
+
+#[test]
+fn test_dust_utxos_lists_amounts_below_threshold() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 9, 10, 50, 3]);
+    vault.set_dust_threshold(10);
+
+    assert_eq!(amounts(&vault.dust_utxos()), vec![1, 3, 9]);
+}
+
+#[test]
+fn test_dust_utxos_includes_locked() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 2, 100]);
+    vault.set_dust_threshold(5);
+
+    let id = vault.dust_utxos()[0].id().clone();
+    vault.lock_utxo(&id).unwrap();
+
+    assert_eq!(vault.dust_utxos().len(), 2);
+}
+
+#[test]
+fn test_dust_threshold_survives_roundtrip() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 100]);
+    vault.set_dust_threshold(25);
+
+    let restored = Vault::from_bytes(&vault.to_bytes()).unwrap();
+    assert_eq!(restored.dust_threshold(), 25);
+    assert_eq!(restored.dust_utxos().len(), 1);
+}
+
+// ============================================================================
+// COIN SELECTION
+// ============================================================================
+
+#[test]
+fn test_payment_skips_dust_when_larger_utxos_cover() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &dusty_amounts(&[100, 200]));
+    vault.set_dust_threshold(5);
+
+    // SmallestFirst would normally start with the dust
+    vault.set_coin_selection(CoinSelection::SmallestFirst);
+    pay(&mut vault, &bob, 150).unwrap();
+
+    assert_eq!(vault.dust_utxos().len(), 50);
+    assert_eq!(vault.balance(), 75 + 150);
+}
+
+#[test]
+fn test_no_strategy_selects_dust_when_larger_utxos_cover() {
+    let bob = Keypair::generate();
+    let strategies = [
+        CoinSelection::LargestFirst,
+        CoinSelection::SmallestFirst,
+        CoinSelection::MinimizeChange,
+        CoinSelection::MinimizeInputs,
+        CoinSelection::BranchAndBoundExactMatch { tolerance: 0 },
+        CoinSelection::OldestFirst,
+    ];
+
+    for strategy in strategies {
+        let mut vault = vault_with_utxos(&bob, &dusty_amounts(&[100, 200]));
+        vault.set_dust_threshold(5);
+        vault.set_coin_selection(strategy);
+
+        // 101 is reachable exactly with dust, but 100 + 200 covers it without
+        pay(&mut vault, &bob, 101).unwrap();
+        assert_eq!(vault.dust_utxos().len(), 50, "{:?}", strategy);
+    }
+}
+
+#[test]
+fn test_dust_spent_when_needed() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &dusty_amounts(&[100]));
+    vault.set_dust_threshold(5);
+
+    pay(&mut vault, &bob, 150).unwrap();
+
+    assert_eq!(vault.balance(), 25);
+    assert!(vault.dust_utxos().len() < 50);
+}
+
+#[test]
+fn test_reservation_skips_dust() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &dusty_amounts(&[100]));
+    vault.set_dust_threshold(5);
+    vault.set_coin_selection(CoinSelection::SmallestFirst);
+
+    let reservation = vault.reserve_for_amount(60).unwrap();
+
+    let reserved = vault.reservation_utxos(reservation).unwrap();
+    assert_eq!(reserved.len(), 1);
+    assert_eq!(vault.get_utxo(&reserved[0]).unwrap().amount(), 100);
+}
+
+#[test]
+fn test_utxo_set_select_avoiding_dust() {
+    let owner = Keypair::generate().public_key();
+    let mut set = UTXOSet::new();
+    for (i, amount) in [1u64, 2, 3, 40].iter().enumerate() {
+        set.add(UTXO::new(owner.clone(), *amount, IOUId::from_bytes([i as u8; 32])));
+    }
+
+    let (selected, change) = set.select_avoiding_dust(6, CoinSelection::SmallestFirst, 5).unwrap();
+    assert_eq!(selected.iter().map(|u| u.amount()).collect::<Vec<_>>(), vec![40]);
+    assert_eq!(change, 34);
+
+    let (selected, _) = set.select_avoiding_dust(45, CoinSelection::SmallestFirst, 5).unwrap();
+    assert_eq!(selected.len(), 4);
+
+    assert!(set.select_avoiding_dust(47, CoinSelection::SmallestFirst, 5).is_none());
+}
+
+// ============================================================================
+// SWEEPING
+// ============================================================================
+
+#[test]
+fn test_sweep_dust_merges_fifty_dust_utxos() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &dusty_amounts(&[100, 200]));
+    vault.set_dust_threshold(5);
+    assert_eq!(vault.utxo_set().len(), 52);
+
+    let iou = vault.sweep_dust(&bob).unwrap();
+
+    assert_eq!(iou.iou().amount(), 75);
+    assert!(iou.verify(&bob.public_key()));
+    assert_eq!(vault.utxo_set().len(), 3);
+    assert_eq!(vault.balance(), 375);
+    assert!(vault.dust_utxos().is_empty());
+
+    let mut remaining: Vec<u64> = vault.utxo_set().iter().map(|u| u.amount()).collect();
+    remaining.sort();
+    assert_eq!(remaining, vec![75, 100, 200]);
+}
+
+#[test]
+fn test_sweep_dust_when_balance_is_all_dust() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &dusty_amounts(&[]));
+    vault.set_dust_threshold(100);
+
+    vault.sweep_dust(&bob).unwrap();
+
+    // The swept output is still dust, but there is only one of it
+    let utxos = vault.utxo_set();
+    assert_eq!(utxos.len(), 1);
+    assert_eq!(utxos[0].amount(), 75);
+    assert_eq!(utxos[0].utxo_type(), UTXOType::Consolidated);
+    assert_eq!(vault.balance(), 75);
+    assert_eq!(vault.dust_utxos().len(), 1);
+}
+
+#[test]
+fn test_sweep_dust_then_pay_from_swept_output() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &dusty_amounts(&[]));
+    vault.set_dust_threshold(10);
+
+    vault.sweep_dust(&bob).unwrap();
+    pay(&mut vault, &bob, 70).unwrap();
+
+    assert_eq!(vault.balance(), 5);
+}
+
+#[test]
+fn test_sweep_dust_skips_locked_dust() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 2, 3, 100]);
+    vault.set_dust_threshold(5);
+    let locked = vault.dust_utxos()[0].id().clone();
+    vault.lock_utxo(&locked).unwrap();
+
+    let iou = vault.sweep_dust(&bob).unwrap();
+
+    assert_eq!(iou.iou().amount(), 5);
+    assert!(vault.get_utxo(&locked).is_some());
+}
+
+#[test]
+fn test_sweep_dust_needs_two_dust_utxos() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 100, 200]);
+    vault.set_dust_threshold(5);
+
+    assert!(matches!(vault.sweep_dust(&bob), Err(VaultError::NothingToConsolidate)));
+    assert_eq!(vault.utxo_set().len(), 3);
+}
+
+#[test]
+fn test_sweep_dust_rejects_other_keypair() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 2]);
+    vault.set_dust_threshold(5);
+
+    let result = vault.sweep_dust(&Keypair::generate());
+    assert!(matches!(result, Err(VaultError::NotOwner)));
+}
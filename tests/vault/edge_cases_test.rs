@@ -168,7 +168,7 @@ fn test_many_senders() {
 
     let num_senders = 50;
 
-    for i in 0..num_senders {
+    for _ in 0..num_senders {
         let sender = Keypair::generate();
         let iou = IOUBuilder::new()
             .sender(&sender)
@@ -426,19 +426,21 @@ fn test_multiple_reservations() {
     let bob = Keypair::generate();
     let mut vault = Vault::new(alice.public_key());
 
-    // Receive 100
-    let incoming = IOUBuilder::new()
-        .sender(&bob)
-        .recipient(Did::from_public_key(&alice.public_key()))
-        .amount(100)
-        .build()
-        .unwrap();
-    vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    // Receive 100 as one UTXO per reservation plus 10 spare
+    for amount in [30, 20, 40, 10] {
+        let incoming = IOUBuilder::new()
+            .sender(&bob)
+            .recipient(Did::from_public_key(&alice.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    }
 
     // Multiple reservations
-    let r1 = vault.reserve_balance(30).unwrap();
+    let _r1 = vault.reserve_balance(30).unwrap();
     let r2 = vault.reserve_balance(20).unwrap();
-    let r3 = vault.reserve_balance(40).unwrap();
+    let _r3 = vault.reserve_balance(40).unwrap();
 
     assert_eq!(vault.available_balance(), 10);
 
@@ -482,7 +484,14 @@ fn test_commit_already_released_reservation_fails() {
     let reservation = vault.reserve_balance(30).unwrap();
     vault.release_reservation(reservation).unwrap();
 
-    let result = vault.commit_reservation(reservation);
+    let outgoing = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(30)
+        .build()
+        .unwrap();
+
+    let result = vault.commit_reservation(reservation, outgoing);
     assert!(matches!(result, Err(VaultError::ReservationNotFound)));
 }
 
@@ -563,7 +572,8 @@ fn test_utxo_set_select_zero_amount() {
     let selected = set.select_for_amount(0);
     // Either returns empty selection or None
     if let Some((utxos, change)) = selected {
-        assert!(utxos.is_empty() || change >= 0);
+        assert!(utxos.is_empty());
+        assert_eq!(change, 0);
     }
 }
 
@@ -0,0 +1,130 @@
+// Encrypted vault tests
+// Tests passphrase-protected serialization of a vault at rest
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::IOUBuilder;
+use p2pmesh::storage::{sealed_params, KdfParams, SEALED_MAGIC};
+use p2pmesh::vault::{Vault, VaultError};
+
+/// Cheap Argon2id params so tests stay fast
+fn test_params() -> KdfParams {
+    KdfParams::new(64, 1)
+}
+
+fn funded_vault(amount: u64) -> (Keypair, Vault) {
+    let owner = Keypair::generate();
+    let sender = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    let iou = IOUBuilder::new()
+        .sender(&sender)
+        .recipient(Did::from_public_key(&owner.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.receive_iou(iou, &sender.public_key()).unwrap();
+    (owner, vault)
+}
+
+// ============================================================================
+// ROUNDTRIP TESTS
+// ============================================================================
+
+#[test]
+fn test_encrypted_vault_roundtrip() {
+    let (owner, vault) = funded_vault(750);
+
+    let blob = vault.to_encrypted_bytes_with_params("hunter2", &test_params()).unwrap();
+    let restored = Vault::from_encrypted_bytes(&blob, "hunter2").unwrap();
+
+    assert_eq!(restored.owner(), &owner.public_key());
+    assert_eq!(restored.balance(), 750);
+    assert_eq!(restored.transaction_count(), vault.transaction_count());
+}
+
+#[test]
+fn test_encrypted_vault_default_params() {
+    let (_, vault) = funded_vault(20);
+
+    let blob = vault.to_encrypted_bytes("correct horse");
+
+    assert_eq!(sealed_params(&blob).unwrap(), KdfParams::default());
+    assert_eq!(Vault::from_encrypted_bytes(&blob, "correct horse").unwrap().balance(), 20);
+}
+
+#[test]
+fn test_encrypted_vault_hides_contents() {
+    let (owner, vault) = funded_vault(100);
+
+    let blob = vault.to_encrypted_bytes_with_params("hunter2", &test_params()).unwrap();
+    let owner_key = owner.public_key();
+
+    assert_eq!(&blob[..4], SEALED_MAGIC);
+    assert!(!blob.windows(32).any(|w| w == owner_key.as_bytes()));
+}
+
+#[test]
+fn test_encrypted_vault_fresh_salt_each_time() {
+    let (_, vault) = funded_vault(100);
+
+    let first = vault.to_encrypted_bytes_with_params("hunter2", &test_params()).unwrap();
+    let second = vault.to_encrypted_bytes_with_params("hunter2", &test_params()).unwrap();
+
+    assert_ne!(first, second);
+}
+
+// ============================================================================
+// FAILURE TESTS
+// ============================================================================
+
+#[test]
+fn test_encrypted_vault_wrong_passphrase() {
+    let (_, vault) = funded_vault(100);
+    let blob = vault.to_encrypted_bytes_with_params("hunter2", &test_params()).unwrap();
+
+    let result = Vault::from_encrypted_bytes(&blob, "hunter3");
+
+    assert!(matches!(result, Err(VaultError::WrongPassphrase)));
+}
+
+#[test]
+fn test_encrypted_vault_tampered_blob() {
+    let (_, vault) = funded_vault(100);
+    let mut blob = vault.to_encrypted_bytes_with_params("hunter2", &test_params()).unwrap();
+    let last = blob.len() - 1;
+    blob[last] ^= 0x01;
+
+    assert!(matches!(Vault::from_encrypted_bytes(&blob, "hunter2"), Err(VaultError::WrongPassphrase)));
+}
+
+#[test]
+fn test_encrypted_vault_malformed_blob_is_state_error() {
+    let (_, vault) = funded_vault(100);
+
+    // Plaintext is not a sealed blob
+    let result = Vault::from_encrypted_bytes(&vault.to_bytes(), "hunter2");
+    assert!(matches!(result, Err(VaultError::StateError(_))));
+
+    let blob = vault.to_encrypted_bytes_with_params("hunter2", &test_params()).unwrap();
+    let result = Vault::from_encrypted_bytes(&blob[..20], "hunter2");
+    assert!(matches!(result, Err(VaultError::StateError(_))));
+}
+
+#[test]
+fn test_encrypted_vault_invalid_params_rejected() {
+    let (_, vault) = funded_vault(100);
+
+    let result = vault.to_encrypted_bytes_with_params("hunter2", &KdfParams::new(64, 0));
+
+    assert!(matches!(result, Err(VaultError::StateError(_))));
+}
+
+#[test]
+fn test_encrypted_vault_above_default_cost_needs_limit() {
+    let (_, vault) = funded_vault(100);
+    let params = KdfParams::new(64, KdfParams::default().iterations + 1);
+    let blob = vault.to_encrypted_bytes_with_params("hunter2", &params).unwrap();
+
+    assert!(matches!(Vault::from_encrypted_bytes(&blob, "hunter2"), Err(VaultError::StateError(_))));
+    let restored = Vault::from_encrypted_bytes_within(&blob, "hunter2", &params).unwrap();
+    assert_eq!(restored.balance(), 100);
+}
@@ -0,0 +1,170 @@
+// Transaction export tests for the vault module
+// Tests CSV headers, row order and escaping, and JSON round trips
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::vault::{
+    TransactionDirection, TransactionExport, Vault, COUNTERPARTY_SEPARATOR, TRANSACTION_EXPORT_COLUMNS,
+};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn receive(vault: &mut Vault, owner: &Keypair, from: &Keypair, amount: u64, memo: Option<&str>) -> SignedIOU {
+    let mut builder = IOUBuilder::new().sender(from).recipient(did(owner)).amount(amount);
+    if let Some(memo) = memo {
+        builder = builder.memo(memo);
+    }
+    let iou = builder.build().unwrap();
+    vault.receive_iou(iou.clone(), &from.public_key()).unwrap();
+    iou
+}
+
+fn send(vault: &mut Vault, owner: &Keypair, to: &Keypair, amount: u64) -> SignedIOU {
+    let iou = IOUBuilder::new().sender(owner).recipient(did(to)).amount(amount).build().unwrap();
+    vault.record_sent_iou(iou.clone()).unwrap();
+    iou
+}
+
+fn lines(csv: &str) -> Vec<&str> {
+    csv.split_terminator("\r\n").collect()
+}
+
+// ============================================================================
+// CSV TESTS
+// ============================================================================
+
+#[test]
+fn test_csv_header_is_stable() {
+    let owner = Keypair::generate();
+    let vault = Vault::new(owner.public_key());
+
+    let csv = vault.export_transactions_csv();
+
+    assert_eq!(lines(&csv), vec!["direction,counterparty,amount,timestamp,iou_id,memo"]);
+    assert_eq!(lines(&csv)[0], TRANSACTION_EXPORT_COLUMNS.join(","));
+}
+
+#[test]
+fn test_csv_one_row_per_transaction_oldest_first() {
+    let owner = Keypair::generate();
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+    let mut vault = Vault::new(owner.public_key());
+    let received = receive(&mut vault, &owner, &alice, 100, None);
+    let sent = send(&mut vault, &owner, &bob, 30);
+
+    let csv = vault.export_transactions_csv();
+    let rows = lines(&csv);
+
+    assert_eq!(rows.len(), 3);
+    let ts = vault.transaction_history()[0].timestamp();
+    assert_eq!(
+        rows[1],
+        format!("received,{},100,{},{},", did(&alice), ts, hex::encode(received.id().as_bytes()))
+    );
+    assert!(rows[2].starts_with(&format!("sent,{},30,", did(&bob))));
+    assert!(rows[2].contains(&hex::encode(sent.id().as_bytes())));
+}
+
+#[test]
+fn test_csv_escapes_memo() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &alice, 5, Some("lunch, \"the usual\"\nthanks"));
+
+    let csv = vault.export_transactions_csv();
+
+    assert!(csv.ends_with(",\"lunch, \"\"the usual\"\"\nthanks\"\r\n"));
+}
+
+#[test]
+fn test_csv_leaves_plain_memo_unquoted() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &alice, 5, Some("order 4711"));
+
+    assert!(vault.export_transactions_csv().ends_with(",order 4711\r\n"));
+}
+
+#[test]
+fn test_multi_output_send_lists_every_recipient() {
+    let owner = Keypair::generate();
+    let (funder, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &funder, 100, None);
+    let split = IOUBuilder::new()
+        .sender(&owner)
+        .add_output(did(&bob), 20)
+        .add_output(did(&carol), 10)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(split).unwrap();
+
+    let export = vault.transaction_exports().pop().unwrap();
+
+    assert_eq!(export.amount(), 30);
+    assert_eq!(export.counterparty(), format!("{}{}{}", did(&bob), COUNTERPARTY_SEPARATOR, did(&carol)));
+}
+
+// ============================================================================
+// JSON TESTS
+// ============================================================================
+
+#[test]
+fn test_json_round_trips_to_records() {
+    let owner = Keypair::generate();
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &alice, 100, Some("rent \"march\"\n\\ split"));
+    send(&mut vault, &owner, &bob, 30);
+
+    let parsed = TransactionExport::parse_json(&vault.export_transactions_json()).unwrap();
+
+    assert_eq!(parsed, vault.transaction_exports());
+    let records = vault.transaction_history();
+    for (export, record) in parsed.iter().zip(&records) {
+        assert_eq!(export.direction(), record.direction());
+        assert_eq!(export.timestamp(), record.timestamp());
+        assert_eq!(export.iou_id(), &record.iou().id());
+        assert_eq!(export.amount(), record.iou().iou().amount());
+        assert_eq!(export.memo(), record.iou().iou().memo());
+    }
+    assert_eq!(parsed[0].counterparty(), did(&alice).to_string());
+    assert_eq!(parsed[1].direction(), TransactionDirection::Sent);
+}
+
+#[test]
+fn test_json_keys_in_column_order() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &alice, 7, None);
+
+    let json = vault.export_transactions_json();
+
+    let positions: Vec<usize> = TRANSACTION_EXPORT_COLUMNS
+        .iter()
+        .map(|column| json.find(&format!("\"{}\":", column)).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(json.contains("\"memo\":null"));
+}
+
+#[test]
+fn test_empty_history_exports_empty_array() {
+    let owner = Keypair::generate();
+    let vault = Vault::new(owner.public_key());
+
+    assert_eq!(vault.export_transactions_json(), "[]");
+    assert!(TransactionExport::parse_json("[]").unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_json_rejects_malformed_input() {
+    assert!(TransactionExport::parse_json("{}").is_err());
+    assert!(TransactionExport::parse_json("[{\"direction\":\"gifted\"}]").is_err());
+    assert!(TransactionExport::parse_json("not json").is_err());
+}
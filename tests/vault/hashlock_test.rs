@@ -0,0 +1,293 @@
+// Hashlock tests for the vault module
+// Tests holding hashlocked IOUs until their preimage is revealed, and reclaiming them after expiry
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{Hashlock, IOUBuilder, SignedIOU};
+use p2pmesh::storage::MeshStore;
+use p2pmesh::vault::{PersistentVault, TransactionDirection, UTXOId, Vault, VaultError};
+use tempfile::TempDir;
+
+const PREIMAGE: &[u8] = b"swap secret";
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn hashlocked_iou(from: &Keypair, to: &Keypair, amount: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(from)
+        .recipient(did(to))
+        .amount(amount)
+        .hashlock(Hashlock::from_preimage(PREIMAGE))
+        .ttl_secs(3600)
+        .build()
+        .unwrap()
+}
+
+/// A vault for `owner` funded with `amount` from a fresh sender
+fn funded_vault(owner: &Keypair, amount: u64) -> Vault {
+    let faucet = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    let iou = IOUBuilder::new()
+        .sender(&faucet)
+        .recipient(did(owner))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.receive_iou(iou, &faucet.public_key()).unwrap();
+    vault
+}
+
+fn expiry(iou: &SignedIOU) -> u64 {
+    iou.iou().expiry().unwrap()
+}
+
+// ============================================================================
+// RECEIVING
+// ============================================================================
+
+#[test]
+fn test_hashlocked_iou_is_held_not_credited() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    let iou = hashlocked_iou(&alice, &bob, 300);
+
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    assert_eq!(vault.balance(), 0);
+    assert!(vault.is_held(&iou.id()));
+    assert_eq!(vault.held_balance(), 300);
+    assert_eq!(vault.held_ious(), vec![&iou]);
+    assert!(vault.has_processed_iou(&iou.id()));
+    assert!(vault.receive_iou(iou, &alice.public_key()).is_err());
+}
+
+#[test]
+fn test_correct_preimage_releases_iou() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let released = vault.reveal_preimage(&iou.id(), PREIMAGE).unwrap();
+
+    assert_eq!(released, 300);
+    assert_eq!(vault.balance(), 300);
+    assert!(vault.get_utxo(&UTXOId::from_iou(&iou.id())).is_some());
+    assert!(!vault.is_held(&iou.id()));
+    assert_eq!(vault.held_balance(), 0);
+}
+
+#[test]
+fn test_wrong_preimage_is_rejected() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let result = vault.reveal_preimage(&iou.id(), b"wrong secret");
+
+    assert!(matches!(result, Err(VaultError::WrongPreimage)));
+    assert_eq!(vault.balance(), 0);
+    assert!(vault.is_held(&iou.id()));
+
+    // The right preimage still works afterwards
+    assert_eq!(vault.reveal_preimage(&iou.id(), PREIMAGE).unwrap(), 300);
+}
+
+#[test]
+fn test_preimage_after_expiry_is_rejected() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let result = vault.reveal_preimage_at(&iou.id(), PREIMAGE, expiry(&iou));
+
+    assert!(matches!(result, Err(VaultError::HashlockExpired)));
+    assert_eq!(vault.balance(), 0);
+}
+
+#[test]
+fn test_reveal_twice_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+    vault.reveal_preimage(&iou.id(), PREIMAGE).unwrap();
+
+    let result = vault.reveal_preimage(&iou.id(), PREIMAGE);
+
+    assert!(matches!(result, Err(VaultError::NotHeld)));
+    assert_eq!(vault.balance(), 300);
+}
+
+#[test]
+fn test_reveal_for_plain_iou_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    let iou = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(did(&bob))
+        .amount(50)
+        .build()
+        .unwrap();
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let result = vault.reveal_preimage(&iou.id(), PREIMAGE);
+
+    assert!(matches!(result, Err(VaultError::NotHeld)));
+}
+
+#[test]
+fn test_invalidating_held_iou_is_not_overdrawn() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let reversed = vault.invalidate_received_iou(&iou.id()).unwrap();
+
+    assert_eq!(reversed, 0);
+    assert!(!vault.is_overdrawn());
+    assert!(!vault.is_held(&iou.id()));
+    assert!(matches!(vault.reveal_preimage(&iou.id(), PREIMAGE), Err(VaultError::NotHeld)));
+}
+
+// ============================================================================
+// RECLAIMING
+// ============================================================================
+
+#[test]
+fn test_sender_reclaims_after_timeout() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = funded_vault(&alice, 500);
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.record_sent_iou(iou.clone()).unwrap();
+    assert_eq!(vault.balance(), 200);
+
+    let reclaimed = vault.reclaim_hashlocked_at(&iou.id(), expiry(&iou)).unwrap();
+
+    assert_eq!(reclaimed, 300);
+    assert_eq!(vault.balance(), 500);
+    let last = vault.transaction_history().last().copied().unwrap();
+    assert_eq!(last.direction(), TransactionDirection::Reclaimed);
+    assert_eq!(vault.transactions_with(&did(&bob)).len(), 2);
+}
+
+#[test]
+fn test_reclaim_before_timeout_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = funded_vault(&alice, 500);
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.record_sent_iou(iou.clone()).unwrap();
+
+    let early = vault.reclaim_hashlocked_at(&iou.id(), expiry(&iou) - 1);
+    let now = vault.reclaim_hashlocked(&iou.id());
+
+    assert!(matches!(early, Err(VaultError::HashlockActive)));
+    assert!(matches!(now, Err(VaultError::HashlockActive)));
+    assert_eq!(vault.balance(), 200);
+}
+
+#[test]
+fn test_reclaim_twice_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = funded_vault(&alice, 500);
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.record_sent_iou(iou.clone()).unwrap();
+    vault.reclaim_hashlocked_at(&iou.id(), expiry(&iou)).unwrap();
+
+    let result = vault.reclaim_hashlocked_at(&iou.id(), expiry(&iou) + 10);
+
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+    assert_eq!(vault.balance(), 500);
+}
+
+#[test]
+fn test_reclaim_settled_iou_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = funded_vault(&alice, 500);
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.record_sent_iou(iou.clone()).unwrap();
+    vault.mark_sent_settled(&iou.id()).unwrap();
+
+    let result = vault.reclaim_hashlocked_at(&iou.id(), expiry(&iou));
+
+    assert!(matches!(result, Err(VaultError::AlreadySettled)));
+}
+
+#[test]
+fn test_reclaim_plain_or_unknown_iou_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = funded_vault(&alice, 500);
+    let plain = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(did(&bob))
+        .amount(100)
+        .ttl_secs(60)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(plain.clone()).unwrap();
+    let unknown = hashlocked_iou(&alice, &bob, 300);
+
+    let plain_result = vault.reclaim_hashlocked_at(&plain.id(), u64::MAX);
+    let unknown_result = vault.reclaim_hashlocked_at(&unknown.id(), u64::MAX);
+
+    assert!(matches!(plain_result, Err(VaultError::NotHashlocked)));
+    assert!(matches!(unknown_result, Err(VaultError::UnknownIOU)));
+}
+
+// ============================================================================
+// PERSISTENCE
+// ============================================================================
+
+#[test]
+fn test_held_iou_survives_reload() {
+    let dir = TempDir::new().unwrap();
+    let store = MeshStore::open(dir.path()).unwrap();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let iou = hashlocked_iou(&alice, &bob, 300);
+
+    let mut vault = PersistentVault::new(store.clone(), Vault::new(bob.public_key()));
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+    vault.persist().unwrap();
+
+    let mut loaded = PersistentVault::load(store.clone()).unwrap().unwrap();
+    assert!(loaded.is_held(&iou.id()));
+    assert_eq!(loaded.balance(), 0);
+
+    loaded.reveal_preimage(&iou.id(), PREIMAGE).unwrap();
+    loaded.persist().unwrap();
+
+    let reloaded = PersistentVault::load(store).unwrap().unwrap();
+    assert!(!reloaded.is_held(&iou.id()));
+    assert_eq!(reloaded.balance(), 300);
+}
+
+#[test]
+fn test_held_iou_survives_to_bytes() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    let iou = hashlocked_iou(&alice, &bob, 300);
+    vault.receive_iou(iou.clone(), &alice.public_key()).unwrap();
+
+    let restored = Vault::from_bytes(&vault.to_bytes()).unwrap();
+
+    assert!(restored.is_held(&iou.id()));
+    assert_eq!(restored.held_balance(), 300);
+}
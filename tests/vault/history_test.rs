@@ -0,0 +1,410 @@
+// Transaction history query tests for the vault module
+// Tests pagination, time ranges, counterparty lookups and filtered queries
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::vault::{
+    TransactionDirection, TransactionPage, TransactionQuery, TransactionRecord, TransactionSource, Vault,
+    DEFAULT_TRANSACTION_PAGE_SIZE,
+};
+
+fn receive(vault: &mut Vault, owner: &Keypair, from: &Keypair, amount: u64) -> SignedIOU {
+    let iou = IOUBuilder::new()
+        .sender(from)
+        .recipient(Did::from_public_key(&owner.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.receive_iou(iou.clone(), &from.public_key()).unwrap();
+    iou
+}
+
+fn send(vault: &mut Vault, owner: &Keypair, to: &Keypair, amount: u64) -> SignedIOU {
+    let iou = IOUBuilder::new()
+        .sender(owner)
+        .recipient(Did::from_public_key(&to.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(iou.clone()).unwrap();
+    iou
+}
+
+fn amounts(records: &[&TransactionRecord]) -> Vec<u64> {
+    records.iter().map(|r| r.iou().iou().amount()).collect()
+}
+
+fn page_amounts(page: &TransactionPage) -> Vec<u64> {
+    page.records().iter().map(|r| r.iou().iou().amount()).collect()
+}
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+/// Vault that received 1..=count from a single sender
+fn vault_with_receipts(owner: &Keypair, count: u64) -> Vault {
+    let sender = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    for amount in 1..=count {
+        receive(&mut vault, owner, &sender, amount);
+    }
+    vault
+}
+
+// ============================================================================
+// PAGINATION TESTS
+// ============================================================================
+
+#[test]
+fn test_transactions_page_newest_first() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 10);
+
+    assert_eq!(amounts(&vault.transactions_page(0, 3)), vec![10, 9, 8]);
+    assert_eq!(amounts(&vault.transactions_page(3, 3)), vec![7, 6, 5]);
+    assert_eq!(amounts(&vault.transactions_page(9, 3)), vec![1]);
+}
+
+#[test]
+fn test_transactions_page_out_of_range_is_empty() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 3);
+
+    assert!(vault.transactions_page(3, 10).is_empty());
+    assert!(vault.transactions_page(0, 0).is_empty());
+    assert!(Vault::new(owner.public_key()).transactions_page(0, 10).is_empty());
+}
+
+#[test]
+fn test_pages_cover_full_history() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 25);
+
+    let mut all = Vec::new();
+    for page in 0..5 {
+        all.extend(amounts(&vault.transactions_page(page * 7, 7)));
+    }
+
+    assert_eq!(all, (1..=25).rev().collect::<Vec<u64>>());
+}
+
+// ============================================================================
+// TIME RANGE TESTS
+// ============================================================================
+
+#[test]
+fn test_transactions_since_and_between_bounds() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 3);
+    let ts = vault.transaction_history()[0].timestamp();
+    let last_ts = vault.transaction_history()[2].timestamp();
+
+    assert_eq!(amounts(&vault.transactions_since(ts)), vec![3, 2, 1]);
+    assert!(vault.transactions_since(last_ts + 1).is_empty());
+    assert_eq!(vault.transactions_between(ts, last_ts).len(), 3);
+    assert!(vault.transactions_between(0, ts - 1).is_empty());
+    assert!(vault.transactions_between(last_ts, ts.saturating_sub(1)).is_empty());
+}
+
+#[test]
+fn test_transactions_between_selects_time_window() {
+    let owner = Keypair::generate();
+    let sender = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+
+    receive(&mut vault, &owner, &sender, 1);
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    receive(&mut vault, &owner, &sender, 2);
+    receive(&mut vault, &owner, &sender, 3);
+
+    let early = vault.transaction_history()[0].timestamp();
+    let late = vault.transaction_history()[1].timestamp();
+    assert!(late > early);
+
+    assert_eq!(amounts(&vault.transactions_between(early, early)), vec![1]);
+    assert_eq!(amounts(&vault.transactions_since(late)), vec![3, 2]);
+    assert_eq!(amounts(&vault.transactions_between(early, late)), vec![3, 2, 1]);
+}
+
+// ============================================================================
+// COUNTERPARTY TESTS
+// ============================================================================
+
+#[test]
+fn test_transactions_with_counterparty() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+
+    receive(&mut vault, &owner, &alice, 100);
+    receive(&mut vault, &owner, &bob, 50);
+    send(&mut vault, &owner, &alice, 30);
+
+    let with_alice = vault.transactions_with(&Did::from_public_key(&alice.public_key()));
+    let directions: Vec<TransactionDirection> = with_alice.iter().map(|r| r.direction()).collect();
+    assert_eq!(amounts(&with_alice), vec![30, 100]);
+    assert_eq!(directions, vec![TransactionDirection::Sent, TransactionDirection::Received]);
+
+    let with_bob = vault.transactions_with(&Did::from_public_key(&bob.public_key()));
+    assert_eq!(amounts(&with_bob), vec![50]);
+
+    let stranger = Keypair::generate();
+    assert!(vault.transactions_with(&Did::from_public_key(&stranger.public_key())).is_empty());
+}
+
+// ============================================================================
+// QUERY FILTER TESTS
+// ============================================================================
+
+/// Owner received 100 from alice and 50 from bob, then sent 30 to alice and 5 to bob
+fn mixed_vault(owner: &Keypair, alice: &Keypair, bob: &Keypair) -> Vault {
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, owner, alice, 100);
+    receive(&mut vault, owner, bob, 50);
+    send(&mut vault, owner, alice, 30);
+    send(&mut vault, owner, bob, 5);
+    vault
+}
+
+#[test]
+fn test_query_without_filters_matches_everything() {
+    let (owner, alice, bob) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let vault = mixed_vault(&owner, &alice, &bob);
+
+    let page = vault.query_transactions(&TransactionQuery::new());
+
+    assert_eq!(page_amounts(&page), vec![5, 30, 50, 100]);
+    assert_eq!(page.total(), 4);
+    assert!(!page.has_more());
+}
+
+#[test]
+fn test_query_by_direction() {
+    let (owner, alice, bob) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let vault = mixed_vault(&owner, &alice, &bob);
+
+    let received = vault.query_transactions(&TransactionQuery::new().direction(TransactionDirection::Received));
+    let sent = vault.query_transactions(&TransactionQuery::new().direction(TransactionDirection::Sent));
+    let refunded = vault.query_transactions(&TransactionQuery::new().direction(TransactionDirection::Refunded));
+
+    assert_eq!(page_amounts(&received), vec![50, 100]);
+    assert_eq!(page_amounts(&sent), vec![5, 30]);
+    assert!(refunded.is_empty());
+    assert_eq!(refunded.total(), 0);
+}
+
+#[test]
+fn test_query_by_sender_and_recipient() {
+    let (owner, alice, bob) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let vault = mixed_vault(&owner, &alice, &bob);
+
+    let from_alice = vault.query_transactions(&TransactionQuery::new().sender(did(&alice)));
+    let to_bob = vault.query_transactions(&TransactionQuery::new().recipient(did(&bob)));
+    let from_owner = vault.query_transactions(&TransactionQuery::new().sender(did(&owner)));
+    let to_owner = vault.query_transactions(&TransactionQuery::new().recipient(did(&owner)));
+
+    assert_eq!(page_amounts(&from_alice), vec![100]);
+    assert_eq!(page_amounts(&to_bob), vec![5]);
+    assert_eq!(page_amounts(&from_owner), vec![5, 30]);
+    assert_eq!(page_amounts(&to_owner), vec![50, 100]);
+}
+
+#[test]
+fn test_query_recipient_matches_any_output() {
+    let owner = Keypair::generate();
+    let (alice, bob) = (Keypair::generate(), Keypair::generate());
+    let mut vault = vault_with_receipts(&owner, 1);
+    receive(&mut vault, &owner, &alice, 100);
+    let split = IOUBuilder::new()
+        .sender(&owner)
+        .add_output(did(&alice), 10)
+        .add_output(did(&bob), 20)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(split).unwrap();
+
+    let to_bob = vault.query_transactions(&TransactionQuery::new().recipient(did(&bob)));
+
+    assert_eq!(page_amounts(&to_bob), vec![30]);
+}
+
+#[test]
+fn test_query_by_amount_range_is_inclusive() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 10);
+
+    let at_least = vault.query_transactions(&TransactionQuery::new().min_amount(8));
+    let at_most = vault.query_transactions(&TransactionQuery::new().max_amount(2));
+    let window = vault.query_transactions(&TransactionQuery::new().min_amount(4).max_amount(6));
+    let empty = vault.query_transactions(&TransactionQuery::new().min_amount(6).max_amount(4));
+
+    assert_eq!(page_amounts(&at_least), vec![10, 9, 8]);
+    assert_eq!(page_amounts(&at_most), vec![2, 1]);
+    assert_eq!(page_amounts(&window), vec![6, 5, 4]);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_query_by_time_range() {
+    let owner = Keypair::generate();
+    let sender = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    receive(&mut vault, &owner, &sender, 1);
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    receive(&mut vault, &owner, &sender, 2);
+
+    let early = vault.transaction_history()[0].timestamp();
+    let late = vault.transaction_history()[1].timestamp();
+
+    assert_eq!(page_amounts(&vault.query_transactions(&TransactionQuery::new().since(late))), vec![2]);
+    assert_eq!(page_amounts(&vault.query_transactions(&TransactionQuery::new().until(early))), vec![1]);
+    assert_eq!(
+        page_amounts(&vault.query_transactions(&TransactionQuery::new().between(early, late))),
+        vec![2, 1]
+    );
+    assert!(vault.query_transactions(&TransactionQuery::new().between(late, early)).is_empty());
+}
+
+#[test]
+fn test_query_filters_combine() {
+    let (owner, alice, bob) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let mut vault = mixed_vault(&owner, &alice, &bob);
+    receive(&mut vault, &owner, &alice, 7);
+
+    let query = TransactionQuery::new()
+        .direction(TransactionDirection::Received)
+        .sender(did(&alice))
+        .min_amount(10);
+
+    assert_eq!(page_amounts(&vault.query_transactions(&query)), vec![100]);
+}
+
+// ============================================================================
+// QUERY PAGINATION TESTS
+// ============================================================================
+
+#[test]
+fn test_query_default_page_size() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, DEFAULT_TRANSACTION_PAGE_SIZE as u64 + 5);
+
+    let page = vault.query_transactions(&TransactionQuery::new());
+
+    assert_eq!(page.len(), DEFAULT_TRANSACTION_PAGE_SIZE);
+    assert_eq!(page.total(), DEFAULT_TRANSACTION_PAGE_SIZE + 5);
+    assert_eq!(page.next_offset(), Some(DEFAULT_TRANSACTION_PAGE_SIZE));
+}
+
+#[test]
+fn test_query_pages_walk_filtered_results() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 20);
+    let mut query = TransactionQuery::new().min_amount(6).limit(4);
+
+    let mut all = Vec::new();
+    let mut pages = 0;
+    loop {
+        let page = vault.query_transactions(&query);
+        assert_eq!(page.total(), 15);
+        all.extend(page_amounts(&page));
+        pages += 1;
+        if !page.has_more() {
+            break;
+        }
+        query = query.next_page();
+    }
+
+    assert_eq!(pages, 4);
+    assert_eq!(all, (6..=20).rev().collect::<Vec<u64>>());
+}
+
+#[test]
+fn test_query_pagination_boundaries() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 10);
+
+    let last = vault.query_transactions(&TransactionQuery::new().offset(8).limit(5));
+    assert_eq!(page_amounts(&last), vec![2, 1]);
+    assert!(!last.has_more());
+    assert_eq!(last.next_offset(), None);
+
+    let exact = vault.query_transactions(&TransactionQuery::new().offset(5).limit(5));
+    assert_eq!(exact.len(), 5);
+    assert!(!exact.has_more());
+
+    let past_end = vault.query_transactions(&TransactionQuery::new().offset(10));
+    assert!(past_end.is_empty());
+    assert_eq!(past_end.total(), 10);
+    assert_eq!(past_end.offset(), 10);
+
+    let zero = vault.query_transactions(&TransactionQuery::new().limit(0));
+    assert!(zero.is_empty());
+    assert!(zero.has_more());
+
+    let huge = vault.query_transactions(&TransactionQuery::new().offset(usize::MAX).limit(usize::MAX));
+    assert!(huge.is_empty());
+}
+
+#[test]
+fn test_iter_transactions_is_lazy_and_ignores_paging() {
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 10);
+    let query = TransactionQuery::new().max_amount(5).limit(1);
+
+    let first_two: Vec<u64> = vault.iter_transactions(&query).take(2).map(|r| r.iou().iou().amount()).collect();
+
+    assert_eq!(first_two, vec![5, 4]);
+    assert_eq!(vault.iter_transactions(&query).count(), 5);
+}
+
+#[test]
+fn test_query_through_transaction_source() {
+    fn newest(source: &dyn TransactionSource) -> Vec<TransactionRecord> {
+        source.query_transactions(&TransactionQuery::new().limit(2)).into_records()
+    }
+
+    let owner = Keypair::generate();
+    let vault = vault_with_receipts(&owner, 3);
+
+    let amounts: Vec<u64> = newest(&vault).iter().map(|r| r.iou().iou().amount()).collect();
+    assert_eq!(amounts, vec![3, 2]);
+}
+
+// ============================================================================
+// PERSISTENCE TESTS
+// ============================================================================
+
+#[test]
+fn test_index_survives_serialization_roundtrip() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = vault_with_receipts(&owner, 5);
+    receive(&mut vault, &owner, &alice, 100);
+
+    let restored = Vault::from_bytes(&vault.to_bytes()).unwrap();
+
+    assert_eq!(amounts(&restored.transactions_page(0, 3)), vec![100, 5, 4]);
+    let alice_did = Did::from_public_key(&alice.public_key());
+    assert_eq!(amounts(&restored.transactions_with(&alice_did)), vec![100]);
+}
+
+#[test]
+fn test_index_rebuilt_on_import_state() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = vault_with_receipts(&owner, 3);
+    receive(&mut vault, &owner, &alice, 100);
+
+    let mut imported = Vault::new(owner.public_key());
+    imported.import_state(vault.export_state().unwrap()).unwrap();
+
+    assert_eq!(amounts(&imported.transactions_page(0, 10)), vec![100, 3, 2, 1]);
+    let alice_did = Did::from_public_key(&alice.public_key());
+    assert_eq!(amounts(&imported.transactions_with(&alice_did)), vec![100]);
+
+    // New records keep extending the rebuilt index
+    receive(&mut imported, &owner, &alice, 7);
+    assert_eq!(amounts(&imported.transactions_with(&alice_did)), vec![7, 100]);
+}
@@ -0,0 +1,197 @@
+// Invalidation tests for the vault module
+// Tests reversing credit for received IOUs that lost a double-spend conflict
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::ledger::{ConflictDetector, ResolutionStrategy, SpendingClaim};
+use p2pmesh::storage::MeshStore;
+use p2pmesh::vault::{PersistentVault, TransactionDirection, UTXOId, Vault, VaultError};
+use tempfile::TempDir;
+
+fn receive(vault: &mut Vault, owner: &Keypair, from: &Keypair, amount: u64) -> SignedIOU {
+    let iou = IOUBuilder::new()
+        .sender(from)
+        .recipient(Did::from_public_key(&owner.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.receive_iou(iou.clone(), &from.public_key()).unwrap();
+    iou
+}
+
+fn send(vault: &mut Vault, owner: &Keypair, to: &Keypair, amount: u64) -> SignedIOU {
+    let iou = IOUBuilder::new()
+        .sender(owner)
+        .recipient(Did::from_public_key(&to.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(iou.clone()).unwrap();
+    iou
+}
+
+// ============================================================================
+// INVALIDATE TESTS
+// ============================================================================
+
+#[test]
+fn test_invalidate_unspent_iou_removes_utxo() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    let iou = receive(&mut vault, &alice, &bob, 40);
+    receive(&mut vault, &alice, &bob, 60);
+
+    let reversed = vault.invalidate_received_iou(&iou.id()).unwrap();
+
+    assert_eq!(reversed, 40);
+    assert_eq!(vault.balance(), 60);
+    assert!(vault.get_utxo(&UTXOId::from_iou(&iou.id())).is_none());
+    assert!(!vault.is_overdrawn());
+}
+
+#[test]
+fn test_invalidate_records_invalidated_transaction() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    let iou = receive(&mut vault, &alice, &bob, 40);
+
+    vault.invalidate_received_iou(&iou.id()).unwrap();
+
+    let last = vault.transaction_history().last().copied().unwrap();
+    assert_eq!(last.direction(), TransactionDirection::Invalidated);
+    assert_eq!(last.iou().id(), iou.id());
+    assert_eq!(vault.transactions_with(&Did::from_public_key(&bob.public_key())).len(), 2);
+}
+
+#[test]
+fn test_invalidate_spent_iou_flags_overdrawn() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    let iou = receive(&mut vault, &alice, &bob, 40);
+    send(&mut vault, &alice, &charlie, 40);
+
+    let reversed = vault.invalidate_received_iou(&iou.id()).unwrap();
+
+    assert_eq!(reversed, 0);
+    assert_eq!(vault.balance(), 0);
+    assert!(vault.is_overdrawn());
+    assert_eq!(vault.overdrawn_amount(), 40);
+}
+
+#[test]
+fn test_invalidate_releases_reservation_holding_utxo() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    let iou = receive(&mut vault, &alice, &bob, 40);
+    receive(&mut vault, &alice, &bob, 60);
+    let reservation = vault.reserve_for_amount(100).unwrap();
+
+    vault.invalidate_received_iou(&iou.id()).unwrap();
+
+    assert_eq!(vault.active_reservation_count(), 0);
+    assert!(vault.reservation_utxos(reservation).is_none());
+    assert_eq!(vault.available_balance(), 60);
+}
+
+#[test]
+fn test_invalidate_twice_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    let iou = receive(&mut vault, &alice, &bob, 40);
+    vault.invalidate_received_iou(&iou.id()).unwrap();
+
+    let result = vault.invalidate_received_iou(&iou.id());
+
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+}
+
+#[test]
+fn test_invalidate_unknown_iou_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    receive(&mut vault, &alice, &bob, 40);
+    let sent = send(&mut vault, &alice, &bob, 10);
+
+    let result = vault.invalidate_received_iou(&sent.id());
+
+    assert!(matches!(result, Err(VaultError::UnknownReceivedIOU)));
+    assert_eq!(vault.balance(), 30);
+}
+
+#[test]
+fn test_invalidate_conflict_losers() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    let first = receive(&mut vault, &alice, &bob, 40);
+    let second = receive(&mut vault, &alice, &bob, 40);
+
+    // Bob spent the same UTXO on both payments
+    let utxo_id = UTXOId::from_bytes([7u8; 32]);
+    let mut detector = ConflictDetector::with_strategy(ResolutionStrategy::PreferSettled);
+    detector.mark_settled(second.id());
+    detector
+        .register_claim(SpendingClaim::with_timestamp(utxo_id.clone(), first.id(), bob.public_key(), 1))
+        .unwrap();
+    let _ = detector.register_claim(SpendingClaim::with_timestamp(utxo_id, second.id(), bob.public_key(), 2));
+
+    for loser in detector.losing_iou_ids() {
+        vault.invalidate_received_iou(&loser).unwrap();
+    }
+
+    assert_eq!(vault.balance(), 40);
+    assert!(vault.get_utxo(&UTXOId::from_iou(&first.id())).is_none());
+    assert!(vault.get_utxo(&UTXOId::from_iou(&second.id())).is_some());
+}
+
+// ============================================================================
+// PERSISTENCE
+// ============================================================================
+
+#[test]
+fn test_invalidation_survives_serialization() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+    let mut vault = Vault::new(alice.public_key());
+    let iou = receive(&mut vault, &alice, &bob, 40);
+    send(&mut vault, &alice, &charlie, 40);
+    vault.invalidate_received_iou(&iou.id()).unwrap();
+
+    let mut restored = Vault::from_bytes(&vault.to_bytes()).unwrap();
+
+    assert_eq!(restored.overdrawn_amount(), 40);
+    let result = restored.invalidate_received_iou(&iou.id());
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+}
+
+#[test]
+fn test_invalidation_is_persisted_incrementally() {
+    let dir = TempDir::new().unwrap();
+    let store = MeshStore::open(dir.path()).unwrap();
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+
+    let mut vault = PersistentVault::new(store.clone(), Vault::new(alice.public_key()));
+    let spent = receive(&mut vault, &alice, &bob, 40);
+    send(&mut vault, &alice, &charlie, 40);
+    let kept = receive(&mut vault, &alice, &bob, 60);
+    vault.persist().unwrap();
+
+    vault.invalidate_received_iou(&spent.id()).unwrap();
+    vault.invalidate_received_iou(&kept.id()).unwrap();
+    vault.persist().unwrap();
+
+    let loaded = PersistentVault::load(store).unwrap().unwrap();
+    assert_eq!(loaded.balance(), 0);
+    assert_eq!(loaded.overdrawn_amount(), 40);
+    assert_eq!(loaded.transaction_history().len(), 5);
+}
@@ -3,3 +3,18 @@ mod critical_fixes_test;
 mod edge_cases_test;
 mod spending_test;
 mod utxo_test;
+mod rejection_test;
+mod history_test;
+mod persistent_test;
+mod credit_limit_test;
+mod reservation_test;
+mod multi_output_test;
+mod cancellation_test;
+mod policy_test;
+mod dust_test;
+mod encrypted_test;
+mod invalidation_test;
+mod hashlock_test;
+mod conflicting_spend_test;
+mod statement_test;
+mod export_test;
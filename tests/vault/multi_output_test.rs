@@ -0,0 +1,155 @@
+// Multi-output IOU tests for the vault module
+// Tests that each recipient is credited only their own output and the sender pays the total
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, RejectionReason, SignedIOU, SignedRejection};
+use p2pmesh::vault::{Vault, VaultError};
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+fn funded_vault(owner: &Keypair, amount: u64) -> Vault {
+    let funder = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    let incoming = IOUBuilder::new()
+        .sender(&funder)
+        .recipient(did(owner))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.receive_iou(incoming, &funder.public_key()).unwrap();
+    vault
+}
+
+/// Bill split from `payer` to alice (30), bob (50) and carol (20)
+fn split_bill(payer: &Keypair, alice: &Keypair, bob: &Keypair, carol: &Keypair) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(payer)
+        .add_output(did(alice), 30)
+        .add_output(did(bob), 50)
+        .add_output(did(carol), 20)
+        .build()
+        .unwrap()
+}
+
+// ============================================================================
+// RECEIVING
+// ============================================================================
+
+#[test]
+fn test_each_recipient_credited_own_output() {
+    let payer = Keypair::generate();
+    let (alice, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let iou = split_bill(&payer, &alice, &bob, &carol);
+
+    let mut alice_vault = Vault::new(alice.public_key());
+    let mut bob_vault = Vault::new(bob.public_key());
+    let mut carol_vault = Vault::new(carol.public_key());
+    alice_vault.receive_iou(iou.clone(), &payer.public_key()).unwrap();
+    bob_vault.receive_iou(iou.clone(), &payer.public_key()).unwrap();
+    carol_vault.receive_iou(iou, &payer.public_key()).unwrap();
+
+    assert_eq!(alice_vault.balance(), 30);
+    assert_eq!(bob_vault.balance(), 50);
+    assert_eq!(carol_vault.balance(), 20);
+}
+
+#[test]
+fn test_non_recipient_cannot_receive_split() {
+    let payer = Keypair::generate();
+    let (alice, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let mallory = Keypair::generate();
+    let iou = split_bill(&payer, &alice, &bob, &carol);
+    let mut vault = Vault::new(mallory.public_key());
+
+    let result = vault.receive_iou(iou, &payer.public_key());
+
+    assert!(matches!(result, Err(VaultError::RecipientMismatch)));
+    assert_eq!(vault.balance(), 0);
+}
+
+#[test]
+fn test_split_received_once() {
+    let payer = Keypair::generate();
+    let (alice, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let iou = split_bill(&payer, &alice, &bob, &carol);
+    let mut vault = Vault::new(bob.public_key());
+    vault.receive_iou(iou.clone(), &payer.public_key()).unwrap();
+
+    let result = vault.receive_iou(iou, &payer.public_key());
+
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+    assert_eq!(vault.balance(), 50);
+}
+
+#[test]
+fn test_credit_limit_counts_own_output_only() {
+    let payer = Keypair::generate();
+    let (alice, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let mut vault = Vault::new(carol.public_key());
+    vault.set_credit_limit(did(&payer), 25);
+
+    // Only carol's 20 counts against the limit, not the 100 total
+    vault.receive_iou(split_bill(&payer, &alice, &bob, &carol), &payer.public_key()).unwrap();
+
+    assert_eq!(vault.outstanding_from_sender(&did(&payer)), 20);
+    assert_eq!(vault.balance_from_sender(&did(&payer)), 20);
+}
+
+// ============================================================================
+// SENDING
+// ============================================================================
+
+#[test]
+fn test_sender_pays_total() {
+    let payer = Keypair::generate();
+    let (alice, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let mut vault = funded_vault(&payer, 150);
+
+    vault.record_sent_iou(split_bill(&payer, &alice, &bob, &carol)).unwrap();
+
+    assert_eq!(vault.balance(), 50);
+}
+
+#[test]
+fn test_sender_needs_total_balance() {
+    let payer = Keypair::generate();
+    let (alice, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let mut vault = funded_vault(&payer, 60);
+
+    let result = vault.record_sent_iou(split_bill(&payer, &alice, &bob, &carol));
+
+    assert!(matches!(result, Err(VaultError::InsufficientBalance { available: 60, required: 100 })));
+}
+
+#[test]
+fn test_split_indexed_under_every_recipient() {
+    let payer = Keypair::generate();
+    let (alice, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let mut vault = funded_vault(&payer, 150);
+    let iou = split_bill(&payer, &alice, &bob, &carol);
+
+    vault.record_sent_iou(iou.clone()).unwrap();
+
+    for recipient in [&alice, &bob, &carol] {
+        let history = vault.transactions_with(&did(recipient));
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].iou().id(), iou.id());
+    }
+}
+
+#[test]
+fn test_rejection_refunds_rejecting_output() {
+    let payer = Keypair::generate();
+    let (alice, bob, carol) = (Keypair::generate(), Keypair::generate(), Keypair::generate());
+    let mut vault = funded_vault(&payer, 150);
+    let iou = split_bill(&payer, &alice, &bob, &carol);
+    vault.record_sent_iou(iou.clone()).unwrap();
+
+    let rejection = SignedRejection::sign(&bob, iou.id(), RejectionReason::Declined);
+    let refunded = vault.process_rejection(&rejection).unwrap();
+
+    assert_eq!(refunded, 50);
+    assert_eq!(vault.balance(), 100);
+}
@@ -0,0 +1,510 @@
+// Incremental persistence tests for the vault module
+// Tests delta writes, reloading and recovery from truncated write sequences
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::storage::{MeshStore, StoreWrite};
+use p2pmesh::vault::{CoinSelectionStrategy, PersistentVault, Vault};
+use tempfile::TempDir;
+
+fn receive(vault: &mut Vault, owner: &Keypair, from: &Keypair, amount: u64) -> SignedIOU {
+    let iou = IOUBuilder::new()
+        .sender(from)
+        .recipient(Did::from_public_key(&owner.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.receive_iou(iou.clone(), &from.public_key()).unwrap();
+    iou
+}
+
+fn send(vault: &mut Vault, owner: &Keypair, to: &Keypair, amount: u64) -> SignedIOU {
+    let iou = IOUBuilder::new()
+        .sender(owner)
+        .recipient(Did::from_public_key(&to.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(iou.clone()).unwrap();
+    iou
+}
+
+fn open(dir: &TempDir) -> MeshStore {
+    MeshStore::open(dir.path()).unwrap()
+}
+
+fn new_persistent(store: &MeshStore, owner: &Keypair) -> PersistentVault {
+    let mut vault = PersistentVault::new(store.clone(), Vault::new(owner.public_key()));
+    vault.persist().unwrap();
+    vault
+}
+
+fn load(store: &MeshStore) -> Vault {
+    PersistentVault::load(store.clone()).unwrap().unwrap().into_inner()
+}
+
+fn utxo_summary(vault: &Vault) -> Vec<([u8; 32], u64, u64, bool)> {
+    let mut utxos: Vec<_> = vault
+        .utxo_set()
+        .iter()
+        .map(|u| (*u.id().as_bytes(), u.amount(), u.sequence(), u.is_locked()))
+        .collect();
+    utxos.sort();
+    utxos
+}
+
+fn spent_summary(vault: &Vault) -> Vec<([u8; 32], [u8; 32], u64)> {
+    let mut spent: Vec<_> = vault
+        .spent_outputs()
+        .iter()
+        .map(|s| (*s.utxo_id().as_bytes(), *s.spending_iou_id().as_bytes(), s.spent_at()))
+        .collect();
+    spent.sort();
+    spent
+}
+
+fn history_summary(vault: &Vault) -> Vec<([u8; 32], u64)> {
+    vault
+        .transaction_history()
+        .iter()
+        .map(|t| (*t.iou().id().as_bytes(), t.timestamp()))
+        .collect()
+}
+
+fn assert_same_vault(loaded: &Vault, expected: &Vault) {
+    assert_eq!(loaded.owner(), expected.owner());
+    assert_eq!(loaded.balance(), expected.balance());
+    assert_eq!(loaded.available_balance(), expected.available_balance());
+    assert_eq!(utxo_summary(loaded), utxo_summary(expected));
+    assert_eq!(spent_summary(loaded), spent_summary(expected));
+    assert_eq!(history_summary(loaded), history_summary(expected));
+    assert_eq!(loaded.processed_iou_count(), expected.processed_iou_count());
+    assert_eq!(loaded.active_lock_count(), expected.active_lock_count());
+    assert_eq!(loaded.coin_selection(), expected.coin_selection());
+}
+
+// ============================================================================
+// ROUND TRIP
+// ============================================================================
+
+#[test]
+fn test_load_empty_store_returns_none() {
+    let dir = TempDir::new().unwrap();
+
+    assert!(PersistentVault::load(open(&dir)).unwrap().is_none());
+}
+
+#[test]
+fn test_round_trip_after_payments() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+
+    vault.update(|v| receive(v, &owner, &peer, 100)).unwrap();
+    vault.update(|v| receive(v, &owner, &peer, 50)).unwrap();
+    vault.update(|v| send(v, &owner, &peer, 120)).unwrap();
+
+    let loaded = load(&store);
+
+    assert_same_vault(&loaded, vault.vault());
+    assert_eq!(loaded.balance(), 30);
+}
+
+#[test]
+fn test_round_trip_across_reopen() {
+    let dir = TempDir::new().unwrap();
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let expected = {
+        let store = open(&dir);
+        let mut vault = new_persistent(&store, &owner);
+        vault.update(|v| receive(v, &owner, &peer, 75)).unwrap();
+        vault.update(|v| send(v, &owner, &peer, 25)).unwrap();
+        store.flush().unwrap();
+        vault.into_inner()
+    };
+
+    let loaded = load(&open(&dir));
+
+    assert_same_vault(&loaded, &expected);
+}
+
+#[test]
+fn test_attach_existing_vault_writes_everything() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut plain = Vault::new(owner.public_key());
+    receive(&mut plain, &owner, &peer, 10);
+    receive(&mut plain, &owner, &peer, 20);
+    send(&mut plain, &owner, &peer, 15);
+
+    let mut vault = PersistentVault::new(store.clone(), plain.clone());
+    vault.persist().unwrap();
+
+    assert_same_vault(&load(&store), &plain);
+}
+
+#[test]
+fn test_loaded_vault_keeps_persisting() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    vault.update(|v| receive(v, &owner, &peer, 40)).unwrap();
+
+    let mut reloaded = PersistentVault::load(store.clone()).unwrap().unwrap();
+    reloaded.update(|v| receive(v, &owner, &peer, 2)).unwrap();
+
+    let loaded = load(&store);
+    assert_same_vault(&loaded, reloaded.vault());
+    assert_eq!(loaded.transaction_count(), 2);
+}
+
+#[test]
+fn test_loaded_vault_rejects_duplicate_iou() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    let iou = vault.update(|v| receive(v, &owner, &peer, 40)).unwrap();
+
+    let mut loaded = load(&store);
+
+    assert!(loaded.receive_iou(iou, &peer.public_key()).is_err());
+}
+
+// ============================================================================
+// VAULT API
+// ============================================================================
+
+#[test]
+fn test_open_creates_then_reopens_vault() {
+    let dir = TempDir::new().unwrap();
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    {
+        let store = open(&dir);
+        let mut vault = PersistentVault::open(store.clone(), owner.public_key()).unwrap();
+        assert_eq!(vault.balance(), 0);
+        receive(&mut vault, &owner, &peer, 60);
+        vault.persist().unwrap();
+        store.flush().unwrap();
+    }
+
+    let reopened = PersistentVault::open(open(&dir), owner.public_key()).unwrap();
+
+    assert_eq!(reopened.balance(), 60);
+    assert_eq!(reopened.owner(), &owner.public_key());
+}
+
+#[test]
+fn test_reopened_vault_recovers_balance_and_dedup() {
+    let dir = TempDir::new().unwrap();
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let received = {
+        let store = open(&dir);
+        let mut vault = Vault::new(owner.public_key()).with_store(store.clone());
+        let received = receive(&mut vault, &owner, &peer, 100);
+        send(&mut vault, &owner, &peer, 35);
+        vault.persist().unwrap();
+        store.flush().unwrap();
+        received
+    };
+
+    let mut reopened = PersistentVault::open(open(&dir), owner.public_key()).unwrap();
+
+    assert_eq!(reopened.balance(), 65);
+    assert!(reopened.has_processed_iou(&received.id()));
+    assert!(reopened.receive_iou(received, &peer.public_key()).is_err());
+    assert_eq!(reopened.balance(), 65);
+}
+
+#[test]
+fn test_changes_through_deref_are_persisted_incrementally() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    receive(&mut vault, &owner, &peer, 10);
+    let full = vault.pending_writes().unwrap().len();
+    vault.persist().unwrap();
+
+    receive(&mut vault, &owner, &peer, 20);
+
+    assert_eq!(vault.pending_writes().unwrap().len(), full);
+    vault.persist().unwrap();
+    assert_same_vault(&load(&store), &vault);
+}
+
+// ============================================================================
+// DELTA WRITES
+// ============================================================================
+
+#[test]
+fn test_receive_writes_constant_delta() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+
+    for amount in 1..=50 {
+        vault.update(|v| receive(v, &owner, &peer, amount)).unwrap();
+    }
+    receive(vault.vault_mut(), &owner, &peer, 1000);
+
+    // meta + transaction + processed IOU + UTXO, regardless of history size
+    assert_eq!(vault.pending_writes().unwrap().len(), 4);
+    assert_eq!(vault.persist().unwrap(), 4);
+}
+
+#[test]
+fn test_send_writes_spent_before_removing_utxos() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    vault.update(|v| receive(v, &owner, &peer, 100)).unwrap();
+
+    send(vault.vault_mut(), &owner, &peer, 60);
+    let writes = vault.pending_writes().unwrap();
+
+    let first_spent = writes
+        .iter()
+        .position(|w| matches!(w, StoreWrite::Put { key, .. } if key.starts_with(b"vault:spent:")))
+        .unwrap();
+    let first_delete = writes
+        .iter()
+        .position(|w| matches!(w, StoreWrite::Delete { .. }))
+        .unwrap();
+    assert!(first_spent < first_delete);
+}
+
+#[test]
+fn test_persist_without_changes_writes_meta_only() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+
+    assert_eq!(vault.persist().unwrap(), 1);
+}
+
+#[test]
+fn test_lock_state_persisted() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    vault.update(|v| receive(v, &owner, &peer, 100)).unwrap();
+    let id = vault.vault().utxo_set()[0].id().clone();
+
+    vault.update(|v| v.lock_utxo_with_timeout(&id, 60_000)).unwrap().unwrap();
+
+    let loaded = load(&store);
+    assert!(loaded.get_utxo(&id).unwrap().is_locked());
+    assert!(loaded.get_lock_info(&id).is_some());
+    assert_eq!(loaded.available_balance(), 0);
+}
+
+#[test]
+fn test_reservations_and_coin_selection_persisted() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    vault.update(|v| receive(v, &owner, &peer, 70)).unwrap();
+    vault.update(|v| receive(v, &owner, &peer, 30)).unwrap();
+
+    let reservation = vault.update(|v| v.reserve_balance(30)).unwrap().unwrap();
+    vault.update(|v| v.set_coin_selection(CoinSelectionStrategy::OldestFirst)).unwrap();
+
+    let mut loaded = load(&store);
+    assert_eq!(loaded.available_balance(), 70);
+    assert_eq!(loaded.reservation_utxos(reservation).map(|ids| ids.len()), Some(1));
+    assert_eq!(loaded.coin_selection(), CoinSelectionStrategy::OldestFirst);
+    assert!(loaded.release_reservation(reservation).is_ok());
+    assert_eq!(loaded.available_balance(), 100);
+}
+
+#[test]
+fn test_credit_limits_and_settlements_persisted() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let peer_did = Did::from_public_key(&peer.public_key());
+    let mut vault = new_persistent(&store, &owner);
+    vault.update(|v| v.set_credit_limit(peer_did.clone(), 100)).unwrap();
+    let first = vault.update(|v| receive(v, &owner, &peer, 60)).unwrap();
+    vault.update(|v| receive(v, &owner, &peer, 30)).unwrap();
+
+    vault.update(|v| v.mark_settled(&first.id())).unwrap().unwrap();
+
+    let loaded = load(&store);
+    assert_eq!(loaded.credit_limit(&peer_did), Some(100));
+    assert!(loaded.is_settled(&first.id()));
+    assert_eq!(loaded.outstanding_from_sender(&peer_did), 30);
+}
+
+#[test]
+fn test_pruned_processed_ious_deleted() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    for amount in 1..=5 {
+        vault.update(|v| receive(v, &owner, &peer, amount)).unwrap();
+    }
+
+    let pruned = vault.update(|v| v.prune_processed_ious_to_max(2)).unwrap();
+
+    assert_eq!(pruned, 3);
+    assert_eq!(store.list_keys_with_prefix(b"vault:processed:").unwrap().len(), 2);
+    assert_eq!(load(&store).processed_iou_count(), 2);
+}
+
+#[test]
+fn test_import_state_replaces_stored_records() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    for amount in 1..=4 {
+        vault.update(|v| receive(v, &owner, &peer, amount)).unwrap();
+    }
+    let mut smaller = Vault::new(owner.public_key());
+    receive(&mut smaller, &owner, &peer, 9);
+
+    vault
+        .update(|v| v.import_state(smaller.export_state().unwrap()))
+        .unwrap()
+        .unwrap();
+
+    let loaded = load(&store);
+    assert_same_vault(&loaded, &smaller);
+    assert_eq!(store.list_keys_with_prefix(b"vault:tx:").unwrap().len(), 1);
+}
+
+#[test]
+fn test_unpersisted_changes_not_visible_after_load() {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = new_persistent(&store, &owner);
+    vault.update(|v| receive(v, &owner, &peer, 10)).unwrap();
+
+    receive(vault.vault_mut(), &owner, &peer, 20);
+
+    assert_eq!(load(&store).balance(), 10);
+}
+
+// ============================================================================
+// CRASH RECOVERY
+// ============================================================================
+
+/// Deterministic xorshift generator
+fn rng(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    }
+}
+
+/// Apply one random operation
+fn random_op(vault: &mut Vault, owner: &Keypair, peers: &[Keypair], next: &mut impl FnMut() -> u64) {
+    let peer = &peers[(next() % peers.len() as u64) as usize];
+    match next() % 8 {
+        0..=2 => {
+            receive(vault, owner, peer, 1 + next() % 500);
+        }
+        3 | 4 => {
+            let available = vault.available_balance();
+            if available > 0 {
+                send(vault, owner, peer, 1 + next() % available);
+            }
+        }
+        5 => {
+            let utxos: Vec<_> = vault.utxo_set().iter().map(|u| u.id().clone()).collect();
+            if !utxos.is_empty() {
+                let id = &utxos[(next() % utxos.len() as u64) as usize];
+                if vault.get_utxo(id).unwrap().is_locked() {
+                    vault.unlock_utxo(id).unwrap();
+                } else {
+                    vault.lock_utxo(id).unwrap();
+                }
+            }
+        }
+        6 => {
+            let _ = vault.consolidate(owner, 4);
+        }
+        _ => {
+            let _ = vault.reserve_balance(next() % 50);
+        }
+    }
+}
+
+/// Run random operations, then replay every prefix of the write sequence
+/// (a crash after any single write) and check each loads consistently.
+fn check_truncated_writes(seed: u64, operations: usize) {
+    let dir = TempDir::new().unwrap();
+    let store = open(&dir);
+    let owner = Keypair::generate();
+    let peers: Vec<Keypair> = (0..3).map(|_| Keypair::generate()).collect();
+    let mut next = rng(seed);
+
+    let mut vault = PersistentVault::new(store.clone(), Vault::new(owner.public_key()));
+    let mut log = Vec::new();
+    // Balance and UTXO count after each complete batch, keyed by log length
+    let mut checkpoints = Vec::new();
+    for _ in 0..operations {
+        random_op(vault.vault_mut(), &owner, &peers, &mut next);
+        log.extend(vault.pending_writes().unwrap());
+        vault.persist().unwrap();
+        checkpoints.push((log.len(), vault.vault().balance(), vault.vault().utxo_set().len()));
+    }
+
+    let replay = store.namespace("replay").unwrap();
+    for (applied, write) in log.iter().enumerate() {
+        replay.apply_batch(std::slice::from_ref(write)).unwrap();
+
+        let loaded = load(&replay);
+        let utxo_sum: u64 = loaded.utxo_set().iter().map(|u| u.amount()).sum();
+        assert_eq!(loaded.balance(), utxo_sum, "seed {} after {} writes", seed, applied + 1);
+        assert!(loaded.available_balance() <= loaded.balance());
+        for utxo in loaded.utxo_set() {
+            assert!(!loaded.is_utxo_spent(utxo.id()), "spent UTXO loaded as spendable");
+        }
+
+        if let Some((_, balance, count)) = checkpoints.iter().find(|(len, _, _)| *len == applied + 1) {
+            assert_eq!(loaded.balance(), *balance);
+            assert_eq!(loaded.utxo_set().len(), *count);
+        }
+    }
+
+    assert_same_vault(&load(&replay), vault.vault());
+}
+
+#[test]
+fn test_truncated_write_sequence_stays_consistent() {
+    for seed in 1..=8 {
+        check_truncated_writes(seed, 30);
+    }
+}
@@ -0,0 +1,106 @@
+// Validation policy tests for the vault module
+// Tests that receive_iou applies the vault's ValidationPolicy
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU, ValidationError, ValidationPolicy};
+use p2pmesh::vault::{Vault, VaultError};
+
+fn iou(from: &Keypair, to: &Keypair, amount: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(from)
+        .recipient(Did::from_public_key(&to.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap()
+}
+
+fn did(keypair: &Keypair) -> Did {
+    Did::from_public_key(&keypair.public_key())
+}
+
+#[test]
+fn test_new_vault_has_default_policy() {
+    let vault = Vault::new(Keypair::generate().public_key());
+    assert_eq!(vault.validation_policy(), &ValidationPolicy::default());
+}
+
+#[test]
+fn test_receive_iou_applies_policy() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let policy = ValidationPolicy::new().with_max_amount(100);
+    let mut vault = Vault::with_policy(owner.public_key(), policy);
+
+    let result = vault.receive_iou(iou(&alice, &owner, 101), &alice.public_key());
+    assert!(matches!(
+        result,
+        Err(VaultError::ValidationFailed(ValidationError::AmountAboveMaximum { amount: 101, max: 100 }))
+    ));
+    assert_eq!(vault.balance(), 0);
+    assert_eq!(vault.transaction_count(), 0);
+
+    vault.receive_iou(iou(&alice, &owner, 100), &alice.public_key()).unwrap();
+    assert_eq!(vault.balance(), 100);
+}
+
+#[test]
+fn test_receive_iou_rejects_denied_sender() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mallory = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    vault.set_validation_policy(ValidationPolicy::new().deny_sender(did(&mallory)));
+
+    let result = vault.receive_iou(iou(&mallory, &owner, 10), &mallory.public_key());
+    assert!(matches!(result, Err(VaultError::ValidationFailed(ValidationError::SenderDenied(_)))));
+
+    vault.receive_iou(iou(&alice, &owner, 10), &alice.public_key()).unwrap();
+    assert_eq!(vault.balance(), 10);
+}
+
+#[test]
+fn test_rejected_iou_can_be_received_after_policy_change() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::with_policy(owner.public_key(), ValidationPolicy::new().with_min_amount(50));
+    let small = iou(&alice, &owner, 20);
+
+    assert!(vault.receive_iou(small.clone(), &alice.public_key()).is_err());
+
+    vault.set_validation_policy(ValidationPolicy::default());
+    vault.receive_iou(small, &alice.public_key()).unwrap();
+    assert_eq!(vault.balance(), 20);
+}
+
+#[test]
+fn test_policy_is_not_persisted() {
+    let owner = Keypair::generate();
+    let vault = Vault::with_policy(owner.public_key(), ValidationPolicy::new().with_require_expiry(true));
+
+    let restored = Vault::from_bytes(&vault.to_bytes()).unwrap();
+    assert_eq!(restored.validation_policy(), &ValidationPolicy::default());
+}
+
+#[test]
+fn test_receive_iou_at_checks_expiry_at_given_time() {
+    let owner = Keypair::generate();
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    let expiring = |amount| {
+        IOUBuilder::new()
+            .sender(&alice)
+            .recipient(did(&owner))
+            .amount(amount)
+            .timestamp(1000)
+            .expiry(1060)
+            .build()
+            .unwrap()
+    };
+
+    let result = vault.receive_iou_at(expiring(10), &alice.public_key(), 2000);
+    assert!(matches!(result, Err(VaultError::ValidationFailed(ValidationError::Expired))));
+    assert_eq!(vault.balance(), 0);
+
+    vault.receive_iou_at(expiring(20), &alice.public_key(), 1030).unwrap();
+    assert_eq!(vault.balance(), 20);
+}
@@ -0,0 +1,206 @@
+// Rejection and refund tests for the vault module
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{
+    IOUBuilder, PaymentRequestBuilder, PaymentRequestId, RejectionReason, SignedIOU, SignedRejection,
+};
+use p2pmesh::vault::{TransactionDirection, Vault, VaultError};
+
+/// Fund `owner`'s vault with 100 and send `amount` to `recipient`
+fn vault_with_sent_iou(owner: &Keypair, recipient: &Keypair, amount: u64) -> (Vault, SignedIOU) {
+    let funder = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+
+    let incoming = IOUBuilder::new()
+        .sender(&funder)
+        .recipient(Did::from_public_key(&owner.public_key()))
+        .amount(100)
+        .build()
+        .unwrap();
+    vault.receive_iou(incoming, &funder.public_key()).unwrap();
+
+    let outgoing = IOUBuilder::new()
+        .sender(owner)
+        .recipient(Did::from_public_key(&recipient.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(outgoing.clone()).unwrap();
+
+    (vault, outgoing)
+}
+
+// ============================================================================
+// REFUND TESTS
+// ============================================================================
+
+#[test]
+fn test_rejection_restores_balance() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+    assert_eq!(vault.balance(), 70);
+
+    let rejection = SignedRejection::sign(&bob, iou.id(), RejectionReason::Declined);
+    let refunded = vault.process_rejection(&rejection).unwrap();
+
+    assert_eq!(refunded, 30);
+    assert_eq!(vault.balance(), 100);
+    assert_eq!(vault.available_balance(), 100);
+}
+
+#[test]
+fn test_rejection_records_refunded_transaction() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+
+    let rejection = SignedRejection::sign(&bob, iou.id(), RejectionReason::Declined);
+    vault.process_rejection(&rejection).unwrap();
+
+    let last = vault.transaction_history().last().cloned().unwrap();
+    assert_eq!(last.direction(), TransactionDirection::Refunded);
+    assert_eq!(last.iou().id(), iou.id());
+}
+
+#[test]
+fn test_refunded_funds_can_be_spent() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 100);
+    assert_eq!(vault.balance(), 0);
+
+    let rejection = SignedRejection::sign(&bob, iou.id(), RejectionReason::Declined);
+    vault.process_rejection(&rejection).unwrap();
+
+    let retry = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(100)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(retry).unwrap();
+    assert_eq!(vault.balance(), 0);
+}
+
+#[test]
+fn test_duplicate_rejection_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+
+    let rejection = SignedRejection::sign(&bob, iou.id(), RejectionReason::Declined);
+    vault.process_rejection(&rejection).unwrap();
+
+    // Same message again, or a freshly signed one for the same IOU
+    let again = SignedRejection::sign(&bob, iou.id(), RejectionReason::Other);
+    assert!(matches!(vault.process_rejection(&rejection), Err(VaultError::DuplicateTransaction)));
+    assert!(matches!(vault.process_rejection(&again), Err(VaultError::DuplicateTransaction)));
+    assert_eq!(vault.balance(), 100);
+}
+
+#[test]
+fn test_rejection_from_non_recipient_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mallory = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+
+    let forged = SignedRejection::sign(&mallory, iou.id(), RejectionReason::Declined);
+    assert!(matches!(vault.process_rejection(&forged), Err(VaultError::RecipientMismatch)));
+    assert_eq!(vault.balance(), 70);
+}
+
+#[test]
+fn test_rejection_with_bad_signature_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let mallory = Keypair::generate();
+    let (mut vault, iou) = vault_with_sent_iou(&alice, &bob, 30);
+
+    // Claims to be from bob but signed by mallory
+    let genuine = SignedRejection::sign(&bob, iou.id(), RejectionReason::Declined);
+    let forged_sig = SignedRejection::sign(&mallory, iou.id(), RejectionReason::Declined);
+    let forged = SignedRejection::from_parts(
+        genuine.rejection().clone(),
+        forged_sig.signature().clone(),
+    );
+
+    assert!(matches!(vault.process_rejection(&forged), Err(VaultError::InvalidSignature)));
+    assert_eq!(vault.balance(), 70);
+}
+
+#[test]
+fn test_rejection_of_unknown_iou_fails() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, _) = vault_with_sent_iou(&alice, &bob, 30);
+
+    let other = IOUBuilder::new()
+        .sender(&alice)
+        .recipient(Did::from_public_key(&bob.public_key()))
+        .amount(30)
+        .build()
+        .unwrap();
+
+    let rejection = SignedRejection::sign(&bob, other.id(), RejectionReason::Declined);
+    assert!(matches!(vault.process_rejection(&rejection), Err(VaultError::UnknownIOU)));
+}
+
+// ============================================================================
+// PAID REQUEST TESTS
+// ============================================================================
+
+/// Fund `owner`'s vault with 100 and pay a fresh request from `merchant` for `amount`
+fn vault_with_paid_request(
+    owner: &Keypair,
+    merchant: &Keypair,
+    amount: u64,
+) -> (Vault, SignedIOU, PaymentRequestId) {
+    let (mut vault, _) = vault_with_sent_iou(owner, merchant, 1);
+    let request = PaymentRequestBuilder::new()
+        .requester(merchant)
+        .amount(amount)
+        .build()
+        .unwrap();
+
+    let payment = IOUBuilder::new()
+        .sender(owner)
+        .for_request(request.request())
+        .build()
+        .unwrap();
+    vault.record_sent_iou(payment.clone()).unwrap();
+
+    (vault, payment, request.id())
+}
+
+#[test]
+fn test_sent_payment_marks_request_paid() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (vault, _, request_id) = vault_with_paid_request(&alice, &bob, 30);
+
+    assert!(vault.has_paid_request(&request_id));
+}
+
+#[test]
+fn test_unpaid_request_not_marked_paid() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (vault, _) = vault_with_sent_iou(&alice, &bob, 30);
+    let request = PaymentRequestBuilder::new().requester(&bob).amount(30).build().unwrap();
+
+    assert!(!vault.has_paid_request(&request.id()));
+}
+
+#[test]
+fn test_refunded_request_can_be_paid_again() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let (mut vault, payment, request_id) = vault_with_paid_request(&alice, &bob, 30);
+
+    let rejection = SignedRejection::sign(&bob, payment.id(), RejectionReason::Declined);
+    vault.process_rejection(&rejection).unwrap();
+
+    assert!(!vault.has_paid_request(&request_id));
+}
@@ -0,0 +1,245 @@
+// Reservation tests for the vault module
+// Tests that reservations hold concrete UTXOs until committed, released or expired
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedIOU};
+use p2pmesh::vault::{Vault, VaultError, DEFAULT_RESERVATION_TIMEOUT_MS};
+
+fn vault_with_utxos(owner: &Keypair, amounts: &[u64]) -> Vault {
+    let sender = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    for &amount in amounts {
+        let incoming = IOUBuilder::new()
+            .sender(&sender)
+            .recipient(Did::from_public_key(&owner.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(incoming, &sender.public_key()).unwrap();
+    }
+    vault
+}
+
+fn payment(from: &Keypair, amount: u64) -> SignedIOU {
+    IOUBuilder::new()
+        .sender(from)
+        .recipient(Did::from_public_key(&Keypair::generate().public_key()))
+        .amount(amount)
+        .build()
+        .unwrap()
+}
+
+// ============================================================================
+// RESERVING UTXOS
+// ============================================================================
+
+#[test]
+fn test_reserve_locks_concrete_utxos() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[50, 30]);
+
+    let reservation = vault.reserve_for_amount(30).unwrap();
+
+    let held = vault.reservation_utxos(reservation).unwrap().to_vec();
+    assert_eq!(held.len(), 1);
+    let utxo = vault.get_utxo(&held[0]).unwrap();
+    assert_eq!(utxo.amount(), 30);
+    assert!(utxo.is_locked());
+    assert_eq!(vault.available_balance(), 50);
+    assert_eq!(vault.active_reservation_count(), 1);
+}
+
+#[test]
+fn test_reservation_holds_whole_utxo() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[100]);
+
+    vault.reserve_for_amount(30).unwrap();
+
+    // The 100 UTXO can't be split until the reservation commits
+    assert_eq!(vault.balance(), 100);
+    assert_eq!(vault.available_balance(), 0);
+}
+
+#[test]
+fn test_concurrent_reservations_use_distinct_utxos() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[40, 40]);
+
+    let first = vault.reserve_for_amount(40).unwrap();
+    let second = vault.reserve_for_amount(40).unwrap();
+
+    assert_ne!(vault.reservation_utxos(first), vault.reservation_utxos(second));
+    assert!(vault.commit_reservation(first, payment(&alice, 40)).is_ok());
+    assert!(vault.commit_reservation(second, payment(&alice, 40)).is_ok());
+    assert_eq!(vault.balance(), 0);
+}
+
+#[test]
+fn test_reserve_fails_when_unlocked_utxos_insufficient() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[40, 40]);
+    vault.reserve_for_amount(40).unwrap();
+
+    let result = vault.reserve_for_amount(50);
+
+    assert!(matches!(
+        result,
+        Err(VaultError::InsufficientBalance { available: 40, required: 50 })
+    ));
+    assert_eq!(vault.active_reservation_count(), 1);
+}
+
+#[test]
+fn test_unreserved_spend_cannot_take_reserved_utxos() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[60, 40]);
+    let reservation = vault.reserve_for_amount(60).unwrap();
+
+    let result = vault.record_sent_iou(payment(&alice, 50));
+
+    assert!(matches!(result, Err(VaultError::InsufficientBalance { available: 40, .. })));
+    assert!(vault.commit_reservation(reservation, payment(&alice, 60)).is_ok());
+}
+
+#[test]
+fn test_reserve_zero_is_noop() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[10]);
+
+    assert_eq!(vault.reserve_for_amount(0).unwrap(), 0);
+    assert_eq!(vault.active_reservation_count(), 0);
+    assert_eq!(vault.available_balance(), 10);
+}
+
+// ============================================================================
+// COMMIT AND RELEASE
+// ============================================================================
+
+#[test]
+fn test_commit_spends_exactly_reserved_utxos() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[10, 25, 70]);
+    let reservation = vault.reserve_for_amount(25).unwrap();
+    let held = vault.reservation_utxos(reservation).unwrap().to_vec();
+
+    vault.commit_reservation(reservation, payment(&alice, 25)).unwrap();
+
+    for id in &held {
+        assert!(vault.is_utxo_spent(id));
+    }
+    assert_eq!(vault.balance(), 80);
+    assert_eq!(vault.utxo_set().len(), 2);
+    assert_eq!(vault.active_reservation_count(), 0);
+}
+
+#[test]
+fn test_commit_returns_excess_as_unlocked_change() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[100]);
+    let reservation = vault.reserve_for_amount(50).unwrap();
+
+    vault.commit_reservation(reservation, payment(&alice, 20)).unwrap();
+
+    assert_eq!(vault.balance(), 80);
+    assert_eq!(vault.available_balance(), 80);
+}
+
+#[test]
+fn test_release_unlocks_reserved_utxos() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[30, 70]);
+    let reservation = vault.reserve_for_amount(30).unwrap();
+    let held = vault.reservation_utxos(reservation).unwrap().to_vec();
+
+    vault.release_reservation(reservation).unwrap();
+
+    assert!(!vault.get_utxo(&held[0]).unwrap().is_locked());
+    assert_eq!(vault.available_balance(), 100);
+    assert!(vault.reservation_utxos(reservation).is_none());
+}
+
+#[test]
+fn test_failed_commit_keeps_utxos_locked() {
+    let alice = Keypair::generate();
+    let mallory = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[30, 70]);
+    let reservation = vault.reserve_for_amount(30).unwrap();
+    let held = vault.reservation_utxos(reservation).unwrap().to_vec();
+
+    let result = vault.commit_reservation(reservation, payment(&mallory, 30));
+
+    assert!(matches!(result, Err(VaultError::NotOwner)));
+    assert!(vault.get_utxo(&held[0]).unwrap().is_locked());
+    assert_eq!(vault.reservation_utxos(reservation).unwrap(), held.as_slice());
+}
+
+// ============================================================================
+// EXPIRY
+// ============================================================================
+
+#[test]
+fn test_default_reservation_timeout() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[30]);
+
+    assert_eq!(vault.reservation_timeout_ms(), DEFAULT_RESERVATION_TIMEOUT_MS);
+    let reservation = vault.reserve_for_amount(30).unwrap();
+
+    let lock = vault.reservation_lock(reservation).unwrap();
+    assert!(!lock.is_expired());
+    assert!(lock.remaining_ms() <= DEFAULT_RESERVATION_TIMEOUT_MS);
+    assert_eq!(vault.cleanup_expired_locks(), 0);
+    assert_eq!(vault.active_reservation_count(), 1);
+}
+
+#[test]
+fn test_expired_reservation_released_by_cleanup() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[30, 70]);
+    vault.set_reservation_timeout(0);
+    let reservation = vault.reserve_for_amount(30).unwrap();
+    assert_eq!(vault.available_balance(), 70);
+
+    assert_eq!(vault.cleanup_expired_locks(), 1);
+
+    assert_eq!(vault.available_balance(), 100);
+    assert_eq!(vault.active_reservation_count(), 0);
+    let result = vault.commit_reservation(reservation, payment(&alice, 30));
+    assert!(matches!(result, Err(VaultError::ReservationNotFound)));
+}
+
+#[test]
+fn test_cleanup_keeps_live_reservations() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[30, 70]);
+    vault.set_reservation_timeout(0);
+    vault.reserve_for_amount(30).unwrap();
+    vault.set_reservation_timeout(60_000);
+    let live = vault.reserve_for_amount(70).unwrap();
+
+    assert_eq!(vault.cleanup_expired_locks(), 1);
+
+    assert_eq!(vault.active_reservation_count(), 1);
+    assert!(vault.reservation_utxos(live).is_some());
+    assert_eq!(vault.available_balance(), 30);
+}
+
+// ============================================================================
+// SERIALIZATION
+// ============================================================================
+
+#[test]
+fn test_reservations_serialize() {
+    let alice = Keypair::generate();
+    let mut vault = vault_with_utxos(&alice, &[30, 70]);
+    vault.set_reservation_timeout(60_000);
+    let reservation = vault.reserve_for_amount(30).unwrap();
+
+    let mut restored = Vault::from_bytes(&vault.to_bytes()).unwrap();
+
+    assert_eq!(restored.reservation_timeout_ms(), 60_000);
+    assert_eq!(restored.available_balance(), 70);
+    assert!(restored.commit_reservation(reservation, payment(&alice, 30)).is_ok());
+    assert_eq!(restored.balance(), 70);
+}
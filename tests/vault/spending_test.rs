@@ -1,7 +1,7 @@
 // Spending logic and double-spend prevention tests
 
 use p2pmesh::identity::{Did, Keypair};
-use p2pmesh::iou::{IOUBuilder, SignedIOU, IOUId};
+use p2pmesh::iou::{IOUBuilder, IOUId};
 use p2pmesh::vault::{Vault, VaultError, SpentOutput, SpentOutputSet};
 
 // ============================================================================
@@ -171,7 +171,6 @@ fn test_double_spend_detection_across_sync() {
         .amount(100)
         .build()
         .unwrap();
-    let incoming_id = incoming.id();
     vault.receive_iou(incoming, &bob.public_key()).unwrap();
 
     // Get the UTXO ID before spending
@@ -188,7 +187,7 @@ fn test_double_spend_detection_across_sync() {
     vault.record_sent_iou(outgoing_bob.clone()).unwrap();
 
     // Now simulate receiving a conflicting spend (same UTXO spent to Charlie)
-    let outgoing_charlie = IOUBuilder::new()
+    let _outgoing_charlie = IOUBuilder::new()
         .sender(&alice)
         .recipient(Did::from_public_key(&charlie.public_key()))
         .amount(100)
@@ -346,7 +345,7 @@ fn test_spent_output_set_add() {
     let spending_id = IOUId::from_bytes([2u8; 32]);
 
     let spent = SpentOutput::new(utxo_id.clone(), spending_id, 12345);
-    set.add(spent);
+    set.add(spent).unwrap();
 
     assert_eq!(set.len(), 1);
     assert!(set.contains(&utxo_id));
@@ -410,14 +409,16 @@ fn test_can_afford_considers_pending() {
     let bob = Keypair::generate();
     let mut vault = Vault::new(alice.public_key());
 
-    // Receive 100
-    let incoming = IOUBuilder::new()
-        .sender(&bob)
-        .recipient(Did::from_public_key(&alice.public_key()))
-        .amount(100)
-        .build()
-        .unwrap();
-    vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    // Receive 100 as two UTXOs so a reservation can hold one of them
+    for amount in [70, 30] {
+        let incoming = IOUBuilder::new()
+            .sender(&bob)
+            .recipient(Did::from_public_key(&alice.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(incoming, &bob.public_key()).unwrap();
+    }
 
     // Reserve 30
     vault.reserve_balance(30).unwrap();
@@ -0,0 +1,236 @@
+// Balance history and statement tests for the vault module
+// Tests running balances over receives and sends, ordering by timestamp, and
+// statement opening and closing balances
+
+use p2pmesh::identity::{Did, Keypair};
+use p2pmesh::iou::{IOUBuilder, SignedCancellation, SignedIOU};
+use p2pmesh::vault::{BalancePoint, TransactionDirection, Vault};
+
+fn receive_at(vault: &mut Vault, owner: &Keypair, from: &Keypair, amount: u64, ts: u64) -> SignedIOU {
+    let iou = IOUBuilder::new()
+        .sender(from)
+        .recipient(Did::from_public_key(&owner.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.receive_iou_at(iou.clone(), &from.public_key(), ts).unwrap();
+    iou
+}
+
+fn send_at(vault: &mut Vault, owner: &Keypair, to: &Keypair, amount: u64, ts: u64) -> SignedIOU {
+    let iou = IOUBuilder::new()
+        .sender(owner)
+        .recipient(Did::from_public_key(&to.public_key()))
+        .amount(amount)
+        .build()
+        .unwrap();
+    vault.record_sent_iou_at(iou.clone(), ts).unwrap();
+    iou
+}
+
+fn balances(points: &[BalancePoint]) -> Vec<u64> {
+    points.iter().map(BalancePoint::balance).collect()
+}
+
+fn timestamps(points: &[BalancePoint]) -> Vec<u64> {
+    points.iter().map(BalancePoint::timestamp).collect()
+}
+
+/// Receives of 100 (t=10) and 50 (t=30), sends of 30 (t=20) and 40 (t=40)
+fn mixed_vault(owner: &Keypair) -> Vault {
+    let (peer, other) = (Keypair::generate(), Keypair::generate());
+    let mut vault = Vault::new(owner.public_key());
+    receive_at(&mut vault, owner, &peer, 100, 10);
+    send_at(&mut vault, owner, &other, 30, 20);
+    receive_at(&mut vault, owner, &peer, 50, 30);
+    send_at(&mut vault, owner, &other, 40, 40);
+    vault
+}
+
+// ============================================================================
+// BALANCE HISTORY TESTS
+// ============================================================================
+
+#[test]
+fn test_balance_history_is_cumulative() {
+    let owner = Keypair::generate();
+    let vault = mixed_vault(&owner);
+
+    let history = vault.balance_history();
+
+    assert_eq!(balances(&history), vec![100, 70, 120, 80]);
+    let changes: Vec<(u64, u64)> = history.iter().map(|p| (p.credit(), p.debit())).collect();
+    assert_eq!(changes, vec![(100, 0), (0, 30), (50, 0), (0, 40)]);
+    assert_eq!(history[1].direction(), TransactionDirection::Sent);
+}
+
+#[test]
+fn test_last_point_matches_balance() {
+    let owner = Keypair::generate();
+    let vault = mixed_vault(&owner);
+
+    assert_eq!(vault.balance_history().last().unwrap().balance(), vault.balance());
+}
+
+#[test]
+fn test_out_of_order_timestamps_are_sorted() {
+    let owner = Keypair::generate();
+    let (peer, other) = (Keypair::generate(), Keypair::generate());
+    let mut vault = Vault::new(owner.public_key());
+    receive_at(&mut vault, &owner, &peer, 100, 100);
+    // Recorded later but stamped earlier, e.g. after a clock correction
+    receive_at(&mut vault, &owner, &peer, 50, 300);
+    send_at(&mut vault, &owner, &other, 30, 200);
+
+    let history = vault.balance_history();
+
+    assert_eq!(timestamps(&history), vec![100, 200, 300]);
+    assert_eq!(balances(&history), vec![100, 70, 120]);
+    assert_eq!(history.last().unwrap().balance(), vault.balance());
+}
+
+#[test]
+fn test_equal_timestamps_keep_log_order() {
+    let owner = Keypair::generate();
+    let (peer, other) = (Keypair::generate(), Keypair::generate());
+    let mut vault = Vault::new(owner.public_key());
+    receive_at(&mut vault, &owner, &peer, 100, 5);
+    send_at(&mut vault, &owner, &other, 60, 5);
+
+    assert_eq!(balances(&vault.balance_history()), vec![100, 40]);
+}
+
+#[test]
+fn test_empty_vault_has_no_history() {
+    let owner = Keypair::generate();
+    let vault = Vault::new(owner.public_key());
+
+    assert!(vault.balance_history().is_empty());
+    let statement = vault.statement(0, u64::MAX);
+    assert!(statement.is_empty());
+    assert_eq!((statement.opening_balance(), statement.closing_balance()), (0, 0));
+}
+
+#[test]
+fn test_cancellation_credits_history() {
+    let owner = Keypair::generate();
+    let other = Keypair::generate();
+    let mut vault = mixed_vault(&owner);
+    let sent = send_at(&mut vault, &owner, &other, 20, 50);
+
+    vault.cancel_sent_iou(&SignedCancellation::sign(&owner, sent.id())).unwrap();
+
+    let history = vault.balance_history();
+    let last = history.last().unwrap();
+    assert_eq!(last.direction(), TransactionDirection::Cancelled);
+    assert_eq!(last.credit(), 20);
+    assert_eq!(last.balance(), vault.balance());
+}
+
+#[test]
+fn test_invalidating_spent_receipt_takes_nothing_back() {
+    let owner = Keypair::generate();
+    let (peer, other) = (Keypair::generate(), Keypair::generate());
+    let mut vault = Vault::new(owner.public_key());
+    let incoming = receive_at(&mut vault, &owner, &peer, 100, 10);
+    send_at(&mut vault, &owner, &other, 30, 20);
+
+    // Its UTXO went into the send, so the value is overdrawn rather than reversed
+    vault.invalidate_received_iou(&incoming.id()).unwrap();
+
+    let last = vault.balance_history().pop().unwrap();
+    assert_eq!(last.direction(), TransactionDirection::Invalidated);
+    assert_eq!((last.debit(), last.balance()), (0, 70));
+    assert_eq!(last.balance(), vault.balance());
+}
+
+#[test]
+fn test_invalidating_unspent_receipt_is_debited() {
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    receive_at(&mut vault, &owner, &peer, 100, 10);
+    let disputed = receive_at(&mut vault, &owner, &peer, 40, 20);
+
+    vault.invalidate_received_iou(&disputed.id()).unwrap();
+
+    let last = vault.balance_history().pop().unwrap();
+    assert_eq!((last.debit(), last.balance()), (40, 100));
+    assert_eq!(last.balance(), vault.balance());
+}
+
+#[test]
+fn test_consolidation_and_dust_sweep_leave_balance_unchanged() {
+    let owner = Keypair::generate();
+    let peer = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+    for (ts, amount) in [(10, 1), (20, 2), (30, 3), (40, 4), (50, 50)] {
+        receive_at(&mut vault, &owner, &peer, amount, ts);
+    }
+
+    vault.consolidate(&owner, 2).unwrap();
+    vault.set_dust_threshold(5);
+    vault.sweep_dust(&owner).unwrap();
+
+    assert_eq!(vault.balance(), 60);
+    let history = vault.balance_history();
+    let merges: Vec<(u64, u64)> = history[5..].iter().map(|p| (p.credit(), p.debit())).collect();
+    assert_eq!(merges, vec![(0, 0), (0, 0)]);
+    assert_eq!(history.last().unwrap().balance(), vault.balance());
+    assert_eq!(vault.statement(0, u64::MAX).closing_balance(), vault.balance());
+}
+
+// ============================================================================
+// STATEMENT TESTS
+// ============================================================================
+
+#[test]
+fn test_statement_opening_and_closing_balances() {
+    let owner = Keypair::generate();
+    let vault = mixed_vault(&owner);
+
+    let statement = vault.statement(20, 30);
+
+    assert_eq!((statement.start(), statement.end()), (20, 30));
+    assert_eq!(statement.opening_balance(), 100);
+    assert_eq!(timestamps(statement.items()), vec![20, 30]);
+    assert_eq!(statement.closing_balance(), 120);
+    assert_eq!((statement.total_credits(), statement.total_debits()), (50, 30));
+}
+
+#[test]
+fn test_statement_totals_reconcile() {
+    let owner = Keypair::generate();
+    let vault = mixed_vault(&owner);
+
+    let statement = vault.statement(15, 45);
+
+    assert_eq!(
+        statement.opening_balance() + statement.total_credits() - statement.total_debits(),
+        statement.closing_balance()
+    );
+}
+
+#[test]
+fn test_full_statement_closes_at_balance() {
+    let owner = Keypair::generate();
+    let vault = mixed_vault(&owner);
+
+    let statement = vault.statement(0, u64::MAX);
+
+    assert_eq!(statement.opening_balance(), 0);
+    assert_eq!(statement.items().len(), 4);
+    assert_eq!(statement.closing_balance(), vault.balance());
+}
+
+#[test]
+fn test_quiet_period_carries_balance() {
+    let owner = Keypair::generate();
+    let vault = mixed_vault(&owner);
+
+    let statement = vault.statement(41, 100);
+
+    assert!(statement.is_empty());
+    assert_eq!(statement.opening_balance(), 80);
+    assert_eq!(statement.closing_balance(), 80);
+}
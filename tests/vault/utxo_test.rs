@@ -1,8 +1,8 @@
 // UTXO (Unspent Transaction Output) management tests
 
 use p2pmesh::identity::{Did, Keypair};
-use p2pmesh::iou::{IOUBuilder, SignedIOU, IOUId};
-use p2pmesh::vault::{Vault, VaultError, UTXO, UTXOSet, UTXOId};
+use p2pmesh::iou::{IOUBuilder, IOUId};
+use p2pmesh::vault::{CoinSelection, CoinSelectionStrategy, Vault, VaultError, UTXO, UTXOSet, UTXOId, UTXOType};
 
 // ============================================================================
 // UTXO CREATION TESTS
@@ -530,3 +530,489 @@ fn test_locked_utxos_excluded_from_available_balance() {
     assert_eq!(vault.balance(), 100);
     assert_eq!(vault.available_balance(), 0);
 }
+
+// ============================================================================
+// COIN SELECTION STRATEGY TESTS
+// ============================================================================
+
+fn strategy_test_set() -> UTXOSet {
+    let mut set = UTXOSet::new();
+    let owner = Keypair::generate().public_key();
+
+    // Add 5, 20, 30, 55, 60
+    for (i, amount) in [5u64, 20, 30, 55, 60].iter().enumerate() {
+        set.add(UTXO::new(owner.clone(), *amount, IOUId::from_bytes([i as u8 + 1; 32])));
+    }
+    set
+}
+
+fn selected_amounts(utxos: &[UTXO]) -> Vec<u64> {
+    let mut amounts: Vec<u64> = utxos.iter().map(|u| u.amount()).collect();
+    amounts.sort();
+    amounts
+}
+
+#[test]
+fn test_largest_first_matches_select_for_amount() {
+    let set = strategy_test_set();
+
+    let (utxos, change) = set
+        .select_with_strategy(50, CoinSelectionStrategy::LargestFirst)
+        .unwrap();
+    let (expected, expected_change) = set.select_for_amount(50).unwrap();
+
+    assert_eq!(selected_amounts(&utxos), selected_amounts(&expected));
+    assert_eq!(change, expected_change);
+    assert_eq!(selected_amounts(&utxos), vec![60]);
+}
+
+#[test]
+fn test_smallest_first_spends_small_utxos() {
+    let set = strategy_test_set();
+
+    let (utxos, change) = set
+        .select_with_strategy(50, CoinSelectionStrategy::SmallestFirst)
+        .unwrap();
+
+    assert_eq!(selected_amounts(&utxos), vec![5, 20, 30]);
+    assert_eq!(change, 5);
+}
+
+#[test]
+fn test_minimize_change_finds_exact_combination() {
+    let set = strategy_test_set();
+
+    let (utxos, change) = set
+        .select_with_strategy(50, CoinSelectionStrategy::MinimizeChange)
+        .unwrap();
+
+    assert_eq!(selected_amounts(&utxos), vec![20, 30]);
+    assert_eq!(change, 0);
+}
+
+#[test]
+fn test_minimize_inputs_picks_smallest_covering_utxo() {
+    let set = strategy_test_set();
+
+    let (utxos, change) = set
+        .select_with_strategy(50, CoinSelectionStrategy::MinimizeInputs)
+        .unwrap();
+
+    assert_eq!(selected_amounts(&utxos), vec![55]);
+    assert_eq!(change, 5);
+}
+
+#[test]
+fn test_minimize_inputs_falls_back_to_largest_first() {
+    let set = strategy_test_set();
+
+    let (utxos, change) = set
+        .select_with_strategy(100, CoinSelectionStrategy::MinimizeInputs)
+        .unwrap();
+
+    assert_eq!(selected_amounts(&utxos), vec![55, 60]);
+    assert_eq!(change, 15);
+}
+
+#[test]
+fn test_all_strategies_return_none_when_insufficient() {
+    let set = strategy_test_set();
+
+    for strategy in [
+        CoinSelectionStrategy::LargestFirst,
+        CoinSelectionStrategy::SmallestFirst,
+        CoinSelectionStrategy::MinimizeChange,
+        CoinSelectionStrategy::MinimizeInputs,
+    ] {
+        assert!(set.select_with_strategy(171, strategy).is_none());
+    }
+}
+
+#[test]
+fn test_vault_default_coin_selection_is_largest_first() {
+    let vault = Vault::new(Keypair::generate().public_key());
+    assert_eq!(vault.coin_selection(), CoinSelectionStrategy::LargestFirst);
+}
+
+#[test]
+fn test_vault_record_sent_iou_uses_configured_strategy() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+    let mut vault = Vault::new(bob.public_key());
+    vault.set_coin_selection(CoinSelectionStrategy::SmallestFirst);
+
+    for amount in [10, 20, 100] {
+        let iou = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(Did::from_public_key(&bob.public_key()))
+            .amount(amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(iou, &alice.public_key()).unwrap();
+    }
+
+    let outgoing = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&charlie.public_key()))
+        .amount(25)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(outgoing).unwrap();
+
+    // Smallest-first spends 10 + 20, leaving 100 untouched and 5 as change
+    let remaining: Vec<u64> = vault
+        .utxo_set_sorted_by_amount()
+        .iter()
+        .map(|u| u.amount())
+        .collect();
+    assert_eq!(remaining, vec![5, 100]);
+    assert_eq!(vault.balance(), 105);
+}
+
+#[test]
+fn test_oldest_first_spends_in_arrival_order() {
+    let owner = Keypair::generate().public_key();
+    let mut set = UTXOSet::new();
+    // Arrival order differs from both amount and ID order
+    for (i, amount) in [40u64, 10, 70, 20].iter().enumerate() {
+        set.add(UTXO::new(owner.clone(), *amount, IOUId::from_bytes([9 - i as u8; 32])));
+    }
+
+    let (utxos, change) = set
+        .select_for_amount_with_strategy(45, CoinSelection::OldestFirst)
+        .unwrap();
+
+    let amounts: Vec<u64> = utxos.iter().map(|u| u.amount()).collect();
+    assert_eq!(amounts, vec![40, 10]);
+    assert_eq!(change, 5);
+}
+
+#[test]
+fn test_utxo_sequence_assigned_on_add_and_kept_on_readd() {
+    let owner = Keypair::generate().public_key();
+    let mut set = UTXOSet::new();
+    let first = UTXO::new(owner.clone(), 10, IOUId::from_bytes([1; 32]));
+    assert_eq!(first.sequence(), 0);
+
+    set.add(first.clone());
+    set.add(UTXO::new(owner, 20, IOUId::from_bytes([2; 32])));
+    let removed = set.remove(first.id()).unwrap();
+    assert_eq!(removed.sequence(), 1);
+
+    set.add(removed);
+    assert_eq!(set.get(first.id()).unwrap().sequence(), 1);
+}
+
+#[test]
+fn test_branch_and_bound_exact_match_has_no_change() {
+    let set = strategy_test_set();
+
+    let (utxos, change) = set
+        .select_for_amount_with_strategy(75, CoinSelection::BranchAndBoundExactMatch { tolerance: 0 })
+        .unwrap();
+
+    // 20 + 55 is exact; largest-first would have taken 60 + 55
+    assert_eq!(selected_amounts(&utxos), vec![20, 55]);
+    assert_eq!(change, 0);
+}
+
+#[test]
+fn test_branch_and_bound_accepts_near_match_within_tolerance() {
+    let set = strategy_test_set();
+
+    // Best combination for 48 is 20 + 30 (change 2)
+    let within = set
+        .select_with_strategy(48, CoinSelection::BranchAndBoundExactMatch { tolerance: 2 })
+        .unwrap();
+    assert_eq!(selected_amounts(&within.0), vec![20, 30]);
+    assert_eq!(within.1, 2);
+
+    // With a tighter tolerance it falls back to largest-first
+    let fallback = set
+        .select_with_strategy(48, CoinSelection::BranchAndBoundExactMatch { tolerance: 1 })
+        .unwrap();
+    let largest_first = set.select_for_amount(48).unwrap();
+    assert_eq!(selected_amounts(&fallback.0), selected_amounts(&largest_first.0));
+    assert_eq!(fallback.1, 12);
+}
+
+#[test]
+fn test_vault_set_coin_selection_branch_and_bound() {
+    let bob = Keypair::generate();
+    let charlie = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[30, 45, 70]);
+    vault.set_coin_selection(CoinSelection::BranchAndBoundExactMatch { tolerance: 0 });
+
+    let outgoing = IOUBuilder::new()
+        .sender(&bob)
+        .recipient(Did::from_public_key(&charlie.public_key()))
+        .amount(75)
+        .build()
+        .unwrap();
+    vault.record_sent_iou(outgoing).unwrap();
+
+    // 30 + 45 is spent exactly: no change UTXO is created
+    let remaining: Vec<u64> = vault.utxo_set().iter().map(|u| u.amount()).collect();
+    assert_eq!(remaining, vec![70]);
+}
+
+// ============================================================================
+// COIN SELECTION PROPERTIES
+// ============================================================================
+
+const ALL_STRATEGIES: [CoinSelection; 7] = [
+    CoinSelection::LargestFirst,
+    CoinSelection::SmallestFirst,
+    CoinSelection::MinimizeChange,
+    CoinSelection::MinimizeInputs,
+    CoinSelection::BranchAndBoundExactMatch { tolerance: 0 },
+    CoinSelection::BranchAndBoundExactMatch { tolerance: 25 },
+    CoinSelection::OldestFirst,
+];
+
+/// Deterministic pseudo-random UTXO set (xorshift, so failures reproduce)
+fn random_set(seed: u64) -> UTXOSet {
+    let owner = Keypair::generate().public_key();
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let mut set = UTXOSet::new();
+    let count = 1 + next() % 12;
+    for i in 0..count {
+        let mut id = [0u8; 32];
+        id[..8].copy_from_slice(&seed.to_le_bytes());
+        id[8] = i as u8;
+        set.add(UTXO::new(owner.clone(), 1 + next() % 500, IOUId::from_bytes(id)));
+    }
+    set
+}
+
+#[test]
+fn test_property_selection_covers_amount() {
+    for seed in 0..200u64 {
+        let set = random_set(seed);
+        let total = set.unlocked_value();
+
+        for amount in [1, total / 3, total / 2, total] {
+            for strategy in ALL_STRATEGIES {
+                let (utxos, change) = set
+                    .select_with_strategy(amount, strategy)
+                    .unwrap_or_else(|| panic!("seed {} {:?} amount {}", seed, strategy, amount));
+                let selected: u64 = utxos.iter().map(|u| u.amount()).sum();
+
+                assert!(selected >= amount, "seed {} {:?}", seed, strategy);
+                assert_eq!(selected - amount, change, "seed {} {:?}", seed, strategy);
+            }
+        }
+
+        for strategy in ALL_STRATEGIES {
+            assert!(set.select_with_strategy(total + 1, strategy).is_none());
+        }
+    }
+}
+
+#[test]
+fn test_property_exact_match_avoids_change() {
+    for seed in 0..200u64 {
+        let set = random_set(seed);
+        let utxos = set.to_vec();
+
+        // Any subset sum is an exactly reachable amount
+        let amount: u64 = utxos.iter().step_by(2).map(|u| u.amount()).sum();
+        let (selected, change) = set
+            .select_with_strategy(amount, CoinSelection::BranchAndBoundExactMatch { tolerance: 0 })
+            .unwrap();
+
+        assert_eq!(change, 0, "seed {}", seed);
+        assert_eq!(selected.iter().map(|u| u.amount()).sum::<u64>(), amount);
+    }
+}
+
+// ============================================================================
+// CONSOLIDATION TESTS
+// ============================================================================
+
+fn vault_with_utxos(owner: &Keypair, amounts: &[u64]) -> Vault {
+    let alice = Keypair::generate();
+    let mut vault = Vault::new(owner.public_key());
+
+    for amount in amounts {
+        let iou = IOUBuilder::new()
+            .sender(&alice)
+            .recipient(Did::from_public_key(&owner.public_key()))
+            .amount(*amount)
+            .build()
+            .unwrap();
+        vault.receive_iou(iou, &alice.public_key()).unwrap();
+    }
+    vault
+}
+
+#[test]
+fn test_consolidate_reduces_utxo_count_and_keeps_balance() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 2, 3, 4, 5, 100]);
+    assert_eq!(vault.utxo_set().len(), 6);
+    assert_eq!(vault.balance(), 115);
+
+    vault.consolidate(&bob, 10).unwrap();
+
+    assert_eq!(vault.utxo_set().len(), 1);
+    assert_eq!(vault.balance(), 115);
+}
+
+#[test]
+fn test_consolidate_sweeps_smallest_utxos_up_to_max_inputs() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 2, 3, 4, 5, 100]);
+
+    let iou = vault.consolidate(&bob, 3).unwrap();
+
+    // 1 + 2 + 3 swept into a single 6, leaving 4, 5, 100 untouched
+    assert_eq!(iou.iou().amount(), 6);
+    let remaining: Vec<u64> = vault
+        .utxo_set_sorted_by_amount()
+        .iter()
+        .map(|u| u.amount())
+        .collect();
+    assert_eq!(remaining, vec![4, 5, 6, 100]);
+    assert_eq!(vault.balance(), 115);
+}
+
+#[test]
+fn test_consolidate_returns_self_directed_signed_iou() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[10, 20]);
+
+    let iou = vault.consolidate(&bob, 2).unwrap();
+
+    let own_did = Did::from_public_key(&bob.public_key());
+    assert_eq!(iou.iou().sender(), &own_did);
+    assert_eq!(iou.iou().recipient(), &own_did);
+    assert!(iou.verify(&bob.public_key()));
+}
+
+#[test]
+fn test_consolidated_iou_cannot_be_received_again() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[10, 20]);
+
+    let iou = vault.consolidate(&bob, 2).unwrap();
+    let result = vault.receive_iou(iou, &bob.public_key());
+
+    assert!(matches!(result, Err(VaultError::DuplicateTransaction)));
+    assert_eq!(vault.balance(), 30);
+}
+
+#[test]
+fn test_consolidate_skips_locked_utxos() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 2, 3]);
+
+    let smallest = vault.utxo_set_sorted_by_amount()[0].id().clone();
+    vault.lock_utxo(&smallest).unwrap();
+
+    let iou = vault.consolidate(&bob, 10).unwrap();
+
+    assert_eq!(iou.iou().amount(), 5);
+    assert!(vault.get_utxo(&smallest).is_some());
+    assert_eq!(vault.balance(), 6);
+}
+
+#[test]
+fn test_consolidate_with_single_utxo_fails() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[50]);
+
+    let result = vault.consolidate(&bob, 10);
+    assert!(matches!(result, Err(VaultError::NothingToConsolidate)));
+    assert_eq!(vault.utxo_set().len(), 1);
+}
+
+#[test]
+fn test_consolidate_with_wrong_keypair_fails() {
+    let bob = Keypair::generate();
+    let mallory = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[10, 20]);
+
+    let result = vault.consolidate(&mallory, 10);
+    assert!(matches!(result, Err(VaultError::NotOwner)));
+    assert_eq!(vault.utxo_set().len(), 2);
+}
+
+#[test]
+fn test_consolidate_creates_consolidated_utxo_and_spends_inputs() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[10, 20, 30]);
+    let inputs: Vec<UTXOId> = vault.utxo_set().iter().map(|u| u.id().clone()).collect();
+
+    let iou = vault.consolidate_utxos(&bob, 10).unwrap();
+
+    let utxos = vault.utxo_set();
+    assert_eq!(utxos.len(), 1);
+    assert_eq!(utxos[0].utxo_type(), UTXOType::Consolidated);
+    assert_eq!(utxos[0].amount(), 60);
+    for id in &inputs {
+        assert!(vault.is_utxo_spent(id));
+    }
+    assert_eq!(iou.iou().amount(), 60);
+}
+
+#[test]
+fn test_consolidate_leaves_reserved_balance_untouched() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 2, 3, 4, 100]);
+    vault.reserve_balance(104).unwrap();
+
+    // The reservation locks 100 and 4, leaving 1 + 2 + 3 to sweep
+    let iou = vault.consolidate(&bob, 10).unwrap();
+
+    assert_eq!(iou.iou().amount(), 6);
+    assert_eq!(vault.balance(), 110);
+    assert_eq!(vault.available_balance(), 6);
+}
+
+#[test]
+fn test_consolidate_fully_reserved_fails() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[10, 20]);
+    vault.reserve_balance(25).unwrap();
+
+    let result = vault.consolidate(&bob, 10);
+
+    assert!(matches!(result, Err(VaultError::NothingToConsolidate)));
+    assert_eq!(vault.utxo_set().len(), 2);
+}
+
+#[test]
+fn test_consolidate_continues_sent_nonce_sequence() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[10, 20, 30]);
+
+    let first = vault.consolidate(&bob, 2).unwrap();
+    assert_eq!(first.iou().nonce(), 1);
+
+    let second = vault.consolidate(&bob, 2).unwrap();
+    assert_eq!(second.iou().nonce(), 2);
+    assert_eq!(vault.highest_sent_nonce(), Some(2));
+}
+
+#[test]
+fn test_should_consolidate_threshold() {
+    let bob = Keypair::generate();
+    let mut vault = vault_with_utxos(&bob, &[1, 2, 3]);
+
+    assert!(vault.should_consolidate(3));
+    assert!(!vault.should_consolidate(4));
+    // Never suggests consolidating a single UTXO
+    assert!(vault.should_consolidate(0));
+
+    vault.consolidate(&bob, 10).unwrap();
+    assert!(!vault.should_consolidate(0));
+}